@@ -3,11 +3,68 @@
 #
 # SPDX-License-Identifier: MIT
 # noinspection PyProtectedMember
+from importlib import import_module as _import_module
 from importlib import metadata as _metadata
+from typing import TYPE_CHECKING
 
 from charbot_rust import _charbot_rust
 
-from . import tictactoe, minesweeper
+if TYPE_CHECKING:
+    from . import tictactoe, minesweeper, connect4, checkers, reversi, battleship, sudoku, shrugman, twenty48, wordle, chess, blackjack, poker, dice, trivia, words, slots, brackets, maze, ledger, levels, leaderboard, cooldowns, giveaway, economy, streaks, moderation, markdown, ratelimit, images, colors, timeparse, schedule, discordfmt, snowflake, cache, metrics, crypto, json, zstd, store, hashing, emoji, fuzzy, search, markov, namegen
+
+# the subpackages of every part of the library, a smaller build leaves out the parts it wasn't built with
+_SUBPACKAGES = (
+    "tictactoe",
+    "minesweeper",
+    "connect4",
+    "checkers",
+    "reversi",
+    "battleship",
+    "sudoku",
+    "shrugman",
+    "twenty48",
+    "wordle",
+    "chess",
+    "blackjack",
+    "poker",
+    "dice",
+    "trivia",
+    "words",
+    "slots",
+    "brackets",
+    "maze",
+    "ledger",
+    "levels",
+    "leaderboard",
+    "cooldowns",
+    "giveaway",
+    "economy",
+    "streaks",
+    "moderation",
+    "markdown",
+    "ratelimit",
+    "images",
+    "colors",
+    "timeparse",
+    "schedule",
+    "discordfmt",
+    "snowflake",
+    "cache",
+    "metrics",
+    "crypto",
+    "json",
+    "zstd",
+    "store",
+    "hashing",
+    "emoji",
+    "fuzzy",
+    "search",
+    "markov",
+    "namegen",
+)
+_built = tuple(_name for _name in _SUBPACKAGES if hasattr(_charbot_rust, f"_{_name}"))
+for _name in _built:
+    _import_module(f".{_name}", __name__)
 
 __doc__ = _charbot_rust.__doc__
 __title__ = "charbot_rust"
@@ -15,8 +72,89 @@ __author__ = "Bluesy1"
 __license__ = "MIT"
 __copyright__ = "Copyright 2022-present Bluesy1"
 __version__ = _metadata.version(__title__)
-__all__ = ("tictactoe", "minesweeper", "translate")
-translate = _charbot_rust.translate
+__all__ = (
+    *_built,
+    "Buffer",
+    "init_logging",
+    "Profile",
+    "set_profiling",
+    "profiling_report",
+)
+Buffer = _charbot_rust.Buffer
+init_logging = _charbot_rust.init_logging
+Profile = _charbot_rust.Profile
+set_profiling = _charbot_rust.set_profiling
+profiling_report = _charbot_rust.profiling_report
+
+# the translator, which is left out of builds without the fluent feature
+if hasattr(_charbot_rust, "translate"):
+    __all__ += (
+        "translate",
+        "translate_many",
+        "translate_matrix",
+        "translate_all",
+        "reload_translations",
+        "register_function",
+        "set_use_isolating",
+        "clear_translation_cache",
+        "format_number",
+        "humanize_duration",
+        "plural_category",
+        "negotiate_locale",
+        "detect_language",
+        "set_fallback_chain",
+        "add_locale",
+        "available_locales",
+        "message_keys",
+        "message_metadata",
+        "check_bundles",
+        "validate_ftl",
+        "Translator",
+        "BundleReport",
+        "MessageMetadata",
+        "Diagnostic",
+        "TranslationKeyError",
+        "LocaleNotFoundError",
+        "FormattingError",
+        "MissingArgumentWarning",
+    )
+    translate = _charbot_rust.translate
+    translate_many = _charbot_rust.translate_many
+    translate_matrix = _charbot_rust.translate_matrix
+    translate_all = _charbot_rust.translate_all
+    reload_translations = _charbot_rust.reload_translations
+    register_function = _charbot_rust.register_function
+    set_use_isolating = _charbot_rust.set_use_isolating
+    clear_translation_cache = _charbot_rust.clear_translation_cache
+    format_number = _charbot_rust.format_number
+    humanize_duration = _charbot_rust.humanize_duration
+    plural_category = _charbot_rust.plural_category
+    negotiate_locale = _charbot_rust.negotiate_locale
+    detect_language = _charbot_rust.detect_language
+    set_fallback_chain = _charbot_rust.set_fallback_chain
+    add_locale = _charbot_rust.add_locale
+    available_locales = _charbot_rust.available_locales
+    message_keys = _charbot_rust.message_keys
+    message_metadata = _charbot_rust.message_metadata
+    check_bundles = _charbot_rust.check_bundles
+    validate_ftl = _charbot_rust.validate_ftl
+    Translator = _charbot_rust.Translator
+    BundleReport = _charbot_rust.BundleReport
+    MessageMetadata = _charbot_rust.MessageMetadata
+    Diagnostic = _charbot_rust.Diagnostic
+    TranslationKeyError = _charbot_rust.TranslationKeyError
+    LocaleNotFoundError = _charbot_rust.LocaleNotFoundError
+    FormattingError = _charbot_rust.FormattingError
+    MissingArgumentWarning = _charbot_rust.MissingArgumentWarning
+
+# the awaitable version, which is left out of builds without the async feature too
+if hasattr(_charbot_rust, "translate_many_async"):
+    __all__ += ("translate_many_async",)
+    translate_many_async = _charbot_rust.translate_many_async
 
 # noinspection PyUnresolvedReferences
-del _charbot_rust
+del _charbot_rust, _built
+try:
+    del _name  # pyright: ignore[reportUnboundVariable]
+except NameError:
+    pass
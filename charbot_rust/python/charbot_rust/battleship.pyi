@@ -0,0 +1,345 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Ship(Enum):
+    """The ships in a fleet, one of each."""
+
+    Carrier: ...
+    Battleship: ...
+    Cruiser: ...
+    Submarine: ...
+    Destroyer: ...
+
+    @property
+    def length(self) -> int:
+        """How many squares the ship takes up, from 5 for the carrier to 2 for the destroyer."""
+        ...
+
+class Shot(Enum):
+    """What a shot did."""
+
+    Miss: ...
+    Hit: ...
+    Sunk: ...
+    """The shot hit the last square of a ship that wasn't hit yet."""
+
+class Report:
+    """What happened when a square was shot at."""
+
+    square: str
+    shot: Shot
+    ship: Ship | None
+    """The ship that was hit, None for a miss."""
+
+class Fleet:
+    """One player's ships on a 10 by 10 board, and the shots the other player has made at them.
+
+    Squares are named with a letter for the row and a number for the column, like ``B7``. Ships are
+    placed from their top or left end, and can never overlap.
+
+    In a game between two players each player has their own fleet, which can be saved on its own so
+    neither player can see the other's ships.
+
+    Parameters
+    ----------
+    allow_touching : bool
+        Whether ships can be placed next to each other, diagonally included. The default is False.
+    """
+
+    def __init__(self, allow_touching: bool = False): ...
+    def __new__(cls, allow_touching: bool = False) -> "Fleet": ...
+    @staticmethod
+    def random(allow_touching: bool = False, seed: int | None = None) -> "Fleet":
+        """A fleet with every ship placed at random.
+
+        Parameters
+        ----------
+        allow_touching : bool
+            Whether ships can be next to each other. The default is False.
+        seed : int | None
+            The seed to place the ships with, random if None.
+
+        Returns
+        -------
+        Fleet
+            The fleet, ready to be shot at.
+        """
+        ...
+    @property
+    def allow_touching(self) -> bool:
+        """Whether ships can be placed next to each other."""
+        ...
+    def place(self, ship: Ship, square: str, horizontal: bool) -> None:
+        """Place a ship on the board.
+
+        Parameters
+        ----------
+        ship : Ship
+            The ship to place.
+        square : str
+            The square of the ship's top end, or left end if the ship is horizontal.
+        horizontal : bool
+            Whether the ship goes right from the square, otherwise it goes down.
+
+        Raises
+        ------
+        ValueError
+            If the square isn't on the board, the ship is already placed, doesn't fit on the board,
+            would overlap or touch another ship, or the fleet has already been shot at.
+        """
+        ...
+    def remove(self, ship: Ship) -> bool:
+        """Take a ship off the board, to place it again.
+
+        Returns
+        -------
+        bool
+            True if the ship was placed, False otherwise.
+
+        Raises
+        ------
+        ValueError
+            If the fleet has already been shot at.
+        """
+        ...
+    def ships(self) -> list[tuple[Ship, list[str]]]:
+        """The ships placed so far, with the squares each one is on."""
+        ...
+    def unplaced(self) -> list[Ship]:
+        """The ships that still have to be placed."""
+        ...
+    def is_ready(self) -> bool:
+        """Check if every ship is placed, so the fleet can be shot at."""
+        ...
+    def fire(self, square: str) -> Report:
+        """Shoot at a square.
+
+        Parameters
+        ----------
+        square : str
+            The square, like ``B7``.
+
+        Returns
+        -------
+        Report
+            Whether the shot missed, hit, or sunk a ship, and which ship.
+
+        Raises
+        ------
+        ValueError
+            If the square isn't on the board or was already shot at, not every ship is placed yet, or
+            every ship is already sunk.
+        """
+        ...
+    def all_sunk(self) -> bool:
+        """Check if every ship was sunk, which loses the game."""
+        ...
+    def to_emoji(self, reveal: bool = True) -> str:
+        """The board as emoji, for an embed or message.
+
+        The first line is keycap numbers over the columns, then each row is a line from the top starting
+        with its letter. Water is blue, the ships brown, misses white and hits an explosion, turning red
+        once the ship is sunk.
+
+        Parameters
+        ----------
+        reveal : bool
+            Whether to show where the ships are, for the fleet's own player, otherwise only the shots
+            are shown, for the other player. The default is True.
+
+        Returns
+        -------
+        str
+            The emoji grid, ``str(fleet)`` is the same with the ships shown.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the fleet as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved fleet, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Fleet":
+        """Load a fleet saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved fleet.
+
+        Returns
+        -------
+        Fleet
+            The fleet, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved fleet, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the fleet as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved fleet, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Fleet":
+        """Load a fleet saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved fleet.
+
+        Returns
+        -------
+        Fleet
+            The fleet, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved fleet, is from another version, or couldn't have come from a real game.
+        """
+        ...
+
+class Game:
+    """A rust based implementation of battleship against the computer.
+
+    The computer places its ships at random, with the same rules as the player's fleet. Each turn the
+    player shoots at the computer's ships and the computer shoots back, hunting across the board until
+    it hits a ship, then going after that ship until it sinks.
+
+    Parameters
+    ----------
+    fleet : Fleet
+        The player's ships, which all have to be placed.
+    seed : int | None
+        The seed for the computer's ships and shots, random if None.
+
+    Raises
+    ------
+    ValueError
+        If not every ship in the fleet is placed, or it has already been shot at.
+    """
+
+    def __init__(self, fleet: Fleet, seed: int | None = None): ...
+    def __new__(cls, fleet: Fleet, seed: int | None = None) -> "Game": ...
+    @property
+    def seed(self) -> int:
+        """The seed the computer's ships were placed with, so a game can be replayed to check it."""
+        ...
+    @property
+    def fleet(self) -> Fleet:
+        """A copy of the player's fleet, with the computer's shots at it."""
+        ...
+    def play(self, square: str) -> tuple[Report, Report | None]:
+        """Shoot at the computer's ships, and have the computer shoot back if the game is not complete.
+
+        Parameters
+        ----------
+        square : str
+            The square, like ``B7``.
+
+        Returns
+        -------
+        tuple[Report, Report | None]
+            What the player's shot did, and the computer's shot, or None if the player sank the last ship.
+
+        Raises
+        ------
+        ValueError
+            If the square isn't on the board or was already shot at, or the game is already over.
+        """
+        ...
+    def has_player_won(self) -> bool:
+        """Check if the player has sunk every one of the computer's ships."""
+        ...
+    def has_player_lost(self) -> bool:
+        """Check if the computer has sunk every one of the player's ships."""
+        ...
+    def is_over(self) -> bool:
+        """Check if either side has lost every ship."""
+        ...
+    def target_emoji(self) -> str:
+        """The computer's board as emoji, with only the player's shots shown."""
+        ...
+    def fleet_emoji(self) -> str:
+        """The player's board as emoji, with their ships and the computer's shots.
+
+        ``str(game)`` is this under ``target_emoji``, with a blank line between them.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
@@ -0,0 +1,282 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Card:
+    """A playing card, ``str(card)`` is its rank and suit, like 'A♠' or '10♥'."""
+
+    @property
+    def rank(self) -> str:
+        """The rank, from 'A' and '2' to '10', 'J', 'Q' and 'K'."""
+        ...
+    @property
+    def suit(self) -> str:
+        """The suit, one of '♠', '♥', '♦' and '♣'."""
+        ...
+    @property
+    def value(self) -> int:
+        """What the card counts for, with aces as 1, hands count one of them as 11 when that doesn't bust."""
+        ...
+
+class Hand:
+    """A hand of the player.
+
+    Attributes
+    ----------
+    cards : list[Card]
+        The cards in the hand, in the order they were dealt.
+    bet : int
+        The bet on the hand, twice the first bet if it was doubled.
+    doubled : bool
+        Whether the hand was doubled.
+    split : bool
+        Whether the hand came from splitting a pair, so two cards making 21 aren't a blackjack.
+    """
+
+    cards: list[Card]
+    bet: int
+    doubled: bool
+    split: bool
+
+    @property
+    def total(self) -> int:
+        """The total, counting an ace as 11 if that doesn't go over 21."""
+        ...
+    @property
+    def is_soft(self) -> bool:
+        """Whether an ace is counted as 11."""
+        ...
+    @property
+    def is_blackjack(self) -> bool:
+        """Whether the hand is an ace and a ten valued card, not from a split."""
+        ...
+    @property
+    def is_bust(self) -> bool:
+        """Whether the total is over 21."""
+        ...
+
+class Outcome(Enum):
+    """How a hand did against the dealer.
+
+    Lose:
+        The hand lost, the bet is gone.
+    Push:
+        The hand tied with the dealer, the bet is returned.
+    Win:
+        The hand beat the dealer, the bet is paid 1:1.
+    Blackjack:
+        The hand was a blackjack and the dealer's wasn't, the bet is paid 3:2.
+    """
+
+    Lose: ...
+    Push: ...
+    Win: ...
+    Blackjack: ...
+
+class Table:
+    """A blackjack table against the dealer, with a shoe of several decks.
+
+    The dealer checks for a blackjack as soon as the cards are dealt, and stands on 17. Pairs can be
+    split into up to 4 hands, split aces only get one card each, and a hand can be doubled on its
+    first two cards, splits included. The shoe is shuffled again before a round once less than a
+    quarter of it is left.
+
+    Parameters
+    ----------
+    decks : int
+        How many decks are in the shoe, from 1 to 8. The default is 6.
+    seed : int | None
+        The seed to shuffle the shoe with, so a table can be replayed to check its results. The default
+        is None, for a random seed, which is still available from ``seed``.
+    hit_soft_17 : bool
+        Whether the dealer hits on a soft 17 instead of standing. The default is False.
+
+    Raises
+    ------
+    ValueError
+        If the number of decks isn't from 1 to 8.
+    """
+
+    def __init__(self, decks: int = 6, seed: int | None = None, hit_soft_17: bool = False): ...
+    def __new__(cls, decks: int = 6, seed: int | None = None, hit_soft_17: bool = False) -> "Table": ...
+    @property
+    def seed(self) -> int:
+        """The seed the shoe was shuffled with."""
+        ...
+    @property
+    def cards_left(self) -> int:
+        """How many cards are left in the shoe."""
+        ...
+    @property
+    def hands(self) -> list[Hand]:
+        """The player's hands this round, more than one after a split."""
+        ...
+    @property
+    def active_hand_index(self) -> int | None:
+        """The index in ``hands`` of the hand being played, None once the round is over."""
+        ...
+    @property
+    def dealer_cards(self) -> list[Card]:
+        """The dealer's cards, only the first one while a hand is still being played."""
+        ...
+    @property
+    def dealer_total(self) -> int | None:
+        """The dealer's total, or None while the hole card is hidden."""
+        ...
+    def deal(self, bet: int) -> None:
+        """Start a round, dealing two cards to the player and the dealer.
+
+        If either has a blackjack the round is over straight away.
+
+        Parameters
+        ----------
+        bet : int
+            The bet on the hand.
+
+        Raises
+        ------
+        ValueError
+            If the last round isn't over yet, or the bet is 0.
+        """
+        ...
+    def hit(self) -> Card:
+        """Take a card on the active hand, which ends it if it reaches 21 or more.
+
+        Returns
+        -------
+        Card
+            The card taken.
+
+        Raises
+        ------
+        ValueError
+            If no hand is being played.
+        """
+        ...
+    def stand(self) -> None:
+        """End the active hand, once every hand is done the dealer plays and the round is over.
+
+        Raises
+        ------
+        ValueError
+            If no hand is being played.
+        """
+        ...
+    def double(self) -> Card:
+        """Double the bet on the active hand and take exactly one more card, which ends it.
+
+        Returns
+        -------
+        Card
+            The card taken.
+
+        Raises
+        ------
+        ValueError
+            If no hand is being played, or the hand doesn't have just two cards.
+        """
+        ...
+    def split(self) -> None:
+        """Split a pair into two hands with the same bet, each getting a second card.
+
+        Raises
+        ------
+        ValueError
+            If no hand is being played, the hand isn't two cards of the same value, or there are
+            already 4 hands.
+        """
+        ...
+    def is_round_over(self) -> bool:
+        """Check if the round is over and settled.
+
+        Returns
+        -------
+        bool
+            True if the round is over, False while a hand is being played.
+        """
+        ...
+    def outcomes(self) -> list[Outcome] | None:
+        """How each hand did against the dealer.
+
+        Returns
+        -------
+        list[Outcome] | None
+            The outcome of each hand in order, or None while the round is still being played.
+        """
+        ...
+    def payout(self) -> int | None:
+        """What the player gets back over every hand, bets included, with half points rounded down.
+
+        Returns
+        -------
+        int | None
+            The payout, or None while the round is still being played.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the table as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved table, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Table":
+        """Load a table saved by ``to_bytes``.
+
+        The shoe goes on dealing and shuffling as the saved table would have.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved table.
+
+        Returns
+        -------
+        Table
+            The table, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved table, is from another version, or couldn't have come from a real table.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the table as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved table, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Table":
+        """Load a table saved by ``to_json``.
+
+        The shoe goes on dealing and shuffling as the saved table would have.
+
+        Parameters
+        ----------
+        data : str
+            The saved table.
+
+        Returns
+        -------
+        Table
+            The table, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved table, is from another version, or couldn't have come from a real table.
+        """
+        ...
@@ -0,0 +1,216 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Format(Enum):
+    """How a tournament is played."""
+
+    SingleElimination: ...
+    """The winner of each match goes through to the next round, until one is left."""
+    RoundRobin: ...
+    """Everyone plays everyone once, and the most points wins."""
+
+class Match:
+    """A match in a bracket.
+
+    Attributes
+    ----------
+    id : int
+        The id of the match, to record its result with.
+    round : int
+        The round of the match, from 0 for the first.
+    players : tuple[int | None, int | None]
+        The players, None for a bye or a player that isn't known until an earlier match is played.
+    winner : int | None
+        The winner, None if the match wasn't played yet or was a draw. The player with a bye is the winner of it.
+    is_draw : bool
+        Whether the match was a draw, only possible in a round robin.
+    is_bye : bool
+        Whether a player goes through without playing, there's nothing to record for a bye.
+    """
+
+    id: int
+    round: int
+    players: tuple[int | None, int | None]
+    winner: int | None
+    is_draw: bool
+    is_bye: bool
+
+class Standing:
+    """How a participant has done so far.
+
+    Attributes
+    ----------
+    participant : int
+        The participant.
+    wins : int
+        The matches won, going through on a bye isn't a win.
+    draws : int
+        The matches drawn.
+    losses : int
+        The matches lost.
+    """
+
+    participant: int
+    wins: int
+    draws: int
+    losses: int
+
+    @property
+    def points(self) -> int:
+        """Three points for a win and one for a draw."""
+        ...
+
+class Bracket:
+    """A tournament, its matches and the results so far.
+
+    In a single elimination tournament, the bracket is filled to the next power of two with byes, which go
+    to the top seeds, and the top seeds are spread out so they can only meet in the later rounds. In a
+    round robin, everyone plays once each round, apart from one participant sitting out each round if
+    there's an odd number of them.
+
+    Parameters
+    ----------
+    format : Format
+        How the tournament is played.
+    participants : list[int]
+        The ids of the participants, like discord user ids, in seeding order with the top seed first.
+
+    Raises
+    ------
+    ValueError
+        If there aren't from 2 to 256 participants, or a participant is in the list more than once.
+    """
+
+    def __new__(cls, format: Format, participants: list[int]) -> "Bracket": ...
+    @property
+    def format(self) -> Format: ...
+    @property
+    def participants(self) -> list[int]: ...
+    @property
+    def rounds(self) -> int:
+        """The number of rounds."""
+        ...
+    def matches(self, round: int | None = None) -> list[Match]:
+        """Get the matches, in the order of their ids.
+
+        Parameters
+        ----------
+        round : int | None
+            The round to get the matches of. The default is None, for every match.
+
+        Returns
+        -------
+        list[Match]
+            The matches.
+        """
+        ...
+    def pending(self) -> list[Match]:
+        """Get the matches that can be played now, with both players known and no result yet.
+
+        Returns
+        -------
+        list[Match]
+            The matches.
+        """
+        ...
+    def record(self, match_id: int, winner: int | None = None) -> None:
+        """Record the result of a match, advancing the winner in a single elimination tournament.
+
+        A result can be changed by recording it again, as long as the winner hasn't played their next match.
+
+        Parameters
+        ----------
+        match_id : int
+            The id of the match.
+        winner : int | None
+            The winner, None for a draw. The default is None.
+
+        Raises
+        ------
+        ValueError
+            If there's no such match, it's a bye, its players aren't known yet, the winner isn't one of the
+            players, it's a draw in a single elimination tournament, or the winner already played their next match.
+        """
+        ...
+    def standings(self) -> list[Standing]:
+        """Get everyone's wins, draws and losses.
+
+        Returns
+        -------
+        list[Standing]
+            The standings, the most points first, and the higher seed first when they're level.
+        """
+        ...
+    @property
+    def is_over(self) -> bool:
+        """Whether every match has been played."""
+        ...
+    @property
+    def winner(self) -> int | None:
+        """The winner of the tournament, None if it isn't over or the top of a round robin is tied."""
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the bracket as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved bracket, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Bracket":
+        """Load a bracket saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved bracket.
+
+        Returns
+        -------
+        Bracket
+            The bracket, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved bracket, is from another version, or couldn't have come from a real tournament.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the bracket as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved bracket, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Bracket":
+        """Load a bracket saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved bracket.
+
+        Returns
+        -------
+        Bracket
+            The bracket, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved bracket, is from another version, or couldn't have come from a real tournament.
+        """
+        ...
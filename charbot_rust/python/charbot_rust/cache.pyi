@@ -0,0 +1,98 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from typing import Any, TypeVar as _TypeVar
+
+_T = _TypeVar("_T")
+
+Key = int | str | tuple["Key", ...]
+
+class TtlCache:
+    """A cache of at most a number of entries, dropping the least recently used one to make room, where
+    entries can expire after some time.
+
+    Looking up, setting and removing a key take the same time however many entries there are. Expired
+    entries are removed when they're looked up, or all at once with expire.
+
+    Parameters
+    ----------
+    maxsize : int
+        The most entries the cache holds, at least 1.
+    ttl : float | None
+        How many seconds entries expire after, unless they're set with a TTL of their own. The default is
+        None, for never.
+
+    Raises
+    ------
+    ValueError
+        If the size is 0 or the TTL isn't positive.
+    """
+
+    def __init__(self, maxsize: int, ttl: float | None = None) -> None: ...
+    @property
+    def maxsize(self) -> int:
+        """The most entries the cache holds."""
+        ...
+    @property
+    def ttl(self) -> float | None:
+        """How many seconds entries expire after, unless they're set with a TTL of their own."""
+        ...
+    @property
+    def hits(self) -> int:
+        """How many times get found a key."""
+        ...
+    @property
+    def misses(self) -> int:
+        """How many times get didn't find a key, or found it expired."""
+        ...
+    def get(self, key: Key, default: _T = None, now: float | None = None) -> Any | _T:
+        """Get the value for a key, which becomes the most recently used, counting a hit or a miss.
+
+        Parameters
+        ----------
+        key : int | str | tuple
+            The key, like a member ID, or a tuple of a guild ID and an endpoint.
+        default : Any
+            What to get if the key isn't cached or has expired. The default is None.
+        now : float | None
+            The unix timestamp in seconds to look up at. The default is None, for now.
+
+        Returns
+        -------
+        Any
+            The value, or the default.
+        """
+        ...
+    def set(self, key: Key, value: Any, ttl: float | None = None, now: float | None = None) -> None:
+        """Cache a value for a key, dropping the least recently used entry if the cache is full.
+
+        Parameters
+        ----------
+        key : int | str | tuple
+            The key, like a member ID, or a tuple of a guild ID and an endpoint.
+        value : Any
+            The value to cache.
+        ttl : float | None
+            How many seconds the entry expires after. The default is None, for the TTL of the cache.
+        now : float | None
+            The unix timestamp in seconds to set it at. The default is None, for now.
+
+        Raises
+        ------
+        ValueError
+            If the TTL isn't positive.
+        """
+        ...
+    def pop(self, key: Key, default: _T = None, now: float | None = None) -> Any | _T:
+        """Remove a key and get its value, or the default if it isn't cached or has expired."""
+        ...
+    def expire(self, now: float | None = None) -> int:
+        """Remove every entry that has expired by now, and get how many there were."""
+        ...
+    def clear(self) -> None:
+        """Remove every entry, keeping the hit and miss counts."""
+        ...
+    def __contains__(self, key: Key) -> bool: ...
+    def __len__(self) -> int:
+        """How many entries there are, counting expired ones that haven't been looked up since."""
+        ...
@@ -2,29 +2,797 @@
 # SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
 #
 # SPDX-License-Identifier: MIT
-from typing import Literal as _Literal
+from asyncio import Future as _Future
+from datetime import datetime as _datetime
+from enum import Enum as _Enum
+from os import PathLike as _PathLike
+from typing import Callable as _Callable, Literal as _Literal
 
-def translate(locale: _Literal["en-US", "es-ES", "fr", "nl"], key: str, args: dict[str, int | float | str]) -> str:
+_Arg = bool | int | float | str | _datetime | list[str] | _Enum | None
+
+class TranslationKeyError(RuntimeError):
+    """The key to translate doesn't exist in the locale."""
+
+class LocaleNotFoundError(RuntimeError):
+    """The translations for the locale couldn't be found or loaded."""
+
+class FormattingError(RuntimeError):
+    """The message exists, but couldn't be formatted with the given arguments."""
+
+class MissingArgumentWarning(UserWarning):
+    """A lenient translation was missing arguments, placeholders were left for them."""
+
+def translate(
+    locale: _Literal["en-US", "es-ES", "fr", "nl", "pseudo"],
+    key: str,
+    args: dict[str, _Arg],
+    /,
+    overrides: str | None = None,
+    strict: bool = False,
+    lenient: bool = False,
+    terms: dict[str, str] | None = None,
+) -> str:
     """Translate a string into the given locale.
 
     Parameters
     ----------
-    locale : {'en-US', 'es-ES', 'fr', 'nl'}
-        The locale to translate to, e.g. 'en-US'. If the locale exists, but the key does not,
-         en-US will be used if the key exists there.
+    locale : {'en-US', 'es-ES', 'fr', 'nl', 'pseudo'}
+        The locale to translate to, e.g. 'en-US'. Keys missing from the locale are looked up along its
+        fallback chain, see ``set_fallback_chain``. ``'pseudo'`` is en-US with accented, padded and
+        bracketed text, like ``[Ĥééļļöö]``, to spot hard coded strings and truncation before translating.
     key : str
-        The key to translate.
-    args : dict[str, int | float | str]
-        The arguments to format the string with. If no arguments, pass an empty dict, ie ``{}``.
+        The key to translate, or ``key.attribute`` to translate one of its attributes.
+    args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]
+        The arguments to format the string with. If no arguments, pass an empty dict, ie ``{}``. Booleans
+        select ``[true]`` or ``[false]`` variants, datetimes format like ``DATETIME`` for the locale, lists
+        are joined like ``a, b, and c``, enum members select the variant named after their str value or their
+        name, raising if there isn't one, and ``None`` formats as nothing.
+    overrides : str, optional
+        An FTL source, such as a guild's custom strings, that is checked for the key before the
+        built in translations. Prefer ``Translator.with_overrides`` when translating several strings.
+    strict : bool, optional
+        Raise instead of falling back along the chain when the locale is unknown, or the message is
+        missing or fails to format. Defaults to ``False``.
+    lenient : bool, optional
+        Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+        ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+    terms : dict[str, str], optional
+        Values for terms, by name without the leading ``-``, that replace the terms shared by every
+        locale, e.g. ``{'bot-name': 'Charbot'}`` for a guild's nickname for the bot. Prefer
+        ``Translator.with_terms`` when translating several strings.
 
     Returns
     -------
     str
         The translated string.
 
+    Raises
+    ------
+    TranslationKeyError
+        If the key doesn't exist.
+    LocaleNotFoundError
+        If the translations couldn't be loaded, or ``strict`` is set and the locale is unknown.
+    FormattingError
+        If the message couldn't be formatted with ``args``, ``overrides`` is not valid FTL, or a name in
+        ``terms`` is not a valid term name.
+    """
+    ...
+
+def translate_many(
+    locale: _Literal["en-US", "es-ES", "fr", "nl"],
+    items: list[tuple[str, dict[str, _Arg]]],
+    /,
+    strict: bool = False,
+    lenient: bool = False,
+) -> list[str]:
+    """Translate several strings into the given locale at once.
+
+    Parameters
+    ----------
+    locale : {'en-US', 'es-ES', 'fr', 'nl'}
+        The locale to translate to, e.g. 'en-US'. Keys missing from the locale are looked up along its
+        fallback chain, see ``set_fallback_chain``.
+    items : list[tuple[str, dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]]]
+        The ``(key, args)`` pairs to translate, in the same form as ``translate`` takes them.
+    strict : bool, optional
+        Raise instead of falling back along the chain when the locale is unknown, or a message is
+        missing or fails to format. Defaults to ``False``.
+    lenient : bool, optional
+        Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+        ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+
+    Returns
+    -------
+    list[str]
+        The translated strings, in the same order as ``items``.
+
+    Raises
+    ------
+    TranslationKeyError
+        If any of the keys don't exist.
+    LocaleNotFoundError
+        If the translations couldn't be loaded, or ``strict`` is set and the locale is unknown.
+    FormattingError
+        If any of the messages couldn't be formatted with their args.
+    """
+    ...
+
+def translate_matrix(
+    keys: list[str],
+    locales: list[str],
+    /,
+    args: dict[str, _Arg] | None = None,
+    strict: bool = False,
+    lenient: bool = False,
+) -> list[list[str]]:
+    """Translate several strings into several locales at once, like for the localized names of every
+    command, with each locale on its own thread without holding the GIL.
+
+    Parameters
+    ----------
+    keys : list[str]
+        The keys to translate, or ``key.attribute`` to translate one of their attributes.
+    locales : list[str]
+        The locales to translate to, e.g. ``['en-US', 'fr']``. Keys missing from a locale are looked up
+        along its fallback chain, see ``set_fallback_chain``.
+    args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None] | None
+        The arguments to format every string with, in the same form as ``translate`` takes them. The
+        default is None, for none.
+    strict : bool, optional
+        Raise instead of falling back along the chain when a locale is unknown, or a message is missing
+        or fails to format. Defaults to ``False``.
+    lenient : bool, optional
+        Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+        ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+
+    Returns
+    -------
+    list[list[str]]
+        A row for each key in the order of ``keys``, with the translation into each locale in the order
+        of ``locales``.
+
+    Raises
+    ------
+    TranslationKeyError
+        If any of the keys don't exist.
+    LocaleNotFoundError
+        If the translations couldn't be loaded, or ``strict`` is set and a locale is unknown.
+    FormattingError
+        If any of the messages couldn't be formatted with the args.
+    """
+    ...
+
+def translate_many_async(
+    locale: _Literal["en-US", "es-ES", "fr", "nl"],
+    items: list[tuple[str, dict[str, _Arg]]],
+    /,
+    strict: bool = False,
+    lenient: bool = False,
+) -> _Future[list[str]]:
+    """Translate several strings into the given locale at once on a thread pool, so the event loop keeps
+    running meanwhile. It takes and gives the same as ``translate_many``, once awaited.
+
+    Returns
+    -------
+    asyncio.Future[list[str]]
+        The translated strings, in the same order as ``items``.
+
+    Raises
+    ------
+    RuntimeError
+        If there isn't an event loop running.
+    """
+    ...
+
+def translate_all(key: str, args: dict[str, _Arg]) -> dict[str, str]:
+    """Translate a string into every available locale at once, e.g. for the localized names and
+    descriptions of application commands.
+
+    Parameters
+    ----------
+    key : str
+        The key to translate, or ``key.attribute`` to translate one of its attributes.
+    args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]
+        The arguments to format the string with, in the same form as ``translate`` takes them.
+
+    Returns
+    -------
+    dict[str, str]
+        The translations by locale code. Locales that don't have the key, or can't format it, are left
+        out instead of falling back to en-US, so discord falls back for them itself.
+
+    Raises
+    ------
+    TranslationKeyError
+        If the key doesn't exist in en-US.
+    LocaleNotFoundError
+        If the translations couldn't be loaded.
+    FormattingError
+        If the en-US message couldn't be formatted with ``args``.
+    """
+    ...
+
+def reload_translations(path: str | _PathLike[str]) -> None:
+    """Reload the translations from a directory on disk, without restarting.
+
+    The terms every locale shares, like ``-bot-name``, are read again too.
+
+    Parameters
+    ----------
+    path : str | os.PathLike[str]
+        The directory to load from. It should be laid out like the built in translations, one folder per
+        locale code (e.g. ``fr/``) holding that locale's ``.ftl`` files. Locales without a folder keep
+        using the built in translations.
+
+    Raises
+    ------
+    RuntimeError
+        If the directory can't be read, or any of the files fail to parse. The current translations are
+        kept when this happens.
+    """
+    ...
+
+def register_function(name: str, callable: _Callable[..., str | int | float | None]) -> None:
+    """Make a python function callable from inside translations, e.g. ``{ EMOJI($name) }``.
+
+    Parameters
+    ----------
+    name : str
+        The name of the function in the FTL files. Fluent function names are upper case, e.g. ``EMOJI``.
+    callable : Callable[..., str | int | float | None]
+        Called with the positional arguments of the call as ``str`` or ``float``, and its named
+        arguments as keyword arguments. The result is converted with ``str`` if it isn't a number,
+        string, or ``None``, and any exception it raises is logged to ``charbot_rust.fluent`` and shown as an
+        error in the message.
+
+    Raises
+    ------
+    RuntimeError
+        If the name is not a valid function name, is ``NUMBER`` or ``DATETIME``, or the translations couldn't
+        be rebuilt with the function.
+
+    Notes
+    -----
+    Existing ``Translator`` instances don't see the function, create new ones after registering.
+    """
+    ...
+
+def set_use_isolating(use_isolating: bool) -> None:
+    """Set whether arguments in translations are wrapped in unicode bidi isolation marks (FSI and PDI).
+
+    They're left out by default, since some discord clients render them as boxes, but they keep right to
+    left text in arguments displaying correctly.
+
+    Parameters
+    ----------
+    use_isolating : bool
+        ``True`` to wrap the arguments of every translation made from now on in the marks, ``False`` to
+        leave them out again.
+
+    Raises
+    ------
+    RuntimeError
+        If the translations couldn't be rebuilt with the new setting.
+
+    Notes
+    -----
+    Existing ``Translator`` instances keep the setting they were created with.
+    """
+    ...
+
+def clear_translation_cache() -> None:
+    """Drop every cached translation.
+
+    Translations are cached by locale, key and arguments, since the same ones are made over and over.
+    Ones that call a registered function, or that were made with errors like a missing argument, aren't
+    cached, so they're made again each time.
+
+    Notes
+    -----
+    Reloading the translations, registering a function, adding a locale and ``set_use_isolating`` all
+    clear the cache already, and the cache never hands out translations made from other bundles.
+    This is only needed to free the memory.
+    """
+    ...
+
+def format_number(
+    locale: str, value: int | float, /, style: _Literal["decimal", "percent", "compact", "grouping"] = "decimal"
+) -> str:
+    """Format a number for a locale outside of a message, such as points or a percentage.
+
+    Parameters
+    ----------
+    locale : {'en-US', 'es-ES', 'fr', 'nl'}
+        The locale to format for. Other locales use the first locale of their fallback chain, see
+        ``set_fallback_chain``, and unknown ones format like en-US. Locales added at runtime format like
+        the built in locale with their language, and there are no formats for other languages.
+    value : int | float
+        The number to format.
+    style : {'decimal', 'percent', 'compact', 'grouping'}, optional
+        ``'decimal'`` formats like ``NUMBER`` does, with up to three fraction digits, ``'percent'``
+        multiplies by 100 and adds the percent sign, ``'compact'`` shortens large numbers like ``1.2K``,
+        and ``'grouping'`` rounds to a whole number with the digits grouped like ``1,234,567``.
+        Defaults to ``'decimal'``.
+
+    Returns
+    -------
+    str
+        The formatted number.
+
+    Raises
+    ------
+    ValueError
+        If the style is not one of the above, or there are no formats for the locale.
+    """
+    ...
+
+def humanize_duration(locale: str, seconds: int | float, /, precision: int = 2, compact: bool = False) -> str:
+    """Describe a number of seconds in a locale, like ``2 hours 5 minutes``, such as how long a cooldown has left.
+
+    Parameters
+    ----------
+    locale : {'en-US', 'es-ES', 'fr', 'nl'}
+        The locale to describe the duration in. Other locales use the first locale of their fallback
+        chain, see ``set_fallback_chain``, and unknown ones use en-US. Locales added at runtime use the
+        built in locale with their language, and there are no unit names for other languages.
+    seconds : int | float
+        The duration, fractions of a second are dropped.
+    precision : int, optional
+        The most units to use, biggest first, the smaller ones left over are dropped. Units that are
+        zero don't count, so a day and five minutes is ``1 day 5 minutes``. Defaults to 2.
+    compact : bool, optional
+        Use short units like ``2h 5m`` instead. Defaults to ``False``.
+
+    Returns
+    -------
+    str
+        The duration, in days, hours, minutes and seconds.
+
+    Raises
+    ------
+    ValueError
+        If ``seconds`` is negative, ``precision`` is less than 1, or there are no unit names for the locale.
+    """
+    ...
+
+def plural_category(
+    locale: str, n: int | float | str, /, ordinal: bool = False
+) -> _Literal["zero", "one", "two", "few", "many", "other"]:
+    """Get the CLDR plural category of a number, as used to pick the variant in translations.
+
+    Parameters
+    ----------
+    locale : str
+        The locale to use the plural rules of. This can be any locale CLDR has rules for, not just the
+        ones translated into.
+    n : int | float | str
+        The number. Pass a string to keep its formatting, e.g. in en-US ``'1.0'`` is ``'other'`` while
+        ``1`` is ``'one'``.
+    ordinal : bool, optional
+        Use the ordinal rules (1st, 2nd, ...) instead of the cardinal ones. Defaults to ``False``.
+
+    Returns
+    -------
+    {'zero', 'one', 'two', 'few', 'many', 'other'}
+        The plural category.
+
+    Raises
+    ------
+    LocaleNotFoundError
+        If there are no plural rules for the locale.
+    TypeError
+        If ``n`` is not an int, float or str.
+    ValueError
+        If ``n`` isn't a number the plural rules can read, like the string ``'abc'`` or NaN.
+    """
+    ...
+
+def negotiate_locale(requested: list[str]) -> _Literal["en-US", "es-ES", "fr", "nl"]:
+    """Pick the available locale that best matches the requested ones, e.g. from a discord interaction.
+
+    Parameters
+    ----------
+    requested : list[str]
+        The locales the user wants, most preferred first, e.g. ``['es-419', 'en-GB']``. Regional
+        variants match their language, so ``'es-419'`` gets ``'es-ES'``.
+
+    Returns
+    -------
+    {'en-US', 'es-ES', 'fr', 'nl'}
+        The best available locale, en-US if none of them match.
+    """
+    ...
+
+def detect_language(text: str) -> _Literal["en-US", "es-ES", "fr", "nl"] | None:
+    """Guess which available locale a message was written in, to reply in it when a user hasn't picked a locale.
+
+    The guess is from the most common words and the letters typical of each language, so it works for
+    messages of a few words, but not for a lone word shared by languages or a mix of them.
+
+    Parameters
+    ----------
+    text : str
+        The message.
+
+    Returns
+    -------
+    {'en-US', 'es-ES', 'fr', 'nl'} | None
+        The locale, or None if the message is too short or unclear to tell. Locales added with
+        ``add_locale`` are never guessed.
+    """
+    ...
+
+def set_fallback_chain(locale: str, chain: list[_Literal["en-US", "es-ES", "fr", "nl"]]) -> None:
+    """Set the locales to try, in order, for each key when translating into a locale.
+
+    By default each available locale falls back to en-US, and some regional locales like ``es-MX`` and
+    ``fr-BE`` fall back to their closest available locale first.
+
+    Parameters
+    ----------
+    locale : str
+        The locale tag to set the chain for, e.g. ``'es-MX'``.
+    chain : list[{'en-US', 'es-ES', 'fr', 'nl'}]
+        The locales to try. en-US is added to the end if it isn't in the chain.
+
+    Raises
+    ------
+    LocaleNotFoundError
+        If the chain is empty, or any locale in it is not available.
+    """
+    ...
+
+def add_locale(code: str, source: str | _PathLike[str]) -> None:
+    """Add a locale that isn't built in, e.g. community contributed translations, without a new release.
+
+    Parameters
+    ----------
+    code : str
+        The locale code, e.g. ``'de'``. The locale can be translated into, used in fallback chains and
+        negotiated like the built in ones.
+    source : str | os.PathLike[str]
+        The FTL messages of the locale, or the path of an ``.ftl`` file holding them. Keys missing from
+        them fall back to en-US.
+
     Raises
     ------
     RuntimeError
-        If anything errors.
+        If the code is built in or not a valid locale code, the file can't be read, or the messages
+        are not valid FTL. Nothing is added when this happens.
+
+    Notes
+    -----
+    Adding a locale again replaces its messages. Numbers and dates in added locales are formatted like
+    en-US, and existing ``Translator`` instances keep the messages they were created with.
+    """
+    ...
+
+def available_locales() -> set[str]:
+    """Get the locales that can be translated into.
+
+    Returns
+    -------
+    set[str]
+        The supported locale codes, e.g. ``{'en-US', 'es-ES', 'fr', 'nl'}``, including the ones added with
+        ``add_locale``.
+    """
+    ...
+
+def message_keys(locale: _Literal["en-US", "es-ES", "fr", "nl"]) -> set[str]:
+    """Get the keys of every message available in a locale.
+
+    Parameters
+    ----------
+    locale : {'en-US', 'es-ES', 'fr', 'nl'}
+        The locale to list the keys of. Unknown locales are not replaced by en-US here.
+
+    Returns
+    -------
+    set[str]
+        The message keys, as passed to ``translate``.
+
+    Raises
+    ------
+    LocaleNotFoundError
+        If the locale is unknown, or its translations couldn't be loaded.
+    """
+    ...
+
+def message_metadata(locale: _Literal["en-US", "es-ES", "fr", "nl"]) -> dict[str, MessageMetadata]:
+    """Get the comment and placeables of every message available in a locale, e.g. to document them.
+
+    Parameters
+    ----------
+    locale : {'en-US', 'es-ES', 'fr', 'nl'}
+        The locale to describe the messages of. Unknown locales are not replaced by en-US here.
+
+    Returns
+    -------
+    dict[str, MessageMetadata]
+        The comment right above each message and the variables it uses, by message key.
+
+    Raises
+    ------
+    LocaleNotFoundError
+        If the locale is unknown, or its translations couldn't be loaded.
+    """
+    ...
+
+class MessageMetadata:
+    """What tooling needs to know about a message, such as to write a handbook for translators."""
+
+    @property
+    def comment(self) -> str | None:
+        """The ``#`` comment right above the message, without the leading ``#``, if it has one."""
+        ...
+    @property
+    def placeables(self) -> set[str]:
+        """The names of the variables the message uses anywhere, in its value or its attributes."""
+        ...
+
+def check_bundles() -> dict[str, BundleReport]:
+    """Compare the translations of every locale against en-US, e.g. to check translation PRs in tests.
+
+    Returns
+    -------
+    dict[str, BundleReport]
+        A report for every locale but en-US, by locale code. ``BundleReport.is_consistent()`` is true
+        when the locale has exactly the keys of en-US, using the same variables.
+
+    Raises
+    ------
+    LocaleNotFoundError
+        If the translations couldn't be loaded.
+    """
+    ...
+
+class BundleReport:
+    """How one locale differs from en-US."""
+
+    @property
+    def missing_keys(self) -> set[str]:
+        """Keys en-US has that the locale doesn't."""
+        ...
+    @property
+    def mismatched_placeables(self) -> dict[str, set[str]]:
+        """Keys whose variables differ from en-US, with the variables only one of them uses."""
+        ...
+    @property
+    def extra_keys(self) -> set[str]:
+        """Keys the locale has that en-US doesn't."""
+        ...
+    def is_consistent(self) -> bool:
+        """Whether the locale has exactly the keys of en-US, using the same variables."""
+        ...
+
+def validate_ftl(source: str, /) -> list[Diagnostic]:
+    """Check an FTL source, such as a translation submitted by the community, before using it.
+
+    Parameters
+    ----------
+    source : str
+        The FTL source to check.
+
+    Returns
+    -------
+    list[Diagnostic]
+        Everything wrong with the source, in the order it appears, empty if it is fine. Diagnostics
+        with the ``'error'`` severity stop the source from loading, ``'warning'`` ones are likely
+        mistakes, such as a message defined twice.
+    """
+    ...
+
+class Diagnostic:
+    """Something wrong with an FTL source, and where it is."""
+
+    @property
+    def line(self) -> int:
+        """The line it is on, starting from 1."""
+        ...
+    @property
+    def column(self) -> int:
+        """The character in the line it starts at, starting from 1."""
+        ...
+    @property
+    def message(self) -> str:
+        """What is wrong."""
+        ...
+    @property
+    def severity(self) -> _Literal["error", "warning"]:
+        """``'error'`` for what stops the source from loading, ``'warning'`` for what loads but is likely a mistake."""
+        ...
+
+class Translator:
+    """A translator bound to a single locale, reusable for as many translations as needed.
+
+    Parameters
+    ----------
+    locale : {'en-US', 'es-ES', 'fr', 'nl'}
+        The locale to translate to. Unknown locales fall back to en-US, unless ``strict`` is set.
+        Keys missing from the locale are looked up along its fallback chain, see ``set_fallback_chain``.
+    strict : bool, optional
+        Raise instead of falling back along the chain when the locale is unknown, or a message is
+        missing or fails to format. Defaults to ``False``.
+    lenient : bool, optional
+        Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+        ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+    """
+
+    def __init__(
+        self, locale: _Literal["en-US", "es-ES", "fr", "nl"], strict: bool = False, lenient: bool = False
+    ) -> None: ...
+    def __new__(
+        cls, locale: _Literal["en-US", "es-ES", "fr", "nl"], strict: bool = False, lenient: bool = False
+    ) -> "Translator": ...
+    @staticmethod
+    def with_overrides(
+        locale: _Literal["en-US", "es-ES", "fr", "nl"], overrides: str, strict: bool = False, lenient: bool = False
+    ) -> "Translator":
+        """Create a translator that checks ``overrides`` before the built in translations.
+
+        Parameters
+        ----------
+        locale : {'en-US', 'es-ES', 'fr', 'nl'}
+            The locale to translate to. Unknown locales fall back to en-US, unless ``strict`` is set.
+        overrides : str
+            An FTL source, such as a guild's custom strings. It only needs to define the messages it
+            changes, anything else uses the built in translations.
+        strict : bool, optional
+            Raise instead of falling back along the chain when the locale is unknown, or a message is
+            missing or fails to format. Defaults to ``False``.
+        lenient : bool, optional
+            Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+            ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+
+        Raises
+        ------
+        LocaleNotFoundError
+            If the translations couldn't be loaded, or ``strict`` is set and the locale is unknown.
+        FormattingError
+            If ``overrides`` is not valid FTL.
+        """
+        ...
+    def with_terms(self, terms: dict[str, str], /) -> "Translator":
+        """Create a copy of this translator with ``terms`` replacing the terms shared by every locale.
+
+        Parameters
+        ----------
+        terms : dict[str, str]
+            Values for terms, by name without the leading ``-``, such as ``{'bot-name': 'Charbot'}``
+            for a guild's nickname for the bot. Overrides see the new values too.
+
+        Raises
+        ------
+        FormattingError
+            If a name in ``terms`` is not a valid term name.
+        """
+        ...
+    @property
+    def locale(self) -> _Literal["en-US", "es-ES", "fr", "nl"]:
+        """The locale this translator translates into."""
+        ...
+    @property
+    def strict(self) -> bool:
+        """Whether this translator errors instead of falling back along its chain."""
+        ...
+    @property
+    def lenient(self) -> bool:
+        """Whether this translator warns about missing arguments instead of failing."""
+        ...
+    @property
+    def fallback_chain(self) -> list[_Literal["en-US", "es-ES", "fr", "nl"]]:
+        """The locales tried in order for each key, starting with ``locale``."""
+        ...
+    def translate(self, key: str, args: dict[str, _Arg]) -> str:
+        """Translate a string into this translator's locale.
+
+        Parameters
+        ----------
+        key : str
+            The key to translate, or ``key.attribute`` to translate one of its attributes.
+        args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]
+            The arguments to format the string with. If no arguments, pass an empty dict, ie ``{}``. Booleans
+            select ``[true]`` or ``[false]`` variants, datetimes format like ``DATETIME`` for the locale, lists
+            are joined like ``a, b, and c``, enum members select the variant named after their str value or their
+            name, raising if there isn't one, and ``None`` formats as nothing.
+
+        Returns
+        -------
+        str
+            The translated string.
+
+        Raises
+        ------
+        TranslationKeyError
+            If the key doesn't exist.
+        FormattingError
+            If the message couldn't be formatted with ``args``, other than missing arguments when the
+            translator is lenient.
+        """
+        ...
+
+class Buffer:
+    """Bytes made in rust, like an image or a saved game, which python reads in place through the buffer
+    protocol instead of copying them into ``bytes``.
+
+    ``memoryview``, ``io.BytesIO``, ``discord.File`` and PIL take it as is, and ``bytes(buffer)`` makes a
+    copy for when it has to be ``bytes``. The functions taking binary data take it too, along with
+    ``bytes``, ``bytearray`` and ``memoryview``, none of which are copied either.
+    """
+
+    def __buffer__(self, flags: int, /) -> memoryview: ...
+    def __bytes__(self) -> bytes: ...
+    def __len__(self) -> int: ...
+
+def init_logging(level: int | str, /) -> None:
+    """Forward what goes wrong inside charbot_rust to the logging module, at the level given and above.
+
+    Events are logged to ``charbot_rust.fluent`` for translations, like a locale falling back to another
+    because a message failed to format, ``charbot_rust.games`` for games, like a saved game that couldn't
+    be loaded, and ``charbot_rust.tasks`` for the thread pool of the awaitable functions. Nothing is
+    forwarded until this is called, and calling it again changes the level.
+
+    Parameters
+    ----------
+    level : int | str
+        The lowest level to forward, like ``logging.INFO`` or ``'WARNING'``.
+
+    Raises
+    ------
+    ValueError
+        If the level is a name that isn't a level of the logging module.
+    """
+    ...
+
+class Profile:
+    """How long a function, or a part of one, took and how many allocations it made while profiling."""
+
+    @property
+    def name(self) -> str:
+        """The function, like ``fluent.translate``, or a part of it, like ``fluent.translate/format``."""
+        ...
+    @property
+    def calls(self) -> int:
+        """How many times it was called."""
+        ...
+    @property
+    def seconds(self) -> float:
+        """How many seconds the calls took altogether."""
+        ...
+    @property
+    def slowest(self) -> float:
+        """How many seconds the slowest call took."""
+        ...
+    @property
+    def allocations(self) -> int:
+        """How many allocations the calls made altogether, on the thread they were called on."""
+        ...
+
+def set_profiling(enabled: bool, /) -> None:
+    """Turn profiling on or off. While it's on, the functions that ``charbot_rust.metrics`` counts, and the
+    parts of the slow ones, like making a translator, formatting a message and finishing the translation,
+    are timed and their allocations are counted.
+
+    It's off by default, as it slows every function down a little and every allocation a little more.
+
+    Parameters
+    ----------
+    enabled : bool
+        Whether to profile. Turning it on starts a new report, and turning it off keeps the report.
+    """
+    ...
+
+def profiling_report() -> list[Profile]:
+    """Get what has been profiled since profiling was turned on.
+
+    A function's profile includes those of its parts, and doesn't include converting its arguments and
+    result between python and rust, so that's how much longer a call takes timed in python. Allocations
+    are counted on the thread the function was called on, so ones it leaves to other threads, like a
+    thread pool, aren't counted.
+
+    Returns
+    -------
+    list[Profile]
+        A profile for each function and part that was called, the slowest altogether first.
     """
     ...
@@ -0,0 +1,200 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Side(Enum):
+    """Represents a side in the game, black starts at the top and always moves first."""
+
+    Black: ...
+    White: ...
+
+    def swap(self) -> "Side":
+        """The other side."""
+        ...
+
+class Piece:
+    """A piece on the board."""
+
+    side: Side
+    king: bool
+    """Whether the piece was crowned, kings can move backwards too."""
+
+    @property
+    def letter(self) -> str:
+        """The letter the piece is drawn as, ``b`` or ``w``, capitalized for a king."""
+        ...
+
+class Game:
+    """A rust based implementation of checkers against the computer, with the usual english rules.
+
+    The dark squares are numbered from 1 at the top left to 32 at the bottom right, and moves are
+    written in the usual notation with them, like ``11-15`` for a move or ``15x24x31`` for a capture.
+    Captures have to be made, all the way to the end if there are several jumps in a row, and a man
+    reaching the far side is crowned, which ends the move.
+
+    Parameters
+    ----------
+    depth : int
+        How many moves ahead the computer looks, from 1 to 8. The default is 6, which takes around
+        10ms a move, the higher depths are stronger but slower, up to about 100ms.
+    human_first : bool
+        Whether the player moves first as black, otherwise the computer makes its first move as black
+        right away. The default is True.
+
+    Raises
+    ------
+    ValueError
+        If the depth isn't from 1 to 8.
+    """
+
+    def __init__(self, depth: int = 6, human_first: bool = True): ...
+    def __new__(cls, depth: int = 6, human_first: bool = True) -> "Game": ...
+    @property
+    def board(self) -> list[list[Piece | None]]:
+        """The board for the game, as 8 rows of 8 squares from the top row down, None for the empty squares."""
+        ...
+    @property
+    def player(self) -> Side:
+        """The side the player is playing as."""
+        ...
+    def play(self, move: str) -> str | None:
+        """Make the player's move, and have the computer reply if the game is not complete.
+
+        Parameters
+        ----------
+        move: str
+            The move, like ``11-15`` or ``15x24x31``. A capture can be given with just where it starts and
+            ends, like ``15x31``, if only one capture goes there.
+
+        Returns
+        -------
+        str | None
+            The computer's move, or None if the computer didn't move.
+
+        Raises
+        ------
+        ValueError
+            If the move isn't legal, which includes not capturing when a capture can be made, or the
+            game is already over.
+        """
+        ...
+    def legal_moves(self) -> list[str]:
+        """The moves the player can make, none once the game is over.
+
+        Returns
+        -------
+        list[str]
+            The moves, only the captures if any can be made.
+        """
+        ...
+    def winner(self) -> Side | None:
+        """Check what side, if any, has won, by leaving the other side without a piece it can move.
+
+        Returns
+        -------
+        Side | None
+            The side that won the game, or None if the game has no winner.
+        """
+        ...
+    def is_draw(self) -> bool:
+        """Check if the game is drawn.
+
+        A game is drawn once the same position comes up three times, or after 40 moves each without a
+        capture or a man moving.
+
+        Returns
+        -------
+        bool
+            True if the game is a draw, False otherwise.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if the game is finished, by a win for either side or a draw.
+
+        Returns
+        -------
+        bool
+            True if no more moves can be made, False otherwise.
+        """
+        ...
+    def has_player_won(self) -> bool:
+        """Check if the player has won the game.
+
+        Returns
+        -------
+        bool
+            True if the player has won, False otherwise.
+        """
+        ...
+    def has_player_lost(self) -> bool:
+        """Check if the player has lost the game.
+
+        Returns
+        -------
+        bool
+            True if the player has lost, False otherwise.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
@@ -0,0 +1,148 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+STARTING_FEN: str
+"""The FEN of the starting position."""
+
+class Status(Enum):
+    """Where a game stands for the side to move.
+
+    Ongoing:
+        The side to move has moves and isn't in check.
+    Check:
+        The side to move is in check, but can get out of it.
+    Checkmate:
+        The side to move is in check and can't get out of it, the game is lost for them.
+    Stalemate:
+        The side to move isn't in check but has no moves, the game is a draw.
+    """
+
+    Ongoing: ...
+    Check: ...
+    Checkmate: ...
+    Stalemate: ...
+
+def validate_fen(fen: str, /) -> str:
+    """Check a FEN describes a position that can be played from.
+
+    Parameters
+    ----------
+    fen : str
+        The position. The move counters can be left out, and default to 0 and 1.
+
+    Returns
+    -------
+    str
+        The FEN written out in full, without any castling rights the king and rooks aren't in place for.
+
+    Raises
+    ------
+    ValueError
+        If the FEN is malformed, a side doesn't have exactly one king, a pawn is on the first or last
+        rank, or the side not to move is in check.
+    """
+
+def legal_moves(fen: str, /) -> list[str]:
+    """Every legal move in a position.
+
+    Parameters
+    ----------
+    fen : str
+        The position.
+
+    Returns
+    -------
+    list[str]
+        The moves in UCI notation, like 'e2e4' or 'e7e8q', none if the game is over.
+
+    Raises
+    ------
+    ValueError
+        If the FEN isn't valid.
+    """
+
+def is_legal(fen: str, move: str, /) -> bool:
+    """Check if a move can be played in a position.
+
+    Parameters
+    ----------
+    fen : str
+        The position.
+    move : str
+        The move, in SAN like 'Nf3' or UCI like 'g1f3'. Check marks and annotations are ignored, and
+        castling can be written with zeros.
+
+    Returns
+    -------
+    bool
+        True if the move is legal, False if it isn't or can't be read as a move.
+
+    Raises
+    ------
+    ValueError
+        If the FEN isn't valid.
+    """
+
+def apply_move(fen: str, move: str, /) -> str:
+    """Play a move in a position.
+
+    Parameters
+    ----------
+    fen : str
+        The position.
+    move : str
+        The move, in SAN like 'Nf3' or UCI like 'g1f3'. Check marks and annotations are ignored, and
+        castling can be written with zeros.
+
+    Returns
+    -------
+    str
+        The FEN of the position after the move.
+
+    Raises
+    ------
+    ValueError
+        If the FEN isn't valid, or the move isn't legal.
+    """
+
+def to_san(fen: str, move: str, /) -> str:
+    """Write a move in standard algebraic notation, for move lists.
+
+    Parameters
+    ----------
+    fen : str
+        The position the move is played in.
+    move : str
+        The move, in SAN or UCI.
+
+    Returns
+    -------
+    str
+        The move in SAN, like 'Nbd7', 'exd5', 'e8=Q+' or 'O-O-O#'.
+
+    Raises
+    ------
+    ValueError
+        If the FEN isn't valid, or the move isn't legal.
+    """
+
+def status(fen: str, /) -> Status:
+    """Check if the side to move is in check, checkmated or stalemated.
+
+    Parameters
+    ----------
+    fen : str
+        The position.
+
+    Returns
+    -------
+    Status
+        Where the game stands for the side to move.
+
+    Raises
+    ------
+    ValueError
+        If the FEN isn't valid.
+    """
@@ -0,0 +1,22 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+import sys as __sys
+
+# noinspection PyProtectedMember
+from charbot_rust._charbot_rust import _chess
+
+if hasattr(_chess, "__doc__"):
+    __doc__ = _chess.__doc__
+
+if hasattr(_chess, "__all__"):
+    __all__ = (*_chess.__all__,)  # pyright: ignore[reportUnsupportedDunderAll]
+    __name: str
+    for __name in _chess.__all__:
+        setattr(__sys.modules[__name__], __name, getattr(_chess, __name))
+    try:
+        del __name  # pyright: ignore[reportUnboundVariable]
+    except NameError:
+        pass
+
+del _chess
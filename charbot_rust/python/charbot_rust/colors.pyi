@@ -0,0 +1,76 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+def dominant_color(image_bytes: _BytesLike, /) -> int:
+    """Find the color most of an image is, such as to color an embed to match someone's avatar.
+
+    Transparent parts of the image are left out. Large images are sampled, so this is fast for any
+    image, and it happens without holding the GIL, so other threads can run meanwhile.
+
+    Parameters
+    ----------
+    image_bytes : bytes-like
+        The image, as a file in any common format.
+
+    Returns
+    -------
+    int
+        The color, as a 0xRRGGBB integer, like ``discord.Colour`` takes.
+
+    Raises
+    ------
+    ValueError
+        If the image is too big, can't be read, or is completely transparent.
+    """
+
+def contrast_ratio(a: int, b: int, /) -> float:
+    """How much two colors contrast, as WCAG measures it for how readable text is.
+
+    Parameters
+    ----------
+    a : int
+        A color, as a 0xRRGGBB integer.
+    b : int
+        The other color.
+
+    Returns
+    -------
+    float
+        The contrast, from 1 for the same color to 21 for black and white. WCAG asks for at least 4.5
+        for normal text and 3 for large text.
+
+    Raises
+    ------
+    ValueError
+        If either isn't a color.
+    """
+
+def readable_on(background: int, color: int | None = None, minimum: float = 4.5) -> int:
+    """Find a color that's readable on a background, such as an embed accent on Discord's dark theme.
+
+    Parameters
+    ----------
+    background : int
+        The background, as a 0xRRGGBB integer.
+    color : int | None
+        The color to use if it's readable, otherwise it's lightened or darkened as little as needed to
+        be, like for an accent from ``dominant_color``. The default is None, for white or black.
+    minimum : float
+        The least contrast the color needs with the background, from 1 to 21. The default is 4.5, what
+        WCAG asks for normal text.
+
+    Returns
+    -------
+    int
+        The readable color, as a 0xRRGGBB integer, like ``discord.Colour`` takes. If no color contrasts
+        enough, whichever of white and black contrasts more.
+
+    Raises
+    ------
+    ValueError
+        If the background or the color isn't a color, or the minimum isn't from 1 to 21.
+    """
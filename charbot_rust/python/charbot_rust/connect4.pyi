@@ -0,0 +1,198 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Disc(Enum):
+    """Represents a disc in the game, red always moves first."""
+
+    Red: ...
+    Yellow: ...
+
+    def swap(self) -> "Disc":
+        """The other player's disc."""
+        ...
+    @property
+    def emoji(self) -> str:
+        """The emoji the disc is drawn as."""
+        ...
+
+class Game:
+    """A rust based implementation of connect four against the computer.
+
+    Parameters
+    ----------
+    depth : int
+        How many moves ahead the computer looks, from 1 to 8. The default is 6, which takes a few
+        milliseconds a move, the higher depths are stronger but slower, up to about 100ms.
+    human_first : bool
+        Whether the player moves first as red, otherwise the computer makes its first move as red right
+        away. The default is True.
+
+    Raises
+    ------
+    ValueError
+        If the depth isn't from 1 to 8.
+    """
+
+    def __init__(self, depth: int = 6, human_first: bool = True): ...
+    def __new__(cls, depth: int = 6, human_first: bool = True) -> "Game": ...
+    @property
+    def board(self) -> list[list[Disc | None]]:
+        """The board for the game, as 6 rows of 7 cells from the top row down, None for the empty cells."""
+        ...
+    @property
+    def player(self) -> Disc:
+        """The disc the player is playing as."""
+        ...
+    def play(self, column: int) -> int | None:
+        """Drop the player's disc in a column, and have the computer reply if the game is not complete.
+
+        Parameters
+        ----------
+        column: int
+            The column to drop the disc in, from 0 on the left to 6 on the right.
+
+        Returns
+        -------
+        int | None
+            The column the computer dropped its disc in, or None if the computer didn't move.
+
+        Raises
+        ------
+        ValueError
+            If the column isn't from 0 to 6, is full, or the game is already over.
+        """
+        ...
+    def legal_moves(self) -> list[int]:
+        """The columns the player can drop a disc in, none once the game is over.
+
+        Returns
+        -------
+        list[int]
+            The columns that aren't full, in order.
+        """
+        ...
+    def winner(self) -> Disc | None:
+        """Check what disc, if any, has four in a row.
+
+        Returns
+        -------
+        Disc | None
+            The disc that won the game, or None if the game has no winner.
+        """
+        ...
+    def is_draw(self) -> bool:
+        """Check if the board is full without anyone winning.
+
+        Returns
+        -------
+        bool
+            True if the game is a draw, False otherwise.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if the game is finished, by a win for either side or a draw.
+
+        Returns
+        -------
+        bool
+            True if no more moves can be made, False otherwise.
+        """
+        ...
+    def has_player_won(self) -> bool:
+        """Check if the player has won the game.
+
+        Returns
+        -------
+        bool
+            True if the player has won, False otherwise.
+        """
+        ...
+    def has_player_lost(self) -> bool:
+        """Check if the player has lost the game.
+
+        Returns
+        -------
+        bool
+            True if the player has lost, False otherwise.
+        """
+        ...
+    def to_emoji(self) -> str:
+        """The board as emoji, for an embed or message.
+
+        Each row is a line from the top, with a line of keycap numbers under the columns, ie::
+
+            ⚫⚫⚫⚫⚫⚫⚫
+            ...
+            🔴🟡⚫⚫⚫⚫⚫
+            1️⃣2️⃣3️⃣4️⃣5️⃣6️⃣7️⃣
+
+        Returns
+        -------
+        str
+            The emoji grid, the same as ``str(game)``.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
@@ -0,0 +1,65 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+class Kind(Enum):
+    """How often a cooldown resets."""
+
+    Daily: ...
+    """Resets at midnight local time every day."""
+    Weekly: ...
+    """Resets at midnight local time at the start of every monday."""
+
+def next_reset(kind: Kind, tz: str, anchor: int | None = None) -> int:
+    """Get when a cooldown next resets, at midnight in a time zone.
+
+    Time zones are read from the tz database installed on the system, the same one ``zoneinfo`` uses.
+
+    Parameters
+    ----------
+    kind : Kind
+        Whether the cooldown is daily or weekly, weekly ones reset at the start of monday.
+    tz : str
+        The name of the time zone in the tz database, like ``"America/New_York"`` or ``"UTC"``.
+    anchor : int | None
+        The unix timestamp in seconds to find the next reset after. The default is None, for now.
+
+    Returns
+    -------
+    int
+        The unix timestamp in seconds of the next reset, after the anchor. Days the clocks change on are
+        23 or 25 hours long, and if midnight is skipped the reset is when the clocks go forward.
+
+    Raises
+    ------
+    ValueError
+        If the time zone isn't in the tz database, or the anchor isn't from year 1 to 9999.
+    """
+
+def is_available(last_claim: int, kind: Kind, tz: str, now: int | None = None) -> bool:
+    """Check if a cooldown has reset since it was last claimed.
+
+    Parameters
+    ----------
+    last_claim : int
+        The unix timestamp in seconds it was last claimed at.
+    kind : Kind
+        Whether the cooldown is daily or weekly, weekly ones reset at the start of monday.
+    tz : str
+        The name of the time zone in the tz database, like ``"America/New_York"`` or ``"UTC"``.
+    now : int | None
+        The unix timestamp in seconds to check at. The default is None, for now.
+
+    Returns
+    -------
+    bool
+        True if midnight of a later day, or the start of a later week, has been reached since the last
+        claim, False otherwise.
+
+    Raises
+    ------
+    ValueError
+        If the time zone isn't in the tz database, or a timestamp isn't from year 1 to 9999.
+    """
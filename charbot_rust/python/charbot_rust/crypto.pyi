@@ -0,0 +1,99 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from typing import Literal
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+def verify_interaction(
+    public_key: str | _BytesLike, signature: str | _BytesLike, timestamp: str | _BytesLike, body: str | _BytesLike, /
+) -> bool:
+    """Check that an interaction sent to the bot's HTTP endpoint was signed by discord, which has to be
+    done for every request, answering ones that aren't with a 401.
+
+    The signature is checked in constant time, and the key is only parsed the first time it's used.
+
+    Parameters
+    ----------
+    public_key : str | bytes-like
+        The application's public key, from the developer portal, as hex or as the 32 bytes.
+    signature : str | bytes-like
+        The ``X-Signature-Ed25519`` header, as hex or as the 64 bytes.
+    timestamp : str | bytes-like
+        The ``X-Signature-Timestamp`` header.
+    body : str | bytes-like
+        The body of the request, exactly as it was sent.
+
+    Returns
+    -------
+    bool
+        Whether the signature is discord's. It's False for a signature that isn't 64 bytes or isn't hex.
+
+    Raises
+    ------
+    ValueError
+        If the public key isn't 32 bytes or 64 hex digits, or isn't a key of Ed25519.
+    """
+    ...
+
+def sign(secret: str | _BytesLike, payload: str | _BytesLike, /, encoding: Literal["hex", "base64"] = "hex") -> str:
+    """Sign a webhook's payload with HMAC-SHA256, the way GitHub and most other webhooks are signed.
+
+    Parameters
+    ----------
+    secret : str | bytes-like
+        The webhook's secret. Text is used as UTF-8.
+    payload : str | bytes-like
+        The payload, exactly as it's sent.
+    encoding : {'hex', 'base64'}
+        How to write the signature. The default is 'hex', which is what GitHub uses, without its
+        ``sha256=`` prefix.
+
+    Returns
+    -------
+    str
+        The signature.
+
+    Raises
+    ------
+    ValueError
+        If the encoding isn't 'hex' or 'base64'.
+    """
+    ...
+
+def verify(
+    secret: str | _BytesLike,
+    payload: str | _BytesLike,
+    signature: str | _BytesLike,
+    /,
+    encoding: Literal["hex", "base64"] = "hex",
+) -> bool:
+    """Check a webhook's HMAC-SHA256 signature in constant time, so how long it takes doesn't give away how
+    much of a forged signature is right, like comparing the strings in python would.
+
+    Parameters
+    ----------
+    secret : str | bytes-like
+        The webhook's secret. Text is used as UTF-8.
+    payload : str | bytes-like
+        The payload, exactly as it was received.
+    signature : str | bytes-like
+        The signature that came with it, as text in the encoding, which can start with ``sha256=`` like
+        GitHub's ``X-Hub-Signature-256`` header, or as the 32 bytes.
+    encoding : {'hex', 'base64'}
+        How the signature is written, if it's text. The default is 'hex'.
+
+    Returns
+    -------
+    bool
+        Whether it's the payload's signature. It's False for a signature that isn't 32 bytes in the
+        encoding.
+
+    Raises
+    ------
+    ValueError
+        If the encoding isn't 'hex' or 'base64'.
+    """
+    ...
@@ -0,0 +1,149 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Keep:
+    """Which dice of a roll count towards its total, the rest are dropped.
+
+    Attributes
+    ----------
+    highest : bool
+        Whether the highest dice are kept, otherwise the lowest are.
+    count : int
+        How many dice are kept.
+    """
+
+    highest: bool
+    count: int
+
+class Term:
+    """One part of an expression, a number of dice or a constant, added or subtracted.
+
+    ``str(term)`` is the term without its sign, like '4d6kh3' or '2'.
+
+    Attributes
+    ----------
+    negative : bool
+        Whether the term is subtracted.
+    count : int
+        The number of dice, or the value of a constant.
+    sides : int | None
+        The sides of each die, None for a constant.
+    keep : Keep | None
+        Which dice count, None if they all do.
+    """
+
+    negative: bool
+    count: int
+    sides: int | None
+    keep: Keep | None
+
+    @property
+    def is_constant(self) -> bool:
+        """Whether the term is a constant rather than dice."""
+        ...
+
+class Expression:
+    """A parsed dice expression, ``str(expression)`` is the expression written out in full.
+
+    Dice are written as ``NdS``, with an optional ``N`` of 1 and ``%`` for 100 sides, followed by ``khN``
+    (or ``kN``) or ``klN`` to keep the highest or lowest ``N``, ``dhN`` or ``dlN`` to drop them, or ``adv``
+    and ``dis`` to keep the highest or lowest of two. Terms are added or subtracted with ``+`` and ``-``,
+    spaces and case are ignored. There can be up to 100 dice in a term with up to 1000 sides, and up to
+    20 terms.
+
+    Parameters
+    ----------
+    text : str
+        The expression, like ``4d6kh3+2``, ``2d20adv`` or ``d%``.
+
+    Raises
+    ------
+    ValueError
+        If the expression isn't valid.
+    """
+
+    def __init__(self, text: str): ...
+    def __new__(cls, text: str) -> "Expression": ...
+    @property
+    def terms(self) -> list[Term]:
+        """The terms of the expression, in order."""
+        ...
+    @property
+    def min(self) -> int:
+        """The lowest total the expression can roll."""
+        ...
+    @property
+    def max(self) -> int:
+        """The highest total the expression can roll."""
+        ...
+    def roll(self, seed: int | None = None) -> "Roll":
+        """Roll the expression.
+
+        Parameters
+        ----------
+        seed : int | None
+            The seed for the dice, so a roll can be reproduced. The default is None, for a random roll.
+
+        Returns
+        -------
+        Roll
+            The total, and what each die rolled.
+        """
+        ...
+
+class TermRoll:
+    """What a term of an expression rolled, ``str(term_roll)`` is like '4d6kh3 (6, 5, ~~2~~, 4)'.
+
+    Attributes
+    ----------
+    term : Term
+        The term that was rolled.
+    rolls : list[int]
+        What each die rolled, in the order they were rolled, empty for a constant.
+    kept : list[bool]
+        Whether each die counts towards the total.
+    total : int
+        What the term added to the total, negative if it was subtracted.
+    """
+
+    term: Term
+    rolls: list[int]
+    kept: list[bool]
+    total: int
+
+class Roll:
+    """A rolled expression, ``str(roll)`` is a breakdown for display, like '4d6kh3 (6, 5, ~~2~~, 4) + 2 = 17'.
+
+    Attributes
+    ----------
+    total : int
+        The total of the roll.
+    terms : list[TermRoll]
+        What each term of the expression rolled.
+    """
+
+    total: int
+    terms: list[TermRoll]
+
+def roll(expression: str, seed: int | None = None) -> Roll:
+    """Parse a dice expression and roll it.
+
+    Parameters
+    ----------
+    expression : str
+        The expression, like ``4d6kh3+2``, ``2d20adv`` or ``d%``.
+    seed : int | None
+        The seed for the dice, so a roll can be reproduced. The default is None, for a random roll.
+
+    Returns
+    -------
+    Roll
+        The total, and what each die rolled.
+
+    Raises
+    ------
+    ValueError
+        If the expression isn't valid.
+    """
+    ...
@@ -0,0 +1,68 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from datetime import datetime as _datetime, timedelta as _timedelta
+
+def timestamp(dt: _datetime | int | float, style: str = "f") -> str:
+    """The markup discord shows as a time, like '<t:1709737445:f>', in each user's own time zone and
+    language.
+
+    Parameters
+    ----------
+    dt : datetime.datetime | int | float
+        The time, as a datetime or a unix timestamp in seconds. Naive datetimes are local time, like
+        datetime.timestamp(), and fractions of a second are dropped.
+    style : str
+        How discord shows the time, 't' for '3:04 PM', 'T' for '3:04:05 PM', 'd' for '03/06/2024', 'D'
+        for 'March 6, 2024', 'f' for 'March 6, 2024 3:04 PM', 'F' for 'Wednesday, March 6, 2024 3:04 PM'
+        and 'R' for 'in 2 hours'. The default is 'f', like discord's.
+
+    Returns
+    -------
+    str
+        The markup.
+
+    Raises
+    ------
+    ValueError
+        If the style isn't one of the letters above.
+    """
+
+def relative(dt: _datetime | int | float) -> str:
+    """The markup discord shows as how long ago or until a time, like 'in 2 hours', which it keeps up to
+    date.
+
+    Parameters
+    ----------
+    dt : datetime.datetime | int | float
+        The time, as a datetime or a unix timestamp in seconds. Naive datetimes are local time, like
+        datetime.timestamp().
+
+    Returns
+    -------
+    str
+        The markup, like '<t:1709737445:R>'.
+    """
+
+def countdown(until: _datetime | _timedelta | int | float, now: int | None = None) -> str:
+    """The markup discord shows as a countdown to a time, like 'in 5 minutes', for giveaways and events
+    ending.
+
+    Parameters
+    ----------
+    until : datetime.datetime | datetime.timedelta | int | float
+        When the countdown ends, as a datetime or a unix timestamp in seconds, or as a timedelta from now.
+        Fractions of a second in a timedelta are rounded up, so the countdown never ends early.
+    now : int | None
+        The unix timestamp in seconds a timedelta is from. The default is None, for now.
+
+    Returns
+    -------
+    str
+        The markup, like '<t:1709737445:R>'.
+
+    Raises
+    ------
+    ValueError
+        If a timedelta ends outside of year 1 to 9999.
+    """
@@ -0,0 +1,430 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Tier:
+    """A rarity in a drop table, with how often it drops and what it drops.
+
+    Parameters
+    ----------
+    name : str
+        The name of the tier, like ``"legendary"``.
+    weight : int
+        How likely the tier is against the weights of the others. A tier with a weight of 0 only drops from pity.
+    items : list[str]
+        The items the tier drops, each as likely as the others.
+    pity : int | None
+        How many rolls in a row it takes to be sure of the tier or a rarer one. The default is None, for never.
+
+    Raises
+    ------
+    ValueError
+        If the name or items are empty, the pity is 0, or the tier could never drop.
+    """
+
+    name: str
+    weight: int
+    items: list[str]
+    pity: int | None
+
+    def __init__(self, name: str, weight: int, items: list[str], pity: int | None = None) -> None: ...
+
+class Roll:
+    """What a roll dropped.
+
+    Attributes
+    ----------
+    tier : str
+        The name of the tier that dropped.
+    item : str
+        The item that dropped.
+    guaranteed : bool
+        Whether pity made sure of the tier, or one at least as rare.
+    pity : list[int]
+        The pity counters after the roll, to store for the user and pass to their next roll.
+    """
+
+    tier: str
+    item: str
+    guaranteed: bool
+    pity: list[int]
+
+class Table:
+    """A drop table, with tiers from the most common to the rarest.
+
+    Pity is counted for each tier as the rolls since it or a rarer tier dropped. The rarest tier whose
+    pity a roll reaches is guaranteed, so the roll is only between it and the tiers rarer than it.
+
+    Parameters
+    ----------
+    tiers : list[Tier]
+        The tiers, from the most common to the rarest.
+
+    Raises
+    ------
+    ValueError
+        If there are no tiers, two have the same name, or none of them have a weight.
+    """
+
+    tiers: list[Tier]
+
+    def __init__(self, tiers: list[Tier]) -> None: ...
+    def chances(self) -> list[tuple[str, float]]:
+        """Get the chance of each tier on a roll that pity doesn't change."""
+        ...
+    def roll(self, pity: list[int] | None = None, seed: int | None = None) -> Roll:
+        """Roll once.
+
+        Parameters
+        ----------
+        pity : list[int] | None
+            The pity counters from the user's last roll, one for each tier. The default is None, for a new user.
+        seed : int | None
+            The seed for the roll, so it can be reproduced. The default is None, for a random roll.
+
+        Returns
+        -------
+        Roll
+            What dropped, and the pity counters to pass to the next roll.
+
+        Raises
+        ------
+        ValueError
+            If there isn't a pity counter for each tier.
+        """
+        ...
+    def roll_many(self, count: int, pity: list[int] | None = None, seed: int | None = None) -> list[Roll]:
+        """Roll several times in a row, like a ten pull, with the pity carried from each roll to the next.
+
+        Parameters
+        ----------
+        count : int
+            How many times to roll, from 1 to 1000.
+        pity : list[int] | None
+            The pity counters from the user's last roll, one for each tier. The default is None, for a new user.
+        seed : int | None
+            The seed for the rolls, so they can be reproduced. The default is None, for random rolls.
+
+        Returns
+        -------
+        list[Roll]
+            What each roll dropped, the pity counters of the last are the ones to keep.
+
+        Raises
+        ------
+        ValueError
+            If the count isn't from 1 to 1000, or there isn't a pity counter for each tier.
+        """
+        ...
+
+class Rules:
+    """How a balance changes while its owner is away.
+
+    Each period interest is added first, up to the cap, then once the grace is over the part of the
+    balance above the floor decays.
+
+    Parameters
+    ----------
+    interest : float
+        The fraction added each period, compounded, from 0 to 1. The default is 0.
+    decay : float
+        The fraction of the balance above the floor lost each period once the grace is over, from 0 to 1. The default is 0.
+    period : int
+        How long a period is, in seconds. The default is 86400, a day.
+    cap : int | None
+        The most interest can take a balance to. The default is None, for no limit.
+    floor : int
+        The least decay can take a balance to. The default is 0.
+    grace : int
+        How long in seconds someone can be away before their balance starts to decay. The default is 0.
+    max_periods : int | None
+        The most periods that count, like idle games that stop earning after a while offline. The default is None, for no limit.
+
+    Raises
+    ------
+    ValueError
+        If the interest or decay aren't from 0 to 1, the period or max periods are 0, the floor is
+        negative, or the cap is below the floor.
+    """
+
+    interest: float
+    decay: float
+    period: int
+    cap: int | None
+    floor: int
+    grace: int
+    max_periods: int | None
+
+    def __init__(
+        self,
+        interest: float = 0.0,
+        decay: float = 0.0,
+        period: int = 86400,
+        cap: int | None = None,
+        floor: int = 0,
+        grace: int = 0,
+        max_periods: int | None = None,
+    ) -> None: ...
+
+class Settlement:
+    """A balance brought up to date.
+
+    Attributes
+    ----------
+    balance : int
+        The new balance.
+    interest : int
+        How much interest was added, rounded down.
+    decay : int
+        How much was lost to decay, so the balance is always the old one plus the interest minus the decay.
+    periods : int
+        How many whole periods were settled, including any past the most that count.
+    settled_at : int
+        When the last settled period ended, to store as when the user was last seen so part of a period isn't lost.
+    """
+
+    balance: int
+    interest: int
+    decay: int
+    periods: int
+    settled_at: int
+
+def settle(balance: int, last_seen: int, rules: Rules, now: int | None = None) -> Settlement:
+    """Bring a balance up to date with the interest and decay since its owner was last seen.
+
+    Accounts can be settled whenever their owner runs a command, instead of updating every account on a
+    schedule, since settling once over a long time gives the same balance as settling every period.
+
+    Parameters
+    ----------
+    balance : int
+        The balance when its owner was last seen.
+    last_seen : int
+        The unix timestamp in seconds its owner was last seen, or that it was last settled at.
+    rules : Rules
+        The interest, decay and limits to settle with.
+    now : int | None
+        The unix timestamp in seconds to settle up to. The default is None, for now.
+
+    Returns
+    -------
+    Settlement
+        The new balance, the interest and decay that made it, and the time to store as when the owner was last seen.
+
+    Raises
+    ------
+    ValueError
+        If the balance is negative.
+    """
+
+class Status(Enum):
+    """Where a betting pool is up to."""
+
+    Open: ...
+    """Taking stakes."""
+    Closed: ...
+    """No more stakes, waiting for the outcome."""
+    Resolved: ...
+    """Paid out to the winners."""
+    Refunded: ...
+    """Every stake was given back."""
+
+class Payout:
+    """What a user gets back from a betting pool.
+
+    Attributes
+    ----------
+    user : int
+        The user.
+    stake : int
+        How much they staked.
+    payout : int
+        How much they get back, including their stake.
+    """
+
+    user: int
+    stake: int
+    payout: int
+
+class Resolution:
+    """How a betting pool ended.
+
+    Attributes
+    ----------
+    outcome : str | None
+        The outcome that won, None if the stakes were refunded.
+    rake : int
+        How much the house kept.
+    payouts : list[Payout]
+        What each user gets back, the most first, which with the rake adds up to everything staked.
+    """
+
+    outcome: str | None
+    rake: int
+    payouts: list[Payout]
+
+class Pool:
+    """A parimutuel betting pool, where everything staked on the losing outcomes is shared by the winners in proportion to their stakes.
+
+    Parameters
+    ----------
+    outcomes : list[str]
+        The outcomes that can be staked on, from 2 to 25 of them.
+    rake : float
+        The fraction of the pool the house keeps, from 0 up to 1. The default is 0.
+
+    Raises
+    ------
+    ValueError
+        If there aren't from 2 to 25 outcomes, one isn't named or has the same name as another, or the rake isn't from 0 up to 1.
+    """
+
+    outcomes: list[str]
+    rake: float
+    status: Status
+    winner: str | None
+    """The outcome that won, None until the pool is resolved."""
+    total: int
+    """Everything staked on every outcome."""
+
+    def __init__(self, outcomes: list[str], rake: float = 0.0) -> None: ...
+    def totals(self) -> list[tuple[str, int]]:
+        """Get how much was staked on each outcome."""
+        ...
+    def odds(self) -> list[tuple[str, float | None]]:
+        """Get what 1 staked on each outcome would pay back if it won, as things stand, None for outcomes nobody staked on."""
+        ...
+    def stake_of(self, user: int) -> tuple[str, int] | None:
+        """Get the outcome a user staked on and how much, None if they haven't."""
+        ...
+    def stake(self, user: int, outcome: str, amount: int) -> int:
+        """Stake on an outcome.
+
+        Users can add to their stake, but not stake on another outcome, so they can't cover every outcome.
+
+        Parameters
+        ----------
+        user : int
+            The user staking.
+        outcome : str
+            The outcome they're staking on.
+        amount : int
+            How much more they're staking.
+
+        Returns
+        -------
+        int
+            Everything the user has staked.
+
+        Raises
+        ------
+        ValueError
+            If the pool is closed, the outcome is unknown, the amount is 0, the user staked on another
+            outcome, or the pool would be too big.
+        """
+        ...
+    def close(self) -> None:
+        """Stop taking stakes, like when the event starts.
+
+        Raises
+        ------
+        ValueError
+            If the pool is already closed.
+        """
+        ...
+    def resolve(self, outcome: str) -> Resolution:
+        """Pay out to the winners of an outcome.
+
+        After the rake, each winner gets their share rounded down, and what's left from rounding goes one
+        each to the winners that lost the most to it, so the payouts and rake add up to exactly what was
+        staked. If nobody staked on the outcome, every stake is given back without a rake.
+
+        Parameters
+        ----------
+        outcome : str
+            The outcome that won.
+
+        Returns
+        -------
+        Resolution
+            The rake and what each user gets back.
+
+        Raises
+        ------
+        ValueError
+            If the pool was already resolved or refunded, or the outcome is unknown.
+        """
+        ...
+    def refund(self) -> Resolution:
+        """Give every stake back, like when the event is cancelled.
+
+        Raises
+        ------
+        ValueError
+            If the pool was already resolved or refunded.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the pool as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved pool, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Pool":
+        """Load a pool saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved pool.
+
+        Returns
+        -------
+        Pool
+            The pool, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved pool, is from another version, or doesn't add up.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the pool as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved pool, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Pool":
+        """Load a pool saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved pool.
+
+        Returns
+        -------
+        Pool
+            The pool, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved pool, is from another version, or doesn't add up.
+        """
+        ...
@@ -0,0 +1,84 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+def is_emoji(grapheme: str, /) -> bool:
+    """Check whether text is exactly one emoji, like a flag, a keycap, or a family of emoji joined by zero width
+    joiners, whether or not it has skin tones.
+
+    Parameters
+    ----------
+    grapheme : str
+        The text.
+
+    Returns
+    -------
+    bool
+        Whether it's one emoji and nothing else.
+    """
+    ...
+
+def name(emoji: str, /) -> str | None:
+    """Get the name of an emoji, like Unicode's, so 👋🏽 is "waving hand: medium skin tone" and 🇯🇵 is "flag:
+    Japan".
+
+    Parameters
+    ----------
+    emoji : str
+        The emoji, with or without its variation selectors.
+
+    Returns
+    -------
+    str | None
+        Its name, or None if it isn't an emoji, or is emoji joined in a way Unicode doesn't recommend.
+    """
+    ...
+
+def from_shortcode(shortcode: str, /) -> str | None:
+    """Get the emoji of a shortcode, like Discord's, so ":tada:" is 🎉 and ":wave_tone3:" is 👋🏽.
+
+    Parameters
+    ----------
+    shortcode : str
+        The shortcode, with or without its colons.
+
+    Returns
+    -------
+    str | None
+        The emoji, fully qualified, so it shows as an emoji, or None if there's no emoji with the shortcode.
+    """
+    ...
+
+def extract(text: str, /) -> list[str]:
+    """Find the emoji in text, a whole emoji at a time, so skin tones, flags, keycaps and emoji joined by zero
+    width joiners, like families, are each one emoji, the way they're shown.
+
+    Parameters
+    ----------
+    text : str
+        The text.
+
+    Returns
+    -------
+    list[str]
+        The emoji, as they are in the text, in order. Characters that are usually text, like ❤ and ©, are
+        emoji unless they're followed by the variation selector asking for them to be shown as text, and
+        digits, # and * are only emoji as keycaps.
+    """
+    ...
+
+def demojize(text: str, /) -> str:
+    """Replace the emoji in text with their shortcodes, like Discord writes them, so "gg 🎉" is "gg :tada:".
+
+    Parameters
+    ----------
+    text : str
+        The text.
+
+    Returns
+    -------
+    str
+        The text with its emoji as shortcodes. Emoji joined in a way Unicode doesn't recommend are written
+        as the shortcodes of their parts.
+    """
+    ...
@@ -0,0 +1,74 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+def best_matches(query: str, candidates: list[str], /, limit: int = 5, threshold: float = 0.0) -> list[tuple[str, float]]:
+    """Find the candidates most like what someone typed, like the command they meant, ignoring case.
+
+    Names are scored by the better of their Jaro-Winkler similarity, which forgives typos and favours names
+    that start the same, and their trigram similarity, which forgives words in another order or left out.
+
+    Parameters
+    ----------
+    query : str
+        What they typed.
+    candidates : list[str]
+        The names to choose from. To match against the same names many times, use a ``Matcher``.
+    limit : int
+        The most matches to give. The default is 5.
+    threshold : float
+        The lowest score a match can have, from 0 to 1. The default is 0, for any name with anything in
+        common with the query.
+
+    Returns
+    -------
+    list[tuple[str, float]]
+        The matches and their scores, from 0 to 1 for the same name, best first, and in the order of the
+        candidates when they're as good.
+
+    Raises
+    ------
+    ValueError
+        If the threshold isn't from 0 to 1.
+    """
+    ...
+
+class Matcher:
+    """Names, like the commands or the tags of a server, to find the closest of to what someone typed,
+    prepared once so each lookup only scores them.
+
+    Parameters
+    ----------
+    candidates : list[str]
+        The names.
+    """
+
+    def __init__(self, candidates: list[str]) -> None: ...
+    @property
+    def candidates(self) -> list[str]:
+        """The names, as they were given."""
+        ...
+    def best_matches(self, query: str, limit: int = 5, threshold: float = 0.0) -> list[tuple[str, float]]:
+        """Find the names most like what someone typed, scored like ``best_matches``.
+
+        Parameters
+        ----------
+        query : str
+            What they typed.
+        limit : int
+            The most matches to give. The default is 5.
+        threshold : float
+            The lowest score a match can have, from 0 to 1. The default is 0.
+
+        Returns
+        -------
+        list[tuple[str, float]]
+            The matches and their scores, best first.
+
+        Raises
+        ------
+        ValueError
+            If the threshold isn't from 0 to 1.
+        """
+        ...
+    def __len__(self) -> int: ...
@@ -0,0 +1,45 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Draw:
+    """The winners of a giveaway.
+
+    Attributes
+    ----------
+    winners : list[int]
+        The users that won, in the order they were drawn.
+    seed : int
+        The seed the winners were drawn with, so the draw can be checked by drawing again with it.
+    """
+
+    winners: list[int]
+    seed: int
+
+def draw(entries: dict[int, int], winners: int, seed: int | None = None, exclude: list[int] | None = None) -> Draw:
+    """Draw the winners of a giveaway, with more entries giving a better chance, but never winning twice.
+
+    Each winner is drawn from the users left, so the same entries and seed always draw the same winners,
+    whatever order the entries are in.
+
+    Parameters
+    ----------
+    entries : dict[int, int]
+        The weight of each user's entry, like how many tickets they have. Users with a weight of 0 can't win.
+    winners : int
+        How many winners to draw. If there are fewer users that can win, they all do.
+    seed : int | None
+        The seed to draw with, so a draw can be checked by drawing again. The default is None, for a random seed.
+    exclude : list[int] | None
+        Users that can't win, like the host or the winners of the last giveaway. The default is None, for nobody.
+
+    Returns
+    -------
+    Draw
+        The winners in the order they were drawn, and the seed they were drawn with.
+
+    Raises
+    ------
+    ValueError
+        If there are no winners to draw, or the weights add up to more than 2^64 - 1.
+    """
@@ -0,0 +1,22 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+import sys as __sys
+
+# noinspection PyProtectedMember
+from charbot_rust._charbot_rust import _giveaway
+
+if hasattr(_giveaway, "__doc__"):
+    __doc__ = _giveaway.__doc__
+
+if hasattr(_giveaway, "__all__"):
+    __all__ = (*_giveaway.__all__,)  # pyright: ignore[reportUnsupportedDunderAll]
+    __name: str
+    for __name in _giveaway.__all__:
+        setattr(__sys.modules[__name__], __name, getattr(_giveaway, __name))
+    try:
+        del __name  # pyright: ignore[reportUnboundVariable]
+    except NameError:
+        pass
+
+del _giveaway
@@ -0,0 +1,124 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from typing import Literal
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+def xxh3(data: str | _BytesLike, /, seed: int = 0) -> int:
+    """Hash data with the 64 bit XXH3, which is fast enough to hash every attachment, but isn't
+    cryptographic, so someone can make different data with the same hash on purpose.
+
+    Parameters
+    ----------
+    data : str | bytes-like
+        The data. Text is hashed as UTF-8.
+    seed : int
+        A seed from 0 to 2**64 - 1, which gives different hashes for the same data. The default is 0.
+
+    Returns
+    -------
+    int
+        The hash, from 0 to 2**64 - 1, the same as other XXH3 implementations give.
+    """
+    ...
+
+def blake3(data: str | _BytesLike, /) -> str:
+    """Hash data with BLAKE3, which is cryptographic, so no one can make different data with the same
+    digest, and still fast.
+
+    Parameters
+    ----------
+    data : str | bytes-like
+        The data. Text is hashed as UTF-8.
+
+    Returns
+    -------
+    str
+        The 32 byte digest, as hex.
+    """
+    ...
+
+def perceptual_hash(image: _BytesLike, /) -> int:
+    """Hash how an image looks rather than its bytes, so an image that's been resized, recompressed or
+    slightly recolored hashes almost the same as it did before.
+
+    Parameters
+    ----------
+    image : bytes-like
+        The image, in any format the image crate can decode, like PNG, JPEG, GIF and WebP.
+
+    Returns
+    -------
+    int
+        The hash, from 0 to 2**64 - 1, which is compared with ``hamming_distance``. Images that look the
+        same are usually at most 10 apart.
+
+    Raises
+    ------
+    ValueError
+        If the image can't be decoded, or is too big.
+    """
+    ...
+
+def hamming_distance(a: int, b: int, /) -> int:
+    """How many bits two hashes differ in, like two perceptual hashes, which is small for images that look
+    alike.
+
+    Parameters
+    ----------
+    a : int
+        A hash, from 0 to 2**64 - 1.
+    b : int
+        The other hash.
+
+    Returns
+    -------
+    int
+        How many bits differ, from 0 to 64.
+    """
+    ...
+
+class Hasher:
+    """Hash data that comes in parts, like an attachment as it's downloaded, the same as hashing it all at
+    once.
+
+    Parameters
+    ----------
+    algorithm : {'xxh3', 'blake3'}
+        The hash.
+    seed : int | None
+        The seed of an XXH3 hash, like ``xxh3`` takes. The default is None, for 0.
+
+    Raises
+    ------
+    ValueError
+        If the algorithm isn't 'xxh3' or 'blake3', or there's a seed for BLAKE3.
+    """
+
+    def __init__(self, algorithm: Literal["xxh3", "blake3"], /, seed: int | None = None) -> None: ...
+    @property
+    def algorithm(self) -> Literal["xxh3", "blake3"]:
+        """The hash."""
+        ...
+    def update(self, data: str | _BytesLike) -> None:
+        """Add the next part of the data. Text is hashed as UTF-8."""
+        ...
+    def digest(self) -> _Buffer:
+        """Get the digest of the data so far, which can still be added to after.
+
+        Returns
+        -------
+        Buffer
+            BLAKE3's 32 bytes, or the 8 bytes of XXH3's hash from the highest, so
+            ``int.from_bytes(hasher.digest())`` is what ``xxh3`` gives.
+        """
+        ...
+    def hexdigest(self) -> str:
+        """Get the digest of the data so far, as hex."""
+        ...
+    def copy(self) -> Hasher:
+        """Get a hasher of the data so far, for data that starts the same but goes on differently."""
+        ...
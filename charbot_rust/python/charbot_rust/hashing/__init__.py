@@ -0,0 +1,22 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+import sys as __sys
+
+# noinspection PyProtectedMember
+from charbot_rust._charbot_rust import _hashing
+
+if hasattr(_hashing, "__doc__"):
+    __doc__ = _hashing.__doc__
+
+if hasattr(_hashing, "__all__"):
+    __all__ = (*_hashing.__all__,)  # pyright: ignore[reportUnsupportedDunderAll]
+    __name: str
+    for __name in _hashing.__all__:
+        setattr(__sys.modules[__name__], __name, getattr(_hashing, __name))
+    try:
+        del __name  # pyright: ignore[reportUnboundVariable]
+    except NameError:
+        pass
+
+del _hashing
@@ -0,0 +1,427 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from asyncio import Future as _Future
+from enum import Enum
+
+from .connect4 import Game as _Connect4Game
+from .minesweeper import Game as _MinesweeperGame
+from .tictactoe import Game as _TicTacToeGame
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Theme(Enum):
+    """The colors to draw a chart in, to match the Discord theme it's shown on."""
+
+    Dark: ...
+    """A dark background with light text."""
+    Light: ...
+    """A light background with dark text."""
+
+class Effect(Enum):
+    """What an effect does to an image."""
+
+    Grayscale: ...
+    """Shades of gray."""
+    Sepia: ...
+    """The browns of an old photo."""
+    Invert: ...
+    """Every color swapped for its opposite."""
+    DeepFry: ...
+    """Oversaturated, overcontrasted, warm, grainy and banded, like it was fried."""
+    Pixelate: ...
+    """Big square pixels."""
+    HueRotate: ...
+    """Every color turned around the color wheel, half way around to the opposite hue at full intensity."""
+
+class Palette(Enum):
+    """What a mosaic is made out of."""
+
+    Squares: ...
+    """The colored square emoji."""
+    Circles: ...
+    """The colored circle emoji."""
+    Ascii: ...
+    """ASCII characters by brightness, in a code block."""
+
+def rank_card(
+    username: str,
+    level: int,
+    rank: int,
+    xp: int,
+    needed: int,
+    avatar: _BytesLike | None = None,
+    background: _BytesLike | None = None,
+    locale: str = "en-US",
+    accent: int = 0xFCB33F,
+) -> _Buffer:
+    """Draw a rank card for a member, with their avatar, name, level, rank and progress to the next level.
+
+    The text is translated with the bot's translations, and names too long for the card are cut short.
+    Drawing happens without holding the GIL, so other threads can run meanwhile.
+
+    Parameters
+    ----------
+    username : str
+        The member's name.
+    level : int
+        Their level.
+    rank : int
+        Their place on the leaderboard, starting at 1.
+    xp : int
+        The XP they have towards the next level.
+    needed : int
+        The XP the next level needs, more than 0. XP past it fills the bar.
+    avatar : bytes-like | None
+        Their avatar, as an image file in any common format. The default is None, which draws the first
+        letter of their name instead.
+    background : bytes-like | None
+        An image to draw behind the card, cropped to fit and darkened. The default is None, a dark gradient.
+    locale : str
+        The locale to translate the text to. The default is 'en-US'.
+    accent : int
+        The color of the ring, the rank and the bar, as a 0xRRGGBB integer. The default is 0xfcb33f.
+
+    Returns
+    -------
+    Buffer
+        The card, as a 900 by 238 PNG.
+
+    Raises
+    ------
+    ValueError
+        If the XP needed or the rank is 0, the accent isn't a color, an image is too big or can't be read,
+        or there are no number formats for the locale.
+    """
+
+def render_many(cards: list[dict], /) -> list[_Buffer]:
+    """Draw many rank cards at once, like nightly leaderboard posts, split across threads without
+    holding the GIL.
+
+    Parameters
+    ----------
+    cards : list[dict]
+        The cards, each a dict of what rank_card takes by name, like
+        {'username': 'Charlie', 'level': 12, 'rank': 3, 'xp': 450, 'needed': 1200}. Leaving out the
+        optional ones gives the same defaults.
+
+    Returns
+    -------
+    list[Buffer]
+        The cards as 900 by 238 PNGs, in the order they were given.
+
+    Raises
+    ------
+    ValueError
+        If a card is missing a name rank_card needs, or rank_card would raise for it.
+    """
+
+def rank_card_async(
+    username: str,
+    level: int,
+    rank: int,
+    xp: int,
+    needed: int,
+    avatar: _BytesLike | None = None,
+    background: _BytesLike | None = None,
+    locale: str = "en-US",
+    accent: int = 0xFCB33F,
+) -> _Future[_Buffer]:
+    """Draw a rank card on a thread pool, so the event loop keeps running meanwhile. It takes and gives
+    the same as rank_card, once awaited.
+
+    Returns
+    -------
+    asyncio.Future[Buffer]
+        The card, as a 900 by 238 PNG.
+
+    Raises
+    ------
+    RuntimeError
+        If there isn't an event loop running.
+    """
+
+def captcha(length: int = 6, difficulty: int = 2, seed: int | None = None) -> tuple[str, _Buffer]:
+    """Make a captcha, an image of letters and digits for someone to type in to show they're not a bot.
+
+    The letters are uppercase, without ones that are easily mixed up like O and 0, so answers can be
+    compared ignoring case. Drawing happens without holding the GIL, so other threads can run meanwhile.
+
+    Parameters
+    ----------
+    length : int
+        How many letters to draw, from 4 to 10. The default is 6.
+    difficulty : int
+        How distorted the letters are and how much noise is drawn over them, 1 for easy, 2 for medium
+        and 3 for hard. The default is 2.
+    seed : int | None
+        A seed to make the same captcha again, such as for tests. The default is None, a random one.
+
+    Returns
+    -------
+    tuple[str, Buffer]
+        The answer, and the captcha as a PNG.
+
+    Raises
+    ------
+    ValueError
+        If the length or the difficulty is out of range.
+    """
+
+def levelup(username: str, old_level: int, new_level: int, locale: str = "en-US", accent: int = 0xFCB33F) -> _Buffer:
+    """Draw a banner congratulating a member on reaching a new level, to send with the level up message.
+
+    The text is translated with the bot's translations, and names too long for the banner are cut short.
+    Drawing happens without holding the GIL, so other threads can run meanwhile.
+
+    Parameters
+    ----------
+    username : str
+        The member's name.
+    old_level : int
+        The level they were.
+    new_level : int
+        The level they are now, more than the old level. Levels can be skipped.
+    locale : str
+        The locale to translate the text to. The default is 'en-US'.
+    accent : int
+        The color of the title and the badge with the new level on it, as a 0xRRGGBB integer. The default
+        is 0xfcb33f.
+
+    Returns
+    -------
+    Buffer
+        The banner, as a 1000 by 200 PNG.
+
+    Raises
+    ------
+    ValueError
+        If the new level isn't more than the old level, or the accent isn't a color.
+    """
+
+def bar_chart(
+    data: list[tuple[str, float]],
+    title: str | None = None,
+    theme: Theme = Theme.Dark,
+    accent: int = 0xFCB33F,
+    locale: str = "en-US",
+    width: int = 800,
+    height: int = 400,
+) -> _Buffer:
+    """Draw a bar chart of labelled values, like messages sent each day of the week.
+
+    Each bar has its value above it, shortened like 1.2K, and its label below it, cut short if it's too
+    long. Negative values go below the line at 0. Drawing happens without holding the GIL.
+
+    Parameters
+    ----------
+    data : list[tuple[str, float]]
+        The labels and values, in the order to draw them, from 1 to 200 of them.
+    title : str | None
+        A title to draw above the chart. The default is None, for no title.
+    theme : Theme
+        The colors to draw in, to match the Discord theme it's shown on. The default is Theme.Dark.
+    accent : int
+        The color of the bars, as a 0xRRGGBB integer. The default is 0xfcb33f.
+    locale : str
+        The locale to format the values for. The default is 'en-US'.
+    width : int
+        How wide the chart is, from 100 to 2000 pixels. The default is 800.
+    height : int
+        How tall the chart is, from 40 to 1000 pixels. The default is 400.
+
+    Returns
+    -------
+    Buffer
+        The chart, as a PNG.
+
+    Raises
+    ------
+    ValueError
+        If there are no values or too many, a value isn't a finite number, the accent isn't a color, or
+        the size is out of range, or there are no number formats for the locale.
+    """
+
+def sparkline(
+    data: list[tuple[str, float]],
+    theme: Theme = Theme.Dark,
+    accent: int = 0xFCB33F,
+    locale: str = "en-US",
+    width: int = 400,
+    height: int = 80,
+) -> _Buffer:
+    """Draw a small line chart of labelled values, like someone's points over the last month.
+
+    The area under the line is shaded and the last value is written at its end. Charts at least 80
+    pixels tall have the first and last labels below the line. Drawing happens without holding the GIL.
+
+    Parameters
+    ----------
+    data : list[tuple[str, float]]
+        The labels and values, oldest first, from 1 to 200 of them.
+    theme : Theme
+        The colors to draw in, to match the Discord theme it's shown on. The default is Theme.Dark.
+    accent : int
+        The color of the line, as a 0xRRGGBB integer. The default is 0xfcb33f.
+    locale : str
+        The locale to format the last value for. The default is 'en-US'.
+    width : int
+        How wide the chart is, from 100 to 2000 pixels. The default is 400.
+    height : int
+        How tall the chart is, from 40 to 1000 pixels. The default is 80.
+
+    Returns
+    -------
+    Buffer
+        The chart, as a PNG.
+
+    Raises
+    ------
+    ValueError
+        If there are no values or too many, a value isn't a finite number, the accent isn't a color, or
+        the size is out of range, or there are no number formats for the locale.
+    """
+
+def apply_effect(image_bytes: _BytesLike, effect: Effect, intensity: float = 1.0) -> _Buffer:
+    """Apply an effect to an image, like deep frying someone's avatar for a fun command.
+
+    Animated images only have their first frame used. Processing happens without holding the GIL, so
+    other threads can run meanwhile.
+
+    Parameters
+    ----------
+    image_bytes : bytes-like
+        The image, as a file in any common format.
+    effect : Effect
+        The effect to apply.
+    intensity : float
+        How strong the effect is, from 0 for none of it to 1 for all of it. The default is 1.
+
+    Returns
+    -------
+    Buffer
+        The image with the effect, as a PNG, the same size as it was.
+
+    Raises
+    ------
+    ValueError
+        If the intensity isn't from 0 to 1, or the image is too big or can't be read.
+    """
+
+def tictactoe_board(game: _TicTacToeGame, last_move: int | None = None) -> _Buffer:
+    """Draw a tic-tac-toe game's board, with a line through the winning pieces if someone has won.
+
+    Parameters
+    ----------
+    game : tictactoe.Game
+        The game to draw.
+    last_move : int | None
+        The index of the cell last played in, from 0 to 8 across then down, to highlight it.
+
+    Returns
+    -------
+    Buffer
+        The board, as a 400 by 400 PNG.
+
+    Raises
+    ------
+    ValueError
+        If the last move isn't a cell.
+    """
+
+def connect4_board(game: _Connect4Game, last_move: int | None = None) -> _Buffer:
+    """Draw a connect four game's board, with the column numbers under it.
+
+    Parameters
+    ----------
+    game : connect4.Game
+        The game to draw.
+    last_move : int | None
+        The column the last disc was dropped in, from 0 to 6, to mark that disc.
+
+    Returns
+    -------
+    Buffer
+        The board, as a 580 by 530 PNG.
+
+    Raises
+    ------
+    ValueError
+        If the last move isn't a column.
+    """
+
+def minesweeper_board(game: _MinesweeperGame) -> _Buffer:
+    """Draw a minesweeper game's field, with the selected cell's row and column outlined.
+
+    This is the image from ``Game.draw``, as a PNG instead of raw pixels.
+
+    Parameters
+    ----------
+    game : minesweeper.Game
+        The game to draw.
+
+    Returns
+    -------
+    Buffer
+        The field, as a PNG.
+    """
+
+def chess_board(fen: str, last_move: str | None = None, flipped: bool = False) -> _Buffer:
+    """Draw a chess position, with the rank and file names around the board.
+
+    The king of the side to move is marked if it's in check.
+
+    Parameters
+    ----------
+    fen : str
+        The position, in FEN.
+    last_move : str | None
+        The move that led to the position in UCI, like 'e2e4', to highlight the squares it went from and
+        to.
+    flipped : bool
+        Whether to draw the board from black's side, with the eighth rank at the bottom.
+
+    Returns
+    -------
+    Buffer
+        The board, as a 536 by 536 PNG.
+
+    Raises
+    ------
+    ValueError
+        If the FEN isn't a valid position, or the last move isn't in UCI.
+    """
+
+def to_emoji(image_bytes: _BytesLike, width: int = 20, palette: Palette = Palette.Squares, limit: int = 2000) -> list[str]:
+    """Redraw an image out of colored emoji or ASCII characters, for a fun command.
+
+    Each emoji is the closest color to the part of the image it covers, and each ASCII character is
+    brighter the brighter that part is, for the dark theme. See-through parts are drawn over the dark
+    theme's background. The text is split into messages at the ends of lines, and ASCII art is put in a
+    code block so it lines up. Processing happens without holding the GIL, so other threads can run
+    meanwhile.
+
+    Parameters
+    ----------
+    image_bytes : bytes-like
+        The image, as a file in any common format.
+    width : int
+        How many emoji or characters across the mosaic is, from 1 to 80. Images narrower than that are
+        drawn a character per pixel. The default is 20.
+    palette : Palette
+        What the mosaic is made out of. The default is the colored squares.
+    limit : int
+        The most characters a message can have. The default is 2000, Discord's limit.
+
+    Returns
+    -------
+    list[str]
+        The messages to send, in order.
+
+    Raises
+    ------
+    ValueError
+        If the width or limit is out of range, or the image is too big or can't be read.
+    """
@@ -0,0 +1,74 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from typing import Any
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Schema:
+    """A schema, checked and turned into rust once, to check any number of payloads against.
+
+    It's the part of JSON schema that says what shape a payload is: ``type``, as a name or a list of
+    them, ``properties``, ``required`` and ``items``. Anything else is an error rather than ignored, so
+    nothing in a schema looks checked that isn't. Objects can have properties the schema doesn't list.
+
+    Parameters
+    ----------
+    schema : dict[str, Any]
+        The schema, like ``{'type': 'object', 'required': ['op', 'd']}``.
+
+    Raises
+    ------
+    ValueError
+        If the schema has a keyword that isn't one of those, or a type that isn't a JSON schema type.
+    """
+
+    def __new__(cls, schema: dict[str, Any], /) -> Schema: ...
+    def __repr__(self) -> str: ...
+
+def validate(data: str | _BytesLike, schema: Schema, /) -> list[str]:
+    """Check a JSON document, like a gateway payload, against a schema, without making python objects for
+    any of it.
+
+    Parameters
+    ----------
+    data : str | bytes-like
+        The document, as UTF-8.
+    schema : Schema
+        The schema to check it against.
+
+    Returns
+    -------
+    list[str]
+        What's wrong with the document, each starting with where it is, like
+        ``$.d.author.id: expected string, got integer``, or why it isn't JSON. It's empty if the document
+        is valid.
+    """
+    ...
+
+def extract(data: str | _BytesLike, paths: list[str], /) -> list[Any]:
+    """Pull values out of a JSON document, like the author, content and guild of a message event, making
+    python objects for only those.
+
+    Parameters
+    ----------
+    data : str | bytes-like
+        The document, as UTF-8.
+    paths : list[str]
+        Where the values are, as keys separated by dots, with numbers indexing arrays, like
+        ``d.author.id`` or ``d.mentions.0.id``. An empty path is the whole document.
+
+    Returns
+    -------
+    list[Any]
+        The value at each path, like ``json.loads`` would make it, or None where there isn't one.
+
+    Raises
+    ------
+    ValueError
+        If the document isn't JSON, or a value at one of the paths has arrays and objects more than
+        128 deep.
+    """
+    ...
@@ -0,0 +1,118 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+class Ranking(Enum):
+    """How tied users are ranked."""
+
+    Competition: ...
+    """Tied users share a rank and the ranks after them are skipped, like 1, 2, 2, 4."""
+    Dense: ...
+    """Tied users share a rank and the next rank follows on, like 1, 2, 2, 3."""
+
+class Standing:
+    """Where a user is on a leaderboard.
+
+    Attributes
+    ----------
+    rank : int
+        The rank, from 1 for the highest score.
+    position : int
+        Where the user is in the leaderboard, from 0, which is different for every user even when tied.
+    user : int
+        The user.
+    score : int
+        Their score.
+    """
+
+    rank: int
+    position: int
+    user: int
+    score: int
+
+class Leaderboard:
+    """Users ranked by score, kept in order as scores change so nothing has to be sorted again.
+
+    Users with the same score are listed by their id, so the order is always the same. ``len(leaderboard)``
+    is the number of users on it.
+
+    Parameters
+    ----------
+    entries : list[tuple[int, int]]
+        The user and score of everyone on the leaderboard.
+    ranking : Ranking
+        How tied users are ranked. The default is Ranking.Competition.
+
+    Raises
+    ------
+    ValueError
+        If a user is in the entries more than once.
+    """
+
+    def __new__(cls, entries: list[tuple[int, int]], ranking: Ranking = Ranking.Competition) -> "Leaderboard": ...
+    @property
+    def ranking(self) -> Ranking: ...
+    def __len__(self) -> int: ...
+    def set(self, user: int, score: int) -> None:
+        """Set the score of a user, adding them if they aren't on the leaderboard."""
+        ...
+    def remove(self, user: int) -> int | None:
+        """Take a user off the leaderboard, returning their score, or None if they weren't on it."""
+        ...
+    def score(self, user: int) -> int | None:
+        """Get the score of a user, None if they aren't on the leaderboard."""
+        ...
+    def rank(self, user: int) -> Standing | None:
+        """Get where a user is, None if they aren't on the leaderboard."""
+        ...
+    def top(self, count: int = 10) -> list[Standing]:
+        """Get the users with the highest scores, 10 by default."""
+        ...
+    def around(self, user: int, neighbours: int = 2) -> list[Standing] | None:
+        """Get a user and the users either side of them, for a user's own rank.
+
+        Parameters
+        ----------
+        user : int
+            The user.
+        neighbours : int
+            How many users to get on each side, fewer at the top and bottom. The default is 2.
+
+        Returns
+        -------
+        list[Standing] | None
+            The users, the highest score first, or None if the user isn't on the leaderboard.
+        """
+        ...
+    def page(self, page: int, per_page: int = 10) -> list[Standing]:
+        """Get a page of the leaderboard.
+
+        Parameters
+        ----------
+        page : int
+            The page, from 0 for the top.
+        per_page : int
+            The users on each page. The default is 10.
+
+        Returns
+        -------
+        list[Standing]
+            The users on the page, empty past the last page.
+
+        Raises
+        ------
+        ValueError
+            If there are no users on each page.
+        """
+        ...
+    def pages(self, per_page: int = 10) -> int:
+        """Get how many pages the leaderboard takes, with 10 users on each by default.
+
+        Raises
+        ------
+        ValueError
+            If there are no users on each page.
+        """
+        ...
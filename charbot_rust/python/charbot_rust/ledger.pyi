@@ -0,0 +1,217 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Transaction:
+    """A change to a user's balance.
+
+    Attributes
+    ----------
+    id : int
+        Counts up from 0 across every user, so transactions can be put in order.
+    user : int
+        The user whose balance changed.
+    amount : int
+        Positive for a credit, negative for a debit.
+    balance : int
+        The balance after the transaction.
+    reason : str
+        What the transaction was for.
+    timestamp : int
+        When the transaction was made, in seconds since the unix epoch.
+    """
+
+    id: int
+    user: int
+    amount: int
+    balance: int
+    reason: str
+    timestamp: int
+
+class Ledger:
+    """Everyone's points, changed a whole transaction at a time.
+
+    Each change is checked in full before any of it is applied, so a change that would overdraw an account
+    changes nothing, and since each call runs to the end before the next can start, no two commands can
+    interleave and lose an update. Users without an account have a balance of 0.
+
+    Parameters
+    ----------
+    log_size : int
+        How many of the latest transactions are kept for each user, from 1 to 1000. The default is 50.
+
+    Raises
+    ------
+    ValueError
+        If the log size isn't from 1 to 1000.
+    """
+
+    def __new__(cls, log_size: int = 50) -> "Ledger": ...
+    @property
+    def log_size(self) -> int: ...
+    def balance(self, user: int) -> int:
+        """Get the balance of a user, 0 if they don't have an account."""
+        ...
+    def balances(self) -> dict[int, int]:
+        """Get every user with an account and their balance."""
+        ...
+    def history(self, user: int) -> list[Transaction]:
+        """Get the latest transactions of a user, oldest first."""
+        ...
+    def credit(self, user: int, amount: int, reason: str = "") -> Transaction:
+        """Add points to a user's balance.
+
+        Parameters
+        ----------
+        user : int
+            The user.
+        amount : int
+            The points to add, more than 0.
+        reason : str
+            What the points are for, for the log. The default is an empty string.
+
+        Returns
+        -------
+        Transaction
+            The transaction, with the new balance.
+
+        Raises
+        ------
+        ValueError
+            If the amount isn't more than 0, or the balance would overflow.
+        """
+        ...
+    def debit(self, user: int, amount: int, reason: str = "") -> Transaction:
+        """Take points from a user's balance.
+
+        Parameters
+        ----------
+        user : int
+            The user.
+        amount : int
+            The points to take, more than 0.
+        reason : str
+            What the points are for, for the log. The default is an empty string.
+
+        Returns
+        -------
+        Transaction
+            The transaction, with the new balance.
+
+        Raises
+        ------
+        ValueError
+            If the amount isn't more than 0, or the user can't afford it.
+        """
+        ...
+    def transfer(self, sender: int, recipient: int, amount: int, reason: str = "") -> tuple[Transaction, Transaction]:
+        """Move points from one user to another.
+
+        Parameters
+        ----------
+        sender : int
+            The user the points are taken from.
+        recipient : int
+            The user the points are given to.
+        amount : int
+            The points to move, more than 0.
+        reason : str
+            What the points are for, for the log. The default is an empty string.
+
+        Returns
+        -------
+        tuple[Transaction, Transaction]
+            The debit from the sender and the credit to the recipient.
+
+        Raises
+        ------
+        ValueError
+            If the amount isn't more than 0, the sender and recipient are the same, the sender can't afford
+            it, or the recipient's balance would overflow.
+        """
+        ...
+    def apply(self, changes: list[tuple[int, int]], reason: str = "") -> list[Transaction]:
+        """Change many balances at once, for events, either all of them or none.
+
+        A user can be changed more than once, and only their balance after all of their changes has to be
+        covered, so a user can be credited and debited together.
+
+        Parameters
+        ----------
+        changes : list[tuple[int, int]]
+            The user and the amount for each change, positive for a credit and negative for a debit.
+        reason : str
+            What the points are for, for the log. The default is an empty string.
+
+        Returns
+        -------
+        list[Transaction]
+            A transaction for each change, in order.
+
+        Raises
+        ------
+        ValueError
+            If a user can't afford their changes, or their balance would overflow.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the ledger as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved ledger, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Ledger":
+        """Load a ledger saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved ledger.
+
+        Returns
+        -------
+        Ledger
+            The ledger, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved ledger, is from another version, or doesn't add up.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the ledger as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved ledger, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Ledger":
+        """Load a ledger saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved ledger.
+
+        Returns
+        -------
+        Ledger
+            The ledger, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved ledger, is from another version, or doesn't add up.
+        """
+        ...
@@ -0,0 +1,132 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Curve:
+    """How much XP each level takes, from level 0 with no XP up to level 1000.
+
+    Every level has to take more XP than the one before, and level 1000 can't take more than 2^53 XP.
+    """
+
+    @staticmethod
+    def linear(xp_per_level: int) -> "Curve":
+        """A curve where every level takes the same XP.
+
+        Raises
+        ------
+        ValueError
+            If the XP is 0, or level 1000 would take too much.
+        """
+        ...
+    @staticmethod
+    def polynomial(coefficient: float, exponent: float) -> "Curve":
+        """A curve where reaching a level takes ``coefficient * level ** exponent`` XP in total, rounded.
+
+        Raises
+        ------
+        ValueError
+            If the coefficient or exponent aren't positive, a level takes no more XP than the one before
+            it once rounded, or level 1000 would take too much.
+        """
+        ...
+    @staticmethod
+    def mee6() -> "Curve":
+        """The curve of the Mee6 bot, where going from level ``n`` to the next takes ``5n² + 50n + 100`` XP."""
+        ...
+    def xp_for_level(self, level: int) -> int:
+        """Get the XP in total it takes to reach a level.
+
+        Raises
+        ------
+        ValueError
+            If the level is over 1000.
+        """
+        ...
+    def level_for_xp(self, xp: int) -> int:
+        """Get the highest level some XP is enough for."""
+        ...
+    def progress(self, xp: int) -> "Progress":
+        """Get the level for some XP and how far to the next."""
+        ...
+
+class Progress:
+    """How far through their level someone is.
+
+    Attributes
+    ----------
+    level : int
+        The level.
+    current : int
+        The XP since reaching the level.
+    needed : int
+        The XP from the level to the next, 0 at the highest level.
+    fraction : float
+        How far to the next level, from 0 to 1, for a progress bar.
+    """
+
+    level: int
+    current: int
+    needed: int
+    fraction: float
+
+def set_curve(curve: Curve) -> None:
+    """Set the curve the functions of this module use, once when the bot starts.
+
+    The curve is shared by everything using this module, so the rank cards and the message handler
+    always agree.
+
+    Parameters
+    ----------
+    curve : Curve
+        The curve, the default is ``Curve.mee6()``.
+    """
+
+def get_curve() -> Curve:
+    """Get the curve the functions of this module use."""
+
+def xp_for_level(level: int) -> int:
+    """Get the XP in total it takes to reach a level, with the curve set for the module.
+
+    Parameters
+    ----------
+    level : int
+        The level, up to 1000.
+
+    Returns
+    -------
+    int
+        The XP, 0 for level 0.
+
+    Raises
+    ------
+    ValueError
+        If the level is over 1000.
+    """
+
+def level_for_xp(xp: int) -> int:
+    """Get the highest level some XP is enough for, with the curve set for the module.
+
+    Parameters
+    ----------
+    xp : int
+        The XP.
+
+    Returns
+    -------
+    int
+        The level, up to 1000.
+    """
+
+def progress(xp: int) -> Progress:
+    """Get the level for some XP and how far to the next, with the curve set for the module, for a rank card.
+
+    Parameters
+    ----------
+    xp : int
+        The XP.
+
+    Returns
+    -------
+    Progress
+        The level, the XP since reaching it and the XP it takes to reach the next.
+    """
@@ -0,0 +1,119 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+class Feature(Enum):
+    """What Discord's markdown can format."""
+
+    Bold: ...
+    """``**bold**``"""
+    Italic: ...
+    """``*italic*`` or ``_italic_``"""
+    Underline: ...
+    """``__underline__``"""
+    Strikethrough: ...
+    """``~~strikethrough~~``"""
+    Spoiler: ...
+    """``||spoiler||``"""
+    Code: ...
+    """```inline code```"""
+    CodeBlock: ...
+    """````` ```code blocks``` `````, with or without a language."""
+    Quote: ...
+    """``> `` and ``>>> `` at the start of a line."""
+    Header: ...
+    """``# ``, ``## `` and ``### `` at the start of a line."""
+    Subtext: ...
+    """``-# `` at the start of a line."""
+    List: ...
+    """``- ``, ``* `` and ``1. `` at the start of a line."""
+    Link: ...
+    """``[masked](https://links)``"""
+
+def escape(text: str) -> str:
+    """Escape text so Discord shows it as it was typed, without formatting it.
+
+    Everything is escaped, backticks in code blocks too, so text with code in it can't break out of
+    it, other than links, which escaping breaks.
+
+    Parameters
+    ----------
+    text : str
+        The text.
+
+    Returns
+    -------
+    str
+        The escaped text.
+    """
+    ...
+
+def strip(text: str) -> str:
+    """Remove the formatting from a message, leaving the text as it would be read.
+
+    Code keeps what's in it, and masked links have where they go written after them, like 'text (https://example.com)'.
+
+    Parameters
+    ----------
+    text : str
+        The message.
+
+    Returns
+    -------
+    str
+        The text of the message, without markdown.
+    """
+    ...
+
+def sanitize(text: str, allowed_features: list[Feature]) -> str:
+    """Remove the formatting from a message that isn't allowed, and escape the rest of the text.
+
+    Formatting that isn't allowed keeps its text, like 'spoiler' for '||spoiler||' if spoilers aren't
+    allowed, and masked links that aren't have where they go written after them. Nested formatting,
+    code and escapes are handled like Discord does, so backticks in code blocks don't break them.
+
+    Parameters
+    ----------
+    text : str
+        The message.
+    allowed_features : list[Feature]
+        The formatting that's allowed.
+
+    Returns
+    -------
+    str
+        The message with only the allowed formatting.
+    """
+    ...
+
+def chunk(text: str, limit: int = 2000, code_block_lang: str | None = None) -> list[str]:
+    """Split text too long for one message into messages, without breaking up its markdown.
+
+    Text is split at the end of a paragraph if it can be, then a line, a sentence, a word or anywhere.
+    Formatting like bold and masked links is kept in one message unless it's too long to fit, and code
+    blocks that have to be split are closed at the end of the message and reopened at the start of the next.
+
+    Parameters
+    ----------
+    text : str
+        The text.
+    limit : int
+        The most characters a message can have, at least 20. The default is 2000, Discord's limit.
+    code_block_lang : str | None
+        The language to put the text in a code block with, like for log dumps, with any backtick fences in
+        it broken up so they can't close it. The default is None, for text that isn't in a code block.
+
+    Returns
+    -------
+    list[str]
+        The messages, none of them empty.
+
+    Raises
+    ------
+    ValueError
+        If the limit is less than 20 or too short for the formatting nested in the text, or the language
+        isn't a valid code block language.
+    """
+    ...
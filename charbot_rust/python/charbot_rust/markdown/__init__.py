@@ -0,0 +1,22 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+import sys as __sys
+
+# noinspection PyProtectedMember
+from charbot_rust._charbot_rust import _markdown
+
+if hasattr(_markdown, "__doc__"):
+    __doc__ = _markdown.__doc__
+
+if hasattr(_markdown, "__all__"):
+    __all__ = (*_markdown.__all__,)  # pyright: ignore[reportUnsupportedDunderAll]
+    __name: str
+    for __name in _markdown.__all__:
+        setattr(__sys.modules[__name__], __name, getattr(_markdown, __name))
+    try:
+        del __name  # pyright: ignore[reportUnboundVariable]
+    except NameError:
+        pass
+
+del _markdown
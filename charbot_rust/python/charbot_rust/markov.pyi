@@ -0,0 +1,95 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Chain:
+    """A markov chain of the words of messages, to make up messages that sound like a channel.
+
+    Each word is kept once and the chain refers to it by number, so a chain of a busy channel takes a
+    fraction of the memory of a markovify model of it.
+
+    Parameters
+    ----------
+    order : int
+        How many words back the next word depends on, 2 or 3. A higher order sticks closer to the messages
+        it learnt from. The default is 2.
+
+    Raises
+    ------
+    ValueError
+        If the order isn't 2 or 3.
+    """
+
+    def __init__(self, order: int = 2) -> None: ...
+    @property
+    def order(self) -> int:
+        """How many words back the next word depends on."""
+        ...
+    def train(self, text: str) -> None:
+        """Learn from messages, adding to what the chain already learnt.
+
+        Parameters
+        ----------
+        text : str
+            The messages, one a line, of words between whitespace, with their case and punctuation kept.
+        """
+        ...
+    def generate(self, max_length: int = 2000, seed: int | None = None) -> str | None:
+        """Make up a message.
+
+        Parameters
+        ----------
+        max_length : int
+            The most characters the message can have, ending early after the last word that fits if it
+            would be longer. The default is 2000, as long as a discord message can be.
+        seed : int | None
+            What the words are picked with, so the same seed makes the same message from the same training.
+            The default is None, for a random message.
+
+        Returns
+        -------
+        str | None
+            The message, or None if there's nothing the chain could say, since it hasn't learnt anything yet
+            or the first word is already too long.
+
+        Raises
+        ------
+        ValueError
+            If the max length isn't positive.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the chain as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved chain, along with what it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Chain":
+        """Load a chain saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved chain.
+
+        Returns
+        -------
+        Chain
+            The chain, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved chain, is from another version, or couldn't have come from a chain.
+        """
+        ...
+    def __len__(self) -> int:
+        """How many different words the chain knows."""
+        ...
@@ -0,0 +1,111 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+class Algorithm(Enum):
+    """How a maze is carved out."""
+
+    Backtracker: ...
+    """A random walk that backs up when it gets stuck, for long winding passages."""
+    Prim: ...
+    """Grows out from a random cell, for lots of short dead ends."""
+    Kruskal: ...
+    """Joins random cells until they're all connected, for a maze with no overall direction."""
+
+class Maze:
+    """A maze with exactly one way between any two cells, entered at the top left and left at the bottom right.
+
+    Cells are (row, column), with row 0 at the top and column 0 on the left. ``str(maze)`` is the maze
+    drawn with box drawing characters.
+    """
+
+    @property
+    def width(self) -> int: ...
+    @property
+    def height(self) -> int: ...
+    def passages(self, cell: tuple[int, int]) -> tuple[bool, bool, bool, bool]:
+        """Get the ways out of a cell.
+
+        Parameters
+        ----------
+        cell : tuple[int, int]
+            The cell.
+
+        Returns
+        -------
+        tuple[bool, bool, bool, bool]
+            Whether there's a way north, east, south and west.
+
+        Raises
+        ------
+        ValueError
+            If the cell isn't in the maze.
+        """
+        ...
+    def solve(self) -> list[tuple[int, int]]:
+        """Find the way through the maze, for a give up button.
+
+        Returns
+        -------
+        list[tuple[int, int]]
+            The cells from the top left to the bottom right, both included.
+        """
+        ...
+    def to_unicode(self, show_path: bool = False) -> str:
+        """Draw the maze with box drawing characters, to send in a code block so it lines up.
+
+        Parameters
+        ----------
+        show_path : bool
+            Whether to draw the way through the maze, as dots. The default is False.
+
+        Returns
+        -------
+        str
+            The maze, which fits in a discord message with the code block around it.
+        """
+        ...
+    def to_emoji(self, show_path: bool = False) -> str:
+        """Draw the maze with emoji, black walls and white passages, with the start green and the end red.
+
+        Parameters
+        ----------
+        show_path : bool
+            Whether to draw the way through the maze, in yellow. The default is False.
+
+        Returns
+        -------
+        str
+            The maze, which fits in a discord message.
+        """
+        ...
+
+def generate(width: int, height: int, algorithm: Algorithm = Algorithm.Backtracker, seed: int | None = None) -> Maze:
+    """Generate a maze, with exactly one way between any two cells.
+
+    A maze is drawn with a character or emoji for each cell, each wall between cells and each corner, so
+    it is twice as wide and as high as it is in cells, plus one. Up to 21 by 21 fits in a discord message.
+
+    Parameters
+    ----------
+    width : int
+        The number of cells across.
+    height : int
+        The number of cells down.
+    algorithm : Algorithm
+        How the maze is carved out, each gives it a different feel. The default is Algorithm.Backtracker.
+    seed : int | None
+        The seed for the maze, so a maze can be made again. The default is None, for a random maze.
+
+    Returns
+    -------
+    Maze
+        The maze.
+
+    Raises
+    ------
+    ValueError
+        If the maze is empty, or too big to draw in a discord message.
+    """
@@ -0,0 +1,18 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+def export_prometheus() -> str:
+    """Export what has been counted since the bot started, in the prometheus text format, to add to the
+    bot's own ``/metrics``.
+
+    There are counters of the calls to each function and of the ones that raised, and a histogram of how
+    long they took, each labelled with the function, like ``fluent.translate`` or ``connect4.play``.
+    Functions that haven't been called yet are left out.
+
+    Returns
+    -------
+    str
+        The metrics, ending in a newline.
+    """
+    ...
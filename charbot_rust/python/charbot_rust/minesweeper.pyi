@@ -4,6 +4,10 @@
 import typing as __typing
 import enum as __enum
 
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
 @__typing.type_check_only
 class Selected:
     """Represents an internally selected cell.
@@ -52,6 +56,32 @@ class ChordResult(__enum.Enum):
     Success: int = ...  # pyright: ignore[reportGeneralTypeIssues]
     Death: int = ...  # pyright: ignore[reportGeneralTypeIssues]
 
+class CellState:
+    """A cell as the player sees it, for drawing the board as buttons.
+
+    What is under a cell is only given once it is revealed, hidden cells are never a mine and have
+    no adjacent mines.
+
+    It has 5 attributes::
+
+        revealed: bool
+            Whether the cell is revealed or not.
+        marked: bool
+            Whether the cell is flagged or not.
+        mine: bool
+            Whether the cell is a revealed mine.
+        exploded: bool
+            Whether the cell is the mine that ended the game.
+        adjacent: int
+            The number of mines next to a revealed cell, 0 for empty cells and mines.
+    """
+
+    revealed: bool
+    marked: bool
+    mine: bool
+    exploded: bool
+    adjacent: int
+
 class Game:
     """A class that represents a game of minesweeper.
 
@@ -63,6 +93,12 @@ class Game:
         The height of the game board.
     mines: int
         The number of mines in the game.
+
+    Raises
+    ------
+    ValueError
+        If the width or height isn't from 1 to 25, or there are too many mines to keep the 3x3 around
+        the first cell revealed free of them.
     """
 
     def __init__(self, width: int, height: int, mines: int) -> None: ...
@@ -163,18 +199,18 @@ class Game:
             The y coordinate of the selected cell.
         """
         ...
-    def draw(self) -> tuple[list[int], tuple[int, int]]:
+    def draw(self) -> tuple[_Buffer, tuple[int, int]]:
         """Draws the board and returns it alongside its size.
 
         The return is in the form of a tuple of the form::
 
-            (board: list[int], (width: int, height: int))
+            (board: Buffer, (width: int, height: int))
 
-        The `list[int]` is effectively an array of bytes, it just needs a cast to `bytes` or `bytearray`.
+        The `Buffer` is the raw RGB pixels, which `Image.frombytes` reads as is, or `bytes` copies.
 
         Returns
         -------
-        tuple[list[int], tuple[int, int]]
+        tuple[Buffer, tuple[int, int]]
             The board and its size.
         """
         ...
@@ -242,6 +278,77 @@ class Game:
             True if the game is won, False otherwise.
         """
         ...
+    def is_lost(self) -> bool:
+        """Check if the game is lost, by revealing a mine or quitting.
+
+        Returns
+        -------
+        lost: bool
+            True if the game is lost, False otherwise.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if the game is won or lost, a game where nothing was revealed yet isn't over.
+
+        Returns
+        -------
+        over: bool
+            True if no more moves can be made, False otherwise.
+        """
+        ...
+    def cells(self) -> list[list[CellState]]:
+        """Every cell on the board, ready to be drawn as a grid of buttons.
+
+        Returns
+        -------
+        cells: list[list[CellState]]
+            The cells as rows from the top, each from the left.
+        """
+        ...
+    def reveal_at(self, row: int, col: int) -> RevealResult:
+        """Move the internal cursor to a cell and reveal it.
+
+        The first cell revealed in a game, and the cells around it, are never mines.
+
+        Parameters
+        ----------
+        row: int
+            The row of the cell.
+        col: int
+            The column of the cell.
+
+        Returns
+        -------
+        result: RevealResult
+            The result of the reveal operation.
+
+        Raises
+        ------
+        ValueError
+            If the row or column is out of range.
+        """
+        ...
+    def toggle_flag_at(self, row: int, col: int) -> bool:
+        """Move the internal cursor to a cell and toggle its flag.
+
+        Parameters
+        ----------
+        row: int
+            The row of the cell.
+        col: int
+            The column of the cell.
+
+        Returns
+        -------
+        success: bool
+            Whether the flag was toggled or not. If false, the cell was already revealed.
+
+        Raises
+        ------
+        ValueError
+            If the row or column is out of range.
+        """
+        ...
     def quit(self) -> None:
         """Quit the game.
 
@@ -254,3 +361,61 @@ class Game:
         Functionally all this does is reset the game, clearing all moves.
         """
         ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
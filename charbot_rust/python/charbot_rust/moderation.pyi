@@ -0,0 +1,603 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Match:
+    """Where a message matched a banned word or a pattern.
+
+    Attributes
+    ----------
+    start : int
+        The index of the first character of the match in the message.
+    end : int
+        The index after the last character of the match, so ``message[start:end]`` is the match.
+    rule : str
+        The word from the list or the pattern it matched, as it was given.
+    text : str
+        The text of the message that matched.
+    """
+
+    start: int
+    end: int
+    rule: str
+    text: str
+
+class WordFilter:
+    """Banned words, compiled so a message is checked against all of them at once.
+
+    Messages are matched lowercase, without accents, zero width characters or combining marks, with
+    full width letters as plain ones, and with leetspeak like ``b4d`` or ``k1ll`` as the letters it
+    stands for. Words are matched as whole words, unless they start or end with ``*`` like in
+    Discord's automod, so ``*bad`` also matches ``superbad`` and ``bad*`` also matches ``badly``.
+
+    Parameters
+    ----------
+    words : list[str]
+        The words, at most 10000 of at most 100 letters each.
+
+    Raises
+    ------
+    ValueError
+        If there are too many words, or a word is too long or has no letters.
+    """
+
+    words: list[str]
+
+    def __init__(self, words: list[str]) -> None: ...
+    def find(self, text: str) -> list[Match]:
+        """Find the banned words in a message.
+
+        Where matches overlap, the first one is kept, and the longest of those that start in the same place.
+
+        Parameters
+        ----------
+        text : str
+            The message.
+
+        Returns
+        -------
+        list[Match]
+            The matches, in the order they are in the message.
+        """
+        ...
+    def is_clean(self, text: str) -> bool:
+        """Check if a message has no banned words."""
+        ...
+    def censor(self, text: str, mask: str = "*") -> str:
+        """Replace every character of the banned words in a message.
+
+        Parameters
+        ----------
+        text : str
+            The message.
+        mask : str
+            The character to replace them with. The default is ``"*"``.
+
+        Returns
+        -------
+        str
+            The message, censored.
+        """
+        ...
+    def __len__(self) -> int: ...
+
+class Report:
+    """How a message compared to the recent ones.
+
+    Attributes
+    ----------
+    score : float
+        How similar the message is to the most similar recent one, from 0 to 1.
+    flagged : bool
+        Whether the score reaches the threshold.
+    user_matches : int
+        How many of the user's recent messages, in any channel, it's similar to.
+    channel_matches : int
+        How many of the channel's recent messages, from anyone, it's similar to.
+    """
+
+    score: float
+    flagged: bool
+    user_matches: int
+    channel_matches: int
+
+class Similarity:
+    """The recent messages of each user and channel, to spot copy pasted spam from one user or a raid of many.
+
+    Messages are compared by MinHash fingerprints of their letters, folded like the word filter, so
+    checking a message takes the same time however many users and channels there are.
+
+    Parameters
+    ----------
+    window : int
+        How many recent messages are kept for each user and each channel, from 1 to 100. The default is 10.
+    threshold : float
+        How similar from 0 to 1 a message has to be to a recent one to be flagged. The default is 0.8.
+    max_age : float
+        How many seconds messages are kept for. The default is 300.
+    min_length : int
+        The fewest characters a message needs to be checked, shorter ones like "ok" are repeated too often
+        to be spam. The default is 10.
+
+    Raises
+    ------
+    ValueError
+        If the window isn't from 1 to 100, the threshold isn't from 0 to 1, or the max age isn't positive.
+    """
+
+    def __init__(self, window: int = 10, threshold: float = 0.8, max_age: float = 300.0, min_length: int = 10) -> None: ...
+    def check(self, text: str, user: int, channel: int, now: float | None = None) -> Report:
+        """Compare a message to the recent messages of its author and its channel, then remember it.
+
+        Parameters
+        ----------
+        text : str
+            The message.
+        user : int
+            The ID of its author.
+        channel : int
+            The ID of its channel.
+        now : float | None
+            The unix timestamp in seconds it was sent at, messages have to be checked in order. The default is None, for now.
+
+        Returns
+        -------
+        Report
+            How similar it is to the recent messages, and whether that's enough to flag it.
+        """
+        ...
+    def prune(self, now: float | None = None) -> None:
+        """Forget every message older than the max age, for the users and channels that have gone quiet."""
+        ...
+    def forget(self, user: int) -> None:
+        """Forget the messages of a user, like once they've been dealt with."""
+        ...
+    @staticmethod
+    def compare(first: str, second: str) -> float:
+        """Get how similar two messages are, from 0 to 1, however short they are."""
+        ...
+
+class Normalized:
+    """A name or message cleaned up, with what it looks like as ASCII and how odd it is.
+
+    Attributes
+    ----------
+    cleaned : str
+        The text without invisible characters, and without more than two combining marks on a letter.
+    skeleton : str
+        What the text looks like as ASCII, for comparing names.
+    score : float
+        The fraction of the characters that were stripped or are styled or from another script to look
+        like ASCII, from 0 to 1.
+    invisible : int
+        How many invisible characters were stripped, like zero width spaces.
+    marks : int
+        How many stacked combining marks were stripped, like zalgo text.
+    lookalikes : int
+        How many characters are styled letters or from another script to look like ASCII.
+    """
+
+    cleaned: str
+    skeleton: str
+    score: float
+    invisible: int
+    marks: int
+    lookalikes: int
+
+def normalize(text: str) -> Normalized:
+    """Clean up a name or message, and get what it looks like as ASCII and how odd it is.
+
+    Invisible characters are stripped, and combining marks past the second on a letter, which is zalgo
+    text. The skeleton maps characters to the ASCII they look like, like the skeletons of UTS #39, such
+    as Cyrillic letters, full width and mathematical letters, and accents, so look alike names can be compared.
+
+    Parameters
+    ----------
+    text : str
+        The text.
+
+    Returns
+    -------
+    Normalized
+        The cleaned text, its skeleton, and a weirdness score from 0 to 1 of how much of it was stripped
+        or styled to look like ASCII, with what went into it.
+    """
+    ...
+
+def skeleton(text: str) -> str:
+    """Get what text looks like as ASCII, for comparing names, like UTS #39 but also dropping accents.
+
+    Parameters
+    ----------
+    text : str
+        The text.
+
+    Returns
+    -------
+    str
+        The skeleton, which is for comparing and not for showing, since '1', 'I' and 'l' are all 'l'.
+    """
+    ...
+
+def is_confusable(first: str, second: str) -> bool:
+    """Check if two names look alike, like a user impersonating a moderator.
+
+    Parameters
+    ----------
+    first : str
+        The first name.
+    second : str
+        The second name.
+
+    Returns
+    -------
+    bool
+        True if their skeletons are the same, ignoring case, False otherwise.
+    """
+    ...
+
+class Link:
+    """A link or invite found in a message.
+
+    Attributes
+    ----------
+    start : int
+        The index of the first character of the link in the message.
+    end : int
+        The index after the last character of the link, so ``message[start:end]`` is the link.
+    text : str
+        The text of the message that is the link, as it was typed.
+    url : str
+        The link without its obfuscation, with the domain lowercase and in punycode.
+    host : str
+        The domain, lowercase and in punycode, like ``xn--dscord-3ve.com``.
+    display : str
+        The domain as it's shown, with punycode turned back into the letters it spells, like ``dіscord.com``.
+    invite : str | None
+        The code, if it's a Discord invite.
+    allowed : bool
+        Whether the link is on the allowlist.
+    lookalike : str | None
+        The allowed domain this one looks like without being it, like ``discord.com`` for ``dіscord.com``.
+    """
+
+    start: int
+    end: int
+    text: str
+    url: str
+    host: str
+    display: str
+    invite: str | None
+    allowed: bool
+    lookalike: str | None
+
+def scan(text: str, allowlist: list[str] | None = None) -> list[Link]:
+    """Find the links and Discord invites in a message, and check them against an allowlist.
+
+    Obfuscated links are found too, like 'discord(dot)gg/abc', 'discord . gg / abc', or ones with
+    invisible characters in them. Domains that aren't ASCII are turned into punycode, and a domain that
+    looks like an allowed one without being it, like 'dіscord.com' with a Cyrillic і, is reported as a lookalike.
+
+    Parameters
+    ----------
+    text : str
+        The message.
+    allowlist : list[str] | None
+        The domains links are allowed to, which allow their subdomains too, and the invite codes that are
+        allowed. An invite is only allowed if its code is, so allowing 'discord.gg' doesn't allow every
+        server's invites. The default is None, for nothing.
+
+    Returns
+    -------
+    list[Link]
+        The links in the order they're in, with whether each is allowed.
+
+    Raises
+    ------
+    ValueError
+        If an entry in the allowlist isn't a domain or an invite code.
+    """
+    ...
+
+def scan_many(messages: list[str], allowlist: list[str] | None = None) -> list[list[Link]]:
+    """Find the links and Discord invites in many messages at once, like ``scan`` does for one, split
+    across threads without holding the GIL.
+
+    Parameters
+    ----------
+    messages : list[str]
+        The messages.
+    allowlist : list[str] | None
+        The domains and invite codes that are allowed, as for ``scan``. The default is None, for nothing.
+
+    Returns
+    -------
+    list[list[Link]]
+        The links in each message, in the order of the messages.
+
+    Raises
+    ------
+    ValueError
+        If an entry in the allowlist isn't a domain or an invite code.
+    """
+    ...
+
+class Alert:
+    """How likely the joins around one are a raid.
+
+    Attributes
+    ----------
+    confidence : float
+        How likely it is that there's a raid, from 0 to 1.
+    flagged : bool
+        Whether the confidence reaches the threshold.
+    users : list[int]
+        The users that look like part of the raid if it's flagged, in the order they joined, otherwise none.
+        They're the new accounts, accounts made in one batch, and names made from one pattern, or everyone
+        in the window if none of them stand out.
+    joins : int
+        How many joins there were in the window.
+    rate_score : float
+        How unlikely that many joins are at the usual rate, from 0 to 1.
+    age_score : float
+        How much of the window is new accounts, or accounts made within an hour of each other, from 0 to 1.
+    name_score : float
+        How much of the window has names made from one pattern, like ``raider123`` and ``raider456``, from 0 to 1.
+    """
+
+    confidence: float
+    flagged: bool
+    users: list[int]
+    joins: int
+    rate_score: float
+    age_score: float
+    name_score: float
+
+class Detector:
+    """The recent joins to a guild, to spot a raid of accounts joining at once.
+
+    A burst of joins on its own could be the server being shared, so it takes new accounts, accounts
+    made in one batch, or names made from one pattern to be confident it's a raid.
+
+    Parameters
+    ----------
+    window : float
+        How many seconds of joins are looked at together. The default is 60.
+    min_joins : int
+        The fewest joins in the window that can be a raid, from 2 to 500. The default is 5.
+    threshold : float
+        The confidence from 0 to 1 that flags a raid. The default is 0.7.
+    baseline : float
+        How many joins an hour are usual to start with, it follows the joins after that. The default is 10.
+    new_account_age : float
+        How many seconds old an account has to be to not count as new. The default is 604800, a week.
+
+    Raises
+    ------
+    ValueError
+        If the window or baseline aren't positive, the minimum joins aren't from 2 to 500, the threshold
+        isn't from 0 to 1, or the new account age is negative.
+    """
+
+    def __init__(
+        self,
+        window: float = 60.0,
+        min_joins: int = 5,
+        threshold: float = 0.7,
+        baseline: float = 10.0,
+        new_account_age: float = 604800.0,
+    ) -> None: ...
+    def ingest(self, user_id: int, account_created_at: float, join_time: float, username: str) -> Alert:
+        """Add a join, and check the joins in the window around it for a raid.
+
+        Parameters
+        ----------
+        user_id : int
+            The ID of the member.
+        account_created_at : float
+            The unix timestamp in seconds their account was made at.
+        join_time : float
+            The unix timestamp in seconds they joined at, joins have to be added in order.
+        username : str
+            Their name.
+
+        Returns
+        -------
+        Alert
+            How likely the joins in the window are a raid, and who's in it.
+        """
+        ...
+    def reset(self) -> None:
+        """Forget the joins in the window, like once a raid has been dealt with, keeping the usual rate."""
+        ...
+    def __len__(self) -> int: ...
+
+class Weights:
+    """How much each part of a heuristic score counts, from 0 for not at all to 1 for enough on its own.
+
+    Parameters
+    ----------
+    caps : float
+        The weight of the share of letters that are capitals. The default is 0.5.
+    emoji : float
+        The weight of how much of the message is emoji. The default is 0.5.
+    repeats : float
+        The weight of repeated characters. The default is 0.5.
+    mentions : float
+        The weight of mentions. The default is 0.8.
+
+    Raises
+    ------
+    ValueError
+        If a weight isn't from 0 to 1.
+    """
+
+    caps: float
+    emoji: float
+    repeats: float
+    mentions: float
+
+    def __init__(self, caps: float = 0.5, emoji: float = 0.5, repeats: float = 0.5, mentions: float = 0.8) -> None: ...
+
+class Breakdown:
+    """How spammy a message looks, and why.
+
+    Attributes
+    ----------
+    caps_ratio : float
+        The share of the letters that are capitals, from 0 to 1, or 0 if there are too few letters to tell.
+    emoji_count : int
+        How many emoji there are, custom ones included.
+    emoji_density : float
+        The share of the message that's emoji, from 0 to 1, without counting whitespace.
+    longest_run : int
+        The most of the same character, emoji or mention in a row.
+    repeated : int
+        How many characters are in runs past the third of the same one, like the last four of "nooooooo".
+    mentions : int
+        How many users and roles are mentioned, @everyone and @here included.
+    score : float
+        How spammy the message looks overall, from 0 to 1.
+    """
+
+    caps_ratio: float
+    emoji_count: int
+    emoji_density: float
+    longest_run: int
+    repeated: int
+    mentions: int
+    score: float
+
+def score(text: str, weights: Weights | None = None) -> Breakdown:
+    """Score how spammy a message looks from its caps, emoji, repeated characters and mentions, in one pass.
+
+    Each part is scored from 0 to 1, and they're combined so any one part with a weight of 1 is enough on
+    its own: the score is the chance at least one part fires, each firing with its weight times its part.
+    Caps only count with at least 8 letters, emoji are full at half the message, repeats at 30 percent of
+    it past the third of a character in a row, and mentions at 5 of them.
+
+    Parameters
+    ----------
+    text : str
+        The message.
+    weights : Weights | None
+        How much each part counts. The default is None, for Weights().
+
+    Returns
+    -------
+    Breakdown
+        The parts and the score.
+    """
+    ...
+
+class SafeRegex:
+    """A pattern from a guild's automod rules, matched in time linear in the text, so no pattern can hang the bot.
+
+    The syntax is the usual one, with classes, groups, alternation, repetition, anchors, word boundaries,
+    ``\\p{...}`` properties and scripts, and the ``i``, ``m``, ``s`` and ``x`` flags. What can't be matched
+    without backtracking, like lookaround, backreferences and atomic groups, is rejected, and so are
+    patterns over 1000 characters or too big once compiled.
+
+    Parameters
+    ----------
+    pattern : str
+        The pattern.
+    case_insensitive : bool
+        Whether letters match in either case, like the ``i`` flag. The default is False.
+
+    Raises
+    ------
+    ValueError
+        If the pattern is invalid, uses something unsupported, or is too big, saying where.
+    """
+
+    def __init__(self, pattern: str, case_insensitive: bool = False) -> None: ...
+    @property
+    def pattern(self) -> str:
+        """The pattern, as it was given."""
+        ...
+    def is_match(self, text: str) -> bool:
+        """Check if the pattern matches anywhere in a message.
+
+        Parameters
+        ----------
+        text : str
+            The message.
+
+        Returns
+        -------
+        bool
+            True if it matches, False otherwise.
+        """
+        ...
+    def find(self, text: str) -> Match | None:
+        """Find the leftmost match in a message.
+
+        Parameters
+        ----------
+        text : str
+            The message.
+
+        Returns
+        -------
+        Match | None
+            The match, with the pattern as its rule, or None if it doesn't match.
+        """
+        ...
+    def find_all(self, text: str) -> list[Match]:
+        """Find every match in a message that doesn't overlap one before it.
+
+        Parameters
+        ----------
+        text : str
+            The message.
+
+        Returns
+        -------
+        list[Match]
+            The matches, in the order they're in, with the pattern as their rule.
+        """
+        ...
+
+class Toxicity:
+    """How toxic a message looks, and why.
+
+    Attributes
+    ----------
+    score : float
+        How likely the message is to be toxic, from 0 to 1.
+    rules : list[str]
+        The names of the rules that fired, each once, in the order they first did in the message. They're
+        'banned_word', 'slur', 'insult', 'targeted_insult' and 'threat'.
+    matches : list[Match]
+        Where each rule fired, with the name of the rule as the rule of the match.
+    """
+
+    score: float
+    rules: list[str]
+    matches: list[Match]
+
+def toxicity(text: str, words: WordFilter | None = None, slurs: WordFilter | None = None) -> Toxicity:
+    """Score how toxic a message looks, from banned words, slurs, insults and threats, without calling an outside API.
+
+    The message is checked as what it looks like, so look alike letters from other scripts, leetspeak,
+    accents, invisible characters, drawn out letters and words spelled out a letter at a time all match.
+    Insults count for more when they're aimed at someone, like 'you're an idiot', and less when they
+    are taken back, like 'you're not an idiot'. Each time a rule fires it has a chance of the message
+    being toxic, going by how often it's wrong, and the score is the chance at least one of them is right.
+
+    Parameters
+    ----------
+    text : str
+        The message.
+    words : WordFilter | None
+        The guild's banned words, each a chance of 0.5. The default is None, for none.
+    slurs : WordFilter | None
+        The slurs to look for, each a chance of 0.9. The default is None, for none, there's no built in list.
+
+    Returns
+    -------
+    Toxicity
+        The score, the rules that fired and where they did.
+    """
+    ...
@@ -0,0 +1,72 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+def team_names(count: int, locale: str = "en-US", seed: int | None = None) -> list[str]:
+    """Make up names for teams of an adjective and a noun, like "Brave Otter", in the language of a locale.
+
+    The words come from a word list for each language the bot is translated to, built into the library, and
+    are in the order they go in the language with the adjective agreeing, like "Nutria Valiente". No two of
+    the names share a word, so they're easy to tell apart.
+
+    Parameters
+    ----------
+    count : int
+        How many names to make up.
+    locale : str
+        The locale to use the word list of, the closest one is used if it doesn't have one of its own. The default is "en-US".
+    seed : int | None
+        The seed for the names, so they can be reproduced. The default is None, for random names.
+
+    Returns
+    -------
+    list[str]
+        The names, with each word capitalized.
+
+    Raises
+    ------
+    ValueError
+        If there aren't enough words for that many names, which is at least 35.
+    """
+
+def code_phrase(locale: str = "en-US", separator: str = "-", seed: int | None = None) -> str:
+    """Make up a phrase of an adjective and a noun in lowercase, like "brave-otter", in the language of a
+    locale, for names of temporary channels and codes to join with.
+
+    Parameters
+    ----------
+    locale : str
+        The locale to use the word list of, the closest one is used if it doesn't have one of its own. The default is "en-US".
+    separator : str
+        What goes between the words. The default is "-".
+    seed : int | None
+        The seed for the phrase, so it can be reproduced. The default is None, for a random phrase.
+
+    Returns
+    -------
+    str
+        The phrase.
+    """
+
+def fantasy_name(locale: str = "en-US", syllables: int | None = None, seed: int | None = None) -> str:
+    """Make up a fantasy name from syllables that sound like the language of a locale, like "Eldoria".
+
+    Parameters
+    ----------
+    locale : str
+        The locale to use the syllables of, the closest one is used if it doesn't have its own. The default is "en-US".
+    syllables : int | None
+        How many syllables the name has, from 2 to 4. The default is None, for 2 or 3.
+    seed : int | None
+        The seed for the name, so it can be reproduced. The default is None, for a random name.
+
+    Returns
+    -------
+    str
+        The name, capitalized.
+
+    Raises
+    ------
+    ValueError
+        If the number of syllables isn't from 2 to 4.
+    """
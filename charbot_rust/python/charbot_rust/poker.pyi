@@ -0,0 +1,131 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from asyncio import Future as _Future
+from enum import Enum
+
+class Category(Enum):
+    """The kinds of hand, from worst to best."""
+
+    HighCard: ...
+    Pair: ...
+    TwoPair: ...
+    ThreeOfAKind: ...
+    Straight: ...
+    Flush: ...
+    FullHouse: ...
+    FourOfAKind: ...
+    StraightFlush: ...
+
+class HandRank:
+    """The best five card hand in some cards, hand ranks compare like the hands do.
+
+    Attributes
+    ----------
+    category : Category
+        The kind of hand.
+    strength : int
+        The hand as one number, higher for a better hand and equal for a tie.
+    """
+
+    category: Category
+    strength: int
+
+    @property
+    def kickers(self) -> list[str]:
+        """The ranks that break ties between hands of the same category, most important first, like ['A', 'T']."""
+        ...
+    def __lt__(self, other: "HandRank") -> bool: ...
+    def __le__(self, other: "HandRank") -> bool: ...
+    def __gt__(self, other: "HandRank") -> bool: ...
+    def __ge__(self, other: "HandRank") -> bool: ...
+
+class Odds:
+    """How a hand did over a simulation.
+
+    Attributes
+    ----------
+    win : float
+        How often the hand won outright.
+    tie : float
+        How often the hand split the pot.
+    loss : float
+        How often the hand lost.
+    equity : float
+        The share of the pot the hand gets on average, with split pots shared out.
+    """
+
+    win: float
+    tie: float
+    loss: float
+    equity: float
+
+def evaluate(cards: list[str]) -> HandRank:
+    """Find the best five card hand in 5 to 7 cards.
+
+    Parameters
+    ----------
+    cards : list[str]
+        The cards, as a rank from 2 to 9, T or 10, J, Q, K and A followed by a suit of s, h, d or c, or
+        one of the suit symbols. For example ``['As', 'Td', '10h', 'K♣', '2c']``.
+
+    Returns
+    -------
+    HandRank
+        The category of the hand and the ranks that break ties with other hands of that category.
+
+    Raises
+    ------
+    ValueError
+        If there aren't from 5 to 7 cards, one isn't a valid card, or a card is there twice.
+    """
+    ...
+
+def simulate(
+    hand: list[str], n_opponents: int, iterations: int, board: list[str] = [], seed: int | None = None
+) -> Odds:
+    """Estimate how often a hold'em hand wins by dealing out the rest of the cards at random many times.
+
+    Parameters
+    ----------
+    hand : list[str]
+        The two cards in the hand, written as for ``evaluate``.
+    n_opponents : int
+        How many opponents, from 1 to 9, each with two unknown cards.
+    iterations : int
+        How many deals to try, more is more accurate, 10000 is usually within a percent.
+    board : list[str]
+        The community cards already dealt, from none to all 5. The default is none.
+    seed : int | None
+        The seed for the deals, so a result can be reproduced. The default is None, for random deals.
+
+    Returns
+    -------
+    Odds
+        How often the hand won, tied and lost, and its share of the pot on average.
+
+    Raises
+    ------
+    ValueError
+        If the hand isn't two cards, the board is more than 5, a card is invalid or there twice, or the
+        number of opponents or iterations is out of range.
+    """
+    ...
+
+def simulate_async(
+    hand: list[str], n_opponents: int, iterations: int, board: list[str] = [], seed: int | None = None
+) -> _Future[Odds]:
+    """Estimate how often a hold'em hand wins on a thread pool, so the event loop keeps running meanwhile.
+    It takes and gives the same as ``simulate``, once awaited.
+
+    Returns
+    -------
+    asyncio.Future[Odds]
+        How often the hand won, tied and lost, and its share of the pot on average.
+
+    Raises
+    ------
+    RuntimeError
+        If there isn't an event loop running.
+    """
+    ...
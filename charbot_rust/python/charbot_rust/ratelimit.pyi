@@ -0,0 +1,97 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from enum import Enum
+
+Key = int | str | tuple["Key", ...]
+
+class Mode(Enum):
+    """How a limiter counts what's been acquired."""
+
+    TokenBucket: ...
+    """A bucket of tokens that refills steadily, so bursts are allowed up to the rate and then spaced out."""
+    SlidingWindow: ...
+    """At most the rate in any period, counting exactly what was acquired in the last one."""
+
+class Limiter:
+    """Rate limits for many keys at once, each with their own bucket, like cooldowns for each user of a command.
+
+    Keys that have gone idle, whose buckets would be full again, are pruned every so often while acquiring,
+    at most once a minute or once a period if that's longer.
+
+    Parameters
+    ----------
+    rate : int
+        How much can be acquired in a period, from 1 to 10000.
+    per : float
+        How many seconds a period is.
+    mode : Mode
+        Whether to use a token bucket, or a sliding window. The default is Mode.TokenBucket.
+
+    Raises
+    ------
+    ValueError
+        If the rate isn't from 1 to 10000 or the period isn't positive.
+    """
+
+    def __init__(self, rate: int, per: float, mode: Mode = Mode.TokenBucket) -> None: ...
+    @property
+    def rate(self) -> int:
+        """How much can be acquired in a period."""
+        ...
+    @property
+    def per(self) -> float:
+        """How many seconds a period is."""
+        ...
+    @property
+    def mode(self) -> Mode:
+        """Whether it's a token bucket or a sliding window."""
+        ...
+    def try_acquire(self, key: Key, cost: int = 1, now: float | None = None) -> float | None:
+        """Take from the bucket for a key, or get how many seconds until it can be taken.
+
+        Parameters
+        ----------
+        key : int | str | tuple
+            What is limited, like a user ID, or a tuple of a user ID, a guild ID and a command name.
+        cost : int
+            How much to take. The default is 1.
+        now : float | None
+            The unix timestamp in seconds to acquire at. The default is None, for now.
+
+        Returns
+        -------
+        float | None
+            None if it was taken, otherwise the seconds until it could be, when nothing is taken.
+
+        Raises
+        ------
+        ValueError
+            If the cost is more than the rate, so it could never be taken.
+        """
+        ...
+    def retry_after(self, key: Key, cost: int = 1, now: float | None = None) -> float | None:
+        """Get how many seconds until a key could take from its bucket, without taking anything.
+
+        Returns
+        -------
+        float | None
+            None if it could be taken now, otherwise the seconds until it could be.
+
+        Raises
+        ------
+        ValueError
+            If the cost is more than the rate, so it could never be taken.
+        """
+        ...
+    def remaining(self, key: Key, now: float | None = None) -> int:
+        """Get how much a key could take from its bucket now."""
+        ...
+    def reset(self, key: Key) -> None:
+        """Empty the bucket for a key, so it starts over."""
+        ...
+    def prune(self, now: float | None = None) -> int:
+        """Forget the keys whose buckets would be full again by now anyway, and get how many there were."""
+        ...
+    def __len__(self) -> int: ...
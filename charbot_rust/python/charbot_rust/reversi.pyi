@@ -0,0 +1,222 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Disc(Enum):
+    """Represents a disc in the game, black always moves first."""
+
+    Black: ...
+    White: ...
+
+    def swap(self) -> "Disc":
+        """The other player's disc."""
+        ...
+    @property
+    def emoji(self) -> str:
+        """The emoji the disc is drawn as."""
+        ...
+
+class Game:
+    """A rust based implementation of reversi, also known as othello, against the computer.
+
+    Squares are named with a letter for the column and a number for the row from the top, like ``d3``.
+    A disc has to flip at least one of the other player's discs, and a player who can't flip any has to
+    pass, the other player moving again. The game ends once neither player can move.
+
+    Parameters
+    ----------
+    depth : int
+        How many moves ahead the computer looks, from 1 to 5. The default is 4, which takes up to about
+        25ms a move, 5 is stronger but slower, up to about 50ms.
+    human_first : bool
+        Whether the player moves first as black, otherwise the computer makes its first move as black
+        right away. The default is True.
+
+    Raises
+    ------
+    ValueError
+        If the depth isn't from 1 to 5.
+    """
+
+    def __init__(self, depth: int = 4, human_first: bool = True): ...
+    def __new__(cls, depth: int = 4, human_first: bool = True) -> "Game": ...
+    @property
+    def board(self) -> list[list[Disc | None]]:
+        """The board for the game, as 8 rows of 8 squares from the top row down, None for the empty squares."""
+        ...
+    @property
+    def player(self) -> Disc:
+        """The disc the player is playing as."""
+        ...
+    @property
+    def score(self) -> tuple[int, int]:
+        """The black and white discs on the board."""
+        ...
+    def play(self, square: str) -> list[str]:
+        """Put the player's disc on a square, and have the computer reply if the game is not complete.
+
+        Parameters
+        ----------
+        square: str
+            The square, like ``d3``.
+
+        Returns
+        -------
+        list[str]
+            The squares the computer went on, in order. This is more than one if the player had to pass,
+            and empty if the game ended or it's the computer that had to pass.
+
+        Raises
+        ------
+        ValueError
+            If the square isn't on the board, is taken, doesn't flip any discs, or the game is already over.
+        """
+        ...
+    def legal_moves(self) -> list[tuple[str, list[str]]]:
+        """The squares the player can go on, none once the game is over.
+
+        Returns
+        -------
+        list[tuple[str, list[str]]]
+            Each square, from the top left, with the squares of the discs going there would flip.
+        """
+        ...
+    def final_score(self) -> tuple[int, int] | None:
+        """The black and white discs at the end of the game.
+
+        As in tournaments, the squares left empty when neither player could move count for the winner,
+        or are shared in a draw.
+
+        Returns
+        -------
+        tuple[int, int] | None
+            The black and white score, adding up to 64, or None if the game isn't over.
+        """
+        ...
+    def winner(self) -> Disc | None:
+        """Check what disc, if any, has won, by having more discs once neither player can move.
+
+        Returns
+        -------
+        Disc | None
+            The disc that won the game, or None if the game has no winner.
+        """
+        ...
+    def is_draw(self) -> bool:
+        """Check if the game ended with the same number of discs each.
+
+        Returns
+        -------
+        bool
+            True if the game is a draw, False otherwise.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if the game is finished, once neither player can move.
+
+        Returns
+        -------
+        bool
+            True if no more moves can be made, False otherwise.
+        """
+        ...
+    def has_player_won(self) -> bool:
+        """Check if the player has won the game.
+
+        Returns
+        -------
+        bool
+            True if the player has won, False otherwise.
+        """
+        ...
+    def has_player_lost(self) -> bool:
+        """Check if the player has lost the game.
+
+        Returns
+        -------
+        bool
+            True if the player has lost, False otherwise.
+        """
+        ...
+    def to_emoji(self) -> str:
+        """The board as emoji, for an embed or message.
+
+        Each row is a line from the top starting with its number, with a line of letters under the columns, ie::
+
+            1️⃣🟩🟩🟩🟩🟩🟩🟩🟩
+            ...
+            4️⃣🟩🟩🟩⚪⚫🟩🟩🟩
+            ...
+            ⬛🇦🇧🇨🇩🇪🇫🇬🇭
+
+        The letters are kept apart by zero width spaces, so they aren't shown as flags.
+
+        Returns
+        -------
+        str
+            The emoji grid, the same as ``str(game)``.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
@@ -0,0 +1,141 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from datetime import date as _date
+
+class Cron:
+    """A recurring schedule, read from a cron expression.
+
+    There are 5 fields, for the minute, hour, day of the month, month and day of the week, or 6 with
+    the second first. Fields can be ``*``, values, ranges like ``1-5``, steps like ``*/15`` or ``5/15``,
+    and lists of those like ``mon,wed,fri``. Months and days of the week can be given by their first
+    three letters in english, and sunday is 0 or 7. The shortcuts ``@yearly``, ``@annually``,
+    ``@monthly``, ``@weekly``, ``@daily``, ``@midnight`` and ``@hourly`` can be used too.
+
+    When both day fields are set and neither starts with ``*``, a day matching either is enough, like
+    cron does.
+
+    Parameters
+    ----------
+    expression : str
+        The cron expression.
+
+    Raises
+    ------
+    ValueError
+        If the expression isn't valid, or it can never happen, like ``0 0 30 2 *``.
+    """
+
+    def __new__(cls, expression: str) -> Cron: ...
+    @property
+    def expression(self) -> str:
+        """The expression the schedule was read from."""
+    def next(self, count: int = 1, tz: str = "UTC", after: int | None = None) -> list[int]:
+        """The next times the schedule happens.
+
+        The schedule goes by the local time in the time zone. Local times that are skipped when the
+        clocks go forward happen when they do, and ones that are repeated when the clocks go back
+        happen once.
+
+        Parameters
+        ----------
+        count : int
+            How many times to find, at most 1000. The default is 1.
+        tz : str
+            The name of the time zone in the tz database to go by, like 'Europe/Paris'. The default is
+            'UTC'.
+        after : int | None
+            The unix timestamp in seconds to find the times after. The default is None, for now.
+
+        Returns
+        -------
+        list[int]
+            The unix timestamps in seconds, fewer than count if the year 9999 ends first.
+
+        Raises
+        ------
+        ValueError
+            If count is more than 1000, the time zone isn't in the tz database, or after isn't from
+            year 1 to 9999.
+        """
+    def describe(self, locale: str = "en-US") -> str:
+        """A sentence saying when the schedule happens, like 'At 9:00 AM, Monday through Friday'.
+
+        Parameters
+        ----------
+        locale : str
+            The locale to describe the schedule in, falling back like translations do. The default is
+            'en-US'.
+
+        Returns
+        -------
+        str
+            The sentence.
+
+        Raises
+        ------
+        ValueError
+            If the description couldn't be translated.
+        """
+
+class Recurrence:
+    """An event that repeats by a rule like the RRULE of iCalendar, such as every week on tuesday and
+    thursday at 19:00, at the same local time in its time zone whatever the offset.
+
+    The rule has FREQ, which is DAILY, WEEKLY, MONTHLY or YEARLY, and can have INTERVAL, BYDAY like
+    ``TU,TH`` or ``-1FR`` for the last friday of the month, BYMONTHDAY like ``1`` or ``-1`` for the last
+    day, BYMONTH, BYHOUR, BYMINUTE, and COUNT or UNTIL. The times of day and the days the rule doesn't
+    give are taken from the start, weeks start on monday, and months without the day are skipped.
+
+    Parameters
+    ----------
+    rule : str
+        The rule, like 'FREQ=WEEKLY;BYDAY=TU,TH;BYHOUR=19;BYMINUTE=0'.
+    start : int
+        The unix timestamp in seconds of when the event starts, which the rule counts from.
+    tz : str
+        The name of the time zone in the tz database the event is held in, like 'Europe/Amsterdam'.
+        The default is 'UTC'.
+    skip : list[datetime.date]
+        The local dates the event doesn't happen on, like holidays. They still count towards COUNT.
+
+    Raises
+    ------
+    ValueError
+        If the rule isn't valid, the time zone isn't in the tz database, or start isn't from year 1
+        to 9999.
+    """
+
+    rule: str
+    start: int
+    tz: str
+
+    def __new__(cls, rule: str, start: int, tz: str = "UTC", skip: list[_date] = ...) -> Recurrence: ...
+    def skip(self, date: _date) -> None:
+        """Skip the event on the local date, like a holiday."""
+    def skipped(self) -> list[_date]:
+        """The local dates the event is skipped on."""
+    def between(self, start: int, end: int) -> list[int]:
+        """The times the event happens from start and before end, at most 10000 of them.
+
+        Each is the local time the rule gives, so the event stays at 19:00 when the clocks change. A
+        local time the clocks skip happens when they go forward, and one they repeat happens the first
+        time.
+
+        Parameters
+        ----------
+        start : int
+            The unix timestamp in seconds to find times from.
+        end : int
+            The unix timestamp in seconds to find times before.
+
+        Returns
+        -------
+        list[int]
+            The unix timestamps in seconds, in order.
+
+        Raises
+        ------
+        ValueError
+            If start or end isn't from year 1 to 9999.
+        """
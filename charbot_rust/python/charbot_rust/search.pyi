@@ -0,0 +1,95 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Hit:
+    """A document a search found."""
+
+    @property
+    def id(self) -> int | str:
+        """The document's id, as it was added."""
+        ...
+    @property
+    def title(self) -> str:
+        """The document's title."""
+        ...
+    @property
+    def score(self) -> float:
+        """How well it matched, which is only comparable with the scores of the same search."""
+        ...
+    @property
+    def snippet(self) -> str:
+        """About 30 words of the body where it matched best, on one line, with the words that matched in
+        bold and an ellipsis where the body goes on."""
+        ...
+
+class Index:
+    """Documents, like FAQ entries or tags, to search for words in, ranked with BM25.
+
+    Words are found without regard to case or accents, and in other forms of the same word, like "rules"
+    for "ruling", by a light stemmer for the locale. Words that are in nearly everything, like "the", are
+    left out of queries unless they're all there is. Words in the title count three times.
+
+    Parameters
+    ----------
+    locale : str | None
+        The locale of the documents, one of en-US, es-ES, fr or nl, or another locale of their languages.
+        The default is en-US. None is for not stemming words, so they're only found as they are.
+
+    Raises
+    ------
+    ValueError
+        If the locale isn't of a supported language.
+    """
+
+    def __init__(self, locale: str | None = "en-US") -> None: ...
+    @property
+    def locale(self) -> str | None:
+        """The locale words are stemmed for, or None for not stemming them."""
+        ...
+    def add(self, id: int | str, title: str, body: str) -> None:
+        """Add a document, replacing the one with the same id if there is one.
+
+        Parameters
+        ----------
+        id : int | str
+            What tells the document apart, like the name of a tag.
+        title : str
+            The document's title.
+        body : str
+            The document's text.
+        """
+        ...
+    def remove(self, id: int | str) -> bool:
+        """Remove a document.
+
+        Parameters
+        ----------
+        id : int | str
+            The document's id.
+
+        Returns
+        -------
+        bool
+            Whether there was a document with the id.
+        """
+        ...
+    def search(self, query: str, limit: int = 10) -> list[Hit]:
+        """Find the documents that match a query best.
+
+        Parameters
+        ----------
+        query : str
+            What to search for.
+        limit : int
+            The most documents to give. The default is 10.
+
+        Returns
+        -------
+        list[Hit]
+            The documents with any of the query's words, best first, and in the order they were added when
+            they're as good.
+        """
+        ...
+    def __contains__(self, id: int | str) -> bool: ...
+    def __len__(self) -> int: ...
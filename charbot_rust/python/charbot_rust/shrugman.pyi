@@ -0,0 +1,154 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Game:
+    """A rust based implementation of shrugman, hangman where each miss draws more of ``¯\\_(ツ)_/¯``.
+
+    Parameters
+    ----------
+    locale : str
+        The locale to pick the word in, e.g. 'es-ES'. Locales without words of their own, and unknown
+        ones, use the closest that has them, which is en-US if nothing else. The default is 'en-US'.
+    """
+
+    def __init__(self, locale: str = "en-US"): ...
+    def __new__(cls, locale: str = "en-US") -> "Game": ...
+    @property
+    def word(self) -> str:
+        """The word to guess, only show it once the game is over."""
+        ...
+    @property
+    def locale(self) -> str:
+        """The locale the word is in."""
+        ...
+    @property
+    def guessed(self) -> list[str]:
+        """The letters guessed so far, in alphabetical order, without accents."""
+        ...
+    @property
+    def misses(self) -> int:
+        """How many guesses weren't in the word."""
+        ...
+    @property
+    def remaining_misses(self) -> int:
+        """How many more misses lose the game."""
+        ...
+    @property
+    def masked(self) -> str:
+        """The word with a ``_`` for each letter not guessed yet, spaced out, ie ``_ _ t t _ _``."""
+        ...
+    @property
+    def shrug(self) -> str:
+        """As much of the shrug as there have been misses, the whole shrug loses the game."""
+        ...
+    def guess(self, letter: str) -> bool:
+        """Guess a letter.
+
+        Accents don't need to be typed, guessing ``e`` finds ``é`` and ``è`` too. Letters of their own,
+        like the Spanish ``ñ``, do need to be guessed as themselves.
+
+        Parameters
+        ----------
+        letter : str
+            The letter to guess, in either case.
+
+        Returns
+        -------
+        bool
+            True if the letter is in the word, False if it is a miss.
+
+        Raises
+        ------
+        ValueError
+            If the guess isn't a single letter, was already guessed, or the game is already over.
+        """
+        ...
+    def is_won(self) -> bool:
+        """Check if every letter of the word has been guessed.
+
+        Returns
+        -------
+        bool
+            True if the player has won, False otherwise.
+        """
+        ...
+    def is_lost(self) -> bool:
+        """Check if the shrug is complete.
+
+        Returns
+        -------
+        bool
+            True if the player has lost, False otherwise.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if the game is finished, either way.
+
+        Returns
+        -------
+        bool
+            True if no more guesses can be made, False otherwise.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
@@ -0,0 +1,195 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Machine:
+    """A slot machine, its reels and what it pays out.
+
+    Each reel is a strip of symbols that loops around, and the window shows ``rows`` symbols of each,
+    from where the reel stopped down. A line wins with a run of the same symbol from the leftmost reel,
+    paying the bet on the line times the best multiplier for that symbol that the run is long enough for.
+
+    Parameters
+    ----------
+    reels : list[list[str]]
+        The symbols on each reel, in order.
+    payouts : list[tuple[str, int, int]]
+        The symbol, how many have to be in a row, and the multiplier of the bet it pays, for each payout.
+    rows : int
+        How many symbols of each reel the window shows. The default is 3.
+    lines : list[list[int]] | None
+        The row for each reel, from 0 for the top row, of each line that pays. The default is None, for one
+        straight line across each row.
+
+    Raises
+    ------
+    ValueError
+        If there are no reels, lines or payouts, a reel is empty, there are more rows than symbols on a
+        reel, a line doesn't have a row in the window for each reel, or a payout is for a symbol that
+        isn't on any reel, longer than the reels, repeated, or has a multiplier of 0.
+    """
+
+    def __new__(cls, reels: list[list[str]], payouts: list[tuple[str, int, int]], rows: int = 3, lines: list[list[int]] | None = None) -> "Machine": ...
+    @staticmethod
+    def classic() -> "Machine":
+        """A classic three reel machine of fruit, with five lines: the three rows and both diagonals.
+
+        It pays back about 95% of what is bet.
+        """
+        ...
+    @property
+    def reels(self) -> list[list[str]]: ...
+    @property
+    def rows(self) -> int: ...
+    @property
+    def lines(self) -> list[list[int]]: ...
+    @property
+    def payouts(self) -> list[tuple[str, int, int]]: ...
+    def spin(self, server_seed: str, client_seed: str, nonce: int, bet: int) -> "Spin":
+        """Spin the reels, provably fairly.
+
+        Where each reel stops comes from the HMAC-SHA256 of ``"{client_seed}:{nonce}:{round}"`` keyed with
+        the server seed, starting from round 0. The hash is split into 32 bit big endian numbers, one for
+        each reel, with a round for each eight reels, and each number ``n`` stops its reel at
+        ``n * len(reel) >> 32``.
+
+        The hash of the server seed is shown before spinning, and the seed itself once it is replaced, so
+        players can check the spins weren't changed. The client seed is the players' own, so the server
+        can't choose a server seed to suit it, and the nonce counts the spins made with the seeds.
+
+        Parameters
+        ----------
+        server_seed : str
+            The secret server seed, from `new_server_seed`.
+        client_seed : str
+            The seed the player picked.
+        nonce : int
+            How many spins were made with the seeds before this one.
+        bet : int
+            The bet on each line.
+
+        Returns
+        -------
+        Spin
+            The symbols shown, the lines that won, and the winnings.
+
+        Raises
+        ------
+        ValueError
+            If the bet is too large to pay out.
+        """
+        ...
+    def verify(self, spin: "Spin", server_seed: str) -> bool:
+        """Check a spin once its server seed is revealed.
+
+        Parameters
+        ----------
+        spin : Spin
+            The spin to check.
+        server_seed : str
+            The revealed server seed.
+
+        Returns
+        -------
+        bool
+            True if the seed has the hash the spin was made with, and spinning again with it gives the same
+            spin, False otherwise.
+        """
+        ...
+    def return_to_player(self) -> float:
+        """Work out what the machine pays back on average, as a fraction of what is bet.
+
+        Every way the reels can stop is gone through, so this is exact.
+
+        Returns
+        -------
+        float
+            The average winnings for each 1 bet.
+
+        Raises
+        ------
+        ValueError
+            If the reels can stop in more than a million ways.
+        """
+        ...
+
+class LineWin:
+    """A line that won on a spin.
+
+    Attributes
+    ----------
+    line : int
+        The index of the line in the lines of the machine.
+    symbol : str
+        The symbol it won with.
+    count : int
+        How many of the symbol are in a row from the left.
+    payout : int
+        What the line won.
+    """
+
+    line: int
+    symbol: str
+    count: int
+    payout: int
+
+class Spin:
+    """The result of a spin, ``str(spin)`` is the grid for display, a line of symbols for each row.
+
+    Attributes
+    ----------
+    grid : list[list[str]]
+        The symbols in the window, a row at a time.
+    stops : list[int]
+        Where each reel stopped, the index of the symbol in the top row.
+    wins : list[LineWin]
+        The lines that won.
+    winnings : int
+        What every line won together.
+    bet : int
+        The bet on each line.
+    cost : int
+        The bet on every line together, what the spin costs.
+    server_seed_hash : str
+        The hash of the server seed, to check it against once the seed is revealed.
+    client_seed : str
+        The client seed.
+    nonce : int
+        The nonce.
+    """
+
+    grid: list[list[str]]
+    stops: list[int]
+    wins: list[LineWin]
+    winnings: int
+    bet: int
+    cost: int
+    server_seed_hash: str
+    client_seed: str
+    nonce: int
+
+def new_server_seed() -> str:
+    """Make a random server seed, to keep secret until it is replaced.
+
+    Returns
+    -------
+    str
+        64 hex digits from a cryptographically secure random number generator.
+    """
+
+def hash_server_seed(server_seed: str) -> str:
+    """Hash a server seed, to show players before they spin with it.
+
+    Once the seed is revealed, anyone can check it has this hash, so it wasn't changed after the spins
+    were made, and spin again with it to check the results.
+
+    Parameters
+    ----------
+    server_seed : str
+        The server seed.
+
+    Returns
+    -------
+    str
+        The SHA-256 hash of the seed, in hex.
+    """
@@ -0,0 +1,122 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from datetime import datetime as _datetime
+
+class Snowflake:
+    """A discord ID, which says when it was made and by which worker and process.
+
+    They compare by when they were made, which is the same as comparing the numbers, since the time is
+    their top bits. They can be used anywhere an int can, like ``int(snowflake)``.
+
+    Parameters
+    ----------
+    id : int
+        The ID.
+    """
+
+    id: int
+
+    def __new__(cls, id: int) -> Snowflake: ...
+    @staticmethod
+    def from_time(time: _datetime | int | float, high: bool = False) -> Snowflake:
+        """The lowest snowflake made at the time, or the highest if high, for ``before`` and ``after``
+        cursors. See ``from_time`` for the details."""
+    @property
+    def timestamp(self) -> float:
+        """When the snowflake was made, as a unix timestamp in seconds."""
+    @property
+    def worker_id(self) -> int:
+        """The ID of the discord worker that made the snowflake."""
+    @property
+    def process_id(self) -> int:
+        """The ID of the process on the worker that made the snowflake."""
+    @property
+    def increment(self) -> int:
+        """How many snowflakes the process had made before this one, wrapping around at 4096."""
+    def __lt__(self, other: Snowflake) -> bool: ...
+    def __le__(self, other: Snowflake) -> bool: ...
+    def __gt__(self, other: Snowflake) -> bool: ...
+    def __ge__(self, other: Snowflake) -> bool: ...
+    def __hash__(self) -> int: ...
+    def __int__(self) -> int: ...
+    def __index__(self) -> int: ...
+
+def timestamp(id: int) -> float:
+    """When a discord ID was made.
+
+    Parameters
+    ----------
+    id : int
+        The ID.
+
+    Returns
+    -------
+    float
+        The unix timestamp in seconds, to the millisecond.
+    """
+
+def worker_id(id: int) -> int:
+    """The ID of the discord worker that made an ID, from 0 to 31."""
+
+def process_id(id: int) -> int:
+    """The ID of the process on the discord worker that made an ID, from 0 to 31."""
+
+def increment(id: int) -> int:
+    """How many IDs the process had made before an ID, from 0 to 4095 before wrapping around."""
+
+def compare(a: int, b: int) -> int:
+    """Compare two discord IDs by when they were made.
+
+    Parameters
+    ----------
+    a : int
+        The first ID.
+    b : int
+        The second ID.
+
+    Returns
+    -------
+    int
+        -1 if a was made first, 1 if b was, and 0 if they're the same ID.
+    """
+
+def sort(ids: list[int], reverse: bool = False) -> list[int]:
+    """Sort discord IDs by when they were made.
+
+    Parameters
+    ----------
+    ids : list[int]
+        The IDs.
+    reverse : bool
+        Whether to put the newest first instead of the oldest. The default is False.
+
+    Returns
+    -------
+    list[int]
+        The IDs, sorted.
+    """
+
+def from_time(time: _datetime | int | float, high: bool = False) -> int:
+    """Make a discord ID for a time, to get messages from before or after it with the ``before`` and
+    ``after`` cursors of the API.
+
+    Parameters
+    ----------
+    time : datetime.datetime | int | float
+        The time, as a datetime or a unix timestamp in seconds. Naive datetimes are local time, like
+        datetime.timestamp(), and fractions of a millisecond are dropped.
+    high : bool
+        Whether to make the highest ID of the millisecond instead of the lowest. Use False for
+        ``before`` and True for ``after``, so neither includes the time itself. The default is False.
+
+    Returns
+    -------
+    int
+        The ID.
+
+    Raises
+    ------
+    ValueError
+        If the time is before 2015 or after 2154-05-15 07:35:11 UTC, which IDs can't hold.
+    """
@@ -0,0 +1,171 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from os import PathLike
+from typing import Any, TypeVar as _TypeVar
+
+from .charbot_rust import Buffer as _Buffer
+
+_T = _TypeVar("_T")
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+Value = int | str | _Buffer
+
+class KvStore:
+    """A store of small values that lasts, like cooldowns and pity counters, in namespaces of keys that can
+    expire, kept in a file on disk.
+
+    The file is a SQLite database with a write-ahead log, and every change is committed to it before the
+    call making it returns. Any number of stores can have the file open at once, in this process or any
+    other, and each sees the changes of the others. A file that's been corrupted is an error, rather
+    than read up to where it's wrong.
+
+    A store can be used from any thread, and the GIL is let go while it reads and writes, so a slow disk
+    doesn't hold up the rest of the bot.
+
+    Parameters
+    ----------
+    path : str | PathLike[str]
+        The file, which is made if there isn't one.
+    sync : bool
+        Whether each change is synced to disk before the call making it returns, so it survives the
+        machine crashing and not only the bot. Syncing takes a few milliseconds. The default is True.
+
+    Raises
+    ------
+    ValueError
+        If the file can't be opened or isn't a store.
+    """
+
+    def __init__(self, path: str | PathLike[str], /, sync: bool = True) -> None: ...
+    @property
+    def path(self) -> str:
+        """The file the store is kept in."""
+        ...
+    @property
+    def sync(self) -> bool:
+        """Whether each change is synced to disk before the call making it returns."""
+        ...
+    def get(self, namespace: str, key: str, default: _T = None, now: float | None = None) -> Value | _T:
+        """Get the value at a key.
+
+        Parameters
+        ----------
+        namespace : str
+            The namespace, like ``"cooldowns"``, so keys of different cogs can't collide.
+        key : str
+            The key, like a member ID.
+        default : Any
+            What to get if the key isn't set or has expired. The default is None.
+        now : float | None
+            The unix timestamp in seconds to look up at. The default is None, for now.
+
+        Returns
+        -------
+        int | str | Buffer
+            The value, as it was set, with bytes as a Buffer, or the default.
+
+        Raises
+        ------
+        ValueError
+            If the file couldn't be read.
+        """
+        ...
+    def set(self, namespace: str, key: str, value: int | str | _BytesLike, ttl: float | None = None, now: float | None = None) -> None:
+        """Set the value at a key.
+
+        Parameters
+        ----------
+        namespace : str
+            The namespace, like ``"cooldowns"``.
+        key : str
+            The key, like a member ID.
+        value : int | str | bytes-like
+            The value, with integers fitting in 64 bits.
+        ttl : float | None
+            How many seconds the key expires after. The default is None, for never.
+        now : float | None
+            The unix timestamp in seconds to set it at. The default is None, for now.
+
+        Raises
+        ------
+        ValueError
+            If the TTL isn't positive or the change couldn't be written.
+        """
+        ...
+    def delete(self, namespace: str, key: str, now: float | None = None) -> bool:
+        """Delete a key, and get whether it was set and hadn't expired.
+
+        Raises
+        ------
+        ValueError
+            If the change couldn't be written.
+        """
+        ...
+    def increment(self, namespace: str, key: str, amount: int = 1, ttl: float | None = None, now: float | None = None) -> int:
+        """Add to the integer at a key, which counts as 0 if it isn't set, in one change, like for a pity
+        counter.
+
+        Parameters
+        ----------
+        namespace : str
+            The namespace, like ``"pity"``.
+        key : str
+            The key, like a member ID.
+        amount : int
+            How much to add, which can be negative. The default is 1.
+        ttl : float | None
+            How many seconds the key expires after. The default is None, for when it expired already, or
+            never if it wasn't set.
+        now : float | None
+            The unix timestamp in seconds to increment it at. The default is None, for now.
+
+        Returns
+        -------
+        int
+            The total.
+
+        Raises
+        ------
+        ValueError
+            If the value isn't an integer, the total doesn't fit in 64 bits, the TTL isn't positive, or the
+            change couldn't be written.
+        """
+        ...
+    def keys(self, namespace: str, now: float | None = None) -> list[str]:
+        """Get the keys of a namespace that haven't expired, in order.
+
+        Raises
+        ------
+        ValueError
+            If the file couldn't be read.
+        """
+        ...
+    def expire(self, now: float | None = None) -> int:
+        """Delete every key that has expired by now, and get how many there were.
+
+        Raises
+        ------
+        ValueError
+            If the changes couldn't be written.
+        """
+        ...
+    def compact(self, now: float | None = None) -> None:
+        """Delete every key that has expired by now, and rewrite the file without the space they and
+        overwritten values took, with the write-ahead log folded into it.
+
+        Raises
+        ------
+        ValueError
+            If the file couldn't be rewritten.
+        """
+        ...
+    def __len__(self) -> int:
+        """How many keys there are in every namespace, that haven't expired.
+
+        Raises
+        ------
+        ValueError
+            If the file couldn't be read.
+        """
+        ...
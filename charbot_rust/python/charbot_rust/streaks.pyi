@@ -0,0 +1,84 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+from .cooldowns import Kind as Kind
+
+class Streak:
+    """A daily or weekly streak, as it was after the last claim.
+
+    Parameters
+    ----------
+    count : int
+        How many days or weeks in a row have been claimed. The default is 0.
+    best : int
+        The longest the streak has been. The default is 0.
+    tokens : int
+        Grace tokens, each one covers a day or week that was missed without breaking the streak. The default is 0.
+    last_claim : int | None
+        The unix timestamp in seconds of the last claim. The default is None, for no claims yet.
+
+    Raises
+    ------
+    ValueError
+        If the best is less than the count, or there's a count without a last claim.
+    """
+
+    count: int
+    best: int
+    tokens: int
+    last_claim: int | None
+
+    def __init__(self, count: int = 0, best: int = 0, tokens: int = 0, last_claim: int | None = None) -> None: ...
+    def with_tokens(self, tokens: int) -> "Streak":
+        """Get the same streak with a different number of grace tokens, like after buying some."""
+        ...
+
+class Claim:
+    """What claiming a streak did.
+
+    Attributes
+    ----------
+    streak : Streak
+        The streak after the claim, to store for the next one.
+    counted : bool
+        Whether the claim counted, it doesn't if the day or week was already claimed.
+    broke : bool
+        Whether the streak broke and started again at 1.
+    tokens_used : int
+        How many grace tokens were used to cover the days or weeks that were missed.
+    """
+
+    streak: Streak
+    counted: bool
+    broke: bool
+    tokens_used: int
+
+def claim(streak: Streak, now: int, tz: str, kind: Kind = Kind.Daily) -> Claim:
+    """Claim a daily or weekly streak, counting days and weeks in a time zone.
+
+    Days and weeks start at local midnight, weeks on monday, so days the clocks change on are 23 or 25
+    hours long and claims just before and just after midnight are a day apart. Missing days or weeks
+    breaks the streak unless there are enough grace tokens to cover all of them, in which case they're used.
+
+    Parameters
+    ----------
+    streak : Streak
+        The streak as it was after the last claim, or a new Streak for someone that hasn't claimed yet.
+    now : int
+        The unix timestamp in seconds of the claim.
+    tz : str
+        The name of the time zone in the tz database, like ``"America/New_York"`` or ``"UTC"``.
+    kind : Kind
+        Whether the streak is daily or weekly. The default is Kind.Daily.
+
+    Returns
+    -------
+    Claim
+        The streak after the claim, whether the claim counted, whether the streak broke, and how many tokens were used.
+
+    Raises
+    ------
+    ValueError
+        If the time zone isn't in the tz database, or the claim is before the last one.
+    """
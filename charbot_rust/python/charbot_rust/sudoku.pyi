@@ -0,0 +1,195 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+import enum as __enum
+from asyncio import Future as _Future
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Difficulty(__enum.Enum):
+    """How hard a puzzle is, by what it takes to solve it.
+
+    Easy:
+        Every step is a cell with only one value left.
+    Medium:
+        Some steps are a value with only one cell left in a row, column or box.
+    Hard:
+        Some steps need more than that, like trying a value and seeing if it works out.
+    """
+
+    Easy: int = ...  # pyright: ignore[reportGeneralTypeIssues]
+    Medium: int = ...  # pyright: ignore[reportGeneralTypeIssues]
+    Hard: int = ...  # pyright: ignore[reportGeneralTypeIssues]
+
+class Puzzle:
+    """A generated puzzle, along with its solution."""
+
+    board: list[list[int]]
+    """The puzzle, as 9 rows of 9 cells with 0 for the empty ones."""
+    solution: list[list[int]]
+    """The only solution of the puzzle."""
+    difficulty: Difficulty
+    """How hard the puzzle is."""
+
+    def to_bytes(self) -> _Buffer:
+        """Save the puzzle as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved puzzle, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Puzzle":
+        """Load a puzzle saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved puzzle.
+
+        Returns
+        -------
+        Puzzle
+            The puzzle, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved puzzle, is from another version, or couldn't have come from a real puzzle.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the puzzle as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved puzzle, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Puzzle":
+        """Load a puzzle saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved puzzle.
+
+        Returns
+        -------
+        Puzzle
+            The puzzle, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved puzzle, is from another version, or couldn't have come from a real puzzle.
+        """
+        ...
+
+def generate(difficulty: Difficulty, /) -> Puzzle:
+    """Generate a puzzle with exactly one solution.
+
+    Parameters
+    ----------
+    difficulty : Difficulty
+        How hard the puzzle should be. Hard puzzles are rarer, so they take the longest, but still only
+        milliseconds.
+
+    Returns
+    -------
+    Puzzle
+        The puzzle, its solution and its difficulty, which is the one asked for except in the rare case
+        no puzzle that hard turned up.
+    """
+    ...
+
+def generate_async(difficulty: Difficulty, /) -> _Future[Puzzle]:
+    """Generate a puzzle with exactly one solution on a thread pool, so the event loop keeps running
+    meanwhile. It takes and gives the same as ``generate``, once awaited.
+
+    Returns
+    -------
+    asyncio.Future[Puzzle]
+        The puzzle, its solution and its difficulty.
+
+    Raises
+    ------
+    RuntimeError
+        If there isn't an event loop running.
+    """
+    ...
+
+def solve(board: list[list[int]], /) -> list[list[int]] | None:
+    """Solve a puzzle.
+
+    Parameters
+    ----------
+    board : list[list[int]]
+        The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+
+    Returns
+    -------
+    list[list[int]] | None
+        A solution, or None if there isn't one. If there are several, which one is returned is arbitrary.
+
+    Raises
+    ------
+    ValueError
+        If the board isn't 9 rows of 9 cells from 0 to 9.
+    """
+    ...
+
+def validate_move(board: list[list[int]], row: int, col: int, value: int, /) -> bool:
+    """Check if a value can go in a cell, without the same value already being in its row, column or box.
+
+    What is in the cell itself now is ignored, so a value can be changed. This doesn't check that the
+    value is the one in the solution, compare with ``Puzzle.solution`` for that.
+
+    Parameters
+    ----------
+    board : list[list[int]]
+        The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+    row : int
+        The row of the cell, from 0 at the top to 8.
+    col : int
+        The column of the cell, from 0 on the left to 8.
+    value : int
+        The value to check, from 1 to 9.
+
+    Returns
+    -------
+    bool
+        True if the value can go there, False otherwise.
+
+    Raises
+    ------
+    ValueError
+        If the board isn't 9 rows of 9 cells from 0 to 9, or the row, column or value is out of range.
+    """
+    ...
+
+def grade(board: list[list[int]], /) -> Difficulty | None:
+    """Grade how hard a puzzle is.
+
+    Parameters
+    ----------
+    board : list[list[int]]
+        The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+
+    Returns
+    -------
+    Difficulty | None
+        The difficulty, or None if the puzzle doesn't have exactly one solution.
+
+    Raises
+    ------
+    ValueError
+        If the board isn't 9 rows of 9 cells from 0 to 9.
+    """
+    ...
@@ -3,6 +3,10 @@
 # SPDX-License-Identifier: MIT
 from enum import Enum, IntEnum
 
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
 class Difficulty(IntEnum):
     EASY = ...
     MEDIUM = ...
@@ -73,6 +77,29 @@ class Game:
         -------
         int | None
             Return the coputer's move, or None if the computer didn't move
+
+        Raises
+        ------
+        ValueError
+            If the index isn't from 0 to 8, the square is already taken, or the game is already over.
+        """
+        ...
+    def legal_moves(self) -> list[int]:
+        """The indices of the squares the player can play in, none once the game is over.
+
+        Returns
+        -------
+        list[int]
+            The empty squares, in order.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if the game is finished, by a win for either side or a draw.
+
+        Returns
+        -------
+        bool
+            True if no more moves can be made, False otherwise.
         """
         ...
     def display_commands(self) -> list[tuple[Offset, Piece]]:
@@ -129,3 +156,61 @@ class Game:
             The participation and bonus points for the player.
         """
         ...
+    def to_bytes(self) -> _Buffer:
+        """Save the game as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Game":
+        """Load a game saved by ``to_bytes``.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the game as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved game, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Game":
+        """Load a game saved by ``to_json``.
+
+        Parameters
+        ----------
+        data : str
+            The saved game.
+
+        Returns
+        -------
+        Game
+            The game, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved game, is from another version, or couldn't have come from a real game.
+        """
+        ...
@@ -7,7 +7,11 @@ __all__ = ("Difficulty",)
 
 
 class Difficulty(IntEnum):
-    """Represents the difficulty level of the game."""
+    """Represents the difficulty level of the game.
+
+    ``EASY`` plays randomly, ``MEDIUM`` wins and blocks when it can but can be forked, ``HARD`` plays
+    perfectly and moves first, and ``RANDOM`` picks one of those and who moves first at random.
+    """
 
     EASY = 1
     MEDIUM = 2
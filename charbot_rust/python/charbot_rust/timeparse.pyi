@@ -0,0 +1,126 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class When:
+    """A time found in a message, and where it was.
+
+    Attributes
+    ----------
+    timestamp : int
+        The unix timestamp in seconds of the time that was found.
+    start : int
+        The index in the text of the first character of the words the time was found in.
+    end : int
+        The index in the text just after the last character of the words the time was found in, so
+        ``text[start:end]`` is the words.
+    """
+
+    timestamp: int
+    start: int
+    end: int
+
+class Timezone:
+    """A time zone someone meant, and its offset from UTC.
+
+    Attributes
+    ----------
+    name : str
+        The name of the zone in the tz database, like 'Europe/Amsterdam'.
+    offset : int
+        How many seconds ahead of UTC the clocks in the zone are, behind if it is negative.
+    """
+
+    name: str
+    offset: int
+
+def parse_when(text: str, locale: str = "en-US", tz: str = "UTC", now: int | None = None) -> When | None:
+    """Find when a reminder is for in a message, like 'in 2 hours', 'tomorrow at 9pm', 'dans 3 jours' or
+    'mañana a las 9'.
+
+    Durations from now, days, times of day, and days with times are understood, in the words of the
+    locale. Days without a time are at 9am, and tonight without a time is at 8pm. Times without a day
+    are the next time the clock shows them, in the evening if the morning has passed, and days of the
+    week are the next one after today.
+
+    Parameters
+    ----------
+    text : str
+        The message to find the time in.
+    locale : str
+        The locale the message is written in, falling back like translations do. The default is 'en-US'.
+    tz : str
+        The name of the time zone in the tz database the user is in, like 'Europe/Paris'. The default is
+        'UTC'.
+    now : int | None
+        The unix timestamp in seconds to take times from. The default is None, for now.
+
+    Returns
+    -------
+    When | None
+        The first time found, with where it is in the text, or None if there isn't one.
+
+    Raises
+    ------
+    ValueError
+        If the time zone isn't in the tz database, or now isn't from year 1 to 9999.
+    """
+
+def parse_duration(text: str, locale: str = "en-US", maximum: int | None = None) -> int:
+    """Read a compact duration like '90s', '1h30m', '2d 12h' or '1w' as a number of seconds, the other way
+    around from humanize_duration with compact=True.
+
+    The parts are whole numbers each followed by a unit, w, d, h, m or s, or the compact units of the
+    locale like 'j' in french. They go from the biggest unit to the smallest, each once, with or without
+    spaces between them.
+
+    Parameters
+    ----------
+    text : str
+        The duration.
+    locale : str
+        The locale the duration is written in, falling back like translations do. Locales added at
+        runtime in other languages than the built in ones only have the units above, with no grouping.
+        The default is 'en-US'.
+    maximum : int | None
+        The most seconds the duration can be. The default is None, for no limit.
+
+    Returns
+    -------
+    int
+        The number of seconds.
+
+    Raises
+    ------
+    ValueError
+        If the duration isn't valid, saying at which character, or it's more than maximum.
+    """
+
+def find_timezone(query: str, now: int | None = None) -> Timezone | None:
+    """Find the time zone someone means from what they typed, like 'amsterdam', 'EST', 'gmt+5:30' or
+    'pacific'.
+
+    Cities and countries with one time zone are matched forgiving typos and accents, common
+    abbreviations and names like 'CET' or 'eastern' are the zone most people mean by them, and fixed
+    offsets are the Etc zone with that offset, or a place with it when it isn't whole hours. Names in
+    the tz database, including old ones like 'Asia/Calcutta', are used as they are.
+
+    Parameters
+    ----------
+    query : str
+        What the user typed.
+    now : int | None
+        The unix timestamp in seconds to give the offset at. The default is None, for now.
+
+    Returns
+    -------
+    Timezone | None
+        The time zone, or None if nothing is close enough.
+
+    Raises
+    ------
+    ValueError
+        If now isn't from year 1 to 9999.
+    RuntimeError
+        If the embedded tz database is missing a zone or one of its tables.
+    """
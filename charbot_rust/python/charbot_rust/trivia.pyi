@@ -0,0 +1,49 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+class Match:
+    """How well an answer matched the answers accepted for a question.
+
+    Attributes
+    ----------
+    answer : str
+        The accepted answer closest to the one given.
+    score : float
+        How well the answer matched it, from 0 for nothing alike to 1 for the same, for partial credit.
+    is_correct : bool
+        Whether the score reaches the threshold.
+    """
+
+    answer: str
+    score: float
+    is_correct: bool
+
+def match_answer(given: str, accepted: list[str], threshold: float = 0.8) -> Match:
+    """Score an answer to a trivia question against the answers accepted for it.
+
+    Both are compared lowercase and without accents, punctuation or articles, like "the" or "la", so
+    "The Eiffel Tower" matches "eiffel tower". The words are then matched to each other in any order,
+    allowing for typos, with answers missing words or with extra ones scoring lower, and the answers are
+    also compared with the spaces left out.
+
+    Parameters
+    ----------
+    given : str
+        The answer that was given.
+    accepted : list[str]
+        The answers accepted for the question, the first one wins a tie.
+    threshold : float
+        The score from 0 to 1 an answer needs to count as correct. The default is 0.8.
+
+    Returns
+    -------
+    Match
+        The accepted answer closest to the one given, its score, and whether that reaches the threshold.
+
+    Raises
+    ------
+    ValueError
+        If there are no accepted answers or the threshold isn't from 0 to 1.
+    """
+    ...
@@ -0,0 +1,149 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from enum import Enum
+
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+class Direction(Enum):
+    """The directions the tiles can slide in."""
+
+    Up: ...
+    Down: ...
+    Left: ...
+    Right: ...
+
+class Board:
+    """A rust based implementation of the 2048 board.
+
+    Parameters
+    ----------
+    seed : int | None
+        The seed for where new tiles appear and what they are, so a game can be replayed. The default
+        is None, for a random game.
+    """
+
+    def __init__(self, seed: int | None = None): ...
+    def __new__(cls, seed: int | None = None) -> "Board": ...
+    @property
+    def grid(self) -> list[list[int]]:
+        """The tiles as 4 rows from the top, 0 for the empty cells."""
+        ...
+    @property
+    def score(self) -> int:
+        """The total of every tile made by merging two."""
+        ...
+    @property
+    def highest_tile(self) -> int:
+        """The highest tile on the board."""
+        ...
+    def slide(self, direction: Direction) -> bool:
+        """Slide every tile towards a direction, merging the equal ones that meet.
+
+        If any tile moved a new tile, a 2 or sometimes a 4, appears in an empty cell.
+
+        Parameters
+        ----------
+        direction : Direction
+            The direction to slide the tiles in.
+
+        Returns
+        -------
+        bool
+            True if any tile moved, False if nothing could move that way.
+        """
+        ...
+    def has_won(self) -> bool:
+        """Check if the board has a 2048 tile, play can go on afterwards.
+
+        Returns
+        -------
+        bool
+            True if there is a tile of 2048 or higher, False otherwise.
+        """
+        ...
+    def is_over(self) -> bool:
+        """Check if no slide can move any tile.
+
+        Returns
+        -------
+        bool
+            True if the board is full with no equal tiles next to each other, False otherwise.
+        """
+        ...
+    def to_emoji(self) -> str:
+        """The grid as emoji, for an embed or message.
+
+        Each row is a line from the top, with each tile as keycap digits and the empty cells as black
+        squares. Every tile is padded to as many emoji as the widest one, so the columns line up.
+
+        Returns
+        -------
+        str
+            The emoji grid, the same as ``str(board)``.
+        """
+        ...
+    def to_bytes(self) -> _Buffer:
+        """Save the board as msgpack, compact enough to store in the database.
+
+        Returns
+        -------
+        Buffer
+            The saved board, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_bytes(data: _BytesLike) -> "Board":
+        """Load a board saved by ``to_bytes``.
+
+        New tiles appear at random from then on, even if the board was seeded.
+
+        Parameters
+        ----------
+        data : bytes-like
+            The saved board.
+
+        Returns
+        -------
+        Board
+            The board, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved board, is from another version, or couldn't have come from a real board.
+        """
+        ...
+    def to_json(self) -> str:
+        """Save the board as JSON, for reading or editing by hand.
+
+        Returns
+        -------
+        str
+            The saved board, along with what game it is and the version of the save.
+        """
+        ...
+    @staticmethod
+    def from_json(data: str) -> "Board":
+        """Load a board saved by ``to_json``.
+
+        New tiles appear at random from then on, even if the board was seeded.
+
+        Parameters
+        ----------
+        data : str
+            The saved board.
+
+        Returns
+        -------
+        Board
+            The board, as it was when it was saved.
+
+        Raises
+        ------
+        ValueError
+            If the data isn't a saved board, is from another version, or couldn't have come from a real board.
+        """
+        ...
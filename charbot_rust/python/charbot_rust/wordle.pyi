@@ -0,0 +1,86 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+import datetime
+from enum import Enum
+
+class LetterState(Enum):
+    """How a letter of a guess compares to the answer.
+
+    Correct:
+        The letter is in that spot of the answer.
+    Present:
+        The letter is in the answer, but somewhere else.
+    Absent:
+        The letter isn't in the answer, or every copy of it was already accounted for by other guesses of it.
+    """
+
+    Correct: ...
+    Present: ...
+    Absent: ...
+
+def evaluate(guess: str, answer: str, /) -> list[LetterState]:
+    """Compare a guess to the answer, letter by letter.
+
+    Letters are compared lowercase and without accents, so 'arbol' is correct for 'árbol'. A letter
+    guessed more times than it is in the answer is only present as many times as it is there, with the
+    ones in the right spot counted first.
+
+    Parameters
+    ----------
+    guess : str
+        The word guessed.
+    answer : str
+        The word to guess.
+
+    Returns
+    -------
+    list[LetterState]
+        The state of each letter of the guess, in order.
+
+    Raises
+    ------
+    ValueError
+        If the guess and the answer don't have the same number of letters.
+    """
+
+def is_word(word: str, locale: str = "en-US", /) -> bool:
+    """Check if a word can be guessed in a locale, so guesses can be limited to real words.
+
+    Any word in the dictionary of the locale can be guessed, not just the common ones the daily word is
+    picked from.
+
+    Parameters
+    ----------
+    word : str
+        The word to check, case and accents are ignored.
+    locale : str
+        The locale of the dictionary, those without one use the closest that has one, which is en-US if
+        nothing else. The default is 'en-US'.
+
+    Returns
+    -------
+    bool
+        True if the word is in the dictionary, False otherwise.
+    """
+
+def daily_word(date: datetime.date, locale: str = "en-US", seed: int = 0, /) -> str:
+    """The word for a day, the same for everyone who asks for that day, locale and seed.
+
+    It is one of the common words of the locale, never one of the rarer ones that can only be guessed.
+
+    Parameters
+    ----------
+    date : datetime.date
+        The day to pick the word for.
+    locale : str
+        The locale of the dictionary to pick from, those without one use the closest that has one, which
+        is en-US if nothing else. The default is 'en-US'.
+    seed : int
+        Mixed into the pick, so a different seed gives a different run of words. The default is 0.
+
+    Returns
+    -------
+    str
+        The word, with any accents it has.
+    """
@@ -0,0 +1,60 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+def scramble(word: str, seed: int | None = None) -> str:
+    """Scramble the letters of a word, for unscrambling games.
+
+    Parameters
+    ----------
+    word : str
+        The word to scramble.
+    seed : int | None
+        The seed for the order of the letters, so a scramble can be reproduced. The default is None, for a random order.
+
+    Returns
+    -------
+    str
+        The letters of the word in a different order, unless every letter is the same.
+    """
+
+def is_anagram(first: str, second: str) -> bool:
+    """Check if two words or phrases are spelled with the same letters.
+
+    Case, accents, spaces and punctuation are ignored, so "Dormitory" is an anagram of "dirty room!". Letters
+    like "ñ" that are letters of their own in a language aren't the same as the letter without the accent.
+
+    Parameters
+    ----------
+    first : str
+        The first word or phrase.
+    second : str
+        The second word or phrase.
+
+    Returns
+    -------
+    bool
+        True if both have the same letters, as many times each, False otherwise.
+    """
+
+def find_words(letters: str, locale: str = "en-US", min_len: int = 3) -> list[str]:
+    """Find the words that can be spelled from some letters, for a countdown style letters game.
+
+    The words come from a word list for each language the bot is translated to, built into the library,
+    so searching them doesn't have to load a dictionary every round.
+
+    Parameters
+    ----------
+    letters : str
+        The letters, each can be used once for each time it is given. Case and accents are ignored, and
+        anything that isn't a letter.
+    locale : str
+        The locale to use the word list of, the closest one is used if it doesn't have one of its own. The default is "en-US".
+    min_len : int
+        The fewest letters a word can have. The default is 3.
+
+    Returns
+    -------
+    list[str]
+        The words, the longest first, and words as long as each other in alphabetical order.
+    """
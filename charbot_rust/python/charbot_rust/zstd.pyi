@@ -0,0 +1,82 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+from .charbot_rust import Buffer as _Buffer
+
+_BytesLike = bytes | bytearray | memoryview | _Buffer
+
+def compress(data: str | _BytesLike, /, level: int = 3, dictionary: _BytesLike | None = None) -> _Buffer:
+    """Compress data with zstd, like a serialized game state or an archive of messages.
+
+    Parameters
+    ----------
+    data : str | bytes-like
+        The data. Text is compressed as UTF-8.
+    level : int
+        How hard to try to make it smaller, from 1 to 22, with higher levels being slower. The default is
+        3.
+    dictionary : bytes-like, optional
+        A dictionary, like one from ``train_dict``, which decompressing it needs too.
+
+    Returns
+    -------
+    Buffer
+        A zstd frame, with the size of the data and a checksum, which any zstd can decompress.
+
+    Raises
+    ------
+    ValueError
+        If the level isn't from 1 to 22, or the dictionary is invalid.
+    """
+    ...
+
+def decompress(data: _BytesLike, max_size: int, /, dictionary: _BytesLike | None = None) -> _Buffer:
+    """Decompress zstd frames, stopping once they'd be more than ``max_size`` bytes, so a small row in the
+    database can't decompress to more memory than there is.
+
+    Parameters
+    ----------
+    data : bytes-like
+        One or more zstd frames, from ``compress`` or any other zstd.
+    max_size : int
+        The most bytes it can decompress to.
+    dictionary : bytes-like, optional
+        The dictionary it was compressed with, if any.
+
+    Returns
+    -------
+    Buffer
+        The data.
+
+    Raises
+    ------
+    ValueError
+        If it would be more than ``max_size`` bytes, it isn't zstd, it's cut off or corrupted, or it needs
+        a different dictionary.
+    """
+    ...
+
+def train_dict(samples: list[str | _BytesLike], /, size: int = 16384) -> _Buffer:
+    """Train a dictionary for compressing small things that are alike, like the states of the same game,
+    which on their own have too little in them to compress well.
+
+    Parameters
+    ----------
+    samples : list[str | bytes-like]
+        Examples of what's going to be compressed, with more bytes all together than the dictionary.
+    size : int
+        The most bytes the dictionary can be, at least 256. The default is 16384.
+
+    Returns
+    -------
+    Buffer
+        A dictionary trained the way the zstd command line trains them, which it and the zstd libraries
+        can use too.
+
+    Raises
+    ------
+    ValueError
+        If there are fewer than 2 samples, too few bytes of them, the size is smaller than 256, or the
+        samples have too little in common to train a dictionary on.
+    """
+    ...
@@ -0,0 +1,191 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! The files the library ships with, like its translations and the tz database, by their path from `src`.
+//!
+//! They're embedded in the library by default, so it works wherever it's installed. With the
+//! `runtime-assets` feature they're read each time they're loaded instead, from the `src` directory of
+//! the crate or the directory `CHARBOT_RUST_ASSETS` is set to, so they can be edited and picked up with
+//! `reload_translations` without building again.
+use std::borrow::Cow;
+#[cfg(feature = "runtime-assets")]
+use std::collections::HashMap;
+#[cfg(feature = "runtime-assets")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(not(feature = "runtime-assets"))]
+use include_dir::{include_dir, Dir};
+#[cfg(feature = "runtime-assets")]
+use once_cell::sync::Lazy;
+
+/// Every directory of assets, by its path. Only directories holding nothing but assets are embedded, so
+/// the code next to them isn't.
+#[cfg(not(feature = "runtime-assets"))]
+static DIRECTORIES: [(&str, Dir); 8] = [
+    // the translations, with the terms shared by every locale outside the locale folders so crowdin doesn't pick them up
+    ("fluent/i18n", include_dir!("$CARGO_MANIFEST_DIR/src/fluent/i18n")),
+    ("cooldowns/zoneinfo", include_dir!("$CARGO_MANIFEST_DIR/src/cooldowns/zoneinfo")),
+    ("emoji", include_dir!("$CARGO_MANIFEST_DIR/src/emoji")),
+    ("moderation/confusables", include_dir!("$CARGO_MANIFEST_DIR/src/moderation/confusables")),
+    ("namegen", include_dir!("$CARGO_MANIFEST_DIR/src/namegen")),
+    ("shrugman/words", include_dir!("$CARGO_MANIFEST_DIR/src/shrugman/words")),
+    ("wordle", include_dir!("$CARGO_MANIFEST_DIR/src/wordle")),
+    ("words", include_dir!("$CARGO_MANIFEST_DIR/src/words")),
+];
+
+/// The embedded directory `path` is in, its path, and the rest of `path` from there.
+#[cfg(not(feature = "runtime-assets"))]
+fn locate(path: &str) -> Option<(&'static str, &'static Dir<'static>, &str)> {
+    DIRECTORIES.iter().find_map(|(prefix, dir)| {
+        let rest = path.strip_prefix(prefix)?;
+        let rest = if rest.is_empty() { rest } else { rest.strip_prefix('/')? };
+        Some((*prefix, dir, rest))
+    })
+}
+
+/// The bytes of the embedded asset at `path`.
+#[cfg(not(feature = "runtime-assets"))]
+fn embedded_bytes(path: &str) -> Result<&'static [u8], String> {
+    let (_, dir, rest) = locate(path).ok_or_else(|| format!("{path} is not an asset"))?;
+    dir.get_file(rest).map(|file| file.contents()).ok_or_else(|| format!("{path} is not an asset"))
+}
+
+/// The text of the embedded asset at `path`.
+#[cfg(not(feature = "runtime-assets"))]
+fn embedded(path: &str) -> Result<&'static str, String> {
+    std::str::from_utf8(embedded_bytes(path)?).map_err(|_| format!("{path} is not UTF-8"))
+}
+
+/// The directory the assets are read from with the `runtime-assets` feature.
+#[cfg(feature = "runtime-assets")]
+fn root() -> std::path::PathBuf {
+    std::env::var_os("CHARBOT_RUST_ASSETS")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|| std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"))
+}
+
+/// The text of the asset at `path`.
+#[cfg(not(feature = "runtime-assets"))]
+pub(crate) fn text(path: &str) -> Result<Cow<'static, str>, String> {
+    embedded(path).map(Cow::Borrowed)
+}
+
+/// The text of the asset at `path`.
+#[cfg(feature = "runtime-assets")]
+pub(crate) fn text(path: &str) -> Result<Cow<'static, str>, String> {
+    let file = root().join(path);
+    std::fs::read_to_string(&file).map(Cow::Owned).map_err(|e| format!("Failed to read {}: {e}", file.display()))
+}
+
+/// The bytes of the asset at `path`, for the ones that aren't text, like the zones of the tz database.
+#[cfg(not(feature = "runtime-assets"))]
+pub(crate) fn bytes(path: &str) -> Result<Cow<'static, [u8]>, String> {
+    embedded_bytes(path).map(Cow::Borrowed)
+}
+
+/// The bytes of the asset at `path`, for the ones that aren't text, like the zones of the tz database.
+#[cfg(feature = "runtime-assets")]
+pub(crate) fn bytes(path: &str) -> Result<Cow<'static, [u8]>, String> {
+    let file = root().join(path);
+    std::fs::read(&file).map(Cow::Owned).map_err(|e| format!("Failed to read {}: {e}", file.display()))
+}
+
+/// The text of the asset at `path`, for as long as the library is loaded, for what's parsed once and kept,
+/// like word lists.
+///
+/// With the `runtime-assets` feature it's only read the first time, so changes to it need a restart.
+#[cfg(not(feature = "runtime-assets"))]
+pub(crate) fn static_text(path: &str) -> Result<&'static str, String> {
+    embedded(path)
+}
+
+/// The assets [`static_text`] has read, each is leaked once however often it's asked for.
+#[cfg(feature = "runtime-assets")]
+static READ: Lazy<Mutex<HashMap<String, &'static str>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// The text of the asset at `path`, for as long as the library is loaded, for what's parsed once and kept,
+/// like word lists.
+///
+/// With the `runtime-assets` feature it's only read the first time, so changes to it need a restart.
+#[cfg(feature = "runtime-assets")]
+pub(crate) fn static_text(path: &str) -> Result<&'static str, String> {
+    let mut read = READ.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(text) = read.get(path) {
+        return Ok(text);
+    }
+    let text: &'static str = Box::leak(text(path)?.into_owned().into_boxed_str());
+    read.insert(path.to_string(), text);
+    Ok(text)
+}
+
+/// The paths of the assets directly in `directory` ending in `extension`, sorted so they're always loaded
+/// in the same order.
+#[cfg(not(feature = "runtime-assets"))]
+pub(crate) fn files(directory: &str, extension: &str) -> Result<Vec<String>, String> {
+    let missing = || format!("{directory} is not a directory of assets");
+    let (prefix, dir, rest) = locate(directory).ok_or_else(missing)?;
+    let dir = if rest.is_empty() { dir } else { dir.get_dir(rest).ok_or_else(missing)? };
+    let mut files = dir
+        .files()
+        .filter(|file| file.path().extension().is_some_and(|ext| ext == extension))
+        .filter_map(|file| Some(format!("{prefix}/{}", file.path().to_str()?)))
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// The paths of the assets directly in `directory` ending in `extension`, sorted so they're always loaded
+/// in the same order.
+#[cfg(feature = "runtime-assets")]
+pub(crate) fn files(directory: &str, extension: &str) -> Result<Vec<String>, String> {
+    let dir = root().join(directory);
+    let mut files = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.is_file() && file.extension().is_some_and(|ext| ext == extension))
+        .filter_map(|file| Some(format!("{directory}/{}", file.file_name()?.to_str()?)))
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_locale_has_every_file() {
+        let names = |locale: &str| {
+            let directory = format!("fluent/i18n/{locale}");
+            let files = files(&directory, "ftl").expect("The translations are assets");
+            files.iter().map(|file| file[directory.len() + 1..].to_string()).collect::<Vec<_>>()
+        };
+        let english = names("en-US");
+        assert_eq!(english.len(), 8);
+        assert_eq!(english[0], "dice.ftl");
+        for locale in ["es-ES", "fr", "nl"] {
+            assert_eq!(names(locale), english);
+        }
+        assert_eq!(files("fluent/i18n", "ftl"), Ok(vec![String::from("fluent/i18n/terms.ftl")]));
+        assert_eq!(files("fluent/i18n/en-US", "txt"), Ok(Vec::new()));
+        for directory in ["namegen", "shrugman/words", "wordle/answers", "wordle/guesses", "words"] {
+            let lists = files(directory, "txt").expect("The word lists are assets");
+            assert_eq!(lists, ["en-US", "es-ES", "fr", "nl"].map(|locale| format!("{directory}/{locale}.txt")));
+        }
+        assert!(files("fluent/i18n/de", "ftl").is_err());
+    }
+
+    #[test]
+    fn texts() {
+        assert!(text("fluent/i18n/terms.ftl").expect("The terms are an asset").contains("-bot-name"));
+        assert!(text("fluent/i18n/en-US/dice.ftl").is_ok_and(|dice| !dice.is_empty()));
+        assert!(text("fluent/i18n/de/dice.ftl").is_err());
+        let emoji = static_text("emoji/emoji.txt").expect("The emoji are an asset");
+        assert!(std::ptr::eq(emoji, static_text("emoji/emoji.txt").expect("The emoji are an asset")));
+        assert!(static_text("moderation/confusables/confusables.txt").is_ok_and(|confusables| !confusables.is_empty()));
+        assert!(bytes("cooldowns/zoneinfo/Europe/Amsterdam").is_ok_and(|zone| zone.starts_with(b"TZif")));
+        assert!(bytes("cooldowns/zoneinfo/Europe").is_err());
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,403 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod ai; // COV_EXCL_LINE
+mod board;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::battleship::board::{parse_square, square_name, Board, Placement, Report, Ship, Shot, FLEET};
+use crate::persist::{self, Persist};
+use crate::metrics;
+
+// COV_EXCL_START
+/// One player's ships and the shots at them, which is all a player's side of a game is.
+///
+/// In a game between two players each keeps their own fleet, saved on its own so neither can see the other's.
+#[pyclass(module = "battleship")]
+#[derive(Clone, Debug)]
+struct Fleet {
+    board: Board,
+}
+
+/// A game against the computer, which places its fleet at random and hunts for the player's.
+#[pyclass(module = "battleship")]
+#[derive(Debug)]
+struct Game {
+    player: Board,
+    computer: Board,
+    /// Where the computer's ships are comes from the seed, and so does every shot it takes.
+    seed: u64,
+}
+// COV_EXCL_STOP
+
+impl Fleet {
+    fn place(&mut self, ship: Ship, square: &str, horizontal: bool) -> Result<(), String> {
+        let start = parse_square(square)?;
+        self.board.place(Placement { ship, start, horizontal })
+    }
+}
+
+impl Persist for Fleet {
+    const GAME: &'static str = "battleship_fleet";
+    const VERSION: u32 = 1;
+    type State = Board;
+
+    fn save(&self) -> Board {
+        self.board.clone()
+    }
+
+    fn load(board: Board) -> Result<Self, String> {
+        board.check()?;
+        Ok(Fleet { board })
+    }
+}
+
+impl Game {
+    fn new(fleet: Board, seed: u64) -> Result<Self, String> {
+        if !fleet.is_ready() {
+            return Err("Not all the ships are placed yet".to_string());
+        }
+        if !fleet.shots.is_empty() {
+            return Err("The fleet has already been shot at".to_string());
+        }
+        let computer = Board::random(fleet.allow_touching, &mut StdRng::seed_from_u64(seed));
+        Ok(Game { player: fleet, computer, seed })
+    }
+
+    fn is_over(&self) -> bool {
+        self.player.all_sunk() || self.computer.all_sunk()
+    }
+
+    /// Shoot at the computer's ships, then have the computer shoot back if any are left.
+    fn fire(&mut self, square: &str) -> Result<(Report, Option<Report>), String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        let report = self.computer.fire(parse_square(square)?)?;
+        if self.computer.all_sunk() {
+            return Ok((report, None));
+        }
+        // each shot gets its own seed, so a loaded game carries on the same way
+        let mut rng = StdRng::seed_from_u64(self.seed.wrapping_add(self.player.shots.len() as u64 + 1));
+        let target = ai::choose(&self.player, &mut rng).ok_or_else(|| "The computer has nothing to shoot at".to_string())?;
+        Ok((report, Some(self.player.fire(target)?)))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    player: Board,
+    computer: Board,
+    seed: u64,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "battleship";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { player: self.player.clone(), computer: self.computer.clone(), seed: self.seed }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        state.player.check()?;
+        state.computer.check()?;
+        if !state.player.is_ready() {
+            return Err("Not all the player's ships are placed".to_string());
+        }
+        // the computer's ships are never sent anywhere, so they have to be where the seed put them
+        let placed = Board::random(state.player.allow_touching, &mut StdRng::seed_from_u64(state.seed));
+        if state.computer.allow_touching != placed.allow_touching || state.computer.ships != placed.ships {
+            return Err("The computer's ships aren't where they were placed".to_string());
+        }
+        // the computer shoots straight after the player, unless the player just won
+        let shots = state.player.shots.len() + usize::from(state.computer.all_sunk());
+        if state.computer.shots.len() != shots {
+            return Err(format!("The player shot {} times and the computer {} times, which can't happen", state.computer.shots.len(), state.player.shots.len()));
+        }
+        Ok(Game { player: state.player, computer: state.computer, seed: state.seed })
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Fleet {
+    #[new]
+    #[pyo3(signature = (allow_touching=false))]
+    fn __new__(allow_touching: bool) -> Self { // COV_EXCL_LINE
+        Fleet { board: Board::new(allow_touching) }
+    }
+
+    /// A fleet with every ship placed at random.
+    #[staticmethod]
+    #[pyo3(signature = (allow_touching=false, seed=None))]
+    fn random(allow_touching: bool, seed: Option<u64>) -> Self { // COV_EXCL_LINE
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        Fleet { board: Board::random(allow_touching, &mut rng) }
+    }
+
+    /// Whether ships can be placed next to each other, they can never overlap.
+    #[getter]
+    fn allow_touching(&self) -> bool {
+        self.board.allow_touching
+    }
+
+    #[pyo3(name = "place")]
+    fn py_place(&mut self, ship: Ship, square: &str, horizontal: bool) -> PyResult<()> {
+        self.place(ship, square, horizontal).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn remove(&mut self, ship: Ship) -> PyResult<bool> {
+        self.board.remove(ship).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The ships placed so far, with the squares each is on.
+    fn ships(&self) -> Vec<(Ship, Vec<String>)> {
+        self.board.ships.iter().map(|placed| (placed.ship, placed.squares().map(square_name).collect())).collect()
+    }
+
+    fn unplaced(&self) -> Vec<Ship> {
+        FLEET.into_iter().filter(|ship| self.board.ships.iter().all(|placed| placed.ship != *ship)).collect()
+    }
+
+    fn is_ready(&self) -> bool {
+        self.board.is_ready()
+    }
+
+    fn fire(&mut self, square: &str) -> PyResult<Report> {
+        let square = parse_square(square).map_err(PyErr::new::<PyValueError, _>)?;
+        self.board.fire(square).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn all_sunk(&self) -> bool {
+        self.board.all_sunk()
+    }
+
+    #[pyo3(signature = (reveal=true))]
+    fn to_emoji(&self, reveal: bool) -> String { // COV_EXCL_LINE
+        self.board.to_emoji(reveal)
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_emoji(true)
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Game {
+    #[new]
+    #[pyo3(signature = (fleet, seed=None))]
+    fn __new__(fleet: Fleet, seed: Option<u64>) -> PyResult<Self> { // COV_EXCL_LINE
+        let seed = seed.unwrap_or_else(|| StdRng::from_entropy().gen());
+        Self::new(fleet.board, seed).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The seed the computer's ships were placed with, so a game can be replayed to check it.
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The player's ships, and the computer's shots at them.
+    #[getter]
+    fn fleet(&self) -> Fleet {
+        Fleet { board: self.player.clone() }
+    }
+
+    fn play(&mut self, square: &str) -> PyResult<(Report, Option<Report>)> {
+        metrics::observe("battleship.play", || self.fire(square).map_err(PyErr::new::<PyValueError, _>))
+    }
+
+    fn has_player_won(&self) -> bool {
+        self.computer.all_sunk()
+    }
+
+    fn has_player_lost(&self) -> bool {
+        self.player.all_sunk()
+    }
+
+    #[pyo3(name = "is_over")]
+    fn py_is_over(&self) -> bool {
+        self.is_over()
+    }
+
+    /// The computer's board, showing only the player's shots.
+    fn target_emoji(&self) -> String {
+        self.computer.to_emoji(false)
+    }
+
+    /// The player's board, with their ships and the computer's shots.
+    fn fleet_emoji(&self) -> String {
+        self.player.to_emoji(true)
+    }
+
+    fn __str__(&self) -> String {
+        format!("{}\n\n{}", self.target_emoji(), self.fleet_emoji())
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of battleship";
+
+pub(crate) fn register_battleship(py: Python, m: &PyModule) -> PyResult<()> {
+    let battleship = PyModule::new(py, "_battleship")?;
+    battleship.add_class::<Game>()?;
+    battleship.add_class::<Fleet>()?;
+    battleship.add_class::<Ship>()?;
+    battleship.add_class::<Shot>()?;
+    battleship.add_class::<Report>()?;
+    battleship.add("__doc__", DOCSTRING)?;
+    m.add_submodule(battleship)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The fleet along the left side, a row apart, with the carrier at the top.
+    fn fleet() -> Fleet {
+        let mut fleet = Fleet::__new__(false);
+        for (row, ship) in ["A1", "C1", "E1", "G1", "I1"].into_iter().zip(FLEET) {
+            fleet.place(ship, row, true).expect("Failed to place");
+        }
+        fleet
+    }
+
+    #[test]
+    fn placing() {
+        let mut fleet = Fleet::__new__(false);
+        assert_eq!(fleet.unplaced(), FLEET.to_vec());
+        fleet.place(Ship::Destroyer, "B7", false).expect("Failed to place");
+        assert_eq!(fleet.ships(), vec![(Ship::Destroyer, vec!["B7".to_string(), "C7".to_string()])]);
+        fleet.place(Ship::Cruiser, "K1", true).expect_err("Expected the square to be rejected");
+        fleet.place(Ship::Cruiser, "D6", true).expect_err("Expected the touching ship to be rejected");
+        assert_eq!(fleet.unplaced().len(), 4);
+        assert!(!fleet.is_ready());
+        assert!(Fleet::random(true, Some(2)).is_ready());
+    }
+
+    #[test]
+    fn creator() {
+        Game::new(Board::new(false), 1).expect_err("Expected the unplaced fleet to be rejected");
+        let mut shot = fleet();
+        shot.board.fire((9, 9)).expect("Failed to fire");
+        Game::new(shot.board, 1).expect_err("Expected the fleet that was shot at to be rejected");
+        let game = Game::new(fleet().board, 1).expect("Failed to create game");
+        assert!(game.computer.is_ready());
+        assert!(!game.computer.allow_touching);
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn play() {
+        let mut game = Game::new(fleet().board, 1).expect("Failed to create game");
+        let (report, reply) = game.fire("E5").expect("Failed to fire");
+        assert_eq!(report.square, "E5");
+        let reply = reply.expect("Expected the computer to shoot back");
+        assert_eq!(game.player.shots, vec![parse_square(&reply.square).expect("Valid")]);
+        game.fire("E5").expect_err("Expected the repeated shot to be rejected");
+        game.fire("nowhere").expect_err("Expected the square to be rejected");
+        assert_eq!(game.computer.shots.len(), 1);
+    }
+
+    #[test]
+    fn winning() {
+        let mut game = Game::new(fleet().board, 1).expect("Failed to create game");
+        let squares = game.computer.ships.iter().flat_map(|placed| placed.squares().collect::<Vec<_>>()).collect::<Vec<_>>();
+        let (last, rest) = squares.split_last().expect("Expected ship squares");
+        for square in rest {
+            let (report, reply) = game.fire(&square_name(*square)).expect("Failed to fire");
+            assert_ne!(report.shot, Shot::Miss);
+            assert!(reply.is_some());
+        }
+        let (report, reply) = game.fire(&square_name(*last)).expect("Failed to fire");
+        assert_eq!((report.shot, reply), (Shot::Sunk, None));
+        assert!(game.is_over());
+        assert!(game.has_player_won());
+        assert!(!game.has_player_lost());
+        game.fire("J10").expect_err("Expected the game to be over");
+    }
+
+    #[test]
+    fn losing() {
+        let mut game = Game::new(fleet().board, 1).expect("Failed to create game");
+        let mut open = (0..10).flat_map(|row| (0..10).map(move |column| (row, column))).filter(|square| game.computer.ship_at(*square).is_none()).collect::<Vec<_>>();
+        while !game.is_over() {
+            game.fire(&square_name(open.pop().expect("Expected a square to miss"))).expect("Failed to fire");
+        }
+        assert!(game.has_player_lost());
+        assert!(!game.has_player_won());
+    }
+
+    #[test]
+    fn persists() {
+        let fleet = fleet();
+        let json = persist::to_json(&fleet).expect("Failed to save");
+        let loaded: Fleet = persist::from_json(&json).expect("Failed to load");
+        assert_eq!(loaded.board, fleet.board);
+        let mut game = Game::new(fleet.board, 7).expect("Failed to create game");
+        game.fire("A1").expect("Failed to fire");
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        let mut loaded: Game = persist::from_bytes(&bytes).expect("Failed to load");
+        assert_eq!((&loaded.player, &loaded.computer, loaded.seed), (&game.player, &game.computer, 7));
+        assert_eq!(loaded.fire("B2"), game.fire("B2"));
+        let mut state = game.save();
+        state.seed = 8;
+        Game::load(state).expect_err("Expected the moved computer ships to be rejected");
+        let mut state = game.save();
+        state.player.shots.pop();
+        Game::load(state).expect_err("Expected the missing computer shot to be rejected");
+        let mut state = game.save();
+        state.player.ships.pop();
+        state.player.shots.clear();
+        state.computer.shots.clear();
+        Game::load(state).expect_err("Expected the missing ship to be rejected");
+        let mut cheating = game.save().player;
+        cheating.shots.push(cheating.shots[0]);
+        Fleet::load(cheating).expect_err("Expected the repeated shot to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,137 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use rand::seq::SliceRandom;
+use rand::Rng;
+use crate::battleship::board::{Board, Square, SIZE};
+
+/// The steps to the squares above, below, left and right of a square.
+const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+
+fn step((row, column): Square, (row_step, column_step): (isize, isize)) -> Option<Square> {
+    let row = row.checked_add_signed(row_step)?;
+    let column = column.checked_add_signed(column_step)?;
+    (row < SIZE && column < SIZE).then_some((row, column))
+}
+
+/// Where the computer shoots next at `board`, hunting for ships until it hits one, then targeting it until it sinks.
+///
+/// Only what the shooter would know is used: which shots hit, and the ships that were sunk, which are
+/// announced. While hunting, only every few squares in a checkerboard pattern are shot at, spaced by
+/// the shortest ship still afloat, since every ship has to cover one of them.
+pub fn choose(board: &Board, rng: &mut impl Rng) -> Option<Square> {
+    let open = |square: &Square| !board.is_shot(*square);
+    // hits on ships that aren't sunk yet, so there's more of the ship around them
+    let wounded = board
+        .shots
+        .iter()
+        .copied()
+        .filter(|square| board.ship_at(*square).is_some_and(|placed| !board.is_sunk(placed)))
+        .collect::<Vec<_>>();
+    let mut targets = Vec::new();
+    for &hit in &wounded {
+        for direction in DIRECTIONS {
+            // two hits next to each other show which way the ship lies, so carry on along the line
+            if step(hit, direction).is_some_and(|next| wounded.contains(&next)) {
+                let mut end = hit;
+                while let Some(next) = step(end, direction).filter(|next| wounded.contains(next)) {
+                    end = next;
+                }
+                targets.extend(step(end, direction).filter(open));
+                let reverse = (-direction.0, -direction.1);
+                targets.extend(step(hit, reverse).filter(open));
+            }
+        }
+    }
+    if targets.is_empty() {
+        targets = wounded.iter().flat_map(|&hit| DIRECTIONS.iter().filter_map(move |&direction| step(hit, direction))).filter(open).collect();
+    }
+    if let Some(target) = targets.choose(rng) {
+        return Some(*target);
+    }
+    let spacing = board.ships.iter().filter(|placed| !board.is_sunk(placed)).map(|placed| placed.ship.length()).min()?;
+    let squares = (0..SIZE).flat_map(|row| (0..SIZE).map(move |column| (row, column))).filter(open);
+    let hunting = squares.clone().filter(|(row, column)| (row + column) % spacing == 0).collect::<Vec<_>>();
+    if let Some(square) = hunting.choose(rng) {
+        return Some(*square);
+    }
+    squares.collect::<Vec<_>>().choose(rng).copied()
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::battleship::board::{Placement, FLEET};
+
+    fn fleet() -> Board {
+        let mut board = Board::new(false);
+        for (row, ship) in FLEET.into_iter().enumerate() {
+            board.place(Placement { ship, start: (row * 2, 0), horizontal: true }).expect("Failed to place");
+        }
+        board
+    }
+
+    #[test]
+    fn hunts_on_a_checkerboard() {
+        let board = fleet();
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let (row, column) = choose(&board, &mut rng).expect("Expected a square");
+            // the destroyer is two long, so every other square is enough
+            assert_eq!((row + column) % 2, 0);
+        }
+    }
+
+    #[test]
+    fn targets_around_a_hit() {
+        let mut board = fleet();
+        board.fire((4, 1)).expect("Failed to fire");
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let square = choose(&board, &mut rng).expect("Expected a square");
+            assert!([(3, 1), (5, 1), (4, 0), (4, 2)].contains(&square), "Expected a square next to the hit, not {square:?}");
+        }
+    }
+
+    #[test]
+    fn follows_the_line() {
+        let mut board = fleet();
+        for square in [(0, 1), (0, 2), (0, 0)] {
+            board.fire(square).expect("Failed to fire");
+        }
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            // there's nothing left of A1, so only the right end of the carrier is left
+            assert_eq!(choose(&board, &mut rng), Some((0, 3)));
+        }
+    }
+
+    #[test]
+    fn sinks_everything() {
+        let mut board = Board::random(false, &mut StdRng::seed_from_u64(5));
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut shots = 0;
+        while !board.all_sunk() {
+            let square = choose(&board, &mut rng).expect("Expected a square");
+            board.fire(square).expect("Expected a legal shot");
+            shots += 1;
+        }
+        // there are 17 ship squares, hunting and targeting should find them well before shooting everywhere
+        assert!(shots < SIZE * SIZE, "Took {shots} shots");
+    }
+
+    #[test]
+    fn nothing_left() {
+        let mut board = fleet();
+        for placed in board.ships.clone() {
+            for square in placed.squares() {
+                board.fire(square).expect("Failed to fire");
+            }
+        }
+        assert_eq!(choose(&board, &mut StdRng::seed_from_u64(3)), None);
+    }
+}
+// COV_EXCL_STOP
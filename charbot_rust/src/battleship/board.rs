@@ -0,0 +1,411 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use pyo3::{pyclass, pymethods};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+// COV_EXCL_STOP
+
+/// The rows of the board, and the columns.
+pub const SIZE: usize = 10;
+/// How many times a ship is tried somewhere at random before the fleet is started again.
+const ATTEMPTS: usize = 100;
+
+#[pyclass(module = "battleship")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Ship { // COV_EXCL_LINE
+    Carrier,
+    Battleship,
+    Cruiser,
+    Submarine,
+    Destroyer,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Ship {
+    /// How many squares the ship takes up.
+    #[getter] // COV_EXCL_LINE
+    pub fn length(&self) -> usize {
+        match self { // COV_EXCL_LINE
+            Ship::Carrier => 5,
+            Ship::Battleship => 4,
+            Ship::Cruiser | Ship::Submarine => 3,
+            Ship::Destroyer => 2,
+        }
+    }
+}
+
+/// The ships each player has, one of each.
+pub const FLEET: [Ship; 5] = [Ship::Carrier, Ship::Battleship, Ship::Cruiser, Ship::Submarine, Ship::Destroyer];
+
+#[pyclass(module = "battleship")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Shot { // COV_EXCL_LINE
+    Miss,
+    Hit,
+    /// The shot hit the last square of a ship that wasn't hit yet.
+    Sunk,
+}
+
+/// What happened when a square was shot at.
+#[pyclass(module = "battleship")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Debug)] // COV_EXCL_LINE
+pub struct Report {
+    #[pyo3(get)]
+    pub square: String,
+    #[pyo3(get)]
+    pub shot: Shot,
+    /// The ship that was hit, `None` for a miss.
+    #[pyo3(get)]
+    pub ship: Option<Ship>,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Report {
+    fn __repr__(&self) -> String {
+        format!("Report(square={:?}, shot={:?}, ship={:?})", self.square, self.shot, self.ship)
+    }
+}
+
+/// A square as (row, column), with row 0 at the top and column 0 on the left.
+pub type Square = (usize, usize);
+
+/// The name of `square` in the usual notation, a letter for the row and a number for the column, like `B7`.
+pub fn square_name((row, column): Square) -> String {
+    format!("{}{}", char::from(b'A' + row as u8), column + 1)
+}
+
+pub fn parse_square(name: &str) -> Result<Square, String> {
+    let invalid = || format!("Invalid square {name:?}, expected a row from A to J and a column from 1 to 10 like B7");
+    let name = name.trim();
+    let mut chars = name.chars();
+    let Some(row @ 'A'..='J') = chars.next().map(|letter| letter.to_ascii_uppercase()) else {
+        return Err(invalid());
+    };
+    match chars.as_str().parse::<usize>() {
+        Ok(column @ 1..=SIZE) if !chars.as_str().starts_with('+') => Ok((row as usize - 'A' as usize, column - 1)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Where a ship is, from its top or left end.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub struct Placement {
+    pub ship: Ship,
+    pub start: Square,
+    pub horizontal: bool,
+}
+
+impl Placement {
+    pub fn squares(&self) -> impl Iterator<Item = Square> + '_ {
+        let (row, column) = self.start;
+        (0..self.ship.length()).map(move |offset| if self.horizontal { (row, column + offset) } else { (row + offset, column) })
+    }
+
+    fn contains(&self, square: Square) -> bool {
+        self.squares().any(|part| part == square)
+    }
+
+    /// Whether any square of the ship is next to `square`, diagonally too, or on it.
+    fn touches(&self, (row, column): Square) -> bool {
+        self.squares().any(|(part_row, part_column)| part_row.abs_diff(row) <= 1 && part_column.abs_diff(column) <= 1)
+    }
+}
+
+/// One player's ships and the shots the other player has made at them.
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub struct Board {
+    /// Whether ships can be next to each other, they can never overlap.
+    pub(crate) allow_touching: bool,
+    pub(crate) ships: Vec<Placement>,
+    /// The squares shot at, in order.
+    pub(crate) shots: Vec<Square>,
+}
+
+impl Board {
+    pub fn new(allow_touching: bool) -> Self {
+        Board { allow_touching, ships: Vec::new(), shots: Vec::new() }
+    }
+
+    /// A board with the whole fleet placed at random.
+    pub fn random(allow_touching: bool, rng: &mut impl Rng) -> Self {
+        loop {
+            let mut board = Board::new(allow_touching);
+            for ship in FLEET {
+                for _ in 0..ATTEMPTS {
+                    let horizontal = rng.gen();
+                    let (rows, columns) = if horizontal { (SIZE, SIZE + 1 - ship.length()) } else { (SIZE + 1 - ship.length(), SIZE) };
+                    let start = (rng.gen_range(0..rows), rng.gen_range(0..columns));
+                    if board.place(Placement { ship, start, horizontal }).is_ok() {
+                        break;
+                    }
+                }
+            }
+            // the ships placed first can leave no room for the rest, so start over
+            if board.is_ready() {
+                return board;
+            }
+        }
+    }
+
+    pub fn place(&mut self, placement: Placement) -> Result<(), String> {
+        if !self.shots.is_empty() {
+            return Err("Ships can't be moved once the game has started".to_string());
+        }
+        if self.ships.iter().any(|placed| placed.ship == placement.ship) {
+            return Err(format!("The {:?} is already placed", placement.ship));
+        }
+        let (row, column) = placement.start;
+        let length = placement.ship.length();
+        let fits = row < SIZE && column < SIZE && if placement.horizontal { column + length <= SIZE } else { row + length <= SIZE };
+        if !fits {
+            return Err(format!("The {:?} doesn't fit on the board there", placement.ship));
+        }
+        for placed in &self.ships {
+            if placement.squares().any(|square| placed.contains(square)) {
+                return Err(format!("The {:?} would overlap the {:?}", placement.ship, placed.ship));
+            }
+            if !self.allow_touching && placement.squares().any(|square| placed.touches(square)) {
+                return Err(format!("The {:?} would be next to the {:?}", placement.ship, placed.ship));
+            }
+        }
+        self.ships.push(placement);
+        Ok(())
+    }
+
+    /// Take a ship off the board to place it again, returning whether it was placed.
+    pub fn remove(&mut self, ship: Ship) -> Result<bool, String> {
+        if !self.shots.is_empty() {
+            return Err("Ships can't be moved once the game has started".to_string());
+        }
+        let before = self.ships.len();
+        self.ships.retain(|placed| placed.ship != ship);
+        Ok(self.ships.len() != before)
+    }
+
+    /// Whether the whole fleet is placed, so the board can be shot at.
+    pub fn is_ready(&self) -> bool {
+        self.ships.len() == FLEET.len()
+    }
+
+    pub fn ship_at(&self, square: Square) -> Option<&Placement> {
+        self.ships.iter().find(|placed| placed.contains(square))
+    }
+
+    pub fn is_shot(&self, square: Square) -> bool {
+        self.shots.contains(&square)
+    }
+
+    pub fn is_sunk(&self, placement: &Placement) -> bool {
+        placement.squares().all(|square| self.is_shot(square))
+    }
+
+    pub fn all_sunk(&self) -> bool {
+        self.is_ready() && self.ships.iter().all(|placed| self.is_sunk(placed))
+    }
+
+    pub fn fire(&mut self, square: Square) -> Result<Report, String> {
+        if !self.is_ready() {
+            return Err("Not all the ships are placed yet".to_string());
+        }
+        if self.all_sunk() {
+            return Err("All the ships are already sunk".to_string());
+        }
+        if self.is_shot(square) {
+            return Err(format!("{} was already shot at", square_name(square)));
+        }
+        self.shots.push(square);
+        let (shot, ship) = match self.ship_at(square) {
+            None => (Shot::Miss, None),
+            Some(placed) if self.is_sunk(placed) => (Shot::Sunk, Some(placed.ship)),
+            Some(placed) => (Shot::Hit, Some(placed.ship)),
+        };
+        Ok(Report { square: square_name(square), shot, ship })
+    }
+
+    /// Check a saved board could come up in a game, by placing its ships and making its shots again.
+    pub fn check(&self) -> Result<(), String> {
+        let mut board = Board::new(self.allow_touching);
+        for placement in &self.ships {
+            board.place(*placement)?;
+        }
+        for &(row, column) in &self.shots {
+            if row >= SIZE || column >= SIZE {
+                return Err(format!("The shot at row {row} and column {column} is off the board"));
+            }
+            board.fire((row, column))?;
+        }
+        Ok(())
+    }
+
+    /// The board as emoji, showing where the ships are if `reveal`, or only the shots for the other player.
+    pub fn to_emoji(&self, reveal: bool) -> String {
+        let mut lines = vec![COLUMN_EMOJI.concat()];
+        for (row, letter) in ROW_EMOJI.iter().enumerate() {
+            let cells = (0..SIZE).map(|column| match (self.ship_at((row, column)), self.is_shot((row, column))) {
+                (Some(placed), true) if self.is_sunk(placed) => SUNK_EMOJI,
+                (Some(_), true) => HIT_EMOJI,
+                (None, true) => MISS_EMOJI,
+                (Some(_), false) if reveal => SHIP_EMOJI,
+                _ => WATER_EMOJI,
+            });
+            lines.push(letter.to_string() + &cells.collect::<String>());
+        }
+        lines.join("\n")
+    }
+}
+
+const WATER_EMOJI: &str = "\u{1f7e6}";
+const SHIP_EMOJI: &str = "\u{1f7eb}";
+const MISS_EMOJI: &str = "\u{26aa}";
+const HIT_EMOJI: &str = "\u{1f4a5}";
+const SUNK_EMOJI: &str = "\u{1f7e5}";
+/// The emoji letters before each row, a single letter is never shown as a flag.
+const ROW_EMOJI: [&str; SIZE] = [
+    "\u{1f1e6}",
+    "\u{1f1e7}",
+    "\u{1f1e8}",
+    "\u{1f1e9}",
+    "\u{1f1ea}",
+    "\u{1f1eb}",
+    "\u{1f1ec}",
+    "\u{1f1ed}",
+    "\u{1f1ee}",
+    "\u{1f1ef}",
+];
+/// The keycap numbers over each column, after the square in the corner over the row letters.
+const COLUMN_EMOJI: [&str; SIZE + 1] = [
+    "\u{2b1b}",
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+    "4\u{fe0f}\u{20e3}",
+    "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}",
+    "7\u{fe0f}\u{20e3}",
+    "8\u{fe0f}\u{20e3}",
+    "9\u{fe0f}\u{20e3}",
+    "\u{1f51f}",
+];
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// The fleet along the left side, a row apart, with the carrier at the top.
+    fn fleet(allow_touching: bool) -> Board {
+        let mut board = Board::new(allow_touching);
+        for (row, ship) in FLEET.into_iter().enumerate() {
+            board.place(Placement { ship, start: (row * 2, 0), horizontal: true }).expect("Failed to place");
+        }
+        board
+    }
+
+    #[test]
+    fn squares() {
+        assert_eq!(parse_square("B7"), Ok((1, 6)));
+        assert_eq!(parse_square(" j10 "), Ok((9, 9)));
+        assert_eq!(square_name((0, 0)), "A1");
+        assert_eq!(square_name((9, 9)), "J10");
+        for name in ["", "B", "K1", "A0", "A11", "A+1", "7B", "AB"] {
+            parse_square(name).expect_err("Expected the square to be rejected");
+        }
+    }
+
+    #[test]
+    fn placing() {
+        let mut board = Board::new(false);
+        let carrier = Placement { ship: Ship::Carrier, start: (0, 0), horizontal: true };
+        board.place(carrier).expect("Failed to place");
+        assert_eq!(carrier.squares().collect::<Vec<_>>(), vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)]);
+        board.place(carrier).expect_err("Expected the second carrier to be rejected");
+        board.place(Placement { ship: Ship::Destroyer, start: (0, 9), horizontal: true }).expect_err("Expected the ship off the board to be rejected");
+        board.place(Placement { ship: Ship::Carrier, start: (6, 0), horizontal: false }).expect_err("Expected the ship off the board to be rejected");
+        board.place(Placement { ship: Ship::Destroyer, start: (0, 4), horizontal: false }).expect_err("Expected the overlap to be rejected");
+        board.place(Placement { ship: Ship::Destroyer, start: (1, 5), horizontal: false }).expect_err("Expected the diagonal neighbour to be rejected");
+        board.place(Placement { ship: Ship::Destroyer, start: (0, 6), horizontal: false }).expect("Failed to place");
+        assert!(!board.is_ready());
+        assert_eq!(board.remove(Ship::Destroyer), Ok(true));
+        assert_eq!(board.remove(Ship::Destroyer), Ok(false));
+        let mut touching = Board::new(true);
+        touching.place(carrier).expect("Failed to place");
+        touching.place(Placement { ship: Ship::Destroyer, start: (1, 0), horizontal: true }).expect("Failed to place");
+        touching.place(Placement { ship: Ship::Cruiser, start: (1, 1), horizontal: true }).expect_err("Expected the overlap to be rejected");
+    }
+
+    #[test]
+    fn random() {
+        for allow_touching in [false, true] {
+            let board = Board::random(allow_touching, &mut StdRng::seed_from_u64(1));
+            assert!(board.is_ready());
+            board.check().expect("Expected the random fleet to follow the rules");
+        }
+    }
+
+    #[test]
+    fn firing() {
+        let mut board = Board::new(false);
+        board.fire((0, 0)).expect_err("Expected firing before the ships are placed to be rejected");
+        let mut board = fleet(false);
+        let miss = board.fire((9, 9)).expect("Failed to fire");
+        assert_eq!((miss.square.as_str(), miss.shot, miss.ship), ("J10", Shot::Miss, None));
+        board.fire((9, 9)).expect_err("Expected the repeated shot to be rejected");
+        board.remove(Ship::Carrier).expect_err("Expected moving ships after firing to be rejected");
+        assert_eq!(board.fire((8, 0)).map(|report| report.shot), Ok(Shot::Hit));
+        let sunk = board.fire((8, 1)).expect("Failed to fire");
+        assert_eq!((sunk.shot, sunk.ship), (Shot::Sunk, Some(Ship::Destroyer)));
+        assert!(!board.all_sunk());
+        for placed in board.ships.clone() {
+            for square in placed.squares() {
+                if !board.is_shot(square) {
+                    board.fire(square).expect("Failed to fire");
+                }
+            }
+        }
+        assert!(board.all_sunk());
+        board.fire((5, 5)).expect_err("Expected firing at a sunk fleet to be rejected");
+    }
+
+    #[test]
+    fn check() {
+        let mut board = fleet(false);
+        board.fire((0, 0)).expect("Failed to fire");
+        board.check().expect("Expected the board to be valid");
+        let mut cheating = board.clone();
+        cheating.shots.push((0, 0));
+        cheating.check().expect_err("Expected the repeated shot to be rejected");
+        let mut cheating = board.clone();
+        cheating.shots.push((10, 0));
+        cheating.check().expect_err("Expected the shot off the board to be rejected");
+        let mut cheating = board.clone();
+        cheating.allow_touching = false;
+        cheating.ships[1].start = (1, 0);
+        cheating.check().expect_err("Expected the touching ships to be rejected");
+        let mut unready = Board::new(false);
+        unready.shots.push((0, 0));
+        unready.check().expect_err("Expected shots before the ships are placed to be rejected");
+    }
+
+    #[test]
+    fn emoji() {
+        let mut board = fleet(false);
+        board.fire((0, 0)).expect("Failed to fire");
+        board.fire((0, 9)).expect("Failed to fire");
+        board.fire((8, 0)).expect("Failed to fire");
+        board.fire((8, 1)).expect("Failed to fire");
+        let revealed = board.to_emoji(true);
+        let lines = revealed.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), SIZE + 1);
+        assert!(lines[0].ends_with("9\u{fe0f}\u{20e3}\u{1f51f}"));
+        assert_eq!(lines[1], format!("{}{HIT_EMOJI}{}{}{MISS_EMOJI}", ROW_EMOJI[0], SHIP_EMOJI.repeat(4), WATER_EMOJI.repeat(4)));
+        assert_eq!(lines[9], format!("{}{}{}", ROW_EMOJI[8], SUNK_EMOJI.repeat(2), WATER_EMOJI.repeat(8)));
+        let hidden = board.to_emoji(false);
+        assert_eq!(hidden.lines().nth(1), Some(format!("{}{HIT_EMOJI}{}{MISS_EMOJI}", ROW_EMOJI[0], WATER_EMOJI.repeat(8)).as_str()));
+        assert!(!hidden.contains(SHIP_EMOJI));
+    }
+}
+// COV_EXCL_STOP
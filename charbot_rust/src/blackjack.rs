@@ -0,0 +1,596 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod cards; // COV_EXCL_LINE
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::blackjack::cards::{Card, Hand, Shoe};
+use crate::persist::{self, Persist};
+
+const MAX_DECKS: usize = 8;
+/// The most hands a player can have by splitting.
+const MAX_HANDS: usize = 4;
+
+/// How a hand did against the dealer.
+#[pyclass(module = "blackjack")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Outcome { // COV_EXCL_LINE
+    /// The hand lost, the bet is gone.
+    Lose,
+    /// The hand tied with the dealer, the bet is returned.
+    Push,
+    /// The hand beat the dealer, the bet is paid 1:1.
+    Win,
+    /// The hand was a blackjack and the dealer's wasn't, the bet is paid 3:2.
+    Blackjack,
+}
+
+impl Outcome {
+    /// What is given back for `bet`, the bet itself included, with half points rounded down.
+    fn payout(self, bet: u32) -> u32 {
+        match self {
+            Outcome::Lose => 0,
+            Outcome::Push => bet,
+            Outcome::Win => bet * 2,
+            Outcome::Blackjack => bet + bet * 3 / 2,
+        }
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "blackjack")]
+#[derive(Debug)]
+struct Table {
+    shoe: Shoe,
+    /// Whether the dealer hits a soft 17 instead of standing on it.
+    hit_soft_17: bool,
+    hands: Vec<Hand>,
+    dealer: Hand,
+    /// The hand being played, `None` between rounds and once the dealer has played.
+    active: Option<usize>,
+    outcomes: Option<Vec<Outcome>>,
+}
+// COV_EXCL_STOP
+
+impl Table {
+    fn new(decks: usize, seed: u64, hit_soft_17: bool) -> Result<Self, String> {
+        if !(1..=MAX_DECKS).contains(&decks) {
+            return Err(format!("Invalid number of decks {decks}, it must be from 1 to {MAX_DECKS}"));
+        }
+        Ok(Table {
+            shoe: Shoe::new(decks, seed),
+            hit_soft_17,
+            hands: Vec::new(),
+            dealer: Hand::new(0),
+            active: None,
+            outcomes: None,
+        })
+    }
+
+    fn active_hand(&mut self) -> Result<&mut Hand, String> {
+        match self.active {
+            Some(index) => Ok(&mut self.hands[index]),
+            None => Err("No hand is being played".to_string()),
+        }
+    }
+
+    fn start_round(&mut self, bet: u32) -> Result<(), String> {
+        if self.active.is_some() {
+            return Err("The round isn't over yet".to_string());
+        }
+        if bet == 0 {
+            return Err("The bet must be more than 0".to_string());
+        }
+        self.shoe.shuffle_if_low();
+        self.hands = vec![Hand::new(bet)];
+        self.dealer = Hand::new(0);
+        self.outcomes = None;
+        for _ in 0..2 {
+            self.hands[0].cards.push(self.shoe.draw());
+            self.dealer.cards.push(self.shoe.draw());
+        }
+        // the dealer checks for a blackjack straight away, so nobody plays into one
+        if self.dealer.is_blackjack() || self.hands[0].is_blackjack() {
+            self.settle();
+        } else {
+            self.active = Some(0);
+        }
+        Ok(())
+    }
+
+    /// Finish the active hand and move on to the next one, or to the dealer once every hand is done.
+    fn next_hand(&mut self) {
+        let Some(mut index) = self.active else {
+            return; // COV_EXCL_LINE
+        };
+        while index < self.hands.len() {
+            let hand = &mut self.hands[index];
+            if hand.cards.len() == 1 {
+                hand.cards.push(self.shoe.draw());
+                // split aces only get the one card each
+                hand.done = hand.is_aces();
+            }
+            if hand.total() >= 21 {
+                hand.done = true;
+            }
+            if !hand.done {
+                self.active = Some(index);
+                return;
+            }
+            index += 1;
+        }
+        self.play_dealer();
+        self.settle();
+    }
+
+    fn play_dealer(&mut self) {
+        if self.hands.iter().all(Hand::is_bust) {
+            return;
+        }
+        while self.dealer.total() < 17 || (self.hit_soft_17 && self.dealer.total() == 17 && self.dealer.is_soft()) {
+            self.dealer.cards.push(self.shoe.draw());
+        }
+    }
+
+    fn settle(&mut self) {
+        let dealer = &self.dealer;
+        let outcomes = self
+            .hands
+            .iter()
+            .map(|hand| {
+                if hand.is_bust() {
+                    Outcome::Lose
+                } else if hand.is_blackjack() != dealer.is_blackjack() {
+                    if hand.is_blackjack() { Outcome::Blackjack } else { Outcome::Lose }
+                } else if dealer.is_bust() || hand.total() > dealer.total() {
+                    Outcome::Win
+                } else if hand.total() == dealer.total() {
+                    Outcome::Push
+                } else {
+                    Outcome::Lose
+                }
+            })
+            .collect();
+        self.outcomes = Some(outcomes);
+        self.active = None;
+        for hand in &mut self.hands {
+            hand.done = true;
+        }
+    }
+
+    fn take_hit(&mut self) -> Result<Card, String> {
+        let card = self.shoe.draw();
+        let hand = self.active_hand()?;
+        hand.cards.push(card);
+        if hand.total() >= 21 {
+            hand.done = true;
+            self.next_hand();
+        }
+        Ok(card)
+    }
+
+    fn take_stand(&mut self) -> Result<(), String> {
+        self.active_hand()?.done = true;
+        self.next_hand();
+        Ok(())
+    }
+
+    fn take_double(&mut self) -> Result<Card, String> {
+        let hand = self.active_hand()?;
+        if hand.cards.len() != 2 {
+            return Err("A hand can only be doubled on its first two cards".to_string());
+        }
+        let card = self.shoe.draw();
+        let hand = self.active_hand()?;
+        hand.bet *= 2;
+        hand.doubled = true;
+        hand.cards.push(card);
+        hand.done = true;
+        self.next_hand();
+        Ok(card)
+    }
+
+    fn take_split(&mut self) -> Result<(), String> {
+        let hands = self.hands.len();
+        let index = self.active.ok_or_else(|| "No hand is being played".to_string())?;
+        let hand = &mut self.hands[index];
+        if !hand.is_pair() {
+            return Err("Only a pair of cards of the same value can be split".to_string());
+        }
+        if hands >= MAX_HANDS {
+            return Err(format!("A hand can only be split into {MAX_HANDS} hands"));
+        }
+        hand.split = true;
+        let second = Hand {
+            cards: hand.cards.pop().into_iter().collect(),
+            ..hand.clone()
+        };
+        self.hands.insert(index + 1, second);
+        // the active hand gets its second card like the new one will, once play gets to it
+        self.next_hand();
+        Ok(())
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Table {
+    #[new]
+    #[pyo3(signature = (decks=6, seed=None, hit_soft_17=false))]
+    fn __new__(decks: usize, seed: Option<u64>, hit_soft_17: bool) -> PyResult<Self> { // COV_EXCL_LINE
+        let seed = seed.unwrap_or_else(|| StdRng::from_entropy().gen());
+        Self::new(decks, seed, hit_soft_17).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The seed the shoe was shuffled with, so a table can be replayed to check it.
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.shoe.seed()
+    }
+
+    #[getter]
+    fn cards_left(&self) -> usize {
+        self.shoe.len()
+    }
+
+    #[getter]
+    fn hands(&self) -> Vec<Hand> {
+        self.hands.clone()
+    }
+
+    #[getter]
+    fn active_hand_index(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// The dealer's cards, only the first one while a hand is still being played.
+    #[getter]
+    fn dealer_cards(&self) -> Vec<Card> {
+        match self.active {
+            Some(_) => self.dealer.cards[..1].to_vec(),
+            None => self.dealer.cards.clone(),
+        }
+    }
+
+    /// The dealer's total, or `None` while the hole card is hidden.
+    #[getter]
+    fn dealer_total(&self) -> Option<u8> {
+        self.active.is_none().then(|| self.dealer.total())
+    }
+
+    fn deal(&mut self, bet: u32) -> PyResult<()> {
+        self.start_round(bet).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn hit(&mut self) -> PyResult<Card> {
+        self.take_hit().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn stand(&mut self) -> PyResult<()> {
+        self.take_stand().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn double(&mut self) -> PyResult<Card> {
+        self.take_double().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn split(&mut self) -> PyResult<()> {
+        self.take_split().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn is_round_over(&self) -> bool {
+        self.outcomes.is_some()
+    }
+
+    /// How each hand did, or `None` while the round is still being played.
+    fn outcomes(&self) -> Option<Vec<Outcome>> {
+        self.outcomes.clone()
+    }
+
+    /// What the player gets back over every hand, bets included, or `None` while the round is still being played.
+    fn payout(&self) -> Option<u32> {
+        let outcomes = self.outcomes.as_ref()?;
+        Some(outcomes.iter().zip(&self.hands).map(|(outcome, hand)| outcome.payout(hand.bet)).sum())
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    shoe: Shoe,
+    hit_soft_17: bool,
+    hands: Vec<Hand>,
+    dealer: Hand,
+    active: Option<usize>,
+    outcomes: Option<Vec<Outcome>>,
+}
+
+impl Persist for Table {
+    const GAME: &'static str = "blackjack";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State {
+            shoe: self.shoe.clone(),
+            hit_soft_17: self.hit_soft_17,
+            hands: self.hands.clone(),
+            dealer: self.dealer.clone(),
+            active: self.active,
+            outcomes: self.outcomes.clone(),
+        }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        if !(1..=MAX_DECKS).contains(&state.shoe.decks) {
+            return Err(format!("Invalid number of decks {}, it must be from 1 to {MAX_DECKS}", state.shoe.decks));
+        }
+        let dealt = state.hands.iter().chain([&state.dealer]).flat_map(|hand| &hand.cards);
+        state.shoe.check(dealt)?;
+        if state.hands.len() > MAX_HANDS {
+            return Err(format!("A round can only have {MAX_HANDS} hands"));
+        }
+        match (state.active, &state.outcomes) {
+            (Some(index), None) if index < state.hands.len() && state.dealer.cards.len() == 2 => {}
+            (None, Some(outcomes)) if outcomes.len() == state.hands.len() => {}
+            (None, None) if state.hands.is_empty() => {}
+            _ => return Err("The hands, the hand being played and the outcomes don't match".to_string()),
+        }
+        Ok(Table {
+            shoe: state.shoe,
+            hit_soft_17: state.hit_soft_17,
+            hands: state.hands,
+            dealer: state.dealer,
+            active: state.active,
+            outcomes: state.outcomes,
+        })
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of blackjack";
+
+pub(crate) fn register_blackjack(py: Python, m: &PyModule) -> PyResult<()> {
+    let blackjack = PyModule::new(py, "_blackjack")?;
+    blackjack.add_class::<Card>()?;
+    blackjack.add_class::<Hand>()?;
+    blackjack.add_class::<Outcome>()?;
+    blackjack.add_class::<Table>()?;
+    blackjack.add("__doc__", DOCSTRING)?;
+    m.add_submodule(blackjack)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A table that deals `ranks` in order, player and dealer taking turns for the first four.
+    fn stacked(ranks: &[u8], hit_soft_17: bool) -> Table {
+        let mut table = Table::new(1, 0, hit_soft_17).expect("Valid table");
+        table.shoe.cards.extend(ranks.iter().rev().map(|rank| Card::new(*rank, 0)));
+        table
+    }
+
+    fn totals(table: &Table) -> Vec<u8> {
+        table.hands.iter().map(Hand::total).collect()
+    }
+
+    #[test]
+    fn decks() {
+        Table::new(0, 0, false).expect_err("Expected no decks to be rejected");
+        Table::new(9, 0, false).expect_err("Expected nine decks to be rejected");
+        assert_eq!(Table::new(6, 0, false).expect("Valid table").cards_left(), 312);
+    }
+
+    #[test]
+    fn seeded() {
+        let mut first = Table::new(2, 42, false).expect("Valid table");
+        let mut second = Table::new(2, 42, false).expect("Valid table");
+        first.start_round(10).expect("Able to deal");
+        second.start_round(10).expect("Able to deal");
+        assert_eq!(first.hands, second.hands);
+        assert_eq!(first.dealer, second.dealer);
+    }
+
+    #[test]
+    fn standing() {
+        // player 10 9, dealer 10 7
+        let mut table = stacked(&[10, 10, 9, 7], false);
+        table.start_round(10).expect("Able to deal");
+        assert_eq!(table.dealer_cards().len(), 1);
+        assert_eq!(table.dealer_total(), None);
+        assert_eq!(table.payout(), None);
+        table.start_round(10).expect_err("Expected the round to still be going");
+        table.take_stand().expect("Able to stand");
+        assert_eq!(table.dealer_total(), Some(17));
+        assert_eq!(table.outcomes(), Some(vec![Outcome::Win]));
+        assert_eq!(table.payout(), Some(20));
+        table.take_stand().expect_err("Expected the round to be over");
+    }
+
+    #[test]
+    fn busting() {
+        // player 10 6 then 10, dealer 10 6 never draws
+        let mut table = stacked(&[10, 10, 6, 6, 10], false);
+        table.start_round(10).expect("Able to deal");
+        table.take_hit().expect("Able to hit");
+        assert_eq!(table.outcomes(), Some(vec![Outcome::Lose]));
+        assert_eq!(table.dealer.cards.len(), 2);
+        assert_eq!(table.payout(), Some(0));
+    }
+
+    #[test]
+    fn dealer_draws() {
+        // player 10 8, dealer 10 2 then 3 and 9 to bust
+        let mut table = stacked(&[10, 10, 8, 2, 3, 9], false);
+        table.start_round(10).expect("Able to deal");
+        table.take_stand().expect("Able to stand");
+        assert_eq!(table.dealer_total(), Some(24));
+        assert_eq!(table.outcomes(), Some(vec![Outcome::Win]));
+    }
+
+    #[test]
+    fn soft_17() {
+        // player 10 8, dealer A 6, then a 2 only if soft 17 is hit
+        let mut stands = stacked(&[10, 1, 8, 6, 2], false);
+        stands.start_round(10).expect("Able to deal");
+        stands.take_stand().expect("Able to stand");
+        assert_eq!(stands.dealer_total(), Some(17));
+        assert_eq!(stands.outcomes(), Some(vec![Outcome::Win]));
+        let mut hits = stacked(&[10, 1, 8, 6, 2], true);
+        hits.start_round(10).expect("Able to deal");
+        hits.take_stand().expect("Able to stand");
+        assert_eq!(hits.dealer_total(), Some(19));
+        assert_eq!(hits.outcomes(), Some(vec![Outcome::Lose]));
+    }
+
+    #[test]
+    fn blackjacks() {
+        let mut natural = stacked(&[1, 10, 13, 7], false);
+        natural.start_round(10).expect("Able to deal");
+        assert!(natural.is_round_over());
+        assert_eq!(natural.outcomes(), Some(vec![Outcome::Blackjack]));
+        assert_eq!(natural.payout(), Some(25));
+        let mut dealer = stacked(&[10, 1, 9, 13], false);
+        dealer.start_round(10).expect("Able to deal");
+        assert_eq!(dealer.outcomes(), Some(vec![Outcome::Lose]));
+        let mut both = stacked(&[1, 1, 12, 11], false);
+        both.start_round(10).expect("Able to deal");
+        assert_eq!(both.outcomes(), Some(vec![Outcome::Push]));
+        assert_eq!(both.payout(), Some(10));
+        assert_eq!(Outcome::Blackjack.payout(5), 12);
+    }
+
+    #[test]
+    fn doubling() {
+        // player 5 6 then 10, dealer 10 8
+        let mut table = stacked(&[5, 10, 6, 8, 10], false);
+        table.start_round(10).expect("Able to deal");
+        table.take_double().expect("Able to double");
+        assert!(table.hands[0].doubled);
+        assert_eq!(table.hands[0].bet, 20);
+        assert_eq!(table.outcomes(), Some(vec![Outcome::Win]));
+        assert_eq!(table.payout(), Some(40));
+        let mut late = stacked(&[2, 10, 3, 8, 4], false);
+        late.start_round(10).expect("Able to deal");
+        late.take_hit().expect("Able to hit");
+        late.take_double().expect_err("Expected doubling on three cards to be rejected");
+    }
+
+    #[test]
+    fn splitting() {
+        // player 8 8, dealer 10 7, then 3 and 10 to the first hand, 2 and 9 to the second
+        let mut table = stacked(&[8, 10, 8, 7, 3, 10, 2, 9], false);
+        table.start_round(10).expect("Able to deal");
+        table.take_split().expect("Able to split");
+        assert_eq!(table.hands.len(), 2);
+        assert_eq!(totals(&table), vec![11, 8]);
+        assert_eq!(table.active_hand_index(), Some(0));
+        table.take_hit().expect("Able to hit");
+        assert_eq!(table.active_hand_index(), Some(1));
+        assert_eq!(totals(&table), vec![21, 10]);
+        table.take_hit().expect("Able to hit");
+        assert!(!table.is_round_over());
+        table.take_stand().expect("Able to stand");
+        assert!(table.is_round_over());
+        assert_eq!(table.outcomes(), Some(vec![Outcome::Win, Outcome::Win]));
+        assert_eq!(table.payout(), Some(40));
+        let mut unpaired = stacked(&[8, 10, 9, 7], false);
+        unpaired.start_round(10).expect("Able to deal");
+        unpaired.take_split().expect_err("Expected the split to be rejected");
+    }
+
+    #[test]
+    fn splitting_aces() {
+        // player A A, dealer 10 9, then a king to each ace which is 21 but not a blackjack
+        let mut table = stacked(&[1, 10, 1, 9, 13, 13], false);
+        table.start_round(10).expect("Able to deal");
+        table.take_split().expect("Able to split");
+        assert!(table.is_round_over());
+        assert_eq!(totals(&table), vec![21, 21]);
+        assert_eq!(table.outcomes(), Some(vec![Outcome::Win, Outcome::Win]));
+        assert_eq!(table.payout(), Some(40));
+    }
+
+    #[test]
+    fn split_limit() {
+        let mut table = stacked(&[8, 10, 8, 7, 8, 8, 8], false);
+        table.start_round(10).expect("Able to deal");
+        for _ in 1..MAX_HANDS {
+            table.take_split().expect("Able to split");
+        }
+        table.take_split().expect_err("Expected the split to be rejected");
+    }
+
+    #[test]
+    fn persists() {
+        let mut table = Table::new(2, 7, true).expect("Valid table");
+        let fresh: Table = persist::from_json(&persist::to_json(&table).expect("Failed to save")).expect("Failed to load");
+        assert_eq!(fresh.outcomes(), None);
+        table.start_round(10).expect("Able to deal");
+        let json = persist::to_json(&table).expect("Failed to save");
+        let mut loaded: Table = persist::from_json(&json).expect("Failed to load");
+        assert_eq!((&loaded.shoe, &loaded.hands, &loaded.dealer, loaded.active), (&table.shoe, &table.hands, &table.dealer, table.active));
+        assert_eq!((loaded.seed(), loaded.hit_soft_17), (7, true));
+        let bytes = persist::to_bytes(&table).expect("Failed to save");
+        let mut from_bytes: Table = persist::from_bytes(&bytes).expect("Failed to load");
+        // the loaded tables go on dealing the same cards as the table they were saved from
+        for _ in 0..30 {
+            if table.active.is_some() {
+                table.take_stand().expect("Able to stand");
+                loaded.take_stand().expect("Able to stand");
+                from_bytes.take_stand().expect("Able to stand");
+            }
+            table.start_round(10).expect("Able to deal");
+            loaded.start_round(10).expect("Able to deal");
+            from_bytes.start_round(10).expect("Able to deal");
+            assert_eq!((&loaded.hands, &loaded.dealer), (&table.hands, &table.dealer));
+            assert_eq!(from_bytes.shoe, table.shoe);
+        }
+    }
+
+    #[test]
+    fn rejects_saves() {
+        let mut table = Table::new(1, 0, false).expect("Valid table");
+        table.start_round(10).expect("Able to deal");
+        let mut wrong = table.save();
+        wrong.shoe.decks = 9;
+        Table::load(wrong).expect_err("Expected the decks to be rejected");
+        let mut wrong = table.save();
+        let card = wrong.dealer.cards[0];
+        wrong.hands[0].cards.push(card);
+        Table::load(wrong).expect_err("Expected the card dealt twice to be rejected");
+        let mut wrong = table.save();
+        wrong.active = Some(1);
+        Table::load(wrong).expect_err("Expected the active hand to be rejected");
+        let mut wrong = table.save();
+        wrong.outcomes = Some(vec![Outcome::Win, Outcome::Lose]);
+        Table::load(wrong).expect_err("Expected the outcomes to be rejected");
+        let mut wrong = table.save();
+        wrong.hands = vec![wrong.hands[0].clone(); MAX_HANDS + 1];
+        Table::load(wrong).expect_err("Expected too many hands to be rejected");
+    }
+}
+// COV_EXCL_STOP
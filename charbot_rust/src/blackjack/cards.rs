@@ -0,0 +1,271 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::collections::HashMap;
+use std::fmt::{Display, Error, Formatter};
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+// COV_EXCL_STOP
+
+const RANKS: [&str; 13] = ["A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K"];
+const SUITS: [&str; 4] = ["\u{2660}", "\u{2665}", "\u{2666}", "\u{2663}"];
+/// The shoe is shuffled again before a round once less than this part of it is left.
+const RESHUFFLE_FRACTION: usize = 4;
+
+#[pyclass(module = "blackjack")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)] // COV_EXCL_LINE
+pub struct Card {
+    /// From 1 for an ace to 13 for a king.
+    rank: u8,
+    suit: u8,
+}
+
+impl Card {
+    pub fn new(rank: u8, suit: u8) -> Self {
+        Card { rank, suit }
+    }
+
+    fn is_valid(&self) -> bool {
+        (1..=13).contains(&self.rank) && usize::from(self.suit) < SUITS.len()
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Card {
+    #[getter]
+    fn rank(&self) -> &'static str {
+        RANKS[usize::from(self.rank - 1)]
+    }
+
+    #[getter]
+    fn suit(&self) -> &'static str {
+        SUITS[usize::from(self.suit)]
+    }
+
+    /// What the card counts for, with aces as 1, hands count one of them as 11 when that doesn't bust.
+    #[getter]
+    pub fn value(&self) -> u8 {
+        self.rank.min(10)
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Card({}{})", self.rank(), self.suit())
+    }
+}
+
+// COV_EXCL_START
+impl Display for Card {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(formatter, "{}{}", self.rank(), self.suit())
+    }
+}
+// COV_EXCL_STOP
+
+/// Several decks shuffled together, dealt from the end.
+///
+/// Every shuffle is seeded from the seed and the number of shuffles before it, so a saved shoe
+/// shuffles the same way once it is loaded again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Shoe {
+    pub(crate) cards: Vec<Card>,
+    pub(crate) decks: usize,
+    seed: u64,
+    shuffles: u64,
+}
+
+impl Shoe {
+    pub fn new(decks: usize, seed: u64) -> Self {
+        let mut shoe = Shoe { cards: Vec::new(), decks, seed, shuffles: 0 };
+        shoe.shuffle();
+        shoe
+    }
+
+    fn shuffle(&mut self) {
+        self.cards = (0..self.decks)
+            .flat_map(|_| (0..4).flat_map(|suit| (1..=13).map(move |rank| Card::new(rank, suit))))
+            .collect();
+        self.cards.shuffle(&mut StdRng::seed_from_u64(self.seed.wrapping_add(self.shuffles)));
+        self.shuffles += 1;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Check the shoe and the cards `dealt` from it could have come from its decks.
+    pub fn check<'a>(&self, dealt: impl Iterator<Item = &'a Card>) -> Result<(), String> {
+        let mut counts = HashMap::new();
+        for card in self.cards.iter().copied().chain(dealt.copied()) {
+            if !card.is_valid() {
+                return Err(format!("Invalid card with rank {} and suit {}", card.rank, card.suit));
+            }
+            let count = counts.entry(card).or_insert(0);
+            *count += 1;
+            if *count > self.decks {
+                return Err(format!("{card} is in the shoe more than {} times", self.decks));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    /// Shuffle all the decks back in if too little of the shoe is left for another round.
+    pub fn shuffle_if_low(&mut self) -> bool {
+        let low = self.cards.len() * RESHUFFLE_FRACTION < self.decks * 52;
+        if low {
+            self.shuffle();
+        }
+        low
+    }
+
+    pub fn draw(&mut self) -> Card {
+        if self.cards.is_empty() {
+            self.shuffle(); // COV_EXCL_LINE
+        }
+        self.cards.pop().expect("A shuffled shoe has cards") // COV_EXCL_LINE
+    }
+}
+
+#[pyclass(module = "blackjack")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq, Default, Serialize, Deserialize)] // COV_EXCL_LINE
+pub struct Hand {
+    #[pyo3(get)]
+    pub(crate) cards: Vec<Card>,
+    #[pyo3(get)]
+    pub(crate) bet: u32,
+    #[pyo3(get)]
+    pub(crate) doubled: bool,
+    /// Whether the hand came from splitting a pair, so two cards making 21 aren't a blackjack.
+    #[pyo3(get)]
+    pub(crate) split: bool,
+    /// Whether no more cards can be taken on the hand.
+    pub(crate) done: bool,
+}
+
+impl Hand {
+    pub fn new(bet: u32) -> Self {
+        Hand { bet, ..Hand::default() }
+    }
+
+    /// The total, counting an ace as 11 if that doesn't go over 21, and whether one was.
+    fn total_and_soft(&self) -> (u8, bool) {
+        let hard = self.cards.iter().map(Card::value).sum::<u8>();
+        if self.cards.iter().any(|card| card.rank == 1) && hard + 10 <= 21 {
+            (hard + 10, true)
+        } else {
+            (hard, false)
+        }
+    }
+
+    pub fn is_pair(&self) -> bool {
+        matches!(self.cards.as_slice(), [first, second] if first.value() == second.value())
+    }
+
+    pub fn is_aces(&self) -> bool {
+        self.cards.first().is_some_and(|card| card.rank == 1)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Hand {
+    #[getter]
+    pub fn total(&self) -> u8 {
+        self.total_and_soft().0
+    }
+
+    /// Whether an ace is counted as 11.
+    #[getter]
+    pub fn is_soft(&self) -> bool {
+        self.total_and_soft().1
+    }
+
+    #[getter]
+    pub fn is_blackjack(&self) -> bool {
+        !self.split && self.cards.len() == 2 && self.total() == 21
+    }
+
+    #[getter]
+    pub fn is_bust(&self) -> bool {
+        self.total() > 21
+    }
+
+    fn __repr__(&self) -> String {
+        let cards = self.cards.iter().map(Card::to_string).collect::<Vec<_>>().join(" ");
+        format!("Hand({cards}, total={}, bet={})", self.total(), self.bet)
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hand(ranks: &[u8]) -> Hand {
+        Hand { cards: ranks.iter().map(|rank| Card::new(*rank, 0)).collect(), ..Hand::new(10) }
+    }
+
+    #[test]
+    fn cards() {
+        let ace = Card::new(1, 0);
+        assert_eq!(ace.to_string(), "A\u{2660}");
+        assert_eq!(ace.value(), 1);
+        let queen = Card::new(12, 1);
+        assert_eq!(queen.to_string(), "Q\u{2665}");
+        assert_eq!(queen.value(), 10);
+        assert_eq!(Card::new(10, 3).rank(), "10");
+    }
+
+    #[test]
+    fn totals() {
+        assert_eq!((hand(&[1, 6]).total(), hand(&[1, 6]).is_soft()), (17, true));
+        assert_eq!((hand(&[1, 6, 10]).total(), hand(&[1, 6, 10]).is_soft()), (17, false));
+        assert_eq!(hand(&[1, 1, 9]).total(), 21);
+        assert_eq!(hand(&[1, 1]).total(), 12);
+        assert!(hand(&[1, 13]).is_blackjack());
+        assert!(!hand(&[1, 5, 5]).is_blackjack());
+        assert!(!Hand { split: true, ..hand(&[1, 13]) }.is_blackjack());
+        assert!(hand(&[10, 12, 2]).is_bust());
+        assert!(hand(&[11, 13]).is_pair());
+        assert!(!hand(&[11, 9]).is_pair());
+        assert!(!hand(&[11, 11, 2]).is_pair());
+    }
+
+    #[test]
+    fn shoe() {
+        let mut shoe = Shoe::new(2, 0);
+        assert_eq!(shoe.len(), 104);
+        let mut sorted = shoe.cards.iter().map(|card| (card.rank, card.suit)).collect::<Vec<_>>();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 52);
+        let first = shoe.draw();
+        assert_eq!(first, Shoe::new(2, 0).draw());
+        assert!(!shoe.shuffle_if_low());
+        for _ in 0..80 {
+            shoe.draw();
+        }
+        assert!(shoe.shuffle_if_low());
+        assert_eq!(shoe.len(), 104);
+        assert_ne!(shoe.cards, Shoe::new(2, 0).cards);
+    }
+
+    #[test]
+    fn check() {
+        let mut shoe = Shoe::new(1, 0);
+        let card = shoe.draw();
+        shoe.check([card].iter()).expect("Expected the dealt card to be allowed");
+        shoe.check([card, card].iter()).expect_err("Expected the card dealt twice to be rejected");
+        shoe.check([Card::new(14, 0)].iter()).expect_err("Expected the rank to be rejected");
+        shoe.check([Card::new(1, 4)].iter()).expect_err("Expected the suit to be rejected");
+    }
+}
+// COV_EXCL_STOP
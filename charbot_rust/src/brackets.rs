@@ -0,0 +1,461 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod schedule; // COV_EXCL_LINE
+
+use std::cmp::Reverse;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::brackets::schedule::{elimination, round_robin, Fixture, Source};
+use crate::persist::{self, Persist};
+
+/// The most participants a tournament can have.
+const MAX_PARTICIPANTS: usize = 256;
+
+#[pyclass(module = "brackets")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Format { // COV_EXCL_LINE
+    /// The winner of each match goes through to the next round, until one is left.
+    SingleElimination,
+    /// Everyone plays everyone once, and the most points wins.
+    RoundRobin,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+enum Outcome {
+    Win(u64),
+    Draw,
+}
+
+// COV_EXCL_START
+/// A tournament, its matches and the results so far.
+///
+/// The matches only depend on the format and the participants, so only the results are saved with
+/// them, and they are checked by recording them again when the bracket is loaded.
+#[pyclass(module = "brackets")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bracket {
+    format: Format,
+    participants: Vec<u64>,
+    fixtures: Vec<Fixture>,
+    results: Vec<Option<Outcome>>,
+}
+// COV_EXCL_STOP
+
+#[derive(Serialize, Deserialize)]
+pub struct State {
+    format: Format,
+    participants: Vec<u64>,
+    results: Vec<Option<Outcome>>,
+}
+
+impl Bracket {
+    /// A bracket for `participants`, in seeding order with the top seed first.
+    fn new(format: Format, participants: Vec<u64>) -> Result<Self, String> {
+        if !(2..=MAX_PARTICIPANTS).contains(&participants.len()) {
+            return Err(format!("Invalid number of participants {}, it must be from 2 to {MAX_PARTICIPANTS}", participants.len()));
+        }
+        if let Some(repeated) = participants.iter().enumerate().find_map(|(index, id)| participants[..index].contains(id).then_some(id)) {
+            return Err(format!("{repeated} is in the tournament more than once"));
+        }
+        let fixtures = match format {
+            Format::SingleElimination => elimination(participants.len()),
+            Format::RoundRobin => round_robin(participants.len()),
+        };
+        Ok(Bracket { format, participants, results: vec![None; fixtures.len()], fixtures })
+    }
+
+    fn player(&self, source: Source) -> Option<u64> {
+        match source {
+            Source::Seed(seed) => Some(self.participants[seed]),
+            Source::Bye => None,
+            Source::Winner(id) => self.winner_of(id),
+        }
+    }
+
+    fn players(&self, id: usize) -> [Option<u64>; 2] {
+        self.fixtures[id].sources.map(|source| self.player(source))
+    }
+
+    fn winner_of(&self, id: usize) -> Option<u64> {
+        match self.results[id] {
+            Some(Outcome::Win(winner)) => Some(winner),
+            Some(Outcome::Draw) => None,
+            // whoever has a bye goes through without playing
+            None if self.fixtures[id].is_bye() => self.players(id).into_iter().flatten().next(),
+            None => None,
+        }
+    }
+
+    /// The match the winner of match `id` plays next, `None` for the final or in a round robin.
+    fn next(&self, id: usize) -> Option<usize> {
+        self.fixtures.iter().position(|fixture| fixture.sources.contains(&Source::Winner(id)))
+    }
+
+    /// Record the result of match `id`, `None` for a draw, or change it if the winner hasn't played since.
+    fn record(&mut self, id: usize, winner: Option<u64>) -> Result<(), String> {
+        let fixture = self.fixtures.get(id).ok_or_else(|| format!("There's no match {id}"))?;
+        if fixture.is_bye() {
+            return Err(format!("Match {id} is a bye, there's nothing to play"));
+        }
+        let [Some(first), Some(second)] = self.players(id) else {
+            return Err(format!("The players of match {id} aren't known yet"));
+        };
+        let outcome = match winner {
+            Some(winner) if winner == first || winner == second => Outcome::Win(winner),
+            Some(winner) => return Err(format!("{winner} isn't playing in match {id}")),
+            None if self.format == Format::RoundRobin => Outcome::Draw,
+            None => return Err("There are no draws in a single elimination tournament".to_string()),
+        };
+        if let Some(next) = self.next(id).filter(|next| self.results[*next].is_some()) {
+            return Err(format!("Match {next} was already played, so match {id} can't be changed"));
+        }
+        self.results[id] = Some(outcome);
+        Ok(())
+    }
+
+    fn is_over(&self) -> bool {
+        self.fixtures.iter().zip(&self.results).all(|(fixture, result)| fixture.is_bye() || result.is_some())
+    }
+
+    fn to_match(&self, id: usize) -> Match {
+        let [first, second] = self.players(id);
+        Match {
+            id,
+            round: self.fixtures[id].round,
+            players: (first, second),
+            winner: self.winner_of(id),
+            is_draw: self.results[id] == Some(Outcome::Draw),
+            is_bye: self.fixtures[id].is_bye(),
+        }
+    }
+
+    /// Everyone's wins, draws and losses, the most points first, and the higher seed first when they're level.
+    ///
+    /// Going through on a bye doesn't count as a win.
+    fn standings(&self) -> Vec<Standing> {
+        let mut standings = self.participants.iter().map(|participant| Standing { participant: *participant, wins: 0, draws: 0, losses: 0 }).collect::<Vec<_>>();
+        for (id, result) in self.results.iter().enumerate() {
+            let Some(result) = result else { continue };
+            for player in self.players(id).into_iter().flatten() {
+                let standing = standings.iter_mut().find(|standing| standing.participant == player).expect("Every player is a participant");
+                match result {
+                    Outcome::Win(winner) if *winner == player => standing.wins += 1,
+                    Outcome::Win(_) => standing.losses += 1,
+                    Outcome::Draw => standing.draws += 1,
+                }
+            }
+        }
+        // a stable sort keeps the seeding order for ties
+        standings.sort_by_key(|standing| Reverse(standing.points()));
+        standings
+    }
+
+    /// Who won the tournament, once it's over, `None` if the top of a round robin is tied.
+    fn winner(&self) -> Option<u64> {
+        if !self.is_over() {
+            return None;
+        }
+        match self.format {
+            Format::SingleElimination => self.winner_of(self.fixtures.len() - 1),
+            Format::RoundRobin => match self.standings().as_slice() {
+                [first, second, ..] if first.points() == second.points() => None,
+                [first, ..] => Some(first.participant),
+                [] => None,
+            },
+        }
+    }
+}
+
+impl Persist for Bracket {
+    const GAME: &'static str = "bracket";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { format: self.format, participants: self.participants.clone(), results: self.results.clone() }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        let mut bracket = Bracket::new(state.format, state.participants)?;
+        if state.results.len() != bracket.fixtures.len() {
+            return Err(format!("There are {} results for {} matches", state.results.len(), bracket.fixtures.len()));
+        }
+        // matches come after the ones their players come from, so they can be recorded in order
+        for (id, result) in state.results.into_iter().enumerate() {
+            if let Some(result) = result {
+                let winner = match result {
+                    Outcome::Win(winner) => Some(winner),
+                    Outcome::Draw => None,
+                };
+                bracket.record(id, winner)?;
+            }
+        }
+        Ok(bracket)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Bracket {
+    #[new]
+    fn __new__(format: Format, participants: Vec<u64>) -> PyResult<Self> { // COV_EXCL_LINE
+        Bracket::new(format, participants).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter]
+    fn format(&self) -> Format {
+        self.format
+    }
+
+    #[getter]
+    fn participants(&self) -> Vec<u64> {
+        self.participants.clone()
+    }
+
+    #[getter]
+    fn rounds(&self) -> usize {
+        self.fixtures.last().map_or(0, |fixture| fixture.round + 1)
+    }
+
+    /// The matches, in order of their ids, only the ones in `round` if it's given.
+    #[pyo3(signature = (round=None))]
+    fn matches(&self, round: Option<usize>) -> Vec<Match> {
+        (0..self.fixtures.len()).filter(|id| round.map_or(true, |round| self.fixtures[*id].round == round)).map(|id| self.to_match(id)).collect()
+    }
+
+    /// The matches that can be played now, with both players known and no result yet.
+    fn pending(&self) -> Vec<Match> {
+        (0..self.fixtures.len())
+            .filter(|id| !self.fixtures[*id].is_bye() && self.results[*id].is_none() && self.players(*id).iter().all(Option::is_some))
+            .map(|id| self.to_match(id))
+            .collect()
+    }
+
+    #[pyo3(name = "record", signature = (match_id, winner=None))]
+    fn py_record(&mut self, match_id: usize, winner: Option<u64>) -> PyResult<()> {
+        self.record(match_id, winner).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "standings")]
+    fn py_standings(&self) -> Vec<Standing> {
+        self.standings()
+    }
+
+    #[getter(is_over)]
+    fn py_is_over(&self) -> bool {
+        self.is_over()
+    }
+
+    #[getter(winner)]
+    fn py_winner(&self) -> Option<u64> {
+        self.winner()
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "brackets")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// The id of the match, to record its result with.
+    #[pyo3(get)]
+    id: usize,
+    /// The round of the match, from 0 for the first.
+    #[pyo3(get)]
+    round: usize,
+    /// The players, `None` for a bye or a player that isn't known until an earlier match is played.
+    #[pyo3(get)]
+    players: (Option<u64>, Option<u64>),
+    #[pyo3(get)]
+    winner: Option<u64>,
+    #[pyo3(get)]
+    is_draw: bool,
+    /// Whether a player goes through without playing, there's nothing to record for a bye.
+    #[pyo3(get)]
+    is_bye: bool,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Match {
+    fn __repr__(&self) -> String {
+        let player = |player: Option<u64>| player.map_or_else(|| "None".to_string(), |player| player.to_string());
+        format!("Match(id={}, round={}, players=({}, {}), winner={})", self.id, self.round, player(self.players.0), player(self.players.1), player(self.winner))
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "brackets")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    #[pyo3(get)]
+    participant: u64,
+    #[pyo3(get)]
+    wins: u32,
+    #[pyo3(get)]
+    draws: u32,
+    #[pyo3(get)]
+    losses: u32,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Standing {
+    /// Three points for a win and one for a draw.
+    #[getter]
+    fn points(&self) -> u32 {
+        self.wins * 3 + self.draws
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Standing(participant={}, wins={}, draws={}, losses={})", self.participant, self.wins, self.draws, self.losses)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based tournament brackets";
+
+pub(crate) fn register_brackets(py: Python, m: &PyModule) -> PyResult<()> {
+    let brackets = PyModule::new(py, "_brackets")?;
+    brackets.add_class::<Format>()?;
+    brackets.add_class::<Bracket>()?;
+    brackets.add_class::<Match>()?;
+    brackets.add_class::<Standing>()?;
+    brackets.add("__doc__", DOCSTRING)?;
+    m.add_submodule(brackets)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Play every pending match until the end, the higher seed winning.
+    fn play_out(bracket: &mut Bracket) {
+        while let Some(next) = bracket.pending().first().cloned() {
+            let (Some(first), Some(second)) = next.players else { panic!("Expected both players") };
+            let seed = |player| bracket.participants.iter().position(|participant| *participant == player);
+            let winner = if seed(first) < seed(second) { first } else { second };
+            bracket.record(next.id, Some(winner)).expect("Failed to record");
+        }
+    }
+
+    #[test]
+    fn rejects() {
+        Bracket::new(Format::SingleElimination, vec![1]).expect_err("Expected one participant to be rejected");
+        Bracket::new(Format::RoundRobin, (0..=256).collect()).expect_err("Expected too many participants to be rejected");
+        Bracket::new(Format::RoundRobin, vec![1, 2, 1]).expect_err("Expected a repeated participant to be rejected");
+    }
+
+    #[test]
+    fn byes_go_through() {
+        let bracket = Bracket::new(Format::SingleElimination, vec![10, 20, 30, 40, 50, 60]).expect("Failed to create");
+        assert_eq!(bracket.rounds(), 3);
+        let first = bracket.matches(Some(0));
+        assert_eq!(first.len(), 4);
+        assert!(first[0].is_bye && first[2].is_bye);
+        assert_eq!((first[0].players, first[0].winner), ((Some(10), None), Some(10)));
+        assert_eq!(bracket.pending().iter().map(|pending| pending.id).collect::<Vec<_>>(), vec![1, 3]);
+        // the top seed is waiting for the winner of 4 and 5
+        assert_eq!(bracket.to_match(4).players, (Some(10), None));
+        let mut bracket = bracket;
+        bracket.record(0, Some(10)).expect_err("Expected the bye to be rejected");
+        bracket.record(4, Some(10)).expect_err("Expected the unknown player to be rejected");
+    }
+
+    #[test]
+    fn advances_winners() {
+        let mut bracket = Bracket::new(Format::SingleElimination, vec![1, 2, 3, 4]).expect("Failed to create");
+        bracket.record(0, Some(4)).expect("Failed to record");
+        bracket.record(1, Some(2)).expect("Failed to record");
+        assert_eq!(bracket.to_match(2).players, (Some(4), Some(2)));
+        // the result can be fixed until the winner plays again
+        bracket.record(0, Some(1)).expect("Failed to change");
+        assert_eq!(bracket.to_match(2).players, (Some(1), Some(2)));
+        bracket.record(7, Some(1)).expect_err("Expected the missing match to be rejected");
+        bracket.record(2, Some(3)).expect_err("Expected the wrong player to be rejected");
+        bracket.record(2, None).expect_err("Expected the draw to be rejected");
+        assert!(!bracket.is_over());
+        assert_eq!(bracket.winner(), None);
+        bracket.record(2, Some(2)).expect("Failed to record");
+        bracket.record(0, Some(4)).expect_err("Expected the played match to be fixed");
+        assert!(bracket.is_over());
+        assert_eq!(bracket.winner(), Some(2));
+        assert!(bracket.pending().is_empty());
+        let standings = bracket.standings();
+        assert_eq!((standings[0].participant, standings[0].wins), (2, 2));
+    }
+
+    #[test]
+    fn plays_out() {
+        let mut bracket = Bracket::new(Format::SingleElimination, (1..=13).collect()).expect("Failed to create");
+        play_out(&mut bracket);
+        assert_eq!(bracket.winner(), Some(1));
+        let mut bracket = Bracket::new(Format::RoundRobin, (1..=7).collect()).expect("Failed to create");
+        assert_eq!(bracket.rounds(), 7);
+        play_out(&mut bracket);
+        assert_eq!(bracket.winner(), Some(1));
+        let wins = bracket.standings().iter().map(|standing| standing.wins).collect::<Vec<_>>();
+        assert_eq!(wins, vec![6, 5, 4, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn round_robin_draws() {
+        let mut bracket = Bracket::new(Format::RoundRobin, vec![1, 2, 3]).expect("Failed to create");
+        for id in 0..3 {
+            bracket.record(id, None).expect("Failed to record");
+        }
+        assert!(bracket.is_over());
+        // everyone drew, so it's tied
+        assert_eq!(bracket.winner(), None);
+        assert!(bracket.matches(None).iter().all(|played| played.is_draw && played.winner.is_none()));
+        let standings = bracket.standings();
+        assert_eq!(standings.iter().map(|standing| (standing.participant, standing.points())).collect::<Vec<_>>(), vec![(1, 2), (2, 2), (3, 2)]);
+        // a round robin result can always be changed
+        let [Some(first), _] = bracket.players(0) else { panic!("Expected players") };
+        bracket.record(0, Some(first)).expect("Failed to change");
+        assert_eq!(bracket.winner(), Some(first));
+    }
+
+    #[test]
+    fn persists() {
+        let mut bracket = Bracket::new(Format::SingleElimination, vec![5, 6, 7, 8, 9]).expect("Failed to create");
+        bracket.record(1, Some(9)).expect("Failed to record");
+        bracket.record(4, Some(9)).expect("Failed to record");
+        let json = persist::to_json(&bracket).expect("Failed to save");
+        assert_eq!(persist::from_json::<Bracket>(&json), Ok(bracket.clone()));
+        let bytes = persist::to_bytes(&bracket).expect("Failed to save");
+        assert_eq!(persist::from_bytes::<Bracket>(&bytes), Ok(bracket.clone()));
+        let mut state = bracket.save();
+        state.results.pop();
+        Bracket::load(state).expect_err("Expected the missing result to be rejected");
+        let mut state = bracket.save();
+        state.results[1] = Some(Outcome::Win(5));
+        Bracket::load(state).expect_err("Expected the wrong winner to be rejected");
+        let mut state = bracket.save();
+        state.results[2] = Some(Outcome::Win(5));
+        Bracket::load(state).expect_err("Expected the bye result to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,147 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+
+/// Where one of the two players of a match comes from.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Source {
+    /// The participant with this index, 0 for the top seed.
+    Seed(usize),
+    /// No one, so the other player goes through without playing.
+    Bye,
+    /// The winner of the match with this id.
+    Winner(usize),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Fixture {
+    pub round: usize,
+    pub sources: [Source; 2],
+}
+
+impl Fixture {
+    pub fn is_bye(&self) -> bool {
+        self.sources.contains(&Source::Bye)
+    }
+}
+
+/// The seeds in the order they go down a bracket of `size`, a power of two, so the top seeds can only meet in the later rounds.
+///
+/// Each seed plays the seed that makes their sum one more than the size in the first round, so the
+/// top seed plays the bottom seed, and the top two seeds are in different halves, the top four in
+/// different quarters, and so on.
+pub fn seeding(size: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < size {
+        let length = order.len() * 2;
+        order = order.iter().flat_map(|seed| [*seed, length - 1 - seed]).collect();
+    }
+    order
+}
+
+/// The matches of a single elimination bracket for `count` participants, a round at a time.
+///
+/// The bracket is filled to the next power of two with byes, which go to the top seeds.
+pub fn elimination(count: usize) -> Vec<Fixture> {
+    let size = count.next_power_of_two();
+    let mut fixtures = seeding(size)
+        .chunks_exact(2)
+        .map(|pair| Fixture { round: 0, sources: [pair[0], pair[1]].map(|seed| if seed < count { Source::Seed(seed) } else { Source::Bye }) })
+        .collect::<Vec<_>>();
+    let (mut start, mut matches, mut round) = (0, size / 2, 1);
+    while matches > 1 {
+        for index in (start..start + matches).step_by(2) {
+            fixtures.push(Fixture { round, sources: [Source::Winner(index), Source::Winner(index + 1)] });
+        }
+        start += matches;
+        matches /= 2;
+        round += 1;
+    }
+    fixtures
+}
+
+/// The matches of a round robin for `count` participants, a round at a time, so everyone plays everyone once.
+///
+/// This is the circle method: the participants sit in a circle, facing the one across from them, and
+/// all but the first move round one place for the next round. With an odd number of participants one
+/// sits out each round, the one across from the empty place.
+pub fn round_robin(count: usize) -> Vec<Fixture> {
+    let mut circle = (0..count).map(Source::Seed).collect::<Vec<_>>();
+    if count % 2 == 1 {
+        circle.push(Source::Bye);
+    }
+    let size = circle.len();
+    let mut fixtures = Vec::with_capacity(count * (count - 1) / 2);
+    for round in 0..size - 1 {
+        for index in 0..size / 2 {
+            let fixture = Fixture { round, sources: [circle[index], circle[size - 1 - index]] };
+            if !fixture.is_bye() {
+                fixtures.push(fixture);
+            }
+        }
+        circle[1..].rotate_right(1);
+    }
+    fixtures
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use yare::parameterized;
+
+    #[test]
+    fn seeds() {
+        assert_eq!(seeding(1), vec![0]);
+        assert_eq!(seeding(4), vec![0, 3, 1, 2]);
+        assert_eq!(seeding(8), vec![0, 7, 3, 4, 1, 6, 2, 5]);
+    }
+
+    #[test]
+    fn eliminates() {
+        let fixtures = elimination(6);
+        assert_eq!(
+            fixtures,
+            vec![
+                Fixture { round: 0, sources: [Source::Seed(0), Source::Bye] },
+                Fixture { round: 0, sources: [Source::Seed(3), Source::Seed(4)] },
+                Fixture { round: 0, sources: [Source::Seed(1), Source::Bye] },
+                Fixture { round: 0, sources: [Source::Seed(2), Source::Seed(5)] },
+                Fixture { round: 1, sources: [Source::Winner(0), Source::Winner(1)] },
+                Fixture { round: 1, sources: [Source::Winner(2), Source::Winner(3)] },
+                Fixture { round: 2, sources: [Source::Winner(4), Source::Winner(5)] },
+            ]
+        );
+        assert_eq!(elimination(2), vec![Fixture { round: 0, sources: [Source::Seed(0), Source::Seed(1)] }]);
+        assert_eq!(elimination(17).len(), 31);
+    }
+
+    #[parameterized(
+        two = {2},
+        odd = {7},
+        even = {8},
+        many = {33},
+    )]
+    fn everyone_plays_everyone(count: usize) {
+        let fixtures = round_robin(count);
+        assert_eq!(fixtures.len(), count * (count - 1) / 2);
+        let pairs = fixtures
+            .iter()
+            .map(|fixture| match fixture.sources {
+                [Source::Seed(first), Source::Seed(second)] => (first.min(second), first.max(second)),
+                sources => panic!("Expected two seeds, not {sources:?}"),
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(pairs.len(), fixtures.len());
+        let rounds = if count % 2 == 0 { count - 1 } else { count };
+        for round in 0..rounds {
+            let seeds = fixtures.iter().filter(|fixture| fixture.round == round).flat_map(|fixture| fixture.sources).collect::<Vec<_>>();
+            // nobody plays twice in a round, and only one sits out if there's an odd number
+            assert_eq!(seeds.len(), count / 2 * 2);
+            assert_eq!(seeds.iter().collect::<HashSet<_>>().len(), seeds.len());
+        }
+        assert!(fixtures.iter().all(|fixture| fixture.round < rounds));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,163 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Passing binary data, like images and saved games, to and from python without copying it where it can't change.
+use std::ffi::CString;
+use std::ops::Deref;
+use std::os::raw::c_int;
+use std::ptr;
+
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyBufferError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use pyo3::ffi;
+
+/// Bytes made in rust, which python reads in place through the buffer protocol, so `memoryview`,
+/// `bytes`, `io.BytesIO`, `discord.File` and PIL take them without their own copy being made first.
+#[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Buffer {
+    data: Vec<u8>,
+}
+
+impl From<Vec<u8>> for Buffer {
+    fn from(data: Vec<u8>) -> Self {
+        Buffer { data }
+    }
+}
+
+impl Deref for Buffer {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Buffer {
+    /// Lend the bytes to python, read only, for as long as the view of them is held.
+    ///
+    /// # Safety
+    ///
+    /// `view` has to be a buffer python is asking to be filled in, which it is when python calls this.
+    unsafe fn __getbuffer__(slf: &PyCell<Self>, view: *mut ffi::Py_buffer, flags: c_int) -> PyResult<()> {
+        if view.is_null() {
+            return Err(PyBufferError::new_err("The view is null"));
+        }
+        if flags & ffi::PyBUF_WRITABLE == ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("The bytes are read only"));
+        }
+        // the view keeps the buffer alive, and the bytes never change once made, so the pointer stays good
+        ffi::Py_INCREF(slf.as_ptr());
+        (*view).obj = slf.as_ptr();
+        let data = &slf.borrow().data;
+        (*view).buf = data.as_ptr() as *mut _;
+        (*view).len = data.len() as isize;
+        (*view).readonly = 1;
+        (*view).itemsize = 1;
+        (*view).format = if flags & ffi::PyBUF_FORMAT == ffi::PyBUF_FORMAT {
+            CString::new("B").expect("B has no nul").into_raw()
+        } else {
+            ptr::null_mut()
+        };
+        (*view).ndim = 1;
+        (*view).shape = if flags & ffi::PyBUF_ND == ffi::PyBUF_ND { &mut (*view).len } else { ptr::null_mut() };
+        (*view).strides = if flags & ffi::PyBUF_STRIDES == ffi::PyBUF_STRIDES { &mut (*view).itemsize } else { ptr::null_mut() };
+        (*view).suboffsets = ptr::null_mut();
+        (*view).internal = ptr::null_mut();
+        Ok(())
+    }
+
+    /// # Safety
+    ///
+    /// `view` has to be one filled in by `__getbuffer__`, which it is when python calls this.
+    unsafe fn __releasebuffer__(&self, view: *mut ffi::Py_buffer) {
+        if !(*view).format.is_null() {
+            drop(CString::from_raw((*view).format));
+        }
+    }
+
+    /// A copy of the bytes, for when they have to be a `bytes`, like as a dict key.
+    fn __bytes__<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        PyBytes::new(py, &self.data)
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Buffer(len={})", self.data.len())
+    }
+}
+
+/// Binary data from python: a `bytes`, a `bytearray`, a `memoryview`, a [`Buffer`], or anything else with
+/// the buffer protocol.
+///
+/// It holds on to the buffer rather than borrowing it, so it can be sent to another thread. Only read only
+/// buffers of contiguous bytes, like `bytes` and [`Buffer`], are read in place. A `bytearray`, or a view
+/// of one, could be written to by python while it's read without the GIL, so those are copied first.
+pub(crate) struct Bytes(Held);
+
+enum Held {
+    /// A read only buffer of contiguous bytes, which nothing can change while it's held.
+    Borrowed(PyBuffer<u8>),
+    Copied(Vec<u8>),
+}
+
+impl<'source> FromPyObject<'source> for Bytes {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let buffer = PyBuffer::<u8>::get(ob)?;
+        if buffer.readonly() && buffer.is_c_contiguous() {
+            return Ok(Bytes(Held::Borrowed(buffer)));
+        }
+        Ok(Bytes(Held::Copied(buffer.to_vec(ob.py())?)))
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match &self.0 {
+            // SAFETY: the buffer was checked to be read only contiguous bytes, and is held until it's dropped
+            Held::Borrowed(buffer) => unsafe { std::slice::from_raw_parts(buffer.buf_ptr() as *const u8, buffer.len_bytes()) },
+            Held::Copied(data) => data,
+        }
+    }
+}
+
+/// Text, which is read as UTF-8, or [`Bytes`], for what can be either, like a payload or a secret.
+#[derive(FromPyObject)]
+pub(crate) enum Data {
+    #[pyo3(transparent)]
+    Text(String),
+    #[pyo3(transparent)]
+    Raw(Bytes),
+}
+
+impl Data {
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        match self {
+            Data::Text(text) => text.as_bytes(),
+            Data::Raw(bytes) => bytes,
+        }
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffer() {
+        let buffer = Buffer::from(vec![1, 2, 3]);
+        assert_eq!(&*buffer, &[1, 2, 3]);
+        assert_eq!(buffer.__len__(), 3);
+        assert_eq!(buffer.__repr__(), "Buffer(len=3)");
+    }
+}
+// COV_EXCL_STOP
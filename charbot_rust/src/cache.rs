@@ -0,0 +1,384 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::ratelimit::Key;
+
+/// The slot of no entry, at the ends of the list.
+const NIL: usize = usize::MAX;
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64())
+}
+
+fn check_ttl(ttl: Option<f64>) -> Result<(), String> {
+    match ttl {
+        Some(ttl) if !ttl.is_finite() || ttl <= 0.0 => Err(format!("Invalid TTL {ttl}, it must be more than 0 seconds")),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug)]
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    expires: f64,
+}
+
+/// Entries by key, in a list from the most recently used to the least, so the least recently used is
+/// found and each entry is moved to the front without looking through the others.
+///
+/// The list is kept in slots, the links of each slot to the ones before and after it, so the slots of
+/// removed entries are reused rather than allocating for each entry. The keys are the keys python passes
+/// unless the cache is used from rust with keys of its own.
+#[derive(Debug)]
+pub(crate) struct Store<V, K = Key> {
+    size: usize,
+    ttl: Option<f64>,
+    index: HashMap<K, usize>,
+    entries: Vec<Option<Entry<K, V>>>,
+    links: Vec<(usize, usize)>,
+    free: Vec<usize>,
+    head: usize,
+    tail: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl<V, K: Clone + Eq + Hash> Store<V, K> {
+    pub(crate) fn new(size: usize, ttl: Option<f64>) -> Result<Self, String> {
+        if size == 0 {
+            return Err(format!("Invalid size {size}, it must be at least 1"));
+        }
+        check_ttl(ttl)?;
+        Ok(Store {
+            size,
+            ttl,
+            index: HashMap::new(),
+            entries: Vec::new(),
+            links: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            hits: 0,
+            misses: 0,
+        })
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (previous, next) = self.links[slot];
+        match previous {
+            NIL => self.head = next,
+            previous => self.links[previous].1 = next,
+        }
+        match next {
+            NIL => self.tail = previous,
+            next => self.links[next].0 = previous,
+        }
+    }
+
+    fn push_front(&mut self, slot: usize) {
+        self.links[slot] = (NIL, self.head);
+        match self.head {
+            NIL => self.tail = slot,
+            head => self.links[head].0 = slot,
+        }
+        self.head = slot;
+    }
+
+    fn remove_slot(&mut self, slot: usize) -> Entry<K, V> {
+        self.unlink(slot);
+        let entry = self.entries[slot].take().expect("Every slot in the list has an entry");
+        self.index.remove(&entry.key);
+        self.free.push(slot);
+        entry
+    }
+
+    /// The slot of the entry for `key`, unless there is none or it has expired, when it's removed.
+    fn live(&mut self, key: &K, now: f64) -> Option<usize> {
+        let slot = *self.index.get(key)?;
+        if self.entries[slot].as_ref().is_some_and(|entry| entry.expires <= now) {
+            self.remove_slot(slot);
+            return None;
+        }
+        Some(slot)
+    }
+
+    /// The value for `key`, which becomes the most recently used, counting a hit or a miss.
+    pub(crate) fn get(&mut self, key: &K, now: f64) -> Option<&V> {
+        let Some(slot) = self.live(key, now) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.unlink(slot);
+        self.push_front(slot);
+        self.entries[slot].as_ref().map(|entry| &entry.value)
+    }
+
+    /// Set `key` to `value`, expiring after `ttl` or the TTL of the store, and make room for it by
+    /// removing the least recently used entry if the store is full.
+    pub(crate) fn set(&mut self, key: K, value: V, ttl: Option<f64>, now: f64) -> Result<(), String> {
+        check_ttl(ttl)?;
+        let expires = ttl.or(self.ttl).map_or(f64::INFINITY, |ttl| now + ttl);
+        if let Some(&slot) = self.index.get(&key) {
+            self.entries[slot] = Some(Entry { key, value, expires });
+            self.unlink(slot);
+            self.push_front(slot);
+            return Ok(());
+        }
+        if self.index.len() >= self.size {
+            self.remove_slot(self.tail);
+        }
+        let slot = self.free.pop().unwrap_or_else(|| {
+            self.entries.push(None);
+            self.links.push((NIL, NIL));
+            self.entries.len() - 1
+        });
+        self.index.insert(key.clone(), slot);
+        self.entries[slot] = Some(Entry { key, value, expires });
+        self.push_front(slot);
+        Ok(())
+    }
+
+    /// Remove the entry for `key` and get its value, if it hasn't expired.
+    pub(crate) fn pop(&mut self, key: &K, now: f64) -> Option<V> {
+        let slot = self.live(key, now)?;
+        Some(self.remove_slot(slot).value)
+    }
+
+    pub(crate) fn contains(&self, key: &K, now: f64) -> bool {
+        self.index.get(key).and_then(|slot| self.entries[*slot].as_ref()).is_some_and(|entry| entry.expires > now)
+    }
+
+    /// Remove every entry that has expired by `now`, and get how many there were.
+    pub(crate) fn expire(&mut self, now: f64) -> usize {
+        let expired = self.index.values().copied().filter(|slot| self.entries[*slot].as_ref().is_some_and(|entry| entry.expires <= now)).collect::<Vec<_>>();
+        for slot in &expired {
+            self.remove_slot(*slot);
+        }
+        expired.len()
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.index.clear();
+        self.entries.clear();
+        self.links.clear();
+        self.free.clear();
+        (self.head, self.tail) = (NIL, NIL);
+    }
+
+    /// How many entries there are, counting the ones that expired but weren't looked up since.
+    pub(crate) fn len(&self) -> usize {
+        self.index.len()
+    }
+}
+
+/// A cache of at most a number of entries, dropping the least recently used one to make room, where
+/// entries can expire after some time.
+#[pyclass(module = "cache")] // COV_EXCL_LINE
+pub struct TtlCache {
+    store: Store<PyObject>,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl TtlCache {
+    #[new]
+    #[pyo3(signature = (maxsize, ttl=None))]
+    fn py_new(maxsize: usize, ttl: Option<f64>) -> PyResult<Self> {
+        Ok(TtlCache { store: Store::new(maxsize, ttl).map_err(PyErr::new::<PyValueError, _>)? })
+    }
+
+    /// The value for a key, or the default if it isn't cached or has expired.
+    #[pyo3(signature = (key, default=None, now=None))]
+    fn get(&mut self, py: Python, key: Key, default: Option<PyObject>, now: Option<f64>) -> PyObject {
+        match self.store.get(&key, now.unwrap_or_else(self::now)) {
+            Some(value) => value.clone_ref(py),
+            None => default.unwrap_or_else(|| py.None()),
+        }
+    }
+
+    /// Cache a value for a key, expiring after the TTL given or the one of the cache.
+    #[pyo3(signature = (key, value, ttl=None, now=None))]
+    fn set(&mut self, key: Key, value: PyObject, ttl: Option<f64>, now: Option<f64>) -> PyResult<()> {
+        self.store.set(key, value, ttl, now.unwrap_or_else(self::now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Remove a key and get its value, or the default if it isn't cached or has expired.
+    #[pyo3(signature = (key, default=None, now=None))]
+    fn pop(&mut self, py: Python, key: Key, default: Option<PyObject>, now: Option<f64>) -> PyObject {
+        self.store.pop(&key, now.unwrap_or_else(self::now)).unwrap_or_else(|| default.unwrap_or_else(|| py.None()))
+    }
+
+    #[pyo3(signature = (now=None))]
+    fn expire(&mut self, now: Option<f64>) -> usize {
+        self.store.expire(now.unwrap_or_else(self::now))
+    }
+
+    fn clear(&mut self) {
+        self.store.clear();
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn maxsize(&self) -> usize {
+        self.store.size
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn ttl(&self) -> Option<f64> {
+        self.store.ttl
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn hits(&self) -> u64 {
+        self.store.hits
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn misses(&self) -> u64 {
+        self.store.misses
+    }
+
+    fn __contains__(&self, key: Key) -> bool {
+        self.store.contains(&key, now())
+    }
+
+    fn __len__(&self) -> usize {
+        self.store.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let ttl = self.store.ttl.map_or_else(|| String::from("None"), |ttl| ttl.to_string());
+        format!("TtlCache(maxsize={}, ttl={ttl}, entries={}, hits={}, misses={})", self.store.size, self.store.len(), self.store.hits, self.store.misses)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based caches that drop their least recently used entries and expire them";
+
+pub(crate) fn register_cache(py: Python, m: &PyModule) -> PyResult<()> {
+    let cache = PyModule::new(py, "_cache")?;
+    cache.add_class::<TtlCache>()?;
+    cache.add("__doc__", DOCSTRING)?;
+    m.add_submodule(cache)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(size: usize, ttl: Option<f64>) -> Store<u64> {
+        Store::new(size, ttl).expect("Expected a valid cache")
+    }
+
+    /// The keys from the most recently used to the least.
+    fn keys(store: &Store<u64>) -> Vec<u64> {
+        let slots = std::iter::successors(Some(store.head).filter(|slot| *slot != NIL), |slot| Some(store.links[*slot].1).filter(|next| *next != NIL));
+        slots.filter_map(|slot| store.entries[slot].as_ref()).map(|entry| match entry.key {
+            Key::Id(id) => id,
+            _ => unreachable!("The tests only use ids"),
+        }).collect()
+    }
+
+    fn id(id: u64) -> Key {
+        Key::Id(id)
+    }
+
+    #[test]
+    fn least_recently_used() {
+        let mut store = store(3, None);
+        for key in 1..=3 {
+            store.set(id(key), key * 10, None, 0.0).expect("Expected a valid TTL");
+        }
+        assert_eq!(keys(&store), vec![3, 2, 1]);
+        assert_eq!(store.get(&id(1), 0.0), Some(&10));
+        assert_eq!(keys(&store), vec![1, 3, 2]);
+        store.set(id(4), 40, None, 0.0).expect("Expected a valid TTL");
+        assert_eq!(keys(&store), vec![4, 1, 3]);
+        assert_eq!(store.get(&id(2), 0.0), None);
+        // setting a key again replaces it without making room
+        store.set(id(3), 31, None, 0.0).expect("Expected a valid TTL");
+        assert_eq!(keys(&store), vec![3, 4, 1]);
+        assert_eq!(store.get(&id(3), 0.0), Some(&31));
+        assert_eq!(store.len(), 3);
+    }
+
+    #[test]
+    fn expiry() {
+        let mut store = store(10, Some(5.0));
+        store.set(id(1), 1, None, 100.0).expect("Expected a valid TTL");
+        store.set(id(2), 2, Some(60.0), 100.0).expect("Expected a valid TTL");
+        assert!(store.contains(&id(1), 104.9));
+        assert!(!store.contains(&id(1), 105.0));
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.get(&id(1), 105.0), None);
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get(&id(2), 159.0), Some(&2));
+        assert_eq!(store.expire(160.0), 1);
+        assert_eq!(store.len(), 0);
+        let mut forever = self::store(1, None);
+        forever.set(id(1), 1, None, 0.0).expect("Expected a valid TTL");
+        assert_eq!(forever.get(&id(1), 1e15), Some(&1));
+    }
+
+    #[test]
+    fn pop() {
+        let mut store = store(2, None);
+        store.set(id(1), 1, None, 0.0).expect("Expected a valid TTL");
+        store.set(id(2), 2, Some(1.0), 0.0).expect("Expected a valid TTL");
+        assert_eq!(store.pop(&id(1), 0.0), Some(1));
+        assert_eq!(store.pop(&id(1), 0.0), None);
+        assert_eq!(store.pop(&id(2), 1.0), None);
+        assert_eq!(keys(&store), Vec::<u64>::new());
+        store.set(id(3), 3, None, 0.0).expect("Expected a valid TTL");
+        store.clear();
+        assert_eq!(store.len(), 0);
+        assert_eq!(store.get(&id(3), 0.0), None);
+    }
+
+    #[test]
+    fn stats() {
+        let mut store = store(2, None);
+        store.set(id(1), 1, None, 0.0).expect("Expected a valid TTL");
+        store.get(&id(1), 0.0);
+        store.get(&id(1), 0.0);
+        store.get(&id(2), 0.0);
+        // peeking or removing isn't a lookup
+        store.contains(&id(1), 0.0);
+        store.pop(&id(1), 0.0);
+        assert_eq!((store.hits, store.misses), (2, 1));
+    }
+
+    #[test]
+    fn reuses_slots() {
+        let mut store = store(4, None);
+        for key in 0..1000 {
+            store.set(id(key), key, None, 0.0).expect("Expected a valid TTL");
+            if key % 3 == 0 {
+                store.pop(&id(key), 0.0);
+            }
+        }
+        assert_eq!(store.entries.len(), 4);
+        assert_eq!(keys(&store), vec![998, 997, 995]);
+        let mut named = Store::new(1, None).expect("Expected a valid cache");
+        named.set(Key::Name(String::from("member")), "a", None, 0.0).expect("Expected a valid TTL");
+        named.set(Key::Parts(vec![id(1), Key::Name(String::from("guild"))]), "b", None, 0.0).expect("Expected a valid TTL");
+        assert_eq!(named.get(&Key::Parts(vec![id(1), Key::Name(String::from("guild"))]), 0.0), Some(&"b"));
+    }
+
+    #[test]
+    fn rejects() {
+        Store::<u64>::new(0, None).expect_err("Expected a size of 0 to be rejected");
+        Store::<u64>::new(1, Some(0.0)).expect_err("Expected a TTL of 0 to be rejected");
+        Store::<u64>::new(1, Some(f64::NAN)).expect_err("Expected a NaN TTL to be rejected");
+        assert_eq!(store(1, None).set(id(1), 1, Some(-1.0), 0.0), Err(String::from("Invalid TTL -1, it must be more than 0 seconds")));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,321 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod board; // COV_EXCL_LINE
+mod search;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::checkers::board::{Board, Piece, Side};
+use crate::persist::{self, Persist};
+use crate::metrics;
+
+/// How many moves ahead the computer looks by default, around 10ms a move.
+pub(crate) const DEFAULT_DEPTH: u8 = 6;
+/// The most moves ahead the computer can look, so a move never takes more than about 100ms.
+const MAX_DEPTH: u8 = 8;
+/// The moves in a row, by both sides, without a capture or a man moving that draw the game.
+///
+/// That is 40 moves each, as in the usual rules.
+const QUIET_MOVES: usize = 80;
+/// How many times the same position has to come up to draw the game.
+const REPETITIONS: usize = 3;
+
+// COV_EXCL_START
+#[pyclass(module = "checkers")]
+#[derive(Debug)]
+struct Game {
+    board: Board,
+    human: Side,
+    depth: u8,
+    /// The positions since the last capture or man moving, the current one last, for spotting draws.
+    history: Vec<Board>,
+}
+// COV_EXCL_STOP
+
+impl Game {
+    fn new(depth: u8, human_first: bool) -> Result<Self, String> {
+        if !(1..=MAX_DEPTH).contains(&depth) {
+            return Err(format!("Invalid depth {depth}, it must be from 1 to {MAX_DEPTH}"));
+        }
+        // black always goes first
+        let human = if human_first { Side::Black } else { Side::White };
+        let board = Board::new();
+        let mut game = Game {
+            history: vec![board.clone()],
+            board,
+            human,
+            depth,
+        };
+        if !human_first {
+            game.computer_move();
+        }
+        Ok(game)
+    }
+
+    fn apply(&mut self, chosen: &board::Move) {
+        if self.board.is_irreversible(chosen) {
+            self.history.clear();
+        }
+        self.board.play(chosen);
+        self.history.push(self.board.clone());
+    }
+
+    fn computer_move(&mut self) -> Option<String> {
+        if self.is_over() {
+            return None;
+        }
+        let chosen = search::best_move(&self.board, self.depth)?;
+        self.apply(&chosen);
+        Some(chosen.to_string())
+    }
+
+    /// Make the player's move, then the computer's if that didn't end the game.
+    fn make_move(&mut self, text: &str) -> Result<Option<String>, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        let chosen = self.board.parse_move(text)?;
+        self.apply(&chosen);
+        Ok(self.computer_move())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    history: Vec<Board>,
+    human: Side,
+    depth: u8,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "checkers";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { history: self.history.clone(), human: self.human, depth: self.depth }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        if !(1..=MAX_DEPTH).contains(&state.depth) {
+            return Err(format!("Invalid depth {}, it must be from 1 to {MAX_DEPTH}", state.depth));
+        }
+        if state.history.len() > QUIET_MOVES + 1 {
+            return Err(format!("{} positions without a capture or a man moving is more than a game can have", state.history.len()));
+        }
+        for position in &state.history {
+            position.check()?;
+        }
+        let board = state.history.last().cloned().ok_or_else(|| "The game has no position".to_string())?;
+        let game = Game { board, human: state.human, depth: state.depth, history: state.history };
+        // the computer always moves straight after the player, so a saved game is never waiting on it
+        if !game.is_over() && game.board.to_move != game.human {
+            return Err("It can't be the computer's turn in a saved game".to_string());
+        }
+        Ok(game)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Game {
+    #[new]
+    #[pyo3(signature = (depth=DEFAULT_DEPTH, human_first=true))]
+    fn __new__(depth: u8, human_first: bool) -> PyResult<Self> { // COV_EXCL_LINE
+        Self::new(depth, human_first).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The pieces, as 8 rows from the top, with None for the empty and light squares.
+    #[getter]
+    fn board(&self) -> Vec<Vec<Option<Piece>>> {
+        self.board.rows()
+    }
+
+    #[getter]
+    fn player(&self) -> Side {
+        self.human
+    }
+
+    fn play(&mut self, py: Python, r#move: &str) -> PyResult<Option<String>> {
+        metrics::observe("checkers.play", || {
+            // the computer's reply is the slow part, other threads can run meanwhile
+            py.allow_threads(|| self.make_move(r#move)).map_err(PyErr::new::<PyValueError, _>)
+        })
+    }
+
+    /// The moves the player can make, only captures if there are any, and none once the game is over.
+    fn legal_moves(&self) -> Vec<String> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        self.board.legal_moves().iter().map(ToString::to_string).collect()
+    }
+
+    /// The side that won, once the side to move has no pieces or nothing they can move.
+    fn winner(&self) -> Option<Side> {
+        self.board.winner()
+    }
+
+    /// Whether the same position came up three times, or 40 moves each went by without a capture or a man moving.
+    fn is_draw(&self) -> bool {
+        let repeated = self.history.iter().filter(|position| **position == self.board).count() >= REPETITIONS;
+        self.winner().is_none() && (repeated || self.history.len() > QUIET_MOVES)
+    }
+
+    fn is_over(&self) -> bool {
+        self.winner().is_some() || self.is_draw()
+    }
+
+    fn has_player_won(&self) -> bool {
+        self.winner() == Some(self.human)
+    }
+
+    fn has_player_lost(&self) -> bool {
+        self.winner() == Some(self.human.swap())
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_string()
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of checkers";
+
+pub(crate) fn register_checkers(py: Python, m: &PyModule) -> PyResult<()> {
+    let checkers = PyModule::new(py, "_checkers")?;
+    checkers.add_class::<Game>()?;
+    checkers.add_class::<Side>()?;
+    checkers.add_class::<Piece>()?;
+    checkers.add("__doc__", DOCSTRING)?;
+    m.add_submodule(checkers)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkers::board::SQUARES;
+
+    fn game(pieces: &[(usize, Side, bool)], human: Side) -> Game {
+        let mut board = Board { squares: [None; SQUARES], to_move: human };
+        for &(square, side, king) in pieces {
+            board.squares[square - 1] = Some(Piece { side, king });
+        }
+        Game { history: vec![board.clone()], board, human, depth: 2 }
+    }
+
+    #[test]
+    fn creator() {
+        Game::new(0, true).expect_err("Expected error");
+        Game::new(MAX_DEPTH + 1, true).expect_err("Expected error");
+        let human_first = Game::new(DEFAULT_DEPTH, true).expect("Failed to create game");
+        assert_eq!(human_first.player(), Side::Black);
+        assert_eq!(human_first.legal_moves().len(), 7);
+        let computer_first = Game::new(2, false).expect("Failed to create game");
+        assert_eq!(computer_first.player(), Side::White);
+        assert_eq!(computer_first.board.to_move, Side::White);
+        assert_ne!(computer_first.board, Board::new());
+    }
+
+    #[test]
+    fn play() {
+        let mut game = Game::new(2, true).expect("Failed to create game");
+        let reply = game.make_move("11-15").expect("Failed to play").expect("Expected a computer move");
+        assert!(game.board.parse_move(&reply).is_err(), "Expected {reply} to have been played already");
+        assert_eq!(game.board.to_move, Side::Black);
+        assert_eq!(game.board()[3][4], Some(Piece { side: Side::Black, king: false }));
+        game.make_move("11-15").expect_err("Expected the move to be rejected");
+        game.make_move("nonsense").expect_err("Expected the move to be rejected");
+        // moving a man can't lead back to an earlier position
+        assert_eq!(game.history.len(), 1);
+    }
+
+    #[test]
+    fn winning() {
+        // jumping the last white piece wins, and the computer has nothing to reply with
+        let mut game = game(&[(10, Side::Black, false), (14, Side::White, false)], Side::Black);
+        assert_eq!(game.make_move("10x17"), Ok(None));
+        assert!(game.is_over());
+        assert!(!game.is_draw());
+        assert_eq!(game.winner(), Some(Side::Black));
+        assert!(game.has_player_won());
+        assert!(!game.has_player_lost());
+        assert!(game.legal_moves().is_empty());
+        game.make_move("17-21").expect_err("Expected the game to be over");
+    }
+
+    #[test]
+    fn repetition() {
+        // kings shuffling back and forth come back to the same position
+        let mut game = game(&[(1, Side::Black, true), (32, Side::White, true)], Side::Black);
+        for text in ["1-6", "32-27", "6-1", "27-32", "1-6", "32-27", "6-1", "27-32"] {
+            assert!(!game.is_draw());
+            let chosen = game.board.parse_move(text).expect("Legal");
+            game.apply(&chosen);
+        }
+        assert!(game.is_draw());
+        assert!(game.is_over());
+        assert_eq!(game.winner(), None);
+        game.make_move("1-6").expect_err("Expected the game to be over");
+    }
+
+    #[test]
+    fn quiet_moves() {
+        let mut game = game(&[(1, Side::Black, true), (32, Side::White, true)], Side::Black);
+        game.history = (0..QUIET_MOVES).map(|_| Board::new()).chain([game.board.clone()]).collect();
+        assert!(game.is_draw());
+        game.history.remove(0);
+        assert!(!game.is_draw());
+    }
+
+    #[test]
+    fn persists() {
+        let mut game = Game::new(2, false).expect("Failed to create game");
+        let opening = game.legal_moves()[0].clone();
+        game.make_move(&opening).expect("Failed to play");
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Game = persist::from_json(&json).expect("Failed to load");
+        assert_eq!((&loaded.board, &loaded.history), (&game.board, &game.history));
+        assert_eq!((loaded.human, loaded.depth), (Side::White, 2));
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        let mut loaded: Game = persist::from_bytes(&bytes).expect("Failed to load");
+        let next = game.legal_moves()[0].clone();
+        assert_eq!(loaded.make_move(&next), game.make_move(&next));
+        let mut state = game.save();
+        state.human = Side::Black;
+        Game::load(state).expect_err("Expected it being the computer's turn to be rejected");
+        let mut state = game.save();
+        state.depth = 0;
+        Game::load(state).expect_err("Expected the depth to be rejected");
+        let mut state = game.save();
+        state.history.clear();
+        Game::load(state).expect_err("Expected the missing position to be rejected");
+        let mut state = game.save();
+        state.history[0].squares[12] = Some(Piece { side: Side::White, king: false });
+        state.history[0].squares[13] = Some(Piece { side: Side::White, king: false });
+        Game::load(state).expect_err("Expected too many pieces to be rejected");
+    }
+}
+// COV_EXCL_STOP
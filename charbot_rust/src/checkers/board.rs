@@ -0,0 +1,456 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::fmt::{Display, Error, Formatter};
+use pyo3::{pyclass, pymethods};
+use serde::{Deserialize, Serialize};
+// COV_EXCL_STOP
+
+/// The dark squares, the only ones played on.
+pub const SQUARES: usize = 32;
+/// The rows of the board, and the columns.
+pub const SIZE: usize = 8;
+/// The pieces each side starts with, on the three rows nearest them.
+pub const PIECES: usize = 12;
+
+#[pyclass(module = "checkers")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Side { // COV_EXCL_LINE
+    /// Starts at the top on squares 1 to 12, and moves first.
+    Black,
+    /// Starts at the bottom on squares 21 to 32.
+    White,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Side {
+    pub fn swap(&self) -> Self {
+        match self { // COV_EXCL_LINE
+            Self::Black => Self::White,
+            Self::White => Self::Black,
+        }
+    }
+}
+
+impl Side {
+    /// Which way the men of the side move down the rows.
+    fn forward(self) -> isize {
+        match self {
+            Side::Black => 1,
+            Side::White => -1,
+        }
+    }
+
+    /// The row the men of the side are crowned on, the far side of the board.
+    fn king_row(self) -> usize {
+        match self {
+            Side::Black => SIZE - 1,
+            Side::White => 0,
+        }
+    }
+}
+
+#[pyclass(module = "checkers")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub struct Piece {
+    #[pyo3(get)]
+    pub side: Side,
+    /// Whether the piece was crowned, kings move backwards as well as forwards.
+    #[pyo3(get)]
+    pub king: bool, // COV_EXCL_LINE
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Piece {
+    /// The letter the piece is drawn as, a capital for a king.
+    #[getter]
+    pub fn letter(&self) -> char {
+        match (self.side, self.king) {
+            (Side::Black, false) => 'b',
+            (Side::Black, true) => 'B',
+            (Side::White, false) => 'w',
+            (Side::White, true) => 'W',
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Piece({:?}, king={})", self.side, self.king)
+    }
+}
+
+impl Piece {
+    fn man(side: Side) -> Self {
+        Piece { side, king: false }
+    }
+
+    /// The (row, column) steps the piece can move along.
+    fn directions(self) -> impl Iterator<Item = (isize, isize)> {
+        let forward = self.side.forward();
+        let rows = if self.king { vec![forward, -forward] } else { vec![forward] };
+        rows.into_iter().flat_map(|row| [(row, -1), (row, 1)])
+    }
+}
+
+/// The row and column of `square`, which counts the dark squares from 0 at the top left.
+///
+/// The top row has its dark squares in the odd columns, so the rows start on alternate sides.
+pub fn coordinates(square: usize) -> (usize, usize) {
+    let row = square / 4;
+    (row, 2 * (square % 4) + (1 - row % 2))
+}
+
+/// The square at `row`, `column`, if that is a dark square on the board.
+pub fn square_at(row: isize, column: isize) -> Option<usize> {
+    let on_board = (0..SIZE as isize).contains(&row) && (0..SIZE as isize).contains(&column);
+    (on_board && (row + column) % 2 == 1).then(|| row as usize * 4 + column as usize / 2)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Move {
+    /// The squares the piece stops on, starting with where it was.
+    pub path: Vec<usize>,
+    /// The squares of the pieces jumped, in the order they were jumped.
+    pub captured: Vec<usize>,
+}
+
+impl Move {
+    pub fn from(&self) -> usize {
+        self.path[0]
+    }
+
+    pub fn to(&self) -> usize {
+        self.path[self.path.len() - 1]
+    }
+
+    pub fn is_capture(&self) -> bool {
+        !self.captured.is_empty()
+    }
+}
+
+// COV_EXCL_START
+/// The usual notation, with the squares numbered from 1, like `11-15` or `15x24x31`.
+impl Display for Move {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        let separator = if self.is_capture() { "x" } else { "-" };
+        let squares = self.path.iter().map(|square| (square + 1).to_string()).collect::<Vec<_>>();
+        formatter.write_str(&squares.join(separator))
+    }
+}
+// COV_EXCL_STOP
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)] // COV_EXCL_LINE
+pub struct Board {
+    pub(crate) squares: [Option<Piece>; SQUARES],
+    pub(crate) to_move: Side,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        let mut squares = [None; SQUARES];
+        squares[..PIECES].fill(Some(Piece::man(Side::Black)));
+        squares[SQUARES - PIECES..].fill(Some(Piece::man(Side::White)));
+        Board { squares, to_move: Side::Black }
+    }
+
+    /// Check a board from a save could come up in a game.
+    pub fn check(&self) -> Result<(), String> {
+        for side in [Side::Black, Side::White] {
+            let pieces = self.squares.iter().flatten().filter(|piece| piece.side == side).count();
+            if pieces > PIECES {
+                return Err(format!("{side:?} has {pieces} pieces, more than the {PIECES} it starts with"));
+            }
+        }
+        let uncrowned = (0..SQUARES).find(|square| {
+            self.squares[*square].is_some_and(|piece| !piece.king && coordinates(*square).0 == piece.side.king_row())
+        });
+        match uncrowned {
+            Some(square) => Err(format!("The man on square {} should have been crowned", square + 1)),
+            None => Ok(()),
+        }
+    }
+
+    /// Every move the side to move can make, which are only captures if any piece can capture.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let pieces = (0..SQUARES).filter_map(|square| Some((square, self.squares[square]?)));
+        let own = pieces.filter(|(_, piece)| piece.side == self.to_move).collect::<Vec<_>>();
+        let mut captures = Vec::new();
+        for (square, piece) in &own {
+            self.jumps(*piece, &mut vec![*square], &mut Vec::new(), &mut captures);
+        }
+        if !captures.is_empty() {
+            return captures;
+        }
+        own.into_iter()
+            .flat_map(|(square, piece)| {
+                let (row, column) = coordinates(square);
+                piece.directions().filter_map(move |(row_step, column_step)| {
+                    square_at(row as isize + row_step, column as isize + column_step)
+                        .filter(|to| self.squares[*to].is_none())
+                        .map(|to| Move { path: vec![square, to], captured: Vec::new() })
+                })
+            })
+            .collect()
+    }
+
+    /// Add every capture by `piece` carrying on from the end of `path` to `moves`.
+    ///
+    /// A capture has to go on while there is anything left to jump, but the jumped pieces stay on the
+    /// board until it is over, so they can't be jumped twice or landed on.
+    fn jumps(&self, piece: Piece, path: &mut Vec<usize>, captured: &mut Vec<usize>, moves: &mut Vec<Move>) {
+        let (row, column) = coordinates(path[path.len() - 1]);
+        // a man crowned by a capture stops there
+        let crowned = !piece.king && row == piece.side.king_row();
+        let mut extended = false;
+        for (row_step, column_step) in piece.directions().filter(|_| !crowned) {
+            let (row, column) = (row as isize, column as isize);
+            let (Some(over), Some(to)) = (
+                square_at(row + row_step, column + column_step),
+                square_at(row + 2 * row_step, column + 2 * column_step),
+            ) else {
+                continue;
+            };
+            let jumpable = self.squares[over].is_some_and(|jumped| jumped.side != piece.side) && !captured.contains(&over);
+            // the square the piece started from is empty while it moves
+            if !jumpable || (self.squares[to].is_some() && to != path[0]) {
+                continue;
+            }
+            extended = true;
+            path.push(to);
+            captured.push(over);
+            self.jumps(piece, path, captured, moves);
+            path.pop();
+            captured.pop();
+        }
+        if !extended && !captured.is_empty() {
+            moves.push(Move { path: path.clone(), captured: captured.clone() });
+        }
+    }
+
+    /// Make `chosen`, which has to be one of the legal moves.
+    pub fn play(&mut self, chosen: &Move) {
+        let mut piece = self.squares[chosen.from()].take().expect("A legal move starts from a piece");
+        for square in &chosen.captured {
+            self.squares[*square] = None;
+        }
+        if coordinates(chosen.to()).0 == piece.side.king_row() {
+            piece.king = true;
+        }
+        self.squares[chosen.to()] = Some(piece);
+        self.to_move = self.to_move.swap();
+    }
+
+    /// Whether `chosen` can never be undone, a capture or a man moving forwards, so no earlier position can come up again.
+    pub fn is_irreversible(&self, chosen: &Move) -> bool {
+        chosen.is_capture() || self.squares[chosen.from()].is_some_and(|piece| !piece.king)
+    }
+
+    /// Find the legal move `text` is, like `11-15`, `15x24x31`, or just `15x31` if only one capture goes there.
+    pub fn parse_move(&self, text: &str) -> Result<Move, String> {
+        let invalid = || format!("Invalid move {text:?}, expected squares from 1 to 32 like 11-15 or 15x24");
+        let capture = text.contains(['x', 'X']);
+        let path = text
+            .split(['-', 'x', 'X'])
+            .map(|square| match square.trim().parse::<usize>() {
+                Ok(square @ 1..=SQUARES) => Ok(square - 1),
+                _ => Err(invalid()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if path.len() < 2 || (path.len() > 2 && !capture) {
+            return Err(invalid());
+        }
+        let moves = self.legal_moves();
+        if let Some(found) = moves.iter().find(|legal| legal.path == path) {
+            return Ok(found.clone());
+        }
+        let mut ends = moves.into_iter().filter(|legal| path.len() == 2 && legal.from() == path[0] && legal.to() == path[1]);
+        match (ends.next(), ends.next()) {
+            (Some(found), None) => Ok(found),
+            (Some(_), Some(_)) => Err(format!("More than one capture goes from {} to {}, give every square it stops on", path[0] + 1, path[1] + 1)),
+            _ if !capture && self.legal_moves().iter().any(Move::is_capture) => Err(format!("{text} isn't legal, a capture has to be made")),
+            _ => Err(format!("{text} isn't a legal move")),
+        }
+    }
+
+    /// The side that won, once the side to move has no pieces or nothing they can move.
+    pub fn winner(&self) -> Option<Side> {
+        self.legal_moves().is_empty().then(|| self.to_move.swap())
+    }
+
+    /// The pieces as 8 rows from the top, with `None` for the empty and light squares.
+    pub fn rows(&self) -> Vec<Vec<Option<Piece>>> {
+        (0..SIZE as isize)
+            .map(|row| (0..SIZE as isize).map(|column| square_at(row, column).and_then(|square| self.squares[square])).collect())
+            .collect()
+    }
+}
+
+// COV_EXCL_START
+/// The board as rows from the top, with `.` for the empty dark squares and a space for the light ones.
+impl Display for Board {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        let rows = (0..SIZE as isize)
+            .map(|row| {
+                (0..SIZE as isize)
+                    .map(|column| match square_at(row, column) {
+                        Some(square) => self.squares[square].map_or('.', |piece| piece.letter()),
+                        None => ' ',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        formatter.write_str(&rows.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A board with only `pieces`, as (square from 1, side, king), and `to_move` to play.
+    fn board(pieces: &[(usize, Side, bool)], to_move: Side) -> Board {
+        let mut board = Board { squares: [None; SQUARES], to_move };
+        for &(square, side, king) in pieces {
+            board.squares[square - 1] = Some(Piece { side, king });
+        }
+        board
+    }
+
+    fn notation(board: &Board) -> Vec<String> {
+        let mut moves = board.legal_moves().iter().map(Move::to_string).collect::<Vec<_>>();
+        moves.sort();
+        moves
+    }
+
+    #[test]
+    fn squares() {
+        assert_eq!(coordinates(0), (0, 1));
+        assert_eq!(coordinates(4), (1, 0));
+        assert_eq!(coordinates(31), (7, 6));
+        for square in 0..SQUARES {
+            let (row, column) = coordinates(square);
+            assert_eq!(square_at(row as isize, column as isize), Some(square));
+        }
+        assert_eq!(square_at(0, 0), None);
+        assert_eq!(square_at(-1, 1), None);
+        assert_eq!(square_at(7, 8), None);
+    }
+
+    #[test]
+    fn opening() {
+        let board = Board::new();
+        assert_eq!(notation(&board), vec!["10-14", "10-15", "11-15", "11-16", "12-16", "9-13", "9-14"]);
+        assert_eq!(board.to_string().lines().next(), Some(" b b b b"));
+        assert_eq!(board.to_string().lines().nth(3), Some(". . . . "));
+        assert_eq!(board.rows()[7][0], Some(Piece::man(Side::White)));
+        assert_eq!(board.winner(), None);
+        board.check().expect("Expected the opening board to be valid");
+    }
+
+    #[test]
+    fn forced_capture() {
+        // both black men can capture 14, so 9-13 isn't allowed
+        let board = board(&[(10, Side::Black, false), (9, Side::Black, false), (14, Side::White, false)], Side::Black);
+        assert_eq!(notation(&board), vec!["10x17", "9x18"]);
+        board.parse_move("9-13").expect_err("Expected the capture to be forced");
+        assert_eq!(board.parse_move("10x17").map(|found| found.captured), Ok(vec![13]));
+        assert_eq!(board.parse_move("10-17").map(|found| found.to()), Ok(16));
+    }
+
+    #[test]
+    fn multi_jump() {
+        // 1 jumps 6 to 10, then has to carry on over 15 to 19
+        let mut board = board(&[(1, Side::Black, false), (6, Side::White, false), (15, Side::White, false)], Side::Black);
+        assert_eq!(notation(&board), vec!["1x10x19"]);
+        let chosen = board.parse_move("1x19").expect("Expected the only capture to match");
+        assert!(board.is_irreversible(&chosen));
+        board.play(&chosen);
+        assert_eq!(board.squares.iter().flatten().count(), 1);
+        assert_eq!(board.squares[18], Some(Piece::man(Side::Black)));
+        assert_eq!(board.winner(), Some(Side::Black));
+    }
+
+    #[test]
+    fn branching_captures() {
+        // 10 can get to 26 around either side
+        let board = board(
+            &[(10, Side::Black, false), (14, Side::White, false), (15, Side::White, false), (22, Side::White, false), (23, Side::White, false)],
+            Side::Black,
+        );
+        assert_eq!(notation(&board), vec!["10x17x26", "10x19x26"]);
+        board.parse_move("10x26").expect_err("Expected the capture to be ambiguous");
+        assert_eq!(board.parse_move("10x19x26").map(|found| found.captured), Ok(vec![14, 22]));
+    }
+
+    #[test]
+    fn crowning() {
+        // the man crowned on 31 can't carry on capturing backwards over 27 that move
+        let mut board = board(&[(22, Side::Black, false), (26, Side::White, false), (27, Side::White, false)], Side::Black);
+        assert_eq!(notation(&board), vec!["22x31"]);
+        let chosen = board.parse_move("22x31").expect("Expected the capture to be legal");
+        board.play(&chosen);
+        assert_eq!(board.squares[30], Some(Piece { side: Side::Black, king: true }));
+        assert_eq!(board.to_move, Side::White);
+        board.to_move = Side::Black;
+        assert_eq!(notation(&board), vec!["31x24"]);
+    }
+
+    #[test]
+    fn kings() {
+        let king = board(&[(15, Side::White, true)], Side::White);
+        assert_eq!(notation(&king), vec!["15-10", "15-11", "15-18", "15-19"]);
+        let man = board(&[(15, Side::White, false)], Side::White);
+        assert_eq!(notation(&man), vec!["15-10", "15-11"]);
+        let chosen = king.parse_move("15-18").expect("Legal");
+        assert!(!king.is_irreversible(&chosen));
+        assert!(man.is_irreversible(&man.parse_move("15-11").expect("Legal")));
+    }
+
+    #[test]
+    fn king_returns_to_start() {
+        // a king can go around the four pieces back to where it started
+        let board = board(
+            &[(10, Side::Black, true), (14, Side::White, false), (15, Side::White, false), (22, Side::White, false), (23, Side::White, false)],
+            Side::Black,
+        );
+        let around = board.legal_moves().into_iter().find(|found| found.captured.len() == 4).expect("Expected a capture of all four");
+        assert_eq!(around.from(), around.to());
+        let mut after = board.clone();
+        after.play(&around);
+        assert_eq!(after.squares.iter().flatten().count(), 1);
+        assert_eq!(after.squares[9], Some(Piece { side: Side::Black, king: true }));
+    }
+
+    #[test]
+    fn blocked() {
+        // the white man on 32 is stuck behind the black men on 27 and 28, and can't jump 27 onto 23
+        let board = board(&[(32, Side::White, false), (28, Side::Black, false), (27, Side::Black, false), (23, Side::Black, false)], Side::White);
+        assert_eq!(board.winner(), Some(Side::Black));
+    }
+
+    #[test]
+    fn parsing() {
+        let board = Board::new();
+        for text in ["", "11", "11-", "0-4", "11-33", "a-b", "11-15-19"] {
+            board.parse_move(text).expect_err("Expected the move to be rejected");
+        }
+        board.parse_move("11-19").expect_err("Expected the illegal move to be rejected");
+        assert_eq!(board.parse_move(" 11 - 15 ").map(|found| found.to_string()), Ok("11-15".to_string()));
+    }
+
+    #[test]
+    fn checks() {
+        let mut crowded = Board::new();
+        crowded.squares[12] = Some(Piece::man(Side::Black));
+        crowded.check().expect_err("Expected 13 pieces to be rejected");
+        board(&[(30, Side::Black, false)], Side::White).check().expect_err("Expected the uncrowned man to be rejected");
+        board(&[(30, Side::Black, true)], Side::White).check().expect("Expected the king to be allowed");
+    }
+}
+// COV_EXCL_STOP
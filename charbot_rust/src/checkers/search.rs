@@ -0,0 +1,136 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::checkers::board::{coordinates, Board, Move, Side, SIZE};
+
+/// The score of a win, more than any position can get, a sooner win scores a little higher.
+const WIN: i32 = 1_000_000;
+/// More than any score, for the bounds before anything is searched.
+const INFINITY: i32 = 2 * WIN;
+/// What a man is worth, kings are worth more since they can go both ways.
+const MAN: i32 = 100;
+const KING: i32 = 160;
+/// What each row a man has moved forwards is worth, so men head for the king row.
+const ADVANCE: i32 = 3;
+
+/// The best move for the side to move, looking `depth` moves ahead, or `None` if there are no moves.
+///
+/// This is negamax with alpha-beta pruning, like the connect four search, positions where nobody has
+/// won by then are scored by [`evaluate`]. Captures are searched past the depth until none are left,
+/// so a position isn't scored halfway through an exchange.
+pub fn best_move(board: &Board, depth: u8) -> Option<Move> {
+    let mut best = None;
+    let mut alpha = -INFINITY;
+    for candidate in board.legal_moves() {
+        let mut next = board.clone();
+        next.play(&candidate);
+        let score = -negamax(&next, depth.saturating_sub(1), -INFINITY, -alpha);
+        if score > alpha {
+            alpha = score;
+            best = Some(candidate);
+        }
+    }
+    best
+}
+
+/// The score of `board` for the side to move, as good as it gets for them within `depth` moves.
+fn negamax(board: &Board, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    let moves = board.legal_moves();
+    if moves.is_empty() {
+        // the side to move can't, which loses
+        return -(WIN + i32::from(depth));
+    }
+    if depth == 0 && !moves[0].is_capture() {
+        return evaluate(board);
+    }
+    for candidate in moves {
+        let mut next = board.clone();
+        next.play(&candidate);
+        let score = -negamax(&next, depth.saturating_sub(1), -beta, -alpha);
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// A guess at how good `board` is for the side to move, from the pieces each side has left.
+fn evaluate(board: &Board) -> i32 {
+    board
+        .squares
+        .iter()
+        .enumerate()
+        .filter_map(|(square, piece)| {
+            let piece = (*piece)?;
+            let row = coordinates(square).0 as i32;
+            let advanced = match piece.side {
+                Side::Black => row,
+                Side::White => SIZE as i32 - 1 - row,
+            };
+            let value = if piece.king { KING } else { MAN + ADVANCE * advanced };
+            Some(if piece.side == board.to_move { value } else { -value })
+        })
+        .sum()
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+    use crate::checkers::board::{Piece, SQUARES};
+
+    fn board(pieces: &[(usize, Side, bool)], to_move: Side) -> Board {
+        let mut board = Board { squares: [None; SQUARES], to_move };
+        for &(square, side, king) in pieces {
+            board.squares[square - 1] = Some(Piece { side, king });
+        }
+        board
+    }
+
+    fn best(board: &Board, depth: u8) -> Option<String> {
+        best_move(board, depth).map(|found| found.to_string())
+    }
+
+    #[test]
+    fn takes_the_most() {
+        // 1 can jump two pieces or 4 can jump one, and either way the capture is forced
+        let board = board(&[(1, Side::Black, false), (4, Side::Black, false), (6, Side::White, false), (15, Side::White, false), (8, Side::White, false)], Side::Black);
+        assert_eq!(board.legal_moves().len(), 2);
+        assert_eq!(best(&board, 1), Some("1x10x19".to_string()));
+        assert_eq!(best(&board, 4), Some("1x10x19".to_string()));
+    }
+
+    #[test]
+    fn keeps_its_pieces() {
+        // 18-22 or 18-23 walk into a jump from 26 or 27, 11-15 is safe
+        let board = board(&[(11, Side::Black, false), (18, Side::Black, false), (26, Side::White, false), (27, Side::White, false), (32, Side::White, false)], Side::Black);
+        let chosen = best(&board, 4).expect("Expected a move");
+        assert!(chosen.starts_with("11-"), "Expected 11 to move, not {chosen}");
+    }
+
+    #[test]
+    fn no_moves() {
+        let board = board(&[(5, Side::White, false)], Side::Black);
+        assert_eq!(best_move(&board, 4), None);
+    }
+
+    #[test]
+    fn evaluate_is_symmetric() {
+        let mut board = board(&[(10, Side::Black, true), (22, Side::White, false), (27, Side::White, false)], Side::Black);
+        let black = evaluate(&board);
+        board.to_move = Side::White;
+        assert_eq!(evaluate(&board), -black);
+        assert_eq!(evaluate(&Board::new()), 0);
+    }
+
+    #[test]
+    fn default_depth_is_fast() {
+        let start = Instant::now();
+        best_move(&Board::new(), crate::checkers::DEFAULT_DEPTH);
+        // around 10ms optimized, from the start where no capture narrows the moves, even in the unoptimized build
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,261 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+pub(crate) mod position; // COV_EXCL_LINE
+mod san; // COV_EXCL_LINE
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::chess::position::Position;
+use crate::metrics;
+
+/// Where a game stands for the side to move.
+#[pyclass(module = "chess")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Status { // COV_EXCL_LINE
+    /// The side to move has moves and isn't in check.
+    Ongoing,
+    /// The side to move is in check, but can get out of it.
+    Check,
+    /// The side to move is in check and can't get out of it, the game is lost for them.
+    Checkmate,
+    /// The side to move isn't in check but has no moves, the game is a draw.
+    Stalemate,
+}
+
+fn game_status(position: &Position) -> Status {
+    match (position.in_check(position.turn), position.legal_moves().is_empty()) {
+        (false, false) => Status::Ongoing,
+        (true, false) => Status::Check,
+        (true, true) => Status::Checkmate,
+        (false, true) => Status::Stalemate,
+    }
+}
+
+fn parse_fen(fen: &str) -> PyResult<Position> {
+    Position::from_fen(fen).map_err(PyErr::new::<PyValueError, _>)
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+validate_fen(fen, /)
+--
+
+Check a FEN describes a position that can be played from.
+
+Parameters
+----------
+fen : str
+    The position. The move counters can be left out, and default to 0 and 1.
+
+Returns
+-------
+str
+    The FEN written out in full, without any castling rights the king and rooks aren't in place for.
+
+Raises
+------
+ValueError
+    If the FEN is malformed, a side doesn't have exactly one king, a pawn is on the first or last
+    rank, or the side not to move is in check.
+")]
+fn validate_fen(fen: &str) -> PyResult<String> {
+    metrics::observe("chess.validate_fen", || Ok(parse_fen(fen)?.to_fen()))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+legal_moves(fen, /)
+--
+
+Every legal move in a position.
+
+Parameters
+----------
+fen : str
+    The position.
+
+Returns
+-------
+list[str]
+    The moves in UCI notation, like 'e2e4' or 'e7e8q', none if the game is over.
+
+Raises
+------
+ValueError
+    If the FEN isn't valid.
+")]
+fn legal_moves(fen: &str) -> PyResult<Vec<String>> {
+    metrics::observe("chess.legal_moves", || Ok(parse_fen(fen)?.legal_moves().into_iter().map(|mv| mv.to_uci()).collect()))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+is_legal(fen, move, /)
+--
+
+Check if a move can be played in a position.
+
+Parameters
+----------
+fen : str
+    The position.
+move : str
+    The move, in SAN like 'Nf3' or UCI like 'g1f3'. Check marks and annotations are ignored, and
+    castling can be written with zeros.
+
+Returns
+-------
+bool
+    True if the move is legal, False if it isn't or can't be read as a move.
+
+Raises
+------
+ValueError
+    If the FEN isn't valid.
+")]
+fn is_legal(fen: &str, r#move: &str) -> PyResult<bool> {
+    metrics::observe("chess.is_legal", || Ok(san::parse_move(&parse_fen(fen)?, r#move).is_ok()))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+apply_move(fen, move, /)
+--
+
+Play a move in a position.
+
+Parameters
+----------
+fen : str
+    The position.
+move : str
+    The move, in SAN like 'Nf3' or UCI like 'g1f3'. Check marks and annotations are ignored, and
+    castling can be written with zeros.
+
+Returns
+-------
+str
+    The FEN of the position after the move.
+
+Raises
+------
+ValueError
+    If the FEN isn't valid, or the move isn't legal.
+")]
+fn apply_move(fen: &str, r#move: &str) -> PyResult<String> {
+    metrics::observe("chess.apply_move", || {
+        let position = parse_fen(fen)?;
+        let mv = san::parse_move(&position, r#move).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(position.apply(mv).to_fen())
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+to_san(fen, move, /)
+--
+
+Write a move in standard algebraic notation, for move lists.
+
+Parameters
+----------
+fen : str
+    The position the move is played in.
+move : str
+    The move, in SAN or UCI.
+
+Returns
+-------
+str
+    The move in SAN, like 'Nbd7', 'exd5', 'e8=Q+' or 'O-O-O#'.
+
+Raises
+------
+ValueError
+    If the FEN isn't valid, or the move isn't legal.
+")]
+fn to_san(fen: &str, r#move: &str) -> PyResult<String> {
+    metrics::observe("chess.to_san", || {
+        let position = parse_fen(fen)?;
+        let mv = san::parse_move(&position, r#move).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(san::to_san(&position, mv))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+status(fen, /)
+--
+
+Check if the side to move is in check, checkmated or stalemated.
+
+Parameters
+----------
+fen : str
+    The position.
+
+Returns
+-------
+Status
+    Where the game stands for the side to move.
+
+Raises
+------
+ValueError
+    If the FEN isn't valid.
+")]
+fn status(fen: &str) -> PyResult<Status> {
+    metrics::observe("chess.status", || Ok(game_status(&parse_fen(fen)?)))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of the rules of chess";
+
+pub(crate) fn register_chess(py: Python, m: &PyModule) -> PyResult<()> {
+    let chess = PyModule::new(py, "_chess")?;
+    chess.add_class::<Status>()?;
+    chess.add_function(wrap_pyfunction!(validate_fen, chess)?)?;
+    chess.add_function(wrap_pyfunction!(legal_moves, chess)?)?;
+    chess.add_function(wrap_pyfunction!(is_legal, chess)?)?;
+    chess.add_function(wrap_pyfunction!(apply_move, chess)?)?;
+    chess.add_function(wrap_pyfunction!(to_san, chess)?)?;
+    chess.add_function(wrap_pyfunction!(status, chess)?)?;
+    chess.add("__doc__", DOCSTRING)?;
+    chess.add("STARTING_FEN", position::START_FEN)?;
+    m.add_submodule(chess)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::position::START_FEN;
+    use yare::parameterized;
+
+    #[parameterized(
+        start = {START_FEN, Status::Ongoing},
+        check = {"4k3/8/8/8/8/8/4r3/4K3 w - - 0 1", Status::Check},
+        back_rank_mate = {"R5k1/5ppp/8/8/8/8/8/6K1 b - - 1 1", Status::Checkmate},
+        fools_mate = {"rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3", Status::Checkmate},
+        stalemate = {"7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", Status::Stalemate},
+    )]
+    fn statuses(fen: &str, expected: Status) {
+        assert_eq!(game_status(&Position::from_fen(fen).expect("Valid FEN")), expected);
+    }
+
+    #[test]
+    fn playing_a_game() {
+        let mut fen = START_FEN.to_string();
+        for mv in ["f3", "e7e5", "g4", "Qh4#"] {
+            let position = Position::from_fen(&fen).expect("Valid FEN");
+            let parsed = san::parse_move(&position, mv).expect("Legal move");
+            fen = position.apply(parsed).to_fen();
+        }
+        assert_eq!(fen, "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3");
+        let position = Position::from_fen(&fen).expect("Valid FEN");
+        assert_eq!(game_status(&position), Status::Checkmate);
+        assert!(position.legal_moves().is_empty());
+    }
+}
+// COV_EXCL_STOP
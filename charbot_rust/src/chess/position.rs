@@ -0,0 +1,637 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+
+/// Squares are numbered from 0 for a1 to 63 for h8, a rank at a time.
+pub type Square = usize;
+
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+const KNIGHT_STEPS: [(isize, isize); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+const KING_STEPS: [(isize, isize); 8] = [(0, 1), (1, 1), (1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (-1, 1)];
+const ROOK_RAYS: [(isize, isize); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+const BISHOP_RAYS: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, -1), (-1, 1)];
+const PROMOTIONS: [Kind; 4] = [Kind::Queen, Kind::Rook, Kind::Bishop, Kind::Knight];
+
+pub fn file(square: Square) -> usize {
+    square % 8
+}
+
+pub fn rank(square: Square) -> usize {
+    square / 8
+}
+
+/// The square `file_step` files and `rank_step` ranks away, if it is on the board.
+fn offset(square: Square, (file_step, rank_step): (isize, isize)) -> Option<Square> {
+    let file = file(square) as isize + file_step;
+    let rank = rank(square) as isize + rank_step;
+    ((0..8).contains(&file) && (0..8).contains(&rank)).then(|| (rank * 8 + file) as Square)
+}
+
+pub fn square_name(square: Square) -> String {
+    format!("{}{}", (b'a' + file(square) as u8) as char, rank(square) + 1)
+}
+
+pub fn parse_square(name: &str) -> Option<Square> {
+    match name.as_bytes() {
+        &[file @ b'a'..=b'h', rank @ b'1'..=b'8'] => Some(usize::from(rank - b'1') * 8 + usize::from(file - b'a')),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub enum Color {
+    White,
+    Black,
+}
+
+impl Color {
+    pub fn other(self) -> Self {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+
+    /// The way this side's pawns move, as a rank step.
+    fn forward(self) -> isize {
+        match self {
+            Color::White => 1,
+            Color::Black => -1,
+        }
+    }
+
+    /// The rank this side's pieces start on.
+    fn back_rank(self) -> usize {
+        match self {
+            Color::White => 0,
+            Color::Black => 7,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub enum Kind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl Kind {
+    /// The uppercase letter of the piece, as in FEN and SAN.
+    pub fn letter(self) -> char {
+        match self {
+            Kind::Pawn => 'P',
+            Kind::Knight => 'N',
+            Kind::Bishop => 'B',
+            Kind::Rook => 'R',
+            Kind::Queen => 'Q',
+            Kind::King => 'K',
+        }
+    }
+
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter.to_ascii_uppercase() {
+            'P' => Some(Kind::Pawn),
+            'N' => Some(Kind::Knight),
+            'B' => Some(Kind::Bishop),
+            'R' => Some(Kind::Rook),
+            'Q' => Some(Kind::Queen),
+            'K' => Some(Kind::King),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Piece {
+    pub color: Color,
+    pub kind: Kind,
+}
+
+impl Piece {
+    fn fen_letter(self) -> char {
+        match self.color {
+            Color::White => self.kind.letter(),
+            Color::Black => self.kind.letter().to_ascii_lowercase(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+    pub promotion: Option<Kind>,
+}
+
+impl Move {
+    pub fn to_uci(self) -> String {
+        let promotion = self.promotion.map(|kind| kind.letter().to_ascii_lowercase());
+        format!("{}{}{}", square_name(self.from), square_name(self.to), promotion.map(String::from).unwrap_or_default())
+    }
+}
+
+/// Which castling moves are still allowed, by the king and rook not having moved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)] // COV_EXCL_LINE
+pub struct Castling {
+    pub white_king: bool,
+    pub white_queen: bool,
+    pub black_king: bool,
+    pub black_queen: bool,
+}
+
+impl Castling {
+    fn king_side(&mut self, color: Color) -> &mut bool {
+        match color {
+            Color::White => &mut self.white_king,
+            Color::Black => &mut self.black_king,
+        }
+    }
+
+    fn queen_side(&mut self, color: Color) -> &mut bool {
+        match color {
+            Color::White => &mut self.white_queen,
+            Color::Black => &mut self.black_queen,
+        }
+    }
+
+    /// Take away the rights that moving from or capturing on `square` ends.
+    fn touch(&mut self, square: Square) {
+        match square {
+            4 => (self.white_king, self.white_queen) = (false, false),
+            7 => self.white_king = false,
+            0 => self.white_queen = false,
+            60 => (self.black_king, self.black_queen) = (false, false),
+            63 => self.black_king = false,
+            56 => self.black_queen = false,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Position {
+    board: [Option<Piece>; 64],
+    pub turn: Color,
+    castling: Castling,
+    /// The square a pawn can be taken en passant on, behind a pawn that just moved two squares.
+    en_passant: Option<Square>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl Position {
+    pub fn from_fen(fen: &str) -> Result<Self, String> {
+        let fields = fen.split_whitespace().collect::<Vec<_>>();
+        if !(4..=6).contains(&fields.len()) {
+            return Err(format!("Invalid FEN {fen:?}, it must have from 4 to 6 fields"));
+        }
+        let mut board = [None; 64];
+        let ranks = fields[0].split('/').collect::<Vec<_>>();
+        if ranks.len() != 8 {
+            return Err(format!("Invalid board {:?}, it must have 8 ranks", fields[0]));
+        }
+        for (index, pieces) in ranks.iter().enumerate() {
+            let rank = 7 - index;
+            let mut file = 0;
+            for letter in pieces.chars() {
+                if let Some(empty) = letter.to_digit(10).filter(|empty| (1..=8).contains(empty)) {
+                    file += empty as usize;
+                } else if let Some(kind) = Kind::from_letter(letter) {
+                    if file >= 8 {
+                        return Err(format!("Invalid rank {pieces:?}, it must have 8 squares"));
+                    }
+                    let color = if letter.is_ascii_uppercase() { Color::White } else { Color::Black };
+                    board[rank * 8 + file] = Some(Piece { color, kind });
+                    file += 1;
+                } else {
+                    return Err(format!("Invalid piece {letter:?} in the board"));
+                }
+            }
+            if file != 8 {
+                return Err(format!("Invalid rank {pieces:?}, it must have 8 squares"));
+            }
+        }
+        let turn = match fields[1] {
+            "w" => Color::White,
+            "b" => Color::Black,
+            other => return Err(format!("Invalid side to move {other:?}, it must be w or b")),
+        };
+        let mut castling = Castling::default();
+        if fields[2] != "-" {
+            for letter in fields[2].chars() {
+                let right = match letter {
+                    'K' => &mut castling.white_king,
+                    'Q' => &mut castling.white_queen,
+                    'k' => &mut castling.black_king,
+                    'q' => &mut castling.black_queen,
+                    _ => return Err(format!("Invalid castling rights {:?}", fields[2])),
+                };
+                *right = true;
+            }
+        }
+        let en_passant = match fields[3] {
+            "-" => None,
+            name => {
+                let expected_rank = match turn {
+                    Color::White => 5,
+                    Color::Black => 2,
+                };
+                match parse_square(name) {
+                    Some(square) if rank(square) == expected_rank => Some(square),
+                    _ => return Err(format!("Invalid en passant square {name:?}")),
+                }
+            }
+        };
+        let number = |index: usize, default: u32| {
+            fields.get(index).map_or(Ok(default), |field| {
+                field.parse::<u32>().map_err(|_| format!("Invalid move number {field:?}"))
+            })
+        };
+        let mut position = Position {
+            board,
+            turn,
+            castling,
+            en_passant,
+            halfmove_clock: number(4, 0)?,
+            fullmove_number: number(5, 1)?.max(1),
+        };
+        for color in [Color::White, Color::Black] {
+            let kings = position.pieces(color).filter(|(_, piece)| piece.kind == Kind::King).count();
+            if kings != 1 {
+                return Err(format!("Invalid board, {color:?} must have exactly one king"));
+            }
+        }
+        if position
+            .pieces(Color::White)
+            .chain(position.pieces(Color::Black))
+            .any(|(square, piece)| piece.kind == Kind::Pawn && [0, 7].contains(&rank(square)))
+        {
+            return Err("Invalid board, pawns can't be on the first or last rank".to_string());
+        }
+        if position.in_check(turn.other()) {
+            return Err("Invalid position, the side not to move is in check".to_string());
+        }
+        position.drop_impossible_castling();
+        Ok(position)
+    }
+
+    /// Castling rights only stay while the king and rook are still on their squares.
+    fn drop_impossible_castling(&mut self) {
+        for color in [Color::White, Color::Black] {
+            let back = color.back_rank() * 8;
+            let home = |position: &Position, square: Square, kind: Kind| {
+                position.board[square] == Some(Piece { color, kind })
+            };
+            let king_home = home(self, back + 4, Kind::King);
+            if !king_home || !home(self, back + 7, Kind::Rook) {
+                *self.castling.king_side(color) = false;
+            }
+            if !king_home || !home(self, back, Kind::Rook) {
+                *self.castling.queen_side(color) = false;
+            }
+        }
+    }
+
+    pub fn to_fen(&self) -> String {
+        let ranks = (0..8)
+            .rev()
+            .map(|rank| {
+                let mut pieces = String::new();
+                let mut empty = 0;
+                for file in 0..8 {
+                    match self.board[rank * 8 + file] {
+                        Some(piece) => {
+                            if empty > 0 {
+                                pieces.push_str(&empty.to_string());
+                                empty = 0;
+                            }
+                            pieces.push(piece.fen_letter());
+                        }
+                        None => empty += 1,
+                    }
+                }
+                if empty > 0 {
+                    pieces.push_str(&empty.to_string());
+                }
+                pieces
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+        let turn = match self.turn {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+        let rights = [
+            (self.castling.white_king, 'K'),
+            (self.castling.white_queen, 'Q'),
+            (self.castling.black_king, 'k'),
+            (self.castling.black_queen, 'q'),
+        ];
+        let mut castling = rights.iter().filter(|(allowed, _)| *allowed).map(|(_, letter)| *letter).collect::<String>();
+        if castling.is_empty() {
+            castling.push('-');
+        }
+        let en_passant = self.en_passant.map_or_else(|| "-".to_string(), square_name);
+        format!("{ranks} {turn} {castling} {en_passant} {} {}", self.halfmove_clock, self.fullmove_number)
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.board[square]
+    }
+
+    fn pieces(&self, color: Color) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        self.board
+            .iter()
+            .enumerate()
+            .filter_map(move |(square, piece)| piece.filter(|piece| piece.color == color).map(|piece| (square, piece)))
+    }
+
+    fn king(&self, color: Color) -> Square {
+        self.pieces(color)
+            .find(|(_, piece)| piece.kind == Kind::King)
+            .map(|(square, _)| square)
+            .expect("Both sides always have a king") // COV_EXCL_LINE
+    }
+
+    /// Whether any piece of `by` attacks `square`.
+    fn is_attacked(&self, square: Square, by: Color) -> bool {
+        let is = |square: Option<Square>, kinds: &[Kind]| {
+            square
+                .and_then(|square| self.board[square])
+                .is_some_and(|piece| piece.color == by && kinds.contains(&piece.kind))
+        };
+        // a pawn attacks from one rank behind, seen from its side
+        let pawns = [(-1, -by.forward()), (1, -by.forward())];
+        pawns.iter().any(|step| is(offset(square, *step), &[Kind::Pawn]))
+            || KNIGHT_STEPS.iter().any(|step| is(offset(square, *step), &[Kind::Knight]))
+            || KING_STEPS.iter().any(|step| is(offset(square, *step), &[Kind::King]))
+            || ROOK_RAYS.iter().any(|ray| is(self.ray_end(square, *ray), &[Kind::Rook, Kind::Queen]))
+            || BISHOP_RAYS.iter().any(|ray| is(self.ray_end(square, *ray), &[Kind::Bishop, Kind::Queen]))
+    }
+
+    /// The first occupied square from `square` along `ray`.
+    fn ray_end(&self, mut square: Square, ray: (isize, isize)) -> Option<Square> {
+        while let Some(next) = offset(square, ray) {
+            if self.board[next].is_some() {
+                return Some(next);
+            }
+            square = next;
+        }
+        None
+    }
+
+    pub fn in_check(&self, color: Color) -> bool {
+        self.is_attacked(self.king(color), color.other())
+    }
+
+    /// The moves the pieces can make, without checking that they don't leave the king in check.
+    fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        let color = self.turn;
+        let mut add = |from: Square, to: Square, promotes: bool| {
+            if promotes {
+                moves.extend(PROMOTIONS.iter().map(|kind| Move { from, to, promotion: Some(*kind) }));
+            } else {
+                moves.push(Move { from, to, promotion: None });
+            }
+        };
+        for (from, piece) in self.pieces(color) {
+            let can_land = |to: Square| !self.board[to].is_some_and(|target| target.color == color);
+            match piece.kind {
+                Kind::Pawn => {
+                    let forward = color.forward();
+                    let last_rank = color.other().back_rank();
+                    if let Some(to) = offset(from, (0, forward)).filter(|to| self.board[*to].is_none()) {
+                        add(from, to, rank(to) == last_rank);
+                        let start_rank = (color.back_rank() as isize + forward) as usize;
+                        if let Some(to) = offset(to, (0, forward)).filter(|to| self.board[*to].is_none()) {
+                            if rank(from) == start_rank {
+                                add(from, to, false);
+                            }
+                        }
+                    }
+                    for side in [-1, 1] {
+                        if let Some(to) = offset(from, (side, forward)) {
+                            let captures = self.board[to].is_some_and(|target| target.color != color);
+                            if captures || self.en_passant == Some(to) {
+                                add(from, to, rank(to) == last_rank);
+                            }
+                        }
+                    }
+                }
+                Kind::Knight | Kind::King => {
+                    let steps = if piece.kind == Kind::Knight { &KNIGHT_STEPS } else { &KING_STEPS };
+                    for to in steps.iter().filter_map(|step| offset(from, *step)).filter(|to| can_land(*to)) {
+                        add(from, to, false);
+                    }
+                }
+                Kind::Bishop | Kind::Rook | Kind::Queen => {
+                    let rays = match piece.kind {
+                        Kind::Bishop => &BISHOP_RAYS[..],
+                        Kind::Rook => &ROOK_RAYS[..],
+                        _ => &KING_STEPS[..],
+                    };
+                    for ray in rays {
+                        let mut square = from;
+                        while let Some(to) = offset(square, *ray) {
+                            if can_land(to) {
+                                add(from, to, false);
+                            }
+                            if self.board[to].is_some() {
+                                break;
+                            }
+                            square = to;
+                        }
+                    }
+                }
+            }
+        }
+        moves.extend(self.castling_moves());
+        moves
+    }
+
+    fn castling_moves(&self) -> Vec<Move> {
+        let color = self.turn;
+        let king = color.back_rank() * 8 + 4;
+        let mut castling = self.castling;
+        // whether it is allowed, the files that must be empty, the files the king crosses, and where it lands
+        let sides = [
+            (*castling.king_side(color), [5, 6].as_slice(), [5, 6].as_slice(), 6),
+            (*castling.queen_side(color), [1, 2, 3].as_slice(), [3, 2].as_slice(), 2),
+        ];
+        if self.in_check(color) {
+            return Vec::new();
+        }
+        sides
+            .into_iter()
+            .filter(|(allowed, empty, crossed, _)| {
+                *allowed
+                    && empty.iter().all(|file| self.board[king - 4 + file].is_none())
+                    && crossed.iter().all(|file| !self.is_attacked(king - 4 + file, color.other()))
+            })
+            .map(|(_, _, _, file)| Move { from: king, to: king - 4 + file, promotion: None })
+            .collect()
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|mv| !self.apply(*mv).in_check(self.turn))
+            .collect()
+    }
+
+    pub fn is_capture(&self, mv: Move) -> bool {
+        self.board[mv.to].is_some() || self.is_en_passant(mv)
+    }
+
+    fn is_en_passant(&self, mv: Move) -> bool {
+        self.en_passant == Some(mv.to) && self.board[mv.from].is_some_and(|piece| piece.kind == Kind::Pawn)
+    }
+
+    pub fn is_castling(&self, mv: Move) -> bool {
+        self.board[mv.from].is_some_and(|piece| piece.kind == Kind::King) && file(mv.from).abs_diff(file(mv.to)) == 2
+    }
+
+    /// The position after `mv`, which must be at least pseudo legal.
+    pub fn apply(&self, mv: Move) -> Position {
+        let mut next = self.clone();
+        let piece = self.board[mv.from].expect("A move always starts from a piece"); // COV_EXCL_LINE
+        if self.is_en_passant(mv) {
+            next.board[rank(mv.from) * 8 + file(mv.to)] = None;
+        }
+        if self.is_castling(mv) {
+            let (rook_from, rook_to) = if mv.to > mv.from { (mv.from + 3, mv.from + 1) } else { (mv.from - 4, mv.from - 1) };
+            next.board[rook_to] = next.board[rook_from].take();
+        }
+        next.board[mv.from] = None;
+        next.board[mv.to] = Some(Piece { color: piece.color, kind: mv.promotion.unwrap_or(piece.kind) });
+        next.castling.touch(mv.from);
+        next.castling.touch(mv.to);
+        next.en_passant = (piece.kind == Kind::Pawn && rank(mv.from).abs_diff(rank(mv.to)) == 2)
+            .then(|| (mv.from + mv.to) / 2);
+        next.halfmove_clock = if piece.kind == Kind::Pawn || self.is_capture(mv) { 0 } else { self.halfmove_clock + 1 };
+        if self.turn == Color::Black {
+            next.fullmove_number += 1;
+        }
+        next.turn = self.turn.other();
+        next
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn perft(position: &Position, depth: usize) -> usize {
+        if depth == 0 {
+            return 1;
+        }
+        position.legal_moves().iter().map(|mv| perft(&position.apply(*mv), depth - 1)).sum()
+    }
+
+    #[parameterized(
+        start = {START_FEN, 3, 8_902},
+        kiwipete = {"r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1", 2, 2_039},
+        endgame = {"8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43_238},
+        promotions = {"r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1", 3, 9_467},
+    )]
+    fn move_counts(fen: &str, depth: usize, expected: usize) {
+        let position = Position::from_fen(fen).expect("Valid FEN");
+        assert_eq!(perft(&position, depth), expected);
+    }
+
+    #[parameterized(
+        start = {START_FEN},
+        kiwipete = {"r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"},
+        en_passant = {"rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3"},
+        black_to_move = {"8/8/8/8/8/5k2/8/4K3 b - - 12 40"},
+    )]
+    fn fen_round_trip(fen: &str) {
+        assert_eq!(Position::from_fen(fen).expect("Valid FEN").to_fen(), fen);
+    }
+
+    #[parameterized(
+        too_few_fields = {"8/8/8/8/8/8/8/8 w"},
+        short_rank = {"rnbqkbnr/ppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"},
+        long_rank = {"rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"},
+        bad_piece = {"rnbqkbnr/ppppxppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"},
+        no_king = {"rnbq1bnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQ - 0 1"},
+        bad_turn = {"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1"},
+        bad_castling = {"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkx - 0 1"},
+        bad_en_passant = {"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e3 0 1"},
+        pawn_on_last_rank = {"rnbqkbnP/pppppppp/8/8/8/8/PPPPPPP1/RNBQKBNR w KQq - 0 1"},
+        opponent_in_check = {"4k3/8/8/8/8/8/4R3/4K3 w - - 0 1"},
+        bad_number = {"4k3/8/8/8/8/8/8/4K3 w - - x 1"},
+    )]
+    fn invalid_fen(fen: &str) {
+        Position::from_fen(fen).expect_err("Expected the FEN to be rejected");
+    }
+
+    #[test]
+    fn optional_counters() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4K3 w - -").expect("Valid FEN");
+        assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+    }
+
+    #[test]
+    fn impossible_castling_dropped() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4K2R w KQkq - 0 1").expect("Valid FEN");
+        assert_eq!(position.to_fen(), "4k3/8/8/8/8/8/8/4K2R w K - 0 1");
+    }
+
+    #[test]
+    fn applying_moves() {
+        let start = Position::from_fen(START_FEN).expect("Valid FEN");
+        let e4 = start.apply(Move { from: 12, to: 28, promotion: None });
+        assert_eq!(e4.to_fen(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let nf6 = e4.apply(Move { from: 62, to: 45, promotion: None });
+        assert_eq!(nf6.to_fen(), "rnbqkb1r/pppppppp/5n2/8/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 1 2");
+        let castled = Position::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+            .expect("Valid FEN")
+            .apply(Move { from: 4, to: 6, promotion: None });
+        assert_eq!(castled.to_fen(), "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1");
+        let long = castled.apply(Move { from: 60, to: 58, promotion: None });
+        assert_eq!(long.to_fen(), "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2");
+        let en_passant = Position::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1")
+            .expect("Valid FEN")
+            .apply(Move { from: 36, to: 43, promotion: None });
+        assert_eq!(en_passant.to_fen(), "4k3/8/3P4/8/8/8/8/4K3 b - - 0 1");
+        let promoted = Position::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1")
+            .expect("Valid FEN")
+            .apply(Move { from: 49, to: 57, promotion: Some(Kind::Knight) });
+        assert_eq!(promoted.to_fen(), "1N2k3/8/8/8/8/8/8/4K3 b - - 0 1");
+    }
+
+    #[test]
+    fn castling_rules() {
+        let castles = |fen: &str| {
+            let position = Position::from_fen(fen).expect("Valid FEN");
+            position.legal_moves().into_iter().filter(|mv| position.is_castling(*mv)).count()
+        };
+        assert_eq!(castles("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"), 2);
+        // not out of check, through an attacked square or with a piece in the way
+        assert_eq!(castles("r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1"), 0);
+        assert_eq!(castles("r3k2r/8/8/8/8/8/4r3/R3K2R w KQkq - 0 1"), 0);
+        assert_eq!(castles("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1"), 1);
+        assert_eq!(castles("r3k2r/8/8/8/8/8/8/RN2K2R w KQkq - 0 1"), 1);
+        // the rook's path can be attacked though
+        assert_eq!(castles("r3k2r/8/8/8/8/8/1r6/R3K2R w KQkq - 0 1"), 2);
+    }
+
+    #[test]
+    fn squares() {
+        assert_eq!(parse_square("e4"), Some(28));
+        assert_eq!(square_name(28), "e4");
+        assert_eq!(parse_square("i1"), None);
+        assert_eq!(parse_square("a9"), None);
+        assert_eq!(Move { from: 52, to: 60, promotion: Some(Kind::Queen) }.to_uci(), "e7e8q");
+    }
+}
+// COV_EXCL_STOP
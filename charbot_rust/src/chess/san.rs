@@ -0,0 +1,158 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::chess::position::{file, parse_square, rank, square_name, Kind, Move, Position};
+
+/// The move in standard algebraic notation, like `Nbd7`, `exd5`, `e8=Q+` or `O-O-O#`.
+pub fn to_san(position: &Position, mv: Move) -> String {
+    let mut san = san_without_suffix(position, mv, &position.legal_moves());
+    let next = position.apply(mv);
+    if next.in_check(next.turn) {
+        san.push(if next.legal_moves().is_empty() { '#' } else { '+' });
+    }
+    san
+}
+
+fn san_without_suffix(position: &Position, mv: Move, legal_moves: &[Move]) -> String {
+    if position.is_castling(mv) {
+        return if file(mv.to) > file(mv.from) { "O-O" } else { "O-O-O" }.to_string();
+    }
+    let kind = position.piece_at(mv.from).map(|piece| piece.kind).expect("A move always starts from a piece"); // COV_EXCL_LINE
+    let capture = position.is_capture(mv);
+    let mut san = String::new();
+    if kind == Kind::Pawn {
+        if capture {
+            san.push((b'a' + file(mv.from) as u8) as char);
+        }
+    } else {
+        san.push(kind.letter());
+        // the other pieces of the same kind that could go to the same square
+        let rivals = legal_moves
+            .iter()
+            .filter(|other| {
+                other.to == mv.to
+                    && other.from != mv.from
+                    && position.piece_at(other.from).map(|piece| piece.kind) == Some(kind)
+            })
+            .collect::<Vec<_>>();
+        if !rivals.is_empty() {
+            let name = square_name(mv.from);
+            if rivals.iter().all(|other| file(other.from) != file(mv.from)) {
+                san.push_str(&name[..1]);
+            } else if rivals.iter().all(|other| rank(other.from) != rank(mv.from)) {
+                san.push_str(&name[1..]);
+            } else {
+                san.push_str(&name);
+            }
+        }
+    }
+    if capture {
+        san.push('x');
+    }
+    san.push_str(&square_name(mv.to));
+    if let Some(promotion) = mv.promotion {
+        san.push('=');
+        san.push(promotion.letter());
+    }
+    san
+}
+
+/// Whether `text` has the shape of a UCI move, like `e2e4` or `e7e8q`.
+fn looks_like_uci(text: &str) -> bool {
+    text.is_ascii()
+        && (text.len() == 4 || text.len() == 5)
+        && parse_square(&text[..2]).is_some()
+        && parse_square(&text[2..4]).is_some()
+}
+
+/// Find the legal move `text` is, in SAN or UCI.
+///
+/// SAN is taken with or without the check and annotation marks, with `0-0` for castling and with
+/// or without the `=` of promotions, since players type all of those.
+pub fn parse_move(position: &Position, text: &str) -> Result<Move, String> {
+    let text = text.trim();
+    let legal_moves = position.legal_moves();
+    if looks_like_uci(text) {
+        let promotion = match text.chars().nth(4) {
+            Some(letter) => match Kind::from_letter(letter) {
+                Some(kind) if ![Kind::Pawn, Kind::King].contains(&kind) => Some(kind),
+                _ => return Err(format!("Invalid promotion in {text:?}")),
+            },
+            None => None,
+        };
+        let from = parse_square(&text[..2]);
+        let to = parse_square(&text[2..4]);
+        return legal_moves
+            .into_iter()
+            .find(|mv| Some(mv.from) == from && Some(mv.to) == to && mv.promotion == promotion)
+            .ok_or_else(|| format!("Illegal move {text}"));
+    }
+    let wanted = text.trim_end_matches(['+', '#', '!', '?']).replace('0', "O").replace('=', "");
+    if wanted.is_empty() {
+        return Err(format!("Invalid move {text:?}"));
+    }
+    legal_moves
+        .iter()
+        .copied()
+        .find(|mv| san_without_suffix(position, *mv, &legal_moves).replace('=', "") == wanted)
+        .ok_or_else(|| format!("Illegal move {text}"))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chess::position::START_FEN;
+    use yare::parameterized;
+
+    fn position(fen: &str) -> Position {
+        Position::from_fen(fen).expect("Valid FEN")
+    }
+
+    #[parameterized(
+        pawn_push = {START_FEN, "e2e4", "e4"},
+        knight = {START_FEN, "g1f3", "Nf3"},
+        pawn_capture = {"4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5", "exd5"},
+        en_passant = {"4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6", "exd6"},
+        file_disambiguation = {"4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "b1d2", "Nbd2"},
+        rank_disambiguation = {"4k3/8/8/8/R7/8/8/R3K3 w - - 0 1", "a1a2", "R1a2"},
+        square_disambiguation = {"4k3/8/8/8/8/Q1Q5/8/Q3K3 w - - 0 1", "a3b2", "Qa3b2"},
+        promotion_check = {"8/1P6/8/8/8/8/7k/4K3 w - - 0 1", "b7b8q", "b8=Q+"},
+        castling = {"r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "e1g1", "O-O"},
+        long_castling_check = {"3k4/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1c1", "O-O-O+"},
+        mate = {"6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", "a1a8", "Ra8#"},
+    )]
+    fn san(fen: &str, uci: &str, expected: &str) {
+        let position = position(fen);
+        let mv = parse_move(&position, uci).expect("Legal move");
+        assert_eq!(to_san(&position, mv), expected);
+        assert_eq!(parse_move(&position, expected), Ok(mv));
+    }
+
+    #[parameterized(
+        zeros = {"r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1", "0-0-0", "e1c1"},
+        no_equals = {"8/1P6/8/8/8/8/7k/4K3 w - - 0 1", "b8N", "b7b8n"},
+        annotated = {START_FEN, "e4!?", "e2e4"},
+        needless_check = {START_FEN, "Nc3+", "b1c3"},
+    )]
+    fn lenient_san(fen: &str, san: &str, uci: &str) {
+        let position = position(fen);
+        assert_eq!(parse_move(&position, san), parse_move(&position, uci));
+    }
+
+    #[parameterized(
+        blocked = {START_FEN, "e2e5"},
+        wrong_side = {START_FEN, "e7e5"},
+        missing_promotion = {"8/1P6/8/8/8/8/7k/4K3 w - - 0 1", "b7b8"},
+        bad_promotion = {"8/1P6/8/8/8/8/7k/4K3 w - - 0 1", "b7b8k"},
+        ambiguous = {"4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1", "Nd2"},
+        into_check = {"4k3/8/8/8/8/8/4r3/4K3 w - - 0 1", "Kf2"},
+        lowercase_piece = {START_FEN, "nf3"},
+        gibberish = {START_FEN, "hello"},
+        empty = {START_FEN, "+"},
+    )]
+    fn illegal(fen: &str, text: &str) {
+        parse_move(&position(fen), text).expect_err("Expected the move to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,312 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use image::Rgba;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::buffer::Bytes;
+use crate::images;
+use crate::metrics;
+
+/// The most pixels looked at to find the dominant color, larger images are sampled evenly.
+const MAX_SAMPLES: usize = 16_384;
+/// How many groups of similar colors the pixels are cut into.
+const BOXES: usize = 8;
+/// Pixels less opaque than this are left out, like the corners of round avatars.
+const MIN_ALPHA: u8 = 128;
+const WHITE: [u8; 3] = [0xff, 0xff, 0xff];
+const BLACK: [u8; 3] = [0, 0, 0];
+
+fn to_int([red, green, blue]: [u8; 3]) -> u32 {
+    u32::from_be_bytes([0, red, green, blue])
+}
+
+fn from_int(color: u32) -> Result<[u8; 3], String> {
+    images::rgb(color).map(|Rgba([red, green, blue, _])| [red, green, blue])
+}
+
+/// The relative luminance of a color, from 0 for black to 1 for white, as WCAG defines it.
+fn luminance(color: [u8; 3]) -> f64 {
+    let [red, green, blue] = color.map(|channel| {
+        let channel = f64::from(channel) / 255.0;
+        if channel <= 0.040_45 { channel / 12.92 } else { ((channel + 0.055) / 1.055).powf(2.4) }
+    });
+    0.2126 * red + 0.7152 * green + 0.0722 * blue
+}
+
+/// How much two colors contrast, from 1 for the same color to 21 for black and white.
+fn contrast(a: [u8; 3], b: [u8; 3]) -> f64 {
+    let (a, b) = (luminance(a), luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// A group of similar colors, for median cut.
+struct ColorBox {
+    pixels: Vec<[u8; 3]>,
+}
+
+impl ColorBox {
+    /// The channel the colors differ the most in, and by how much.
+    fn widest(&self) -> (usize, u8) {
+        (0..3)
+            .map(|channel| {
+                let values = self.pixels.iter().map(|pixel| pixel[channel]);
+                (channel, values.clone().max().unwrap_or(0) - values.min().unwrap_or(0))
+            })
+            .max_by_key(|(_, range)| *range)
+            .unwrap_or((0, 0))
+    }
+
+    fn average(&self) -> [u8; 3] {
+        let count = self.pixels.len().max(1) as u64;
+        [0, 1, 2].map(|channel| {
+            let sum = self.pixels.iter().map(|pixel| u64::from(pixel[channel])).sum::<u64>();
+            ((sum + count / 2) / count) as u8
+        })
+    }
+}
+
+/// The color most of `pixels` are close to, by median cut, or None if they're all transparent.
+///
+/// The pixels are cut into groups of similar colors by splitting the group with the widest range of
+/// colors at its median along it, and the average of the largest group is the dominant color, so an avatar
+/// with a lot of one color gets that color rather than a muddy mix of everything in it.
+fn dominant(pixels: &[Rgba<u8>]) -> Option<[u8; 3]> {
+    let step = pixels.len().div_ceil(MAX_SAMPLES).max(1);
+    let opaque = pixels.iter().step_by(step).filter(|pixel| pixel[3] >= MIN_ALPHA).map(|Rgba([red, green, blue, _])| [*red, *green, *blue]).collect::<Vec<_>>();
+    if opaque.is_empty() {
+        return None;
+    }
+    let mut boxes = vec![ColorBox { pixels: opaque }];
+    while boxes.len() < BOXES {
+        let Some((index, (channel, _))) = boxes.iter().map(ColorBox::widest).enumerate().filter(|(_, (_, range))| *range > 0).max_by_key(|(_, (_, range))| *range) else {
+            break;
+        };
+        let mut split = boxes.swap_remove(index);
+        split.pixels.sort_unstable_by_key(|pixel| pixel[channel]);
+        // split at the median, but keep pixels with the same value on the same side
+        let median = split.pixels[split.pixels.len() / 2][channel];
+        let mut at = split.pixels.partition_point(|pixel| pixel[channel] < median);
+        if at == 0 {
+            at = split.pixels.partition_point(|pixel| pixel[channel] <= median);
+        }
+        let upper = split.pixels.split_off(at);
+        boxes.push(split);
+        boxes.push(ColorBox { pixels: upper });
+    }
+    // ties go to the first box, so the same image always gets the same color
+    boxes.iter().rev().max_by_key(|colors| colors.pixels.len()).map(ColorBox::average)
+}
+
+/// A color readable on `background`: `color` if it contrasts enough, or mixed towards white or
+/// black until it does, or whichever of white and black contrasts more if there's no color.
+fn readable(background: [u8; 3], color: Option<[u8; 3]>, minimum: f64) -> [u8; 3] {
+    let extreme = if contrast(background, WHITE) >= contrast(background, BLACK) { WHITE } else { BLACK };
+    let Some(color) = color else {
+        return extreme;
+    };
+    // mixing in small steps keeps as much of the color as possible
+    for step in 0..=64 {
+        let amount = f64::from(step) / 64.0;
+        let mixed = [0, 1, 2].map(|channel| (f64::from(color[channel]) + (f64::from(extreme[channel]) - f64::from(color[channel])) * amount).round() as u8);
+        if contrast(background, mixed) >= minimum {
+            return mixed;
+        }
+    }
+    extreme
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+dominant_color(image_bytes, /)
+--
+
+Find the color most of an image is, such as to color an embed to match someone's avatar.
+
+Transparent parts of the image are left out. Large images are sampled, so this is fast for any
+image, and it happens without holding the GIL, so other threads can run meanwhile.
+
+Parameters
+----------
+image_bytes : bytes-like
+    The image, as a file in any common format.
+
+Returns
+-------
+int
+    The color, as a 0xRRGGBB integer, like ``discord.Colour`` takes.
+
+Raises
+------
+ValueError
+    If the image is too big, can't be read, or is completely transparent.
+")]
+fn dominant_color(py: Python, image_bytes: Bytes) -> PyResult<u32> {
+    metrics::observe("colors.dominant_color", || {
+        py.allow_threads(|| {
+            let image = images::decode(&image_bytes)?;
+            let pixels = image.pixels().copied().collect::<Vec<_>>();
+            dominant(&pixels).map(to_int).ok_or_else(|| String::from("Invalid image, it's completely transparent"))
+        })
+        .map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+contrast_ratio(a, b, /)
+--
+
+How much two colors contrast, as WCAG measures it for how readable text is.
+
+Parameters
+----------
+a : int
+    A color, as a 0xRRGGBB integer.
+b : int
+    The other color.
+
+Returns
+-------
+float
+    The contrast, from 1 for the same color to 21 for black and white. WCAG asks for at least 4.5
+    for normal text and 3 for large text.
+
+Raises
+------
+ValueError
+    If either isn't a color.
+")]
+fn contrast_ratio(a: u32, b: u32) -> PyResult<f64> {
+    metrics::observe("colors.contrast_ratio", || {
+        let (a, b) = (from_int(a).map_err(PyErr::new::<PyValueError, _>)?, from_int(b).map_err(PyErr::new::<PyValueError, _>)?);
+        Ok(contrast(a, b))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (background, color=None, minimum=4.5), text_signature = "
+readable_on(background, color=None, minimum=4.5, /)
+--
+
+Find a color that's readable on a background, such as an embed accent on Discord's dark theme.
+
+Parameters
+----------
+background : int
+    The background, as a 0xRRGGBB integer.
+color : int | None
+    The color to use if it's readable, otherwise it's lightened or darkened as little as needed to
+    be, like for an accent from ``dominant_color``. The default is None, for white or black.
+minimum : float
+    The least contrast the color needs with the background, from 1 to 21. The default is 4.5, what
+    WCAG asks for normal text.
+
+Returns
+-------
+int
+    The readable color, as a 0xRRGGBB integer, like ``discord.Colour`` takes. If no color contrasts
+    enough, whichever of white and black contrasts more.
+
+Raises
+------
+ValueError
+    If the background or the color isn't a color, or the minimum isn't from 1 to 21.
+")]
+fn readable_on(background: u32, color: Option<u32>, minimum: f64) -> PyResult<u32> {
+    metrics::observe("colors.readable_on", || {
+        if !(1.0..=21.0).contains(&minimum) {
+            return Err(PyValueError::new_err(format!("Invalid minimum contrast {minimum}, it must be from 1 to 21")));
+        }
+        let background = from_int(background).map_err(PyErr::new::<PyValueError, _>)?;
+        let color = color.map(from_int).transpose().map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(to_int(readable(background, color, minimum)))
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based color utilities, like an image's dominant color and readable contrast";
+
+pub(crate) fn register_colors(py: Python, m: &PyModule) -> PyResult<()> {
+    let colors = PyModule::new(py, "_colors")?;
+    colors.add_function(wrap_pyfunction!(dominant_color, colors)?)?;
+    colors.add_function(wrap_pyfunction!(contrast_ratio, colors)?)?;
+    colors.add_function(wrap_pyfunction!(readable_on, colors)?)?;
+    colors.add("__doc__", DOCSTRING)?;
+    m.add_submodule(colors)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    /// The contrast WCAG asks for normal text to be readable.
+    const AA_CONTRAST: f64 = 4.5;
+    /// Discord's dark theme background.
+    const DARK: [u8; 3] = [0x31, 0x33, 0x38];
+
+    fn pixels(colors: &[([u8; 3], usize)]) -> Vec<Rgba<u8>> {
+        colors.iter().flat_map(|([red, green, blue], count)| std::iter::repeat_n(Rgba([*red, *green, *blue, 255]), *count)).collect()
+    }
+
+    #[test]
+    fn ints() {
+        assert_eq!(to_int([0xfc, 0xb3, 0x3f]), 0xfcb33f);
+        assert_eq!(from_int(0xfcb33f), Ok([0xfc, 0xb3, 0x3f]));
+        assert!(from_int(0x100_0000).is_err());
+    }
+
+    #[parameterized(
+        same = {WHITE, WHITE, 1.0},
+        black_and_white = {BLACK, WHITE, 21.0},
+        either_order = {WHITE, BLACK, 21.0},
+        gray = {[0x77, 0x77, 0x77], WHITE, 4.48},
+        red = {[0xff, 0, 0], WHITE, 4.0},
+    )]
+    fn contrasts(a: [u8; 3], b: [u8; 3], expected: f64) {
+        assert!((contrast(a, b) - expected).abs() < 0.01, "{}", contrast(a, b));
+    }
+
+    #[test]
+    fn dominant_colors() {
+        assert_eq!(dominant(&pixels(&[([200, 30, 30], 100)])), Some([200, 30, 30]));
+        // the largest group of similar colors wins, not the average of everything
+        let avatar = pixels(&[([250, 250, 250], 300), ([30, 90, 200], 500), ([32, 92, 198], 200), ([200, 30, 30], 100)]);
+        let [red, green, blue] = dominant(&avatar).expect("No color");
+        assert!(red < 40 && (85..=95).contains(&green) && blue > 190, "{red} {green} {blue}");
+        // transparent pixels are left out
+        let mut round = pixels(&[([10, 200, 10], 50)]);
+        round.extend(std::iter::repeat_n(Rgba([255, 255, 255, 0]), 500));
+        assert_eq!(dominant(&round), Some([10, 200, 10]));
+        assert_eq!(dominant(&[Rgba([0, 0, 0, 0])]), None);
+        assert_eq!(dominant(&[]), None);
+    }
+
+    #[test]
+    fn large_images_are_sampled() {
+        let large = pixels(&[([0, 0, 255], MAX_SAMPLES * 2), ([255, 0, 0], MAX_SAMPLES)]);
+        assert_eq!(dominant(&large), Some([0, 0, 255]));
+    }
+
+    #[test]
+    fn readable_colors() {
+        assert_eq!(readable(DARK, None, AA_CONTRAST), WHITE);
+        assert_eq!(readable(WHITE, None, AA_CONTRAST), BLACK);
+        // readable colors are kept, others are lightened on dark backgrounds and darkened on light ones
+        let yellow = [0xfc, 0xb3, 0x3f];
+        assert_eq!(readable(DARK, Some(yellow), AA_CONTRAST), yellow);
+        let blue = [0x20, 0x40, 0xa0];
+        let lighter = readable(DARK, Some(blue), AA_CONTRAST);
+        assert!(contrast(DARK, lighter) >= AA_CONTRAST);
+        assert!(lighter.iter().zip(blue).all(|(after, before)| *after >= before));
+        assert_ne!(lighter, WHITE);
+        let darker = readable(WHITE, Some(yellow), AA_CONTRAST);
+        assert!(contrast(WHITE, darker) >= AA_CONTRAST);
+        assert!(darker.iter().zip(yellow).all(|(after, before)| *after <= before));
+        // nothing contrasts 21 with gray, so the better of white and black is used
+        assert_eq!(readable([0x80, 0x80, 0x80], Some(yellow), 21.0), BLACK);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,262 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+pub(crate) mod board; // COV_EXCL_LINE
+mod search;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::connect4::board::{Board, Disc, COLUMNS, ROWS};
+use crate::persist::{self, Persist};
+use crate::metrics;
+
+/// How many moves ahead the computer looks by default, a few milliseconds a move.
+pub(crate) const DEFAULT_DEPTH: u8 = 6;
+/// The most moves ahead the computer can look, so a move never takes more than about 100ms.
+const MAX_DEPTH: u8 = 8;
+
+// COV_EXCL_START
+#[pyclass(module = "connect4")]
+#[derive(Debug)]
+pub(crate) struct Game {
+    pub(crate) board: Board,
+    human: Disc,
+    depth: u8,
+}
+// COV_EXCL_STOP
+
+impl Game {
+    fn new(depth: u8, human_first: bool) -> Result<Self, String> {
+        if !(1..=MAX_DEPTH).contains(&depth) {
+            return Err(format!("Invalid depth {depth}, it must be from 1 to {MAX_DEPTH}"));
+        }
+        // red always goes first
+        let human = if human_first { Disc::Red } else { Disc::Yellow };
+        let mut game = Game {
+            board: Board::new(),
+            human,
+            depth,
+        };
+        if !human_first {
+            game.computer_move();
+        }
+        Ok(game)
+    }
+
+    fn computer_move(&mut self) -> Option<usize> {
+        let column = search::best_move(&self.board, self.human.swap(), self.depth)?;
+        self.board.drop_disc(column, self.human.swap());
+        Some(column)
+    }
+
+    /// Drop the player's disc in `column`, then the computer's if that didn't end the game.
+    fn make_move(&mut self, column: usize) -> Result<Option<usize>, String> {
+        if self.board.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        if !Board::is_valid_column(column) {
+            return Err(format!("Invalid column {column}, it must be from 0 to 6"));
+        }
+        if !self.board.can_drop(column) {
+            return Err(format!("The column at index {column} is full"));
+        }
+        self.board.drop_disc(column, self.human);
+        Ok(self.computer_move())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct State {
+    /// The discs, with row 0 at the bottom.
+    cells: [[Option<Disc>; COLUMNS]; ROWS],
+    human: Disc,
+    depth: u8,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "connect4";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { cells: self.board.cells, human: self.human, depth: self.depth }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        if !(1..=MAX_DEPTH).contains(&state.depth) {
+            return Err(format!("Invalid depth {}, it must be from 1 to {MAX_DEPTH}", state.depth));
+        }
+        let board = Board::from_cells(state.cells)?;
+        // the computer always moves straight after the player, so a saved game is never waiting on it
+        if !board.is_over() && board.to_move() != state.human {
+            return Err("It can't be the computer's turn in a saved game".to_string());
+        }
+        Ok(Game { board, human: state.human, depth: state.depth })
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Game {
+    #[new]
+    #[pyo3(signature = (depth=DEFAULT_DEPTH, human_first=true))]
+    fn __new__(depth: u8, human_first: bool) -> PyResult<Self> { // COV_EXCL_LINE
+        Self::new(depth, human_first).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The discs, as rows from the top, with None for the empty cells.
+    #[getter]
+    fn board(&self) -> Vec<Vec<Option<Disc>>> {
+        self.board.cells.iter().rev().map(|row| row.to_vec()).collect()
+    }
+
+    #[getter]
+    fn player(&self) -> Disc {
+        self.human
+    }
+
+    fn play(&mut self, column: usize) -> PyResult<Option<usize>> {
+        metrics::observe("connect4.play", || self.make_move(column).map_err(PyErr::new::<PyValueError, _>))
+    }
+
+    fn legal_moves(&self) -> Vec<usize> {
+        self.board.legal_moves().collect()
+    }
+
+    fn winner(&self) -> Option<Disc> {
+        self.board.winner()
+    }
+
+    fn is_draw(&self) -> bool {
+        self.board.is_draw()
+    }
+
+    fn is_over(&self) -> bool {
+        self.board.is_over()
+    }
+
+    fn has_player_won(&self) -> bool {
+        self.board.winner() == Some(self.human)
+    }
+
+    fn has_player_lost(&self) -> bool {
+        self.board.winner() == Some(self.human.swap())
+    }
+
+    fn to_emoji(&self) -> String {
+        self.board.to_emoji()
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_emoji()
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of connect four";
+
+pub(crate) fn register_connect4(py: Python, m: &PyModule) -> PyResult<()> {
+    let connect4 = PyModule::new(py, "_connect4")?;
+    connect4.add_class::<Game>()?;
+    connect4.add_class::<Disc>()?;
+    connect4.add("__doc__", DOCSTRING)?;
+    m.add_submodule(connect4)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creator() {
+        Game::new(0, true).expect_err("Expected error");
+        Game::new(MAX_DEPTH + 1, true).expect_err("Expected error");
+        let human_first = Game::new(DEFAULT_DEPTH, true).expect("Failed to create game");
+        assert_eq!(human_first.player(), Disc::Red);
+        assert_eq!(human_first.legal_moves().len(), 7);
+        assert!(human_first.board().iter().flatten().all(Option::is_none));
+        let computer_first = Game::new(DEFAULT_DEPTH, false).expect("Failed to create game");
+        assert_eq!(computer_first.player(), Disc::Yellow);
+        assert_eq!(computer_first.board.cells[0].iter().filter(|cell| **cell == Some(Disc::Red)).count(), 1);
+        assert!(computer_first.board.cells[1].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn play() {
+        let mut game = Game::new(2, true).expect("Failed to create game");
+        let reply = game.make_move(3).expect("Failed to play").expect("Expected a computer move");
+        assert_eq!(game.board().iter().flatten().filter(|cell| cell.is_some()).count(), 2);
+        assert_eq!(game.board()[5][3], Some(Disc::Red));
+        assert!(game.board.cells.iter().any(|row| row[reply] == Some(Disc::Yellow)));
+        game.make_move(7).expect_err("Expected the column to be rejected");
+    }
+
+    #[test]
+    fn full_column() {
+        let mut game = Game::new(1, true).expect("Failed to create game");
+        for _ in 0..3 {
+            game.board.drop_disc(0, Disc::Red);
+            game.board.drop_disc(0, Disc::Yellow);
+        }
+        game.make_move(0).expect_err("Expected the full column to be rejected");
+        assert!(!game.legal_moves().contains(&0));
+    }
+
+    #[test]
+    fn persists() {
+        let mut game = Game::new(2, false).expect("Failed to create game");
+        game.make_move(3).expect("Failed to play");
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Game = persist::from_json(&json).expect("Failed to load");
+        assert_eq!(loaded.board, game.board);
+        assert_eq!((loaded.human, loaded.depth), (Disc::Yellow, 2));
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        let mut loaded: Game = persist::from_bytes(&bytes).expect("Failed to load");
+        assert_eq!(loaded.make_move(0), game.make_move(0));
+        let mut state = game.save();
+        state.human = Disc::Red;
+        Game::load(state).expect_err("Expected it being the computer's turn to be rejected");
+        let mut state = game.save();
+        state.depth = MAX_DEPTH + 1;
+        Game::load(state).expect_err("Expected the depth to be rejected");
+    }
+
+    #[test]
+    fn finished() {
+        let mut game = Game::new(1, true).expect("Failed to create game");
+        for column in 0..3 {
+            game.board.drop_disc(column, Disc::Red);
+        }
+        assert_eq!(game.make_move(3), Ok(None));
+        assert!(game.is_over());
+        assert!(!game.is_draw());
+        assert_eq!(game.winner(), Some(Disc::Red));
+        assert!(game.has_player_won());
+        assert!(!game.has_player_lost());
+        assert!(game.legal_moves().is_empty());
+        game.make_move(4).expect_err("Expected the game to be over");
+        assert_eq!(game.__str__(), game.to_emoji());
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,352 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::fmt::{Display, Error, Formatter};
+use pyo3::{pyclass, pymethods};
+use serde::{Deserialize, Serialize};
+// COV_EXCL_STOP
+
+pub const COLUMNS: usize = 7;
+pub const ROWS: usize = 6;
+
+#[pyclass(module = "connect4")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Disc { // COV_EXCL_LINE
+    Red,
+    Yellow,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Disc {
+    pub fn swap(&self) -> Self {
+        match self { // COV_EXCL_LINE
+            Self::Red => Self::Yellow,
+            Self::Yellow => Self::Red,
+        }
+    }
+
+    /// The emoji the disc is drawn as.
+    #[getter] // COV_EXCL_LINE
+    pub fn emoji(&self) -> &'static str {
+        match self { // COV_EXCL_LINE
+            Self::Red => "\u{1f534}",
+            Self::Yellow => "\u{1f7e1}",
+        }
+    }
+}
+
+/// The emoji for a cell without a disc.
+const EMPTY_EMOJI: &str = "\u{26ab}";
+/// The keycap numbers under each column, so players can see which one to pick.
+const COLUMN_EMOJI: [&str; COLUMNS] = [
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+    "4\u{fe0f}\u{20e3}",
+    "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}",
+    "7\u{fe0f}\u{20e3}",
+];
+
+/// The directions a line of four can go in, as (row, column) steps.
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Board {
+    /// The discs, with row 0 at the bottom.
+    pub(crate) cells: [[Option<Disc>; COLUMNS]; ROWS],
+    /// How many discs are in each column.
+    heights: [usize; COLUMNS],
+    n_discs: usize,
+    winner: Option<Disc>,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Board {
+            cells: [[None; COLUMNS]; ROWS],
+            heights: [0; COLUMNS],
+            n_discs: 0,
+            winner: None,
+        }
+    }
+
+    /// A board from its discs, with row 0 at the bottom, failing if they couldn't have been dropped like that.
+    pub fn from_cells(cells: [[Option<Disc>; COLUMNS]; ROWS]) -> Result<Self, String> {
+        let mut board = Board { cells, ..Board::new() };
+        for (column, height) in board.heights.iter_mut().enumerate() {
+            *height = (0..ROWS).take_while(|row| cells[*row][column].is_some()).count();
+            if (*height..ROWS).any(|row| cells[row][column].is_some()) {
+                return Err(format!("A disc in column {column} is floating above an empty cell"));
+            }
+        }
+        board.n_discs = board.heights.iter().sum();
+        let count = |disc| cells.iter().flatten().filter(|cell| **cell == Some(disc)).count();
+        let (red, yellow) = (count(Disc::Red), count(Disc::Yellow));
+        if red != yellow && red != yellow + 1 {
+            return Err(format!("{red} red and {yellow} yellow discs can't come from taking turns"));
+        }
+        let lines = |disc| {
+            (0..ROWS).any(|row| (0..COLUMNS).any(|column| cells[row][column] == Some(disc) && board.completes_line(row, column)))
+        };
+        board.winner = match (lines(Disc::Red), lines(Disc::Yellow)) {
+            (true, true) => return Err("Both red and yellow have four in a row".to_string()),
+            // the game stops at the first four in a row, so the winner made the last move
+            (true, false) if red == yellow + 1 => Some(Disc::Red),
+            (false, true) if red == yellow => Some(Disc::Yellow),
+            (false, false) => None,
+            _ => return Err("Discs were dropped after the game was won".to_string()),
+        };
+        Ok(board)
+    }
+
+    /// Whose turn it is, red always goes first.
+    pub fn to_move(&self) -> Disc {
+        [Disc::Red, Disc::Yellow][self.n_discs % 2]
+    }
+
+    pub fn is_valid_column(column: usize) -> bool {
+        column < COLUMNS
+    }
+
+    pub fn can_drop(&self, column: usize) -> bool {
+        Self::is_valid_column(column) && self.heights[column] < ROWS && self.winner.is_none()
+    }
+
+    pub fn legal_moves(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..COLUMNS).filter(|column| self.can_drop(*column))
+    }
+
+    /// Drop `disc` into `column`, returning the row it landed in.
+    pub fn drop_disc(&mut self, column: usize, disc: Disc) -> usize {
+        if !self.can_drop(column) {
+            panic!("Tried to drop a disc in a full column, Column: {column}"); // COV_EXCL_LINE
+        }
+        let row = self.heights[column];
+        self.cells[row][column] = Some(disc);
+        self.heights[column] += 1;
+        self.n_discs += 1;
+        if self.completes_line(row, column) {
+            self.winner = Some(disc);
+        }
+        row
+    }
+
+    /// Take the top disc back out of `column`, for searching.
+    pub fn undo(&mut self, column: usize) {
+        self.heights[column] -= 1;
+        self.cells[self.heights[column]][column] = None;
+        self.n_discs -= 1;
+        self.winner = None;
+    }
+
+    pub fn winner(&self) -> Option<Disc> {
+        self.winner
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none() && self.n_discs >= ROWS * COLUMNS
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.is_draw()
+    }
+
+    fn get(&self, row: isize, column: isize) -> Option<Disc> {
+        if (0..ROWS as isize).contains(&row) && (0..COLUMNS as isize).contains(&column) {
+            self.cells[row as usize][column as usize]
+        } else {
+            None
+        }
+    }
+
+    /// Whether the disc at `row`, `column` is part of four in a row in any direction.
+    fn completes_line(&self, row: usize, column: usize) -> bool {
+        let Some(disc) = self.cells[row][column] else {
+            return false; // COV_EXCL_LINE
+        };
+        let (row, column) = (row as isize, column as isize);
+        DIRECTIONS.iter().any(|&(row_step, column_step)| {
+            let count = |sign: isize| {
+                (1..4)
+                    .take_while(|distance| {
+                        self.get(row + sign * distance * row_step, column + sign * distance * column_step) == Some(disc)
+                    })
+                    .count()
+            };
+            1 + count(1) + count(-1) >= 4
+        })
+    }
+
+    /// Every line of four cells on the board, for scoring positions.
+    pub fn windows(&self) -> impl Iterator<Item = [Option<Disc>; 4]> + '_ {
+        (0..ROWS as isize).flat_map(move |row| {
+            (0..COLUMNS as isize).flat_map(move |column| {
+                DIRECTIONS.iter().filter_map(move |&(row_step, column_step)| {
+                    let end = (row + 3 * row_step, column + 3 * column_step);
+                    if !(0..ROWS as isize).contains(&end.0) || !(0..COLUMNS as isize).contains(&end.1) {
+                        return None;
+                    }
+                    let mut window = [None; 4];
+                    for (distance, cell) in window.iter_mut().enumerate() {
+                        let distance = distance as isize;
+                        *cell = self.get(row + distance * row_step, column + distance * column_step);
+                    }
+                    Some(window)
+                })
+            })
+        })
+    }
+
+    /// The board as rows of emoji from the top, with the column numbers underneath.
+    pub fn to_emoji(&self) -> String {
+        let mut lines = self
+            .cells
+            .iter()
+            .rev()
+            .map(|row| row.iter().map(|cell| cell.map_or(EMPTY_EMOJI, |disc| disc.emoji())).collect::<String>())
+            .collect::<Vec<_>>();
+        lines.push(COLUMN_EMOJI.concat());
+        lines.join("\n")
+    }
+}
+
+// COV_EXCL_START
+impl Display for Board {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        formatter.write_str(&self.to_emoji())
+    }
+}
+// COV_EXCL_STOP
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disc() {
+        assert_eq!(Disc::Red.swap(), Disc::Yellow);
+        assert_eq!(Disc::Yellow.swap(), Disc::Red);
+        assert_eq!(Disc::Red.emoji(), "🔴");
+    }
+
+    #[test]
+    fn from_cells() {
+        let mut board = Board::new();
+        for column in [3, 3, 4, 0, 5, 0, 6] {
+            board.drop_disc(column, board.to_move());
+        }
+        assert_eq!(Board::from_cells(board.cells), Ok(board.clone()));
+        assert_eq!(board.winner(), Some(Disc::Red));
+        let mut floating = board.cells;
+        floating[ROWS - 1][1] = Some(Disc::Yellow);
+        Board::from_cells(floating).expect_err("Expected the floating disc to be rejected");
+        let mut extra = board.cells;
+        extra[1][4] = Some(Disc::Red);
+        Board::from_cells(extra).expect_err("Expected the extra red disc to be rejected");
+        let mut after_win = board.cells;
+        after_win[1][6] = Some(Disc::Yellow);
+        Board::from_cells(after_win).expect_err("Expected the disc after the win to be rejected");
+    }
+
+    #[test]
+    fn drop_and_undo() {
+        let mut board = Board::new();
+        assert_eq!(board.drop_disc(3, Disc::Red), 0);
+        assert_eq!(board.drop_disc(3, Disc::Yellow), 1);
+        assert_eq!(board.cells[1][3], Some(Disc::Yellow));
+        board.undo(3);
+        assert_eq!(board.cells[1][3], None);
+        assert_eq!(board.heights[3], 1);
+        for row in 0..ROWS {
+            board.drop_disc(0, if row % 2 == 0 { Disc::Red } else { Disc::Yellow });
+        }
+        assert!(!board.can_drop(7));
+        assert_eq!(board.legal_moves().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn wins() {
+        let mut horizontal = Board::new();
+        for column in [0, 1, 3] {
+            horizontal.drop_disc(column, Disc::Red);
+        }
+        assert_eq!(horizontal.winner(), None);
+        horizontal.drop_disc(2, Disc::Red);
+        assert_eq!(horizontal.winner(), Some(Disc::Red));
+        assert!(horizontal.is_over());
+        assert_eq!(horizontal.legal_moves().count(), 0);
+
+        let mut vertical = Board::new();
+        for _ in 0..4 {
+            vertical.drop_disc(6, Disc::Yellow);
+        }
+        assert_eq!(vertical.winner(), Some(Disc::Yellow));
+
+        // a rising diagonal from the bottom left, with fillers under it
+        let mut diagonal = Board::new();
+        for column in 0..4 {
+            for _ in 0..column {
+                diagonal.drop_disc(column, Disc::Yellow);
+            }
+        }
+        for column in [0, 1, 3, 2] {
+            diagonal.drop_disc(column, Disc::Red);
+        }
+        assert_eq!(diagonal.winner(), Some(Disc::Red));
+
+        let mut falling = Board::new();
+        for column in 3..7 {
+            for _ in 0..(6 - column) {
+                falling.drop_disc(column, Disc::Red);
+            }
+        }
+        for column in 3..7 {
+            falling.drop_disc(column, Disc::Yellow);
+        }
+        assert_eq!(falling.winner(), Some(Disc::Yellow));
+    }
+
+    #[test]
+    fn draw() {
+        let mut board = Board::new();
+        // alternating columns, swapped for the top three rows, is never four in a row
+        for row in 0..ROWS {
+            for column in 0..COLUMNS {
+                let disc = if (row / 3 + column) % 2 == 0 { Disc::Red } else { Disc::Yellow };
+                board.drop_disc(column, disc);
+            }
+        }
+        assert_eq!(board.winner(), None);
+        assert!(board.is_draw());
+    }
+
+    #[test]
+    fn windows() {
+        // 4 horizontal in each of the 6 rows, 3 vertical in each of the 7 columns, 3 * 4 for each diagonal direction
+        assert_eq!(Board::new().windows().count(), 6 * 4 + 7 * 3 + 2 * 12);
+    }
+
+    #[test]
+    fn emoji() {
+        let mut board = Board::new();
+        board.drop_disc(0, Disc::Red);
+        board.drop_disc(1, Disc::Yellow);
+        let emoji = board.to_emoji();
+        let lines = emoji.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), ROWS + 1);
+        assert_eq!(lines[0], "⚫⚫⚫⚫⚫⚫⚫");
+        assert_eq!(lines[5], "🔴🟡⚫⚫⚫⚫⚫");
+        assert_eq!(lines[6], "1️⃣2️⃣3️⃣4️⃣5️⃣6️⃣7️⃣");
+        assert_eq!(format!("{board}"), emoji);
+    }
+}
+// COV_EXCL_STOP
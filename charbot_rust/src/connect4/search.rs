@@ -0,0 +1,144 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::connect4::board::{Board, Disc, COLUMNS};
+
+/// Columns from the middle out, the middle ones are usually better so trying them first prunes more.
+const ORDER: [usize; COLUMNS] = [3, 2, 4, 1, 5, 0, 6];
+
+/// The score of a win, more than any position can get, a sooner win scores a little higher.
+const WIN: i32 = 1_000_000;
+/// More than any score, for the bounds before anything is searched.
+const INFINITY: i32 = 2 * WIN;
+
+/// The best column for `disc` to drop in, looking `depth` moves ahead, or `None` if there are no moves.
+///
+/// This is negamax with alpha-beta pruning, positions where nobody has won by then are scored by
+/// [`evaluate`]. Every extra level of depth makes it several times slower.
+pub fn best_move(board: &Board, disc: Disc, depth: u8) -> Option<usize> {
+    let mut board = board.clone();
+    let mut best = None;
+    let mut alpha = -INFINITY;
+    for column in ORDER {
+        if !board.can_drop(column) {
+            continue;
+        }
+        board.drop_disc(column, disc);
+        let score = -negamax(&mut board, disc.swap(), depth.saturating_sub(1), -INFINITY, -alpha);
+        board.undo(column);
+        if score > alpha {
+            alpha = score;
+            best = Some(column);
+        }
+    }
+    best
+}
+
+/// The score of `board` for `disc`, who moves next, as good as it gets for them within `depth` moves.
+fn negamax(board: &mut Board, disc: Disc, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    if let Some(winner) = board.winner() {
+        // the last move won, which was the other player's
+        let score = WIN + i32::from(depth);
+        return if winner == disc { score } else { -score };
+    }
+    if board.is_draw() {
+        return 0;
+    }
+    if depth == 0 {
+        return evaluate(board, disc);
+    }
+    for column in ORDER {
+        if !board.can_drop(column) {
+            continue;
+        }
+        board.drop_disc(column, disc);
+        let score = -negamax(board, disc.swap(), depth - 1, -beta, -alpha);
+        board.undo(column);
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// A guess at how good `board` is for `disc`, from the lines of four that only one player has discs in.
+fn evaluate(board: &Board, disc: Disc) -> i32 {
+    let centre = board.cells.iter().map(|row| match row[COLUMNS / 2] {
+        Some(cell) if cell == disc => 3,
+        Some(_) => -3,
+        None => 0,
+    });
+    let windows = board.windows().map(|window| {
+        let mine = window.iter().filter(|cell| **cell == Some(disc)).count();
+        let theirs = window.iter().filter(|cell| **cell == Some(disc.swap())).count();
+        match (mine, theirs) {
+            (3, 0) => 5,
+            (2, 0) => 2,
+            (0, 3) => -5,
+            (0, 2) => -2,
+            _ => 0,
+        }
+    });
+    centre.chain(windows).sum()
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn play(columns: &[usize]) -> Board {
+        let mut board = Board::new();
+        let mut disc = Disc::Red;
+        for &column in columns {
+            board.drop_disc(column, disc);
+            disc = disc.swap();
+        }
+        board
+    }
+
+    #[test]
+    fn takes_the_win() {
+        // red has three along the bottom, and yellow has blocked the right end
+        let board = play(&[1, 4, 2, 4, 3, 6]);
+        assert_eq!(best_move(&board, Disc::Red, 1), Some(0));
+        assert_eq!(best_move(&board, Disc::Red, 6), Some(0));
+    }
+
+    #[test]
+    fn blocks_the_loss() {
+        // yellow would win in the first column next
+        let board = play(&[4, 1, 6, 2, 6, 3]);
+        assert_eq!(best_move(&board, Disc::Red, 2), Some(0));
+    }
+
+    #[test]
+    fn prefers_the_centre() {
+        assert_eq!(best_move(&Board::new(), Disc::Red, 1), Some(3));
+    }
+
+    #[test]
+    fn no_moves() {
+        let board = play(&[0, 1, 0, 1, 0, 1, 0]);
+        assert!(board.is_over());
+        assert_eq!(best_move(&board, Disc::Yellow, 4), None);
+    }
+
+    #[test]
+    fn evaluate_is_symmetric() {
+        let board = play(&[3, 2, 3]);
+        assert!(evaluate(&board, Disc::Red) > 0);
+        assert_eq!(evaluate(&board, Disc::Red), -evaluate(&board, Disc::Yellow));
+    }
+
+    #[test]
+    fn default_depth_is_fast() {
+        let start = Instant::now();
+        best_move(&play(&[3, 3, 2]), Disc::Yellow, crate::connect4::DEFAULT_DEPTH);
+        // a few milliseconds optimized, the rest is slack for the unoptimized test build searching every column
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,252 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+pub(crate) mod tz; // COV_EXCL_LINE
+
+use std::collections::HashMap;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use tz::Zone;
+
+use crate::metrics;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// The range of timestamps python datetimes can hold, from 0001-01-01 to the end of 9999-12-31 UTC.
+const TIMESTAMPS: std::ops::RangeInclusive<i64> = -62_135_596_800..=253_402_300_799;
+
+#[pyclass(module = "cooldowns")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Kind { // COV_EXCL_LINE
+    /// Resets at midnight local time every day.
+    Daily,
+    /// Resets at midnight local time at the start of every monday.
+    Weekly,
+}
+
+// zones are read from disk the first time they're used, and kept since the bot only uses a few
+static ZONES: Lazy<RwLock<HashMap<String, Arc<Zone>>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+pub(crate) fn zone(name: &str) -> Result<Arc<Zone>, String> {
+    if let Some(zone) = ZONES.read().unwrap_or_else(PoisonError::into_inner).get(name) {
+        return Ok(Arc::clone(zone));
+    }
+    let zone = Arc::new(Zone::load(name)?);
+    ZONES.write().unwrap_or_else(PoisonError::into_inner).insert(name.to_string(), Arc::clone(&zone));
+    Ok(zone)
+}
+
+pub(crate) fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() as i64)
+}
+
+pub(crate) fn check(timestamp: i64) -> Result<i64, String> {
+    if !TIMESTAMPS.contains(&timestamp) {
+        return Err(format!("Invalid timestamp {timestamp}, it must be from year 1 to 9999"));
+    }
+    Ok(timestamp)
+}
+
+/// The first reset after `anchor`, at local midnight in `zone`.
+///
+/// Resets are counted in local dates rather than a fixed number of seconds, so the day the clocks
+/// change is 23 or 25 hours long, and a date can't be claimed twice because its midnight happened
+/// twice or not at all.
+fn reset_after(kind: Kind, zone: &Zone, anchor: i64) -> i64 {
+    let mut day = zone.to_local(anchor).div_euclid(SECONDS_PER_DAY);
+    loop {
+        day += match kind {
+            Kind::Daily => 1,
+            // mondays are 1, so the next one is 1 to 7 days away
+            Kind::Weekly => (-tz::weekday(day)).rem_euclid(7) + 1,
+        };
+        // if the clocks went back over midnight, the next date can have started before the anchor
+        let reset = zone.to_utc(day * SECONDS_PER_DAY);
+        if reset > anchor {
+            return reset;
+        }
+    }
+}
+
+/// The day or week `time` is in, in `tz`, counted from the one 1970-01-01 was in, weeks starting on monday.
+pub(crate) fn period(kind: Kind, tz: &str, time: i64) -> Result<i64, String> {
+    let day = zone(tz)?.to_local(check(time)?).div_euclid(SECONDS_PER_DAY);
+    Ok(match kind {
+        Kind::Daily => day,
+        // 1970-01-01 was a thursday, so the monday before it was 3 days earlier
+        Kind::Weekly => (day + 3).div_euclid(7),
+    })
+}
+
+fn next(kind: Kind, tz: &str, anchor: i64) -> Result<i64, String> {
+    Ok(reset_after(kind, &*zone(tz)?, check(anchor)?))
+}
+
+fn available(last_claim: i64, kind: Kind, tz: &str, now: i64) -> Result<bool, String> {
+    Ok(check(now)? >= next(kind, tz, last_claim)?)
+}
+
+#[pyfunction]
+#[pyo3(signature = (kind, tz, anchor=None), text_signature = "
+next_reset(kind, tz, anchor=None, /)
+--
+
+Get when a cooldown next resets, at midnight in a time zone.
+
+Parameters
+----------
+kind : Kind
+    Whether the cooldown is daily or weekly, weekly ones reset at the start of monday.
+tz : str
+    The name of the time zone in the tz database, like 'America/New_York' or 'UTC'.
+anchor : int | None
+    The unix timestamp in seconds to find the next reset after. The default is None, for now.
+
+Returns
+-------
+int
+    The unix timestamp in seconds of the next reset, after the anchor. Days the clocks change on are
+    23 or 25 hours long, and if midnight is skipped the reset is when the clocks go forward.
+
+Raises
+------
+ValueError
+    If the time zone isn't in the tz database, or the anchor isn't from year 1 to 9999.
+")]
+fn next_reset(kind: Kind, tz: &str, anchor: Option<i64>) -> PyResult<i64> {
+    metrics::observe("cooldowns.next_reset", || next(kind, tz, anchor.unwrap_or_else(now)).map_err(PyErr::new::<PyValueError, _>))
+}
+
+#[pyfunction]
+#[pyo3(signature = (last_claim, kind, tz, now=None), text_signature = "
+is_available(last_claim, kind, tz, now=None, /)
+--
+
+Check if a cooldown has reset since it was last claimed.
+
+Parameters
+----------
+last_claim : int
+    The unix timestamp in seconds it was last claimed at.
+kind : Kind
+    Whether the cooldown is daily or weekly, weekly ones reset at the start of monday.
+tz : str
+    The name of the time zone in the tz database, like 'America/New_York' or 'UTC'.
+now : int | None
+    The unix timestamp in seconds to check at. The default is None, for now.
+
+Returns
+-------
+bool
+    True if midnight of a later day, or the start of a later week, has been reached since the last
+    claim, False otherwise.
+
+Raises
+------
+ValueError
+    If the time zone isn't in the tz database, or a timestamp isn't from year 1 to 9999.
+")]
+fn is_available(last_claim: i64, kind: Kind, tz: &str, now: Option<i64>) -> PyResult<bool> {
+    metrics::observe("cooldowns.is_available", || available(last_claim, kind, tz, now.unwrap_or_else(self::now)).map_err(PyErr::new::<PyValueError, _>))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based daily and weekly cooldowns that reset at local midnight";
+
+pub(crate) fn register_cooldowns(py: Python, m: &PyModule) -> PyResult<()> {
+    let cooldowns = PyModule::new(py, "_cooldowns")?;
+    cooldowns.add_class::<Kind>()?;
+    cooldowns.add_function(wrap_pyfunction!(next_reset, cooldowns)?)?;
+    cooldowns.add_function(wrap_pyfunction!(is_available, cooldowns)?)?;
+    cooldowns.add("__doc__", DOCSTRING)?;
+    m.add_submodule(cooldowns)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const HOUR: i64 = 3600;
+
+    fn utc(year: i64, month: u32, day: u32, hour: i64) -> i64 {
+        tz::days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * HOUR
+    }
+
+    #[parameterized(
+        before_spring = {Kind::Daily, utc(2024, 3, 9, 17), utc(2024, 3, 10, 5)},
+        spring_forward = {Kind::Daily, utc(2024, 3, 10, 17), utc(2024, 3, 11, 4)},
+        before_fall = {Kind::Daily, utc(2024, 11, 2, 16), utc(2024, 11, 3, 4)},
+        fall_back = {Kind::Daily, utc(2024, 11, 3, 16), utc(2024, 11, 4, 5)},
+        at_midnight = {Kind::Daily, utc(2024, 3, 11, 4), utc(2024, 3, 12, 4)},
+        sunday = {Kind::Weekly, utc(2024, 3, 10, 17), utc(2024, 3, 11, 4)},
+        monday = {Kind::Weekly, utc(2024, 3, 11, 4), utc(2024, 3, 18, 4)},
+        saturday_night = {Kind::Weekly, utc(2024, 11, 3, 3), utc(2024, 11, 4, 5)},
+    )]
+    fn new_york(kind: Kind, anchor: i64, reset: i64) {
+        assert_eq!(next(kind, "America/New_York", anchor), Ok(reset));
+    }
+
+    #[test]
+    fn days_change_length() {
+        let start = next(Kind::Daily, "America/New_York", utc(2024, 3, 9, 17)).expect("Expected a reset");
+        assert_eq!(next(Kind::Daily, "America/New_York", start).expect("Expected a reset") - start, 23 * HOUR);
+        let start = next(Kind::Daily, "America/New_York", utc(2024, 11, 2, 17)).expect("Expected a reset");
+        assert_eq!(next(Kind::Daily, "America/New_York", start).expect("Expected a reset") - start, 25 * HOUR);
+    }
+
+    #[test]
+    fn no_double_claims() {
+        // claimed just after midnight EDT, the hour that repeats after it is still the same day
+        let claimed = utc(2024, 11, 3, 4) + 1800;
+        assert_eq!(available(claimed, Kind::Daily, "America/New_York", claimed + 2 * HOUR), Ok(false));
+        assert_eq!(available(claimed, Kind::Daily, "America/New_York", utc(2024, 11, 4, 5) - 1), Ok(false));
+        assert_eq!(available(claimed, Kind::Daily, "America/New_York", utc(2024, 11, 4, 5)), Ok(true));
+        // claimed just before midnight EST on the day the clocks went forward, the next day is 23 hours long
+        let claimed = utc(2024, 3, 10, 5) - 60;
+        assert_eq!(available(claimed, Kind::Daily, "America/New_York", claimed + 60), Ok(true));
+        let claimed = claimed + 60;
+        assert_eq!(available(claimed, Kind::Daily, "America/New_York", claimed + 23 * HOUR - 1), Ok(false));
+        assert_eq!(available(claimed, Kind::Daily, "America/New_York", claimed + 23 * HOUR), Ok(true));
+    }
+
+    #[test]
+    fn skipped_midnight() {
+        // Havana's clocks went forward at midnight, so 2024-03-10 started at 1am and the reset is then
+        assert_eq!(next(Kind::Daily, "America/Havana", utc(2024, 3, 9, 17)), Ok(utc(2024, 3, 10, 5)));
+        assert_eq!(next(Kind::Daily, "America/Havana", utc(2024, 3, 10, 5)), Ok(utc(2024, 3, 11, 4)));
+    }
+
+    #[test]
+    fn other_zones() {
+        assert_eq!(next(Kind::Daily, "UTC", utc(2024, 1, 1, 12)), Ok(utc(2024, 1, 2, 0)));
+        assert_eq!(next(Kind::Daily, "Asia/Kolkata", utc(2024, 1, 1, 12)), Ok(utc(2024, 1, 2, 0) - 5 * HOUR - 1800));
+        // sydney is ahead of UTC, so its day starts the afternoon before
+        assert_eq!(next(Kind::Daily, "Australia/Sydney", utc(2024, 1, 1, 12)), Ok(utc(2024, 1, 1, 13)));
+        assert_eq!(next(Kind::Daily, "UTC", -1), Ok(0));
+    }
+
+    #[test]
+    fn periods() {
+        assert_eq!(period(Kind::Daily, "UTC", 0), Ok(0));
+        assert_eq!(period(Kind::Daily, "America/New_York", 0), Ok(-1));
+        // 1970-01-05 was a monday, the first of week 1
+        assert_eq!(period(Kind::Weekly, "UTC", 4 * SECONDS_PER_DAY - 1), Ok(0));
+        assert_eq!(period(Kind::Weekly, "UTC", 4 * SECONDS_PER_DAY), Ok(1));
+        assert_eq!(period(Kind::Weekly, "UTC", -3 * SECONDS_PER_DAY - 1), Ok(-1));
+        period(Kind::Daily, "Not/AZone", 0).expect_err("Expected an unknown zone to be rejected");
+    }
+
+    #[test]
+    fn rejects() {
+        next(Kind::Daily, "Mars/Olympus_Mons", 0).expect_err("Expected an unknown zone to be rejected");
+        next(Kind::Daily, "../../etc/passwd", 0).expect_err("Expected a path to be rejected");
+        next(Kind::Daily, "UTC", i64::MAX).expect_err("Expected the timestamp to be rejected");
+        available(0, Kind::Daily, "UTC", i64::MIN).expect_err("Expected the timestamp to be rejected");
+    }
+}
+// COV_EXCL_STOP
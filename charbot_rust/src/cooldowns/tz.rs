@@ -0,0 +1,466 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Dates in the proleptic gregorian calendar, and time zones from the tz database (release 2025b), which is
+//! embedded in `zoneinfo` so zones work the same wherever the library runs, even without tzdata installed.
+use crate::assets;
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Where the tz database is in the assets, with a TZif file for each zone and link.
+const ZONEINFO: &str = "cooldowns/zoneinfo";
+
+/// The days since 1970-01-01 of a date in the proleptic gregorian calendar.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = i64::from(month);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// The date of a number of days since 1970-01-01, as (year, month, day).
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// The day of the week of a number of days since 1970-01-01, from 0 for sunday, since 1970-01-01 was a thursday.
+pub fn weekday(days: i64) -> i64 {
+    (days + 4).rem_euclid(7)
+}
+
+fn is_leap(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// The number of days in `month` of `year`.
+pub fn month_length(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// The day a daylight saving time rule changes on in a year.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Day {
+    /// `Jn`, from 1 to 365 without ever counting february 29th.
+    Julian(u32),
+    /// `n`, from 0 to 365 counting february 29th.
+    Zero(u32),
+    /// `Mm.w.d`, day `d` of week `w` of month `m`, from 0 for sunday, with week 5 for the last.
+    Month { month: u32, week: u32, day: u32 },
+}
+
+impl Day {
+    fn days(self, year: i64) -> i64 {
+        let new_year = days_from_civil(year, 1, 1);
+        match self {
+            Day::Julian(day) => new_year + i64::from(day) - 1 + i64::from(is_leap(year) && day >= 60),
+            Day::Zero(day) => new_year + i64::from(day),
+            Day::Month { month, week, day } => {
+                let first = days_from_civil(year, month, 1);
+                let mut date = 1 + (i64::from(day) - weekday(first)).rem_euclid(7) + 7 * (i64::from(week) - 1);
+                while date > i64::from(month_length(year, month)) {
+                    date -= 7;
+                }
+                first + date - 1
+            }
+        }
+    }
+}
+
+/// When daylight saving time starts and ends each year, from the footer of a tz file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Change {
+    day: Day,
+    /// The local time of day it changes at, in seconds, which can be negative or past midnight.
+    time: i64,
+}
+
+/// A POSIX TZ string like `EST5EDT,M3.2.0,M11.1.0`, how a zone keeps time after the last transition in its file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    /// The offset from UTC of standard time in seconds, east positive, unlike in the string.
+    standard: i64,
+    /// The offset of daylight saving time and when it starts and ends, if the zone has it.
+    daylight: Option<(i64, Change, Change)>,
+}
+
+/// Reads a TZ string a piece at a time.
+struct Reader<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        self.position += usize::from(found);
+        found
+    }
+
+    fn number(&mut self) -> Result<i64, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|byte| byte.is_ascii_digit()) {
+            self.position += 1;
+        }
+        std::str::from_utf8(&self.text[start..self.position]).ok().and_then(|digits| digits.parse().ok()).ok_or_else(|| "Expected a number".to_string())
+    }
+
+    /// A zone abbreviation, `EST` or quoted like `<+0330>`.
+    fn name(&mut self) -> Result<(), String> {
+        let start = self.position;
+        if self.eat(b'<') {
+            while !self.eat(b'>') {
+                self.peek().ok_or_else(|| "Unclosed zone name".to_string())?;
+                self.position += 1;
+            }
+        } else {
+            while self.peek().is_some_and(|byte| byte.is_ascii_alphabetic()) {
+                self.position += 1;
+            }
+        }
+        if self.position - start < 3 {
+            return Err("Zone names have at least 3 letters".to_string());
+        }
+        Ok(())
+    }
+
+    /// `[+-]hh[:mm[:ss]]` in seconds.
+    fn time(&mut self) -> Result<i64, String> {
+        let sign = if self.eat(b'-') {
+            -1
+        } else {
+            self.eat(b'+');
+            1
+        };
+        let mut seconds = self.number()? * 3600;
+        if self.eat(b':') {
+            seconds += self.number()? * 60;
+            if self.eat(b':') {
+                seconds += self.number()?;
+            }
+        }
+        Ok(sign * seconds)
+    }
+
+    fn change(&mut self) -> Result<Change, String> {
+        let day = if self.eat(b'M') {
+            let month = self.number()?;
+            let week = if self.eat(b'.') { self.number()? } else { -1 };
+            let day = if self.eat(b'.') { self.number()? } else { -1 };
+            if !(1..=12).contains(&month) || !(1..=5).contains(&week) || !(0..=6).contains(&day) {
+                return Err("Invalid month rule".to_string());
+            }
+            Day::Month { month: month as u32, week: week as u32, day: day as u32 }
+        } else if self.eat(b'J') {
+            match self.number()? {
+                day @ 1..=365 => Day::Julian(day as u32),
+                _ => return Err("Invalid julian day".to_string()),
+            }
+        } else {
+            match self.number()? {
+                day @ 0..=365 => Day::Zero(day as u32),
+                _ => return Err("Invalid day".to_string()),
+            }
+        };
+        let time = if self.eat(b'/') { self.time()? } else { 2 * 3600 };
+        Ok(Change { day, time })
+    }
+}
+
+impl Rule {
+    pub fn parse(text: &str) -> Result<Rule, String> {
+        let invalid = |error: String| format!("Invalid TZ string {text:?}: {error}");
+        let mut reader = Reader { text: text.as_bytes(), position: 0 };
+        reader.name().map_err(invalid)?;
+        let standard = -reader.time().map_err(invalid)?;
+        let mut daylight = None;
+        if reader.peek().is_some() {
+            reader.name().map_err(invalid)?;
+            let offset = if reader.peek().is_some_and(|byte| byte != b',') { -reader.time().map_err(invalid)? } else { standard + 3600 };
+            let (start, end) = if reader.eat(b',') {
+                let start = reader.change().map_err(invalid)?;
+                if !reader.eat(b',') {
+                    return Err(invalid("Expected when daylight saving time ends".to_string()));
+                }
+                (start, reader.change().map_err(invalid)?)
+            } else {
+                // the rules of the united states, which POSIX leaves to the implementation
+                let change = |month, week| Change { day: Day::Month { month, week, day: 0 }, time: 2 * 3600 };
+                (change(3, 2), change(11, 1))
+            };
+            daylight = Some((offset, start, end));
+        }
+        if reader.peek().is_some() {
+            return Err(invalid("Unexpected text at the end".to_string()));
+        }
+        Ok(Rule { standard, daylight })
+    }
+
+    /// The offset from UTC at `time`, in seconds.
+    pub fn offset(&self, time: i64) -> i64 {
+        let Some((daylight, start, end)) = self.daylight else { return self.standard };
+        let (year, _, _) = civil_from_days((time + self.standard).div_euclid(SECONDS_PER_DAY));
+        // the start is in standard time and the end in daylight saving time
+        let start = start.day.days(year) * SECONDS_PER_DAY + start.time - self.standard;
+        let end = end.day.days(year) * SECONDS_PER_DAY + end.time - daylight;
+        let in_daylight = if start < end { start <= time && time < end } else { time < end || start <= time };
+        if in_daylight {
+            daylight
+        } else {
+            self.standard
+        }
+    }
+}
+
+/// A time zone from the tz database, as a TZif file describes it (RFC 8536).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Zone {
+    /// When the offset changed, and the index of the offset it changed to.
+    transitions: Vec<(i64, usize)>,
+    offsets: Vec<i64>,
+    rule: Option<Rule>,
+}
+
+/// Reads the big endian numbers of a TZif file.
+struct Bytes<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Bytes<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        if self.data.len() < count {
+            return Err("The file ends too soon".to_string());
+        }
+        let (taken, rest) = self.data.split_at(count);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn count(&mut self) -> Result<usize, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+}
+
+/// The text of a table of the tz database, like `zone.tab`.
+#[cfg(all(feature = "fluent", feature = "games"))]
+pub fn table(file: &str) -> Result<&'static str, String> {
+    assets::static_text(&format!("{ZONEINFO}/{file}"))
+}
+
+impl Zone {
+    #[cfg(all(test, feature = "fluent"))]
+    pub fn utc() -> Zone {
+        Zone { transitions: Vec::new(), offsets: vec![0], rule: None }
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Zone, String> {
+        let mut bytes = Bytes { data };
+        let header = |bytes: &mut Bytes| -> Result<(u8, [usize; 6]), String> {
+            if bytes.take(4)? != b"TZif" {
+                return Err("Not a TZif file".to_string());
+            }
+            let version = bytes.take(16)?[0];
+            let mut counts = [0; 6];
+            for count in &mut counts {
+                *count = bytes.count()?;
+            }
+            Ok((version, counts))
+        };
+        let (version, counts) = header(&mut bytes)?;
+        // version 1 files only have 32 bit times, later ones repeat everything with 64 bit times after them
+        let (counts, time_size) = if version >= b'2' {
+            let [utc, standard, leap, times, types, characters] = counts;
+            bytes.take(times * 5 + types * 6 + characters + leap * 8 + standard + utc)?;
+            (header(&mut bytes)?.1, 8)
+        } else {
+            (counts, 4)
+        };
+        let [utc, standard, leap, times, types, characters] = counts;
+        if types == 0 {
+            return Err("The file has no local time types".to_string());
+        }
+        let times = bytes
+            .take(times * time_size)?
+            .chunks_exact(time_size)
+            .map(|time| match time_size {
+                8 => i64::from_be_bytes([time[0], time[1], time[2], time[3], time[4], time[5], time[6], time[7]]),
+                _ => i64::from(i32::from_be_bytes([time[0], time[1], time[2], time[3]])),
+            })
+            .collect::<Vec<_>>();
+        let indices = bytes.take(times.len())?.iter().map(|index| usize::from(*index)).collect::<Vec<_>>();
+        if indices.iter().any(|index| *index >= types) {
+            return Err("A transition is to a local time type that isn't in the file".to_string());
+        }
+        let offsets = bytes.take(types * 6)?.chunks_exact(6).map(|info| i64::from(i32::from_be_bytes([info[0], info[1], info[2], info[3]]))).collect();
+        bytes.take(characters + leap * (time_size + 4) + standard + utc)?;
+        let rule = if version >= b'2' {
+            let footer = std::str::from_utf8(bytes.data).map_err(|_| "The footer isn't text".to_string())?;
+            match footer.trim_matches('\n') {
+                "" => None,
+                text => Some(Rule::parse(text)?),
+            }
+        } else {
+            None
+        };
+        Ok(Zone { transitions: times.into_iter().zip(indices).collect(), offsets, rule })
+    }
+
+    /// The zone called `name` in the tz database, like `America/New_York`.
+    pub fn load(name: &str) -> Result<Zone, String> {
+        if name.is_empty() || name.ends_with(".tab") || name.split('/').any(|part| part.is_empty() || part == ".." || part == ".") {
+            return Err(format!("Unknown time zone {name:?}"));
+        }
+        let data = assets::bytes(&format!("{ZONEINFO}/{name}")).map_err(|_| format!("Unknown time zone {name:?}"))?;
+        Zone::parse(&data).map_err(|error| format!("Invalid time zone {name:?}: {error}"))
+    }
+
+    /// The offset from UTC at `time`, in seconds since the unix epoch.
+    pub fn offset(&self, time: i64) -> i64 {
+        match self.transitions.partition_point(|(transition, _)| *transition <= time) {
+            // RFC 8536 says the first local time type is used before the first transition
+            0 if self.transitions.is_empty() => self.rule.map_or(self.offsets[0], |rule| rule.offset(time)),
+            0 => self.offsets[0],
+            after if after == self.transitions.len() => self.rule.map_or_else(|| self.offsets[self.transitions[after - 1].1], |rule| rule.offset(time)),
+            after => self.offsets[self.transitions[after - 1].1],
+        }
+    }
+
+    /// The local time at `time`, in seconds since the unix epoch as though the zone were UTC.
+    pub fn to_local(&self, time: i64) -> i64 {
+        time + self.offset(time)
+    }
+
+    /// The first time that is `local` or later in the zone.
+    ///
+    /// When the clocks go back a local time happens twice, and the first time is taken. When they go
+    /// forward a local time can be skipped, and the time the clocks went forward is taken instead.
+    pub fn to_utc(&self, local: i64) -> i64 {
+        // offsets never change twice in a day, so one of the offsets a day either side is right
+        let (before, after) = (self.offset(local - SECONDS_PER_DAY), self.offset(local + SECONDS_PER_DAY));
+        let candidates = [local - before, local - after];
+        if let Some(time) = candidates.iter().copied().filter(|time| self.to_local(*time) == local).min() {
+            return time;
+        }
+        // skipped, so find when the clocks went forward, between the two
+        let (mut low, mut high) = (candidates[0].min(candidates[1]), candidates[0].max(candidates[1]));
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if self.to_local(middle) >= local {
+                high = middle;
+            } else {
+                low = middle + 1;
+            }
+        }
+        low
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[test]
+    fn calendar() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        for days in [-800_000, -1, 0, 59, 11_016, 19_000, 800_000] {
+            let (year, month, day) = civil_from_days(days);
+            assert_eq!(days_from_civil(year, month, day), days);
+        }
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        // 2024-03-10 was a sunday
+        assert_eq!(weekday(days_from_civil(2024, 3, 10)), 0);
+    }
+
+    #[parameterized(
+        second_sunday = {Day::Month { month: 3, week: 2, day: 0 }, 2024, (2024, 3, 10)},
+        last_sunday = {Day::Month { month: 10, week: 5, day: 0 }, 2024, (2024, 10, 27)},
+        last_thursday = {Day::Month { month: 2, week: 5, day: 4 }, 2024, (2024, 2, 29)},
+        julian = {Day::Julian(60), 2024, (2024, 3, 1)},
+        zero_based = {Day::Zero(59), 2024, (2024, 2, 29)},
+    )]
+    fn rule_days(day: Day, year: i64, date: (i64, u32, u32)) {
+        assert_eq!(civil_from_days(day.days(year)), date);
+    }
+
+    #[test]
+    fn rules() {
+        let eastern = Rule::parse("EST5EDT,M3.2.0,M11.1.0").expect("Expected a valid rule");
+        let march = days_from_civil(2024, 3, 10) * SECONDS_PER_DAY;
+        // the clocks go forward at 2am EST, 7am UTC
+        assert_eq!((eastern.offset(march + 7 * 3600 - 1), eastern.offset(march + 7 * 3600)), (-5 * 3600, -4 * 3600));
+        let november = days_from_civil(2024, 11, 3) * SECONDS_PER_DAY;
+        // and back at 2am EDT, 6am UTC
+        assert_eq!((eastern.offset(november + 6 * 3600 - 1), eastern.offset(november + 6 * 3600)), (-4 * 3600, -5 * 3600));
+        let sydney = Rule::parse("AEST-10AEDT,M10.1.0,M4.1.0/3").expect("Expected a valid rule");
+        assert_eq!(sydney.offset(days_from_civil(2024, 1, 1) * SECONDS_PER_DAY), 11 * 3600);
+        assert_eq!(sydney.offset(days_from_civil(2024, 7, 1) * SECONDS_PER_DAY), 10 * 3600);
+        let india = Rule::parse("<+0530>-5:30").expect("Expected a valid rule");
+        assert_eq!(india.offset(0), 5 * 3600 + 30 * 60);
+        assert_eq!(Rule::parse("EST5EDT").expect("Expected a valid rule"), eastern);
+        for invalid in ["", "E5", "EST", "EST5EDT,M3.2.0", "EST5EDT,M13.2.0,M11.1.0", "EST5EDT,J0,J10", "<+05", "EST5 "] {
+            Rule::parse(invalid).expect_err("Expected an invalid rule");
+        }
+    }
+
+    #[test]
+    fn zones() {
+        let new_york = Zone::load("America/New_York").expect("Expected the zone to be embedded");
+        // 2024-03-10 at 1:59:59 and 3:00 EDT, the clocks went forward past 2am
+        let march = days_from_civil(2024, 3, 10) * SECONDS_PER_DAY;
+        assert_eq!(new_york.offset(march + 7 * 3600 - 1), -5 * 3600);
+        assert_eq!(new_york.offset(march + 7 * 3600), -4 * 3600);
+        assert_eq!(new_york.to_utc(march + 2 * 3600 + 1800), march + 7 * 3600);
+        // 1:30am on 2024-11-03 happened twice
+        let november = days_from_civil(2024, 11, 3) * SECONDS_PER_DAY;
+        assert_eq!(new_york.to_utc(november + 3600 + 1800), november + 5 * 3600 + 1800);
+        // far in the future, from the rule in the footer
+        let future = days_from_civil(2100, 7, 1) * SECONDS_PER_DAY;
+        assert_eq!(new_york.offset(future), -4 * 3600);
+        // and long ago, before time zones, from local mean time
+        assert_eq!(new_york.offset(-4_000_000_000), -(4 * 3600 + 56 * 60 + 2));
+        assert_eq!(Zone::load("UTC").expect("Expected UTC").offset(future), 0);
+        let kolkata = Zone::load("Asia/Kolkata").expect("Expected the zone to be embedded");
+        assert_eq!(kolkata.to_utc(kolkata.to_local(future)), future);
+        for invalid in ["", "Not/AZone", "../etc/passwd", "/etc/passwd", "America/../UTC"] {
+            Zone::load(invalid).expect_err("Expected an invalid zone");
+        }
+        Zone::parse(b"TZif").expect_err("Expected a short file to be rejected");
+        Zone::parse(b"not a tz file at all, but long enough to have a header").expect_err("Expected a bad file to be rejected");
+    }
+
+    #[test]
+    fn skipped_midnight() {
+        // Havana moved its clocks forward at midnight, so 2024-03-10 started at 1am
+        let havana = Zone::load("America/Havana").expect("Expected the zone to be embedded");
+        let local = days_from_civil(2024, 3, 10) * SECONDS_PER_DAY;
+        let time = havana.to_utc(local);
+        assert_eq!(havana.offset(time), -4 * 3600);
+        assert_eq!(havana.to_local(time), local + 3600);
+        assert_eq!(havana.offset(time - 1), -5 * 3600);
+    }
+}
+// COV_EXCL_STOP
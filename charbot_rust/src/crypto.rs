@@ -0,0 +1,230 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Verifying the signatures discord puts on the interactions it sends to a bot's HTTP endpoint, and
+//! signing and verifying the payloads of webhooks, like GitHub's and Ko-fi's.
+//!
+//! Verifying interactions needs the `signatures` feature, for Ed25519.
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use crate::buffer::{Bytes, Data};
+use crate::metrics;
+
+#[cfg(feature = "signatures")]
+mod interaction; // COV_EXCL_LINE
+
+/// How digests and signatures are written as text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Encoding {
+    Hex,
+    Base64,
+}
+
+fn encoding(name: &str) -> Result<Encoding, String> {
+    match name {
+        "hex" => Ok(Encoding::Hex),
+        "base64" => Ok(Encoding::Base64),
+        _ => Err(format!("Invalid encoding {name:?}, it must be \"hex\" or \"base64\"")),
+    }
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 {
+        return None;
+    }
+    let digit = |digit: u8| char::from(digit).to_digit(16).map(|value| value as u8);
+    text.as_bytes().chunks_exact(2).map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?)).collect()
+}
+
+fn decode(text: &str, encoding: Encoding) -> Option<Vec<u8>> {
+    match encoding {
+        Encoding::Hex => from_hex(text),
+        Encoding::Base64 => BASE64.decode(text).ok(),
+    }
+}
+
+/// The HMAC-SHA256 of `message` with `key`, as in RFC 2104.
+pub(crate) fn hmac(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// A key or signature, as text like discord and webhooks give them, or as the bytes themselves.
+#[derive(FromPyObject)]
+enum Encoded {
+    #[pyo3(transparent)]
+    Text(String),
+    #[pyo3(transparent)]
+    Raw(Bytes),
+}
+
+/// The HMAC-SHA256 of `payload` keyed with `secret`, in `encoding`.
+fn sign_payload(secret: &[u8], payload: &[u8], encoding: Encoding) -> String {
+    let digest = hmac(secret, payload);
+    match encoding {
+        Encoding::Hex => hex(&digest),
+        Encoding::Base64 => BASE64.encode(digest),
+    }
+}
+
+/// Whether `signature` is the HMAC-SHA256 of `payload` keyed with `secret`.
+///
+/// Text can start with `sha256=`, like GitHub's `X-Hub-Signature-256`, and anything that isn't 32 bytes
+/// in `encoding` is just a signature that doesn't verify.
+fn verify_payload(secret: &[u8], payload: &[u8], signature: &Encoded, encoding: Encoding) -> bool {
+    let signature = match signature {
+        Encoded::Text(text) => decode(text.strip_prefix("sha256=").unwrap_or(text), encoding),
+        Encoded::Raw(bytes) => Some(bytes.to_vec()),
+    };
+    // compared in constant time, so how long it takes doesn't say how much of a guessed signature is right
+    signature.is_some_and(|signature| bool::from(hmac(secret, payload).ct_eq(&signature[..])))
+}
+
+#[pyfunction]
+#[pyo3(signature = (secret, payload, /, encoding="hex"), text_signature = "
+sign(secret, payload, /, encoding='hex')
+--
+
+Sign a webhook's payload with HMAC-SHA256, the way GitHub and most other webhooks are signed.
+
+Parameters
+----------
+secret : str | bytes-like
+    The webhook's secret. Text is used as UTF-8.
+payload : str | bytes-like
+    The payload, exactly as it's sent.
+encoding : {'hex', 'base64'}
+    How to write the signature. The default is 'hex', which is what GitHub uses, without its
+    ``sha256=`` prefix.
+
+Returns
+-------
+str
+    The signature.
+
+Raises
+------
+ValueError
+    If the encoding isn't 'hex' or 'base64'.
+")]
+fn sign(secret: Data, payload: Data, encoding: &str) -> PyResult<String> {
+    let encoding = self::encoding(encoding).map_err(PyErr::new::<PyValueError, _>)?;
+    Ok(sign_payload(secret.as_bytes(), payload.as_bytes(), encoding))
+}
+
+#[pyfunction]
+#[pyo3(signature = (secret, payload, signature, /, encoding="hex"), text_signature = "
+verify(secret, payload, signature, /, encoding='hex')
+--
+
+Check a webhook's HMAC-SHA256 signature in constant time, so how long it takes doesn't give away how
+much of a forged signature is right, like comparing the strings in python would.
+
+Parameters
+----------
+secret : str | bytes-like
+    The webhook's secret. Text is used as UTF-8.
+payload : str | bytes-like
+    The payload, exactly as it was received.
+signature : str | bytes-like
+    The signature that came with it, as text in the encoding, which can start with ``sha256=`` like
+    GitHub's ``X-Hub-Signature-256`` header, or as the 32 bytes.
+encoding : {'hex', 'base64'}
+    How the signature is written, if it's text. The default is 'hex'.
+
+Returns
+-------
+bool
+    Whether it's the payload's signature. It's False for a signature that isn't 32 bytes in the
+    encoding.
+
+Raises
+------
+ValueError
+    If the encoding isn't 'hex' or 'base64'.
+")]
+fn verify(secret: Data, payload: Data, signature: Encoded, encoding: &str) -> PyResult<bool> {
+    let encoding = self::encoding(encoding).map_err(PyErr::new::<PyValueError, _>)?;
+    Ok(metrics::timed("crypto.verify", || verify_payload(secret.as_bytes(), payload.as_bytes(), &signature, encoding)))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based verification of the signatures of discord interactions and webhooks";
+
+pub(crate) fn register_crypto(py: Python, m: &PyModule) -> PyResult<()> {
+    let crypto = PyModule::new(py, "_crypto")?;
+    #[cfg(feature = "signatures")]
+    crypto.add_function(wrap_pyfunction!(interaction::verify_interaction, crypto)?)?;
+    crypto.add_function(wrap_pyfunction!(sign, crypto)?)?;
+    crypto.add_function(wrap_pyfunction!(verify, crypto)?)?;
+    crypto.add("__doc__", DOCSTRING)?;
+    m.add_submodule(crypto)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn hex(text: &str) -> Encoded {
+        Encoded::Text(String::from(text))
+    }
+
+    // the example from GitHub's documentation on validating webhook deliveries
+    const SECRET: &[u8] = b"It's a Secret to Everybody";
+    const PAYLOAD: &[u8] = b"Hello, World!";
+
+    #[parameterized(
+        hex = {Encoding::Hex, "757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"},
+        base64 = {Encoding::Base64, "dXEH6g6yUJ/CESIczphLijdXC211hsIsRvQ3nIsEPhc="},
+    )]
+    fn webhooks(encoding: Encoding, signature: &str) {
+        assert_eq!(sign_payload(SECRET, PAYLOAD, encoding), signature);
+        assert!(verify_payload(SECRET, PAYLOAD, &hex(signature), encoding));
+        assert!(!verify_payload(SECRET, b"Hello, World?", &hex(signature), encoding));
+        assert!(!verify_payload(b"It's a secret to everybody", PAYLOAD, &hex(signature), encoding));
+    }
+
+    // the test cases from RFC 4231
+    #[parameterized(
+        short_data = {&[0x0b; 20], b"Hi There", "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"},
+        short_key = {b"Jefe", b"what do ya want for nothing?", "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"},
+        long_key = {&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First", "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"},
+    )]
+    fn authenticates(key: &[u8], message: &[u8], digest: &str) {
+        assert_eq!(super::hex(&hmac(key, message)), digest);
+    }
+
+    #[parameterized(
+        github = {"sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17", Encoding::Hex, true},
+        uppercase = {"757107EA0EB2509FC211221CCE984B8A37570B6D7586C22C46F4379C8B043E17", Encoding::Hex, true},
+        unpadded = {"dXEH6g6yUJ/CESIczphLijdXC211hsIsRvQ3nIsEPhc", Encoding::Base64, false},
+        truncated = {"757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e", Encoding::Hex, false},
+        wrong_encoding = {"dXEH6g6yUJ/CESIczphLijdXC211hsIsRvQ3nIsEPhc=", Encoding::Hex, false},
+        not_base64 = {"dXEH6g6yUJ/CESIczphLijdXC211hsIsRvQ3nIsEPh!", Encoding::Base64, false},
+        empty = {"", Encoding::Hex, false},
+    )]
+    fn signature_forms(signature: &str, encoding: Encoding, valid: bool) {
+        assert_eq!(verify_payload(SECRET, PAYLOAD, &hex(signature), encoding), valid);
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(encoding("hex"), Ok(Encoding::Hex));
+        assert_eq!(encoding("base64"), Ok(Encoding::Base64));
+        assert_eq!(encoding("HEX"), Err(String::from("Invalid encoding \"HEX\", it must be \"hex\" or \"base64\"")));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,157 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::buffer::Data;
+use crate::crypto::{from_hex, Encoded};
+use crate::metrics;
+
+// by the bytes of the key, as decompressing its point is most of the work that isn't verifying
+static KEYS: Lazy<Mutex<HashMap<[u8; 32], VerifyingKey>>> = Lazy::new(Mutex::default);
+
+impl Encoded {
+    /// The `N` bytes it's the encoding of, or `None` if it's the wrong length or isn't hex.
+    fn decode<const N: usize>(&self) -> Option<[u8; N]> {
+        match self {
+            Encoded::Text(text) => from_hex(text)?.try_into().ok(),
+            Encoded::Raw(bytes) => (**bytes).try_into().ok(),
+        }
+    }
+}
+
+/// The key that 32 bytes are, decompressed the first time it's used.
+fn public_key(bytes: [u8; 32]) -> Result<VerifyingKey, String> {
+    let mut keys = KEYS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(key) = keys.get(&bytes) {
+        return Ok(*key);
+    }
+    let key = VerifyingKey::from_bytes(&bytes).map_err(|_| String::from("Invalid public key, it isn't a point of Ed25519"))?;
+    keys.insert(bytes, key);
+    Ok(key)
+}
+
+/// Whether `signature` is the signature by `public_key` of `timestamp` followed by `body`.
+///
+/// A signature that isn't 64 bytes, or isn't hex, is just one that doesn't verify, as it's what the
+/// request says, but a key that isn't one is an error, as it's the bot's own. It's verified strictly, so
+/// a signature can't be changed and still verify, and weak keys verify nothing.
+fn verify_request(public_key: &Encoded, signature: &Encoded, timestamp: &[u8], body: &[u8]) -> Result<bool, String> {
+    let key = public_key
+        .decode::<32>()
+        .ok_or_else(|| String::from("Invalid public key, it must be 32 bytes or 64 hex digits"))
+        .and_then(self::public_key)?;
+    let Some(signature) = signature.decode::<64>() else {
+        return Ok(false);
+    };
+    let message = [timestamp, body].concat();
+    Ok(key.verify_strict(&message, &Signature::from_bytes(&signature)).is_ok())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+verify_interaction(public_key, signature, timestamp, body, /)
+--
+
+Check that an interaction sent to the bot's HTTP endpoint was signed by discord, which has to be
+done for every request, answering ones that aren't with a 401.
+
+The signature is checked in constant time, and the key is only parsed the first time it's used.
+
+Parameters
+----------
+public_key : str | bytes-like
+    The application's public key, from the developer portal, as hex or as the 32 bytes.
+signature : str | bytes-like
+    The ``X-Signature-Ed25519`` header, as hex or as the 64 bytes.
+timestamp : str | bytes-like
+    The ``X-Signature-Timestamp`` header.
+body : str | bytes-like
+    The body of the request, exactly as it was sent.
+
+Returns
+-------
+bool
+    Whether the signature is discord's. It's False for a signature that isn't 64 bytes or isn't hex.
+
+Raises
+------
+ValueError
+    If the public key isn't 32 bytes or 64 hex digits, or isn't a key of Ed25519.
+")]
+pub(crate) fn verify_interaction(py: Python, public_key: Encoded, signature: Encoded, timestamp: Data, body: Data) -> PyResult<bool> {
+    metrics::observe("crypto.verify_interaction", || {
+        py.allow_threads(|| verify_request(&public_key, &signature, timestamp.as_bytes(), body.as_bytes()))
+    })
+    .map_err(PyErr::new::<PyValueError, _>)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const KEY: &str = "03a107bff3ce10be1d70dd18e74bc09967e4d6309ba50d5f1ddc8664125531b8";
+    const SIGNATURE: &str = "c1098e97d711377f30225d53d94b89d43537f92e5b3afaddc590781b1f9f9d4b2eeab335d370be3b9a090bc61a85b86448bc140dcd195f1569c2bff181257607";
+
+    fn hex(text: &str) -> Encoded {
+        Encoded::Text(String::from(text))
+    }
+
+    #[test]
+    fn interaction() {
+        assert_eq!(verify_request(&hex(KEY), &hex(SIGNATURE), b"1700000000", b"{\"type\":1}"), Ok(true));
+        assert_eq!(verify_request(&hex(&KEY.to_uppercase()), &hex(SIGNATURE), b"1700000000", b"{\"type\":1}"), Ok(true));
+        assert_eq!(verify_request(&hex(KEY), &hex(SIGNATURE), b"1700000001", b"{\"type\":1}"), Ok(false));
+        assert_eq!(verify_request(&hex(KEY), &hex(SIGNATURE), b"1700000000", b"{\"type\":2}"), Ok(false));
+        assert!(KEYS.lock().unwrap_or_else(PoisonError::into_inner).contains_key(&hex(KEY).decode::<32>().unwrap()));
+    }
+
+    // from section 7.1 of RFC 8032
+    #[parameterized(
+        empty = {
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+            b"",
+        },
+        one_byte = {
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+            b"\x72",
+        },
+    )]
+    fn rfc_8032(key: &str, signature: &str, message: &[u8]) {
+        // the timestamp and the body are only put together, so how the message is split up doesn't matter
+        assert_eq!(verify_request(&hex(key), &hex(signature), b"", message), Ok(true));
+        assert_eq!(verify_request(&hex(key), &hex(signature), message, b""), Ok(true));
+        let changed = format!("{}{:x}{}", &signature[..11], (u8::from_str_radix(&signature[11..12], 16).unwrap() ^ 1), &signature[12..]);
+        assert_eq!(verify_request(&hex(key), &hex(&changed), b"", message), Ok(false));
+    }
+
+    #[test]
+    fn malformed_signatures() {
+        for signature in ["", &SIGNATURE[2..], &format!("{SIGNATURE}00"), &SIGNATURE.replace('c', "g")] {
+            assert_eq!(verify_request(&hex(KEY), &hex(signature), b"1700000000", b"{\"type\":1}"), Ok(false), "{signature}");
+        }
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert_eq!(
+            verify_request(&hex(&KEY[2..]), &hex(SIGNATURE), b"", b""),
+            Err(String::from("Invalid public key, it must be 32 bytes or 64 hex digits"))
+        );
+        assert_eq!(
+            verify_request(&hex(&format!("02{}", "0".repeat(62))), &hex(SIGNATURE), b"", b""),
+            Err(String::from("Invalid public key, it isn't a point of Ed25519"))
+        );
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,270 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod expr; // COV_EXCL_LINE
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use crate::dice::expr::{format_terms, join_signed, parse, Keep, Term};
+use crate::metrics;
+
+// COV_EXCL_START
+#[pyclass(module = "dice")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expression {
+    terms: Vec<Term>,
+}
+// COV_EXCL_STOP
+
+impl Expression {
+    fn roll_with(&self, rng: &mut StdRng) -> Roll {
+        let terms = self
+            .terms
+            .iter()
+            .map(|term| {
+                let rolls = match term.sides {
+                    Some(sides) => (0..term.count).map(|_| rng.gen_range(1..=sides)).collect(),
+                    None => Vec::new(),
+                };
+                TermRoll::new(*term, rolls)
+            })
+            .collect::<Vec<_>>();
+        Roll { total: terms.iter().map(|term| term.total).sum(), terms }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Expression {
+    #[new]
+    fn __new__(text: &str) -> PyResult<Self> { // COV_EXCL_LINE
+        let terms = parse(text).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Expression { terms })
+    }
+
+    #[getter]
+    fn terms(&self) -> Vec<Term> {
+        self.terms.clone()
+    }
+
+    /// The lowest total the expression can roll.
+    #[getter]
+    fn min(&self) -> i64 {
+        self.terms.iter().map(|term| term.range().0).sum()
+    }
+
+    #[getter]
+    fn max(&self) -> i64 {
+        self.terms.iter().map(|term| term.range().1).sum()
+    }
+
+    #[pyo3(signature = (seed=None))]
+    fn roll(&self, seed: Option<u64>) -> Roll {
+        metrics::timed("dice.Expression.roll", || {
+            let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+            self.roll_with(&mut rng)
+        })
+    }
+
+    fn __str__(&self) -> String {
+        format_terms(&self.terms)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Expression({:?})", format_terms(&self.terms))
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "dice")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermRoll {
+    #[pyo3(get)]
+    term: Term,
+    /// What each die rolled, in the order they were rolled, empty for a constant.
+    #[pyo3(get)]
+    rolls: Vec<u32>,
+    /// Whether each die counts towards the total.
+    #[pyo3(get)]
+    kept: Vec<bool>,
+    /// What the term added to the total, negative if it was subtracted.
+    #[pyo3(get)]
+    total: i64,
+}
+// COV_EXCL_STOP
+
+impl TermRoll {
+    fn new(term: Term, rolls: Vec<u32>) -> Self {
+        let mut kept = vec![true; rolls.len()];
+        if let Some(Keep { highest, count }) = term.keep {
+            let mut order = (0..rolls.len()).collect::<Vec<_>>();
+            order.sort_by_key(|index| rolls[*index]);
+            let dropped = rolls.len() - count as usize;
+            let dropped = if highest { &order[..dropped] } else { &order[count as usize..] };
+            for index in dropped {
+                kept[*index] = false;
+            }
+        }
+        let total = match term.sides {
+            Some(_) => rolls.iter().zip(&kept).filter(|(_, kept)| **kept).map(|(roll, _)| i64::from(*roll)).sum(),
+            None => i64::from(term.count),
+        };
+        TermRoll { term, rolls, kept, total: if term.negative { -total } else { total } }
+    }
+
+    /// The term with what each die rolled, dropped dice struck through, like `4d6kh3 (6, 5, ~~2~~, 4)`.
+    fn breakdown(&self) -> String {
+        if self.term.sides.is_none() {
+            return self.term.to_string();
+        }
+        let rolls = self
+            .rolls
+            .iter()
+            .zip(&self.kept)
+            .map(|(roll, kept)| if *kept { roll.to_string() } else { format!("~~{roll}~~") })
+            .collect::<Vec<_>>();
+        format!("{} ({})", self.term, rolls.join(", "))
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl TermRoll {
+    fn __str__(&self) -> String {
+        self.breakdown()
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "dice")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Roll {
+    #[pyo3(get)]
+    total: i64,
+    #[pyo3(get)]
+    terms: Vec<TermRoll>,
+}
+// COV_EXCL_STOP
+
+impl Roll {
+    fn breakdown(&self) -> String {
+        let terms = join_signed(self.terms.iter().map(|term| (term.term.negative, term.breakdown())));
+        format!("{terms} = {}", self.total)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Roll {
+    /// The breakdown of the roll for display, like `4d6kh3 (6, 5, ~~2~~, 4) + 2 = 17`.
+    fn __str__(&self) -> String {
+        self.breakdown()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Roll(total={})", self.total)
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (expression, seed=None), text_signature = "
+roll(expression, seed=None, /)
+--
+
+Parse a dice expression and roll it.
+
+Parameters
+----------
+expression : str
+    The expression, like ``4d6kh3+2``, ``2d20adv`` or ``d%``.
+seed : int | None
+    The seed for the dice, so a roll can be reproduced. The default is None, for a random roll.
+
+Returns
+-------
+Roll
+    The total, and what each die rolled. ``str(roll)`` is a breakdown of the roll for display.
+
+Raises
+------
+ValueError
+    If the expression isn't valid.
+")]
+fn roll(expression: &str, seed: Option<u64>) -> PyResult<Roll> {
+    metrics::observe("dice.roll", || Ok(Expression::__new__(expression)?.roll(seed)))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based dice expression parser and roller";
+
+pub(crate) fn register_dice(py: Python, m: &PyModule) -> PyResult<()> {
+    let dice = PyModule::new(py, "_dice")?;
+    dice.add_class::<Keep>()?;
+    dice.add_class::<Term>()?;
+    dice.add_class::<Expression>()?;
+    dice.add_class::<TermRoll>()?;
+    dice.add_class::<Roll>()?;
+    dice.add_function(wrap_pyfunction!(roll, dice)?)?;
+    dice.add("__doc__", DOCSTRING)?;
+    m.add_submodule(dice)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expression(text: &str) -> Expression {
+        Expression::__new__(text).expect("Valid expression")
+    }
+
+    #[test]
+    fn invalid() {
+        Expression::__new__("2d").expect_err("Expected the expression to be rejected");
+    }
+
+    #[test]
+    fn bounds() {
+        let expression = expression("4d6kh3 - d4 + 2");
+        assert_eq!((expression.min(), expression.max()), (1, 19));
+        for seed in 0..200 {
+            let roll = expression.roll(Some(seed));
+            assert!((1..=19).contains(&roll.total));
+            assert_eq!(roll.terms[0].rolls.len(), 4);
+            assert_eq!(roll.terms[0].kept.iter().filter(|kept| **kept).count(), 3);
+            assert!(roll.terms[0].rolls.iter().all(|roll| (1..=6).contains(roll)));
+            assert_eq!(roll.terms[2].total, 2);
+        }
+    }
+
+    #[test]
+    fn seeded() {
+        let expression = expression("10d20 + d%");
+        assert_eq!(expression.roll(Some(5)), expression.roll(Some(5)));
+    }
+
+    #[test]
+    fn keeps() {
+        let term = |text: &str| parse(text).expect("Valid expression")[0];
+        let highest = TermRoll::new(term("4d6kh3"), vec![2, 5, 2, 6]);
+        assert_eq!(highest.kept, vec![false, true, true, true]);
+        assert_eq!(highest.total, 13);
+        let lowest = TermRoll::new(term("-2d20dis"), vec![17, 4]);
+        assert_eq!(lowest.kept, vec![false, true]);
+        assert_eq!(lowest.total, -4);
+        let all = TermRoll::new(term("3d8"), vec![8, 1, 3]);
+        assert_eq!(all.total, 12);
+    }
+
+    #[test]
+    fn breakdown() {
+        let terms = parse("4d6kh3 - 1d4 + 2").expect("Valid expression");
+        let terms = vec![
+            TermRoll::new(terms[0], vec![6, 5, 2, 4]),
+            TermRoll::new(terms[1], vec![3]),
+            TermRoll::new(terms[2], Vec::new()),
+        ];
+        let roll = Roll { total: terms.iter().map(|term| term.total).sum(), terms };
+        assert_eq!(roll.total, 14);
+        assert_eq!(roll.breakdown(), "4d6kh3 (6, 5, ~~2~~, 4) - 1d4 (3) + 2 = 14");
+    }
+}
+// COV_EXCL_STOP
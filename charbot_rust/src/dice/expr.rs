@@ -0,0 +1,281 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::fmt::{Display, Error, Formatter};
+use pyo3::prelude::*;
+// COV_EXCL_STOP
+
+/// Limits so a single expression can't take long to roll or fill a message.
+const MAX_DICE: u32 = 100;
+const MAX_SIDES: u32 = 1000;
+const MAX_CONSTANT: u32 = 10_000;
+const MAX_TERMS: usize = 20;
+
+/// Which dice of a roll count towards its total, the rest are dropped.
+#[pyclass(module = "dice")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Keep {
+    /// Whether the highest dice are kept, otherwise the lowest are.
+    #[pyo3(get)]
+    pub highest: bool,
+    #[pyo3(get)]
+    pub count: u32,
+}
+
+/// One part of an expression, a number of dice or a constant, added or subtracted.
+#[pyclass(module = "dice")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Term {
+    #[pyo3(get)]
+    pub negative: bool,
+    /// The number of dice, or the value of a constant.
+    #[pyo3(get)]
+    pub count: u32,
+    /// The sides of each die, None for a constant.
+    #[pyo3(get)]
+    pub sides: Option<u32>,
+    #[pyo3(get)]
+    pub keep: Option<Keep>,
+}
+
+impl Term {
+    /// How many dice count towards the total.
+    fn kept(&self) -> u32 {
+        self.keep.map_or(self.count, |keep| keep.count)
+    }
+
+    /// The lowest and highest the term can add to the total.
+    pub fn range(&self) -> (i64, i64) {
+        let (low, high) = match self.sides {
+            None => (i64::from(self.count), i64::from(self.count)),
+            Some(sides) => (i64::from(self.kept()), i64::from(self.kept()) * i64::from(sides)),
+        };
+        if self.negative {
+            (-high, -low)
+        } else {
+            (low, high)
+        }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Term {
+    #[getter]
+    fn is_constant(&self) -> bool {
+        self.sides.is_none()
+    }
+
+    fn __str__(&self) -> String {
+        self.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Term({}{self})", if self.negative { "-" } else { "+" })
+    }
+}
+
+// COV_EXCL_START
+impl Display for Term {
+    /// The term without its sign, like `4d6kh3` or `2`.
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        match self.sides {
+            None => write!(formatter, "{}", self.count),
+            Some(sides) => {
+                write!(formatter, "{}d{sides}", self.count)?;
+                match self.keep {
+                    Some(keep) => write!(formatter, "k{}{}", if keep.highest { "h" } else { "l" }, keep.count),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+// COV_EXCL_STOP
+
+/// Join terms written out, and whether each is subtracted, with their signs, like `4d6kh3 + 2`.
+pub fn join_signed(parts: impl Iterator<Item = (bool, String)>) -> String {
+    let mut text = String::new();
+    for (index, (negative, part)) in parts.enumerate() {
+        match (index, negative) {
+            (0, false) => {}
+            (0, true) => text.push('-'),
+            (_, false) => text.push_str(" + "),
+            (_, true) => text.push_str(" - "),
+        }
+        text.push_str(&part);
+    }
+    text
+}
+
+pub fn format_terms(terms: &[Term]) -> String {
+    join_signed(terms.iter().map(|term| (term.negative, term.to_string())))
+}
+
+/// Take `expected` from the start of the rest of the expression if it's there.
+fn eat(chars: &[char], pos: &mut usize, expected: &str) -> bool {
+    let matches = expected.chars().enumerate().all(|(offset, char)| chars.get(*pos + offset) == Some(&char));
+    if matches {
+        *pos += expected.chars().count();
+    }
+    matches
+}
+
+fn number(chars: &[char], pos: &mut usize) -> Result<Option<u32>, String> {
+    let digits = chars[*pos..].iter().take_while(|char| char.is_ascii_digit()).collect::<String>();
+    if digits.is_empty() {
+        return Ok(None);
+    }
+    *pos += digits.len();
+    digits.parse().map(Some).map_err(|_| format!("The number {digits} is too big"))
+}
+
+fn unexpected(chars: &[char], pos: usize) -> String {
+    match chars.get(pos) {
+        Some(char) => format!("Unexpected {char:?} at position {}", pos + 1),
+        None => "The expression ended too soon".to_string(),
+    }
+}
+
+/// A keep or drop after some dice, which changes the number of dice for advantage on a single die.
+fn keep(chars: &[char], pos: &mut usize, count: &mut u32) -> Result<Option<Keep>, String> {
+    let advantage = eat(chars, pos, "adv");
+    if advantage || eat(chars, pos, "dis") {
+        if *count == 1 {
+            *count = 2;
+        }
+        return Ok(Some(Keep { highest: advantage, count: 1 }));
+    }
+    let (highest, drop) = if eat(chars, pos, "kl") {
+        (false, false)
+    } else if eat(chars, pos, "kh") || eat(chars, pos, "k") {
+        (true, false)
+    } else if eat(chars, pos, "dh") {
+        (false, true)
+    } else if eat(chars, pos, "dl") {
+        (true, true)
+    } else {
+        return Ok(None);
+    };
+    let amount = number(chars, pos)?.ok_or_else(|| unexpected(chars, *pos))?;
+    if drop {
+        if amount >= *count {
+            return Err(format!("Can't drop {amount} of {count} dice"));
+        }
+        Ok(Some(Keep { highest, count: *count - amount }))
+    } else {
+        if amount == 0 || amount > *count {
+            return Err(format!("Can't keep {amount} of {count} dice"));
+        }
+        Ok(Some(Keep { highest, count: amount }))
+    }
+}
+
+fn term(chars: &[char], pos: &mut usize, negative: bool) -> Result<Term, String> {
+    let count = number(chars, pos)?;
+    if !eat(chars, pos, "d") {
+        let value = count.ok_or_else(|| unexpected(chars, *pos))?;
+        if value > MAX_CONSTANT {
+            return Err(format!("Constants can be at most {MAX_CONSTANT}, not {value}"));
+        }
+        return Ok(Term { negative, count: value, sides: None, keep: None });
+    }
+    let mut count = count.unwrap_or(1);
+    let sides = if eat(chars, pos, "%") { Some(100) } else { number(chars, pos)? };
+    let sides = sides.ok_or_else(|| unexpected(chars, *pos))?;
+    if !(1..=MAX_DICE).contains(&count) {
+        return Err(format!("The number of dice must be from 1 to {MAX_DICE}, not {count}"));
+    }
+    if !(1..=MAX_SIDES).contains(&sides) {
+        return Err(format!("Dice must have from 1 to {MAX_SIDES} sides, not {sides}"));
+    }
+    let keep = keep(chars, pos, &mut count)?;
+    Ok(Term { negative, count, sides: Some(sides), keep })
+}
+
+/// Parse an expression like `4d6kh3+2`, `2d20adv` or `d%`, ignoring spaces and case.
+///
+/// Dice are written as `NdS`, with an optional `N` of 1 and `%` for 100 sides, followed by `khN`
+/// (or `kN`) or `klN` to keep the highest or lowest `N`, `dhN` or `dlN` to drop them, or `adv` and `dis`
+/// to keep the highest or lowest of two. Terms are added or subtracted with `+` and `-`.
+pub fn parse(text: &str) -> Result<Vec<Term>, String> {
+    let chars = text.chars().filter(|char| !char.is_whitespace()).flat_map(char::to_lowercase).collect::<Vec<_>>();
+    let mut pos = 0;
+    let mut terms = Vec::new();
+    while pos < chars.len() || terms.is_empty() {
+        let negative = match chars.get(pos) {
+            Some('+') => false,
+            Some('-') => true,
+            _ if terms.is_empty() => {
+                terms.push(term(&chars, &mut pos, false)?);
+                continue;
+            }
+            _ => return Err(unexpected(&chars, pos)),
+        };
+        pos += 1;
+        terms.push(term(&chars, &mut pos, negative)?);
+        if terms.len() > MAX_TERMS {
+            return Err(format!("Expressions can have at most {MAX_TERMS} terms"));
+        }
+    }
+    Ok(terms)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        keep_highest = {"4d6kh3+2", "4d6kh3 + 2"},
+        spaces_and_case = {" 4D6 K3 + 2 ", "4d6kh3 + 2"},
+        advantage = {"2d20adv", "2d20kh1"},
+        single_advantage = {"d20adv", "2d20kh1"},
+        disadvantage = {"d20dis-1", "2d20kl1 - 1"},
+        percentile = {"d%", "1d100"},
+        drop_lowest = {"4d6dl1", "4d6kh3"},
+        drop_highest = {"3d8dh2", "3d8kl1"},
+        negative_first = {"-2+d4", "-2 + 1d4"},
+        constant = {"7", "7"},
+    )]
+    fn parses(text: &str, expected: &str) {
+        assert_eq!(format_terms(&parse(text).expect("Valid expression")), expected);
+    }
+
+    #[parameterized(
+        empty = {""},
+        trailing_sign = {"d6+"},
+        no_sides = {"2d"},
+        zero_dice = {"0d6"},
+        zero_sides = {"1d0"},
+        too_many_dice = {"101d6"},
+        too_many_sides = {"d1001"},
+        keep_too_many = {"2d6kh3"},
+        keep_none = {"2d6kl0"},
+        drop_all = {"2d6dl2"},
+        keep_without_count = {"2d6kh"},
+        big_number = {"99999999999d6"},
+        big_constant = {"10001"},
+        junk = {"d6x"},
+        two_signs = {"d6+-2"},
+        missing_sign = {"d6d6"},
+    )]
+    fn invalid(text: &str) {
+        parse(text).expect_err("Expected the expression to be rejected");
+    }
+
+    #[test]
+    fn too_many_terms() {
+        assert!(parse(&["1"; 20].join("+")).is_ok());
+        parse(&["1"; 21].join("+")).expect_err("Expected the expression to be rejected");
+    }
+
+    #[test]
+    fn ranges() {
+        let terms = parse("4d6kh3 - 1d4 + 2").expect("Valid expression");
+        let ranges = terms.iter().map(Term::range).collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(3, 18), (-4, -1), (2, 2)]);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,227 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDateTime, PyDelta};
+use crate::cooldowns;
+use crate::metrics;
+
+/// The styles discord can show a timestamp in, see the docs of `timestamp` for what each looks like.
+const STYLES: [char; 7] = ['t', 'T', 'd', 'D', 'f', 'F', 'R'];
+
+fn style(style: &str) -> Result<char, String> {
+    let mut letters = style.chars();
+    match (letters.next(), letters.next()) {
+        (Some(letter), None) if STYLES.contains(&letter) => Ok(letter),
+        _ => Err(format!("Invalid timestamp style {style:?}, it must be one of t, T, d, D, f, F or R")),
+    }
+}
+
+/// The markup discord shows as `timestamp` in `style`, in each user's own time zone and language.
+fn markup(timestamp: i64, style_letter: &str) -> Result<String, String> {
+    let letter = style(style_letter)?;
+    Ok(format!("<t:{timestamp}:{letter}>"))
+}
+
+/// A time as a `datetime.datetime` or a unix timestamp in seconds.
+///
+/// Datetimes are turned into timestamps by python, so naive ones are local time like `datetime.timestamp()`,
+/// and fractions of a second are dropped towards the past, so the markup never shows a time later than it is.
+struct Moment(i64);
+
+// COV_EXCL_START
+impl<'source> FromPyObject<'source> for Moment {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if ob.is_instance_of::<PyDateTime>()? {
+            let timestamp = ob.call_method0("timestamp")?.extract::<f64>()?;
+            Ok(Moment(timestamp.floor() as i64))
+        } else if let Ok(timestamp) = ob.extract::<i64>() {
+            Ok(Moment(timestamp))
+        } else if let Ok(timestamp) = ob.extract::<f64>() {
+            Ok(Moment(timestamp.floor() as i64))
+        } else {
+            Err(PyTypeError::new_err(format!("{} isn't a time, use a datetime or a unix timestamp", ob.get_type().name()?)))
+        }
+    }
+}
+// COV_EXCL_STOP
+
+/// When a countdown ends, as a time or as how long from now.
+enum Until {
+    At(i64),
+    In(f64),
+}
+
+// COV_EXCL_START
+impl<'source> FromPyObject<'source> for Until {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if ob.is_instance_of::<PyDelta>()? {
+            Ok(Until::In(ob.call_method0("total_seconds")?.extract()?))
+        } else {
+            ob.extract().map(|Moment(timestamp)| Until::At(timestamp))
+        }
+    }
+}
+// COV_EXCL_STOP
+
+impl Until {
+    /// The unix timestamp the countdown ends at, going from `now`.
+    fn timestamp(&self, now: i64) -> Result<i64, String> {
+        match *self {
+            Until::At(timestamp) => Ok(timestamp),
+            Until::In(seconds) if seconds.is_finite() => cooldowns::check(now.saturating_add(seconds.ceil() as i64)),
+            Until::In(seconds) => Err(format!("Invalid countdown of {seconds} seconds")),
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (dt, style="f"), text_signature = "
+timestamp(dt, style='f', /)
+--
+
+The markup discord shows as a time, like '<t:1709737445:f>', in each user's own time zone and
+language.
+
+Parameters
+----------
+dt : datetime.datetime | int | float
+    The time, as a datetime or a unix timestamp in seconds. Naive datetimes are local time, like
+    datetime.timestamp(), and fractions of a second are dropped.
+style : str
+    How discord shows the time, 't' for '3:04 PM', 'T' for '3:04:05 PM', 'd' for '03/06/2024', 'D'
+    for 'March 6, 2024', 'f' for 'March 6, 2024 3:04 PM', 'F' for 'Wednesday, March 6, 2024 3:04 PM'
+    and 'R' for 'in 2 hours'. The default is 'f', like discord's.
+
+Returns
+-------
+str
+    The markup.
+
+Raises
+------
+ValueError
+    If the style isn't one of the letters above.
+")]
+fn timestamp(dt: Moment, style: &str) -> PyResult<String> {
+    metrics::observe("discordfmt.timestamp", || markup(dt.0, style).map_err(PyErr::new::<PyValueError, _>))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+relative(dt, /)
+--
+
+The markup discord shows as how long ago or until a time, like 'in 2 hours', which it keeps up to
+date.
+
+Parameters
+----------
+dt : datetime.datetime | int | float
+    The time, as a datetime or a unix timestamp in seconds. Naive datetimes are local time, like
+    datetime.timestamp().
+
+Returns
+-------
+str
+    The markup, like '<t:1709737445:R>'.
+")]
+fn relative(dt: Moment) -> String {
+    metrics::timed("discordfmt.relative", || format!("<t:{}:R>", dt.0))
+}
+
+#[pyfunction]
+#[pyo3(signature = (until, now=None), text_signature = "
+countdown(until, now=None, /)
+--
+
+The markup discord shows as a countdown to a time, like 'in 5 minutes', for giveaways and events
+ending.
+
+Parameters
+----------
+until : datetime.datetime | datetime.timedelta | int | float
+    When the countdown ends, as a datetime or a unix timestamp in seconds, or as a timedelta from now.
+    Fractions of a second in a timedelta are rounded up, so the countdown never ends early.
+now : int | None
+    The unix timestamp in seconds a timedelta is from. The default is None, for now.
+
+Returns
+-------
+str
+    The markup, like '<t:1709737445:R>'.
+
+Raises
+------
+ValueError
+    If a timedelta ends outside of year 1 to 9999.
+")]
+fn countdown(until: Until, now: Option<i64>) -> PyResult<String> {
+    metrics::observe("discordfmt.countdown", || {
+        let timestamp = until.timestamp(now.unwrap_or_else(cooldowns::now)).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(format!("<t:{timestamp}:R>"))
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based discord markup for timestamps";
+
+pub(crate) fn register_discordfmt(py: Python, m: &PyModule) -> PyResult<()> {
+    let discordfmt = PyModule::new(py, "_discordfmt")?;
+    discordfmt.add_function(wrap_pyfunction!(timestamp, discordfmt)?)?;
+    discordfmt.add_function(wrap_pyfunction!(relative, discordfmt)?)?;
+    discordfmt.add_function(wrap_pyfunction!(countdown, discordfmt)?)?;
+    discordfmt.add("__doc__", DOCSTRING)?;
+    m.add_submodule(discordfmt)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        short_time = {"t", "<t:1709737445:t>"},
+        long_time = {"T", "<t:1709737445:T>"},
+        short_date = {"d", "<t:1709737445:d>"},
+        long_date = {"D", "<t:1709737445:D>"},
+        short_datetime = {"f", "<t:1709737445:f>"},
+        long_datetime = {"F", "<t:1709737445:F>"},
+        relative = {"R", "<t:1709737445:R>"},
+    )]
+    fn styles(letter: &str, expected: &str) {
+        assert_eq!(markup(1_709_737_445, letter), Ok(expected.to_string()));
+    }
+
+    #[parameterized(
+        lowercase = {"r"},
+        unknown = {"x"},
+        empty = {""},
+        two = {"fF"},
+        padded = {" f"},
+    )]
+    fn invalid_style(letter: &str) {
+        assert_eq!(markup(0, letter), Err(format!("Invalid timestamp style {letter:?}, it must be one of t, T, d, D, f, F or R")));
+    }
+
+    #[test]
+    fn before_the_epoch() {
+        assert_eq!(markup(-86_400, "D"), Ok(String::from("<t:-86400:D>")));
+    }
+
+    #[parameterized(
+        at = {Until::At(1_709_737_445), Ok(1_709_737_445)},
+        minutes = {Until::In(300.0), Ok(1_709_737_745)},
+        rounded_up = {Until::In(0.25), Ok(1_709_737_446)},
+        past = {Until::In(-60.0), Ok(1_709_737_385)},
+        too_late = {Until::In(1e12), Err(String::from("Invalid timestamp 1001709737445, it must be from year 1 to 9999"))},
+        huge = {Until::In(1e300), Err(format!("Invalid timestamp {}, it must be from year 1 to 9999", i64::MAX))},
+        infinite = {Until::In(f64::INFINITY), Err(String::from("Invalid countdown of inf seconds"))},
+    )]
+    fn countdowns(until: Until, expected: Result<i64, String>) {
+        assert_eq!(until.timestamp(1_709_737_445), expected);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,29 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod betting; // COV_EXCL_LINE
+mod idle;
+mod loot;
+
+use pyo3::prelude::*;
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based economy mechanics, like loot tables with pity, interest and betting pools";
+
+pub(crate) fn register_economy(py: Python, m: &PyModule) -> PyResult<()> {
+    let economy = PyModule::new(py, "_economy")?;
+    economy.add_class::<loot::Tier>()?;
+    economy.add_class::<loot::Roll>()?;
+    economy.add_class::<loot::Table>()?;
+    economy.add_class::<idle::Rules>()?;
+    economy.add_class::<idle::Settlement>()?;
+    economy.add_function(wrap_pyfunction!(idle::settle, economy)?)?;
+    economy.add_class::<betting::Status>()?;
+    economy.add_class::<betting::Payout>()?;
+    economy.add_class::<betting::Resolution>()?;
+    economy.add_class::<betting::Pool>()?;
+    economy.add("__doc__", DOCSTRING)?;
+    m.add_submodule(economy)?;
+    Ok(())
+}
+// COV_EXCL_STOP
@@ -0,0 +1,459 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::persist::{self, Persist};
+// COV_EXCL_STOP
+
+/// The most outcomes a pool can have, as many options as fit in a select menu.
+const MAX_OUTCOMES: usize = 25;
+
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Status { // COV_EXCL_LINE
+    /// Taking stakes.
+    Open,
+    /// No more stakes, waiting for the outcome.
+    Closed,
+    /// Paid out to the winners.
+    Resolved,
+    /// Every stake was given back.
+    Refunded,
+}
+
+/// What a user gets back from a pool.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Payout {
+    #[pyo3(get)]
+    user: u64,
+    /// How much they staked.
+    #[pyo3(get)]
+    stake: u64,
+    /// How much they get back, including their stake.
+    #[pyo3(get)]
+    payout: u64,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Payout {
+    fn __repr__(&self) -> String {
+        format!("Payout(user={}, stake={}, payout={})", self.user, self.stake, self.payout)
+    }
+}
+
+/// How a pool ended.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Resolution {
+    /// The outcome that won, `None` if the stakes were refunded.
+    #[pyo3(get)]
+    outcome: Option<String>,
+    /// How much the house kept.
+    #[pyo3(get)]
+    rake: u64,
+    /// What each user gets back, the most first, which with the rake adds up to everything staked.
+    #[pyo3(get)]
+    payouts: Vec<Payout>,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Resolution {
+    fn __repr__(&self) -> String {
+        format!("Resolution(outcome={:?}, rake={}, payouts={})", self.outcome, self.rake, self.payouts.len())
+    }
+}
+
+/// A parimutuel pool, where everything staked on the losing outcomes is shared by the winners in proportion to their stakes.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Pool {
+    outcomes: Vec<String>,
+    /// The fraction of the pool the house keeps.
+    rake: f64,
+    /// What each user staked on each outcome, the same order as the outcomes.
+    stakes: Vec<BTreeMap<u64, u64>>,
+    status: Status,
+    winner: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct State {
+    outcomes: Vec<String>,
+    rake: f64,
+    stakes: Vec<BTreeMap<u64, u64>>,
+    status: Status,
+    winner: Option<usize>,
+}
+
+impl Pool {
+    fn new(outcomes: Vec<String>, rake: f64) -> Result<Self, String> {
+        if !(2..=MAX_OUTCOMES).contains(&outcomes.len()) {
+            return Err(format!("Invalid pool with {} outcomes, it must have from 2 to {MAX_OUTCOMES}", outcomes.len()));
+        }
+        if let Some(outcome) = outcomes.iter().enumerate().find(|(index, outcome)| outcome.is_empty() || outcomes[..*index].contains(outcome)).map(|(_, outcome)| outcome) {
+            return Err(format!("Invalid outcome {outcome:?}, outcomes must be named and different"));
+        }
+        if !(0.0..1.0).contains(&rake) {
+            return Err(format!("Invalid rake {rake}, it must be from 0 up to 1"));
+        }
+        let stakes = vec![BTreeMap::new(); outcomes.len()];
+        Ok(Pool { outcomes, rake, stakes, status: Status::Open, winner: None })
+    }
+
+    fn outcome(&self, name: &str) -> Result<usize, String> {
+        self.outcomes.iter().position(|outcome| outcome == name).ok_or_else(|| format!("Unknown outcome {name:?}"))
+    }
+
+    fn total(&self) -> u64 {
+        // stakes are checked when they're made, so the total can't overflow
+        self.stakes.iter().flat_map(BTreeMap::values).sum()
+    }
+
+    fn totals(&self) -> Vec<u64> {
+        self.stakes.iter().map(|stakes| stakes.values().sum()).collect()
+    }
+
+    /// The outcome `user` staked on and how much.
+    fn stake_of(&self, user: u64) -> Option<(usize, u64)> {
+        self.stakes.iter().enumerate().find_map(|(outcome, stakes)| stakes.get(&user).map(|stake| (outcome, *stake)))
+    }
+
+    /// Stake `amount` more on `outcome`, returning everything `user` has staked.
+    ///
+    /// Users can add to their stake, but not stake on another outcome, so they can't cover every outcome.
+    fn stake(&mut self, user: u64, outcome: &str, amount: u64) -> Result<u64, String> {
+        if self.status != Status::Open {
+            return Err("The pool isn't taking stakes anymore".to_string());
+        }
+        let outcome = self.outcome(outcome)?;
+        if amount == 0 {
+            return Err("Invalid stake, it has to be more than 0".to_string());
+        }
+        if let Some((other, _)) = self.stake_of(user).filter(|(other, _)| *other != outcome) {
+            return Err(format!("User {user} already staked on {:?}", self.outcomes[other]));
+        }
+        self.total().checked_add(amount).ok_or_else(|| "Invalid stake, the pool would be too big".to_string())?;
+        let stake = self.stakes[outcome].entry(user).or_insert(0);
+        *stake += amount;
+        Ok(*stake)
+    }
+
+    fn close(&mut self) -> Result<(), String> {
+        if self.status != Status::Open {
+            return Err("The pool is already closed".to_string());
+        }
+        self.status = Status::Closed;
+        Ok(())
+    }
+
+    fn is_settled(&self) -> bool {
+        matches!(self.status, Status::Resolved | Status::Refunded)
+    }
+
+    /// What 1 staked on each outcome would pay back if it won, as things stand, `None` for outcomes nobody staked on.
+    fn odds(&self) -> Vec<Option<f64>> {
+        let total = self.total();
+        let net = (total - self.rake_of(total)) as f64;
+        self.totals().into_iter().map(|staked| (staked > 0).then(|| net / staked as f64)).collect()
+    }
+
+    fn rake_of(&self, total: u64) -> u64 {
+        ((total as f64 * self.rake).floor() as u64).min(total)
+    }
+
+    /// Share the pool between the stakes on `winner`, or give every stake back if there are none.
+    ///
+    /// Each winner gets their share rounded down, and then what's left from rounding goes one each to
+    /// the winners that lost the most to it, the bigger stakes first, so the payouts and the rake add
+    /// up to exactly what was staked.
+    fn payouts(&self, winner: usize) -> (u64, Vec<Payout>) {
+        let winning = self.stakes[winner].values().sum::<u64>();
+        if winning == 0 {
+            return (0, self.refunds());
+        }
+        let total = self.total();
+        let rake = self.rake_of(total);
+        let net = u128::from(total - rake);
+        let mut shares = self.stakes[winner]
+            .iter()
+            .map(|(user, stake)| {
+                let exact = u128::from(*stake) * net;
+                // a share is at most the whole pool, so it fits
+                (*user, *stake, (exact / u128::from(winning)) as u64, exact % u128::from(winning))
+            })
+            .collect::<Vec<_>>();
+        let paid = shares.iter().map(|(_, _, share, _)| *share).sum::<u64>();
+        let left = (net as u64 - paid) as usize;
+        shares.sort_by_key(|(user, stake, _, remainder)| (Reverse(*remainder), Reverse(*stake), *user));
+        let mut payouts = shares
+            .iter()
+            .enumerate()
+            .map(|(index, (user, stake, share, _))| Payout { user: *user, stake: *stake, payout: share + u64::from(index < left) })
+            .collect::<Vec<_>>();
+        payouts.sort_by_key(|payout| (Reverse(payout.payout), payout.user));
+        (rake, payouts)
+    }
+
+    fn refunds(&self) -> Vec<Payout> {
+        let mut payouts = self.stakes.iter().flatten().map(|(user, stake)| Payout { user: *user, stake: *stake, payout: *stake }).collect::<Vec<_>>();
+        payouts.sort_by_key(|payout| (Reverse(payout.payout), payout.user));
+        payouts
+    }
+
+    fn resolve(&mut self, outcome: &str) -> Result<Resolution, String> {
+        if self.is_settled() {
+            return Err("The pool was already settled".to_string());
+        }
+        let winner = self.outcome(outcome)?;
+        let (rake, payouts) = self.payouts(winner);
+        self.status = Status::Resolved;
+        self.winner = Some(winner);
+        Ok(Resolution { outcome: Some(self.outcomes[winner].clone()), rake, payouts })
+    }
+
+    fn refund(&mut self) -> Result<Resolution, String> {
+        if self.is_settled() {
+            return Err("The pool was already settled".to_string());
+        }
+        self.status = Status::Refunded;
+        Ok(Resolution { outcome: None, rake: 0, payouts: self.refunds() })
+    }
+}
+
+impl Persist for Pool {
+    const GAME: &'static str = "betting";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { outcomes: self.outcomes.clone(), rake: self.rake, stakes: self.stakes.clone(), status: self.status, winner: self.winner }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        let mut pool = Pool::new(state.outcomes, state.rake)?;
+        if state.stakes.len() != pool.outcomes.len() {
+            return Err(format!("Invalid pool, there are stakes for {} outcomes, not {}", state.stakes.len(), pool.outcomes.len()));
+        }
+        for (outcome, stakes) in state.stakes.iter().enumerate() {
+            for (user, amount) in stakes {
+                pool.stake(*user, &pool.outcomes[outcome].clone(), *amount)?;
+            }
+        }
+        if state.winner.is_some() != (state.status == Status::Resolved) || state.winner.is_some_and(|winner| winner >= pool.outcomes.len()) {
+            return Err("Invalid pool, only a resolved pool has a winner, and it must be one of the outcomes".to_string());
+        }
+        pool.status = state.status;
+        pool.winner = state.winner;
+        Ok(pool)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Pool {
+    #[new]
+    #[pyo3(signature = (outcomes, rake=0.0))]
+    fn py_new(outcomes: Vec<String>, rake: f64) -> PyResult<Self> {
+        Pool::new(outcomes, rake).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn outcomes(&self) -> Vec<String> {
+        self.outcomes.clone()
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn rake(&self) -> f64 {
+        self.rake
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn status(&self) -> Status {
+        self.status
+    }
+
+    /// The outcome that won, `None` until the pool is resolved.
+    #[getter] // COV_EXCL_LINE
+    fn winner(&self) -> Option<String> {
+        self.winner.map(|winner| self.outcomes[winner].clone())
+    }
+
+    #[getter] // COV_EXCL_LINE
+    #[pyo3(name = "total")]
+    fn py_total(&self) -> u64 {
+        self.total()
+    }
+
+    #[pyo3(name = "totals")]
+    fn py_totals(&self) -> Vec<(String, u64)> {
+        self.outcomes.iter().cloned().zip(self.totals()).collect()
+    }
+
+    #[pyo3(name = "odds")]
+    fn py_odds(&self) -> Vec<(String, Option<f64>)> {
+        self.outcomes.iter().cloned().zip(self.odds()).collect()
+    }
+
+    #[pyo3(name = "stake_of")]
+    fn py_stake_of(&self, user: u64) -> Option<(String, u64)> {
+        self.stake_of(user).map(|(outcome, stake)| (self.outcomes[outcome].clone(), stake))
+    }
+
+    #[pyo3(name = "stake")]
+    fn py_stake(&mut self, user: u64, outcome: &str, amount: u64) -> PyResult<u64> {
+        self.stake(user, outcome, amount).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "close")]
+    fn py_close(&mut self) -> PyResult<()> {
+        self.close().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "resolve")]
+    fn py_resolve(&mut self, outcome: &str) -> PyResult<Resolution> {
+        self.resolve(outcome).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "refund")]
+    fn py_refund(&mut self) -> PyResult<Resolution> {
+        self.refund().map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(rake: f64, stakes: &[(u64, &str, u64)]) -> Pool {
+        let mut pool = Pool::new(vec!["red".to_string(), "blue".to_string(), "green".to_string()], rake).expect("Expected a valid pool");
+        for (user, outcome, amount) in stakes {
+            pool.stake(*user, outcome, *amount).expect("Expected a valid stake");
+        }
+        pool
+    }
+
+    fn paid(resolution: &Resolution) -> Vec<(u64, u64)> {
+        resolution.payouts.iter().map(|payout| (payout.user, payout.payout)).collect()
+    }
+
+    #[test]
+    fn proportional_payouts() {
+        let mut pool = pool(0.1, &[(1, "red", 300), (2, "red", 100), (3, "blue", 600)]);
+        let resolution = pool.resolve("red").expect("Expected a resolution");
+        // 1000 staked, 100 raked, 900 shared 3 to 1
+        assert_eq!(resolution.rake, 100);
+        assert_eq!(paid(&resolution), vec![(1, 675), (2, 225)]);
+        assert_eq!((pool.status, pool.winner), (Status::Resolved, Some(0)));
+    }
+
+    #[test]
+    fn rounding_conserves_the_pool() {
+        let mut pool = pool(0.05, &[(1, "red", 1), (2, "red", 1), (3, "red", 1), (4, "blue", 8), (5, "green", 7)]);
+        let resolution = pool.resolve("red").expect("Expected a resolution");
+        // 18 staked, 0.9 rounds down to no rake, 18 shared three ways is 6 each
+        assert_eq!(resolution.rake, 0);
+        assert_eq!(paid(&resolution), vec![(1, 6), (2, 6), (3, 6)]);
+        let mut pool = self::pool(0.1, &[(1, "red", 2), (2, "red", 1), (3, "red", 1), (4, "blue", 7)]);
+        let resolution = pool.resolve("red").expect("Expected a resolution");
+        // 11 staked, 1 raked, 10 shared 2 to 1 to 1 is 5, 2.5, 2.5, and the half goes to the lower id
+        assert_eq!(resolution.rake, 1);
+        assert_eq!(paid(&resolution), vec![(1, 5), (2, 3), (3, 2)]);
+        for winners in 1..30u64 {
+            let stakes = (0..winners).map(|user| (user, "red", user * 7 + 3)).chain([(1000, "blue", 9999)]).collect::<Vec<_>>();
+            let mut pool = self::pool(0.07, &stakes);
+            let resolution = pool.resolve("red").expect("Expected a resolution");
+            assert_eq!(resolution.rake + resolution.payouts.iter().map(|payout| payout.payout).sum::<u64>(), pool.total());
+        }
+    }
+
+    #[test]
+    fn refunds() {
+        // nobody backed the winner, so everyone gets their stake back without a rake
+        let mut pool = pool(0.1, &[(1, "red", 300), (2, "blue", 100)]);
+        let resolution = pool.resolve("green").expect("Expected a resolution");
+        assert_eq!((resolution.rake, paid(&resolution)), (0, vec![(1, 300), (2, 100)]));
+        let mut pool = self::pool(0.1, &[(1, "red", 300), (2, "blue", 100)]);
+        let resolution = pool.refund().expect("Expected a refund");
+        assert_eq!((resolution.outcome.clone(), paid(&resolution)), (None, vec![(1, 300), (2, 100)]));
+        pool.resolve("red").expect_err("Expected a refunded pool to be settled");
+    }
+
+    #[test]
+    fn odds() {
+        let mut pool = pool(0.1, &[(1, "red", 300), (2, "red", 100), (3, "blue", 600)]);
+        assert_eq!(pool.odds(), vec![Some(2.25), Some(1.5), None]);
+        assert_eq!(pool.totals(), vec![400, 600, 0]);
+        assert_eq!(pool.stake(1, "red", 100), Ok(400));
+        assert_eq!(pool.stake_of(1), Some((0, 400)));
+        assert_eq!(pool.stake_of(4), None);
+    }
+
+    #[test]
+    fn stakes() {
+        let mut pool = pool(0.0, &[(1, "red", 10)]);
+        pool.stake(1, "blue", 10).expect_err("Expected hedging to be rejected");
+        pool.stake(2, "purple", 10).expect_err("Expected an unknown outcome to be rejected");
+        pool.stake(2, "red", 0).expect_err("Expected no stake to be rejected");
+        pool.stake(2, "red", u64::MAX).expect_err("Expected an overflow to be rejected");
+        pool.close().expect("Expected to close");
+        pool.stake(2, "red", 10).expect_err("Expected a closed pool to be rejected");
+        pool.close().expect_err("Expected to already be closed");
+        pool.resolve("red").expect("Expected a resolution");
+        pool.resolve("red").expect_err("Expected a resolved pool to be settled");
+        pool.refund().expect_err("Expected a resolved pool to be settled");
+    }
+
+    #[test]
+    fn rejects() {
+        Pool::new(vec!["only".to_string()], 0.0).expect_err("Expected one outcome to be rejected");
+        Pool::new(vec!["same".to_string(), "same".to_string()], 0.0).expect_err("Expected duplicate outcomes to be rejected");
+        Pool::new(vec![String::new(), "other".to_string()], 0.0).expect_err("Expected an unnamed outcome to be rejected");
+        Pool::new(vec!["a".to_string(), "b".to_string()], 1.0).expect_err("Expected the rake to be rejected");
+        Pool::new(vec!["a".to_string(), "b".to_string()], f64::NAN).expect_err("Expected the rake to be rejected");
+    }
+
+    #[test]
+    fn persistence() {
+        let mut pool = pool(0.1, &[(1, "red", 300), (2, "blue", 100)]);
+        pool.close().expect("Expected to close");
+        let loaded = persist::from_bytes::<Pool>(&persist::to_bytes(&pool).expect("Expected to save")).expect("Expected to load");
+        assert_eq!(loaded, pool);
+        let json = persist::to_json(&pool).expect("Expected to save");
+        assert_eq!(persist::from_json::<Pool>(&json), Ok(pool.clone()));
+        pool.resolve("red").expect("Expected a resolution");
+        assert_eq!(persist::from_json::<Pool>(&persist::to_json(&pool).expect("Expected to save")), Ok(pool.clone()));
+        let hedged = json.replace(r#"[{"1":300},{"2":100},{}]"#, r#"[{"1":300},{"1":100},{}]"#);
+        assert_ne!(hedged, json);
+        persist::from_json::<Pool>(&hedged).expect_err("Expected a hedged stake to be rejected");
+        let won = json.replace(r#""winner":null"#, r#""winner":0"#);
+        persist::from_json::<Pool>(&won).expect_err("Expected a winner on a closed pool to be rejected");
+    }
+}
+// COV_EXCL_STOP
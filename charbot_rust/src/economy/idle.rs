@@ -0,0 +1,284 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// The most periods counted in one settlement, long after any balance has stopped changing.
+const MAX_PERIODS: u64 = 1_000_000;
+
+/// How a balance changes while its owner is away.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Rules {
+    /// The fraction added each period, compounded, which stops at the cap.
+    #[pyo3(get)]
+    interest: f64,
+    /// The fraction of the balance above the floor lost each period once the grace is over.
+    #[pyo3(get)]
+    decay: f64,
+    /// How long a period is, in seconds.
+    #[pyo3(get)]
+    period: u64,
+    /// The most interest can take a balance to, `None` for no limit.
+    #[pyo3(get)]
+    cap: Option<i64>,
+    /// The least decay can take a balance to.
+    #[pyo3(get)]
+    floor: i64,
+    /// How long in seconds someone can be away before their balance starts to decay.
+    #[pyo3(get)]
+    grace: u64,
+    /// The most periods that count, like idle games that stop earning after a while offline, `None` for no limit.
+    #[pyo3(get)]
+    max_periods: Option<u64>,
+}
+
+impl Rules {
+    fn new(interest: f64, decay: f64, period: u64, cap: Option<i64>, floor: i64, grace: u64, max_periods: Option<u64>) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&interest) {
+            return Err(format!("Invalid interest {interest}, it must be from 0 to 1 a period"));
+        }
+        if !(0.0..=1.0).contains(&decay) {
+            return Err(format!("Invalid decay {decay}, it must be from 0 to 1 a period"));
+        }
+        if period == 0 {
+            return Err("Invalid period, it has to be at least a second".to_string());
+        }
+        if floor < 0 {
+            return Err(format!("Invalid floor {floor}, it can't be negative"));
+        }
+        if let Some(cap) = cap.filter(|cap| *cap < floor) {
+            return Err(format!("Invalid cap {cap}, it can't be below the floor {floor}"));
+        }
+        if max_periods == Some(0) {
+            return Err("Invalid max periods, at least one has to count".to_string());
+        }
+        Ok(Rules { interest, decay, period, cap, floor, grace, max_periods })
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Rules {
+    #[new]
+    #[pyo3(signature = (interest=0.0, decay=0.0, period=86400, cap=None, floor=0, grace=0, max_periods=None))]
+    fn py_new(interest: f64, decay: f64, period: u64, cap: Option<i64>, floor: i64, grace: u64, max_periods: Option<u64>) -> PyResult<Self> {
+        Rules::new(interest, decay, period, cap, floor, grace, max_periods).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn __repr__(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or_else(|| "None".to_string());
+        format!(
+            "Rules(interest={}, decay={}, period={}, cap={}, floor={}, grace={}, max_periods={})",
+            self.interest,
+            self.decay,
+            self.period,
+            optional(self.cap.map(|cap| cap.to_string())),
+            self.floor,
+            self.grace,
+            optional(self.max_periods.map(|periods| periods.to_string()))
+        )
+    }
+}
+
+/// A balance brought up to date.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Settlement {
+    #[pyo3(get)]
+    balance: i64,
+    /// How much interest was added, rounded down.
+    #[pyo3(get)]
+    interest: i64,
+    /// How much was lost to decay, so the balance is always the old one plus the interest minus the decay.
+    #[pyo3(get)]
+    decay: i64,
+    /// How many whole periods were settled, including any past the most that count.
+    #[pyo3(get)]
+    periods: u64,
+    /// When the last settled period ended, to store as when the user was last seen so part of a period isn't lost.
+    #[pyo3(get)]
+    settled_at: i64,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Settlement {
+    fn __repr__(&self) -> String {
+        format!(
+            "Settlement(balance={}, interest={}, decay={}, periods={}, settled_at={})",
+            self.balance, self.interest, self.decay, self.periods, self.settled_at
+        )
+    }
+}
+
+/// Bring `balance` up to date for every whole period between `last_seen` and `now`.
+///
+/// Each period interest is added first, up to the cap, then once the grace is over the part above
+/// the floor decays. Balances are worked out exactly and only rounded down at the end, and the
+/// periods stop once the balance stops changing, so settling over years is as quick as over days.
+fn settle_at(balance: i64, last_seen: i64, rules: &Rules, now: i64) -> Result<Settlement, String> {
+    if balance < 0 {
+        return Err(format!("Invalid balance {balance}, it can't be negative"));
+    }
+    let elapsed = u64::try_from(now.saturating_sub(last_seen)).unwrap_or(0);
+    let periods = elapsed / rules.period;
+    let counted = periods.min(rules.max_periods.unwrap_or(u64::MAX)).min(MAX_PERIODS);
+    // the first period that ends after the grace is the first to decay
+    let undecayed = rules.grace.div_ceil(rules.period);
+    // without a cap, interest still stops at the most an i64 holds
+    let cap = rules.cap.unwrap_or(i64::MAX) as f64;
+    let floor = rules.floor as f64;
+    let (mut current, mut earned) = (balance as f64, 0.0);
+    for period in 0..counted {
+        let before = current;
+        if current < cap {
+            let interest = (current * rules.interest).min(cap - current);
+            current += interest;
+            earned += interest;
+        }
+        if period >= undecayed && current > floor {
+            current -= (current - floor) * rules.decay;
+        }
+        if current == before && period >= undecayed {
+            break;
+        }
+    }
+    let settled = current.floor() as i64;
+    let interest = earned.floor() as i64;
+    let settled_at = last_seen.saturating_add(i64::try_from(periods.saturating_mul(rules.period)).unwrap_or(i64::MAX));
+    Ok(Settlement { balance: settled, interest, decay: balance.saturating_add(interest).saturating_sub(settled), periods, settled_at })
+}
+
+#[pyfunction]
+#[pyo3(signature = (balance, last_seen, rules, now=None), text_signature = "
+settle(balance, last_seen, rules, now=None, /)
+--
+
+Bring a balance up to date with the interest and decay since its owner was last seen.
+
+Accounts can be settled whenever their owner runs a command, instead of updating every account on a
+schedule, since settling once over a long time gives the same balance as settling every period.
+
+Parameters
+----------
+balance : int
+    The balance when its owner was last seen.
+last_seen : int
+    The unix timestamp in seconds its owner was last seen, or that it was last settled at.
+rules : Rules
+    The interest, decay and limits to settle with.
+now : int | None
+    The unix timestamp in seconds to settle up to. The default is None, for now.
+
+Returns
+-------
+Settlement
+    The new balance, the interest and decay that made it, and the time to store as when the owner was last seen.
+
+Raises
+------
+ValueError
+    If the balance is negative.
+")]
+pub fn settle(balance: i64, last_seen: i64, rules: Rules, now: Option<i64>) -> PyResult<Settlement> {
+    metrics::observe("economy.settle", || {
+        let now = now.unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() as i64));
+        settle_at(balance, last_seen, &rules, now).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 86_400;
+
+    fn rules(interest: f64, decay: f64, cap: Option<i64>, floor: i64, grace: u64) -> Rules {
+        Rules::new(interest, decay, DAY as u64, cap, floor, grace, None).expect("Expected valid rules")
+    }
+
+    fn settled(balance: i64, days: i64, rules: &Rules) -> Settlement {
+        settle_at(balance, 0, rules, days * DAY).expect("Expected a settlement")
+    }
+
+    #[test]
+    fn compound_interest() {
+        let rules = rules(0.1, 0.0, None, 0, 0);
+        let settlement = settled(1000, 2, &rules);
+        assert_eq!((settlement.balance, settlement.interest, settlement.decay, settlement.periods), (1210, 210, 0, 2));
+        // a part of a period doesn't count, and isn't lost
+        let settlement = settle_at(1000, 100, &rules, 100 + 2 * DAY + 500).expect("Expected a settlement");
+        assert_eq!((settlement.balance, settlement.settled_at), (1210, 100 + 2 * DAY));
+        assert_eq!(settle_at(1000, 100, &rules, 50).expect("Expected a settlement").balance, 1000);
+    }
+
+    #[test]
+    fn settling_once_is_settling_every_period() {
+        let rules = rules(0.05, 0.02, Some(5000), 100, 3 * DAY as u64);
+        let once = settled(1000, 30, &rules);
+        let mut balance = 1000.0;
+        for day in 0..30 {
+            balance = (balance * 1.05f64).min(5000.0);
+            if day >= 3 {
+                balance -= (balance - 100.0) * 0.02;
+            }
+        }
+        assert_eq!(once.balance, balance.floor() as i64);
+        assert_eq!(once.balance, 1000 + once.interest - once.decay);
+    }
+
+    #[test]
+    fn caps_and_floors() {
+        let capped = settled(1000, 365, &rules(0.1, 0.0, Some(2000), 0, 0));
+        assert_eq!((capped.balance, capped.interest), (2000, 1000));
+        // already over the cap, so it earns nothing
+        assert_eq!(settled(3000, 10, &rules(0.1, 0.0, Some(2000), 0, 0)).balance, 3000);
+        let decayed = settled(1000, 10_000, &rules(0.0, 0.5, None, 200, 0));
+        assert_eq!((decayed.balance, decayed.decay, decayed.periods), (200, 800, 10_000));
+        // under the floor, there's nothing to lose
+        assert_eq!(settled(100, 10, &rules(0.0, 0.5, None, 200, 0)).balance, 100);
+    }
+
+    #[test]
+    fn grace_and_max_periods() {
+        let grace = rules(0.0, 0.5, None, 0, 2 * DAY as u64);
+        assert_eq!(settled(1000, 2, &grace).balance, 1000);
+        assert_eq!(settled(1000, 3, &grace).balance, 500);
+        // a grace that isn't whole periods starts decaying at the end of the period it ends in
+        let partial = Rules::new(0.0, 0.5, DAY as u64, None, 0, DAY as u64 + 1, None).expect("Expected valid rules");
+        assert_eq!(settled(1000, 2, &partial).balance, 1000);
+        let limited = Rules::new(0.1, 0.0, DAY as u64, None, 0, 0, Some(1)).expect("Expected valid rules");
+        let settlement = settled(1000, 5, &limited);
+        assert_eq!((settlement.balance, settlement.periods, settlement.settled_at), (1100, 5, 5 * DAY));
+    }
+
+    #[test]
+    fn long_absences() {
+        // ten thousand years of minutes, which stops once the cap is reached
+        let rules = Rules::new(0.01, 0.0, 60, Some(1_000_000), 0, 0, None).expect("Expected valid rules");
+        let settlement = settle_at(1, 0, &rules, 10_000 * 365 * DAY).expect("Expected a settlement");
+        assert_eq!(settlement.balance, 1_000_000);
+        let uncapped = settled(i64::MAX / 2, 1000, &self::rules(1.0, 0.0, None, 0, 0));
+        assert_eq!(uncapped.balance, i64::MAX);
+    }
+
+    #[test]
+    fn rejects() {
+        Rules::new(-0.1, 0.0, 1, None, 0, 0, None).expect_err("Expected negative interest to be rejected");
+        Rules::new(0.0, 1.5, 1, None, 0, 0, None).expect_err("Expected the decay to be rejected");
+        Rules::new(f64::NAN, 0.0, 1, None, 0, 0, None).expect_err("Expected the interest to be rejected");
+        Rules::new(0.0, 0.0, 0, None, 0, 0, None).expect_err("Expected no period to be rejected");
+        Rules::new(0.0, 0.0, 1, Some(10), 20, 0, None).expect_err("Expected a cap under the floor to be rejected");
+        Rules::new(0.0, 0.0, 1, None, -1, 0, None).expect_err("Expected a negative floor to be rejected");
+        Rules::new(0.0, 0.0, 1, None, 0, 0, Some(0)).expect_err("Expected no periods to be rejected");
+        settle_at(-1, 0, &rules(0.0, 0.0, None, 0, 0), 0).expect_err("Expected a negative balance to be rejected");
+    }
+}
+// COV_EXCL_STOP
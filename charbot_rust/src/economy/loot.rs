@@ -0,0 +1,264 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+// COV_EXCL_STOP
+
+/// The most rolls at once, so one command can't tie the bot up.
+const MAX_ROLLS: usize = 1000;
+
+/// A rarity, with how often it drops and what it drops.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Tier {
+    #[pyo3(get)]
+    name: String,
+    /// How likely the tier is against the weights of the others.
+    #[pyo3(get)]
+    weight: u32,
+    /// The items the tier drops, each as likely as the others.
+    #[pyo3(get)]
+    items: Vec<String>,
+    /// How many rolls in a row it takes to be sure of the tier or a rarer one, `None` if it isn't guaranteed.
+    #[pyo3(get)]
+    pity: Option<u32>,
+}
+
+impl Tier {
+    fn new(name: String, weight: u32, items: Vec<String>, pity: Option<u32>) -> Result<Self, String> {
+        if name.is_empty() {
+            return Err("A tier has to have a name".to_string());
+        }
+        if items.is_empty() {
+            return Err(format!("Invalid tier {name:?}, it has to drop at least one item"));
+        }
+        if pity == Some(0) {
+            return Err(format!("Invalid tier {name:?}, its pity has to be at least 1 roll"));
+        }
+        if weight == 0 && pity.is_none() {
+            return Err(format!("Invalid tier {name:?}, it can never drop without a weight or pity"));
+        }
+        Ok(Tier { name, weight, items, pity })
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Tier {
+    #[new]
+    #[pyo3(signature = (name, weight, items, pity=None))]
+    fn py_new(name: String, weight: u32, items: Vec<String>, pity: Option<u32>) -> PyResult<Self> {
+        Tier::new(name, weight, items, pity).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Tier(name={:?}, weight={}, items={:?}, pity={})", self.name, self.weight, self.items, self.pity.map_or("None".to_string(), |pity| pity.to_string()))
+    }
+}
+
+/// What a roll dropped.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Roll {
+    #[pyo3(get)]
+    tier: String,
+    #[pyo3(get)]
+    item: String,
+    /// Whether pity made sure of the tier, or one at least as rare.
+    #[pyo3(get)]
+    guaranteed: bool,
+    /// The pity counters after the roll, to pass to the next one.
+    #[pyo3(get)]
+    pity: Vec<u32>,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Roll {
+    fn __repr__(&self) -> String {
+        format!("Roll(tier={:?}, item={:?}, guaranteed={}, pity={:?})", self.tier, self.item, if self.guaranteed { "True" } else { "False" }, self.pity)
+    }
+}
+
+/// A drop table, with tiers from the most common to the rarest.
+#[pyclass(module = "economy")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Table {
+    tiers: Vec<Tier>,
+}
+
+impl Table {
+    fn new(tiers: Vec<Tier>) -> Result<Self, String> {
+        if tiers.is_empty() {
+            return Err("A drop table has to have at least one tier".to_string());
+        }
+        if let Some((index, tier)) = tiers.iter().enumerate().find(|(index, tier)| tiers[..*index].iter().any(|other| other.name == tier.name)) {
+            return Err(format!("Invalid drop table, tier {index} is called {:?} like one before it", tier.name));
+        }
+        if tiers.iter().all(|tier| tier.weight == 0) {
+            return Err("Invalid drop table, at least one tier needs a weight".to_string());
+        }
+        Ok(Table { tiers })
+    }
+
+    /// Roll once, with the counters of how many rolls it's been since each tier or a rarer one dropped.
+    ///
+    /// The rarest tier whose pity this roll reaches is guaranteed, so the roll is only between it and
+    /// the tiers rarer than it. Whatever drops resets the counters of its tier and every more common
+    /// one, since a rarer drop counts as getting them too.
+    fn roll(&self, pity: &[u32], rng: &mut impl Rng) -> Result<Roll, String> {
+        if pity.len() != self.tiers.len() {
+            return Err(format!("Invalid pity, there has to be a counter for each of the {} tiers, not {}", self.tiers.len(), pity.len()));
+        }
+        // counting this roll
+        let counts = pity.iter().map(|count| count.saturating_add(1)).collect::<Vec<_>>();
+        let guaranteed = (0..self.tiers.len()).rev().find(|index| self.tiers[*index].pity.is_some_and(|pity| counts[*index] >= pity));
+        let floor = guaranteed.unwrap_or(0);
+        let candidates = &self.tiers[floor..];
+        let total = candidates.iter().map(|tier| u64::from(tier.weight)).sum::<u64>();
+        let index = if total == 0 {
+            // only pity can drop it, and nothing rarer can drop at all
+            floor
+        } else {
+            let mut ticket = rng.gen_range(0..total);
+            floor
+                + candidates
+                    .iter()
+                    .position(|tier| {
+                        let found = ticket < u64::from(tier.weight);
+                        ticket = ticket.saturating_sub(u64::from(tier.weight));
+                        found
+                    })
+                    .expect("The ticket is below the total so it's in some tier's weight")
+        };
+        let tier = &self.tiers[index];
+        let item = tier.items.choose(rng).expect("Tiers have at least one item").clone();
+        let pity = counts.iter().enumerate().map(|(other, count)| if other <= index { 0 } else { *count }).collect();
+        Ok(Roll { tier: tier.name.clone(), item, guaranteed: guaranteed.is_some(), pity })
+    }
+
+    fn roll_many(&self, count: usize, pity: &[u32], rng: &mut impl Rng) -> Result<Vec<Roll>, String> {
+        if !(1..=MAX_ROLLS).contains(&count) {
+            return Err(format!("Invalid number of rolls {count}, it must be from 1 to {MAX_ROLLS}"));
+        }
+        let mut rolls: Vec<Roll> = Vec::with_capacity(count);
+        for _ in 0..count {
+            let roll = self.roll(rolls.last().map_or(pity, |last| &last.pity), rng)?;
+            rolls.push(roll);
+        }
+        Ok(rolls)
+    }
+
+    fn pity_or_default(&self, pity: Option<Vec<u32>>) -> Vec<u32> {
+        pity.unwrap_or_else(|| vec![0; self.tiers.len()])
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Table {
+    #[new]
+    fn py_new(tiers: Vec<Tier>) -> PyResult<Self> {
+        Table::new(tiers).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn tiers(&self) -> Vec<Tier> {
+        self.tiers.clone()
+    }
+
+    /// The chance of each tier on a roll that pity doesn't change.
+    fn chances(&self) -> Vec<(String, f64)> {
+        let total = self.tiers.iter().map(|tier| f64::from(tier.weight)).sum::<f64>();
+        self.tiers.iter().map(|tier| (tier.name.clone(), f64::from(tier.weight) / total)).collect()
+    }
+
+    #[pyo3(name = "roll", signature = (pity=None, seed=None))]
+    fn py_roll(&self, pity: Option<Vec<u32>>, seed: Option<u64>) -> PyResult<Roll> {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        self.roll(&self.pity_or_default(pity), &mut rng).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "roll_many", signature = (count, pity=None, seed=None))]
+    fn py_roll_many(&self, count: usize, pity: Option<Vec<u32>>, seed: Option<u64>) -> PyResult<Vec<Roll>> {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        self.roll_many(count, &self.pity_or_default(pity), &mut rng).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Table(tiers={:?})", self.tiers.iter().map(|tier| tier.name.as_str()).collect::<Vec<_>>())
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tier(name: &str, weight: u32, pity: Option<u32>) -> Tier {
+        Tier::new(name.to_string(), weight, vec![format!("{name} item")], pity).expect("Expected a valid tier")
+    }
+
+    fn names(rolls: &[Roll]) -> Vec<&str> {
+        rolls.iter().map(|roll| roll.tier.as_str()).collect()
+    }
+
+    #[test]
+    fn guaranteed_after_n() {
+        // the rare tier never drops on its own, so only pity gives it
+        let table = Table::new(vec![tier("common", 1, None), tier("rare", 0, Some(3))]).expect("Expected a valid table");
+        let rolls = table.roll_many(7, &[0, 0], &mut thread_rng()).expect("Expected rolls");
+        assert_eq!(names(&rolls), vec!["common", "common", "rare", "common", "common", "rare", "common"]);
+        assert_eq!(rolls.iter().map(|roll| roll.guaranteed).collect::<Vec<_>>(), vec![false, false, true, false, false, true, false]);
+        assert_eq!(rolls.iter().map(|roll| roll.pity.clone()).collect::<Vec<_>>()[..3], [vec![0, 1], vec![0, 2], vec![0, 0]]);
+        // counters carry over from earlier rolls, and past the pity from a table that changed still count
+        assert_eq!(table.roll(&[0, 2], &mut thread_rng()).expect("Expected a roll").tier, "rare");
+        assert_eq!(table.roll(&[0, 10], &mut thread_rng()).expect("Expected a roll").tier, "rare");
+    }
+
+    #[test]
+    fn rarer_drops_count() {
+        let table = Table::new(vec![tier("common", 1, None), tier("epic", 0, Some(2)), tier("legendary", 0, Some(5))]).expect("Expected a valid table");
+        let rolls = table.roll_many(10, &[0, 0, 0], &mut thread_rng()).expect("Expected rolls");
+        // the legendary on the fifth roll counts as an epic too, so the next epic is two after it
+        assert_eq!(names(&rolls), vec!["common", "epic", "common", "epic", "legendary", "common", "epic", "common", "epic", "legendary"]);
+        assert_eq!(rolls[4].pity, vec![0, 0, 0]);
+        assert_eq!(rolls[3].pity, vec![0, 0, 4]);
+    }
+
+    #[test]
+    fn pity_only_rolls_rarer() {
+        let table = Table::new(vec![tier("common", 1000, None), tier("rare", 1, Some(2)), tier("epic", 1, None)]).expect("Expected a valid table");
+        for _ in 0..50 {
+            let roll = table.roll(&[0, 1, 0], &mut thread_rng()).expect("Expected a roll");
+            assert!(roll.tier == "rare" || roll.tier == "epic", "Rolled {}", roll.tier);
+            assert!(roll.guaranteed);
+        }
+    }
+
+    #[test]
+    fn chances() {
+        let table = Table::new(vec![tier("common", 3, None), tier("rare", 1, None)]).expect("Expected a valid table");
+        assert_eq!(table.chances(), vec![("common".to_string(), 0.75), ("rare".to_string(), 0.25)]);
+        let rares = (0..4000).filter(|_| table.roll(&[0, 0], &mut thread_rng()).expect("Expected a roll").tier == "rare").count();
+        assert!((800..1200).contains(&rares), "Rolled {rares} rares in 4000");
+        let seeded = |seed| table.py_roll_many(20, None, Some(seed)).expect("Expected rolls");
+        assert_eq!(seeded(7), seeded(7));
+    }
+
+    #[test]
+    fn rejects() {
+        Tier::new(String::new(), 1, vec!["item".to_string()], None).expect_err("Expected no name to be rejected");
+        Tier::new("tier".to_string(), 1, vec![], None).expect_err("Expected no items to be rejected");
+        Tier::new("tier".to_string(), 1, vec!["item".to_string()], Some(0)).expect_err("Expected no pity to be rejected");
+        Tier::new("tier".to_string(), 0, vec!["item".to_string()], None).expect_err("Expected a tier that can't drop to be rejected");
+        Table::new(vec![]).expect_err("Expected no tiers to be rejected");
+        Table::new(vec![tier("common", 1, None), tier("common", 1, None)]).expect_err("Expected duplicate tiers to be rejected");
+        Table::new(vec![tier("rare", 0, Some(5))]).expect_err("Expected no weights to be rejected");
+        let table = Table::new(vec![tier("common", 1, None)]).expect("Expected a valid table");
+        table.roll(&[0, 0], &mut thread_rng()).expect_err("Expected the wrong number of counters to be rejected");
+        table.roll_many(0, &[0], &mut thread_rng()).expect_err("Expected no rolls to be rejected");
+        table.roll_many(MAX_ROLLS + 1, &[0], &mut thread_rng()).expect_err("Expected too many rolls to be rejected");
+    }
+}
+// COV_EXCL_STOP
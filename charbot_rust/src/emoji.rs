@@ -0,0 +1,425 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Emoji, found in text a whole emoji at a time, the way Discord shows them, so a family joined by zero
+//! width joiners, a flag, or a thumbs up with a skin tone is one emoji and not the code points it's made
+//! of, with their names and the shortcodes like `:tada:` that Discord writes them as.
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use crate::assets;
+use crate::metrics;
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+/// The variation selector that asks for a character to be shown as an emoji.
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+/// The variation selector that asks for a character to be shown as text, even if it's usually an emoji.
+const TEXT_PRESENTATION: char = '\u{FE0E}';
+const KEYCAP: char = '\u{20E3}';
+/// The flag the tags of England, Scotland and Wales follow.
+const BLACK_FLAG: char = '\u{1F3F4}';
+const CANCEL_TAG: char = '\u{E007F}';
+
+fn is_skin_tone(letter: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&letter)
+}
+
+fn is_regional_indicator(letter: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&letter)
+}
+
+fn is_tag(letter: char) -> bool {
+    ('\u{E0020}'..='\u{E007E}').contains(&letter)
+}
+
+/// An emoji of the table.
+#[derive(Clone, Debug, PartialEq)]
+struct Entry {
+    /// The emoji, fully qualified, so with the variation selectors that make it show as an emoji.
+    emoji: String,
+    name: String,
+    /// Its shortcodes, without the colons, the first being the one to write it as.
+    shortcodes: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    entries: Vec<Entry>,
+    /// The entries by their emoji without variation selectors, which are often left out, or added where
+    /// they don't belong.
+    emoji: HashMap<String, usize>,
+    shortcodes: HashMap<String, usize>,
+    /// The code points that are an emoji on their own.
+    singles: HashSet<char>,
+}
+
+impl Table {
+    fn add(&mut self, entry: Entry) {
+        let key = key(&entry.emoji);
+        let mut letters = key.chars();
+        if let (Some(letter), None) = (letters.next(), letters.next()) {
+            self.singles.insert(letter);
+        }
+        for shortcode in &entry.shortcodes {
+            self.shortcodes.entry(shortcode.clone()).or_insert(self.entries.len());
+        }
+        self.emoji.insert(key, self.entries.len());
+        self.entries.push(entry);
+    }
+
+    fn get(&self, emoji: &str) -> Option<&Entry> {
+        self.emoji.get(&key(emoji)).map(|index| &self.entries[*index])
+    }
+}
+
+/// An emoji without its variation selectors, to look it up by.
+fn key(emoji: &str) -> String {
+    emoji.chars().filter(|letter| *letter != EMOJI_PRESENTATION).collect()
+}
+
+static TABLE: Lazy<Table> = Lazy::new(|| {
+    let code_points = |field: &str| {
+        field.split(' ').map(|hex| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).expect("The table only has valid code points")).collect::<String>()
+    };
+    let mut table = Table::default();
+    let mut toned = Vec::new();
+    for line in assets::static_text("emoji/emoji.txt").expect("The emoji are an asset").lines().filter(|line| !line.starts_with('#') && !line.trim().is_empty()) {
+        match line.split(" ; ").collect::<Vec<_>>()[..] {
+            [emoji, shortcodes, name] => {
+                table.add(Entry { emoji: code_points(emoji), name: name.to_string(), shortcodes: shortcodes.split(' ').map(String::from).collect() });
+            }
+            [emoji] => toned.push(code_points(emoji)),
+            _ => panic!("The table only has lines of one or three fields"),
+        }
+    }
+    // named after the emoji without their skin tones, which are all in the table by now
+    for emoji in toned {
+        let mut tones = emoji.chars().filter(|letter| is_skin_tone(*letter)).collect::<Vec<_>>();
+        // two people with the same skin tone are named with it once
+        tones.dedup();
+        let base = table.get(&emoji.replace(is_skin_tone, "")).expect("The table has the emoji without skin tones");
+        let names = tones.iter().map(|tone| table.get(&tone.to_string()).expect("The table has the skin tones").name.as_str()).collect::<Vec<_>>();
+        let separator = if base.name.contains(':') { ", " } else { ": " };
+        let suffix = tones.iter().map(|tone| format!("_tone{}", u32::from(*tone) - 0x1F3FA)).collect::<String>();
+        let entry = Entry {
+            name: format!("{}{separator}{}", base.name, names.join(", ")),
+            shortcodes: base.shortcodes.iter().map(|shortcode| format!("{shortcode}{suffix}")).collect(),
+            emoji,
+        };
+        table.add(entry);
+    }
+    table
+});
+
+/// The end of the part of an emoji at `index`, which is a flag, a keycap, or an emoji with its variation
+/// selector, skin tone or tags, if there's one there.
+fn element(letters: &[char], index: usize) -> Option<usize> {
+    let letter = *letters.get(index)?;
+    let next = |offset: usize| letters.get(index + offset).copied();
+    if is_regional_indicator(letter) {
+        return next(1).filter(|next| is_regional_indicator(*next)).map(|_| index + 2);
+    }
+    if letter.is_ascii_digit() || letter == '#' || letter == '*' {
+        // these are only emoji as keycaps
+        return match (next(1), next(2)) {
+            (Some(EMOJI_PRESENTATION), Some(KEYCAP)) => Some(index + 3),
+            (Some(KEYCAP), _) => Some(index + 2),
+            _ => None,
+        };
+    }
+    if !TABLE.singles.contains(&letter) {
+        return None;
+    }
+    let mut end = index + 1;
+    match next(1) {
+        Some(TEXT_PRESENTATION) => return None,
+        Some(EMOJI_PRESENTATION) => end += 1,
+        Some(tone) if is_skin_tone(tone) && !is_skin_tone(letter) => end += 1,
+        _ => {}
+    }
+    if letter == BLACK_FLAG {
+        let tags = letters[end..].iter().take_while(|letter| is_tag(**letter)).count();
+        if tags > 0 && letters.get(end + tags) == Some(&CANCEL_TAG) {
+            end += tags + 1;
+        }
+    }
+    Some(end)
+}
+
+/// Where each emoji in `text` is, in bytes.
+///
+/// Emoji joined by zero width joiners are one, like a grapheme cluster, even if they aren't in the
+/// table, since they're shown as one on the devices that know them.
+fn find(text: &str) -> Vec<Range<usize>> {
+    let (offsets, letters): (Vec<_>, Vec<_>) = text.char_indices().unzip();
+    let offset = |index: usize| offsets.get(index).copied().unwrap_or(text.len());
+    let mut found = Vec::new();
+    let mut index = 0;
+    while index < letters.len() {
+        let Some(mut end) = element(&letters, index) else {
+            index += 1;
+            continue;
+        };
+        while letters.get(end) == Some(&ZERO_WIDTH_JOINER) {
+            match element(&letters, end + 1) {
+                Some(next) => end = next,
+                None => break,
+            }
+        }
+        found.push(offset(index)..offset(end));
+        index = end;
+    }
+    found
+}
+
+/// Write `emoji` as shortcodes, as one if it's in the table, or else as its parts, and as itself if
+/// they aren't either.
+fn write_shortcodes(demojized: &mut String, emoji: &str) {
+    if let Some(entry) = TABLE.get(emoji) {
+        demojized.extend([":", &entry.shortcodes[0], ":"]);
+    } else if emoji.contains(ZERO_WIDTH_JOINER) {
+        emoji.split(ZERO_WIDTH_JOINER).for_each(|part| write_shortcodes(demojized, part));
+    } else if let Some(tone) = emoji.find(is_skin_tone).filter(|tone| *tone > 0) {
+        // a skin tone on an emoji that doesn't have skin tones
+        write_shortcodes(demojized, &emoji[..tone]);
+        write_shortcodes(demojized, &emoji[tone..]);
+    } else {
+        demojized.push_str(emoji);
+    }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+is_emoji(grapheme, /)
+--
+
+Check whether text is exactly one emoji, like a flag, a keycap, or a family of emoji joined by zero width
+joiners, whether or not it has skin tones.
+
+Parameters
+----------
+grapheme : str
+    The text.
+
+Returns
+-------
+bool
+    Whether it's one emoji and nothing else.
+")]
+fn is_emoji(grapheme: &str) -> bool {
+    metrics::timed("emoji.is_emoji", || {
+        let found = find(grapheme);
+        found.len() == 1 && found[0] == (0..grapheme.len())
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+name(emoji, /)
+--
+
+Get the name of an emoji, like Unicode's, so 👋🏽 is \"waving hand: medium skin tone\" and 🇯🇵 is \"flag:
+Japan\".
+
+Parameters
+----------
+emoji : str
+    The emoji, with or without its variation selectors.
+
+Returns
+-------
+str | None
+    Its name, or None if it isn't an emoji, or is emoji joined in a way Unicode doesn't recommend.
+")]
+fn name(emoji: &str) -> Option<String> {
+    metrics::timed("emoji.name", || TABLE.get(emoji).map(|entry| entry.name.clone()))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+from_shortcode(shortcode, /)
+--
+
+Get the emoji of a shortcode, like Discord's, so \":tada:\" is 🎉 and \":wave_tone3:\" is 👋🏽.
+
+Parameters
+----------
+shortcode : str
+    The shortcode, with or without its colons.
+
+Returns
+-------
+str | None
+    The emoji, fully qualified, so it shows as an emoji, or None if there's no emoji with the shortcode.
+")]
+fn from_shortcode(shortcode: &str) -> Option<String> {
+    metrics::timed("emoji.from_shortcode", || {
+        let shortcode = shortcode.strip_prefix(':').and_then(|shortcode| shortcode.strip_suffix(':')).unwrap_or(shortcode);
+        TABLE.shortcodes.get(shortcode).map(|index| TABLE.entries[*index].emoji.clone())
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+extract(text, /)
+--
+
+Find the emoji in text, a whole emoji at a time, so skin tones, flags, keycaps and emoji joined by zero
+width joiners, like families, are each one emoji, the way they're shown.
+
+Parameters
+----------
+text : str
+    The text.
+
+Returns
+-------
+list[str]
+    The emoji, as they are in the text, in order. Characters that are usually text, like ❤ and ©, are
+    emoji unless they're followed by the variation selector asking for them to be shown as text, and
+    digits, # and * are only emoji as keycaps.
+")]
+fn extract(text: &str) -> Vec<String> {
+    metrics::timed("emoji.extract", || find(text).into_iter().map(|range| text[range].to_string()).collect())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+demojize(text, /)
+--
+
+Replace the emoji in text with their shortcodes, like Discord writes them, so \"gg 🎉\" is \"gg :tada:\".
+
+Parameters
+----------
+text : str
+    The text.
+
+Returns
+-------
+str
+    The text with its emoji as shortcodes. Emoji joined in a way Unicode doesn't recommend are written
+    as the shortcodes of their parts.
+")]
+fn demojize(text: &str) -> String {
+    metrics::timed("emoji.demojize", || {
+        let mut demojized = String::with_capacity(text.len());
+        let mut last = 0;
+        for range in find(text) {
+            demojized.push_str(&text[last..range.start]);
+            write_shortcodes(&mut demojized, &text[range.clone()]);
+            last = range.end;
+        }
+        demojized.push_str(&text[last..]);
+        demojized
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based emoji, with their names and shortcodes, found a whole emoji at a time";
+
+pub(crate) fn register_emoji(py: Python, m: &PyModule) -> PyResult<()> {
+    let emoji = PyModule::new(py, "_emoji")?;
+    emoji.add_function(wrap_pyfunction!(is_emoji, emoji)?)?;
+    emoji.add_function(wrap_pyfunction!(name, emoji)?)?;
+    emoji.add_function(wrap_pyfunction!(from_shortcode, emoji)?)?;
+    emoji.add_function(wrap_pyfunction!(extract, emoji)?)?;
+    emoji.add_function(wrap_pyfunction!(demojize, emoji)?)?;
+    emoji.add("__doc__", DOCSTRING)?;
+    m.add_submodule(emoji)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        plain = {"gg 🎉 wp", &["🎉"]},
+        skin_tones = {"👋🏽👍🏿👍", &["👋🏽", "👍🏿", "👍"]},
+        family = {"the 👨‍👩‍👧‍👦 is here", &["👨‍👩‍👧‍👦"]},
+        toned_couple = {"🧑🏻‍❤️‍💋‍🧑🏿", &["🧑🏻‍❤️‍💋‍🧑🏿"]},
+        flags = {"🇯🇵🇺🇸🇬", &["🇯🇵", "🇺🇸"]},
+        tag_flag = {"🏴󠁧󠁢󠁳󠁣󠁴󠁿 and 🏴", &["🏴󠁧󠁢󠁳󠁣󠁴󠁿", "🏴"]},
+        keycaps = {"1️⃣ 2⃣ 3 #️⃣ 🔟", &["1️⃣", "2⃣", "#️⃣", "🔟"]},
+        presentation = {"❤ ❤️ ❤︎ © 2023", &["❤", "❤️", "©"]},
+        zwj_not_recommended = {"👨‍🦰‍💻!", &["👨‍🦰‍💻"]},
+        dangling_joiner = {"🎉‍ x", &["🎉"]},
+        none = {"just text, 100% *bold*", &[]},
+    )]
+    fn extracts(text: &str, expected: &[&str]) {
+        assert_eq!(extract(text), expected);
+    }
+
+    #[test]
+    fn single() {
+        for emoji in ["🎉", "👨‍👩‍👧‍👦", "👋🏽", "🇯🇵", "1️⃣", "🏳️‍🌈", "🏴󠁧󠁢󠁥󠁮󠁧󠁿", "🏽"] {
+            assert!(is_emoji(emoji), "{emoji}");
+        }
+        for text in ["", "a", "🎉🎉", "🎉 ", "1", "❤︎", "🇯"] {
+            assert!(!is_emoji(text), "{text}");
+        }
+    }
+
+    #[parameterized(
+        plain = {"🎉", Some("party popper")},
+        without_selector = {"❤", Some("red heart")},
+        skin_tone = {"👋🏽", Some("waving hand: medium skin tone")},
+        people = {"👨‍👩‍👧‍👦", Some("family: man, woman, girl, boy")},
+        toned_people = {"👩🏻‍❤️‍👨🏻", Some("couple with heart: woman, man, light skin tone")},
+        two_skin_tones = {"🧑🏻‍🤝‍🧑🏿", Some("people holding hands: light skin tone, dark skin tone")},
+        holding_hands = {"👩🏻‍🤝‍👨🏼", Some("woman and man holding hands: light skin tone, medium-light skin tone")},
+        flag = {"🇯🇵", Some("flag: Japan")},
+        tag_flag = {"🏴󠁧󠁢󠁷󠁬󠁳󠁿", Some("flag: Wales")},
+        keycap = {"#️⃣", Some("keycap: #")},
+        newer = {"🫠", Some("melting face")},
+        not_recommended = {"👨‍🦰‍💻", None},
+        text = {"a", None},
+    )]
+    fn names(emoji: &str, expected: Option<&str>) {
+        assert_eq!(name(emoji).as_deref(), expected);
+    }
+
+    #[parameterized(
+        colons = {":tada:", Some("🎉")},
+        bare = {"tada", Some("🎉")},
+        alias = {":+1:", Some("👍")},
+        skin_tone = {":wave_tone3:", Some("👋🏽")},
+        two_skin_tones = {":people_holding_hands_tone1_tone5:", Some("🧑🏻‍🤝‍🧑🏿")},
+        flag = {":flag_jp:", Some("🇯🇵")},
+        keycap = {":one:", Some("1️⃣")},
+        fully_qualified = {":heart:", Some("❤️")},
+        unknown = {":not_an_emoji:", None},
+        one_colon = {":tada", None},
+    )]
+    fn shortcodes(shortcode: &str, expected: Option<&str>) {
+        assert_eq!(from_shortcode(shortcode).as_deref(), expected);
+    }
+
+    #[parameterized(
+        plain = {"gg 🎉 wp", "gg :tada: wp"},
+        skin_tone = {"👋🏿", ":wave_tone5:"},
+        family = {"👨‍👩‍👧‍👦!", ":family_man_woman_girl_boy:!"},
+        flag = {"🇯🇵🇺🇸", ":flag_jp::flag_us:"},
+        not_recommended = {"👨‍🦰‍💻", ":man::red_hair::computer:"},
+        misplaced_skin_tone = {"🎉🏽", ":tada::skin-tone-3:"},
+        text = {"❤︎ no emoji", "❤︎ no emoji"},
+    )]
+    fn demojizes(text: &str, expected: &str) {
+        assert_eq!(demojize(text), expected);
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(TABLE.entries.len(), 3664);
+        for entry in &TABLE.entries {
+            // each is found whole, and its first shortcode is it
+            assert!(is_emoji(&entry.emoji), "{}", entry.name);
+            assert_eq!(from_shortcode(&entry.shortcodes[0]).as_ref(), Some(&entry.emoji), "{}", entry.name);
+        }
+    }
+}
+// COV_EXCL_STOP
@@ -1,60 +1,927 @@
 // SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>  // COV_EXCL_START
 //
 // SPDX-License-Identifier: MIT
-mod common;
-mod bundle;
+pub(crate) mod bundle;
+mod cache;
+mod check;
+mod detect;
+pub(crate) mod duration;
+mod functions;
+mod intl;
+mod numbers;
+mod pseudo;
 mod translator;
+mod validate;
 
-use std::collections::HashMap;
+pub(crate) use intl::Formats;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
 
 use pyo3::prelude::PyModule;
-use pyo3::{PyResult, pyfunction, wrap_pyfunction};
-use pyo3::exceptions::PyRuntimeError;
-use crate::fluent::translator::Translator;
+use pyo3::{create_exception, FromPyObject, IntoPy, PyAny, PyErr, PyResult, Python, pyfunction, wrap_pyfunction};
+use pyo3::exceptions::{PyRuntimeError, PyTypeError, PyUserWarning, PyValueError};
+use crate::fluent::translator::{TranslationError, Translator};
+use crate::{metrics, profiling, tasks};
+
+create_exception!(
+    charbot_rust,
+    TranslationKeyError,
+    PyRuntimeError,
+    "The key to translate doesn't exist in the locale."
+);
+create_exception!(
+    charbot_rust,
+    LocaleNotFoundError,
+    PyRuntimeError,
+    "The translations for the locale couldn't be found or loaded."
+);
+create_exception!(
+    charbot_rust,
+    FormattingError,
+    PyRuntimeError,
+    "The message exists, but couldn't be formatted with the given arguments."
+);
+create_exception!(
+    charbot_rust,
+    MissingArgumentWarning,
+    PyUserWarning,
+    "A lenient translation was missing arguments, placeholders were left for them."
+);
+
+impl TranslationError {
+    /// Convert into the matching python exception, with `context` in front of the message.
+    pub(crate) fn into_py_err(self, context: &str) -> PyErr {
+        match self {
+            TranslationError::Key(e) => TranslationKeyError::new_err(format!("{context}: {e}")),
+            TranslationError::Locale(e) => LocaleNotFoundError::new_err(format!("{context}: {e}")),
+            TranslationError::Formatting(e) => FormattingError::new_err(format!("{context}: {e}")),
+        }
+    }
+}
+
+/// Turn the result of translating `key` into what python gets, warning about any arguments it was missing.
+///
+/// Translations are done without the GIL, so the warning has to wait until they're back here.
+pub(crate) fn finish_translation(
+    py: Python,
+    key: &str,
+    result: Result<(String, Vec<String>), TranslationError>,
+    context: &str,
+) -> PyResult<String> {
+    let (value, missing) = result.map_err(|e| e.into_py_err(context))?;
+    if !missing.is_empty() {
+        PyErr::warn(
+            py,
+            py.get_type::<MissingArgumentWarning>(),
+            &format!("Missing arguments for {key}: {}", missing.join(", ")),
+            1,
+        )?;
+    }
+    Ok(value)
+}
 
 #[pyfunction]
 #[pyo3(text_signature = "
-translate(locale, key, args, /)
+translate(locale, key, args, /, overrides=None, strict=False, lenient=False, terms=None)
 --
 
 Translate a string into the given locale.
 
 Parameters
 ----------
-locale : {'en-US', 'es-ES', 'fr', 'nl'}
-    The locale to translate to, e.g. 'en-US'. If the locale exists, but the key does not,
-    en-US will be used if the key exists there.
+locale : {'en-US', 'es-ES', 'fr', 'nl', 'pseudo'}
+    The locale to translate to, e.g. 'en-US'. Keys missing from the locale are looked up along its
+    fallback chain, see ``set_fallback_chain``. ``'pseudo'`` is en-US with accented, padded and
+    bracketed text, like ``[Ĥééļļöö]``, to spot hard coded strings and truncation before translating.
 key : str
-    The key to translate.
-args : dict[str, int | float | str]
-    The arguments to format the string with. If no arguments, pass an empty dict, ie ``{}``.
+    The key to translate, or ``key.attribute`` to translate one of its attributes.
+args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]
+    The arguments to format the string with. If no arguments, pass an empty dict, ie ``{}``. Booleans
+    select ``[true]`` or ``[false]`` variants, datetimes format like ``DATETIME`` for the locale, lists
+    are joined like ``a, b, and c``, enum members select the variant named after their str value or their
+    name, raising if there isn't one, and ``None`` formats as nothing.
+overrides : str, optional
+    An FTL source, such as a guild's custom strings, that is checked for the key before the
+    built in translations. Prefer ``Translator.with_overrides`` when translating several strings.
+strict : bool, optional
+    Raise instead of falling back along the chain when the locale is unknown, or the message is
+    missing or fails to format. Defaults to ``False``.
+lenient : bool, optional
+    Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+    ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+terms : dict[str, str], optional
+    Values for terms, by name without the leading ``-``, that replace the terms shared by every
+    locale, e.g. ``{'bot-name': 'Charbot'}`` for a guild's nickname for the bot. Prefer
+    ``Translator.with_terms`` when translating several strings.
 
 Returns
 -------
 str
     The translated string.
 
+Raises
+------
+TranslationKeyError
+    If the key doesn't exist.
+LocaleNotFoundError
+    If the translations couldn't be loaded, or ``strict`` is set and the locale is unknown.
+FormattingError
+    If the message couldn't be formatted with ``args``, ``overrides`` is not valid FTL, or a name in
+    ``terms`` is not a valid term name.
+")]
+#[pyo3(signature = (locale, key, args, /, overrides=None, strict=false, lenient=false, terms=None))]
+#[allow(clippy::too_many_arguments)] // the optional ones are keyword arguments on the python side
+pub(crate) fn translate(
+    py: Python,
+    locale: String,
+    key: String,
+    args: HashMap<String, translator::ArgTypes>,
+    overrides: Option<String>,
+    strict: bool,
+    lenient: bool,
+    terms: Option<HashMap<String, String>>,
+) -> PyResult<String>{
+    metrics::observe("fluent.translate", || {
+        let result = py.allow_threads(|| {
+            let translator = profiling::profile("fluent.translate/translator", || {
+                let mut translator = match overrides {
+                    Some(overrides) => get_override_translator(&locale, &overrides, strict)?,
+                    None => get_translator(&locale, strict)?,
+                };
+                if let Some(terms) = terms {
+                    translator = translator.terms(&terms).map_err(|e| e.into_py_err("Failed to create translator"))?;
+                }
+                PyResult::Ok(translator.lenient(lenient))
+            })?;
+            PyResult::Ok(profiling::profile("fluent.translate/format", || translator.translate_checked(&key, args)))
+        })?;
+        profiling::profile("fluent.translate/finish", || finish_translation(py, &key, result, "Failed to translate"))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+translate_many(locale, items, /, strict=False, lenient=False)
+--
+
+Translate several strings into the given locale at once.
+
+Parameters
+----------
+locale : {'en-US', 'es-ES', 'fr', 'nl'}
+    The locale to translate to, e.g. 'en-US'. Keys missing from the locale are looked up along its
+    fallback chain, see ``set_fallback_chain``.
+items : list[tuple[str, dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]]]
+    The ``(key, args)`` pairs to translate, in the same form as ``translate`` takes them.
+strict : bool, optional
+    Raise instead of falling back along the chain when the locale is unknown, or a message is
+    missing or fails to format. Defaults to ``False``.
+lenient : bool, optional
+    Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+    ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+
+Returns
+-------
+list[str]
+    The translated strings, in the same order as ``items``.
+
+Raises
+------
+TranslationKeyError
+    If any of the keys don't exist.
+LocaleNotFoundError
+    If the translations couldn't be loaded, or ``strict`` is set and the locale is unknown.
+FormattingError
+    If any of the messages couldn't be formatted with their args.
+")]
+#[pyo3(signature = (locale, items, /, strict=false, lenient=false))]
+pub(crate) fn translate_many(
+    py: Python,
+    locale: String,
+    items: Vec<(String, HashMap<String, translator::ArgTypes>)>,
+    strict: bool,
+    lenient: bool,
+) -> PyResult<Vec<String>>{
+    metrics::observe("fluent.translate_many", || {
+        let results = py.allow_threads(|| {
+            let translator = get_translator(&locale, strict)?.lenient(lenient);
+            PyResult::Ok(
+                items
+                    .into_iter()
+                    .map(|(key, args)| {
+                        let result = translator.translate_checked(&key, args);
+                        (key, result)
+                    })
+                    .collect::<Vec<_>>()
+            )
+        })?;
+        results
+            .into_iter()
+            .map(|(key, result)| finish_translation(py, &key, result, &format!("Failed to translate {key}")))
+            .collect()
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+translate_matrix(keys, locales, /, args=None, strict=False, lenient=False)
+--
+
+Translate several strings into several locales at once, like for the localized names of every
+command, with each locale on its own thread without holding the GIL.
+
+Parameters
+----------
+keys : list[str]
+    The keys to translate, or ``key.attribute`` to translate one of their attributes.
+locales : list[str]
+    The locales to translate to, e.g. ``['en-US', 'fr']``. Keys missing from a locale are looked up
+    along its fallback chain, see ``set_fallback_chain``.
+args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None] | None
+    The arguments to format every string with, in the same form as ``translate`` takes them. The
+    default is None, for none.
+strict : bool, optional
+    Raise instead of falling back along the chain when a locale is unknown, or a message is missing
+    or fails to format. Defaults to ``False``.
+lenient : bool, optional
+    Leave a placeholder like ``{$user}`` for each missing argument and warn with a
+    ``MissingArgumentWarning``, instead of raising. Defaults to ``False``.
+
+Returns
+-------
+list[list[str]]
+    A row for each key in the order of ``keys``, with the translation into each locale in the order
+    of ``locales``.
+
+Raises
+------
+TranslationKeyError
+    If any of the keys don't exist.
+LocaleNotFoundError
+    If the translations couldn't be loaded, or ``strict`` is set and a locale is unknown.
+FormattingError
+    If any of the messages couldn't be formatted with the args.
+")]
+#[pyo3(signature = (keys, locales, /, args=None, strict=false, lenient=false))]
+pub(crate) fn translate_matrix(
+    py: Python,
+    keys: Vec<String>,
+    locales: Vec<String>,
+    args: Option<HashMap<String, translator::ArgTypes>>,
+    strict: bool,
+    lenient: bool,
+) -> PyResult<Vec<Vec<String>>>{
+    metrics::observe("fluent.translate_matrix", || {
+        let args = args.unwrap_or_default();
+        let columns = py.allow_threads(|| {
+            tasks::parallel_map(locales, |locale| {
+                let translator = get_translator(&locale, strict)?.lenient(lenient);
+                PyResult::Ok(keys.iter().map(|key| translator.translate_checked(key, args.clone())).collect::<Vec<_>>())
+            })
+        });
+        let mut columns = columns.into_iter().map(|column| column.map(Vec::into_iter)).collect::<PyResult<Vec<_>>>()?;
+        keys.iter()
+            .map(|key| {
+                columns
+                    .iter_mut()
+                    .map(|column| column.next().expect("Every locale has a translation for every key"))
+                    .map(|result| finish_translation(py, key, result, &format!("Failed to translate {key}")))
+                    .collect()
+            })
+            .collect()
+    })
+}
+
+#[cfg(feature = "async")]
+#[pyfunction]
+#[pyo3(text_signature = "
+translate_many_async(locale, items, /, strict=False, lenient=False)
+--
+
+Translate several strings into the given locale at once on a thread pool, so the event loop keeps
+running meanwhile. It takes and gives the same as ``translate_many``, once awaited.
+
+Returns
+-------
+asyncio.Future[list[str]]
+    The translated strings, in the same order as ``items``.
+
 Raises
 ------
 RuntimeError
-    If anything errors.
-")]
-pub(crate) fn translate(locale: String, key: String, args: HashMap<String, translator::ArgTypes>) -> PyResult<String>{
-    let translator: Translator;
-    if let Some(enum_locale) = bundle::AvailableLocales::from_str(locale.as_str()) {
-        translator = Translator::new(enum_locale).map_err(|e| {
-            PyRuntimeError::new_err(format!("Failed to create translator: {e}"))
-        }).map_err(PyRuntimeError::new_err)?;
-    } else {
-        translator = Translator::new(bundle::AvailableLocales::AmericanEnglish).map_err(|e| {
-            PyRuntimeError::new_err(format!("Failed to create translator: {e}"))
-        }).map_err(PyRuntimeError::new_err)?;
+    If there isn't an event loop running.
+")]
+#[pyo3(signature = (locale, items, /, strict=false, lenient=false))]
+pub(crate) fn translate_many_async<'py>(
+    py: Python<'py>,
+    locale: String,
+    items: Vec<(String, HashMap<String, translator::ArgTypes>)>,
+    strict: bool,
+    lenient: bool,
+) -> PyResult<&'py PyAny>{
+    tasks::spawn(py, move |py| translate_many(py, locale, items, strict, lenient).map(|translated| translated.into_py(py)))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+translate_all(key, args, /)
+--
+
+Translate a string into every available locale at once, e.g. for the localized names and
+descriptions of application commands.
+
+Parameters
+----------
+key : str
+    The key to translate, or ``key.attribute`` to translate one of its attributes.
+args : dict[str, bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None]
+    The arguments to format the string with, in the same form as ``translate`` takes them.
+
+Returns
+-------
+dict[str, str]
+    The translations by locale code. Locales that don't have the key, or can't format it, are left
+    out instead of falling back to en-US, so discord falls back for them itself.
+
+Raises
+------
+TranslationKeyError
+    If the key doesn't exist in en-US.
+LocaleNotFoundError
+    If the translations couldn't be loaded.
+FormattingError
+    If the en-US message couldn't be formatted with ``args``.
+")]
+pub(crate) fn translate_all(
+    py: Python,
+    key: &str,
+    args: HashMap<String, translator::ArgTypes>,
+) -> PyResult<BTreeMap<&'static str, String>> {
+    metrics::observe("fluent.translate_all", || {
+        py.allow_threads(|| translator::translate_all(key, &args)).map_err(|e| e.into_py_err("Failed to translate"))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+reload_translations(path, /)
+--
+
+Reload the translations from a directory on disk, without restarting.
+
+The terms every locale shares, like ``-bot-name``, are read again too.
+
+Parameters
+----------
+path : str | os.PathLike[str]
+    The directory to load from. It should be laid out like the built in translations, one folder per
+    locale code (e.g. ``fr/``) holding that locale's ``.ftl`` files. Locales without a folder keep
+    using the built in translations.
+
+Raises
+------
+RuntimeError
+    If the directory can't be read, or any of the files fail to parse. The current translations are
+    kept when this happens.
+")]
+pub(crate) fn reload_translations(py: Python, path: PathBuf) -> PyResult<()>{
+    py.allow_threads(|| translator::reload_bundles(&path)).map_err(|e| PyRuntimeError::new_err(format!("Failed to reload translations: {e}")))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+register_function(name, callable, /)
+--
+
+Make a python function callable from inside translations, e.g. ``{ EMOJI($name) }``.
+
+Parameters
+----------
+name : str
+    The name of the function in the FTL files. Fluent function names are upper case, e.g. ``EMOJI``.
+callable : Callable[..., str | int | float | None]
+    Called with the positional arguments of the call as ``str`` or ``float``, and its named
+    arguments as keyword arguments. The result is converted with ``str`` if it isn't a number,
+    string, or ``None``, and any exception it raises is logged to ``charbot_rust.fluent`` and shown as an
+    error in the message.
+
+Raises
+------
+RuntimeError
+    If the name is not a valid function name, is ``NUMBER`` or ``DATETIME``, or the translations couldn't
+    be rebuilt with the function.
+
+Notes
+-----
+Existing ``Translator`` instances don't see the function, create new ones after registering.
+")]
+pub(crate) fn register_function(name: &str, callable: &PyAny) -> PyResult<()> {
+    if !callable.is_callable() {
+        return Err(PyRuntimeError::new_err(format!("Failed to register function {name}: it is not callable")));
     }
-    translator.translate(&key, args).map_err(|e| PyRuntimeError::new_err(format!("Failed to translate: {e}")))
+    translator::register_function(name, callable.into_py(callable.py()))
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to register function {name}: {e}")))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+set_use_isolating(use_isolating, /)
+--
+
+Set whether arguments in translations are wrapped in unicode bidi isolation marks (FSI and PDI).
+
+They're left out by default, since some discord clients render them as boxes, but they keep right to
+left text in arguments displaying correctly.
+
+Parameters
+----------
+use_isolating : bool
+    ``True`` to wrap the arguments of every translation made from now on in the marks, ``False`` to
+    leave them out again.
+
+Raises
+------
+RuntimeError
+    If the translations couldn't be rebuilt with the new setting.
+
+Notes
+-----
+Existing ``Translator`` instances keep the setting they were created with.
+")]
+pub(crate) fn set_use_isolating(use_isolating: bool) -> PyResult<()> {
+    translator::set_use_isolating(use_isolating)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to set use_isolating: {e}")))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+clear_translation_cache()
+--
+
+Drop every cached translation.
+
+Translations are cached by locale, key and arguments, since the same ones are made over and over.
+Ones that call a registered function, or that were made with errors like a missing argument, aren't
+cached, so they're made again each time.
+
+Notes
+-----
+Reloading the translations, registering a function, adding a locale and ``set_use_isolating`` all
+clear the cache already, and the cache never hands out translations made from other bundles.
+This is only needed to free the memory.
+")]
+pub(crate) fn clear_translation_cache() {
+    translator::clear_cache();
+}
+
+#[pyfunction]
+#[pyo3(signature = (locale, value, /, style="decimal"), text_signature = "
+format_number(locale, value, /, style='decimal')
+--
+
+Format a number for a locale outside of a message, such as points or a percentage.
+
+Parameters
+----------
+locale : {'en-US', 'es-ES', 'fr', 'nl'}
+    The locale to format for. Other locales use the first locale of their fallback chain, see
+    ``set_fallback_chain``, and unknown ones format like en-US. Locales added at runtime format like
+    the built in locale with their language, and there are no formats for other languages.
+value : int | float
+    The number to format.
+style : {'decimal', 'percent', 'compact', 'grouping'}, optional
+    ``'decimal'`` formats like ``NUMBER`` does, with up to three fraction digits, ``'percent'``
+    multiplies by 100 and adds the percent sign, ``'compact'`` shortens large numbers like ``1.2K``,
+    and ``'grouping'`` rounds to a whole number with the digits grouped like ``1,234,567``.
+    Defaults to ``'decimal'``.
+
+Returns
+-------
+str
+    The formatted number.
+
+Raises
+------
+ValueError
+    If the style is not one of the above, or there are no formats for the locale.
+")]
+pub(crate) fn format_number(locale: &str, value: f64, style: &str) -> PyResult<String> {
+    let style = numbers::NumberStyle::from_str(style)
+        .ok_or_else(|| PyValueError::new_err(format!("Failed to format number: unknown style {style}")))?;
+    let formats = formats(locale).map_err(|e| PyValueError::new_err(format!("Failed to format number: {e}")))?;
+    Ok(numbers::format_number(formats, value, style))
+}
+
+#[pyfunction]
+#[pyo3(signature = (locale, seconds, /, precision=2, compact=false), text_signature = "
+humanize_duration(locale, seconds, /, precision=2, compact=False)
+--
+
+Describe a number of seconds in a locale, like ``2 hours 5 minutes``, such as how long a cooldown has left.
+
+Parameters
+----------
+locale : {'en-US', 'es-ES', 'fr', 'nl'}
+    The locale to describe the duration in. Other locales use the first locale of their fallback
+    chain, see ``set_fallback_chain``, and unknown ones use en-US. Locales added at runtime use the
+    built in locale with their language, and there are no unit names for other languages.
+seconds : int | float
+    The duration, fractions of a second are dropped.
+precision : int, optional
+    The most units to use, biggest first, the smaller ones left over are dropped. Units that are
+    zero don't count, so a day and five minutes is ``1 day 5 minutes``. Defaults to 2.
+compact : bool, optional
+    Use short units like ``2h 5m`` instead. Defaults to ``False``.
+
+Returns
+-------
+str
+    The duration, in days, hours, minutes and seconds.
+
+Raises
+------
+ValueError
+    If ``seconds`` is negative, ``precision`` is less than 1, or there are no unit names for the locale.
+")]
+pub(crate) fn humanize_duration(locale: &str, seconds: f64, precision: usize, compact: bool) -> PyResult<String> {
+    if seconds.is_nan() || seconds < 0.0 {
+        return Err(PyValueError::new_err(format!("Failed to humanize duration: {seconds} is not a valid number of seconds")));
+    }
+    if precision == 0 {
+        return Err(PyValueError::new_err("Failed to humanize duration: precision must be at least 1"));
+    }
+    let formats = formats(locale).map_err(|e| PyValueError::new_err(format!("Failed to humanize duration: {e}")))?;
+    Ok(duration::humanize_duration(formats, seconds as u64, precision, compact))
+}
+
+#[pyfunction]
+#[pyo3(signature = (locale, n, /, ordinal=false), text_signature = "
+plural_category(locale, n, /, ordinal=False)
+--
+
+Get the CLDR plural category of a number, as used to pick the variant in translations.
+
+Parameters
+----------
+locale : str
+    The locale to use the plural rules of. This can be any locale CLDR has rules for, not just the
+    ones translated into.
+n : int | float | str
+    The number. Pass a string to keep its formatting, e.g. in en-US ``'1.0'`` is ``'other'`` while
+    ``1`` is ``'one'``.
+ordinal : bool, optional
+    Use the ordinal rules (1st, 2nd, ...) instead of the cardinal ones. Defaults to ``False``.
+
+Returns
+-------
+{'zero', 'one', 'two', 'few', 'many', 'other'}
+    The plural category.
+
+Raises
+------
+LocaleNotFoundError
+    If there are no plural rules for the locale.
+TypeError
+    If ``n`` is not an int, float or str.
+ValueError
+    If ``n`` isn't a number the plural rules can read, like the string ``'abc'`` or NaN.
+")]
+pub(crate) fn plural_category(locale: &str, n: translator::ArgTypes, ordinal: bool) -> PyResult<&'static str> {
+    let number = match &n {
+        translator::ArgTypes::Int(int) => intl::PluralNumber::Int(*int),
+        translator::ArgTypes::Float(float) => intl::PluralNumber::Float(*float),
+        translator::ArgTypes::String(string) => intl::PluralNumber::String(string),
+        _ => return Err(PyTypeError::new_err("n must be an int, float or str")),
+    };
+    intl::plural_category(locale, number, ordinal).map_err(|e| match e {
+        intl::PluralError::Locale(e) => LocaleNotFoundError::new_err(format!("Failed to get plural category: {e}")),
+        intl::PluralError::Number(e) => PyValueError::new_err(format!("Failed to get plural category: {e}")),
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+negotiate_locale(requested, /)
+--
+
+Pick the available locale that best matches the requested ones, e.g. from a discord interaction.
+
+Parameters
+----------
+requested : list[str]
+    The locales the user wants, most preferred first, e.g. ``['es-419', 'en-GB']``. Regional
+    variants match their language, so ``'es-419'`` gets ``'es-ES'``.
+
+Returns
+-------
+{'en-US', 'es-ES', 'fr', 'nl'}
+    The best available locale, en-US if none of them match.
+")]
+pub(crate) fn negotiate_locale(requested: Vec<&str>) -> &'static str {
+    bundle::negotiate(&requested).as_str()
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+detect_language(text, /)
+--
+
+Guess which available locale a message was written in, to reply in it when a user hasn't picked a locale.
+
+The guess is from the most common words and the letters typical of each language, so it works for
+messages of a few words, but not for a lone word shared by languages or a mix of them.
+
+Parameters
+----------
+text : str
+    The message.
+
+Returns
+-------
+{'en-US', 'es-ES', 'fr', 'nl'} | None
+    The locale, or None if the message is too short or unclear to tell. Locales added with
+    ``add_locale`` are never guessed.
+")]
+pub(crate) fn detect_language(text: &str) -> Option<&'static str> {
+    detect::detect(text).map(|locale| locale.as_str())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+set_fallback_chain(locale, chain, /)
+--
+
+Set the locales to try, in order, for each key when translating into a locale.
+
+By default each available locale falls back to en-US, and some regional locales like ``es-MX`` and
+``fr-BE`` fall back to their closest available locale first.
+
+Parameters
+----------
+locale : str
+    The locale tag to set the chain for, e.g. ``'es-MX'``.
+chain : list[{'en-US', 'es-ES', 'fr', 'nl'}]
+    The locales to try. en-US is added to the end if it isn't in the chain.
+
+Raises
+------
+LocaleNotFoundError
+    If the chain is empty, or any locale in it is not available.
+")]
+pub(crate) fn set_fallback_chain(locale: &str, chain: Vec<String>) -> PyResult<()> {
+    let chain = chain
+        .iter()
+        .map(|item| translator::resolve_locale(item, true))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.into_py_err("Failed to set fallback chain"))?;
+    bundle::set_fallback_chain(locale, chain)
+        .map_err(|e| LocaleNotFoundError::new_err(format!("Failed to set fallback chain: {e}")))
+}
+
+/// The messages for [`add_locale`], a `str` is FTL and anything path like is a file to read.
+#[derive(FromPyObject)]
+pub(crate) enum LocaleSource {
+    #[pyo3(transparent)]
+    Ftl(String),
+    #[pyo3(transparent)]
+    File(PathBuf),
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+add_locale(code, source, /)
+--
+
+Add a locale that isn't built in, e.g. community contributed translations, without a new release.
+
+Parameters
+----------
+code : str
+    The locale code, e.g. ``'de'``. The locale can be translated into, used in fallback chains and
+    negotiated like the built in ones.
+source : str | os.PathLike[str]
+    The FTL messages of the locale, or the path of an ``.ftl`` file holding them. Keys missing from
+    them fall back to en-US.
+
+Raises
+------
+RuntimeError
+    If the code is built in or not a valid locale code, the file can't be read, or the messages
+    are not valid FTL. Nothing is added when this happens.
+
+Notes
+-----
+Adding a locale again replaces its messages. Numbers and dates in added locales are formatted like
+en-US, and existing ``Translator`` instances keep the messages they were created with.
+")]
+pub(crate) fn add_locale(py: Python, code: &str, source: LocaleSource) -> PyResult<()> {
+    let source = match source {
+        LocaleSource::Ftl(source) => source,
+        LocaleSource::File(path) => fs::read_to_string(&path)
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to add locale {code}: can't read {}: {e}", path.display())))?,
+    };
+    py.allow_threads(|| translator::add_locale(code, source)).map_err(|e| PyRuntimeError::new_err(format!("Failed to add locale {code}: {e}")))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+available_locales()
+--
+
+Get the locales that can be translated into.
+
+Returns
+-------
+set[str]
+    The supported locale codes, e.g. ``{'en-US', 'es-ES', 'fr', 'nl'}``, including the ones added with
+    ``add_locale``.
+")]
+pub(crate) fn available_locales() -> HashSet<&'static str> {
+    bundle::AvailableLocales::all().iter().map(|locale| locale.as_str()).collect()
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+message_keys(locale, /)
+--
+
+Get the keys of every message available in a locale.
+
+Parameters
+----------
+locale : {'en-US', 'es-ES', 'fr', 'nl'}
+    The locale to list the keys of. Unknown locales are not replaced by en-US here.
+
+Returns
+-------
+set[str]
+    The message keys, as passed to ``translate``.
+
+Raises
+------
+LocaleNotFoundError
+    If the locale is unknown, or its translations couldn't be loaded.
+")]
+pub(crate) fn message_keys(locale: &str) -> PyResult<HashSet<String>> {
+    translator::resolve_locale(locale, true)
+        .and_then(translator::message_ids)
+        .map(|ids| ids.into_iter().collect())
+        .map_err(|e| e.into_py_err("Failed to get message keys"))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+message_metadata(locale, /)
+--
+
+Get the comment and placeables of every message available in a locale, e.g. to document them.
+
+Parameters
+----------
+locale : {'en-US', 'es-ES', 'fr', 'nl'}
+    The locale to describe the messages of. Unknown locales are not replaced by en-US here.
+
+Returns
+-------
+dict[str, MessageMetadata]
+    The comment right above each message and the variables it uses, by message key.
+
+Raises
+------
+LocaleNotFoundError
+    If the locale is unknown, or its translations couldn't be loaded.
+")]
+pub(crate) fn message_metadata(locale: &str) -> PyResult<BTreeMap<String, bundle::MessageMetadata>> {
+    translator::resolve_locale(locale, true)
+        .and_then(translator::message_metadata)
+        .map_err(|e| e.into_py_err("Failed to get message metadata"))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+check_bundles()
+--
+
+Compare the translations of every locale against en-US, e.g. to check translation PRs in tests.
+
+Returns
+-------
+dict[str, BundleReport]
+    A report for every locale but en-US, by locale code. ``BundleReport.is_consistent()`` is true
+    when the locale has exactly the keys of en-US, using the same variables.
+
+Raises
+------
+LocaleNotFoundError
+    If the translations couldn't be loaded.
+")]
+pub(crate) fn check_bundles(py: Python) -> PyResult<BTreeMap<&'static str, check::BundleReport>> {
+    py.allow_threads(check::check_bundles).map_err(|e| LocaleNotFoundError::new_err(format!("Failed to check bundles: {e}")))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+validate_ftl(source, /)
+--
+
+Check an FTL source, such as a translation submitted by the community, before using it.
+
+Parameters
+----------
+source : str
+    The FTL source to check.
+
+Returns
+-------
+list[Diagnostic]
+    Everything wrong with the source, in the order it appears, empty if it is fine. Diagnostics
+    with the ``'error'`` severity stop the source from loading, ``'warning'`` ones are likely
+    mistakes, such as a message defined twice.
+")]
+pub(crate) fn validate_ftl(py: Python, source: &str) -> Vec<validate::Diagnostic> {
+    py.allow_threads(|| validate::validate(source))
+}
+
+/// Translate `key` into `locale` for the rest of the crate, like the text drawn on images, with strings for arguments.
+pub(crate) fn translate_text(locale: &str, key: &str, args: &[(&str, String)]) -> Result<String, String> {
+    let args = args.iter().map(|(name, value)| (name.to_string(), translator::ArgTypes::String(value.clone()))).collect();
+    translator::resolve_chain(locale, false)
+        .and_then(|chain| Translator::from_chain(&chain)?.translate(key, args))
+        .map_err(|e| format!("Failed to translate {key}: {e}"))
+}
+
+/// The formats of the first locale translating into `locale` tries, for what is localized outside of messages.
+///
+/// Locales added at runtime in a language without formats are an error, rather than formatting like en-US.
+pub(crate) fn formats(locale: &str) -> Result<Formats, String> {
+    Formats::of(bundle::closest_locale(locale))
+}
+
+/// `value` shortened like "1.2K", for text drawn where there's little room.
+#[cfg(feature = "images")]
+pub(crate) fn format_compact(formats: Formats, value: f64) -> String {
+    numbers::format_number(formats, value, numbers::NumberStyle::Compact)
+}
+
+/// `items` joined into a list like "a, b, and c".
+pub(crate) fn format_list(formats: Formats, items: &[String]) -> String {
+    intl::format_list(formats, items)
+}
+
+/// The time of day `seconds_of_day` seconds after midnight as clocks show it, with or without the seconds.
+pub(crate) fn format_clock(formats: Formats, seconds_of_day: i64, seconds: bool) -> String {
+    let style = if seconds { intl::DateStyle::Medium } else { intl::DateStyle::Short };
+    intl::format_time(formats, style, seconds_of_day)
+}
+
+fn get_translator(locale: &str, strict: bool) -> PyResult<Translator> {
+    translator::resolve_chain(locale, strict)
+        .and_then(|chain| Translator::from_chain(&chain))
+        .map(|translator| translator.strict(strict))
+        .map_err(|e| e.into_py_err("Failed to create translator"))
+}
+
+fn get_override_translator(locale: &str, overrides: &str, strict: bool) -> PyResult<Translator> {
+    translator::resolve_chain(locale, strict)
+        .and_then(|chain| Translator::from_chain(&chain)?.overrides(overrides))
+        .map(|translator| translator.strict(strict))
+        .map_err(|e| e.into_py_err("Failed to create translator"))
 }
 
 pub(crate) fn register_fluent(m: &PyModule) -> PyResult<()>{
     m.add_function(wrap_pyfunction!(translate, m)?)?;
+    m.add_function(wrap_pyfunction!(translate_many, m)?)?;
+    #[cfg(feature = "async")]
+    m.add_function(wrap_pyfunction!(translate_many_async, m)?)?;
+    m.add_function(wrap_pyfunction!(translate_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(translate_all, m)?)?;
+    m.add_function(wrap_pyfunction!(reload_translations, m)?)?;
+    m.add_function(wrap_pyfunction!(register_function, m)?)?;
+    m.add_function(wrap_pyfunction!(set_use_isolating, m)?)?;
+    m.add_function(wrap_pyfunction!(clear_translation_cache, m)?)?;
+    m.add_function(wrap_pyfunction!(format_number, m)?)?;
+    m.add_function(wrap_pyfunction!(humanize_duration, m)?)?;
+    m.add_function(wrap_pyfunction!(plural_category, m)?)?;
+    m.add_function(wrap_pyfunction!(negotiate_locale, m)?)?;
+    m.add_function(wrap_pyfunction!(detect_language, m)?)?;
+    m.add_function(wrap_pyfunction!(set_fallback_chain, m)?)?;
+    m.add_function(wrap_pyfunction!(add_locale, m)?)?;
+    m.add_function(wrap_pyfunction!(available_locales, m)?)?;
+    m.add_function(wrap_pyfunction!(message_keys, m)?)?;
+    m.add_function(wrap_pyfunction!(message_metadata, m)?)?;
+    m.add_function(wrap_pyfunction!(check_bundles, m)?)?;
+    m.add_function(wrap_pyfunction!(validate_ftl, m)?)?;
+    m.add_class::<Translator>()?;
+    m.add_class::<check::BundleReport>()?;
+    m.add_class::<bundle::MessageMetadata>()?;
+    m.add_class::<validate::Diagnostic>()?;
+    m.add("TranslationKeyError", m.py().get_type::<TranslationKeyError>())?;
+    m.add("LocaleNotFoundError", m.py().get_type::<LocaleNotFoundError>())?;
+    m.add("FormattingError", m.py().get_type::<FormattingError>())?;
+    m.add("MissingArgumentWarning", m.py().get_type::<MissingArgumentWarning>())?;
+    translator::load_cached_bundles();
     Ok(())
 }
 // COV_EXCL_STOP
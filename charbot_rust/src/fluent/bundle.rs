@@ -1,111 +1,569 @@
 // SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>  // COV_EXCL_LINE
 //
 // SPDX-License-Identifier: MIT
-use fluent::{FluentBundle, FluentResource}; // fluent translation stuff
-use crate::fluent::common; // ftl files
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::ops::{Deref, DerefMut};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
 
-#[derive(Debug, PartialEq, Eq)]  // COV_EXCL_LINE
+use fluent::FluentResource; // fluent translation stuff
+use fluent_syntax::{ast, parser}; // to list the messages in a resource
+use once_cell::sync::Lazy;
+use pyo3::{pyclass, pymethods}; // message metadata for python
+use fluent_bundle::concurrent::FluentBundle; // thread safe bundles, so they can be cached
+use fluent_langneg::{convert_vec_str_to_langids_lossy, negotiate_languages, NegotiationStrategy};
+use unic_langid::LanguageIdentifier;
+use crate::assets; // ftl files
+use crate::fluent::intl; // NUMBER and DATETIME
+use crate::fluent::pseudo; // the pseudo locale
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]  // COV_EXCL_LINE
 pub enum AvailableLocales {
     AmericanEnglish,
     EuropeanSpanish,
     French,
     Dutch,
+    /// A locale added at runtime with [`add_locale`], by its code.
+    Custom(&'static str),
+    /// en-US made to look translated, for testing, see [`pseudo`]. Only used when asked for by name.
+    Pseudo,
+}
+
+/// The FTL sources of the locales added at runtime, by code.
+///
+/// The codes are leaked so [`AvailableLocales`] can stay `Copy`, adding a locale again only replaces its source.
+static CUSTOM_LOCALES: Lazy<RwLock<BTreeMap<&'static str, String>>> = Lazy::new(|| RwLock::new(BTreeMap::new()));
+
+/// Add the locale `code` with the messages in `source`, or replace the messages of one added before.
+///
+/// Returns the source it replaced, if any. Only bundles built afterwards see the locale.
+pub(crate) fn add_locale(code: &str, source: String) -> Result<Option<String>, String> {
+    if AvailableLocales::ALL.iter().chain([&AvailableLocales::Pseudo]).any(|locale| locale.as_str() == code) {
+        return Err(format!("{code} is built in, use reload_translations to change its messages"));
+    }
+    code.parse::<LanguageIdentifier>().map_err(|e| format!("{code} is not a valid locale code: {e}"))?;
+    FluentResource::try_new(source.clone()).map_err(|_| format!("The messages for {code} are not valid FTL"))?;
+    let mut locales = CUSTOM_LOCALES.write().unwrap_or_else(PoisonError::into_inner);
+    match locales.get_mut(code) {
+        Some(existing) => Ok(Some(std::mem::replace(existing, source))),
+        None => {
+            locales.insert(Box::leak(code.to_string().into_boxed_str()), source);
+            Ok(None)
+        }
+    }
+}
+
+/// Undo an [`add_locale`], putting back the source it replaced.
+pub(crate) fn restore_locale(code: &str, previous: Option<String>) {
+    let mut locales = CUSTOM_LOCALES.write().unwrap_or_else(PoisonError::into_inner);
+    match previous {
+        Some(source) => {
+            if let Some(existing) = locales.get_mut(code) {
+                *existing = source;
+            }
+        }
+        None => {
+            locales.remove(code);
+        }
+    }
 }
 
 impl AvailableLocales {
+    pub const ALL: [AvailableLocales; 4] = [
+        AvailableLocales::AmericanEnglish,
+        AvailableLocales::EuropeanSpanish,
+        AvailableLocales::French,
+        AvailableLocales::Dutch,
+    ];
+
+    /// The built in locales followed by the ones added at runtime.
+    pub fn all() -> Vec<AvailableLocales> {
+        let custom = CUSTOM_LOCALES.read().unwrap_or_else(PoisonError::into_inner);
+        AvailableLocales::ALL
+            .into_iter()
+            .chain(custom.keys().map(|code| AvailableLocales::Custom(code)))
+            .collect()
+    }
+
     pub fn from_str(locale: &str) -> Option<AvailableLocales> {
         match locale {
             "en-US" => Some(AvailableLocales::AmericanEnglish),
             "es-ES" => Some(AvailableLocales::EuropeanSpanish),
             "fr" => Some(AvailableLocales::French),
             "nl" => Some(AvailableLocales::Dutch),
-            _ => None,  //COV_EXCL_LINE
+            "pseudo" => Some(AvailableLocales::Pseudo),
+            _ => CUSTOM_LOCALES
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get_key_value(locale)
+                .map(|(code, _)| AvailableLocales::Custom(code)),
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AvailableLocales::AmericanEnglish => "en-US",
+            AvailableLocales::EuropeanSpanish => "es-ES",
+            AvailableLocales::French => "fr",
+            AvailableLocales::Dutch => "nl",
+            AvailableLocales::Custom(code) => code,
+            AvailableLocales::Pseudo => "pseudo",
+        }
+    }
+}
+
+/// Pick the available locale that best matches `requested`, which is in order of preference.
+///
+/// Regional variants match their language, so `es-419` gets `es-ES`, and en-US is used when nothing matches.
+pub(crate) fn negotiate(requested: &[&str]) -> AvailableLocales {
+    let requested = convert_vec_str_to_langids_lossy(requested);
+    let available = convert_vec_str_to_langids_lossy(AvailableLocales::all().iter().map(|locale| locale.as_str()));
+    let default = AvailableLocales::AmericanEnglish.as_str().parse().expect("Parsing failed");
+    negotiate_languages(&requested, &available, Some(&default), NegotiationStrategy::Lookup)
+        .first()
+        .and_then(|locale| AvailableLocales::from_str(&locale.to_string()))
+        .unwrap_or(AvailableLocales::AmericanEnglish)
+}
+
+/// Fallback chains for locale tags other than the available locales themselves, which fall back to just en-US.
+static FALLBACK_CHAINS: Lazy<RwLock<HashMap<String, Vec<AvailableLocales>>>> = Lazy::new(|| {
+    let chains = [
+        ("en-GB", AvailableLocales::AmericanEnglish),
+        ("es-419", AvailableLocales::EuropeanSpanish),
+        ("es-MX", AvailableLocales::EuropeanSpanish),
+        ("fr-BE", AvailableLocales::French),
+        ("fr-CA", AvailableLocales::French),
+        ("nl-BE", AvailableLocales::Dutch),
+    ];
+    RwLock::new(
+        chains
+            .into_iter()
+            .map(|(tag, locale)| (tag.to_string(), ending_in_english(vec![locale])))
+            .collect()
+    )
+});
+
+/// The locales to try in order when translating into `locale`, if it is known.
+pub(crate) fn fallback_chain(locale: &str) -> Option<Vec<AvailableLocales>> {
+    let chains = FALLBACK_CHAINS.read().unwrap_or_else(PoisonError::into_inner);
+    chains
+        .get(locale)
+        .cloned()
+        .or_else(|| AvailableLocales::from_str(locale).map(|locale| ending_in_english(vec![locale])))
+}
+
+/// The first locale translating into `locale` tries, or en-US if it isn't known, for what is localized outside of messages.
+pub(crate) fn closest_locale(locale: &str) -> AvailableLocales {
+    fallback_chain(locale)
+        .and_then(|chain| chain.first().copied())
+        .unwrap_or(AvailableLocales::AmericanEnglish)
+}
+
+/// Make translating into `locale` try the locales of `chain` in order, en-US is added to the end if missing.
+pub(crate) fn set_fallback_chain(locale: &str, chain: Vec<AvailableLocales>) -> Result<(), String> {
+    if chain.is_empty() {
+        return Err(format!("The fallback chain for {locale} is empty"));
+    }
+    FALLBACK_CHAINS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(locale.to_string(), ending_in_english(chain));
+    Ok(())
+}
+
+/// Drop repeats from `chain` and make sure it ends with en-US, the one locale with every message.
+fn ending_in_english(chain: Vec<AvailableLocales>) -> Vec<AvailableLocales> {
+    let mut deduped = Vec::with_capacity(chain.len() + 1);
+    for locale in chain {
+        if !deduped.contains(&locale) {
+            deduped.push(locale);
+        }
+    }
+    if !deduped.contains(&AvailableLocales::AmericanEnglish) {
+        deduped.push(AvailableLocales::AmericanEnglish);
+    }
+    deduped
+}
+
+/// The bundles translations are formatted with, the resources are shared so a bundle can be rebuilt cheaply.
+pub(crate) type Bundle = FluentBundle<Arc<FluentResource>>;
+
+/// The terms every locale has, such as `-bot-name`, read the first time a bundle needs them and again on
+/// every reload.
+static SHARED_TERMS: RwLock<Option<Arc<FluentResource>>> = RwLock::new(None);
+
+fn read_shared_terms() -> Result<Arc<FluentResource>, String> {
+    let source = assets::text("fluent/i18n/terms.ftl")?;
+    FluentResource::try_new(source.into_owned())
+        .map(Arc::new)
+        .map_err(|(_, errors)| format!("The shared terms are not valid FTL: {errors:?}"))
+}
+
+/// The terms every locale has, reading them if no bundle has needed them yet.
+fn shared_terms() -> Result<Arc<FluentResource>, String> {
+    if let Some(terms) = &*SHARED_TERMS.read().unwrap_or_else(PoisonError::into_inner) {
+        return Ok(terms.clone());
+    }
+    let mut shared = SHARED_TERMS.write().unwrap_or_else(PoisonError::into_inner);
+    match &*shared {
+        Some(terms) => Ok(terms.clone()),
+        None => Ok(shared.insert(read_shared_terms()?).clone()),
+    }
+}
+
+/// Read the terms every locale has again, so bundles built from now on use them.
+///
+/// Returns the terms it replaced, to undo it with [`restore_shared_terms`].
+pub(crate) fn reload_shared_terms() -> Result<Option<Arc<FluentResource>>, String> {
+    let terms = read_shared_terms()?;
+    Ok(SHARED_TERMS.write().unwrap_or_else(PoisonError::into_inner).replace(terms))
+}
+
+/// Undo a [`reload_shared_terms`], putting back the terms it replaced.
+pub(crate) fn restore_shared_terms(previous: Option<Arc<FluentResource>>) {
+    *SHARED_TERMS.write().unwrap_or_else(PoisonError::into_inner) = previous;
+}
+
+/// A locale's bundle, along with the ids of every message added to it, since bundles can't list them.
+pub(crate) struct LocaleBundle {
+    locale: AvailableLocales,
+    bundle: Bundle,
+    message_ids: BTreeSet<String>,
+    /// Every resource added so far, in order, to rebuild the bundle with other terms.
+    resources: Vec<Arc<FluentResource>>,
+    /// The terms every locale has, as they were when added, which don't replace the locale's own.
+    shared_terms: Option<Arc<FluentResource>>,
+}
+
+impl LocaleBundle {
+    pub(crate) fn new(locale: AvailableLocales) -> Self {
+        Self {
+            locale,
+            bundle: new_bundle(locale),
+            message_ids: BTreeSet::new(),
+            resources: Vec::new(),
+            shared_terms: None,
+        }
+    }
+
+    pub(crate) fn add_resource(&mut self, source: &str) -> Result<(), String> {
+        let resource = parse_resource(source)?;
+        if self.bundle.add_resource(resource.clone()).is_err() {
+            return Err("Failed to add FTL resources to the bundle.".to_string());  //COV_EXCL_LINE
+        }
+        self.message_ids.extend(message_ids(&resource));
+        self.resources.push(resource);
+        Ok(())
+    }
+
+    /// Add the terms shared by every locale, after its own resources, so a locale can still translate a term.
+    pub(crate) fn add_shared_terms(&mut self) -> Result<(), String> {
+        let terms = shared_terms()?;
+        // the only errors are for the terms the locale already defines, which it keeps
+        let _ = self.bundle.add_resource(terms.clone());
+        self.resources.push(terms.clone());
+        self.shared_terms = Some(terms);
+        Ok(())
+    }
+
+    /// A copy of this bundle with the terms in `terms` replacing any it had, see [`terms_resource`].
+    ///
+    /// Python functions have to be installed again, like with any new bundle.
+    pub(crate) fn with_terms(&self, terms: &Arc<FluentResource>) -> LocaleBundle {
+        let mut rebuilt = self.rebuilt(self.locale);
+        rebuilt.bundle.add_resource_overriding(terms.clone());
+        rebuilt.resources.push(terms.clone());
+        rebuilt
+    }
+
+    /// A copy of this bundle's messages in a new bundle for `locale`, without any python functions.
+    pub(crate) fn rebuilt(&self, locale: AvailableLocales) -> LocaleBundle {
+        let mut bundle = new_bundle(locale);
+        for resource in &self.resources {
+            if self.shared_terms.as_ref().is_some_and(|terms| Arc::ptr_eq(resource, terms)) {
+                // as before, the shared terms don't replace the ones the locale defines itself
+                let _ = bundle.add_resource(resource.clone());
+            } else {
+                // nothing else conflicted when first added, except terms overridden by an earlier `with_terms`
+                bundle.add_resource_overriding(resource.clone());
+            }
+        }
+        LocaleBundle {
+            locale,
+            bundle,
+            message_ids: self.message_ids.clone(),
+            resources: self.resources.clone(),
+            shared_terms: self.shared_terms.clone(),
+        }
+    }
+
+    pub(crate) fn locale(&self) -> AvailableLocales {
+        self.locale
+    }
+
+    /// The ids of every message in this bundle.
+    pub(crate) fn message_ids(&self) -> &BTreeSet<String> {
+        &self.message_ids
+    }
+
+    /// The comment and variables of every message in this bundle, by id.
+    pub(crate) fn message_metadata(&self) -> BTreeMap<String, MessageMetadata> {
+        let mut metadata = BTreeMap::new();
+        for resource in &self.resources {
+            // bundles parse without comments, so the source is parsed again to get them
+            let parsed = parser::parse(resource.source()).unwrap_or_else(|(parsed, _)| parsed);
+            for entry in &parsed.body {
+                let ast::Entry::Message(message) = entry else {
+                    continue;
+                };
+                let mut variables = BTreeSet::new();
+                if let Some(pattern) = &message.value {
+                    pattern_variables(pattern, &mut variables);
+                }
+                for attribute in &message.attributes {
+                    pattern_variables(&attribute.value, &mut variables);
+                }
+                let comment = message.comment.as_ref().map(|comment| comment.content.join("\n"));
+                metadata.insert(message.id.name.to_string(), MessageMetadata { comment, placeables: variables });
+            }
+        }
+        metadata
+    }
+}
+
+/// What tooling needs to know about a message, such as to write a handbook for translators.
+#[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
+#[derive(Debug, Clone, PartialEq, Eq)] // COV_EXCL_LINE
+pub(crate) struct MessageMetadata {
+    /// The `#` comment right above the message, without the leading `#`, if it has one.
+    #[pyo3(get)]
+    pub(crate) comment: Option<String>,
+    /// The names of the variables the message uses anywhere, in its value or its attributes.
+    #[pyo3(get)]
+    pub(crate) placeables: BTreeSet<String>,
+}
+
+// COV_EXCL_START
+#[pymethods]
+impl MessageMetadata {
+    fn __repr__(&self) -> String {
+        format!("MessageMetadata(comment={:?}, placeables={:?})", self.comment, self.placeables)
+    }
+}
+// COV_EXCL_STOP
+
+impl Deref for LocaleBundle {
+    type Target = Bundle;
+
+    fn deref(&self) -> &Self::Target {
+        &self.bundle
+    }
+}
+
+impl DerefMut for LocaleBundle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.bundle
+    }
+}
+
+/// Whether new bundles wrap placeables in unicode bidi isolation marks. Fluent does by default, but they're
+/// left out unless asked for, since some discord clients render them as boxes.
+static USE_ISOLATING: AtomicBool = AtomicBool::new(false);
+
+/// Set whether bundles built from now on wrap placeables in bidi isolation marks, returning the old setting.
+pub(crate) fn set_use_isolating(use_isolating: bool) -> bool {
+    USE_ISOLATING.swap(use_isolating, Ordering::Relaxed)
+}
+
+/// An empty bundle for `locale`, with the locale aware `NUMBER` and `DATETIME` functions.
+fn new_bundle(locale: AvailableLocales) -> Bundle {
+    // the pseudo locale is en-US underneath, so it selects plurals the same way
+    let code = match locale {
+        AvailableLocales::Pseudo => AvailableLocales::AmericanEnglish.as_str(),
+        _ => locale.as_str(),
+    };
+    let mut bundle = FluentBundle::new_concurrent(vec![code.parse().expect("Parsing failed")]);
+    bundle.set_use_isolating(USE_ISOLATING.load(Ordering::Relaxed));
+    if locale == AvailableLocales::Pseudo {
+        bundle.set_transform(Some(pseudo::transform));
+    }
+    intl::install(&mut bundle, locale).expect("A new bundle has no functions yet");
+    bundle
 }
 
-#[allow(unused)]
-pub(crate) fn get_bundle(locale: AvailableLocales) -> Result<FluentBundle<FluentResource>, String>{
+/// The bundle with the built in translations for `locale`, or the messages it was added with.
+pub(crate) fn get_bundle(locale: AvailableLocales) -> Result<LocaleBundle, String>{
+    let mut bundle = LocaleBundle::new(locale);
     match locale {
-        AvailableLocales::AmericanEnglish => {
-            let mut bundle = FluentBundle::new(
-                vec!["en-US".parse().expect("Parsing failed")]
-            );
-            let resources = vec![
-                common::EN_US_DICE,
-                common::EN_US_ERRORS,
-                common::EN_US_GIVEAWAY,
-                common::EN_US_LEVELS,
-                common::EN_US_MINESWEEPER,
-                common::EN_US_PROGRAMS
-            ];
-            for resource in resources {add_resource(&mut bundle, resource)?}
-            Ok(bundle)
-        },
-        //COV_EXCL_START
-        AvailableLocales::EuropeanSpanish => {
-            let mut bundle = FluentBundle::new(
-                vec!["es-ES".parse().expect("Parsing failed")])
-                ;
-            let resources = vec![
-                common::ES_ES_DICE,
-                common::ES_ES_ERRORS,
-                common::ES_ES_GIVEAWAY,
-                common::ES_ES_LEVELS,
-                common::ES_ES_MINESWEEPER,
-                common::ES_ES_PROGRAMS];
-            for resource in resources {add_resource(&mut bundle, resource)?}
-            Ok(bundle)
-        },
-        AvailableLocales::French => {
-            let mut bundle = FluentBundle::new(
-                vec!["fr".parse().expect("Parsing failed")]
-            );
-            let resources = vec![
-                common::FR_DICE,
-                common::FR_ERRORS,
-                common::FR_GIVEAWAY,
-                common::FR_LEVELS,
-                common::FR_MINESWEEPER,
-                common::FR_PROGRAMS
-            ];
-            for resource in resources {add_resource(&mut bundle, resource)?}
-            Ok(bundle)
+        AvailableLocales::AmericanEnglish | AvailableLocales::EuropeanSpanish | AvailableLocales::French | AvailableLocales::Dutch => {
+            for file in assets::files(&format!("fluent/i18n/{}", locale.as_str()), "ftl")? {
+                bundle.add_resource(&assets::text(&file)?).map_err(|e| format!("{file}: {e}"))?;
+            }
         },
-        AvailableLocales::Dutch => {
-            let mut bundle = FluentBundle::new(
-                vec!["nl".parse().expect("Parsing failed")]
-            );
-            let resources = vec![
-                common::NL_DICE,
-                common::NL_ERRORS,
-                common::NL_GIVEAWAY,
-                common::NL_LEVELS,
-                common::NL_MINESWEEPER,
-                common::NL_PROGRAMS
-            ];
-            for resource in resources {add_resource(&mut bundle, resource)?}
-            Ok(bundle)
+        AvailableLocales::Custom(code) => {
+            let locales = CUSTOM_LOCALES.read().unwrap_or_else(PoisonError::into_inner);
+            let source = locales.get(code).ok_or_else(|| format!("Locale {code} was never added"))?;  //COV_EXCL_LINE
+            bundle.add_resource(source)?;
         },
-        //COV_EXCL_STOP
+        AvailableLocales::Pseudo => return get_bundle(AvailableLocales::AmericanEnglish).map(|english| pseudo_bundle(&english)),
+    }
+    bundle.add_shared_terms()?;
+    Ok(bundle)
+}
+
+/// The bundle for the pseudo locale, made from `english`, the en-US bundle, so it follows reloads.
+pub(crate) fn pseudo_bundle(english: &LocaleBundle) -> LocaleBundle {
+    english.rebuilt(AvailableLocales::Pseudo)
+}
+
+/// Load the bundles for every locale from `path`, which is laid out like the built in `i18n` directory:
+/// one folder per locale code, each holding that locale's `.ftl` files.
+///
+/// Locales without a folder keep using their built in translations, or the messages they were added with.
+pub(crate) fn load_from_dir(path: &Path) -> Result<HashMap<AvailableLocales, LocaleBundle>, String> {
+    if !path.is_dir() {
+        return Err(format!("{} is not a directory", path.display()));
+    }
+    let mut bundles = HashMap::new();
+    for locale in AvailableLocales::all() {
+        let locale_dir = path.join(locale.as_str());
+        let bundle = if locale_dir.is_dir() {
+            load_locale_dir(locale, &locale_dir)?
+        } else {
+            get_bundle(locale)?
+        };
+        bundles.insert(locale, bundle);
     }
+    Ok(bundles)
 }
 
-fn add_resource(bundle: &mut FluentBundle<FluentResource>, source: &'static str) -> Result<(), String> {
-    match FluentResource::try_new(source.to_string()) {
-        Ok(res) => {
-            if bundle.add_resource(res).is_err() {
-                return Err("Failed to add FTL resources to the bundle.".to_string());  //COV_EXCL_LINE
+/// Build a bundle holding only `source` and the shared terms, to be consulted before the regular bundle for `locale`.
+///
+/// This is how a guild customizes its strings, it only has to define the messages it wants changed.
+pub(crate) fn get_override_bundle(locale: AvailableLocales, source: &str) -> Result<LocaleBundle, String> {
+    let mut bundle = LocaleBundle::new(locale);
+    bundle.add_resource(source)?;
+    bundle.add_shared_terms()?;
+    Ok(bundle)
+}
+
+/// Turn `terms`, term names without the leading `-` mapped to their values, into a resource defining them.
+pub(crate) fn terms_resource(terms: &HashMap<String, String>) -> Result<Arc<FluentResource>, String> {
+    let mut source = String::new();
+    for (name, value) in terms {
+        let mut chars = name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(format!("{name} is not a valid term name"));
+        }
+        // as a string literal the value is never read as FTL, even if it has braces or several lines
+        source.push_str(&format!("-{name} = {{ \"{}\" }}\n", escape_string_literal(value)));
+    }
+    parse_resource(&source)
+}
+
+fn escape_string_literal(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04X}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn load_locale_dir(locale: AvailableLocales, dir: &Path) -> Result<LocaleBundle, String> {
+    let mut files = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {e}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|file| file.extension().is_some_and(|ext| ext == "ftl"))
+        .collect::<Vec<_>>();
+    // sort so that resources are always added in the same order
+    files.sort();
+    let mut bundle = LocaleBundle::new(locale);
+    for file in files {
+        let source = fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read {}: {e}", file.display()))?;
+        bundle.add_resource(&source)
+            .map_err(|e| format!("{}: {e}", file.display()))?;
+    }
+    bundle.add_shared_terms()?;
+    Ok(bundle)
+}
+
+fn parse_resource(source: &str) -> Result<Arc<FluentResource>, String> {
+    FluentResource::try_new(source.to_string())
+        .map(Arc::new)
+        .map_err(|_| "String could not be turned into a FluentResource".to_string())  //COV_EXCL_LINE
+}
+
+/// The ids of the messages `resource` defines, terms aren't included.
+fn message_ids(resource: &FluentResource) -> impl Iterator<Item = String> + '_ {
+    resource.entries().filter_map(|entry| match entry {
+        ast::Entry::Message(message) => Some(message.id.name.to_string()),
+        _ => None,
+    })
+}
+
+/// Add the names of the variables `pattern` uses to `variables`, including in selectors and function arguments.
+pub(crate) fn pattern_variables(pattern: &ast::Pattern<&str>, variables: &mut BTreeSet<String>) {
+    for element in &pattern.elements {
+        if let ast::PatternElement::Placeable { expression } = element {
+            expression_variables(expression, variables);
+        }
+    }
+}
+
+fn expression_variables(expression: &ast::Expression<&str>, variables: &mut BTreeSet<String>) {
+    match expression {
+        ast::Expression::Select { selector, variants } => {
+            inline_variables(selector, variables);
+            for variant in variants {
+                pattern_variables(&variant.value, variables);
             }
-        },
-        Err(_) => return Err("String could not be turned into a FluentResource".to_string()),  //COV_EXCL_LINE
+        }
+        ast::Expression::Inline(inline) => inline_variables(inline, variables),
+    }
+}
+
+fn inline_variables(inline: &ast::InlineExpression<&str>, variables: &mut BTreeSet<String>) {
+    match inline {
+        ast::InlineExpression::VariableReference { id } => {
+            variables.insert(id.name.to_string());
+        }
+        ast::InlineExpression::FunctionReference { arguments, .. }
+        | ast::InlineExpression::TermReference { arguments: Some(arguments), .. } => {
+            for argument in &arguments.positional {
+                inline_variables(argument, variables);
+            }
+            for argument in &arguments.named {
+                inline_variables(&argument.value, variables);
+            }
+        }
+        ast::InlineExpression::Placeable { expression } => expression_variables(expression, variables),
+        _ => {}
     }
-    Ok(())
 }
 
 
+// COV_EXCL_START
 #[cfg(test)]
 mod tests{
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn temp_i18n_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("charbot_rust_{name}_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("fr")).expect("Failed to create temp dir");
+        dir
+    }
 
     #[test]
     fn test_available_locales_from_str() {
@@ -116,4 +574,212 @@ mod tests{
         assert_eq!(AvailableLocales::from_str("nl"), Some(AvailableLocales::Dutch));
         assert_eq!(AvailableLocales::from_str("de"), None);
     }
+
+    #[test]
+    fn test_load_from_dir() {
+        use super::{load_from_dir, AvailableLocales};
+        let dir = temp_i18n_dir("load");
+        fs::write(dir.join("fr").join("extra.ftl"), "reloaded = Rechargé\n").expect("Failed to write ftl");
+        let bundles = load_from_dir(&dir).expect("Failed to load bundles");
+        assert_eq!(bundles.len(), AvailableLocales::ALL.len());
+        assert!(bundles[&AvailableLocales::French].has_message("reloaded"));
+        assert_eq!(bundles[&AvailableLocales::French].message_ids().len(), 1);
+        assert!(!bundles[&AvailableLocales::French].has_message("check-failed"));
+        assert!(bundles[&AvailableLocales::AmericanEnglish].has_message("check-failed"));
+        fs::remove_dir_all(dir).expect("Failed to clean up");
+    }
+
+    #[test]
+    fn test_load_from_dir_errors() {
+        use super::load_from_dir;
+        let dir = temp_i18n_dir("errors");
+        assert!(load_from_dir(&dir.join("missing")).is_err());
+        fs::write(dir.join("fr").join("broken.ftl"), "broken = { $\n").expect("Failed to write ftl");
+        assert!(load_from_dir(&dir).is_err());
+        fs::remove_dir_all(dir).expect("Failed to clean up");
+    }
+
+    #[test]
+    fn test_get_override_bundle() {
+        use super::{get_override_bundle, AvailableLocales};
+        let bundle = get_override_bundle(AvailableLocales::Dutch, "custom = Hallo\n")
+            .expect("Failed to build override bundle");
+        assert!(bundle.has_message("custom"));
+        assert!(!bundle.has_message("check-failed"));
+        assert!(get_override_bundle(AvailableLocales::Dutch, "custom = { $\n").is_err());
+    }
+
+    #[test]
+    fn test_shared_terms() {
+        use super::{get_bundle, get_override_bundle, AvailableLocales};
+        let format = |bundle: &super::LocaleBundle| {
+            let pattern = bundle.get_message("about").and_then(|message| message.value()).expect("Missing message");
+            bundle.format_pattern(pattern, None, &mut vec![]).to_string()
+        };
+        let bundle = get_override_bundle(AvailableLocales::French, "about = { -bot-name } de { -owner-name }\n")
+            .expect("Failed to build bundle");
+        assert_eq!(format(&bundle), "CharB0T de Charlie");
+        assert!(!bundle.message_ids().contains("-bot-name"));
+        let bundle = get_override_bundle(AvailableLocales::French, "-bot-name = Le Bot\nabout = { -bot-name }\n")
+            .expect("Failed to build bundle");
+        assert_eq!(format(&bundle), "Le Bot");
+        for locale in AvailableLocales::ALL {
+            assert!(!get_bundle(locale).expect("Failed to get bundle").message_ids().iter().any(|id| id.starts_with('-')));
+        }
+    }
+
+    #[test]
+    fn test_with_terms() {
+        use std::collections::HashMap;
+        use super::{get_override_bundle, terms_resource, AvailableLocales};
+        let format = |bundle: &super::LocaleBundle| {
+            let pattern = bundle.get_message("about").and_then(|message| message.value()).expect("Missing message");
+            bundle.format_pattern(pattern, None, &mut vec![]).to_string()
+        };
+        let bundle = get_override_bundle(AvailableLocales::Dutch, "about = { -bot-name } van { -owner-name }\n")
+            .expect("Failed to build bundle");
+        let terms = terms_resource(&HashMap::from([("bot-name".to_string(), "Bot {\"B\"}\\\n".to_string())]))
+            .expect("Failed to build terms");
+        let renamed = bundle.with_terms(&terms);
+        assert_eq!(format(&renamed), "Bot {\"B\"}\\\n van Charlie");
+        assert_eq!(format(&bundle), "CharB0T van Charlie");
+        let terms = terms_resource(&HashMap::from([("owner-name".to_string(), "Bluesy".to_string())]))
+            .expect("Failed to build terms");
+        assert_eq!(format(&renamed.with_terms(&terms)), "Bot {\"B\"}\\\n van Bluesy");
+        assert!(terms_resource(&HashMap::from([("not a term".to_string(), "x".to_string())])).is_err());
+        assert!(terms_resource(&HashMap::from([("-bot-name".to_string(), "x".to_string())])).is_err());
+    }
+
+    #[test]
+    fn test_message_ids() {
+        use super::{get_bundle, AvailableLocales};
+        for locale in AvailableLocales::ALL {
+            let bundle = get_bundle(locale).expect("Failed to get bundle");
+            assert!(bundle.message_ids().contains("check-failed"));
+            assert!(bundle.message_ids().iter().all(|id| bundle.has_message(id)));
+        }
+    }
+
+    #[test]
+    fn test_negotiate() {
+        use super::{negotiate, AvailableLocales};
+        assert_eq!(negotiate(&["nl"]), AvailableLocales::Dutch);
+        assert_eq!(negotiate(&["es-419"]), AvailableLocales::EuropeanSpanish);
+        assert_eq!(negotiate(&["en-GB"]), AvailableLocales::AmericanEnglish);
+        assert_eq!(negotiate(&["pt-BR", "fr"]), AvailableLocales::French);
+        assert_eq!(negotiate(&["pt-BR"]), AvailableLocales::AmericanEnglish);
+        assert_eq!(negotiate(&[]), AvailableLocales::AmericanEnglish);
+    }
+
+    #[test]
+    fn test_fallback_chains() {
+        use super::{fallback_chain, set_fallback_chain, AvailableLocales};
+        assert_eq!(fallback_chain("nl"), Some(vec![AvailableLocales::Dutch, AvailableLocales::AmericanEnglish]));
+        assert_eq!(fallback_chain("es-MX"), Some(vec![AvailableLocales::EuropeanSpanish, AvailableLocales::AmericanEnglish]));
+        assert_eq!(fallback_chain("pt-BR"), None);
+        set_fallback_chain("test-chain", vec![AvailableLocales::Dutch, AvailableLocales::French, AvailableLocales::Dutch])
+            .expect("Failed to set chain");
+        assert_eq!(
+            fallback_chain("test-chain"),
+            Some(vec![AvailableLocales::Dutch, AvailableLocales::French, AvailableLocales::AmericanEnglish])
+        );
+        assert!(set_fallback_chain("test-chain", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_use_isolating() {
+        use super::{get_override_bundle, set_use_isolating, AvailableLocales};
+        let format = |bundle: &super::Bundle| {
+            let pattern = bundle.get_message("greeting").and_then(|message| message.value()).expect("Missing message");
+            let mut args = fluent::FluentArgs::new();
+            args.set("user", "John");
+            bundle.format_pattern(pattern, Some(&args), &mut vec![]).to_string()
+        };
+        let plain = get_override_bundle(AvailableLocales::AmericanEnglish, "greeting = Hi { $user }!\n")
+            .expect("Failed to build bundle");
+        let text = format(&plain);
+        assert_eq!(text, "Hi John!");
+        assert!(!text.contains(['\u{2068}', '\u{2069}']));
+        set_use_isolating(true);
+        let isolated = get_override_bundle(AvailableLocales::AmericanEnglish, "greeting = Hi { $user }!\n")
+            .expect("Failed to build bundle");
+        set_use_isolating(false);
+        assert_eq!(format(&isolated), "Hi \u{2068}John\u{2069}!");
+    }
+
+    #[test]
+    fn test_add_locale() {
+        use super::{add_locale, fallback_chain, get_bundle, negotiate, restore_locale, AvailableLocales};
+        assert!(add_locale("fr", "hello = Salut\n".to_string()).is_err());
+        assert!(add_locale("not a locale", "hello = Hi\n".to_string()).is_err());
+        assert!(add_locale("qaa", "hello = { $\n".to_string()).is_err());
+        assert_eq!(AvailableLocales::from_str("qaa"), None);
+
+        assert_eq!(add_locale("qaa", "hello = Hi\n".to_string()), Ok(None));
+        let locale = AvailableLocales::from_str("qaa").expect("Locale wasn't added");
+        assert_eq!(locale.as_str(), "qaa");
+        assert!(AvailableLocales::all().contains(&locale));
+        assert_eq!(fallback_chain("qaa"), Some(vec![locale, AvailableLocales::AmericanEnglish]));
+        assert_eq!(negotiate(&["qaa-001"]), locale);
+        let bundle = get_bundle(locale).expect("Failed to get bundle");
+        assert!(bundle.has_message("hello"));
+        assert!(!bundle.has_message("check-failed"));
+
+        let previous = add_locale("qaa", "bye = Bye\n".to_string()).expect("Failed to replace locale");
+        assert_eq!(previous.as_deref(), Some("hello = Hi\n"));
+        assert!(get_bundle(locale).expect("Failed to get bundle").has_message("bye"));
+        restore_locale("qaa", previous);
+        assert!(get_bundle(locale).expect("Failed to get bundle").has_message("hello"));
+        restore_locale("qaa", None);
+        assert_eq!(AvailableLocales::from_str("qaa"), None);
+    }
+
+    #[test]
+    fn test_pseudo_locale() {
+        use super::{add_locale, fallback_chain, get_bundle, negotiate, AvailableLocales};
+        assert_eq!(AvailableLocales::from_str("pseudo"), Some(AvailableLocales::Pseudo));
+        assert_eq!(AvailableLocales::Pseudo.as_str(), "pseudo");
+        assert!(!AvailableLocales::all().contains(&AvailableLocales::Pseudo));
+        assert_eq!(negotiate(&["pseudo"]), AvailableLocales::AmericanEnglish);
+        assert_eq!(fallback_chain("pseudo"), Some(vec![AvailableLocales::Pseudo, AvailableLocales::AmericanEnglish]));
+        assert!(add_locale("pseudo", "hello = Hi\n".to_string()).is_err());
+        let english = get_bundle(AvailableLocales::AmericanEnglish).expect("Failed to get bundle");
+        let pseudo = get_bundle(AvailableLocales::Pseudo).expect("Failed to get bundle");
+        assert_eq!(pseudo.locale(), AvailableLocales::Pseudo);
+        assert_eq!(pseudo.message_ids(), english.message_ids());
+    }
+
+    #[test]
+    fn test_message_metadata() {
+        use super::{get_bundle, get_override_bundle, AvailableLocales};
+        let bundle = get_override_bundle(
+            AvailableLocales::AmericanEnglish,
+            concat!(
+                "# $user is who asked\n# $count is how many\n",
+                "counted = { $user } has { $count ->\n    [one] one { $thing }\n   *[other] { NUMBER($count) } things\n}\n",
+                "plain = No comment\n    .title = Hi { $user }\n",
+            ),
+        )
+        .expect("Failed to build bundle");
+        let metadata = bundle.message_metadata();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata["counted"].comment.as_deref(), Some("$user is who asked\n$count is how many"));
+        assert_eq!(
+            metadata["counted"].placeables.iter().collect::<Vec<_>>(),
+            vec!["count", "thing", "user"]
+        );
+        assert_eq!(metadata["plain"].comment, None);
+        assert_eq!(metadata["plain"].placeables.iter().collect::<Vec<_>>(), vec!["user"]);
+        let english = get_bundle(AvailableLocales::AmericanEnglish).expect("Failed to get bundle");
+        assert!(english.message_metadata().keys().eq(english.message_ids().iter()));
+    }
+
+    #[test]
+    fn test_available_locales_round_trip() {
+        use super::AvailableLocales;
+        for locale in AvailableLocales::ALL {
+            assert_eq!(AvailableLocales::from_str(locale.as_str()), Some(locale));
+        }
+    }
 }
+// COV_EXCL_STOP
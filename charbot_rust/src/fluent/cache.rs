@@ -0,0 +1,37 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! A cache of finished translations, since the same button labels and errors get translated over and over.
+use std::sync::{Mutex, PoisonError};
+
+use once_cell::sync::Lazy;
+
+use crate::cache::Store;
+
+/// How many translations are kept before the least recently used ones are dropped.
+const CAPACITY: usize = 1024;
+
+/// A translation, along with the arguments it was missing.
+type Translation = (String, Vec<String>);
+
+/// The translations, by a hash of everything that went into them. They never expire, they're dropped when
+/// the bundles they were made from are replaced.
+static CACHE: Lazy<Mutex<Store<Translation, u64>>> =
+    Lazy::new(|| Mutex::new(Store::new(CAPACITY, None).expect("The capacity is more than 0")));
+
+pub(crate) fn get(key: u64) -> Option<Translation> {
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner).get(&key, 0.0).cloned()
+}
+
+pub(crate) fn insert(key: u64, value: Translation) {
+    CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .set(key, value, None, 0.0)
+        .expect("There's no TTL to be invalid");
+}
+
+/// Drop every cached translation.
+pub(crate) fn clear() {
+    CACHE.lock().unwrap_or_else(PoisonError::into_inner).clear();
+}
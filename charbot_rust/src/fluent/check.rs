@@ -0,0 +1,131 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Comparing every locale's bundle against en-US, so translation PRs can be checked in CI.
+use std::collections::{BTreeMap, BTreeSet};
+
+use pyo3::{pyclass, pymethods};
+
+use crate::fluent::bundle::{self, AvailableLocales, Bundle, LocaleBundle};
+use crate::fluent::translator;
+
+/// How one locale differs from en-US.
+#[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
+#[derive(Debug, Clone, PartialEq, Eq, Default)] // COV_EXCL_LINE
+pub(crate) struct BundleReport {
+    /// Keys en-US has that the locale doesn't.
+    #[pyo3(get)]
+    pub(crate) missing_keys: BTreeSet<String>,
+    /// Keys whose variables differ from en-US, with the variables only one of them uses.
+    #[pyo3(get)]
+    pub(crate) mismatched_placeables: BTreeMap<String, BTreeSet<String>>,
+    /// Keys the locale has that en-US doesn't.
+    #[pyo3(get)]
+    pub(crate) extra_keys: BTreeSet<String>,
+}
+
+// COV_EXCL_START
+#[pymethods]
+impl BundleReport {
+    /// Whether the locale has exactly the keys of en-US, using the same variables.
+    pub(crate) fn is_consistent(&self) -> bool {
+        self.missing_keys.is_empty() && self.mismatched_placeables.is_empty() && self.extra_keys.is_empty()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BundleReport(missing_keys={:?}, mismatched_placeables={:?}, extra_keys={:?})",
+            self.missing_keys, self.mismatched_placeables, self.extra_keys
+        )
+    }
+}
+// COV_EXCL_STOP
+
+/// Compare `bundle` against `english`, the bundle every other locale is translated from.
+pub(crate) fn compare(english: &LocaleBundle, bundle: &LocaleBundle) -> BundleReport {
+    let mut report = BundleReport {
+        missing_keys: english.message_ids().difference(bundle.message_ids()).cloned().collect(),
+        extra_keys: bundle.message_ids().difference(english.message_ids()).cloned().collect(),
+        ..BundleReport::default()
+    };
+    for id in english.message_ids().intersection(bundle.message_ids()) {
+        let expected = message_variables(english, id);
+        let found = message_variables(bundle, id);
+        if expected != found {
+            report.mismatched_placeables.insert(id.clone(), expected.symmetric_difference(&found).cloned().collect());
+        }
+    }
+    report
+}
+
+/// Compare the cached bundle of every locale but en-US against en-US, see [`compare`].
+pub(crate) fn check_bundles() -> Result<BTreeMap<&'static str, BundleReport>, String> {
+    let english = translator::cached_bundle(AvailableLocales::AmericanEnglish)?;
+    AvailableLocales::all()
+        .into_iter()
+        .filter(|locale| *locale != AvailableLocales::AmericanEnglish)
+        .map(|locale| Ok((locale.as_str(), compare(&english, &*translator::cached_bundle(locale)?))))
+        .collect()
+}
+
+/// The names of the variables the message `id` uses anywhere, in its value or its attributes.
+fn message_variables(bundle: &Bundle, id: &str) -> BTreeSet<String> {
+    let mut variables = BTreeSet::new();
+    if let Some(message) = bundle.get_message(id) {
+        if let Some(pattern) = message.value() {
+            bundle::pattern_variables(pattern, &mut variables);
+        }
+        for attribute in message.attributes() {
+            bundle::pattern_variables(attribute.value(), &mut variables);
+        }
+    }
+    variables
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::compare;
+    use crate::fluent::bundle::{AvailableLocales, LocaleBundle};
+
+    fn bundle(locale: AvailableLocales, source: &str) -> LocaleBundle {
+        let mut bundle = LocaleBundle::new(locale);
+        bundle.add_resource(source).expect("Failed to add resource");
+        bundle
+    }
+
+    #[test]
+    fn test_compare() {
+        let english = bundle(
+            AvailableLocales::AmericanEnglish,
+            concat!(
+                "same = Hi { $user }\n",
+                "missing = Bye\n",
+                "renamed = { $count -> \n    [one] One { $thing }\n   *[other] { $count } { $thing }s\n}\n",
+                "attribute = Roll\n    .description = Rolls { NUMBER($sides) } sided dice\n",
+            ),
+        );
+        let dutch = bundle(
+            AvailableLocales::Dutch,
+            concat!(
+                "same = Hoi { $user }\n",
+                "renamed = { $count } { $item }\n",
+                "attribute = Rol\n    .description = Rolt dobbelstenen\n",
+                "extra = Extra\n",
+            ),
+        );
+        let report = compare(&english, &dutch);
+        assert!(!report.is_consistent());
+        assert_eq!(report.missing_keys.into_iter().collect::<Vec<_>>(), vec!["missing"]);
+        assert_eq!(report.extra_keys.into_iter().collect::<Vec<_>>(), vec!["extra"]);
+        assert_eq!(
+            report.mismatched_placeables.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("attribute".to_string(), ["sides".to_string()].into()),
+                ("renamed".to_string(), ["item".to_string(), "thing".to_string()].into()),
+            ]
+        );
+        assert!(compare(&english, &english).is_consistent());
+    }
+}
+// COV_EXCL_STOP
@@ -1,36 +0,0 @@
-// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
-//
-// SPDX-License-Identifier: MIT
-
-// en-US translations, these are the default and the source strings used in crowdin for translation
-// from
-pub const EN_US_DICE: &str = include_str!("i18n/en-US/dice.ftl");
-pub const EN_US_ERRORS: &str = include_str!("i18n/en-US/errors.ftl");
-pub const EN_US_GIVEAWAY: &str = include_str!("i18n/en-US/giveaway.ftl");
-pub const EN_US_LEVELS: &str = include_str!("i18n/en-US/levels.ftl");
-pub const EN_US_MINESWEEPER: &str = include_str!("i18n/en-US/minesweeper.ftl");
-pub const EN_US_PROGRAMS: &str = include_str!("i18n/en-US/programs.ftl");
-
-// es-ES translations of the above source strings
-pub const ES_ES_DICE: &str = include_str!("i18n/es-ES/dice.ftl");
-pub const ES_ES_ERRORS: &str = include_str!("i18n/es-ES/errors.ftl");
-pub const ES_ES_GIVEAWAY: &str = include_str!("i18n/es-ES/giveaway.ftl");
-pub const ES_ES_LEVELS: &str = include_str!("i18n/es-ES/levels.ftl");
-pub const ES_ES_MINESWEEPER: &str = include_str!("i18n/es-ES/minesweeper.ftl");
-pub const ES_ES_PROGRAMS: &str = include_str!("i18n/es-ES/programs.ftl");
-
-// fr translations of the above source strings
-pub const FR_DICE: &str = include_str!("i18n/fr/dice.ftl");
-pub const FR_ERRORS: &str = include_str!("i18n/fr/errors.ftl");
-pub const FR_GIVEAWAY: &str = include_str!("i18n/fr/giveaway.ftl");
-pub const FR_LEVELS: &str = include_str!("i18n/fr/levels.ftl");
-pub const FR_MINESWEEPER: &str = include_str!("i18n/fr/minesweeper.ftl");
-pub const FR_PROGRAMS: &str = include_str!("i18n/fr/programs.ftl");
-
-// nl translations of the above source strings
-pub const NL_DICE: &str = include_str!("i18n/nl/dice.ftl");
-pub const NL_ERRORS: &str = include_str!("i18n/nl/errors.ftl");
-pub const NL_GIVEAWAY: &str = include_str!("i18n/nl/giveaway.ftl");
-pub const NL_LEVELS: &str = include_str!("i18n/nl/levels.ftl");
-pub const NL_MINESWEEPER: &str = include_str!("i18n/nl/minesweeper.ftl");
-pub const NL_PROGRAMS: &str = include_str!("i18n/nl/programs.ftl");
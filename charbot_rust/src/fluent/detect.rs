@@ -0,0 +1,151 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Guessing which of the built in locales a message was written in, to reply in it when a user hasn't picked one.
+//!
+//! Each language is recognized by its most common words, the letters only it uses, and letter
+//! combinations typical of it, which is enough for chat messages of a few words without a model.
+//! Locales added at runtime aren't guessed, since there's nothing to recognize them by.
+use crate::fluent::bundle::AvailableLocales;
+
+/// Words common in each language, in the same order as [`AvailableLocales::ALL`].
+///
+/// Words can be common in more than one, like "de", they count for each and the rest tell them apart.
+const WORDS: [&[&str]; 4] = [
+    &[
+        "the", "and", "is", "are", "was", "were", "be", "been", "you", "your", "it", "this", "that", "what", "with", "have",
+        "has", "not", "for", "of", "to", "on", "my", "he", "she", "they", "we", "do", "does", "can", "will", "just", "so",
+        "but", "how", "why", "there", "here", "hello", "hi", "thanks", "thank", "please", "yes", "would", "could", "about",
+        "from", "all", "if", "or", "at", "get", "like", "know", "i", "im", "dont", "an", "who", "when", "where",
+    ],
+    &[
+        "el", "la", "los", "las", "un", "una", "que", "de", "y", "es", "en", "por", "para", "con", "no", "se", "lo", "su",
+        "al", "del", "pero", "como", "más", "muy", "está", "están", "estoy", "soy", "eres", "hay", "qué", "cómo", "yo", "tú",
+        "usted", "mi", "tu", "también", "gracias", "hola", "sí", "bien", "todo", "esto", "eso", "este", "esta", "cuando",
+        "donde", "porque", "tengo", "tiene", "puedo", "quiero", "ya", "nada", "bueno", "buenos", "días",
+    ],
+    &[
+        "le", "la", "les", "un", "une", "des", "du", "de", "et", "est", "sont", "je", "tu", "il", "elle", "nous", "vous", "ils",
+        "ce", "cette", "c", "qui", "que", "pas", "ne", "n", "pour", "avec", "dans", "sur", "mais", "ou", "où", "très", "bien",
+        "merci", "bonjour", "salut", "oui", "non", "moi", "toi", "mon", "ma", "mes", "ton", "son", "au", "aux", "être",
+        "avoir", "j", "l", "d", "qu", "fait", "comme", "tout", "aussi", "ça", "suis", "bonsoir",
+    ],
+    &[
+        "de", "het", "een", "en", "van", "is", "dat", "die", "niet", "ik", "je", "jij", "u", "we", "wij", "zij", "hij", "ze",
+        "op", "te", "met", "voor", "zijn", "was", "maar", "ook", "als", "bij", "er", "nog", "wat", "hoe", "waarom", "naar",
+        "dan", "om", "kan", "wel", "geen", "heb", "heeft", "hebben", "mijn", "jouw", "dit", "dus", "nu", "hallo", "hoi",
+        "dank", "bedankt", "alsjeblieft", "ja", "nee", "goed", "graag", "even", "toch", "goedemorgen",
+    ],
+];
+
+/// Letters only one of the languages uses, in the same order.
+const LETTERS: [&[char]; 4] = [
+    &[],
+    &['ñ', 'á', 'í', 'ó', 'ú'],
+    &['ç', 'œ', 'à', 'â', 'è', 'ê', 'î', 'ô', 'ù', 'û'],
+    &['ĳ'],
+];
+
+/// Letter combinations typical of each language, which help with words that aren't in [`WORDS`].
+const PATTERNS: [&[&str]; 4] = [
+    &["th", "wh", "ght"],
+    &["ción", "dad"],
+    &["eau", "eux", "oi"],
+    &["ij", "aa", "uu", "sch", "oe"],
+];
+
+/// How much a combination from [`PATTERNS`] counts, compared to a word or letter.
+const PATTERN_WEIGHT: f64 = 0.5;
+/// The least a language has to score to be guessed.
+const MIN_SCORE: f64 = 1.0;
+/// How many times the score of the next best language the best one has to score, so mixed or unclear messages aren't guessed.
+const MARGIN: f64 = 1.5;
+
+/// The words of `text`, lowercase, with apostrophes dropped so "don't" is "dont", but french elisions
+/// like "c'est" split into "c" and "est".
+fn words(text: &str) -> Vec<String> {
+    let apostrophe = |letter: char| letter == '\'' || letter == '\u{2019}';
+    let mut words = Vec::new();
+    for word in text.to_lowercase().split(|letter: char| !letter.is_alphabetic() && !apostrophe(letter)) {
+        let parts = word.split(apostrophe).filter(|part| !part.is_empty()).collect::<Vec<_>>();
+        if parts.len() > 1 && parts[0].chars().count() <= 2 {
+            words.extend(parts.into_iter().map(str::to_string));
+        } else if !parts.is_empty() {
+            words.push(parts.concat());
+        }
+    }
+    words
+}
+
+/// How much `text` looks like each language, in the same order as [`AvailableLocales::ALL`].
+fn scores(text: &str) -> [f64; 4] {
+    let mut scores = [0.0; 4];
+    for word in words(text) {
+        for (index, score) in scores.iter_mut().enumerate() {
+            if WORDS[index].contains(&word.as_str()) {
+                *score += 1.0;
+            }
+            *score += word.chars().filter(|letter| LETTERS[index].contains(letter)).count() as f64;
+            *score += PATTERN_WEIGHT * PATTERNS[index].iter().map(|pattern| word.matches(pattern).count()).sum::<usize>() as f64;
+        }
+    }
+    // the inverted question and exclamation marks aren't part of words
+    scores[1] += text.matches(['¿', '¡']).count() as f64;
+    scores
+}
+
+/// The built in locale `text` was most likely written in, or None if it's too short or unclear to tell.
+pub(crate) fn detect(text: &str) -> Option<AvailableLocales> {
+    let scores = scores(text);
+    let (best, score) = scores.iter().copied().enumerate().fold((0, f64::MIN), |best, (index, score)| if score > best.1 { (index, score) } else { best });
+    let next = scores.iter().copied().enumerate().filter(|(index, _)| *index != best).map(|(_, score)| score).fold(0.0, f64::max);
+    (score >= MIN_SCORE && score >= next * MARGIN && score > next).then_some(AvailableLocales::ALL[best])
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        english = {"Hey, what is the best way to get started with this game?", Some(AvailableLocales::AmericanEnglish)},
+        english_contraction = {"I don't know how that works", Some(AvailableLocales::AmericanEnglish)},
+        spanish = {"¿Cómo puedo jugar a este juego con mis amigos?", Some(AvailableLocales::EuropeanSpanish)},
+        spanish_plain = {"no se donde esta la informacion del servidor", Some(AvailableLocales::EuropeanSpanish)},
+        french = {"Je ne sais pas comment ça marche, vous pouvez m'aider ?", Some(AvailableLocales::French)},
+        french_elision = {"c'est très bien, merci", Some(AvailableLocales::French)},
+        dutch = {"Ik weet niet hoe dat werkt, kan iemand mij helpen?", Some(AvailableLocales::Dutch)},
+        dutch_short = {"hoi, hoe gaat het?", Some(AvailableLocales::Dutch)},
+        greeting_english = {"hello", Some(AvailableLocales::AmericanEnglish)},
+        greeting_spanish = {"hola", Some(AvailableLocales::EuropeanSpanish)},
+        greeting_french = {"bonjour", Some(AvailableLocales::French)},
+        greeting_dutch = {"hallo", Some(AvailableLocales::Dutch)},
+        empty = {"", None},
+        no_letters = {"123 :) <@1234>", None},
+        shared_word = {"de", None},
+        unknown = {"xyzzy", None},
+    )]
+    fn detects(text: &str, expected: Option<AvailableLocales>) {
+        assert_eq!(detect(text), expected, "{:?}", scores(text));
+    }
+
+    #[test]
+    fn mixed_messages() {
+        // mostly one language wins, an even mix is unclear
+        assert_eq!(detect("merci! thanks for the help with the bot, it is great"), Some(AvailableLocales::AmericanEnglish));
+        assert_eq!(detect("hola hello"), None);
+    }
+
+    #[test]
+    fn lists_are_lowercase() {
+        for (index, words) in WORDS.iter().enumerate() {
+            for word in *words {
+                assert_eq!(word.to_lowercase(), *word);
+                assert!(word.chars().all(char::is_alphabetic), "{word}");
+            }
+            assert!(words.len() >= 50, "{}", AvailableLocales::ALL[index].as_str());
+        }
+    }
+}
+// COV_EXCL_STOP
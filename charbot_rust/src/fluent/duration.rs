@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Durations like "2 hours 5 minutes" or "2h 5m", such as how long a cooldown has left.
+//!
+//! The unit names are from CLDR, with the plural form picked by the same plural rules fluent uses.
+use crate::fluent::intl::{self, Formats, NumberOptions, PluralNumber};
+
+/// The length of each unit in seconds, biggest first, matching the order of the names below.
+const UNIT_SECONDS: [u64; 4] = [86_400, 3_600, 60, 1];
+
+/// The singular, plural and compact name of each unit, `{}` is where the number goes in the compact one.
+type UnitNames = [(&'static str, &'static str, &'static str); 4];
+
+const EN_US_UNITS: UnitNames = [
+    ("day", "days", "{}d"),
+    ("hour", "hours", "{}h"),
+    ("minute", "minutes", "{}m"),
+    ("second", "seconds", "{}s"),
+];
+const ES_ES_UNITS: UnitNames = [
+    ("día", "días", "{}d"),
+    ("hora", "horas", "{}h"),
+    ("minuto", "minutos", "{}min"),
+    ("segundo", "segundos", "{}s"),
+];
+const FR_UNITS: UnitNames = [
+    ("jour", "jours", "{}j"),
+    ("heure", "heures", "{}h"),
+    ("minute", "minutes", "{}min"),
+    ("seconde", "secondes", "{}s"),
+];
+const NL_UNITS: UnitNames = [
+    ("dag", "dagen", "{}d"),
+    ("uur", "uur", "{}u"),
+    ("minuut", "minuten", "{}m"),
+    ("seconde", "seconden", "{}s"),
+];
+
+fn unit_names(formats: Formats) -> &'static UnitNames {
+    match formats {
+        Formats::AmericanEnglish => &EN_US_UNITS,
+        Formats::EuropeanSpanish => &ES_ES_UNITS,
+        Formats::French => &FR_UNITS,
+        Formats::Dutch => &NL_UNITS,
+    }
+}
+
+/// Describe `seconds` in the language of `formats` with at most `precision` units, the smaller units left over are dropped.
+///
+/// Units that are zero are skipped, so a day and five minutes is "1 day 5 minutes". `compact` gives "1d 5m".
+pub(crate) fn humanize_duration(formats: Formats, seconds: u64, precision: usize, compact: bool) -> String {
+    let names = unit_names(formats);
+    let mut remaining = seconds;
+    let mut parts = Vec::new();
+    for (index, unit) in UNIT_SECONDS.iter().enumerate() {
+        let count = remaining / unit;
+        remaining %= unit;
+        if count > 0 && parts.len() < precision {
+            parts.push(format_unit(formats, names[index], count, compact));
+        }
+    }
+    if parts.is_empty() {
+        parts.push(format_unit(formats, names[UNIT_SECONDS.len() - 1], 0, compact));
+    }
+    parts.join(" ")
+}
+
+/// The compact name of each unit in `formats`, like `"min"`, with its length in seconds, and the separator
+/// numbers are grouped with, so compact durations can be read back.
+#[cfg(feature = "games")]
+pub(crate) fn compact_units(formats: Formats) -> ([(&'static str, u64); 4], char) {
+    let names = unit_names(formats);
+    let units = [0, 1, 2, 3].map(|index| (names[index].2.trim_start_matches("{}"), UNIT_SECONDS[index]));
+    (units, intl::group_separator(formats))
+}
+
+fn format_unit(
+    formats: Formats,
+    (one, other, short): (&str, &str, &str),
+    count: u64,
+    compact: bool,
+) -> String {
+    let number = intl::format_number(formats, count as f64, &NumberOptions::default());
+    if compact {
+        return short.replace("{}", &number);
+    }
+    let count = i64::try_from(count).unwrap_or(i64::MAX);
+    match intl::plural_category(formats.as_str(), PluralNumber::Int(count), false) {
+        Ok("one") => format!("{number} {one}"),
+        _ => format!("{number} {other}"),
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::humanize_duration;
+    use crate::fluent::intl::Formats;
+
+    #[test]
+    fn test_humanize_duration() {
+        let english = Formats::AmericanEnglish;
+        assert_eq!(humanize_duration(english, 7_530, 2, false), "2 hours 5 minutes");
+        assert_eq!(humanize_duration(english, 7_530, 3, false), "2 hours 5 minutes 30 seconds");
+        assert_eq!(humanize_duration(english, 7_530, 1, false), "2 hours");
+        assert_eq!(humanize_duration(english, 86_700, 2, false), "1 day 5 minutes");
+        assert_eq!(humanize_duration(english, 61, 2, false), "1 minute 1 second");
+        assert_eq!(humanize_duration(english, 0, 2, false), "0 seconds");
+        assert_eq!(humanize_duration(english, 7_530, 2, true), "2h 5m");
+        assert_eq!(humanize_duration(english, 86_400 * 1_500, 1, false), "1,500 days");
+    }
+
+    #[test]
+    fn test_humanize_duration_locales() {
+        assert_eq!(humanize_duration(Formats::EuropeanSpanish, 90_000, 2, false), "1 día 1 hora");
+        assert_eq!(humanize_duration(Formats::EuropeanSpanish, 150, 2, true), "2min 30s");
+        assert_eq!(humanize_duration(Formats::French, 0, 2, false), "0 seconde");
+        assert_eq!(humanize_duration(Formats::French, 172_800, 2, true), "2j");
+        assert_eq!(humanize_duration(Formats::Dutch, 7_260, 2, false), "2 uur 1 minuut");
+        assert_eq!(humanize_duration(Formats::Dutch, 7_320, 2, false), "2 uur 2 minuten");
+        assert_eq!(humanize_duration(Formats::Dutch, 7_320, 2, true), "2u 2m");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+
+use fluent::{FluentArgs, FluentValue};
+use once_cell::sync::Lazy;
+use pyo3::types::{PyDict, PyTuple};
+use pyo3::{IntoPy, PyAny, PyObject, Python};
+
+use crate::fluent::bundle::Bundle;
+use crate::fluent::intl;
+
+/// Python callables registered as fluent functions, installed into every bundle as it gets built.
+static FUNCTIONS: Lazy<RwLock<HashMap<String, PyObject>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+thread_local! {
+    /// How many times this thread has called a registered function.
+    static CALLS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// How many times this thread has called a registered function, so a translation that called one, whose
+/// result can change from call to call, can be told apart from one that didn't.
+pub(crate) fn calls() -> u64 {
+    CALLS.with(Cell::get)
+}
+
+/// Fluent function names are upper case, e.g. `EMOJI` or `CHANNEL_NAME`.
+pub(crate) fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|first| first.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_' || c == '-')
+}
+
+/// Register `callable` as the fluent function `name`, returning the callable it replaced if there was one.
+///
+/// This only affects bundles built afterwards, the caller is responsible for rebuilding the cached ones.
+pub(crate) fn register(name: &str, callable: PyObject) -> Result<Option<PyObject>, String> {
+    if !is_valid_name(name) {
+        return Err(format!("{name} is not a valid function name, it must be upper case like EMOJI"));
+    }
+    if intl::BUILTIN_FUNCTIONS.contains(&name) {
+        return Err(format!("{name} is built in and can't be replaced"));
+    }
+    Ok(FUNCTIONS.write().unwrap_or_else(PoisonError::into_inner).insert(name.to_string(), callable))
+}
+
+/// Undo a [`register`], putting back the callable it replaced.
+pub(crate) fn restore(name: &str, previous: Option<PyObject>) {
+    let mut functions = FUNCTIONS.write().unwrap_or_else(PoisonError::into_inner);
+    match previous {
+        Some(callable) => functions.insert(name.to_string(), callable),
+        None => functions.remove(name),
+    };
+}
+
+// COV_EXCL_START
+/// Add every registered function to `bundle`.
+pub(crate) fn install(bundle: &mut Bundle) -> Result<(), String> {
+    // bundles can be built without the GIL, so take it before the lock like registering does, never after
+    let functions = Python::with_gil(|py| {
+        let functions = FUNCTIONS.read().unwrap_or_else(PoisonError::into_inner);
+        functions
+            .iter()
+            .map(|(name, callable)| (name.clone(), callable.clone_ref(py)))
+            .collect::<Vec<_>>()
+    });
+    for (name, callable) in functions {
+        let logged = name.clone();
+        bundle
+            .add_function(&name, move |positional, named| call(&logged, &callable, positional, named))
+            .map_err(|e| format!("Failed to add function {name}: {e}"))?;
+    }
+    Ok(())
+}
+
+/// Call the registered function `name`, a python exception renders as an error value in the message
+/// and is logged to `charbot_rust.fluent`.
+fn call<'a>(name: &str, callable: &PyObject, positional: &[FluentValue<'a>], named: &FluentArgs) -> FluentValue<'a> {
+    CALLS.with(|calls| calls.set(calls.get() + 1));
+    Python::with_gil(|py| {
+        let args = PyTuple::new(py, positional.iter().map(|value| to_python(py, value)));
+        let kwargs = PyDict::new(py);
+        for (key, value) in named.iter() {
+            if kwargs.set_item(key, to_python(py, value)).is_err() {
+                return FluentValue::Error;
+            }
+        }
+        match callable.as_ref(py).call(args, Some(kwargs)) {
+            Ok(result) => from_python(result),
+            Err(e) => {
+                tracing::warn!(target: "charbot_rust.fluent", "The fluent function {name} raised {e}");
+                FluentValue::Error
+            }
+        }
+    })
+}
+
+fn to_python(py: Python, value: &FluentValue) -> PyObject {
+    match value {
+        FluentValue::String(string) => string.as_ref().into_py(py),
+        FluentValue::Number(number) => number.value.into_py(py),
+        _ => py.None(),
+    }
+}
+
+fn from_python<'a>(result: &PyAny) -> FluentValue<'a> {
+    if result.is_none() {
+        FluentValue::None
+    } else if let Ok(string) = result.extract::<String>() {
+        FluentValue::from(string)
+    } else if let Ok(number) = result.extract::<f64>() {
+        FluentValue::from(number)
+    } else {
+        match result.str().and_then(|string| string.to_str().map(str::to_string)) {
+            Ok(string) => FluentValue::from(string),
+            Err(_) => FluentValue::Error,
+        }
+    }
+}
+// COV_EXCL_STOP
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::is_valid_name;
+
+    #[test]
+    fn test_is_valid_name() {
+        assert!(is_valid_name("EMOJI"));
+        assert!(is_valid_name("CHANNEL_NAME-2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("emoji"));
+        assert!(!is_valid_name("2EMOJI"));
+        assert!(!is_valid_name("EMO JI"));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,15 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+#
+# SPDX-License-Identifier: MIT
+
+### Text drawn on the images the bot generates
+
+## Rank cards
+rankcard-level = Level { $level }
+rankcard-rank = Rank #{ $rank }
+rankcard-xp = { $xp } / { $needed } XP
+
+## Level up banners
+banner-levelup-title = LEVEL UP!
+banner-levelup-message = { $username } is now level { $level }
+banner-levelup-levels = Level { $old } → { $new }
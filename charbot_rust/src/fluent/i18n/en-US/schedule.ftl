@@ -0,0 +1,49 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+#
+# SPDX-License-Identifier: MIT
+
+### Descriptions of when recurring announcements happen, put together into sentences like "At 9:00 AM, Monday through Friday"
+
+## Times of day
+schedule-every-second = every second
+schedule-every-seconds = every { $count } seconds
+schedule-every-minute = every minute
+schedule-every-minutes = every { $count } minutes
+schedule-every-hour = every hour
+schedule-every-hours = every { $count } hours
+schedule-at-seconds = at { $seconds } seconds past the minute
+schedule-at-minutes = at { $minutes } minutes past the hour
+schedule-at = at { $times }
+schedule-between = between { $start } and { $end }
+schedule-during-hours = during the { $hours } hours
+
+## Days
+schedule-every-day = every day
+schedule-weekdays-range = { $first } through { $last }
+schedule-on-weekdays = on { $weekdays }
+schedule-on-days = on day { $days } of the month
+schedule-on-days-or-weekdays = on day { $days } of the month or on { $weekdays }
+schedule-in-months = in { $months }
+
+## Days of the week
+schedule-sunday = Sunday
+schedule-monday = Monday
+schedule-tuesday = Tuesday
+schedule-wednesday = Wednesday
+schedule-thursday = Thursday
+schedule-friday = Friday
+schedule-saturday = Saturday
+
+## Months
+schedule-january = January
+schedule-february = February
+schedule-march = March
+schedule-april = April
+schedule-may = May
+schedule-june = June
+schedule-july = July
+schedule-august = August
+schedule-september = September
+schedule-october = October
+schedule-november = November
+schedule-december = December
@@ -0,0 +1,14 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+### Text drawn on the images the bot generates
+
+## Rank cards
+rankcard-level = Nivel { $level }
+rankcard-rank = Puesto #{ $rank }
+rankcard-xp = { $xp } / { $needed } XP
+
+## Level up banners
+banner-levelup-title = ¡SUBISTE DE NIVEL!
+banner-levelup-message = { $username } ahora es nivel { $level }
+banner-levelup-levels = Nivel { $old } → { $new }
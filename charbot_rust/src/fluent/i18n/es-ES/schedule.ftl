@@ -0,0 +1,48 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+### Descriptions of when recurring announcements happen, put together into sentences like "At 9:00 AM, Monday through Friday"
+
+## Times of day
+schedule-every-second = cada segundo
+schedule-every-seconds = cada { $count } segundos
+schedule-every-minute = cada minuto
+schedule-every-minutes = cada { $count } minutos
+schedule-every-hour = cada hora
+schedule-every-hours = cada { $count } horas
+schedule-at-seconds = en el segundo { $seconds } de cada minuto
+schedule-at-minutes = en el minuto { $minutes } de cada hora
+schedule-at = a las { $times }
+schedule-between = entre las { $start } y las { $end }
+schedule-during-hours = durante las horas de las { $hours }
+
+## Days
+schedule-every-day = todos los días
+schedule-weekdays-range = de { $first } a { $last }
+schedule-on-weekdays = cada { $weekdays }
+schedule-on-days = el día { $days } del mes
+schedule-on-days-or-weekdays = el día { $days } del mes o cada { $weekdays }
+schedule-in-months = en { $months }
+
+## Days of the week
+schedule-sunday = domingo
+schedule-monday = lunes
+schedule-tuesday = martes
+schedule-wednesday = miércoles
+schedule-thursday = jueves
+schedule-friday = viernes
+schedule-saturday = sábado
+
+## Months
+schedule-january = enero
+schedule-february = febrero
+schedule-march = marzo
+schedule-april = abril
+schedule-may = mayo
+schedule-june = junio
+schedule-july = julio
+schedule-august = agosto
+schedule-september = septiembre
+schedule-october = octubre
+schedule-november = noviembre
+schedule-december = diciembre
@@ -0,0 +1,48 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+### Descriptions of when recurring announcements happen, put together into sentences like "At 9:00 AM, Monday through Friday"
+
+## Times of day
+schedule-every-second = toutes les secondes
+schedule-every-seconds = toutes les { $count } secondes
+schedule-every-minute = toutes les minutes
+schedule-every-minutes = toutes les { $count } minutes
+schedule-every-hour = toutes les heures
+schedule-every-hours = toutes les { $count } heures
+schedule-at-seconds = à { $seconds } secondes de chaque minute
+schedule-at-minutes = à { $minutes } minutes de chaque heure
+schedule-at = à { $times }
+schedule-between = entre { $start } et { $end }
+schedule-during-hours = pendant les heures de { $hours }
+
+## Days
+schedule-every-day = tous les jours
+schedule-weekdays-range = du { $first } au { $last }
+schedule-on-weekdays = le { $weekdays }
+schedule-on-days = le { $days } du mois
+schedule-on-days-or-weekdays = le { $days } du mois ou le { $weekdays }
+schedule-in-months = en { $months }
+
+## Days of the week
+schedule-sunday = dimanche
+schedule-monday = lundi
+schedule-tuesday = mardi
+schedule-wednesday = mercredi
+schedule-thursday = jeudi
+schedule-friday = vendredi
+schedule-saturday = samedi
+
+## Months
+schedule-january = janvier
+schedule-february = février
+schedule-march = mars
+schedule-april = avril
+schedule-may = mai
+schedule-june = juin
+schedule-july = juillet
+schedule-august = août
+schedule-september = septembre
+schedule-october = octobre
+schedule-november = novembre
+schedule-december = décembre
@@ -0,0 +1,14 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+### Text drawn on the images the bot generates
+
+## Rank cards
+rankcard-level = Niveau { $level }
+rankcard-rank = Rang #{ $rank }
+rankcard-xp = { $xp } / { $needed } XP
+
+## Level up banners
+banner-levelup-title = LEVEL OMHOOG!
+banner-levelup-message = { $username } is nu level { $level }
+banner-levelup-levels = Level { $old } → { $new }
@@ -0,0 +1,48 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+
+### Descriptions of when recurring announcements happen, put together into sentences like "At 9:00 AM, Monday through Friday"
+
+## Times of day
+schedule-every-second = elke seconde
+schedule-every-seconds = elke { $count } seconden
+schedule-every-minute = elke minuut
+schedule-every-minutes = elke { $count } minuten
+schedule-every-hour = elk uur
+schedule-every-hours = elke { $count } uur
+schedule-at-seconds = op seconde { $seconds } van elke minuut
+schedule-at-minutes = op minuut { $minutes } van elk uur
+schedule-at = om { $times }
+schedule-between = tussen { $start } en { $end }
+schedule-during-hours = tijdens de uren van { $hours }
+
+## Days
+schedule-every-day = elke dag
+schedule-weekdays-range = van { $first } tot en met { $last }
+schedule-on-weekdays = op { $weekdays }
+schedule-on-days = op dag { $days } van de maand
+schedule-on-days-or-weekdays = op dag { $days } van de maand of op { $weekdays }
+schedule-in-months = in { $months }
+
+## Days of the week
+schedule-sunday = zondag
+schedule-monday = maandag
+schedule-tuesday = dinsdag
+schedule-wednesday = woensdag
+schedule-thursday = donderdag
+schedule-friday = vrijdag
+schedule-saturday = zaterdag
+
+## Months
+schedule-january = januari
+schedule-february = februari
+schedule-march = maart
+schedule-april = april
+schedule-may = mei
+schedule-june = juni
+schedule-july = juli
+schedule-august = augustus
+schedule-september = september
+schedule-october = oktober
+schedule-november = november
+schedule-december = december
@@ -0,0 +1,9 @@
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+#
+# SPDX-License-Identifier: MIT
+
+### Terms shared by every locale, such as names that stay the same in every language.
+### A locale can still define one of these itself, and translations can override them, e.g. per guild.
+
+-bot-name = CharB0T
+-owner-name = Charlie
@@ -0,0 +1,567 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Locale aware `NUMBER()` and `DATETIME()` for the bundles, date and list arguments, and the plural rules fluent uses.
+//!
+//! The formatting data is taken from CLDR, for just the built in locales in [`AvailableLocales`]. Locales
+//! added at runtime use the data of the built in locale with their language, see [`Formats::of`], and
+//! locales in other languages have no `NUMBER()` or `DATETIME()`, so their messages fall back.
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+
+use fluent::{FluentArgs, FluentValue};
+use fluent_bundle::types::FluentType;
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use unic_langid::LanguageIdentifier;
+
+use crate::fluent::bundle::{AvailableLocales, Bundle};
+
+/// Names with a built in meaning, python can't register functions with these.
+pub(crate) const BUILTIN_FUNCTIONS: [&str; 2] = ["NUMBER", "DATETIME"];
+
+/// The number, date and list formats of a locale, which there is CLDR data for here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Formats {
+    AmericanEnglish,
+    EuropeanSpanish,
+    French,
+    Dutch,
+}
+
+impl Formats {
+    /// The formats `locale` uses.
+    ///
+    /// The pseudo locale is en-US underneath, and locales added at runtime use the built in locale with
+    /// their language, like es-MX uses the es-ES formats. There's no data for other languages, so they're
+    /// an error instead of formatting like en-US.
+    pub(crate) fn of(locale: AvailableLocales) -> Result<Self, String> {
+        match locale {
+            AvailableLocales::AmericanEnglish | AvailableLocales::Pseudo => Ok(Formats::AmericanEnglish),
+            AvailableLocales::EuropeanSpanish => Ok(Formats::EuropeanSpanish),
+            AvailableLocales::French => Ok(Formats::French),
+            AvailableLocales::Dutch => Ok(Formats::Dutch),
+            AvailableLocales::Custom(code) => {
+                let langid = code.parse::<LanguageIdentifier>().map_err(|e| format!("Invalid locale {code}: {e}"))?;
+                match langid.language.as_str() {
+                    "en" => Ok(Formats::AmericanEnglish),
+                    "es" => Ok(Formats::EuropeanSpanish),
+                    "fr" => Ok(Formats::French),
+                    "nl" => Ok(Formats::Dutch),
+                    _ => Err(format!("There are no number, date or list formats for {code}, only for en, es, fr and nl")),
+                }
+            }
+        }
+    }
+
+    /// The built in locale these are the formats of, for its plural rules.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Formats::AmericanEnglish => AvailableLocales::AmericanEnglish.as_str(),
+            Formats::EuropeanSpanish => AvailableLocales::EuropeanSpanish.as_str(),
+            Formats::French => AvailableLocales::French.as_str(),
+            Formats::Dutch => AvailableLocales::Dutch.as_str(),
+        }
+    }
+}
+
+/// Add `NUMBER` and `DATETIME` for `locale` to `bundle`.
+///
+/// Locales without [`Formats`] get neither, so messages using them are errors and fall back.
+pub(crate) fn install(bundle: &mut Bundle, locale: AvailableLocales) -> Result<(), String> {
+    let formats = match Formats::of(locale) {
+        Ok(formats) => formats,
+        Err(e) => {
+            tracing::warn!(target: "charbot_rust.fluent", "{e}, so its messages can't use NUMBER or DATETIME");
+            return Ok(());
+        }
+    };
+    bundle
+        .add_function("NUMBER", move |positional, named| number(formats, positional, named))
+        .map_err(|e| format!("Failed to add function NUMBER: {e}"))?;
+    bundle
+        .add_function("DATETIME", move |positional, named| datetime(formats, positional, named))
+        .map_err(|e| format!("Failed to add function DATETIME: {e}"))
+}
+
+fn number<'a>(formats: Formats, positional: &[FluentValue<'a>], named: &FluentArgs) -> FluentValue<'a> {
+    match positional.first().and_then(as_f64) {
+        Some(value) => FluentValue::from(format_number(formats, value, &NumberOptions::from_args(named))),
+        None => FluentValue::Error,
+    }
+}
+
+fn datetime<'a>(formats: Formats, positional: &[FluentValue<'a>], named: &FluentArgs) -> FluentValue<'a> {
+    match positional.first().and_then(as_f64) {
+        Some(timestamp) => FluentValue::from(format_datetime(formats, timestamp as i64, &DateTimeOptions::from_args(named))),
+        None => FluentValue::Error,
+    }
+}
+
+fn as_f64(value: &FluentValue) -> Option<f64> {
+    match value {
+        FluentValue::Number(number) => Some(number.value),
+        FluentValue::String(string) => string.parse().ok(),
+        FluentValue::Custom(custom) => custom
+            .as_any()
+            .downcast_ref::<FluentDateTime>()
+            .map(|datetime| datetime.timestamp as f64),
+        _ => None,
+    }
+}
+
+fn as_str<'a>(value: &'a FluentValue) -> Option<&'a str> {
+    match value {
+        FluentValue::String(string) => Some(string.as_ref()),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct NumberOptions {
+    pub(crate) minimum_fraction_digits: usize,
+    pub(crate) maximum_fraction_digits: usize,
+    pub(crate) use_grouping: bool,
+    pub(crate) percent: bool,
+}
+
+impl Default for NumberOptions {
+    fn default() -> Self {
+        Self {
+            minimum_fraction_digits: 0,
+            maximum_fraction_digits: 3,
+            use_grouping: true,
+            percent: false,
+        }
+    }
+}
+
+impl NumberOptions {
+    /// Read the options named like the `Intl.NumberFormat` ones, unknown options are ignored.
+    fn from_args(args: &FluentArgs) -> Self {
+        let mut options = Self::default();
+        let mut maximum_set = false;
+        for (key, value) in args.iter() {
+            match key {
+                "minimumFractionDigits" => if let Some(digits) = as_f64(value) {
+                    options.minimum_fraction_digits = digits.clamp(0.0, 20.0) as usize;
+                },
+                "maximumFractionDigits" => if let Some(digits) = as_f64(value) {
+                    options.maximum_fraction_digits = digits.clamp(0.0, 20.0) as usize;
+                    maximum_set = true;
+                },
+                "useGrouping" => options.use_grouping = as_str(value) != Some("false"),
+                "style" => options.percent = as_str(value) == Some("percent"),
+                _ => {}
+            }
+        }
+        if !maximum_set {
+            options.maximum_fraction_digits = options.maximum_fraction_digits.max(options.minimum_fraction_digits);
+        }
+        options.minimum_fraction_digits = options.minimum_fraction_digits.min(options.maximum_fraction_digits);
+        options
+    }
+}
+
+struct NumberSymbols {
+    decimal: char,
+    group: char,
+    /// How many digits the integer part needs before it is grouped at all.
+    minimum_grouping_digits: usize,
+    percent: &'static str,
+}
+
+fn number_symbols(formats: Formats) -> NumberSymbols {
+    match formats {
+        Formats::AmericanEnglish => NumberSymbols {
+            decimal: '.', group: ',', minimum_grouping_digits: 4, percent: "%",
+        },
+        Formats::EuropeanSpanish => NumberSymbols {
+            decimal: ',', group: '.', minimum_grouping_digits: 5, percent: "\u{a0}%",
+        },
+        Formats::French => NumberSymbols {
+            decimal: ',', group: '\u{202f}', minimum_grouping_digits: 4, percent: "\u{202f}%",
+        },
+        Formats::Dutch => NumberSymbols {
+            decimal: ',', group: '.', minimum_grouping_digits: 4, percent: "%",
+        },
+    }
+}
+
+/// What goes between groups of thousands, like the `,` of `1,000`.
+#[cfg(feature = "games")]
+pub(crate) fn group_separator(formats: Formats) -> char {
+    number_symbols(formats).group
+}
+
+pub(crate) fn format_number(formats: Formats, value: f64, options: &NumberOptions) -> String {
+    let symbols = number_symbols(formats);
+    let value = if options.percent { value * 100.0 } else { value };
+    let rounded = format!("{:.*}", options.maximum_fraction_digits, value.abs());
+    let (integer, fraction) = rounded.split_once('.').unwrap_or((&rounded, ""));
+    let mut fraction = fraction.trim_end_matches('0').to_string();
+    while fraction.len() < options.minimum_fraction_digits {
+        fraction.push('0');
+    }
+    let mut formatted = String::new();
+    // "-0" after rounding should just be "0"
+    if value < 0.0 && (integer.bytes().any(|b| b != b'0') || !fraction.is_empty()) {
+        formatted.push('-');
+    }
+    if options.use_grouping && integer.len() >= symbols.minimum_grouping_digits {
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                formatted.push(symbols.group);
+            }
+            formatted.push(digit);
+        }
+    } else {
+        formatted.push_str(integer);
+    }
+    if !fraction.is_empty() {
+        formatted.push(symbols.decimal);
+        formatted.push_str(&fraction);
+    }
+    if options.percent {
+        formatted.push_str(symbols.percent);
+    }
+    formatted
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DateStyle {
+    Short,
+    Medium,
+    Long,
+}
+
+impl DateStyle {
+    fn from_str(style: &str) -> Option<Self> {
+        match style {
+            "short" => Some(DateStyle::Short),
+            "medium" => Some(DateStyle::Medium),
+            // there are no weekdays here, so full is the same as long
+            "long" | "full" => Some(DateStyle::Long),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct DateTimeOptions {
+    pub(crate) date_style: Option<DateStyle>,
+    /// `Short` leaves out the seconds, anything else includes them.
+    pub(crate) time_style: Option<DateStyle>,
+}
+
+impl Default for DateTimeOptions {
+    fn default() -> Self {
+        Self { date_style: Some(DateStyle::Short), time_style: None }
+    }
+}
+
+impl DateTimeOptions {
+    /// Read `dateStyle` and `timeStyle` like `Intl.DateTimeFormat`, with just the date when neither is given.
+    fn from_args(args: &FluentArgs) -> Self {
+        let date_style = args.get("dateStyle").and_then(as_str).and_then(DateStyle::from_str);
+        let time_style = args.get("timeStyle").and_then(as_str).and_then(DateStyle::from_str);
+        if date_style.is_none() && time_style.is_none() {
+            Self::default()
+        } else {
+            Self { date_style, time_style }
+        }
+    }
+}
+
+const EN_US_MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+const EN_US_SHORT_MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const ES_ES_MONTHS: [&str; 12] = [
+    "enero", "febrero", "marzo", "abril", "mayo", "junio",
+    "julio", "agosto", "septiembre", "octubre", "noviembre", "diciembre",
+];
+const ES_ES_SHORT_MONTHS: [&str; 12] = [
+    "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+];
+const FR_MONTHS: [&str; 12] = [
+    "janvier", "février", "mars", "avril", "mai", "juin",
+    "juillet", "août", "septembre", "octobre", "novembre", "décembre",
+];
+const FR_SHORT_MONTHS: [&str; 12] = [
+    "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc.",
+];
+const NL_MONTHS: [&str; 12] = [
+    "januari", "februari", "maart", "april", "mei", "juni",
+    "juli", "augustus", "september", "oktober", "november", "december",
+];
+const NL_SHORT_MONTHS: [&str; 12] = [
+    "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+];
+
+/// Split days since the unix epoch into `(year, month, day)`, see <http://howardhinnant.github.io/date_algorithms.html>.
+fn civil_from_days(days: i64) -> (i64, usize, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month as usize, day)
+}
+
+fn format_date(formats: Formats, style: DateStyle, year: i64, month: usize, day: i64) -> String {
+    let index = month - 1;
+    match (formats, style) {
+        (Formats::AmericanEnglish, DateStyle::Short) => format!("{month}/{day}/{:02}", year.rem_euclid(100)),
+        (Formats::AmericanEnglish, DateStyle::Medium) => format!("{} {day}, {year}", EN_US_SHORT_MONTHS[index]),
+        (Formats::AmericanEnglish, DateStyle::Long) => format!("{} {day}, {year}", EN_US_MONTHS[index]),
+        (Formats::EuropeanSpanish, DateStyle::Short) => format!("{day}/{month}/{:02}", year.rem_euclid(100)),
+        (Formats::EuropeanSpanish, DateStyle::Medium) => format!("{day} {} {year}", ES_ES_SHORT_MONTHS[index]),
+        (Formats::EuropeanSpanish, DateStyle::Long) => format!("{day} de {} de {year}", ES_ES_MONTHS[index]),
+        (Formats::French, DateStyle::Short) => format!("{day:02}/{month:02}/{year}"),
+        (Formats::French, DateStyle::Medium) => format!("{day} {} {year}", FR_SHORT_MONTHS[index]),
+        (Formats::French, DateStyle::Long) => format!("{day} {} {year}", FR_MONTHS[index]),
+        (Formats::Dutch, DateStyle::Short) => format!("{day:02}-{month:02}-{year}"),
+        (Formats::Dutch, DateStyle::Medium) => format!("{day} {} {year}", NL_SHORT_MONTHS[index]),
+        (Formats::Dutch, DateStyle::Long) => format!("{day} {} {year}", NL_MONTHS[index]),
+    }
+}
+
+pub(crate) fn format_time(formats: Formats, style: DateStyle, seconds_of_day: i64) -> String {
+    let (hour, minute, second) = (seconds_of_day / 3600, seconds_of_day / 60 % 60, seconds_of_day % 60);
+    let seconds = if style == DateStyle::Short { String::new() } else { format!(":{second:02}") };
+    match formats {
+        Formats::AmericanEnglish => {
+            let period = if hour < 12 { "AM" } else { "PM" };
+            let hour = if hour % 12 == 0 { 12 } else { hour % 12 };
+            format!("{hour}:{minute:02}{seconds}\u{202f}{period}")
+        }
+        Formats::EuropeanSpanish => format!("{hour}:{minute:02}{seconds}"),
+        Formats::French | Formats::Dutch => format!("{hour:02}:{minute:02}{seconds}"),
+    }
+}
+
+/// Format `timestamp`, in seconds since the unix epoch, as UTC.
+pub(crate) fn format_datetime(formats: Formats, timestamp: i64, options: &DateTimeOptions) -> String {
+    let (year, month, day) = civil_from_days(timestamp.div_euclid(86_400));
+    let date = options.date_style.map(|style| format_date(formats, style, year, month, day));
+    let time = options.time_style.map(|style| format_time(formats, style, timestamp.rem_euclid(86_400)));
+    match (date, time) {
+        (Some(date), Some(time)) => match formats {
+            Formats::AmericanEnglish | Formats::EuropeanSpanish => {
+                format!("{date}, {time}")
+            }
+            Formats::French | Formats::Dutch => format!("{date} {time}"),
+        },
+        (Some(date), None) => date,
+        (None, Some(time)) => time,
+        (None, None) => String::new(),
+    }
+}
+
+/// A date argument, which formats like `DATETIME()` with its default options when used on its own.
+///
+/// It is still a timestamp to `DATETIME()`, so `{ DATETIME($when, timeStyle: "short") }` works too.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FluentDateTime {
+    formats: Formats,
+    /// Seconds since the unix epoch.
+    timestamp: i64,
+}
+
+impl FluentDateTime {
+    pub(crate) fn new(formats: Formats, timestamp: i64) -> Self {
+        Self { formats, timestamp }
+    }
+}
+
+impl FluentType for FluentDateTime {
+    fn duplicate(&self) -> Box<dyn FluentType + Send> {
+        Box::new(self.clone())
+    }
+
+    fn as_string(&self, _intls: &intl_memoizer::IntlLangMemoizer) -> Cow<'static, str> {
+        format_datetime(self.formats, self.timestamp, &DateTimeOptions::default()).into()
+    }
+
+    fn as_string_threadsafe(&self, _intls: &intl_memoizer::concurrent::IntlLangMemoizer) -> Cow<'static, str> {
+        format_datetime(self.formats, self.timestamp, &DateTimeOptions::default()).into()
+    }
+}
+
+/// Join `items` into a list like "a, b, and c", the CLDR standard list pattern of `formats`.
+pub(crate) fn format_list(formats: Formats, items: &[String]) -> String {
+    let (separator, last_separator, pair_separator) = match formats {
+        Formats::AmericanEnglish => (", ", ", and ", " and "),
+        Formats::EuropeanSpanish => (", ", " y ", " y "),
+        Formats::French => (", ", " et ", " et "),
+        Formats::Dutch => (", ", " en ", " en "),
+    };
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{first}{pair_separator}{second}"),
+        [rest @ .., last] => format!("{}{last_separator}{last}", rest.join(separator)),
+    }
+}
+
+/// A number to pick the plural category of, the same ways translation arguments can be given.
+///
+/// Strings keep their formatting, so `"1.0"` can be a different category than `1`.
+pub(crate) enum PluralNumber<'a> {
+    Int(i64),
+    Float(f64),
+    String(&'a str),
+}
+
+/// Why a plural category couldn't be picked, so python can raise a locale error or a value error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PluralError {
+    /// The locale isn't valid, or CLDR has no plural rules for it.
+    Locale(String),
+    /// The number can't be read as plural operands, like `"abc"` or NaN.
+    Number(String),
+}
+
+impl Display for PluralError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PluralError::Locale(e) | PluralError::Number(e) => f.write_str(e),
+        }
+    }
+}
+
+/// The CLDR plural category of `number` in `locale`, which can be any locale CLDR has rules for.
+pub(crate) fn plural_category(locale: &str, number: PluralNumber, ordinal: bool) -> Result<&'static str, PluralError> {
+    let langid = locale
+        .parse::<LanguageIdentifier>()
+        .map_err(|e| PluralError::Locale(format!("Invalid locale {locale}: {e}")))?;
+    let rule_type = if ordinal { PluralRuleType::ORDINAL } else { PluralRuleType::CARDINAL };
+    let rules = PluralRules::create(langid, rule_type)
+        .map_err(|e| PluralError::Locale(format!("No plural rules for {locale}: {e}")))?;
+    let category = match number {
+        PluralNumber::Int(int) => rules.select(int),
+        PluralNumber::Float(float) => rules.select(float),
+        PluralNumber::String(string) => rules.select(string),
+    }.map_err(|e| PluralError::Number(format!("Invalid number: {e}")))?;
+    Ok(match category {
+        PluralCategory::ZERO => "zero",
+        PluralCategory::ONE => "one",
+        PluralCategory::TWO => "two",
+        PluralCategory::FEW => "few",
+        PluralCategory::MANY => "many",
+        PluralCategory::OTHER => "other",
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::{plural_category, PluralError, PluralNumber};
+    use super::{civil_from_days, format_datetime, format_list, format_number, DateStyle, DateTimeOptions, Formats, NumberOptions};
+    use crate::fluent::bundle::AvailableLocales;
+
+    #[test]
+    fn test_formats_of() {
+        assert_eq!(Formats::of(AvailableLocales::Pseudo), Ok(Formats::AmericanEnglish));
+        assert_eq!(Formats::of(AvailableLocales::French), Ok(Formats::French));
+        assert_eq!(Formats::of(AvailableLocales::Custom("es-MX")), Ok(Formats::EuropeanSpanish));
+        assert_eq!(Formats::of(AvailableLocales::Custom("en-GB")), Ok(Formats::AmericanEnglish));
+        assert!(Formats::of(AvailableLocales::Custom("de")).is_err());
+        assert!(Formats::of(AvailableLocales::Custom("ja")).is_err());
+    }
+
+    #[test]
+    fn test_format_number_grouping() {
+        let options = NumberOptions { maximum_fraction_digits: 0, ..NumberOptions::default() };
+        assert_eq!(format_number(Formats::AmericanEnglish, 1234567.4, &options), "1,234,567");
+        assert_eq!(format_number(Formats::EuropeanSpanish, 1234567.4, &options), "1.234.567");
+        assert_eq!(format_number(Formats::French, 1234567.4, &options), "1\u{202f}234\u{202f}567");
+        assert_eq!(format_number(Formats::Dutch, 1234567.4, &options), "1.234.567");
+    }
+
+    #[test]
+    fn test_format_number_minimum_grouping() {
+        let options = NumberOptions::default();
+        assert_eq!(format_number(Formats::EuropeanSpanish, 1234.0, &options), "1234");
+        assert_eq!(format_number(Formats::EuropeanSpanish, 12345.0, &options), "12.345");
+        assert_eq!(format_number(Formats::Dutch, 1234.0, &options), "1.234");
+    }
+
+    #[test]
+    fn test_format_number_fractions() {
+        let options = NumberOptions::default();
+        assert_eq!(format_number(Formats::AmericanEnglish, 1.5, &options), "1.5");
+        assert_eq!(format_number(Formats::Dutch, -1.23456, &options), "-1,235");
+        assert_eq!(format_number(Formats::French, -0.0001, &options), "0");
+        let options = NumberOptions { minimum_fraction_digits: 2, ..NumberOptions::default() };
+        assert_eq!(format_number(Formats::AmericanEnglish, 3.0, &options), "3.00");
+        let options = NumberOptions { use_grouping: false, ..NumberOptions::default() };
+        assert_eq!(format_number(Formats::AmericanEnglish, 12345.0, &options), "12345");
+    }
+
+    #[test]
+    fn test_format_number_percent() {
+        let options = NumberOptions { percent: true, ..NumberOptions::default() };
+        assert_eq!(format_number(Formats::AmericanEnglish, 0.25, &options), "25%");
+        assert_eq!(format_number(Formats::EuropeanSpanish, 0.25, &options), "25\u{a0}%");
+    }
+
+    #[test]
+    fn test_format_list() {
+        let items = ["a", "b", "c"].map(String::from);
+        assert_eq!(format_list(Formats::AmericanEnglish, &items), "a, b, and c");
+        assert_eq!(format_list(Formats::AmericanEnglish, &items[..2]), "a and b");
+        assert_eq!(format_list(Formats::EuropeanSpanish, &items), "a, b y c");
+        assert_eq!(format_list(Formats::French, &items), "a, b et c");
+        assert_eq!(format_list(Formats::Dutch, &items[..2]), "a en b");
+        assert_eq!(format_list(Formats::Dutch, &items[..1]), "a");
+        assert_eq!(format_list(Formats::Dutch, &[]), "");
+    }
+
+    #[test]
+    fn test_plural_category() {
+        assert_eq!(plural_category("en-US", PluralNumber::Int(1), false), Ok("one"));
+        assert_eq!(plural_category("en-US", PluralNumber::Int(2), false), Ok("other"));
+        assert_eq!(plural_category("en-US", PluralNumber::String("1.0"), false), Ok("other"));
+        assert_eq!(plural_category("en-US", PluralNumber::Int(2), true), Ok("two"));
+        assert_eq!(plural_category("fr", PluralNumber::Float(1.5), false), Ok("one"));
+        assert_eq!(plural_category("pl", PluralNumber::Int(3), false), Ok("few"));
+        assert!(matches!(plural_category("not a locale!", PluralNumber::Int(1), false), Err(PluralError::Locale(_))));
+        assert!(matches!(plural_category("tlh", PluralNumber::Int(1), false), Err(PluralError::Locale(_))));
+        assert!(matches!(plural_category("en-US", PluralNumber::String("abc"), false), Err(PluralError::Number(_))));
+        assert!(matches!(plural_category("en-US", PluralNumber::Float(f64::NAN), false), Err(PluralError::Number(_))));
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_417), (2023, 3, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_format_datetime() {
+        // 2023-03-01 14:05:09 UTC
+        let timestamp = 1_677_679_509;
+        let date = DateTimeOptions::default();
+        assert_eq!(format_datetime(Formats::AmericanEnglish, timestamp, &date), "3/1/23");
+        assert_eq!(format_datetime(Formats::French, timestamp, &date), "01/03/2023");
+        assert_eq!(format_datetime(Formats::Dutch, timestamp, &date), "01-03-2023");
+        let both = DateTimeOptions { date_style: Some(DateStyle::Long), time_style: Some(DateStyle::Short) };
+        assert_eq!(
+            format_datetime(Formats::AmericanEnglish, timestamp, &both),
+            "March 1, 2023, 2:05\u{202f}PM"
+        );
+        assert_eq!(format_datetime(Formats::EuropeanSpanish, timestamp, &both), "1 de marzo de 2023, 14:05");
+        let time = DateTimeOptions { date_style: None, time_style: Some(DateStyle::Medium) };
+        assert_eq!(format_datetime(Formats::Dutch, timestamp, &time), "14:05:09");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Formatting numbers outside of messages, such as points, percentages and compact counts like "1.2K".
+//!
+//! This uses the same CLDR data as `NUMBER()` in [`intl`], with the compact units added.
+use crate::fluent::intl::{self, Formats, NumberOptions};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NumberStyle {
+    /// Up to three fraction digits, grouped, like `NUMBER()` does by default.
+    Decimal,
+    /// Multiplied by 100, without fraction digits.
+    Percent,
+    /// Short for large numbers, like "1.2K" or "12 mil", with about two significant digits.
+    Compact,
+    /// Rounded to a whole number and grouped, like "1,234,567", for points and other counts.
+    Grouping,
+}
+
+impl NumberStyle {
+    pub(crate) fn from_str(style: &str) -> Option<Self> {
+        match style {
+            "decimal" => Some(NumberStyle::Decimal),
+            "percent" => Some(NumberStyle::Percent),
+            "compact" => Some(NumberStyle::Compact),
+            "grouping" => Some(NumberStyle::Grouping),
+            _ => None,
+        }
+    }
+}
+
+/// Format `value` with `formats` in `style`.
+pub(crate) fn format_number(formats: Formats, value: f64, style: NumberStyle) -> String {
+    let options = match style {
+        NumberStyle::Decimal => NumberOptions::default(),
+        NumberStyle::Percent => NumberOptions {
+            maximum_fraction_digits: 0,
+            percent: true,
+            ..NumberOptions::default()
+        },
+        NumberStyle::Compact => return format_compact(formats, value),
+        NumberStyle::Grouping => NumberOptions {
+            maximum_fraction_digits: 0,
+            ..NumberOptions::default()
+        },
+    };
+    intl::format_number(formats, value, &options)
+}
+
+/// The short units of each locale, by the power of ten they stand for, from CLDR.
+///
+/// Locales without a unit for a power use the one below, so Spanish writes a billion as "1000 M".
+fn compact_units(formats: Formats) -> &'static [(i32, &'static str)] {
+    match formats {
+        Formats::AmericanEnglish => &[(3, "K"), (6, "M"), (9, "B"), (12, "T")],
+        Formats::EuropeanSpanish => &[(3, "\u{a0}mil"), (6, "\u{a0}M"), (12, "\u{a0}B")],
+        Formats::French => &[(3, "\u{a0}k"), (6, "\u{a0}M"), (9, "\u{a0}Md"), (12, "\u{a0}Bn")],
+        Formats::Dutch => &[(3, "K"), (6, "\u{a0}mln."), (9, "\u{a0}mld."), (12, "\u{a0}bln.")],
+    }
+}
+
+fn format_compact(formats: Formats, value: f64) -> String {
+    let units = compact_units(formats);
+    // rounding can carry into the next unit, 999_950 is "1M" rather than "1000K"
+    let mut unit = units.iter().rposition(|(power, _)| value.abs() >= 10f64.powi(*power));
+    loop {
+        let (power, suffix) = unit.map_or((0, ""), |index| units[index]);
+        let scaled = value / 10f64.powi(power);
+        let options = NumberOptions {
+            // two significant digits, but never rounding away whole digits
+            maximum_fraction_digits: usize::from(scaled.abs() < 10.0),
+            use_grouping: false,
+            ..NumberOptions::default()
+        };
+        let next = unit.map_or(0, |index| index + 1);
+        let carries = next < units.len()
+            && round(scaled, options.maximum_fraction_digits).abs() * 10f64.powi(power) >= 10f64.powi(units[next].0);
+        if carries {
+            unit = Some(next);
+            continue;
+        }
+        return intl::format_number(formats, scaled, &options) + suffix;
+    }
+}
+
+fn round(value: f64, fraction_digits: usize) -> f64 {
+    let factor = 10f64.powi(fraction_digits as i32);
+    (value * factor).round() / factor
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::{format_number, NumberStyle};
+    use crate::fluent::intl::Formats;
+
+    #[test]
+    fn test_number_style_from_str() {
+        assert_eq!(NumberStyle::from_str("compact"), Some(NumberStyle::Compact));
+        assert_eq!(NumberStyle::from_str("grouping"), Some(NumberStyle::Grouping));
+        assert_eq!(NumberStyle::from_str("currency"), None);
+    }
+
+    #[test]
+    fn test_format_number_styles() {
+        let english = Formats::AmericanEnglish;
+        assert_eq!(format_number(english, 1234.5678, NumberStyle::Decimal), "1,234.568");
+        assert_eq!(format_number(english, 0.256, NumberStyle::Percent), "26%");
+        assert_eq!(format_number(english, 1234567.8, NumberStyle::Grouping), "1,234,568");
+        assert_eq!(format_number(Formats::French, 0.5, NumberStyle::Percent), "50\u{202f}%");
+        assert_eq!(format_number(Formats::Dutch, 1234567.0, NumberStyle::Grouping), "1.234.567");
+    }
+
+    #[test]
+    fn test_format_compact() {
+        let english = Formats::AmericanEnglish;
+        let compact = |locale, value| format_number(locale, value, NumberStyle::Compact);
+        assert_eq!(compact(english, 999.0), "999");
+        assert_eq!(compact(english, 1.26), "1.3");
+        assert_eq!(compact(english, 1234.0), "1.2K");
+        assert_eq!(compact(english, 12_345.0), "12K");
+        assert_eq!(compact(english, 123_456.0), "123K");
+        assert_eq!(compact(english, 999_950.0), "1M");
+        assert_eq!(compact(english, -1_500_000.0), "-1.5M");
+        assert_eq!(compact(english, 2.5e12), "2.5T");
+        assert_eq!(compact(english, 2.5e15), "2500T");
+        assert_eq!(compact(Formats::EuropeanSpanish, 1234.0), "1,2\u{a0}mil");
+        assert_eq!(compact(Formats::EuropeanSpanish, 1.2e9), "1200\u{a0}M");
+        assert_eq!(compact(Formats::French, 3.4e9), "3,4\u{a0}Md");
+        assert_eq!(compact(Formats::Dutch, 1234.0), "1,2K");
+        assert_eq!(compact(Formats::Dutch, 5e6), "5\u{a0}mln.");
+    }
+}
+// COV_EXCL_STOP
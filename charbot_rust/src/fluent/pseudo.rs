@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! The `pseudo` locale, en-US made to look translated so untranslated strings and truncation stand out.
+//!
+//! Letters are accented, vowels are doubled to make the text about a third longer like most translations
+//! are, and each translation is bracketed so cut off embeds are easy to spot. Arguments are left alone.
+use std::borrow::Cow;
+
+const LOWERCASE: [char; 26] = [
+    'á', 'ƀ', 'ç', 'ð', 'é', 'ƒ', 'ĝ', 'ĥ', 'î', 'ĵ', 'ķ', 'ļ', 'ɱ', 'ñ', 'ö', 'þ', 'ǫ', 'ŕ', 'š', 'ţ', 'û', 'ṽ', 'ŵ', 'ẋ',
+    'ý', 'ž',
+];
+const UPPERCASE: [char; 26] = [
+    'Å', 'Ɓ', 'Ç', 'Đ', 'É', 'Ƒ', 'Ĝ', 'Ĥ', 'Î', 'Ĵ', 'Ķ', 'Ļ', 'Ṁ', 'Ñ', 'Ö', 'Þ', 'Ǫ', 'Ŕ', 'Š', 'Ţ', 'Û', 'Ṽ', 'Ŵ', 'Ẋ',
+    'Ý', 'Ž',
+];
+
+/// Accent and pad the text of a message, the bundle calls this for every bit of text between placeables.
+pub(crate) fn transform(text: &str) -> Cow<'_, str> {
+    if !text.contains(|c: char| c.is_ascii_alphabetic()) {
+        return Cow::Borrowed(text);
+    }
+    let mut transformed = String::with_capacity(text.len() * 2);
+    for c in text.chars() {
+        let accented = match c {
+            'a'..='z' => LOWERCASE[usize::from(c as u8 - b'a')],
+            'A'..='Z' => UPPERCASE[usize::from(c as u8 - b'A')],
+            _ => c,
+        };
+        transformed.push(accented);
+        if "aeiouAEIOU".contains(c) {
+            transformed.push(accented);
+        }
+    }
+    Cow::Owned(transformed)
+}
+
+/// Bracket a whole translation, dropping the bidi isolation marks, which are no use in a fake locale.
+pub(crate) fn bracket(translation: &str) -> String {
+    format!("[{}]", translation.replace(['\u{2068}', '\u{2069}'], ""))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::{bracket, transform};
+
+    #[test]
+    fn test_transform() {
+        assert_eq!(transform("Hello, World!"), "Ĥééļļöö, Ŵööŕļð!");
+        assert_eq!(transform("Quiet"), "Ǫûûîîééţ");
+        assert_eq!(transform(" `1` + 2 "), " `1` + 2 ");
+        assert!(transform("Roll the dice").chars().count() > "Roll the dice".len());
+    }
+
+    #[test]
+    fn test_bracket() {
+        assert_eq!(bracket("Ĥîî \u{2068}John\u{2069}"), "[Ĥîî John]");
+        assert_eq!(bracket(""), "[]");
+    }
+}
+// COV_EXCL_STOP
@@ -1,97 +1,756 @@
 // SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
 //
 // SPDX-License-Identifier: MIT
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::{self, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
 
-use crate::fluent::bundle;
-use fluent::{FluentBundle, FluentResource, FluentArgs, FluentValue};
-use pyo3::{FromPyObject};
-use encoding::all::ASCII;
-use encoding::{DecoderTrap, EncoderTrap, Encoding};
+use crate::fluent::{bundle, cache, functions, intl, pseudo};
+use crate::metrics;
+use fluent::{FluentArgs, FluentError, FluentValue};
+use fluent_bundle::resolver::{ReferenceKind, ResolverError};
+use fluent_syntax::ast;
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyTypeError;
+use pyo3::types::{PyBool, PyDateTime};
+use pyo3::{FromPyObject, pyclass, pymethods, PyAny, PyObject, PyResult, Python};
 
-// COV_EXCL_START
-#[derive(FromPyObject, Clone)]
+#[derive(Clone)]
 pub enum ArgTypes {
-    #[pyo3(transparent)]
+    /// Formatted as `true` or `false`, so messages can select on it.
+    Bool(bool),
     Int(i64),
-    #[pyo3(transparent)]
     Float(f64),
-    #[pyo3(transparent)]
     String(String),
+    /// A `datetime.datetime`, as seconds since the unix epoch. Naive ones are local time, like `datetime.timestamp()`.
+    DateTime(i64),
+    /// Joined into a list like "a, b, and c" for the locale.
+    List(Vec<String>),
+    /// An `enum.Enum` member, as its value if that is a str and its name otherwise, for select expressions.
+    ///
+    /// Unlike a plain string, selecting on one fails when the select has no variant for it.
+    Enum(String),
+    /// Formatted as nothing, and selects the default variant.
+    None,
+}
+
+impl Hash for ArgTypes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            ArgTypes::Bool(boolean) => boolean.hash(state),
+            ArgTypes::Int(int) => int.hash(state),
+            ArgTypes::Float(float) => float.to_bits().hash(state),
+            ArgTypes::String(string) => string.hash(state),
+            ArgTypes::DateTime(timestamp) => timestamp.hash(state),
+            ArgTypes::List(items) => items.hash(state),
+            ArgTypes::Enum(name) => name.hash(state),
+            ArgTypes::None => {}
+        }
+    }
+}
+
+// COV_EXCL_START
+impl<'source> FromPyObject<'source> for ArgTypes {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        // bool is a subclass of int and a str is a sequence, so the order of these matters,
+        // an IntEnum stays an int so it can still be used for plurals, but a StrEnum is an enum
+        if ob.is_none() {
+            Ok(ArgTypes::None)
+        } else if let Ok(boolean) = ob.downcast::<PyBool>() {
+            Ok(ArgTypes::Bool(boolean.is_true()))
+        } else if let Ok(int) = ob.extract::<i64>() {
+            Ok(ArgTypes::Int(int))
+        } else if let Ok(float) = ob.extract::<f64>() {
+            Ok(ArgTypes::Float(float))
+        } else if ob.is_instance(ob.py().import("enum")?.getattr("Enum")?)? {
+            let value = ob.getattr("value")?;
+            match value.extract::<String>() {
+                Ok(value) => Ok(ArgTypes::Enum(value)),
+                Err(_) => Ok(ArgTypes::Enum(ob.getattr("name")?.extract()?)),
+            }
+        } else if let Ok(string) = ob.extract::<String>() {
+            Ok(ArgTypes::String(string))
+        } else if ob.is_instance_of::<PyDateTime>()? {
+            let timestamp = ob.call_method0("timestamp")?.extract::<f64>()?;
+            Ok(ArgTypes::DateTime(timestamp.floor() as i64))
+        } else if let Ok(list) = ob.extract::<Vec<String>>() {
+            Ok(ArgTypes::List(list))
+        } else {
+            Err(PyTypeError::new_err(format!(
+                "{} can't be used as a translation argument, use a bool, int, float, str, datetime, list[str], enum or None",
+                ob.get_type().name()?
+            )))
+        }
+    }
 }
 // COV_EXCL_STOP
 
+/// Why a translation failed, so the python side can raise a matching exception.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TranslationError {
+    /// The key doesn't exist, or has no value.
+    Key(String),
+    /// The bundles for the locale couldn't be loaded.
+    Locale(String),
+    /// The message exists, but couldn't be formatted with the given arguments.
+    Formatting(String),
+}
+
+impl Display for TranslationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TranslationError::Key(e) | TranslationError::Locale(e) | TranslationError::Formatting(e) => f.write_str(e),
+        }
+    }
+}
+
+type Bundles = HashMap<bundle::AvailableLocales, Arc<bundle::LocaleBundle>>;
+
+/// Parsed bundles for every locale, built on first use and shared by every translator afterwards.
+///
+/// Reloading swaps the whole map out, translators created before the reload keep the bundles they started with.
+static BUNDLES: Lazy<RwLock<Result<Bundles, String>>> = Lazy::new(|| RwLock::new(load_bundles(None)));
+
+/// The directory the cached bundles were loaded from, `None` for the built in translations.
+static BUNDLE_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Bumped every time the cached bundles are replaced, so translations cached from older bundles are never reused.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+fn load_bundles(path: Option<&Path>) -> Result<Bundles, String> {
+    let mut bundles = match path {
+        Some(path) => bundle::load_from_dir(path)?,
+        None => bundle::AvailableLocales::all()
+            .into_iter()
+            .map(|locale| Ok((locale, bundle::get_bundle(locale)?)))
+            .collect::<Result<HashMap<_, _>, String>>()?,
+    };
+    // the pseudo locale is made from whichever en-US was loaded
+    if let Some(english) = bundles.get(&bundle::AvailableLocales::AmericanEnglish) {
+        let pseudo = bundle::pseudo_bundle(english);
+        bundles.insert(bundle::AvailableLocales::Pseudo, pseudo);
+    }
+    bundles
+        .into_iter()
+        .map(|(locale, mut bundle)| {
+            functions::install(&mut bundle)?;
+            Ok((locale, Arc::new(bundle)))
+        })
+        .collect()
+}
+
+pub(crate) fn cached_bundle(locale: bundle::AvailableLocales) -> Result<Arc<bundle::LocaleBundle>, String> {
+    cached_bundles(&[locale]).map(|(mut bundles, _)| bundles.remove(0))
+}
+
+/// The cached bundles for each of `chain`, along with the generation they're from.
+fn cached_bundles(chain: &[bundle::AvailableLocales]) -> Result<(Vec<Arc<bundle::LocaleBundle>>, u64), String> {
+    let bundles = BUNDLES.read().unwrap_or_else(PoisonError::into_inner);
+    // read while the lock is held, replacing the bundles needs the write lock
+    let generation = GENERATION.load(Ordering::Relaxed);
+    let bundles = bundles.as_ref().map_err(|e| e.clone())?;
+    let chain = chain
+        .iter()
+        .map(|locale| bundles.get(locale).cloned().ok_or_else(|| format!("No bundle loaded for {locale:?}")))  // COV_EXCL_LINE
+        .collect::<Result<_, _>>()?;
+    Ok((chain, generation))
+}
+
+/// Swap in new cached bundles, and drop the translations cached from the old ones.
+fn replace_bundles(bundles: Bundles) {
+    let mut cached = BUNDLES.write().unwrap_or_else(PoisonError::into_inner);
+    *cached = Ok(bundles);
+    GENERATION.fetch_add(1, Ordering::Relaxed);
+    cache::clear();
+}
+
+/// Build the cached bundles now, while the module is imported.
+///
+/// Building them installs the registered functions, which takes the GIL. A thread holding the GIL that
+/// waits for another thread to finish building them first would never let that thread have it.
+pub(crate) fn load_cached_bundles() {
+    Lazy::force(&BUNDLES);
+}
+
+/// Drop every cached translation, they're rebuilt as they're used again.
+pub(crate) fn clear_cache() {
+    cache::clear();
+}
+
+/// The ids of every message available in `locale`.
+pub(crate) fn message_ids(locale: bundle::AvailableLocales) -> Result<BTreeSet<String>, TranslationError> {
+    cached_bundle(locale)
+        .map(|bundle| bundle.message_ids().clone())
+        .map_err(TranslationError::Locale)
+}
+
+/// The comment and variables of every message available in `locale`, by id.
+pub(crate) fn message_metadata(
+    locale: bundle::AvailableLocales,
+) -> Result<BTreeMap<String, bundle::MessageMetadata>, TranslationError> {
+    cached_bundle(locale)
+        .map(|bundle| bundle.message_metadata())
+        .map_err(TranslationError::Locale)
+}
+
+/// Look up `locale`, falling back to en-US for unknown locales unless `strict` is set.
+pub(crate) fn resolve_locale(locale: &str, strict: bool) -> Result<bundle::AvailableLocales, TranslationError> {
+    match bundle::AvailableLocales::from_str(locale) {
+        Some(locale) => Ok(locale),
+        None if strict => Err(TranslationError::Locale(format!("Locale {locale} is not available"))),
+        None => Ok(bundle::AvailableLocales::AmericanEnglish),
+    }
+}
+
+/// The fallback chain for `locale`, with just en-US for unknown locales unless `strict` is set.
+pub(crate) fn resolve_chain(locale: &str, strict: bool) -> Result<Vec<bundle::AvailableLocales>, TranslationError> {
+    match bundle::fallback_chain(locale) {
+        Some(chain) => Ok(chain),
+        None if strict => Err(TranslationError::Locale(format!("Locale {locale} is not available"))),
+        None => Ok(vec![bundle::AvailableLocales::AmericanEnglish]),
+    }
+}
+
+/// Translate `key` into every available locale, by locale code.
+///
+/// Each locale is translated on its own, without falling back, and locales that can't translate the key are
+/// left out so discord can fall back itself. en-US has every message, so its errors are returned instead.
+pub(crate) fn translate_all(key: &str, args: &HashMap<String, ArgTypes>) -> Result<BTreeMap<&'static str, String>, TranslationError> {
+    let mut translations = BTreeMap::new();
+    for locale in bundle::AvailableLocales::all() {
+        match Translator::from_chain(&[locale]).and_then(|translator| translator.translate(key, args.clone())) {
+            Ok(value) => {
+                translations.insert(locale.as_str(), value);
+            }
+            Err(e) if locale == bundle::AvailableLocales::AmericanEnglish => return Err(e),
+            Err(_) => {}
+        }
+    }
+    Ok(translations)
+}
+
+/// Replace the cached bundles with the ones found in `path`, see [`bundle::load_from_dir`], reading the terms
+/// every locale has again too.
+pub(crate) fn reload_bundles(path: &Path) -> Result<(), String> {
+    let previous = bundle::reload_shared_terms()?;
+    let bundles = load_bundles(Some(path)).inspect_err(|_| bundle::restore_shared_terms(previous))?;
+    replace_bundles(bundles);
+    *BUNDLE_DIR.write().unwrap_or_else(PoisonError::into_inner) = Some(path.to_path_buf());
+    Ok(())
+}
+
+/// Register a python callable as the fluent function `name`, and rebuild the cached bundles to include it.
+///
+/// Like reloading, translators created before this won't have the function.
+pub(crate) fn register_function(name: &str, callable: PyObject) -> Result<(), String> {
+    let previous = functions::register(name, callable)?;
+    rebuild_bundles().inspect_err(|_| functions::restore(name, previous))
+}
+
+/// Add the locale `code` with the messages in `source`, and rebuild the cached bundles to include it.
+///
+/// Adding a locale that was added before replaces its messages, translators created before this keep the old ones.
+pub(crate) fn add_locale(code: &str, source: String) -> Result<(), String> {
+    let previous = bundle::add_locale(code, source)?;
+    rebuild_bundles().inspect_err(|_| bundle::restore_locale(code, previous))
+}
+
+/// Set whether placeables are wrapped in bidi isolation marks, and rebuild the cached bundles to match.
+pub(crate) fn set_use_isolating(use_isolating: bool) -> Result<(), String> {
+    let previous = bundle::set_use_isolating(use_isolating);
+    rebuild_bundles().inspect_err(|_| {
+        bundle::set_use_isolating(previous);
+    })
+}
+
+/// Rebuild the cached bundles from wherever they were last loaded from.
+fn rebuild_bundles() -> Result<(), String> {
+    let path = BUNDLE_DIR.read().unwrap_or_else(PoisonError::into_inner).clone();
+    let bundles = load_bundles(path.as_deref())?;
+    replace_bundles(bundles);
+    Ok(())
+}
+
+/// A translator bound to a single locale, reusable for as many translations as needed.
+#[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
+#[derive(Clone)]
 pub(crate) struct Translator {
-    bundle: FluentBundle<FluentResource>,
-    fallback_bundle: FluentBundle<FluentResource>,
+    locale: bundle::AvailableLocales,
+    /// The bundles of the fallback chain, starting with `locale`'s.
+    bundles: Vec<Arc<bundle::LocaleBundle>>,
+    overrides: Option<Arc<bundle::LocaleBundle>>,
+    /// The generation of the cached bundles this translator uses, `None` once it has overrides or terms of
+    /// its own, which keeps its translations out of the cache.
+    generation: Option<u64>,
+    /// Error instead of falling back along the chain when a message is missing or fails to format.
+    strict: bool,
+    /// Warn about missing arguments, leaving a placeholder like `{$user}`, instead of failing.
+    lenient: bool,
 }
 
+// COV_EXCL_START
+#[pymethods]
+impl Translator {
+    #[new]
+    #[pyo3(signature = (locale, strict=false, lenient=false))]
+    fn __new__(locale: &str, strict: bool, lenient: bool) -> PyResult<Self> {
+        resolve_chain(locale, strict)
+            .and_then(|chain| Self::from_chain(&chain))
+            .map(|translator| translator.strict(strict).lenient(lenient))
+            .map_err(|e| e.into_py_err("Failed to create translator"))
+    }
+
+    /// Create a translator that checks `overrides`, an FTL source such as a guild's custom strings,
+    /// before the built in translations.
+    #[staticmethod]
+    #[pyo3(
+        name = "with_overrides",
+        signature = (locale, overrides, strict=false, lenient=false),
+        text_signature = "(locale, overrides, strict=False, lenient=False)"
+    )]
+    fn py_with_overrides(locale: &str, overrides: &str, strict: bool, lenient: bool) -> PyResult<Self> {
+        resolve_chain(locale, strict)
+            .and_then(|chain| Self::from_chain(&chain)?.overrides(overrides))
+            .map(|translator| translator.strict(strict).lenient(lenient))
+            .map_err(|e| e.into_py_err("Failed to create translator"))
+    }
+
+    /// Create a copy of this translator with `terms`, term names without the leading `-` mapped to their values,
+    /// replacing the shared terms, such as a guild's nickname for the bot.
+    #[pyo3(name = "with_terms", text_signature = "(self, terms, /)")]
+    fn py_with_terms(&self, py: Python, terms: HashMap<String, String>) -> PyResult<Self> {
+        py.allow_threads(|| self.clone().terms(&terms))
+            .map_err(|e| e.into_py_err("Failed to create translator"))
+    }
+
+    /// The locale this translator translates into.
+    #[getter]
+    fn locale(&self) -> &'static str {
+        self.locale.as_str()
+    }
+
+    /// The locales tried in order for each key, starting with `locale`.
+    #[getter]
+    fn fallback_chain(&self) -> Vec<&'static str> {
+        self.bundles.iter().map(|bundle| bundle.locale().as_str()).collect()
+    }
+
+    /// Whether this translator errors instead of falling back along its chain.
+    #[getter(strict)]
+    fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Whether this translator warns about missing arguments instead of failing.
+    #[getter(lenient)]
+    fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    /// Translate a string into this translator's locale.
+    #[pyo3(name = "translate", text_signature = "(self, key, args, /)")]
+    fn py_translate(&self, py: Python, key: &str, args: HashMap<String, ArgTypes>) -> PyResult<String> {
+        metrics::observe("fluent.Translator.translate", || {
+            let result = py.allow_threads(|| self.translate_checked(key, args));
+            crate::fluent::finish_translation(py, key, result, "Failed to translate")
+        })
+    }
+}
+// COV_EXCL_STOP
+
 impl Translator{
-    pub(crate) fn new(locale: bundle::AvailableLocales) -> Result<Self, String> {
-        let bundle = bundle::get_bundle(locale)?;
-        let fallback_bundle = bundle::get_bundle(bundle::AvailableLocales::AmericanEnglish)?;
+    #[cfg(test)]
+    pub(crate) fn new(locale: bundle::AvailableLocales) -> Result<Self, TranslationError> {
+        Self::from_chain(&bundle::fallback_chain(locale.as_str()).unwrap_or_else(|| vec![locale]))
+    }
+
+    /// Create a translator that tries each locale of `chain` in order, for each key.
+    pub(crate) fn from_chain(chain: &[bundle::AvailableLocales]) -> Result<Self, TranslationError> {
+        let locale = *chain.first().ok_or_else(|| TranslationError::Locale("The fallback chain is empty".to_string()))?;
+        let (bundles, generation) = cached_bundles(chain).map_err(TranslationError::Locale)?;
         Ok(Self {
-            bundle,
-            fallback_bundle,
+            locale,
+            bundles,
+            overrides: None,
+            generation: Some(generation),
+            strict: false,
+            lenient: false,
         })
     }
 
-    pub(crate) fn translate(&self, key: &str, args: HashMap<String, ArgTypes>) -> Result<String, String> {
-        let message = self.bundle.get_message(key).ok_or_else(|| {
-            format!("Message with key {key} not found")
-        })?;
-        let pattern = message.value().ok_or_else(|| {
-            format!("Message with key {key} has no value")  //COV_EXCL_LINE
-        })?;  //COV_EXCL_LINE
-        let mut fluent_args = FluentArgs::new();
-        args.iter().for_each(|(key, value)| {
-            match value {
-                ArgTypes::Int(int) => {
-                    fluent_args.set(key, FluentValue::from(int));
-                }
-                ArgTypes::Float(float) => {
-                    fluent_args.set(key, FluentValue::from(float));
+    /// Set whether missing or broken messages error instead of falling back along the chain.
+    pub(crate) fn strict(self, strict: bool) -> Self {
+        Self { strict, ..self }
+    }
+
+    /// Set whether missing arguments are left as placeholders, see [`Translator::translate_checked`].
+    pub(crate) fn lenient(self, lenient: bool) -> Self {
+        Self { lenient, ..self }
+    }
+
+    /// Check `overrides`, an FTL source, before any of the locales.
+    pub(crate) fn overrides(self, overrides: &str) -> Result<Self, TranslationError> {
+        let mut overrides = bundle::get_override_bundle(self.locale, overrides).map_err(TranslationError::Formatting)?;
+        functions::install(&mut overrides).map_err(TranslationError::Formatting)?;
+        Ok(Self {
+            overrides: Some(Arc::new(overrides)),
+            generation: None,
+            ..self
+        })
+    }
+
+    /// Replace the terms of every bundle with `terms`, term names without the leading `-` mapped to their values.
+    ///
+    /// The bundles are rebuilt from the resources they've already parsed, so this is cheap, and the cached bundles
+    /// other translators use are left alone.
+    pub(crate) fn terms(self, terms: &HashMap<String, String>) -> Result<Self, TranslationError> {
+        if terms.is_empty() {
+            return Ok(self);
+        }
+        let terms = bundle::terms_resource(terms).map_err(TranslationError::Formatting)?;
+        let with_terms = |bundle: &bundle::LocaleBundle| {
+            let mut bundle = bundle.with_terms(&terms);
+            functions::install(&mut bundle).map_err(TranslationError::Formatting)?;
+            Ok(Arc::new(bundle))
+        };
+        let bundles = self.bundles.iter().map(|bundle| with_terms(bundle)).collect::<Result<_, _>>()?;
+        let overrides = self.overrides.as_deref().map(with_terms).transpose()?;
+        Ok(Self {
+            bundles,
+            overrides,
+            generation: None,
+            ..self
+        })
+    }
+
+    pub(crate) fn translate(&self, key: &str, args: HashMap<String, ArgTypes>) -> Result<String, TranslationError> {
+        self.translate_inner(key, &args, false).map(|(value, _)| value)
+    }
+
+    /// Translate `key`, returning the translation along with the names of the arguments it was missing.
+    ///
+    /// Lenient translators leave a placeholder like `{$user}` for each missing argument instead of failing,
+    /// for the others the missing arguments are always empty.
+    pub(crate) fn translate_checked(
+        &self,
+        key: &str,
+        args: HashMap<String, ArgTypes>,
+    ) -> Result<(String, Vec<String>), TranslationError> {
+        self.translate_inner(key, &args, self.lenient)
+    }
+
+    /// Translate `key`, reusing the translation from the cache when the same one was done before.
+    ///
+    /// Translations that called a registered function aren't cached, since what the function gives can change
+    /// from call to call, and neither are ones made with errors, like a missing argument or a locale that
+    /// failed to format, so they're logged and retried each time.
+    fn translate_inner(
+        &self,
+        key: &str,
+        args: &HashMap<String, ArgTypes>,
+        lenient: bool,
+    ) -> Result<(String, Vec<String>), TranslationError> {
+        let Some(generation) = self.generation else {
+            return self.translate_uncached(key, args, lenient).map(|(value, _)| value);
+        };
+        let cache_key = self.cache_key(generation, key, args, lenient);
+        if let Some(value) = cache::get(cache_key) {
+            return Ok(value);
+        }
+        let calls = functions::calls();
+        let (value, clean) = self.translate_uncached(key, args, lenient)?;
+        if clean && value.1.is_empty() && functions::calls() == calls {
+            cache::insert(cache_key, value.clone());
+        }
+        Ok(value)
+    }
+
+    /// A hash of everything the translation of `key` depends on, the arguments in any order give the same hash.
+    fn cache_key(&self, generation: u64, key: &str, args: &HashMap<String, ArgTypes>, lenient: bool) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        generation.hash(&mut hasher);
+        for bundle in &self.bundles {
+            bundle.locale().hash(&mut hasher);
+        }
+        (self.strict, lenient, key).hash(&mut hasher);
+        let mut args = args.iter().collect::<Vec<_>>();
+        args.sort_unstable_by_key(|(name, _)| *name);
+        args.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Translate `key` without the cache, along with whether no locale failed to format it on the way.
+    fn translate_uncached(
+        &self,
+        key: &str,
+        args: &HashMap<String, ArgTypes>,
+        lenient: bool,
+    ) -> Result<((String, Vec<String>), bool), TranslationError> {
+        if let Some(value) = self.translate_override(key, args, lenient)? {
+            return Ok((value, true));
+        }
+        // strict translators only get the first locale, so nothing is hidden by a fallback
+        let tried = if self.strict { 1 } else { self.bundles.len() };
+        let mut first_error = None;
+        for bundle in self.bundles.iter().take(tried) {
+            // dates and lists are formatted for the locale of the bundle the message ends up coming from
+            let formatted = check_variants(bundle, key, args)
+                .and_then(|()| fluent_args(args, bundle.locale()))
+                .and_then(|fluent_args| format_message(bundle, key, &fluent_args, lenient));
+            match formatted {
+                Ok((value, missing)) => {
+                    if let Some(error) = &first_error {
+                        // a message missing from a locale is just untranslated, one failing to format is a bug
+                        let (used, wanted) = (bundle.locale().as_str(), self.locale.as_str());
+                        if matches!(error, TranslationError::Formatting(_)) {
+                            tracing::warn!(target: "charbot_rust.fluent", "Translated {key} in {used} instead of {wanted}: {error}");
+                        } else {
+                            tracing::debug!(target: "charbot_rust.fluent", "Translated {key} in {used} instead of {wanted}: {error}");
+                        }
+                    }
+                    let clean = !matches!(first_error, Some(TranslationError::Formatting(_)));
+                    return Ok(((finish(bundle.locale(), value), missing), clean));
                 }
-                ArgTypes::String(string) => {
-                    fluent_args.set(key, FluentValue::from(string.as_str()));
+                Err(e) => {
+                    first_error.get_or_insert(e);
                 }
             }
-        });
-        let mut errors = vec![];
-        let value = self.bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
-        if errors.is_empty() {
-            Ok(ASCII.decode(
-                &ASCII.encode(value.parse::<String>()
-                         .map_err(|e| format!("Failed to parse value: {e}"))?  //COV_EXCL_LINE
-                         .as_str(), EncoderTrap::Ignore)?,
-                DecoderTrap::Ignore
-                )?)  // COV_EXCL_LINE
+        }
+        let error = first_error.unwrap_or_else(|| TranslationError::Key(format!("Message with key {key} not found")));  // COV_EXCL_LINE
+        Err(self.with_suggestions(key, error))
+    }
+
+    /// Add the closest known keys to `error`, if it is about a message that doesn't exist in any of the locales.
+    fn with_suggestions(&self, key: &str, error: TranslationError) -> TranslationError {
+        let TranslationError::Key(message) = error else {
+            return error;
+        };
+        let id = key.split_once('.').map_or(key, |(id, _)| id);
+        if self.bundles.iter().any(|bundle| bundle.has_message(id)) {
+            return TranslationError::Key(message);
+        }
+        let suggestions = suggest(id, self.bundles.iter().flat_map(|bundle| bundle.message_ids()));
+        if suggestions.is_empty() {
+            TranslationError::Key(message)
         } else {
-            // COV_EXCL_START
-            let message = self.fallback_bundle.get_message(key).ok_or_else(|| {
-                format!("Message with key {key} not found")
-            })?;
-            let pattern = message.value().ok_or_else(|| {
-                format!("Message with key {key} has no value")
-            })?;
-            let mut errors = vec![];
-            let value = self.fallback_bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
-            if errors.is_empty() {
-                Ok(ASCII.decode(
-                    &ASCII.encode(value.parse::<String>()
-                                  .map_err(|e| format!("Failed to parse value: {e}"))?
-                                  .as_str(), EncoderTrap::Ignore)?,
-                    DecoderTrap::Ignore
-                )?)
-            } else {// COV_EXCL_STOP  // COV_EXCL_LINE
-                Err(format!("Translation failed: {}", errors
-                    .iter()
-                    .map(|e| e.to_string())
-                    .collect::<Vec<String>>()
-                    .join(", ")))
+            TranslationError::Key(format!("{message}, did you mean {}?", suggestions.join(", ")))
+        }
+    }
+
+    /// Translate `key` with the overrides, if there are any and they define it.
+    ///
+    /// An override that fails to format is skipped, so a broken custom string never hides the built in one.
+    fn translate_override(
+        &self,
+        key: &str,
+        args: &HashMap<String, ArgTypes>,
+        lenient: bool,
+    ) -> Result<Option<(String, Vec<String>)>, TranslationError> {
+        let Some(overrides) = &self.overrides else {
+            return Ok(None);
+        };
+        let formatted = check_variants(overrides, key, args)
+            .and_then(|()| fluent_args(args, self.locale))
+            .and_then(|fluent_args| format_message(overrides, key, &fluent_args, lenient));
+        match formatted {
+            Ok((value, missing)) => Ok(Some((finish(self.locale, value), missing))),
+            Err(TranslationError::Formatting(error)) => {
+                tracing::warn!(target: "charbot_rust.fluent", "The override of {key} failed to format, so it was skipped: {error}");
+                Ok(None)
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// The arguments for fluent, with dates and lists formatted for `locale`, which has to have formats for them.
+fn fluent_args<'a>(
+    args: &'a HashMap<String, ArgTypes>,
+    locale: bundle::AvailableLocales,
+) -> Result<FluentArgs<'a>, TranslationError> {
+    let formats = || intl::Formats::of(locale).map_err(TranslationError::Formatting);
+    let mut fluent_args = FluentArgs::new();
+    for (key, value) in args {
+        match value {
+            ArgTypes::Bool(boolean) => {
+                fluent_args.set(key, FluentValue::from(if *boolean { "true" } else { "false" }));
+            }
+            ArgTypes::Int(int) => {
+                fluent_args.set(key, FluentValue::from(int));
+            }
+            ArgTypes::Float(float) => {
+                fluent_args.set(key, FluentValue::from(float));
             }
+            ArgTypes::String(string) | ArgTypes::Enum(string) => {
+                fluent_args.set(key, FluentValue::from(string.as_str()));
+            }
+            ArgTypes::DateTime(timestamp) => {
+                fluent_args.set(key, FluentValue::Custom(Box::new(intl::FluentDateTime::new(formats()?, *timestamp))));
+            }
+            ArgTypes::List(items) => {
+                fluent_args.set(key, FluentValue::from(intl::format_list(formats()?, items)));
+            }
+            ArgTypes::None => {
+                fluent_args.set(key, FluentValue::None);
+            }
+        }
+    }
+    Ok(fluent_args)
+}
+
+/// Up to three of `candidates` that are close enough to `id` to be what was meant, closest first.
+fn suggest<'a>(id: &str, candidates: impl Iterator<Item = &'a String>) -> Vec<&'a str> {
+    let max_distance = (id.chars().count() / 4).max(1);
+    let mut close = candidates
+        .map(|candidate| (levenshtein(id, candidate), candidate.as_str()))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<_>>();
+    close.sort_unstable();
+    close.dedup();
+    close.into_iter().take(3).map(|(_, candidate)| candidate).collect()
+}
+
+/// The number of single character insertions, deletions or substitutions to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Find the pattern for `key`, which is either a message id, or `message.attribute` for one of its attributes.
+fn find_pattern<'b>(bundle: &'b bundle::Bundle, key: &str) -> Result<&'b ast::Pattern<&'b str>, TranslationError> {
+    let (id, attribute) = match key.split_once('.') {
+        Some((id, attribute)) => (id, Some(attribute)),
+        None => (key, None),
+    };
+    let message = bundle.get_message(id).ok_or_else(|| {
+        TranslationError::Key(format!("Message with key {id} not found"))
+    })?;
+    match attribute {
+        Some(attribute) => message.get_attribute(attribute).map(|attr| attr.value()).ok_or_else(|| {
+            TranslationError::Key(format!("Message with key {id} has no attribute {attribute}"))
+        }),
+        None => message.value().ok_or_else(|| {
+            TranslationError::Key(format!("Message with key {key} has no value"))  //COV_EXCL_LINE
+        }),  //COV_EXCL_LINE
+    }
+}
+
+/// Make sure every select on an enum argument in the message for `key` has a variant for its member.
+///
+/// Fluent quietly picks the default variant for a value it has no variant for, which for an enum is almost
+/// always a variant that was forgotten, so this is an error instead.
+fn check_variants(bundle: &bundle::Bundle, key: &str, args: &HashMap<String, ArgTypes>) -> Result<(), TranslationError> {
+    if !args.values().any(|arg| matches!(arg, ArgTypes::Enum(_))) {
+        return Ok(());
+    }
+    match uncovered_variant(find_pattern(bundle, key)?, args) {
+        Some((id, value)) => Err(TranslationError::Formatting(format!(
+            "Message with key {key} has no [{value}] variant for ${id}"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// The first enum argument selected on in `pattern` without a variant for its member, and the member.
+fn uncovered_variant<'a>(pattern: &ast::Pattern<&'a str>, args: &HashMap<String, ArgTypes>) -> Option<(&'a str, String)> {
+    pattern.elements.iter().find_map(|element| match element {
+        ast::PatternElement::Placeable { expression } => uncovered_in_expression(expression, args),
+        ast::PatternElement::TextElement { .. } => None,
+    })
+}
 
+fn uncovered_in_expression<'a>(
+    expression: &ast::Expression<&'a str>,
+    args: &HashMap<String, ArgTypes>,
+) -> Option<(&'a str, String)> {
+    match expression {
+        ast::Expression::Select { selector, variants } => {
+            if let ast::InlineExpression::VariableReference { id } = selector {
+                if let Some(ArgTypes::Enum(value)) = args.get(id.name) {
+                    let covered = variants.iter().any(|variant| match variant.key {
+                        ast::VariantKey::Identifier { name } | ast::VariantKey::NumberLiteral { value: name } => {
+                            name == value
+                        }
+                    });
+                    if !covered {
+                        return Some((id.name, value.clone()));
+                    }
+                }
+            }
+            variants.iter().find_map(|variant| uncovered_variant(&variant.value, args))
         }
+        ast::Expression::Inline(ast::InlineExpression::Placeable { expression }) => {
+            uncovered_in_expression(expression, args)
+        }
+        ast::Expression::Inline(_) => None,
+    }
+}
+
+/// Format the message for `key`, along with the arguments it was missing when `lenient`, which aren't errors then.
+fn format_message<'b>(
+    bundle: &'b bundle::Bundle,
+    key: &str,
+    fluent_args: &FluentArgs,
+    lenient: bool,
+) -> Result<(Cow<'b, str>, Vec<String>), TranslationError> {
+    let pattern = find_pattern(bundle, key)?;
+    let mut errors = vec![];
+    let value = bundle.format_pattern(pattern, Some(fluent_args), &mut errors);
+    let mut missing = vec![];
+    if lenient {
+        errors.retain(|error| match missing_argument(error) {
+            Some(id) => {
+                if !missing.contains(id) {
+                    missing.push(id.clone());
+                }
+                false
+            }
+            None => true,
+        });
+    }
+    if errors.is_empty() {
+        Ok((value, missing))
+    } else {
+        Err(formatting_error(&errors))
+    }
+}
+
+/// The name of the argument `error` is about, if it is about a missing one.
+fn missing_argument(error: &FluentError) -> Option<&String> {
+    match error {
+        FluentError::ResolverError(ResolverError::Reference(ReferenceKind::Variable { id })) => Some(id),
+        _ => None,
+    }
+}
+
+fn formatting_error(errors: &[FluentError]) -> TranslationError {
+    TranslationError::Formatting(format!("Translation failed: {}", errors
+        .iter()
+        .map(|e| e.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")))
+}
+
+/// Turn a formatted message from a bundle for `locale` into the translation python gets.
+fn finish(locale: bundle::AvailableLocales, value: Cow<str>) -> String {
+    match locale {
+        bundle::AvailableLocales::Pseudo => pseudo::bracket(&value),
+        _ => value.into_owned(),
     }
 }
 
@@ -99,9 +758,11 @@ impl Translator{
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::sync::Arc;
 
-    use crate::fluent::bundle::AvailableLocales;
-    use crate::fluent::translator::{ArgTypes, Translator};
+    use crate::fluent::bundle::{self, AvailableLocales};
+    use crate::fluent::{cache, functions};
+    use crate::fluent::translator::{check_variants, levenshtein, message_ids, resolve_locale, suggest, translate_all, ArgTypes, TranslationError, Translator};
 
     #[test]
     fn test_translate() {
@@ -132,7 +793,7 @@ mod tests {
                 panic!("Translation should have failed");
             }
             Err(e) => {
-                assert_eq!(e, "Message with key nonexistent-key not found");
+                assert_eq!(e, TranslationError::Key("Message with key nonexistent-key not found".to_string()));
             }
         }
     }
@@ -156,6 +817,184 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rich_arg_types() {
+        let translator = Translator::new(AvailableLocales::French)
+            .and_then(|translator| translator.overrides(concat!(
+                "flag = { $flag -> \n    [true] oui\n   *[false] non\n}\n",
+                "nothing = [{ $nothing }]\n",
+                "when = { $when }, { DATETIME($when, dateStyle: \"long\") }\n",
+                "users = { $users }\n",
+            )))
+            .expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("flag".to_string(), ArgTypes::Bool(true));
+        args.insert("nothing".to_string(), ArgTypes::None);
+        // 2023-03-01 14:05:09 UTC
+        args.insert("when".to_string(), ArgTypes::DateTime(1_677_679_509));
+        args.insert(
+            "users".to_string(),
+            ArgTypes::List(vec!["Anne".to_string(), "Bob".to_string(), "Chloe".to_string()]),
+        );
+        assert_eq!(translator.translate("flag", args.clone()).expect("Failed to translate"), "oui");
+        assert_eq!(translator.translate("nothing", args.clone()).expect("Failed to translate"), "[]");
+        assert_eq!(translator.translate("when", args.clone()).expect("Failed to translate"), "01/03/2023, 1 mars 2023");
+        assert_eq!(translator.translate("users", args.clone()).expect("Failed to translate"), "Anne, Bob et Chloe");
+        args.insert("flag".to_string(), ArgTypes::Bool(false));
+        assert_eq!(translator.translate("flag", args).expect("Failed to translate"), "non");
+    }
+
+    #[test]
+    fn test_isolation_marks() {
+        let mut translator = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides("greeting = Hi { $user }!\n"))
+            .expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        assert_eq!(translator.translate("greeting", args.clone()), Ok("Hi John!".to_string()));
+        // turned on on just this bundle, the global switch would rebuild the bundles the other tests use
+        Arc::get_mut(translator.overrides.as_mut().expect("Missing overrides"))
+            .expect("The overrides aren't shared")
+            .set_use_isolating(true);
+        assert_eq!(translator.translate("greeting", args), Ok("Hi \u{2068}John\u{2069}!".to_string()));
+    }
+
+    #[test]
+    fn test_non_ascii_output() {
+        let overrides = concat!(
+            "big = { NUMBER($n) }\n",
+            "share = { NUMBER($ratio, style: \"percent\") }\n",
+            "when = { DATETIME($when, dateStyle: \"long\") }\n",
+            "at = { DATETIME($when, timeStyle: \"short\") }\n",
+        );
+        let mut args = HashMap::new();
+        args.insert("n".to_string(), ArgTypes::Int(1_234_567));
+        args.insert("ratio".to_string(), ArgTypes::Float(0.25));
+        // 2023-02-14 14:05:00 UTC
+        args.insert("when".to_string(), ArgTypes::DateTime(1_676_383_500));
+        let french = Translator::new(AvailableLocales::French)
+            .and_then(|translator| translator.overrides(overrides))
+            .expect("Failed to create translator");
+        assert_eq!(french.translate("big", args.clone()), Ok("1\u{202f}234\u{202f}567".to_string()));
+        assert_eq!(french.translate("share", args.clone()), Ok("25\u{202f}%".to_string()));
+        assert_eq!(french.translate("when", args.clone()), Ok("14 février 2023".to_string()));
+        let english = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides(overrides))
+            .expect("Failed to create translator");
+        assert_eq!(english.translate("at", args), Ok("2:05\u{202f}PM".to_string()));
+    }
+
+    #[test]
+    fn test_add_locale_non_ascii() {
+        bundle::add_locale("ja", "greeting = こんにちは、{ $user }さん！\njoined = { $when }に参加\n".to_string()).expect("Failed to add locale");
+        let locale = AvailableLocales::from_str("ja").expect("Locale wasn't added");
+        let added = bundle::get_bundle(locale);
+        bundle::restore_locale("ja", None);
+        // built from the bundle directly, rebuilding the cached bundles would swap them under the other tests
+        let translator = Translator {
+            locale,
+            bundles: vec![Arc::new(added.expect("Failed to get bundle"))],
+            overrides: None,
+            generation: None,
+            strict: true,
+            lenient: false,
+        };
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("Jürgen".to_string()));
+        assert_eq!(translator.translate("greeting", args), Ok("こんにちは、Jürgenさん！".to_string()));
+        // there are no japanese date formats, so dates are an error instead of being american
+        let mut args = HashMap::new();
+        args.insert("when".to_string(), ArgTypes::DateTime(0));
+        assert!(matches!(translator.translate("joined", args), Err(TranslationError::Formatting(_))));
+    }
+
+    #[test]
+    fn test_enum_arg() {
+        let translator = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides(concat!(
+                "event = { $kind -> \n    [giveaway] A giveaway\n    [1] The first\n   *[other] Something\n} started\n",
+                "nested = { $user }{ $kind -> \n    [giveaway] { $tier -> \n        [gold] !\n       *[silver] .\n    }\n   *[other] ?\n}\n",
+            )))
+            .expect("Failed to create translator");
+        let overrides = translator.overrides.as_deref().expect("Missing overrides");
+        let mut args = HashMap::new();
+        args.insert("kind".to_string(), ArgTypes::Enum("giveaway".to_string()));
+        assert_eq!(translator.translate("event", args.clone()), Ok("A giveaway started".to_string()));
+        args.insert("kind".to_string(), ArgTypes::Enum("1".to_string()));
+        assert_eq!(check_variants(overrides, "event", &args), Ok(()));
+        args.insert("kind".to_string(), ArgTypes::Enum("raffle".to_string()));
+        assert_eq!(
+            check_variants(overrides, "event", &args),
+            Err(TranslationError::Formatting("Message with key event has no [raffle] variant for $kind".to_string()))
+        );
+        assert!(translator.translate("event", args.clone()).is_err());
+        // a plain string isn't checked, it gets the default variant like it always has
+        args.insert("kind".to_string(), ArgTypes::String("raffle".to_string()));
+        assert_eq!(translator.translate("event", args.clone()), Ok("Something started".to_string()));
+        args.insert("kind".to_string(), ArgTypes::Enum("giveaway".to_string()));
+        args.insert("tier".to_string(), ArgTypes::Enum("bronze".to_string()));
+        assert!(check_variants(overrides, "nested", &args).is_err());
+    }
+
+    #[test]
+    fn test_translate_lenient() {
+        let translator = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides("greeting = Hi { $user }, { $user } used { $command }!\n"))
+            .map(|translator| translator.lenient(true))
+            .expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        assert_eq!(
+            translator.translate_checked("greeting", args.clone()),
+            Ok(("Hi {$user}, {$user} used help!".to_string(), vec!["user".to_string()]))
+        );
+        assert!(translator.translate("greeting", args.clone()).is_err());
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        assert_eq!(
+            translator.translate_checked("greeting", args),
+            Ok(("Hi John, John used help!".to_string(), vec![]))
+        );
+        assert!(matches!(translator.translate_checked("missing-key", HashMap::new()), Err(TranslationError::Key(_))));
+    }
+
+    #[test]
+    fn test_translate_all() {
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        args.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        let translations = translate_all("check-failed", &args).expect("Failed to translate");
+        assert!(AvailableLocales::ALL.iter().all(|locale| translations.contains_key(locale.as_str())));
+        assert_eq!(translations["en-US"], "John, you can't use help.");
+        assert!(matches!(translate_all("missing-key", &args), Err(TranslationError::Key(_))));
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("roll", ""), 4);
+        assert_eq!(levenshtein("check-failed", "check-failed"), 0);
+        assert_eq!(levenshtein("chek-failed", "check-failed"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_suggest() {
+        let ids = ["check-failed", "check-passed", "roll", "rolls", "giveaway-won"].map(String::from);
+        assert_eq!(suggest("chek-failed", ids.iter()), vec!["check-failed"]);
+        assert_eq!(suggest("rol", ids.iter()), vec!["roll"]);
+        assert_eq!(suggest("check-faile", ids.iter()), vec!["check-failed"]);
+        assert!(suggest("nonexistent-key", ids.iter()).is_empty());
+    }
+
+    #[test]
+    fn test_key_suggestions() {
+        let translator = Translator::new(AvailableLocales::French).expect("Failed to create translator");
+        assert_eq!(
+            translator.translate("check-faild", HashMap::new()),
+            Err(TranslationError::Key("Message with key check-faild not found, did you mean check-failed?".to_string()))
+        );
+    }
+
     #[test]
     fn test_missing_test_key() {
         let translator = Translator::new(AvailableLocales::AmericanEnglish)
@@ -170,11 +1009,200 @@ mod tests {
                 panic!("Translation should have failed");
             }
             Err(e) => {
-                assert_eq!(e, "Translation failed: Resolver error: Unknown variable: $result");
+                assert_eq!(
+                    e,
+                    TranslationError::Formatting("Translation failed: Resolver error: Unknown variable: $result".to_string())
+                );
             }
         }
     }
 
+    #[test]
+    fn test_py_constructor() {
+        let translator = Translator::__new__("nl", false, false).expect("Failed to create translator");
+        assert_eq!(translator.locale(), "nl");
+        assert!(!translator.is_strict());
+        let fallback = Translator::__new__("de", false, false).expect("Failed to create translator");
+        assert_eq!(fallback.locale(), "en-US");
+    }
+
+    #[test]
+    fn test_attributes() {
+        let translator = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides("command-help = Help\n    .description = Shows help for { $command }\n"))
+            .expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("command".to_string(), ArgTypes::String("roll".to_string()));
+        assert_eq!(
+            translator.translate("command-help.description", args.clone()).expect("Failed to translate"),
+            "Shows help for roll"
+        );
+        assert_eq!(translator.translate("command-help", args.clone()).expect("Failed to translate"), "Help");
+        assert_eq!(
+            translator.translate("check-failed.title", args),
+            Err(TranslationError::Key("Message with key check-failed has no attribute title".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_strict_constructor() {
+        let translator = Translator::__new__("nl", true, false).expect("Failed to create translator");
+        assert!(translator.is_strict());
+        assert!(Translator::__new__("de", true, false).is_err());
+        assert!(Translator::py_with_overrides("de", "custom = Hallo\n", true, false).is_err());
+    }
+
+    #[test]
+    fn test_resolve_locale() {
+        assert_eq!(resolve_locale("fr", true), Ok(AvailableLocales::French));
+        assert_eq!(resolve_locale("de", false), Ok(AvailableLocales::AmericanEnglish));
+        assert!(matches!(resolve_locale("de", true), Err(TranslationError::Locale(_))));
+    }
+
+    #[test]
+    fn test_message_ids() {
+        let ids = message_ids(AvailableLocales::Dutch).expect("Failed to get message ids");
+        assert!(ids.contains("check-failed"));
+        assert!(ids.contains("giveaway-bid-success"));
+    }
+
+    #[test]
+    fn test_fallback_chain() {
+        let translator = Translator::__new__("es-MX", true, false).expect("Failed to create translator");
+        assert_eq!(translator.locale(), "es-ES");
+        assert_eq!(translator.fallback_chain(), vec!["es-ES", "en-US"]);
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        args.insert("retry_after".to_string(), ArgTypes::Int(5));
+        args.insert("unix-timestamp".to_string(), ArgTypes::Int(0));
+        // not translated into es-ES
+        assert!(matches!(translator.translate("command-on-cooldown", args.clone()), Err(TranslationError::Key(_))));
+        let translator = Translator::__new__("es-MX", false, false).expect("Failed to create translator");
+        assert_eq!(
+            translator.translate("command-on-cooldown", args).expect("Failed to translate"),
+            "John, this command is on cooldown for 5 seconds you can retry after 0."
+        );
+    }
+
+    #[test]
+    fn test_resolve_chain() {
+        use super::resolve_chain;
+        assert_eq!(resolve_chain("fr-BE", true), Ok(vec![AvailableLocales::French, AvailableLocales::AmericanEnglish]));
+        assert_eq!(resolve_chain("en-US", true), Ok(vec![AvailableLocales::AmericanEnglish]));
+        assert_eq!(resolve_chain("de", false), Ok(vec![AvailableLocales::AmericanEnglish]));
+        assert!(matches!(resolve_chain("de", true), Err(TranslationError::Locale(_))));
+    }
+
+    #[test]
+    fn test_bundles_are_cached() {
+        let first = Translator::new(AvailableLocales::French).expect("Failed to create translator");
+        let second = Translator::new(AvailableLocales::French).expect("Failed to create translator");
+        assert_eq!(first.bundles.len(), 2);
+        assert!(first.bundles.iter().zip(&second.bundles).all(|(first, second)| Arc::ptr_eq(first, second)));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let translator = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides("check-failed = Sorry { $user }, no { $command } here.\nbroken = { $missing }\n"))
+            .expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        args.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        assert_eq!(
+            translator.translate("check-failed", args.clone()).expect("Failed to translate"),
+            "Sorry John, no help here."
+        );
+        args.insert("dice".to_string(), ArgTypes::String("1d6".to_string()));
+        args.insert("result".to_string(), ArgTypes::Int(1));
+        args.insert("total".to_string(), ArgTypes::Float(1.0));
+        assert_eq!(
+            translator.translate("success", args.clone()).expect("Failed to translate"),
+            "John rolled `1d6` and got `1`for a total of `1`."
+        );
+        assert!(matches!(translator.translate("broken", args), Err(TranslationError::Key(_))));
+    }
+
+    #[test]
+    fn test_terms() {
+        let translator = Translator::new(AvailableLocales::AmericanEnglish)
+            .and_then(|translator| translator.overrides("about = I'm { -bot-name }, { $user }\n"))
+            .expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        let renamed = translator
+            .clone()
+            .terms(&HashMap::from([("bot-name".to_string(), "Charbot".to_string())]))
+            .expect("Failed to set terms");
+        assert_eq!(renamed.translate("about", args.clone()), Ok("I'm Charbot, John".to_string()));
+        assert_eq!(translator.translate("about", args.clone()), Ok("I'm CharB0T, John".to_string()));
+        assert_eq!(renamed.bundles.len(), translator.bundles.len());
+        args.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        assert!(renamed.translate("check-failed", args).is_ok());
+        assert!(matches!(
+            translator.terms(&HashMap::from([("bot name".to_string(), "Charbot".to_string())])),
+            Err(TranslationError::Formatting(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let translator = Translator::new(AvailableLocales::Dutch).expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        args.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        let mut reordered = HashMap::new();
+        reordered.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        reordered.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        let key = translator.cache_key(0, "check-failed", &args, false);
+        assert_eq!(key, translator.cache_key(0, "check-failed", &reordered, false));
+        assert_ne!(key, translator.cache_key(1, "check-failed", &args, false));
+        assert_ne!(key, translator.cache_key(0, "check-failed", &args, true));
+        assert_ne!(key, translator.cache_key(0, "bad-code", &args, false));
+        args.insert("command".to_string(), ArgTypes::Float(1.0));
+        assert_ne!(key, translator.cache_key(0, "check-failed", &args, false));
+        let english = Translator::new(AvailableLocales::AmericanEnglish).expect("Failed to create translator");
+        assert_ne!(key, english.cache_key(0, "check-failed", &reordered, false));
+        assert!(translator.generation.is_some());
+        assert!(translator.overrides("custom = Hallo\n").expect("Failed to add overrides").generation.is_none());
+    }
+
+    #[test]
+    fn test_errors_are_not_cached() {
+        let translator = Translator::new(AvailableLocales::Dutch).expect("Failed to create translator").lenient(true);
+        let generation = translator.generation.expect("Missing generation");
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("Jan".to_string()));
+        let missing = translator.translate_checked("check-failed", args.clone()).expect("Failed to translate");
+        assert_eq!(missing.1, ["command"]);
+        assert_eq!(cache::get(translator.cache_key(generation, "check-failed", &args, true)), None);
+        args.insert("command".to_string(), ArgTypes::String("hulp".to_string()));
+        let calls = functions::calls();
+        let translated = translator.translate_checked("check-failed", args.clone()).expect("Failed to translate");
+        assert_eq!(functions::calls(), calls);
+        assert_eq!(cache::get(translator.cache_key(generation, "check-failed", &args, true)), Some(translated));
+    }
+
+    #[test]
+    fn test_pseudo() {
+        let translator = Translator::from_chain(&[AvailableLocales::Pseudo]).expect("Failed to create translator");
+        let mut args = HashMap::new();
+        args.insert("user".to_string(), ArgTypes::String("John".to_string()));
+        args.insert("command".to_string(), ArgTypes::String("help".to_string()));
+        assert_eq!(
+            translator.translate("check-failed", args),
+            Ok("[John, ýööûû çááñ'ţ ûûšéé help.]".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_overrides() {
+        assert!(matches!(
+            Translator::new(AvailableLocales::AmericanEnglish).and_then(|translator| translator.overrides("check-failed = { $\n")),
+            Err(TranslationError::Formatting(_))
+        ));
+    }
+
     #[test]
     #[allow(clippy::assertions_on_constants)]
     fn test_giveaway_check_success_key() {
@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Checking FTL sources before they're used, such as the ones community translators submit.
+use std::collections::HashSet;
+
+use fluent_syntax::{ast, parser};
+use pyo3::{pyclass, pymethods};
+
+/// Something wrong with an FTL source, and where it is.
+#[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
+#[derive(Debug, Clone, PartialEq, Eq)] // COV_EXCL_LINE
+pub(crate) struct Diagnostic {
+    /// The line it is on, starting from 1.
+    #[pyo3(get)]
+    pub(crate) line: usize,
+    /// The character in the line it starts at, starting from 1.
+    #[pyo3(get)]
+    pub(crate) column: usize,
+    /// What is wrong.
+    #[pyo3(get)]
+    pub(crate) message: String,
+    /// `error` for what stops the source from loading, `warning` for what loads but is likely a mistake.
+    #[pyo3(get)]
+    pub(crate) severity: &'static str,
+}
+
+// COV_EXCL_START
+#[pymethods]
+impl Diagnostic {
+    fn __repr__(&self) -> String {
+        format!(
+            "Diagnostic(line={}, column={}, message={:?}, severity={:?})",
+            self.line, self.column, self.message, self.severity
+        )
+    }
+}
+// COV_EXCL_STOP
+
+impl Diagnostic {
+    fn new(source: &str, offset: usize, message: String, severity: &'static str) -> Self {
+        let (line, column) = line_and_column(source, offset);
+        Self {
+            line,
+            column,
+            message,
+            severity,
+        }
+    }
+}
+
+/// Parse `source`, returning everything wrong with it in the order it appears, nothing if it is fine.
+pub(crate) fn validate(source: &str) -> Vec<Diagnostic> {
+    let (resource, errors) = match parser::parse(source) {
+        Ok(resource) => (resource, vec![]),
+        Err((resource, errors)) => (resource, errors),
+    };
+    let mut diagnostics = errors
+        .iter()
+        .map(|error| Diagnostic::new(source, error.pos.start, error.to_string(), "error"))
+        .collect::<Vec<_>>();
+    // bundles refuse a resource that defines an id twice, but it parses fine
+    let mut seen = HashSet::new();
+    for entry in &resource.body {
+        let (id, kind) = match entry {
+            ast::Entry::Message(message) => (message.id.name, "Message"),
+            ast::Entry::Term(term) => (term.id.name, "Term"),
+            _ => continue,
+        };
+        if !seen.insert((kind, id)) {
+            let offset = id.as_ptr() as usize - source.as_ptr() as usize;
+            diagnostics.push(Diagnostic::new(source, offset, format!("{kind} {id} is defined more than once"), "warning"));
+        }
+    }
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+    diagnostics
+}
+
+/// The line and the character in it that the byte `offset` of `source` is at, both starting from 1.
+fn line_and_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::{line_and_column, validate};
+
+    #[test]
+    fn test_line_and_column() {
+        let source = "hello = Hi\nbyé = { $\n";
+        assert_eq!(line_and_column(source, 0), (1, 1));
+        assert_eq!(line_and_column(source, 8), (1, 9));
+        assert_eq!(line_and_column(source, 11), (2, 1));
+        assert_eq!(line_and_column(source, source.find('{').expect("Missing brace")), (2, 7));
+        assert_eq!(line_and_column(source, 100), (3, 1));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(validate("hello = Hi { $user }\n-brand = Charlie\n").is_empty());
+        let diagnostics = validate("hello = Hi\nbroken = { $\nhello = Again\n-brand = A\n-brand = B\n");
+        assert_eq!(
+            diagnostics.iter().map(|diagnostic| (diagnostic.line, diagnostic.severity)).collect::<Vec<_>>(),
+            vec![(2, "error"), (3, "warning"), (5, "warning")]
+        );
+        assert_eq!(diagnostics[1].column, 1);
+        assert_eq!(diagnostics[1].message, "Message hello is defined more than once");
+        assert_eq!(diagnostics[2].message, "Term brand is defined more than once");
+    }
+}
+// COV_EXCL_STOP
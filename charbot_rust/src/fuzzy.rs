@@ -0,0 +1,301 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Fuzzy matching of names, like commands and tags, to suggest the one someone meant when what they
+//! typed isn't one, like "did you mean `/profile`?" for `/profle`.
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::metrics;
+
+/// How much a shared prefix raises the Jaro similarity, for each of its first `PREFIX` letters.
+const PREFIX_SCALE: f64 = 0.1;
+const PREFIX: usize = 4;
+/// How alike two names have to be in the Jaro similarity before a shared prefix counts.
+const BOOST_THRESHOLD: f64 = 0.7;
+
+/// A name as it's compared, lowercase and without the whitespace around it.
+#[derive(Clone, Debug, PartialEq)]
+struct Name {
+    letters: Vec<char>,
+    /// Its trigrams, sorted and without repeats.
+    trigrams: Vec<u64>,
+}
+
+impl Name {
+    fn new(name: &str) -> Self {
+        let letters = name.trim().chars().flat_map(char::to_lowercase).collect::<Vec<_>>();
+        let trigrams = trigrams(&letters);
+        Name { letters, trigrams }
+    }
+}
+
+/// The trigrams of each word of `letters`, with two spaces before them and one after, like PostgreSQL's
+/// pg_trgm, so short words and the starts of words have trigrams of their own.
+fn trigrams(letters: &[char]) -> Vec<u64> {
+    let pack = |window: &[char]| window.iter().fold(0, |packed, letter| packed << 21 | u64::from(*letter));
+    let mut trigrams = Vec::new();
+    for word in letters.split(|letter| letter.is_whitespace()).filter(|word| !word.is_empty()) {
+        let padded = [&[' ', ' '], word, &[' ']].concat();
+        trigrams.extend(padded.windows(3).map(pack));
+    }
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// The trigrams `first` and `second` share, out of all of theirs, from 0 to 1.
+fn trigram_similarity(first: &[u64], second: &[u64]) -> f64 {
+    if first.is_empty() || second.is_empty() {
+        return 0.0;
+    }
+    let (mut shared, mut left, mut right) = (0, 0, 0);
+    while left < first.len() && right < second.len() {
+        match first[left].cmp(&second[right]) {
+            std::cmp::Ordering::Less => left += 1,
+            std::cmp::Ordering::Greater => right += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                left += 1;
+                right += 1;
+            }
+        }
+    }
+    shared as f64 / (first.len() + second.len() - shared) as f64
+}
+
+/// The Jaro-Winkler similarity of two names, from 0 to 1, which forgives typos and swapped letters, and
+/// favours names that start the same.
+fn jaro_winkler(first: &[char], second: &[char]) -> f64 {
+    if first.is_empty() || second.is_empty() {
+        return if first == second { 1.0 } else { 0.0 };
+    }
+    let window = (first.len().max(second.len()) / 2).saturating_sub(1);
+    let mut matched = vec![false; second.len()];
+    let mut first_matches = Vec::new();
+    for (index, letter) in first.iter().enumerate() {
+        let start = index.saturating_sub(window);
+        let end = (index + window + 1).min(second.len());
+        if let Some(other) = (start..end).find(|other| !matched[*other] && second[*other] == *letter) {
+            matched[other] = true;
+            first_matches.push(*letter);
+        }
+    }
+    if first_matches.is_empty() {
+        return 0.0;
+    }
+    let second_matches = second.iter().zip(&matched).filter(|(_, matched)| **matched).map(|(letter, _)| *letter);
+    let transpositions = first_matches.iter().zip(second_matches).filter(|(first, second)| **first != *second).count() / 2;
+    let matches = first_matches.len() as f64;
+    let jaro = (matches / first.len() as f64 + matches / second.len() as f64 + (matches - transpositions as f64) / matches) / 3.0;
+    if jaro < BOOST_THRESHOLD {
+        return jaro;
+    }
+    let prefix = first.iter().zip(second).take(PREFIX).take_while(|(first, second)| first == second).count();
+    jaro + prefix as f64 * PREFIX_SCALE * (1.0 - jaro)
+}
+
+/// How alike two names are, from 0 to 1 for the same: the better of their Jaro-Winkler similarity, for
+/// typos, and their trigram similarity, for words in another order or left out.
+fn score(query: &Name, candidate: &Name) -> f64 {
+    if query.letters == candidate.letters {
+        return 1.0;
+    }
+    jaro_winkler(&query.letters, &candidate.letters).max(trigram_similarity(&query.trigrams, &candidate.trigrams))
+}
+
+fn check_threshold(threshold: f64) -> Result<f64, String> {
+    if (0.0..=1.0).contains(&threshold) {
+        Ok(threshold)
+    } else {
+        Err(format!("Invalid threshold {threshold}, it must be from 0 to 1"))
+    }
+}
+
+/// Names, like the commands or the tags of a server, to find the closest of to what someone typed.
+#[pyclass(module = "fuzzy")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)]
+pub struct Matcher {
+    candidates: Vec<(String, Name)>,
+}
+
+impl Matcher {
+    fn new(candidates: Vec<String>) -> Self {
+        let candidates = candidates
+            .into_iter()
+            .map(|candidate| {
+                let name = Name::new(&candidate);
+                (candidate, name)
+            })
+            .collect();
+        Matcher { candidates }
+    }
+
+    /// The `limit` candidates most like `query` with at least `threshold`, best first, and in the order
+    /// they were given when they're as good.
+    fn best_matches(&self, query: &str, limit: usize, threshold: f64) -> Vec<(String, f64)> {
+        let query = Name::new(query);
+        if query.letters.is_empty() {
+            return Vec::new();
+        }
+        let mut scored = self
+            .candidates
+            .iter()
+            .map(|(candidate, name)| (candidate, score(&query, name)))
+            .filter(|(_, score)| *score > 0.0 && *score >= threshold)
+            .collect::<Vec<_>>();
+        scored.sort_by(|(_, first), (_, second)| second.total_cmp(first));
+        scored.into_iter().take(limit).map(|(candidate, score)| (candidate.clone(), score)).collect()
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Matcher {
+    #[new]
+    fn py_new(candidates: Vec<String>) -> Self {
+        Matcher::new(candidates)
+    }
+
+    /// The names, as they were given.
+    #[getter] // COV_EXCL_LINE
+    fn candidates(&self) -> Vec<String> {
+        self.candidates.iter().map(|(candidate, _)| candidate.clone()).collect()
+    }
+
+    #[pyo3(name = "best_matches", signature = (query, limit=5, threshold=0.0))]
+    fn py_best_matches(&self, py: Python, query: &str, limit: usize, threshold: f64) -> PyResult<Vec<(String, f64)>> {
+        let threshold = check_threshold(threshold).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(metrics::timed("fuzzy.Matcher.best_matches", || py.allow_threads(|| self.best_matches(query, limit, threshold))))
+    }
+
+    fn __len__(&self) -> usize {
+        self.candidates.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Matcher(candidates={})", self.candidates.len())
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (query, candidates, /, limit=5, threshold=0.0), text_signature = "
+best_matches(query, candidates, /, limit=5, threshold=0.0)
+--
+
+Find the candidates most like what someone typed, like the command they meant, ignoring case.
+
+Names are scored by the better of their Jaro-Winkler similarity, which forgives typos and favours names
+that start the same, and their trigram similarity, which forgives words in another order or left out.
+
+Parameters
+----------
+query : str
+    What they typed.
+candidates : list[str]
+    The names to choose from. To match against the same names many times, use a ``Matcher``.
+limit : int
+    The most matches to give. The default is 5.
+threshold : float
+    The lowest score a match can have, from 0 to 1. The default is 0, for any name with anything in
+    common with the query.
+
+Returns
+-------
+list[tuple[str, float]]
+    The matches and their scores, from 0 to 1 for the same name, best first, and in the order of the
+    candidates when they're as good.
+
+Raises
+------
+ValueError
+    If the threshold isn't from 0 to 1.
+")]
+fn best_matches(py: Python, query: &str, candidates: Vec<String>, limit: usize, threshold: f64) -> PyResult<Vec<(String, f64)>> {
+    let threshold = check_threshold(threshold).map_err(PyErr::new::<PyValueError, _>)?;
+    Ok(metrics::timed("fuzzy.best_matches", || py.allow_threads(|| Matcher::new(candidates).best_matches(query, limit, threshold))))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based fuzzy matching, to suggest the command or tag someone meant";
+
+pub(crate) fn register_fuzzy(py: Python, m: &PyModule) -> PyResult<()> {
+    let fuzzy = PyModule::new(py, "_fuzzy")?;
+    fuzzy.add_class::<Matcher>()?;
+    fuzzy.add_function(wrap_pyfunction!(best_matches, fuzzy)?)?;
+    fuzzy.add("__doc__", DOCSTRING)?;
+    m.add_submodule(fuzzy)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn names(candidates: &[&str]) -> Matcher {
+        Matcher::new(candidates.iter().map(ToString::to_string).collect())
+    }
+
+    #[parameterized(
+        same = {"martha", "martha", 1.0},
+        swapped = {"martha", "marhta", 0.961},
+        typos = {"dwayne", "duane", 0.84},
+        different = {"dixon", "dicksonx", 0.813},
+        nothing_shared = {"abc", "xyz", 0.0},
+        empty = {"", "abc", 0.0},
+    )]
+    fn jaro_winkler_similarity(first: &str, second: &str, expected: f64) {
+        let (first, second) = (first.chars().collect::<Vec<_>>(), second.chars().collect::<Vec<_>>());
+        assert!((jaro_winkler(&first, &second) - expected).abs() < 0.001, "{}", jaro_winkler(&first, &second));
+        assert!((jaro_winkler(&second, &first) - expected).abs() < 0.001);
+    }
+
+    #[test]
+    fn trigram_similarities() {
+        let similarity = |first: &str, second: &str| trigram_similarity(&Name::new(first).trigrams, &Name::new(second).trigrams);
+        assert_eq!(similarity("cat", "cat"), 1.0);
+        // "  c", " ca" and "cat" are shared, "at ", "ats" and "ts " aren't
+        assert_eq!(similarity("cat", "cats"), 0.5);
+        assert_eq!(similarity("word order", "order word"), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert_eq!(similarity("", "abc"), 0.0);
+    }
+
+    #[test]
+    fn suggests() {
+        let commands = names(&["profile", "rank", "leaderboard", "give", "gamble", "help", "prefix"]);
+        let matches = commands.best_matches("/profle", 3, 0.0);
+        assert_eq!(matches[0].0, "profile");
+        assert_eq!(commands.best_matches("Profle", 1, 0.0)[0].0, "profile");
+        assert_eq!(commands.best_matches("leader board", 1, 0.0)[0].0, "leaderboard");
+        assert_eq!(commands.best_matches("profile", 1, 0.0), vec![(String::from("profile"), 1.0)]);
+        // best first, and no more than the limit
+        assert_eq!(matches.len(), 3);
+        assert!(matches.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        // nothing passes the threshold when nothing's close
+        assert!(commands.best_matches("qqqqqqqq", 5, 0.5).is_empty());
+        assert!(commands.best_matches("   ", 5, 0.0).is_empty());
+    }
+
+    #[test]
+    fn tags() {
+        let tags = names(&["rules", "how to ask for help", "server rules", "faq", "roles"]);
+        // words in another order are as good as the same
+        assert_eq!(tags.best_matches("ask for help how to", 1, 0.0), vec![(String::from("how to ask for help"), 1.0)]);
+        let matches = tags.best_matches("rule", 5, 0.6);
+        assert_eq!(matches[0].0, "rules");
+        assert!(matches.iter().all(|(_, score)| *score >= 0.6));
+        // as good a match keeps the order the candidates were given in
+        let twins = names(&["tag", "tag", "tab"]);
+        assert_eq!(twins.best_matches("tag", 2, 0.0), vec![(String::from("tag"), 1.0), (String::from("tag"), 1.0)]);
+    }
+
+    #[test]
+    fn thresholds() {
+        assert_eq!(check_threshold(0.5), Ok(0.5));
+        assert_eq!(check_threshold(1.5), Err(String::from("Invalid threshold 1.5, it must be from 0 to 1")));
+        assert!(check_threshold(f64::NAN).is_err());
+        assert_eq!(names(&["a", "b"]).__repr__(), "Matcher(candidates=2)");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,171 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::{HashMap, HashSet};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+
+use crate::metrics;
+
+// COV_EXCL_START
+#[pyclass(module = "giveaway")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Draw {
+    /// The users that won, in the order they were drawn.
+    #[pyo3(get)]
+    winners: Vec<u64>,
+    /// The seed the winners were drawn with, so the draw can be checked by drawing again with it.
+    #[pyo3(get)]
+    seed: u64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Draw {
+    fn __repr__(&self) -> String {
+        format!("Draw(winners={:?}, seed={})", self.winners, self.seed)
+    }
+}
+
+/// Draw `winners` users from `entries`, each with a chance proportional to their weight, without drawing anyone twice.
+///
+/// Each winner is drawn from whoever is left, and the entries are sorted by user first, so the same
+/// entries and seed always draw the same winners whatever order they were given in.
+fn weighted(entries: &HashMap<u64, u64>, winners: usize, seed: u64, exclude: &HashSet<u64>) -> Result<Draw, String> {
+    if winners == 0 {
+        return Err("There has to be at least one winner".to_string());
+    }
+    let mut eligible = entries.iter().filter(|(user, weight)| **weight > 0 && !exclude.contains(user)).map(|(user, weight)| (*user, *weight)).collect::<Vec<_>>();
+    eligible.sort_unstable();
+    let mut total = eligible
+        .iter()
+        .try_fold(0u64, |total, (_, weight)| total.checked_add(*weight))
+        .ok_or_else(|| format!("Invalid entries, the weights can't add up to more than {}", u64::MAX))?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut drawn = Vec::with_capacity(winners.min(eligible.len()));
+    while drawn.len() < winners && !eligible.is_empty() {
+        let mut ticket = rng.gen_range(0..total);
+        let index = eligible
+            .iter()
+            .position(|(_, weight)| {
+                let found = ticket < *weight;
+                ticket = ticket.saturating_sub(*weight);
+                found
+            })
+            .expect("The ticket is below the total so it's in someone's weight");
+        let (user, weight) = eligible.remove(index);
+        total -= weight;
+        drawn.push(user);
+    }
+    Ok(Draw { winners: drawn, seed })
+}
+
+#[pyfunction]
+#[pyo3(signature = (entries, winners, seed=None, exclude=None), text_signature = "
+draw(entries, winners, seed=None, exclude=None, /)
+--
+
+Draw the winners of a giveaway, with more entries giving a better chance, but never winning twice.
+
+Parameters
+----------
+entries : dict[int, int]
+    The weight of each user's entry, like how many tickets they have. Users with a weight of 0 can't win.
+winners : int
+    How many winners to draw. If there are fewer users that can win, they all do.
+seed : int | None
+    The seed to draw with, so a draw can be checked by drawing again. The default is None, for a random seed.
+exclude : list[int] | None
+    Users that can't win, like the host or the winners of the last giveaway. The default is None, for nobody.
+
+Returns
+-------
+Draw
+    The winners in the order they were drawn, and the seed they were drawn with.
+
+Raises
+------
+ValueError
+    If there are no winners to draw, or the weights add up to more than 2^64 - 1.
+")]
+fn draw(entries: HashMap<u64, u64>, winners: usize, seed: Option<u64>, exclude: Option<HashSet<u64>>) -> PyResult<Draw> {
+    metrics::observe("giveaway.draw", || {
+        let seed = seed.unwrap_or_else(|| thread_rng().gen());
+        weighted(&entries, winners, seed, &exclude.unwrap_or_default()).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based weighted giveaway draws";
+
+pub(crate) fn register_giveaway(py: Python, m: &PyModule) -> PyResult<()> {
+    let giveaway = PyModule::new(py, "_giveaway")?;
+    giveaway.add_class::<Draw>()?;
+    giveaway.add_function(wrap_pyfunction!(draw, giveaway)?)?;
+    giveaway.add("__doc__", DOCSTRING)?;
+    m.add_submodule(giveaway)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(weights: &[(u64, u64)]) -> HashMap<u64, u64> {
+        weights.iter().copied().collect()
+    }
+
+    #[test]
+    fn no_duplicates() {
+        let given = entries(&[(1, 100), (2, 1), (3, 1), (4, 5)]);
+        for seed in 0..100 {
+            let drawn = weighted(&given, 3, seed, &HashSet::new()).expect("Expected a draw");
+            assert_eq!(drawn.winners.len(), 3);
+            assert_eq!(drawn.winners.iter().collect::<HashSet<_>>().len(), 3, "Drew {:?}", drawn.winners);
+            assert_eq!(drawn.seed, seed);
+        }
+    }
+
+    #[test]
+    fn reproducible() {
+        let given = entries(&[(1, 3), (2, 5), (3, 7), (4, 11), (5, 13)]);
+        let first = weighted(&given, 3, 42, &HashSet::new()).expect("Expected a draw");
+        // the same entries in another order
+        let reordered = [(5, 13), (4, 11), (3, 7), (2, 5), (1, 3)].into_iter().collect::<HashMap<_, _>>();
+        assert_eq!(weighted(&reordered, 3, 42, &HashSet::new()), Ok(first.clone()));
+        let again = draw(given, 3, Some(first.seed), None).expect("Expected a draw");
+        assert_eq!(again, first);
+    }
+
+    #[test]
+    fn weighted_chances() {
+        let given = entries(&[(1, 1), (2, 3)]);
+        let wins = (0..4000).filter(|seed| weighted(&given, 1, *seed, &HashSet::new()).expect("Expected a draw").winners == [2]).count();
+        // 3 in 4, give or take
+        assert!((2800..3200).contains(&wins), "Won {wins} of 4000");
+    }
+
+    #[test]
+    fn exclusions() {
+        let given = entries(&[(1, 1000), (2, 1), (3, 0), (4, 1)]);
+        let exclude = HashSet::from([1]);
+        for seed in 0..50 {
+            let mut drawn = weighted(&given, 5, seed, &exclude).expect("Expected a draw").winners;
+            drawn.sort_unstable();
+            // nobody left out or without a weight wins, and everybody else does since there aren't enough
+            assert_eq!(drawn, vec![2, 4]);
+        }
+        assert_eq!(weighted(&HashMap::new(), 1, 0, &HashSet::new()).expect("Expected a draw").winners, Vec::<u64>::new());
+        assert_eq!(weighted(&entries(&[(1, u64::MAX), (2, 0)]), 2, 0, &HashSet::new()).expect("Expected a draw").winners, vec![1]);
+    }
+
+    #[test]
+    fn rejects() {
+        weighted(&entries(&[(1, 1)]), 0, 0, &HashSet::new()).expect_err("Expected no winners to be rejected");
+        weighted(&entries(&[(1, u64::MAX), (2, 1)]), 1, 0, &HashSet::new()).expect_err("Expected the total to be rejected");
+        draw(entries(&[(1, 1)]), 1, None, None).expect("Expected a random seed");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,322 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Hashing attachments, with XXH3 for quickly checking whether the same bytes were seen before, BLAKE3
+//! for a digest no one can make another attachment match, and perceptual hashes for images that look
+//! the same but whose bytes don't, like a removed image that's been resized and uploaded again.
+#[cfg(feature = "images")]
+mod perceptual; // COV_EXCL_LINE
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use xxhash_rust::xxh3::{xxh3_64_with_seed, Xxh3};
+
+use crate::buffer::{Buffer, Data};
+#[cfg(feature = "images")]
+use crate::buffer::Bytes;
+use crate::crypto::hex;
+#[cfg(feature = "images")]
+use crate::{images, metrics};
+
+#[pyfunction]
+#[pyo3(signature = (data, /, seed=0), text_signature = "
+xxh3(data, /, seed=0)
+--
+
+Hash data with the 64 bit XXH3, which is fast enough to hash every attachment, but isn't
+cryptographic, so someone can make different data with the same hash on purpose.
+
+Parameters
+----------
+data : str | bytes-like
+    The data. Text is hashed as UTF-8.
+seed : int
+    A seed from 0 to 2**64 - 1, which gives different hashes for the same data. The default is 0.
+
+Returns
+-------
+int
+    The hash, from 0 to 2**64 - 1, the same as other XXH3 implementations give.
+")]
+fn xxh3(py: Python, data: Data, seed: u64) -> u64 {
+    py.allow_threads(|| xxh3_64_with_seed(data.as_bytes(), seed))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+blake3(data, /)
+--
+
+Hash data with BLAKE3, which is cryptographic, so no one can make different data with the same
+digest, and still fast.
+
+Parameters
+----------
+data : str | bytes-like
+    The data. Text is hashed as UTF-8.
+
+Returns
+-------
+str
+    The 32 byte digest, as hex.
+")]
+fn blake3(py: Python, data: Data) -> String {
+    hex(py.allow_threads(|| ::blake3::hash(data.as_bytes())).as_bytes())
+}
+
+#[cfg(feature = "images")]
+#[pyfunction]
+#[pyo3(text_signature = "
+perceptual_hash(image, /)
+--
+
+Hash how an image looks rather than its bytes, so an image that's been resized, recompressed or
+slightly recolored hashes almost the same as it did before.
+
+Parameters
+----------
+image : bytes-like
+    The image, in any format the image crate can decode, like PNG, JPEG, GIF and WebP.
+
+Returns
+-------
+int
+    The hash, from 0 to 2**64 - 1, which is compared with ``hamming_distance``. Images that look the
+    same are usually at most 10 apart.
+
+Raises
+------
+ValueError
+    If the image can't be decoded, or is too big.
+")]
+fn perceptual_hash(py: Python, image: Bytes) -> PyResult<u64> {
+    metrics::observe("hashing.perceptual_hash", || {
+        py.allow_threads(|| images::decode(&image).map(|image| perceptual::perceptual_hash(&image))).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+hamming_distance(a, b, /)
+--
+
+How many bits two hashes differ in, like two perceptual hashes, which is small for images that look
+alike.
+
+Parameters
+----------
+a : int
+    A hash, from 0 to 2**64 - 1.
+b : int
+    The other hash.
+
+Returns
+-------
+int
+    How many bits differ, from 0 to 64.
+")]
+fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// The state of either hash, which is hundreds of bytes for XXH3 and more for BLAKE3, so it's boxed.
+#[derive(Clone)]
+enum State {
+    Xxh3(Box<Xxh3>),
+    Blake3(Box<::blake3::Hasher>),
+}
+
+/// Hash data that comes in parts, like an attachment as it's downloaded, the same as hashing it all at
+/// once.
+#[pyclass(module = "hashing")] // COV_EXCL_LINE
+#[derive(Clone)]
+pub struct Hasher {
+    state: State,
+}
+
+impl std::fmt::Debug for Hasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Hasher").field("algorithm", &self.algorithm()).finish_non_exhaustive()
+    }
+}
+
+impl Hasher {
+    fn new(algorithm: &str, seed: Option<u64>) -> Result<Self, String> {
+        let state = match (algorithm, seed) {
+            ("xxh3", seed) => State::Xxh3(Box::new(Xxh3::with_seed(seed.unwrap_or(0)))),
+            ("blake3", None) => State::Blake3(Box::default()),
+            ("blake3", Some(_)) => return Err(String::from("Invalid seed, only xxh3 takes one")),
+            _ => return Err(format!("Invalid algorithm {algorithm:?}, it must be \"xxh3\" or \"blake3\"")),
+        };
+        Ok(Hasher { state })
+    }
+
+    /// The digest, with XXH3's hash as its 8 bytes from the highest.
+    fn finish(&self) -> Vec<u8> {
+        match &self.state {
+            State::Xxh3(hasher) => hasher.digest().to_be_bytes().to_vec(),
+            State::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+        }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Hasher {
+    #[new]
+    #[pyo3(signature = (algorithm, /, seed=None))]
+    fn py_new(algorithm: &str, seed: Option<u64>) -> PyResult<Self> {
+        Hasher::new(algorithm, seed).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn update(&mut self, py: Python, data: Data) {
+        let state = &mut self.state;
+        py.allow_threads(|| match state {
+            State::Xxh3(hasher) => hasher.update(data.as_bytes()),
+            State::Blake3(hasher) => {
+                hasher.update(data.as_bytes());
+            }
+        });
+    }
+
+    fn digest(&self) -> Buffer {
+        Buffer::from(self.finish())
+    }
+
+    fn hexdigest(&self) -> String {
+        hex(&self.finish())
+    }
+
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn algorithm(&self) -> &'static str {
+        match self.state {
+            State::Xxh3(_) => "xxh3",
+            State::Blake3(_) => "blake3",
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Hasher(algorithm={:?})", self.algorithm())
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based hashing of attachments, by their bytes or by how images look";
+
+pub(crate) fn register_hashing(py: Python, m: &PyModule) -> PyResult<()> {
+    let hashing = PyModule::new(py, "_hashing")?;
+    hashing.add_class::<Hasher>()?;
+    hashing.add_function(wrap_pyfunction!(xxh3, hashing)?)?;
+    hashing.add_function(wrap_pyfunction!(blake3, hashing)?)?;
+    #[cfg(feature = "images")]
+    hashing.add_function(wrap_pyfunction!(perceptual_hash, hashing)?)?;
+    hashing.add_function(wrap_pyfunction!(hamming_distance, hashing)?)?;
+    hashing.add("__doc__", DOCSTRING)?;
+    m.add_submodule(hashing)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    /// The input of the official BLAKE3 test vectors, counting up through 251 bytes.
+    fn counting(length: usize) -> Vec<u8> {
+        (0..length).map(|index| (index % 251) as u8).collect()
+    }
+
+    /// Bytes that don't repeat in any short stretch, so every part of the input counts.
+    fn scattered(length: usize) -> Vec<u8> {
+        let mut state = 0x9E37_79B1u32;
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(0x85EB_CA77).wrapping_add(0x1234_5679);
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    /// The digest of `data` fed to a hasher `size` bytes at a time.
+    fn streamed(algorithm: &str, seed: Option<u64>, data: &[u8], size: usize) -> Vec<u8> {
+        let mut hasher = Hasher::new(algorithm, seed).unwrap();
+        for part in data.chunks(size) {
+            match &mut hasher.state {
+                State::Xxh3(state) => state.update(part),
+                State::Blake3(state) => {
+                    state.update(part);
+                }
+            }
+        }
+        hasher.finish()
+    }
+
+    // from the reference implementation, for every way an input can be hashed
+    #[parameterized(
+        empty = {0, 0x2d06_8005_38d3_94c2, 0xb029_411f_f43d_84d2},
+        three = {3, 0x9b8e_136d_9a94_cd87, 0x5078_51ba_06fa_3bbc},
+        eight = {8, 0xc075_c4ba_4c4d_10fa, 0xa2f2_97eb_242f_2a4d},
+        sixteen = {16, 0xd564_68f1_524b_944b, 0xacd9_ac8a_700d_810e},
+        seventeen = {17, 0xe83c_865a_5ede_921b, 0x1d48_49ca_9652_a95d},
+        one_twenty_eight = {128, 0x9c96_6fa3_5b96_eb72, 0x8bf7_b0f8_a8da_b420},
+        two_hundred = {200, 0xd885_a2ce_da95_08de, 0x1e6a_e394_053a_7e76},
+        two_forty = {240, 0x7c14_f633_8adb_9c53, 0xc8cd_ab71_a268_5f44},
+        two_forty_one = {241, 0xca96_5893_5835_903b, 0x76c6_ecb7_9bf2_028e},
+        block = {1024, 0x6d43_8374_8ad2_617f, 0x1db0_6a40_df94_ad01},
+        past_a_block = {1025, 0xc8bf_5a1d_accd_98e5, 0xc43c_9ed1_0f77_efdf},
+        blocks = {5000, 0x9969_d2f4_12fc_e5bf, 0x227d_6e72_e930_9f95},
+    )]
+    fn xxh3_vectors(length: usize, hash: u64, seeded: u64) {
+        let data = scattered(length);
+        assert_eq!(xxh3_64_with_seed(&data, 0), hash);
+        assert_eq!(xxh3_64_with_seed(&data, 42), seeded);
+        assert_eq!(streamed("xxh3", Some(42), &data, 65), seeded.to_be_bytes());
+    }
+
+    #[parameterized(
+        empty = {0, "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"},
+        one = {1, "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213"},
+        short_of_a_chunk = {1023, "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11"},
+        chunk = {1024, "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7"},
+        past_a_chunk = {1025, "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"},
+    )]
+    fn blake3_vectors(length: usize, digest: &str) {
+        let data = counting(length);
+        assert_eq!(hex(::blake3::hash(&data).as_bytes()), digest);
+        assert_eq!(hex(&streamed("blake3", None, &data, 63)), digest);
+    }
+
+    #[test]
+    fn hashers() {
+        let data = b"an attachment, downloaded a part at a time".repeat(100);
+        let mut hasher = Hasher::new("xxh3", Some(7)).unwrap();
+        let mut copied = None;
+        for (index, part) in data.chunks(1000).enumerate() {
+            match &mut hasher.state {
+                State::Xxh3(state) => state.update(part),
+                State::Blake3(_) => unreachable!(),
+            }
+            if index == 0 {
+                copied = Some(hasher.copy());
+            }
+        }
+        assert_eq!(hasher.finish(), xxh3_64_with_seed(&data, 7).to_be_bytes());
+        assert_eq!(hasher.hexdigest(), format!("{:016x}", xxh3_64_with_seed(&data, 7)));
+        assert_eq!(copied.unwrap().finish(), xxh3_64_with_seed(&data[..1000], 7).to_be_bytes());
+        assert_eq!(Hasher::new("blake3", None).unwrap().hexdigest(), "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+        assert_eq!(Hasher::new("blake3", Some(1)).unwrap_err(), "Invalid seed, only xxh3 takes one");
+        assert_eq!(Hasher::new("md5", None).unwrap_err(), r#"Invalid algorithm "md5", it must be "xxh3" or "blake3""#);
+        assert_eq!(Hasher::new("blake3", None).unwrap().__repr__(), r#"Hasher(algorithm="blake3")"#);
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(hamming_distance(0b1011, 0b0110), 3);
+        assert_eq!(hamming_distance(u64::MAX, 0), 64);
+        assert_eq!(hamming_distance(42, 42), 0);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,121 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Perceptual hashes of images, which are alike for images that look alike, even once they're resized,
+//! recompressed or slightly recolored, unlike hashes of their bytes.
+use std::f64::consts::PI;
+
+use image::RgbaImage;
+
+/// How many pixels wide and tall the image is shrunk to.
+const SIZE: usize = 32;
+/// How many of the lowest frequencies across and down make up the hash.
+const FREQUENCIES: usize = 8;
+
+/// The pixels each of `SIZE` cells across `side` pixels covers, and how much of each, adding up to 1.
+fn spans(side: usize) -> Vec<Vec<(u32, f64)>> {
+    let scale = side as f64 / SIZE as f64;
+    (0..SIZE)
+        .map(|cell| {
+            let (start, end) = (cell as f64 * scale, (cell + 1) as f64 * scale);
+            (start.floor() as usize..(end.ceil() as usize).min(side))
+                .map(|pixel| (pixel as u32, (end.min(pixel as f64 + 1.0) - start.max(pixel as f64)) / scale))
+                .collect()
+        })
+        .collect()
+}
+
+/// The brightness of the image, over white where it's transparent, averaged into `SIZE` by `SIZE` cells.
+fn shrink(image: &RgbaImage) -> [[f64; SIZE]; SIZE] {
+    let (columns, rows) = (spans(image.width() as usize), spans(image.height() as usize));
+    let mut cells = [[0.0; SIZE]; SIZE];
+    for (row, pixel_rows) in cells.iter_mut().zip(&rows) {
+        for (cell, pixel_columns) in row.iter_mut().zip(&columns) {
+            for (y, height) in pixel_rows {
+                for (x, width) in pixel_columns {
+                    let [red, green, blue, alpha] = image.get_pixel(*x, *y).0.map(f64::from);
+                    let alpha = alpha / 255.0;
+                    *cell += ((0.299 * red + 0.587 * green + 0.114 * blue) * alpha + 255.0 * (1.0 - alpha)) * width * height;
+                }
+            }
+        }
+    }
+    cells
+}
+
+/// The hash of an image: the lowest frequencies of its brightness, each a bit of whether it's above
+/// their median, from the top left, with the first in the highest bit.
+pub fn perceptual_hash(image: &RgbaImage) -> u64 {
+    if image.width() == 0 || image.height() == 0 {
+        return 0;
+    }
+    let cells = shrink(image);
+    let cosines: Vec<[f64; SIZE]> =
+        (0..FREQUENCIES).map(|frequency| std::array::from_fn(|x| (PI * (2 * x + 1) as f64 * frequency as f64 / (2 * SIZE) as f64).cos())).collect();
+    // the discrete cosine transform of the rows, and then of the columns of that
+    let rows: Vec<[f64; FREQUENCIES]> = cells.iter().map(|row| std::array::from_fn(|u| row.iter().zip(&cosines[u]).map(|(cell, cosine)| cell * cosine).sum())).collect();
+    let mut coefficients = Vec::with_capacity(FREQUENCIES * FREQUENCIES);
+    for cosine in &cosines {
+        for u in 0..FREQUENCIES {
+            coefficients.push(rows.iter().zip(cosine).map(|(row, cosine)| row[u] * cosine).sum::<f64>());
+        }
+    }
+    let mut sorted = coefficients.clone();
+    sorted.sort_by(f64::total_cmp);
+    let median = (sorted[sorted.len() / 2 - 1] + sorted[sorted.len() / 2]) / 2.0;
+    coefficients.iter().fold(0, |hash, coefficient| hash << 1 | u64::from(*coefficient > median))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::hamming_distance;
+    use image::Rgba;
+
+    /// A picture of a bright circle on a dark diagonal gradient, at any size.
+    fn picture(width: u32, height: u32, brightness: i32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            let (u, v) = (f64::from(x) / f64::from(width), f64::from(y) / f64::from(height));
+            let base = if (u - 0.6).hypot(v - 0.4) < 0.25 { 220.0 } else { 100.0 * (u + v) / 2.0 };
+            let value = (base as i32 + brightness).clamp(0, 255) as u8;
+            Rgba([value, value / 2, 255 - value, 255])
+        })
+    }
+
+    #[test]
+    fn alike() {
+        let hash = perceptual_hash(&picture(256, 256, 0));
+        assert_eq!(perceptual_hash(&picture(256, 256, 0)), hash);
+        // resized, stretched and brightened, it still looks the same
+        for (width, height, brightness) in [(100, 100, 0), (64, 64, 0), (400, 300, 0), (256, 256, 20)] {
+            assert!(hamming_distance(perceptual_hash(&picture(width, height, brightness)), hash) <= 4);
+        }
+        // but mirrored it doesn't
+        let picture = picture(256, 256, 0);
+        let mirrored = RgbaImage::from_fn(256, 256, |x, y| *picture.get_pixel(255 - x, y));
+        assert!(hamming_distance(perceptual_hash(&mirrored), hash) > 16);
+    }
+
+    #[test]
+    fn different() {
+        let hash = perceptual_hash(&picture(128, 128, 0));
+        let stripes = RgbaImage::from_fn(128, 128, |x, _| if x / 16 % 2 == 0 { Rgba([0, 0, 0, 255]) } else { Rgba([255, 255, 255, 255]) });
+        assert!(hamming_distance(perceptual_hash(&stripes), hash) > 16);
+        // half the bits are set, less any that are exactly the median
+        assert!((28..=32).contains(&hash.count_ones()));
+    }
+
+    #[test]
+    fn small_and_transparent() {
+        // smaller than the cells it's shrunk to
+        let tiny = perceptual_hash(&picture(8, 8, 0));
+        assert_eq!(tiny, perceptual_hash(&picture(8, 8, 0)));
+        // a transparent image is as if it's white
+        let clear = RgbaImage::from_pixel(64, 64, Rgba([0, 0, 0, 0]));
+        let white = RgbaImage::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+        assert_eq!(perceptual_hash(&clear), perceptual_hash(&white));
+        assert_eq!(perceptual_hash(&RgbaImage::new(0, 0)), 0);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,228 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod banner; // COV_EXCL_LINE
+mod boards;
+mod captcha;
+mod charts;
+mod effects;
+mod font;
+mod mosaic;
+mod rankcard;
+
+// COV_EXCL_START
+use std::io::Cursor;
+
+use image::{imageops::FilterType, io::{Limits, Reader as ImageReader}, DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use pyo3::prelude::*;
+// COV_EXCL_STOP
+
+/// The most pixels wide or tall an uploaded image can be, so a small file can't decode into gigabytes.
+const MAX_SIDE: u32 = 4096;
+/// The most bytes an uploaded image can be, Discord's upload limit without nitro.
+const MAX_BYTES: usize = 8 * 1024 * 1024;
+
+/// Decode an uploaded image, like an avatar or a background, in any format the image crate knows.
+pub(crate) fn decode(bytes: &[u8]) -> Result<RgbaImage, String> {
+    if bytes.len() > MAX_BYTES {
+        return Err(format!("Invalid image, it's {} bytes and can be at most {MAX_BYTES}", bytes.len()));
+    }
+    let mut reader = ImageReader::new(Cursor::new(bytes)).with_guessed_format().map_err(|e| format!("Invalid image: {e}"))?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SIDE);
+    limits.max_image_height = Some(MAX_SIDE);
+    reader.limits(limits);
+    reader.decode().map(DynamicImage::into_rgba8).map_err(|e| format!("Invalid image: {e}"))
+}
+
+/// Encode a generated image as a PNG, to send as an attachment.
+pub(crate) fn encode(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Cursor::new(Vec::new());
+    image.write_to(&mut bytes, ImageOutputFormat::Png).map_err(|e| format!("Failed to encode image: {e}"))?;
+    Ok(bytes.into_inner())
+}
+
+/// `image` resized to fill `width` by `height`, cropping what's left over evenly from both sides.
+pub(crate) fn cover(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image.clone();
+    }
+    DynamicImage::ImageRgba8(image.clone()).resize_to_fill(width, height, FilterType::Triangle).into_rgba8()
+}
+
+/// The color of a `0xRRGGBB` integer, like the ones discord.py uses.
+pub(crate) fn rgb(color: u32) -> Result<Rgba<u8>, String> {
+    if color > 0xff_ffff {
+        return Err(format!("Invalid color {color:#x}, it must be from 0x000000 to 0xffffff"));
+    }
+    let [_, red, green, blue] = color.to_be_bytes();
+    Ok(Rgba([red, green, blue, 0xff]))
+}
+
+/// An image fading from `top` at the top to `bottom` at the bottom.
+pub(crate) fn gradient(width: u32, height: u32, top: Rgba<u8>, bottom: Rgba<u8>) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |_, y| {
+        let amount = f64::from(y) / f64::from(height.saturating_sub(1).max(1));
+        let mut color = top;
+        for channel in 0..4 {
+            let (from, to) = (f64::from(top[channel]), f64::from(bottom[channel]));
+            color[channel] = (from + (to - from) * amount).round() as u8;
+        }
+        color
+    })
+}
+
+/// `color` with its alpha multiplied by `coverage`, from 0 to 1, for anti-aliased edges.
+pub(crate) fn faded(color: Rgba<u8>, coverage: f64) -> Rgba<u8> {
+    let Rgba([red, green, blue, alpha]) = color;
+    Rgba([red, green, blue, (f64::from(alpha) * coverage.clamp(0.0, 1.0)).round() as u8])
+}
+
+/// Draw `color` over the pixel at `x` and `y`, mixing by its alpha, if the pixel is in the image.
+pub(crate) fn blend(image: &mut RgbaImage, x: i64, y: i64, color: Rgba<u8>) {
+    let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+        return;
+    };
+    if x >= image.width() || y >= image.height() || color[3] == 0 {
+        return;
+    }
+    let below = *image.get_pixel(x, y);
+    let (top, bottom) = (f64::from(color[3]) / 255.0, f64::from(below[3]) / 255.0);
+    let alpha = top + bottom * (1.0 - top);
+    let mut mixed = [0; 4];
+    for (channel, value) in mixed.iter_mut().take(3).enumerate() {
+        let (over, under) = (f64::from(color[channel]), f64::from(below[channel]));
+        *value = ((over * top + under * bottom * (1.0 - top)) / alpha).round() as u8;
+    }
+    mixed[3] = (alpha * 255.0).round() as u8;
+    image.put_pixel(x, y, Rgba(mixed));
+}
+
+/// How much of the pixel at `x` and `y` is inside a rectangle `width` by `height` with its corners
+/// rounded by `radius`, from 0 to 1.
+pub(crate) fn rounded_coverage(x: f64, y: f64, width: f64, height: f64, radius: f64) -> f64 {
+    let radius = radius.min(width / 2.0).min(height / 2.0).max(0.0);
+    // the signed distance from the center of the pixel to the edge, negative inside
+    let qx = (x + 0.5 - width / 2.0).abs() - (width / 2.0 - radius);
+    let qy = (y + 0.5 - height / 2.0).abs() - (height / 2.0 - radius);
+    let distance = qx.max(0.0).hypot(qy.max(0.0)) + qx.max(qy).min(0.0) - radius;
+    (0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Fill a rectangle with its top left corner at `x` and `y` and its corners rounded by `radius`.
+pub(crate) fn fill_rounded(image: &mut RgbaImage, (x, y): (i64, i64), (width, height): (u32, u32), radius: u32, color: Rgba<u8>) {
+    for dy in 0..height {
+        for dx in 0..width {
+            let coverage = rounded_coverage(f64::from(dx), f64::from(dy), f64::from(width), f64::from(height), f64::from(radius));
+            if coverage > 0.0 {
+                blend(image, x + i64::from(dx), y + i64::from(dy), faded(color, coverage));
+            }
+        }
+    }
+}
+
+/// Draw `top` over `image` with its top left corner at `x` and `y`, cut to a circle.
+pub(crate) fn paste_circle(image: &mut RgbaImage, top: &RgbaImage, x: i64, y: i64) {
+    let (width, height) = (f64::from(top.width()), f64::from(top.height()));
+    let radius = width.min(height) / 2.0;
+    for (dx, dy, pixel) in top.enumerate_pixels() {
+        let coverage = rounded_coverage(f64::from(dx), f64::from(dy), width, height, radius);
+        if coverage > 0.0 {
+            blend(image, x + i64::from(dx), y + i64::from(dy), faded(*pixel, coverage));
+        }
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based image generation and effects, such as rank cards, level up banners, game boards, charts, emoji mosaics and captchas";
+
+pub(crate) fn register_images(py: Python, m: &PyModule) -> PyResult<()> {
+    let images = PyModule::new(py, "_images")?;
+    images.add_function(wrap_pyfunction!(rankcard::rank_card, images)?)?;
+    #[cfg(feature = "async")]
+    images.add_function(wrap_pyfunction!(rankcard::rank_card_async, images)?)?;
+    images.add_function(wrap_pyfunction!(rankcard::render_many, images)?)?;
+    images.add_function(wrap_pyfunction!(captcha::py_generate, images)?)?;
+    images.add_function(wrap_pyfunction!(banner::levelup, images)?)?;
+    images.add_class::<charts::Theme>()?;
+    images.add_function(wrap_pyfunction!(charts::bar_chart, images)?)?;
+    images.add_function(wrap_pyfunction!(charts::sparkline, images)?)?;
+    images.add_class::<effects::Effect>()?;
+    images.add_function(wrap_pyfunction!(effects::apply_effect, images)?)?;
+    images.add_function(wrap_pyfunction!(boards::tictactoe_board, images)?)?;
+    images.add_function(wrap_pyfunction!(boards::connect4_board, images)?)?;
+    images.add_function(wrap_pyfunction!(boards::minesweeper_board, images)?)?;
+    images.add_function(wrap_pyfunction!(boards::chess_board, images)?)?;
+    images.add_class::<mosaic::Palette>()?;
+    images.add_function(wrap_pyfunction!(mosaic::to_emoji, images)?)?;
+    images.add("__doc__", DOCSTRING)?;
+    m.add_submodule(images)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blending() {
+        let mut image = RgbaImage::from_pixel(2, 1, Rgba([0, 0, 0, 255]));
+        blend(&mut image, 0, 0, Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        blend(&mut image, 1, 0, Rgba([255, 0, 0, 128]));
+        assert_eq!(*image.get_pixel(1, 0), Rgba([128, 0, 0, 255]));
+        // over a transparent pixel the color stays as it is and only the alpha is mixed
+        let mut clear = RgbaImage::new(1, 1);
+        blend(&mut clear, 0, 0, Rgba([0, 255, 0, 128]));
+        assert_eq!(*clear.get_pixel(0, 0), Rgba([0, 255, 0, 128]));
+        // outside the image nothing happens
+        blend(&mut clear, -1, 0, Rgba([255, 255, 255, 255]));
+        blend(&mut clear, 0, 1, Rgba([255, 255, 255, 255]));
+        assert_eq!(*clear.get_pixel(0, 0), Rgba([0, 255, 0, 128]));
+    }
+
+    #[test]
+    fn rounded_rectangles() {
+        let mut image = RgbaImage::new(20, 10);
+        fill_rounded(&mut image, (0, 0), (20, 10), 5, Rgba([255, 255, 255, 255]));
+        assert_eq!(image.get_pixel(10, 5)[3], 255);
+        assert_eq!(image.get_pixel(10, 0)[3], 255);
+        // the corners are cut off, and the pixels along the curve are partly covered
+        assert_eq!(image.get_pixel(0, 0)[3], 0);
+        assert_eq!(image.get_pixel(19, 9)[3], 0);
+        assert!((1..255).contains(&image.get_pixel(1, 1)[3]));
+        // no radius is a plain rectangle
+        let mut square = RgbaImage::new(4, 4);
+        fill_rounded(&mut square, (1, 1), (2, 2), 0, Rgba([255, 0, 0, 255]));
+        assert_eq!(square.pixels().filter(|pixel| pixel[3] == 255).count(), 4);
+        assert_eq!(square.pixels().filter(|pixel| pixel[3] != 0).count(), 4);
+    }
+
+    #[test]
+    fn circles() {
+        let mut image = RgbaImage::new(12, 12);
+        paste_circle(&mut image, &RgbaImage::from_pixel(10, 10, Rgba([0, 0, 255, 255])), 1, 1);
+        assert_eq!(*image.get_pixel(6, 6), Rgba([0, 0, 255, 255]));
+        assert_eq!(*image.get_pixel(6, 2), Rgba([0, 0, 255, 255]));
+        assert_eq!(image.get_pixel(1, 1)[3], 0);
+        assert_eq!(image.get_pixel(0, 6)[3], 0);
+        // already the right size, so nothing is resized
+        let avatar = RgbaImage::from_pixel(3, 3, Rgba([1, 2, 3, 4]));
+        assert_eq!(cover(&avatar, 3, 3), avatar);
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(rgb(0xfcb33f), Ok(Rgba([0xfc, 0xb3, 0x3f, 0xff])));
+        assert_eq!(rgb(0), Ok(Rgba([0, 0, 0, 255])));
+        assert!(rgb(0x100_0000).is_err());
+        let faded = gradient(1, 3, Rgba([0, 0, 0, 255]), Rgba([200, 100, 0, 255]));
+        assert_eq!(faded.pixels().copied().collect::<Vec<_>>(), [Rgba([0, 0, 0, 255]), Rgba([100, 50, 0, 255]), Rgba([200, 100, 0, 255])]);
+    }
+
+    #[test]
+    fn limits() {
+        assert!(decode(&vec![0; MAX_BYTES + 1]).unwrap_err().contains("at most"));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,197 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Banners announcing things in chat, like a member reaching a new level.
+// COV_EXCL_START
+use image::{Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::buffer::Buffer;
+use crate::fluent::translate_text;
+use crate::images::{self, font};
+use crate::metrics;
+// COV_EXCL_STOP
+
+const WIDTH: u32 = 1000;
+const HEIGHT: u32 = 200;
+const BADGE_SIZE: u32 = 150;
+const BADGE_X: i64 = 25;
+const BADGE_Y: i64 = 25;
+/// Where the text starts and ends across, right of the badge.
+const TEXT_LEFT: i64 = 200;
+const TEXT_RIGHT: i64 = 975;
+/// The most room a name gets in the message, so the rest of it still fits.
+const MAX_NAME_WIDTH: u32 = 400;
+
+const TOP_COLOR: Rgba<u8> = Rgba([0x2b, 0x2d, 0x31, 0xff]);
+const BOTTOM_COLOR: Rgba<u8> = Rgba([0x1e, 0x1f, 0x22, 0xff]);
+const MESSAGE_COLOR: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+const LEVELS_COLOR: Rgba<u8> = Rgba([0xb5, 0xba, 0xc1, 0xff]);
+
+/// Everything drawn on a level up banner, with the text already translated.
+#[derive(Clone, Debug, PartialEq)]
+struct LevelUp {
+    title: String,
+    message: String,
+    levels: String,
+    /// The new level, drawn on the badge.
+    level: String,
+    accent: Rgba<u8>,
+}
+
+impl LevelUp {
+    fn render(&self) -> RgbaImage {
+        let mut banner = images::gradient(WIDTH, HEIGHT, TOP_COLOR, BOTTOM_COLOR);
+        // a strip of the accent down the left edge, and the badge with the new level
+        images::fill_rounded(&mut banner, (0, 0), (6, HEIGHT), 0, self.accent);
+        images::fill_rounded(&mut banner, (BADGE_X, BADGE_Y), (BADGE_SIZE, BADGE_SIZE), BADGE_SIZE / 2, self.accent);
+        let scale = (4..=8).rev().find(|scale| font::width(&self.level, *scale) <= BADGE_SIZE - 30).unwrap_or(4);
+        let level = font::fit(&self.level, BADGE_SIZE - 30, scale);
+        let center = i64::from(BADGE_SIZE / 2);
+        let x = BADGE_X + center - i64::from(font::width(&level, scale)) / 2;
+        // the digits are in the middle of the glyph's rows, below the room left for accents
+        let y = BADGE_Y + center - i64::from(scale) * 11 / 2;
+        font::draw(&mut banner, &level, x, y, scale, BOTTOM_COLOR);
+
+        let room = (TEXT_RIGHT - TEXT_LEFT) as u32;
+        for (text, scale, y, color) in [(&self.title, 6, 10, self.accent), (&self.message, 3, 100, MESSAGE_COLOR), (&self.levels, 3, 145, LEVELS_COLOR)] {
+            let text = font::fit(text, room, scale);
+            let x = TEXT_LEFT + i64::from(room - font::width(&text, scale)) / 2;
+            font::draw(&mut banner, &text, x, y, scale, color);
+        }
+        banner
+    }
+}
+
+/// The banner for `username` reaching `new_level`, with its text translated to `locale`.
+fn levelup_banner(username: &str, old_level: u64, new_level: u64, locale: &str, accent: u32) -> Result<LevelUp, String> {
+    if new_level <= old_level {
+        return Err(format!("Invalid levels, the new level {new_level} must be more than the old level {old_level}"));
+    }
+    let accent = images::rgb(accent)?;
+    let username = font::fit(username, MAX_NAME_WIDTH, 3);
+    Ok(LevelUp {
+        title: translate_text(locale, "banner-levelup-title", &[])?,
+        message: translate_text(locale, "banner-levelup-message", &[("username", username), ("level", new_level.to_string())])?,
+        levels: translate_text(locale, "banner-levelup-levels", &[("old", old_level.to_string()), ("new", new_level.to_string())])?,
+        level: new_level.to_string(),
+        accent,
+    })
+}
+
+fn draw_levelup(username: &str, old_level: u64, new_level: u64, locale: &str, accent: u32) -> Result<Vec<u8>, String> {
+    images::encode(&levelup_banner(username, old_level, new_level, locale, accent)?.render())
+}
+
+#[pyfunction]
+#[pyo3(signature = (username, old_level, new_level, locale="en-US", accent=0xfcb33f), text_signature = "
+levelup(username, old_level, new_level, locale='en-US', accent=0xfcb33f, /)
+--
+
+Draw a banner congratulating a member on reaching a new level, to send with the level up message.
+
+The text is translated with the bot's translations, and names too long for the banner are cut short.
+Drawing happens without holding the GIL, so other threads can run meanwhile.
+
+Parameters
+----------
+username : str
+    The member's name.
+old_level : int
+    The level they were.
+new_level : int
+    The level they are now, more than the old level. Levels can be skipped.
+locale : str
+    The locale to translate the text to. The default is 'en-US'.
+accent : int
+    The color of the title and the badge with the new level on it, as a 0xRRGGBB integer. The default
+    is 0xfcb33f.
+
+Returns
+-------
+Buffer
+    The banner, as a 1000 by 200 PNG.
+
+Raises
+------
+ValueError
+    If the new level isn't more than the old level, or the accent isn't a color.
+")]
+pub(crate) fn levelup(py: Python, username: &str, old_level: u64, new_level: u64, locale: &str, accent: u32) -> PyResult<Buffer> {
+    metrics::observe("images.levelup", || {
+        let bytes = py.allow_threads(|| draw_levelup(username, old_level, new_level, locale, accent)).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCENT: Rgba<u8> = Rgba([0xfc, 0xb3, 0x3f, 0xff]);
+
+    fn banner() -> LevelUp {
+        LevelUp {
+            title: String::from("LEVEL UP!"),
+            message: String::from("bluesy is now level 12"),
+            levels: String::from("Level 11 → 12"),
+            level: String::from("12"),
+            accent: ACCENT,
+        }
+    }
+
+    /// The leftmost and rightmost columns from `left` on with a pixel of `color` between `top` and `bottom`.
+    fn span(image: &RgbaImage, left: u32, (top, bottom): (u32, u32), color: Rgba<u8>) -> Option<(u32, u32)> {
+        let columns = (left..image.width()).filter(|x| (top..bottom).any(|y| *image.get_pixel(*x, y) == color)).collect::<Vec<_>>();
+        Some((*columns.first()?, *columns.last()?))
+    }
+
+    #[test]
+    fn layout() {
+        let image = banner().render();
+        assert_eq!(image.dimensions(), (WIDTH, HEIGHT));
+        assert_eq!(*image.get_pixel(2, 100), ACCENT);
+        assert_eq!(*image.get_pixel(BADGE_X as u32 + 10, 100), ACCENT);
+        // the text is centered right of the badge
+        let middle = (TEXT_LEFT + TEXT_RIGHT) as u32 / 2;
+        for (top, bottom, color) in [(10, 80, ACCENT), (100, 130, MESSAGE_COLOR), (145, 175, LEVELS_COLOR)] {
+            let (left, right) = span(&image, TEXT_LEFT as u32 - 20, (top, bottom), color).expect("Missing text");
+            assert!(left >= TEXT_LEFT as u32 && right < TEXT_RIGHT as u32, "{left} {right}");
+            assert!(((left + right) / 2).abs_diff(middle) <= 10, "{left} {right}");
+        }
+        // the level is drawn on the badge, in the middle
+        let (left, right) = span(&image, 0, (BADGE_Y as u32, BADGE_Y as u32 + BADGE_SIZE), BOTTOM_COLOR).expect("Missing level");
+        assert!(((left + right) / 2).abs_diff(BADGE_X as u32 + BADGE_SIZE / 2) <= 3, "{left} {right}");
+    }
+
+    #[test]
+    fn long_text_fits() {
+        let image = LevelUp { message: "w".repeat(200), level: String::from("123456789"), ..banner() }.render();
+        let (left, right) = span(&image, 0, (100, 130), MESSAGE_COLOR).expect("Missing message");
+        assert!(left >= TEXT_LEFT as u32 && right < TEXT_RIGHT as u32);
+        let (left, right) = span(&image, 0, (BADGE_Y as u32, BADGE_Y as u32 + BADGE_SIZE), BOTTOM_COLOR).expect("Missing level");
+        assert!(left > BADGE_X as u32 && right < (BADGE_X as u32 + BADGE_SIZE));
+    }
+
+    #[test]
+    fn accented_text() {
+        let banner = levelup_banner("José", 11, 12, "fr", 0xfcb33f).expect("Failed to make banner");
+        assert_eq!(banner.title, "NIVEAU SUPÉRIEUR!");
+        assert_eq!(banner.message, "José est maintenant niveau 12");
+        assert_eq!(banner.levels, "Niveau 11 → 12");
+        // the accents are drawn, so the text differs from the same letters without them
+        let plain = LevelUp { title: String::from("NIVEAU SUPERIEUR!"), message: banner.message.replace('é', "e"), ..banner.clone() };
+        let (image, plain) = (banner.render(), plain.render());
+        assert_eq!(span(&image, 0, (10, 80), ACCENT), span(&plain, 0, (10, 80), ACCENT));
+        assert_ne!(image, plain);
+    }
+
+    #[test]
+    fn validation() {
+        assert!(draw_levelup("a", 2, 2, "en-US", 0).unwrap_err().contains("more than"));
+        assert!(draw_levelup("a", 3, 2, "en-US", 0).unwrap_err().contains("more than"));
+        assert!(draw_levelup("a", 1, 2, "en-US", 0x100_0000).unwrap_err().contains("color"));
+    }
+}
+// COV_EXCL_STOP
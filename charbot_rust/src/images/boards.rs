@@ -0,0 +1,635 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Pictures of the game boards, to send in place of the emoji boards where images look better.
+// COV_EXCL_START
+use image::{DynamicImage, Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::buffer::Buffer;
+use crate::chess::position::{self, Color, Kind, Position, Square};
+use crate::connect4::{self, board::{Disc, COLUMNS, ROWS}};
+use crate::images::{self, font};
+use crate::metrics;
+use crate::minesweeper;
+use crate::tictactoe::{self, board::{Board as TicTacToeBoard, Index, Piece}};
+// COV_EXCL_STOP
+
+const BACKGROUND: Rgba<u8> = Rgba([0x31, 0x33, 0x38, 0xff]);
+const LABEL_COLOR: Rgba<u8> = Rgba([0xb5, 0xba, 0xc1, 0xff]);
+/// Drawn over the squares of the last move, so players can see what changed.
+const HIGHLIGHT: Rgba<u8> = Rgba([0xfc, 0xb3, 0x3f, 0x60]);
+
+const TICTACTOE_CELL: u32 = 120;
+const TICTACTOE_MARGIN: u32 = 20;
+const TICTACTOE_SIZE: u32 = 3 * TICTACTOE_CELL + 2 * TICTACTOE_MARGIN;
+const GRID_WIDTH: f64 = 6.0;
+const PIECE_WIDTH: f64 = 14.0;
+const X_COLOR: Rgba<u8> = Rgba([0xed, 0x42, 0x45, 0xff]);
+const O_COLOR: Rgba<u8> = Rgba([0x58, 0x65, 0xf2, 0xff]);
+const WIN_COLOR: Rgba<u8> = Rgba([0xf2, 0xf3, 0xf5, 0xd0]);
+
+const CONNECT4_CELL: u32 = 80;
+const CONNECT4_MARGIN: u32 = 10;
+/// The room under the board for the column numbers.
+const CONNECT4_LABELS: u32 = 30;
+const CONNECT4_HOLE: f64 = 32.0;
+const CONNECT4_BOARD: Rgba<u8> = Rgba([0x1e, 0x4d, 0xb7, 0xff]);
+const RED: Rgba<u8> = Rgba([0xed, 0x42, 0x45, 0xff]);
+const YELLOW: Rgba<u8> = Rgba([0xfe, 0xe7, 0x5c, 0xff]);
+const LAST_DISC: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xc0]);
+
+const SQUARE: u32 = 64;
+/// The room left and under the board for the rank and file names.
+const CHESS_LABELS: u32 = 24;
+const CHESS_SIZE: u32 = CHESS_LABELS + 8 * SQUARE;
+const LIGHT_SQUARE: Rgba<u8> = Rgba([0xf0, 0xd9, 0xb5, 0xff]);
+const DARK_SQUARE: Rgba<u8> = Rgba([0xb5, 0x88, 0x63, 0xff]);
+const CHECK: Rgba<u8> = Rgba([0xed, 0x42, 0x45, 0xa0]);
+/// How many pixels across each pixel of a piece sprite is drawn.
+const SPRITE_SCALE: u32 = SQUARE / 16;
+/// How far out the outline around a piece goes, in pixels.
+const OUTLINE: i64 = 2;
+
+/// The pieces as 16 by 16 pixel art, filled in where there's a `#`.
+const PAWN: [&str; 16] = [
+    "................",
+    "................",
+    "................",
+    "......####......",
+    ".....######.....",
+    ".....######.....",
+    "......####......",
+    ".....######.....",
+    "......####......",
+    "......####......",
+    ".....######.....",
+    "....########....",
+    "...##########...",
+    "...##########...",
+    "................",
+    "................",
+];
+const KNIGHT: [&str; 16] = [
+    "................",
+    "................",
+    "......##........",
+    ".....#####......",
+    "....########....",
+    "...##########...",
+    "...####.#####...",
+    "..#####..####...",
+    "..###...#####...",
+    ".......######...",
+    "......######....",
+    ".....#######....",
+    "....########....",
+    "...##########...",
+    "..############..",
+    "................",
+];
+const BISHOP: [&str; 16] = [
+    "................",
+    ".......##.......",
+    "......####......",
+    ".....######.....",
+    ".....###.##.....",
+    ".....##.###.....",
+    ".....######.....",
+    "......####......",
+    ".......##.......",
+    "......####......",
+    ".....######.....",
+    "......####......",
+    "....########....",
+    "...##########...",
+    "..############..",
+    "................",
+];
+const ROOK: [&str; 16] = [
+    "................",
+    "................",
+    "...##..##..##...",
+    "...##########...",
+    "...##########...",
+    "....########....",
+    ".....######.....",
+    ".....######.....",
+    ".....######.....",
+    ".....######.....",
+    ".....######.....",
+    "....########....",
+    "...##########...",
+    "..############..",
+    "..############..",
+    "................",
+];
+const QUEEN: [&str; 16] = [
+    "................",
+    "..#....##....#..",
+    "..##..####..##..",
+    "..###.####.###..",
+    "..############..",
+    "...##########...",
+    "...##########...",
+    "....########....",
+    ".....######.....",
+    ".....######.....",
+    "....########....",
+    "...##########...",
+    "...##########...",
+    "..############..",
+    "..############..",
+    "................",
+];
+const KING: [&str; 16] = [
+    "................",
+    ".......##.......",
+    "......####......",
+    ".......##.......",
+    "..###..##..###..",
+    ".#####.##.#####.",
+    ".##############.",
+    ".##############.",
+    "..############..",
+    "...##########...",
+    "....########....",
+    "....########....",
+    "...##########...",
+    "..############..",
+    "..############..",
+    "................",
+];
+
+/// How much of the pixel at `x` and `y` is within `width / 2` of the line from `from` to `to`.
+fn line_coverage(x: f64, y: f64, from: (f64, f64), to: (f64, f64), width: f64) -> f64 {
+    let (px, py) = (x + 0.5 - from.0, y + 0.5 - from.1);
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let along = ((px * dx + py * dy) / (dx * dx + dy * dy)).clamp(0.0, 1.0);
+    let distance = (px - along * dx).hypot(py - along * dy);
+    (width / 2.0 + 0.5 - distance).clamp(0.0, 1.0)
+}
+
+/// Draw a line `width` across with round ends from `from` to `to`.
+fn stroke(image: &mut RgbaImage, from: (f64, f64), to: (f64, f64), width: f64, color: Rgba<u8>) {
+    let reach = width / 2.0 + 1.0;
+    let left = (from.0.min(to.0) - reach).floor() as i64;
+    let right = (from.0.max(to.0) + reach).ceil() as i64;
+    let top = (from.1.min(to.1) - reach).floor() as i64;
+    let bottom = (from.1.max(to.1) + reach).ceil() as i64;
+    for y in top..=bottom {
+        for x in left..=right {
+            let coverage = line_coverage(x as f64, y as f64, from, to, width);
+            if coverage > 0.0 {
+                images::blend(image, x, y, images::faded(color, coverage));
+            }
+        }
+    }
+}
+
+/// Draw a ring `width` across around `center`, with its middle `radius` out.
+fn ring(image: &mut RgbaImage, (cx, cy): (f64, f64), radius: f64, width: f64, color: Rgba<u8>) {
+    let reach = radius + width / 2.0 + 1.0;
+    for y in (cy - reach).floor() as i64..=(cy + reach).ceil() as i64 {
+        for x in (cx - reach).floor() as i64..=(cx + reach).ceil() as i64 {
+            let distance = (x as f64 + 0.5 - cx).hypot(y as f64 + 0.5 - cy);
+            let coverage = (width / 2.0 + 0.5 - (distance - radius).abs()).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                images::blend(image, x, y, images::faded(color, coverage));
+            }
+        }
+    }
+}
+
+/// Fill a circle `radius` across around `center`.
+fn disc(image: &mut RgbaImage, (cx, cy): (f64, f64), radius: f64, color: Rgba<u8>) {
+    let size = (radius * 2.0).round() as u32;
+    images::fill_rounded(image, ((cx - radius).round() as i64, (cy - radius).round() as i64), (size, size), size / 2, color);
+}
+
+/// The middle of the tic-tac-toe cell at `index`.
+fn tictactoe_center(index: Index) -> (f64, f64) {
+    let (column, row) = ((index % 3) as u32, (index / 3) as u32);
+    let corner = |cell: u32| f64::from(TICTACTOE_MARGIN + cell * TICTACTOE_CELL + TICTACTOE_CELL / 2);
+    (corner(column), corner(row))
+}
+
+fn render_tictactoe(board: &TicTacToeBoard, last_move: Option<Index>) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(TICTACTOE_SIZE, TICTACTOE_SIZE, BACKGROUND);
+    if let Some(index) = last_move {
+        let (cx, cy) = tictactoe_center(index);
+        let half = f64::from(TICTACTOE_CELL / 2) - GRID_WIDTH;
+        let size = (half * 2.0) as u32;
+        images::fill_rounded(&mut image, ((cx - half) as i64, (cy - half) as i64), (size, size), 8, HIGHLIGHT);
+    }
+    let (start, end) = (f64::from(TICTACTOE_MARGIN), f64::from(TICTACTOE_SIZE - TICTACTOE_MARGIN));
+    for line in 1..3 {
+        let at = f64::from(TICTACTOE_MARGIN + line * TICTACTOE_CELL);
+        stroke(&mut image, (at, start), (at, end), GRID_WIDTH, LABEL_COLOR);
+        stroke(&mut image, (start, at), (end, at), GRID_WIDTH, LABEL_COLOR);
+    }
+    // the pieces fill the cell but for a gap, so they don't touch the grid
+    let reach = f64::from(TICTACTOE_CELL) * 0.3;
+    for (index, piece) in board.board.iter().enumerate() {
+        let (cx, cy) = tictactoe_center(index);
+        match piece {
+            Piece::X => {
+                stroke(&mut image, (cx - reach, cy - reach), (cx + reach, cy + reach), PIECE_WIDTH, X_COLOR);
+                stroke(&mut image, (cx - reach, cy + reach), (cx + reach, cy - reach), PIECE_WIDTH, X_COLOR);
+            }
+            Piece::O => ring(&mut image, (cx, cy), reach, PIECE_WIDTH, O_COLOR),
+            Piece::Empty => {}
+        }
+    }
+    if let Some((first, _, last)) = board.winning_line() {
+        let ((x1, y1), (x2, y2)) = (tictactoe_center(first), tictactoe_center(last));
+        // past the middle of the end cells a little, so the line reads as going through them
+        let (dx, dy) = ((x2 - x1) * 0.15, (y2 - y1) * 0.15);
+        stroke(&mut image, (x1 - dx, y1 - dy), (x2 + dx, y2 + dy), 10.0, WIN_COLOR);
+    }
+    image
+}
+
+/// The middle of the connect four cell at `column` and `row`, with row 0 at the bottom.
+fn connect4_center(column: usize, row: usize) -> (f64, f64) {
+    let x = CONNECT4_MARGIN + column as u32 * CONNECT4_CELL + CONNECT4_CELL / 2;
+    let y = CONNECT4_MARGIN + (ROWS - 1 - row) as u32 * CONNECT4_CELL + CONNECT4_CELL / 2;
+    (f64::from(x), f64::from(y))
+}
+
+fn render_connect4(cells: &[[Option<Disc>; COLUMNS]; ROWS], last_move: Option<usize>) -> RgbaImage {
+    let (width, height) = (COLUMNS as u32 * CONNECT4_CELL, ROWS as u32 * CONNECT4_CELL);
+    let mut image = RgbaImage::from_pixel(width + 2 * CONNECT4_MARGIN, height + 2 * CONNECT4_MARGIN + CONNECT4_LABELS, BACKGROUND);
+    images::fill_rounded(&mut image, (i64::from(CONNECT4_MARGIN), i64::from(CONNECT4_MARGIN)), (width, height), 20, CONNECT4_BOARD);
+    for (row, discs) in cells.iter().enumerate() {
+        for (column, cell) in discs.iter().enumerate() {
+            let color = match cell {
+                Some(Disc::Red) => RED,
+                Some(Disc::Yellow) => YELLOW,
+                None => BACKGROUND,
+            };
+            disc(&mut image, connect4_center(column, row), CONNECT4_HOLE, color);
+        }
+    }
+    // the last disc dropped is the highest one in its column
+    if let Some(column) = last_move {
+        if let Some(row) = (0..ROWS).rev().find(|row| cells[*row][column].is_some()) {
+            ring(&mut image, connect4_center(column, row), CONNECT4_HOLE * 0.7, 4.0, LAST_DISC);
+        }
+    }
+    let y = i64::from(CONNECT4_MARGIN * 2 + height) + 4;
+    for column in 0..COLUMNS {
+        let label = (column + 1).to_string();
+        let (cx, _) = connect4_center(column, 0);
+        font::draw(&mut image, &label, cx as i64 - i64::from(font::width(&label, 2)) / 2, y, 2, LABEL_COLOR);
+    }
+    image
+}
+
+fn sprite(kind: Kind) -> &'static [&'static str; 16] {
+    match kind {
+        Kind::Pawn => &PAWN,
+        Kind::Knight => &KNIGHT,
+        Kind::Bishop => &BISHOP,
+        Kind::Rook => &ROOK,
+        Kind::Queen => &QUEEN,
+        Kind::King => &KING,
+    }
+}
+
+/// Whether the pixel at `x` and `y` of a square is part of the piece drawn with `sprite`.
+fn in_sprite(sprite: &[&str; 16], x: i64, y: i64) -> bool {
+    let size = i64::from(SQUARE);
+    (0..size).contains(&x) && (0..size).contains(&y) && sprite[(y / i64::from(SPRITE_SCALE)) as usize].as_bytes()[(x / i64::from(SPRITE_SCALE)) as usize] == b'#'
+}
+
+/// Draw a piece on the square with its top left corner at `left` and `top`, outlined so it shows
+/// up on either color of square.
+fn draw_piece(image: &mut RgbaImage, (left, top): (i64, i64), color: Color, kind: Kind) {
+    let (fill, outline) = match color {
+        Color::White => (Rgba([0xff, 0xff, 0xff, 0xff]), Rgba([0x20, 0x20, 0x20, 0xff])),
+        Color::Black => (Rgba([0x20, 0x20, 0x20, 0xff]), Rgba([0xe0, 0xe0, 0xe0, 0xff])),
+    };
+    let sprite = sprite(kind);
+    for y in 0..i64::from(SQUARE) {
+        for x in 0..i64::from(SQUARE) {
+            if in_sprite(sprite, x, y) {
+                images::blend(image, left + x, top + y, fill);
+            } else if (-OUTLINE..=OUTLINE).any(|dy| (-OUTLINE..=OUTLINE).any(|dx| in_sprite(sprite, x + dx, y + dy))) {
+                images::blend(image, left + x, top + y, outline);
+            }
+        }
+    }
+}
+
+/// The top left corner of `square` on the picture, with white at the bottom unless `flipped`.
+fn square_corner(square: Square, flipped: bool) -> (i64, i64) {
+    let (file, rank) = (position::file(square), position::rank(square));
+    let (column, row) = if flipped { (7 - file, rank) } else { (file, 7 - rank) };
+    (i64::from(CHESS_LABELS + column as u32 * SQUARE), i64::from(row as u32 * SQUARE))
+}
+
+/// Read the squares a move in UCI, like `e2e4`, went from and to. The move has already been made,
+/// so it isn't checked against the position.
+fn parse_last_move(text: &str) -> Result<(Square, Square), String> {
+    let squares = (text.len() == 4 || text.len() == 5) && text.is_ascii();
+    match (squares.then(|| position::parse_square(&text[..2])).flatten(), squares.then(|| position::parse_square(&text[2..4])).flatten()) {
+        (Some(from), Some(to)) => Ok((from, to)),
+        _ => Err(format!("Invalid last move {text:?}, it must be in UCI, like 'e2e4'")),
+    }
+}
+
+fn render_chess(position: &Position, last_move: Option<(Square, Square)>, flipped: bool) -> RgbaImage {
+    let mut image = RgbaImage::from_pixel(CHESS_SIZE, CHESS_SIZE, BACKGROUND);
+    for square in 0..64 {
+        let (left, top) = square_corner(square, flipped);
+        let light = (position::file(square) + position::rank(square)) % 2 == 1;
+        images::fill_rounded(&mut image, (left, top), (SQUARE, SQUARE), 0, if light { LIGHT_SQUARE } else { DARK_SQUARE });
+        if last_move.is_some_and(|(from, to)| square == from || square == to) {
+            images::fill_rounded(&mut image, (left, top), (SQUARE, SQUARE), 0, HIGHLIGHT);
+        }
+        if let Some(piece) = position.piece_at(square) {
+            if piece.kind == Kind::King && piece.color == position.turn && position.in_check(piece.color) {
+                images::fill_rounded(&mut image, (left, top), (SQUARE, SQUARE), SQUARE / 2, CHECK);
+            }
+            draw_piece(&mut image, (left, top), piece.color, piece.kind);
+        }
+    }
+    for line in 0..8 {
+        let (column, row) = if flipped { (7 - line, line) } else { (line, 7 - line) };
+        let file = ((b'a' + line as u8) as char).to_string();
+        let x = i64::from(CHESS_LABELS + column * SQUARE + SQUARE / 2) - i64::from(font::width(&file, 2)) / 2;
+        font::draw(&mut image, &file, x, i64::from(8 * SQUARE) + 2, 2, LABEL_COLOR);
+        let rank = (line + 1).to_string();
+        let y = i64::from(row * SQUARE + SQUARE / 2) - 11;
+        font::draw(&mut image, &rank, i64::from(CHESS_LABELS) / 2 - i64::from(font::width(&rank, 2)) / 2, y, 2, LABEL_COLOR);
+    }
+    image
+}
+
+#[pyfunction]
+#[pyo3(signature = (game, last_move=None), text_signature = "
+tictactoe_board(game, last_move=None, /)
+--
+
+Draw a tic-tac-toe game's board, with a line through the winning pieces if someone has won.
+
+Parameters
+----------
+game : tictactoe.Game
+    The game to draw.
+last_move : int | None
+    The index of the cell last played in, from 0 to 8 across then down, to highlight it.
+
+Returns
+-------
+Buffer
+    The board, as a 400 by 400 PNG.
+
+Raises
+------
+ValueError
+    If the last move isn't a cell.
+")]
+pub(crate) fn tictactoe_board(py: Python, game: PyRef<tictactoe::Game>, last_move: Option<Index>) -> PyResult<Buffer> {
+    metrics::observe("images.tictactoe_board", || {
+        if last_move.is_some_and(|index| !TicTacToeBoard::is_valid_index(index)) {
+            return Err(PyValueError::new_err("Invalid last move, it must be from 0 to 8"));
+        }
+        let board = &game.board;
+        let bytes = py.allow_threads(|| images::encode(&render_tictactoe(board, last_move))).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (game, last_move=None), text_signature = "
+connect4_board(game, last_move=None, /)
+--
+
+Draw a connect four game's board, with the column numbers under it.
+
+Parameters
+----------
+game : connect4.Game
+    The game to draw.
+last_move : int | None
+    The column the last disc was dropped in, from 0 to 6, to mark that disc.
+
+Returns
+-------
+Buffer
+    The board, as a 580 by 530 PNG.
+
+Raises
+------
+ValueError
+    If the last move isn't a column.
+")]
+pub(crate) fn connect4_board(py: Python, game: PyRef<connect4::Game>, last_move: Option<usize>) -> PyResult<Buffer> {
+    metrics::observe("images.connect4_board", || {
+        if last_move.is_some_and(|column| column >= COLUMNS) {
+            return Err(PyValueError::new_err("Invalid last move, it must be from 0 to 6"));
+        }
+        let cells = game.board.cells;
+        let bytes = py.allow_threads(|| images::encode(&render_connect4(&cells, last_move))).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+minesweeper_board(game, /)
+--
+
+Draw a minesweeper game's field, with the selected cell's row and column outlined.
+
+This is the image from ``Game.draw``, as a PNG instead of raw pixels.
+
+Parameters
+----------
+game : minesweeper.Game
+    The game to draw.
+
+Returns
+-------
+Buffer
+    The field, as a PNG.
+")]
+pub(crate) fn minesweeper_board(py: Python, game: PyRef<minesweeper::game::Game>) -> PyResult<Buffer> {
+    metrics::observe("images.minesweeper_board", || {
+        let field = &game.field;
+        let bytes = py.allow_threads(|| images::encode(&DynamicImage::ImageRgb8(field.draw()).to_rgba8())).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (fen, last_move=None, flipped=false), text_signature = "
+chess_board(fen, last_move=None, flipped=False, /)
+--
+
+Draw a chess position, with the rank and file names around the board.
+
+The king of the side to move is marked if it's in check.
+
+Parameters
+----------
+fen : str
+    The position, in FEN.
+last_move : str | None
+    The move that led to the position in UCI, like 'e2e4', to highlight the squares it went from and
+    to.
+flipped : bool
+    Whether to draw the board from black's side, with the eighth rank at the bottom.
+
+Returns
+-------
+Buffer
+    The board, as a 536 by 536 PNG.
+
+Raises
+------
+ValueError
+    If the FEN isn't a valid position, or the last move isn't in UCI.
+")]
+pub(crate) fn chess_board(py: Python, fen: &str, last_move: Option<&str>, flipped: bool) -> PyResult<Buffer> {
+    metrics::observe("images.chess_board", || {
+        let draw = || -> Result<Vec<u8>, String> {
+            let position = Position::from_fen(fen)?;
+            let last_move = last_move.map(parse_last_move).transpose()?;
+            images::encode(&render_chess(&position, last_move, flipped))
+        };
+        let bytes = py.allow_threads(draw).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+
+    fn tictactoe(pieces: &str) -> TicTacToeBoard {
+        let mut board = TicTacToeBoard::new();
+        for (index, letter) in pieces.chars().enumerate() {
+            match letter {
+                'X' => assert!(board.place_piece(index, Piece::X)),
+                'O' => assert!(board.place_piece(index, Piece::O)),
+                _ => {}
+            }
+        }
+        board
+    }
+
+    fn pixel(image: &RgbaImage, (x, y): (f64, f64)) -> Rgba<u8> {
+        *image.get_pixel(x as u32, y as u32)
+    }
+
+    #[test]
+    fn tictactoe_pieces() {
+        let image = render_tictactoe(&tictactoe("X...O...."), Some(4));
+        assert_eq!(image.dimensions(), (TICTACTOE_SIZE, TICTACTOE_SIZE));
+        // the cross goes through the middle of its cell, the ring leaves it empty
+        assert_eq!(pixel(&image, tictactoe_center(0)), X_COLOR);
+        let (cx, cy) = tictactoe_center(4);
+        assert_eq!(pixel(&image, (cx + f64::from(TICTACTOE_CELL) * 0.3, cy)), O_COLOR);
+        // the last move is highlighted, the other empty cells aren't
+        assert_ne!(pixel(&image, (cx, cy)), BACKGROUND);
+        assert_eq!(pixel(&image, tictactoe_center(8)), BACKGROUND);
+        // the grid is between the cells
+        let line = f64::from(TICTACTOE_MARGIN + TICTACTOE_CELL);
+        assert_eq!(pixel(&image, (line, cy + 20.0)), LABEL_COLOR);
+    }
+
+    #[test]
+    fn tictactoe_winner() {
+        let image = render_tictactoe(&tictactoe("XXXOO...."), None);
+        // the line through the winning row crosses the grid between them
+        let (_, cy) = tictactoe_center(0);
+        let line = f64::from(TICTACTOE_MARGIN + TICTACTOE_CELL);
+        assert_ne!(pixel(&image, (line, cy)), LABEL_COLOR);
+        assert_eq!(pixel(&image, (line, cy + 20.0)), LABEL_COLOR);
+        let image = render_tictactoe(&tictactoe("XX.OO...."), None);
+        assert_eq!(pixel(&image, (line, cy)), LABEL_COLOR);
+    }
+
+    #[test]
+    fn connect4_discs() {
+        let mut cells = [[None; COLUMNS]; ROWS];
+        cells[0][3] = Some(Disc::Red);
+        cells[1][3] = Some(Disc::Yellow);
+        let image = render_connect4(&cells, Some(3));
+        assert_eq!(image.dimensions(), (580, 530));
+        assert_eq!(pixel(&image, connect4_center(3, 0)), RED);
+        // the last disc has a ring around its middle
+        let (cx, cy) = connect4_center(3, 1);
+        assert_eq!(pixel(&image, (cx, cy)), YELLOW);
+        assert_ne!(pixel(&image, (cx + CONNECT4_HOLE * 0.7, cy)), YELLOW);
+        assert_eq!(pixel(&image, connect4_center(0, 0)), BACKGROUND);
+        // between the holes is the board
+        let (cx, cy) = connect4_center(0, 0);
+        assert_eq!(pixel(&image, (cx + f64::from(CONNECT4_CELL) / 2.0, cy)), CONNECT4_BOARD);
+        // nothing is marked for an empty column
+        let image = render_connect4(&cells, Some(0));
+        let (cx, cy) = connect4_center(3, 1);
+        assert_eq!(pixel(&image, (cx + CONNECT4_HOLE * 0.7, cy)), YELLOW);
+    }
+
+    #[test]
+    fn chess_squares() {
+        let position = Position::from_fen(START).expect("Valid FEN");
+        let e2 = position::parse_square("e2").unwrap();
+        let e4 = position::parse_square("e4").unwrap();
+        let image = render_chess(&position, Some((e2, e4)), false);
+        assert_eq!(image.dimensions(), (CHESS_SIZE, CHESS_SIZE));
+        // a1 is dark and at the bottom left, h1 light
+        let (left, top) = square_corner(0, false);
+        assert_eq!((left, top), (i64::from(CHESS_LABELS), i64::from(7 * SQUARE)));
+        assert_eq!(*image.get_pixel(left as u32 + 1, top as u32 + 1), DARK_SQUARE);
+        let (left, top) = square_corner(7, false);
+        assert_eq!(*image.get_pixel(left as u32 + 1, top as u32 + 1), LIGHT_SQUARE);
+        // the squares of the last move are highlighted
+        let (left, top) = square_corner(e2, false);
+        assert_ne!(*image.get_pixel(left as u32 + 1, top as u32 + 1), LIGHT_SQUARE);
+        assert_ne!(*image.get_pixel(left as u32 + 1, top as u32 + 1), DARK_SQUARE);
+        // flipped, a1 is at the top right
+        assert_eq!(square_corner(0, true), (i64::from(CHESS_LABELS + 7 * SQUARE), 0));
+    }
+
+    #[test]
+    fn chess_pieces() {
+        let position = Position::from_fen(START).expect("Valid FEN");
+        let image = render_chess(&position, None, false);
+        // the middle of the base of a white piece is white, and of a black one is dark
+        let (left, top) = square_corner(position::parse_square("a1").unwrap(), false);
+        assert_eq!(*image.get_pixel(left as u32 + SQUARE / 2, top as u32 + 13 * SPRITE_SCALE), Rgba([0xff, 0xff, 0xff, 0xff]));
+        let (left, top) = square_corner(position::parse_square("a8").unwrap(), false);
+        assert_eq!(*image.get_pixel(left as u32 + SQUARE / 2, top as u32 + 13 * SPRITE_SCALE), Rgba([0x20, 0x20, 0x20, 0xff]));
+        // empty squares are left plain
+        let (left, top) = square_corner(position::parse_square("d5").unwrap(), false);
+        assert!((0..SQUARE).all(|dy| (0..SQUARE).all(|dx| *image.get_pixel(left as u32 + dx, top as u32 + dy) == LIGHT_SQUARE)));
+    }
+
+    #[test]
+    fn chess_check() {
+        let position = Position::from_fen("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1").expect("Valid FEN");
+        let image = render_chess(&position, None, false);
+        let (left, top) = square_corner(position::parse_square("e8").unwrap(), false);
+        // the corner of the square isn't marked, but next to the king is
+        assert_eq!(*image.get_pixel(left as u32, top as u32), LIGHT_SQUARE);
+        assert_ne!(*image.get_pixel(left as u32 + 4, top as u32 + SQUARE / 2), LIGHT_SQUARE);
+    }
+
+    #[test]
+    fn last_moves() {
+        assert_eq!(parse_last_move("e2e4"), Ok((12, 28)));
+        assert_eq!(parse_last_move("e7e8q"), Ok((52, 60)));
+        for text in ["", "e2", "e2e9", "e2e4e5", "é2e4"] {
+            assert!(parse_last_move(text).unwrap_err().contains("UCI"), "{text}");
+        }
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,266 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Captchas, images of a few letters distorted so they're easy to read for people and hard for bots.
+//!
+//! Each letter is drawn at its own size, weight, slant and angle, the lines through them are the
+//! colors of the letters so they can't be filtered out by color, and the whole image is warped in
+//! waves so the letters can't be cut apart along straight lines.
+// COV_EXCL_START
+use std::f64::consts::TAU;
+
+use image::{Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::buffer::Buffer;
+use crate::images::{self, font};
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// The letters a captcha can have, without ones easily mistaken for each other like 0 and O or 1 and I.
+const ALPHABET: [char; 32] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'Q', 'R',
+    'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '2', '3', '4', '5', '6', '7', '8', '9',
+];
+const MIN_LENGTH: usize = 4;
+const MAX_LENGTH: usize = 10;
+/// How much room each letter gets across.
+const CELL: u32 = 50;
+/// The space left and right of the letters.
+const PADDING: u32 = 20;
+const HEIGHT: u32 = 100;
+
+/// How hard a captcha is to read, from the difficulty passed in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Level {
+    /// The most a letter is turned either way, in radians.
+    angle: f64,
+    /// The most a letter is slanted either way, in pixels across for each pixel down.
+    slant: f64,
+    /// How far letters can be moved from their place, in pixels.
+    jitter: i64,
+    /// How many pixels the waves move the image by at most.
+    wave: f64,
+    lines: u32,
+    /// How many of the pixels get a speck of noise.
+    specks: f64,
+}
+
+impl Level {
+    fn new(difficulty: u8) -> Result<Self, String> {
+        match difficulty {
+            1 => Ok(Level { angle: 0.15, slant: 0.1, jitter: 3, wave: 2.0, lines: 3, specks: 0.01 }),
+            2 => Ok(Level { angle: 0.3, slant: 0.2, jitter: 6, wave: 4.0, lines: 6, specks: 0.03 }),
+            3 => Ok(Level { angle: 0.45, slant: 0.3, jitter: 9, wave: 6.0, lines: 10, specks: 0.06 }),
+            _ => Err(format!("Invalid difficulty {difficulty}, it must be 1, 2 or 3")),
+        }
+    }
+}
+
+fn light(rng: &mut StdRng) -> Rgba<u8> {
+    Rgba([rng.gen_range(215..=255), rng.gen_range(215..=255), rng.gen_range(215..=255), 255])
+}
+
+fn dark(rng: &mut StdRng) -> Rgba<u8> {
+    Rgba([rng.gen_range(10..=120), rng.gen_range(10..=120), rng.gen_range(10..=120), 255])
+}
+
+/// Draw `letter` turned by `angle` and slanted by `slant`, centered on `x` and `y`.
+fn draw_letter(image: &mut RgbaImage, letter: char, (x, y): (i64, i64), (angle, slant): (f64, f64), rng: &mut StdRng) {
+    let scale = rng.gen_range(6..=8);
+    let text = letter.to_string();
+    let mut glyph = RgbaImage::new(font::width(&text, scale) + 1, font::HEIGHT * scale);
+    let color = dark(rng);
+    font::draw(&mut glyph, &text, 0, 0, scale, color);
+    if rng.gen_bool(0.5) {
+        // bold, by drawing it again a pixel over
+        font::draw(&mut glyph, &text, 1, 0, scale, color);
+    }
+    // the glyph has room for accents above the letter, so its middle is lower than the middle of the glyph
+    let center = (f64::from(glyph.width()) / 2.0, f64::from(scale) * 5.5);
+    let reach = f64::from(glyph.width()).hypot(f64::from(glyph.height())).ceil() as i64;
+    let (sin, cos) = angle.sin_cos();
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            // where in the glyph this pixel comes from, turning and slanting back
+            let (turned_x, turned_y) = (dx as f64 * cos + dy as f64 * sin, -(dx as f64) * sin + dy as f64 * cos);
+            let (source_x, source_y) = (turned_x - turned_y * slant + center.0, turned_y + center.1);
+            if source_x < 0.0 || source_y < 0.0 || source_x >= f64::from(glyph.width()) || source_y >= f64::from(glyph.height()) {
+                continue;
+            }
+            let pixel = *glyph.get_pixel(source_x as u32, source_y as u32);
+            images::blend(image, x + dx, y + dy, pixel);
+        }
+    }
+}
+
+/// Draw a curved line across the image, through a random point in the middle.
+fn draw_line(image: &mut RgbaImage, rng: &mut StdRng) {
+    let (width, height) = (f64::from(image.width()), f64::from(image.height()));
+    let start = (rng.gen_range(0.0..width * 0.3), rng.gen_range(0.0..height));
+    let end = (rng.gen_range(width * 0.7..width), rng.gen_range(0.0..height));
+    let control = (rng.gen_range(0.0..width), rng.gen_range(0.0..height));
+    let thickness = rng.gen_range(1..=3);
+    let color = dark(rng);
+    let steps = (width * 2.0) as u32;
+    for step in 0..=steps {
+        let t = f64::from(step) / f64::from(steps);
+        let point = |(a, b, c): (f64, f64, f64)| (1.0 - t) * (1.0 - t) * a + 2.0 * (1.0 - t) * t * b + t * t * c;
+        let (x, y) = (point((start.0, control.0, end.0)) as i64, point((start.1, control.1, end.1)) as i64);
+        for dy in 0..thickness {
+            for dx in 0..thickness {
+                images::blend(image, x + dx, y + dy, color);
+            }
+        }
+    }
+}
+
+/// `image` warped by a wave across and one down, each moving pixels by up to `amount`.
+fn warp(image: &RgbaImage, amount: f64, background: Rgba<u8>, rng: &mut StdRng) -> RgbaImage {
+    let (across, down) = (rng.gen_range(40.0..80.0), rng.gen_range(60.0..120.0));
+    let (phase_x, phase_y) = (rng.gen_range(0.0..TAU), rng.gen_range(0.0..TAU));
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let source_x = f64::from(x) + amount * (TAU * f64::from(y) / across + phase_x).sin();
+        let source_y = f64::from(y) + amount * (TAU * f64::from(x) / down + phase_y).sin();
+        if source_x < 0.0 || source_y < 0.0 || source_x >= f64::from(image.width()) || source_y >= f64::from(image.height()) {
+            return background;
+        }
+        *image.get_pixel(source_x as u32, source_y as u32)
+    })
+}
+
+/// The answer to a new captcha, and the captcha.
+fn generate(length: usize, difficulty: u8, rng: &mut StdRng) -> Result<(String, RgbaImage), String> {
+    if !(MIN_LENGTH..=MAX_LENGTH).contains(&length) {
+        return Err(format!("Invalid length {length}, it must be from {MIN_LENGTH} to {MAX_LENGTH}"));
+    }
+    let level = Level::new(difficulty)?;
+    let answer = (0..length).map(|_| *ALPHABET.choose(rng).unwrap_or(&'A')).collect::<String>();
+    let background = light(rng);
+    let mut image = RgbaImage::from_pixel(2 * PADDING + CELL * length as u32, HEIGHT, background);
+    for (index, letter) in answer.chars().enumerate() {
+        let x = i64::from(PADDING + CELL * index as u32 + CELL / 2) + rng.gen_range(-level.jitter..=level.jitter);
+        let y = i64::from(HEIGHT / 2) + rng.gen_range(-level.jitter..=level.jitter);
+        let shape = (rng.gen_range(-level.angle..=level.angle), rng.gen_range(-level.slant..=level.slant));
+        draw_letter(&mut image, letter, (x, y), shape, rng);
+    }
+    for _ in 0..level.lines {
+        draw_line(&mut image, rng);
+    }
+    let mut image = warp(&image, level.wave, background, rng);
+    let specks = (f64::from(image.width() * image.height()) * level.specks) as u32;
+    for _ in 0..specks {
+        let (x, y) = (rng.gen_range(0..image.width()), rng.gen_range(0..image.height()));
+        let color = if rng.gen_bool(0.5) { dark(rng) } else { light(rng) };
+        image.put_pixel(x, y, color);
+    }
+    Ok((answer, image))
+}
+
+#[pyfunction]
+#[pyo3(name = "captcha", signature = (length=6, difficulty=2, seed=None), text_signature = "
+captcha(length=6, difficulty=2, seed=None, /)
+--
+
+Make a captcha, an image of letters and digits for someone to type in to show they're not a bot.
+
+The letters are uppercase, without ones that are easily mixed up like O and 0, so answers can be
+compared ignoring case. Drawing happens without holding the GIL, so other threads can run meanwhile.
+
+Parameters
+----------
+length : int
+    How many letters to draw, from 4 to 10. The default is 6.
+difficulty : int
+    How distorted the letters are and how much noise is drawn over them, 1 for easy, 2 for medium
+    and 3 for hard. The default is 2.
+seed : int | None
+    A seed to make the same captcha again, such as for tests. The default is None, a random one.
+
+Returns
+-------
+tuple[str, Buffer]
+    The answer, and the captcha as a PNG.
+
+Raises
+------
+ValueError
+    If the length or the difficulty is out of range.
+")]
+pub(crate) fn py_generate(py: Python, length: usize, difficulty: u8, seed: Option<u64>) -> PyResult<(String, Buffer)> {
+    metrics::observe("images.captcha", || {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        let (answer, bytes) = py
+            .allow_threads(|| generate(length, difficulty, &mut rng).and_then(|(answer, image)| Ok((answer, images::encode(&image)?))))
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok((answer, Buffer::from(bytes)))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captcha(length: usize, difficulty: u8, seed: u64) -> (String, RgbaImage) {
+        generate(length, difficulty, &mut StdRng::seed_from_u64(seed)).expect("Failed to generate captcha")
+    }
+
+    /// How many pixels of `image` are darker than the background could be.
+    fn dark_pixels(image: &RgbaImage) -> usize {
+        image.pixels().filter(|pixel| pixel[0] < 200 || pixel[1] < 200 || pixel[2] < 200).count()
+    }
+
+    #[test]
+    fn answers() {
+        for seed in 0..20 {
+            let (answer, image) = captcha(6, 2, seed);
+            assert_eq!(answer.chars().count(), 6);
+            assert!(answer.chars().all(|letter| ALPHABET.contains(&letter)), "{answer}");
+            assert_eq!(image.dimensions(), (2 * PADDING + 6 * CELL, HEIGHT));
+        }
+        assert_eq!(captcha(4, 1, 0).0.len(), 4);
+        assert_eq!(captcha(10, 3, 0).0.len(), 10);
+    }
+
+    #[test]
+    fn seeded() {
+        assert_eq!(captcha(6, 2, 42), captcha(6, 2, 42));
+        assert_ne!(captcha(6, 2, 42).1, captcha(6, 2, 43).1);
+    }
+
+    #[test]
+    fn letters_are_drawn() {
+        let (_, image) = captcha(5, 1, 7);
+        // each letter's cell has plenty of dark pixels, more than the lines and specks alone
+        for index in 0..5 {
+            let left = PADDING + CELL * index;
+            let count = (left..left + CELL).flat_map(|x| (0..HEIGHT).map(move |y| (x, y)))
+                .filter(|(x, y)| image.get_pixel(*x, *y)[0] < 200)
+                .count();
+            assert!(count > 150, "{index}: {count}");
+        }
+        // nothing is transparent, so it reads the same on light and dark themes
+        assert!(image.pixels().all(|pixel| pixel[3] == 255));
+    }
+
+    #[test]
+    fn harder_is_noisier() {
+        let average = |difficulty| (0..10).map(|seed| dark_pixels(&captcha(6, difficulty, seed).1)).sum::<usize>() / 10;
+        assert!(average(1) < average(2));
+        assert!(average(2) < average(3));
+    }
+
+    #[test]
+    fn invalid() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(generate(3, 2, &mut rng).unwrap_err().contains("length"));
+        assert!(generate(11, 2, &mut rng).unwrap_err().contains("length"));
+        assert!(generate(6, 0, &mut rng).unwrap_err().contains("difficulty"));
+        assert!(generate(6, 4, &mut rng).unwrap_err().contains("difficulty"));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,440 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Charts of labelled values, like activity each day of the week or points over time.
+// COV_EXCL_START
+use image::{Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::buffer::Buffer;
+use crate::fluent::{self, format_compact, Formats};
+use crate::images::{self, font};
+use crate::metrics;
+// COV_EXCL_STOP
+
+const MAX_VALUES: usize = 200;
+const MIN_SIZE: (u32, u32) = (100, 40);
+const MAX_SIZE: (u32, u32) = (2000, 1000);
+const MARGIN: u32 = 20;
+/// How many lines across a bar chart has, roughly, to read the values off.
+const TICKS: f64 = 4.0;
+/// How much of its slot a bar takes up across, the rest is the gap between bars.
+const BAR_FILL: f64 = 0.7;
+/// How tall a line of text at scale 2 is, without the rows above it left for accents.
+const TEXT_HEIGHT: i64 = 16;
+
+/// The colors to draw a chart in, to match the Discord theme it's shown on.
+#[pyclass(module = "images")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Theme { // COV_EXCL_LINE
+    /// A dark background with light text.
+    Dark,
+    /// A light background with dark text.
+    Light,
+}
+
+/// The colors of a theme.
+struct Palette {
+    background: Rgba<u8>,
+    text: Rgba<u8>,
+    muted: Rgba<u8>,
+    grid: Rgba<u8>,
+}
+
+impl Theme {
+    fn palette(self) -> Palette {
+        match self {
+            Theme::Dark => Palette {
+                background: Rgba([0x31, 0x33, 0x38, 0xff]),
+                text: Rgba([0xf2, 0xf3, 0xf5, 0xff]),
+                muted: Rgba([0xb5, 0xba, 0xc1, 0xff]),
+                grid: Rgba([0x3f, 0x41, 0x47, 0xff]),
+            },
+            Theme::Light => Palette {
+                background: Rgba([0xff, 0xff, 0xff, 0xff]),
+                text: Rgba([0x06, 0x06, 0x07, 0xff]),
+                muted: Rgba([0x4e, 0x50, 0x58, 0xff]),
+                grid: Rgba([0xe3, 0xe5, 0xe8, 0xff]),
+            },
+        }
+    }
+}
+
+/// A round step between lines across a chart, like 1, 2 or 5 times a power of ten, so there are
+/// about `ticks` of them from `low` to `high`, and the lines below and above the values.
+fn axis(low: f64, high: f64, ticks: f64) -> (f64, f64, f64) {
+    if high <= low {
+        // every value is the same, like all zeroes for a quiet week, so the axis goes from 0 to them, or to 1
+        return if low == 0.0 { axis(0.0, 1.0, ticks) } else { axis(low.min(0.0), low.max(0.0), ticks) };
+    }
+    let raw = (high - low) / ticks;
+    let power = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0].into_iter().map(|factor| factor * power).find(|step| *step >= raw).unwrap_or(10.0 * power);
+    ((low / step).floor() * step, (high / step).ceil() * step, step)
+}
+
+/// Labelled values to chart, checked and with how to draw them.
+#[derive(Debug)]
+struct Chart<'a> {
+    data: &'a [(String, f64)],
+    theme: Theme,
+    accent: Rgba<u8>,
+    formats: Formats,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> Chart<'a> {
+    fn new(data: &'a [(String, f64)], theme: Theme, accent: u32, locale: &str, (width, height): (u32, u32)) -> Result<Self, String> {
+        if data.is_empty() || data.len() > MAX_VALUES {
+            return Err(format!("Invalid data, it has {} values and must have from 1 to {MAX_VALUES}", data.len()));
+        }
+        if let Some((label, value)) = data.iter().find(|(_, value)| !value.is_finite()) {
+            return Err(format!("Invalid value {value} for {label}, it must be a finite number"));
+        }
+        if !(MIN_SIZE.0..=MAX_SIZE.0).contains(&width) || !(MIN_SIZE.1..=MAX_SIZE.1).contains(&height) {
+            return Err(format!(
+                "Invalid size {width}x{height}, it must be from {}x{} to {}x{}",
+                MIN_SIZE.0, MIN_SIZE.1, MAX_SIZE.0, MAX_SIZE.1
+            ));
+        }
+        Ok(Chart { data, theme, accent: images::rgb(accent)?, formats: fluent::formats(locale)?, width, height })
+    }
+
+    fn label(&self, value: f64) -> String {
+        format_compact(self.formats, value)
+    }
+
+    /// A bar for each value, with the value above it and its label below, and `title` at the top.
+    fn bars(&self, title: Option<&str>) -> RgbaImage {
+        let palette = self.theme.palette();
+        let mut chart = RgbaImage::from_pixel(self.width, self.height, palette.background);
+        let margin = i64::from(MARGIN);
+        let mut top = margin;
+        if let Some(title) = title {
+            let title = font::fit(title, self.width - 2 * MARGIN, 3);
+            font::draw(&mut chart, &title, (i64::from(self.width) - i64::from(font::width(&title, 3))) / 2, top - 6, 3, palette.text);
+            top += 34;
+        }
+        // room above the bars for their values, and below them for their labels
+        top += TEXT_HEIGHT + 8;
+        let bottom = i64::from(self.height) - margin - TEXT_HEIGHT - 10;
+        let low = self.data.iter().map(|(_, value)| *value).fold(0.0, f64::min);
+        let high = self.data.iter().map(|(_, value)| *value).fold(0.0, f64::max);
+        let (low, high, step) = axis(low, high, TICKS);
+        let ticks = ((high - low) / step).round() as i64;
+        let axis_width = (0..=ticks).map(|tick| font::width(&self.label(low + tick as f64 * step), 2)).max().unwrap_or(0);
+        let left = margin + i64::from(axis_width) + 10;
+        let right = i64::from(self.width) - margin;
+        if bottom - top < 10 || right - left < self.data.len() as i64 {
+            // too small to draw anything readable, so just the background
+            return chart;
+        }
+        let y = |value: f64| bottom - ((value - low) / (high - low) * (bottom - top) as f64).round() as i64;
+
+        for tick in 0..=ticks {
+            let value = low + tick as f64 * step;
+            let line = y(value);
+            images::fill_rounded(&mut chart, (left, line), ((right - left) as u32, 1), 0, palette.grid);
+            let label = self.label(value);
+            font::draw(&mut chart, &label, left - 8 - i64::from(font::width(&label, 2)), line - 11, 2, palette.muted);
+        }
+
+        let slot = (right - left) as f64 / self.data.len() as f64;
+        let scale = if slot >= 40.0 { 2 } else { 1 };
+        let zero = y(0.0);
+        for (index, (label, value)) in self.data.iter().enumerate() {
+            let start = left as f64 + index as f64 * slot;
+            let bar_width = ((slot * BAR_FILL).round() as u32).max(1);
+            let bar_left = (start + (slot - f64::from(bar_width)) / 2.0).round() as i64;
+            let end = y(*value);
+            let (bar_top, bar_height) = (end.min(zero), (end - zero).unsigned_abs() as u32);
+            if bar_height > 0 {
+                let radius = 4.min(bar_width / 2).min(bar_height / 2);
+                images::fill_rounded(&mut chart, (bar_left, bar_top), (bar_width, bar_height), radius, self.accent);
+            }
+            let center = bar_left + i64::from(bar_width) / 2;
+            // the value goes past the end of the bar, so above it or below it for negative values
+            let text = self.label(*value);
+            if font::width(&text, scale) as f64 <= slot {
+                let text_y = if *value < 0.0 { end + 2 } else { end - 2 - i64::from(scale) * 9 };
+                font::draw(&mut chart, &text, center - i64::from(font::width(&text, scale)) / 2, text_y, scale, palette.text);
+            }
+            let label = font::fit(label, (slot as u32).saturating_sub(4), scale);
+            font::draw(&mut chart, &label, center - i64::from(font::width(&label, scale)) / 2, bottom + 6, scale, palette.muted);
+        }
+        chart
+    }
+
+    /// A line through the values with the area under it shaded, the last value at its end, and the
+    /// first and last labels below it if it's tall enough for them.
+    fn sparkline(&self) -> RgbaImage {
+        let palette = self.theme.palette();
+        let mut chart = RgbaImage::from_pixel(self.width, self.height, palette.background);
+        let margin = 8;
+        let last = self.data[self.data.len() - 1].1;
+        let value = self.label(last);
+        let left = margin;
+        let right = i64::from(self.width) - margin - i64::from(font::width(&value, 2)) - 10;
+        let labels = self.height >= 80;
+        let (top, bottom) = (margin, i64::from(self.height) - margin - if labels { TEXT_HEIGHT + 6 } else { 0 });
+        if right - left < 2 || bottom - top < 2 {
+            return chart;
+        }
+        let low = self.data.iter().map(|(_, value)| *value).fold(f64::INFINITY, f64::min);
+        let high = self.data.iter().map(|(_, value)| *value).fold(f64::NEG_INFINITY, f64::max);
+        let y = |value: f64| {
+            if high == low {
+                return (top + bottom) / 2;
+            }
+            bottom - ((value - low) / (high - low) * (bottom - top) as f64).round() as i64
+        };
+        // the line's height in each column, between the values either side of it
+        let columns = (left..=right)
+            .map(|x| {
+                let at = (x - left) as f64 / (right - left) as f64 * (self.data.len() - 1) as f64;
+                let (before, after) = (self.data[at.floor() as usize].1, self.data[(at.ceil() as usize).min(self.data.len() - 1)].1);
+                (x, y(before + (after - before) * at.fract()))
+            })
+            .collect::<Vec<_>>();
+        let shade = images::faded(self.accent, 0.25);
+        for (x, line) in &columns {
+            images::fill_rounded(&mut chart, (*x, *line), (1, (bottom - line + 1) as u32), 0, shade);
+        }
+        for pair in columns.windows(2) {
+            let ((x, from), (_, to)) = (pair[0], pair[1]);
+            let start = from.min(to) - 1;
+            images::fill_rounded(&mut chart, (x, start), (2, (from.max(to) + 1 - start + 1) as u32), 0, self.accent);
+        }
+        let end = y(last);
+        images::fill_rounded(&mut chart, (right - 4, end - 4), (9, 9), 5, self.accent);
+        font::draw(&mut chart, &value, right + 10, end - 11, 2, palette.text);
+        if labels {
+            let first = font::fit(&self.data[0].0, (right - left) as u32 / 2 - 4, 2);
+            font::draw(&mut chart, &first, left, bottom + 4, 2, palette.muted);
+            if self.data.len() > 1 {
+                let final_label = font::fit(&self.data[self.data.len() - 1].0, (right - left) as u32 / 2 - 4, 2);
+                font::draw(&mut chart, &final_label, right - i64::from(font::width(&final_label, 2)), bottom + 4, 2, palette.muted);
+            }
+        }
+        chart
+    }
+}
+
+#[pyfunction]
+#[allow(clippy::too_many_arguments)] // the optional ones are keyword arguments on the python side
+#[pyo3(signature = (data, title=None, theme=Theme::Dark, accent=0xfcb33f, locale="en-US", width=800, height=400), text_signature = "
+bar_chart(data, title=None, theme=Theme.Dark, accent=0xfcb33f, locale='en-US', width=800, height=400, /)
+--
+
+Draw a bar chart of labelled values, like messages sent each day of the week.
+
+Each bar has its value above it, shortened like 1.2K, and its label below it, cut short if it's too
+long. Negative values go below the line at 0. Drawing happens without holding the GIL.
+
+Parameters
+----------
+data : list[tuple[str, float]]
+    The labels and values, in the order to draw them, from 1 to 200 of them.
+title : str | None
+    A title to draw above the chart. The default is None, for no title.
+theme : Theme
+    The colors to draw in, to match the Discord theme it's shown on. The default is Theme.Dark.
+accent : int
+    The color of the bars, as a 0xRRGGBB integer. The default is 0xfcb33f.
+locale : str
+    The locale to format the values for. The default is 'en-US'.
+width : int
+    How wide the chart is, from 100 to 2000 pixels. The default is 800.
+height : int
+    How tall the chart is, from 40 to 1000 pixels. The default is 400.
+
+Returns
+-------
+Buffer
+    The chart, as a PNG.
+
+Raises
+------
+ValueError
+    If there are no values or too many, a value isn't a finite number, the accent isn't a color, or
+    the size is out of range, or there are no number formats for the locale.
+")]
+pub(crate) fn bar_chart(py: Python, data: Vec<(String, f64)>, title: Option<&str>, theme: Theme, accent: u32, locale: &str, width: u32, height: u32) -> PyResult<Buffer> {
+    metrics::observe("images.bar_chart", || {
+        let bytes = py
+            .allow_threads(|| Chart::new(&data, theme, accent, locale, (width, height)).and_then(|chart| images::encode(&chart.bars(title))))
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, theme=Theme::Dark, accent=0xfcb33f, locale="en-US", width=400, height=80), text_signature = "
+sparkline(data, theme=Theme.Dark, accent=0xfcb33f, locale='en-US', width=400, height=80, /)
+--
+
+Draw a small line chart of labelled values, like someone's points over the last month.
+
+The area under the line is shaded and the last value is written at its end. Charts at least 80
+pixels tall have the first and last labels below the line. Drawing happens without holding the GIL.
+
+Parameters
+----------
+data : list[tuple[str, float]]
+    The labels and values, oldest first, from 1 to 200 of them.
+theme : Theme
+    The colors to draw in, to match the Discord theme it's shown on. The default is Theme.Dark.
+accent : int
+    The color of the line, as a 0xRRGGBB integer. The default is 0xfcb33f.
+locale : str
+    The locale to format the last value for. The default is 'en-US'.
+width : int
+    How wide the chart is, from 100 to 2000 pixels. The default is 400.
+height : int
+    How tall the chart is, from 40 to 1000 pixels. The default is 80.
+
+Returns
+-------
+Buffer
+    The chart, as a PNG.
+
+Raises
+------
+ValueError
+    If there are no values or too many, a value isn't a finite number, the accent isn't a color, or
+    the size is out of range, or there are no number formats for the locale.
+")]
+pub(crate) fn sparkline(py: Python, data: Vec<(String, f64)>, theme: Theme, accent: u32, locale: &str, width: u32, height: u32) -> PyResult<Buffer> {
+    metrics::observe("images.sparkline", || {
+        let bytes = py
+            .allow_threads(|| Chart::new(&data, theme, accent, locale, (width, height)).and_then(|chart| images::encode(&chart.sparkline())))
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const ACCENT: Rgba<u8> = Rgba([0xfc, 0xb3, 0x3f, 0xff]);
+
+    fn week() -> Vec<(String, f64)> {
+        ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"].iter().zip([12.0, 40.0, 25.0, 0.0, 31.0, 55.0, 8.0]).map(|(day, value)| (day.to_string(), value)).collect()
+    }
+
+    fn chart(data: &[(String, f64)], theme: Theme, size: (u32, u32)) -> Chart<'_> {
+        Chart::new(data, theme, 0xfcb33f, "en-US", size).expect("Invalid chart")
+    }
+
+    /// The rows of column `x` that are `color`.
+    fn rows(image: &RgbaImage, x: u32, color: Rgba<u8>) -> Vec<u32> {
+        (0..image.height()).filter(|y| *image.get_pixel(x, *y) == color).collect()
+    }
+
+    #[parameterized(
+        small = {0.0, 55.0, 4.0, (0.0, 60.0, 20.0)},
+        large = {0.0, 12_345.0, 4.0, (0.0, 15_000.0, 5000.0)},
+        fractions = {0.0, 0.3, 4.0, (0.0, 0.3, 0.1)},
+        negative = {-30.0, 50.0, 4.0, (-40.0, 60.0, 20.0)},
+        flat = {0.0, 0.0, 4.0, (0.0, 1.0, 0.5)},
+        flat_positive = {5.0, 5.0, 4.0, (0.0, 6.0, 2.0)},
+        flat_negative = {-5.0, -5.0, 4.0, (-6.0, 0.0, 2.0)},
+    )]
+    fn axes(low: f64, high: f64, ticks: f64, expected: (f64, f64, f64)) {
+        let (bottom, top, step) = axis(low, high, ticks);
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        assert!(close(bottom, expected.0) && close(top, expected.1) && close(step, expected.2), "{bottom} {top} {step}");
+    }
+
+    #[test]
+    fn bars() {
+        let data = week();
+        let image = chart(&data, Theme::Dark, (800, 400)).bars(Some("Messages this week"));
+        assert_eq!(image.dimensions(), (800, 400));
+        assert_eq!(*image.get_pixel(0, 0), Theme::Dark.palette().background);
+        // the bars are as tall as their values, the tallest one is more than twice wednesday's
+        let slot = |index: u32| {
+            let (left, right) = (MARGIN + 60, 800 - MARGIN);
+            left + (right - left) * index / 7 + (right - left) / 14
+        };
+        let height = |index| rows(&image, slot(index), ACCENT).len();
+        assert!(height(5) > 2 * height(2), "{} {}", height(5), height(2));
+        assert_eq!(height(3), 0);
+        assert!(height(0) < height(1));
+        // the bars stand on the same line
+        assert_eq!(rows(&image, slot(0), ACCENT).last(), rows(&image, slot(5), ACCENT).last());
+        // the title, labels and values are drawn
+        let text = Theme::Dark.palette().text;
+        assert!((0..60).any(|y| (0..800).any(|x| *image.get_pixel(x, y) == text)));
+        let muted = Theme::Dark.palette().muted;
+        assert!((360..400).any(|y| (0..800).any(|x| *image.get_pixel(x, y) == muted)));
+    }
+
+    #[test]
+    fn negative_bars() {
+        let data = vec![(String::from("up"), 10.0), (String::from("down"), -10.0)];
+        let image = chart(&data, Theme::Light, (400, 300)).bars(None);
+        let (up, down) = (rows(&image, 150, ACCENT), rows(&image, 320, ACCENT));
+        assert!(!up.is_empty() && !down.is_empty());
+        // one goes up from the line at 0 and the other down from it
+        assert!(up.last() < down.first(), "{up:?} {down:?}");
+        assert_eq!(*image.get_pixel(0, 0), Theme::Light.palette().background);
+    }
+
+    #[test]
+    fn many_bars() {
+        let data = (0..200).map(|index| (format!("day {index}"), f64::from(index))).collect::<Vec<_>>();
+        let image = chart(&data, Theme::Dark, (800, 400)).bars(None);
+        assert!(image.pixels().filter(|pixel| **pixel == ACCENT).count() > 1000);
+        // too small to fit the chart still draws the background
+        let tiny = chart(&data, Theme::Dark, (100, 40)).bars(Some("title"));
+        assert_eq!(tiny.dimensions(), (100, 40));
+    }
+
+    #[test]
+    fn sparklines() {
+        let data = week();
+        let image = chart(&data, Theme::Dark, (400, 80)).sparkline();
+        assert_eq!(image.dimensions(), (400, 80));
+        // the line is highest on saturday, the 6th of 7 values
+        let line_top = |x: u32| rows(&image, x, ACCENT).first().copied();
+        let plot_right = 400 - 8 - font::width("8", 2) - 10;
+        let saturday = 8 + (plot_right - 8) * 5 / 6;
+        let wednesday = 8 + (plot_right - 8) * 2 / 6;
+        assert!(line_top(saturday) < line_top(wednesday), "{:?} {:?}", line_top(saturday), line_top(wednesday));
+        // the area under the line is shaded
+        let below = *image.get_pixel(wednesday, 48);
+        assert_ne!(below, Theme::Dark.palette().background);
+        assert_ne!(below, ACCENT);
+        // the labels are drawn below the line
+        let muted = Theme::Dark.palette().muted;
+        assert!((60..80).any(|y| (0..60).any(|x| *image.get_pixel(x, y) == muted)));
+    }
+
+    #[test]
+    fn flat_sparklines() {
+        let data = vec![(String::from("only"), 3.0)];
+        let image = chart(&data, Theme::Light, (200, 40)).sparkline();
+        let line = rows(&image, 50, ACCENT);
+        assert!(!line.is_empty());
+        assert!(line.iter().all(|y| (18..=22).contains(y)), "{line:?}");
+    }
+
+    #[test]
+    fn invalid() {
+        let data = week();
+        assert!(Chart::new(&[], Theme::Dark, 0, "en-US", (800, 400)).is_err());
+        let too_many = vec![(String::new(), 1.0); MAX_VALUES + 1];
+        assert!(Chart::new(&too_many, Theme::Dark, 0, "en-US", (800, 400)).is_err());
+        let infinite = vec![(String::from("a"), f64::INFINITY)];
+        assert!(Chart::new(&infinite, Theme::Dark, 0, "en-US", (800, 400)).unwrap_err().contains("finite"));
+        assert!(Chart::new(&data, Theme::Dark, 0x100_0000, "en-US", (800, 400)).is_err());
+        assert!(Chart::new(&data, Theme::Dark, 0, "en-US", (99, 400)).unwrap_err().contains("size"));
+        assert!(Chart::new(&data, Theme::Dark, 0, "en-US", (800, 1001)).unwrap_err().contains("size"));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Effects for fun commands, like deep frying someone's avatar.
+// COV_EXCL_START
+use image::{Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::buffer::{Buffer, Bytes};
+use crate::images;
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// How many blocks across the smaller side of an image is pixelated into at full intensity.
+const MIN_BLOCKS: f64 = 12.0;
+
+/// What an effect does to an image.
+#[pyclass(module = "images")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Effect { // COV_EXCL_LINE
+    /// Shades of gray.
+    Grayscale,
+    /// The browns of an old photo.
+    Sepia,
+    /// Every color swapped for its opposite.
+    Invert,
+    /// Oversaturated, overcontrasted, warm, grainy and banded, like it was fried.
+    DeepFry,
+    /// Big square pixels.
+    Pixelate,
+    /// Every color turned around the color wheel, half way around to the opposite hue at full intensity.
+    HueRotate,
+}
+
+fn luma([red, green, blue]: [f64; 3]) -> f64 {
+    0.299 * red + 0.587 * green + 0.114 * blue
+}
+
+/// `matrix` applied to the color of every pixel, keeping their alpha.
+fn transform(image: &RgbaImage, matrix: [[f64; 3]; 3]) -> RgbaImage {
+    map(image, |color, _, _| matrix.map(|row| row[0] * color[0] + row[1] * color[1] + row[2] * color[2]))
+}
+
+/// `change` applied to the color of every pixel, as floats from 0 to 255 and where the pixel is,
+/// keeping their alpha.
+fn map(image: &RgbaImage, change: impl Fn([f64; 3], u32, u32) -> [f64; 3]) -> RgbaImage {
+    RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+        let Rgba([red, green, blue, alpha]) = *image.get_pixel(x, y);
+        let [red, green, blue] = change([red, green, blue].map(f64::from), x, y).map(|channel| channel.round().clamp(0.0, 255.0) as u8);
+        Rgba([red, green, blue, alpha])
+    })
+}
+
+/// `effect` mixed with the original by `intensity`, from 0 for the original to 1 for the whole effect.
+fn mix(original: &RgbaImage, effect: &RgbaImage, intensity: f64) -> RgbaImage {
+    RgbaImage::from_fn(original.width(), original.height(), |x, y| {
+        let (before, after) = (original.get_pixel(x, y), effect.get_pixel(x, y));
+        let mut mixed = *before;
+        for channel in 0..4 {
+            let (from, to) = (f64::from(before[channel]), f64::from(after[channel]));
+            mixed[channel] = (from + (to - from) * intensity).round() as u8;
+        }
+        mixed
+    })
+}
+
+/// The hue rotation matrix from the filter effects spec, turning colors by `angle` radians.
+fn hue_matrix(angle: f64) -> [[f64; 3]; 3] {
+    let (sin, cos) = angle.sin_cos();
+    [
+        [0.213 + cos * 0.787 - sin * 0.213, 0.715 - cos * 0.715 - sin * 0.715, 0.072 - cos * 0.072 + sin * 0.928],
+        [0.213 - cos * 0.213 + sin * 0.143, 0.715 + cos * 0.285 + sin * 0.140, 0.072 - cos * 0.072 - sin * 0.283],
+        [0.213 - cos * 0.213 - sin * 0.787, 0.715 - cos * 0.715 + sin * 0.715, 0.072 + cos * 0.928 + sin * 0.072],
+    ]
+}
+
+/// A number from -1 to 1 that looks random for each pixel, but is the same each time, for grain.
+fn grain(x: u32, y: u32) -> f64 {
+    let mut hash = u64::from(x).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ u64::from(y).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 32;
+    (hash % 2001) as f64 / 1000.0 - 1.0
+}
+
+fn deep_fry(image: &RgbaImage, intensity: f64) -> RgbaImage {
+    // bands of this many shades, more of them at lower intensities
+    let band = 1.0 + 40.0 * intensity;
+    map(image, |color, x, y| {
+        let gray = luma(color);
+        let saturated = color.map(|channel| gray + (channel - gray) * (1.0 + 2.0 * intensity));
+        let contrasted = saturated.map(|channel| (channel - 128.0) * (1.0 + intensity) + 128.0);
+        let noise = grain(x, y) * 40.0 * intensity;
+        let [red, green, blue] = contrasted;
+        [red + 50.0 * intensity, green + 10.0 * intensity, blue - 40.0 * intensity].map(|channel| ((channel + noise).clamp(0.0, 255.0) / band).round() * band)
+    })
+}
+
+fn pixelate(image: &RgbaImage, intensity: f64) -> RgbaImage {
+    let smaller = f64::from(image.width().min(image.height()));
+    let block = (intensity * smaller / MIN_BLOCKS).round().max(1.0) as u32;
+    let mut pixelated = RgbaImage::new(image.width(), image.height());
+    for top in (0..image.height()).step_by(block as usize) {
+        for left in (0..image.width()).step_by(block as usize) {
+            let (right, bottom) = ((left + block).min(image.width()), (top + block).min(image.height()));
+            let pixels = (top..bottom).flat_map(|y| (left..right).map(move |x| (x, y))).collect::<Vec<_>>();
+            let mut sums = [0u64; 4];
+            for (x, y) in &pixels {
+                for (sum, channel) in sums.iter_mut().zip(image.get_pixel(*x, *y).0) {
+                    *sum += u64::from(channel);
+                }
+            }
+            let count = pixels.len() as u64;
+            let average = Rgba(sums.map(|sum| ((sum + count / 2) / count) as u8));
+            for (x, y) in pixels {
+                pixelated.put_pixel(x, y, average);
+            }
+        }
+    }
+    pixelated
+}
+
+/// `image` with `effect` applied at `intensity`, from 0 to 1.
+fn apply(image: &RgbaImage, effect: Effect, intensity: f64) -> RgbaImage {
+    match effect {
+        Effect::Grayscale => mix(image, &map(image, |color, _, _| [luma(color); 3]), intensity),
+        Effect::Sepia => mix(image, &transform(image, [[0.393, 0.769, 0.189], [0.349, 0.686, 0.168], [0.272, 0.534, 0.131]]), intensity),
+        Effect::Invert => mix(image, &map(image, |color, _, _| color.map(|channel| 255.0 - channel)), intensity),
+        Effect::DeepFry => deep_fry(image, intensity),
+        Effect::Pixelate => pixelate(image, intensity),
+        Effect::HueRotate => transform(image, hue_matrix(intensity * std::f64::consts::PI)),
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (image_bytes, effect, intensity=1.0), text_signature = "
+apply_effect(image_bytes, effect, intensity=1.0, /)
+--
+
+Apply an effect to an image, like deep frying someone's avatar for a fun command.
+
+Animated images only have their first frame used. Processing happens without holding the GIL, so
+other threads can run meanwhile.
+
+Parameters
+----------
+image_bytes : bytes-like
+    The image, as a file in any common format.
+effect : Effect
+    The effect to apply.
+intensity : float
+    How strong the effect is, from 0 for none of it to 1 for all of it. The default is 1.
+
+Returns
+-------
+Buffer
+    The image with the effect, as a PNG, the same size as it was.
+
+Raises
+------
+ValueError
+    If the intensity isn't from 0 to 1, or the image is too big or can't be read.
+")]
+pub(crate) fn apply_effect(py: Python, image_bytes: Bytes, effect: Effect, intensity: f64) -> PyResult<Buffer> {
+    metrics::observe("images.apply_effect", || {
+        if !(0.0..=1.0).contains(&intensity) {
+            return Err(PyValueError::new_err(format!("Invalid intensity {intensity}, it must be from 0 to 1")));
+        }
+        let bytes = py
+            .allow_threads(|| images::decode(&image_bytes).and_then(|image| images::encode(&apply(&image, effect, intensity))))
+            .map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const EVERY_EFFECT: [Effect; 6] = [Effect::Grayscale, Effect::Sepia, Effect::Invert, Effect::DeepFry, Effect::Pixelate, Effect::HueRotate];
+
+    /// A small image with a different color in each corner, and see-through at the bottom.
+    fn avatar() -> RgbaImage {
+        RgbaImage::from_fn(24, 24, |x, y| Rgba([(x * 10) as u8, (y * 10) as u8, 200, if y >= 20 { 0 } else { 255 }]))
+    }
+
+    #[parameterized(
+        red = {Rgba([255, 0, 0, 255]), Effect::Grayscale, Rgba([76, 76, 76, 255])},
+        white = {Rgba([255, 255, 255, 255]), Effect::Grayscale, Rgba([255, 255, 255, 255])},
+        sepia = {Rgba([100, 100, 100, 255]), Effect::Sepia, Rgba([135, 120, 94, 255])},
+        invert = {Rgba([0, 100, 255, 128]), Effect::Invert, Rgba([255, 155, 0, 128])},
+    )]
+    fn colors(before: Rgba<u8>, effect: Effect, after: Rgba<u8>) {
+        let image = RgbaImage::from_pixel(1, 1, before);
+        assert_eq!(*apply(&image, effect, 1.0).get_pixel(0, 0), after);
+    }
+
+    #[test]
+    fn intensity() {
+        let image = avatar();
+        for effect in EVERY_EFFECT {
+            let applied = apply(&image, effect, 0.0);
+            assert_eq!(applied.dimensions(), image.dimensions(), "{effect:?}");
+            // alpha is kept, so round avatars stay round
+            assert!(applied.pixels().zip(image.pixels()).all(|(after, before)| after[3] == before[3]), "{effect:?}");
+            assert_eq!(applied, image, "{effect:?}");
+            assert_ne!(apply(&image, effect, 1.0), image, "{effect:?}");
+        }
+        // half the effect is half way between
+        let half = apply(&RgbaImage::from_pixel(1, 1, Rgba([0, 100, 200, 255])), Effect::Invert, 0.5);
+        assert_eq!(*half.get_pixel(0, 0), Rgba([128, 128, 128, 255]));
+    }
+
+    #[test]
+    fn hue_rotation() {
+        let image = RgbaImage::from_pixel(1, 1, Rgba([200, 50, 50, 255]));
+        // all the way turns red to cyan, two thirds of the way turns it green
+        let opposite = *apply(&image, Effect::HueRotate, 1.0).get_pixel(0, 0);
+        assert!(opposite[0] < opposite[1] && opposite[0] < opposite[2], "{opposite:?}");
+        let green = *apply(&image, Effect::HueRotate, 2.0 / 3.0).get_pixel(0, 0);
+        assert!(green[1] > green[0] && green[1] > green[2], "{green:?}");
+    }
+
+    #[test]
+    fn pixelation() {
+        let image = avatar();
+        let pixelated = apply(&image, Effect::Pixelate, 1.0);
+        // blocks of 2 at full intensity on a 24 pixel image, each the average of its pixels
+        assert_eq!(pixelated.get_pixel(0, 0), pixelated.get_pixel(1, 1));
+        assert_ne!(pixelated.get_pixel(1, 1), pixelated.get_pixel(2, 2));
+        assert_eq!(*pixelated.get_pixel(0, 0), Rgba([5, 5, 200, 255]));
+        // a block that's half see-through is half as opaque
+        let striped = RgbaImage::from_fn(24, 24, |x, _| Rgba([0, 0, 0, if x % 2 == 0 { 255 } else { 0 }]));
+        assert!(pixelate(&striped, 1.0).pixels().all(|pixel| *pixel == Rgba([0, 0, 0, 128])));
+        let large = RgbaImage::from_pixel(10, 4, Rgba([9, 9, 9, 255]));
+        assert_eq!(pixelate(&large, 1.0), large);
+    }
+
+    #[test]
+    fn deep_frying() {
+        let image = RgbaImage::from_pixel(8, 8, Rgba([120, 100, 90, 255]));
+        let fried = apply(&image, Effect::DeepFry, 1.0);
+        // warmer and grainy, the same every time
+        let average = |channel: usize| fried.pixels().map(|pixel| u32::from(pixel[channel])).sum::<u32>() / 64;
+        assert!(average(0) > 120 && average(2) < 90, "{} {}", average(0), average(2));
+        assert!(fried.pixels().any(|pixel| pixel != fried.get_pixel(0, 0)));
+        assert_eq!(fried, apply(&image, Effect::DeepFry, 1.0));
+        assert!((0..100).all(|x| (-1.0..=1.0).contains(&grain(x, x * 7))));
+    }
+}
+// COV_EXCL_STOP
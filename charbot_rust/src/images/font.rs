@@ -0,0 +1,279 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! A built in 5 by 7 pixel font, so text can be drawn on images without shipping a font file and a rasterizer.
+//!
+//! Glyphs are scaled up by whole pixels, which keeps them sharp. Accented latin letters are drawn as
+//! the plain letter with its accent on top, so every letter the bot is translated to has a glyph, and
+//! anything else is drawn as a box like a font without the character would.
+use image::{Rgba, RgbaImage};
+use crate::images::blend;
+
+/// The width of a glyph, in pixels before scaling.
+const GLYPH_WIDTH: u32 = 5;
+/// The pixels between glyphs.
+const SPACING: u32 = 1;
+/// The height of a line: two rows for accents on capitals, the seven of a glyph, and one for cedillas.
+pub(crate) const HEIGHT: u32 = 10;
+/// The rows above where glyphs start, for the accents on capitals.
+const ACCENT_ROWS: u32 = 2;
+
+/// The glyphs for printable ASCII from the space on, each as five columns with the top pixel in the lowest bit.
+const ASCII: [[u8; 5]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00], [0x00, 0x00, 0x5f, 0x00, 0x00], [0x00, 0x07, 0x00, 0x07, 0x00], [0x14, 0x7f, 0x14, 0x7f, 0x14],
+    [0x24, 0x2a, 0x7f, 0x2a, 0x12], [0x23, 0x13, 0x08, 0x64, 0x62], [0x36, 0x49, 0x55, 0x22, 0x50], [0x00, 0x05, 0x03, 0x00, 0x00],
+    [0x00, 0x1c, 0x22, 0x41, 0x00], [0x00, 0x41, 0x22, 0x1c, 0x00], [0x08, 0x2a, 0x1c, 0x2a, 0x08], [0x08, 0x08, 0x3e, 0x08, 0x08],
+    [0x00, 0x50, 0x30, 0x00, 0x00], [0x08, 0x08, 0x08, 0x08, 0x08], [0x00, 0x60, 0x60, 0x00, 0x00], [0x20, 0x10, 0x08, 0x04, 0x02],
+    [0x3e, 0x51, 0x49, 0x45, 0x3e], [0x00, 0x42, 0x7f, 0x40, 0x00], [0x42, 0x61, 0x51, 0x49, 0x46], [0x21, 0x41, 0x45, 0x4b, 0x31],
+    [0x18, 0x14, 0x12, 0x7f, 0x10], [0x27, 0x45, 0x45, 0x45, 0x39], [0x3c, 0x4a, 0x49, 0x49, 0x30], [0x01, 0x71, 0x09, 0x05, 0x03],
+    [0x36, 0x49, 0x49, 0x49, 0x36], [0x06, 0x49, 0x49, 0x29, 0x1e], [0x00, 0x36, 0x36, 0x00, 0x00], [0x00, 0x56, 0x36, 0x00, 0x00],
+    [0x08, 0x14, 0x22, 0x41, 0x00], [0x14, 0x14, 0x14, 0x14, 0x14], [0x00, 0x41, 0x22, 0x14, 0x08], [0x02, 0x01, 0x51, 0x09, 0x06],
+    [0x32, 0x49, 0x79, 0x41, 0x3e], [0x7e, 0x11, 0x11, 0x11, 0x7e], [0x7f, 0x49, 0x49, 0x49, 0x36], [0x3e, 0x41, 0x41, 0x41, 0x22],
+    [0x7f, 0x41, 0x41, 0x22, 0x1c], [0x7f, 0x49, 0x49, 0x49, 0x41], [0x7f, 0x09, 0x09, 0x01, 0x01], [0x3e, 0x41, 0x41, 0x51, 0x32],
+    [0x7f, 0x08, 0x08, 0x08, 0x7f], [0x00, 0x41, 0x7f, 0x41, 0x00], [0x20, 0x40, 0x41, 0x3f, 0x01], [0x7f, 0x08, 0x14, 0x22, 0x41],
+    [0x7f, 0x40, 0x40, 0x40, 0x40], [0x7f, 0x02, 0x04, 0x02, 0x7f], [0x7f, 0x04, 0x08, 0x10, 0x7f], [0x3e, 0x41, 0x41, 0x41, 0x3e],
+    [0x7f, 0x09, 0x09, 0x09, 0x06], [0x3e, 0x41, 0x51, 0x21, 0x5e], [0x7f, 0x09, 0x19, 0x29, 0x46], [0x46, 0x49, 0x49, 0x49, 0x31],
+    [0x01, 0x01, 0x7f, 0x01, 0x01], [0x3f, 0x40, 0x40, 0x40, 0x3f], [0x1f, 0x20, 0x40, 0x20, 0x1f], [0x7f, 0x20, 0x18, 0x20, 0x7f],
+    [0x63, 0x14, 0x08, 0x14, 0x63], [0x03, 0x04, 0x78, 0x04, 0x03], [0x61, 0x51, 0x49, 0x45, 0x43], [0x00, 0x7f, 0x41, 0x41, 0x00],
+    [0x02, 0x04, 0x08, 0x10, 0x20], [0x00, 0x41, 0x41, 0x7f, 0x00], [0x04, 0x02, 0x01, 0x02, 0x04], [0x40, 0x40, 0x40, 0x40, 0x40],
+    [0x00, 0x01, 0x02, 0x04, 0x00], [0x20, 0x54, 0x54, 0x54, 0x78], [0x7f, 0x48, 0x44, 0x44, 0x38], [0x38, 0x44, 0x44, 0x44, 0x20],
+    [0x38, 0x44, 0x44, 0x48, 0x7f], [0x38, 0x54, 0x54, 0x54, 0x18], [0x08, 0x7e, 0x09, 0x01, 0x02], [0x08, 0x14, 0x54, 0x54, 0x3c],
+    [0x7f, 0x08, 0x04, 0x04, 0x78], [0x00, 0x44, 0x7d, 0x40, 0x00], [0x20, 0x40, 0x44, 0x3d, 0x00], [0x00, 0x7f, 0x10, 0x28, 0x44],
+    [0x00, 0x41, 0x7f, 0x40, 0x00], [0x7c, 0x04, 0x18, 0x04, 0x78], [0x7c, 0x08, 0x04, 0x04, 0x78], [0x38, 0x44, 0x44, 0x44, 0x38],
+    [0x7c, 0x14, 0x14, 0x14, 0x08], [0x08, 0x14, 0x14, 0x18, 0x7c], [0x7c, 0x08, 0x04, 0x04, 0x08], [0x48, 0x54, 0x54, 0x54, 0x20],
+    [0x04, 0x3f, 0x44, 0x40, 0x20], [0x3c, 0x40, 0x40, 0x20, 0x7c], [0x1c, 0x20, 0x40, 0x20, 0x1c], [0x3c, 0x40, 0x30, 0x40, 0x3c],
+    [0x44, 0x28, 0x10, 0x28, 0x44], [0x0c, 0x50, 0x50, 0x50, 0x3c], [0x44, 0x64, 0x54, 0x4c, 0x44], [0x00, 0x08, 0x36, 0x41, 0x00],
+    [0x00, 0x00, 0x7f, 0x00, 0x00], [0x00, 0x41, 0x36, 0x08, 0x00], [0x02, 0x01, 0x02, 0x04, 0x02],
+];
+
+/// Glyphs for the characters outside ASCII that aren't an accented letter.
+fn extra(letter: char) -> Option<[u8; 5]> {
+    Some(match letter {
+        '¡' => [0x00, 0x00, 0x7d, 0x00, 0x00],
+        '¿' => [0x30, 0x48, 0x45, 0x40, 0x20],
+        '°' => [0x00, 0x02, 0x05, 0x02, 0x00],
+        '·' => [0x00, 0x00, 0x08, 0x00, 0x00],
+        '«' => [0x08, 0x14, 0x2a, 0x14, 0x22],
+        '»' => [0x22, 0x14, 0x2a, 0x14, 0x08],
+        '€' => [0x14, 0x3e, 0x55, 0x41, 0x22],
+        'ß' => [0x7e, 0x01, 0x49, 0x4e, 0x30],
+        'æ' => [0x24, 0x54, 0x7c, 0x54, 0x58],
+        'œ' => [0x38, 0x44, 0x7c, 0x54, 0x58],
+        '→' => [0x08, 0x08, 0x2a, 0x1c, 0x08],
+        // the dot of an i makes way for its accent
+        'ı' => [0x00, 0x44, 0x7c, 0x40, 0x00],
+        _ => return None,
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Accent {
+    Acute,
+    Grave,
+    Circumflex,
+    Diaeresis,
+    Tilde,
+    Ring,
+    Cedilla,
+}
+
+impl Accent {
+    /// The accent's two rows, as columns like the glyphs, the top row in the lowest bit.
+    fn columns(self) -> [u8; 5] {
+        match self {
+            Accent::Acute => [0, 0, 2, 1, 0],
+            Accent::Grave => [0, 1, 2, 0, 0],
+            Accent::Circumflex => [0, 2, 1, 2, 0],
+            Accent::Diaeresis => [0, 2, 0, 2, 0],
+            Accent::Tilde => [2, 1, 2, 1, 2],
+            Accent::Ring => [0, 3, 1, 3, 0],
+            Accent::Cedilla => [0, 0, 1, 1, 0],
+        }
+    }
+}
+
+/// The plain letter and accent an accented letter is drawn as, the letter alone for ones with a stroke.
+fn decompose(letter: char) -> Option<(char, Option<Accent>)> {
+    let accent = match letter {
+        'À' | 'à' | 'È' | 'è' | 'Ì' | 'ì' | 'Ò' | 'ò' | 'Ù' | 'ù' => Accent::Grave,
+        'Á' | 'á' | 'É' | 'é' | 'Í' | 'í' | 'Ó' | 'ó' | 'Ú' | 'ú' | 'Ý' | 'ý' => Accent::Acute,
+        'Â' | 'â' | 'Ê' | 'ê' | 'Î' | 'î' | 'Ô' | 'ô' | 'Û' | 'û' => Accent::Circumflex,
+        'Ä' | 'ä' | 'Ë' | 'ë' | 'Ï' | 'ï' | 'Ö' | 'ö' | 'Ü' | 'ü' | 'ÿ' => Accent::Diaeresis,
+        'Ã' | 'ã' | 'Õ' | 'õ' | 'Ñ' | 'ñ' => Accent::Tilde,
+        'Å' | 'å' => Accent::Ring,
+        'Ç' | 'ç' => Accent::Cedilla,
+        'Ø' => return Some(('O', None)),
+        'ø' => return Some(('o', None)),
+        'Ł' => return Some(('L', None)),
+        'ł' => return Some(('l', None)),
+        'Đ' => return Some(('D', None)),
+        'đ' => return Some(('d', None)),
+        _ => return None,
+    };
+    let base = match letter.to_lowercase().next()? {
+        'à' | 'á' | 'â' | 'ä' | 'ã' | 'å' => 'a',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        // a lowercase i loses its dot under an accent
+        'ì' | 'í' | 'î' | 'ï' => 'ı',
+        'ò' | 'ó' | 'ô' | 'ö' | 'õ' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ñ' => 'n',
+        _ => 'c',
+    };
+    let base = match base {
+        'ı' if letter.is_uppercase() => 'I',
+        _ if letter.is_uppercase() => base.to_ascii_uppercase(),
+        _ => base,
+    };
+    Some((base, Some(accent)))
+}
+
+/// Whether `letter` takes no space, like the marks fluent isolates arguments with.
+fn is_invisible(letter: char) -> bool {
+    matches!(letter, '\u{200b}'..='\u{200f}' | '\u{2060}'..='\u{2069}' | '\u{feff}' | '\u{fe0f}')
+}
+
+/// The ASCII glyph for `letter`, or a box if there's none.
+fn ascii(letter: char) -> [u8; 5] {
+    match letter {
+        ' '..='~' => ASCII[letter as usize - ' ' as usize],
+        // the non breaking spaces locales put between numbers and units
+        '\u{a0}' | '\u{202f}' => ASCII[0],
+        _ => extra(letter).unwrap_or([0x7f, 0x41, 0x41, 0x41, 0x7f]),
+    }
+}
+
+/// The pixels of `letter`, as columns of [`HEIGHT`] rows, the top row in the lowest bit.
+fn glyph(letter: char) -> [u16; 5] {
+    let (base, accent) = decompose(letter).unwrap_or((letter, None));
+    let mut columns = ascii(base).map(|column| u16::from(column) << ACCENT_ROWS);
+    if let Some(accent) = accent {
+        // capitals are as tall as the glyph, so their accents go above it, and lowercase ones in its top rows
+        let shift = match accent {
+            Accent::Cedilla => HEIGHT - 1,
+            _ if base.is_uppercase() => 0,
+            _ => ACCENT_ROWS,
+        };
+        for (column, marks) in columns.iter_mut().zip(accent.columns()) {
+            *column |= u16::from(marks) << shift;
+        }
+    }
+    columns
+}
+
+fn visible(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars().filter(|letter| !is_invisible(*letter) && !letter.is_control())
+}
+
+/// How wide `text` is drawn at `scale`, in pixels.
+pub(crate) fn width(text: &str, scale: u32) -> u32 {
+    let count = visible(text).count() as u32;
+    (count * (GLYPH_WIDTH + SPACING)).saturating_sub(SPACING) * scale
+}
+
+/// `text`, cut short with "..." if it's wider than `max_width` at `scale`.
+pub(crate) fn fit(text: &str, max_width: u32, scale: u32) -> String {
+    if width(text, scale) <= max_width {
+        return text.to_string();
+    }
+    let mut cut = visible(text).collect::<Vec<_>>();
+    while !cut.is_empty() && width(&format!("{}...", cut.iter().collect::<String>()), scale) > max_width {
+        cut.pop();
+    }
+    format!("{}...", cut.into_iter().collect::<String>().trim_end())
+}
+
+/// Draw `text` with its top left corner at `x` and `y`, each pixel of the font as a square `scale` pixels across.
+pub(crate) fn draw(image: &mut RgbaImage, text: &str, x: i64, y: i64, scale: u32, color: Rgba<u8>) {
+    let scale = i64::from(scale.max(1));
+    let advance = i64::from(GLYPH_WIDTH + SPACING) * scale;
+    for (index, letter) in visible(text).enumerate() {
+        let left = x + index as i64 * advance;
+        for (column, bits) in glyph(letter).into_iter().enumerate() {
+            for row in (0..HEIGHT).filter(|row| bits & (1 << row) != 0) {
+                let (top, start) = (y + i64::from(row) * scale, left + column as i64 * scale);
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        blend(image, start + dx, top + dy, color);
+                    }
+                }
+            }
+        }
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The rows of `letter` as text, `#` for a pixel, to compare against.
+    fn rows(letter: char) -> Vec<String> {
+        let columns = glyph(letter);
+        (0..HEIGHT).map(|row| columns.iter().map(|column| if column & (1 << row) != 0 { '#' } else { '.' }).collect()).collect()
+    }
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(rows('A')[2..9], [".###.", "#...#", "#...#", "#...#", "#####", "#...#", "#...#"]);
+        assert_eq!(rows('1')[2..9], ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."]);
+        assert!(rows(' ').iter().all(|row| row == "....."));
+        assert_eq!(rows('→')[3..8], ["..#..", "...#.", "#####", "...#.", "..#.."]);
+        // every printable character has pixels, apart from the space
+        for letter in '!'..='~' {
+            assert!(glyph(letter).iter().any(|column| *column != 0), "{letter}");
+        }
+    }
+
+    #[test]
+    fn accents() {
+        // the acute goes above a capital, and in the top rows of a lowercase letter
+        assert_eq!(rows('É')[..2], ["...#.", "..#.."]);
+        assert_eq!(rows('É')[2..], rows('E')[2..]);
+        assert_eq!(rows('é')[2..4], ["...#.", "..#.."]);
+        assert_eq!(rows('é')[4..], rows('e')[4..]);
+        // the dot of an i gives way to its accent
+        assert_eq!(rows('í')[2..4], ["...#.", "..#.."]);
+        assert_eq!(rows('ç')[9], "..##.");
+        assert_eq!(rows('ø'), rows('o'));
+        for letter in "àáâäãåèéêëìíîïòóôöõùúûüýÿñçÀÉÎÖÑÇ".chars() {
+            assert_ne!(glyph(letter), glyph('漢'), "{letter}");
+        }
+    }
+
+    #[test]
+    fn unknown_characters_are_boxes() {
+        assert_eq!(rows('漢')[2], "#####");
+        assert_eq!(rows('漢')[8], "#####");
+        assert_eq!(glyph('漢'), glyph('😀'));
+        assert_eq!(glyph('\u{a0}'), glyph(' '));
+    }
+
+    #[test]
+    fn measuring() {
+        assert_eq!(width("", 2), 0);
+        assert_eq!(width("a", 1), 5);
+        assert_eq!(width("ab", 2), 22);
+        // fluent's isolation marks take no space
+        assert_eq!(width("\u{2068}12\u{2069}", 1), width("12", 1));
+        assert_eq!(fit("short", 100, 1), "short");
+        let cut = fit("a rather long username", 60, 1);
+        assert_eq!(cut, "a rathe...");
+        assert!(width(&cut, 1) <= 60);
+    }
+
+    #[test]
+    fn drawing() {
+        let mut image = RgbaImage::new(20, 30);
+        draw(&mut image, "I", 1, 0, 2, Rgba([255, 0, 0, 255]));
+        // the serifs of the I are in its first row, two rows down for the accents, at double size
+        assert_eq!(*image.get_pixel(3, 4), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(3, 5), Rgba([255, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(1, 4), Rgba([0, 0, 0, 0]));
+        assert_eq!(*image.get_pixel(3, 3), Rgba([0, 0, 0, 0]));
+        // drawing off the edge is clipped
+        draw(&mut image, "WWWW", -5, 25, 1, Rgba([255, 255, 255, 255]));
+    }
+}
+// COV_EXCL_STOP
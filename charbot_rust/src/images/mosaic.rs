@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Images redrawn as text, out of colored emoji or ASCII characters, to send as messages.
+// COV_EXCL_START
+use image::{Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::buffer::Bytes;
+use crate::images;
+use crate::metrics;
+use crate::markdown::chunk::chunk;
+// COV_EXCL_STOP
+
+const MAX_WIDTH: u32 = 80;
+const MAX_ROWS: u32 = 80;
+/// What see-through parts of an image are drawn over, the background of Discord's dark theme.
+const BACKGROUND: [f64; 3] = [49.0, 51.0, 56.0];
+/// The characters for ASCII art from darkest to brightest, as seen on a dark background.
+const RAMP: &[u8] = b" .:-=+*#%@";
+
+const SQUARES: [(&str, [u8; 3]); 9] = [
+    ("\u{1f7e5}", [0xdd, 0x2e, 0x44]),
+    ("\u{1f7e7}", [0xf4, 0x90, 0x0c]),
+    ("\u{1f7e8}", [0xfd, 0xcb, 0x58]),
+    ("\u{1f7e9}", [0x78, 0xb1, 0x59]),
+    ("\u{1f7e6}", [0x55, 0xac, 0xee]),
+    ("\u{1f7ea}", [0xaa, 0x8e, 0xd6]),
+    ("\u{1f7eb}", [0xc1, 0x69, 0x4f]),
+    ("\u{2b1b}", [0x31, 0x37, 0x3d]),
+    ("\u{2b1c}", [0xe6, 0xe7, 0xe8]),
+];
+const CIRCLES: [(&str, [u8; 3]); 9] = [
+    ("\u{1f534}", [0xdd, 0x2e, 0x44]),
+    ("\u{1f7e0}", [0xf4, 0x90, 0x0c]),
+    ("\u{1f7e1}", [0xfd, 0xcb, 0x58]),
+    ("\u{1f7e2}", [0x78, 0xb1, 0x59]),
+    ("\u{1f535}", [0x55, 0xac, 0xee]),
+    ("\u{1f7e3}", [0xaa, 0x8e, 0xd6]),
+    ("\u{1f7e4}", [0xc1, 0x69, 0x4f]),
+    ("\u{26ab}", [0x31, 0x37, 0x3d]),
+    ("\u{26aa}", [0xe6, 0xe7, 0xe8]),
+];
+
+/// What a mosaic is made out of.
+#[pyclass(module = "images")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Palette { // COV_EXCL_LINE
+    /// The colored square emoji.
+    Squares,
+    /// The colored circle emoji.
+    Circles,
+    /// ASCII characters by brightness, in a code block.
+    Ascii,
+}
+
+impl Palette {
+    /// How many rows a mosaic `columns` across has for an image `width` by `height`. Characters are
+    /// about twice as tall as they are wide, so ASCII art has half as many rows as emoji.
+    fn rows(self, columns: u32, (width, height): (u32, u32)) -> u32 {
+        let aspect = f64::from(height) / f64::from(width);
+        let aspect = if self == Palette::Ascii { aspect / 2.0 } else { aspect };
+        ((f64::from(columns) * aspect).round() as u32).clamp(1, MAX_ROWS)
+    }
+
+    fn pick(self, color: [f64; 3]) -> &'static str {
+        let emoji = match self {
+            Palette::Squares => &SQUARES,
+            Palette::Circles => &CIRCLES,
+            Palette::Ascii => {
+                let luma = (0.299 * color[0] + 0.587 * color[1] + 0.114 * color[2]) / 255.0;
+                let index = (luma * (RAMP.len() - 1) as f64).round() as usize;
+                return std::str::from_utf8(&RAMP[index..=index]).unwrap_or(" ");
+            }
+        };
+        emoji.iter().min_by(|(_, a), (_, b)| distance(color, *a).total_cmp(&distance(color, *b))).map_or(" ", |(emoji, _)| *emoji)
+    }
+}
+
+/// How different two colors look, with the "redmean" weighting, which is close to how people see it
+/// without converting to another color space.
+fn distance(color: [f64; 3], other: [u8; 3]) -> f64 {
+    let [red, green, blue] = [0, 1, 2].map(|channel| color[channel] - f64::from(other[channel]));
+    let mean = (color[0] + f64::from(other[0])) / 2.0;
+    (2.0 + mean / 256.0) * red * red + 4.0 * green * green + (2.0 + (255.0 - mean) / 256.0) * blue * blue
+}
+
+/// The average color of the image in each cell of a `columns` by `rows` grid, with see-through
+/// parts drawn over the background.
+fn downsample(image: &RgbaImage, columns: u32, rows: u32) -> Vec<Vec<[f64; 3]>> {
+    let (width, height) = image.dimensions();
+    // the edges of each cell, so every pixel is counted once even when the sizes don't divide
+    let edge = |index: u32, cells: u32, size: u32| (u64::from(index) * u64::from(size) / u64::from(cells)) as u32;
+    (0..rows)
+        .map(|row| {
+            let (top, bottom) = (edge(row, rows, height), edge(row + 1, rows, height).max(edge(row, rows, height) + 1));
+            (0..columns)
+                .map(|column| {
+                    let (left, right) = (edge(column, columns, width), edge(column + 1, columns, width).max(edge(column, columns, width) + 1));
+                    let mut sum = [0.0; 3];
+                    for y in top..bottom.min(height) {
+                        for x in left..right.min(width) {
+                            let Rgba([red, green, blue, alpha]) = *image.get_pixel(x, y);
+                            let alpha = f64::from(alpha) / 255.0;
+                            for (total, (channel, background)) in sum.iter_mut().zip([red, green, blue].into_iter().zip(BACKGROUND)) {
+                                *total += f64::from(channel) * alpha + background * (1.0 - alpha);
+                            }
+                        }
+                    }
+                    let count = f64::from((bottom.min(height) - top) * (right.min(width) - left));
+                    sum.map(|total| total / count)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `image` as lines of `palette`, `columns` across.
+fn mosaic(image: &RgbaImage, columns: u32, palette: Palette) -> String {
+    let columns = columns.min(image.width());
+    let rows = palette.rows(columns, image.dimensions()).min(image.height());
+    let lines = downsample(image, columns, rows)
+        .into_iter()
+        .map(|row| {
+            let line = row.into_iter().map(|color| palette.pick(color)).collect::<String>();
+            // spaces at the ends of lines only take up room in the message
+            if palette == Palette::Ascii { line.trim_end().to_string() } else { line }
+        })
+        .collect::<Vec<_>>();
+    lines.join("\n")
+}
+
+fn to_emoji_messages(image_bytes: &[u8], width: u32, palette: Palette, limit: usize) -> Result<Vec<String>, String> {
+    if !(1..=MAX_WIDTH).contains(&width) {
+        return Err(format!("Invalid width {width}, it must be from 1 to {MAX_WIDTH}"));
+    }
+    let image = images::decode(image_bytes)?;
+    let text = mosaic(&image, width, palette);
+    chunk(&text, limit, (palette == Palette::Ascii).then_some(""))
+}
+
+#[pyfunction]
+#[pyo3(signature = (image_bytes, width=20, palette=Palette::Squares, limit=2000), text_signature = "
+to_emoji(image_bytes, width=20, palette=Palette.Squares, limit=2000, /)
+--
+
+Redraw an image out of colored emoji or ASCII characters, for a fun command.
+
+Each emoji is the closest color to the part of the image it covers, and each ASCII character is
+brighter the brighter that part is, for the dark theme. See-through parts are drawn over the dark
+theme's background. The text is split into messages at the ends of lines, and ASCII art is put in a
+code block so it lines up. Processing happens without holding the GIL, so other threads can run
+meanwhile.
+
+Parameters
+----------
+image_bytes : bytes-like
+    The image, as a file in any common format.
+width : int
+    How many emoji or characters across the mosaic is, from 1 to 80. Images narrower than that are
+    drawn a character per pixel. The default is 20.
+palette : Palette
+    What the mosaic is made out of. The default is the colored squares.
+limit : int
+    The most characters a message can have. The default is 2000, Discord's limit.
+
+Returns
+-------
+list[str]
+    The messages to send, in order.
+
+Raises
+------
+ValueError
+    If the width or limit is out of range, or the image is too big or can't be read.
+")]
+pub(crate) fn to_emoji(py: Python, image_bytes: Bytes, width: u32, palette: Palette, limit: usize) -> PyResult<Vec<String>> {
+    metrics::observe("images.to_emoji", || py.allow_threads(|| to_emoji_messages(&image_bytes, width, palette, limit)).map_err(PyErr::new::<PyValueError, _>))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([0xff, 0x00, 0x00, 0xff]);
+    const BLUE: Rgba<u8> = Rgba([0x00, 0x40, 0xff, 0xff]);
+    const WHITE: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+
+    /// An image `width` by `height`, red on the left half and blue on the right.
+    fn halves(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, _| if x < width / 2 { RED } else { BLUE })
+    }
+
+    #[test]
+    fn averages() {
+        let image = RgbaImage::from_fn(4, 2, |x, _| if x % 2 == 0 { Rgba([0, 0, 0, 0xff]) } else { WHITE });
+        assert_eq!(downsample(&image, 2, 1), vec![vec![[127.5; 3], [127.5; 3]]]);
+        // see-through pixels are over the background
+        let clear = RgbaImage::from_pixel(3, 3, Rgba([0xff, 0xff, 0xff, 0]));
+        assert_eq!(downsample(&clear, 1, 1), vec![vec![BACKGROUND]]);
+        // cells that don't divide evenly still cover every pixel
+        let image = RgbaImage::from_fn(5, 1, |x, _| if x < 2 { Rgba([0, 0, 0, 0xff]) } else { WHITE });
+        assert_eq!(downsample(&image, 2, 1), vec![vec![[0.0; 3], [255.0; 3]]]);
+    }
+
+    #[test]
+    fn palettes() {
+        assert_eq!(Palette::Squares.pick([255.0, 0.0, 0.0]), "\u{1f7e5}");
+        assert_eq!(Palette::Squares.pick([0.0, 64.0, 255.0]), "\u{1f7e6}");
+        assert_eq!(Palette::Circles.pick([250.0, 250.0, 250.0]), "\u{26aa}");
+        assert_eq!(Palette::Circles.pick([10.0, 10.0, 10.0]), "\u{26ab}");
+        assert_eq!(Palette::Ascii.pick([0.0; 3]), " ");
+        assert_eq!(Palette::Ascii.pick([255.0; 3]), "@");
+        assert_eq!(Palette::Ascii.pick([128.0; 3]), "+");
+    }
+
+    #[test]
+    fn rows() {
+        assert_eq!(Palette::Squares.rows(20, (100, 50)), 10);
+        assert_eq!(Palette::Ascii.rows(20, (100, 50)), 5);
+        assert_eq!(Palette::Squares.rows(20, (1000, 1)), 1);
+        assert_eq!(Palette::Squares.rows(80, (1, 1000)), MAX_ROWS);
+    }
+
+    #[test]
+    fn mosaics() {
+        let text = mosaic(&halves(40, 20), 4, Palette::Squares);
+        assert_eq!(text, ["\u{1f7e5}\u{1f7e5}\u{1f7e6}\u{1f7e6}"; 2].join("\n"));
+        let image = RgbaImage::from_fn(40, 20, |x, _| if x < 20 { WHITE } else { Rgba([0, 0, 0, 0xff]) });
+        assert_eq!(mosaic(&image, 4, Palette::Ascii), "@@");
+        // small images aren't blown up
+        assert_eq!(mosaic(&halves(2, 2), 20, Palette::Circles), ["\u{1f534}\u{1f535}"; 2].join("\n"));
+    }
+
+    #[test]
+    fn messages() {
+        let text = mosaic(&halves(80, 80), 80, Palette::Squares);
+        let pieces = chunk(&text, 500, None).expect("Valid limit");
+        assert!(pieces.len() > 1);
+        assert!(pieces.iter().all(|piece| piece.chars().count() <= 500));
+        // every piece ends at the end of a line
+        assert_eq!(pieces.iter().map(|piece| piece.lines().count()).sum::<usize>(), 80);
+        assert!(pieces.iter().flat_map(|piece| piece.lines()).all(|line| line.chars().count() == 80));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(to_emoji_messages(&[], 0, Palette::Squares, 2000).unwrap_err().contains("width"));
+        assert!(to_emoji_messages(&[], MAX_WIDTH + 1, Palette::Squares, 2000).unwrap_err().contains("width"));
+    }
+}
+// COV_EXCL_STOP
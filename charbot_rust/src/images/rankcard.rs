@@ -0,0 +1,380 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Rank cards, showing a member's level, rank and progress to the next level over their avatar.
+// COV_EXCL_START
+use image::{Rgba, RgbaImage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+use crate::buffer::{Buffer, Bytes};
+use crate::fluent::{formats, format_compact, translate_text};
+use crate::images::{self, font};
+use crate::metrics;
+use crate::tasks;
+// COV_EXCL_STOP
+
+const WIDTH: u32 = 900;
+const HEIGHT: u32 = 238;
+/// The space between the edge of the card and the panel, and the panel and what's on it.
+const MARGIN: u32 = 15;
+const AVATAR_SIZE: u32 = 180;
+const AVATAR_X: i64 = 29;
+const AVATAR_Y: i64 = 29;
+/// How wide the accent colored ring around the avatar is.
+const RING: u32 = 5;
+/// Where the text and the progress bar start, right of the avatar.
+const TEXT_X: i64 = 245;
+/// Where right aligned text ends.
+const TEXT_RIGHT: i64 = 870;
+const BAR_Y: i64 = 165;
+const BAR_HEIGHT: u32 = 30;
+
+const TOP_COLOR: Rgba<u8> = Rgba([0x2b, 0x2d, 0x31, 0xff]);
+const BOTTOM_COLOR: Rgba<u8> = Rgba([0x1e, 0x1f, 0x22, 0xff]);
+/// Darkens a custom background so the text stays readable on it.
+const SHADE: Rgba<u8> = Rgba([0, 0, 0, 0x78]);
+const PANEL: Rgba<u8> = Rgba([0, 0, 0, 0x5a]);
+const TRACK: Rgba<u8> = Rgba([0x48, 0x4b, 0x4e, 0xff]);
+const NAME_COLOR: Rgba<u8> = Rgba([0xff, 0xff, 0xff, 0xff]);
+const LEVEL_COLOR: Rgba<u8> = Rgba([0xdb, 0xde, 0xe1, 0xff]);
+const XP_COLOR: Rgba<u8> = Rgba([0xb5, 0xba, 0xc1, 0xff]);
+
+/// Everything drawn on a rank card, with the text already translated.
+#[derive(Clone, Debug, PartialEq)]
+struct Card {
+    username: String,
+    level: String,
+    rank: String,
+    xp: String,
+    /// How far to the next level, from 0 to 1.
+    progress: f64,
+    avatar: Option<RgbaImage>,
+    background: Option<RgbaImage>,
+    accent: Rgba<u8>,
+}
+
+impl Card {
+    fn render(&self) -> RgbaImage {
+        let mut card = match &self.background {
+            Some(background) => {
+                let mut card = images::cover(background, WIDTH, HEIGHT);
+                images::fill_rounded(&mut card, (0, 0), (WIDTH, HEIGHT), 0, SHADE);
+                card
+            }
+            None => images::gradient(WIDTH, HEIGHT, TOP_COLOR, BOTTOM_COLOR),
+        };
+        let panel = (WIDTH - 2 * MARGIN, HEIGHT - 2 * MARGIN);
+        images::fill_rounded(&mut card, (i64::from(MARGIN), i64::from(MARGIN)), panel, 20, PANEL);
+        self.draw_avatar(&mut card);
+
+        // the rank goes in the corner and the name gets what's left of the line
+        let rank_width = font::width(&self.rank, 3);
+        let name_width = (TEXT_RIGHT - TEXT_X) as u32 - rank_width - 2 * MARGIN;
+        font::draw(&mut card, &font::fit(&self.username, name_width, 4), TEXT_X, 40, 4, NAME_COLOR);
+        font::draw(&mut card, &self.rank, TEXT_RIGHT - i64::from(rank_width), 49, 3, self.accent);
+        font::draw(&mut card, &self.level, TEXT_X, 110, 3, LEVEL_COLOR);
+        let xp = font::fit(&self.xp, (TEXT_RIGHT - TEXT_X) as u32 / 2, 2);
+        font::draw(&mut card, &xp, TEXT_RIGHT - i64::from(font::width(&xp, 2)), 119, 2, XP_COLOR);
+
+        let bar_width = (TEXT_RIGHT - TEXT_X) as u32;
+        images::fill_rounded(&mut card, (TEXT_X, BAR_Y), (bar_width, BAR_HEIGHT), BAR_HEIGHT / 2, TRACK);
+        let filled = (f64::from(bar_width) * self.progress.clamp(0.0, 1.0)).round() as u32;
+        if filled > 0 {
+            // a sliver of progress is still drawn as a full circle rather than squashed
+            images::fill_rounded(&mut card, (TEXT_X, BAR_Y), (filled.max(BAR_HEIGHT), BAR_HEIGHT), BAR_HEIGHT / 2, self.accent);
+        }
+        card
+    }
+
+    fn draw_avatar(&self, card: &mut RgbaImage) {
+        let ring = AVATAR_SIZE + 2 * RING;
+        let offset = i64::from(RING);
+        images::fill_rounded(card, (AVATAR_X - offset, AVATAR_Y - offset), (ring, ring), ring / 2, self.accent);
+        if let Some(avatar) = &self.avatar {
+            images::paste_circle(card, &images::cover(avatar, AVATAR_SIZE, AVATAR_SIZE), AVATAR_X, AVATAR_Y);
+            return;
+        }
+        // without an avatar the ring is filled in, with the first letter of the name on it
+        let initial = self.username.chars().find(|letter| !letter.is_whitespace()).map_or(String::from("?"), |letter| letter.to_uppercase().collect());
+        let scale = 10;
+        let center = i64::from(AVATAR_SIZE / 2);
+        // the letter itself is in the middle of the glyph's rows, below the room left for accents
+        let (x, y) = (AVATAR_X + center - i64::from(font::width(&initial, scale)) / 2, AVATAR_Y + center - i64::from(scale) * 11 / 2);
+        font::draw(card, &initial, x, y, scale, BOTTOM_COLOR);
+    }
+}
+
+#[allow(clippy::too_many_arguments)] // the optional ones are keyword arguments on the python side
+fn draw(username: &str, level: u64, rank: u64, xp: u64, needed: u64, avatar: Option<&[u8]>, background: Option<&[u8]>, locale: &str, accent: u32) -> Result<Vec<u8>, String> {
+    if needed == 0 {
+        return Err(String::from("Invalid XP needed, it must be more than 0"));
+    }
+    if rank == 0 {
+        return Err(String::from("Invalid rank, ranks start at 1"));
+    }
+    let formats = formats(locale)?;
+    let card = Card {
+        username: username.to_string(),
+        level: translate_text(locale, "rankcard-level", &[("level", level.to_string())])?,
+        rank: translate_text(locale, "rankcard-rank", &[("rank", rank.to_string())])?,
+        xp: translate_text(locale, "rankcard-xp", &[
+            ("xp", format_compact(formats, xp as f64)),
+            ("needed", format_compact(formats, needed as f64)),
+        ])?,
+        progress: xp as f64 / needed as f64,
+        avatar: avatar.map(images::decode).transpose()?,
+        background: background.map(images::decode).transpose()?,
+        accent: images::rgb(accent)?,
+    };
+    images::encode(&card.render())
+}
+
+#[pyfunction]
+#[allow(clippy::too_many_arguments)] // the optional ones are keyword arguments on the python side
+#[pyo3(signature = (username, level, rank, xp, needed, avatar=None, background=None, locale="en-US", accent=0xfcb33f), text_signature = "
+rank_card(username, level, rank, xp, needed, avatar=None, background=None, locale='en-US', accent=0xfcb33f, /)
+--
+
+Draw a rank card for a member, with their avatar, name, level, rank and progress to the next level.
+
+The text is translated with the bot's translations, and names too long for the card are cut short.
+Drawing happens without holding the GIL, so other threads can run meanwhile.
+
+Parameters
+----------
+username : str
+    The member's name.
+level : int
+    Their level.
+rank : int
+    Their place on the leaderboard, starting at 1.
+xp : int
+    The XP they have towards the next level.
+needed : int
+    The XP the next level needs, more than 0. XP past it fills the bar.
+avatar : bytes-like | None
+    Their avatar, as an image file in any common format. The default is None, which draws the first
+    letter of their name instead.
+background : bytes-like | None
+    An image to draw behind the card, cropped to fit and darkened. The default is None, a dark gradient.
+locale : str
+    The locale to translate the text to. The default is 'en-US'.
+accent : int
+    The color of the ring, the rank and the bar, as a 0xRRGGBB integer. The default is 0xfcb33f.
+
+Returns
+-------
+Buffer
+    The card, as a 900 by 238 PNG.
+
+Raises
+------
+ValueError
+    If the XP needed or the rank is 0, the accent isn't a color, an image is too big or can't be read,
+    or there are no number formats for the locale.
+")]
+pub(crate) fn rank_card(py: Python, username: &str, level: u64, rank: u64, xp: u64, needed: u64, avatar: Option<Bytes>, background: Option<Bytes>, locale: &str, accent: u32) -> PyResult<Buffer> {
+    metrics::observe("images.rank_card", || {
+        let (avatar, background) = (avatar.as_deref(), background.as_deref());
+        let bytes = py.allow_threads(|| draw(username, level, rank, xp, needed, avatar, background, locale, accent)).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    })
+}
+
+/// The arguments of a rank card for `render_many`, from a dict with the names `rank_card` takes.
+pub(crate) struct Request {
+    username: String,
+    level: u64,
+    rank: u64,
+    xp: u64,
+    needed: u64,
+    avatar: Option<Bytes>,
+    background: Option<Bytes>,
+    locale: String,
+    accent: u32,
+}
+
+// COV_EXCL_START
+impl<'source> FromPyObject<'source> for Request {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        let card: &PyDict = ob.downcast()?;
+        let given = |key: &str| card.get_item(key).filter(|value| !value.is_none());
+        let required = |key: &str| given(key).ok_or_else(|| PyValueError::new_err(format!("Invalid card, it must have a {key}")));
+        Ok(Request {
+            username: required("username")?.extract()?,
+            level: required("level")?.extract()?,
+            rank: required("rank")?.extract()?,
+            xp: required("xp")?.extract()?,
+            needed: required("needed")?.extract()?,
+            avatar: given("avatar").map(PyAny::extract).transpose()?,
+            background: given("background").map(PyAny::extract).transpose()?,
+            locale: given("locale").map_or_else(|| Ok(String::from("en-US")), PyAny::extract)?,
+            accent: given("accent").map_or(Ok(0xfcb33f), PyAny::extract)?,
+        })
+    }
+}
+// COV_EXCL_STOP
+
+#[pyfunction]
+#[pyo3(text_signature = "
+render_many(cards, /)
+--
+
+Draw many rank cards at once, like nightly leaderboard posts, split across threads without holding
+the GIL.
+
+Parameters
+----------
+cards : list[dict]
+    The cards, each a dict of what rank_card takes by name, like
+    {'username': 'Charlie', 'level': 12, 'rank': 3, 'xp': 450, 'needed': 1200}. Leaving out the
+    optional ones gives the same defaults.
+
+Returns
+-------
+list[Buffer]
+    The cards as 900 by 238 PNGs, in the order they were given.
+
+Raises
+------
+ValueError
+    If a card is missing a name rank_card needs, or rank_card would raise for it.
+")]
+pub(crate) fn render_many(py: Python<'_>, cards: Vec<Request>) -> PyResult<Vec<Buffer>> {
+    metrics::observe("images.render_many", || {
+        let drawn = py.allow_threads(|| {
+            tasks::parallel_map(cards, |card| {
+                let Request { username, level, rank, xp, needed, avatar, background, locale, accent } = card;
+                draw(&username, level, rank, xp, needed, avatar.as_deref(), background.as_deref(), &locale, accent)
+            })
+        });
+        drawn
+            .into_iter()
+            .enumerate()
+            .map(|(index, bytes)| bytes.map(Buffer::from).map_err(|error| PyValueError::new_err(format!("Invalid card {index}, {error}"))))
+            .collect()
+    })
+}
+
+#[cfg(feature = "async")]
+#[pyfunction]
+#[allow(clippy::too_many_arguments)] // the optional ones are keyword arguments on the python side
+#[pyo3(signature = (username, level, rank, xp, needed, avatar=None, background=None, locale=String::from("en-US"), accent=0xfcb33f), text_signature = "
+rank_card_async(username, level, rank, xp, needed, avatar=None, background=None, locale='en-US', accent=0xfcb33f, /)
+--
+
+Draw a rank card on a thread pool, so the event loop keeps running meanwhile. It takes and gives the
+same as rank_card, once awaited.
+
+Returns
+-------
+asyncio.Future[Buffer]
+    The card, as a 900 by 238 PNG.
+
+Raises
+------
+RuntimeError
+    If there isn't an event loop running.
+")]
+pub(crate) fn rank_card_async<'py>(py: Python<'py>, username: String, level: u64, rank: u64, xp: u64, needed: u64, avatar: Option<Bytes>, background: Option<Bytes>, locale: String, accent: u32) -> PyResult<&'py PyAny> {
+    tasks::spawn(py, move |py| {
+        rank_card(py, &username, level, rank, xp, needed, avatar, background, &locale, accent).map(|card| card.into_py(py))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCENT: Rgba<u8> = Rgba([0xfc, 0xb3, 0x3f, 0xff]);
+
+    fn card() -> Card {
+        Card {
+            username: String::from("bluesy"),
+            level: String::from("Level 12"),
+            rank: String::from("Rank #3"),
+            xp: String::from("450 / 1.2K XP"),
+            progress: 0.5,
+            avatar: None,
+            background: None,
+            accent: ACCENT,
+        }
+    }
+
+    /// Whether any pixel of `image` in the box from `left` and `top` to `right` and `bottom` is `color`.
+    fn has(image: &RgbaImage, (left, top): (u32, u32), (right, bottom): (u32, u32), color: Rgba<u8>) -> bool {
+        (top..bottom).any(|y| (left..right).any(|x| *image.get_pixel(x, y) == color))
+    }
+
+    #[test]
+    fn layout() {
+        let image = card().render();
+        assert_eq!(image.dimensions(), (WIDTH, HEIGHT));
+        // the gradient behind the panel, darker towards the bottom
+        assert_eq!(*image.get_pixel(0, 0), TOP_COLOR);
+        assert_eq!(*image.get_pixel(0, HEIGHT - 1), BOTTOM_COLOR);
+        // half the bar is filled with the accent and the rest is the track
+        assert_eq!(*image.get_pixel(400, 180), ACCENT);
+        assert_ne!(*image.get_pixel(800, 180), ACCENT);
+        assert_eq!(image.get_pixel(800, 180)[3], 0xff);
+        // the name is in white, the rank in the accent in the top right
+        assert!(has(&image, (245, 40), (400, 80), NAME_COLOR));
+        assert!(has(&image, (700, 40), (870, 80), ACCENT));
+        assert!(has(&image, (245, 110), (500, 140), LEVEL_COLOR));
+        assert!(has(&image, (600, 110), (870, 140), XP_COLOR));
+    }
+
+    #[test]
+    fn avatars() {
+        let center = (AVATAR_X as u32 + AVATAR_SIZE / 2, AVATAR_Y as u32 + AVATAR_SIZE / 2);
+        // without an avatar the circle is the accent, with the initial drawn on it
+        let image = card().render();
+        assert_eq!(*image.get_pixel(AVATAR_X as u32 + 20, center.1), ACCENT);
+        assert!(has(&image, (center.0 - 25, center.1 - 35), (center.0 + 25, center.1 + 35), BOTTOM_COLOR));
+        // an avatar is cut to a circle inside the ring
+        let red = Rgba([255, 0, 0, 255]);
+        let image = Card { avatar: Some(RgbaImage::from_pixel(AVATAR_SIZE, AVATAR_SIZE, red)), ..card() }.render();
+        assert_eq!(*image.get_pixel(center.0, center.1), red);
+        assert_eq!(*image.get_pixel(center.0, AVATAR_Y as u32 - 2), ACCENT);
+        assert_ne!(*image.get_pixel(AVATAR_X as u32, AVATAR_Y as u32), red);
+    }
+
+    #[test]
+    fn progress() {
+        let empty = Card { progress: 0.0, ..card() }.render();
+        assert!(!has(&empty, (245, 175), (870, 185), ACCENT));
+        // a little progress is still a visible dot, and too much doesn't go past the end
+        let little = Card { progress: 0.001, ..card() }.render();
+        assert_eq!(*little.get_pixel(260, 180), ACCENT);
+        let over = Card { progress: 3.0, ..card() }.render();
+        assert_eq!(*over.get_pixel(860, 180), ACCENT);
+        assert_ne!(*over.get_pixel(880, 180), ACCENT);
+    }
+
+    #[test]
+    fn backgrounds() {
+        let white = Rgba([255, 255, 255, 255]);
+        let image = Card { background: Some(RgbaImage::from_pixel(WIDTH, HEIGHT, white)), ..card() }.render();
+        // darkened, outside the panel too
+        let corner = *image.get_pixel(2, 2);
+        assert!(corner[0] < 255 && corner[0] > 100, "{corner:?}");
+        assert_eq!(corner[3], 255);
+    }
+
+    #[test]
+    fn long_names_fit() {
+        let image = Card { username: "w".repeat(100), ..card() }.render();
+        // the name stops before the rank
+        let rank_left = TEXT_RIGHT as u32 - font::width("Rank #3", 3);
+        assert!(!has(&image, (rank_left - 2 * MARGIN, 40), (rank_left, 80), NAME_COLOR));
+    }
+
+    #[test]
+    fn validation() {
+        assert!(draw("a", 1, 1, 0, 0, None, None, "en-US", 0).unwrap_err().contains("more than 0"));
+        assert!(draw("a", 1, 0, 0, 10, None, None, "en-US", 0).unwrap_err().contains("start at 1"));
+    }
+}
+// COV_EXCL_STOP
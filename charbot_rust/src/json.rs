@@ -0,0 +1,279 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Checking gateway payloads against a schema and pulling out the few fields the bot needs from them,
+//! without making python objects for everything else in them.
+mod schema; // COV_EXCL_LINE
+
+use std::collections::BTreeMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::value::RawValue;
+
+use crate::buffer::Data;
+use crate::json::schema::TYPES;
+use crate::metrics;
+
+/// How deep arrays and objects can be inside each other in what's made into python, so a payload can't
+/// overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// Parse a whole JSON document, checking all of it, but leaving the values in it as they're written
+/// until they're looked at.
+fn parse(bytes: &[u8]) -> Result<&RawValue, String> {
+    serde_json::from_slice(bytes).map_err(|error| format!("Invalid JSON: {error}"))
+}
+
+/// What kind of value it is, as JSON schema names them.
+fn kind(value: &RawValue) -> &'static str {
+    match value.get().as_bytes().first() {
+        Some(b'n') => "null",
+        Some(b't' | b'f') => "boolean",
+        Some(b'"') => "string",
+        Some(b'[') => "array",
+        Some(b'{') => "object",
+        // a number without a fraction or an exponent
+        _ if !value.get().contains(['.', 'e', 'E']) => "integer",
+        _ => "number",
+    }
+}
+
+/// The items of an array, or `None` if it isn't one.
+fn items(value: &RawValue) -> Option<Vec<&RawValue>> {
+    value.get().starts_with('[').then(|| serde_json::from_str(value.get()).ok()).flatten()
+}
+
+/// The members of an object, the last one of a key if there's more than one, like python's json
+/// module, or `None` if it isn't one.
+fn members(value: &RawValue) -> Option<BTreeMap<String, &RawValue>> {
+    value.get().starts_with('{').then(|| serde_json::from_str(value.get()).ok()).flatten()
+}
+
+/// The value at `path`, like `d.author.id` or `d.mentions.0`, with a number indexing an array.
+fn at<'a>(value: &'a RawValue, path: &str) -> Option<&'a RawValue> {
+    if path.is_empty() {
+        return Some(value);
+    }
+    path.split('.').try_fold(value, |value, segment| match items(value) {
+        Some(items) => items.get(segment.parse::<usize>().ok()?).copied(),
+        None => members(value)?.remove(segment),
+    })
+}
+
+/// A schema, checked and turned into rust once, to check any number of payloads against. Only `type`,
+/// `properties`, `required` and `items` are supported, and anything else is an error rather than ignored.
+#[pyclass(module = "json")] // COV_EXCL_LINE
+#[derive(Clone, Debug)] // COV_EXCL_LINE
+pub struct Schema {
+    schema: schema::Schema,
+}
+
+/// The schema in `dict`, which is at `path` in the whole schema, for the errors.
+fn compile(dict: &PyDict, path: &str) -> PyResult<schema::Schema> {
+    let invalid = |problem: String| PyValueError::new_err(format!("Invalid schema at {path}: {problem}"));
+    let nested = |value: &PyAny, path: &str| {
+        let dict = value.downcast::<PyDict>().map_err(|_| PyValueError::new_err(format!("Invalid schema at {path}: it must be a dict")))?;
+        compile(dict, path)
+    };
+    let mut compiled = schema::Schema::default();
+    for (keyword, value) in dict.iter() {
+        match keyword.extract::<&str>()? {
+            "type" => {
+                let names = match value.extract::<String>() {
+                    Ok(name) => vec![name],
+                    Err(_) => value.extract::<Vec<String>>()?,
+                };
+                for name in names {
+                    let kind = TYPES.iter().find(|kind| **kind == name).ok_or_else(|| invalid(format!("unknown type {name:?}")))?;
+                    compiled.types.push(kind);
+                }
+            }
+            "properties" => {
+                let properties = value.downcast::<PyDict>().map_err(|_| invalid(String::from("properties must be a dict")))?;
+                for (name, property) in properties.iter() {
+                    let name = name.extract::<String>()?;
+                    let schema = nested(property, &format!("{path}.properties.{name}"))?;
+                    compiled.properties.push((name, schema));
+                }
+            }
+            "required" => compiled.required = value.extract()?,
+            "items" => compiled.items = Some(Box::new(nested(value, &format!("{path}.items"))?)),
+            other => return Err(invalid(format!("unsupported keyword {other:?}"))),
+        }
+    }
+    Ok(compiled)
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Schema {
+    #[new]
+    fn __new__(schema: &PyDict) -> PyResult<Self> { // COV_EXCL_LINE
+        Ok(Schema { schema: compile(schema, "$")? })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Schema(types={:?}, properties={}, required={:?})", self.schema.types, self.schema.properties.len(), self.schema.required)
+    }
+}
+
+/// A value as python has it, like `json.loads` would make it, `depth` arrays and objects deep.
+fn to_python(py: Python, value: &RawValue, depth: usize) -> PyResult<PyObject> {
+    let text = value.get();
+    let invalid = |error: String| PyValueError::new_err(format!("Invalid JSON: {error}"));
+    Ok(match kind(value) {
+        "null" => py.None(),
+        "boolean" => (text == "true").into_py(py),
+        "integer" => match text.parse::<i64>() {
+            Ok(number) => number.into_py(py),
+            // too big for rust, but not for python
+            Err(_) => py.import("builtins")?.getattr("int")?.call1((text,))?.into_py(py),
+        },
+        "number" => text.parse::<f64>().map_err(|error| invalid(error.to_string()))?.into_py(py),
+        "string" => serde_json::from_str::<String>(text).map_err(|error| invalid(error.to_string()))?.into_py(py),
+        _ if depth == MAX_DEPTH => return Err(invalid(format!("arrays and objects can only be {MAX_DEPTH} deep"))),
+        "array" => {
+            let list = PyList::empty(py);
+            for item in items(value).unwrap_or_default() {
+                list.append(to_python(py, item, depth + 1)?)?;
+            }
+            list.into_py(py)
+        }
+        _ => {
+            let dict = PyDict::new(py);
+            for (key, value) in members(value).unwrap_or_default() {
+                dict.set_item(key, to_python(py, value, depth + 1)?)?;
+            }
+            dict.into_py(py)
+        }
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+validate(data, schema, /)
+--
+
+Check a JSON document, like a gateway payload, against a schema, without making python objects for
+any of it.
+
+Parameters
+----------
+data : str | bytes-like
+    The document, as UTF-8.
+schema : Schema
+    The schema to check it against.
+
+Returns
+-------
+list[str]
+    What's wrong with the document, each starting with where it is, like
+    ``$.d.author.id: expected string, got integer``, or why it isn't JSON. It's empty if the document
+    is valid.
+")]
+fn validate(data: Data, schema: &Schema) -> Vec<String> {
+    metrics::timed("json.validate", || match parse(data.as_bytes()) {
+        Ok(value) => schema.schema.problems(value),
+        Err(error) => vec![error],
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+extract(data, paths, /)
+--
+
+Pull values out of a JSON document, like the author, content and guild of a message event, making
+python objects for only those.
+
+Parameters
+----------
+data : str | bytes-like
+    The document, as UTF-8.
+paths : list[str]
+    Where the values are, as keys separated by dots, with numbers indexing arrays, like
+    ``d.author.id`` or ``d.mentions.0.id``. An empty path is the whole document.
+
+Returns
+-------
+list[Any]
+    The value at each path, like ``json.loads`` would make it, or None where there isn't one.
+
+Raises
+------
+ValueError
+    If the document isn't JSON, or a value at one of the paths has arrays and objects more than
+    128 deep.
+")]
+fn extract(py: Python, data: Data, paths: Vec<&str>) -> PyResult<Vec<PyObject>> {
+    metrics::observe("json.extract", || {
+        let value = parse(data.as_bytes()).map_err(PyErr::new::<PyValueError, _>)?;
+        paths.iter().map(|path| at(value, path).map_or_else(|| Ok(py.None()), |value| to_python(py, value, 0))).collect()
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based checking of JSON documents and pulling values out of them";
+
+pub(crate) fn register_json(py: Python, m: &PyModule) -> PyResult<()> {
+    let json = PyModule::new(py, "_json")?;
+    json.add_class::<Schema>()?;
+    json.add_function(wrap_pyfunction!(validate, json)?)?;
+    json.add_function(wrap_pyfunction!(extract, json)?)?;
+    json.add("__doc__", DOCSTRING)?;
+    m.add_submodule(json)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[test]
+    fn values() {
+        let value = parse(br#" {"a": [1, -2.5e3, "x\"y"], "b": {"c": null, "d": true}, "e": false} "#).unwrap();
+        assert_eq!(kind(value), "object");
+        assert_eq!(at(value, "a.2").map(kind), Some("string"));
+        assert_eq!(at(value, "a.2").map(RawValue::get), Some(r#""x\"y""#));
+        assert_eq!(at(value, "a.1").map(kind), Some("number"));
+        assert_eq!(at(value, "a.0").map(kind), Some("integer"));
+        assert_eq!(at(value, "b.c").map(kind), Some("null"));
+        assert_eq!(at(value, "b.d").map(RawValue::get), Some("true"));
+        assert_eq!(at(value, "b.missing"), None);
+        assert_eq!(at(value, "a.3"), None);
+        assert_eq!(at(value, "a.first"), None);
+        assert_eq!(at(value, "e.f"), None);
+        assert_eq!(at(value, ""), Some(value));
+        assert_eq!(members(value).map(|members| members.into_keys().collect::<Vec<_>>()), Some(vec![String::from("a"), String::from("b"), String::from("e")]));
+        assert_eq!(items(value), None);
+    }
+
+    #[parameterized(
+        empty = {b""},
+        trailing = {b"{} {}"},
+        trailing_comma = {b"[1,]"},
+        unquoted_key = {b"{a: 1}"},
+        unclosed = {b"[1, 2"},
+        bad_escape = {b"\"\\x\""},
+        control = {b"\"a\nb\""},
+        leading_zero = {b"01"},
+        bare_minus = {b"-"},
+        misspelt = {b"nul"},
+        not_utf8 = {b"\"\xff\""},
+    )]
+    fn invalid(bytes: &[u8]) {
+        let error = parse(bytes).expect_err("Expected the document to be rejected");
+        assert!(error.starts_with("Invalid JSON: "), "{error}");
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        // the last one wins, like python's json module
+        let value = parse(br#"{"a": 1, "a": 2, "\u0062": 3}"#).unwrap();
+        assert_eq!(at(value, "a").map(RawValue::get), Some("2"));
+        assert_eq!(at(value, "b").map(RawValue::get), Some("3"));
+    }
+}
+// COV_EXCL_STOP
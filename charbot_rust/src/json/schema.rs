@@ -0,0 +1,140 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! The part of JSON schema that checking the shape of a payload needs: the types of values, the
+//! properties of objects and which are required, and the items of arrays.
+use std::fmt::Write;
+
+use serde_json::value::RawValue;
+
+use crate::json::{items, kind, members};
+
+/// The types a schema can allow, as JSON schema names them.
+pub const TYPES: [&str; 7] = ["null", "boolean", "integer", "number", "string", "array", "object"];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Schema {
+    /// The types a value can be, from [`TYPES`], or any if there are none.
+    pub types: Vec<&'static str>,
+    /// The schemas of the properties of an object, which can have others too.
+    pub properties: Vec<(String, Schema)>,
+    /// The properties an object has to have.
+    pub required: Vec<String>,
+    /// The schema of every item of an array.
+    pub items: Option<Box<Schema>>,
+}
+
+impl Schema {
+    /// What's wrong with `value`, at `path`, added to `problems`.
+    pub fn check(&self, value: &RawValue, path: &mut String, problems: &mut Vec<String>) {
+        let kind = kind(value);
+        // an integer is a number too
+        if !self.types.is_empty() && !self.types.iter().any(|allowed| *allowed == kind || (*allowed == "number" && kind == "integer")) {
+            problems.push(format!("{path}: expected {}, got {kind}", self.types.join(" or ")));
+            return;
+        }
+        // only what the schema looks into is parsed
+        if !self.required.is_empty() || !self.properties.is_empty() {
+            if let Some(members) = members(value) {
+                for required in &self.required {
+                    if !members.contains_key(required) {
+                        problems.push(format!("{path}: missing {required:?}"));
+                    }
+                }
+                for (name, schema) in &self.properties {
+                    if let Some(property) = members.get(name) {
+                        let length = path.len();
+                        path.push('.');
+                        path.push_str(name);
+                        schema.check(property, path, problems);
+                        path.truncate(length);
+                    }
+                }
+            }
+        }
+        if let Some((schema, items)) = self.items.as_ref().zip(items(value)) {
+            for (index, item) in items.into_iter().enumerate() {
+                let length = path.len();
+                let _ = write!(path, "[{index}]");
+                schema.check(item, path, problems);
+                path.truncate(length);
+            }
+        }
+    }
+
+    /// Everything that's wrong with `value`, each starting with where it is, like `$.d.author.id`.
+    pub fn problems(&self, value: &RawValue) -> Vec<String> {
+        let mut problems = Vec::new();
+        self.check(value, &mut String::from("$"), &mut problems);
+        problems
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse;
+
+    fn typed(types: &[&'static str]) -> Schema {
+        Schema { types: types.to_vec(), ..Schema::default() }
+    }
+
+    fn message() -> Schema {
+        let author = Schema {
+            types: vec!["object"],
+            properties: vec![(String::from("id"), typed(&["string"]))],
+            required: vec![String::from("id")],
+            ..Schema::default()
+        };
+        let data = Schema {
+            types: vec!["object"],
+            properties: vec![
+                (String::from("author"), author),
+                (String::from("content"), typed(&["string"])),
+                (String::from("guild_id"), typed(&["string", "null"])),
+                (String::from("mentions"), Schema { types: vec!["array"], items: Some(Box::new(typed(&["object"]))), ..Schema::default() }),
+            ],
+            required: vec![String::from("author"), String::from("content")],
+            ..Schema::default()
+        };
+        Schema {
+            types: vec!["object"],
+            properties: vec![(String::from("op"), typed(&["integer"])), (String::from("d"), data)],
+            required: vec![String::from("op"), String::from("d")],
+            ..Schema::default()
+        }
+    }
+
+    #[test]
+    fn valid() {
+        let payload = parse(br#"{"op": 0, "t": "MESSAGE_CREATE", "d": {"author": {"id": "1"}, "content": "hi", "guild_id": null, "mentions": [{}]}}"#).unwrap();
+        assert_eq!(message().problems(payload), Vec::<String>::new());
+    }
+
+    #[test]
+    fn invalid() {
+        let payload = parse(br#"{"op": 0.5, "d": {"author": {"id": 1}, "guild_id": 2, "mentions": [{}, 3]}}"#).unwrap();
+        assert_eq!(
+            message().problems(payload),
+            [
+                "$.op: expected integer, got number",
+                "$.d: missing \"content\"",
+                "$.d.author.id: expected string, got integer",
+                "$.d.guild_id: expected string or null, got integer",
+                "$.d.mentions[1]: expected object, got integer",
+            ]
+        );
+        assert_eq!(message().problems(parse(b"[]").unwrap()), ["$: expected object, got array"]);
+    }
+
+    #[test]
+    fn numbers() {
+        // integers are numbers, but numbers aren't integers
+        assert!(typed(&["number"]).problems(parse(b"1").unwrap()).is_empty());
+        assert!(typed(&["number"]).problems(parse(b"1.5").unwrap()).is_empty());
+        assert_eq!(typed(&["integer"]).problems(parse(b"1e3").unwrap()), ["$: expected integer, got number"]);
+        assert!(Schema::default().problems(parse(b"null").unwrap()).is_empty());
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,284 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+#[pyclass(module = "leaderboard")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Ranking { // COV_EXCL_LINE
+    /// Tied users share a rank and the ranks after them are skipped, like 1, 2, 2, 4.
+    Competition,
+    /// Tied users share a rank and the next rank follows on, like 1, 2, 2, 3.
+    Dense,
+}
+
+// COV_EXCL_START
+#[pyclass(module = "leaderboard")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Standing {
+    /// The rank, from 1 for the highest score.
+    #[pyo3(get)]
+    rank: usize,
+    /// Where the user is in the leaderboard, from 0, which is different for every user even when tied.
+    #[pyo3(get)]
+    position: usize,
+    #[pyo3(get)]
+    user: u64,
+    #[pyo3(get)]
+    score: i64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Standing {
+    fn __repr__(&self) -> String {
+        format!("Standing(rank={}, position={}, user={}, score={})", self.rank, self.position, self.user, self.score)
+    }
+}
+
+// COV_EXCL_START
+/// Users ranked by score, kept in order as scores change so nothing has to be sorted again.
+///
+/// Users with the same score are listed by their id, so the order is always the same.
+#[pyclass(module = "leaderboard")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Leaderboard {
+    ranking: Ranking,
+    /// Every score and its user, the highest score first.
+    entries: Vec<(i64, u64)>,
+    /// Every different score, the highest first, for dense ranks.
+    distinct: Vec<i64>,
+    scores: HashMap<u64, i64>,
+}
+// COV_EXCL_STOP
+
+fn key(&(score, user): &(i64, u64)) -> (Reverse<i64>, u64) {
+    (Reverse(score), user)
+}
+
+impl Leaderboard {
+    fn new(entries: Vec<(u64, i64)>, ranking: Ranking) -> Result<Self, String> {
+        let mut scores = HashMap::with_capacity(entries.len());
+        for (user, score) in &entries {
+            if scores.insert(*user, *score).is_some() {
+                return Err(format!("{user} is in the leaderboard more than once"));
+            }
+        }
+        let mut entries = entries.into_iter().map(|(user, score)| (score, user)).collect::<Vec<_>>();
+        entries.sort_unstable_by_key(key);
+        let mut distinct = entries.iter().map(|(score, _)| *score).collect::<Vec<_>>();
+        distinct.dedup();
+        Ok(Leaderboard { ranking, entries, distinct, scores })
+    }
+
+    fn position(&self, user: u64) -> Option<usize> {
+        let score = *self.scores.get(&user)?;
+        self.entries.binary_search_by_key(&key(&(score, user)), key).ok()
+    }
+
+    fn standing(&self, position: usize) -> Standing {
+        let (score, user) = self.entries[position];
+        let rank = match self.ranking {
+            Ranking::Competition => self.entries.partition_point(|(other, _)| *other > score) + 1,
+            Ranking::Dense => self.distinct.partition_point(|other| *other > score) + 1,
+        };
+        Standing { rank, position, user, score }
+    }
+
+    fn standings(&self, start: usize, end: usize) -> Vec<Standing> {
+        (start.min(self.entries.len())..end.min(self.entries.len())).map(|position| self.standing(position)).collect()
+    }
+
+    fn set(&mut self, user: u64, score: i64) {
+        self.remove(user);
+        let entry = (score, user);
+        let position = self.entries.binary_search_by_key(&key(&entry), key).unwrap_or_else(|position| position);
+        self.entries.insert(position, entry);
+        if let Err(position) = self.distinct.binary_search_by_key(&Reverse(score), |other| Reverse(*other)) {
+            self.distinct.insert(position, score);
+        }
+        self.scores.insert(user, score);
+    }
+
+    fn remove(&mut self, user: u64) -> Option<i64> {
+        let position = self.position(user)?;
+        let (score, _) = self.entries.remove(position);
+        self.scores.remove(&user);
+        // the score is only gone for dense ranks if no one else has it
+        if self.entries.binary_search_by_key(&Reverse(score), |(other, _)| Reverse(*other)).is_err() {
+            if let Ok(position) = self.distinct.binary_search_by_key(&Reverse(score), |other| Reverse(*other)) {
+                self.distinct.remove(position);
+            }
+        }
+        Some(score)
+    }
+
+    fn around(&self, user: u64, neighbours: usize) -> Option<Vec<Standing>> {
+        let position = self.position(user)?;
+        Some(self.standings(position.saturating_sub(neighbours), position.saturating_add(neighbours).saturating_add(1)))
+    }
+
+    fn page(&self, page: usize, per_page: usize) -> Result<Vec<Standing>, String> {
+        if per_page == 0 {
+            return Err("A page needs room for at least one user".to_string());
+        }
+        let start = page.saturating_mul(per_page);
+        Ok(self.standings(start, start.saturating_add(per_page)))
+    }
+
+    fn pages(&self, per_page: usize) -> Result<usize, String> {
+        if per_page == 0 {
+            return Err("A page needs room for at least one user".to_string());
+        }
+        Ok(self.entries.len().div_ceil(per_page))
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Leaderboard {
+    #[new]
+    #[pyo3(signature = (entries, ranking=Ranking::Competition))]
+    fn __new__(entries: Vec<(u64, i64)>, ranking: Ranking) -> PyResult<Self> { // COV_EXCL_LINE
+        Leaderboard::new(entries, ranking).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter]
+    fn ranking(&self) -> Ranking {
+        self.ranking
+    }
+
+    fn __len__(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Set the score of `user`, adding them if they aren't on the leaderboard.
+    #[pyo3(name = "set")]
+    fn py_set(&mut self, user: u64, score: i64) {
+        self.set(user, score);
+    }
+
+    /// Take `user` off the leaderboard, returning their score if they were on it.
+    #[pyo3(name = "remove")]
+    fn py_remove(&mut self, user: u64) -> Option<i64> {
+        self.remove(user)
+    }
+
+    fn score(&self, user: u64) -> Option<i64> {
+        self.scores.get(&user).copied()
+    }
+
+    fn rank(&self, user: u64) -> Option<Standing> {
+        self.position(user).map(|position| self.standing(position))
+    }
+
+    #[pyo3(signature = (count=10))]
+    fn top(&self, count: usize) -> Vec<Standing> {
+        self.standings(0, count)
+    }
+
+    #[pyo3(name = "around", signature = (user, neighbours=2))]
+    fn py_around(&self, user: u64, neighbours: usize) -> Option<Vec<Standing>> {
+        self.around(user, neighbours)
+    }
+
+    #[pyo3(name = "page", signature = (page, per_page=10))]
+    fn py_page(&self, page: usize, per_page: usize) -> PyResult<Vec<Standing>> {
+        self.page(page, per_page).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "pages", signature = (per_page=10))]
+    fn py_pages(&self, per_page: usize) -> PyResult<usize> {
+        self.pages(per_page).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based leaderboard ranking";
+
+pub(crate) fn register_leaderboard(py: Python, m: &PyModule) -> PyResult<()> {
+    let leaderboard = PyModule::new(py, "_leaderboard")?;
+    leaderboard.add_class::<Ranking>()?;
+    leaderboard.add_class::<Standing>()?;
+    leaderboard.add_class::<Leaderboard>()?;
+    leaderboard.add("__doc__", DOCSTRING)?;
+    m.add_submodule(leaderboard)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn board(ranking: Ranking) -> Leaderboard {
+        Leaderboard::new(vec![(5, 10), (1, 50), (4, 30), (2, 30), (3, 40), (6, 10)], ranking).expect("Failed to create")
+    }
+
+    fn ranks(standings: &[Standing]) -> Vec<(usize, u64)> {
+        standings.iter().map(|standing| (standing.rank, standing.user)).collect()
+    }
+
+    #[parameterized(
+        competition = {Ranking::Competition, vec![(1, 1), (2, 3), (3, 2), (3, 4), (5, 5), (5, 6)]},
+        dense = {Ranking::Dense, vec![(1, 1), (2, 3), (3, 2), (3, 4), (4, 5), (4, 6)]},
+    )]
+    fn ranks_ties(ranking: Ranking, expected: Vec<(usize, u64)>) {
+        assert_eq!(ranks(&board(ranking).standings(0, 10)), expected);
+    }
+
+    #[test]
+    fn rejects_repeats() {
+        Leaderboard::new(vec![(1, 5), (1, 6)], Ranking::Dense).expect_err("Expected the repeated user to be rejected");
+    }
+
+    #[test]
+    fn finds_users() {
+        let board = board(Ranking::Competition);
+        let standing = board.rank(4).expect("Expected a rank");
+        assert_eq!((standing.rank, standing.position, standing.score), (3, 3, 30));
+        assert_eq!(board.rank(7), None);
+        assert_eq!(ranks(&board.around(4, 1).expect("Expected neighbours")), vec![(3, 2), (3, 4), (5, 5)]);
+        assert_eq!(ranks(&board.around(1, 2).expect("Expected neighbours")), vec![(1, 1), (2, 3), (3, 2)]);
+        assert_eq!(ranks(&board.around(6, 0).expect("Expected neighbours")), vec![(5, 6)]);
+        assert_eq!(board.around(7, 2), None);
+        assert_eq!(board.around(1, usize::MAX).expect("Expected neighbours").len(), 6);
+    }
+
+    #[test]
+    fn pages() {
+        let board = board(Ranking::Dense);
+        assert_eq!(ranks(&board.page(1, 4).expect("Expected a page")), vec![(4, 5), (4, 6)]);
+        assert!(board.page(2, 4).expect("Expected a page").is_empty());
+        assert!(board.page(usize::MAX, usize::MAX).expect("Expected a page").is_empty());
+        assert_eq!((board.pages(4), board.pages(6), board.pages(1)), (Ok(2), Ok(1), Ok(6)));
+        board.page(0, 0).expect_err("Expected an empty page to be rejected");
+        board.pages(0).expect_err("Expected an empty page to be rejected");
+        assert_eq!(board.pages(3), Ok(2));
+        assert_eq!(Leaderboard::new(vec![], Ranking::Dense).expect("Failed to create").pages(10), Ok(0));
+    }
+
+    #[test]
+    fn updates() {
+        let mut board = board(Ranking::Dense);
+        board.set(5, 45);
+        assert_eq!(ranks(&board.standings(0, 3)), vec![(1, 1), (2, 5), (3, 3)]);
+        // 6 is the only one left on 10
+        assert_eq!(board.rank(6).map(|standing| standing.rank), Some(5));
+        board.set(7, 30);
+        assert_eq!(ranks(&board.standings(3, 10)), vec![(4, 2), (4, 4), (4, 7), (5, 6)]);
+        assert_eq!(board.remove(6), Some(10));
+        assert_eq!(board.remove(6), None);
+        assert_eq!(board.distinct, vec![50, 45, 40, 30]);
+        board.remove(2);
+        board.remove(4);
+        assert_eq!(board.distinct, vec![50, 45, 40, 30]);
+        board.remove(7);
+        assert_eq!(board.distinct, vec![50, 45, 40]);
+        assert_eq!(board, Leaderboard::new(vec![(1, 50), (5, 45), (3, 40)], Ranking::Dense).expect("Failed to create"));
+    }
+}
+// COV_EXCL_STOP
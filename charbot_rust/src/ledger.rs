@@ -0,0 +1,347 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::persist::{self, Persist};
+
+/// How many transactions are kept for each user by default.
+const DEFAULT_LOG_SIZE: usize = 50;
+/// The most transactions that can be kept for each user, so the log can't take all the memory.
+const MAX_LOG_SIZE: usize = 1000;
+
+// COV_EXCL_START
+/// A change to a user's balance.
+#[pyclass(module = "ledger")]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Counts up from 0 across every user, so transactions can be put in order.
+    #[pyo3(get)]
+    id: u64,
+    #[pyo3(get)]
+    user: u64,
+    /// Positive for a credit, negative for a debit.
+    #[pyo3(get)]
+    amount: i64,
+    /// The balance after the transaction.
+    #[pyo3(get)]
+    balance: i64,
+    #[pyo3(get)]
+    reason: String,
+    /// When the transaction was made, in seconds since the unix epoch.
+    #[pyo3(get)]
+    timestamp: u64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Transaction {
+    fn __repr__(&self) -> String {
+        format!("Transaction(id={}, user={}, amount={}, balance={}, reason={:?})", self.id, self.user, self.amount, self.balance, self.reason)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Account {
+    balance: i64,
+    /// The latest transactions, oldest first.
+    log: VecDeque<Transaction>,
+}
+
+// COV_EXCL_START
+/// Everyone's points, changed a whole transaction at a time.
+///
+/// Each change is checked in full before any of it is applied, so a change that would overdraw an
+/// account changes nothing, and since a call runs to the end before the next can start, no two
+/// commands can interleave and lose an update.
+#[pyclass(module = "ledger")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ledger {
+    log_size: usize,
+    next_id: u64,
+    accounts: BTreeMap<u64, Account>,
+}
+// COV_EXCL_STOP
+
+#[derive(Serialize, Deserialize)]
+pub struct State {
+    log_size: usize,
+    next_id: u64,
+    accounts: BTreeMap<u64, Account>,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+impl Ledger {
+    fn new(log_size: usize) -> Result<Self, String> {
+        if !(1..=MAX_LOG_SIZE).contains(&log_size) {
+            return Err(format!("Invalid log size {log_size}, it must be from 1 to {MAX_LOG_SIZE}"));
+        }
+        Ok(Ledger { log_size, next_id: 0, accounts: BTreeMap::new() })
+    }
+
+    fn balance(&self, user: u64) -> i64 {
+        self.accounts.get(&user).map_or(0, |account| account.balance)
+    }
+
+    /// Apply `changes` to the balances of their users, all of them or none if any would overdraw an account or overflow.
+    ///
+    /// A user can be in `changes` more than once, and only their balance after all of their changes
+    /// has to be covered, so a user can be credited and debited in the same transaction.
+    fn apply(&mut self, changes: &[(u64, i64)], reason: &str) -> Result<Vec<Transaction>, String> {
+        let mut balances: BTreeMap<u64, i64> = BTreeMap::new();
+        for (user, amount) in changes {
+            let balance = balances.entry(*user).or_insert_with(|| self.balance(*user));
+            *balance = balance.checked_add(*amount).ok_or_else(|| format!("The balance of {user} would overflow"))?;
+        }
+        if let Some((user, balance)) = balances.iter().find(|(_, balance)| **balance < 0) {
+            return Err(format!("{user} can't afford this, it would leave them with {balance}"));
+        }
+        let timestamp = now();
+        let mut transactions = Vec::with_capacity(changes.len());
+        for (user, amount) in changes {
+            let account = self.accounts.entry(*user).or_default();
+            account.balance += amount;
+            let transaction = Transaction { id: self.next_id, user: *user, amount: *amount, balance: account.balance, reason: reason.to_string(), timestamp };
+            self.next_id += 1;
+            if account.log.len() == self.log_size {
+                account.log.pop_front();
+            }
+            account.log.push_back(transaction.clone());
+            transactions.push(transaction);
+        }
+        Ok(transactions)
+    }
+
+    fn credit(&mut self, user: u64, amount: i64, reason: &str) -> Result<Transaction, String> {
+        if amount <= 0 {
+            return Err(format!("Invalid amount {amount}, it must be positive"));
+        }
+        Ok(self.apply(&[(user, amount)], reason)?.remove(0))
+    }
+
+    fn debit(&mut self, user: u64, amount: i64, reason: &str) -> Result<Transaction, String> {
+        if amount <= 0 {
+            return Err(format!("Invalid amount {amount}, it must be positive"));
+        }
+        Ok(self.apply(&[(user, -amount)], reason)?.remove(0))
+    }
+
+    fn transfer(&mut self, from: u64, to: u64, amount: i64, reason: &str) -> Result<(Transaction, Transaction), String> {
+        if amount <= 0 {
+            return Err(format!("Invalid amount {amount}, it must be positive"));
+        }
+        if from == to {
+            return Err("Can't transfer to the same user".to_string());
+        }
+        let mut transactions = self.apply(&[(from, -amount), (to, amount)], reason)?;
+        let credit = transactions.pop().expect("Two transactions");
+        Ok((transactions.pop().expect("Two transactions"), credit))
+    }
+}
+
+impl Persist for Ledger {
+    const GAME: &'static str = "ledger";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { log_size: self.log_size, next_id: self.next_id, accounts: self.accounts.clone() }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        let mut ledger = Ledger::new(state.log_size)?;
+        for (user, account) in &state.accounts {
+            if account.balance < 0 {
+                return Err(format!("{user} has a negative balance"));
+            }
+            if account.log.len() > state.log_size {
+                return Err(format!("{user} has more transactions than the log keeps"));
+            }
+            if account.log.iter().any(|transaction| transaction.user != *user || transaction.id >= state.next_id) {
+                return Err(format!("The log of {user} has transactions that aren't theirs or haven't happened yet"));
+            }
+            if account.log.iter().zip(account.log.iter().skip(1)).any(|(first, second)| first.id >= second.id || first.balance + second.amount != second.balance) {
+                return Err(format!("The transactions of {user} don't add up"));
+            }
+            if account.log.back().is_some_and(|last| last.balance != account.balance) {
+                return Err(format!("The balance of {user} isn't what their last transaction left"));
+            }
+        }
+        ledger.next_id = state.next_id;
+        ledger.accounts = state.accounts;
+        Ok(ledger)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Ledger {
+    #[new]
+    #[pyo3(signature = (log_size=DEFAULT_LOG_SIZE))]
+    fn __new__(log_size: usize) -> PyResult<Self> { // COV_EXCL_LINE
+        Ledger::new(log_size).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter]
+    fn log_size(&self) -> usize {
+        self.log_size
+    }
+
+    #[pyo3(name = "balance")]
+    fn py_balance(&self, user: u64) -> i64 {
+        self.balance(user)
+    }
+
+    /// Every user with an account and their balance.
+    fn balances(&self) -> BTreeMap<u64, i64> {
+        self.accounts.iter().map(|(user, account)| (*user, account.balance)).collect()
+    }
+
+    /// The latest transactions of `user`, oldest first.
+    fn history(&self, user: u64) -> Vec<Transaction> {
+        self.accounts.get(&user).map_or_else(Vec::new, |account| account.log.iter().cloned().collect())
+    }
+
+    #[pyo3(name = "credit", signature = (user, amount, reason=""))]
+    fn py_credit(&mut self, user: u64, amount: i64, reason: &str) -> PyResult<Transaction> {
+        self.credit(user, amount, reason).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "debit", signature = (user, amount, reason=""))]
+    fn py_debit(&mut self, user: u64, amount: i64, reason: &str) -> PyResult<Transaction> {
+        self.debit(user, amount, reason).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "transfer", signature = (sender, recipient, amount, reason=""))]
+    fn py_transfer(&mut self, sender: u64, recipient: u64, amount: i64, reason: &str) -> PyResult<(Transaction, Transaction)> {
+        self.transfer(sender, recipient, amount, reason).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "apply", signature = (changes, reason=""))]
+    fn py_apply(&mut self, changes: Vec<(u64, i64)>, reason: &str) -> PyResult<Vec<Transaction>> {
+        self.apply(&changes, reason).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based points ledger with a transaction log";
+
+pub(crate) fn register_ledger(py: Python, m: &PyModule) -> PyResult<()> {
+    let ledger = PyModule::new(py, "_ledger")?;
+    ledger.add_class::<Ledger>()?;
+    ledger.add_class::<Transaction>()?;
+    ledger.add("__doc__", DOCSTRING)?;
+    m.add_submodule(ledger)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn credits_and_debits() {
+        let mut ledger = Ledger::new(DEFAULT_LOG_SIZE).expect("Failed to create");
+        assert_eq!(ledger.balance(1), 0);
+        let credit = ledger.credit(1, 100, "daily").expect("Failed to credit");
+        assert_eq!((credit.id, credit.amount, credit.balance, credit.reason.as_str()), (0, 100, 100, "daily"));
+        let debit = ledger.debit(1, 30, "bet").expect("Failed to debit");
+        assert_eq!((debit.id, debit.amount, debit.balance), (1, -30, 70));
+        assert_eq!(ledger.balance(1), 70);
+        ledger.debit(1, 71, "bet").expect_err("Expected the overdraft to be rejected");
+        assert_eq!(ledger.balance(1), 70);
+        ledger.credit(1, 0, "").expect_err("Expected nothing to be rejected");
+        ledger.debit(1, -5, "").expect_err("Expected a negative debit to be rejected");
+        ledger.debit(2, 1, "").expect_err("Expected an empty account to be overdrawn");
+        assert!(!ledger.accounts.contains_key(&2));
+    }
+
+    #[test]
+    fn transfers() {
+        let mut ledger = Ledger::new(DEFAULT_LOG_SIZE).expect("Failed to create");
+        ledger.credit(1, 50, "").expect("Failed to credit");
+        let (debit, credit) = ledger.transfer(1, 2, 20, "gift").expect("Failed to transfer");
+        assert_eq!((debit.user, debit.amount, credit.user, credit.amount), (1, -20, 2, 20));
+        assert_eq!((ledger.balance(1), ledger.balance(2)), (30, 20));
+        ledger.transfer(2, 1, 21, "").expect_err("Expected the overdraft to be rejected");
+        ledger.transfer(1, 1, 1, "").expect_err("Expected the same user to be rejected");
+        assert_eq!((ledger.balance(1), ledger.balance(2)), (30, 20));
+    }
+
+    #[test]
+    fn applies_everything_or_nothing() {
+        let mut ledger = Ledger::new(DEFAULT_LOG_SIZE).expect("Failed to create");
+        ledger.apply(&[(1, 10), (2, 10), (3, 10)], "event").expect("Failed to apply");
+        ledger.apply(&[(1, 5), (2, -11), (3, 5)], "event").expect_err("Expected the overdraft to be rejected");
+        assert_eq!(ledger.balances(), BTreeMap::from([(1, 10), (2, 10), (3, 10)]));
+        // only what's left after every change has to be covered
+        let applied = ledger.apply(&[(1, -15), (1, 5)], "swap").expect("Failed to apply");
+        assert_eq!(applied.iter().map(|transaction| transaction.balance).collect::<Vec<_>>(), vec![-5, 0]);
+        ledger.apply(&[(1, i64::MAX)], "").expect("Failed to apply");
+        ledger.apply(&[(2, 1), (1, 1)], "").expect_err("Expected the overflow to be rejected");
+        assert_eq!((ledger.balance(1), ledger.balance(2)), (i64::MAX, 10));
+    }
+
+    #[test]
+    fn caps_the_log() {
+        let mut ledger = Ledger::new(3).expect("Failed to create");
+        for amount in 1..=5 {
+            ledger.credit(1, amount, "").expect("Failed to credit");
+        }
+        ledger.credit(2, 1, "").expect("Failed to credit");
+        let history = ledger.history(1);
+        assert_eq!(history.iter().map(|transaction| (transaction.id, transaction.amount)).collect::<Vec<_>>(), vec![(2, 3), (3, 4), (4, 5)]);
+        assert!(ledger.history(3).is_empty());
+        Ledger::new(0).expect_err("Expected no log to be rejected");
+        Ledger::new(MAX_LOG_SIZE + 1).expect_err("Expected too long a log to be rejected");
+    }
+
+    #[test]
+    fn persists() {
+        let mut ledger = Ledger::new(2).expect("Failed to create");
+        ledger.apply(&[(1, 10), (2, 10), (1, 5), (1, -3)], "event").expect("Failed to apply");
+        let json = persist::to_json(&ledger).expect("Failed to save");
+        assert_eq!(persist::from_json::<Ledger>(&json), Ok(ledger.clone()));
+        let bytes = persist::to_bytes(&ledger).expect("Failed to save");
+        let mut loaded = persist::from_bytes::<Ledger>(&bytes).expect("Failed to load");
+        assert_eq!(loaded.credit(3, 1, "").expect("Failed to credit").id, 4);
+        let broken = |change: fn(&mut State)| {
+            let mut state = ledger.save();
+            change(&mut state);
+            Ledger::load(state)
+        };
+        broken(|state| state.accounts.get_mut(&1).expect("Account").balance = 13).expect_err("Expected the wrong balance to be rejected");
+        broken(|state| state.accounts.get_mut(&1).expect("Account").balance = -1).expect_err("Expected the negative balance to be rejected");
+        broken(|state| state.next_id = 2).expect_err("Expected the future transactions to be rejected");
+        broken(|state| state.log_size = 1).expect_err("Expected the long log to be rejected");
+        broken(|state| state.accounts.get_mut(&1).expect("Account").log[1].amount = -2).expect_err("Expected the wrong amount to be rejected");
+        broken(|state| state.accounts.get_mut(&2).expect("Account").log[0].user = 1).expect_err("Expected the wrong user to be rejected");
+    }
+}
+// COV_EXCL_STOP
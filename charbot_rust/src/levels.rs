@@ -0,0 +1,363 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::sync::{PoisonError, RwLock};
+
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::metrics;
+
+/// The highest level, so every curve can be checked up to it.
+const MAX_LEVEL: u32 = 1000;
+/// The most XP a level can need, so polynomial curves are exact as floats.
+const MAX_XP: f64 = 9_007_199_254_740_992.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Formula {
+    /// The same XP for every level.
+    Linear(u64),
+    /// `coefficient * level ^ exponent` XP in total for each level, rounded.
+    Polynomial { coefficient: f64, exponent: f64 },
+    /// `5n² + 50n + 100` XP to go from level `n` to the next, like the Mee6 bot.
+    Mee6,
+}
+
+// COV_EXCL_START
+/// How much XP each level takes.
+#[pyclass(module = "levels")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Curve {
+    formula: Formula,
+}
+// COV_EXCL_STOP
+
+// the curve the module functions use, the same everywhere they're called from
+static CURVE: Lazy<RwLock<Curve>> = Lazy::new(|| RwLock::new(Curve { formula: Formula::Mee6 }));
+
+impl Curve {
+    /// Check the curve needs more XP for every level than the one before, up to the highest level.
+    fn new(formula: Formula) -> Result<Self, String> {
+        let curve = Curve { formula };
+        if let Formula::Polynomial { coefficient, exponent } = formula {
+            if !(coefficient > 0.0 && exponent > 0.0) || coefficient.is_infinite() || exponent.is_infinite() {
+                return Err(format!("Invalid curve {coefficient} * level ^ {exponent}, both have to be positive"));
+            }
+            if coefficient * f64::from(MAX_LEVEL).powf(exponent) > MAX_XP {
+                return Err(format!("Invalid curve {coefficient} * level ^ {exponent}, level {MAX_LEVEL} needs too much XP"));
+            }
+        }
+        if let Formula::Linear(step) = formula {
+            if step == 0 || step as f64 * f64::from(MAX_LEVEL) > MAX_XP {
+                return Err(format!("Invalid curve of {step} XP a level, it must be from 1 to {}", MAX_XP as u64 / u64::from(MAX_LEVEL)));
+            }
+        }
+        if let Some(level) = (1..=MAX_LEVEL).find(|level| curve.total(*level) <= curve.total(level - 1)) {
+            return Err(format!("Invalid curve, level {level} doesn't need more XP than level {}", level - 1));
+        }
+        Ok(curve)
+    }
+
+    /// The XP in total to reach `level`, from 0 at level 0.
+    fn total(&self, level: u32) -> u64 {
+        let level = u64::from(level);
+        match self.formula {
+            Formula::Linear(step) => step * level,
+            Formula::Polynomial { coefficient, exponent } => (coefficient * (level as f64).powf(exponent)).round() as u64,
+            // the sum of 5n² + 50n + 100 for every level n below it
+            Formula::Mee6 => 5 * (level.saturating_sub(1) * level * (2 * level).saturating_sub(1)) / 6 + 25 * level * level.saturating_sub(1) + 100 * level,
+        }
+    }
+
+    fn xp_for_level(&self, level: u32) -> Result<u64, String> {
+        if level > MAX_LEVEL {
+            return Err(format!("Invalid level {level}, the highest is {MAX_LEVEL}"));
+        }
+        Ok(self.total(level))
+    }
+
+    /// The highest level `xp` is enough for.
+    fn level_for_xp(&self, xp: u64) -> u32 {
+        // the totals only go up, so the first level it isn't enough for is one after
+        let (mut low, mut high) = (0, MAX_LEVEL);
+        while low < high {
+            let middle = (low + high).div_ceil(2);
+            if self.total(middle) <= xp {
+                low = middle;
+            } else {
+                high = middle - 1;
+            }
+        }
+        low
+    }
+
+    fn progress(&self, xp: u64) -> Progress {
+        let level = self.level_for_xp(xp);
+        let start = self.total(level);
+        let needed = if level == MAX_LEVEL { 0 } else { self.total(level + 1) - start };
+        let current = xp - start;
+        let fraction = if needed == 0 { 1.0 } else { current as f64 / needed as f64 };
+        Progress { level, current, needed, fraction }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Curve {
+    /// The same XP for every level.
+    #[staticmethod]
+    fn linear(xp_per_level: u64) -> PyResult<Self> {
+        Curve::new(Formula::Linear(xp_per_level)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn polynomial(coefficient: f64, exponent: f64) -> PyResult<Self> {
+        Curve::new(Formula::Polynomial { coefficient, exponent }).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn mee6() -> Self {
+        Curve { formula: Formula::Mee6 }
+    }
+
+    #[pyo3(name = "xp_for_level")]
+    fn py_xp_for_level(&self, level: u32) -> PyResult<u64> {
+        self.xp_for_level(level).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "level_for_xp")]
+    fn py_level_for_xp(&self, xp: u64) -> u32 {
+        self.level_for_xp(xp)
+    }
+
+    #[pyo3(name = "progress")]
+    fn py_progress(&self, xp: u64) -> Progress {
+        self.progress(xp)
+    }
+
+    fn __repr__(&self) -> String {
+        match self.formula {
+            Formula::Linear(step) => format!("Curve.linear({step})"),
+            Formula::Polynomial { coefficient, exponent } => format!("Curve.polynomial({coefficient:?}, {exponent:?})"),
+            Formula::Mee6 => "Curve.mee6()".to_string(),
+        }
+    }
+}
+
+// COV_EXCL_START
+/// How far through their level someone is.
+#[pyclass(module = "levels")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    #[pyo3(get)]
+    level: u32,
+    /// The XP since reaching the level.
+    #[pyo3(get)]
+    current: u64,
+    /// The XP from the level to the next, 0 at the highest level.
+    #[pyo3(get)]
+    needed: u64,
+    /// How far to the next level, from 0 to 1, for a progress bar.
+    #[pyo3(get)]
+    fraction: f64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Progress {
+    fn __repr__(&self) -> String {
+        format!("Progress(level={}, current={}, needed={})", self.level, self.current, self.needed)
+    }
+}
+
+fn curve() -> Curve {
+    *CURVE.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+set_curve(curve, /)
+--
+
+Set the curve the functions of this module use, once when the bot starts.
+
+Parameters
+----------
+curve : Curve
+    The curve, the default is ``Curve.mee6()``.
+")]
+fn set_curve(curve: Curve) {
+    *CURVE.write().unwrap_or_else(PoisonError::into_inner) = curve;
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+get_curve()
+--
+
+Get the curve the functions of this module use.
+
+Returns
+-------
+Curve
+    The curve.
+")]
+fn get_curve() -> Curve {
+    curve()
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+xp_for_level(level, /)
+--
+
+Get the XP in total it takes to reach a level, with the curve set for the module.
+
+Parameters
+----------
+level : int
+    The level, up to 1000.
+
+Returns
+-------
+int
+    The XP, 0 for level 0.
+
+Raises
+------
+ValueError
+    If the level is over 1000.
+")]
+fn xp_for_level(level: u32) -> PyResult<u64> {
+    metrics::observe("levels.xp_for_level", || curve().py_xp_for_level(level))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+level_for_xp(xp, /)
+--
+
+Get the highest level some XP is enough for, with the curve set for the module.
+
+Parameters
+----------
+xp : int
+    The XP.
+
+Returns
+-------
+int
+    The level, up to 1000.
+")]
+fn level_for_xp(xp: u64) -> u32 {
+    metrics::timed("levels.level_for_xp", || curve().level_for_xp(xp))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+progress(xp, /)
+--
+
+Get the level for some XP and how far to the next, with the curve set for the module, for a rank card.
+
+Parameters
+----------
+xp : int
+    The XP.
+
+Returns
+-------
+Progress
+    The level, the XP since reaching it and the XP it takes to reach the next.
+")]
+fn progress(xp: u64) -> Progress {
+    metrics::timed("levels.progress", || curve().progress(xp))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based XP leveling curves";
+
+pub(crate) fn register_levels(py: Python, m: &PyModule) -> PyResult<()> {
+    let levels = PyModule::new(py, "_levels")?;
+    levels.add_class::<Curve>()?;
+    levels.add_class::<Progress>()?;
+    levels.add_function(wrap_pyfunction!(set_curve, levels)?)?;
+    levels.add_function(wrap_pyfunction!(get_curve, levels)?)?;
+    levels.add_function(wrap_pyfunction!(xp_for_level, levels)?)?;
+    levels.add_function(wrap_pyfunction!(level_for_xp, levels)?)?;
+    levels.add_function(wrap_pyfunction!(progress, levels)?)?;
+    levels.add("__doc__", DOCSTRING)?;
+    m.add_submodule(levels)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[test]
+    fn mee6() {
+        let curve = Curve::mee6();
+        let totals = (0..=5).map(|level| curve.total(level)).collect::<Vec<_>>();
+        assert_eq!(totals, vec![0, 100, 255, 475, 770, 1150]);
+        // the closed form matches adding up every level
+        let mut total = 0;
+        for level in 0..MAX_LEVEL {
+            assert_eq!(curve.total(level), total);
+            let level = u64::from(level);
+            total += 5 * level * level + 50 * level + 100;
+        }
+        assert_eq!(curve.total(MAX_LEVEL), total);
+    }
+
+    #[parameterized(
+        nothing = {0, 0},
+        almost = {99, 0},
+        exactly = {100, 1},
+        between = {1149, 4},
+        fifth = {1150, 5},
+        everything = {u64::MAX, MAX_LEVEL},
+    )]
+    fn levels(xp: u64, level: u32) {
+        assert_eq!(Curve::mee6().level_for_xp(xp), level);
+    }
+
+    #[test]
+    fn other_curves() {
+        let linear = Curve::new(Formula::Linear(100)).expect("Expected a valid curve");
+        assert_eq!((linear.total(3), linear.level_for_xp(299), linear.level_for_xp(300)), (300, 2, 3));
+        let squared = Curve::new(Formula::Polynomial { coefficient: 50.0, exponent: 2.0 }).expect("Expected a valid curve");
+        assert_eq!((squared.total(2), squared.total(10)), (200, 5000));
+        assert_eq!(squared.level_for_xp(4999), 9);
+        Curve::new(Formula::Linear(0)).expect_err("Expected a flat curve to be rejected");
+        Curve::new(Formula::Polynomial { coefficient: 0.0, exponent: 2.0 }).expect_err("Expected a flat curve to be rejected");
+        Curve::new(Formula::Polynomial { coefficient: 1.0, exponent: -1.0 }).expect_err("Expected a falling curve to be rejected");
+        Curve::new(Formula::Polynomial { coefficient: f64::NAN, exponent: 2.0 }).expect_err("Expected an invalid curve to be rejected");
+        // rounding leaves levels needing no more than the one before
+        Curve::new(Formula::Polynomial { coefficient: 0.1, exponent: 1.0 }).expect_err("Expected a rounded flat curve to be rejected");
+        Curve::new(Formula::Polynomial { coefficient: 1.0, exponent: 10.0 }).expect_err("Expected a huge curve to be rejected");
+        Curve::new(Formula::Linear(u64::MAX)).expect_err("Expected a huge curve to be rejected");
+    }
+
+    #[test]
+    fn progresses() {
+        let curve = Curve::mee6();
+        assert_eq!(curve.progress(0), Progress { level: 0, current: 0, needed: 100, fraction: 0.0 });
+        assert_eq!(curve.progress(300), Progress { level: 2, current: 45, needed: 220, fraction: 45.0 / 220.0 });
+        let top = curve.progress(u64::MAX);
+        assert_eq!((top.level, top.needed, top.fraction), (MAX_LEVEL, 0, 1.0));
+        curve.xp_for_level(MAX_LEVEL + 1).expect_err("Expected too high a level to be rejected");
+        assert_eq!(curve.xp_for_level(3), Ok(475));
+    }
+
+    #[test]
+    fn module_curve() {
+        assert_eq!(get_curve(), Curve::mee6());
+        set_curve(Curve::new(Formula::Linear(10)).expect("Expected a valid curve"));
+        assert_eq!((level_for_xp(25), progress(25).current), (2, 5));
+        set_curve(Curve::mee6());
+        assert_eq!(level_for_xp(25), 0);
+    }
+}
+// COV_EXCL_STOP
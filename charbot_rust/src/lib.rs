@@ -3,17 +3,196 @@
 // SPDX-License-Identifier: MIT
 // COV_EXCL_START COV_EXCL_LINE
 use pyo3::prelude::*;
+#[cfg(feature = "games")]
 mod tictactoe;
+#[cfg(feature = "games")]
+mod connect4;
+#[cfg(feature = "games")]
+mod checkers;
+#[cfg(feature = "games")]
+mod reversi;
+#[cfg(feature = "games")]
+mod battleship;
+#[cfg(feature = "games")]
+mod sudoku;
+#[cfg(feature = "games")]
+mod shrugman;
+#[cfg(feature = "games")]
+mod twenty48;
+#[cfg(feature = "games")]
+mod wordle;
+#[cfg(feature = "games")]
+mod chess;
+#[cfg(feature = "games")]
+mod blackjack;
+#[cfg(feature = "games")]
+mod poker;
+#[cfg(feature = "games")]
+mod dice;
+#[cfg(feature = "games")]
+mod trivia;
+#[cfg(feature = "games")]
+mod words;
+#[cfg(feature = "games")]
+mod slots;
+#[cfg(feature = "community")]
+mod brackets;
+#[cfg(feature = "games")]
+mod maze;
+#[cfg(feature = "community")]
+mod ledger;
+#[cfg(feature = "community")]
+mod levels;
+#[cfg(feature = "community")]
+mod leaderboard;
+// the time zones of cooldowns are used by the schedules and the time parsing too
+#[cfg_attr(not(feature = "community"), allow(dead_code))]
+mod cooldowns;
+#[cfg(feature = "community")]
+mod giveaway;
+#[cfg(feature = "community")]
+mod economy;
+#[cfg(feature = "community")]
+mod streaks;
+#[cfg(feature = "moderation")]
+mod moderation;
+#[cfg(feature = "markdown")]
+mod markdown;
+// the keys of rate limits are the keys of the cache too
+#[cfg_attr(not(feature = "community"), allow(dead_code))]
+mod ratelimit;
+mod cache;
+#[cfg(feature = "images")]
+mod images;
+#[cfg(feature = "images")]
+mod colors;
+#[cfg(all(feature = "fluent", feature = "games"))]
+mod timeparse;
+#[cfg(feature = "fluent")]
+mod schedule;
+mod discordfmt;
+mod snowflake;
+mod crypto;
+mod json;
+#[cfg(feature = "compression")]
+mod zstd;
+#[cfg(feature = "store")]
+mod store;
+mod hashing;
+mod emoji;
+mod fuzzy;
+mod search;
+mod markov;
+#[cfg(feature = "fluent")]
+mod namegen;
+#[cfg(feature = "games")]
 mod points;
+mod persist;
+#[cfg(any(feature = "fluent", feature = "moderation"))]
+mod tasks;
+// the emoji are assets too, so they're there without the translations, which are the rest of it
+#[cfg_attr(not(feature = "fluent"), allow(dead_code))]
+mod assets;
+mod buffer;
+mod logging;
+mod metrics;
+mod profiling;
+#[cfg(feature = "games")]
 mod minesweeper;
+#[cfg(feature = "fluent")]
 mod fluent;
 
 /// A rewrite of parts of charbot in rust.
 #[pymodule]
 #[pyo3(name = "_charbot_rust")]
 fn charbot_rust(py: Python, m: &PyModule) -> PyResult<()> {
+    #[cfg(feature = "games")]
     tictactoe::register_tictactoe(py, m)?;
+    #[cfg(feature = "games")]
     minesweeper::register_minesweeper(py, m)?;
+    #[cfg(feature = "games")]
+    connect4::register_connect4(py, m)?;
+    #[cfg(feature = "games")]
+    checkers::register_checkers(py, m)?;
+    #[cfg(feature = "games")]
+    reversi::register_reversi(py, m)?;
+    #[cfg(feature = "games")]
+    battleship::register_battleship(py, m)?;
+    #[cfg(feature = "games")]
+    sudoku::register_sudoku(py, m)?;
+    #[cfg(feature = "games")]
+    shrugman::register_shrugman(py, m)?;
+    #[cfg(feature = "games")]
+    twenty48::register_twenty48(py, m)?;
+    #[cfg(feature = "games")]
+    wordle::register_wordle(py, m)?;
+    #[cfg(feature = "games")]
+    chess::register_chess(py, m)?;
+    #[cfg(feature = "games")]
+    blackjack::register_blackjack(py, m)?;
+    #[cfg(feature = "games")]
+    poker::register_poker(py, m)?;
+    #[cfg(feature = "games")]
+    dice::register_dice(py, m)?;
+    #[cfg(feature = "games")]
+    trivia::register_trivia(py, m)?;
+    #[cfg(feature = "games")]
+    words::register_words(py, m)?;
+    #[cfg(feature = "games")]
+    slots::register_slots(py, m)?;
+    #[cfg(feature = "community")]
+    brackets::register_brackets(py, m)?;
+    #[cfg(feature = "games")]
+    maze::register_maze(py, m)?;
+    #[cfg(feature = "community")]
+    ledger::register_ledger(py, m)?;
+    #[cfg(feature = "community")]
+    levels::register_levels(py, m)?;
+    #[cfg(feature = "community")]
+    leaderboard::register_leaderboard(py, m)?;
+    #[cfg(feature = "community")]
+    cooldowns::register_cooldowns(py, m)?;
+    #[cfg(feature = "community")]
+    giveaway::register_giveaway(py, m)?;
+    #[cfg(feature = "community")]
+    economy::register_economy(py, m)?;
+    #[cfg(feature = "community")]
+    streaks::register_streaks(py, m)?;
+    #[cfg(feature = "moderation")]
+    moderation::register_moderation(py, m)?;
+    #[cfg(feature = "markdown")]
+    markdown::register_markdown(py, m)?;
+    #[cfg(feature = "community")]
+    ratelimit::register_ratelimit(py, m)?;
+    cache::register_cache(py, m)?;
+    #[cfg(feature = "images")]
+    images::register_images(py, m)?;
+    #[cfg(feature = "images")]
+    colors::register_colors(py, m)?;
+    #[cfg(all(feature = "fluent", feature = "games"))]
+    timeparse::register_timeparse(py, m)?;
+    #[cfg(feature = "fluent")]
+    schedule::register_schedule(py, m)?;
+    discordfmt::register_discordfmt(py, m)?;
+    snowflake::register_snowflake(py, m)?;
+    crypto::register_crypto(py, m)?;
+    json::register_json(py, m)?;
+    #[cfg(feature = "compression")]
+    zstd::register_zstd(py, m)?;
+    #[cfg(feature = "store")]
+    store::register_store(py, m)?;
+    hashing::register_hashing(py, m)?;
+    emoji::register_emoji(py, m)?;
+    fuzzy::register_fuzzy(py, m)?;
+    search::register_search(py, m)?;
+    markov::register_markov(py, m)?;
+    #[cfg(feature = "fluent")]
+    namegen::register_namegen(py, m)?;
+    m.add_class::<buffer::Buffer>()?;
+    logging::register_logging(m)?;
+    metrics::register_metrics(py, m)?;
+    profiling::register_profiling(m)?;
+    #[cfg(feature = "fluent")]
     fluent::register_fluent(m)?;
     Ok(())
 }
@@ -0,0 +1,183 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Forwarding what goes wrong inside the extension to python's `logging`, once `init_logging` is called.
+//!
+//! The extension logs with `tracing`, to targets named after the part of the extension it happened in, like
+//! `charbot_rust.fluent` for translating, `charbot_rust.games` for games and `charbot_rust.tasks` for the
+//! threads the awaitable functions run on, which are the names of the python loggers they're forwarded to.
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Once;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use tracing::field::{Field, Visit};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::prelude::*;
+
+// nothing is forwarded until init_logging is called, so there's no cost to not using it
+static THRESHOLD: AtomicU32 = AtomicU32::new(u32::MAX);
+
+/// A level of python's `logging`, by its number or its name.
+#[derive(FromPyObject)]
+pub(crate) enum Threshold {
+    #[pyo3(transparent)]
+    Number(u32),
+    #[pyo3(transparent)]
+    Name(String),
+}
+
+fn threshold(level: Threshold) -> Result<u32, String> {
+    match level {
+        Threshold::Number(number) => Ok(number),
+        Threshold::Name(name) => match name.to_ascii_uppercase().as_str() {
+            "NOTSET" => Ok(0),
+            "DEBUG" => Ok(10),
+            "INFO" => Ok(20),
+            "WARNING" | "WARN" => Ok(30),
+            "ERROR" => Ok(40),
+            "CRITICAL" | "FATAL" => Ok(50),
+            _ => Err(format!("Invalid level {name:?}, it must be DEBUG, INFO, WARNING, ERROR, CRITICAL or a number")),
+        },
+    }
+}
+
+/// The level of python's `logging` an event at `level` is logged at, python has nothing below `DEBUG`.
+fn python_level(level: &Level) -> u32 {
+    match *level {
+        Level::ERROR => 40,
+        Level::WARN => 30,
+        Level::INFO => 20,
+        Level::DEBUG => 10,
+        _ => 5,
+    }
+}
+
+/// The message of an event, followed by any other fields it has as `name=value`.
+#[derive(Default)]
+struct Message(String);
+
+impl Visit for Message {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{value:?}");
+        } else {
+            let _ = write!(self.0, " {field}={value:?}");
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.0.push_str(value);
+        } else {
+            let _ = write!(self.0, " {field}={value}");
+        }
+    }
+}
+
+/// Forwards the events of the extension to the python logger named by their target, like
+/// `charbot_rust.fluent`, if they're at least the level `init_logging` was called with.
+struct ToPython;
+
+impl<S: Subscriber> Layer<S> for ToPython {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // the level can change with init_logging, so whether an event is logged can't be cached
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        python_level(metadata.level()) >= THRESHOLD.load(Ordering::Relaxed)
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = Message::default();
+        event.record(&mut message);
+        let (target, level) = (event.metadata().target().replace("::", "."), python_level(event.metadata().level()));
+        Python::with_gil(|py| {
+            // a failure to log has nowhere to be logged, and mustn't fail what was being done
+            let _ = py
+                .import("logging")
+                .and_then(|logging| logging.call_method1("getLogger", (target,)))
+                .and_then(|logger| logger.call_method1("log", (level, message.0)));
+        });
+    }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+init_logging(level, /)
+--
+
+Forward what goes wrong inside charbot_rust to the logging module, at the level given and above.
+
+Events are logged to ``charbot_rust.fluent`` for translations, like a locale falling back to another
+because a message failed to format, ``charbot_rust.games`` for games, like a saved game that couldn't
+be loaded, and ``charbot_rust.tasks`` for the thread pool of the awaitable functions. Nothing is
+forwarded until this is called, and calling it again changes the level.
+
+Parameters
+----------
+level : int | str
+    The lowest level to forward, like ``logging.INFO`` or ``'WARNING'``.
+
+Raises
+------
+ValueError
+    If the level is a name that isn't a level of the logging module.
+")]
+pub(crate) fn init_logging(level: Threshold) -> PyResult<()> {
+    static FORWARDING: Once = Once::new();
+    THRESHOLD.store(threshold(level).map_err(PyErr::new::<PyValueError, _>)?, Ordering::Relaxed);
+    // if the program embedding python already set a subscriber, the events go to it instead
+    FORWARDING.call_once(|| {
+        let _ = tracing::subscriber::set_global_default(tracing_subscriber::registry().with(ToPython));
+    });
+    Ok(())
+}
+
+// COV_EXCL_START
+pub(crate) fn register_logging(m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(init_logging, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        number = {Threshold::Number(25), 25},
+        debug = {Threshold::Name(String::from("DEBUG")), 10},
+        lowercase = {Threshold::Name(String::from("info")), 20},
+        warn = {Threshold::Name(String::from("Warn")), 30},
+        critical = {Threshold::Name(String::from("CRITICAL")), 50},
+        notset = {Threshold::Name(String::from("NOTSET")), 0},
+    )]
+    fn levels(level: Threshold, expected: u32) {
+        assert_eq!(threshold(level), Ok(expected));
+    }
+
+    #[test]
+    fn invalid_level() {
+        assert_eq!(
+            threshold(Threshold::Name(String::from("loud"))),
+            Err(String::from("Invalid level \"loud\", it must be DEBUG, INFO, WARNING, ERROR, CRITICAL or a number"))
+        );
+    }
+
+    #[parameterized(
+        error = {Level::ERROR, 40},
+        warn = {Level::WARN, 30},
+        info = {Level::INFO, 20},
+        debug = {Level::DEBUG, 10},
+        trace = {Level::TRACE, 5},
+    )]
+    fn python_levels(level: Level, expected: u32) {
+        assert_eq!(python_level(&level), expected);
+    }
+}
+// COV_EXCL_STOP
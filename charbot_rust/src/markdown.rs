@@ -0,0 +1,344 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+pub(crate) mod chunk; // COV_EXCL_LINE
+mod parse;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::markdown::parse::{parse, Node};
+use crate::metrics;
+
+/// What Discord's markdown can format.
+#[pyclass(module = "markdown")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Feature { // COV_EXCL_LINE
+    /// `**bold**`
+    Bold,
+    /// `*italic*` or `_italic_`
+    Italic,
+    /// `__underline__`
+    Underline,
+    /// `~~strikethrough~~`
+    Strikethrough,
+    /// `||spoiler||`
+    Spoiler,
+    /// `` `inline code` ``
+    Code,
+    /// ```` ```code blocks``` ````, with or without a language.
+    CodeBlock,
+    /// `> ` and `>>> ` at the start of a line.
+    Quote,
+    /// `# `, `## ` and `### ` at the start of a line.
+    Header,
+    /// `-# ` at the start of a line.
+    Subtext,
+    /// `- `, `* ` and `1. ` at the start of a line.
+    List,
+    /// `[masked](https://links)`
+    Link,
+}
+
+/// Whether the next character written to `out` starts a line, after any indent.
+fn at_line_start(out: &str) -> bool {
+    let mut letters = out.chars().rev().skip_while(|letter| *letter == ' ');
+    letters.next().map_or(true, |letter| letter == '\n')
+}
+
+/// Write `text` to `out` with everything Discord would format escaped, so it shows as it was typed.
+///
+/// Links are left as they are, since escaping them breaks them and they aren't formatted anyway.
+fn escape_into(text: &str, out: &mut String) {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut index = 0;
+    while index < chars.len() {
+        let rest = || chars[index..].iter().take(8).collect::<String>();
+        if rest().starts_with("https://") || rest().starts_with("http://") {
+            let length = chars[index..].iter().take_while(|letter| !letter.is_whitespace()).count();
+            out.extend(&chars[index..index + length]);
+            index += length;
+            continue;
+        }
+        let letter = chars[index];
+        let line_start = at_line_start(out);
+        match letter {
+            '\\' | '*' | '_' | '~' | '|' | '`' | '[' | ']' => out.push('\\'),
+            '>' | '#' if line_start => out.push('\\'),
+            '-' if line_start && chars.get(index + 1).is_some_and(|next| matches!(next, ' ' | '#')) => out.push('\\'),
+            // a number and a dot start a list, so the dot is escaped
+            '0'..='9' if line_start => {
+                let digits = chars[index..].iter().take_while(|letter| letter.is_ascii_digit()).count();
+                out.extend(&chars[index..index + digits]);
+                index += digits;
+                if chars.get(index) == Some(&'.') && chars.get(index + 1) == Some(&' ') {
+                    out.push_str("\\.");
+                    index += 1;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        out.push(letter);
+        index += 1;
+    }
+}
+
+/// Write `nodes` to `out` with only the `allowed` features, or as plain text if there aren't any.
+///
+/// What isn't allowed has its formatting removed and its text kept, and masked links have where they
+/// go written after them, so they can't hide it.
+fn render(nodes: &[Node], allowed: Option<&[Feature]>, out: &mut String) {
+    let allows = |feature: Feature| allowed.is_some_and(|allowed| allowed.contains(&feature));
+    let text = |text: &str, out: &mut String| match allowed {
+        Some(_) => escape_into(text, out),
+        None => out.push_str(text),
+    };
+    for node in nodes {
+        match node {
+            Node::Text(plain) => text(plain, out),
+            Node::Marker(feature, marker) if allows(*feature) => out.push_str(marker),
+            // lists are kept as text, since they're as much what was written as how it looks
+            Node::Marker(Feature::List, marker) => text(marker, out),
+            Node::Marker(_, _) => {}
+            Node::Span(feature, delimiter, children) if allows(*feature) => {
+                out.push_str(delimiter);
+                render(children, allowed, out);
+                out.push_str(delimiter);
+            }
+            Node::Span(_, _, children) => render(children, allowed, out),
+            Node::Code(ticks, code) if allows(Feature::Code) => {
+                let ticks = "`".repeat(*ticks);
+                out.push_str(&format!("{ticks}{code}{ticks}"));
+            }
+            Node::Code(_, code) => text(code, out),
+            Node::Block(language, body) if allows(Feature::CodeBlock) => match language {
+                Some(language) => out.push_str(&format!("```{language}\n{body}```")),
+                None => out.push_str(&format!("```{body}```")),
+            },
+            Node::Block(_, body) => text(body.strip_prefix('\n').unwrap_or(body), out),
+            Node::Link(children, url) if allows(Feature::Link) => {
+                out.push('[');
+                render(children, allowed, out);
+                out.push_str(&format!("]({url})"));
+            }
+            Node::Link(children, url) => {
+                render(children, allowed, out);
+                out.push_str(&format!(" ({})", url.trim_start_matches('<').trim_end_matches('>')));
+            }
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+escape(text, /)
+--
+
+Escape text so Discord shows it as it was typed, without formatting it.
+
+Everything is escaped, backticks in code blocks too, so text with code in it can't break out of
+it, other than links, which escaping breaks.
+
+Parameters
+----------
+text : str
+    The text.
+
+Returns
+-------
+str
+    The escaped text.
+")]
+fn escape(text: &str) -> String {
+    metrics::timed("markdown.escape", || {
+        let mut escaped = String::with_capacity(text.len());
+        escape_into(text, &mut escaped);
+        escaped
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+strip(text, /)
+--
+
+Remove the formatting from a message, leaving the text as it would be read.
+
+Code keeps what's in it, and masked links have where they go written after them, like 'text (https://example.com)'.
+
+Parameters
+----------
+text : str
+    The message.
+
+Returns
+-------
+str
+    The text of the message, without markdown.
+")]
+fn strip(text: &str) -> String {
+    metrics::timed("markdown.strip", || {
+        let mut stripped = String::with_capacity(text.len());
+        render(&parse(text), None, &mut stripped);
+        stripped
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+sanitize(text, allowed_features, /)
+--
+
+Remove the formatting from a message that isn't allowed, and escape the rest of the text.
+
+Formatting that isn't allowed keeps its text, like 'spoiler' for '||spoiler||' if spoilers aren't
+allowed, and masked links that aren't have where they go written after them. Nested formatting,
+code and escapes are handled like Discord does, so backticks in code blocks don't break them.
+
+Parameters
+----------
+text : str
+    The message.
+allowed_features : list[Feature]
+    The formatting that's allowed.
+
+Returns
+-------
+str
+    The message with only the allowed formatting.
+")]
+fn sanitize(text: &str, allowed_features: Vec<Feature>) -> String {
+    metrics::timed("markdown.sanitize", || {
+        let mut sanitized = String::with_capacity(text.len());
+        render(&parse(text), Some(&allowed_features), &mut sanitized);
+        sanitized
+    })
+}
+
+#[pyfunction]
+#[pyo3(name = "chunk", signature = (text, limit=2000, code_block_lang=None), text_signature = "
+chunk(text, limit=2000, code_block_lang=None, /)
+--
+
+Split text too long for one message into messages, without breaking up its markdown.
+
+Text is split at the end of a paragraph if it can be, then a line, a sentence, a word or anywhere.
+Formatting like bold and masked links is kept in one message unless it's too long to fit, and code
+blocks that have to be split are closed at the end of the message and reopened at the start of the next.
+
+Parameters
+----------
+text : str
+    The text.
+limit : int
+    The most characters a message can have, at least 20. The default is 2000, Discord's limit.
+code_block_lang : str | None
+    The language to put the text in a code block with, like for log dumps, with any backtick fences in
+    it broken up so they can't close it. The default is None, for text that isn't in a code block.
+
+Returns
+-------
+list[str]
+    The messages, none of them empty.
+
+Raises
+------
+ValueError
+    If the limit is less than 20 or too short for the formatting nested in the text, or the language
+    isn't a valid code block language.
+")]
+fn py_chunk(text: &str, limit: usize, code_block_lang: Option<&str>) -> PyResult<Vec<String>> {
+    metrics::observe("markdown.chunk", || chunk::chunk(text, limit, code_block_lang).map_err(PyErr::new::<PyValueError, _>))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based Discord markdown escaping, sanitizing and splitting";
+
+pub(crate) fn register_markdown(py: Python, m: &PyModule) -> PyResult<()> {
+    let markdown = PyModule::new(py, "_markdown")?;
+    markdown.add_class::<Feature>()?;
+    markdown.add_function(wrap_pyfunction!(escape, markdown)?)?;
+    markdown.add_function(wrap_pyfunction!(strip, markdown)?)?;
+    markdown.add_function(wrap_pyfunction!(sanitize, markdown)?)?;
+    markdown.add_function(wrap_pyfunction!(py_chunk, markdown)?)?;
+    markdown.add("__doc__", DOCSTRING)?;
+    m.add_submodule(markdown)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const EVERYTHING: [Feature; 12] = [
+        Feature::Bold,
+        Feature::Italic,
+        Feature::Underline,
+        Feature::Strikethrough,
+        Feature::Spoiler,
+        Feature::Code,
+        Feature::CodeBlock,
+        Feature::Quote,
+        Feature::Header,
+        Feature::Subtext,
+        Feature::List,
+        Feature::Link,
+    ];
+
+    #[parameterized(
+        plain = {"hello", "hello"},
+        formatting = {"**a** _b_ ~~c~~ ||d||", "\\*\\*a\\*\\* \\_b\\_ \\~\\~c\\~\\~ \\|\\|d\\|\\|"},
+        code_fence = {"```py\nx = `1`\n```", "\\`\\`\\`py\nx = \\`1\\`\n\\`\\`\\`"},
+        backslash = {"a\\b", "a\\\\b"},
+        link = {"[a](https://example.com/a_b)", "\\[a\\](https://example.com/a_b)"},
+        bare_link = {"see https://example.com/a_b_c", "see https://example.com/a_b_c"},
+        quote = {"> a\n  > b", "\\> a\n  \\> b"},
+        header = {"# a #b", "\\# a #b"},
+        list = {"- a\n1. b\n2.5", "\\- a\n1\\. b\n2.5"},
+        subtext = {"-# a - b", "\\-# a - b"},
+    )]
+    fn escaping(text: &str, expected: &str) {
+        assert_eq!(escape(text), expected);
+        // escaped text has no formatting left in it
+        assert_eq!(strip(&escape(text)), text);
+    }
+
+    #[parameterized(
+        formatting = {"**a** _b_ __c__ ~~d~~ ||e||", "a b c d e"},
+        nested = {"**a _b_ ~~c~~**", "a b c"},
+        code = {"use `**` for bold", "use ** for bold"},
+        block = {"```py\nprint('hi')\n```", "print('hi')\n"},
+        link = {"[click **here**](https://example.com)", "click here (https://example.com)"},
+        markers = {"# a\n> b\n-# c\n- d", "a\nb\nc\n- d"},
+        escapes = {"\\*a\\*", "*a*"},
+    )]
+    fn stripping(text: &str, expected: &str) {
+        assert_eq!(strip(text), expected);
+    }
+
+    #[test]
+    fn sanitizing() {
+        let only = |text: &str, allowed: &[Feature]| sanitize(text, allowed.to_vec());
+        assert_eq!(only("**a** ||b||", &[Feature::Bold]), "**a** b");
+        assert_eq!(only("||**a**||", &[Feature::Bold]), "**a**");
+        assert_eq!(only("`**` ||x||", &[Feature::Code]), "`**` x");
+        assert_eq!(only("`**`", &[]), "\\*\\*");
+        assert_eq!(only("```py\nx `y`\n```", &[Feature::CodeBlock]), "```py\nx `y`\n```");
+        assert_eq!(only("[a](https://evil.com)", &[Feature::Bold]), "a (https://evil.com)");
+        assert_eq!(only("# big", &[]), "big");
+        assert_eq!(only("- item", &[]), "\\- item");
+        assert_eq!(only("\\*not italic\\*", &EVERYTHING), "\\*not italic\\*");
+    }
+
+    #[parameterized(
+        formatting = {"**a** _b_ __c__ ~~d~~ ||e|| *f*"},
+        code = {"`a` ``b`c`` ```py\nd\n```"},
+        lines = {"# a\n> b\n-# c\n- d\n1. e"},
+        link = {"[**a**](https://example.com)"},
+    )]
+    fn sanitizing_everything(text: &str) {
+        assert_eq!(sanitize(text, EVERYTHING.to_vec()), text);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,242 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::markdown::parse::{frames, Frame};
+
+/// The shortest limit a message can be split to, enough for the delimiters around a piece.
+const MIN_LIMIT: usize = 20;
+/// What breaks up the fences in text put in a code block, so it can't close the block early.
+const BROKEN_FENCE: &str = "`\u{200b}`\u{200b}`";
+
+/// How good a place is to split at, the start of a paragraph being the best.
+fn class(chars: &[char], index: usize) -> usize {
+    let before = chars[index - 1];
+    match before {
+        '\n' if index >= 2 && chars[index - 2] == '\n' => 4,
+        '\n' => 3,
+        _ if before.is_whitespace() && index >= 2 && matches!(chars[index - 2], '.' | '!' | '?') => 2,
+        _ if before.is_whitespace() => 1,
+        _ => 0,
+    }
+}
+
+/// The formatting open at each place in a message, the delimiters it needs around a piece, and where it can't be split.
+struct Layout {
+    frames: Vec<Frame>,
+    parents: Vec<Option<usize>>,
+    /// The innermost frame each place is in.
+    innermost: Vec<Option<usize>>,
+    /// Whether a place is between the characters of a delimiter or an escape.
+    blocked: Vec<bool>,
+}
+
+impl Layout {
+    fn new(chars: &[char], frames: Vec<Frame>) -> Self {
+        let mut parents = Vec::with_capacity(frames.len());
+        let mut open: Vec<usize> = Vec::new();
+        let mut innermost = vec![None; chars.len() + 1];
+        let mut blocked = (0..=chars.len()).map(|index| index > 0 && index < chars.len() && chars[index - 1] == '\\' && chars[index].is_ascii_punctuation()).collect::<Vec<_>>();
+        for (index, frame) in frames.iter().enumerate() {
+            // frames come outermost first, so the open ones that end before this one starts are done
+            while open.last().is_some_and(|parent| frames[*parent].content_end < frame.start) {
+                open.pop();
+            }
+            parents.push(open.last().copied());
+            open.push(index);
+            innermost[frame.content_start..=frame.content_end].fill(Some(index));
+            // splitting between a delimiter and what's in it would leave an empty piece of formatting
+            blocked[frame.start + 1..=frame.content_start].fill(true);
+            if frame.content_end < frame.end {
+                blocked[frame.content_end..frame.end].fill(true);
+            }
+        }
+        Layout { frames, parents, innermost, blocked }
+    }
+
+    /// The frames open at `index`, outermost first.
+    fn open(&self, index: usize) -> Vec<&Frame> {
+        let mut open = std::iter::successors(self.innermost[index], |frame| self.parents[*frame]).map(|frame| &self.frames[frame]).collect::<Vec<_>>();
+        open.reverse();
+        open
+    }
+
+    fn opener_len(&self, index: usize) -> usize {
+        self.open(index).iter().map(|frame| frame.opener.chars().count()).sum()
+    }
+
+    fn closer_len(&self, index: usize) -> usize {
+        self.open(index).iter().map(|frame| frame.closer.chars().count()).sum()
+    }
+
+    /// Whether splitting at `index` doesn't break up any formatting.
+    fn is_clean(&self, index: usize) -> bool {
+        self.open(index).iter().all(|frame| !frame.breaks)
+    }
+}
+
+/// The best place to split the piece starting at `start`, which is the end if the rest fits.
+fn split_at(chars: &[char], layout: &Layout, start: usize, limit: usize) -> Option<usize> {
+    let opener = layout.opener_len(start);
+    let window = limit.checked_sub(opener)?;
+    // the latest place of each class to split at, for places that are clean and ones that aren't
+    let mut best = [[None; 5]; 2];
+    for index in start + 1..=chars.len() {
+        if index - start > window {
+            break;
+        }
+        if layout.blocked[index] || index - start + layout.closer_len(index) > window {
+            continue;
+        }
+        if index == chars.len() {
+            return Some(index);
+        }
+        best[usize::from(!layout.is_clean(index))][class(chars, index)] = Some(index);
+    }
+    let long_enough = |index: &usize| index - start >= window / 2;
+    let clean = best[0][1..].iter().rev().flatten().copied();
+    clean.clone().find(long_enough).or_else(|| clean.clone().next()).or_else(|| best[1][1..].iter().rev().flatten().copied().next()).or(best[0][0]).or(best[1][0])
+}
+
+/// `text` split into pieces of at most `limit` characters, at the ends of paragraphs, lines, sentences
+/// or words, without splitting up formatting if there's anywhere else to split.
+///
+/// Formatting that has to be split, like a long code block, is closed at the end of the piece and
+/// reopened at the start of the next. Whitespace between pieces is dropped, unless it's in code.
+pub fn chunk(text: &str, limit: usize, language: Option<&str>) -> Result<Vec<String>, String> {
+    if limit < MIN_LIMIT {
+        return Err(format!("Invalid limit {limit}, it must be at least {MIN_LIMIT}"));
+    }
+    let (chars, frames) = match language {
+        Some(language) => {
+            if !language.chars().all(|letter| letter.is_ascii_alphanumeric() || "+-_#.".contains(letter)) {
+                return Err(format!("Invalid code block language {language:?}"));
+            }
+            let chars = text.replace("```", BROKEN_FENCE).chars().collect::<Vec<_>>();
+            let length = chars.len();
+            let block = Frame {
+                start: 0,
+                content_start: 0,
+                content_end: length,
+                end: length,
+                opener: format!("```{language}\n"),
+                closer: "```".to_string(),
+                breaks: false,
+            };
+            (chars, vec![block])
+        }
+        None => {
+            let chars = text.chars().collect::<Vec<_>>();
+            let frames = frames(&chars);
+            (chars, frames)
+        }
+    };
+    let layout = Layout::new(&chars, frames);
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let whitespace = |index: &usize| chars[*index].is_whitespace();
+        if layout.open(start).is_empty() && whitespace(&start) {
+            start += 1;
+            continue;
+        }
+        let end = split_at(&chars, &layout, start, limit).ok_or_else(|| format!("Invalid limit {limit}, the formatting is nested too deep to fit"))?;
+        let mut piece = layout.open(start).iter().map(|frame| frame.opener.as_str()).collect::<String>();
+        let mut content = chars[start..end].iter().collect::<String>();
+        let closers = layout.open(end).iter().rev().map(|frame| frame.closer.as_str()).collect::<String>();
+        if layout.open(end).is_empty() {
+            content.truncate(content.trim_end().len());
+        }
+        piece.push_str(&content);
+        piece.push_str(&closers);
+        if !content.is_empty() {
+            pieces.push(piece);
+        }
+        start = end;
+    }
+    Ok(pieces)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown::parse::{parse, Node};
+
+    fn pieces(text: &str, limit: usize) -> Vec<String> {
+        chunk(text, limit, None).expect("Expected the text to be split")
+    }
+
+    #[test]
+    fn fits() {
+        assert_eq!(pieces("short", 2000), vec!["short"]);
+        assert!(pieces("", 2000).is_empty());
+        assert!(pieces(" \n ", 2000).is_empty());
+    }
+
+    #[test]
+    fn boundaries() {
+        let paragraphs = "First paragraph here.\n\nSecond one is here.";
+        assert_eq!(pieces(paragraphs, 30), vec!["First paragraph here.", "Second one is here."]);
+        let sentences = "One sentence here. Another sentence. And one more.";
+        assert_eq!(pieces(sentences, 40), vec!["One sentence here. Another sentence.", "And one more."]);
+        let words = "no sentences just a lot of words in a row";
+        assert_eq!(pieces(words, 20), vec!["no sentences just a", "lot of words in a", "row"]);
+        let word = "a".repeat(50);
+        assert_eq!(pieces(&word, 20), vec!["a".repeat(20), "a".repeat(20), "a".repeat(10)]);
+    }
+
+    #[test]
+    fn keeps_formatting_together() {
+        let text = "some words **bold words stay** together";
+        assert_eq!(pieces(text, 32), vec!["some words **bold words stay**", "together"]);
+        assert_eq!(pieces(text, 25), vec!["some words", "**bold words stay**", "together"]);
+        let text = "see [the masked link text](https://example.com) here";
+        assert_eq!(pieces(text, 45), vec!["see", "[the masked link text](https://example.com)", "here"]);
+    }
+
+    #[test]
+    fn reopens_code_blocks() {
+        let code = (0..20).map(|line| format!("line {line}\n")).collect::<String>();
+        let text = format!("Here:\n```py\n{code}```");
+        let split = pieces(&text, 60);
+        assert!(split.len() > 2);
+        assert!(split[0].starts_with("Here:\n```py\nline 0\n"));
+        for piece in &split {
+            assert!(piece.ends_with("\n```") && piece.chars().count() <= 60, "Expected a code block split between lines, got {piece:?}");
+        }
+        for piece in &split[1..] {
+            assert!(matches!(parse(piece).as_slice(), [Node::Block(Some(language), _)] if language == "py"), "Expected one code block, got {piece:?}");
+        }
+        let joined = split.iter().map(|piece| piece.trim_start_matches("Here:\n").trim_start_matches("```py\n").trim_end_matches("```")).collect::<String>();
+        assert_eq!(joined, code);
+    }
+
+    #[test]
+    fn splits_long_formatting() {
+        let text = format!("**{}**", "word ".repeat(20).trim_end());
+        let split = pieces(&text, 40);
+        assert!(split.iter().all(|piece| piece.starts_with("**") && piece.ends_with("**") && piece.chars().count() <= 40), "Got {split:?}");
+        let text = format!(">>> {}", "quoted text ".repeat(10).trim_end());
+        assert!(pieces(&text, 40).iter().all(|piece| piece.starts_with(">>> ")));
+    }
+
+    #[test]
+    fn in_a_code_block() {
+        let text = "a log line\nwith ``` in it\n".repeat(5);
+        let split = chunk(&text, 60, Some("txt")).expect("Expected the text to be split");
+        assert!(split.len() > 1);
+        for piece in &split {
+            assert!(piece.starts_with("```txt\n") && piece.ends_with("```") && piece.chars().count() <= 60);
+            assert_eq!(piece.matches("```").count(), 2);
+        }
+    }
+
+    #[test]
+    fn rejects() {
+        chunk("text", 5, None).expect_err("Expected a tiny limit to be rejected");
+        chunk("text", 100, Some("py\n")).expect_err("Expected a bad language to be rejected");
+        let nested = format!("{}a{}", "||".repeat(12), "||".repeat(12));
+        chunk(&nested.repeat(2), 30, None).expect_err("Expected formatting too deep to fit to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,349 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::markdown::Feature;
+
+/// How deep spans can be nested before the rest is taken as text, so a message of nothing but
+/// delimiters can't recurse forever.
+const MAX_DEPTH: usize = 32;
+/// The delimiters of spans, in the order they're tried, so `**` is bold before `*` is italic.
+const SPANS: [(&str, Feature); 6] = [
+    ("||", Feature::Spoiler),
+    ("~~", Feature::Strikethrough),
+    ("**", Feature::Bold),
+    ("__", Feature::Underline),
+    ("*", Feature::Italic),
+    ("_", Feature::Italic),
+];
+
+/// A piece of a message, as Discord renders it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Text(String),
+    /// What starts a line, like `> ` for a quote or `## ` for a header, with the space after it.
+    Marker(Feature, String),
+    /// Formatting around some text, with the delimiter it was written with.
+    Span(Feature, &'static str, Vec<Node>),
+    /// Inline code, with how many backticks are around it.
+    Code(usize, String),
+    /// A code block, with its language and everything after it up to the closing fence.
+    Block(Option<String>, String),
+    /// A masked link, with its text and where it goes.
+    Link(Vec<Node>, String),
+}
+
+/// Where a piece of formatting is in a message, so it can be split without breaking it.
+///
+/// Positions are indices of characters, and the content is between the delimiters, so `**a**` starts
+/// at 0, has its content from 2 to 3, and ends at 5.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub start: usize,
+    pub content_start: usize,
+    pub content_end: usize,
+    pub end: usize,
+    /// What reopens the formatting at the start of a piece split from inside it.
+    pub opener: String,
+    /// What closes the formatting at the end of a piece split from inside it.
+    pub closer: String,
+    /// Whether splitting inside it breaks it up, which code blocks and `>>> ` quotes don't, since they're long.
+    pub breaks: bool,
+}
+
+fn starts_with(chars: &[char], index: usize, end: usize, pattern: &str) -> bool {
+    let length = pattern.chars().count();
+    index + length <= end && chars[index..index + length].iter().copied().eq(pattern.chars())
+}
+
+fn run(chars: &[char], index: usize, end: usize, letter: char) -> usize {
+    chars[index..end].iter().take_while(|candidate| **candidate == letter).count()
+}
+
+/// Where the code starting with the backticks at `index` starts and ends, and the index after it, if it's closed.
+///
+/// Three or more backticks start a code block, which ends at the next three. One or two start inline
+/// code, which ends at the next run of as many.
+fn code(chars: &[char], index: usize, end: usize) -> Option<(usize, usize, usize)> {
+    let ticks = run(chars, index, end, '`');
+    if ticks >= 3 {
+        let start = index + 3;
+        let close = (start..end).find(|close| starts_with(chars, *close, end, "```"))?;
+        return Some((start, close, close + 3));
+    }
+    let mut close = index + ticks;
+    while close < end {
+        if chars[close] == '`' {
+            let length = run(chars, close, end, '`');
+            if length == ticks {
+                return Some((index + ticks, close, close + ticks));
+            }
+            close += length;
+        } else {
+            close += 1;
+        }
+    }
+    None
+}
+
+/// The first place `delimiter` closes a span after `from`, skipping escaped characters and code.
+///
+/// A delimiter in a longer run of the same character closes at the end of the run, so `***bold***`
+/// closes its bold after the italic.
+fn close(chars: &[char], delimiter: &str, from: usize, end: usize) -> Option<usize> {
+    let first = delimiter.chars().next()?;
+    let length = delimiter.chars().count();
+    let mut index = from;
+    while index < end {
+        match chars[index] {
+            '\\' => index += 2,
+            '`' => index = code(chars, index, end).map_or_else(|| index + run(chars, index, end, '`'), |(_, _, after)| after),
+            _ if starts_with(chars, index, end, delimiter) && (index + length == end || chars[index + length] != first) => return Some(index),
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+/// The end of the span `delimiter` opens at `index`, if it's a span.
+fn span(chars: &[char], index: usize, end: usize, delimiter: &str) -> Option<usize> {
+    let length = delimiter.chars().count();
+    if !starts_with(chars, index, end, delimiter) {
+        return None;
+    }
+    let close = close(chars, delimiter, index + length, end)?;
+    let content = &chars[index + length..close];
+    let valid = match delimiter {
+        // italics can't start or end with a space, so "2 * 3 * 4" isn't one
+        "*" => content.first().is_some_and(|letter| !letter.is_whitespace()) && content.last().is_some_and(|letter| !letter.is_whitespace()),
+        // and underscores have to be around words, so snake_case_names aren't
+        "_" => {
+            let before = index.checked_sub(1).map(|before| chars[before]);
+            let after = chars.get(close + 1).filter(|_| close + 1 < end);
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(|letter| letter.is_alphanumeric())
+        }
+        _ => true,
+    };
+    (valid && !content.is_empty()).then_some(close)
+}
+
+/// The end of the text and the url of the masked link starting at `index`, and the index after it, if it's one.
+fn link(chars: &[char], index: usize, end: usize) -> Option<(usize, String, usize)> {
+    let mut text_end = index + 1;
+    while text_end < end && chars[text_end] != ']' {
+        text_end += if chars[text_end] == '\\' { 2 } else { 1 };
+    }
+    if text_end == index + 1 || !starts_with(chars, text_end, end, "](") {
+        return None;
+    }
+    let start = text_end + 2;
+    let close = (start..end).find(|close| chars[*close] == ')' || chars[*close].is_whitespace())?;
+    let url = chars[start..close].iter().collect::<String>();
+    let target = url.strip_prefix('<').and_then(|url| url.strip_suffix('>')).unwrap_or(&url);
+    (chars[close] == ')' && (target.starts_with("https://") || target.starts_with("http://"))).then(|| (text_end, url, close + 1))
+}
+
+/// The marker starting the line at `index`, and how long it is, if there is one.
+fn marker(chars: &[char], index: usize, end: usize) -> Option<(Feature, usize)> {
+    let indent = run(chars, index, end, ' ');
+    let markers = [(">>> ", Feature::Quote), ("> ", Feature::Quote), ("-# ", Feature::Subtext), ("### ", Feature::Header), ("## ", Feature::Header), ("# ", Feature::Header)];
+    if let Some((marker, feature)) = markers.into_iter().find(|(marker, _)| starts_with(chars, index, end, marker)) {
+        return Some((feature, marker.len()));
+    }
+    // lists can be indented to nest them
+    if starts_with(chars, index + indent, end, "- ") || starts_with(chars, index + indent, end, "* ") {
+        return Some((Feature::List, indent + 2));
+    }
+    let digits = chars[index + indent..end].iter().take_while(|letter| letter.is_ascii_digit()).count();
+    ((1..=9).contains(&digits) && starts_with(chars, index + indent + digits, end, ". ")).then_some((Feature::List, indent + digits + 2))
+}
+
+fn inline(chars: &[char], start: usize, end: usize, depth: usize, frames: &mut Vec<Frame>) -> Vec<Node> {
+    let mut nodes = Vec::new();
+    let mut text = String::new();
+    let mut index = start;
+    while index < end {
+        let pending = |text: &mut String, nodes: &mut Vec<Node>| {
+            if !text.is_empty() {
+                nodes.push(Node::Text(std::mem::take(text)));
+            }
+        };
+        if depth == 0 && (index == 0 || chars[index - 1] == '\n') {
+            if let Some((feature, length)) = marker(chars, index, end) {
+                pending(&mut text, &mut nodes);
+                let marker = chars[index..index + length].iter().collect::<String>();
+                // a >>> quote goes on to the end of the message, so it's reopened in every piece after it
+                let content_end = if marker == ">>> " { end } else { index + length };
+                let opener = if marker == ">>> " { marker.clone() } else { String::new() };
+                frames.push(Frame { start: index, content_start: index + length, content_end, end: content_end, opener, closer: String::new(), breaks: false });
+                nodes.push(Node::Marker(feature, marker));
+                index += length;
+                continue;
+            }
+        }
+        let letter = chars[index];
+        if letter == '\\' && chars.get(index + 1).is_some_and(|next| index + 1 < end && next.is_ascii_punctuation()) {
+            text.push(chars[index + 1]);
+            index += 2;
+            continue;
+        }
+        if letter == '`' {
+            let ticks = run(chars, index, end, '`');
+            if let Some((code_start, code_end, after)) = code(chars, index, end) {
+                pending(&mut text, &mut nodes);
+                let code = chars[code_start..code_end].iter().collect::<String>();
+                let node = if ticks >= 3 { block(code) } else { Node::Code(ticks, code) };
+                let (content_start, opener, closer) = match &node {
+                    Node::Block(Some(language), _) => (code_start + language.chars().count() + 1, format!("```{language}\n"), "```".to_string()),
+                    Node::Block(None, _) => (code_start, "```\n".to_string(), "```".to_string()),
+                    _ => (code_start, "`".repeat(ticks), "`".repeat(ticks)),
+                };
+                frames.push(Frame { start: index, content_start, content_end: code_end, end: after, opener, closer, breaks: ticks < 3 });
+                nodes.push(node);
+                index = after;
+            } else {
+                text.extend(&chars[index..index + ticks]);
+                index += ticks;
+            }
+            continue;
+        }
+        if depth < MAX_DEPTH {
+            if letter == '[' {
+                if let Some((text_end, url, after)) = link(chars, index, end) {
+                    pending(&mut text, &mut nodes);
+                    frames.push(Frame {
+                        start: index,
+                        content_start: index + 1,
+                        content_end: text_end,
+                        end: after,
+                        opener: "[".to_string(),
+                        closer: format!("]({url})"),
+                        breaks: true,
+                    });
+                    nodes.push(Node::Link(inline(chars, index + 1, text_end, depth + 1, frames), url));
+                    index = after;
+                    continue;
+                }
+            }
+            if let Some((delimiter, feature, close)) =
+                SPANS.iter().find_map(|(delimiter, feature)| span(chars, index, end, delimiter).map(|close| (*delimiter, *feature, close)))
+            {
+                pending(&mut text, &mut nodes);
+                let length = delimiter.len();
+                frames.push(Frame {
+                    start: index,
+                    content_start: index + length,
+                    content_end: close,
+                    end: close + length,
+                    opener: delimiter.to_string(),
+                    closer: delimiter.to_string(),
+                    breaks: true,
+                });
+                nodes.push(Node::Span(feature, delimiter, inline(chars, index + length, close, depth + 1, frames)));
+                index = close + length;
+                continue;
+            }
+        }
+        text.push(letter);
+        index += 1;
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    nodes
+}
+
+/// A code block, with the language if the first line is one, like the `py` of ```` ```py ````.
+fn block(code: String) -> Node {
+    match code.split_once('\n') {
+        Some((language, body)) if !language.is_empty() && language.chars().all(|letter| letter.is_ascii_alphanumeric() || "+-_#.".contains(letter)) => {
+            Node::Block(Some(language.to_string()), body.to_string())
+        }
+        _ => Node::Block(None, code),
+    }
+}
+
+/// The pieces of a message, as Discord renders it.
+pub fn parse(text: &str) -> Vec<Node> {
+    let chars = text.chars().collect::<Vec<_>>();
+    inline(&chars, 0, chars.len(), 0, &mut Vec::new())
+}
+
+/// Where the formatting in a message is, the outermost first.
+pub fn frames(chars: &[char]) -> Vec<Frame> {
+    let mut frames = Vec::new();
+    inline(chars, 0, chars.len(), 0, &mut frames);
+    frames
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn text(text: &str) -> Node {
+        Node::Text(text.to_string())
+    }
+
+    fn span(feature: Feature, delimiter: &'static str, children: Vec<Node>) -> Node {
+        Node::Span(feature, delimiter, children)
+    }
+
+    #[parameterized(
+        plain = {"hello", vec![text("hello")]},
+        bold = {"a **b** c", vec![text("a "), span(Feature::Bold, "**", vec![text("b")]), text(" c")]},
+        nested = {"**_a_**", vec![span(Feature::Bold, "**", vec![span(Feature::Italic, "_", vec![text("a")])])]},
+        bold_italic = {"***a***", vec![span(Feature::Bold, "**", vec![span(Feature::Italic, "*", vec![text("a")])])]},
+        unclosed = {"**a", vec![text("**a")]},
+        maths = {"2 * 3 * 4", vec![text("2 * 3 * 4")]},
+        snake_case = {"snake_case_name", vec![text("snake_case_name")]},
+        escaped = {"\\*not\\*", vec![text("*not*")]},
+        spoiler = {"||secret||", vec![span(Feature::Spoiler, "||", vec![text("secret")])]},
+        strike = {"~~no~~", vec![span(Feature::Strikethrough, "~~", vec![text("no")])]},
+        underline = {"__yes__", vec![span(Feature::Underline, "__", vec![text("yes")])]},
+        code = {"`a*b*`", vec![Node::Code(1, "a*b*".to_string())]},
+        double_code = {"``a`b``", vec![Node::Code(2, "a`b".to_string())]},
+        code_in_bold = {"**a `**` b**", vec![span(Feature::Bold, "**", vec![text("a "), Node::Code(1, "**".to_string()), text(" b")])]},
+        block = {"```py\nx = `1`\n```", vec![Node::Block(Some("py".to_string()), "x = `1`\n".to_string())]},
+        block_without_language = {"```a b```", vec![Node::Block(None, "a b".to_string())]},
+        unclosed_block = {"```py", vec![text("```py")]},
+        link = {"[**site**](https://example.com)", vec![Node::Link(vec![span(Feature::Bold, "**", vec![text("site")])], "https://example.com".to_string())]},
+        not_a_link = {"[a](javascript:alert)", vec![text("[a](javascript:alert)")]},
+        quote = {"> hi\n>>> all", vec![Node::Marker(Feature::Quote, "> ".to_string()), text("hi\n"), Node::Marker(Feature::Quote, ">>> ".to_string()), text("all")]},
+        header = {"## Title", vec![Node::Marker(Feature::Header, "## ".to_string()), text("Title")]},
+        subtext = {"-# small", vec![Node::Marker(Feature::Subtext, "-# ".to_string()), text("small")]},
+        lists = {"- a\n  1. b", vec![Node::Marker(Feature::List, "- ".to_string()), text("a\n"), Node::Marker(Feature::List, "  1. ".to_string()), text("b")]},
+        not_at_the_start = {"a > b # c", vec![text("a > b # c")]},
+    )]
+    fn parsing(message: &str, expected: Vec<Node>) {
+        assert_eq!(parse(message), expected);
+    }
+
+    #[test]
+    fn framing() {
+        let chars = "> **a [b](https://x.com)** `c`".chars().collect::<Vec<_>>();
+        let found = frames(&chars).into_iter().map(|frame| (frame.start, frame.content_start, frame.content_end, frame.end, frame.closer)).collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![(0, 2, 2, 2, String::new()), (2, 4, 24, 26, "**".to_string()), (6, 7, 8, 24, "](https://x.com)".to_string()), (27, 28, 29, 30, "`".to_string())]
+        );
+        let chars = "```py\nx\n```".chars().collect::<Vec<_>>();
+        let block = &frames(&chars)[0];
+        assert_eq!((block.content_start, block.content_end, block.opener.as_str(), block.breaks), (6, 8, "```py\n", false));
+    }
+
+    #[test]
+    fn deep() {
+        // past the deepest spans can be nested, the rest is text
+        let nodes = parse(&"||".repeat(200));
+        let mut depth = 0;
+        let mut node = &nodes[0];
+        while let Node::Span(_, _, children) = node {
+            depth += 1;
+            node = &children[0];
+        }
+        assert_eq!(depth, MAX_DEPTH);
+        assert!(!parse(&format!("{}a{}", "**".repeat(100), "*".repeat(201))).is_empty());
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,314 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Markov chains of the words of messages, to make up messages that sound like a channel.
+//!
+//! Each word is kept once, and the chain refers to words by their place in that list, so a chain of a
+//! busy channel takes a fraction of the memory of one keeping the words of every state as strings.
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::buffer::{Buffer, Bytes};
+use crate::metrics;
+use crate::persist::{self, Persist};
+
+/// Where a message starts and ends, kept as the first word so it can't be mistaken for one.
+const BOUNDARY: u32 = 0;
+/// How long a message from the chain is by default, which is as long as a discord message can be.
+const MAX_LENGTH: usize = 2000;
+
+/// The words a chain comes up with next after the last few, and how many times each came next.
+type Followers = Vec<(u32, u32)>;
+
+#[derive(Clone, Debug, PartialEq)]
+#[pyclass(module = "markov")] // COV_EXCL_LINE
+pub struct Chain {
+    /// How many words a state has, so how many words back the next word depends on.
+    order: usize,
+    words: Vec<String>,
+    ids: HashMap<String, u32>,
+    /// The words after each state, in the order they were first seen after it, so a seed always makes
+    /// the same message from the same training.
+    transitions: HashMap<Box<[u32]>, Followers>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct State {
+    order: usize,
+    words: Vec<String>,
+    /// Sorted by state so the same chain is always saved the same way.
+    transitions: Vec<(Vec<u32>, Followers)>,
+}
+
+impl Chain {
+    fn new(order: usize) -> Result<Self, String> {
+        if !(2..=3).contains(&order) {
+            return Err(format!("Invalid order {order}, it must be 2 or 3"));
+        }
+        Ok(Chain { order, words: vec![String::new()], ids: HashMap::new(), transitions: HashMap::new() })
+    }
+
+    fn id(&mut self, word: &str) -> u32 {
+        if let Some(id) = self.ids.get(word) {
+            return *id;
+        }
+        let id = u32::try_from(self.words.len()).expect("Fewer than 4 billion distinct words");
+        self.words.push(word.to_string());
+        self.ids.insert(word.to_string(), id);
+        id
+    }
+
+    /// Learn from `text`, where each line is a message of words between whitespace, with their case and
+    /// punctuation kept.
+    fn train(&mut self, text: &str) {
+        for line in text.lines() {
+            let mut state = vec![BOUNDARY; self.order];
+            let words = line.split_whitespace().map(|word| self.id(word)).collect::<Vec<_>>();
+            if words.is_empty() {
+                continue;
+            }
+            for word in words.into_iter().chain([BOUNDARY]) {
+                let followers = self.transitions.entry(state.clone().into_boxed_slice()).or_default();
+                match followers.iter_mut().find(|(follower, _)| *follower == word) {
+                    Some((_, count)) => *count = count.saturating_add(1),
+                    None => followers.push((word, 1)),
+                }
+                state.rotate_left(1);
+                state[self.order - 1] = word;
+            }
+        }
+    }
+
+    /// A message made up from the chain, of at most `max_length` characters, ending early after the last
+    /// word that fits if it would be longer, or None if there's nothing it could say, since it hasn't learnt
+    /// anything yet or the first word is already too long.
+    fn generate(&self, max_length: usize, seed: u64) -> Result<Option<String>, String> {
+        if max_length == 0 {
+            return Err("Invalid max length 0, it must be positive".to_string());
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut state = vec![BOUNDARY; self.order];
+        let mut message = String::new();
+        let mut length = 0;
+        while let Some(followers) = self.transitions.get(&state[..]) {
+            let total = followers.iter().map(|(_, count)| u64::from(*count)).sum::<u64>();
+            let mut ticket = rng.gen_range(0..total);
+            let word = followers
+                .iter()
+                .find(|(_, count)| {
+                    let found = ticket < u64::from(*count);
+                    ticket = ticket.saturating_sub(u64::from(*count));
+                    found
+                })
+                .map(|(word, _)| *word)
+                .expect("The ticket is below the total so it's in some word's count");
+            if word == BOUNDARY {
+                break;
+            }
+            let text = &self.words[word as usize];
+            let added = text.chars().count() + usize::from(!message.is_empty());
+            if length + added > max_length {
+                break;
+            }
+            if !message.is_empty() {
+                message.push(' ');
+            }
+            message.push_str(text);
+            length += added;
+            state.rotate_left(1);
+            state[self.order - 1] = word;
+        }
+        Ok(Some(message).filter(|message| !message.is_empty()))
+    }
+}
+
+impl Persist for Chain {
+    const GAME: &'static str = "markov";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        let mut transitions = self.transitions.iter().map(|(state, followers)| (state.to_vec(), followers.clone())).collect::<Vec<_>>();
+        transitions.sort_unstable();
+        State { order: self.order, words: self.words.clone(), transitions }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        let mut chain = Chain::new(state.order)?;
+        let mut words = state.words.into_iter();
+        if words.next().as_deref() != Some("") {
+            return Err("The first word isn't where messages start and end".to_string());
+        }
+        for word in words {
+            if word.is_empty() || word.contains(char::is_whitespace) {
+                return Err(format!("{word:?} isn't a word"));
+            }
+            if chain.ids.contains_key(&word) {
+                return Err(format!("{word:?} is there more than once"));
+            }
+            chain.id(&word);
+        }
+        let count = chain.words.len();
+        let known = |word: &u32| (*word as usize) < count;
+        for (key, followers) in state.transitions {
+            if key.len() != chain.order || !key.iter().all(known) {
+                return Err(format!("The state {key:?} isn't {} known words", chain.order));
+            }
+            if followers.is_empty() || followers.iter().any(|(word, count)| !known(word) || *count == 0) {
+                return Err(format!("The words after {key:?} aren't known words that came after it"));
+            }
+            if followers.iter().enumerate().any(|(index, (word, _))| followers[..index].iter().any(|(other, _)| other == word)) {
+                return Err(format!("The words after {key:?} are there more than once"));
+            }
+            if chain.transitions.insert(key.clone().into_boxed_slice(), followers).is_some() {
+                return Err(format!("The state {key:?} is there more than once"));
+            }
+        }
+        Ok(chain)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Chain {
+    #[new]
+    #[pyo3(signature = (order=2))]
+    fn __new__(order: usize) -> PyResult<Self> { // COV_EXCL_LINE
+        Chain::new(order).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn order(&self) -> usize {
+        self.order
+    }
+
+    #[pyo3(name = "train")]
+    fn py_train(&mut self, py: Python, text: &str) {
+        metrics::timed("markov.Chain.train", || py.allow_threads(|| self.train(text)));
+    }
+
+    #[pyo3(name = "generate", signature = (max_length=MAX_LENGTH, seed=None))]
+    fn py_generate(&self, py: Python, max_length: usize, seed: Option<u64>) -> PyResult<Option<String>> {
+        let seed = seed.unwrap_or_else(|| StdRng::from_entropy().gen());
+        metrics::observe("markov.Chain.generate", || py.allow_threads(|| self.generate(max_length, seed))).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// How many different words the chain knows.
+    fn __len__(&self) -> usize {
+        self.words.len() - 1
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Chain(order={}, words={}, states={})", self.order, self.words.len() - 1, self.transitions.len())
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based markov chains for making up messages";
+
+pub(crate) fn register_markov(py: Python, m: &PyModule) -> PyResult<()> {
+    let markov = PyModule::new(py, "_markov")?;
+    markov.add_class::<Chain>()?;
+    markov.add("__doc__", DOCSTRING)?;
+    m.add_submodule(markov)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const MESSAGES: &str = "the cat sat on the mat\nthe dog sat on the log\n\nthe cat ate the fish\n  \nsat on the mat again";
+
+    fn trained(order: usize) -> Chain {
+        let mut chain = Chain::new(order).unwrap();
+        chain.train(MESSAGES);
+        chain
+    }
+
+    #[parameterized(low = {1}, high = {4})]
+    fn bad_order(order: usize) {
+        assert_eq!(Chain::new(order), Err(format!("Invalid order {order}, it must be 2 or 3")));
+    }
+
+    /// Each run of `order` words and the word after it in `line`, with the start and end of it as "".
+    fn runs(line: &str, order: usize) -> Vec<Vec<&str>> {
+        let words = [vec![""; order], line.split_whitespace().collect(), vec![""]].concat();
+        words.windows(order + 1).map(<[&str]>::to_vec).collect()
+    }
+
+    #[parameterized(two = {2}, three = {3})]
+    fn generates(order: usize) {
+        let chain = trained(order);
+        let trained_runs = MESSAGES.lines().filter(|line| !line.trim().is_empty()).flat_map(|line| runs(line, order)).collect::<Vec<_>>();
+        for seed in 0..50 {
+            let message = chain.generate(MAX_LENGTH, seed).unwrap().unwrap();
+            // every word came after the words before it in a message it was trained on
+            for run in runs(&message, order) {
+                assert!(trained_runs.contains(&run), "{run:?} of {message:?}");
+            }
+            assert_eq!(chain.generate(MAX_LENGTH, seed), Ok(Some(message)));
+        }
+    }
+
+    #[test]
+    fn one_message() {
+        let mut chain = Chain::new(3).unwrap();
+        assert_eq!(chain.generate(10, 0), Ok(None));
+        chain.train("Hello there, General Kenobi!");
+        assert_eq!(chain.generate(MAX_LENGTH, 0), Ok(Some(String::from("Hello there, General Kenobi!"))));
+        // cut after the last word that fits
+        assert_eq!(chain.generate(20, 0), Ok(Some(String::from("Hello there, General"))));
+        assert_eq!(chain.generate(3, 0), Ok(None));
+        assert_eq!(chain.generate(0, 0), Err(String::from("Invalid max length 0, it must be positive")));
+        assert_eq!(chain.__len__(), 4);
+        // training again counts the same words again, without knowing more
+        chain.train("Hello there, General Kenobi!");
+        assert_eq!((chain.__len__(), chain.transitions.len()), (4, 5));
+        assert_eq!(chain.transitions[&[BOUNDARY; 3][..]], [(1, 2)]);
+    }
+
+    #[test]
+    fn saves() {
+        let chain = trained(2);
+        let bytes = persist::to_bytes(&chain).expect("Failed to save");
+        let loaded: Chain = persist::from_bytes(&bytes).expect("Failed to load");
+        assert_eq!(loaded, chain);
+        assert_eq!(loaded.generate(MAX_LENGTH, 7), chain.generate(MAX_LENGTH, 7));
+        assert_eq!(persist::to_bytes(&loaded).expect("Failed to save"), bytes);
+    }
+
+    #[test]
+    fn rejects() {
+        let chain = trained(2);
+        let broken = |change: fn(&mut State)| {
+            let mut state = chain.save();
+            change(&mut state);
+            Chain::load(state).unwrap_err()
+        };
+        assert_eq!(broken(|state| state.order = 5), "Invalid order 5, it must be 2 or 3");
+        assert_eq!(broken(|state| state.words[0] = String::from("the")), "The first word isn't where messages start and end");
+        assert_eq!(broken(|state| state.words[2] = String::from("the")), r#""the" is there more than once"#);
+        assert_eq!(broken(|state| state.words[2] = String::from("a b")), r#""a b" isn't a word"#);
+        assert_eq!(broken(|state| state.transitions[0].0.push(1)), "The state [0, 0, 1] isn't 2 known words");
+        assert_eq!(broken(|state| state.transitions[0].1[0].0 = 100), "The words after [0, 0] aren't known words that came after it");
+        assert_eq!(broken(|state| state.transitions[0].1.clear()), "The words after [0, 0] aren't known words that came after it");
+        assert_eq!(broken(|state| state.transitions[0].1 = vec![(1, 1), (1, 2)]), "The words after [0, 0] are there more than once");
+        assert_eq!(broken(|state| state.transitions[1] = state.transitions[0].clone()), "The state [0, 0] is there more than once");
+    }
+}
+// COV_EXCL_STOP
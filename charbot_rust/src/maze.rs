@@ -0,0 +1,356 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod render; // COV_EXCL_LINE
+
+use std::collections::VecDeque;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use crate::maze::render::{fits, to_emoji, to_unicode};
+use crate::metrics;
+
+/// A cell as (row, column), with row 0 at the top and column 0 on the left.
+pub type Cell = (usize, usize);
+
+/// The bit for the passage out of each side of a cell, in the order of `DIRECTIONS`.
+pub const NORTH: u8 = 1;
+pub const EAST: u8 = 2;
+pub const SOUTH: u8 = 4;
+pub const WEST: u8 = 8;
+const DIRECTIONS: [u8; 4] = [NORTH, EAST, SOUTH, WEST];
+
+fn opposite(direction: u8) -> u8 {
+    match direction {
+        NORTH => SOUTH,
+        EAST => WEST,
+        SOUTH => NORTH,
+        _ => EAST,
+    }
+}
+
+#[pyclass(module = "maze")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Algorithm { // COV_EXCL_LINE
+    /// A random walk that backs up when it gets stuck, for long winding passages.
+    Backtracker,
+    /// Grows out from a random cell, for lots of short dead ends.
+    Prim,
+    /// Joins random cells until they're all connected, for a maze with no overall direction.
+    Kruskal,
+}
+
+// COV_EXCL_START
+/// A maze with exactly one way between any two cells, entered at the top left and left at the bottom right.
+#[pyclass(module = "maze")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Maze {
+    width: usize,
+    height: usize,
+    /// The sides of each cell with a passage through them, a row at a time.
+    passages: Vec<u8>,
+}
+// COV_EXCL_STOP
+
+impl Maze {
+    fn new(width: usize, height: usize, algorithm: Algorithm, rng: &mut impl Rng) -> Result<Self, String> {
+        if width == 0 || height == 0 || !fits(width, height) {
+            return Err(format!("Invalid size {width} by {height}, it has to fit in a discord message"));
+        }
+        let mut maze = Maze { width, height, passages: vec![0; width * height] };
+        match algorithm {
+            Algorithm::Backtracker => maze.backtrack(rng),
+            Algorithm::Prim => maze.prim(rng),
+            Algorithm::Kruskal => maze.kruskal(rng),
+        }
+        Ok(maze)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, (row, column): Cell) -> usize {
+        row * self.width + column
+    }
+
+    fn neighbour(&self, (row, column): Cell, direction: u8) -> Option<Cell> {
+        match direction {
+            NORTH => row.checked_sub(1).map(|row| (row, column)),
+            EAST => (column + 1 < self.width).then_some((row, column + 1)),
+            SOUTH => (row + 1 < self.height).then_some((row + 1, column)),
+            _ => column.checked_sub(1).map(|column| (row, column)),
+        }
+    }
+
+    pub fn is_open(&self, cell: Cell, direction: u8) -> bool {
+        self.passages[self.index(cell)] & direction != 0
+    }
+
+    fn carve(&mut self, cell: Cell, direction: u8) {
+        let next = self.neighbour(cell, direction).expect("Only carving between cells");
+        let (from, to) = (self.index(cell), self.index(next));
+        self.passages[from] |= direction;
+        self.passages[to] |= opposite(direction);
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Cell> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |column| (row, column)))
+    }
+
+    fn backtrack(&mut self, rng: &mut impl Rng) {
+        let mut visited = vec![false; self.passages.len()];
+        let mut stack = vec![(0, 0)];
+        visited[0] = true;
+        while let Some(&cell) = stack.last() {
+            let unvisited = DIRECTIONS
+                .into_iter()
+                .filter(|direction| self.neighbour(cell, *direction).is_some_and(|next| !visited[self.index(next)]))
+                .collect::<Vec<_>>();
+            match unvisited.choose(rng) {
+                Some(&direction) => {
+                    self.carve(cell, direction);
+                    let next = self.neighbour(cell, direction).expect("Only unvisited neighbours");
+                    visited[self.index(next)] = true;
+                    stack.push(next);
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    fn prim(&mut self, rng: &mut impl Rng) {
+        let mut visited = vec![false; self.passages.len()];
+        let start = (rng.gen_range(0..self.height), rng.gen_range(0..self.width));
+        visited[self.index(start)] = true;
+        let mut frontier = DIRECTIONS.into_iter().map(|direction| (start, direction)).collect::<Vec<_>>();
+        while !frontier.is_empty() {
+            let (cell, direction) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            let Some(next) = self.neighbour(cell, direction) else { continue };
+            if visited[self.index(next)] {
+                continue;
+            }
+            self.carve(cell, direction);
+            visited[self.index(next)] = true;
+            frontier.extend(DIRECTIONS.into_iter().map(|direction| (next, direction)));
+        }
+    }
+
+    fn kruskal(&mut self, rng: &mut impl Rng) {
+        // each cell starts in a set of its own, and a wall is knocked down if it's between two sets
+        let mut sets = (0..self.passages.len()).collect::<Vec<_>>();
+        fn root(sets: &mut [usize], mut set: usize) -> usize {
+            while sets[set] != set {
+                sets[set] = sets[sets[set]];
+                set = sets[set];
+            }
+            set
+        }
+        let mut walls = self.cells().flat_map(|cell| [(cell, EAST), (cell, SOUTH)]).filter(|(cell, direction)| self.neighbour(*cell, *direction).is_some()).collect::<Vec<_>>();
+        walls.shuffle(rng);
+        for (cell, direction) in walls {
+            let next = self.neighbour(cell, direction).expect("Only walls between cells");
+            let (first, second) = (root(&mut sets, self.index(cell)), root(&mut sets, self.index(next)));
+            if first != second {
+                sets[first] = second;
+                self.carve(cell, direction);
+            }
+        }
+    }
+
+    /// The way through the maze from the top left to the bottom right, both included.
+    pub fn solve(&self) -> Vec<Cell> {
+        let end = (self.height - 1, self.width - 1);
+        let mut previous = vec![None; self.passages.len()];
+        let mut queue = VecDeque::from([(0, 0)]);
+        while let Some(cell) = queue.pop_front() {
+            if cell == end {
+                break;
+            }
+            for direction in DIRECTIONS {
+                if let Some(next) = self.neighbour(cell, direction).filter(|next| self.is_open(cell, direction) && previous[self.index(*next)].is_none() && *next != (0, 0)) {
+                    previous[self.index(next)] = Some(cell);
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut path = vec![end];
+        while let Some(cell) = previous[self.index(path[path.len() - 1])] {
+            path.push(cell);
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Maze {
+    #[getter(width)]
+    fn py_width(&self) -> usize {
+        self.width
+    }
+
+    #[getter(height)]
+    fn py_height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether there's a way from `cell` in each direction, north, east, south and west.
+    fn passages(&self, cell: Cell) -> PyResult<(bool, bool, bool, bool)> {
+        if cell.0 >= self.height || cell.1 >= self.width {
+            return Err(PyValueError::new_err(format!("Invalid cell {cell:?}, the maze is {} by {}", self.width, self.height)));
+        }
+        Ok((self.is_open(cell, NORTH), self.is_open(cell, EAST), self.is_open(cell, SOUTH), self.is_open(cell, WEST)))
+    }
+
+    #[pyo3(name = "solve")]
+    fn py_solve(&self) -> Vec<Cell> {
+        metrics::timed("maze.Maze.solve", || self.solve())
+    }
+
+    #[pyo3(name = "to_unicode", signature = (show_path=false))]
+    fn py_to_unicode(&self, show_path: bool) -> String {
+        to_unicode(self, show_path.then(|| self.solve()).as_deref())
+    }
+
+    #[pyo3(name = "to_emoji", signature = (show_path=false))]
+    fn py_to_emoji(&self, show_path: bool) -> String {
+        to_emoji(self, show_path.then(|| self.solve()).as_deref())
+    }
+
+    fn __str__(&self) -> String {
+        to_unicode(self, None)
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (width, height, algorithm=Algorithm::Backtracker, seed=None), text_signature = "
+generate(width, height, algorithm=Algorithm.Backtracker, seed=None, /)
+--
+
+Generate a maze, with exactly one way between any two cells.
+
+Parameters
+----------
+width : int
+    The number of cells across.
+height : int
+    The number of cells down.
+algorithm : Algorithm
+    How the maze is carved out, each gives it a different feel. The default is Algorithm.Backtracker.
+seed : int | None
+    The seed for the maze, so a maze can be made again. The default is None, for a random maze.
+
+Returns
+-------
+Maze
+    The maze.
+
+Raises
+------
+ValueError
+    If the maze is empty, or too big to draw in a discord message.
+")]
+fn generate(width: usize, height: usize, algorithm: Algorithm, seed: Option<u64>) -> PyResult<Maze> {
+    metrics::observe("maze.generate", || {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        Maze::new(width, height, algorithm, &mut rng).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based maze generation, drawing and solving";
+
+pub(crate) fn register_maze(py: Python, m: &PyModule) -> PyResult<()> {
+    let maze = PyModule::new(py, "_maze")?;
+    maze.add_class::<Algorithm>()?;
+    maze.add_class::<Maze>()?;
+    maze.add_function(wrap_pyfunction!(generate, maze)?)?;
+    maze.add("__doc__", DOCSTRING)?;
+    m.add_submodule(maze)?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    /// A maze from a picture of its passages, `-` and `|` for passages east and south of each cell.
+    pub(crate) fn drawn(rows: &[&str]) -> Maze {
+        let (height, width) = (rows.len() / 2 + 1, rows[0].len() / 2 + 1);
+        let mut maze = Maze { width, height, passages: vec![0; width * height] };
+        for (row, line) in rows.iter().enumerate() {
+            for (column, symbol) in line.chars().enumerate() {
+                match symbol {
+                    '-' => maze.carve((row / 2, column / 2), EAST),
+                    '|' => maze.carve((row / 2, column / 2), SOUTH),
+                    _ => {}
+                }
+            }
+        }
+        maze
+    }
+
+    #[parameterized(
+        backtracker = {Algorithm::Backtracker},
+        prim = {Algorithm::Prim},
+        kruskal = {Algorithm::Kruskal},
+    )]
+    fn perfect(algorithm: Algorithm) {
+        for seed in 0..5 {
+            let maze = Maze::new(12, 7, algorithm, &mut StdRng::seed_from_u64(seed)).expect("Failed to generate");
+            // a maze with one way between any two cells is a tree, so it has one passage fewer than cells
+            let passages = maze.passages.iter().map(|sides| sides.count_ones()).sum::<u32>() / 2;
+            assert_eq!(passages as usize, 12 * 7 - 1);
+            let mut seen = vec![false; maze.passages.len()];
+            let mut stack = vec![(0, 0)];
+            while let Some(cell) = stack.pop() {
+                if std::mem::replace(&mut seen[maze.index(cell)], true) {
+                    continue;
+                }
+                stack.extend(DIRECTIONS.into_iter().filter(|direction| maze.is_open(cell, *direction)).filter_map(|direction| maze.neighbour(cell, direction)));
+            }
+            assert!(seen.into_iter().all(|seen| seen), "Expected every cell to be reachable");
+        }
+    }
+
+    #[test]
+    fn rejects() {
+        let mut rng = StdRng::seed_from_u64(1);
+        Maze::new(0, 5, Algorithm::Prim, &mut rng).expect_err("Expected an empty maze to be rejected");
+        Maze::new(5, 0, Algorithm::Prim, &mut rng).expect_err("Expected an empty maze to be rejected");
+        Maze::new(40, 40, Algorithm::Prim, &mut rng).expect_err("Expected a huge maze to be rejected");
+        Maze::new(1, 1, Algorithm::Kruskal, &mut rng).expect("Expected a single cell");
+    }
+
+    #[test]
+    fn solves() {
+        let maze = drawn(&[
+            "o-o-o",
+            "    |",
+            "o-o-o",
+            "|    ",
+            "o-o-o",
+        ]);
+        assert_eq!(maze.solve(), vec![(0, 0), (0, 1), (0, 2), (1, 2), (1, 1), (1, 0), (2, 0), (2, 1), (2, 2)]);
+        let maze = Maze::new(1, 1, Algorithm::Backtracker, &mut StdRng::seed_from_u64(1)).expect("Expected a single cell");
+        assert_eq!(maze.solve(), vec![(0, 0)]);
+        let maze = Maze::new(15, 10, Algorithm::Kruskal, &mut StdRng::seed_from_u64(3)).expect("Failed to generate");
+        let path = maze.solve();
+        assert_eq!((path[0], path[path.len() - 1]), ((0, 0), (9, 14)));
+        for pair in path.windows(2) {
+            let step = DIRECTIONS.into_iter().find(|direction| maze.neighbour(pair[0], *direction) == Some(pair[1])).expect("Expected neighbours");
+            assert!(maze.is_open(pair[0], step));
+        }
+    }
+}
+// COV_EXCL_STOP
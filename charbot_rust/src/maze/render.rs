@@ -0,0 +1,198 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::maze::{Cell, Maze, EAST, SOUTH};
+
+/// The most characters in a discord message.
+const MESSAGE_LIMIT: usize = 2000;
+/// The characters around a drawing to send it as a code block, so it lines up.
+const CODE_BLOCK: usize = "```\n\n```".len();
+
+const WALL_EMOJI: &str = "⬛";
+const OPEN_EMOJI: &str = "⬜";
+const PATH_EMOJI: &str = "🟨";
+const START_EMOJI: &str = "🟩";
+const END_EMOJI: &str = "🟥";
+const PATH_SYMBOL: char = '•';
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+enum Tile {
+    Wall,
+    Open,
+    Path,
+    Start,
+    End,
+}
+
+/// Whether a maze `width` by `height` can be drawn in a discord message, in a code block.
+///
+/// Both drawings are a character or emoji for each cell and each wall between them, with a line for
+/// each row of cells and each row of walls.
+pub fn fits(width: usize, height: usize) -> bool {
+    match (width.checked_mul(2), height.checked_mul(2)) {
+        (Some(across), Some(down)) => (across + 2).checked_mul(down + 1).is_some_and(|size| size - 1 + CODE_BLOCK <= MESSAGE_LIMIT),
+        _ => false,
+    }
+}
+
+/// The maze as tiles, with a tile for each cell, each wall between cells and each corner, and `path` marked if it's given.
+///
+/// The walls are open above the top left cell and below the bottom right, the ways in and out.
+fn tiles(maze: &Maze, path: Option<&[Cell]>) -> Vec<Vec<Tile>> {
+    let (width, height) = (maze.width(), maze.height());
+    let mut tiles = vec![vec![Tile::Wall; width * 2 + 1]; height * 2 + 1];
+    for row in 0..height {
+        for column in 0..width {
+            tiles[row * 2 + 1][column * 2 + 1] = Tile::Open;
+            if maze.is_open((row, column), EAST) {
+                tiles[row * 2 + 1][column * 2 + 2] = Tile::Open;
+            }
+            if maze.is_open((row, column), SOUTH) {
+                tiles[row * 2 + 2][column * 2 + 1] = Tile::Open;
+            }
+        }
+    }
+    tiles[0][1] = Tile::Open;
+    tiles[height * 2][width * 2 - 1] = Tile::Open;
+    if let Some(path) = path {
+        for pair in path.windows(2) {
+            let ((first_row, first_column), (second_row, second_column)) = (pair[0], pair[1]);
+            // the wall between two cells is half way between them
+            tiles[first_row + second_row + 1][first_column + second_column + 1] = Tile::Path;
+        }
+        for (row, column) in path {
+            tiles[row * 2 + 1][column * 2 + 1] = Tile::Path;
+        }
+    }
+    tiles[1][1] = Tile::Start;
+    tiles[height * 2 - 1][width * 2 - 1] = Tile::End;
+    tiles
+}
+
+/// The maze drawn with emoji, black walls, white passages, yellow for `path`, and green and red squares for the start and end.
+pub fn to_emoji(maze: &Maze, path: Option<&[Cell]>) -> String {
+    tiles(maze, path)
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|tile| match tile {
+                    Tile::Wall => WALL_EMOJI,
+                    Tile::Open => OPEN_EMOJI,
+                    Tile::Path => PATH_EMOJI,
+                    Tile::Start => START_EMOJI,
+                    Tile::End => END_EMOJI,
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The box drawing character for a corner joined to the walls above, right, below and left of it.
+fn corner(up: bool, right: bool, down: bool, left: bool) -> char {
+    match (up, right, down, left) {
+        (false, false, false, false) => ' ',
+        (true, false, false, false) => '╵',
+        (false, true, false, false) => '╶',
+        (false, false, true, false) => '╷',
+        (false, false, false, true) => '╴',
+        (true, false, true, false) => '│',
+        (false, true, false, true) => '─',
+        (false, true, true, false) => '┌',
+        (false, false, true, true) => '┐',
+        (true, true, false, false) => '└',
+        (true, false, false, true) => '┘',
+        (true, true, true, false) => '├',
+        (true, false, true, true) => '┤',
+        (false, true, true, true) => '┬',
+        (true, true, false, true) => '┴',
+        (true, true, true, true) => '┼',
+    }
+}
+
+/// The maze drawn with box drawing characters, for a code block, with `path` as dots.
+pub fn to_unicode(maze: &Maze, path: Option<&[Cell]>) -> String {
+    let tiles = tiles(maze, path);
+    let wall = |row: Option<usize>, column: Option<usize>| match (row, column) {
+        (Some(row), Some(column)) => tiles.get(row).and_then(|tiles| tiles.get(column)) == Some(&Tile::Wall),
+        _ => false,
+    };
+    tiles
+        .iter()
+        .enumerate()
+        .map(|(row, line)| {
+            line.iter()
+                .enumerate()
+                .map(|(column, tile)| match (row % 2, column % 2, tile) {
+                    (_, _, Tile::Path) => PATH_SYMBOL,
+                    (_, _, Tile::Open | Tile::Start | Tile::End) => ' ',
+                    (0, 0, Tile::Wall) => corner(
+                        wall(row.checked_sub(1), Some(column)),
+                        wall(Some(row), Some(column + 1)),
+                        wall(Some(row + 1), Some(column)),
+                        wall(Some(row), column.checked_sub(1)),
+                    ),
+                    (0, _, Tile::Wall) => '─',
+                    (_, _, Tile::Wall) => '│',
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use crate::maze::tests::drawn;
+    use crate::maze::Algorithm;
+
+    fn snake() -> Maze {
+        drawn(&[
+            "o-o-o",
+            "    |",
+            "o-o-o",
+            "|    ",
+            "o-o-o",
+        ])
+    }
+
+    #[test]
+    fn sizes() {
+        assert!(fits(1, 1));
+        assert!(fits(21, 21));
+        assert!(!fits(22, 22));
+        assert!(fits(60, 7));
+        assert!(!fits(usize::MAX, 1));
+    }
+
+    #[test]
+    fn draws_with_unicode() {
+        assert_eq!(to_unicode(&snake(), None), ["╷ ╶───┐", "│     │", "├───╴ │", "│     │", "│ ╶───┤", "│     │", "└───╴ ╵"].join("\n"));
+        let solved = to_unicode(&snake(), Some(&snake().solve()));
+        assert_eq!(solved, ["╷ ╶───┐", "│ ••••│", "├───╴•│", "│•••••│", "│•╶───┤", "│•••• │", "└───╴ ╵"].join("\n"));
+    }
+
+    #[test]
+    fn draws_with_emoji() {
+        let emoji = to_emoji(&snake(), Some(&snake().solve()));
+        let rows = emoji.lines().collect::<Vec<_>>();
+        assert_eq!(rows[0], "⬛⬜⬛⬛⬛⬛⬛");
+        assert_eq!(rows[1], "⬛🟩🟨🟨🟨🟨⬛");
+        assert_eq!(rows[5], "⬛🟨🟨🟨🟨🟥⬛");
+        assert_eq!(rows[6], "⬛⬛⬛⬛⬛⬜⬛");
+        assert!(!to_emoji(&snake(), None).contains(PATH_EMOJI));
+    }
+
+    #[test]
+    fn biggest_fits() {
+        let maze = Maze::new(21, 21, Algorithm::Backtracker, &mut StdRng::seed_from_u64(1)).expect("Failed to generate");
+        for drawing in [to_emoji(&maze, Some(&maze.solve())), to_unicode(&maze, Some(&maze.solve()))] {
+            assert!(drawing.chars().count() + CODE_BLOCK <= MESSAGE_LIMIT);
+        }
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,240 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Counting the calls to the functions the bot calls the most, like translating, playing a move in a game
+//! and scanning a message, with how many failed and how long they took, to export with the bot's own
+//! metrics.
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{PoisonError, RwLock};
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+
+use crate::profiling;
+
+/// The upper bounds of the latency buckets in seconds, from a cached translation to a computer's move.
+const BUCKETS: [f64; 12] = [0.00005, 0.0001, 0.00025, 0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.1, 0.5, 2.5];
+
+/// What has been counted for a function, as of when it was exported.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Series {
+    calls: u64,
+    errors: u64,
+    /// The calls that took at most each bound of [`BUCKETS`] and more than the one before, the slower ones
+    /// are only in `calls`.
+    buckets: [u64; BUCKETS.len()],
+    seconds: f64,
+}
+
+/// The counters of a function, which calls add to without waiting on each other.
+///
+/// An export can see a call in `calls` before its bucket, which is off by one call at most.
+#[derive(Debug, Default)]
+struct Counters {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    buckets: [AtomicU64; BUCKETS.len()],
+    nanoseconds: AtomicU64,
+}
+
+impl Counters {
+    fn series(&self) -> Series {
+        Series {
+            calls: self.calls.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            buckets: std::array::from_fn(|bucket| self.buckets[bucket].load(Ordering::Relaxed)),
+            seconds: self.nanoseconds.load(Ordering::Relaxed) as f64 / 1e9,
+        }
+    }
+}
+
+/// The counters by the name of the function, so the export is always in the same order.
+///
+/// The lock is only written the first time a function is called, after that calls just read it to find
+/// their counters. There are as many as there are names in the code, so they're leaked rather than
+/// reference counted.
+static COUNTERS: Lazy<RwLock<BTreeMap<&'static str, &'static Counters>>> = Lazy::new(RwLock::default);
+
+fn counters(function: &'static str) -> &'static Counters {
+    if let Some(counters) = COUNTERS.read().unwrap_or_else(PoisonError::into_inner).get(function) {
+        return counters;
+    }
+    COUNTERS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .entry(function)
+        .or_insert_with(|| Box::leak(Box::default()))
+}
+
+fn record(function: &'static str, seconds: f64, failed: bool) {
+    let counters = counters(function);
+    counters.calls.fetch_add(1, Ordering::Relaxed);
+    counters.errors.fetch_add(u64::from(failed), Ordering::Relaxed);
+    counters.nanoseconds.fetch_add((seconds * 1e9) as u64, Ordering::Relaxed);
+    if let Some(bucket) = BUCKETS.iter().position(|bound| seconds <= *bound) {
+        counters.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Do `work`, counting it as a call to `function`, and as an error if it fails.
+pub(crate) fn observe<T, E>(function: &'static str, work: impl FnOnce() -> Result<T, E>) -> Result<T, E> {
+    let start = Instant::now();
+    let result = profiling::profile(function, work);
+    record(function, start.elapsed().as_secs_f64(), result.is_err());
+    result
+}
+
+/// Do `work`, which can't fail, counting it as a call to `function`.
+pub(crate) fn timed<T>(function: &'static str, work: impl FnOnce() -> T) -> T {
+    let start = Instant::now();
+    let value = profiling::profile(function, work);
+    record(function, start.elapsed().as_secs_f64(), false);
+    value
+}
+
+/// Append a metric of `series` in the prometheus text format, with a line for each function.
+fn metric(text: &mut String, name: &str, kind: &str, help: &str, series: &BTreeMap<&'static str, Series>, value: impl Fn(&Series) -> u64) {
+    let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} {kind}");
+    for (function, series) in series {
+        let _ = writeln!(text, "{name}{{function=\"{function}\"}} {}", value(series));
+    }
+}
+
+fn export(series: &BTreeMap<&'static str, Series>) -> String {
+    let mut text = String::new();
+    metric(&mut text, "charbot_rust_calls_total", "counter", "Calls to the functions of charbot_rust.", series, |series| series.calls);
+    metric(&mut text, "charbot_rust_errors_total", "counter", "Calls to the functions of charbot_rust that raised.", series, |series| series.errors);
+    let name = "charbot_rust_call_duration_seconds";
+    let _ = writeln!(text, "# HELP {name} How long the calls to the functions of charbot_rust took.\n# TYPE {name} histogram");
+    for (function, series) in series {
+        let mut calls = 0;
+        for (bound, count) in BUCKETS.iter().zip(series.buckets) {
+            calls += count;
+            let _ = writeln!(text, "{name}_bucket{{function=\"{function}\",le=\"{bound}\"}} {calls}");
+        }
+        let _ = writeln!(text, "{name}_bucket{{function=\"{function}\",le=\"+Inf\"}} {}", series.calls);
+        let _ = writeln!(text, "{name}_sum{{function=\"{function}\"}} {}", series.seconds);
+        let _ = writeln!(text, "{name}_count{{function=\"{function}\"}} {}", series.calls);
+    }
+    text
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+export_prometheus()
+--
+
+Export what has been counted since the bot started, in the prometheus text format, to add to the
+bot's own ``/metrics``.
+
+There are counters of the calls to each function and of the ones that raised, and a histogram of how
+long they took, each labelled with the function, like ``fluent.translate`` or ``connect4.play``.
+Functions that haven't been called yet are left out.
+
+Returns
+-------
+str
+    The metrics, ending in a newline.
+")]
+pub fn export_prometheus() -> String {
+    // read out first so the lock isn't held while formatting, which would hold up the first call of a function
+    let series = COUNTERS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .map(|(function, counters)| (*function, counters.series()))
+        .collect();
+    export(&series)
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based counters of the calls to charbot_rust, to export to prometheus";
+
+pub(crate) fn register_metrics(py: Python, m: &PyModule) -> PyResult<()> {
+    let metrics = PyModule::new(py, "_metrics")?;
+    metrics.add_function(wrap_pyfunction!(export_prometheus, metrics)?)?;
+    metrics.add("__doc__", DOCSTRING)?;
+    m.add_submodule(metrics)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counted(function: &'static str) -> Series {
+        counters(function).series()
+    }
+
+    #[test]
+    fn counting() {
+        assert_eq!(observe("tests.counting", || Ok::<_, String>(1)), Ok(1));
+        assert_eq!(observe("tests.counting", || Err::<u8, _>(String::from("failed"))), Err(String::from("failed")));
+        assert_eq!(timed("tests.counting", || 2), 2);
+        let series = counted("tests.counting");
+        assert_eq!((series.calls, series.errors), (3, 1));
+        assert!(series.buckets.iter().sum::<u64>() <= 3);
+    }
+
+    #[test]
+    fn buckets() {
+        record("tests.buckets", 0.00001, false);
+        record("tests.buckets", 0.0003, false);
+        record("tests.buckets", 0.0005, true);
+        record("tests.buckets", 10.0, false);
+        let series = counted("tests.buckets");
+        assert_eq!(series.buckets, [1, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!((series.calls, series.errors), (4, 1));
+    }
+
+    #[test]
+    fn counting_from_threads() {
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| (0..1000).for_each(|_| record("tests.threads", 0.001, false)));
+            }
+        });
+        let series = counted("tests.threads");
+        assert_eq!((series.calls, series.buckets[4]), (4000, 4000));
+    }
+
+    #[test]
+    fn prometheus() {
+        let series = Series { calls: 3, errors: 1, buckets: [1, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0], seconds: 3.5 };
+        let text = export(&BTreeMap::from([("fluent.translate", series)]));
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..6],
+            [
+                "# HELP charbot_rust_calls_total Calls to the functions of charbot_rust.",
+                "# TYPE charbot_rust_calls_total counter",
+                "charbot_rust_calls_total{function=\"fluent.translate\"} 3",
+                "# HELP charbot_rust_errors_total Calls to the functions of charbot_rust that raised.",
+                "# TYPE charbot_rust_errors_total counter",
+                "charbot_rust_errors_total{function=\"fluent.translate\"} 1",
+            ]
+        );
+        assert_eq!(lines[8], "charbot_rust_call_duration_seconds_bucket{function=\"fluent.translate\",le=\"0.00005\"} 1");
+        assert_eq!(lines[11], "charbot_rust_call_duration_seconds_bucket{function=\"fluent.translate\",le=\"0.0005\"} 2");
+        assert_eq!(lines[19], "charbot_rust_call_duration_seconds_bucket{function=\"fluent.translate\",le=\"2.5\"} 2");
+        assert_eq!(
+            lines[20..],
+            [
+                "charbot_rust_call_duration_seconds_bucket{function=\"fluent.translate\",le=\"+Inf\"} 3",
+                "charbot_rust_call_duration_seconds_sum{function=\"fluent.translate\"} 3.5",
+                "charbot_rust_call_duration_seconds_count{function=\"fluent.translate\"} 3",
+            ]
+        );
+        assert!(text.ends_with('\n'));
+    }
+
+    #[test]
+    fn nothing_counted() {
+        let text = export(&BTreeMap::new());
+        assert_eq!(text.lines().filter(|line| !line.starts_with('#')).count(), 0);
+    }
+}
+// COV_EXCL_STOP
@@ -14,6 +14,7 @@ pub(crate) fn register_minesweeper(py: Python, m: &PyModule) -> PyResult<()> {
     minesweeper.add_class::<game::Game>()?;
     minesweeper.add_class::<game::ChordResult>()?;
     minesweeper.add_class::<game::RevealResult>()?;
+    minesweeper.add_class::<game::CellState>()?;
     minesweeper.add("__doc__", DOCSTRING)?;
     m.add_submodule(minesweeper)?;
     Ok(())
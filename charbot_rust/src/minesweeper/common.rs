@@ -1,5 +1,7 @@
 // SPDX-FileCopyrightText: 2021 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
 // SPDX-License-Identifier: MIT
+// the labels and tiles are only drawn with the images feature
+#![cfg_attr(not(feature = "images"), allow(dead_code))]
 /*#[derive(PartialEq)]
 pub enum ParamType {
     Width,
@@ -55,6 +57,7 @@ pub const TILE_MINE_EXPLODED: &[u8] = include_bytes!("tiles/mine1.png");
 pub const TILE_MINE_UNEXPLODED: &[u8] = include_bytes!("tiles/mine2.png");
 pub const TILE_MINE_TRIGGER: &[u8] = include_bytes!("tiles/mine3.png");
 
+// COV_EXCL_START
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,3 +107,4 @@ mod tests {
         assert!(!img.is_empty(), "{} is empty/missing.", name);
     }
 }
+// COV_EXCL_STOP
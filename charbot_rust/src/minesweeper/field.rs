@@ -1,18 +1,25 @@
 // SPDX-FileCopyrightText: 2021 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
 // SPDX-License-Identifier: MIT
 // COV_EXCL_START
+#[cfg(feature = "images")]
 use std::io::Cursor;
 use std::collections::VecDeque;
+#[cfg(feature = "images")]
 use image::{ImageBuffer, RgbImage, imageops, Rgb, io::Reader as ImageReader};
+#[cfg(feature = "images")]
 use imageproc::{rect::Rect, drawing::{draw_filled_rect_mut, draw_hollow_rect_mut}};
 use rand::rngs::StdRng;
 use rand::prelude::SliceRandom;
+use serde::{Deserialize, Serialize};
 use crate::minesweeper::common::MoveDestination;
+#[cfg(feature = "images")]
 use crate::minesweeper::common;
-use crate::minesweeper::game::ReturnCell;
+use crate::minesweeper::game::{CellState, ReturnCell};
 // COV_EXCL_STOP
 
+#[cfg(feature = "images")]
 pub const TILE_WIDTH: u32 = 50;
+#[cfg(feature = "images")]
 pub const TILE_HEIGHT: u32 = 50;
 
 #[derive(Debug, PartialEq, Eq)] // COV_EXCL_LINE
@@ -66,12 +73,49 @@ impl Cell {
             marked: self.marked,
         }
     }
+
+    /// What the player can see of the cell, the content stays hidden until it is revealed.
+    pub fn to_cell_state(&self) -> CellState {
+        let (mine, exploded, adjacent) = match (self.revealed, &self.content) {
+            (false, _) | (true, Content::None) => (false, false, 0),
+            (true, &Content::Mine(killer)) => (true, killer, 0),
+            (true, &Content::Number(n)) => (false, false, n),
+        };
+        CellState {
+            revealed: self.revealed,
+            marked: self.marked,
+            mine,
+            exploded,
+            adjacent,
+        }
+    }
     #[allow(dead_code)]
     pub fn content(&self) -> &Content {
         &self.content
     }
 }
 
+/// A cell as it is saved, the numbers are counted again when the field is loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedCell {
+    pub mine: bool,
+    pub exploded: bool,
+    pub revealed: bool,
+    pub marked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedField {
+    pub width: u32,
+    pub height: u32,
+    pub mines: u32,
+    /// The cells as rows from the top, one after another.
+    pub cells: Vec<SavedCell>,
+    pub selected: (u32, u32),
+    /// Whether the mines have been placed, before that no cell can be a mine or revealed.
+    pub started: bool,
+}
+
 pub struct Field {
     cells: Vec<Cell>,
     width: u32,
@@ -353,18 +397,14 @@ impl Field {
         // closure to check for blank cells
         let mut check = |x, d: &mut VecDeque<i32>| {
             match self.get_content_safe(x) { // COV_EXCL_LINE
-                Some(&Content::None) => {
-                    if !self.revealed(x as u32) {
-                        d.push_back(x);
-                        self.get_cell_mut(x as u32).marked = false;
-                        self.reveal(x as u32);
-                    }
+                Some(&Content::None) if !self.revealed(x as u32) => {
+                    d.push_back(x);
+                    self.get_cell_mut(x as u32).marked = false;
+                    self.reveal(x as u32);
                 }
-                Some(&Content::Number(_n)) => {
-                    if !(self.revealed(x as u32)) {
-                        self.get_cell_mut(x as u32).marked = false;
-                        self.reveal(x as u32);
-                    }
+                Some(&Content::Number(_n)) if !self.revealed(x as u32) => {
+                    self.get_cell_mut(x as u32).marked = false;
+                    self.reveal(x as u32);
                 }
                 _ => {}
             }
@@ -393,6 +433,7 @@ impl Field {
         }
     }
 
+    #[cfg(feature = "images")]
     pub fn draw(&self) -> RgbImage {
         let mut img: RgbImage = ImageBuffer::new(self.width * TILE_WIDTH + 50, self.height * TILE_HEIGHT + 50);
         let labels = vec![common::LABEL_A, common::LABEL_B, common::LABEL_C, common::LABEL_D, common::LABEL_E, common::LABEL_F, common::LABEL_G, common::LABEL_H, common::LABEL_I, common::LABEL_J, common::LABEL_K, common::LABEL_L, common::LABEL_M, common::LABEL_N, common::LABEL_O, common::LABEL_P, common::LABEL_Q, common::LABEL_R, common::LABEL_S, common::LABEL_T, common::LABEL_U, common::LABEL_V, common::LABEL_W, common::LABEL_X, common::LABEL_Y];
@@ -509,6 +550,19 @@ impl Field {
         self.get_cell_index(x, y)
     }
 
+    /// The state of every cell, as rows from the top.
+    pub fn cell_states(&self) -> Vec<Vec<CellState>> {
+        self.cells
+            .chunks(self.width as usize)
+            .map(|row| row.iter().map(Cell::to_cell_state).collect())
+            .collect()
+    }
+
+    /// Whether the mines have been placed, which happens on the first reveal.
+    pub fn is_started(&self) -> bool {
+        !self.need_regen
+    }
+
     pub fn is_victory(&self) -> bool {
         self.numbers_total == self.numbers_opened
     }
@@ -566,6 +620,63 @@ impl Field {
         self.mines
     }
 
+    pub fn save(&self) -> SavedField {
+        SavedField {
+            width: self.width,
+            height: self.height,
+            mines: self.mines,
+            cells: self
+                .cells
+                .iter()
+                .map(|cell| SavedCell {
+                    mine: matches!(cell.content, Content::Mine(_)),
+                    exploded: cell.content == Content::Mine(true),
+                    revealed: cell.revealed,
+                    marked: cell.marked,
+                })
+                .collect(),
+            selected: (self.selected_x, self.selected_y),
+            started: self.is_started(),
+        }
+    }
+
+    /// Rebuild a saved field, the size and mine count are expected to be checked already.
+    pub fn load(saved: SavedField, rng: StdRng) -> Result<Field, String> {
+        if saved.cells.len() != (saved.width * saved.height) as usize {
+            return Err(format!("A {}x{} field has {} cells, not {}", saved.width, saved.height, saved.width * saved.height, saved.cells.len()));
+        }
+        let (x, y) = saved.selected;
+        if x >= saved.width || y >= saved.height {
+            return Err(format!("The selected cell {x}, {y} is outside of the field"));
+        }
+        let mines = saved.cells.iter().filter(|cell| cell.mine).count() as u32;
+        if saved.started && mines != saved.mines {
+            return Err(format!("The field has {mines} mines, not {}", saved.mines));
+        }
+        if !saved.started && (mines > 0 || saved.cells.iter().any(|cell| cell.revealed)) {
+            return Err("The mines are placed on the first reveal, before that no cell can be a mine or revealed".to_string());
+        }
+        if saved.cells.iter().any(|cell| cell.exploded && !(cell.mine && cell.revealed)) || saved.cells.iter().filter(|cell| cell.exploded).count() > 1 {
+            return Err("Only one revealed mine can have exploded".to_string());
+        }
+        let mut field = Field::new(saved.width, saved.height, saved.mines, rng);
+        field.cells = saved
+            .cells
+            .iter()
+            .map(|cell| Cell {
+                content: if cell.mine { Content::Mine(cell.exploded) } else { Content::None },
+                revealed: cell.revealed,
+                marked: cell.marked,
+            })
+            .collect();
+        field.write_numbers_near_mines();
+        field.numbers_opened = field.cells.iter().filter(|cell| cell.revealed && matches!(cell.content, Content::Number(_))).count() as u32;
+        field.selected_x = x;
+        field.selected_y = y;
+        field.need_regen = !saved.started;
+        Ok(field)
+    }
+
     pub fn get_selected_cell(&mut self) -> &Cell {
         let x = self.selected_x;
         let y = self.selected_y;
@@ -575,7 +686,7 @@ impl Field {
 }
 
 // COV_EXCL_START
-#[cfg(test)]
+#[cfg(all(test, feature = "images"))]
 mod tests {
     use rand::SeedableRng;
     use super::*;
@@ -601,4 +712,4 @@ mod tests {
         field.draw();
     }
 }
-//COV_EXCL_STOP
+// COV_EXCL_STOP
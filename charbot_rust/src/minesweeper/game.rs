@@ -2,10 +2,16 @@
 // SPDX-License-Identifier: MIT
 // COV_EXCL_START
 use pyo3::exceptions::PyValueError;
-use crate::minesweeper::{field::{Field, Content, TILE_HEIGHT, TILE_WIDTH}, common::MoveDestination};
+use crate::buffer::{Buffer, Bytes};
+use crate::minesweeper::{field::{Field, Content, SavedField}, common::MoveDestination};
+#[cfg(feature = "images")]
+use crate::minesweeper::field::{TILE_HEIGHT, TILE_WIDTH};
+use crate::metrics;
+use crate::persist::{self, Persist};
 use pyo3::prelude::*;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
 // COV_EXCL_STOP
 
 #[pyclass(module = "minesweeper")] // COV_EXCL_LINE
@@ -34,9 +40,43 @@ pub struct ReturnCell {
     pub marked: bool, // COV_EXCL_LINE
 }
 
+/// A cell as the player sees it, for drawing it as a button.
+///
+/// What is under a cell is only given once it is revealed, hidden cells are never a mine and have no adjacent mines.
+#[pyclass(module = "minesweeper")] // COV_EXCL_LINE
+#[derive(Debug, PartialEq, Eq, Clone)] // COV_EXCL_LINE
+pub struct CellState {
+    #[pyo3(get)]
+    pub revealed: bool,
+    #[pyo3(get)]
+    pub marked: bool,
+    #[pyo3(get)]
+    pub mine: bool,
+    /// Whether this is the mine that lost the game.
+    #[pyo3(get)]
+    pub exploded: bool,
+    #[pyo3(get)]
+    pub adjacent: u8, // COV_EXCL_LINE
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl CellState {
+    fn __repr__(&self) -> String {
+        format!(
+            "CellState(revealed={}, marked={}, mine={}, exploded={}, adjacent={})",
+            self.revealed, self.marked, self.mine, self.exploded, self.adjacent
+        )
+    }
+}
+
+/// The largest width and height, the board image only has labels for 25 rows and columns.
+const MAX_SIDE: u32 = 25;
+/// The cells kept free of mines around the first cell revealed.
+const SAFE_CELLS: u32 = 9;
+
 #[pyclass(module = "minesweeper")] // COV_EXCL_LINE
 pub struct Game {
-    field: Field,
+    pub(crate) field: Field,
     win_points: (u8, u8),
     lose_points: (u8, u8),
     quit: bool,
@@ -45,14 +85,25 @@ pub struct Game {
 #[pymethods] // COV_EXCL_LINE
 impl Game {
     #[new] // COV_EXCL_LINE
-    fn new(width: u32, height: u32, mines: u32) -> Self { // COV_EXCL_LINE
+    fn new(width: u32, height: u32, mines: u32) -> PyResult<Self> { // COV_EXCL_LINE
+        if !(1..=MAX_SIDE).contains(&width) || !(1..=MAX_SIDE).contains(&height) {
+            return Err(PyValueError::new_err(format!(
+                "Invalid board size {width}x{height}, the width and height must be from 1 to {MAX_SIDE}"
+            )));
+        }
+        if mines + SAFE_CELLS > width * height {
+            return Err(PyValueError::new_err(format!(
+                "Too many mines, a {width}x{height} board fits at most {}",
+                (width * height).saturating_sub(SAFE_CELLS)
+            )));
+        }
         let rng = StdRng::from_entropy();
-        Game {
+        Ok(Game {
             field: Field::new(width, height, mines, rng,),
             win_points: (1, 0),
             lose_points: (1, 0),
             quit: false,
-        }
+        })
     }
 
     #[staticmethod] // COV_EXCL_LINE
@@ -138,14 +189,11 @@ impl Game {
         self.field.get_y()
     }
 
-    fn draw(&mut self) -> (Vec<u8>, (u32, u32)) {
-        (
-            self.field.draw().to_vec(),
-            (
-                (self.field.get_width() + 1) * TILE_WIDTH,
-                (self.field.get_height() + 1) * TILE_HEIGHT,
-            ),
-        )
+    #[cfg(feature = "images")]
+    fn draw(&mut self, py: Python) -> (Buffer, (u32, u32)) {
+        // drawing the image is the slow part of a move, other threads can run meanwhile
+        let (pixels, size) = py.allow_threads(|| self.render());
+        (Buffer::from(pixels), size)
     }
 
     fn change_row(&mut self, row: u32) -> PyResult<ReturnCell> { // COV_EXCL_LINE
@@ -216,6 +264,7 @@ impl Game {
                             Content::Mine(_) => {
                                 self.field.set_killer(id);
                                 self.field.reveal_all();
+                                self.quit = true;
                                 return ChordResult::Death;
                             },
                             Content::None => {
@@ -242,6 +291,35 @@ impl Game {
         }
     }
 
+    /// Whether a mine was revealed or the game was quit.
+    fn is_lost(&self) -> bool {
+        self.quit
+    }
+
+    /// Whether the game is won or lost, a game where nothing was revealed yet isn't over.
+    fn is_over(&self) -> bool {
+        self.is_lost() || (self.field.is_started() && self.is_win())
+    }
+
+    /// Every cell as rows from the top, so the board can be drawn as buttons without an image.
+    fn cells(&self) -> Vec<Vec<CellState>> {
+        self.field.cell_states()
+    }
+
+    /// Select the cell at `row`, `col` and reveal it, as a button press would.
+    fn reveal_at(&mut self, row: u32, col: u32) -> PyResult<RevealResult> {
+        metrics::observe("minesweeper.reveal", || {
+            self.select(row, col)?;
+            Ok(self.reveal())
+        })
+    }
+
+    /// Select the cell at `row`, `col` and toggle its flag, as a button press would.
+    fn toggle_flag_at(&mut self, row: u32, col: u32) -> PyResult<bool> {
+        self.select(row, col)?;
+        Ok(self.toggle_flag())
+    }
+
     fn quit(&mut self) {
         self.quit = true;
         self.field.reveal_all();
@@ -250,6 +328,85 @@ impl Game {
     fn restart(&mut self) {
         self.field.restart();
     }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod] // COV_EXCL_LINE
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod] // COV_EXCL_LINE
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+impl Game {
+    fn select(&mut self, row: u32, col: u32) -> PyResult<()> {
+        self.change_row(row)?;
+        self.change_col(col)?;
+        Ok(())
+    }
+
+    /// The image of the field as raw RGB bytes, and its size.
+    #[cfg(feature = "images")]
+    fn render(&mut self) -> (Vec<u8>, (u32, u32)) {
+        (
+            self.field.draw().to_vec(),
+            (
+                (self.field.get_width() + 1) * TILE_WIDTH,
+                (self.field.get_height() + 1) * TILE_HEIGHT,
+            ),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct State {
+    field: SavedField,
+    win_points: (u8, u8),
+    lose_points: (u8, u8),
+    quit: bool,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "minesweeper";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State {
+            field: self.field.save(),
+            win_points: self.win_points,
+            lose_points: self.lose_points,
+            quit: self.quit,
+        }
+    }
+
+    /// The mines of a game that hasn't started are placed with a new random generator.
+    fn load(state: State) -> Result<Self, String> {
+        let (width, height, mines) = (state.field.width, state.field.height, state.field.mines);
+        if !(1..=MAX_SIDE).contains(&width) || !(1..=MAX_SIDE).contains(&height) || mines + SAFE_CELLS > width * height {
+            return Err(format!("Invalid board of {width}x{height} with {mines} mines"));
+        }
+        if state.field.cells.iter().any(|cell| cell.exploded) && !state.quit {
+            return Err("A mine exploded but the game isn't over".to_string());
+        }
+        Ok(Game {
+            field: Field::load(state.field, StdRng::from_entropy())?,
+            win_points: state.win_points,
+            lose_points: state.lose_points,
+            quit: state.quit,
+        })
+    }
 }
 
 // COV_EXCL_START
@@ -258,7 +415,7 @@ mod tests {
     use super::*;
     #[test]
     fn custom() {
-        let game = Game::new(10, 10, 10);
+        let game = Game::new(10, 10, 10).expect("Valid board");
         assert_eq!(game.width(), 10);
         assert_eq!(game.height(), 10);
         assert_eq!(game.mine_count(), 10);
@@ -325,7 +482,7 @@ mod tests {
     }
     #[test]
     fn change_row() {
-        let mut game = Game::new(5, 5, 5);
+        let mut game = Game::new(5, 5, 5).expect("Valid board");
         let out1 = game.change_row(4).expect("Saw row 4 as out of bounds incorrectly.");
         assert_eq!(game.y(), 4);
         assert_eq!(out1, ReturnCell{ revealed: false, marked: false });
@@ -338,7 +495,7 @@ mod tests {
     }
     #[test]
     fn change_col() {
-        let mut game = Game::new(5, 5, 5);
+        let mut game = Game::new(5, 5, 5).expect("Valid board");
         let out1 = game.change_col(4).expect("Saw col 4 as out of bounds incorrectly.");
         assert_eq!(game.x(), 4);
         assert_eq!(out1, ReturnCell{ revealed: false, marked: false });
@@ -394,7 +551,7 @@ mod tests {
     }
     #[test]
     fn reveal() {
-        let mut game = Game::new(5, 5, 10);
+        let mut game = Game::new(5, 5, 10).expect("Valid board");
         let result = game.reveal();
         assert_ne!(result, RevealResult::Flagged);
         assert_ne!(result, RevealResult::Mine);
@@ -416,12 +573,13 @@ mod tests {
             }
         }
     }
+    #[cfg(feature = "images")]
     #[test]
     fn draw() {
-        let mut game = Game::new(5, 5, 5);
+        let mut game = Game::new(5, 5, 5).expect("Valid board");
         let field = Field::new(5, 5, 5, StdRng::from_entropy());
         let drawn_field = field.draw().to_vec();
-        let (drawn_game, dims) = game.draw();
+        let (drawn_game, dims) = game.render();
         assert_eq!(dims, (300, 300));
         assert_eq!(drawn_field, drawn_game);
     }
@@ -460,6 +618,137 @@ mod tests {
         game.toggle_flag();
         game.change_col(5).unwrap();
         assert_eq!(game.chord(), ChordResult::Death);
+        assert!(game.is_lost());
+    }
+    #[test]
+    fn validation() {
+        assert!(Game::new(0, 5, 0).is_err());
+        assert!(Game::new(5, 26, 0).is_err());
+        assert!(Game::new(5, 5, 17).is_err());
+        assert!(Game::new(25, 5, 116).is_ok());
+        assert!(Game::new(5, 5, 16).is_ok());
+    }
+    #[test]
+    fn first_reveal_is_safe() {
+        for seed in 0..20 {
+            let mut game = Game {
+                field: Field::new(8, 8, 55, StdRng::from_seed([seed; 32])),
+                win_points: (0, 0),
+                lose_points: (0, 0),
+                quit: false
+            };
+            assert_ne!(game.reveal_at(0, 0).expect("In Bounds"), RevealResult::Mine);
+            assert!(!game.is_lost());
+        }
+    }
+    #[test]
+    fn cells() {
+        let mut game = Game {
+            field: Field::new(8, 8, 5, StdRng::from_seed([0; 32])),
+            win_points: (0, 0),
+            lose_points: (0, 0),
+            quit: false
+        };
+        let hidden = CellState { revealed: false, marked: false, mine: false, exploded: false, adjacent: 0 };
+        assert!(game.cells().iter().flatten().all(|cell| *cell == hidden));
+        assert_ne!(game.reveal_at(4, 4).expect("In Bounds"), RevealResult::Mine);
+        let cells = game.cells();
+        assert_eq!(cells.len(), 8);
+        let mut mine = None;
+        for y in 0..8 {
+            for x in 0..8 {
+                let ind = game.field.get_cell_index(x, y);
+                let state = &cells[y as usize][x as usize];
+                let expected = match (game.field.revealed(ind), game.field.get_content(ind)) {
+                    (false, content) => {
+                        if let Content::Mine(_) = content {
+                            mine = Some((y, x));
+                        }
+                        hidden.clone()
+                    },
+                    (true, Content::Number(n)) => CellState { revealed: true, adjacent: *n, ..hidden.clone() },
+                    (true, _) => CellState { revealed: true, ..hidden.clone() },
+                };
+                assert_eq!(*state, expected);
+            }
+        }
+        let (row, col) = mine.expect("The board has mines");
+        assert!(game.toggle_flag_at(row, col).expect("In Bounds"));
+        assert_eq!(game.cells()[row as usize][col as usize], CellState { marked: true, ..hidden.clone() });
+        assert!(game.toggle_flag_at(row, col).expect("In Bounds"));
+        assert!(game.reveal_at(8, 0).is_err());
+        assert!(game.toggle_flag_at(0, 8).is_err());
+        assert!(!game.is_over());
+        assert_eq!(game.reveal_at(row, col).expect("In Bounds"), RevealResult::Mine);
+        assert!(game.is_lost());
+        assert!(game.is_over());
+        let cells = game.cells();
+        assert_eq!(cells[row as usize][col as usize], CellState { revealed: true, mine: true, exploded: true, ..hidden });
+        assert_eq!(cells.iter().flatten().filter(|cell| cell.mine).count(), 5);
+        assert_eq!(cells.iter().flatten().filter(|cell| cell.exploded).count(), 1);
+    }
+    #[test]
+    fn win() {
+        let mut game = Game::new(4, 4, 0).expect("Valid board");
+        assert!(!game.is_over());
+        assert_eq!(game.reveal_at(1, 1).expect("In Bounds"), RevealResult::Empty);
+        assert!(game.cells().iter().flatten().all(|cell| cell.revealed));
+        assert!(game.is_win());
+        assert!(game.is_over());
+        assert!(!game.is_lost());
+    }
+    #[test]
+    fn persists() {
+        let mut game = Game {
+            field: Field::new(8, 8, 5, StdRng::from_seed([0; 32])),
+            win_points: (1, 2),
+            lose_points: (1, 0),
+            quit: false
+        };
+        let fresh: Game = persist::from_json(&persist::to_json(&game).expect("Failed to save")).expect("Failed to load");
+        assert!(!fresh.field.is_started());
+        game.reveal_at(4, 4).expect("In Bounds");
+        game.toggle_flag_at(0, 0).expect("In Bounds");
+        game.change_col(6).expect("In Bounds");
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Game = persist::from_json(&json).expect("Failed to load");
+        assert_eq!(loaded.cells(), game.cells());
+        assert_eq!((loaded.x(), loaded.y(), loaded.mine_count()), (game.x(), game.y(), game.mine_count()));
+        assert_eq!((loaded.win_points, loaded.lose_points), (game.win_points, game.lose_points));
+        assert_eq!(loaded.field.save(), game.field.save());
+        assert_eq!(loaded.is_win(), game.is_win());
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        assert_eq!(persist::from_bytes::<Game>(&bytes).map(|loaded| loaded.cells()), Ok(game.cells()));
+        game.quit();
+        let lost: Game = persist::from_bytes(&persist::to_bytes(&game).expect("Failed to save")).expect("Failed to load");
+        assert!(lost.is_lost());
+    }
+    #[test]
+    fn rejects_saves() {
+        let mut game = Game::new(5, 5, 3).expect("Valid board");
+        game.reveal_at(2, 2).expect("In Bounds");
+        let state = game.save();
+        let mut wrong = game.save();
+        wrong.field.cells.pop();
+        assert!(Game::load(wrong).is_err());
+        let mut wrong = game.save();
+        wrong.field.mines = 4;
+        assert!(Game::load(wrong).is_err());
+        let mut wrong = game.save();
+        wrong.field.width = 30;
+        assert!(Game::load(wrong).is_err());
+        let mut wrong = game.save();
+        wrong.field.selected = (5, 0);
+        assert!(Game::load(wrong).is_err());
+        let mut wrong = game.save();
+        let mine = wrong.field.cells.iter_mut().find(|cell| cell.mine).expect("The board has mines");
+        mine.revealed = true;
+        mine.exploded = true;
+        assert!(Game::load(wrong).is_err());
+        let mut wrong = Game::new(5, 5, 3).expect("Valid board").save();
+        wrong.field.cells[0].revealed = true;
+        assert!(Game::load(wrong).is_err());
+        assert!(Game::load(state).is_ok());
     }
 }
-// GCOV_EXCL_STOP
+// COV_EXCL_STOP
@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod fold; // COV_EXCL_LINE
+mod heuristics;
+mod links;
+mod normalize;
+mod punycode;
+mod raid;
+mod safe_regex;
+mod spam;
+mod toxicity;
+mod wordfilter;
+
+use pyo3::prelude::*;
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based moderation tools, like filtering banned words, spotting spam, raids and toxicity, checking links and look alike names";
+
+pub(crate) fn register_moderation(py: Python, m: &PyModule) -> PyResult<()> {
+    let moderation = PyModule::new(py, "_moderation")?;
+    moderation.add_class::<wordfilter::WordFilter>()?;
+    moderation.add_class::<wordfilter::Match>()?;
+    moderation.add_class::<spam::Similarity>()?;
+    moderation.add_class::<spam::Report>()?;
+    moderation.add_class::<normalize::Normalized>()?;
+    moderation.add_function(wrap_pyfunction!(normalize::normalize, moderation)?)?;
+    moderation.add_function(wrap_pyfunction!(normalize::py_skeleton, moderation)?)?;
+    moderation.add_function(wrap_pyfunction!(normalize::is_confusable, moderation)?)?;
+    moderation.add_class::<links::Link>()?;
+    moderation.add_function(wrap_pyfunction!(links::scan, moderation)?)?;
+    moderation.add_function(wrap_pyfunction!(links::scan_many, moderation)?)?;
+    moderation.add_class::<raid::Detector>()?;
+    moderation.add_class::<raid::Alert>()?;
+    moderation.add_class::<heuristics::Weights>()?;
+    moderation.add_class::<heuristics::Breakdown>()?;
+    moderation.add_function(wrap_pyfunction!(heuristics::score, moderation)?)?;
+    moderation.add_class::<safe_regex::SafeRegex>()?;
+    moderation.add_class::<toxicity::Toxicity>()?;
+    moderation.add_function(wrap_pyfunction!(toxicity::score, moderation)?)?;
+    moderation.add("__doc__", DOCSTRING)?;
+    m.add_submodule(moderation)?;
+    Ok(())
+}
+// COV_EXCL_STOP
@@ -0,0 +1,109 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+
+/// Characters that take no space, used to split up words so filters don't see them.
+pub fn is_invisible(letter: char) -> bool {
+    matches!(letter, '\u{ad}' | '\u{34f}' | '\u{61c}' | '\u{180e}' | '\u{200b}'..='\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2060}'..='\u{2064}' | '\u{feff}')
+}
+
+/// Combining marks, which stack on the letter before them.
+pub fn is_combining(letter: char) -> bool {
+    matches!(letter, '\u{300}'..='\u{36f}' | '\u{1ab0}'..='\u{1aff}' | '\u{1dc0}'..='\u{1dff}' | '\u{20d0}'..='\u{20ff}' | '\u{fe20}'..='\u{fe2f}')
+}
+
+/// The plain letters a lowercase letter with a diacritic or ligature stands for, empty if it's plain already.
+fn plain(letter: char) -> &'static str {
+    match letter {
+        'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'é' | 'è' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'í' | 'ì' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' | 'ō' | 'ő' => "o",
+        'ú' | 'ù' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ç' | 'ć' | 'č' => "c",
+        'ñ' | 'ń' | 'ň' => "n",
+        'š' | 'ś' | 'ş' => "s",
+        'ž' | 'ź' | 'ż' => "z",
+        'ď' | 'đ' => "d",
+        'ğ' => "g",
+        'ł' | 'ľ' => "l",
+        'ř' => "r",
+        'ť' => "t",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        'þ' => "th",
+        _ => "",
+    }
+}
+
+/// `text` lowercase, without diacritics, invisible characters or combining marks, and with full width
+/// letters as ascii, each character with the index of the character in `text` it came from.
+pub fn fold(text: &str) -> Vec<(char, usize)> {
+    let mut folded = Vec::with_capacity(text.len());
+    for (index, letter) in text.chars().enumerate() {
+        if is_invisible(letter) {
+            continue;
+        }
+        for lower in letter.to_lowercase() {
+            let lower = match lower {
+                '\u{ff01}'..='\u{ff5e}' => char::from_u32(lower as u32 - 0xfee0).unwrap_or(lower),
+                _ => lower,
+            };
+            match plain(lower) {
+                _ if is_combining(lower) => {}
+                "" => folded.push((lower, index)),
+                letters => folded.extend(letters.chars().map(|letter| (letter, index))),
+            }
+        }
+    }
+    folded
+}
+
+/// The letters a character can stand for in leetspeak, besides itself.
+pub fn leet(letter: char) -> &'static [char] {
+    match letter {
+        '0' => &['o'],
+        '1' => &['i', 'l'],
+        '!' => &['i'],
+        '|' => &['l', 'i'],
+        '3' | '€' => &['e'],
+        '4' | '@' => &['a'],
+        '5' | '$' => &['s'],
+        '7' | '+' => &['t'],
+        '8' => &['b'],
+        '9' => &['g'],
+        _ => &[],
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folded(text: &str) -> String {
+        fold(text).into_iter().map(|(letter, _)| letter).collect()
+    }
+
+    #[test]
+    fn folding() {
+        assert_eq!(folded("Ñandú"), "nandu");
+        assert_eq!(folded("Straße"), "strasse");
+        assert_eq!(folded("b\u{200b}a\u{feff}d"), "bad");
+        assert_eq!(folded("ba\u{301}\u{302}d"), "bad");
+        assert_eq!(folded("ＢＡＤ"), "bad");
+        assert_eq!(folded("İ"), "i");
+        // the indices are of the characters the folded ones came from
+        assert_eq!(fold("a\u{200b}ß"), vec![('a', 0), ('s', 2), ('s', 2)]);
+    }
+
+    #[test]
+    fn leetspeak() {
+        assert_eq!(leet('1'), ['i', 'l']);
+        assert_eq!(leet('@'), ['a']);
+        assert!(leet('a').is_empty());
+    }
+}
+// COV_EXCL_STOP
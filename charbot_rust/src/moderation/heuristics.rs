@@ -0,0 +1,314 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// The fewest letters a message needs for its caps to count, so "OK" and "LOL" aren't shouting.
+const MIN_LETTERS: usize = 8;
+/// The share of a message that's emoji at which its emoji score is full.
+const FULL_EMOJI: f64 = 0.5;
+/// The share of a message that's repeated characters at which its repeats score is full.
+const FULL_REPEATS: f64 = 0.3;
+/// How many mentions make the mentions score full.
+const FULL_MENTIONS: f64 = 5.0;
+/// How many of the same character in a row are fine, like "sooo", before the rest count as repeated.
+const RUN_ALLOWED: usize = 3;
+
+/// A piece of a message that's looked at as one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Unit {
+    Letter(char),
+    Emoji(String),
+    Mention(String),
+    Space,
+}
+
+fn is_emoji(letter: char) -> bool {
+    matches!(u32::from(letter),
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B05..=0x2B07 | 0x2B1B | 0x2B1C | 0x2B50 | 0x2B55 | 0x3030 | 0x303D | 0x3297 | 0x3299 | 0x2190..=0x21FF | 0x231A | 0x231B | 0x23E9..=0x23FA)
+        // skin tones are part of the emoji before them
+        && !(0x1F3FB..=0x1F3FF).contains(&u32::from(letter))
+}
+
+fn is_regional_indicator(letter: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&letter)
+}
+
+/// What's joined onto an emoji, like skin tones, variation selectors and keycaps.
+fn is_emoji_part(letter: char) -> bool {
+    matches!(letter, '\u{FE0F}' | '\u{FE0E}' | '\u{20E3}' | '\u{1F3FB}'..='\u{1F3FF}' | '\u{E0020}'..='\u{E007F}')
+}
+
+/// The end of the Discord tag like `<@123>` or `<:name:123>` at `index`, and the unit it is, if there's one.
+fn tag(chars: &[char], index: usize) -> Option<(usize, Unit)> {
+    let close = index + chars[index..].iter().take(100).position(|letter| *letter == '>')?;
+    let inside = chars[index + 1..close].iter().collect::<String>();
+    let digits = |text: &str| !text.is_empty() && text.chars().all(|letter| letter.is_ascii_digit());
+    let mention = inside.strip_prefix("@!").or_else(|| inside.strip_prefix("@&")).or_else(|| inside.strip_prefix('@'));
+    if mention.is_some_and(digits) {
+        return Some((close + 1, Unit::Mention(inside)));
+    }
+    let emoji = inside.strip_prefix("a:").or_else(|| inside.strip_prefix(':')).and_then(|emoji| emoji.rsplit_once(':'));
+    if emoji.is_some_and(|(name, id)| !name.is_empty() && digits(id)) {
+        return Some((close + 1, Unit::Emoji(inside)));
+    }
+    // channels and timestamps show as one thing, like a word
+    (inside.starts_with('#') && digits(&inside[1..]) || inside.starts_with("t:")).then(|| (close + 1, Unit::Letter('#')))
+}
+
+fn units(text: &str) -> Vec<Unit> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut units = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let letter = chars[index];
+        if letter == '<' {
+            if let Some((end, unit)) = tag(&chars, index) {
+                units.push(unit);
+                index = end;
+                continue;
+            }
+        }
+        if letter == '@' {
+            let rest = chars[index + 1..].iter().take(8).collect::<String>();
+            if let Some(everyone) = ["everyone", "here"].into_iter().find(|everyone| rest.starts_with(everyone)) {
+                units.push(Unit::Mention(everyone.to_string()));
+                index += 1 + everyone.len();
+                continue;
+            }
+        }
+        if is_emoji(letter) || (letter.is_ascii_digit() || letter == '#' || letter == '*') && chars.get(index + 1..index + 3) == Some(&['\u{FE0F}', '\u{20E3}']) {
+            let mut end = index + 1;
+            // a flag is two regional indicators, and emoji joined by zero width joiners are one
+            if is_regional_indicator(letter) && chars.get(end).copied().is_some_and(is_regional_indicator) {
+                end += 1;
+            }
+            loop {
+                while chars.get(end).copied().is_some_and(is_emoji_part) {
+                    end += 1;
+                }
+                if chars.get(end) == Some(&'\u{200D}') && chars.get(end + 1).copied().is_some_and(is_emoji) {
+                    end += 2;
+                } else {
+                    break;
+                }
+            }
+            units.push(Unit::Emoji(chars[index..end].iter().collect()));
+            index = end;
+            continue;
+        }
+        units.push(if letter.is_whitespace() { Unit::Space } else { Unit::Letter(letter) });
+        index += 1;
+    }
+    units
+}
+
+/// How much each part counts towards the score, from 0 for not at all to 1 for enough on its own.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Weights {
+    #[pyo3(get)]
+    caps: f64,
+    #[pyo3(get)]
+    emoji: f64,
+    #[pyo3(get)]
+    repeats: f64,
+    #[pyo3(get)]
+    mentions: f64,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Weights { caps: 0.5, emoji: 0.5, repeats: 0.5, mentions: 0.8 }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Weights {
+    #[new]
+    #[pyo3(signature = (caps=0.5, emoji=0.5, repeats=0.5, mentions=0.8))]
+    fn py_new(caps: f64, emoji: f64, repeats: f64, mentions: f64) -> PyResult<Self> {
+        for (name, weight) in [("caps", caps), ("emoji", emoji), ("repeats", repeats), ("mentions", mentions)] {
+            if !(0.0..=1.0).contains(&weight) {
+                return Err(PyValueError::new_err(format!("Invalid {name} weight {weight}, it must be from 0 to 1")));
+            }
+        }
+        Ok(Weights { caps, emoji, repeats, mentions })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Weights(caps={}, emoji={}, repeats={}, mentions={})", self.caps, self.emoji, self.repeats, self.mentions)
+    }
+}
+
+/// How spammy a message looks, and why.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Breakdown {
+    /// The share of the letters that are capitals, from 0 to 1, or 0 if there are too few letters to tell.
+    #[pyo3(get)]
+    caps_ratio: f64,
+    /// How many emoji there are, custom ones included.
+    #[pyo3(get)]
+    emoji_count: usize,
+    /// The share of the message that's emoji, from 0 to 1, without counting whitespace.
+    #[pyo3(get)]
+    emoji_density: f64,
+    /// The most of the same character, emoji or mention in a row.
+    #[pyo3(get)]
+    longest_run: usize,
+    /// How many characters are in runs past the third of the same one, like the last four of "nooooooo".
+    #[pyo3(get)]
+    repeated: usize,
+    /// How many users and roles are mentioned, @everyone and @here included.
+    #[pyo3(get)]
+    mentions: usize,
+    /// How spammy the message looks overall, from 0 to 1.
+    #[pyo3(get)]
+    score: f64,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Breakdown {
+    fn __repr__(&self) -> String {
+        format!(
+            "Breakdown(score={:.3}, caps_ratio={:.3}, emoji_count={}, longest_run={}, mentions={})",
+            self.score, self.caps_ratio, self.emoji_count, self.longest_run, self.mentions
+        )
+    }
+}
+
+/// Score `text`, each part from 0 to 1 and combined so any one part with a weight of 1 is enough:
+/// the score is the chance at least one part "fires", each firing with its weight times its part.
+fn breakdown(text: &str, weights: &Weights) -> Breakdown {
+    let units = units(text);
+    let visible = units.iter().filter(|unit| **unit != Unit::Space).count();
+    let (mut upper, mut cased) = (0, 0);
+    for unit in &units {
+        if let Unit::Letter(letter) = unit {
+            cased += usize::from(letter.is_uppercase() || letter.is_lowercase());
+            upper += usize::from(letter.is_uppercase());
+        }
+    }
+    let caps_ratio = if cased < MIN_LETTERS { 0.0 } else { upper as f64 / cased as f64 };
+    let emoji_count = units.iter().filter(|unit| matches!(unit, Unit::Emoji(_))).count();
+    let mentions = units.iter().filter(|unit| matches!(unit, Unit::Mention(_))).count();
+    let emoji_density = if visible == 0 { 0.0 } else { emoji_count as f64 / visible as f64 };
+    let (mut longest_run, mut repeated, mut run) = (0, 0, 0);
+    for (index, unit) in units.iter().enumerate() {
+        let same = |previous: &Unit| match (previous, unit) {
+            (Unit::Letter(previous), Unit::Letter(letter)) => previous.to_lowercase().eq(letter.to_lowercase()),
+            _ => previous == unit,
+        };
+        run = if *unit != Unit::Space && index > 0 && same(&units[index - 1]) { run + 1 } else { usize::from(*unit != Unit::Space) };
+        longest_run = longest_run.max(run);
+        repeated += usize::from(run > RUN_ALLOWED);
+    }
+    let parts = [
+        (weights.caps, caps_ratio),
+        (weights.emoji, (emoji_density / FULL_EMOJI).min(1.0)),
+        (weights.repeats, if visible == 0 { 0.0 } else { (repeated as f64 / visible as f64 / FULL_REPEATS).min(1.0) }),
+        (weights.mentions, (mentions as f64 / FULL_MENTIONS).min(1.0)),
+    ];
+    let score = 1.0 - parts.iter().map(|(weight, part)| 1.0 - weight * part).product::<f64>();
+    Breakdown { caps_ratio, emoji_count, emoji_density, longest_run, repeated, mentions, score }
+}
+
+#[pyfunction]
+#[pyo3(signature = (text, weights=None), text_signature = "
+score(text, weights=None, /)
+--
+
+Score how spammy a message looks from its caps, emoji, repeated characters and mentions, in one pass.
+
+Each part is scored from 0 to 1, and they're combined so any one part with a weight of 1 is enough on
+its own: the score is the chance at least one part fires, each firing with its weight times its part.
+Caps only count with at least 8 letters, emoji are full at half the message, repeats at 30 percent of
+it past the third of a character in a row, and mentions at 5 of them.
+
+Parameters
+----------
+text : str
+    The message.
+weights : Weights | None
+    How much each part counts. The default is None, for Weights().
+
+Returns
+-------
+Breakdown
+    The parts and the score.
+")]
+pub fn score(text: &str, weights: Option<Weights>) -> Breakdown {
+    metrics::timed("moderation.score", || breakdown(text, &weights.unwrap_or_default()))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn parts(text: &str) -> Breakdown {
+        breakdown(text, &Weights::default())
+    }
+
+    #[parameterized(
+        plain = {"hello there", 0},
+        unicode = {"nice 😀 work 👍", 2},
+        joined = {"👨‍👩‍👧 family", 1},
+        skin_tone = {"👍🏽", 1},
+        flag = {"🇨🇦🇫🇷", 2},
+        keycap = {"1️⃣", 1},
+        custom = {"<:pog:123456> <a:dance:99>", 2},
+        not_custom = {"<notanemoji> <:x:>", 0},
+    )]
+    fn emoji(text: &str, count: usize) {
+        assert_eq!(parts(text).emoji_count, count);
+    }
+
+    #[test]
+    fn caps() {
+        assert_eq!(parts("THIS IS ALL CAPS").caps_ratio, 1.0);
+        assert_eq!(parts("LOL OK").caps_ratio, 0.0);
+        assert_eq!(parts("Half UPPER lower").caps_ratio, 6.0 / 14.0);
+        // custom emoji names and mentions aren't letters that were typed
+        assert_eq!(parts("quiet words here <:LOUDEMOJI:1>").caps_ratio, 0.0);
+        assert_eq!(parts("日本語のテキストです").caps_ratio, 0.0);
+    }
+
+    #[test]
+    fn repeats() {
+        let found = parts("nooooooo");
+        assert_eq!((found.longest_run, found.repeated), (7, 4));
+        assert_eq!(parts("sooo good").repeated, 0);
+        assert_eq!(parts("NoOoOo").longest_run, 5);
+        assert_eq!(parts("aaa aaa").longest_run, 3);
+        assert_eq!(parts("😂😂😂😂😂").repeated, 2);
+    }
+
+    #[test]
+    fn mentions() {
+        assert_eq!(parts("hi <@123> and <@!456> and <@&789>").mentions, 3);
+        assert_eq!(parts("@everyone @here look").mentions, 2);
+        assert_eq!(parts("<#123> email@here.com").mentions, 1);
+        assert_eq!(parts("<@abc>").mentions, 0);
+    }
+
+    #[test]
+    fn scores() {
+        assert_eq!(parts("").score, 0.0);
+        assert!(parts("just a normal message, nothing to see").score < 0.05);
+        let shouting = parts("WHY IS NOBODY ANSWERING ME");
+        assert!((shouting.score - 0.5).abs() < 1e-9);
+        let spam = parts("FREE NITRO 😀😀😀😀😀😀 <@1> <@2> <@3> <@4> <@5> @everyone");
+        assert!(spam.score > 0.9, "Got {spam:?}");
+        let only_mentions = Weights { caps: 0.0, emoji: 0.0, repeats: 0.0, mentions: 1.0 };
+        assert_eq!(breakdown("<@1> <@2> <@3> <@4> <@5> HELLO EVERYONE 😀😀😀", &only_mentions).score, 1.0);
+        assert_eq!(breakdown("WHY IS NOBODY ANSWERING ME", &only_mentions).score, 0.0);
+    }
+}
+// COV_EXCL_STOP
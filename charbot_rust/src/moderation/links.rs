@@ -0,0 +1,520 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::moderation::fold::is_invisible;
+use crate::moderation::normalize::skeleton;
+use crate::moderation::punycode;
+use crate::metrics;
+use crate::tasks;
+// COV_EXCL_STOP
+
+/// The top level domains a domain without `http://` is taken as a link for, so `script.py` isn't one.
+const TLDS: [&str; 64] = [
+    "com", "net", "org", "io", "gg", "me", "co", "uk", "us", "ca", "de", "fr", "nl", "es", "it", "ru", "cn", "jp", "au", "in", "br",
+    "pl", "se", "no", "eu", "xyz", "info", "biz", "tv", "app", "dev", "ly", "gl", "to", "cc", "be", "link", "site", "online", "shop",
+    "store", "club", "top", "live", "fun", "gift", "gifts", "click", "website", "space", "tech", "pro", "ws", "su", "tk", "ml", "ga",
+    "cf", "gq", "pw", "sh", "ai", "gd", "fm",
+];
+/// The domains Discord invites are on, and the path before the code on each.
+const INVITE_HOSTS: [(&str, &str); 5] =
+    [("discord.gg", ""), ("discord.com", "invite/"), ("discordapp.com", "invite/"), ("discord.me", ""), ("discord.io", "")];
+/// Characters that end a link, since they're what Discord and markdown put around them.
+const TERMINATORS: [char; 5] = ['<', '>', '"', '`', '|'];
+
+/// A link or invite found in a message.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Link {
+    /// The index of the first character of the link in the message.
+    #[pyo3(get)]
+    start: usize,
+    /// The index after the last character of the link.
+    #[pyo3(get)]
+    end: usize,
+    /// The text of the message that is the link, as it was typed.
+    #[pyo3(get)]
+    text: String,
+    /// The link without its obfuscation, with the domain lowercase and in punycode.
+    #[pyo3(get)]
+    url: String,
+    /// The domain, lowercase and in punycode, like `xn--dscord-3ve.com`.
+    #[pyo3(get)]
+    host: String,
+    /// The domain as it's shown, with punycode turned back into the letters it spells, like `dіscord.com`.
+    #[pyo3(get)]
+    display: String,
+    /// The code, if it's a Discord invite.
+    #[pyo3(get)]
+    invite: Option<String>,
+    /// Whether the link is on the allowlist.
+    #[pyo3(get)]
+    allowed: bool,
+    /// The allowed domain this one looks like without being it, like `dіscord.com` for `discord.com`.
+    #[pyo3(get)]
+    lookalike: Option<String>,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Link {
+    fn __repr__(&self) -> String {
+        format!("Link(start={}, end={}, url={:?}, allowed={})", self.start, self.end, self.url, if self.allowed { "True" } else { "False" })
+    }
+}
+
+/// The domains and invite codes links are allowed to.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Allowlist {
+    /// Each domain in punycode, as it's shown, and its skeleton.
+    domains: Vec<(String, String, String)>,
+    invites: Vec<String>,
+}
+
+impl Allowlist {
+    /// Entries with a dot are domains, which allow their subdomains too, and the rest are invite codes.
+    fn new(entries: &[String]) -> Result<Self, String> {
+        let mut allowlist = Allowlist::default();
+        for entry in entries {
+            let trimmed = entry.trim();
+            if trimmed.contains('.') {
+                let (host, display) = host(trimmed).ok_or_else(|| format!("Invalid allowlist entry {entry:?}, it isn't a domain"))?;
+                let skeleton = lookalike_key(&display);
+                allowlist.domains.push((host, display, skeleton));
+            } else if is_code(trimmed) {
+                allowlist.invites.push(trimmed.to_string());
+            } else {
+                return Err(format!("Invalid allowlist entry {entry:?}, it isn't a domain or an invite code"));
+            }
+        }
+        Ok(allowlist)
+    }
+
+    fn allows_host(&self, host: &str) -> bool {
+        self.domains.iter().any(|(domain, _, _)| host == domain || host.strip_suffix(domain.as_str()).is_some_and(|sub| sub.ends_with('.')))
+    }
+
+    fn lookalike(&self, display: &str) -> Option<String> {
+        let key = lookalike_key(display);
+        self.domains
+            .iter()
+            .find(|(_, _, skeleton)| key == *skeleton || key.strip_suffix(skeleton.as_str()).is_some_and(|sub| sub.ends_with('.')))
+            .map(|(_, display, _)| display.clone())
+    }
+}
+
+/// What a domain looks like, so look alike domains have the same key.
+fn lookalike_key(display: &str) -> String {
+    skeleton(display).to_lowercase()
+}
+
+fn is_code(code: &str) -> bool {
+    (2..=32).contains(&code.len()) && code.chars().all(|letter| letter.is_ascii_alphanumeric() || letter == '-')
+}
+
+/// `domain` lowercase in punycode and as it's shown, or None if it isn't a valid domain.
+fn host(domain: &str) -> Option<(String, String)> {
+    let (mut host, mut display) = (Vec::new(), Vec::new());
+    for label in domain.trim_end_matches('.').to_lowercase().split('.') {
+        if label.is_empty() || !label.chars().all(|letter| letter.is_alphanumeric() || letter == '-') {
+            return None;
+        }
+        let (ascii, shown) = if label.is_ascii() {
+            // a label that's already punycode is shown as what it spells, or as it is if it doesn't spell anything
+            let shown = label.strip_prefix("xn--").and_then(punycode::decode).unwrap_or_else(|| label.to_string());
+            (label.to_string(), shown)
+        } else {
+            (format!("xn--{}", punycode::encode(label)?), label.to_string())
+        };
+        if ascii.len() > 63 {
+            return None;
+        }
+        host.push(ascii);
+        display.push(shown);
+    }
+    let host = host.join(".");
+    (host.len() <= 253).then(|| (host, display.join(".")))
+}
+
+/// A link in the message, with indices into its characters after it's deobfuscated.
+struct Found {
+    end: usize,
+    scheme: String,
+    host: String,
+    /// The port and path, as they were typed.
+    rest: String,
+}
+
+fn starts_with(chars: &[char], index: usize, pattern: &str) -> bool {
+    let mut letters = chars.iter().skip(index);
+    pattern.chars().all(|expected| letters.next().is_some_and(|letter| letter.to_ascii_lowercase() == expected))
+}
+
+fn skip_spaces(chars: &[char], mut index: usize) -> usize {
+    while chars.get(index).is_some_and(|letter| letter.is_whitespace()) {
+        index += 1;
+    }
+    index
+}
+
+/// The end of a dot written like `(dot)` or `[.]` starting at `index`, with the spaces around it.
+fn bracketed_dot(chars: &[char], index: usize) -> Option<usize> {
+    let start = skip_spaces(chars, index);
+    let close = match chars.get(start)? {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        '<' => '>',
+        _ => return None,
+    };
+    let mut end = skip_spaces(chars, start + 1);
+    if chars.get(end) == Some(&'.') {
+        end += 1;
+    } else if starts_with(chars, end, "dot") {
+        end += 3;
+    } else {
+        return None;
+    }
+    end = skip_spaces(chars, end);
+    (chars.get(end) == Some(&close)).then(|| skip_spaces(chars, end + 1))
+}
+
+/// The characters of `text` with obfuscated dots turned back into dots and invisible characters dropped,
+/// each with the index it came from.
+fn deobfuscate(text: &[char]) -> Vec<(char, usize)> {
+    let mut deobfuscated = Vec::with_capacity(text.len());
+    let mut index = 0;
+    while index < text.len() {
+        if let Some(end) = bracketed_dot(text, index) {
+            deobfuscated.push(('.', index));
+            index = end;
+            continue;
+        }
+        match text[index] {
+            letter if is_invisible(letter) => {}
+            // the full stops domains can be written with, like the ideographic one
+            '\u{3002}' | '\u{ff0e}' | '\u{ff61}' => deobfuscated.push(('.', index)),
+            letter => deobfuscated.push((letter, index)),
+        }
+        index += 1;
+    }
+    deobfuscated
+}
+
+fn is_host_char(letter: char) -> bool {
+    letter.is_alphanumeric() || letter == '-' || letter == '.'
+}
+
+/// The end of the domain starting at `index`, without a dot at the end.
+fn host_end(chars: &[char], index: usize) -> usize {
+    let mut end = index;
+    while chars.get(end).copied().is_some_and(is_host_char) {
+        end += 1;
+    }
+    while end > index && chars[end - 1] == '.' {
+        end -= 1;
+    }
+    end
+}
+
+/// The end of the port and path starting at `index`, without the punctuation at the end of a sentence.
+fn rest_end(chars: &[char], index: usize) -> usize {
+    let mut end = index;
+    if chars.get(end) == Some(&':') && chars.get(end + 1).is_some_and(char::is_ascii_digit) {
+        end += 1;
+        while chars.get(end).is_some_and(char::is_ascii_digit) {
+            end += 1;
+        }
+    }
+    if !chars.get(end).is_some_and(|letter| matches!(letter, '/' | '?' | '#')) {
+        return end;
+    }
+    let start = end;
+    while chars.get(end).is_some_and(|letter| !letter.is_whitespace() && !TERMINATORS.contains(letter)) {
+        end += 1;
+    }
+    loop {
+        let count = |letter: char| chars[start..end].iter().filter(|candidate| **candidate == letter).count();
+        match chars[end - 1] {
+            '.' | ',' | '!' | '?' | ';' | ':' | '\'' | '"' | '*' | '_' | '~' => end -= 1,
+            // a bracket at the end is only part of the link if it closes one in it, like on wikipedia
+            ')' if count(')') > count('(') => end -= 1,
+            ']' if count(']') > count('[') => end -= 1,
+            _ => break,
+        }
+        if end == start {
+            break;
+        }
+    }
+    end
+}
+
+/// A link with a scheme starting at `index`, like `https://discord.gg/abc`.
+fn with_scheme(chars: &[char], index: usize) -> Option<Found> {
+    let scheme = ["https", "http"].into_iter().find(|scheme| starts_with(chars, index, &format!("{scheme}://")))?;
+    let mut start = index + scheme.len() + 3;
+    // anything before an @ is a user name, so https://discord.com@evil.com goes to evil.com
+    let mut authority = start;
+    while chars.get(authority).is_some_and(|letter| !letter.is_whitespace() && !matches!(letter, '/' | '?' | '#') && !TERMINATORS.contains(letter)) {
+        if chars[authority] == '@' {
+            start = authority + 1;
+        }
+        authority += 1;
+    }
+    let end = host_end(chars, start);
+    (end > start).then(|| Found {
+        end: rest_end(chars, end),
+        scheme: scheme.to_string(),
+        host: chars[start..end].iter().collect(),
+        rest: chars[end..rest_end(chars, end)].iter().collect(),
+    })
+}
+
+/// A link without a scheme starting at `index`, like `discord.gg/abc`, if it ends with a known top level domain.
+fn without_scheme(chars: &[char], index: usize) -> Option<Found> {
+    let end = host_end(chars, index);
+    let domain = chars[index..end].iter().collect::<String>().to_lowercase();
+    let (_, tld) = domain.rsplit_once('.')?;
+    if !TLDS.contains(&tld) && !tld.starts_with("xn--") {
+        return None;
+    }
+    let rest = rest_end(chars, end);
+    Some(Found { end: rest, scheme: "https".to_string(), host: domain, rest: chars[end..rest].iter().collect() })
+}
+
+/// An invite with spaces or a spelled out dot starting at `index`, like `discord dot gg / abc`.
+fn spaced_invite(chars: &[char], index: usize) -> Option<Found> {
+    if !starts_with(chars, index, "discord") {
+        return None;
+    }
+    let mut end = skip_spaces(chars, index + 7);
+    if chars.get(end) == Some(&'.') {
+        end += 1;
+    } else if starts_with(chars, end, "dot") {
+        end += 3;
+    } else {
+        return None;
+    }
+    end = skip_spaces(chars, end);
+    if !starts_with(chars, end, "gg") {
+        return None;
+    }
+    end = skip_spaces(chars, end + 2);
+    if chars.get(end) != Some(&'/') {
+        return None;
+    }
+    let start = skip_spaces(chars, end + 1);
+    end = start;
+    while chars.get(end).is_some_and(|letter| letter.is_ascii_alphanumeric() || *letter == '-') {
+        end += 1;
+    }
+    let code = chars[start..end].iter().collect::<String>();
+    is_code(&code).then(|| Found { end, scheme: "https".to_string(), host: "discord.gg".to_string(), rest: format!("/{code}") })
+}
+
+/// The code of the invite at `host` and `rest`, if it is one.
+fn invite_code(host: &str, rest: &str) -> Option<String> {
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    let (_, prefix) = INVITE_HOSTS.iter().find(|(invite_host, _)| *invite_host == host)?;
+    let path = rest.strip_prefix('/')?;
+    let path = path.strip_prefix(prefix).or_else(|| path.strip_prefix("invite/"))?;
+    let code = path.split(['/', '?', '#']).next()?;
+    is_code(code).then(|| code.to_string())
+}
+
+fn scan_text(text: &str, allowlist: &Allowlist) -> Vec<Link> {
+    let original = text.chars().collect::<Vec<_>>();
+    let (chars, origins): (Vec<char>, Vec<usize>) = deobfuscate(&original).into_iter().unzip();
+    let mut links = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        // links start at the start of a word, and not in an email address or the middle of a domain
+        let boundary = index == 0 || !(chars[index - 1].is_alphanumeric() || matches!(chars[index - 1], '@' | '.' | '-' | '/' | '_'));
+        let found = boundary.then(|| with_scheme(&chars, index).or_else(|| without_scheme(&chars, index)).or_else(|| spaced_invite(&chars, index)));
+        let Some(found) = found.flatten() else {
+            index += 1;
+            continue;
+        };
+        if let Some((host, display)) = host(&found.host) {
+            let invite = invite_code(&host, &found.rest);
+            let allowed = match &invite {
+                // allowing discord.gg doesn't allow every server's invites, only the codes on the list are
+                Some(code) => allowlist.invites.contains(code),
+                None => allowlist.allows_host(&host),
+            };
+            let lookalike = if allowlist.allows_host(&host) { None } else { allowlist.lookalike(&display) };
+            let (start, end) = (origins[index], origins[found.end - 1] + 1);
+            links.push(Link {
+                start,
+                end,
+                text: original[start..end].iter().collect(),
+                url: format!("{}://{host}{}", found.scheme, found.rest),
+                host,
+                display,
+                invite,
+                allowed,
+                lookalike,
+            });
+        }
+        index = found.end;
+    }
+    links
+}
+
+#[pyfunction]
+#[pyo3(signature = (text, allowlist=None), text_signature = "
+scan(text, allowlist=None, /)
+--
+
+Find the links and Discord invites in a message, and check them against an allowlist.
+
+Obfuscated links are found too, like 'discord(dot)gg/abc', 'discord . gg / abc', or ones with
+invisible characters in them. Domains that aren't ASCII are turned into punycode, and a domain that
+looks like an allowed one without being it, like 'dіscord.com' with a Cyrillic і, is reported as a lookalike.
+
+Parameters
+----------
+text : str
+    The message.
+allowlist : list[str] | None
+    The domains links are allowed to, which allow their subdomains too, and the invite codes that are
+    allowed. An invite is only allowed if its code is, so allowing 'discord.gg' doesn't allow every
+    server's invites. The default is None, for nothing.
+
+Returns
+-------
+list[Link]
+    The links in the order they're in, with whether each is allowed.
+
+Raises
+------
+ValueError
+    If an entry in the allowlist isn't a domain or an invite code.
+")]
+pub fn scan(text: &str, allowlist: Option<Vec<String>>) -> PyResult<Vec<Link>> {
+    metrics::observe("moderation.scan", || {
+        let allowlist = Allowlist::new(&allowlist.unwrap_or_default()).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(scan_text(text, &allowlist))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (messages, allowlist=None), text_signature = "
+scan_many(messages, allowlist=None, /)
+--
+
+Find the links and Discord invites in many messages at once, like ``scan`` does for one, split across
+threads without holding the GIL.
+
+Parameters
+----------
+messages : list[str]
+    The messages.
+allowlist : list[str] | None
+    The domains and invite codes that are allowed, as for ``scan``. The default is None, for nothing.
+
+Returns
+-------
+list[list[Link]]
+    The links in each message, in the order of the messages.
+
+Raises
+------
+ValueError
+    If an entry in the allowlist isn't a domain or an invite code.
+")]
+pub fn scan_many(py: Python, messages: Vec<String>, allowlist: Option<Vec<String>>) -> PyResult<Vec<Vec<Link>>> {
+    metrics::observe("moderation.scan_many", || {
+        let allowlist = Allowlist::new(&allowlist.unwrap_or_default()).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(py.allow_threads(|| tasks::parallel_map(messages, |text| scan_text(&text, &allowlist))))
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn allowlist(entries: &[&str]) -> Allowlist {
+        Allowlist::new(&entries.iter().map(ToString::to_string).collect::<Vec<_>>()).expect("Expected a valid allowlist")
+    }
+
+    fn urls(text: &str) -> Vec<String> {
+        scan_text(text, &Allowlist::default()).into_iter().map(|link| link.url).collect()
+    }
+
+    #[parameterized(
+        plain = {"see https://example.com/page for more", vec!["https://example.com/page"]},
+        bare = {"go to Example.COM", vec!["https://example.com"]},
+        sentence = {"try https://example.com/a/b.", vec!["https://example.com/a/b"]},
+        brackets = {"(https://en.wikipedia.org/wiki/Rust_(programming_language))", vec!["https://en.wikipedia.org/wiki/Rust_(programming_language)"]},
+        embed_suppressed = {"<https://example.com/x>", vec!["https://example.com/x"]},
+        port = {"http://localhost:8080/status", vec!["http://localhost:8080/status"]},
+        user_name = {"https://discord.com@evil.xyz/login", vec!["https://evil.xyz/login"]},
+        several = {"a.com and b.org", vec!["https://a.com", "https://b.org"]},
+        files = {"run script.py or e.g. this", vec![]},
+        email = {"mail me@example.com", vec![]},
+        numbers = {"version 1.5.2", vec![]},
+        bracketed_dot = {"discord(dot)gg/abc", vec!["https://discord.gg/abc"]},
+        spaced_dot = {"example [ . ] com", vec!["https://example.com"]},
+        spaced_invite = {"discord . gg / abcd", vec!["https://discord.gg/abcd"]},
+        spelled_invite = {"join discord dot gg/abcd", vec!["https://discord.gg/abcd"]},
+        invisible = {"disc\u{200b}ord.gg/abc", vec!["https://discord.gg/abc"]},
+        ideographic = {"example。com", vec!["https://example.com"]},
+        idn = {"https://bücher.de/", vec!["https://xn--bcher-kva.de/"]},
+    )]
+    fn finding(text: &str, expected: Vec<&str>) {
+        assert_eq!(urls(text), expected);
+    }
+
+    #[test]
+    fn spans() {
+        let link = &scan_text("hi discord(dot)gg/abc!", &Allowlist::default())[0];
+        assert_eq!((link.start, link.end, link.text.as_str()), (3, 21, "discord(dot)gg/abc"));
+        let link = &scan_text("é https://ü.com", &Allowlist::default())[0];
+        assert_eq!((link.start, link.end, link.display.as_str()), (2, 15, "ü.com"));
+    }
+
+    #[parameterized(
+        short = {"discord.gg/abc", Some("abc")},
+        full = {"https://discord.com/invite/Ab-12", Some("Ab-12")},
+        app = {"discordapp.com/invite/xyz", Some("xyz")},
+        www = {"https://www.discord.com/invite/xyz?event=1", Some("xyz")},
+        channel = {"https://discord.com/channels/1/2", None},
+        not_discord = {"https://example.com/invite/xyz", None},
+    )]
+    fn invites(text: &str, code: Option<&str>) {
+        assert_eq!(scan_text(text, &Allowlist::default())[0].invite.as_deref(), code);
+    }
+
+    #[test]
+    fn allowing() {
+        let allowed = allowlist(&["example.com", "discord.com", "charlie"]);
+        let scan = |text: &str| scan_text(text, &allowed).into_iter().map(|link| link.allowed).collect::<Vec<_>>();
+        assert_eq!(scan("example.com sub.example.com notexample.com example.co"), vec![true, true, false, false]);
+        assert_eq!(scan("discord.gg/charlie discord.gg/other https://discord.com/channels/1"), vec![true, false, true]);
+        assert!(scan("").is_empty());
+    }
+
+    #[test]
+    fn lookalikes() {
+        let allowed = allowlist(&["discord.com", "paypal.com"]);
+        let links = scan_text("https://dіscord.com/gift https://xn--pypal-4ve.com http://paypa1.com discord.com", &allowed);
+        let lookalikes = links.iter().map(|link| (link.allowed, link.lookalike.as_deref())).collect::<Vec<_>>();
+        assert_eq!(lookalikes, vec![(false, Some("discord.com")), (false, Some("paypal.com")), (false, Some("paypal.com")), (true, None)]);
+        assert_eq!(links[0].host, format!("xn--{}.com", punycode::encode("dіscord").expect("Expected an encoding")));
+        assert_eq!(links[1].display, "pаypal.com");
+        assert_eq!(scan_text("https://login.dіscord.com", &allowed)[0].lookalike.as_deref(), Some("discord.com"));
+    }
+
+    #[test]
+    fn rejects() {
+        Allowlist::new(&["not a domain".to_string()]).expect_err("Expected spaces to be rejected");
+        Allowlist::new(&["a..com".to_string()]).expect_err("Expected an empty label to be rejected");
+        Allowlist::new(&["x".to_string()]).expect_err("Expected a short code to be rejected");
+        assert_eq!(allowlist(&["Bücher.DE"]).domains[0].0, "xn--bcher-kva.de");
+    }
+}
+// COV_EXCL_STOP
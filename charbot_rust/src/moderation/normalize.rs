@@ -0,0 +1,257 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use crate::{assets, metrics};
+use crate::moderation::fold::{is_combining, is_invisible};
+// COV_EXCL_STOP
+
+/// The most combining marks kept on a letter, enough for the accents of any language, like Vietnamese.
+const MAX_MARKS: usize = 2;
+
+/// Why a character is in the table.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+    /// A letter with diacritics, which is normal in names.
+    Accent,
+    /// A styled form, like full width or mathematical letters.
+    Compatibility,
+    /// A letter from another script, or punctuation, that looks like an ASCII one.
+    Lookalike,
+    /// ASCII that looks like other ASCII, like `1` and `l`.
+    Ascii,
+}
+
+static TABLE: Lazy<HashMap<char, (String, Kind)>> = Lazy::new(|| {
+    let code_point = |hex: &str| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32).expect("The table only has valid code points");
+    assets::static_text("moderation/confusables/confusables.txt")
+        .expect("The confusables are an asset")
+        .lines()
+        .filter(|line| !line.starts_with('#') && !line.trim().is_empty())
+        .map(|line| {
+            let fields = line.split(" ; ").collect::<Vec<_>>();
+            let kind = match fields[2] {
+                "accent" => Kind::Accent,
+                "compatibility" => Kind::Compatibility,
+                "lookalike" => Kind::Lookalike,
+                _ => Kind::Ascii,
+            };
+            (code_point(fields[0]), (fields[1].split(' ').map(code_point).collect(), kind))
+        })
+        .collect()
+});
+
+/// The ASCII `letter` passes for, itself if it isn't in the table.
+fn ascii(letter: char) -> Option<&'static str> {
+    TABLE.get(&letter).map(|(target, _)| target.as_str())
+}
+
+/// The skeleton of `text`, what it looks like as ASCII, so two texts that look alike have the same skeleton.
+///
+/// Like UTS #39, the skeleton is for comparing and not for showing, since `1`, `I` and `l` are all `l`.
+/// Unlike it, combining marks are dropped, so accented letters have the skeleton of the plain ones.
+pub fn skeleton(text: &str) -> String {
+    let mut skeleton = String::with_capacity(text.len());
+    for letter in text.chars().filter(|letter| !is_invisible(*letter) && !is_combining(*letter)) {
+        match ascii(letter) {
+            // what styled letters decompose to can be ASCII that looks like other ASCII, like Ⅰ to I
+            Some(target) => skeleton.extend(target.chars().flat_map(|letter| ascii(letter).map_or_else(|| vec![letter], |target| target.chars().collect()))),
+            None => skeleton.push(letter),
+        }
+    }
+    skeleton
+}
+
+// COV_EXCL_START
+#[pyclass(module = "moderation")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalized {
+    /// The text without invisible characters, and without more than two combining marks on a letter.
+    #[pyo3(get)]
+    cleaned: String,
+    /// What the text looks like as ASCII, for comparing names.
+    #[pyo3(get)]
+    skeleton: String,
+    /// The fraction of the characters that were stripped or are styled or from another script to look like ASCII, from 0 to 1.
+    #[pyo3(get)]
+    score: f64,
+    /// How many invisible characters were stripped, like zero width spaces.
+    #[pyo3(get)]
+    invisible: usize,
+    /// How many stacked combining marks were stripped, like zalgo text.
+    #[pyo3(get)]
+    marks: usize,
+    /// How many characters are styled letters or from another script to look like ASCII.
+    #[pyo3(get)]
+    lookalikes: usize,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Normalized {
+    fn __repr__(&self) -> String {
+        format!("Normalized(cleaned={:?}, skeleton={:?}, score={:.3})", self.cleaned, self.skeleton, self.score)
+    }
+}
+
+fn normalized(text: &str) -> Normalized {
+    let (mut cleaned, mut invisible, mut marks, mut lookalikes, mut stacked) = (String::with_capacity(text.len()), 0, 0, 0, 0);
+    for letter in text.chars() {
+        if is_invisible(letter) {
+            invisible += 1;
+        } else if is_combining(letter) {
+            stacked += 1;
+            if stacked > MAX_MARKS {
+                marks += 1;
+            } else {
+                cleaned.push(letter);
+            }
+        } else {
+            stacked = 0;
+            lookalikes += usize::from(TABLE.get(&letter).is_some_and(|(_, kind)| matches!(kind, Kind::Compatibility | Kind::Lookalike)));
+            cleaned.push(letter);
+        }
+    }
+    let total = text.chars().count();
+    let score = if total == 0 { 0.0 } else { (invisible + marks + lookalikes) as f64 / total as f64 };
+    Normalized { skeleton: skeleton(&cleaned), cleaned, score, invisible, marks, lookalikes }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+normalize(text, /)
+--
+
+Clean up a name or message, and get what it looks like as ASCII and how odd it is.
+
+Invisible characters are stripped, and combining marks past the second on a letter, which is zalgo
+text. The skeleton maps characters to the ASCII they look like, like the skeletons of UTS #39, such
+as Cyrillic letters, full width and mathematical letters, and accents, so look alike names can be compared.
+
+Parameters
+----------
+text : str
+    The text.
+
+Returns
+-------
+Normalized
+    The cleaned text, its skeleton, and a weirdness score from 0 to 1 of how much of it was stripped
+    or styled to look like ASCII, with what went into it.
+")]
+pub fn normalize(text: &str) -> Normalized {
+    metrics::timed("moderation.normalize", || normalized(text))
+}
+
+#[pyfunction]
+#[pyo3(name = "skeleton", text_signature = "
+skeleton(text, /)
+--
+
+Get what text looks like as ASCII, for comparing names, like UTS #39 but also dropping accents.
+
+Parameters
+----------
+text : str
+    The text.
+
+Returns
+-------
+str
+    The skeleton, which is for comparing and not for showing, since '1', 'I' and 'l' are all 'l'.
+")]
+pub fn py_skeleton(text: &str) -> String {
+    metrics::timed("moderation.skeleton", || skeleton(text))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+is_confusable(first, second, /)
+--
+
+Check if two names look alike, like a user impersonating a moderator.
+
+Parameters
+----------
+first : str
+    The first name.
+second : str
+    The second name.
+
+Returns
+-------
+bool
+    True if their skeletons are the same, ignoring case, False otherwise.
+")]
+pub fn is_confusable(first: &str, second: &str) -> bool {
+    metrics::timed("moderation.is_confusable", || {
+        // an uppercase I passes for an l, but a lowercase i doesn't, so names are compared both in the case they're in and lowercase
+        skeleton(first).to_lowercase() == skeleton(second).to_lowercase() || skeleton(&first.to_lowercase()) == skeleton(&second.to_lowercase())
+    })
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[test]
+    fn table() {
+        assert!(TABLE.len() > 2000);
+        assert!(TABLE.values().all(|(target, _)| !target.is_empty() && target.chars().all(|letter| letter.is_ascii_graphic())));
+        assert_eq!(TABLE.get(&'а'), Some(&("a".to_string(), Kind::Lookalike)));
+        assert_eq!(TABLE.get(&'é'), Some(&("e".to_string(), Kind::Accent)));
+    }
+
+    #[parameterized(
+        cyrillic = {"Ρаураl", "Paypal"},
+        math = {"𝐁𝐨𝐭", "Bot"},
+        full_width = {"ＣｈａｒＢ０Ｔ", "CharBOT"},
+        ligature = {"ﬁne", "fine"},
+        accents = {"Crème brûlée", "Creme brulee"},
+        digits = {"B0T 1", "BOT l"},
+        roman = {"Ⅰ", "l"},
+        invisible = {"a\u{200b}dmin", "admin"},
+        other_scripts = {"日本", "日本"},
+    )]
+    fn skeletons(text: &str, expected: &str) {
+        assert_eq!(skeleton(text), expected);
+    }
+
+    #[test]
+    fn zalgo() {
+        let zalgo = "h\u{300}\u{301}\u{302}\u{303}\u{304}i";
+        let cleaned = normalized(zalgo);
+        assert_eq!(cleaned.cleaned, "h\u{300}\u{301}i");
+        assert_eq!((cleaned.marks, cleaned.skeleton.as_str()), (3, "hi"));
+        // vietnamese stacks two marks, which is fine
+        let vietnamese = normalized("Nguyê\u{303}n");
+        assert_eq!((vietnamese.marks, vietnamese.score), (0, 0.0));
+    }
+
+    #[test]
+    fn weirdness() {
+        assert_eq!(normalized("Charlie").score, 0.0);
+        assert_eq!(normalized("").score, 0.0);
+        assert_eq!(normalized("José").score, 0.0);
+        let styled = normalized("𝓒𝓱𝓪𝓻𝓵𝓲𝓮");
+        assert_eq!((styled.lookalikes, styled.score, styled.skeleton.as_str()), (7, 1.0, "Charlie"));
+        let sneaky = normalized("Ch\u{200b}аrlie");
+        assert_eq!((sneaky.invisible, sneaky.lookalikes), (1, 1));
+        assert!((sneaky.score - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn confusables() {
+        assert!(is_confusable("Moderator", "ΜοdеrаτοR".replace('τ', "t").as_str()));
+        assert!(is_confusable("Bluesy", "bIuesy"));
+        assert!(is_confusable("admin", "ＡＤＭＩＮ"));
+        assert!(!is_confusable("admin", "admins"));
+    }
+}
+// COV_EXCL_STOP
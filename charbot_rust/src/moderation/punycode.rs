@@ -0,0 +1,152 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Punycode from RFC 3492, which is how domains that aren't ASCII are spelled in ASCII, after `xn--`.
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 128;
+
+fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / DAMP } else { delta / 2 };
+    delta += delta / points;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+/// The threshold for the digit at `k`, clamped to the range the bias allows.
+fn threshold(k: u32, bias: u32) -> u32 {
+    k.saturating_sub(bias).clamp(T_MIN, T_MAX)
+}
+
+fn digit(value: u32) -> char {
+    // a to z are 0 to 25, and 0 to 9 are 26 to 35
+    char::from(if value < 26 { b'a' + value as u8 } else { b'0' + (value - 26) as u8 })
+}
+
+fn value(digit: u8) -> Option<u32> {
+    match digit {
+        b'a'..=b'z' => Some(u32::from(digit - b'a')),
+        b'A'..=b'Z' => Some(u32::from(digit - b'A')),
+        b'0'..=b'9' => Some(u32::from(digit - b'0') + 26),
+        _ => None,
+    }
+}
+
+/// `label` in punycode, without the `xn--`, or None if it's too long to encode.
+pub fn encode(label: &str) -> Option<String> {
+    let points = label.chars().map(u32::from).collect::<Vec<_>>();
+    let mut output = label.chars().filter(char::is_ascii).collect::<String>();
+    let basic = output.len() as u32;
+    let mut handled = basic;
+    if basic > 0 {
+        output.push('-');
+    }
+    let (mut n, mut delta, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+    while (handled as usize) < points.len() {
+        let next = points.iter().copied().filter(|point| *point >= n).min()?;
+        delta = delta.checked_add((next - n).checked_mul(handled + 1)?)?;
+        n = next;
+        for &point in &points {
+            if point < n {
+                delta = delta.checked_add(1)?;
+            } else if point == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(digit(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(digit(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1)?;
+        n += 1;
+    }
+    Some(output)
+}
+
+/// The label `encoded` spells in punycode, without the `xn--`, or None if it isn't valid punycode.
+pub fn decode(encoded: &str) -> Option<String> {
+    let (basic, digits) = encoded.rfind('-').map_or(("", encoded), |index| (&encoded[..index], &encoded[index + 1..]));
+    if !basic.is_ascii() || !digits.is_ascii() {
+        return None;
+    }
+    let mut output = basic.chars().collect::<Vec<_>>();
+    let (mut n, mut i, mut bias) = (INITIAL_N, 0u32, INITIAL_BIAS);
+    let mut digits = digits.bytes();
+    while digits.len() > 0 {
+        let old = i;
+        let (mut weight, mut k) = (1u32, BASE);
+        loop {
+            let value = value(digits.next()?)?;
+            i = i.checked_add(value.checked_mul(weight)?)?;
+            let t = threshold(k, bias);
+            if value < t {
+                break;
+            }
+            weight = weight.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - old, length, old == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        german = {"bücher", "bcher-kva"},
+        city = {"münchen", "mnchen-3ya"},
+        japanese = {"テスト", "zckzah"},
+        ascii = {"plain", "plain-"},
+    )]
+    fn round_trips(label: &str, encoded: &str) {
+        assert_eq!(encode(label).as_deref(), Some(encoded));
+        assert_eq!(decode(encoded).as_deref(), Some(label));
+    }
+
+    #[test]
+    fn lookalikes() {
+        // a cyrillic а in an otherwise latin name
+        let encoded = encode("pаypal").expect("Expected an encoding");
+        assert_ne!(encoded, "paypal");
+        assert_eq!(decode(&encoded).as_deref(), Some("pаypal"));
+        for label in ["dіscord", "ñandú", "日本語", "😀"] {
+            assert_eq!(decode(&encode(label).expect("Expected an encoding")).as_deref(), Some(label));
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(decode("ü-abc"), None);
+        assert_eq!(decode("abc!"), None);
+        assert_eq!(decode("99999999999"), None);
+    }
+}
+// COV_EXCL_STOP
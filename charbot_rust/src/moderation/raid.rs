@@ -0,0 +1,342 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::collections::VecDeque;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::moderation::normalize::skeleton;
+// COV_EXCL_STOP
+
+/// The most joins a window can hold, so a huge raid can't make every join compare thousands of names.
+const MAX_JOINS: usize = 500;
+/// How many seconds the usual join rate is averaged over, long enough that a raid barely moves it.
+const HORIZON: f64 = 6.0 * 3600.0;
+/// How close together accounts have to be created to count as made in one batch.
+const BATCH: f64 = 3600.0;
+/// How alike two names have to be, from 0 to 1, to count as made from one pattern.
+const SIMILAR_NAMES: f64 = 0.7;
+
+/// A member joining.
+#[derive(Clone, Debug, PartialEq)]
+struct Join {
+    user: u64,
+    created_at: f64,
+    joined_at: f64,
+    /// The name as it's compared, with look alike letters as ASCII and runs of digits as one `#`.
+    pattern: String,
+}
+
+/// How likely the joins around one are a raid.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Alert {
+    /// How likely it is that there's a raid, from 0 to 1.
+    #[pyo3(get)]
+    confidence: f64,
+    /// Whether the confidence reaches the threshold.
+    #[pyo3(get)]
+    flagged: bool,
+    /// The users that look like part of the raid if it's flagged, in the order they joined, otherwise none.
+    #[pyo3(get)]
+    users: Vec<u64>,
+    /// How many joins there were in the window.
+    #[pyo3(get)]
+    joins: usize,
+    /// How unlikely that many joins are at the usual rate, from 0 to 1.
+    #[pyo3(get)]
+    rate_score: f64,
+    /// How much of the window is new accounts, or accounts made in one batch, from 0 to 1.
+    #[pyo3(get)]
+    age_score: f64,
+    /// How much of the window has names made from one pattern, from 0 to 1.
+    #[pyo3(get)]
+    name_score: f64,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Alert {
+    fn __repr__(&self) -> String {
+        format!("Alert(confidence={:.3}, flagged={}, joins={}, users={})", self.confidence, if self.flagged { "True" } else { "False" }, self.joins, self.users.len())
+    }
+}
+
+/// The name as it's compared, so `Raider_123` and `Rаider_9876` with a Cyrillic а have the same pattern.
+fn pattern(username: &str) -> String {
+    // digits go first, since the skeleton would turn 0 and 1 into letters, and case, since it turns I into l
+    let mut digits = String::new();
+    for letter in username.to_lowercase().chars() {
+        let letter = if letter.is_ascii_digit() { '#' } else { letter };
+        if letter != '#' || !digits.ends_with('#') {
+            digits.push(letter);
+        }
+    }
+    skeleton(&digits).to_lowercase()
+}
+
+/// How alike two name patterns are from 0 to 1, by the pairs of letters they share.
+fn similarity(first: &str, second: &str) -> f64 {
+    let pairs = |text: &str| {
+        let letters = text.chars().collect::<Vec<_>>();
+        letters.windows(2).map(|pair| (pair[0], pair[1])).collect::<Vec<_>>()
+    };
+    let (first_pairs, mut second_pairs) = (pairs(first), pairs(second));
+    if first_pairs.is_empty() || second_pairs.is_empty() {
+        return if first == second { 1.0 } else { 0.0 };
+    }
+    let total = first_pairs.len() + second_pairs.len();
+    let mut shared = 0;
+    for pair in first_pairs {
+        if let Some(index) = second_pairs.iter().position(|other| *other == pair) {
+            second_pairs.swap_remove(index);
+            shared += 1;
+        }
+    }
+    2.0 * f64::from(shared) / total as f64
+}
+
+/// The complementary error function, from Abramowitz and Stegun 7.1.26, good to about 1e-7.
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let value = poly * (-x * x).exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// How likely at least `count` joins are in a window expecting `expected`, from a Poisson distribution.
+fn tail(count: usize, expected: f64) -> f64 {
+    if count == 0 {
+        return 1.0;
+    }
+    if expected > 100.0 {
+        // past where the terms underflow, the normal approximation is close enough
+        let z = (count as f64 - 0.5 - expected) / expected.sqrt();
+        return 0.5 * erfc(z / std::f64::consts::SQRT_2);
+    }
+    let (mut term, mut below) = ((-expected).exp(), 0.0);
+    for k in 0..count {
+        below += term;
+        term *= expected / (k + 1) as f64;
+    }
+    (1.0 - below).clamp(0.0, 1.0)
+}
+
+/// The recent joins to a guild, to spot a raid of accounts joining at once.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Detector {
+    window: f64,
+    min_joins: usize,
+    threshold: f64,
+    new_account_age: f64,
+    joins: VecDeque<Join>,
+    /// The usual joins a second, averaged over the horizon.
+    rate: f64,
+    updated: Option<f64>,
+}
+
+impl Detector {
+    fn new(window: f64, min_joins: usize, threshold: f64, baseline: f64, new_account_age: f64) -> Result<Self, String> {
+        if !window.is_finite() || window <= 0.0 {
+            return Err(format!("Invalid window {window}, it must be more than 0 seconds"));
+        }
+        if !(2..=MAX_JOINS).contains(&min_joins) {
+            return Err(format!("Invalid minimum of {min_joins} joins, it must be from 2 to {MAX_JOINS}"));
+        }
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(format!("Invalid threshold {threshold}, it must be from 0 to 1"));
+        }
+        if !baseline.is_finite() || baseline <= 0.0 {
+            return Err(format!("Invalid baseline of {baseline} joins an hour, it must be more than 0"));
+        }
+        if new_account_age.is_nan() || new_account_age < 0.0 {
+            return Err(format!("Invalid new account age {new_account_age}, it can't be negative"));
+        }
+        Ok(Detector { window, min_joins, threshold, new_account_age, joins: VecDeque::new(), rate: baseline / 3600.0, updated: None })
+    }
+
+    /// The largest share of the window in a group where `related` holds between every member and the first.
+    fn largest_group(&self, related: impl Fn(&Join, &Join) -> bool) -> Vec<bool> {
+        let mut best = Vec::new();
+        for join in &self.joins {
+            let group = self.joins.iter().map(|other| related(join, other)).collect::<Vec<_>>();
+            if group.iter().filter(|member| **member).count() > best.iter().filter(|member| **member).count() {
+                best = group;
+            }
+        }
+        best
+    }
+
+    /// Add a join, and check the joins in the window around it for a raid.
+    fn ingest(&mut self, user: u64, created_at: f64, joined_at: f64, username: &str) -> Alert {
+        // the usual rate decays towards how often members are joining, the raid scored before it counts
+        let elapsed = self.updated.map_or(0.0, |updated| (joined_at - updated).max(0.0));
+        let expected = self.rate * self.window;
+        self.rate = self.rate * (-elapsed / HORIZON).exp() + 1.0 / HORIZON;
+        self.updated = Some(self.updated.map_or(joined_at, |updated| updated.max(joined_at)));
+        self.joins.push_back(Join { user, created_at, joined_at, pattern: pattern(username) });
+        while self.joins.front().is_some_and(|join| join.joined_at <= joined_at - self.window) || self.joins.len() > MAX_JOINS {
+            self.joins.pop_front();
+        }
+        let count = self.joins.len();
+        let rate_score = if count < self.min_joins { 0.0 } else { 1.0 - tail(count, expected) };
+        let young = self.joins.iter().map(|join| join.joined_at - join.created_at < self.new_account_age).collect::<Vec<_>>();
+        let batch = self.largest_group(|first, other| (other.created_at - first.created_at).abs() <= BATCH / 2.0);
+        let names = self.largest_group(|first, other| similarity(&first.pattern, &other.pattern) >= SIMILAR_NAMES);
+        let share = |members: &[bool]| members.iter().filter(|member| **member).count() as f64 / count as f64;
+        // one account is always in a batch and a name pattern with itself, so groups of one don't count
+        let grouped = |members: &[bool]| if members.iter().filter(|member| **member).count() > 1 { share(members) } else { 0.0 };
+        let age_score = share(&young).max(grouped(&batch));
+        let name_score = grouped(&names);
+        // lots of joins alone could be the server being shared, so it takes odd accounts to be sure
+        let confidence = rate_score * (0.5 + 0.25 * age_score + 0.25 * name_score);
+        let flagged = count >= self.min_joins && confidence >= self.threshold;
+        let users = if flagged {
+            let suspicious = (0..count).filter(|index| young[*index] || batch[*index] && age_score > 0.0 || names[*index] && name_score > 0.0).collect::<Vec<_>>();
+            // a burst with nothing odd about any one account implicates all of it
+            let implicated = if suspicious.is_empty() { (0..count).collect() } else { suspicious };
+            implicated.into_iter().map(|index| self.joins[index].user).collect()
+        } else {
+            Vec::new()
+        };
+        Alert { confidence, flagged, users, joins: count, rate_score, age_score, name_score }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Detector {
+    #[new]
+    #[pyo3(signature = (window=60.0, min_joins=5, threshold=0.7, baseline=10.0, new_account_age=604800.0))]
+    fn py_new(window: f64, min_joins: usize, threshold: f64, baseline: f64, new_account_age: f64) -> PyResult<Self> {
+        Detector::new(window, min_joins, threshold, baseline, new_account_age).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "ingest")]
+    fn py_ingest(&mut self, user_id: u64, account_created_at: f64, join_time: f64, username: &str) -> Alert {
+        self.ingest(user_id, account_created_at, join_time, username)
+    }
+
+    /// Forget the joins in the window, like once a raid has been dealt with, keeping the usual rate.
+    fn reset(&mut self) {
+        self.joins.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.joins.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Detector(window={}, min_joins={}, threshold={}, joins={})", self.window, self.min_joins, self.threshold, self.joins.len())
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const DAY: f64 = 86400.0;
+    const NOW: f64 = 1_700_000_000.0;
+
+    fn detector() -> Detector {
+        Detector::new(60.0, 5, 0.7, 10.0, 7.0 * DAY).expect("Expected a valid detector")
+    }
+
+    #[parameterized(
+        numbers = {"raider123", "raider#"},
+        lookalikes = {"RΑIDER_0042", "raider_#"},
+        plain = {"Charlie", "charlie"},
+    )]
+    fn patterns(username: &str, expected: &str) {
+        assert_eq!(pattern(username), expected);
+    }
+
+    #[test]
+    fn similar_names() {
+        assert_eq!(similarity(&pattern("raider123"), &pattern("raider456")), 1.0);
+        assert!(similarity(&pattern("spam_bot_1"), &pattern("spam_bat_2")) >= SIMILAR_NAMES);
+        assert!(similarity(&pattern("alice"), &pattern("bob")) < 0.1);
+        assert_eq!(similarity("a", "a"), 1.0);
+        assert_eq!(similarity("a", "ab"), 0.0);
+    }
+
+    #[test]
+    fn poisson() {
+        assert_eq!(tail(0, 1.0), 1.0);
+        assert!((tail(1, 1.0) - (1.0 - (-1.0f64).exp())).abs() < 1e-12);
+        assert!(tail(10, 0.2) < 1e-9);
+        assert!(tail(1000, 1000.0) > 0.4 && tail(1000, 1000.0) < 0.6);
+        assert!(tail(1200, 1000.0) < 1e-6);
+    }
+
+    #[test]
+    fn raid_of_new_accounts() {
+        let mut detector = detector();
+        let mut alert = None;
+        for index in 0..8 {
+            let created = NOW - DAY + index as f64 * 60.0;
+            alert = Some(detector.ingest(index, created, NOW + index as f64 * 2.0, &format!("raider{}", index * 7)));
+        }
+        let alert = alert.expect("Expected an alert");
+        assert!(alert.flagged, "Expected a raid, got {alert:?}");
+        assert!(alert.confidence > 0.95);
+        assert_eq!(alert.users, (0..8).collect::<Vec<_>>());
+        assert_eq!((alert.joins, alert.age_score, alert.name_score), (8, 1.0, 1.0));
+    }
+
+    #[test]
+    fn busy_but_normal() {
+        let mut detector = detector();
+        let names = ["alice", "bob", "charlie", "dmitri", "esperanza", "fumiko", "gustavo", "hana"];
+        let mut alert = None;
+        for (index, name) in names.into_iter().enumerate() {
+            // old accounts made years apart
+            let created = NOW - (index as f64 + 1.0) * 400.0 * DAY;
+            alert = Some(detector.ingest(index as u64, created, NOW + index as f64 * 5.0, name));
+        }
+        let alert = alert.expect("Expected an alert");
+        assert!(alert.rate_score > 0.99);
+        assert!(!alert.flagged, "Expected no raid, got {alert:?}");
+        assert!(alert.users.is_empty());
+    }
+
+    #[test]
+    fn slow_trickle() {
+        let mut detector = detector();
+        for index in 0..20 {
+            // new accounts, but one every ten minutes is never a burst
+            let alert = detector.ingest(index, NOW - DAY, NOW + index as f64 * 600.0, &format!("raider{index}"));
+            assert!(!alert.flagged);
+            assert_eq!(alert.joins, 1);
+        }
+    }
+
+    #[test]
+    fn implicates_the_odd_ones() {
+        let mut detector = detector();
+        for index in 0..6 {
+            detector.ingest(index, NOW - 3600.0, NOW + index as f64, &format!("bot{index}"));
+        }
+        let alert = detector.ingest(100, NOW - 900.0 * DAY, NOW + 7.0, "longtime_member");
+        assert!(alert.flagged);
+        assert_eq!(alert.users, (0..6).collect::<Vec<_>>());
+        detector.reset();
+        assert_eq!(detector.joins.len(), 0);
+    }
+
+    #[test]
+    fn rejects() {
+        Detector::new(0.0, 5, 0.7, 10.0, DAY).expect_err("Expected a window of 0 to be rejected");
+        Detector::new(60.0, 1, 0.7, 10.0, DAY).expect_err("Expected a minimum of 1 join to be rejected");
+        Detector::new(60.0, 5, 1.5, 10.0, DAY).expect_err("Expected a threshold over 1 to be rejected");
+        Detector::new(60.0, 5, 0.7, 0.0, DAY).expect_err("Expected a baseline of 0 to be rejected");
+        Detector::new(60.0, 5, 0.7, 10.0, -1.0).expect_err("Expected a negative age to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,234 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use regex::{Regex, RegexBuilder};
+use crate::moderation::wordfilter::Match;
+// COV_EXCL_STOP
+
+/// The most characters a pattern can have.
+const MAX_PATTERN_LENGTH: usize = 1000;
+/// The most memory a pattern can compile to, which bounds how long matching takes for each character.
+const SIZE_LIMIT: usize = 256 * 1024;
+/// The most memory the lazy DFA of a pattern can use while matching, it falls back to slower matching after.
+const DFA_SIZE_LIMIT: usize = 1024 * 1024;
+/// How deep groups and classes can be nested.
+const MAX_NESTING: u32 = 64;
+
+/// The message of a rejected pattern, saying where in the pattern and why.
+fn error(e: regex::Error) -> String {
+    match e {
+        // the last line of a syntax error says what's wrong, the ones before it point to where
+        regex::Error::Syntax(message) => {
+            let reason = message.lines().last().unwrap_or_default().trim_start_matches("error: ");
+            let at = message.lines().rev().nth(1).and_then(|carets| carets.find('^')).map_or(0, |column| column.saturating_sub(4));
+            format!("Invalid pattern at character {at}: {reason}")
+        }
+        regex::Error::CompiledTooBig(limit) => format!("Invalid pattern, it's too big once compiled, the limit is {limit} bytes"),
+        e => format!("Invalid pattern: {e}"),
+    }
+}
+
+/// A pattern from a guild's automod rules, matched in time linear in the text, so no pattern can hang the bot.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug)] // COV_EXCL_LINE
+pub struct SafeRegex {
+    pattern: String,
+    regex: Regex,
+}
+
+impl SafeRegex {
+    fn new(pattern: &str, case_insensitive: bool) -> Result<Self, String> {
+        if pattern.chars().count() > MAX_PATTERN_LENGTH {
+            return Err(format!("Invalid pattern, it can have at most {MAX_PATTERN_LENGTH} characters"));
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .size_limit(SIZE_LIMIT)
+            .dfa_size_limit(DFA_SIZE_LIMIT)
+            .nest_limit(MAX_NESTING)
+            .build()
+            .map_err(error)?;
+        Ok(SafeRegex { pattern: pattern.to_string(), regex })
+    }
+
+    /// `text` found `start` characters in, as a match counted in characters like the word filter's.
+    fn to_match(&self, start: usize, text: &str) -> Match {
+        let end = start + text.chars().count();
+        Match::new(start, end, self.pattern.clone(), text.to_string())
+    }
+
+    fn matches(&self, text: &str) -> Vec<Match> {
+        // the characters are counted on from the last match, so the text is only gone through once
+        let (mut bytes, mut chars) = (0, 0);
+        self.regex
+            .find_iter(text)
+            .map(|found| {
+                chars += text[bytes..found.start()].chars().count();
+                bytes = found.start();
+                self.to_match(chars, found.as_str())
+            })
+            .collect()
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl SafeRegex {
+    #[new]
+    #[pyo3(signature = (pattern, case_insensitive=false))]
+    fn py_new(pattern: &str, case_insensitive: bool) -> PyResult<Self> {
+        SafeRegex::new(pattern, case_insensitive).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The pattern, as it was given.
+    #[getter] // COV_EXCL_LINE
+    fn pattern(&self) -> String {
+        self.pattern.clone()
+    }
+
+    /// Whether the pattern matches anywhere in `text`, stopping at the first match.
+    fn is_match(&self, text: &str) -> bool {
+        self.regex.is_match(text)
+    }
+
+    /// The leftmost match in `text`, if there is one.
+    fn find(&self, text: &str) -> Option<Match> {
+        self.regex.find(text).map(|found| self.to_match(text[..found.start()].chars().count(), found.as_str()))
+    }
+
+    /// Every match in `text` that doesn't overlap one before it.
+    fn find_all(&self, text: &str) -> Vec<Match> {
+        self.matches(text)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("SafeRegex({:?})", self.pattern)
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn regex(pattern: &str) -> SafeRegex {
+        SafeRegex::new(pattern, false).expect("Expected a valid pattern")
+    }
+
+    fn first(pattern: &str, text: &str) -> Option<String> {
+        regex(pattern).regex.find(text).map(|found| found.as_str().to_string())
+    }
+
+    #[parameterized(
+        literal = {"bad", "a bad word", Some("bad")},
+        missing = {"bad", "a good word", None},
+        dot = {"b.d", "bid", Some("bid")},
+        dot_newline = {"a.b", "a\nb", None},
+        dotall = {"(?s)a.b", "a\nb", Some("a\nb")},
+        class = {"[a-c]+", "xxabcaz", Some("abca")},
+        negated = {"[^a-z ]+", "abc DEF", Some("DEF")},
+        shorthand = {"\\d+", "call 555 1234", Some("555")},
+        word = {"\\w+", "¡hola señor!", Some("hola")},
+        boundary = {"\\bcat\\b", "concat cat", Some("cat")},
+        not_boundary = {"\\Bcat", "cat concat", Some("cat")},
+        alternation = {"cat|category", "category", Some("cat")},
+        leftmost_first = {"a|ab", "ab", Some("a")},
+        greedy = {"a+", "aaa", Some("aaa")},
+        lazy = {"a+?", "aaa", Some("a")},
+        optional = {"colou?r", "color", Some("color")},
+        counted = {"a{2,3}", "aaaa", Some("aaa")},
+        exactly = {"\\d{3}", "12 345", Some("345")},
+        at_least = {"a{2,}", "aaaaa", Some("aaaaa")},
+        anchors = {"^abc$", "abc", Some("abc")},
+        anchored_miss = {"^abc", "xabc", None},
+        multiline = {"(?m)^b$", "a\nb\nc", Some("b")},
+        insensitive = {"(?i)bad", "BaD", Some("BaD")},
+        insensitive_class = {"(?i)[a-c]+", "ABC", Some("ABC")},
+        scoped_flags = {"(?i:a)b", "Ab AB", Some("Ab")},
+        groups = {"(?:ab)+", "ababab", Some("ababab")},
+        named = {"(?P<word>b\\w+)", "a bee", Some("bee")},
+        escapes = {"\\.\\*\\x41\\u{42}", ".*AB", Some(".*AB")},
+        property = {"\\p{Cyrillic}+", "pаypаl", Some("а")},
+        not_property = {"\\P{L}+", "abc123", Some("123")},
+        posix = {"[[:digit:]]+", "a12", Some("12")},
+        verbose = {"(?x) a b  # the letters", "ab", Some("ab")},
+        empty = {"", "abc", Some("")},
+        nested_empty_loop = {"(a*)*b", "aab", Some("aab")},
+    )]
+    fn searching(pattern: &str, text: &str, expected: Option<&str>) {
+        assert_eq!(first(pattern, text).as_deref(), expected);
+    }
+
+    #[test]
+    fn finding_all() {
+        let found = |pattern: &str, text: &str, texts: &[(usize, usize, &str)]| {
+            let expected = texts.iter().map(|(start, end, text)| Match::new(*start, *end, pattern.to_string(), text.to_string())).collect::<Vec<_>>();
+            assert_eq!(regex(pattern).matches(text), expected, "{pattern} in {text}");
+        };
+        found("\\d+", "1 22 333", &[(0, 1, "1"), (2, 4, "22"), (5, 8, "333")]);
+        found("a*", "baa", &[(0, 0, ""), (1, 3, "aa")]);
+        found("", "ab", &[(0, 0, ""), (1, 1, ""), (2, 2, "")]);
+        found("é", "café é", &[(3, 4, "é"), (5, 6, "é")]);
+        found("b.d", "bad bid", &[(0, 3, "bad"), (4, 7, "bid")]);
+        assert_eq!(regex("é+").find("café éé"), Some(Match::new(3, 4, String::from("é+"), String::from("é"))));
+    }
+
+    #[test]
+    fn catastrophic_patterns_are_fast() {
+        // each of these takes exponential time with backtracking
+        let text = format!("{}!", "a".repeat(5000));
+        for pattern in ["^(a+)+$", "^(a|a)*$", "^(a|aa)+$", "(.*a){20}$"] {
+            let started = std::time::Instant::now();
+            assert_eq!(first(pattern, &text), None, "{pattern}");
+            assert!(started.elapsed().as_secs() < 5, "{pattern} took {:?}", started.elapsed());
+        }
+    }
+
+    #[test]
+    fn case_insensitive_option() {
+        let regex = SafeRegex::new("free nitro", true).expect("Expected a valid pattern");
+        assert_eq!(regex.matches("FREE Nitro here").len(), 1);
+        // flags in the pattern can turn it back off
+        let regex = SafeRegex::new("(?-i)free", true).expect("Expected a valid pattern");
+        assert!(regex.matches("FREE").is_empty());
+    }
+
+    #[parameterized(
+        backreference = {"(a)\\1"},
+        lookahead = {"a(?=b)"},
+        negative_lookahead = {"a(?!b)"},
+        lookbehind = {"(?<=a)b"},
+        atomic = {"(?>a)"},
+        nothing_to_repeat = {"*a"},
+        unclosed_group = {"(a"},
+        unopened_group = {"a)"},
+        unclosed_class = {"[a-"},
+        backwards_range = {"[z-a]"},
+        unknown_escape = {"\\q"},
+        trailing_backslash = {"a\\"},
+        unknown_property = {"\\p{Klingon}"},
+        backwards_repeat = {"a{3,2}"},
+        too_big = {"(a{1000}){20}"},
+    )]
+    fn rejects(pattern: &str) {
+        let error = SafeRegex::new(pattern, false).expect_err("Expected the pattern to be rejected");
+        assert!(error.starts_with("Invalid pattern"), "{error}");
+    }
+
+    #[test]
+    fn limits() {
+        SafeRegex::new(&"a".repeat(MAX_PATTERN_LENGTH + 1), false).expect_err("Expected a long pattern to be rejected");
+        let nested = MAX_NESTING as usize + 1;
+        SafeRegex::new(&format!("{}a{}", "(".repeat(nested), ")".repeat(nested)), false).expect_err("Expected deep nesting to be rejected");
+        SafeRegex::new(&"a".repeat(MAX_PATTERN_LENGTH), false).expect("Expected a long pattern to be accepted");
+        let error = SafeRegex::new("(?=a)", false).expect_err("Expected lookahead to be rejected");
+        assert!(error.starts_with("Invalid pattern at character "), "{error}");
+        assert!(error.ends_with(": look-around, including look-ahead and look-behind, is not supported"), "{error}");
+        let error = SafeRegex::new("(a{1000}){20}", false).expect_err("Expected a big pattern to be rejected");
+        assert_eq!(error, format!("Invalid pattern, it's too big once compiled, the limit is {SIZE_LIMIT} bytes"));
+    }
+}
+// COV_EXCL_STOP
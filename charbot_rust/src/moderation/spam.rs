@@ -0,0 +1,288 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::collections::{HashMap, VecDeque};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::moderation::fold::fold;
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// How many hashes a fingerprint has, each slot agreeing with a chance of the similarity of the messages.
+const HASHES: usize = 64;
+/// How many characters each shingle has.
+const SHINGLE: usize = 3;
+/// The most messages kept for each user and channel.
+const MAX_WINDOW: usize = 100;
+
+type Fingerprint = [u64; HASHES];
+
+/// Mix the bits of `value`, the finalizer of splitmix64.
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// The MinHash of the shingles of `text` once it's folded and its runs of spaces are one, `None` if it's shorter than `min_length`.
+///
+/// The hashes don't depend on anything random, so the same text always has the same fingerprint.
+fn fingerprint(text: &str, min_length: usize) -> Option<Fingerprint> {
+    let mut letters = Vec::new();
+    for (letter, _) in fold(text.trim()) {
+        if !(letter.is_whitespace() && letters.last().is_some_and(|last: &char| last.is_whitespace())) {
+            letters.push(if letter.is_whitespace() { ' ' } else { letter });
+        }
+    }
+    if letters.len() < min_length.max(1) {
+        return None;
+    }
+    let mut minimums = [u64::MAX; HASHES];
+    for shingle in letters.windows(SHINGLE.min(letters.len())) {
+        // FNV-1a, then a different mix for each slot
+        let hash = shingle.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, letter| (hash ^ u64::from(u32::from(*letter))).wrapping_mul(0x0100_0000_01b3));
+        for (slot, minimum) in minimums.iter_mut().enumerate() {
+            *minimum = (*minimum).min(mix(hash ^ mix(slot as u64 + 1)));
+        }
+    }
+    Some(minimums)
+}
+
+/// The fraction of the slots two fingerprints agree on, an estimate of how many of their shingles they share.
+fn similarity(first: &Fingerprint, second: &Fingerprint) -> f64 {
+    first.iter().zip(second).filter(|(a, b)| a == b).count() as f64 / HASHES as f64
+}
+
+/// How a message compared to the recent ones.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Copy, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Report {
+    /// How similar the message is to the most similar recent one, from 0 to 1.
+    #[pyo3(get)]
+    score: f64,
+    /// Whether the score reaches the threshold.
+    #[pyo3(get)]
+    flagged: bool,
+    /// How many of the user's recent messages, in any channel, it's similar to.
+    #[pyo3(get)]
+    user_matches: usize,
+    /// How many of the channel's recent messages, from anyone, it's similar to.
+    #[pyo3(get)]
+    channel_matches: usize,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Report {
+    fn __repr__(&self) -> String {
+        format!(
+            "Report(score={:.3}, flagged={}, user_matches={}, channel_matches={})",
+            self.score,
+            if self.flagged { "True" } else { "False" },
+            self.user_matches,
+            self.channel_matches
+        )
+    }
+}
+
+/// The recent messages of each user and channel, to spot copy pasted spam from one user or a raid of them.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Similarity {
+    window: usize,
+    threshold: f64,
+    max_age: f64,
+    min_length: usize,
+    users: HashMap<u64, VecDeque<(f64, Fingerprint)>>,
+    channels: HashMap<u64, VecDeque<(f64, Fingerprint)>>,
+}
+
+/// Drop the messages in `recent` from before `oldest`, which are at the front since messages come in order.
+fn expire(recent: &mut VecDeque<(f64, Fingerprint)>, oldest: f64) {
+    while recent.front().is_some_and(|(time, _)| *time < oldest) {
+        recent.pop_front();
+    }
+}
+
+impl Similarity {
+    fn new(window: usize, threshold: f64, max_age: f64, min_length: usize) -> Result<Self, String> {
+        if !(1..=MAX_WINDOW).contains(&window) {
+            return Err(format!("Invalid window of {window} messages, it must be from 1 to {MAX_WINDOW}"));
+        }
+        if !(0.0..=1.0).contains(&threshold) {
+            return Err(format!("Invalid threshold {threshold}, it must be from 0 to 1"));
+        }
+        if max_age.is_nan() || max_age <= 0.0 {
+            return Err(format!("Invalid max age {max_age}, it must be more than 0 seconds"));
+        }
+        Ok(Similarity { window, threshold, max_age, min_length, users: HashMap::new(), channels: HashMap::new() })
+    }
+
+    /// Compare `text` to the recent messages of `user` and of `channel`, then remember it.
+    ///
+    /// Messages shorter than the minimum length, like "ok" or "lol", are repeated too often to be
+    /// spam, so they're neither flagged nor remembered.
+    fn check(&mut self, text: &str, user: u64, channel: u64, now: f64) -> Report {
+        let Some(fingerprint) = fingerprint(text, self.min_length) else {
+            return Report { score: 0.0, flagged: false, user_matches: 0, channel_matches: 0 };
+        };
+        let (oldest, threshold, window) = (now - self.max_age, self.threshold, self.window);
+        let mut score = 0.0f64;
+        let mut compare = |recent: &mut VecDeque<(f64, Fingerprint)>| {
+            expire(recent, oldest);
+            let matches = recent
+                .iter()
+                .map(|(_, other)| similarity(&fingerprint, other))
+                .inspect(|similar| score = score.max(*similar))
+                .filter(|similar| *similar >= threshold)
+                .count();
+            if recent.len() == window {
+                recent.pop_front();
+            }
+            recent.push_back((now, fingerprint));
+            matches
+        };
+        let user_matches = compare(self.users.entry(user).or_default());
+        let channel_matches = compare(self.channels.entry(channel).or_default());
+        Report { score, flagged: score >= threshold && score > 0.0, user_matches, channel_matches }
+    }
+
+    /// Forget every message from before the max age, and the users and channels with none left.
+    fn prune(&mut self, now: f64) {
+        let oldest = now - self.max_age;
+        for recent in [&mut self.users, &mut self.channels] {
+            recent.retain(|_, messages| {
+                expire(messages, oldest);
+                !messages.is_empty()
+            });
+        }
+    }
+}
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64())
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Similarity {
+    #[new]
+    #[pyo3(signature = (window=10, threshold=0.8, max_age=300.0, min_length=10))]
+    fn py_new(window: usize, threshold: f64, max_age: f64, min_length: usize) -> PyResult<Self> {
+        Similarity::new(window, threshold, max_age, min_length).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "check", signature = (text, user, channel, now=None))]
+    fn py_check(&mut self, text: &str, user: u64, channel: u64, now: Option<f64>) -> Report {
+        metrics::timed("moderation.Similarity.check", || self.check(text, user, channel, now.unwrap_or_else(self::now)))
+    }
+
+    #[pyo3(name = "prune", signature = (now=None))]
+    fn py_prune(&mut self, now: Option<f64>) {
+        self.prune(now.unwrap_or_else(self::now));
+    }
+
+    /// Forget the messages of a user, like once they've been dealt with.
+    fn forget(&mut self, user: u64) {
+        self.users.remove(&user);
+    }
+
+    /// How similar two messages are, from 0 to 1, ignoring the minimum length.
+    #[staticmethod]
+    fn compare(first: &str, second: &str) -> f64 {
+        match (fingerprint(first, 1), fingerprint(second, 1)) {
+            (Some(first), Some(second)) => similarity(&first, &second),
+            (None, None) => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Similarity(window={}, threshold={}, max_age={}, min_length={})", self.window, self.threshold, self.max_age, self.min_length)
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPAM: &str = "FREE NITRO for everyone, claim it at the link below before it runs out!!";
+
+    fn detector() -> Similarity {
+        Similarity::new(10, 0.8, 60.0, 10).expect("Expected a valid detector")
+    }
+
+    #[test]
+    fn fingerprints() {
+        assert_eq!(fingerprint(SPAM, 10), fingerprint(SPAM, 10));
+        // case, spacing and sneaky characters don't change it
+        assert_eq!(fingerprint(SPAM, 10), fingerprint(&SPAM.to_lowercase().replace(' ', "  \u{200b}"), 10));
+        assert_eq!(fingerprint("short", 10), None);
+        assert!(fingerprint("ab", 1).is_some());
+    }
+
+    #[test]
+    fn similarities() {
+        assert_eq!(Similarity::compare(SPAM, SPAM), 1.0);
+        let edited = Similarity::compare(SPAM, &SPAM.replace("everyone", "every1"));
+        assert!(edited > 0.7 && edited < 1.0, "Got {edited}");
+        let unrelated = Similarity::compare(SPAM, "Has anyone finished the new expansion yet? The last boss is brutal.");
+        assert!(unrelated < 0.2, "Got {unrelated}");
+        assert_eq!(Similarity::compare("", ""), 1.0);
+        assert_eq!(Similarity::compare("", "text"), 0.0);
+    }
+
+    #[test]
+    fn copy_paste_from_one_user() {
+        let mut detector = detector();
+        assert!(!detector.check(SPAM, 1, 10, 0.0).flagged);
+        // posting it again in another channel
+        let report = detector.check(SPAM, 1, 11, 1.0);
+        assert!(report.flagged);
+        assert_eq!((report.user_matches, report.channel_matches), (1, 0));
+        let report = detector.check(&format!("{SPAM} ?"), 1, 12, 2.0);
+        assert_eq!((report.flagged, report.user_matches), (true, 2));
+    }
+
+    #[test]
+    fn raids() {
+        let mut detector = detector();
+        for user in 0..5 {
+            let report = detector.check(SPAM, user, 10, f64::from(user as u32));
+            assert_eq!((report.user_matches, report.channel_matches), (0, user as usize));
+        }
+    }
+
+    #[test]
+    fn windows() {
+        let mut detector = Similarity::new(2, 0.8, 60.0, 10).expect("Expected a valid detector");
+        detector.check(SPAM, 1, 10, 0.0);
+        detector.check("something else entirely, about the weather today", 1, 10, 1.0);
+        detector.check("and a third message, about what's for dinner", 1, 10, 2.0);
+        // the spam was pushed out of the window
+        assert!(!detector.check(SPAM, 1, 10, 3.0).flagged);
+        // and messages from too long ago are forgotten
+        assert!(!detector.check(SPAM, 1, 10, 100.0).flagged);
+        detector.prune(1000.0);
+        assert!(detector.users.is_empty() && detector.channels.is_empty());
+        detector.check(SPAM, 1, 10, 1000.0);
+        detector.forget(1);
+        let report = detector.check(SPAM, 1, 11, 1001.0);
+        assert_eq!((report.user_matches, report.channel_matches), (0, 0));
+        // short messages aren't remembered
+        detector.check("lol", 2, 12, 1002.0);
+        assert!(!detector.check("lol", 2, 12, 1003.0).flagged);
+    }
+
+    #[test]
+    fn rejects() {
+        Similarity::new(0, 0.8, 60.0, 10).expect_err("Expected no window to be rejected");
+        Similarity::new(MAX_WINDOW + 1, 0.8, 60.0, 10).expect_err("Expected a big window to be rejected");
+        Similarity::new(10, 1.5, 60.0, 10).expect_err("Expected the threshold to be rejected");
+        Similarity::new(10, 0.8, 0.0, 10).expect_err("Expected no max age to be rejected");
+        Similarity::new(10, 0.8, f64::NAN, 10).expect_err("Expected the max age to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,362 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use pyo3::prelude::*;
+use crate::moderation::fold::{fold, leet};
+use crate::moderation::normalize::skeleton;
+use crate::moderation::wordfilter::{Match, WordFilter};
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// Words that are insults on their own, and more so aimed at someone.
+const INSULTS: [&str; 24] = [
+    "idiot", "stupid", "dumb", "dumbass", "moron", "loser", "trash", "garbage", "pathetic", "worthless", "useless", "ugly",
+    "clown", "braindead", "imbecile", "scum", "freak", "creep", "cretin", "jerk", "asshole", "bitch", "dickhead", "noob",
+];
+/// Words that aim what comes after them at the person being talked to.
+const SECOND_PERSON: [&str; 9] = ["you", "u", "ya", "youre", "ur", "ure", "your", "yourself", "yall"];
+/// Words that take an insult back, like "you're not stupid".
+const NEGATIONS: [&str; 7] = ["not", "never", "no", "nt", "arent", "isnt", "aint"];
+/// Threats and telling someone to hurt themselves, as the words in a row.
+const THREATS: [&[&str]; 12] = [
+    &["kys"],
+    &["kill", "yourself"],
+    &["kill", "urself"],
+    &["kill", "yoself"],
+    &["neck", "yourself"],
+    &["i", "will", "kill", "you"],
+    &["ill", "kill", "you"],
+    &["im", "going", "to", "kill", "you"],
+    &["gonna", "kill", "you"],
+    &["hope", "you", "die"],
+    &["go", "die"],
+    &["i", "will", "find", "you"],
+];
+/// How many words after "you" an insult still counts as aimed at them.
+const REACH: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Rule {
+    BannedWord,
+    Slur,
+    Insult,
+    TargetedInsult,
+    Threat,
+}
+
+impl Rule {
+    fn name(self) -> &'static str {
+        match self {
+            Rule::BannedWord => "banned_word",
+            Rule::Slur => "slur",
+            Rule::Insult => "insult",
+            Rule::TargetedInsult => "targeted_insult",
+            Rule::Threat => "threat",
+        }
+    }
+
+    /// The chance a message is toxic from this rule firing once in it, going by how often each is a false positive.
+    ///
+    /// An insult on its own is often about a thing, like "this game is stupid", while one aimed at
+    /// someone rarely isn't meant, and slurs and threats almost never are.
+    fn weight(self) -> f64 {
+        match self {
+            Rule::BannedWord => 0.5,
+            Rule::Slur => 0.9,
+            Rule::Insult => 0.3,
+            Rule::TargetedInsult => 0.75,
+            Rule::Threat => 0.9,
+        }
+    }
+}
+
+/// How toxic a message looks, and why.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Toxicity {
+    /// How likely the message is to be toxic, from 0 to 1.
+    #[pyo3(get)]
+    score: f64,
+    /// The names of the rules that fired, each once, in the order they first did in the message.
+    #[pyo3(get)]
+    rules: Vec<String>,
+    /// Where each rule fired, with the name of the rule as the rule of the match.
+    #[pyo3(get)]
+    matches: Vec<Match>,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Toxicity {
+    fn __repr__(&self) -> String {
+        format!("Toxicity(score={:.3}, rules={:?})", self.score, self.rules)
+    }
+}
+
+fn is_letter(letter: char) -> bool {
+    letter.is_alphanumeric() || !leet(letter).is_empty()
+}
+
+/// `text` as it's checked, each character as the ASCII it looks like, lowercase and folded like the word
+/// filter does, with the index of the character in `text` it came from.
+fn view(text: &str) -> Vec<(char, usize)> {
+    let mut view = Vec::with_capacity(text.len());
+    for (index, letter) in text.chars().enumerate() {
+        // ASCII is left alone, since leetspeak is matched on its own and the skeleton of 1 is l
+        let looks = if letter.is_ascii() { letter.to_string() } else { skeleton(&letter.to_string()) };
+        view.extend(fold(&looks).into_iter().map(|(letter, _)| (letter, index)));
+    }
+    view
+}
+
+/// `view` without the gaps in words spelled out a letter at a time, like "i d i o t" or "i.d.i.o.t",
+/// once there are at least three letters.
+fn joined(view: &[(char, usize)]) -> Vec<(char, usize)> {
+    let single = |at: usize| is_letter(view[at].0) && (at == 0 || !is_letter(view[at - 1].0)) && view.get(at + 1).map_or(true, |(next, _)| !is_letter(*next));
+    let mut kept = vec![true; view.len()];
+    let mut at = 0;
+    while at < view.len() {
+        if !single(at) {
+            at += 1;
+            continue;
+        }
+        let mut end = at;
+        // the letters are one apart, so the gap between them is one character that isn't a letter
+        while end + 2 < view.len() && view[end + 1].0 != '\n' && single(end + 2) {
+            end += 2;
+        }
+        if (end - at) / 2 + 1 >= 3 {
+            for gap in (at + 1..end).step_by(2) {
+                kept[gap] = false;
+            }
+        }
+        at = end + 1;
+    }
+    view.iter().zip(kept).filter(|(_, kept)| *kept).map(|(letter, _)| *letter).collect()
+}
+
+/// A word of the message, as the characters it's checked as and the span of the message it covers.
+struct Token {
+    letters: Vec<char>,
+    start: usize,
+    end: usize,
+}
+
+/// The words of `view`, with apostrophes left out of them, so "you're" is "youre".
+fn tokens(view: &[(char, usize)]) -> Vec<Token> {
+    let apostrophe = |letter: char| letter == '\'' || letter == '\u{2019}';
+    let mut tokens = Vec::new();
+    for word in view.split(|(letter, _)| !is_letter(*letter) && !apostrophe(*letter)) {
+        let Some(first) = word.iter().position(|(letter, _)| !apostrophe(*letter)) else {
+            continue;
+        };
+        let last = word.iter().rposition(|(letter, _)| !apostrophe(*letter)).unwrap_or(first);
+        let letters = word[first..=last].iter().map(|(letter, _)| *letter).filter(|letter| !apostrophe(*letter)).collect();
+        tokens.push(Token { letters, start: word[first].1, end: word[last].1 + 1 });
+    }
+    tokens
+}
+
+/// Whether `letters` spell `word`, with leetspeak, letters drawn out like "idiooot", and a plural s for longer words.
+fn spells(letters: &[char], word: &str) -> bool {
+    let word = word.chars().collect::<Vec<_>>();
+    let fits = |letter: char, wanted: char| letter == wanted || leet(letter).contains(&wanted);
+    let spelled = |letters: &[char]| {
+        // the letters of the word matched so far, every way they could be
+        let mut states = vec![0];
+        for letter in letters {
+            let mut next = Vec::with_capacity(states.len() * 2);
+            for &state in &states {
+                if state < word.len() && fits(*letter, word[state]) {
+                    next.push(state + 1);
+                }
+                if state > 0 && state <= word.len() && fits(*letter, word[state - 1]) {
+                    next.push(state);
+                }
+                // short words aren't made plural, so "us" isn't "u"
+                if state == word.len() && word.len() > 3 && fits(*letter, 's') {
+                    next.push(word.len() + 1);
+                }
+            }
+            next.sort_unstable();
+            next.dedup();
+            states = next;
+        }
+        states.iter().any(|state| *state >= word.len())
+    };
+    // punctuation after a word can be leetspeak, like the ! in "idiot!", so it's tried without it too
+    let trimmed = letters.iter().rposition(|letter| letter.is_alphanumeric()).map_or(&letters[..0], |last| &letters[..=last]);
+    spelled(letters) || trimmed.len() < letters.len() && spelled(trimmed)
+}
+
+fn is_any(token: &Token, words: &[&str]) -> bool {
+    words.iter().any(|word| spells(&token.letters, word))
+}
+
+/// Where each built in rule fires in `tokens`, as the rule and the span of the message.
+fn patterns(tokens: &[Token]) -> Vec<(Rule, usize, usize)> {
+    let mut found = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        for threat in THREATS {
+            if tokens.get(index..index + threat.len()).is_some_and(|words| words.iter().zip(threat.iter()).all(|(token, word)| spells(&token.letters, word))) {
+                found.push((Rule::Threat, token.start, tokens[index + threat.len() - 1].end));
+            }
+        }
+        if !is_any(token, &INSULTS) {
+            continue;
+        }
+        let target = (index.saturating_sub(REACH)..index).rev().take_while(|before| !is_any(&tokens[*before], &NEGATIONS)).find(|before| is_any(&tokens[*before], &SECOND_PERSON));
+        match target {
+            Some(target) => found.push((Rule::TargetedInsult, tokens[target].start, token.end)),
+            None => found.push((Rule::Insult, token.start, token.end)),
+        }
+    }
+    found
+}
+
+fn toxicity(text: &str, words: Option<&WordFilter>, slurs: Option<&WordFilter>) -> Toxicity {
+    let checked = joined(&view(text));
+    let string = checked.iter().map(|(letter, _)| *letter).collect::<String>();
+    let mut found = Vec::new();
+    for (filter, rule) in [(words, Rule::BannedWord), (slurs, Rule::Slur)] {
+        for (start, end, _) in filter.map(|filter| filter.find(&string)).unwrap_or_default() {
+            found.push((rule, checked[start].1, checked[end - 1].1 + 1));
+        }
+    }
+    found.extend(patterns(&tokens(&checked)));
+    found.sort_by_key(|(_, start, end)| (*start, *end));
+    let characters = text.chars().collect::<Vec<_>>();
+    let mut rules = Vec::new();
+    let mut clean = 1.0;
+    let mut matches = Vec::with_capacity(found.len());
+    for (rule, start, end) in found {
+        clean *= 1.0 - rule.weight();
+        if !rules.contains(&rule.name().to_string()) {
+            rules.push(rule.name().to_string());
+        }
+        matches.push(Match::new(start, end, rule.name().to_string(), characters[start..end].iter().collect()));
+    }
+    Toxicity { score: 1.0 - clean, rules, matches }
+}
+
+#[pyfunction]
+#[pyo3(name = "toxicity", signature = (text, words=None, slurs=None), text_signature = "
+toxicity(text, words=None, slurs=None, /)
+--
+
+Score how toxic a message looks, from banned words, slurs, insults and threats, without calling an outside API.
+
+The message is checked as what it looks like, so look alike letters from other scripts, leetspeak,
+accents, invisible characters, drawn out letters and words spelled out a letter at a time all match.
+Insults count for more when they're aimed at someone, like 'you're an idiot', and less when they
+are taken back, like 'you're not an idiot'. Each time a rule fires it has a chance of the message
+being toxic, going by how often it's wrong, and the score is the chance at least one of them is right.
+
+Parameters
+----------
+text : str
+    The message.
+words : WordFilter | None
+    The guild's banned words, each a chance of 0.5. The default is None, for none.
+slurs : WordFilter | None
+    The slurs to look for, each a chance of 0.9. The default is None, for none, there's no built in list.
+
+Returns
+-------
+Toxicity
+    The score, the rules that fired and where they did.
+")]
+pub fn score(text: &str, words: Option<PyRef<WordFilter>>, slurs: Option<PyRef<WordFilter>>) -> Toxicity {
+    metrics::timed("moderation.toxicity", || toxicity(text, words.as_deref(), slurs.as_deref()))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn filter(words: &[&str]) -> WordFilter {
+        WordFilter::new(&words.iter().map(ToString::to_string).collect::<Vec<_>>()).expect("Expected a valid filter")
+    }
+
+    fn rules(text: &str) -> Vec<String> {
+        toxicity(text, None, None).rules
+    }
+
+    #[parameterized(
+        clean = {"good game everyone, that was fun", vec![]},
+        insult = {"this update is stupid", vec!["insult"]},
+        targeted = {"you are an idiot", vec!["targeted_insult"]},
+        contraction = {"you're such a loser", vec!["targeted_insult"]},
+        shorthand = {"ur trash", vec!["targeted_insult"]},
+        too_far = {"you said the other team was trash", vec!["insult"]},
+        negated = {"you're not stupid", vec!["insult"]},
+        leetspeak = {"y0u @re an 1d10t!", vec!["targeted_insult"]},
+        drawn_out = {"you idiooooot", vec!["targeted_insult"]},
+        plural = {"you guys are idiots", vec!["targeted_insult"]},
+        spaced = {"you are an i d i o t", vec!["targeted_insult"]},
+        dotted = {"you m.o.r.o.n", vec!["targeted_insult"]},
+        lookalike = {"уоu іdіоt", vec!["targeted_insult"]},
+        threat = {"kys", vec!["threat"]},
+        threat_phrase = {"just kill yourself lol", vec!["threat"]},
+        both = {"kill yourself you clown", vec!["threat", "targeted_insult"]},
+        not_inside_words = {"the stupidity of it", vec![]},
+    )]
+    fn rules_fired(text: &str, expected: Vec<&str>) {
+        assert_eq!(rules(text), expected);
+    }
+
+    #[test]
+    fn spelling() {
+        let letters = |text: &str| text.chars().collect::<Vec<_>>();
+        assert!(spells(&letters("idiot"), "idiot"));
+        assert!(spells(&letters("1d1o7"), "idiot"));
+        assert!(spells(&letters("iiidiot"), "idiot"));
+        assert!(spells(&letters("idiots"), "idiot"));
+        assert!(spells(&letters("idiot!!"), "idiot"));
+        assert!(!spells(&letters("idio"), "idiot"));
+        assert!(!spells(&letters("idiotic"), "idiot"));
+        assert!(!spells(&letters(""), "idiot"));
+    }
+
+    #[test]
+    fn joining() {
+        let joined = |text: &str| joined(&view(text)).into_iter().map(|(letter, _)| letter).collect::<String>();
+        assert_eq!(joined("i d i o t"), "idiot");
+        assert_eq!(joined("a.b.c and d e"), "abc and d e");
+        assert_eq!(joined("i am a bot"), "i am a bot");
+    }
+
+    #[test]
+    fn matches() {
+        let found = toxicity("hey, y0u 1d10t", None, None);
+        assert_eq!(found.matches, vec![Match::new(5, 14, "targeted_insult".to_string(), "y0u 1d10t".to_string())]);
+        let found = toxicity("so і d і о t", None, None);
+        assert_eq!(found.matches, vec![Match::new(3, 12, "insult".to_string(), "і d і о t".to_string())]);
+    }
+
+    #[test]
+    fn filters() {
+        let words = filter(&["heck"]);
+        let slurs = filter(&["zorp"]);
+        let found = toxicity("what the h3ck, z o r p", Some(&words), Some(&slurs));
+        assert_eq!(found.rules, vec!["banned_word", "slur"]);
+        assert_eq!(found.matches[1], Match::new(15, 22, "slur".to_string(), "z o r p".to_string()));
+        assert!((found.score - (1.0 - 0.5 * 0.1)).abs() < 1e-9);
+        assert_eq!(toxicity("zоrp", None, Some(&slurs)).rules, vec!["slur"]);
+    }
+
+    #[test]
+    fn calibration() {
+        assert_eq!(toxicity("hello there", None, None).score, 0.0);
+        let insult = toxicity("this is so dumb", None, None).score;
+        let targeted = toxicity("you are so dumb", None, None).score;
+        let threat = toxicity("i will kill you", None, None).score;
+        assert!(insult < 0.5 && targeted > 0.7 && threat > 0.85, "{insult} {targeted} {threat}");
+        // more of the same adds up, but never past 1
+        let ranting = toxicity("you idiot, you moron, you clown, you loser", None, None).score;
+        assert!(ranting > 0.99 && ranting < 1.0, "{ranting}");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,314 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::moderation::fold::{fold, leet};
+use crate::metrics;
+// COV_EXCL_STOP
+
+/// The most words a filter can have.
+const MAX_WORDS: usize = 10_000;
+/// The most characters a word can have.
+const MAX_WORD_LENGTH: usize = 100;
+
+/// A word from the list, as it's matched.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+    /// The word as it was given, with its wildcards.
+    entry: String,
+    length: usize,
+    /// Whether the match has to start at the start of a word, without a `*` before it.
+    word_start: bool,
+    /// Whether the match has to end at the end of a word, without a `*` after it.
+    word_end: bool,
+}
+
+/// A state of the automaton, the end of a prefix of some of the words.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Node {
+    children: HashMap<char, usize>,
+    /// The state for the longest suffix of this prefix that's a prefix of another word.
+    fail: usize,
+    /// The rules whose words end here, including at the suffixes.
+    outputs: Vec<usize>,
+}
+
+/// Where a message matched a word, or a pattern.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Match {
+    /// The index of the first character of the match in the message.
+    #[pyo3(get)]
+    start: usize,
+    /// The index after the last character of the match.
+    #[pyo3(get)]
+    end: usize,
+    /// The word from the list or the pattern it matched, as it was given.
+    #[pyo3(get)]
+    rule: String,
+    /// The text of the message that matched.
+    #[pyo3(get)]
+    text: String,
+}
+
+impl Match {
+    pub(crate) fn new(start: usize, end: usize, rule: String, text: String) -> Self {
+        Match { start, end, rule, text }
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Match {
+    fn __repr__(&self) -> String {
+        format!("Match(start={}, end={}, rule={:?}, text={:?})", self.start, self.end, self.rule, self.text)
+    }
+}
+
+/// Banned words compiled into an Aho-Corasick automaton, so a message is checked against every word in one pass.
+#[pyclass(module = "moderation")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct WordFilter {
+    rules: Vec<Rule>,
+    nodes: Vec<Node>,
+}
+
+impl WordFilter {
+    /// Compile `words`, each matched as a whole word unless it starts or ends with `*`, like Discord's automod.
+    pub(crate) fn new(words: &[String]) -> Result<Self, String> {
+        if words.len() > MAX_WORDS {
+            return Err(format!("Invalid word list of {} words, it can have at most {MAX_WORDS}", words.len()));
+        }
+        let mut rules = Vec::with_capacity(words.len());
+        let mut nodes = vec![Node::default()];
+        for entry in words {
+            let trimmed = entry.trim();
+            let word = trimmed.trim_start_matches('*').trim_end_matches('*');
+            let pattern = fold(word).into_iter().map(|(letter, _)| letter).collect::<Vec<_>>();
+            if !pattern.iter().any(|letter| letter.is_alphanumeric()) {
+                return Err(format!("Invalid word {entry:?}, it has no letters"));
+            }
+            if pattern.len() > MAX_WORD_LENGTH {
+                return Err(format!("Invalid word {entry:?}, it can have at most {MAX_WORD_LENGTH} letters"));
+            }
+            let mut state = 0;
+            for letter in &pattern {
+                state = match nodes[state].children.get(letter) {
+                    Some(next) => *next,
+                    None => {
+                        nodes.push(Node::default());
+                        let next = nodes.len() - 1;
+                        nodes[state].children.insert(*letter, next);
+                        next
+                    }
+                };
+            }
+            nodes[state].outputs.push(rules.len());
+            rules.push(Rule { entry: entry.clone(), length: pattern.len(), word_start: !trimmed.starts_with('*'), word_end: !trimmed.ends_with('*') });
+        }
+        let mut filter = WordFilter { rules, nodes };
+        filter.link();
+        Ok(filter)
+    }
+
+    /// Find the failure links breadth first, so the link of every shorter prefix is known before it's needed.
+    fn link(&mut self) {
+        let mut queue = self.nodes[0].children.values().copied().collect::<VecDeque<_>>();
+        while let Some(state) = queue.pop_front() {
+            let children = self.nodes[state].children.iter().map(|(letter, child)| (*letter, *child)).collect::<Vec<_>>();
+            for (letter, child) in children {
+                let fail = self.step(self.nodes[state].fail, letter);
+                // the root's children fail back to the root, not to themselves
+                let fail = if fail == child { 0 } else { fail };
+                self.nodes[child].fail = fail;
+                let inherited = self.nodes[fail].outputs.clone();
+                self.nodes[child].outputs.extend(inherited);
+                queue.push_back(child);
+            }
+        }
+    }
+
+    fn step(&self, mut state: usize, letter: char) -> usize {
+        loop {
+            if let Some(next) = self.nodes[state].children.get(&letter) {
+                return *next;
+            }
+            if state == 0 {
+                return 0;
+            }
+            state = self.nodes[state].fail;
+        }
+    }
+
+    /// Find the words in `text`, after folding it, as the spans of `text` they cover and the rules they matched.
+    ///
+    /// Leetspeak can stand for more than one letter, like `1` for `i` or `l`, so the automaton follows
+    /// every state the text could be in at once. Where matches overlap, the first is kept, and the
+    /// longest of those that start in the same place.
+    pub(crate) fn find(&self, text: &str) -> Vec<(usize, usize, usize)> {
+        let folded = fold(text);
+        let is_boundary = |index: Option<usize>| index.and_then(|index| folded.get(index)).map_or(true, |(letter, _)| !letter.is_alphanumeric());
+        let mut found = Vec::new();
+        let mut states = vec![0];
+        for (position, (letter, original)) in folded.iter().enumerate() {
+            let mut next = Vec::with_capacity(states.len());
+            for state in &states {
+                next.extend(std::iter::once(letter).chain(leet(*letter)).map(|letter| self.step(*state, *letter)));
+            }
+            next.sort_unstable();
+            next.dedup();
+            for rule in next.iter().flat_map(|state| &self.nodes[*state].outputs) {
+                let rule_info = &self.rules[*rule];
+                let start = position + 1 - rule_info.length;
+                if rule_info.word_start && !is_boundary(start.checked_sub(1)) || rule_info.word_end && !is_boundary(Some(position + 1)) {
+                    continue;
+                }
+                found.push((folded[start].1, original + 1, *rule));
+            }
+            states = next;
+        }
+        found.sort_unstable_by_key(|(start, end, rule)| (*start, Reverse(*end), *rule));
+        let mut kept: Vec<(usize, usize, usize)> = Vec::new();
+        for span in found {
+            if kept.last().map_or(true, |last| span.0 >= last.1) {
+                kept.push(span);
+            }
+        }
+        kept
+    }
+
+    fn matches(&self, text: &str) -> Vec<Match> {
+        let characters = text.chars().collect::<Vec<_>>();
+        self.find(text)
+            .into_iter()
+            .map(|(start, end, rule)| Match { start, end, rule: self.rules[rule].entry.clone(), text: characters[start..end].iter().collect() })
+            .collect()
+    }
+
+    fn censor(&self, text: &str, mask: char) -> String {
+        let mut characters = text.chars().collect::<Vec<_>>();
+        for (start, end, _) in self.find(text) {
+            characters[start..end].fill(mask);
+        }
+        characters.into_iter().collect()
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl WordFilter {
+    #[new]
+    fn py_new(words: Vec<String>) -> PyResult<Self> {
+        WordFilter::new(&words).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The words, as they were given.
+    #[getter] // COV_EXCL_LINE
+    fn words(&self) -> Vec<String> {
+        self.rules.iter().map(|rule| rule.entry.clone()).collect()
+    }
+
+    #[pyo3(name = "find")]
+    fn py_find(&self, text: &str) -> Vec<Match> {
+        metrics::timed("moderation.WordFilter.find", || self.matches(text))
+    }
+
+    fn is_clean(&self, text: &str) -> bool {
+        metrics::timed("moderation.WordFilter.is_clean", || self.find(text).is_empty())
+    }
+
+    #[pyo3(name = "censor", signature = (text, mask='*'))]
+    fn py_censor(&self, text: &str, mask: char) -> String {
+        metrics::timed("moderation.WordFilter.censor", || self.censor(text, mask))
+    }
+
+    fn __len__(&self) -> usize {
+        self.rules.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("WordFilter(words={})", self.rules.len())
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn filter(words: &[&str]) -> WordFilter {
+        WordFilter::new(&words.iter().map(ToString::to_string).collect::<Vec<_>>()).expect("Expected a valid filter")
+    }
+
+    fn spans(filter: &WordFilter, text: &str) -> Vec<(usize, usize, String)> {
+        filter.matches(text).into_iter().map(|found| (found.start, found.end, found.rule)).collect()
+    }
+
+    #[parameterized(
+        plain = {"this is bad", vec![(8, 11, "bad")]},
+        capitals = {"BAD idea", vec![(0, 3, "bad")]},
+        punctuation = {"bad!", vec![(0, 3, "bad")]},
+        leet = {"b4d", vec![(0, 3, "bad")]},
+        symbols = {"b@d", vec![(0, 3, "bad")]},
+        accents = {"bád", vec![(0, 3, "bad")]},
+        zero_width = {"b\u{200b}ad", vec![(0, 4, "bad")]},
+        combining = {"ba\u{301}d", vec![(0, 4, "bad")]},
+        full_width = {"ＢＡＤ", vec![(0, 3, "bad")]},
+        inside_a_word = {"badge", vec![]},
+        substring = {"sk8ers", vec![(0, 3, "*sk8*")]},
+        ambiguous_leet = {"ki11", vec![(0, 4, "kill")]},
+        both_leets = {"k1|l", vec![(0, 4, "kill")]},
+        prefix = {"uglyness", vec![(0, 4, "ugly*")]},
+        suffix = {"megajerk", vec![(4, 8, "*jerk")]},
+        phrase = {"you are a bad   word", vec![(10, 13, "bad")]},
+        several = {"bad, worse, b4d", vec![(0, 3, "bad"), (12, 15, "bad")]},
+    )]
+    fn matching(text: &str, expected: Vec<(usize, usize, &str)>) {
+        let filter = filter(&["bad", "*sk8*", "kill", "ugly*", "*jerk"]);
+        let expected = expected.into_iter().map(|(start, end, rule)| (start, end, rule.to_string())).collect::<Vec<_>>();
+        assert_eq!(spans(&filter, text), expected);
+    }
+
+    #[test]
+    fn overlapping() {
+        let filter = filter(&["*ass*", "*assassin*", "*sin*"]);
+        // the longest match at the first place one starts
+        assert_eq!(spans(&filter, "assassins"), vec![(0, 8, "*assassin*".to_string())]);
+        assert_eq!(spans(&filter, "lasso"), vec![(1, 4, "*ass*".to_string())]);
+        let filter = self::filter(&["he", "she", "*hers*", "his"]);
+        assert_eq!(spans(&filter, "ushers"), vec![(2, 6, "*hers*".to_string())]);
+        assert_eq!(spans(&filter, "she said his"), vec![(0, 3, "she".to_string()), (9, 12, "his".to_string())]);
+    }
+
+    #[test]
+    fn censoring() {
+        let filter = filter(&["bad", "*sk8*"]);
+        assert_eq!(filter.censor("so b\u{200b}ad, sk8r", '#'), "so ####, ###r");
+        assert_eq!(filter.matches("a bád day")[0].text, "bád");
+        assert!(filter.is_clean("a good day"));
+        assert!(!filter.is_clean("a BAD day"));
+    }
+
+    #[test]
+    fn many_words() {
+        let words = (0..2000).map(|index| format!("word{index}x")).collect::<Vec<_>>();
+        let filter = WordFilter::new(&words).expect("Expected a valid filter");
+        assert_eq!(filter.rules.len(), 2000);
+        assert_eq!(spans(&filter, "so word1999x and word7x"), vec![(3, 12, "word1999x".to_string()), (17, 23, "word7x".to_string())]);
+        assert!(filter.is_clean(&"word ".repeat(1000)));
+    }
+
+    #[test]
+    fn rejects() {
+        WordFilter::new(&["*".to_string()]).expect_err("Expected a wildcard on its own to be rejected");
+        WordFilter::new(&["  ".to_string()]).expect_err("Expected an empty word to be rejected");
+        WordFilter::new(&["a".repeat(MAX_WORD_LENGTH + 1)]).expect_err("Expected a long word to be rejected");
+        WordFilter::new(&vec!["word".to_string(); MAX_WORDS + 1]).expect_err("Expected too many words to be rejected");
+        assert_eq!(filter(&[]).matches("anything"), vec![]);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,348 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Random names in the languages the bot is translated to, like team names for events and names for
+//! temporary voice channels, from word lists built into the library.
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use crate::assets;
+use crate::fluent::bundle::{self, AvailableLocales};
+use crate::metrics;
+
+/// An adjective, as it is with nouns of each form, like the masculine and the feminine.
+struct Adjective {
+    forms: [&'static str; 2],
+}
+
+/// A noun, with the form of the adjectives that go with it.
+struct Noun {
+    word: &'static str,
+    form: usize,
+}
+
+#[derive(Default)]
+struct Lists {
+    adjectives: Vec<Adjective>,
+    nouns: Vec<Noun>,
+    /// The syllables fantasy names start with, have in the middle, and end with.
+    starts: Vec<&'static str>,
+    middles: Vec<&'static str>,
+    ends: Vec<&'static str>,
+}
+
+fn parse(list: &'static str) -> Result<Lists, String> {
+    let mut lists = Lists::default();
+    let mut section = None;
+    for line in list.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        if let Some(name) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            section = Some(name);
+            continue;
+        }
+        match section {
+            Some("adjectives") => lists.adjectives.push(match line.split_once('/') {
+                Some((_, feminine)) if feminine.contains('/') => return Err(format!("{line} has more than two forms")),
+                Some((masculine, feminine)) => Adjective { forms: [masculine, feminine] },
+                None => Adjective { forms: [line; 2] },
+            }),
+            Some("nouns") => {
+                let (word, form) = match line.split_once(' ') {
+                    // the form the adjectives in the list are first given in
+                    Some((word, "m" | "het")) => (word, 0),
+                    Some((word, "f" | "de")) => (word, 1),
+                    Some((_, gender)) => return Err(format!("{line} has an unknown gender {gender}")),
+                    None => (line, 0),
+                };
+                lists.nouns.push(Noun { word, form });
+            }
+            Some("starts") => lists.starts.push(line),
+            Some("middles") => lists.middles.push(line),
+            Some("ends") => lists.ends.push(line),
+            Some(name) => return Err(format!("Unknown section {name}")),
+            None => return Err(format!("{line} isn't in a section")),
+        }
+    }
+    Ok(lists)
+}
+
+/// The built in lists at `path`, which are always valid.
+fn load(path: &str) -> Lists {
+    assets::static_text(path).and_then(parse).unwrap_or_else(|e| panic!("The built in name lists are valid: {e}"))
+}
+
+// each list is only parsed the first time it is used
+static EN_US: Lazy<Lists> = Lazy::new(|| load("namegen/en-US.txt"));
+static ES_ES: Lazy<Lists> = Lazy::new(|| load("namegen/es-ES.txt"));
+static FR: Lazy<Lists> = Lazy::new(|| load("namegen/fr.txt"));
+static NL: Lazy<Lists> = Lazy::new(|| load("namegen/nl.txt"));
+
+fn lists(locale: AvailableLocales) -> &'static Lists {
+    match locale {
+        // runtime locales have no words of their own, and the pseudo locale is english underneath
+        AvailableLocales::AmericanEnglish | AvailableLocales::Custom(_) | AvailableLocales::Pseudo => &EN_US,
+        AvailableLocales::EuropeanSpanish => &ES_ES,
+        AvailableLocales::French => &FR,
+        AvailableLocales::Dutch => &NL,
+    }
+}
+
+/// The words of an adjective and a noun, in the order they go in `locale`, with the adjective agreeing.
+fn pair(locale: AvailableLocales, adjective: &Adjective, noun: &Noun) -> [&'static str; 2] {
+    let adjective = adjective.forms[noun.form];
+    match locale {
+        AvailableLocales::EuropeanSpanish | AvailableLocales::French => [noun.word, adjective],
+        _ => [adjective, noun.word],
+    }
+}
+
+/// `word` with its first letter in uppercase.
+fn capitalize(word: &str) -> String {
+    let mut letters = word.chars();
+    letters.next().map_or_else(String::new, |first| first.to_uppercase().chain(letters).collect())
+}
+
+/// `count` names of an adjective and a noun, like "Brave Otter", without any two sharing a word.
+fn teams(count: usize, locale: AvailableLocales, rng: &mut impl Rng) -> Result<Vec<String>, String> {
+    let lists = lists(locale);
+    let most = lists.adjectives.len().min(lists.nouns.len());
+    if count > most {
+        return Err(format!("Invalid count {count}, there can be at most {most} teams"));
+    }
+    let adjectives = lists.adjectives.choose_multiple(rng, count).collect::<Vec<_>>();
+    let nouns = lists.nouns.choose_multiple(rng, count);
+    Ok(adjectives
+        .into_iter()
+        .zip(nouns)
+        .map(|(adjective, noun)| pair(locale, adjective, noun).map(capitalize).join(" "))
+        .collect())
+}
+
+/// An adjective and a noun in lowercase with `separator` between them, like "brave-otter".
+fn phrase(locale: AvailableLocales, separator: &str, rng: &mut impl Rng) -> String {
+    let lists = lists(locale);
+    let adjective = lists.adjectives.choose(rng).expect("The lists have words");
+    let noun = lists.nouns.choose(rng).expect("The lists have words");
+    pair(locale, adjective, noun).join(separator)
+}
+
+/// A made up name of `syllables` syllables, or 2 or 3 if not given, like "Eldoria".
+fn fantasy(locale: AvailableLocales, syllables: Option<usize>, rng: &mut impl Rng) -> Result<String, String> {
+    let syllables = syllables.unwrap_or_else(|| rng.gen_range(2..=3));
+    if !(2..=4).contains(&syllables) {
+        return Err(format!("Invalid syllables {syllables}, it must be from 2 to 4"));
+    }
+    let lists = lists(locale);
+    let mut name = String::from(*lists.starts.choose(rng).expect("The lists have syllables"));
+    for _ in 2..syllables {
+        name.push_str(lists.middles.choose(rng).expect("The lists have syllables"));
+    }
+    name.push_str(lists.ends.choose(rng).expect("The lists have syllables"));
+    Ok(capitalize(&name))
+}
+
+#[pyfunction]
+#[pyo3(signature = (count, locale="en-US", seed=None), text_signature = "
+team_names(count, locale='en-US', seed=None, /)
+--
+
+Make up names for teams of an adjective and a noun, like 'Brave Otter', in the language of a locale.
+
+The words come from a word list for each language the bot is translated to, built into the library, and
+are in the order they go in the language with the adjective agreeing, like 'Nutria Valiente'. No two of
+the names share a word, so they're easy to tell apart.
+
+Parameters
+----------
+count : int
+    How many names to make up.
+locale : str
+    The locale to use the word list of, the closest one is used if it doesn't have one of its own. The default is 'en-US'.
+seed : int | None
+    The seed for the names, so they can be reproduced. The default is None, for random names.
+
+Returns
+-------
+list[str]
+    The names, with each word capitalized.
+
+Raises
+------
+ValueError
+    If there aren't enough words for that many names, which is at least 35.
+")]
+fn team_names(count: usize, locale: &str, seed: Option<u64>) -> PyResult<Vec<String>> {
+    metrics::observe("namegen.team_names", || {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        teams(count, bundle::closest_locale(locale), &mut rng).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (locale="en-US", separator="-", seed=None), text_signature = "
+code_phrase(locale='en-US', separator='-', seed=None, /)
+--
+
+Make up a phrase of an adjective and a noun in lowercase, like 'brave-otter', in the language of a locale,
+for names of temporary channels and codes to join with.
+
+Parameters
+----------
+locale : str
+    The locale to use the word list of, the closest one is used if it doesn't have one of its own. The default is 'en-US'.
+separator : str
+    What goes between the words. The default is '-'.
+seed : int | None
+    The seed for the phrase, so it can be reproduced. The default is None, for a random phrase.
+
+Returns
+-------
+str
+    The phrase.
+")]
+fn code_phrase(locale: &str, separator: &str, seed: Option<u64>) -> String {
+    metrics::timed("namegen.code_phrase", || {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        phrase(bundle::closest_locale(locale), separator, &mut rng)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (locale="en-US", syllables=None, seed=None), text_signature = "
+fantasy_name(locale='en-US', syllables=None, seed=None, /)
+--
+
+Make up a fantasy name from syllables that sound like the language of a locale, like 'Eldoria'.
+
+Parameters
+----------
+locale : str
+    The locale to use the syllables of, the closest one is used if it doesn't have its own. The default is 'en-US'.
+syllables : int | None
+    How many syllables the name has, from 2 to 4. The default is None, for 2 or 3.
+seed : int | None
+    The seed for the name, so it can be reproduced. The default is None, for a random name.
+
+Returns
+-------
+str
+    The name, capitalized.
+
+Raises
+------
+ValueError
+    If the number of syllables isn't from 2 to 4.
+")]
+fn fantasy_name(locale: &str, syllables: Option<usize>, seed: Option<u64>) -> PyResult<String> {
+    metrics::observe("namegen.fantasy_name", || {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        fantasy(bundle::closest_locale(locale), syllables, &mut rng).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based random names for teams, channels and fantasy characters";
+
+pub(crate) fn register_namegen(py: Python, m: &PyModule) -> PyResult<()> {
+    let namegen = PyModule::new(py, "_namegen")?;
+    namegen.add_function(wrap_pyfunction!(team_names, namegen)?)?;
+    namegen.add_function(wrap_pyfunction!(code_phrase, namegen)?)?;
+    namegen.add_function(wrap_pyfunction!(fantasy_name, namegen)?)?;
+    namegen.add("__doc__", DOCSTRING)?;
+    m.add_submodule(namegen)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        english = {AvailableLocales::AmericanEnglish},
+        spanish = {AvailableLocales::EuropeanSpanish},
+        french = {AvailableLocales::French},
+        dutch = {AvailableLocales::Dutch},
+    )]
+    fn name_lists(locale: AvailableLocales) {
+        let lists = lists(locale);
+        assert!(lists.adjectives.len() >= 35 && lists.nouns.len() >= 35);
+        assert!(lists.starts.len() >= 15 && lists.middles.len() >= 10 && lists.ends.len() >= 10);
+        let words = lists.adjectives.iter().flat_map(|adjective| adjective.forms).chain(lists.nouns.iter().map(|noun| noun.word));
+        for word in words.chain(lists.starts.iter().chain(&lists.middles).chain(&lists.ends).copied()) {
+            assert!(!word.is_empty() && word.chars().all(|letter| letter.is_alphabetic() && letter.is_lowercase()), "Bad word {word:?}");
+        }
+        // only the languages with genders have nouns of both
+        let both = lists.nouns.iter().any(|noun| noun.form == 0) && lists.nouns.iter().any(|noun| noun.form == 1);
+        assert_eq!(both, locale != AvailableLocales::AmericanEnglish);
+    }
+
+    #[parameterized(
+        section = {"[verbs]\nrun", "Unknown section verbs"},
+        outside = {"brave\n[adjectives]", "brave isn't in a section"},
+        forms = {"[adjectives]\na/b/c", "a/b/c has more than two forms"},
+        gender = {"[nouns]\notter n", "otter n has an unknown gender n"},
+    )]
+    fn invalid_lists(list: &'static str, error: &str) {
+        assert_eq!(parse(list).err().as_deref(), Some(error));
+    }
+
+    #[parameterized(
+        english = {AvailableLocales::AmericanEnglish, "brave", "otter", "Brave Otter"},
+        spanish = {AvailableLocales::EuropeanSpanish, "rápido", "tortuga", "Tortuga Rápida"},
+        french = {AvailableLocales::French, "courageux", "loutre", "Loutre Courageuse"},
+        dutch_de = {AvailableLocales::Dutch, "dapper", "otter", "Dappere Otter"},
+        dutch_het = {AvailableLocales::Dutch, "dapper", "paard", "Dapper Paard"},
+    )]
+    fn agrees(locale: AvailableLocales, adjective: &str, noun: &str, expected: &str) {
+        let lists = lists(locale);
+        let adjective = lists.adjectives.iter().find(|found| found.forms[0] == adjective).expect("The adjective is in the list");
+        let noun = lists.nouns.iter().find(|found| found.word == noun).expect("The noun is in the list");
+        assert_eq!(pair(locale, adjective, noun).map(capitalize).join(" "), expected);
+    }
+
+    #[test]
+    fn distinct_teams() {
+        let names = teams(10, AvailableLocales::AmericanEnglish, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(names.len(), 10);
+        // no two share a word
+        let mut words = names.iter().flat_map(|name| name.split(' ')).collect::<Vec<_>>();
+        words.sort_unstable();
+        words.dedup();
+        assert_eq!(words.len(), 20);
+        assert_eq!(teams(10, AvailableLocales::AmericanEnglish, &mut StdRng::seed_from_u64(1)), Ok(names));
+        assert_eq!(teams(0, AvailableLocales::French, &mut StdRng::seed_from_u64(1)), Ok(vec![]));
+        let most = EN_US.adjectives.len().min(EN_US.nouns.len());
+        assert_eq!(teams(most, AvailableLocales::Pseudo, &mut StdRng::seed_from_u64(1)).map(|names| names.len()), Ok(most));
+        assert_eq!(teams(most + 1, AvailableLocales::AmericanEnglish, &mut thread_rng()), Err(format!("Invalid count {}, there can be at most {most} teams", most + 1)));
+    }
+
+    #[test]
+    fn code_phrases() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..20 {
+            let phrase = phrase(AvailableLocales::EuropeanSpanish, "-", &mut rng);
+            let (noun, adjective) = phrase.split_once('-').expect("Two words");
+            let noun = ES_ES.nouns.iter().find(|found| found.word == noun).expect("A noun first in spanish");
+            assert!(ES_ES.adjectives.iter().any(|found| found.forms[noun.form] == adjective), "{phrase}");
+        }
+        assert_eq!(phrase(AvailableLocales::Dutch, " ", &mut StdRng::seed_from_u64(3)), phrase(AvailableLocales::Dutch, " ", &mut StdRng::seed_from_u64(3)));
+    }
+
+    #[parameterized(two = {Some(2)}, three = {Some(3)}, four = {Some(4)}, either = {None})]
+    fn fantasy_names(syllables: Option<usize>) {
+        for seed in 0..20 {
+            let name = fantasy(AvailableLocales::French, syllables, &mut StdRng::seed_from_u64(seed)).unwrap();
+            assert!(name.chars().next().is_some_and(char::is_uppercase), "{name}");
+            assert!(name.chars().skip(1).all(char::is_lowercase), "{name}");
+            let start = FR.starts.iter().find(|start| name.to_lowercase().starts_with(**start)).expect("Starts with a start");
+            assert!(FR.ends.iter().any(|end| name[start.len()..].ends_with(end)), "{name}");
+        }
+    }
+
+    #[parameterized(none = {1}, many = {5})]
+    fn bad_syllables(syllables: usize) {
+        assert_eq!(fantasy(AvailableLocales::Dutch, Some(syllables), &mut thread_rng()), Err(format!("Invalid syllables {syllables}, it must be from 2 to 4")));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A game that can be saved and loaded again, so games in progress survive the bot restarting.
+///
+/// The state is saved along with the name of the game and the version of its state, so the state of
+/// one game can't be loaded as another, and a state from before a change to it is rejected instead
+/// of being misread.
+pub trait Persist: Sized {
+    const GAME: &'static str;
+    /// Bumped whenever `State` changes in a way older saves can't be read as.
+    const VERSION: u32;
+    /// What is saved, plain data apart from the game so it can be checked when it is loaded.
+    type State: Serialize + DeserializeOwned;
+
+    fn save(&self) -> Self::State;
+
+    /// Rebuild the game, failing if the state couldn't have come from a game.
+    fn load(state: Self::State) -> Result<Self, String>;
+}
+
+#[derive(Serialize, Deserialize)]
+struct Saved<T> {
+    game: String,
+    version: u32,
+    state: T,
+}
+
+/// The start of a save, read on its own to check it before the state.
+#[derive(Deserialize)]
+struct Header {
+    game: String,
+    version: u32,
+}
+
+fn saved<T: Persist>(game: &T) -> Saved<T::State> {
+    Saved { game: T::GAME.to_string(), version: T::VERSION, state: game.save() }
+}
+
+fn check<T: Persist>(header: Header) -> Result<(), String> {
+    if header.game != T::GAME {
+        return Err(format!("This is a saved {} game, not a {} game", header.game, T::GAME));
+    }
+    if header.version > T::VERSION {
+        return Err(format!("The saved {} game is from a newer version {}", T::GAME, header.version));
+    }
+    if header.version != T::VERSION {
+        return Err(format!("The saved {} game is from version {}, which can't be loaded anymore", T::GAME, header.version));
+    }
+    Ok(())
+}
+
+/// Save a game as msgpack, compact enough for a database column.
+pub fn to_bytes<T: Persist>(game: &T) -> Result<Vec<u8>, String> {
+    rmp_serde::to_vec_named(&saved(game)).map_err(|error| format!("Couldn't save the game: {error}"))
+}
+
+/// Pass on whether a saved game could be loaded, logging why it couldn't, since that's a row of the
+/// database to look at.
+fn logged<T: Persist>(loaded: Result<T, String>) -> Result<T, String> {
+    if let Err(error) = &loaded {
+        tracing::info!(target: "charbot_rust.games", "Couldn't load a saved {} game: {error}", T::GAME);
+    }
+    loaded
+}
+
+pub fn from_bytes<T: Persist>(bytes: &[u8]) -> Result<T, String> {
+    logged(read_bytes(bytes))
+}
+
+fn read_bytes<T: Persist>(bytes: &[u8]) -> Result<T, String> {
+    let invalid = |error: rmp_serde::decode::Error| format!("Invalid saved game: {error}");
+    check::<T>(rmp_serde::from_slice(bytes).map_err(invalid)?)?;
+    let saved: Saved<T::State> = rmp_serde::from_slice(bytes).map_err(invalid)?;
+    T::load(saved.state)
+}
+
+/// Save a game as JSON, for reading or editing the state by hand.
+#[cfg(any(test, feature = "games", feature = "community"))]
+pub fn to_json<T: Persist>(game: &T) -> Result<String, String> {
+    serde_json::to_string(&saved(game)).map_err(|error| format!("Couldn't save the game: {error}"))
+}
+
+#[cfg(any(test, feature = "games", feature = "community"))]
+pub fn from_json<T: Persist>(text: &str) -> Result<T, String> {
+    logged(read_json(text))
+}
+
+#[cfg(any(test, feature = "games", feature = "community"))]
+fn read_json<T: Persist>(text: &str) -> Result<T, String> {
+    let invalid = |error: serde_json::Error| format!("Invalid saved game: {error}");
+    check::<T>(serde_json::from_str(text).map_err(invalid)?)?;
+    let saved: Saved<T::State> = serde_json::from_str(text).map_err(invalid)?;
+    T::load(saved.state)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Counter(u32);
+
+    impl Persist for Counter {
+        const GAME: &'static str = "counter";
+        const VERSION: u32 = 2;
+        type State = u32;
+
+        fn save(&self) -> u32 {
+            self.0
+        }
+
+        fn load(state: u32) -> Result<Self, String> {
+            if state > 10 {
+                return Err("Too high".to_string());
+            }
+            Ok(Counter(state))
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let bytes = to_bytes(&Counter(3)).expect("Saved");
+        assert_eq!(from_bytes::<Counter>(&bytes), Ok(Counter(3)));
+        let json = to_json(&Counter(3)).expect("Saved");
+        assert_eq!(json, r#"{"game":"counter","version":2,"state":3}"#);
+        assert_eq!(from_json::<Counter>(&json), Ok(Counter(3)));
+    }
+
+    #[test]
+    fn rejects() {
+        from_json::<Counter>(r#"{"game":"other","version":2,"state":3}"#).expect_err("Expected the other game to be rejected");
+        from_json::<Counter>(r#"{"game":"counter","version":1,"state":3}"#).expect_err("Expected the old version to be rejected");
+        from_json::<Counter>(r#"{"game":"counter","version":3,"state":3}"#).expect_err("Expected the new version to be rejected");
+        from_json::<Counter>(r#"{"game":"counter","version":2,"state":11}"#).expect_err("Expected the state to be rejected");
+        from_json::<Counter>(r#"{"game":"counter","version":2}"#).expect_err("Expected the missing state to be rejected");
+        from_json::<Counter>("not json").expect_err("Expected the text to be rejected");
+        from_bytes::<Counter>(b"not msgpack").expect_err("Expected the bytes to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -3,9 +3,10 @@
 // SPDX-License-Identifier: MIT
 use crate::tictactoe::Difficulty; // COV_EXCL_LINE
 use pyo3::prelude::*; // COV_EXCL_LINE
+use serde::{Deserialize, Serialize}; // COV_EXCL_LINE
 
 #[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
-#[derive(Debug)] // COV_EXCL_LINE
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)] // COV_EXCL_LINE
 pub struct Points {
     #[pyo3(get)]
     pub win: (i8, i8), // COV_EXCL_LINE
@@ -0,0 +1,340 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod eval; // COV_EXCL_LINE
+
+use std::collections::HashSet;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use rand::prelude::*;
+use crate::metrics;
+use crate::poker::eval::{rank_name, score, Card, Category};
+#[cfg(feature = "async")]
+use crate::tasks;
+
+/// The most opponents a hand can be simulated against, so there are always enough cards.
+const MAX_OPPONENTS: usize = 9;
+const BOARD_SIZE: usize = 5;
+
+// COV_EXCL_START
+#[pyclass(module = "poker")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandRank {
+    #[pyo3(get)]
+    category: Category,
+    /// The ranks that break ties between hands of the same category, most important first.
+    kickers: Vec<u8>,
+    /// The hand as one number, higher for a better hand and equal for a tie.
+    #[pyo3(get)]
+    strength: u32,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl HandRank {
+    /// The kickers as rank letters, like `['A', 'T']`.
+    #[getter]
+    fn kickers(&self) -> Vec<char> {
+        self.kickers.iter().copied().map(rank_name).collect()
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        op.matches(self.strength.cmp(&other.strength))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("HandRank({:?}, kickers={:?})", self.category, self.kickers())
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "poker")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Odds {
+    /// How often the hand won outright.
+    #[pyo3(get)]
+    win: f64,
+    /// How often the hand split the pot.
+    #[pyo3(get)]
+    tie: f64,
+    #[pyo3(get)]
+    loss: f64,
+    /// The share of the pot the hand gets on average, with split pots shared out.
+    #[pyo3(get)]
+    equity: f64,
+}
+
+#[pymethods]
+impl Odds {
+    fn __repr__(&self) -> String {
+        format!("Odds(win={:.3}, tie={:.3}, loss={:.3}, equity={:.3})", self.win, self.tie, self.loss, self.equity)
+    }
+}
+// COV_EXCL_STOP
+
+/// Parse cards, none of which can be the same card.
+fn parse_cards(cards: &[String], seen: &mut HashSet<Card>) -> Result<Vec<Card>, String> {
+    cards
+        .iter()
+        .map(|text| {
+            let card = Card::parse(text)?;
+            if !seen.insert(card) {
+                return Err(format!("The card {} is there more than once", card.name()));
+            }
+            Ok(card)
+        })
+        .collect()
+}
+
+fn rank_hand(cards: &[Card]) -> HandRank {
+    let (category, kickers) = eval::evaluate(cards);
+    HandRank { strength: score(category, &kickers), category, kickers }
+}
+
+fn strength(cards: &[Card]) -> u32 {
+    let (category, kickers) = eval::evaluate(cards);
+    score(category, &kickers)
+}
+
+/// Deal out the rest of the board and the opponents' hands `iterations` times, counting how `hand` does.
+fn simulate_odds(hand: &[Card], board: &[Card], opponents: usize, iterations: u32, rng: &mut StdRng) -> Odds {
+    let known = hand.iter().chain(board).copied().collect::<HashSet<_>>();
+    let mut deck = Card::deck().filter(|card| !known.contains(card)).collect::<Vec<_>>();
+    let needed = BOARD_SIZE - board.len() + 2 * opponents;
+    let (mut wins, mut ties, mut equity) = (0u32, 0u32, 0.0);
+    let mut cards = Vec::with_capacity(BOARD_SIZE + 2);
+    for _ in 0..iterations {
+        let (dealt, _) = deck.partial_shuffle(rng, needed);
+        let (rest_of_board, opponent_cards) = dealt.split_at(BOARD_SIZE - board.len());
+        cards.clear();
+        cards.extend(board.iter().chain(rest_of_board));
+        let full_board = cards.clone();
+        cards.extend(hand);
+        let own = strength(&cards);
+        let mut best = 0;
+        let mut tied = 0;
+        for holding in opponent_cards.chunks(2) {
+            cards.clear();
+            cards.extend(full_board.iter().chain(holding));
+            let theirs = strength(&cards);
+            if theirs > best {
+                best = theirs;
+                tied = 0;
+            }
+            if theirs == best {
+                tied += 1;
+            }
+        }
+        if own > best {
+            wins += 1;
+            equity += 1.0;
+        } else if own == best {
+            ties += 1;
+            equity += 1.0 / f64::from(tied + 1);
+        }
+    }
+    let iterations = f64::from(iterations);
+    Odds {
+        win: f64::from(wins) / iterations,
+        tie: f64::from(ties) / iterations,
+        loss: 1.0 - f64::from(wins + ties) / iterations,
+        equity: equity / iterations,
+    }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+evaluate(cards, /)
+--
+
+Find the best five card hand in 5 to 7 cards.
+
+Parameters
+----------
+cards : list[str]
+    The cards, as a rank from 2 to 9, T or 10, J, Q, K and A followed by a suit of s, h, d or c, or
+    one of the suit symbols. For example ``['As', 'Td', '10h', 'K♣', '2c']``.
+
+Returns
+-------
+HandRank
+    The category of the hand and the ranks that break ties with other hands of that category. Hand
+    ranks compare like the hands do.
+
+Raises
+------
+ValueError
+    If there aren't from 5 to 7 cards, one isn't a valid card, or a card is there twice.
+")]
+fn evaluate(cards: Vec<String>) -> PyResult<HandRank> {
+    metrics::observe("poker.evaluate", || {
+        if !(5..=7).contains(&cards.len()) {
+            return Err(PyValueError::new_err(format!("Expected from 5 to 7 cards, got {}", cards.len())));
+        }
+        let cards = parse_cards(&cards, &mut HashSet::new()).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(rank_hand(&cards))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (hand, n_opponents, iterations, board=Vec::new(), seed=None), text_signature = "
+simulate(hand, n_opponents, iterations, board=[], seed=None, /)
+--
+
+Estimate how often a hold'em hand wins by dealing out the rest of the cards at random many times.
+
+Parameters
+----------
+hand : list[str]
+    The two cards in the hand, written as for ``evaluate``.
+n_opponents : int
+    How many opponents, from 1 to 9, each with two unknown cards.
+iterations : int
+    How many deals to try, more is more accurate, 10000 is usually within a percent and takes a few
+    milliseconds.
+board : list[str]
+    The community cards already dealt, from none to all 5. The default is none.
+seed : int | None
+    The seed for the deals, so a result can be reproduced. The default is None, for random deals.
+
+Returns
+-------
+Odds
+    How often the hand won, tied and lost, and its share of the pot on average.
+
+Raises
+------
+ValueError
+    If the hand isn't two cards, the board is more than 5, a card is invalid or there twice, or the
+    number of opponents or iterations is out of range.
+")]
+fn simulate(
+    py: Python,
+    hand: Vec<String>,
+    n_opponents: usize,
+    iterations: u32,
+    board: Vec<String>,
+    seed: Option<u64>,
+) -> PyResult<Odds> {
+    metrics::observe("poker.simulate", || {
+        if hand.len() != 2 {
+            return Err(PyValueError::new_err(format!("Expected 2 cards in the hand, got {}", hand.len())));
+        }
+        if board.len() > BOARD_SIZE {
+            return Err(PyValueError::new_err(format!("Expected at most 5 cards on the board, got {}", board.len())));
+        }
+        if !(1..=MAX_OPPONENTS).contains(&n_opponents) {
+            return Err(PyValueError::new_err(format!("Invalid number of opponents {n_opponents}, it must be from 1 to 9")));
+        }
+        if iterations == 0 {
+            return Err(PyValueError::new_err("There must be at least one iteration"));
+        }
+        let mut seen = HashSet::new();
+        let hand = parse_cards(&hand, &mut seen).map_err(PyErr::new::<PyValueError, _>)?;
+        let board = parse_cards(&board, &mut seen).map_err(PyErr::new::<PyValueError, _>)?;
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        Ok(py.allow_threads(|| simulate_odds(&hand, &board, n_opponents, iterations, &mut rng)))
+    })
+}
+
+#[cfg(feature = "async")]
+#[pyfunction]
+#[pyo3(signature = (hand, n_opponents, iterations, board=Vec::new(), seed=None), text_signature = "
+simulate_async(hand, n_opponents, iterations, board=[], seed=None, /)
+--
+
+Estimate how often a hold'em hand wins on a thread pool, so the event loop keeps running meanwhile.
+It takes and gives the same as ``simulate``, once awaited.
+
+Returns
+-------
+asyncio.Future[Odds]
+    How often the hand won, tied and lost, and its share of the pot on average.
+
+Raises
+------
+RuntimeError
+    If there isn't an event loop running.
+")]
+fn simulate_async<'py>(
+    py: Python<'py>,
+    hand: Vec<String>,
+    n_opponents: usize,
+    iterations: u32,
+    board: Vec<String>,
+    seed: Option<u64>,
+) -> PyResult<&'py PyAny> {
+    tasks::spawn(py, move |py| simulate(py, hand, n_opponents, iterations, board, seed).map(|odds| odds.into_py(py)))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based poker hand evaluation";
+
+pub(crate) fn register_poker(py: Python, m: &PyModule) -> PyResult<()> {
+    let poker = PyModule::new(py, "_poker")?;
+    poker.add_class::<Category>()?;
+    poker.add_class::<HandRank>()?;
+    poker.add_class::<Odds>()?;
+    poker.add_function(wrap_pyfunction!(evaluate, poker)?)?;
+    poker.add_function(wrap_pyfunction!(simulate, poker)?)?;
+    #[cfg(feature = "async")]
+    poker.add_function(wrap_pyfunction!(simulate_async, poker)?)?;
+    poker.add("__doc__", DOCSTRING)?;
+    m.add_submodule(poker)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(text: &str) -> Vec<Card> {
+        let texts = text.split_whitespace().map(String::from).collect::<Vec<_>>();
+        parse_cards(&texts, &mut HashSet::new()).expect("Valid cards")
+    }
+
+    #[test]
+    fn duplicates() {
+        let texts = ["As", "Kd", "AS"].map(String::from);
+        parse_cards(&texts, &mut HashSet::new()).expect_err("Expected the repeated card to be rejected");
+    }
+
+    #[test]
+    fn ranks() {
+        let rank = rank_hand(&cards("Ac Ad Th 4s 2d"));
+        assert_eq!(rank.category, Category::Pair);
+        assert_eq!(rank.kickers(), vec!['A', 'T', '4', '2']);
+        assert!(rank.strength > rank_hand(&cards("Kc Kd Ah Qs Jd")).strength);
+    }
+
+    #[test]
+    fn locked_board() {
+        // a royal flush on the board is a split pot for everyone
+        let odds = simulate_odds(&cards("2c 3d"), &cards("As Ks Qs Js Ts"), 3, 200, &mut StdRng::seed_from_u64(0));
+        assert_eq!(odds.tie, 1.0);
+        assert_eq!(odds.equity, 0.25);
+        let nuts = simulate_odds(&cards("Ah Ad"), &cards("Ac As 7d 2h 9c"), 2, 200, &mut StdRng::seed_from_u64(0));
+        assert_eq!(nuts.win, 1.0);
+        assert_eq!(nuts.loss, 0.0);
+    }
+
+    #[test]
+    fn pocket_aces() {
+        // aces against one random hand win about 85% of the time
+        let odds = simulate_odds(&cards("Ah Ad"), &[], 1, 20_000, &mut StdRng::seed_from_u64(1));
+        assert!((0.83..0.88).contains(&odds.equity), "Equity was {}", odds.equity);
+        assert!((odds.win + odds.tie + odds.loss - 1.0).abs() < 1e-9);
+        let crowded = simulate_odds(&cards("Ah Ad"), &[], 5, 20_000, &mut StdRng::seed_from_u64(1));
+        assert!(crowded.equity < odds.equity);
+    }
+
+    #[test]
+    fn seeded() {
+        let first = simulate_odds(&cards("7h 2d"), &cards("Kc 9s 3h"), 2, 500, &mut StdRng::seed_from_u64(9));
+        let second = simulate_odds(&cards("7h 2d"), &cards("Kc 9s 3h"), 2, 500, &mut StdRng::seed_from_u64(9));
+        assert_eq!(first, second);
+    }
+}
+// COV_EXCL_STOP
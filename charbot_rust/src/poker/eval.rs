@@ -0,0 +1,222 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use pyo3::pyclass;
+
+const RANKS: &str = "23456789TJQKA";
+const SUITS: &str = "shdc";
+const ACE: u8 = 14;
+
+/// The kinds of hand, from worst to best.
+#[pyclass(module = "poker")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Category { // COV_EXCL_LINE
+    HighCard,
+    Pair,
+    TwoPair,
+    ThreeOfAKind,
+    Straight,
+    Flush,
+    FullHouse,
+    FourOfAKind,
+    StraightFlush,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)] // COV_EXCL_LINE
+pub struct Card {
+    /// From 2 to 14 for an ace.
+    pub rank: u8,
+    pub suit: u8,
+}
+
+impl Card {
+    /// Every card, in rank then suit order.
+    pub fn deck() -> impl Iterator<Item = Card> {
+        (2..=ACE).flat_map(|rank| (0..4).map(move |suit| Card { rank, suit }))
+    }
+
+    /// A card from its rank and suit, like `As`, `Td`, `10h` or `K♣`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        let mut chars = text.chars();
+        let suit = chars.next_back().and_then(|suit| match suit {
+            '\u{2660}' => Some(0),
+            '\u{2665}' => Some(1),
+            '\u{2666}' => Some(2),
+            '\u{2663}' => Some(3),
+            suit => SUITS.find(suit.to_ascii_lowercase()).map(|suit| suit as u8),
+        });
+        let rank = match chars.as_str().to_ascii_uppercase().as_str() {
+            "10" => Some(10),
+            rank if rank.len() == 1 => RANKS.find(rank).map(|rank| rank as u8 + 2),
+            _ => None,
+        };
+        match (rank, suit) {
+            (Some(rank), Some(suit)) => Ok(Card { rank, suit }),
+            _ => Err(format!("Invalid card {text:?}, it must be a rank from 2 to A and a suit of s, h, d or c")),
+        }
+    }
+
+    /// The card as its rank and suit letters, like `Td`.
+    pub fn name(self) -> String {
+        format!("{}{}", rank_name(self.rank), SUITS.as_bytes()[usize::from(self.suit)] as char)
+    }
+}
+
+pub fn rank_name(rank: u8) -> char {
+    RANKS.as_bytes()[usize::from(rank - 2)] as char
+}
+
+/// The highest card of a straight in the ranks set in `mask`, an ace can also be low.
+fn straight_high(mask: u16) -> Option<u8> {
+    let mask = if mask & (1 << ACE) != 0 { mask | 0b10 } else { mask };
+    (5..=ACE).rev().find(|high| (mask >> (high - 4)) & 0b11111 == 0b11111)
+}
+
+/// The highest `count` ranks set in `mask`.
+fn top_ranks(mask: u16, count: usize) -> Vec<u8> {
+    (2..=ACE).rev().filter(|rank| mask & (1 << rank) != 0).take(count).collect()
+}
+
+/// The best five card hand in `cards`, which must be from 5 to 7 cards, and the ranks that break ties within it.
+pub fn evaluate(cards: &[Card]) -> (Category, Vec<u8>) {
+    let mut counts = [0u8; ACE as usize + 1];
+    let mut suits = [0u16; 4];
+    for card in cards {
+        counts[usize::from(card.rank)] += 1;
+        suits[usize::from(card.suit)] |= 1 << card.rank;
+    }
+    if let Some(flush) = suits.iter().copied().find(|suit| suit.count_ones() >= 5) {
+        if let Some(high) = straight_high(flush) {
+            return (Category::StraightFlush, vec![high]);
+        }
+    }
+    // ranks by how many of them there are, then by rank
+    let mut groups = (2..=ACE).rev().filter(|rank| counts[usize::from(*rank)] > 0).collect::<Vec<_>>();
+    groups.sort_by_key(|rank| std::cmp::Reverse(counts[usize::from(*rank)]));
+    let count = |index: usize| groups.get(index).map_or(0, |rank| counts[usize::from(*rank)]);
+    let all = suits.iter().fold(0, |all, suit| all | suit);
+    let without = |ranks: &[u8]| ranks.iter().fold(all, |mask, rank| mask & !(1 << rank));
+    if count(0) == 4 {
+        let quads = groups[0];
+        return (Category::FourOfAKind, [vec![quads], top_ranks(without(&[quads]), 1)].concat());
+    }
+    if count(0) == 3 && count(1) >= 2 {
+        // a second three of a kind makes the pair, the groups are ranked highest first within a count
+        let pair = groups[1..].iter().copied().filter(|rank| counts[usize::from(*rank)] >= 2).max();
+        return (Category::FullHouse, vec![groups[0], pair.unwrap_or(groups[1])]);
+    }
+    if let Some(flush) = suits.iter().copied().find(|suit| suit.count_ones() >= 5) {
+        return (Category::Flush, top_ranks(flush, 5));
+    }
+    if let Some(high) = straight_high(all) {
+        return (Category::Straight, vec![high]);
+    }
+    if count(0) == 3 {
+        let trips = groups[0];
+        return (Category::ThreeOfAKind, [vec![trips], top_ranks(without(&[trips]), 2)].concat());
+    }
+    if count(0) == 2 && count(1) == 2 {
+        let (high, low) = (groups[0], groups[1]);
+        return (Category::TwoPair, vec![high, low, top_ranks(without(&[high, low]), 1)[0]]);
+    }
+    if count(0) == 2 {
+        let pair = groups[0];
+        return (Category::Pair, [vec![pair], top_ranks(without(&[pair]), 3)].concat());
+    }
+    (Category::HighCard, top_ranks(all, 5))
+}
+
+/// The hand as one number, higher for a better hand and equal for a tie.
+pub fn score(category: Category, kickers: &[u8]) -> u32 {
+    let kickers = (0..5).fold(0, |packed, index| packed << 4 | u32::from(kickers.get(index).copied().unwrap_or(0)));
+    (category as u32) << 20 | kickers
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.split_whitespace().map(|card| Card::parse(card).expect("Valid card")).collect()
+    }
+
+    fn strength(text: &str) -> u32 {
+        let (category, kickers) = evaluate(&cards(text));
+        score(category, &kickers)
+    }
+
+    #[parameterized(
+        ten = {"Td", Card { rank: 10, suit: 2 }},
+        ten_digits = {"10h", Card { rank: 10, suit: 1 }},
+        lowercase = {"as", Card { rank: 14, suit: 0 }},
+        unicode = {"K\u{2663}", Card { rank: 13, suit: 3 }},
+    )]
+    fn parses(text: &str, expected: Card) {
+        assert_eq!(Card::parse(text), Ok(expected));
+    }
+
+    #[parameterized(
+        empty = {""},
+        no_suit = {"A"},
+        bad_rank = {"1s"},
+        bad_suit = {"Ax"},
+        too_long = {"AKs"},
+    )]
+    fn invalid_card(text: &str) {
+        Card::parse(text).expect_err("Expected the card to be rejected");
+    }
+
+    #[test]
+    fn deck() {
+        let deck = Card::deck().collect::<Vec<_>>();
+        assert_eq!(deck.len(), 52);
+        assert!(deck.iter().all(|card| Card::parse(&card.name()) == Ok(*card)));
+    }
+
+    #[parameterized(
+        royal = {"As Ks Qs Js Ts", Category::StraightFlush, vec![14]},
+        steel_wheel = {"5h 4h 3h 2h Ah", Category::StraightFlush, vec![5]},
+        quads = {"9c 9d 9h 9s 2d Kc 3s", Category::FourOfAKind, vec![9, 13]},
+        full_house = {"Kc Kd Kh 2s 2d", Category::FullHouse, vec![13, 2]},
+        two_trips = {"Kc Kd Kh 2s 2d 2h 7c", Category::FullHouse, vec![13, 2]},
+        trips_and_two_pairs = {"3c 3d 3h 9s 9d 8h 8c", Category::FullHouse, vec![3, 9]},
+        flush = {"Ah 9h 7h 4h 2h Kh 3d", Category::Flush, vec![14, 13, 9, 7, 4]},
+        flush_over_straight = {"6h 7h 8h 9d Th 2h", Category::Flush, vec![10, 8, 7, 6, 2]},
+        straight = {"9c Td Jh Qs Kd", Category::Straight, vec![13]},
+        wheel = {"Ac 2d 3h 4s 5d Kc", Category::Straight, vec![5]},
+        long_straight = {"4c 5d 6h 7s 8d 9c Th", Category::Straight, vec![10]},
+        trips = {"7c 7d 7h As 2d", Category::ThreeOfAKind, vec![7, 14, 2]},
+        two_pair = {"Jc Jd 4h 4s Ad", Category::TwoPair, vec![11, 4, 14]},
+        three_pairs = {"Jc Jd 4h 4s Ad Ac 2c", Category::TwoPair, vec![14, 11, 4]},
+        pair = {"Qc Qd 8h 4s 2d", Category::Pair, vec![12, 8, 4, 2]},
+        high_card = {"Ac Jd 8h 4s 2d 3c 9h", Category::HighCard, vec![14, 11, 9, 8, 4]},
+    )]
+    fn evaluates(text: &str, category: Category, kickers: Vec<u8>) {
+        assert_eq!(evaluate(&cards(text)), (category, kickers));
+    }
+
+    #[test]
+    fn ordering() {
+        let hands = [
+            "Ac Jd 8h 4s 2d",
+            "2c 2d 8h 4s 3d",
+            "2c 2d 3h 3s 4d",
+            "2c 2d 2h 3s 4d",
+            "Ac 2d 3h 4s 5d",
+            "2h 3h 4h 5h 7h",
+            "2c 2d 2h 3s 3d",
+            "2c 2d 2h 2s 3d",
+            "Ac 2c 3c 4c 5c",
+        ];
+        for pair in hands.windows(2) {
+            assert!(strength(pair[0]) < strength(pair[1]), "{} should lose to {}", pair[0], pair[1]);
+        }
+        assert!(strength("Ac Ad Kh 4s 2d") > strength("Ac Ad Qh Js Td"));
+        assert_eq!(strength("Ac Ad Kh 4s 2d"), strength("Ah As Kd 4c 2h"));
+        assert!(strength("Kc Kd Kh 2s 2d") > strength("2c 2d 2h As Ad"));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,221 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Profiling the functions counted by [`metrics`](crate::metrics), and the parts of the slow ones, like
+//! making a translator and formatting a message, by how long they take and how many allocations they
+//! make, once `set_profiling(True)` is called.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Instant;
+
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    /// The allocations made on this thread while profiling. It's const and has nothing to drop, so the
+    /// allocator can use it without allocating.
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Count an allocation on this thread if profiling is on.
+fn count() {
+    if ENABLED.load(Ordering::Relaxed) {
+        // the thread's locals are gone while it's exiting, its allocations then aren't profiled anyway
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+    }
+}
+
+/// How many allocations this thread has made while profiling.
+fn allocations() -> u64 {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// The system allocator, counting the allocations made while profiling.
+struct Counting;
+
+// SAFETY: every call is passed on to the system allocator as is
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// How long a function, or a part of one, took and how many allocations it made while profiling.
+#[pyclass(module = "charbot_rust")] // COV_EXCL_LINE
+#[derive(Clone, Debug, Default, PartialEq)] // COV_EXCL_LINE
+pub struct Profile {
+    /// The function, like `fluent.translate`, or a part of it, like `fluent.translate/format`.
+    #[pyo3(get)]
+    name: &'static str,
+    /// How many times it was called.
+    #[pyo3(get)]
+    calls: u64,
+    /// How many seconds the calls took altogether.
+    #[pyo3(get)]
+    seconds: f64,
+    /// How many seconds the slowest call took.
+    #[pyo3(get)]
+    slowest: f64,
+    /// How many allocations the calls made altogether, on the thread they were called on.
+    #[pyo3(get)]
+    allocations: u64,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Profile {
+    fn __repr__(&self) -> String {
+        format!(
+            "Profile(name={:?}, calls={}, seconds={}, slowest={}, allocations={})",
+            self.name, self.calls, self.seconds, self.slowest, self.allocations
+        )
+    }
+}
+
+static PROFILES: Lazy<Mutex<BTreeMap<&'static str, Profile>>> = Lazy::new(Mutex::default);
+
+/// Do `work`, profiling it as `name` if profiling is on.
+///
+/// The parts of a function are named after it, like `fluent.translate/format`, and are profiled inside
+/// it, so its own profile includes theirs.
+pub(crate) fn profile<T>(name: &'static str, work: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return work();
+    }
+    let (start, before) = (Instant::now(), allocations());
+    let value = work();
+    let (seconds, allocations) = (start.elapsed().as_secs_f64(), allocations() - before);
+    let mut profiles = PROFILES.lock().unwrap_or_else(PoisonError::into_inner);
+    let profile = profiles.entry(name).or_insert_with(|| Profile { name, ..Profile::default() });
+    profile.calls += 1;
+    profile.seconds += seconds;
+    profile.slowest = profile.slowest.max(seconds);
+    profile.allocations += allocations;
+    value
+}
+
+/// Turn profiling on, starting a new report, or off, keeping the report.
+fn enable(enabled: bool) {
+    if enabled {
+        PROFILES.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// What has been profiled, the slowest altogether first.
+fn report() -> Vec<Profile> {
+    let mut report = PROFILES.lock().unwrap_or_else(PoisonError::into_inner).values().cloned().collect::<Vec<_>>();
+    report.sort_by(|a, b| b.seconds.total_cmp(&a.seconds).then(a.name.cmp(b.name)));
+    report
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+set_profiling(enabled, /)
+--
+
+Turn profiling on or off. While it's on, the functions that ``charbot_rust.metrics`` counts, and the
+parts of the slow ones, like making a translator, formatting a message and finishing the translation,
+are timed and their allocations are counted.
+
+It's off by default, as it slows every function down a little and every allocation a little more.
+
+Parameters
+----------
+enabled : bool
+    Whether to profile. Turning it on starts a new report, and turning it off keeps the report.
+")]
+pub fn set_profiling(enabled: bool) {
+    enable(enabled);
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+profiling_report()
+--
+
+Get what has been profiled since profiling was turned on.
+
+A function's profile includes those of its parts, and doesn't include converting its arguments and
+result between python and rust, so that's how much longer a call takes timed in python. Allocations
+are counted on the thread the function was called on, so ones it leaves to other threads, like a
+thread pool, aren't counted.
+
+Returns
+-------
+list[Profile]
+    A profile for each function and part that was called, the slowest altogether first.
+")]
+pub fn profiling_report() -> Vec<Profile> {
+    report()
+}
+
+// COV_EXCL_START
+pub(crate) fn register_profiling(m: &PyModule) -> PyResult<()> {
+    m.add_class::<Profile>()?;
+    m.add_function(wrap_pyfunction!(set_profiling, m)?)?;
+    m.add_function(wrap_pyfunction!(profiling_report, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // profiling is global, so it's tested in one go rather than across tests running at once
+    #[test]
+    fn profiling() {
+        assert_eq!(profile("tests.off", || 1), 1);
+        assert!(report().iter().all(|profile| profile.name != "tests.off"));
+
+        enable(true);
+        let made = profile("tests.outer", || profile("tests.inner", || (0..100).map(|i| vec![i]).collect::<Vec<_>>()));
+        // what other threads allocate meanwhile isn't counted
+        let elsewhere = profile("tests.elsewhere", || std::thread::spawn(|| (0..1000).map(|i| vec![i]).collect::<Vec<_>>().len()).join());
+        assert_eq!(elsewhere.ok(), Some(1000));
+        assert_eq!(made.len(), 100);
+        profile("tests.inner", || ());
+        enable(false);
+        profile("tests.inner", || ());
+
+        let profiles = report();
+        let find = |name| profiles.iter().find(|profile| profile.name == name).expect("It was profiled");
+        let (outer, inner) = (find("tests.outer"), find("tests.inner"));
+        assert_eq!((outer.calls, inner.calls), (1, 2));
+        assert!(inner.allocations >= 101, "{inner:?}");
+        assert!(find("tests.elsewhere").allocations < 1000, "{:?}", find("tests.elsewhere"));
+        assert!(outer.allocations >= inner.allocations);
+        assert!(outer.seconds >= outer.slowest && inner.slowest <= inner.seconds);
+        assert_eq!(
+            outer.__repr__(),
+            format!("Profile(name=\"tests.outer\", calls=1, seconds={0}, slowest={0}, allocations={1})", outer.seconds, outer.allocations)
+        );
+
+        enable(true);
+        assert!(report().iter().all(|profile| profile.name != "tests.outer"));
+        enable(false);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,368 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::{HashMap, VecDeque};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// The most a limiter can allow in a period, which bounds what a sliding window remembers for each key.
+const MAX_RATE: u32 = 10_000;
+/// The least time between pruning idle keys, so acquiring doesn't look through every key each time.
+const PRUNE_INTERVAL: f64 = 60.0;
+/// How far off a float can be and still count, so a bucket refilled to 0.9999999 tokens still has 1.
+const EPSILON: f64 = 1e-9;
+
+/// What is limited, like a user, a guild and a command together.
+#[derive(FromPyObject, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Key {
+    #[pyo3(transparent)]
+    Id(u64),
+    #[pyo3(transparent)]
+    Name(String),
+    #[pyo3(transparent)]
+    Parts(Vec<Key>),
+}
+
+/// How a limiter counts what's been acquired.
+#[pyclass(module = "ratelimit")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Mode { // COV_EXCL_LINE
+    /// A bucket of tokens that refills steadily, so bursts are allowed up to the rate and then spaced out.
+    TokenBucket,
+    /// At most the rate in any period, counting exactly what was acquired in the last one.
+    SlidingWindow,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Bucket {
+    /// The tokens left, as of when it was last used.
+    Tokens { tokens: f64, updated: f64 },
+    /// When each acquisition in the last period was and what it cost, oldest first, and what they cost together.
+    Window { acquired: VecDeque<(f64, u32)>, used: u32 },
+}
+
+/// The unix timestamp when the clock was first read, and the instant it was read at.
+static START: Lazy<(f64, Instant)> =
+    Lazy::new(|| (SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64()), Instant::now()));
+
+/// A unix timestamp that only ever moves forward, so the system clock being set back doesn't hand out tokens.
+fn now() -> f64 {
+    let (timestamp, instant) = *START;
+    timestamp + instant.elapsed().as_secs_f64()
+}
+
+impl Bucket {
+    fn new(mode: Mode, rate: u32, now: f64) -> Self {
+        match mode {
+            Mode::TokenBucket => Bucket::Tokens { tokens: f64::from(rate), updated: now },
+            Mode::SlidingWindow => Bucket::Window { acquired: VecDeque::new(), used: 0 },
+        }
+    }
+
+    /// Bring the bucket up to `now`, refilling its tokens or forgetting what's left the window.
+    fn refresh(&mut self, rate: u32, per: f64, now: f64) {
+        match self {
+            Bucket::Tokens { tokens, updated } => {
+                // a clock that went backwards refills nothing, rather than taking tokens away
+                let elapsed = (now - *updated).max(0.0);
+                *tokens = (*tokens + elapsed * f64::from(rate) / per).min(f64::from(rate));
+                *updated = updated.max(now);
+            }
+            Bucket::Window { acquired, used } => {
+                while acquired.front().is_some_and(|(time, _)| *time <= now - per) {
+                    let (_, cost) = acquired.pop_front().unwrap_or_default();
+                    *used -= cost;
+                }
+            }
+        }
+    }
+
+    /// How long until `cost` can be acquired from the bucket, or None if it can now.
+    fn wait(&self, rate: u32, per: f64, cost: u32, now: f64) -> Option<f64> {
+        match self {
+            Bucket::Tokens { tokens, .. } => {
+                let missing = f64::from(cost) - tokens;
+                (missing > EPSILON).then(|| missing * per / f64::from(rate))
+            }
+            Bucket::Window { acquired, used } => {
+                let excess = (used + cost).checked_sub(rate).filter(|excess| *excess > 0)?;
+                // the acquisitions that have to leave the window first, oldest first
+                let mut freed = 0;
+                acquired.iter().find_map(|(time, spent)| {
+                    freed += spent;
+                    (freed >= excess).then(|| (time + per - now).max(0.0))
+                })
+            }
+        }
+    }
+
+    fn take(&mut self, cost: u32, now: f64) {
+        match self {
+            Bucket::Tokens { tokens, .. } => *tokens = (*tokens - f64::from(cost)).max(0.0),
+            Bucket::Window { acquired, used } => {
+                acquired.push_back((now, cost));
+                *used += cost;
+            }
+        }
+    }
+}
+
+/// Rate limits for many keys at once, each with their own bucket, like cooldowns for each user of a command.
+#[pyclass(module = "ratelimit")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Limiter {
+    rate: u32,
+    per: f64,
+    mode: Mode,
+    buckets: HashMap<Key, Bucket>,
+    pruned: f64,
+}
+
+impl Limiter {
+    fn new(rate: u32, per: f64, mode: Mode) -> Result<Self, String> {
+        if !(1..=MAX_RATE).contains(&rate) {
+            return Err(format!("Invalid rate {rate}, it must be from 1 to {MAX_RATE}"));
+        }
+        if !per.is_finite() || per <= 0.0 {
+            return Err(format!("Invalid period {per}, it must be more than 0 seconds"));
+        }
+        Ok(Limiter { rate, per, mode, buckets: HashMap::new(), pruned: f64::NEG_INFINITY })
+    }
+
+    /// Take `cost` from the bucket for `key`, or get how many seconds until it can be taken.
+    fn acquire(&mut self, key: Key, cost: u32, now: f64, take: bool) -> Result<Option<f64>, String> {
+        if cost > self.rate {
+            return Err(format!("Invalid cost {cost}, it can never be acquired at a rate of {}", self.rate));
+        }
+        if take && now - self.pruned >= PRUNE_INTERVAL.max(self.per) {
+            self.prune(now);
+        }
+        let (rate, per, mode) = (self.rate, self.per, self.mode);
+        let bucket = if take {
+            self.buckets.entry(key).or_insert_with(|| Bucket::new(mode, rate, now))
+        } else {
+            match self.buckets.get_mut(&key) {
+                Some(bucket) => bucket,
+                // a key with no bucket has a full one, and the cost is at most the rate
+                None => return Ok(None),
+            }
+        };
+        bucket.refresh(rate, per, now);
+        let wait = bucket.wait(rate, per, cost, now);
+        if take && wait.is_none() {
+            bucket.take(cost, now);
+        }
+        Ok(wait)
+    }
+
+    fn remaining(&mut self, key: &Key, now: f64) -> u32 {
+        let Some(bucket) = self.buckets.get_mut(key) else {
+            return self.rate;
+        };
+        bucket.refresh(self.rate, self.per, now);
+        match bucket {
+            Bucket::Tokens { tokens, .. } => (*tokens + EPSILON).floor() as u32,
+            Bucket::Window { used, .. } => self.rate - *used,
+        }
+    }
+
+    /// Forget the keys that would be back to a full bucket by `now` anyway, and how many there were.
+    fn prune(&mut self, now: f64) -> usize {
+        let before = self.buckets.len();
+        let (rate, per) = (f64::from(self.rate), self.per);
+        self.buckets.retain(|_, bucket| match bucket {
+            Bucket::Tokens { tokens, updated } => *tokens + (now - *updated).max(0.0) * rate / per < rate - EPSILON,
+            Bucket::Window { acquired, .. } => acquired.back().is_some_and(|(time, _)| *time > now - per),
+        });
+        self.pruned = now;
+        before - self.buckets.len()
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Limiter {
+    #[new]
+    #[pyo3(signature = (rate, per, mode=Mode::TokenBucket))]
+    fn py_new(rate: u32, per: f64, mode: Mode) -> PyResult<Self> {
+        Limiter::new(rate, per, mode).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Take from the bucket for a key, or get how many seconds until it can be taken.
+    #[pyo3(signature = (key, cost=1, now=None))]
+    fn try_acquire(&mut self, key: Key, cost: u32, now: Option<f64>) -> PyResult<Option<f64>> {
+        self.acquire(key, cost, now.unwrap_or_else(self::now), true).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// How many seconds until a key could take from its bucket, without taking anything.
+    #[pyo3(signature = (key, cost=1, now=None))]
+    fn retry_after(&mut self, key: Key, cost: u32, now: Option<f64>) -> PyResult<Option<f64>> {
+        self.acquire(key, cost, now.unwrap_or_else(self::now), false).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(name = "remaining", signature = (key, now=None))]
+    fn py_remaining(&mut self, key: Key, now: Option<f64>) -> u32 {
+        self.remaining(&key, now.unwrap_or_else(self::now))
+    }
+
+    /// Empty the bucket for a key, so it starts over.
+    fn reset(&mut self, key: Key) {
+        self.buckets.remove(&key);
+    }
+
+    #[pyo3(name = "prune", signature = (now=None))]
+    fn py_prune(&mut self, now: Option<f64>) -> usize {
+        self.prune(now.unwrap_or_else(self::now))
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn per(&self) -> f64 {
+        self.per
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    fn __len__(&self) -> usize {
+        self.buckets.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Limiter(rate={}, per={}, mode={:?}, keys={})", self.rate, self.per, self.mode, self.buckets.len())
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based rate limits for many keys at once";
+
+pub(crate) fn register_ratelimit(py: Python, m: &PyModule) -> PyResult<()> {
+    let ratelimit = PyModule::new(py, "_ratelimit")?;
+    ratelimit.add_class::<Mode>()?;
+    ratelimit.add_class::<Limiter>()?;
+    ratelimit.add("__doc__", DOCSTRING)?;
+    m.add_submodule(ratelimit)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(rate: u32, per: f64, mode: Mode) -> Limiter {
+        Limiter::new(rate, per, mode).expect("Expected a valid limiter")
+    }
+
+    fn user(id: u64) -> Key {
+        Key::Id(id)
+    }
+
+    fn close(actual: Option<f64>, expected: f64) -> bool {
+        actual.is_some_and(|actual| (actual - expected).abs() < 1e-6)
+    }
+
+    #[test]
+    fn token_bucket() {
+        let mut limiter = limiter(3, 30.0, Mode::TokenBucket);
+        for _ in 0..3 {
+            assert_eq!(limiter.acquire(user(1), 1, 100.0, true), Ok(None));
+        }
+        // a token comes back every 10 seconds
+        let wait = limiter.acquire(user(1), 1, 100.0, true).expect("Expected a valid cost");
+        assert!(close(wait, 10.0), "Got {wait:?}");
+        assert!(close(limiter.acquire(user(1), 1, 104.0, true).expect("Expected a valid cost"), 6.0));
+        assert_eq!(limiter.acquire(user(1), 1, 110.0, true), Ok(None));
+        assert!(close(limiter.acquire(user(1), 2, 110.0, true).expect("Expected a valid cost"), 20.0));
+        // other keys have buckets of their own
+        assert_eq!(limiter.acquire(user(2), 3, 110.0, true), Ok(None));
+        assert_eq!(limiter.remaining(&user(1), 140.0), 3);
+    }
+
+    #[test]
+    fn sliding_window() {
+        let mut limiter = limiter(2, 10.0, Mode::SlidingWindow);
+        assert_eq!(limiter.acquire(user(1), 1, 100.0, true), Ok(None));
+        assert_eq!(limiter.acquire(user(1), 1, 105.0, true), Ok(None));
+        assert!(close(limiter.acquire(user(1), 1, 106.0, true).expect("Expected a valid cost"), 4.0));
+        // the first one leaves the window after 10 seconds, but the second is still in it
+        assert_eq!(limiter.acquire(user(1), 1, 110.0, true), Ok(None));
+        assert!(close(limiter.acquire(user(1), 2, 111.0, true).expect("Expected a valid cost"), 9.0));
+        assert_eq!(limiter.remaining(&user(1), 111.0), 0);
+        assert_eq!(limiter.remaining(&user(1), 116.0), 1);
+    }
+
+    #[test]
+    fn peeking() {
+        let mut limiter = limiter(1, 5.0, Mode::TokenBucket);
+        assert_eq!(limiter.acquire(user(1), 1, 0.0, false), Ok(None));
+        assert_eq!(limiter.acquire(user(1), 1, 0.0, false), Ok(None));
+        assert!(limiter.buckets.is_empty());
+        assert_eq!(limiter.acquire(user(1), 1, 0.0, true), Ok(None));
+        assert!(close(limiter.acquire(user(1), 1, 1.0, false).expect("Expected a valid cost"), 4.0));
+        assert_eq!(limiter.remaining(&user(1), 1.0), 0);
+    }
+
+    #[test]
+    fn keys() {
+        let mut limiter = limiter(1, 60.0, Mode::SlidingWindow);
+        let command = |user: u64, guild: u64| Key::Parts(vec![Key::Id(user), Key::Id(guild), Key::Name("roll".to_string())]);
+        assert_eq!(limiter.acquire(command(1, 10), 1, 0.0, true), Ok(None));
+        assert_eq!(limiter.acquire(command(1, 11), 1, 0.0, true), Ok(None));
+        assert!(limiter.acquire(command(1, 10), 1, 0.0, true).expect("Expected a valid cost").is_some());
+        limiter.reset(command(1, 10));
+        assert_eq!(limiter.acquire(command(1, 10), 1, 0.0, true), Ok(None));
+    }
+
+    #[test]
+    fn pruning() {
+        let mut limiter = limiter(2, 10.0, Mode::TokenBucket);
+        for id in 0..1000 {
+            limiter.acquire(user(id), 1, 0.0, true).expect("Expected a valid cost");
+        }
+        limiter.acquire(user(0), 1, 4.0, true).expect("Expected a valid cost");
+        assert_eq!(limiter.prune(4.9), 0);
+        // one token takes 5 seconds to come back, and the first user took two
+        assert_eq!(limiter.prune(5.0), 999);
+        assert_eq!(limiter.buckets.len(), 1);
+        // acquiring prunes by itself once in a while
+        limiter.acquire(user(5), 1, 100.0, true).expect("Expected a valid cost");
+        assert_eq!(limiter.buckets.len(), 1);
+        let mut windows = self::limiter(1, 10.0, Mode::SlidingWindow);
+        windows.acquire(user(1), 1, 0.0, true).expect("Expected a valid cost");
+        assert_eq!(windows.prune(9.0), 0);
+        assert_eq!(windows.prune(10.0), 1);
+    }
+
+    #[test]
+    fn clock_going_backwards() {
+        let mut limiter = limiter(1, 10.0, Mode::TokenBucket);
+        assert_eq!(limiter.acquire(user(1), 1, 100.0, true), Ok(None));
+        assert!(close(limiter.acquire(user(1), 1, 90.0, true).expect("Expected a valid cost"), 10.0));
+    }
+
+    #[test]
+    fn clock() {
+        let system = SystemTime::now().duration_since(UNIX_EPOCH).expect("Expected a clock after 1970").as_secs_f64();
+        let first = now();
+        assert!((first - system).abs() < 1.0, "Got {first} for {system}");
+        assert!(now() >= first);
+    }
+
+    #[test]
+    fn rejects() {
+        Limiter::new(0, 1.0, Mode::TokenBucket).expect_err("Expected a rate of 0 to be rejected");
+        Limiter::new(MAX_RATE + 1, 1.0, Mode::TokenBucket).expect_err("Expected a huge rate to be rejected");
+        Limiter::new(1, 0.0, Mode::TokenBucket).expect_err("Expected a period of 0 to be rejected");
+        Limiter::new(1, f64::NAN, Mode::SlidingWindow).expect_err("Expected a NaN period to be rejected");
+        limiter(2, 1.0, Mode::TokenBucket).acquire(user(1), 3, 0.0, true).expect_err("Expected a cost over the rate to be rejected");
+    }
+}
+// COV_EXCL_STOP
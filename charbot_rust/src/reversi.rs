@@ -0,0 +1,309 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod board; // COV_EXCL_LINE
+mod search;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::persist::{self, Persist};
+use crate::reversi::board::{parse_square, square_name, Board, Disc, SIZE};
+use crate::metrics;
+
+/// How many moves ahead the computer looks by default, up to about 25ms a move.
+pub(crate) const DEFAULT_DEPTH: u8 = 4;
+/// The most moves ahead the computer can look, so a move never takes more than about 50ms.
+///
+/// There are many more moves to look through than in connect four, so looking deeper gets slow much sooner.
+const MAX_DEPTH: u8 = 5;
+
+// COV_EXCL_START
+#[pyclass(module = "reversi")]
+#[derive(Debug)]
+struct Game {
+    board: Board,
+    human: Disc,
+    depth: u8,
+}
+// COV_EXCL_STOP
+
+impl Game {
+    fn new(depth: u8, human_first: bool) -> Result<Self, String> {
+        if !(1..=MAX_DEPTH).contains(&depth) {
+            return Err(format!("Invalid depth {depth}, it must be from 1 to {MAX_DEPTH}"));
+        }
+        // black always goes first
+        let human = if human_first { Disc::Black } else { Disc::White };
+        let mut game = Game {
+            board: Board::new(),
+            human,
+            depth,
+        };
+        game.computer_moves();
+        Ok(game)
+    }
+
+    /// Make the computer's moves until it is the player's turn, several if the player has to pass.
+    fn computer_moves(&mut self) -> Vec<String> {
+        let mut played = Vec::new();
+        while self.board.to_move != self.human {
+            let Some((square, flips)) = search::best_move(&self.board, self.depth) else {
+                break;
+            };
+            self.board.play(square, &flips);
+            played.push(square_name(square));
+        }
+        played
+    }
+
+    /// Put the player's disc on `square`, then make the computer's moves.
+    fn make_move(&mut self, square: &str) -> Result<Vec<String>, String> {
+        if self.board.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        let square = parse_square(square)?;
+        let flips = self.board.flips(square, self.human);
+        if flips.is_empty() {
+            return Err(format!("{} doesn't flip any discs", square_name(square)));
+        }
+        self.board.play(square, &flips);
+        Ok(self.computer_moves())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    /// The discs, with row 0 at the top.
+    cells: [[Option<Disc>; SIZE]; SIZE],
+    to_move: Disc,
+    human: Disc,
+    depth: u8,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "reversi";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { cells: self.board.cells, to_move: self.board.to_move, human: self.human, depth: self.depth }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        if !(1..=MAX_DEPTH).contains(&state.depth) {
+            return Err(format!("Invalid depth {}, it must be from 1 to {MAX_DEPTH}", state.depth));
+        }
+        let board = Board::from_cells(state.cells, state.to_move)?;
+        // the computer always moves straight after the player, so a saved game is never waiting on it
+        if !board.is_over() && board.to_move != state.human {
+            return Err("It can't be the computer's turn in a saved game".to_string());
+        }
+        Ok(Game { board, human: state.human, depth: state.depth })
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Game {
+    #[new]
+    #[pyo3(signature = (depth=DEFAULT_DEPTH, human_first=true))]
+    fn __new__(depth: u8, human_first: bool) -> PyResult<Self> { // COV_EXCL_LINE
+        Self::new(depth, human_first).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The discs, as rows from the top, with None for the empty squares.
+    #[getter]
+    fn board(&self) -> Vec<Vec<Option<Disc>>> {
+        self.board.cells.iter().map(|row| row.to_vec()).collect()
+    }
+
+    #[getter]
+    fn player(&self) -> Disc {
+        self.human
+    }
+
+    fn play(&mut self, py: Python, square: &str) -> PyResult<Vec<String>> {
+        metrics::observe("reversi.play", || {
+            // the computer's replies are the slow part, other threads can run meanwhile
+            py.allow_threads(|| self.make_move(square)).map_err(PyErr::new::<PyValueError, _>)
+        })
+    }
+
+    /// The squares the player can go on, with the discs each would flip, none once the game is over.
+    fn legal_moves(&self) -> Vec<(String, Vec<String>)> {
+        if self.board.to_move != self.human {
+            return Vec::new();
+        }
+        self.board
+            .legal_moves(self.human)
+            .into_iter()
+            .map(|(square, flips)| (square_name(square), flips.into_iter().map(square_name).collect()))
+            .collect()
+    }
+
+    /// The black and white discs on the board.
+    #[getter]
+    fn score(&self) -> (usize, usize) {
+        (self.board.count(Disc::Black), self.board.count(Disc::White))
+    }
+
+    /// The black and white discs at the end, with the empty squares going to the winner, or None before then.
+    fn final_score(&self) -> Option<(usize, usize)> {
+        self.board.is_over().then(|| self.board.final_score())
+    }
+
+    fn winner(&self) -> Option<Disc> {
+        self.board.winner()
+    }
+
+    fn is_draw(&self) -> bool {
+        self.board.is_draw()
+    }
+
+    fn is_over(&self) -> bool {
+        self.board.is_over()
+    }
+
+    fn has_player_won(&self) -> bool {
+        self.board.winner() == Some(self.human)
+    }
+
+    fn has_player_lost(&self) -> bool {
+        self.board.winner() == Some(self.human.swap())
+    }
+
+    fn to_emoji(&self) -> String {
+        self.board.to_emoji()
+    }
+
+    fn __str__(&self) -> String {
+        self.board.to_emoji()
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of reversi";
+
+pub(crate) fn register_reversi(py: Python, m: &PyModule) -> PyResult<()> {
+    let reversi = PyModule::new(py, "_reversi")?;
+    reversi.add_class::<Game>()?;
+    reversi.add_class::<Disc>()?;
+    reversi.add("__doc__", DOCSTRING)?;
+    m.add_submodule(reversi)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(discs: &[((usize, usize), Disc)], human: Disc) -> Game {
+        let mut board = Board { cells: [[None; SIZE]; SIZE], to_move: human };
+        for &((row, column), disc) in discs {
+            board.cells[row][column] = Some(disc);
+        }
+        Game { board, human, depth: 2 }
+    }
+
+    #[test]
+    fn creator() {
+        Game::new(0, true).expect_err("Expected error");
+        Game::new(MAX_DEPTH + 1, true).expect_err("Expected error");
+        let human_first = Game::new(DEFAULT_DEPTH, true).expect("Failed to create game");
+        assert_eq!(human_first.player(), Disc::Black);
+        assert_eq!(human_first.score(), (2, 2));
+        assert_eq!(human_first.legal_moves().len(), 4);
+        let computer_first = Game::new(2, false).expect("Failed to create game");
+        assert_eq!(computer_first.player(), Disc::White);
+        assert_eq!(computer_first.board.to_move, Disc::White);
+        assert_eq!(computer_first.score(), (4, 1));
+    }
+
+    #[test]
+    fn play() {
+        let mut game = Game::new(2, true).expect("Failed to create game");
+        assert_eq!(game.legal_moves()[0], ("d3".to_string(), vec!["d4".to_string()]));
+        let replies = game.make_move("d3").expect("Failed to play");
+        assert_eq!(replies.len(), 1);
+        assert_eq!(game.board()[2][3], Some(Disc::Black));
+        assert_eq!(game.score().0 + game.score().1, 6);
+        game.make_move("d3").expect_err("Expected the taken square to be rejected");
+        game.make_move("a1").expect_err("Expected a square that flips nothing to be rejected");
+        game.make_move("nonsense").expect_err("Expected the square to be rejected");
+    }
+
+    #[test]
+    fn computer_plays_on() {
+        // black can never flip the white discs in the corners, so has to pass while white takes both b discs
+        let mut game = game(&[((0, 0), Disc::White), ((0, 1), Disc::Black), ((7, 0), Disc::White), ((7, 1), Disc::Black)], Disc::Black);
+        game.board.to_move = Disc::White;
+        let mut replies = game.computer_moves();
+        replies.sort();
+        assert_eq!(replies, vec!["c1".to_string(), "c8".to_string()]);
+        assert!(game.is_over());
+        assert!(game.has_player_lost());
+        assert_eq!(game.score(), (0, 6));
+        assert_eq!(game.final_score(), Some((0, 64)));
+    }
+
+    #[test]
+    fn finished() {
+        // black on f4 flips the last white disc
+        let mut game = game(&[((3, 3), Disc::Black), ((3, 4), Disc::White)], Disc::Black);
+        assert_eq!(game.make_move("f4"), Ok(Vec::new()));
+        assert!(game.is_over());
+        assert!(!game.is_draw());
+        assert_eq!(game.winner(), Some(Disc::Black));
+        assert!(game.has_player_won());
+        assert!(!game.has_player_lost());
+        assert_eq!(game.final_score(), Some((64, 0)));
+        assert!(game.legal_moves().is_empty());
+        game.make_move("a1").expect_err("Expected the game to be over");
+        assert_eq!(game.__str__(), game.to_emoji());
+    }
+
+    #[test]
+    fn persists() {
+        let mut game = Game::new(2, false).expect("Failed to create game");
+        let opening = game.legal_moves()[0].0.clone();
+        game.make_move(&opening).expect("Failed to play");
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Game = persist::from_json(&json).expect("Failed to load");
+        assert_eq!(loaded.board, game.board);
+        assert_eq!((loaded.human, loaded.depth), (Disc::White, 2));
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        let mut loaded: Game = persist::from_bytes(&bytes).expect("Failed to load");
+        let next = game.legal_moves()[0].0.clone();
+        assert_eq!(loaded.make_move(&next), game.make_move(&next));
+        let mut state = game.save();
+        state.human = Disc::Black;
+        Game::load(state).expect_err("Expected it being the computer's turn to be rejected");
+        let mut state = game.save();
+        state.depth = MAX_DEPTH + 1;
+        Game::load(state).expect_err("Expected the depth to be rejected");
+        let mut state = game.save();
+        state.cells[4][4] = None;
+        Game::load(state).expect_err("Expected the empty middle to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,363 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+// COV_EXCL_START
+use std::fmt::{Display, Error, Formatter};
+use pyo3::{pyclass, pymethods};
+use serde::{Deserialize, Serialize};
+// COV_EXCL_STOP
+
+/// The rows of the board, and the columns.
+pub const SIZE: usize = 8;
+
+#[pyclass(module = "reversi")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Disc { // COV_EXCL_LINE
+    Black,
+    White,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Disc {
+    pub fn swap(&self) -> Self {
+        match self { // COV_EXCL_LINE
+            Self::Black => Self::White,
+            Self::White => Self::Black,
+        }
+    }
+
+    /// The emoji the disc is drawn as.
+    #[getter] // COV_EXCL_LINE
+    pub fn emoji(&self) -> &'static str {
+        match self { // COV_EXCL_LINE
+            Self::Black => "\u{26ab}",
+            Self::White => "\u{26aa}",
+        }
+    }
+}
+
+/// The emoji for a square without a disc, the green of the board.
+const EMPTY_EMOJI: &str = "\u{1f7e9}";
+/// The keycap numbers before each row, so players can see which square is which.
+const ROW_EMOJI: [&str; SIZE] = [
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+    "4\u{fe0f}\u{20e3}",
+    "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}",
+    "7\u{fe0f}\u{20e3}",
+    "8\u{fe0f}\u{20e3}",
+];
+/// The emoji letters under each column, after the square in the corner under the row numbers.
+///
+/// Letters next to each other would be shown as a flag, so they are kept apart by zero width spaces.
+const COLUMN_EMOJI: [&str; SIZE + 1] = [
+    "\u{2b1b}",
+    "\u{1f1e6}",
+    "\u{1f1e7}",
+    "\u{1f1e8}",
+    "\u{1f1e9}",
+    "\u{1f1ea}",
+    "\u{1f1eb}",
+    "\u{1f1ec}",
+    "\u{1f1ed}",
+];
+
+/// The directions discs are flipped in, as (row, column) steps.
+const DIRECTIONS: [(isize, isize); 8] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+/// A square as (row, column), with row 0 at the top and column 0 on the left.
+pub type Square = (usize, usize);
+
+/// The name of `square` in the usual notation, a letter for the column and a number for the row from the top, like `d3`.
+pub fn square_name((row, column): Square) -> String {
+    format!("{}{}", char::from(b'a' + column as u8), row + 1)
+}
+
+pub fn parse_square(name: &str) -> Result<Square, String> {
+    let invalid = || format!("Invalid square {name:?}, expected a column from a to h and a row from 1 to 8 like d3");
+    let mut chars = name.trim().chars();
+    let (Some(column @ 'a'..='h'), Some(row @ '1'..='8'), None) = (chars.next().map(|letter| letter.to_ascii_lowercase()), chars.next(), chars.next()) else {
+        return Err(invalid());
+    };
+    Ok((row as usize - '1' as usize, column as usize - 'a' as usize))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Board {
+    pub(crate) cells: [[Option<Disc>; SIZE]; SIZE],
+    /// Whose turn it is, a side with no moves is skipped so this always has a move unless the game is over.
+    pub(crate) to_move: Disc,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    pub fn new() -> Self {
+        let mut cells = [[None; SIZE]; SIZE];
+        let middle = SIZE / 2;
+        cells[middle - 1][middle - 1] = Some(Disc::White);
+        cells[middle][middle] = Some(Disc::White);
+        cells[middle - 1][middle] = Some(Disc::Black);
+        cells[middle][middle - 1] = Some(Disc::Black);
+        Board { cells, to_move: Disc::Black }
+    }
+
+    /// Rebuild a board from a save, checking it could come up in a game.
+    pub fn from_cells(cells: [[Option<Disc>; SIZE]; SIZE], to_move: Disc) -> Result<Self, String> {
+        let middle = SIZE / 2;
+        if cells[middle - 1..=middle].iter().any(|row| row[middle - 1..=middle].iter().any(Option::is_none)) {
+            return Err("The four squares in the middle start with discs, so they are never empty".to_string());
+        }
+        let board = Board { cells, to_move };
+        if !board.is_over() && board.legal_moves(to_move).is_empty() {
+            return Err(format!("{to_move:?} has no moves, so it can't be their turn"));
+        }
+        Ok(board)
+    }
+
+    fn get(&self, row: isize, column: isize) -> Option<Disc> {
+        if (0..SIZE as isize).contains(&row) && (0..SIZE as isize).contains(&column) {
+            self.cells[row as usize][column as usize]
+        } else {
+            None
+        }
+    }
+
+    /// The discs `disc` would flip by going on `square`, empty if it can't go there.
+    pub fn flips(&self, (row, column): Square, disc: Disc) -> Vec<Square> {
+        if self.cells[row][column].is_some() {
+            return Vec::new();
+        }
+        let mut flips = Vec::new();
+        for (row_step, column_step) in DIRECTIONS {
+            let line = (1..SIZE as isize)
+                .map(|distance| (row as isize + distance * row_step, column as isize + distance * column_step))
+                .take_while(|(row, column)| self.get(*row, *column) == Some(disc.swap()))
+                .collect::<Vec<_>>();
+            // the line only flips if it ends in one of the player's own discs
+            let end = (row as isize + (line.len() as isize + 1) * row_step, column as isize + (line.len() as isize + 1) * column_step);
+            if !line.is_empty() && self.get(end.0, end.1) == Some(disc) {
+                flips.extend(line.into_iter().map(|(row, column)| (row as usize, column as usize)));
+            }
+        }
+        flips
+    }
+
+    /// Every square `disc` can go on, in order from the top left, with the discs it would flip.
+    pub fn legal_moves(&self, disc: Disc) -> Vec<(Square, Vec<Square>)> {
+        (0..SIZE)
+            .flat_map(|row| (0..SIZE).map(move |column| (row, column)))
+            .filter_map(|square| {
+                let flips = self.flips(square, disc);
+                (!flips.is_empty()).then_some((square, flips))
+            })
+            .collect()
+    }
+
+    fn has_moves(&self, disc: Disc) -> bool {
+        (0..SIZE).any(|row| (0..SIZE).any(|column| !self.flips((row, column), disc).is_empty()))
+    }
+
+    /// Put the disc of the side to move on `square`, flipping `flips`, which have to be what [`Board::flips`] gave.
+    ///
+    /// The turn then passes to the other side, unless they have no moves and this side still does.
+    pub fn play(&mut self, (row, column): Square, flips: &[Square]) {
+        let disc = self.to_move;
+        self.cells[row][column] = Some(disc);
+        for (row, column) in flips {
+            self.cells[*row][*column] = Some(disc);
+        }
+        if self.has_moves(disc.swap()) || !self.has_moves(disc) {
+            self.to_move = disc.swap();
+        }
+    }
+
+    /// Whether neither side can move, which happens when the board is full but can happen sooner.
+    pub fn is_over(&self) -> bool {
+        !self.has_moves(Disc::Black) && !self.has_moves(Disc::White)
+    }
+
+    pub fn count(&self, disc: Disc) -> usize {
+        self.cells.iter().flatten().filter(|cell| **cell == Some(disc)).count()
+    }
+
+    /// The side with more discs once the game is over.
+    pub fn winner(&self) -> Option<Disc> {
+        if !self.is_over() {
+            return None;
+        }
+        match self.count(Disc::Black).cmp(&self.count(Disc::White)) {
+            std::cmp::Ordering::Greater => Some(Disc::Black),
+            std::cmp::Ordering::Less => Some(Disc::White),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.is_over() && self.winner().is_none()
+    }
+
+    /// The black and white discs at the end of the game, with the empty squares counted for the winner as in tournaments.
+    pub fn final_score(&self) -> (usize, usize) {
+        let (black, white) = (self.count(Disc::Black), self.count(Disc::White));
+        let empty = SIZE * SIZE - black - white;
+        match self.winner() {
+            Some(Disc::Black) => (black + empty, white),
+            Some(Disc::White) => (black, white + empty),
+            // a draw shares the empty squares
+            None => (black + empty / 2, white + empty / 2),
+        }
+    }
+
+    /// The board as rows of emoji from the top, with the row numbers before them and the column letters underneath.
+    pub fn to_emoji(&self) -> String {
+        let mut lines = self
+            .cells
+            .iter()
+            .zip(ROW_EMOJI)
+            .map(|(row, number)| number.to_string() + &row.iter().map(|cell| cell.map_or(EMPTY_EMOJI, |disc| disc.emoji())).collect::<String>())
+            .collect::<Vec<_>>();
+        lines.push(COLUMN_EMOJI.join("\u{200b}"));
+        lines.join("\n")
+    }
+}
+
+// COV_EXCL_START
+impl Display for Board {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        formatter.write_str(&self.to_emoji())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(moves: &[(Square, Vec<Square>)]) -> Vec<String> {
+        moves.iter().map(|(square, _)| square_name(*square)).collect()
+    }
+
+    #[test]
+    fn disc() {
+        assert_eq!(Disc::Black.swap(), Disc::White);
+        assert_eq!(Disc::White.swap(), Disc::Black);
+        assert_eq!(Disc::Black.emoji(), "⚫");
+    }
+
+    #[test]
+    fn squares() {
+        assert_eq!(parse_square("d3"), Ok((2, 3)));
+        assert_eq!(parse_square(" H8 "), Ok((7, 7)));
+        assert_eq!(square_name((0, 0)), "a1");
+        for name in ["", "d", "i1", "a0", "a9", "d33", "3d"] {
+            parse_square(name).expect_err("Expected the square to be rejected");
+        }
+    }
+
+    #[test]
+    fn opening() {
+        let board = Board::new();
+        let moves = board.legal_moves(Disc::Black);
+        assert_eq!(names(&moves), vec!["d3", "c4", "f5", "e6"]);
+        assert_eq!(moves[0].1, vec![(3, 3)]);
+        assert_eq!((board.count(Disc::Black), board.count(Disc::White)), (2, 2));
+        assert!(!board.is_over());
+        assert_eq!(board.winner(), None);
+    }
+
+    #[test]
+    fn flipping() {
+        let mut board = Board::new();
+        let flips = board.flips((2, 3), Disc::Black);
+        board.play((2, 3), &flips);
+        assert_eq!(board.cells[3][3], Some(Disc::Black));
+        assert_eq!((board.count(Disc::Black), board.count(Disc::White)), (4, 1));
+        assert_eq!(board.to_move, Disc::White);
+        // white on c3 flips d4 back along the diagonal to e5
+        assert_eq!(board.flips((2, 2), Disc::White), vec![(3, 3)]);
+        assert!(board.flips((0, 0), Disc::White).is_empty());
+        assert!(board.flips((3, 3), Disc::White).is_empty());
+    }
+
+    #[test]
+    fn several_directions() {
+        let mut cells = [[None; SIZE]; SIZE];
+        for (row, column) in [(0, 2), (2, 0), (2, 4), (4, 2)] {
+            cells[row][column] = Some(Disc::Black);
+        }
+        for (row, column) in [(1, 2), (2, 1), (2, 3), (3, 2), (1, 1)] {
+            cells[row][column] = Some(Disc::White);
+        }
+        let board = Board { cells, to_move: Disc::Black };
+        let mut flips = board.flips((2, 2), Disc::Black);
+        flips.sort_unstable();
+        // b2 isn't flipped, there's no black disc behind it
+        assert_eq!(flips, vec![(1, 2), (2, 1), (2, 3), (3, 2)]);
+        assert!(board.flips((1, 2), Disc::Black).is_empty(), "The square is taken");
+    }
+
+    #[test]
+    fn passing() {
+        // white can't go anywhere after black takes h1, so black goes again
+        let mut cells = [[None; SIZE]; SIZE];
+        cells[0][5] = Some(Disc::Black);
+        cells[0][6] = Some(Disc::White);
+        cells[3][0] = Some(Disc::Black);
+        cells[3][1] = Some(Disc::White);
+        let mut board = Board { cells, to_move: Disc::Black };
+        let flips = board.flips((0, 7), Disc::Black);
+        board.play((0, 7), &flips);
+        assert_eq!(board.to_move, Disc::Black);
+        assert!(board.legal_moves(Disc::White).is_empty());
+        let flips = board.flips((3, 2), Disc::Black);
+        board.play((3, 2), &flips);
+        assert!(board.is_over());
+        assert_eq!(board.winner(), Some(Disc::Black));
+        assert_eq!(board.final_score(), (64, 0));
+    }
+
+    #[test]
+    fn draws() {
+        let mut cells = [[Some(Disc::Black); SIZE]; SIZE];
+        for row in cells.iter_mut().take(SIZE / 2) {
+            *row = [Some(Disc::White); SIZE];
+        }
+        let board = Board { cells, to_move: Disc::Black };
+        assert!(board.is_over());
+        assert!(board.is_draw());
+        assert_eq!(board.final_score(), (32, 32));
+    }
+
+    #[test]
+    fn from_cells() {
+        let board = Board::new();
+        assert_eq!(Board::from_cells(board.cells, Disc::Black), Ok(board.clone()));
+        let mut cells = board.cells;
+        cells[3][3] = None;
+        Board::from_cells(cells, Disc::Black).expect_err("Expected the empty middle to be rejected");
+        let mut cells = [[Some(Disc::White); SIZE]; SIZE];
+        cells[0][0] = None;
+        cells[0][1] = Some(Disc::Black);
+        // black can't go on a1, but white can
+        Board::from_cells(cells, Disc::Black).expect_err("Expected black having no moves to be rejected");
+        Board::from_cells(cells, Disc::White).expect("Expected white to be able to move");
+    }
+
+    #[test]
+    fn emoji() {
+        let board = Board::new();
+        let emoji = board.to_emoji();
+        let lines = emoji.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), SIZE + 1);
+        assert_eq!(lines[3], format!("4\u{fe0f}\u{20e3}{}⚪⚫{}", EMPTY_EMOJI.repeat(3), EMPTY_EMOJI.repeat(3)));
+        assert!(lines[SIZE].starts_with("\u{2b1b}\u{200b}\u{1f1e6}\u{200b}"));
+        assert_eq!(board.to_string(), emoji);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,148 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::reversi::board::{Board, Disc, Square, SIZE};
+
+/// The score of a win, more than any position can get, a bigger win scores a little higher.
+const WIN: i32 = 1_000_000;
+/// More than any score, for the bounds before anything is searched.
+const INFINITY: i32 = 2 * WIN;
+/// What a disc on each square is worth, from the usual tables.
+///
+/// Corners can never be flipped so they are worth the most, and the squares next to them are worth
+/// the least since going there lets the other side into the corner.
+const WEIGHTS: [[i32; SIZE]; SIZE] = [
+    [100, -20, 10, 5, 5, 10, -20, 100],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [10, -2, -1, -1, -1, -1, -2, 10],
+    [5, -2, -1, -1, -1, -1, -2, 5],
+    [5, -2, -1, -1, -1, -1, -2, 5],
+    [10, -2, -1, -1, -1, -1, -2, 10],
+    [-20, -50, -2, -2, -2, -2, -50, -20],
+    [100, -20, 10, 5, 5, 10, -20, 100],
+];
+
+/// The best square for the side to move, looking `depth` moves ahead, or `None` if the game is over.
+///
+/// This is negamax with alpha-beta pruning, like the connect four and checkers searches, positions
+/// where the game isn't over by then are scored by [`evaluate`]. A side that has to pass doesn't
+/// count as a move, the same side just searches on.
+pub fn best_move(board: &Board, depth: u8) -> Option<(Square, Vec<Square>)> {
+    let mut best = None;
+    let mut alpha = -INFINITY;
+    for (square, flips) in board.legal_moves(board.to_move) {
+        let score = reply(board, square, &flips, depth.saturating_sub(1), alpha, INFINITY);
+        if score > alpha {
+            alpha = score;
+            best = Some((square, flips));
+        }
+    }
+    best
+}
+
+/// The score for the side to move of going on `square`, looking `depth` moves further.
+fn reply(board: &Board, square: Square, flips: &[Square], depth: u8, alpha: i32, beta: i32) -> i32 {
+    let mut next = board.clone();
+    next.play(square, flips);
+    if next.to_move == board.to_move {
+        // the other side had to pass, or the game is over
+        negamax(&next, depth, alpha, beta)
+    } else {
+        -negamax(&next, depth, -beta, -alpha)
+    }
+}
+
+/// The score of `board` for the side to move, as good as it gets for them within `depth` moves.
+fn negamax(board: &Board, depth: u8, mut alpha: i32, beta: i32) -> i32 {
+    let moves = board.legal_moves(board.to_move);
+    if moves.is_empty() {
+        // nobody can move, so the game is over and whoever has more discs won
+        let margin = board.count(board.to_move) as i32 - board.count(board.to_move.swap()) as i32;
+        return WIN * margin.signum() + margin;
+    }
+    if depth == 0 {
+        return evaluate(board);
+    }
+    for (square, flips) in moves {
+        let score = reply(board, square, &flips, depth - 1, alpha, beta);
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+/// A guess at how good `board` is for the side to move, from the squares each side has.
+fn evaluate(board: &Board) -> i32 {
+    board
+        .cells
+        .iter()
+        .zip(WEIGHTS)
+        .flat_map(|(row, weights)| row.iter().zip(weights))
+        .filter_map(|(cell, weight)| {
+            let disc: Disc = (*cell)?;
+            Some(if disc == board.to_move { weight } else { -weight })
+        })
+        .sum()
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn board(discs: &[(Square, Disc)], to_move: Disc) -> Board {
+        let mut board = Board { cells: [[None; SIZE]; SIZE], to_move };
+        for &((row, column), disc) in discs {
+            board.cells[row][column] = Some(disc);
+        }
+        board
+    }
+
+    #[test]
+    fn takes_the_corner() {
+        // black can take the a1 corner or go on f6
+        let board = board(&[((0, 1), Disc::White), ((0, 2), Disc::Black), ((4, 4), Disc::White), ((3, 3), Disc::Black)], Disc::Black);
+        assert_eq!(board.legal_moves(Disc::Black).len(), 2);
+        for depth in [1, 3] {
+            let (square, _) = best_move(&board, depth).expect("Expected a move");
+            assert_eq!(square, (0, 0));
+        }
+    }
+
+    #[test]
+    fn wins_outright() {
+        // f4 flips both white discs, which wins, f6 only flips e5
+        let board = board(&[((3, 3), Disc::Black), ((5, 3), Disc::Black), ((3, 4), Disc::White), ((4, 4), Disc::White)], Disc::Black);
+        assert_eq!(board.legal_moves(Disc::Black).len(), 2);
+        let (square, flips) = best_move(&board, 1).expect("Expected a move");
+        assert_eq!((square, flips), ((3, 5), vec![(3, 4), (4, 4)]));
+    }
+
+    #[test]
+    fn no_moves() {
+        let board = board(&[((0, 0), Disc::White)], Disc::Black);
+        assert_eq!(best_move(&board, 4), None);
+    }
+
+    #[test]
+    fn evaluate_is_symmetric() {
+        let mut board = board(&[((0, 0), Disc::Black), ((1, 1), Disc::White), ((3, 3), Disc::White)], Disc::Black);
+        let black = evaluate(&board);
+        assert_eq!(black, 100 + 50 + 1);
+        board.to_move = Disc::White;
+        assert_eq!(evaluate(&board), -black);
+        assert_eq!(evaluate(&Board::new()), 0);
+    }
+
+    #[test]
+    fn default_depth_is_fast() {
+        let start = Instant::now();
+        best_move(&Board::new(), crate::reversi::DEFAULT_DEPTH);
+        // the start only has four moves, so this catches a default that's too deep rather than a slow midgame
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,462 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod describe; // COV_EXCL_LINE
+mod recurrence;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::cooldowns::{self, tz::{self, Zone}};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// The most occurrences that can be asked for at once.
+const MAX_COUNT: usize = 1000;
+/// The days in each month of a leap year, for checking a schedule can happen at all.
+const MONTH_DAYS: [u32; 12] = [31, 29, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+
+/// The values one field of a cron expression allows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Field {
+    /// Bit `n` is set when the field allows `n`.
+    values: u64,
+    /// Whether the field was written starting with `*` or `?`, which changes how the day fields combine.
+    star: bool,
+}
+
+impl Field {
+    fn has(&self, value: u32) -> bool {
+        self.values >> value & 1 == 1
+    }
+
+    /// The first value the field allows that is `value` or more.
+    fn next(&self, value: u32) -> Option<u32> {
+        let rest = self.values.checked_shr(value)?.checked_shl(value)?;
+        (rest != 0).then(|| rest.trailing_zeros())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..64).filter(|value| self.has(*value))
+    }
+}
+
+/// What one field of a cron expression can hold.
+struct Spec {
+    name: &'static str,
+    min: u32,
+    max: u32,
+    /// The names that can be used instead of numbers, from `min`.
+    names: &'static [&'static str],
+}
+
+const SECONDS: Spec = Spec { name: "second", min: 0, max: 59, names: &[] };
+const MINUTES: Spec = Spec { name: "minute", min: 0, max: 59, names: &[] };
+const HOURS: Spec = Spec { name: "hour", min: 0, max: 23, names: &[] };
+const DAYS: Spec = Spec { name: "day of the month", min: 1, max: 31, names: &[] };
+const MONTHS: Spec = Spec {
+    name: "month",
+    min: 1,
+    max: 12,
+    names: &["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"],
+};
+// 7 is sunday too, it is folded into 0 once the field is read
+const WEEKDAYS: Spec = Spec { name: "day of the week", min: 0, max: 7, names: &["sun", "mon", "tue", "wed", "thu", "fri", "sat"] };
+
+impl Spec {
+    /// Every value the field can hold, as a field would have them.
+    fn all(&self) -> u64 {
+        (self.min..=self.max).fold(0, |values, value| values | 1 << value)
+    }
+
+    fn value(&self, text: &str, field: &str) -> Result<u32, String> {
+        if let Some(index) = self.names.iter().position(|name| name.eq_ignore_ascii_case(text)) {
+            return Ok(self.min + index as u32);
+        }
+        text.parse().ok().filter(|value| (self.min..=self.max).contains(value)).ok_or_else(|| {
+            format!("Invalid {} {text:?} in {field:?}, it must be from {} to {}", self.name, self.min, self.max)
+        })
+    }
+
+    /// Read a field like `*/15`, `1-5`, `mon,wed,fri` or `9`.
+    fn parse(&self, field: &str) -> Result<Field, String> {
+        let mut values = 0;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => {
+                    let step = step.parse().ok().filter(|step| *step > 0).ok_or_else(|| {
+                        format!("Invalid step {step:?} in {field:?}, it must be a whole number from 1")
+                    })?;
+                    (range, Some(step))
+                }
+                None => (part, None),
+            };
+            let (low, high) = match range.split_once('-') {
+                _ if range == "*" || range == "?" => (self.min, self.max),
+                Some((low, high)) => (self.value(low, field)?, self.value(high, field)?),
+                // a single value with a step carries on to the end, like `5/15`
+                None => {
+                    let low = self.value(range, field)?;
+                    (low, if step.is_some() { self.max } else { low })
+                }
+            };
+            // ranges of weekdays can end on sunday, like `fri-sun`
+            let high = if high == 0 && low > 0 && self.max == 7 { 7 } else { high };
+            if low > high {
+                return Err(format!("Invalid range {range:?} in {field:?}, it must go from low to high"));
+            }
+            values = (low..=high).step_by(step.unwrap_or(1) as usize).fold(values, |values, value| values | 1 << value);
+        }
+        Ok(Field { values, star: field.starts_with('*') || field.starts_with('?') })
+    }
+}
+
+/// The expression each shortcut like `@daily` stands for.
+fn shortcut(name: &str) -> Option<&'static str> {
+    match name.to_ascii_lowercase().as_str() {
+        "@yearly" | "@annually" => Some("0 0 1 1 *"),
+        "@monthly" => Some("0 0 1 * *"),
+        "@weekly" => Some("0 0 * * 0"),
+        "@daily" | "@midnight" => Some("0 0 * * *"),
+        "@hourly" => Some("0 * * * *"),
+        _ => None,
+    }
+}
+
+// COV_EXCL_START
+/// A recurring schedule, read from a cron expression.
+///
+/// There are 5 fields, for the minute, hour, day of the month, month and day of the week, or 6 with
+/// the second first. When both day fields are set and neither starts with `*`, a day matching either
+/// is enough, like cron does.
+#[pyclass(module = "schedule")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cron {
+    expression: String,
+    seconds: Field,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+}
+// COV_EXCL_STOP
+
+impl Cron {
+    fn parse(expression: &str) -> Result<Cron, String> {
+        let trimmed = expression.trim();
+        let expanded = match shortcut(trimmed) {
+            Some(expanded) => expanded,
+            None if trimmed.starts_with('@') => {
+                return Err(format!(
+                    "Unknown shortcut {trimmed:?}, it must be @yearly, @annually, @monthly, @weekly, @daily, @midnight or @hourly"
+                ))
+            }
+            None => trimmed,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let (seconds, rest) = match fields.len() {
+            5 => ("0", &fields[..]),
+            6 => (fields[0], &fields[1..]),
+            count => return Err(format!("Invalid cron expression {trimmed:?}, it must have 5 or 6 fields but has {count}")),
+        };
+        let mut weekdays = WEEKDAYS.parse(rest[4])?;
+        if weekdays.has(7) {
+            weekdays.values = weekdays.values & !(1 << 7) | 1;
+        }
+        let cron = Cron {
+            expression: trimmed.to_string(),
+            seconds: SECONDS.parse(seconds)?,
+            minutes: MINUTES.parse(rest[0])?,
+            hours: HOURS.parse(rest[1])?,
+            days: DAYS.parse(rest[2])?,
+            months: MONTHS.parse(rest[3])?,
+            weekdays,
+        };
+        // with the days of the week too any month has days that match, otherwise some month must have one of the days
+        let possible = cron.either_day() || cron.months.iter().any(|month| cron.days.next(1).is_some_and(|day| day <= MONTH_DAYS[month as usize - 1]));
+        if !possible {
+            return Err(format!("Invalid cron expression {trimmed:?}, none of the months have any of the days"));
+        }
+        Ok(cron)
+    }
+
+    /// Whether a day matching either day field is enough, rather than it having to match both.
+    fn either_day(&self) -> bool {
+        !self.days.star && !self.weekdays.star
+    }
+
+    fn day_matches(&self, days: i64, day: u32) -> bool {
+        let (day_of_month, weekday) = (self.days.has(day), self.weekdays.has(tz::weekday(days) as u32));
+        if self.either_day() {
+            day_of_month || weekday
+        } else {
+            day_of_month && weekday
+        }
+    }
+
+    /// The first time after `after` the schedule happens in `zone`, going by the local time there.
+    ///
+    /// Local times the clocks skip happen when the clocks go forward, and ones they repeat happen once.
+    fn next_after(&self, zone: &Zone, after: i64) -> Option<i64> {
+        let last = tz::days_from_civil(10_000, 1, 1) * SECONDS_PER_DAY;
+        let mut local = zone.to_local(after) + 1;
+        while local < last {
+            let days = local.div_euclid(SECONDS_PER_DAY);
+            let (year, month, day) = tz::civil_from_days(days);
+            let time = local.rem_euclid(SECONDS_PER_DAY);
+            let (hour, minute, second) = ((time / 3600) as u32, (time / 60 % 60) as u32, (time % 60) as u32);
+            let midnight = days * SECONDS_PER_DAY;
+            if !self.months.has(month) {
+                let (year, month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+                local = tz::days_from_civil(year, month, 1) * SECONDS_PER_DAY;
+            } else if !self.day_matches(days, day) {
+                local = midnight + SECONDS_PER_DAY;
+            } else if !self.hours.has(hour) {
+                local = self.hours.next(hour).map_or(midnight + SECONDS_PER_DAY, |hour| midnight + i64::from(hour) * 3600);
+            } else if !self.minutes.has(minute) {
+                let hour_start = midnight + i64::from(hour) * 3600;
+                local = self.minutes.next(minute).map_or(hour_start + 3600, |minute| hour_start + i64::from(minute) * 60);
+            } else {
+                let minute_start = local - i64::from(second);
+                match self.seconds.next(second) {
+                    Some(second) => {
+                        local = minute_start + i64::from(second);
+                        let time = zone.to_utc(local);
+                        if time > after {
+                            return Some(time);
+                        }
+                        // a repeated local time that already happened the first time through
+                        local += 1;
+                    }
+                    None => local = minute_start + 60,
+                }
+            }
+        }
+        None
+    }
+
+    /// The next `count` times after `after` the schedule happens in `zone`, fewer if the calendar runs out first.
+    fn occurrences(&self, zone: &Zone, after: i64, count: usize) -> Vec<i64> {
+        let mut times = Vec::with_capacity(count);
+        let mut after = after;
+        while times.len() < count {
+            let Some(time) = self.next_after(zone, after) else { break };
+            times.push(time);
+            after = time;
+        }
+        times
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Cron {
+    #[new]
+    fn __new__(expression: &str) -> PyResult<Self> { // COV_EXCL_LINE
+        Cron::parse(expression).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The expression the schedule was read from.
+    #[getter]
+    fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The next `count` unix timestamps the schedule happens at in the time zone `tz`, after `after` or now.
+    #[pyo3(name = "next", signature = (count=1, tz="UTC", after=None))]
+    fn py_next(&self, count: usize, tz: &str, after: Option<i64>) -> PyResult<Vec<i64>> {
+        if count > MAX_COUNT {
+            return Err(PyValueError::new_err(format!("Invalid count {count}, it must be at most {MAX_COUNT}")));
+        }
+        let zone = cooldowns::zone(tz).map_err(PyErr::new::<PyValueError, _>)?;
+        let after = cooldowns::check(after.unwrap_or_else(cooldowns::now)).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(self.occurrences(&zone, after, count))
+    }
+
+    /// A sentence saying when the schedule happens, in `locale`.
+    #[pyo3(signature = (locale="en-US"))]
+    fn describe(&self, locale: &str) -> PyResult<String> {
+        describe::sentence(self, locale).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Cron({:?})", self.expression)
+    }
+
+    fn __str__(&self) -> String {
+        self.expression.clone()
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based cron schedules for recurring announcements, and recurring events";
+
+pub(crate) fn register_schedule(py: Python, m: &PyModule) -> PyResult<()> {
+    let schedule = PyModule::new(py, "_schedule")?;
+    schedule.add_class::<Cron>()?;
+    schedule.add_class::<recurrence::Recurrence>()?;
+    schedule.add("__doc__", DOCSTRING)?;
+    m.add_submodule(schedule)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const HOUR: i64 = 3600;
+
+    fn utc(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> i64 {
+        tz::days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * HOUR + minute * 60
+    }
+
+    fn next(expression: &str, zone: &Zone, after: i64, count: usize) -> Vec<i64> {
+        Cron::parse(expression).expect("Valid expression").occurrences(zone, after, count)
+    }
+
+    #[test]
+    fn fields() {
+        let cron = Cron::parse("*/15 9-17 1,15 jan-MAR mon-fri").expect("Valid expression");
+        assert_eq!(cron.seconds.iter().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(cron.minutes.iter().collect::<Vec<_>>(), vec![0, 15, 30, 45]);
+        assert_eq!(cron.hours.iter().collect::<Vec<_>>(), (9..=17).collect::<Vec<_>>());
+        assert_eq!(cron.days.iter().collect::<Vec<_>>(), vec![1, 15]);
+        assert_eq!(cron.months.iter().collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(cron.weekdays.iter().collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+        assert!(cron.either_day());
+    }
+
+    #[parameterized(
+        seconds_first = {"30 */5 * * * *", vec![30], vec![0, 5, 10, 15, 20, 25, 30, 35, 40, 45, 50, 55]},
+        offset_step = {"5/20 * * * *", vec![0], vec![5, 25, 45]},
+        stepped_range = {"10-20/5 * * * *", vec![0], vec![10, 15, 20]},
+        list = {"0,59 * * * *", vec![0], vec![0, 59]},
+    )]
+    fn seconds_and_minutes(expression: &str, seconds: Vec<u32>, minutes: Vec<u32>) {
+        let cron = Cron::parse(expression).expect("Valid expression");
+        assert_eq!(cron.seconds.iter().collect::<Vec<_>>(), seconds);
+        assert_eq!(cron.minutes.iter().collect::<Vec<_>>(), minutes);
+    }
+
+    #[parameterized(
+        seven = {"0 0 * * 7", vec![0]},
+        to_sunday = {"0 0 * * fri-sun", vec![0, 5, 6]},
+        to_seven = {"0 0 * * 5-7", vec![0, 5, 6]},
+        every = {"0 0 * * *", vec![0, 1, 2, 3, 4, 5, 6]},
+    )]
+    fn sundays(expression: &str, weekdays: Vec<u32>) {
+        let cron = Cron::parse(expression).expect("Valid expression");
+        assert_eq!(cron.weekdays.iter().collect::<Vec<_>>(), weekdays);
+    }
+
+    #[parameterized(
+        yearly = {"@yearly", "0 0 1 1 *"},
+        annually = {"@ANNUALLY", "0 0 1 1 *"},
+        monthly = {"@monthly", "0 0 1 * *"},
+        weekly = {"@weekly", "0 0 * * 0"},
+        daily = {"@daily", "0 0 * * *"},
+        midnight = {"@midnight", "0 0 * * *"},
+        hourly = {" @hourly ", "0 * * * *"},
+    )]
+    fn shortcuts(expression: &str, expanded: &str) {
+        let (cron, expected) = (Cron::parse(expression).expect("Valid shortcut"), Cron::parse(expanded).expect("Valid expression"));
+        assert_eq!(cron.expression, expression.trim());
+        assert_eq!((cron.seconds, cron.minutes, cron.hours, cron.days, cron.months, cron.weekdays), (expected.seconds, expected.minutes, expected.hours, expected.days, expected.months, expected.weekdays));
+    }
+
+    #[parameterized(
+        too_few = {"* * * *", "it must have 5 or 6 fields but has 4"},
+        too_many = {"* * * * * * *", "it must have 5 or 6 fields but has 7"},
+        empty = {"", "it must have 5 or 6 fields but has 0"},
+        minute = {"60 * * * *", "Invalid minute \"60\" in \"60\", it must be from 0 to 59"},
+        hour = {"0 24 * * *", "Invalid hour \"24\""},
+        day = {"0 0 0 * *", "Invalid day of the month \"0\""},
+        month = {"0 0 * 13 *", "Invalid month \"13\""},
+        month_name = {"0 0 * january *", "Invalid month \"january\""},
+        weekday = {"0 0 * * 8", "Invalid day of the week \"8\""},
+        word = {"0 noon * * *", "Invalid hour \"noon\""},
+        empty_part = {"0,,5 * * * *", "Invalid minute \"\""},
+        zero_step = {"*/0 * * * *", "Invalid step \"0\" in \"*/0\""},
+        bad_step = {"*/x * * * *", "Invalid step \"x\""},
+        reversed = {"0 17-9 * * *", "Invalid range \"17-9\" in \"17-9\", it must go from low to high"},
+        shortcut = {"@reboot", "Unknown shortcut \"@reboot\""},
+        never = {"0 0 30,31 feb *", "none of the months have any of the days"},
+        april = {"0 0 31 4,6 *", "none of the months have any of the days"},
+    )]
+    fn invalid(expression: &str, message: &str) {
+        let error = Cron::parse(expression).expect_err("Invalid expression");
+        assert!(error.contains(message), "{error}");
+    }
+
+    #[parameterized(
+        leap_day = {"0 0 29 2 *"},
+        either_day = {"0 0 31 2 mon"},
+        stepped_day = {"0 0 */31 feb *"},
+    )]
+    fn possible(expression: &str) {
+        assert!(Cron::parse(expression).is_ok());
+    }
+
+    #[parameterized(
+        every_minute = {"* * * * *", utc(2024, 3, 6, 15, 0), vec![utc(2024, 3, 6, 15, 1), utc(2024, 3, 6, 15, 2), utc(2024, 3, 6, 15, 3)]},
+        every_second = {"* * * * * *", utc(2024, 3, 6, 15, 0), vec![utc(2024, 3, 6, 15, 0) + 1, utc(2024, 3, 6, 15, 0) + 2, utc(2024, 3, 6, 15, 0) + 3]},
+        mid_minute = {"*/15 * * * *", utc(2024, 3, 6, 15, 0) + 30, vec![utc(2024, 3, 6, 15, 15), utc(2024, 3, 6, 15, 30), utc(2024, 3, 6, 15, 45)]},
+        daily = {"@daily", utc(2024, 3, 6, 15, 0), vec![utc(2024, 3, 7, 0, 0), utc(2024, 3, 8, 0, 0), utc(2024, 3, 9, 0, 0)]},
+        workdays = {"0 9 * * mon-fri", utc(2024, 3, 7, 12, 0), vec![utc(2024, 3, 8, 9, 0), utc(2024, 3, 11, 9, 0), utc(2024, 3, 12, 9, 0)]},
+        hours = {"30 9-17/4 * * *", utc(2024, 3, 6, 10, 0), vec![utc(2024, 3, 6, 13, 30), utc(2024, 3, 6, 17, 30), utc(2024, 3, 7, 9, 30)]},
+        month_end = {"0 12 31 * *", utc(2024, 3, 6, 15, 0), vec![utc(2024, 3, 31, 12, 0), utc(2024, 5, 31, 12, 0), utc(2024, 7, 31, 12, 0)]},
+        leap_day = {"0 0 29 2 *", utc(2024, 3, 6, 15, 0), vec![utc(2028, 2, 29, 0, 0), utc(2032, 2, 29, 0, 0), utc(2036, 2, 29, 0, 0)]},
+        yearly = {"@yearly", utc(2024, 1, 1, 0, 0), vec![utc(2025, 1, 1, 0, 0), utc(2026, 1, 1, 0, 0), utc(2027, 1, 1, 0, 0)]},
+        // the 13th or any friday, the 8th of march 2024 being a friday
+        either_day = {"0 0 13 * fri", utc(2024, 3, 6, 15, 0), vec![utc(2024, 3, 8, 0, 0), utc(2024, 3, 13, 0, 0), utc(2024, 3, 15, 0, 0)]},
+        // only the fridays that are the 1st, 14th or 27th, since the day of the month starts with `*`
+        both_days = {"0 0 */13 * fri", utc(2024, 3, 6, 15, 0), vec![utc(2024, 6, 14, 0, 0), utc(2024, 9, 27, 0, 0), utc(2024, 11, 1, 0, 0)]},
+    )]
+    fn occurrences(expression: &str, after: i64, expected: Vec<i64>) {
+        assert_eq!(next(expression, &Zone::utc(), after, 3), expected);
+    }
+
+    #[test]
+    fn end_of_the_calendar() {
+        let after = utc(9999, 12, 31, 23, 0);
+        assert_eq!(next("0 * * * *", &Zone::utc(), after, 3), vec![]);
+        assert_eq!(next("30 * * * *", &Zone::utc(), after, 3), vec![after + 30 * 60]);
+    }
+
+    #[test]
+    fn local_time() {
+        let zone = cooldowns::zone("America/New_York").expect("Valid zone");
+        // 9am is 2pm in UTC in the winter, and 1pm once the clocks go forward on the 10th of march 2024
+        let times = next("0 9 * * *", &zone, utc(2024, 3, 8, 15, 0), 3);
+        assert_eq!(times, vec![utc(2024, 3, 9, 14, 0), utc(2024, 3, 10, 13, 0), utc(2024, 3, 11, 13, 0)]);
+    }
+
+    #[test]
+    fn clocks_go_forward() {
+        let zone = cooldowns::zone("America/New_York").expect("Valid zone");
+        // 2:30am doesn't happen on the 10th of march 2024, so it is at 3am when the clocks go forward, 7am UTC
+        let times = next("30 2 * * *", &zone, utc(2024, 3, 9, 12, 0), 3);
+        assert_eq!(times, vec![utc(2024, 3, 10, 7, 0), utc(2024, 3, 11, 6, 30), utc(2024, 3, 12, 6, 30)]);
+        // and the minutes that are skipped all happen then, once
+        let times = next("*/20 * * * *", &zone, utc(2024, 3, 10, 6, 50), 3);
+        assert_eq!(times, vec![utc(2024, 3, 10, 7, 0), utc(2024, 3, 10, 7, 20), utc(2024, 3, 10, 7, 40)]);
+    }
+
+    #[test]
+    fn clocks_go_back() {
+        let zone = cooldowns::zone("America/New_York").expect("Valid zone");
+        // 1:30am happens twice on the 3rd of november 2024, at 5:30 and 6:30 UTC, and runs the first time
+        let times = next("30 1 * * *", &zone, utc(2024, 11, 2, 12, 0), 2);
+        assert_eq!(times, vec![utc(2024, 11, 3, 5, 30), utc(2024, 11, 4, 6, 30)]);
+        // from during the second time through, the repeated hour is skipped
+        let times = next("*/30 * * * *", &zone, utc(2024, 11, 3, 6, 10), 2);
+        assert_eq!(times, vec![utc(2024, 11, 3, 7, 0), utc(2024, 11, 3, 7, 30)]);
+    }
+
+    #[test]
+    fn repr() {
+        let cron = Cron::parse(" 0 9 * * mon ").expect("Valid expression");
+        assert_eq!(cron.__repr__(), "Cron(\"0 9 * * mon\")");
+        assert_eq!(cron.__str__(), "0 9 * * mon");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,291 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Saying when a schedule happens, as phrases like "at 9:00 AM" and "Monday through Friday".
+use super::{Cron, Field, Spec, DAYS, HOURS, MINUTES, MONTHS, SECONDS, WEEKDAYS};
+use crate::fluent::{format_clock, format_list, formats, translate_text};
+
+/// The most times of day said one by one, more are said as how often they happen.
+const MAX_TIMES: usize = 6;
+
+const WEEKDAY_KEYS: [&str; 7] = [
+    "schedule-sunday", "schedule-monday", "schedule-tuesday", "schedule-wednesday",
+    "schedule-thursday", "schedule-friday", "schedule-saturday",
+];
+const MONTH_KEYS: [&str; 12] = [
+    "schedule-january", "schedule-february", "schedule-march", "schedule-april", "schedule-may", "schedule-june",
+    "schedule-july", "schedule-august", "schedule-september", "schedule-october", "schedule-november", "schedule-december",
+];
+
+/// Text that is put together once the locale is known.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(super) enum Text {
+    /// A message, with the text of each argument.
+    Message(&'static str, Vec<(&'static str, Text)>),
+    Plain(String),
+    /// Joined like "a, b, and c".
+    List(Vec<Text>),
+    /// A time of day, in seconds after midnight, and whether to show the seconds.
+    Clock(i64, bool),
+}
+
+impl Text {
+    fn message(key: &'static str) -> Text {
+        Text::Message(key, Vec::new())
+    }
+
+    fn render(&self, locale: &str) -> Result<String, String> {
+        match self {
+            Text::Message(key, args) => {
+                let args = args.iter().map(|(name, arg)| Ok((*name, arg.render(locale)?))).collect::<Result<Vec<_>, String>>()?;
+                translate_text(locale, key, &args)
+            }
+            Text::Plain(text) => Ok(text.clone()),
+            Text::List(items) => Ok(format_list(formats(locale)?, &items.iter().map(|item| item.render(locale)).collect::<Result<Vec<_>, _>>()?)),
+            Text::Clock(time, seconds) => Ok(format_clock(formats(locale)?, *time, *seconds)),
+        }
+    }
+}
+
+fn values(field: Field) -> Vec<u32> {
+    field.iter().collect()
+}
+
+/// How far apart the values are, when they go from the first value the field can hold to its end in even steps.
+fn step(field: Field, spec: &Spec) -> Option<u32> {
+    let values = values(field);
+    let [first, second, ..] = values[..] else { return None };
+    let step = second - first;
+    let even = values.windows(2).all(|pair| pair[1] - pair[0] == step);
+    (first == spec.min && step > 1 && even && values[values.len() - 1] + step > spec.max).then_some(step)
+}
+
+/// The first and last value, when there are at least `least` values with none missing between them.
+fn range(field: Field, least: usize) -> Option<(u32, u32)> {
+    let values = values(field);
+    let (first, last) = (*values.first()?, *values.last()?);
+    (values.len() >= least && (last - first) as usize + 1 == values.len()).then_some((first, last))
+}
+
+/// The values as numbers, with runs of three or more shortened like "1–5".
+fn numbers(field: Field) -> Text {
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for value in field.iter() {
+        match runs.last_mut() {
+            Some((_, last)) if *last + 1 == value => *last = value,
+            _ => runs.push((value, value)),
+        }
+    }
+    Text::List(runs.into_iter().flat_map(|(first, last)| match last - first {
+        0 => vec![Text::Plain(first.to_string())],
+        1 => vec![Text::Plain(first.to_string()), Text::Plain(last.to_string())],
+        _ => vec![Text::Plain(format!("{first}–{last}"))],
+    }).collect())
+}
+
+fn every(one: &'static str, many: &'static str, step: u32) -> Text {
+    if step == 1 {
+        Text::message(one)
+    } else {
+        Text::Message(many, vec![("count", Text::Plain(step.to_string()))])
+    }
+}
+
+/// The phrases for the time of day, and whether they are single times rather than how often it happens.
+fn times(cron: &Cron) -> (Vec<Text>, bool) {
+    let (seconds, minutes, hours) = (values(cron.seconds), values(cron.minutes), values(cron.hours));
+    let count = seconds.len() * minutes.len() * hours.len();
+    if cron.minutes.values != MINUTES.all() && cron.hours.values != HOURS.all() && count <= MAX_TIMES {
+        let show_seconds = seconds != [0];
+        let mut times = Vec::with_capacity(count);
+        for hour in &hours {
+            for minute in &minutes {
+                times.extend(seconds.iter().map(|second| Text::Clock(i64::from(hour * 3600 + minute * 60 + second), show_seconds)));
+            }
+        }
+        return (vec![Text::Message("schedule-at", vec![("times", Text::List(times))])], true);
+    }
+    let mut phrases = Vec::new();
+    let on_the_minute = seconds == [0];
+    if cron.seconds.values == SECONDS.all() {
+        phrases.push(Text::message("schedule-every-second"));
+    } else if let Some(step) = step(cron.seconds, &SECONDS) {
+        phrases.push(every("schedule-every-second", "schedule-every-seconds", step));
+    } else if !on_the_minute {
+        phrases.push(Text::Message("schedule-at-seconds", vec![("seconds", numbers(cron.seconds))]));
+    }
+    let hour_range = range(cron.hours, 2);
+    if cron.minutes.values == MINUTES.all() {
+        // the seconds already happen every minute otherwise
+        if on_the_minute {
+            phrases.push(Text::message("schedule-every-minute"));
+        }
+    } else if let Some(step) = step(cron.minutes, &MINUTES) {
+        phrases.push(every("schedule-every-minute", "schedule-every-minutes", step));
+    } else if minutes == [0] && on_the_minute {
+        // the hours say how often it happens when they have steps of their own
+        if hour_range.is_some() {
+            phrases.push(Text::message("schedule-every-hour"));
+        }
+    } else {
+        phrases.push(Text::Message("schedule-at-minutes", vec![("minutes", numbers(cron.minutes))]));
+    }
+    if cron.hours.values == HOURS.all() {
+        return (phrases, false);
+    }
+    if let Some(step) = step(cron.hours, &HOURS) {
+        phrases.push(every("schedule-every-hour", "schedule-every-hours", step));
+    } else if let Some((first, last)) = hour_range {
+        let show_seconds = !on_the_minute;
+        let (last_minute, last_second) = (minutes[minutes.len() - 1], seconds[seconds.len() - 1]);
+        let end = i64::from(last * 3600 + last_minute * 60 + last_second);
+        phrases.push(Text::Message("schedule-between", vec![
+            ("start", Text::Clock(i64::from(first * 3600 + minutes[0] * 60 + seconds[0]), show_seconds)),
+            ("end", Text::Clock(end, show_seconds)),
+        ]));
+    } else {
+        let hours = hours.iter().map(|hour| Text::Clock(i64::from(hour * 3600), false)).collect();
+        phrases.push(Text::Message("schedule-during-hours", vec![("hours", Text::List(hours))]));
+    }
+    (phrases, false)
+}
+
+/// The days of the week, as a range like "Monday through Friday" when there are three or more in a row.
+fn weekdays(field: Field) -> Text {
+    match range(field, 3) {
+        Some((first, last)) => Text::Message("schedule-weekdays-range", vec![
+            ("first", Text::message(WEEKDAY_KEYS[first as usize])),
+            ("last", Text::message(WEEKDAY_KEYS[last as usize])),
+        ]),
+        None => Text::List(field.iter().map(|weekday| Text::message(WEEKDAY_KEYS[weekday as usize])).collect()),
+    }
+}
+
+fn on_weekdays(field: Field) -> Text {
+    match weekdays(field) {
+        list @ Text::List(_) => Text::Message("schedule-on-weekdays", vec![("weekdays", list)]),
+        range => range,
+    }
+}
+
+/// The phrases saying when the schedule happens, in the order they are said.
+pub(super) fn phrases(cron: &Cron) -> Vec<Text> {
+    let (mut phrases, single_times) = times(cron);
+    // sundays are 0 and 7, but only 0 is kept
+    let every_weekday = WEEKDAYS.all() & !(1 << 7);
+    let (some_days, some_weekdays) = (cron.days.values != DAYS.all(), cron.weekdays.values != every_weekday);
+    let some_months = cron.months.values != MONTHS.all();
+    match (some_days, some_weekdays) {
+        (true, true) if cron.either_day() => phrases.push(Text::Message("schedule-on-days-or-weekdays", vec![
+            ("days", numbers(cron.days)),
+            ("weekdays", weekdays(cron.weekdays)),
+        ])),
+        (true, _) | (_, true) => {
+            if some_days {
+                phrases.push(Text::Message("schedule-on-days", vec![("days", numbers(cron.days))]));
+            }
+            if some_weekdays {
+                phrases.push(on_weekdays(cron.weekdays));
+            }
+        }
+        (false, false) if single_times && !some_months => phrases.push(Text::message("schedule-every-day")),
+        (false, false) => {}
+    }
+    if some_months {
+        let months = cron.months.iter().map(|month| Text::message(MONTH_KEYS[month as usize - 1])).collect();
+        phrases.push(Text::Message("schedule-in-months", vec![("months", Text::List(months))]));
+    }
+    phrases
+}
+
+/// The sentence saying when `cron` happens in `locale`, like "At 9:00 AM, Monday through Friday".
+pub(super) fn sentence(cron: &Cron, locale: &str) -> Result<String, String> {
+    let phrases = phrases(cron).iter().map(|phrase| phrase.render(locale)).collect::<Result<Vec<_>, _>>()?;
+    let sentence = phrases.join(", ");
+    let mut letters = sentence.chars();
+    Ok(letters.next().map_or_else(String::new, |first| first.to_uppercase().chain(letters).collect()))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    /// The text with the keys of the messages in it, like `schedule-at(times=[32400])`.
+    fn show(text: &Text) -> String {
+        match text {
+            Text::Message(key, args) if args.is_empty() => key.trim_start_matches("schedule-").to_string(),
+            Text::Message(key, args) => {
+                let args: Vec<String> = args.iter().map(|(name, arg)| format!("{name}={}", show(arg))).collect();
+                format!("{}({})", key.trim_start_matches("schedule-"), args.join(", "))
+            }
+            Text::Plain(text) => text.clone(),
+            Text::List(items) => format!("[{}]", items.iter().map(show).collect::<Vec<_>>().join(", ")),
+            Text::Clock(time, false) => format!("{:02}:{:02}", time / 3600, time / 60 % 60),
+            Text::Clock(time, true) => format!("{:02}:{:02}:{:02}", time / 3600, time / 60 % 60, time % 60),
+        }
+    }
+
+    #[parameterized(
+        every_minute = {"* * * * *", "every-minute"},
+        every_second = {"* * * * * *", "every-second"},
+        every_seconds = {"*/10 * * * * *", "every-seconds(count=10)"},
+        at_seconds = {"15,45 * * * * *", "at-seconds(seconds=[15, 45])"},
+        every_minutes = {"*/15 * * * *", "every-minutes(count=15)"},
+        hourly = {"@hourly", "every-hour"},
+        at_minutes = {"30 * * * *", "at-minutes(minutes=[30])"},
+        minute_runs = {"0-4,10,20-21 * * * *", "at-minutes(minutes=[0–4, 10, 20, 21])"},
+        daily = {"@daily", "at(times=[00:00]) | every-day"},
+        twice = {"0 9,17 * * *", "at(times=[09:00, 17:00]) | every-day"},
+        with_seconds = {"30 0 9 * * *", "at(times=[09:00:30]) | every-day"},
+        every_hours = {"0 */2 * * *", "every-hours(count=2)"},
+        past_every_hours = {"30 */2 * * *", "at-minutes(minutes=[30]) | every-hours(count=2)"},
+        few_hours = {"30 */6 * * *", "at(times=[00:30, 06:30, 12:30, 18:30]) | every-day"},
+        between = {"*/15 9-17 * * *", "every-minutes(count=15) | between(start=09:00, end=17:45)"},
+        hours_between = {"0 9-17 * * *", "every-hour | between(start=09:00, end=17:00)"},
+        during = {"*/30 1,5,9,13 * * *", "every-minutes(count=30) | during-hours(hours=[01:00, 05:00, 09:00, 13:00])"},
+        offset_step = {"5/20 * * * *", "at-minutes(minutes=[5, 25, 45])"},
+    )]
+    fn times_of_day(expression: &str, expected: &str) {
+        let cron = Cron::parse(expression).expect("Valid expression");
+        assert_eq!(phrases(&cron).iter().map(show).collect::<Vec<_>>().join(" | "), expected);
+    }
+
+    #[parameterized(
+        workdays = {"0 9 * * mon-fri", "at(times=[09:00]) | weekdays-range(first=monday, last=friday)"},
+        weekend = {"0 9 * * sat,sun", "at(times=[09:00]) | on-weekdays(weekdays=[sunday, saturday])"},
+        weekly = {"@weekly", "at(times=[00:00]) | on-weekdays(weekdays=[sunday])"},
+        monthly = {"@monthly", "at(times=[00:00]) | on-days(days=[1])"},
+        yearly = {"@yearly", "at(times=[00:00]) | on-days(days=[1]) | in-months(months=[january])"},
+        days = {"0 0 1,15 * *", "at(times=[00:00]) | on-days(days=[1, 15])"},
+        either = {"0 0 13 * fri", "at(times=[00:00]) | on-days-or-weekdays(days=[13], weekdays=[friday])"},
+        either_range = {"0 0 1 * 1-5", "at(times=[00:00]) | on-days-or-weekdays(days=[1], weekdays=weekdays-range(first=monday, last=friday))"},
+        both = {"0 0 */13 * fri", "at(times=[00:00]) | on-days(days=[1, 14, 27]) | on-weekdays(weekdays=[friday])"},
+        months = {"0 12 * jun-aug *", "at(times=[12:00]) | in-months(months=[june, july, august])"},
+        every_minute_in = {"* * * dec *", "every-minute | in-months(months=[december])"},
+    )]
+    fn days(expression: &str, expected: &str) {
+        let cron = Cron::parse(expression).expect("Valid expression");
+        assert_eq!(phrases(&cron).iter().map(show).collect::<Vec<_>>().join(" | "), expected);
+    }
+
+    #[parameterized(
+        french_months = {"0 9 * feb,aug *", "fr", "À 09:00, en février et août"},
+        french_weekdays = {"30 8 * * mon-fri", "fr", "À 08:30, du lundi au vendredi"},
+        spanish_days = {"0 0 * * *", "es-ES", "A las 0:00, todos los días"},
+        spanish_weekdays = {"0 18 * * wed,sat", "es-ES", "A las 18:00, cada miércoles y sábado"},
+    )]
+    fn sentences(expression: &str, locale: &str, expected: &str) {
+        let cron = Cron::parse(expression).expect("Valid expression");
+        assert_eq!(sentence(&cron, locale), Ok(String::from(expected)));
+    }
+
+    #[test]
+    fn plain_render() {
+        let text = Text::List(vec![Text::Plain(String::from("1")), Text::Plain(String::from("2")), Text::Plain(String::from("3"))]);
+        assert_eq!(text.render("en-US"), Ok(String::from("1, 2, and 3")));
+        assert_eq!(Text::Clock(9 * 3600 + 30 * 60, false).render("fr"), Ok(String::from("09:30")));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,540 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Events that repeat by a rule like the `RRULE` of iCalendar (RFC 5545), such as every week on
+//! tuesday and thursday at 19:00, in the time zone they are held in.
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDate;
+use crate::cooldowns::{self, tz::{self, Zone}};
+use super::SECONDS_PER_DAY;
+
+/// The most occurrences returned at once.
+const MAX_OCCURRENCES: usize = 10_000;
+/// `date.toordinal()` of 1970-01-01.
+const EPOCH_ORDINAL: i64 = 719_163;
+/// The names of the days of the week in a rule, from sunday.
+const WEEKDAYS: [&str; 7] = ["SU", "MO", "TU", "WE", "TH", "FR", "SA"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A day of the week in `BYDAY`, like `TU`, or `-1FR` for the last friday of the month.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Weekday {
+    /// Which of them in the month, from the end when negative, or 0 for all of them.
+    nth: i32,
+    /// From 0 for sunday.
+    weekday: u32,
+}
+
+/// The parts of a rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Rule {
+    frequency: Frequency,
+    interval: i64,
+    weekdays: Vec<Weekday>,
+    /// From the end of the month when negative, like -1 for the last day.
+    month_days: Vec<i32>,
+    months: Vec<u32>,
+    hours: Vec<u32>,
+    minutes: Vec<u32>,
+    count: Option<u32>,
+    until: Option<Until>,
+}
+
+/// The last time a rule can happen, as `UNTIL` gives it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Until {
+    /// In UTC, like `20241231T230000Z`.
+    Time(i64),
+    /// In the local time of the event, like `20241231T230000`.
+    Local(i64),
+    /// The whole of a local date, like `20241231`, as days since the unix epoch.
+    Date(i64),
+}
+
+fn number<T: std::str::FromStr + PartialOrd>(part: &str, text: &str, range: std::ops::RangeInclusive<T>) -> Result<T, String> {
+    text.parse().ok().filter(|value| range.contains(value)).ok_or_else(|| format!("Invalid {part} {text:?}"))
+}
+
+/// The values of a part like `BYHOUR=9,17`, sorted.
+fn numbers(part: &str, text: &str, min: u32, max: u32) -> Result<Vec<u32>, String> {
+    let values = text.split(',').map(|value| number(part, value, min..=max)).collect::<Result<BTreeSet<_>, _>>()?;
+    Ok(values.into_iter().collect())
+}
+
+fn weekday(text: &str) -> Result<Weekday, String> {
+    let error = || format!("Invalid BYDAY {text:?}, it must be a day like MO, or a day with which of them in the month like 2MO or -1FR");
+    let split = text.len().checked_sub(2).filter(|split| text.is_char_boundary(*split)).ok_or_else(error)?;
+    let (nth, name) = text.split_at(split);
+    let weekday = WEEKDAYS.iter().position(|known| *known == name).ok_or_else(error)? as u32;
+    let nth = match nth {
+        "" => 0,
+        nth => nth.parse::<i32>().ok().filter(|nth| *nth != 0 && (-5..=5).contains(nth)).ok_or_else(error)?,
+    };
+    Ok(Weekday { nth, weekday })
+}
+
+fn until(text: &str) -> Result<Until, String> {
+    let error = || format!("Invalid UNTIL {text:?}, it must be a date like 20241231 or a time like 20241231T230000Z");
+    let digits = |range: std::ops::Range<usize>| text.get(range).filter(|digits| digits.bytes().all(|digit| digit.is_ascii_digit())).and_then(|digits| digits.parse::<u32>().ok()).ok_or_else(error);
+    let (year, month, day) = (digits(0..4)?, digits(4..6)?, digits(6..8)?);
+    if !(1..=12).contains(&month) || day == 0 || day > tz::month_length(i64::from(year), month) {
+        return Err(error());
+    }
+    let days = tz::days_from_civil(i64::from(year), month, day);
+    if text.len() == 8 {
+        return Ok(Until::Date(days));
+    }
+    if text.get(8..9) != Some("T") {
+        return Err(error());
+    }
+    let (hour, minute, second) = (digits(9..11)?, digits(11..13)?, digits(13..15)?);
+    if hour > 23 || minute > 59 || second > 59 {
+        return Err(error());
+    }
+    let local = days * SECONDS_PER_DAY + i64::from(hour * 3600 + minute * 60 + second);
+    match text.get(15..) {
+        Some("") => Ok(Until::Local(local)),
+        Some("Z") => Ok(Until::Time(local)),
+        _ => Err(error()),
+    }
+}
+
+impl Rule {
+    fn parse(text: &str) -> Result<Rule, String> {
+        let text = text.trim();
+        let text = text.strip_prefix("RRULE:").unwrap_or(text);
+        let mut frequency = None;
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            weekdays: Vec::new(),
+            month_days: Vec::new(),
+            months: Vec::new(),
+            hours: Vec::new(),
+            minutes: Vec::new(),
+            count: None,
+            until: None,
+        };
+        for part in text.split(';').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=').ok_or_else(|| format!("Invalid rule part {part:?}, it must be like NAME=VALUE"))?;
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Invalid FREQ {value:?}, it must be DAILY, WEEKLY, MONTHLY or YEARLY")),
+                    })
+                }
+                "INTERVAL" => rule.interval = number("INTERVAL", value, 1..=10_000)?,
+                "BYDAY" => rule.weekdays = value.split(',').map(weekday).collect::<Result<_, _>>()?,
+                "BYMONTHDAY" => {
+                    let days = value.split(',').map(|day| number("BYMONTHDAY", day, -31..=31).and_then(|number| {
+                        if number == 0 { Err(format!("Invalid BYMONTHDAY {day:?}")) } else { Ok(number) }
+                    }));
+                    rule.month_days = days.collect::<Result<_, _>>()?;
+                }
+                "BYMONTH" => rule.months = numbers("BYMONTH", value, 1, 12)?,
+                "BYHOUR" => rule.hours = numbers("BYHOUR", value, 0, 23)?,
+                "BYMINUTE" => rule.minutes = numbers("BYMINUTE", value, 0, 59)?,
+                "COUNT" => rule.count = Some(number("COUNT", value, 1..=u32::MAX)?),
+                "UNTIL" => rule.until = Some(until(value)?),
+                _ => return Err(format!(
+                    "Unsupported rule part {name:?}, it must be FREQ, INTERVAL, BYDAY, BYMONTHDAY, BYMONTH, BYHOUR, BYMINUTE, COUNT or UNTIL"
+                )),
+            }
+        }
+        rule.frequency = frequency.ok_or_else(|| format!("Invalid rule {text:?}, it has no FREQ"))?;
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err(String::from("Invalid rule, it can't have both COUNT and UNTIL"));
+        }
+        if rule.frequency == Frequency::Weekly && !rule.month_days.is_empty() {
+            return Err(String::from("Invalid rule, BYMONTHDAY can't be used with FREQ=WEEKLY"));
+        }
+        let monthly = matches!(rule.frequency, Frequency::Monthly | Frequency::Yearly);
+        if !monthly && rule.weekdays.iter().any(|weekday| weekday.nth != 0) {
+            return Err(String::from("Invalid rule, which of a day in the month, like 1MO, needs FREQ=MONTHLY or FREQ=YEARLY"));
+        }
+        Ok(rule)
+    }
+
+    fn weekday_matches(&self, days: i64) -> bool {
+        let weekday = tz::weekday(days) as u32;
+        self.weekdays.is_empty() || self.weekdays.iter().any(|day| day.weekday == weekday)
+    }
+
+    fn month_day_matches(&self, day: u32, length: u32) -> bool {
+        self.month_days.is_empty() || self.month_days.iter().any(|month_day| {
+            let month_day = if *month_day < 0 { length as i32 + month_day + 1 } else { *month_day };
+            month_day == day as i32
+        })
+    }
+
+    /// The days of `month` of `year` the rule happens on, or the day `default` when it doesn't say.
+    fn days_of_month(&self, year: i64, month: u32, default: u32) -> Vec<i64> {
+        let (first, length) = (tz::days_from_civil(year, month, 1), tz::month_length(year, month));
+        if self.weekdays.is_empty() && self.month_days.is_empty() {
+            // a month too short for the day is skipped, like RFC 5545 says
+            return if default <= length { vec![first + i64::from(default) - 1] } else { Vec::new() };
+        }
+        (1..=length).filter(|day| {
+            let days = first + i64::from(*day) - 1;
+            let weekday = self.weekdays.is_empty() || self.weekdays.iter().any(|weekday| {
+                let (from_start, from_end) = ((day - 1) / 7 + 1, (length - day) / 7 + 1);
+                weekday.weekday == tz::weekday(days) as u32 && match weekday.nth {
+                    0 => true,
+                    nth if nth > 0 => from_start as i32 == nth,
+                    nth => from_end as i32 == -nth,
+                }
+            });
+            weekday && self.month_day_matches(*day, length)
+        }).map(|day| first + i64::from(day) - 1).collect()
+    }
+
+    /// The first day of the `period`th period from `start`, the day the event starts as days since the unix
+    /// epoch, and the days in it the rule happens on.
+    fn days(&self, start: i64, period: i64) -> (i64, Vec<i64>) {
+        let (year, month, day) = tz::civil_from_days(start);
+        let in_month = |days: &i64| {
+            let (_, month, _) = tz::civil_from_days(*days);
+            self.months.is_empty() || self.months.contains(&month)
+        };
+        match self.frequency {
+            Frequency::Daily => {
+                let days = start + period * self.interval;
+                let (year, month, day) = tz::civil_from_days(days);
+                let matches = in_month(&days) && self.weekday_matches(days) && self.month_day_matches(day, tz::month_length(year, month));
+                (days, if matches { vec![days] } else { Vec::new() })
+            }
+            Frequency::Weekly => {
+                // weeks start on monday, like RFC 5545 has them by default
+                let monday = start - (tz::weekday(start) + 6) % 7 + period * self.interval * 7;
+                let weekday = tz::weekday(start) as u32;
+                let days = (monday..monday + 7)
+                    .filter(|days| if self.weekdays.is_empty() { tz::weekday(*days) as u32 == weekday } else { self.weekday_matches(*days) })
+                    .filter(in_month)
+                    .collect();
+                (monday, days)
+            }
+            Frequency::Monthly => {
+                let months = year * 12 + i64::from(month) - 1 + period * self.interval;
+                let (year, month) = (months.div_euclid(12), months.rem_euclid(12) as u32 + 1);
+                let days = if self.months.is_empty() || self.months.contains(&month) { self.days_of_month(year, month, day) } else { Vec::new() };
+                (tz::days_from_civil(year, month, 1), days)
+            }
+            Frequency::Yearly => {
+                // which of a day of the week, like 4TH, counts within each month rather than the year
+                let year = year + period * self.interval;
+                let months = if self.months.is_empty() { vec![month] } else { self.months.clone() };
+                (tz::days_from_civil(year, 1, 1), months.into_iter().flat_map(|month| self.days_of_month(year, month, day)).collect())
+            }
+        }
+    }
+}
+
+// COV_EXCL_START
+/// An event that repeats by a rule, at the same local time in its time zone whatever the offset.
+#[pyclass(module = "schedule")]
+#[derive(Clone, Debug)]
+pub struct Recurrence {
+    #[pyo3(get)]
+    rule: String,
+    /// The unix timestamp of the first time the event happens, which the rule counts from.
+    #[pyo3(get)]
+    start: i64,
+    #[pyo3(get)]
+    tz: String,
+    parsed: Rule,
+    zone: Arc<Zone>,
+    /// The local dates the event is skipped on, as days since the unix epoch.
+    skipped: BTreeSet<i64>,
+}
+// COV_EXCL_STOP
+
+impl Recurrence {
+    fn new(rule: &str, start: i64, tz: &str, skipped: BTreeSet<i64>) -> Result<Recurrence, String> {
+        let parsed = Rule::parse(rule)?;
+        let zone = cooldowns::zone(tz)?;
+        cooldowns::check(start)?;
+        Ok(Recurrence { rule: rule.trim().to_string(), start, tz: tz.to_string(), parsed, zone, skipped })
+    }
+
+    /// The last time the event can happen by `UNTIL`.
+    fn until(&self) -> Option<i64> {
+        self.parsed.until.map(|until| match until {
+            Until::Time(time) => time,
+            Until::Local(local) => self.zone.to_utc(local),
+            Until::Date(days) => self.zone.to_utc((days + 1) * SECONDS_PER_DAY) - 1,
+        })
+    }
+
+    /// The times from `from` and before `to` the event happens, as unix timestamps.
+    ///
+    /// Each is the local time the rule gives converted with the offset of that day, so the event stays at
+    /// 19:00 when the clocks change. A local time the clocks skip happens when they go forward, and one they
+    /// repeat happens the first time.
+    fn between(&self, from: i64, to: i64) -> Vec<i64> {
+        let local_start = self.zone.to_local(self.start);
+        let (start_day, time) = (local_start.div_euclid(SECONDS_PER_DAY), local_start.rem_euclid(SECONDS_PER_DAY));
+        let hours = if self.parsed.hours.is_empty() { vec![(time / 3600) as u32] } else { self.parsed.hours.clone() };
+        let minutes = if self.parsed.minutes.is_empty() { vec![(time / 60 % 60) as u32] } else { self.parsed.minutes.clone() };
+        let second = time % 60;
+        let (until, last) = (self.until(), tz::days_from_civil(10_000, 1, 1));
+        // a day of margin either side for the offset, local times are only ever a day from UTC
+        let end = self.zone.to_local(to).min(until.map_or(i64::MAX, |until| self.zone.to_local(until))).saturating_add(SECONDS_PER_DAY).div_euclid(SECONDS_PER_DAY);
+        let mut found = Vec::new();
+        let mut counted = 0;
+        for period in 0.. {
+            // periods without days still have to be stepped through, so stop by when the period begins
+            let (begins, days) = self.parsed.days(start_day, period);
+            if begins > end || begins >= last {
+                break;
+            }
+            for day in days {
+                for hour in &hours {
+                    for minute in &minutes {
+                        let time = self.zone.to_utc(day * SECONDS_PER_DAY + i64::from(hour * 3600 + minute * 60) + second);
+                        // a rule doesn't always say the day it starts on, like a weekly event starting on a monday but held on tuesdays
+                        if time < self.start || found.last() == Some(&time) {
+                            continue;
+                        }
+                        counted += 1;
+                        if time >= to || until.is_some_and(|until| time > until) || self.parsed.count.is_some_and(|count| counted > count) {
+                            return found;
+                        }
+                        // skipped days still count towards COUNT, like EXDATE in RFC 5545
+                        if time >= from && !self.skipped.contains(&day) {
+                            found.push(time);
+                            if found.len() == MAX_OCCURRENCES {
+                                return found;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Days since the unix epoch of `date`.
+fn unix_days(date: &PyDate) -> PyResult<i64> {
+    Ok(date.call_method0("toordinal")?.extract::<i64>()? - EPOCH_ORDINAL)
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Recurrence {
+    #[new]
+    #[pyo3(signature = (rule, start, tz="UTC", skip=Vec::new()))]
+    fn __new__(rule: &str, start: i64, tz: &str, skip: Vec<&PyDate>) -> PyResult<Self> { // COV_EXCL_LINE
+        let skipped = skip.into_iter().map(unix_days).collect::<PyResult<_>>()?;
+        Recurrence::new(rule, start, tz, skipped).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// Skip the event on the local date `date`, like a holiday.
+    fn skip(&mut self, date: &PyDate) -> PyResult<()> {
+        self.skipped.insert(unix_days(date)?);
+        Ok(())
+    }
+
+    /// The local dates the event is skipped on.
+    fn skipped<'py>(&self, py: Python<'py>) -> PyResult<Vec<&'py PyDate>> {
+        self.skipped.iter().map(|days| {
+            let (year, month, day) = tz::civil_from_days(*days);
+            PyDate::new(py, year as i32, month as u8, day as u8)
+        }).collect()
+    }
+
+    #[pyo3(name = "between")]
+    fn py_between(&self, start: i64, end: i64) -> PyResult<Vec<i64>> {
+        cooldowns::check(start).and_then(|_| cooldowns::check(end)).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(self.between(start, end))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Recurrence({:?}, start={}, tz={:?})", self.rule, self.start, self.tz)
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const HOUR: i64 = 3600;
+
+    fn utc(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> i64 {
+        tz::days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * HOUR + minute * 60
+    }
+
+    fn recurrence(rule: &str, start: i64, zone: &str) -> Recurrence {
+        Recurrence::new(rule, start, zone, BTreeSet::new()).expect("Valid rule")
+    }
+
+    #[test]
+    fn parts() {
+        let rule = Rule::parse("RRULE:FREQ=MONTHLY;INTERVAL=2;BYDAY=MO,-1FR;BYMONTHDAY=1,-1;BYMONTH=3,1;BYHOUR=19,9;BYMINUTE=30;COUNT=5").expect("Valid rule");
+        assert_eq!(rule, Rule {
+            frequency: Frequency::Monthly,
+            interval: 2,
+            weekdays: vec![Weekday { nth: 0, weekday: 1 }, Weekday { nth: -1, weekday: 5 }],
+            month_days: vec![1, -1],
+            months: vec![1, 3],
+            hours: vec![9, 19],
+            minutes: vec![30],
+            count: Some(5),
+            until: None,
+        });
+    }
+
+    #[parameterized(
+        in_utc = {"20241231T230000Z", Until::Time(utc(2024, 12, 31, 23, 0))},
+        local = {"20241231T230000", Until::Local(utc(2024, 12, 31, 23, 0))},
+        date = {"20241231", Until::Date(tz::days_from_civil(2024, 12, 31))},
+    )]
+    fn untils(text: &str, expected: Until) {
+        assert_eq!(until(text), Ok(expected));
+    }
+
+    #[parameterized(
+        no_frequency = {"INTERVAL=2", "it has no FREQ"},
+        frequency = {"FREQ=HOURLY", "Invalid FREQ \"HOURLY\""},
+        not_a_part = {"FREQ=DAILY;WEEKLY", "Invalid rule part \"WEEKLY\""},
+        unsupported = {"FREQ=DAILY;BYSETPOS=1", "Unsupported rule part \"BYSETPOS\""},
+        interval = {"FREQ=DAILY;INTERVAL=0", "Invalid INTERVAL \"0\""},
+        weekday = {"FREQ=WEEKLY;BYDAY=TUE", "Invalid BYDAY \"TUE\""},
+        nth = {"FREQ=MONTHLY;BYDAY=6MO", "Invalid BYDAY \"6MO\""},
+        zero_nth = {"FREQ=MONTHLY;BYDAY=0MO", "Invalid BYDAY \"0MO\""},
+        short = {"FREQ=MONTHLY;BYDAY=M", "Invalid BYDAY \"M\""},
+        month_day = {"FREQ=MONTHLY;BYMONTHDAY=32", "Invalid BYMONTHDAY \"32\""},
+        zero_month_day = {"FREQ=MONTHLY;BYMONTHDAY=0", "Invalid BYMONTHDAY \"0\""},
+        month = {"FREQ=YEARLY;BYMONTH=13", "Invalid BYMONTH \"13\""},
+        hour = {"FREQ=DAILY;BYHOUR=24", "Invalid BYHOUR \"24\""},
+        minute = {"FREQ=DAILY;BYMINUTE=60", "Invalid BYMINUTE \"60\""},
+        count = {"FREQ=DAILY;COUNT=0", "Invalid COUNT \"0\""},
+        until = {"FREQ=DAILY;UNTIL=20240230", "Invalid UNTIL \"20240230\""},
+        until_time = {"FREQ=DAILY;UNTIL=20241231T250000Z", "Invalid UNTIL"},
+        until_zone = {"FREQ=DAILY;UNTIL=20241231T230000+01", "Invalid UNTIL"},
+        both_ends = {"FREQ=DAILY;COUNT=2;UNTIL=20241231", "both COUNT and UNTIL"},
+        weekly_month_day = {"FREQ=WEEKLY;BYMONTHDAY=1", "BYMONTHDAY can't be used with FREQ=WEEKLY"},
+        weekly_nth = {"FREQ=WEEKLY;BYDAY=1MO", "needs FREQ=MONTHLY or FREQ=YEARLY"},
+    )]
+    fn invalid(rule: &str, message: &str) {
+        let error = Rule::parse(rule).expect_err("Invalid rule");
+        assert!(error.contains(message), "{error}");
+    }
+
+    #[test]
+    fn weekly_across_dst() {
+        // tuesdays and thursdays at 19:00 in amsterdam, which is 18:00 UTC in winter and 17:00 in summer,
+        // the clocks going forward on the 31st of march 2024 and back on the 27th of october
+        let event = recurrence("FREQ=WEEKLY;BYDAY=TU,TH", utc(2024, 3, 19, 18, 0), "Europe/Amsterdam");
+        assert_eq!(event.between(utc(2024, 3, 25, 0, 0), utc(2024, 4, 5, 0, 0)), vec![
+            utc(2024, 3, 26, 18, 0),
+            utc(2024, 3, 28, 18, 0),
+            utc(2024, 4, 2, 17, 0),
+            utc(2024, 4, 4, 17, 0),
+        ]);
+        assert_eq!(event.between(utc(2024, 10, 22, 0, 0), utc(2024, 11, 1, 0, 0)), vec![
+            utc(2024, 10, 22, 17, 0),
+            utc(2024, 10, 24, 17, 0),
+            utc(2024, 10, 29, 18, 0),
+            utc(2024, 10, 31, 18, 0),
+        ]);
+    }
+
+    #[parameterized(
+        // the rule doesn't hold on the start, a monday, so the first is the tuesday after
+        weekly = {"FREQ=WEEKLY;BYDAY=TU;BYHOUR=19;BYMINUTE=0", utc(2024, 3, 4, 12, 0), vec![utc(2024, 3, 5, 19, 0), utc(2024, 3, 12, 19, 0), utc(2024, 3, 19, 19, 0)]},
+        every_other_week = {"FREQ=WEEKLY;INTERVAL=2", utc(2024, 3, 6, 12, 0), vec![utc(2024, 3, 6, 12, 0), utc(2024, 3, 20, 12, 0), utc(2024, 4, 3, 12, 0)]},
+        daily = {"FREQ=DAILY;BYHOUR=9,21", utc(2024, 3, 6, 12, 0), vec![utc(2024, 3, 6, 21, 0), utc(2024, 3, 7, 9, 0), utc(2024, 3, 7, 21, 0)]},
+        weekdays = {"FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR", utc(2024, 3, 7, 8, 0), vec![utc(2024, 3, 7, 8, 0), utc(2024, 3, 8, 8, 0), utc(2024, 3, 11, 8, 0)]},
+        first_of_month = {"FREQ=MONTHLY;BYMONTHDAY=1", utc(2024, 3, 6, 12, 0), vec![utc(2024, 4, 1, 12, 0), utc(2024, 5, 1, 12, 0), utc(2024, 6, 1, 12, 0)]},
+        last_of_month = {"FREQ=MONTHLY;BYMONTHDAY=-1", utc(2024, 1, 6, 12, 0), vec![utc(2024, 1, 31, 12, 0), utc(2024, 2, 29, 12, 0), utc(2024, 3, 31, 12, 0)]},
+        // months without a 31st are skipped
+        the_31st = {"FREQ=MONTHLY", utc(2024, 1, 31, 12, 0), vec![utc(2024, 1, 31, 12, 0), utc(2024, 3, 31, 12, 0), utc(2024, 5, 31, 12, 0)]},
+        last_friday = {"FREQ=MONTHLY;BYDAY=-1FR", utc(2024, 3, 1, 20, 0), vec![utc(2024, 3, 29, 20, 0), utc(2024, 4, 26, 20, 0), utc(2024, 5, 31, 20, 0)]},
+        second_monday = {"FREQ=MONTHLY;BYDAY=2MO", utc(2024, 3, 1, 20, 0), vec![utc(2024, 3, 11, 20, 0), utc(2024, 4, 8, 20, 0), utc(2024, 5, 13, 20, 0)]},
+        friday_13th = {"FREQ=MONTHLY;BYDAY=FR;BYMONTHDAY=13", utc(2024, 1, 1, 0, 0), vec![utc(2024, 9, 13, 0, 0), utc(2024, 12, 13, 0, 0), utc(2025, 6, 13, 0, 0)]},
+        quarterly = {"FREQ=MONTHLY;INTERVAL=3", utc(2024, 11, 15, 12, 0), vec![utc(2024, 11, 15, 12, 0), utc(2025, 2, 15, 12, 0), utc(2025, 5, 15, 12, 0)]},
+        yearly = {"FREQ=YEARLY;BYMONTH=6,12;BYMONTHDAY=21", utc(2024, 3, 6, 12, 0), vec![utc(2024, 6, 21, 12, 0), utc(2024, 12, 21, 12, 0), utc(2025, 6, 21, 12, 0)]},
+        leap_day = {"FREQ=YEARLY", utc(2024, 2, 29, 12, 0), vec![utc(2024, 2, 29, 12, 0), utc(2028, 2, 29, 12, 0), utc(2032, 2, 29, 12, 0)]},
+        thanksgiving = {"FREQ=YEARLY;BYMONTH=11;BYDAY=4TH", utc(2024, 1, 1, 18, 0), vec![utc(2024, 11, 28, 18, 0), utc(2025, 11, 27, 18, 0), utc(2026, 11, 26, 18, 0)]},
+        count = {"FREQ=DAILY;COUNT=2", utc(2024, 3, 6, 12, 0), vec![utc(2024, 3, 6, 12, 0), utc(2024, 3, 7, 12, 0)]},
+        until = {"FREQ=DAILY;UNTIL=20240307T120000Z", utc(2024, 3, 6, 12, 0), vec![utc(2024, 3, 6, 12, 0), utc(2024, 3, 7, 12, 0)]},
+        until_date = {"FREQ=DAILY;UNTIL=20240307", utc(2024, 3, 6, 12, 0), vec![utc(2024, 3, 6, 12, 0), utc(2024, 3, 7, 12, 0)]},
+    )]
+    fn occurrences(rule: &str, start: i64, expected: Vec<i64>) {
+        let event = recurrence(rule, start, "UTC");
+        let mut found = event.between(start, utc(2040, 1, 1, 0, 0));
+        found.truncate(3);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn window() {
+        let event = recurrence("FREQ=DAILY", utc(2024, 3, 1, 12, 0), "UTC");
+        // from is included and to isn't
+        assert_eq!(event.between(utc(2024, 3, 5, 12, 0), utc(2024, 3, 7, 12, 0)), vec![utc(2024, 3, 5, 12, 0), utc(2024, 3, 6, 12, 0)]);
+        assert_eq!(event.between(utc(2024, 1, 1, 0, 0), utc(2024, 3, 2, 0, 0)), vec![utc(2024, 3, 1, 12, 0)]);
+        assert_eq!(event.between(utc(2024, 3, 7, 0, 0), utc(2024, 3, 5, 0, 0)), vec![]);
+    }
+
+    #[test]
+    fn count_before_the_window() {
+        let event = recurrence("FREQ=WEEKLY;COUNT=3", utc(2024, 3, 6, 12, 0), "UTC");
+        assert_eq!(event.between(utc(2024, 3, 10, 0, 0), utc(2025, 1, 1, 0, 0)), vec![utc(2024, 3, 13, 12, 0), utc(2024, 3, 20, 12, 0)]);
+    }
+
+    #[test]
+    fn skipped_dates() {
+        let mut skipped = BTreeSet::new();
+        skipped.insert(tz::days_from_civil(2024, 12, 26));
+        let event = Recurrence::new("FREQ=WEEKLY;BYDAY=TH;COUNT=3", utc(2024, 12, 19, 18, 0), "Europe/Amsterdam", skipped).expect("Valid rule");
+        // 19:00 in amsterdam is 18:00 UTC, and the skipped day still counts
+        assert_eq!(event.between(utc(2024, 12, 1, 0, 0), utc(2025, 2, 1, 0, 0)), vec![utc(2024, 12, 19, 18, 0), utc(2025, 1, 2, 18, 0)]);
+    }
+
+    #[test]
+    fn clocks_go_forward() {
+        // 2:30 doesn't happen in amsterdam on the 31st of march 2024, so it is at 3:00 when the clocks go forward, 1:00 UTC
+        let event = recurrence("FREQ=DAILY;BYHOUR=2;BYMINUTE=30", utc(2024, 3, 29, 0, 0), "Europe/Amsterdam");
+        assert_eq!(event.between(utc(2024, 3, 29, 0, 0), utc(2024, 4, 2, 0, 0)), vec![
+            utc(2024, 3, 29, 1, 30),
+            utc(2024, 3, 30, 1, 30),
+            utc(2024, 3, 31, 1, 0),
+            utc(2024, 4, 1, 0, 30),
+        ]);
+    }
+
+    #[test]
+    fn clocks_go_back() {
+        // 2:30 happens twice in amsterdam on the 27th of october 2024, and the event is the first time
+        let event = recurrence("FREQ=DAILY;BYHOUR=2;BYMINUTE=30", utc(2024, 10, 25, 0, 0), "Europe/Amsterdam");
+        assert_eq!(event.between(utc(2024, 10, 26, 0, 0), utc(2024, 10, 29, 0, 0)), vec![
+            utc(2024, 10, 26, 0, 30),
+            utc(2024, 10, 27, 0, 30),
+            utc(2024, 10, 28, 1, 30),
+        ]);
+    }
+
+    #[test]
+    fn repr() {
+        let event = recurrence(" FREQ=WEEKLY;BYDAY=TU ", 0, "UTC");
+        assert_eq!(event.__repr__(), "Recurrence(\"FREQ=WEEKLY;BYDAY=TU\", start=0, tz=\"UTC\")");
+    }
+}
+// COV_EXCL_STOP
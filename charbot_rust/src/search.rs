@@ -0,0 +1,466 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Full text search of documents like FAQ entries and tags, kept in memory, ranked with BM25 and with
+//! snippets of where they matched.
+mod stem; // COV_EXCL_LINE
+
+use std::collections::HashMap;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::metrics;
+use stem::Language;
+
+/// How quickly more of a word in a document stops counting for more, from BM25.
+const K1: f64 = 1.2;
+/// How much a long document's words count for less, from BM25.
+const B: f64 = 0.75;
+/// How many times a word in the title counts, since a title says what the document is about.
+const TITLE_WEIGHT: f64 = 3.0;
+/// How many words a snippet has.
+const SNIPPET_WORDS: usize = 30;
+
+/// Words that are in nearly everything, in the languages the bot is translated to, which are left out
+/// of queries unless they're all there is.
+const STOP_WORDS: [(Language, &[&str]); 4] = [
+    (Language::English, &["a", "an", "and", "are", "do", "for", "how", "i", "in", "is", "it", "of", "on", "or", "the", "to", "what", "with"]),
+    (Language::Spanish, &["a", "de", "del", "el", "en", "es", "la", "las", "lo", "los", "que", "un", "una", "y"]),
+    (Language::French, &["a", "au", "d", "de", "des", "du", "en", "est", "et", "l", "la", "le", "les", "qu", "que", "un", "une"]),
+    (Language::Dutch, &["de", "een", "en", "het", "in", "is", "op", "te", "van"]),
+];
+
+/// The letters `letter` is compared as, without accents and with ligatures spelled out, so a query
+/// finds a word whether or not it was typed with its accents.
+fn fold(letter: char) -> &'static str {
+    match letter {
+        'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' => "a",
+        'é' | 'è' | 'ê' | 'ë' => "e",
+        'í' | 'ì' | 'î' | 'ï' => "i",
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' => "o",
+        'ú' | 'ù' | 'û' | 'ü' => "u",
+        'ý' | 'ÿ' => "y",
+        'ç' => "c",
+        'ñ' => "n",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        _ => "",
+    }
+}
+
+/// The language of the stemmer and stop words for `locale`, by its language, so "en-GB" is English too.
+fn language(locale: &str) -> Result<Language, String> {
+    match locale.split(['-', '_']).next().unwrap_or_default().to_lowercase().as_str() {
+        "en" => Ok(Language::English),
+        "es" => Ok(Language::Spanish),
+        "fr" => Ok(Language::French),
+        "nl" => Ok(Language::Dutch),
+        _ => Err(format!("Invalid locale {locale:?}, it must be one of en-US, es-ES, fr or nl")),
+    }
+}
+
+/// A word of a text, as it's searched for, and where it is in the text, in bytes.
+#[derive(Clone, Debug, PartialEq)]
+struct Token {
+    term: String,
+    start: usize,
+    end: usize,
+}
+
+/// The words of `text`, lowercase, without accents and stemmed for `language`.
+///
+/// Apostrophes are dropped without splitting the word, so "don't" is "dont", apart from after a single
+/// letter or "qu", like the french l' and qu', which are words of their own.
+fn tokenize(text: &str, language: Option<Language>) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut start = 0;
+    let mut finish = |word: &mut String, start: usize, end: usize| {
+        if !word.is_empty() {
+            let term = language.map_or_else(|| word.clone(), |language| stem::stem(word, language));
+            tokens.push(Token { term, start, end });
+            word.clear();
+        }
+    };
+    for (index, letter) in text.char_indices() {
+        if letter.is_alphanumeric() {
+            if word.is_empty() {
+                start = index;
+            }
+            for lower in letter.to_lowercase() {
+                match fold(lower) {
+                    "" => word.push(lower),
+                    folded => word.push_str(folded),
+                }
+            }
+        } else if matches!(letter, '\'' | '\u{2019}') && word.chars().count() > 1 && word != "qu" {
+            continue;
+        } else if !matches!(letter, '\u{300}'..='\u{36f}') {
+            finish(&mut word, start, index);
+        }
+    }
+    finish(&mut word, start, text.len());
+    tokens
+}
+
+/// What documents can be told apart by, which python gives as an int or a str.
+#[derive(FromPyObject, Clone, Debug, PartialEq, Eq, Hash)]
+enum Id {
+    #[pyo3(transparent)]
+    Int(i64),
+    #[pyo3(transparent)]
+    Text(String),
+}
+
+impl IntoPy<PyObject> for Id {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Id::Int(id) => id.into_py(py),
+            Id::Text(id) => id.into_py(py),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Document {
+    id: Id,
+    title: String,
+    body: String,
+    /// How many of each word the title and body have, with the title's counting for more.
+    terms: HashMap<String, f64>,
+    length: f64,
+}
+
+/// A document a search found.
+#[pyclass(module = "search")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)] // COV_EXCL_LINE
+pub struct Hit {
+    id: Id,
+    /// The document's title.
+    #[pyo3(get)]
+    title: String,
+    /// How well it matched, which is only comparable with the scores of the same search.
+    #[pyo3(get)]
+    score: f64,
+    /// Where in the body it matched best, with the words that matched in bold.
+    #[pyo3(get)]
+    snippet: String,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Hit {
+    /// The document's id, as it was added.
+    #[getter] // COV_EXCL_LINE
+    fn id(&self, py: Python) -> PyObject {
+        self.id.clone().into_py(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Hit(title={:?}, score={:.3})", self.title, self.score)
+    }
+}
+
+/// Documents, like FAQ entries or tags, to search for words in.
+#[pyclass(module = "search")] // COV_EXCL_LINE
+#[derive(Clone, Debug, PartialEq)]
+pub struct Index {
+    locale: Option<String>,
+    language: Option<Language>,
+    /// The documents, where a removed one leaves a gap so the others keep their place.
+    documents: Vec<Option<Document>>,
+    ids: HashMap<Id, usize>,
+    /// The documents each word is in, and how many times.
+    postings: HashMap<String, HashMap<usize, f64>>,
+    total_length: f64,
+}
+
+impl Index {
+    fn new(locale: Option<String>) -> Result<Self, String> {
+        let language = locale.as_deref().map(language).transpose()?;
+        Ok(Index { locale, language, documents: Vec::new(), ids: HashMap::new(), postings: HashMap::new(), total_length: 0.0 })
+    }
+
+    fn add(&mut self, id: Id, title: String, body: String) {
+        self.remove(&id);
+        let mut terms = HashMap::new();
+        for (text, weight) in [(&title, TITLE_WEIGHT), (&body, 1.0)] {
+            for token in tokenize(text, self.language) {
+                *terms.entry(token.term).or_insert(0.0) += weight;
+            }
+        }
+        let slot = self.documents.len();
+        for (term, count) in &terms {
+            self.postings.entry(term.clone()).or_default().insert(slot, *count);
+        }
+        let length = terms.values().sum();
+        self.total_length += length;
+        self.ids.insert(id.clone(), slot);
+        self.documents.push(Some(Document { id, title, body, terms, length }));
+    }
+
+    fn remove(&mut self, id: &Id) -> bool {
+        let Some((slot, document)) = self.ids.remove(id).and_then(|slot| self.documents[slot].take().map(|document| (slot, document))) else {
+            return false;
+        };
+        for term in document.terms.keys() {
+            if let Some(postings) = self.postings.get_mut(term) {
+                postings.remove(&slot);
+                if postings.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        self.total_length -= document.length;
+        true
+    }
+
+    /// The words of `query` to look for, without the stop words unless that's all there is.
+    fn query_terms(&self, query: &str) -> Vec<String> {
+        let mut terms = tokenize(query, self.language).into_iter().map(|token| token.term).collect::<Vec<_>>();
+        terms.sort();
+        terms.dedup();
+        let stop_words = STOP_WORDS.iter().find(|(language, _)| Some(*language) == self.language).map_or(&[][..], |(_, words)| words);
+        let stop = |term: &String| stop_words.iter().any(|word| self.language.map_or_else(|| word.to_string(), |language| stem::stem(word, language)) == *term);
+        if terms.iter().all(stop) {
+            terms
+        } else {
+            terms.into_iter().filter(|term| !stop(term)).collect()
+        }
+    }
+
+    /// The `limit` documents that match `query` best, best first, and in the order they were added when
+    /// they're as good.
+    fn search(&self, query: &str, limit: usize) -> Vec<Hit> {
+        let terms = self.query_terms(query);
+        let count = self.ids.len() as f64;
+        let average = self.total_length / count.max(1.0);
+        let mut scores = HashMap::new();
+        for term in &terms {
+            let Some(postings) = self.postings.get(term) else {
+                continue;
+            };
+            let found = postings.len() as f64;
+            let idf = (1.0 + (count - found + 0.5) / (found + 0.5)).ln();
+            for (slot, frequency) in postings {
+                let length = self.documents[*slot].as_ref().map_or(0.0, |document| document.length);
+                let weight = frequency * (K1 + 1.0) / (frequency + K1 * (1.0 - B + B * length / average));
+                *scores.entry(*slot).or_insert(0.0) += idf * weight;
+            }
+        }
+        let mut ranked = scores.into_iter().collect::<Vec<_>>();
+        ranked.sort_by(|(slot, score), (other, other_score)| other_score.total_cmp(score).then(slot.cmp(other)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .filter_map(|(slot, score)| self.documents[slot].as_ref().map(|document| (document, score)))
+            .map(|(document, score)| Hit { id: document.id.clone(), title: document.title.clone(), score, snippet: snippet(&document.body, &terms, self.language) })
+            .collect()
+    }
+}
+
+/// The `SNIPPET_WORDS` words of `body` with the most of `terms` in them, with those in bold, on one line,
+/// and with an ellipsis where the body goes on.
+fn snippet(body: &str, terms: &[String], language: Option<Language>) -> String {
+    let tokens = tokenize(body, language);
+    if tokens.is_empty() {
+        return String::new();
+    }
+    let hits = tokens.iter().map(|token| terms.contains(&token.term)).collect::<Vec<_>>();
+    let size = SNIPPET_WORDS.min(tokens.len());
+    // the first window with the most matches, started a few words before the first of them
+    let mut best = (hits[..size].iter().filter(|hit| **hit).count(), 0);
+    let mut matched = best.0;
+    for start in 1..=tokens.len() - size {
+        matched = matched + usize::from(hits[start + size - 1]) - usize::from(hits[start - 1]);
+        if matched > best.0 {
+            best = (matched, start);
+        }
+    }
+    let first_hit = (best.1..best.1 + size).find(|index| hits[*index]).unwrap_or(best.1);
+    let start = first_hit.saturating_sub(3).min(tokens.len() - size);
+    let window = &tokens[start..start + size];
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut last = window[0].start;
+    for (token, hit) in window.iter().zip(&hits[start..]) {
+        // the text between words, with its lines and spaces as one space
+        let between = &body[last..token.start];
+        if !between.is_empty() {
+            let trimmed = between.split_whitespace().collect::<Vec<_>>().join(" ");
+            let space = |side: Option<char>| side.is_some_and(char::is_whitespace);
+            snippet.push_str(if space(between.chars().next()) { " " } else { "" });
+            snippet.push_str(&trimmed);
+            snippet.push_str(if space(between.chars().last()) && !trimmed.is_empty() { " " } else { "" });
+        }
+        let word = &body[token.start..token.end];
+        if *hit {
+            snippet.extend(["**", word, "**"]);
+        } else {
+            snippet.push_str(word);
+        }
+        last = token.end;
+    }
+    if start + size < tokens.len() {
+        snippet.push('…');
+    } else {
+        snippet.push_str(body[last..].trim_end());
+    }
+    snippet
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Index {
+    #[new]
+    #[pyo3(signature = (locale="en-US"))]
+    fn py_new(locale: Option<&str>) -> PyResult<Self> {
+        Index::new(locale.map(String::from)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The locale words are stemmed for, or None for not stemming them.
+    #[getter] // COV_EXCL_LINE
+    fn locale(&self) -> Option<String> {
+        self.locale.clone()
+    }
+
+    #[pyo3(name = "add")]
+    fn py_add(&mut self, id: Id, title: String, body: String) {
+        self.add(id, title, body);
+    }
+
+    #[pyo3(name = "remove")]
+    fn py_remove(&mut self, id: Id) -> bool {
+        self.remove(&id)
+    }
+
+    #[pyo3(name = "search", signature = (query, limit=10))]
+    fn py_search(&self, py: Python, query: &str, limit: usize) -> Vec<Hit> {
+        metrics::timed("search.Index.search", || py.allow_threads(|| self.search(query, limit)))
+    }
+
+    fn __contains__(&self, id: Id) -> bool {
+        self.ids.contains_key(&id)
+    }
+
+    fn __len__(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Index(locale={:?}, documents={})", self.locale, self.ids.len())
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based full text search of FAQ entries and tags";
+
+pub(crate) fn register_search(py: Python, m: &PyModule) -> PyResult<()> {
+    let search = PyModule::new(py, "_search")?;
+    search.add_class::<Index>()?;
+    search.add_class::<Hit>()?;
+    search.add("__doc__", DOCSTRING)?;
+    m.add_submodule(search)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn faq() -> Index {
+        let mut index = Index::new(Some(String::from("en-US"))).unwrap();
+        index.add(Id::Int(1), String::from("Server rules"), String::from("Be kind to each other.\nNo spamming, and no advertising other servers without asking a moderator."));
+        index.add(Id::Int(2), String::from("How to get roles"), String::from("Roles are given for levels. Run /rank to see yours, and read the rules first."));
+        index.add(Id::Text(String::from("banned")), String::from("Appealing a ban"), String::from("If you were banned, you can appeal by emailing the moderators."));
+        index
+    }
+
+    fn terms(tokens: Vec<Token>) -> Vec<String> {
+        tokens.into_iter().map(|token| token.term).collect()
+    }
+
+    #[parameterized(
+        plain = {"Hello, world!", None, &["hello", "world"]},
+        accents = {"Règles du café", None, &["regles", "du", "cafe"]},
+        combining = {"Re\u{301}gles", None, &["regles"]},
+        apostrophes = {"don't l'équipe qu'il", None, &["dont", "l", "equipe", "qu", "il"]},
+        stemmed = {"Running the rules", Some(Language::English), &["run", "the", "rul"]},
+    )]
+    fn tokenizes(text: &str, language: Option<Language>, expected: &[&str]) {
+        assert_eq!(terms(tokenize(text, language)), expected);
+    }
+
+    #[test]
+    fn token_positions() {
+        let text = "the Règles, ok";
+        let tokens = tokenize(text, None);
+        assert_eq!(tokens.iter().map(|token| &text[token.start..token.end]).collect::<Vec<_>>(), ["the", "Règles", "ok"]);
+    }
+
+    #[test]
+    fn ranks() {
+        let index = faq();
+        let hits = index.search("rules", 10);
+        // the title counts for more than the body
+        assert_eq!(hits.iter().map(|hit| hit.id.clone()).collect::<Vec<_>>(), [Id::Int(1), Id::Int(2)]);
+        assert!(hits[0].score > hits[1].score);
+        // stemmed, so another form of the word is found
+        assert_eq!(index.search("ruling", 10).len(), 2);
+        assert_eq!(index.search("appeal my ban", 1)[0].id, Id::Text(String::from("banned")));
+        // stop words are left out, and the limit is kept to
+        assert_eq!(index.search("how to get the roles", 10)[0].id, Id::Int(2));
+        assert_eq!(index.search("rules", 1).len(), 1);
+        assert!(index.search("nothing matches", 10).is_empty());
+        assert!(index.search("", 10).is_empty());
+    }
+
+    #[test]
+    fn changes() {
+        let mut index = faq();
+        assert!(index.remove(&Id::Int(1)));
+        assert!(!index.remove(&Id::Int(1)));
+        assert_eq!(index.search("rules", 10).iter().map(|hit| hit.id.clone()).collect::<Vec<_>>(), [Id::Int(2)]);
+        // adding a document with the same id replaces it
+        index.add(Id::Int(2), String::from("Colors"), String::from("Pick a color in #roles."));
+        assert_eq!(index.ids.len(), 2);
+        assert!(index.search("levels", 10).is_empty());
+        assert_eq!(index.search("color", 10)[0].title, "Colors");
+        assert_eq!(index.__repr__(), r#"Index(locale=Some("en-US"), documents=2)"#);
+    }
+
+    #[test]
+    fn locales() {
+        assert_eq!(language("en-GB"), Ok(Language::English));
+        assert_eq!(language("es-ES"), Ok(Language::Spanish));
+        assert_eq!(language("nl"), Ok(Language::Dutch));
+        assert_eq!(language("de"), Err(String::from(r#"Invalid locale "de", it must be one of en-US, es-ES, fr or nl"#)));
+        let mut french = Index::new(Some(String::from("fr"))).unwrap();
+        french.add(Id::Int(1), String::from("Les règles"), String::from("Soyez gentils."));
+        assert_eq!(french.search("regle", 10).len(), 1);
+        // without a locale words are only found as they are
+        let mut plain = Index::new(None).unwrap();
+        plain.add(Id::Int(1), String::from("Rules"), String::new());
+        assert!(plain.search("rule", 10).is_empty());
+        assert_eq!(plain.search("RULES", 10).len(), 1);
+    }
+
+    #[test]
+    fn snippets() {
+        let index = faq();
+        assert_eq!(
+            index.search("advertising", 1)[0].snippet,
+            "Be kind to each other. No spamming, and no **advertising** other servers without asking a moderator."
+        );
+        let long = format!("{} the answer is here. {}", "filler ".repeat(40), "more ".repeat(40));
+        let snippet = snippet(&long, &[String::from("answer")], Some(Language::English));
+        assert!(snippet.starts_with("…filler filler the **answer** is here. more"), "{snippet}");
+        assert!(snippet.ends_with('…'));
+        assert_eq!(snippet.matches(' ').count(), SNIPPET_WORDS - 1);
+        assert_eq!(super::snippet("", &[], None), "");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,160 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Light stemmers for the languages the bot is translated to, which take the common endings off words
+//! so "running" and "runs" are found by "run", without the rules and exceptions of full stemmers.
+//!
+//! They're meant for words that are already lowercase and without accents, and only have to stem the
+//! same word the same way, since documents and queries are stemmed alike, so a stem needn't be a word.
+
+/// A language a stemmer is for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    Spanish,
+    French,
+    Dutch,
+}
+
+fn is_vowel(letter: char) -> bool {
+    matches!(letter, 'a' | 'e' | 'i' | 'o' | 'u' | 'y')
+}
+
+/// `word` without `suffix`, if it ends with it and is left with at least `rest` letters.
+fn strip<'a>(word: &'a str, suffix: &str, rest: usize) -> Option<&'a str> {
+    word.strip_suffix(suffix).filter(|stem| stem.chars().count() >= rest)
+}
+
+/// `word` with a doubled consonant at the end made single, like "runn" to "run".
+fn undouble(word: &str, keep: &[char]) -> String {
+    let mut letters = word.chars().rev();
+    match (letters.next(), letters.next()) {
+        (Some(last), Some(before)) if last == before && !is_vowel(last) && !keep.contains(&last) => word[..word.len() - last.len_utf8()].to_string(),
+        _ => word.to_string(),
+    }
+}
+
+fn english(word: &str) -> String {
+    let mut stem = if let Some(stem) = strip(word, "ies", 2).filter(|stem| !stem.ends_with(['a', 'e'])) {
+        format!("{stem}y")
+    } else if let Some(stem) = ["sses", "shes", "ches", "xes", "zes"].iter().find_map(|suffix| word.strip_suffix(suffix).map(|_| &word[..word.len() - 2])) {
+        stem.to_string()
+    } else if let Some(stem) = strip(word, "s", 3).filter(|stem| !stem.ends_with(['s', 'u', 'i'])) {
+        stem.to_string()
+    } else {
+        word.to_string()
+    };
+    // the stem has to have a vowel, so "string" and "bed" are left alone
+    if let Some(base) = ["ing", "ed"].iter().find_map(|suffix| strip(&stem, suffix, 3)).filter(|base| base.chars().any(is_vowel)) {
+        stem = undouble(base, &['l', 's', 'z']);
+    }
+    if let Some(base) = strip(&stem, "ly", 5) {
+        stem = base.to_string();
+    }
+    if let Some(base) = strip(&stem, "e", 3).filter(|base| !base.ends_with('e')) {
+        stem = base.to_string();
+    }
+    stem
+}
+
+fn spanish(word: &str) -> String {
+    let mut stem = strip(word, "es", 4).or_else(|| strip(word, "s", 3)).unwrap_or(word).to_string();
+    if let Some(base) = strip(&stem, "mente", 3) {
+        stem = base.to_string();
+    }
+    // the masculine and the feminine, like "gato" and "gata"
+    if let Some(base) = ["a", "o", "e"].iter().find_map(|suffix| strip(&stem, suffix, 3)) {
+        stem = base.to_string();
+    }
+    stem
+}
+
+fn french(word: &str) -> String {
+    let mut stem = strip(word, "aux", 3).map(|stem| format!("{stem}al")).unwrap_or_else(|| word.to_string());
+    if let Some(base) = strip(&stem, "s", 3).filter(|stem| !stem.ends_with('s')).or_else(|| strip(&stem, "x", 3)) {
+        stem = base.to_string();
+    }
+    if let Some(base) = ["ment", "er", "ez"].iter().find_map(|suffix| strip(&stem, suffix, 4)) {
+        stem = base.to_string();
+    }
+    // the feminine, and the past participles, like "arrivée" and "arrivé"
+    for _ in 0..2 {
+        if let Some(base) = strip(&stem, "e", 3) {
+            stem = base.to_string();
+        }
+    }
+    undouble(&stem, &[])
+}
+
+fn dutch(word: &str) -> String {
+    let mut stem = strip(word, "heden", 2).map(|stem| format!("{stem}heid")).unwrap_or_else(|| word.to_string());
+    if let Some(base) = strip(&stem, "en", 3) {
+        stem = undouble(base, &[]);
+    } else if let Some(base) = strip(&stem, "s", 3).filter(|stem| stem.ends_with('e') || ["el", "em", "en", "er"].iter().any(|end| stem.ends_with(end))) {
+        // plurals with an s are of words ending in an unstressed e, so "huis" is left alone
+        stem = base.to_string();
+    }
+    if let Some(base) = strip(&stem, "je", 3) {
+        stem = base.to_string();
+    } else if let Some(base) = strip(&stem, "e", 3) {
+        stem = base.to_string();
+    }
+    // long vowels are written twice before a consonant at the end, like "groot" and "grote"
+    let letters = stem.chars().collect::<Vec<_>>();
+    if let [.., first, second, last] = letters[..] {
+        if first == second && matches!(first, 'a' | 'e' | 'o' | 'u') && !is_vowel(last) {
+            return letters[..letters.len() - 2].iter().chain([&last]).collect();
+        }
+    }
+    stem
+}
+
+/// The stem of `word`, which is lowercase and without accents.
+pub fn stem(word: &str, language: Language) -> String {
+    match language {
+        Language::English => english(word),
+        Language::Spanish => spanish(word),
+        Language::French => french(word),
+        Language::Dutch => dutch(word),
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        english = {Language::English, &[&["run", "runs", "running"], &["rule", "rules", "ruled", "ruling"], &["party", "parties"], &["box", "boxes"], &["quick", "quickly"], &["class", "classes"]]},
+        spanish = {Language::Spanish, &[&["regla", "reglas"], &["gato", "gata", "gatos"], &["cancion", "canciones"], &["rapido", "rapidamente"]]},
+        french = {Language::French, &[&["regle", "regles"], &["arrive", "arrivee", "arrivees", "arriver"], &["cheval", "chevaux"], &["belle", "bel"]]},
+        dutch = {Language::Dutch, &[&["boek", "boeken"], &["regel", "regels"], &["groot", "grote"], &["boom", "bomen"], &["huis", "huisje", "huisjes"], &["mogelijkheid", "mogelijkheden"]]},
+    )]
+    fn stems_alike(language: Language, groups: &[&[&str]]) {
+        for group in groups {
+            let stems = group.iter().map(|word| stem(word, language)).collect::<Vec<_>>();
+            assert!(stems.iter().all(|stem| *stem == stems[0]), "{group:?} stems to {stems:?}");
+        }
+    }
+
+    #[parameterized(
+        string = {"string", "string"},
+        bed = {"bed", "bed"},
+        thing = {"thing", "thing"},
+        bus = {"bus", "bus"},
+        short = {"is", "is"},
+    )]
+    fn leaves_alone(word: &str, expected: &str) {
+        assert_eq!(stem(word, Language::English), expected);
+    }
+
+    #[test]
+    fn apart() {
+        // words that only start the same aren't the same
+        assert_ne!(stem("ruler", Language::English), stem("rule", Language::English));
+        assert_ne!(stem("parties", Language::English), stem("part", Language::English));
+        assert_ne!(stem("boek", Language::Dutch), stem("boer", Language::Dutch));
+    }
+}
+// COV_EXCL_STOP
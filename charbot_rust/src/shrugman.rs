@@ -0,0 +1,337 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::BTreeSet;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::assets;
+use crate::buffer::{Buffer, Bytes};
+use crate::fluent::bundle::{self, AvailableLocales};
+use crate::persist::{self, Persist};
+use crate::metrics;
+
+
+/// The shrug drawn one character per miss, the game is lost once it is complete.
+const SHRUG: &str = r"¯\_(ツ)_/¯";
+
+fn words(locale: AvailableLocales) -> impl Iterator<Item = &'static str> {
+    let locale = match locale {
+        // runtime locales have no words of their own, and the pseudo locale is english underneath
+        AvailableLocales::Custom(_) | AvailableLocales::Pseudo => AvailableLocales::AmericanEnglish,
+        locale => locale,
+    };
+    let list = assets::static_text(&format!("shrugman/words/{}.txt", locale.as_str())).expect("The word lists are assets");
+    list.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// The letter a guess of `letter` is, so `e` finds `é` and `è` too, since not every keyboard has them.
+///
+/// Letters that are their own letter in the alphabets of the locales, like `ñ`, are left alone.
+pub(crate) fn fold(letter: char) -> char {
+    match letter {
+        'á' | 'à' | 'â' | 'ä' => 'a',
+        'é' | 'è' | 'ê' | 'ë' => 'e',
+        'í' | 'ì' | 'î' | 'ï' => 'i',
+        'ó' | 'ò' | 'ô' | 'ö' => 'o',
+        'ú' | 'ù' | 'û' | 'ü' => 'u',
+        'ç' => 'c',
+        _ => letter,
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "shrugman")]
+#[derive(Debug)]
+struct Game {
+    word: &'static str,
+    locale: AvailableLocales,
+    /// The guesses, folded.
+    guessed: BTreeSet<char>,
+    misses: usize,
+}
+// COV_EXCL_STOP
+
+impl Game {
+    fn new(locale: AvailableLocales, rng: &mut StdRng) -> Self {
+        Game {
+            word: words(locale).choose(rng).expect("Every locale has words"), // COV_EXCL_LINE
+            locale,
+            guessed: BTreeSet::new(),
+            misses: 0,
+        }
+    }
+
+    fn max_misses() -> usize {
+        SHRUG.chars().count()
+    }
+
+    fn is_revealed(&self, letter: char) -> bool {
+        !letter.is_alphabetic() || self.guessed.contains(&fold(letter))
+    }
+
+    fn make_guess(&mut self, letter: &str) -> Result<bool, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        let mut chars = letter.chars().flat_map(char::to_lowercase);
+        let guess = match (chars.next(), chars.next()) {
+            (Some(guess), None) if guess.is_alphabetic() => fold(guess),
+            _ => return Err(format!("Invalid guess {letter:?}, it must be a single letter")),
+        };
+        if !self.guessed.insert(guess) {
+            return Err(format!("{guess} was already guessed"));
+        }
+        let hit = self.word.chars().any(|letter| fold(letter) == guess);
+        if !hit {
+            self.misses += 1;
+        }
+        Ok(hit)
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Game {
+    #[new]
+    #[pyo3(signature = (locale="en-US"))]
+    fn __new__(locale: &str) -> Self { // COV_EXCL_LINE
+        Self::new(bundle::closest_locale(locale), &mut StdRng::from_entropy())
+    }
+
+    #[getter]
+    fn word(&self) -> &'static str {
+        self.word
+    }
+
+    #[getter]
+    fn locale(&self) -> &'static str {
+        self.locale.as_str()
+    }
+
+    #[getter]
+    fn guessed(&self) -> Vec<char> {
+        self.guessed.iter().copied().collect()
+    }
+
+    #[getter]
+    fn misses(&self) -> usize {
+        self.misses
+    }
+
+    #[getter]
+    fn remaining_misses(&self) -> usize {
+        Self::max_misses().saturating_sub(self.misses)
+    }
+
+    /// The word with a `_` for each letter not guessed yet, spaced out so they can be counted.
+    #[getter]
+    fn masked(&self) -> String {
+        self.word
+            .chars()
+            .map(|letter| if self.is_revealed(letter) { letter } else { '_' })
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// As much of the shrug as there have been misses.
+    #[getter]
+    fn shrug(&self) -> String {
+        SHRUG.chars().take(self.misses).collect()
+    }
+
+    fn guess(&mut self, letter: &str) -> PyResult<bool> {
+        metrics::observe("shrugman.guess", || self.make_guess(letter).map_err(PyErr::new::<PyValueError, _>))
+    }
+
+    fn is_won(&self) -> bool {
+        self.word.chars().all(|letter| self.is_revealed(letter))
+    }
+
+    fn is_lost(&self) -> bool {
+        self.misses >= Self::max_misses()
+    }
+
+    fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    word: String,
+    locale: String,
+    guessed: BTreeSet<char>,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "shrugman";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State {
+            word: self.word.to_string(),
+            locale: self.locale.as_str().to_string(),
+            guessed: self.guessed.clone(),
+        }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        let locale = bundle::closest_locale(&state.locale);
+        if locale.as_str() != state.locale {
+            return Err(format!("Unknown locale {:?}", state.locale));
+        }
+        let word = words(locale)
+            .find(|word| *word == state.word)
+            .ok_or_else(|| format!("{:?} isn't a {} word", state.word, state.locale))?;
+        if let Some(guess) = state.guessed.iter().find(|guess| !guess.is_alphabetic() || !guess.is_lowercase() || fold(**guess) != **guess) {
+            return Err(format!("Invalid guess {guess:?}"));
+        }
+        let misses = state.guessed.iter().filter(|guess| !word.chars().any(|letter| fold(letter) == **guess)).count();
+        if misses > Self::max_misses() {
+            return Err(format!("{misses} misses is more than the game allows"));
+        }
+        Ok(Game { word, locale, guessed: state.guessed, misses })
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of shrugman, hangman with a shrug";
+
+pub(crate) fn register_shrugman(py: Python, m: &PyModule) -> PyResult<()> {
+    let shrugman = PyModule::new(py, "_shrugman")?;
+    shrugman.add_class::<Game>()?;
+    shrugman.add("__doc__", DOCSTRING)?;
+    m.add_submodule(shrugman)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn game(locale: AvailableLocales, word: &'static str) -> Game {
+        Game {
+            word,
+            ..Game::new(locale, &mut StdRng::from_seed([0; 32]))
+        }
+    }
+
+    #[parameterized(
+        english = {AvailableLocales::AmericanEnglish},
+        spanish = {AvailableLocales::EuropeanSpanish},
+        french = {AvailableLocales::French},
+        dutch = {AvailableLocales::Dutch},
+    )]
+    fn word_lists(locale: AvailableLocales) {
+        let words = words(locale).collect::<Vec<_>>();
+        assert!(words.len() >= 50);
+        for word in words {
+            assert!(word.chars().all(|letter| letter.is_alphabetic() && letter.is_lowercase()), "Bad word {word}");
+        }
+    }
+
+    #[test]
+    fn localized_words() {
+        let mut rng = StdRng::from_seed([0; 32]);
+        let spanish = Game::new(AvailableLocales::EuropeanSpanish, &mut rng);
+        assert!(words(AvailableLocales::EuropeanSpanish).any(|word| word == spanish.word));
+        assert!(!words(AvailableLocales::AmericanEnglish).any(|word| word == "cigüeña"));
+        assert_eq!(Game::__new__("es-MX").locale(), "es-ES");
+        assert_eq!(Game::__new__("pt-BR").locale(), "en-US");
+    }
+
+    #[test]
+    fn guessing() {
+        let mut game = game(AvailableLocales::AmericanEnglish, "kitten");
+        assert_eq!(game.masked(), "_ _ _ _ _ _");
+        assert_eq!(game.make_guess("T"), Ok(true));
+        assert_eq!(game.masked(), "_ _ t t _ _");
+        assert_eq!(game.make_guess("z"), Ok(false));
+        assert_eq!(game.misses(), 1);
+        assert_eq!(game.remaining_misses(), 8);
+        assert_eq!(game.shrug(), "¯");
+        game.make_guess("t").expect_err("Expected the repeat to be rejected");
+        game.make_guess("ab").expect_err("Expected the guess to be rejected");
+        game.make_guess("1").expect_err("Expected the guess to be rejected");
+        game.make_guess("").expect_err("Expected the guess to be rejected");
+        assert_eq!(game.guessed(), vec!['t', 'z']);
+        for letter in ["k", "i", "e", "n"] {
+            assert_eq!(game.make_guess(letter), Ok(true));
+        }
+        assert!(game.is_won());
+        assert!(game.is_over());
+        game.make_guess("q").expect_err("Expected the game to be over");
+    }
+
+    #[test]
+    fn accents() {
+        let mut game = game(AvailableLocales::EuropeanSpanish, "cigüeña");
+        assert_eq!(game.make_guess("u"), Ok(true));
+        assert_eq!(game.make_guess("n"), Ok(false));
+        assert_eq!(game.make_guess("Ñ"), Ok(true));
+        assert_eq!(game.masked(), "_ _ _ ü _ ñ _");
+        game.make_guess("ü").expect_err("Expected ü to be the same guess as u");
+    }
+
+    #[test]
+    fn losing() {
+        let mut game = game(AvailableLocales::French, "île");
+        for letter in "abcdfghjk".chars() {
+            assert_eq!(game.make_guess(&letter.to_string()), Ok(false));
+        }
+        assert!(game.is_lost());
+        assert!(!game.is_won());
+        assert_eq!(game.remaining_misses(), 0);
+        assert_eq!(game.shrug(), SHRUG);
+        assert_eq!(game.masked(), "_ _ _");
+    }
+
+    #[test]
+    fn persists() {
+        let mut game = game(AvailableLocales::EuropeanSpanish, "cigüeña");
+        for letter in ["u", "n", "z"] {
+            game.make_guess(letter).expect("A new guess");
+        }
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Game = persist::from_json(&json).expect("Failed to load");
+        assert_eq!((loaded.word, loaded.locale, &loaded.guessed, loaded.misses), (game.word, game.locale, &game.guessed, game.misses));
+        assert_eq!(loaded.masked(), game.masked());
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        assert_eq!(persist::from_bytes::<Game>(&bytes).map(|loaded| loaded.guessed), Ok(game.guessed.clone()));
+        let state = |word: &str, locale: &str, guessed: &str| State {
+            word: word.to_string(),
+            locale: locale.to_string(),
+            guessed: guessed.chars().collect(),
+        };
+        Game::load(state("cigüeña", "en-US", "")).expect_err("Expected the word of another locale to be rejected");
+        Game::load(state("kitten", "klingon", "")).expect_err("Expected the locale to be rejected");
+        Game::load(state("kitten", "en-US", "é")).expect_err("Expected the unfolded guess to be rejected");
+        Game::load(state("kitten", "en-US", "1")).expect_err("Expected the guess to be rejected");
+        Game::load(state("kitten", "en-US", "abcdfghjlm")).expect_err("Expected too many misses to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,428 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod fair; // COV_EXCL_LINE
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use sha2::{Digest, Sha256};
+use crate::crypto::hex;
+use crate::metrics;
+use crate::slots::fair::stops;
+
+/// The most stop combinations `return_to_player` will go through.
+const MAX_COMBINATIONS: usize = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Payout {
+    symbol: String,
+    count: usize,
+    multiplier: u64,
+}
+
+// COV_EXCL_START
+#[pyclass(module = "slots")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine {
+    reels: Vec<Vec<String>>,
+    rows: usize,
+    lines: Vec<Vec<usize>>,
+    payouts: Vec<Payout>,
+}
+// COV_EXCL_STOP
+
+impl Machine {
+    fn new(reels: Vec<Vec<String>>, payouts: Vec<(String, usize, u64)>, rows: usize, lines: Option<Vec<Vec<usize>>>) -> Result<Self, String> {
+        if reels.is_empty() {
+            return Err("A machine needs at least one reel".to_string());
+        }
+        let shortest = reels.iter().map(Vec::len).min().unwrap_or(0);
+        if shortest == 0 {
+            return Err("Every reel needs at least one symbol".to_string());
+        }
+        if rows == 0 || rows > shortest {
+            return Err(format!("Invalid number of rows {rows}, it must be from 1 to {shortest}, the length of the shortest reel"));
+        }
+        // one straight line across each row, unless the lines are given
+        let lines = lines.unwrap_or_else(|| (0..rows).map(|row| vec![row; reels.len()]).collect());
+        if lines.is_empty() {
+            return Err("A machine needs at least one line".to_string());
+        }
+        for line in &lines {
+            if line.len() != reels.len() || line.iter().any(|row| *row >= rows) {
+                return Err(format!("Invalid line {line:?}, it needs a row from 0 to {} for each of the {} reels", rows - 1, reels.len()));
+            }
+        }
+        let mut table: Vec<Payout> = Vec::with_capacity(payouts.len());
+        for (symbol, count, multiplier) in payouts {
+            if !reels.iter().any(|reel| reel.contains(&symbol)) {
+                return Err(format!("{symbol} isn't on any reel"));
+            }
+            if count == 0 || count > reels.len() || multiplier == 0 {
+                return Err(format!("Invalid payout for {count} {symbol}, it needs from 1 to {} in a row and a multiplier of at least 1", reels.len()));
+            }
+            if table.iter().any(|payout| payout.symbol == symbol && payout.count == count) {
+                return Err(format!("There's already a payout for {count} {symbol}"));
+            }
+            table.push(Payout { symbol, count, multiplier });
+        }
+        if table.is_empty() {
+            return Err("A machine needs at least one payout".to_string());
+        }
+        Ok(Machine { reels, rows, lines, payouts: table })
+    }
+
+    /// What the window shows with the reels stopped at `stops`, a row at a time, the stop in the top row.
+    fn grid(&self, stops: &[usize]) -> Vec<Vec<String>> {
+        (0..self.rows)
+            .map(|row| self.reels.iter().zip(stops).map(|(reel, stop)| reel[(stop + row) % reel.len()].clone()).collect())
+            .collect()
+    }
+
+    /// The lines in `grid` that win, with the multiplier each pays.
+    ///
+    /// A line wins with a run of the same symbol from the leftmost reel, paying the best multiplier for
+    /// that symbol that the run is long enough for.
+    fn wins<'a>(&self, grid: &'a [Vec<String>]) -> Vec<(usize, &'a str, usize, u64)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let mut symbols = line.iter().enumerate().map(|(reel, row)| grid[*row][reel].as_str());
+                let first = symbols.next()?;
+                let run = 1 + symbols.take_while(|symbol| *symbol == first).count();
+                let multiplier = self.payouts.iter().filter(|payout| payout.symbol == first && payout.count <= run).map(|payout| payout.multiplier).max()?;
+                Some((index, first, run, multiplier))
+            })
+            .collect()
+    }
+
+    fn spin(&self, server_seed: &str, client_seed: &str, nonce: u64, bet: u64) -> Result<Spin, String> {
+        let lengths = self.reels.iter().map(Vec::len).collect::<Vec<_>>();
+        let stops = stops(server_seed, client_seed, nonce, &lengths);
+        let grid = self.grid(&stops);
+        let overflow = || "The bet is too large to pay out".to_string();
+        let cost = bet.checked_mul(self.lines.len() as u64).ok_or_else(overflow)?;
+        let wins = self
+            .wins(&grid)
+            .into_iter()
+            .map(|(line, symbol, count, multiplier)| Ok(LineWin { line, symbol: symbol.to_string(), count, payout: bet.checked_mul(multiplier).ok_or_else(overflow)? }))
+            .collect::<Result<Vec<_>, String>>()?;
+        let winnings = wins.iter().try_fold(0u64, |total, win| total.checked_add(win.payout)).ok_or_else(overflow)?;
+        Ok(Spin {
+            grid,
+            stops,
+            wins,
+            winnings,
+            bet,
+            cost,
+            server_seed_hash: hash_server_seed(server_seed),
+            client_seed: client_seed.to_string(),
+            nonce,
+        })
+    }
+
+    /// What the machine pays back on average, as a fraction of what is bet, found by going through every way the reels can stop.
+    fn return_to_player(&self) -> Result<f64, String> {
+        let combinations = self.reels.iter().try_fold(1usize, |total, reel| total.checked_mul(reel.len())).filter(|total| *total <= MAX_COMBINATIONS);
+        let combinations = combinations.ok_or_else(|| format!("The reels can stop in more than {MAX_COMBINATIONS} ways, too many to go through"))?;
+        let mut stops = vec![0; self.reels.len()];
+        let mut paid = 0u64;
+        for _ in 0..combinations {
+            paid += self.wins(&self.grid(&stops)).iter().map(|(_, _, _, multiplier)| multiplier).sum::<u64>();
+            // count up through the stops, like an odometer with a wheel for each reel
+            for (stop, reel) in stops.iter_mut().zip(&self.reels) {
+                *stop = (*stop + 1) % reel.len();
+                if *stop != 0 {
+                    break;
+                }
+            }
+        }
+        Ok(paid as f64 / (combinations * self.lines.len()) as f64)
+    }
+}
+
+/// A classic three reel machine, with five lines: the three rows and both diagonals.
+fn classic() -> Machine {
+    let reel = "🍒🍋🍊🍇🔔⭐💎🍒🍋🍊🍇🔔🍒🍋🍊🍇🍒🍋🍊🍒".chars().map(String::from).collect::<Vec<_>>();
+    let payouts = [("🍒", 2, 2), ("🍒", 3, 15), ("🍋", 3, 20), ("🍊", 3, 20), ("🍇", 3, 40), ("🔔", 3, 80), ("⭐", 3, 200), ("💎", 3, 500)];
+    let payouts = payouts.into_iter().map(|(symbol, count, multiplier)| (symbol.to_string(), count, multiplier)).collect();
+    let lines = vec![vec![0, 0, 0], vec![1, 1, 1], vec![2, 2, 2], vec![0, 1, 2], vec![2, 1, 0]];
+    Machine::new(vec![reel; 3], payouts, 3, Some(lines)).expect("The classic machine is valid")
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Machine {
+    #[new]
+    #[pyo3(signature = (reels, payouts, rows=3, lines=None))]
+    fn __new__(reels: Vec<Vec<String>>, payouts: Vec<(String, usize, u64)>, rows: usize, lines: Option<Vec<Vec<usize>>>) -> PyResult<Self> { // COV_EXCL_LINE
+        Machine::new(reels, payouts, rows, lines).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    #[pyo3(name = "classic")]
+    fn py_classic() -> Self {
+        classic()
+    }
+
+    #[getter]
+    fn reels(&self) -> Vec<Vec<String>> {
+        self.reels.clone()
+    }
+
+    #[getter]
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    #[getter]
+    fn lines(&self) -> Vec<Vec<usize>> {
+        self.lines.clone()
+    }
+
+    #[getter]
+    fn payouts(&self) -> Vec<(String, usize, u64)> {
+        self.payouts.iter().map(|payout| (payout.symbol.clone(), payout.count, payout.multiplier)).collect()
+    }
+
+    #[pyo3(name = "spin")]
+    fn py_spin(&self, server_seed: &str, client_seed: &str, nonce: u64, bet: u64) -> PyResult<Spin> {
+        metrics::observe("slots.Machine.spin", || self.spin(server_seed, client_seed, nonce, bet).map_err(PyErr::new::<PyValueError, _>))
+    }
+
+    /// Whether `spin` is what the machine spins with the revealed `server_seed`, and the seed is the one that was committed to.
+    fn verify(&self, spin: &Spin, server_seed: &str) -> bool {
+        metrics::timed("slots.Machine.verify", || {
+            hash_server_seed(server_seed) == spin.server_seed_hash
+                && self.spin(server_seed, &spin.client_seed, spin.nonce, spin.bet).is_ok_and(|respun| respun == *spin)
+        })
+    }
+
+    #[pyo3(name = "return_to_player")]
+    fn py_return_to_player(&self) -> PyResult<f64> {
+        metrics::observe("slots.Machine.return_to_player", || self.return_to_player().map_err(PyErr::new::<PyValueError, _>))
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "slots")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineWin {
+    /// The index of the line in the lines of the machine.
+    #[pyo3(get)]
+    line: usize,
+    #[pyo3(get)]
+    symbol: String,
+    /// How many of the symbol are in a row from the left.
+    #[pyo3(get)]
+    count: usize,
+    #[pyo3(get)]
+    payout: u64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl LineWin {
+    fn __repr__(&self) -> String {
+        format!("LineWin(line={}, symbol={:?}, count={}, payout={})", self.line, self.symbol, self.count, self.payout)
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "slots")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Spin {
+    /// The symbols in the window, a row at a time.
+    #[pyo3(get)]
+    grid: Vec<Vec<String>>,
+    /// Where each reel stopped, the index of the symbol in the top row.
+    #[pyo3(get)]
+    stops: Vec<usize>,
+    #[pyo3(get)]
+    wins: Vec<LineWin>,
+    #[pyo3(get)]
+    winnings: u64,
+    /// The bet on each line.
+    #[pyo3(get)]
+    bet: u64,
+    /// The bet on every line together, what the spin costs.
+    #[pyo3(get)]
+    cost: u64,
+    #[pyo3(get)]
+    server_seed_hash: String,
+    #[pyo3(get)]
+    client_seed: String,
+    #[pyo3(get)]
+    nonce: u64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Spin {
+    /// The grid for display, a line of symbols for each row.
+    fn __str__(&self) -> String {
+        self.grid.iter().map(|row| row.concat()).collect::<Vec<_>>().join("\n")
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Spin(winnings={}, cost={})", self.winnings, self.cost)
+    }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+new_server_seed()
+--
+
+Make a random server seed, to keep secret until it is replaced.
+
+Returns
+-------
+str
+    64 hex digits from a cryptographically secure random number generator.
+")]
+fn new_server_seed() -> String {
+    metrics::timed("slots.new_server_seed", || hex(&thread_rng().gen::<[u8; 32]>()))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+hash_server_seed(server_seed, /)
+--
+
+Hash a server seed, to show players before they spin with it.
+
+Once the seed is revealed, anyone can check it has this hash, so it wasn't changed after the spins
+were made, and spin again with it to check the results.
+
+Parameters
+----------
+server_seed : str
+    The server seed.
+
+Returns
+-------
+str
+    The SHA-256 hash of the seed, in hex.
+")]
+fn hash_server_seed(server_seed: &str) -> String {
+    metrics::timed("slots.hash_server_seed", || hex(&Sha256::digest(server_seed.as_bytes())))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based provably fair slot machines";
+
+pub(crate) fn register_slots(py: Python, m: &PyModule) -> PyResult<()> {
+    let slots = PyModule::new(py, "_slots")?;
+    slots.add_class::<Machine>()?;
+    slots.add_class::<LineWin>()?;
+    slots.add_class::<Spin>()?;
+    slots.add_function(wrap_pyfunction!(new_server_seed, slots)?)?;
+    slots.add_function(wrap_pyfunction!(hash_server_seed, slots)?)?;
+    slots.add("__doc__", DOCSTRING)?;
+    m.add_submodule(slots)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(text: &str) -> Vec<String> {
+        text.chars().map(String::from).collect()
+    }
+
+    fn payouts(table: &[(&str, usize, u64)]) -> Vec<(String, usize, u64)> {
+        table.iter().map(|(symbol, count, multiplier)| (symbol.to_string(), *count, *multiplier)).collect()
+    }
+
+    fn simple() -> Machine {
+        Machine::new(vec![symbols("abc"); 3], payouts(&[("a", 2, 1), ("a", 3, 5), ("b", 3, 3)]), 1, None).expect("Expected a valid machine")
+    }
+
+    #[test]
+    fn rejects() {
+        let valid = payouts(&[("a", 3, 5)]);
+        Machine::new(vec![], valid.clone(), 1, None).expect_err("Expected no reels to be rejected");
+        Machine::new(vec![symbols("abc"), vec![]], valid.clone(), 1, None).expect_err("Expected an empty reel to be rejected");
+        Machine::new(vec![symbols("abc"); 3], valid.clone(), 0, None).expect_err("Expected no rows to be rejected");
+        Machine::new(vec![symbols("abc"); 3], valid.clone(), 4, None).expect_err("Expected more rows than symbols to be rejected");
+        Machine::new(vec![symbols("abc"); 3], valid.clone(), 2, Some(vec![])).expect_err("Expected no lines to be rejected");
+        Machine::new(vec![symbols("abc"); 3], valid.clone(), 2, Some(vec![vec![0, 1]])).expect_err("Expected a short line to be rejected");
+        Machine::new(vec![symbols("abc"); 3], valid, 2, Some(vec![vec![0, 1, 2]])).expect_err("Expected a line off the window to be rejected");
+        Machine::new(vec![symbols("abc"); 3], vec![], 1, None).expect_err("Expected no payouts to be rejected");
+        Machine::new(vec![symbols("abc"); 3], payouts(&[("z", 3, 5)]), 1, None).expect_err("Expected a missing symbol to be rejected");
+        Machine::new(vec![symbols("abc"); 3], payouts(&[("a", 4, 5)]), 1, None).expect_err("Expected too long a run to be rejected");
+        Machine::new(vec![symbols("abc"); 3], payouts(&[("a", 3, 0)]), 1, None).expect_err("Expected no multiplier to be rejected");
+        Machine::new(vec![symbols("abc"); 3], payouts(&[("a", 3, 5), ("a", 3, 6)]), 1, None).expect_err("Expected a repeated payout to be rejected");
+    }
+
+    #[test]
+    fn grids() {
+        let machine = classic();
+        let grid = machine.grid(&[0, 19, 6]);
+        assert_eq!(grid[0], symbols("🍒🍒💎"));
+        assert_eq!(grid[1], symbols("🍋🍒🍒"));
+        assert_eq!(grid[2], symbols("🍊🍋🍋"));
+    }
+
+    #[test]
+    fn wins() {
+        let machine = simple();
+        assert_eq!(machine.wins(&[symbols("aaa")]), vec![(0, "a", 3, 5)]);
+        assert_eq!(machine.wins(&[symbols("aab")]), vec![(0, "a", 2, 1)]);
+        assert_eq!(machine.wins(&[symbols("bbb")]), vec![(0, "b", 3, 3)]);
+        // the run has to start on the leftmost reel
+        assert!(machine.wins(&[symbols("baa")]).is_empty());
+        assert!(machine.wins(&[symbols("bba")]).is_empty());
+        let machine = classic();
+        let grid = [symbols("🍒🍋💎"), symbols("🍋🍒🍒"), symbols("🍊🍋🍒")];
+        assert_eq!(machine.wins(&grid), vec![(3, "🍒", 3, 15)]);
+    }
+
+    #[test]
+    fn spins() {
+        let machine = classic();
+        let spin = machine.spin("server", "client", 1, 10).expect("Expected a spin");
+        assert_eq!(spin, machine.spin("server", "client", 1, 10).expect("Expected a spin"));
+        assert_eq!(spin.grid, machine.grid(&spin.stops));
+        assert_eq!(spin.cost, 50);
+        assert_eq!(spin.winnings, spin.wins.iter().map(|win| win.payout).sum::<u64>());
+        assert_eq!(spin.server_seed_hash, hash_server_seed("server"));
+        machine.spin("server", "client", 1, u64::MAX).expect_err("Expected the bet to be too large");
+    }
+
+    #[test]
+    fn verifies() {
+        let machine = classic();
+        let spin = machine.spin("server", "client", 1, 10).expect("Expected a spin");
+        assert!(machine.verify(&spin, "server"));
+        assert!(!machine.verify(&spin, "other"));
+        let mut changed = spin.clone();
+        changed.winnings += 1;
+        assert!(!machine.verify(&changed, "server"));
+        assert!(!simple().verify(&spin, "server"));
+    }
+
+    #[test]
+    fn pays_back() {
+        // a and b each win on 1 in 27 spins, and a pair of a on 2 in 27
+        let rtp = simple().return_to_player().expect("Expected the return to player");
+        assert!((rtp - 10.0 / 27.0).abs() < 1e-9, "Got {rtp}");
+        let rtp = classic().return_to_player().expect("Expected the return to player");
+        assert!((rtp - 0.950_625).abs() < 1e-9, "Got {rtp}");
+        let huge = Machine::new(vec![symbols("abcdefghij"); 7], payouts(&[("a", 7, 5)]), 1, None).expect("Expected a valid machine");
+        huge.return_to_player().expect_err("Expected too many combinations");
+    }
+
+    #[test]
+    fn server_seeds() {
+        let seed = new_server_seed();
+        assert_eq!(seed.len(), 64);
+        assert!(seed.chars().all(|digit| digit.is_ascii_hexdigit()));
+        assert_ne!(seed, new_server_seed());
+        assert_eq!(hash_server_seed(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    }
+}
+// COV_EXCL_STOP
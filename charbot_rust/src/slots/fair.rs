@@ -0,0 +1,40 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use crate::crypto::hmac;
+
+/// Where each reel stops, for reels `lengths` long, from the seeds and nonce of a spin.
+///
+/// The HMAC of `"{client_seed}:{nonce}:{round}"` keyed with the server seed is split into 32 bit
+/// numbers, one for each reel, hashing the next round for machines with more than eight reels. Each
+/// number is scaled to the length of its reel, so stop `n` of a reel `length` long is picked by the
+/// numbers from `n * 2^32 / length` up to `(n + 1) * 2^32 / length`.
+pub fn stops(server_seed: &str, client_seed: &str, nonce: u64, lengths: &[usize]) -> Vec<usize> {
+    let numbers = (0u64..).flat_map(|round| {
+        let digest = hmac(server_seed.as_bytes(), format!("{client_seed}:{nonce}:{round}").as_bytes());
+        digest.chunks_exact(4).map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect::<Vec<_>>()
+    });
+    lengths.iter().zip(numbers).map(|(length, number)| ((u64::from(number) * *length as u64) >> 32) as usize).collect()
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stops_are_reproducible() {
+        let lengths = [20; 10];
+        let first = stops("server", "client", 1, &lengths);
+        assert_eq!(first, stops("server", "client", 1, &lengths));
+        assert_ne!(first, stops("server", "client", 2, &lengths));
+        assert_ne!(first, stops("server", "other", 1, &lengths));
+        assert!(first.iter().all(|stop| *stop < 20));
+        // the first eight come from the first round, so they don't change with more reels
+        assert_eq!(stops("server", "client", 1, &lengths[..3]), first[..3]);
+        assert_eq!(stops("server", "client", 1, &[1]), vec![0]);
+        // worked out separately with python's hmac module, so spins can be checked without this library
+        assert_eq!(stops("server", "client", 1, &[20, 20, 20]), vec![10, 6, 19]);
+    }
+}
+// COV_EXCL_STOP
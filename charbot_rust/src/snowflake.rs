@@ -0,0 +1,355 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::types::PyDateTime;
+
+use crate::metrics;
+
+/// The first moment of 2015 in milliseconds since the unix epoch, which discord counts snowflakes from.
+const DISCORD_EPOCH: i64 = 1_420_070_400_000;
+/// The most milliseconds after the discord epoch the 42 bits at the top of a snowflake can hold.
+const MAX_MILLISECONDS: i64 = (1 << 42) - 1;
+const TIMESTAMP_SHIFT: u32 = 22;
+const WORKER_SHIFT: u32 = 17;
+const PROCESS_SHIFT: u32 = 12;
+/// Every bit below the timestamp, which are the worker, the process and the increment.
+const LOW_BITS: u64 = (1 << TIMESTAMP_SHIFT) - 1;
+
+// COV_EXCL_START
+/// A discord ID, which says when it was made and by which worker and process.
+///
+/// They compare by when they were made, which is the same as comparing the numbers, since the time is
+/// their top bits.
+#[pyclass(module = "snowflake")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Snowflake {
+    #[pyo3(get)]
+    id: u64,
+}
+// COV_EXCL_STOP
+
+impl Snowflake {
+    /// When the snowflake was made, in milliseconds since the unix epoch.
+    fn milliseconds(self) -> i64 {
+        (self.id >> TIMESTAMP_SHIFT) as i64 + DISCORD_EPOCH
+    }
+
+    /// The lowest snowflake made at `milliseconds` since the unix epoch, or the highest if `high`.
+    fn at(milliseconds: i64, high: bool) -> Result<Self, String> {
+        let since = milliseconds.saturating_sub(DISCORD_EPOCH);
+        if !(0..=MAX_MILLISECONDS).contains(&since) {
+            return Err(format!(
+                "Invalid time {milliseconds} ms after the unix epoch, snowflakes can only be from 2015-01-01 to 2154-05-15 07:35:11 UTC"
+            ));
+        }
+        let id = (since as u64) << TIMESTAMP_SHIFT;
+        Ok(Snowflake { id: if high { id | LOW_BITS } else { id } })
+    }
+}
+
+/// A time as a `datetime.datetime` or a unix timestamp in seconds, in milliseconds since the unix epoch.
+///
+/// Naive datetimes are local time like `datetime.timestamp()`, and fractions of a millisecond are dropped.
+struct Milliseconds(i64);
+
+// COV_EXCL_START
+impl<'source> FromPyObject<'source> for Milliseconds {
+    fn extract(ob: &'source PyAny) -> PyResult<Self> {
+        if ob.is_instance_of::<PyDateTime>()? {
+            let timestamp = ob.call_method0("timestamp")?.extract::<f64>()?;
+            Ok(Milliseconds((timestamp * 1000.0).floor() as i64))
+        } else if let Ok(timestamp) = ob.extract::<i64>() {
+            Ok(Milliseconds(timestamp.saturating_mul(1000)))
+        } else if let Ok(timestamp) = ob.extract::<f64>() {
+            Ok(Milliseconds((timestamp * 1000.0).floor() as i64))
+        } else {
+            Err(PyTypeError::new_err(format!("{} isn't a time, use a datetime or a unix timestamp", ob.get_type().name()?)))
+        }
+    }
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Snowflake {
+    #[new]
+    fn __new__(id: u64) -> Self { // COV_EXCL_LINE
+        Snowflake { id }
+    }
+
+    /// The lowest snowflake made at `time`, or the highest if `high`, for `before` and `after` cursors.
+    #[staticmethod]
+    #[pyo3(signature = (time, high=false))]
+    fn from_time(time: Milliseconds, high: bool) -> PyResult<Self> {
+        Snowflake::at(time.0, high).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// When the snowflake was made, as a unix timestamp in seconds.
+    #[getter]
+    fn timestamp(&self) -> f64 {
+        self.milliseconds() as f64 / 1000.0
+    }
+
+    /// The ID of the discord worker that made the snowflake.
+    #[getter]
+    fn worker_id(&self) -> u8 {
+        ((self.id >> WORKER_SHIFT) & 0x1f) as u8
+    }
+
+    /// The ID of the process on the worker that made the snowflake.
+    #[getter]
+    fn process_id(&self) -> u8 {
+        ((self.id >> PROCESS_SHIFT) & 0x1f) as u8
+    }
+
+    /// How many snowflakes the process had made before this one, wrapping around at 4096.
+    #[getter]
+    fn increment(&self) -> u16 {
+        (self.id & 0xfff) as u16
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp) -> bool {
+        op.matches(self.cmp(other))
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.id
+    }
+
+    fn __int__(&self) -> u64 {
+        self.id
+    }
+
+    fn __index__(&self) -> u64 {
+        self.id
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Snowflake({})", self.id)
+    }
+
+    fn __str__(&self) -> String {
+        self.id.to_string()
+    }
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+timestamp(id, /)
+--
+
+When a discord ID was made.
+
+Parameters
+----------
+id : int
+    The ID.
+
+Returns
+-------
+float
+    The unix timestamp in seconds, to the millisecond.
+")]
+fn timestamp(id: u64) -> f64 {
+    metrics::timed("snowflake.timestamp", || Snowflake { id }.timestamp())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+worker_id(id, /)
+--
+
+The ID of the discord worker that made an ID, from 0 to 31.
+")]
+fn worker_id(id: u64) -> u8 {
+    metrics::timed("snowflake.worker_id", || Snowflake { id }.worker_id())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+process_id(id, /)
+--
+
+The ID of the process on the discord worker that made an ID, from 0 to 31.
+")]
+fn process_id(id: u64) -> u8 {
+    metrics::timed("snowflake.process_id", || Snowflake { id }.process_id())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+increment(id, /)
+--
+
+How many IDs the process had made before an ID, from 0 to 4095 before wrapping around.
+")]
+fn increment(id: u64) -> u16 {
+    metrics::timed("snowflake.increment", || Snowflake { id }.increment())
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+compare(a, b, /)
+--
+
+Compare two discord IDs by when they were made.
+
+Parameters
+----------
+a : int
+    The first ID.
+b : int
+    The second ID.
+
+Returns
+-------
+int
+    -1 if a was made first, 1 if b was, and 0 if they're the same ID.
+")]
+fn compare(a: u64, b: u64) -> i8 {
+    metrics::timed("snowflake.compare", || Snowflake { id: a }.cmp(&Snowflake { id: b }) as i8)
+}
+
+#[pyfunction]
+#[pyo3(signature = (ids, reverse=false), text_signature = "
+sort(ids, reverse=False, /)
+--
+
+Sort discord IDs by when they were made.
+
+Parameters
+----------
+ids : list[int]
+    The IDs.
+reverse : bool
+    Whether to put the newest first instead of the oldest. The default is False.
+
+Returns
+-------
+list[int]
+    The IDs, sorted.
+")]
+fn sort(mut ids: Vec<u64>, reverse: bool) -> Vec<u64> {
+    metrics::timed("snowflake.sort", || {
+        ids.sort_unstable();
+        if reverse {
+            ids.reverse();
+        }
+        ids
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (time, high=false), text_signature = "
+from_time(time, high=False, /)
+--
+
+Make a discord ID for a time, to get messages from before or after it with the `before` and `after`
+cursors of the API.
+
+Parameters
+----------
+time : datetime.datetime | int | float
+    The time, as a datetime or a unix timestamp in seconds. Naive datetimes are local time, like
+    datetime.timestamp(), and fractions of a millisecond are dropped.
+high : bool
+    Whether to make the highest ID of the millisecond instead of the lowest. Use False for `before`
+    and True for `after`, so neither includes the time itself. The default is False.
+
+Returns
+-------
+int
+    The ID.
+
+Raises
+------
+ValueError
+    If the time is before 2015 or after 2154-05-15 07:35:11 UTC, which IDs can't hold.
+")]
+fn from_time(time: Milliseconds, high: bool) -> PyResult<u64> {
+    metrics::observe("snowflake.from_time", || Snowflake::from_time(time, high).map(|snowflake| snowflake.id))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based tools for discord IDs, which are snowflakes that say when they were made";
+
+pub(crate) fn register_snowflake(py: Python, m: &PyModule) -> PyResult<()> {
+    let snowflake = PyModule::new(py, "_snowflake")?;
+    snowflake.add_class::<Snowflake>()?;
+    snowflake.add_function(wrap_pyfunction!(timestamp, snowflake)?)?;
+    snowflake.add_function(wrap_pyfunction!(worker_id, snowflake)?)?;
+    snowflake.add_function(wrap_pyfunction!(process_id, snowflake)?)?;
+    snowflake.add_function(wrap_pyfunction!(increment, snowflake)?)?;
+    snowflake.add_function(wrap_pyfunction!(compare, snowflake)?)?;
+    snowflake.add_function(wrap_pyfunction!(sort, snowflake)?)?;
+    snowflake.add_function(wrap_pyfunction!(from_time, snowflake)?)?;
+    snowflake.add("__doc__", DOCSTRING)?;
+    m.add_submodule(snowflake)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    /// The example in discord's docs.
+    const EXAMPLE: Snowflake = Snowflake { id: 175_928_847_299_117_063 };
+
+    #[test]
+    fn parts() {
+        assert_eq!(EXAMPLE.milliseconds(), 1_462_015_105_796);
+        assert_eq!(EXAMPLE.timestamp(), 1_462_015_105.796);
+        assert_eq!(EXAMPLE.worker_id(), 1);
+        assert_eq!(EXAMPLE.process_id(), 0);
+        assert_eq!(EXAMPLE.increment(), 7);
+    }
+
+    #[test]
+    fn every_bit_set() {
+        let snowflake = Snowflake { id: u64::MAX };
+        assert_eq!(snowflake.milliseconds(), DISCORD_EPOCH + MAX_MILLISECONDS);
+        assert_eq!((snowflake.worker_id(), snowflake.process_id(), snowflake.increment()), (31, 31, 4095));
+    }
+
+    #[parameterized(
+        low = {1_709_737_445_000, false, Ok(1_214_951_645_511_680_000)},
+        high = {1_709_737_445_000, true, Ok(1_214_951_645_515_874_303)},
+        epoch = {DISCORD_EPOCH, false, Ok(0)},
+        last = {DISCORD_EPOCH + MAX_MILLISECONDS, true, Ok(u64::MAX)},
+        before_2015 = {DISCORD_EPOCH - 1, false, Err(String::from("Invalid time 1420070399999 ms after the unix epoch, snowflakes can only be from 2015-01-01 to 2154-05-15 07:35:11 UTC"))},
+        too_late = {DISCORD_EPOCH + MAX_MILLISECONDS + 1, false, Err(String::from("Invalid time 5818116911104 ms after the unix epoch, snowflakes can only be from 2015-01-01 to 2154-05-15 07:35:11 UTC"))},
+        far_past = {i64::MIN, false, Err(format!("Invalid time {} ms after the unix epoch, snowflakes can only be from 2015-01-01 to 2154-05-15 07:35:11 UTC", i64::MIN))},
+    )]
+    fn at(milliseconds: i64, high: bool, expected: Result<u64, String>) {
+        assert_eq!(Snowflake::at(milliseconds, high).map(|snowflake| snowflake.id), expected);
+    }
+
+    #[test]
+    fn made_at_its_time() {
+        let low = Snowflake::at(EXAMPLE.milliseconds(), false).expect("In range");
+        let high = Snowflake::at(EXAMPLE.milliseconds(), true).expect("In range");
+        assert!(low < EXAMPLE && EXAMPLE < high);
+        assert_eq!((low.milliseconds(), high.milliseconds()), (EXAMPLE.milliseconds(), EXAMPLE.milliseconds()));
+    }
+
+    #[parameterized(
+        older = {175_928_847_299_117_063, 1_214_951_645_511_680_000, -1},
+        newer = {1_214_951_645_511_680_000, 175_928_847_299_117_063, 1},
+        same = {175_928_847_299_117_063, 175_928_847_299_117_063, 0},
+        same_millisecond = {1_214_951_645_511_680_000, 1_214_951_645_515_874_303, -1},
+    )]
+    fn comparing(a: u64, b: u64, expected: i8) {
+        assert_eq!(compare(a, b), expected);
+    }
+
+    #[test]
+    fn sorting() {
+        let ids = vec![1_214_951_645_511_680_000, 175_928_847_299_117_063, 1_214_951_645_515_874_303];
+        assert_eq!(sort(ids.clone(), false), vec![175_928_847_299_117_063, 1_214_951_645_511_680_000, 1_214_951_645_515_874_303]);
+        assert_eq!(sort(ids, true), vec![1_214_951_645_515_874_303, 1_214_951_645_511_680_000, 175_928_847_299_117_063]);
+    }
+}
+// COV_EXCL_STOP
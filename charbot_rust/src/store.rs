@@ -0,0 +1,502 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! A store of small values on disk, like cooldowns and pity counters, that outlives the bot restarting
+//! without a round trip to the database.
+//!
+//! The store is a SQLite database in WAL mode, so any number of processes can have it open at once, and
+//! each change is one statement, committed, and synced unless asked not to, before the call making it
+//! returns. A file that's been corrupted is an error, rather than read up to where it's wrong.
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSqlOutput, ValueRef};
+use rusqlite::{params, Connection, OptionalExtension, ToSql};
+
+use crate::buffer::{Buffer, Bytes};
+
+/// How long a change waits for another process that's writing to the store at the same time.
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// The values of every namespace, with when they expire in seconds since the unix epoch, or null for
+/// never.
+const SCHEMA: &str = "CREATE TABLE IF NOT EXISTS store (
+    namespace TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value NOT NULL,
+    expires REAL,
+    PRIMARY KEY (namespace, key)
+) WITHOUT ROWID";
+
+fn now() -> f64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0.0, |since| since.as_secs_f64())
+}
+
+fn check_ttl(ttl: Option<f64>) -> Result<(), String> {
+    match ttl {
+        Some(ttl) if !ttl.is_finite() || ttl <= 0.0 => Err(format!("Invalid TTL {ttl}, it must be more than 0 seconds")),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Int(i64),
+    Text(String),
+    Raw(Vec<u8>),
+}
+
+impl IntoPy<PyObject> for Value {
+    fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Value::Int(number) => number.into_py(py),
+            Value::Text(text) => text.into_py(py),
+            Value::Raw(bytes) => Buffer::from(bytes).into_py(py),
+        }
+    }
+}
+
+impl ToSql for Value {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(ToSqlOutput::Borrowed(match self {
+            Value::Int(number) => ValueRef::Integer(*number),
+            Value::Text(text) => ValueRef::Text(text.as_bytes()),
+            Value::Raw(bytes) => ValueRef::Blob(bytes),
+        }))
+    }
+}
+
+impl FromSql for Value {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        match value {
+            ValueRef::Integer(number) => Ok(Value::Int(number)),
+            ValueRef::Text(text) => String::from_utf8(text.to_vec()).map(Value::Text).map_err(|error| FromSqlError::Other(Box::new(error))),
+            ValueRef::Blob(bytes) => Ok(Value::Raw(bytes.to_vec())),
+            _ => Err(FromSqlError::InvalidType),
+        }
+    }
+}
+
+/// A value as python passes it, before the bytes are copied out of it.
+#[derive(FromPyObject)]
+enum Input {
+    #[pyo3(transparent)]
+    Int(i64),
+    #[pyo3(transparent)]
+    Text(String),
+    #[pyo3(transparent)]
+    Raw(Bytes),
+}
+
+impl From<Input> for Value {
+    fn from(input: Input) -> Self {
+        match input {
+            Input::Int(number) => Value::Int(number),
+            Input::Text(text) => Value::Text(text),
+            Input::Raw(bytes) => Value::Raw(bytes.to_vec()),
+        }
+    }
+}
+
+/// The values of every namespace, in the database they're kept in.
+///
+/// The connection is behind a lock, since SQLite connections can't be used by two threads at once, so a
+/// store can be shared between threads.
+#[derive(Debug)]
+pub(crate) struct Store {
+    path: PathBuf,
+    sync: bool,
+    connection: Mutex<Connection>,
+}
+
+impl Store {
+    /// Open the store at `path`, making it if there isn't one.
+    pub(crate) fn open(path: PathBuf, sync: bool) -> Result<Self, String> {
+        let failed = |error: rusqlite::Error| format!("Couldn't open the store {}: {error}", path.display());
+        let connection = Connection::open(&path).map_err(failed)?;
+        connection.busy_timeout(BUSY_TIMEOUT).map_err(failed)?;
+        // the journal mode is kept in the file, and setting it says what it is now
+        let mode: String = connection.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0)).map_err(failed)?;
+        if !mode.eq_ignore_ascii_case("wal") {
+            return Err(format!("Couldn't open the store {}: it can't be put in WAL mode", path.display()));
+        }
+        // without syncing each commit, a crash of the machine, but not of the bot, can lose the last ones
+        connection.pragma_update(None, "synchronous", if sync { "FULL" } else { "NORMAL" }).map_err(failed)?;
+        connection.execute_batch(SCHEMA).map_err(failed)?;
+        Ok(Store { path, sync, connection: Mutex::new(connection) })
+    }
+
+    fn connection(&self) -> MutexGuard<'_, Connection> {
+        self.connection.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn read_failed(&self, error: rusqlite::Error) -> String {
+        format!("Couldn't read the store {}: {error}", self.path.display())
+    }
+
+    fn write_failed(&self, error: rusqlite::Error) -> String {
+        format!("Couldn't write to the store {}: {error}", self.path.display())
+    }
+
+    pub(crate) fn get(&self, namespace: &str, key: &str, now: f64) -> Result<Option<Value>, String> {
+        self.connection()
+            .query_row(
+                "SELECT value FROM store WHERE namespace = ?1 AND key = ?2 AND (expires IS NULL OR expires > ?3)",
+                params![namespace, key, now],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| self.read_failed(error))
+    }
+
+    pub(crate) fn set(&self, namespace: &str, key: &str, value: Value, ttl: Option<f64>, now: f64) -> Result<(), String> {
+        check_ttl(ttl)?;
+        self.connection()
+            .execute(
+                "INSERT INTO store (namespace, key, value, expires) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (namespace, key) DO UPDATE SET value = excluded.value, expires = excluded.expires",
+                params![namespace, key, value, ttl.map(|ttl| now + ttl)],
+            )
+            .map_err(|error| self.write_failed(error))?;
+        Ok(())
+    }
+
+    /// Delete a key, and whether it was there and hadn't expired. A key that's expired is left for
+    /// [`Store::expire`], it's already as good as gone.
+    pub(crate) fn delete(&self, namespace: &str, key: &str, now: f64) -> Result<bool, String> {
+        let deleted = self
+            .connection()
+            .execute(
+                "DELETE FROM store WHERE namespace = ?1 AND key = ?2 AND (expires IS NULL OR expires > ?3)",
+                params![namespace, key, now],
+            )
+            .map_err(|error| self.write_failed(error))?;
+        Ok(deleted > 0)
+    }
+
+    /// Add `amount` to the integer at a key, which is 0 if it isn't set, in one statement, and the total.
+    ///
+    /// The key keeps when it expires unless a TTL is given. A key that's expired counts as unset, and one
+    /// that isn't an integer, or would overflow, isn't changed.
+    pub(crate) fn increment(&self, namespace: &str, key: &str, amount: i64, ttl: Option<f64>, now: f64) -> Result<i64, String> {
+        check_ttl(ttl)?;
+        // adding integers that overflow makes a real in SQLite, so the sum being an integer is the check
+        let total = self
+            .connection()
+            .query_row(
+                "INSERT INTO store (namespace, key, value, expires) VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (namespace, key) DO UPDATE SET
+                     value = CASE WHEN store.expires <= ?5 THEN excluded.value ELSE store.value + excluded.value END,
+                     expires = CASE WHEN ?6 THEN excluded.expires WHEN store.expires <= ?5 THEN NULL ELSE store.expires END
+                 WHERE store.expires <= ?5 OR (typeof(store.value) = 'integer' AND typeof(store.value + excluded.value) = 'integer')
+                 RETURNING value",
+                params![namespace, key, amount, ttl.map(|ttl| now + ttl), now, ttl.is_some()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|error| self.write_failed(error))?;
+        match total {
+            Some(total) => Ok(total),
+            None => match self.get(namespace, key, now)? {
+                Some(Value::Int(_)) => Err(format!("Adding {amount} to {key:?} in {namespace:?} overflows")),
+                _ => Err(format!("The value of {key:?} in {namespace:?} isn't an integer")),
+            },
+        }
+    }
+
+    /// The keys in a namespace that haven't expired, in order.
+    pub(crate) fn keys(&self, namespace: &str, now: f64) -> Result<Vec<String>, String> {
+        let failed = |error: rusqlite::Error| self.read_failed(error);
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare("SELECT key FROM store WHERE namespace = ?1 AND (expires IS NULL OR expires > ?2) ORDER BY key")
+            .map_err(failed)?;
+        let keys: rusqlite::Result<Vec<String>> = statement.query_map(params![namespace, now], |row| row.get(0)).map_err(failed)?.collect();
+        keys.map_err(failed)
+    }
+
+    /// Delete the keys that have expired, and how many there were.
+    pub(crate) fn expire(&self, now: f64) -> Result<usize, String> {
+        self.connection().execute("DELETE FROM store WHERE expires <= ?1", params![now]).map_err(|error| self.write_failed(error))
+    }
+
+    /// Delete the keys that have expired, and rewrite the database without the space they and the values
+    /// overwritten since took, folding the write-ahead log into it.
+    pub(crate) fn compact(&self, now: f64) -> Result<(), String> {
+        self.expire(now)?;
+        let connection = self.connection();
+        let compacted = connection
+            .execute_batch("VACUUM")
+            .and_then(|_| connection.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(())));
+        compacted.map_err(|error| format!("Couldn't compact the store {}: {error}", self.path.display()))
+    }
+
+    /// How many keys there are that haven't expired.
+    pub(crate) fn len(&self, now: f64) -> Result<usize, String> {
+        self.connection()
+            .query_row("SELECT COUNT(*) FROM store WHERE expires IS NULL OR expires > ?1", params![now], |row| row.get(0))
+            .map_err(|error| self.read_failed(error))
+    }
+}
+
+/// A store of small values that lasts, like cooldowns and pity counters, in namespaces of keys that can
+/// expire, kept in a file on disk.
+///
+/// The GIL is let go while SQLite reads and writes, so a slow disk, or another process writing, doesn't
+/// hold up the rest of the bot.
+#[pyclass(module = "store")] // COV_EXCL_LINE
+pub struct KvStore {
+    store: Store,
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl KvStore {
+    #[new]
+    #[pyo3(signature = (path, /, sync=true))]
+    fn py_new(path: PathBuf, sync: bool) -> PyResult<Self> {
+        Ok(KvStore { store: Store::open(path, sync).map_err(PyErr::new::<PyValueError, _>)? })
+    }
+
+    /// The value at a key, or the default if it isn't set or has expired.
+    #[pyo3(signature = (namespace, key, default=None, now=None))]
+    fn get(&self, py: Python, namespace: &str, key: &str, default: Option<PyObject>, now: Option<f64>) -> PyResult<PyObject> {
+        let now = now.unwrap_or_else(self::now);
+        match py.allow_threads(|| self.store.get(namespace, key, now)).map_err(PyErr::new::<PyValueError, _>)? {
+            Some(value) => Ok(value.into_py(py)),
+            None => Ok(default.unwrap_or_else(|| py.None())),
+        }
+    }
+
+    #[pyo3(signature = (namespace, key, value, ttl=None, now=None))]
+    fn set(&self, py: Python, namespace: &str, key: &str, value: Input, ttl: Option<f64>, now: Option<f64>) -> PyResult<()> {
+        let (value, now) = (Value::from(value), now.unwrap_or_else(self::now));
+        py.allow_threads(|| self.store.set(namespace, key, value, ttl, now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(signature = (namespace, key, now=None))]
+    fn delete(&self, py: Python, namespace: &str, key: &str, now: Option<f64>) -> PyResult<bool> {
+        let now = now.unwrap_or_else(self::now);
+        py.allow_threads(|| self.store.delete(namespace, key, now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(signature = (namespace, key, amount=1, ttl=None, now=None))]
+    fn increment(&self, py: Python, namespace: &str, key: &str, amount: i64, ttl: Option<f64>, now: Option<f64>) -> PyResult<i64> {
+        let now = now.unwrap_or_else(self::now);
+        py.allow_threads(|| self.store.increment(namespace, key, amount, ttl, now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(signature = (namespace, now=None))]
+    fn keys(&self, py: Python, namespace: &str, now: Option<f64>) -> PyResult<Vec<String>> {
+        let now = now.unwrap_or_else(self::now);
+        py.allow_threads(|| self.store.keys(namespace, now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(signature = (now=None))]
+    fn expire(&self, py: Python, now: Option<f64>) -> PyResult<usize> {
+        let now = now.unwrap_or_else(self::now);
+        py.allow_threads(|| self.store.expire(now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[pyo3(signature = (now=None))]
+    fn compact(&self, py: Python, now: Option<f64>) -> PyResult<()> {
+        let now = now.unwrap_or_else(self::now);
+        py.allow_threads(|| self.store.compact(now)).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn path(&self) -> String {
+        self.store.path.display().to_string()
+    }
+
+    #[getter] // COV_EXCL_LINE
+    fn sync(&self) -> bool {
+        self.store.sync
+    }
+
+    fn __len__(&self, py: Python) -> PyResult<usize> {
+        py.allow_threads(|| self.store.len(now())).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn __repr__(&self, py: Python) -> String {
+        let entries = py.allow_threads(|| self.store.len(now())).map_or_else(|_| String::from("?"), |entries| entries.to_string());
+        format!("KvStore(path={:?}, entries={entries})", self.store.path.display().to_string())
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based stores of small values on disk, with keys that can expire";
+
+pub(crate) fn register_store(py: Python, m: &PyModule) -> PyResult<()> {
+    let store = PyModule::new(py, "_store")?;
+    store.add_class::<KvStore>()?;
+    store.add("__doc__", DOCSTRING)?;
+    m.add_submodule(store)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::Path;
+
+    /// A path for a store no other test uses, with nothing there yet.
+    fn path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("charbot_store_{name}_{}.db", std::process::id()));
+        remove(&path);
+        path
+    }
+
+    /// Remove a store, with its write-ahead log.
+    fn remove(path: &Path) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.as_os_str().to_owned();
+            file.push(suffix);
+            let _ = fs::remove_file(file);
+        }
+    }
+
+    #[test]
+    fn lasts() {
+        let path = path("lasts");
+        let store = Store::open(path.clone(), true).unwrap();
+        store.set("cooldowns", "daily:1", Value::Int(100), None, 0.0).unwrap();
+        store.set("cooldowns", "daily:2", Value::Text(String::from("soon")), Some(10.0), 0.0).unwrap();
+        store.set("pity", "1", Value::Raw(vec![0, 1, 2]), None, 0.0).unwrap();
+        store.set("pity", "2", Value::Int(3), None, 0.0).unwrap();
+        assert!(store.delete("pity", "2", 0.0).unwrap());
+        assert!(!store.delete("pity", "2", 0.0).unwrap());
+        assert_eq!(store.keys("cooldowns", 5.0).unwrap(), ["daily:1", "daily:2"]);
+        drop(store);
+        let store = Store::open(path.clone(), true).unwrap();
+        assert_eq!(store.get("cooldowns", "daily:1", 5.0), Ok(Some(Value::Int(100))));
+        assert_eq!(store.get("cooldowns", "daily:2", 5.0), Ok(Some(Value::Text(String::from("soon")))));
+        assert_eq!(store.get("cooldowns", "daily:2", 10.0), Ok(None));
+        assert_eq!(store.get("pity", "1", 5.0), Ok(Some(Value::Raw(vec![0, 1, 2]))));
+        assert_eq!(store.get("pity", "2", 5.0), Ok(None));
+        assert_eq!((store.len(5.0), store.len(10.0)), (Ok(3), Ok(2)));
+        // an expired key isn't there to delete
+        assert!(!store.delete("cooldowns", "daily:2", 10.0).unwrap());
+        assert_eq!(store.expire(10.0), Ok(1));
+        assert_eq!(store.keys("cooldowns", 0.0).unwrap(), ["daily:1"]);
+        drop(store);
+        remove(&path);
+    }
+
+    #[test]
+    fn increment() {
+        let path = path("increment");
+        let store = Store::open(path.clone(), false).unwrap();
+        assert_eq!(store.increment("pity", "1", 1, None, 0.0), Ok(1));
+        assert_eq!(store.increment("pity", "1", 5, Some(10.0), 0.0), Ok(6));
+        // keeps when it expires without a TTL
+        assert_eq!(store.increment("pity", "1", -2, None, 5.0), Ok(4));
+        assert_eq!(store.get("pity", "1", 10.0), Ok(None));
+        assert_eq!(store.increment("pity", "1", 1, None, 10.0), Ok(1));
+        store.set("pity", "2", Value::Int(i64::MAX), None, 0.0).unwrap();
+        assert_eq!(store.increment("pity", "2", 1, None, 0.0).unwrap_err(), r#"Adding 1 to "2" in "pity" overflows"#);
+        assert_eq!(store.get("pity", "2", 0.0), Ok(Some(Value::Int(i64::MAX))));
+        store.set("pity", "3", Value::Text(String::from("3")), None, 0.0).unwrap();
+        assert_eq!(store.increment("pity", "3", 1, None, 0.0).unwrap_err(), r#"The value of "3" in "pity" isn't an integer"#);
+        assert_eq!(store.get("pity", "3", 0.0), Ok(Some(Value::Text(String::from("3")))));
+        assert_eq!(store.increment("pity", "1", 1, Some(0.0), 0.0).unwrap_err(), "Invalid TTL 0, it must be more than 0 seconds");
+        drop(store);
+        // the key that expired and was incremented again never expires
+        assert_eq!(Store::open(path.clone(), false).unwrap().get("pity", "1", 1e9), Ok(Some(Value::Int(1))));
+        remove(&path);
+    }
+
+    #[test]
+    fn shared() {
+        let path = path("shared");
+        let first = Store::open(path.clone(), true).unwrap();
+        let second = Store::open(path.clone(), true).unwrap();
+        first.set("a", "b", Value::Int(1), None, 0.0).unwrap();
+        assert_eq!(second.increment("a", "b", 2, None, 0.0), Ok(3));
+        assert_eq!(first.increment("a", "b", 3, None, 0.0), Ok(6));
+        assert_eq!(second.get("a", "b", 0.0), Ok(Some(Value::Int(6))));
+        first.compact(0.0).unwrap();
+        assert_eq!(second.keys("a", 0.0).unwrap(), ["b"]);
+        drop((first, second));
+        remove(&path);
+    }
+
+    #[test]
+    fn threads() {
+        let path = path("threads");
+        let store = Store::open(path.clone(), false).unwrap();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        store.increment("a", "b", 1, None, 0.0).unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(store.get("a", "b", 0.0), Ok(Some(Value::Int(400))));
+        drop(store);
+        remove(&path);
+    }
+
+    #[test]
+    fn corrupted() {
+        let path = path("corrupted");
+        fs::write(&path, b"something else").unwrap();
+        assert!(Store::open(path.clone(), true).unwrap_err().starts_with("Couldn't open the store "));
+        remove(&path);
+        let store = Store::open(path.clone(), true).unwrap();
+        for count in 0..2000 {
+            store.set("a", &count.to_string(), Value::Text("x".repeat(100)), None, 0.0).unwrap();
+        }
+        store.compact(0.0).unwrap();
+        drop(store);
+        // garbage over the middle of the file, with the pages after it left as they were
+        let mut bytes = fs::read(&path).unwrap();
+        let middle = bytes.len() / 2;
+        bytes[middle..middle + 4096].fill(0xa5);
+        fs::write(&path, bytes).unwrap();
+        let failed = Store::open(path.clone(), true).and_then(|store| store.keys("a", 0.0).map(|keys| keys.len()));
+        assert!(failed.is_err(), "{failed:?}");
+        remove(&path);
+    }
+
+    #[test]
+    fn compacts() {
+        let path = path("compacts");
+        let store = Store::open(path.clone(), false).unwrap();
+        for count in 0..5000 {
+            store.increment("counters", &format!("{}", count % 10), 1, None, 0.0).unwrap();
+        }
+        store.set("cooldowns", "1", Value::Int(1), Some(1.0), 0.0).unwrap();
+        assert_eq!(store.expire(1.0), Ok(1));
+        assert_eq!(store.expire(1.0), Ok(0));
+        store.compact(1.0).unwrap();
+        // the write-ahead log is folded into the database
+        let mut wal = path.clone().into_os_string();
+        wal.push("-wal");
+        assert_eq!(fs::metadata(wal).map(|wal| wal.len()).unwrap_or(0), 0);
+        store.increment("counters", "0", 1, None, 1.0).unwrap();
+        drop(store);
+        let store = Store::open(path.clone(), false).unwrap();
+        assert_eq!(store.get("counters", "0", 1.0), Ok(Some(Value::Int(501))));
+        assert_eq!(store.get("counters", "9", 1.0), Ok(Some(Value::Int(500))));
+        assert_eq!(store.len(1.0), Ok(10));
+        drop(store);
+        remove(&path);
+    }
+
+    #[test]
+    fn rejects() {
+        let path = path("rejects");
+        let store = Store::open(path.clone(), false).unwrap();
+        assert_eq!(store.set("a", "b", Value::Int(1), Some(f64::NAN), 0.0).unwrap_err(), "Invalid TTL NaN, it must be more than 0 seconds");
+        assert_eq!(store.set("a", "b", Value::Int(1), Some(-1.0), 0.0).unwrap_err(), "Invalid TTL -1, it must be more than 0 seconds");
+        assert_eq!(store.len(0.0), Ok(0));
+        drop(store);
+        remove(&path);
+    }
+}
+// COV_EXCL_STOP
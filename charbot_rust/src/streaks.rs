@@ -0,0 +1,246 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::cooldowns::{self, Kind};
+use crate::metrics;
+
+// COV_EXCL_START
+#[pyclass(module = "streaks")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Streak {
+    /// How many days or weeks in a row have been claimed.
+    #[pyo3(get)]
+    count: u32,
+    /// The longest the streak has been.
+    #[pyo3(get)]
+    best: u32,
+    /// Grace tokens, each one covers a day or week that was missed without breaking the streak.
+    #[pyo3(get)]
+    tokens: u32,
+    /// The unix timestamp in seconds of the last claim, `None` if there hasn't been one.
+    #[pyo3(get)]
+    last_claim: Option<i64>,
+}
+// COV_EXCL_STOP
+
+impl Streak {
+    fn new(count: u32, best: u32, tokens: u32, last_claim: Option<i64>) -> Result<Self, String> {
+        if best < count {
+            return Err(format!("Invalid streak, the best of {best} can't be less than the count of {count}"));
+        }
+        if count > 0 && last_claim.is_none() {
+            return Err(format!("Invalid streak, a count of {count} needs a last claim"));
+        }
+        Ok(Streak { count, best, tokens, last_claim })
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Streak {
+    #[new]
+    #[pyo3(signature = (count=0, best=0, tokens=0, last_claim=None))]
+    fn py_new(count: u32, best: u32, tokens: u32, last_claim: Option<i64>) -> PyResult<Self> {
+        Streak::new(count, best, tokens, last_claim).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    /// The same streak with more grace tokens, like from a shop.
+    fn with_tokens(&self, tokens: u32) -> Self {
+        Streak { tokens, ..*self }
+    }
+
+    fn __repr__(&self) -> String {
+        let last_claim = self.last_claim.map_or("None".to_string(), |time| time.to_string());
+        format!("Streak(count={}, best={}, tokens={}, last_claim={last_claim})", self.count, self.best, self.tokens)
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "streaks")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Claim {
+    /// The streak after the claim, to store for the next one.
+    #[pyo3(get)]
+    streak: Streak,
+    /// Whether the claim counted, it doesn't if the day or week was already claimed.
+    #[pyo3(get)]
+    counted: bool,
+    /// Whether the streak broke and started again at 1.
+    #[pyo3(get)]
+    broke: bool,
+    /// How many grace tokens were used to cover the days or weeks that were missed.
+    #[pyo3(get)]
+    tokens_used: u32,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Claim {
+    fn __repr__(&self) -> String {
+        let bool = |value: bool| if value { "True" } else { "False" };
+        format!("Claim(streak={}, counted={}, broke={}, tokens_used={})", self.streak.__repr__(), bool(self.counted), bool(self.broke), self.tokens_used)
+    }
+}
+
+/// Claim the day or week `now` is in, local to `tz`.
+///
+/// Days and weeks are counted in local dates, so claiming just before and just after midnight keeps
+/// a streak going however many hours apart the claims are. Missing some breaks the streak, unless
+/// there are enough tokens to cover every one that was missed, in which case they're all used.
+fn claim_at(streak: &Streak, now: i64, tz: &str, kind: Kind) -> Result<Claim, String> {
+    let current = cooldowns::period(kind, tz, now)?;
+    let (count, tokens_used) = match streak.last_claim {
+        None => (1, 0),
+        Some(last_claim) => {
+            let missed = current - cooldowns::period(kind, tz, last_claim)? - 1;
+            match missed {
+                ..=-2 => return Err(format!("Invalid claim at {now}, it's before the last claim at {last_claim}")),
+                -1 => return Ok(Claim { streak: *streak, counted: false, broke: false, tokens_used: 0 }),
+                0 => (streak.count.saturating_add(1), 0),
+                missed if missed <= i64::from(streak.tokens) => (streak.count.saturating_add(1), missed as u32),
+                _ => (1, 0),
+            }
+        }
+    };
+    let broke = count == 1 && streak.count > 0;
+    let streak = Streak { count, best: streak.best.max(count), tokens: streak.tokens - tokens_used, last_claim: Some(now) };
+    Ok(Claim { streak, counted: true, broke, tokens_used })
+}
+
+#[pyfunction]
+#[pyo3(signature = (streak, now, tz, kind=Kind::Daily), text_signature = "
+claim(streak, now, tz, kind=Kind.Daily, /)
+--
+
+Claim a daily or weekly streak, counting days and weeks in a time zone.
+
+Days and weeks start at local midnight, weeks on monday, so days the clocks change on are 23 or 25
+hours long and claims just before and just after midnight are a day apart. Missing days or weeks
+breaks the streak unless there are enough grace tokens to cover all of them, in which case they're used.
+
+Parameters
+----------
+streak : Streak
+    The streak as it was after the last claim, or a new Streak for someone that hasn't claimed yet.
+now : int
+    The unix timestamp in seconds of the claim.
+tz : str
+    The name of the time zone in the tz database, like 'America/New_York' or 'UTC'.
+kind : Kind
+    Whether the streak is daily or weekly. The default is Kind.Daily.
+
+Returns
+-------
+Claim
+    The streak after the claim, whether the claim counted, whether the streak broke, and how many tokens were used.
+
+Raises
+------
+ValueError
+    If the time zone isn't in the tz database, or the claim is before the last one.
+")]
+fn claim(streak: Streak, now: i64, tz: &str, kind: Kind) -> PyResult<Claim> {
+    metrics::observe("streaks.claim", || claim_at(&streak, now, tz, kind).map_err(PyErr::new::<PyValueError, _>))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based daily and weekly streaks with grace tokens";
+
+pub(crate) fn register_streaks(py: Python, m: &PyModule) -> PyResult<()> {
+    let streaks = PyModule::new(py, "_streaks")?;
+    streaks.add_class::<Streak>()?;
+    streaks.add_class::<Claim>()?;
+    streaks.add_class::<Kind>()?;
+    streaks.add_function(wrap_pyfunction!(claim, streaks)?)?;
+    streaks.add("__doc__", DOCSTRING)?;
+    m.add_submodule(streaks)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3600;
+    const DAY: i64 = 24 * HOUR;
+    /// 2024-03-09 00:00 UTC, the day before the clocks went forward in New York.
+    const MARCH_9: i64 = 1_709_942_400;
+
+    fn claims(times: &[i64], tz: &str, kind: Kind, tokens: u32) -> Vec<Claim> {
+        let mut streak = Streak::new(0, 0, tokens, None).expect("Expected a valid streak");
+        times
+            .iter()
+            .map(|time| {
+                let claim = claim_at(&streak, *time, tz, kind).expect("Expected a claim");
+                streak = claim.streak;
+                claim
+            })
+            .collect()
+    }
+
+    #[test]
+    fn counting() {
+        let made = claims(&[0, DAY, 2 * DAY + 5, 2 * DAY + 10, 3 * DAY], "UTC", Kind::Daily, 0);
+        assert_eq!(made.iter().map(|claim| claim.streak.count).collect::<Vec<_>>(), vec![1, 2, 3, 3, 4]);
+        assert_eq!(made.iter().map(|claim| claim.counted).collect::<Vec<_>>(), vec![true, true, true, false, true]);
+        // a claim that didn't count doesn't move the last claim
+        assert_eq!(made[3].streak.last_claim, Some(2 * DAY + 5));
+        assert!(made.iter().all(|claim| !claim.broke));
+    }
+
+    #[test]
+    fn breaking() {
+        let made = claims(&[0, DAY, 3 * DAY, 4 * DAY], "UTC", Kind::Daily, 0);
+        assert_eq!(made.iter().map(|claim| claim.streak.count).collect::<Vec<_>>(), vec![1, 2, 1, 2]);
+        assert!(made[2].broke);
+        assert_eq!(made[3].streak.best, 2);
+    }
+
+    #[test]
+    fn grace_tokens() {
+        let made = claims(&[0, 2 * DAY, 5 * DAY, 9 * DAY], "UTC", Kind::Daily, 3);
+        // one missed day uses a token, two more use the rest, and three more than that break it
+        assert_eq!(made.iter().map(|claim| claim.tokens_used).collect::<Vec<_>>(), vec![0, 1, 2, 0]);
+        assert_eq!(made.iter().map(|claim| claim.streak.count).collect::<Vec<_>>(), vec![1, 2, 3, 1]);
+        assert_eq!(made[3].streak.tokens, 0);
+        assert!(made[3].broke);
+        // tokens aren't used up on a streak that breaks anyway
+        let made = claims(&[0, 3 * DAY], "UTC", Kind::Daily, 1);
+        assert_eq!((made[1].broke, made[1].streak.tokens), (true, 1));
+    }
+
+    #[test]
+    fn local_midnight() {
+        // 11:30pm and 12:30am EST are an hour apart but on consecutive days
+        let late = MARCH_9 + DAY + 4 * HOUR + 30 * 60;
+        let made = claims(&[late, late + HOUR], "America/New_York", Kind::Daily, 0);
+        assert_eq!(made.iter().map(|claim| claim.streak.count).collect::<Vec<_>>(), vec![1, 2]);
+        // the same times in UTC are on the same day
+        let made = claims(&[late, late + HOUR], "UTC", Kind::Daily, 0);
+        assert_eq!(made[1].streak.count, 1);
+        // the day the clocks went forward is 23 hours long
+        let midnight = MARCH_9 + DAY + 5 * HOUR;
+        let made = claims(&[midnight, midnight + 23 * HOUR - 1, midnight + 23 * HOUR], "America/New_York", Kind::Daily, 0);
+        assert_eq!(made.iter().map(|claim| (claim.streak.count, claim.counted)).collect::<Vec<_>>(), vec![(1, true), (1, false), (2, true)]);
+    }
+
+    #[test]
+    fn weekly() {
+        // a saturday, the monday after, the thursday of the week after, and the tuesday two weeks after that
+        let made = claims(&[MARCH_9, MARCH_9 + 2 * DAY, MARCH_9 + 12 * DAY, MARCH_9 + 24 * DAY], "UTC", Kind::Weekly, 1);
+        assert_eq!(made.iter().map(|claim| claim.streak.count).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+        assert_eq!(made[3].tokens_used, 1);
+    }
+
+    #[test]
+    fn rejects() {
+        Streak::new(3, 2, 0, Some(0)).expect_err("Expected a best under the count to be rejected");
+        Streak::new(3, 3, 0, None).expect_err("Expected a count without a claim to be rejected");
+        let streak = Streak::new(1, 1, 0, Some(DAY)).expect("Expected a valid streak");
+        claim_at(&streak, -DAY, "UTC", Kind::Daily).expect_err("Expected a claim before the last to be rejected");
+        claim_at(&streak, 2 * DAY, "Not/AZone", Kind::Daily).expect_err("Expected an unknown zone to be rejected");
+        assert_eq!(streak.with_tokens(5).tokens, 5);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,388 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod grid; // COV_EXCL_LINE
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::sudoku::grid::{Grid, SIZE};
+use crate::persist::{self, Persist};
+use crate::metrics;
+#[cfg(feature = "async")]
+use crate::tasks;
+
+/// How hard a puzzle is, by what it takes to solve it.
+#[pyclass(module = "sudoku")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
+pub enum Difficulty { // COV_EXCL_LINE
+    /// Every step is a cell with only one value left.
+    Easy = 1,
+    /// Some steps are a value with only one cell left in a row, column or box.
+    Medium = 2,
+    /// Some steps need more than that, like trying a value and seeing if it works out.
+    Hard = 3,
+}
+
+impl Difficulty {
+    /// Puzzles aren't dug below this many clues, so easy ones don't look hard.
+    fn min_clues(self) -> usize {
+        match self {
+            Difficulty::Easy => 36,
+            Difficulty::Medium => 30,
+            Difficulty::Hard => 22,
+        }
+    }
+}
+
+/// How many full grids are tried to get a puzzle of the difficulty asked for, before settling for the last one.
+const MAX_ATTEMPTS: usize = 50;
+
+// COV_EXCL_START
+#[pyclass(module = "sudoku")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Puzzle {
+    /// The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+    #[pyo3(get)]
+    board: Vec<Vec<u8>>,
+    /// The only solution of the puzzle.
+    #[pyo3(get)]
+    solution: Vec<Vec<u8>>,
+    #[pyo3(get)]
+    difficulty: Difficulty,
+}
+
+#[pymethods]
+impl Puzzle {
+    fn __repr__(&self) -> String {
+        format!("Puzzle(difficulty={:?}, clues={})", self.difficulty, self.board.iter().flatten().filter(|value| **value != 0).count())
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+// COV_EXCL_STOP
+
+impl Persist for Puzzle {
+    const GAME: &'static str = "sudoku";
+    const VERSION: u32 = 1;
+    type State = Puzzle;
+
+    fn save(&self) -> Puzzle {
+        self.clone()
+    }
+
+    fn load(state: Puzzle) -> Result<Self, String> {
+        let board = Grid::from_rows(&state.board)?;
+        let solution = Grid::from_rows(&state.solution)?;
+        let mut solved = None;
+        if !board.is_consistent() || board.count_solutions(2, &mut solved) != 1 || solved != Some(solution) {
+            return Err("The solution isn't the only solution of the board".to_string());
+        }
+        if logic_grade(&board) != state.difficulty {
+            return Err(format!("The board isn't {:?}", state.difficulty));
+        }
+        Ok(state)
+    }
+}
+
+/// The difficulty of a puzzle with a unique solution, by how far filling in singles gets.
+fn logic_grade(puzzle: &Grid) -> Difficulty {
+    let mut filled = puzzle.clone();
+    let used_hidden = filled.fill_singles();
+    if !filled.is_full() {
+        Difficulty::Hard
+    } else if used_hidden {
+        Difficulty::Medium
+    } else {
+        Difficulty::Easy
+    }
+}
+
+/// The difficulty of `puzzle`, or `None` if it doesn't have exactly one solution.
+fn grade_grid(puzzle: &Grid) -> Option<Difficulty> {
+    (puzzle.is_consistent() && puzzle.count_solutions(2, &mut None) == 1).then(|| logic_grade(puzzle))
+}
+
+/// A puzzle with exactly one solution, made by emptying the cells of a random solved grid one at a time.
+///
+/// A cell is only emptied if the solution stays unique and the puzzle doesn't get harder than `difficulty`.
+/// Full grids are tried until one ends up exactly as hard as asked, which for hard ones can take a few.
+fn generate_with(difficulty: Difficulty, rng: &mut StdRng) -> Puzzle {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let solution = Grid::random_solution(rng);
+        let mut puzzle = solution.clone();
+        let mut cells = (0..SIZE * SIZE).collect::<Vec<_>>();
+        cells.shuffle(rng);
+        for index in cells {
+            if puzzle.clues() <= difficulty.min_clues() {
+                break;
+            }
+            let (row, column) = (index / SIZE, index % SIZE);
+            let value = puzzle.get(row, column);
+            puzzle.set(row, column, 0);
+            // nothing is harder than hard, so that check is skipped for them
+            let too_hard = difficulty != Difficulty::Hard && logic_grade(&puzzle) > difficulty;
+            if too_hard || puzzle.count_solutions(2, &mut None) != 1 {
+                puzzle.set(row, column, value);
+            }
+        }
+        let grade = logic_grade(&puzzle);
+        if grade == difficulty || attempt >= MAX_ATTEMPTS {
+            return Puzzle {
+                board: puzzle.to_rows(),
+                solution: solution.to_rows(),
+                difficulty: grade,
+            };
+        }
+    }
+}
+
+fn parse_board(board: &[Vec<u8>]) -> PyResult<Grid> {
+    Grid::from_rows(board).map_err(PyErr::new::<PyValueError, _>)
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+generate(difficulty, /)
+--
+
+Generate a puzzle with exactly one solution.
+
+Parameters
+----------
+difficulty : Difficulty
+    How hard the puzzle should be. Hard puzzles are rarer, so they take the longest, but still only
+    milliseconds.
+
+Returns
+-------
+Puzzle
+    The puzzle, its solution and its difficulty, which is the one asked for except in the rare case
+    no puzzle that hard turned up.
+")]
+fn generate(py: Python, difficulty: Difficulty) -> Puzzle {
+    metrics::timed("sudoku.generate", || py.allow_threads(|| generate_with(difficulty, &mut StdRng::from_entropy())))
+}
+
+#[cfg(feature = "async")]
+#[pyfunction]
+#[pyo3(text_signature = "
+generate_async(difficulty, /)
+--
+
+Generate a puzzle with exactly one solution on a thread pool, so the event loop keeps running
+meanwhile. It takes and gives the same as ``generate``, once awaited.
+
+Returns
+-------
+asyncio.Future[Puzzle]
+    The puzzle, its solution and its difficulty.
+
+Raises
+------
+RuntimeError
+    If there isn't an event loop running.
+")]
+fn generate_async(py: Python<'_>, difficulty: Difficulty) -> PyResult<&PyAny> {
+    tasks::spawn(py, move |py| Ok(generate(py, difficulty).into_py(py)))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+solve(board, /)
+--
+
+Solve a puzzle.
+
+Parameters
+----------
+board : list[list[int]]
+    The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+
+Returns
+-------
+list[list[int]] | None
+    A solution, or None if there isn't one. If there are several, which one is returned is arbitrary.
+
+Raises
+------
+ValueError
+    If the board isn't 9 rows of 9 cells from 0 to 9.
+")]
+fn solve(py: Python, board: Vec<Vec<u8>>) -> PyResult<Option<Vec<Vec<u8>>>> {
+    metrics::observe("sudoku.solve", || {
+        let grid = parse_board(&board)?;
+        if !grid.is_consistent() {
+            return Ok(None);
+        }
+        let mut solution = None;
+        py.allow_threads(|| grid.count_solutions(1, &mut solution));
+        Ok(solution.map(|solution| solution.to_rows()))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+validate_move(board, row, col, value, /)
+--
+
+Check if a value can go in a cell, without the same value already being in its row, column or box.
+
+What is in the cell itself now is ignored, so a value can be changed. This doesn't check that the
+value is the one in the solution, compare with ``Puzzle.solution`` for that.
+
+Parameters
+----------
+board : list[list[int]]
+    The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+row : int
+    The row of the cell, from 0 at the top to 8.
+col : int
+    The column of the cell, from 0 on the left to 8.
+value : int
+    The value to check, from 1 to 9.
+
+Returns
+-------
+bool
+    True if the value can go there, False otherwise.
+
+Raises
+------
+ValueError
+    If the board isn't 9 rows of 9 cells from 0 to 9, or the row, column or value is out of range.
+")]
+fn validate_move(board: Vec<Vec<u8>>, row: usize, col: usize, value: u8) -> PyResult<bool> {
+    metrics::observe("sudoku.validate_move", || {
+        let grid = parse_board(&board)?;
+        if row >= SIZE || col >= SIZE {
+            return Err(PyValueError::new_err(format!("Invalid cell ({row}, {col}), rows and columns are from 0 to 8")));
+        }
+        if !(1..=SIZE as u8).contains(&value) {
+            return Err(PyValueError::new_err(format!("Invalid value {value}, it must be from 1 to 9")));
+        }
+        Ok(grid.can_place(row, col, value))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+grade(board, /)
+--
+
+Grade how hard a puzzle is.
+
+Parameters
+----------
+board : list[list[int]]
+    The puzzle, as 9 rows of 9 cells with 0 for the empty ones.
+
+Returns
+-------
+Difficulty | None
+    The difficulty, or None if the puzzle doesn't have exactly one solution.
+
+Raises
+------
+ValueError
+    If the board isn't 9 rows of 9 cells from 0 to 9.
+")]
+fn grade(py: Python, board: Vec<Vec<u8>>) -> PyResult<Option<Difficulty>> {
+    metrics::observe("sudoku.grade", || {
+        let grid = parse_board(&board)?;
+        Ok(py.allow_threads(|| grade_grid(&grid)))
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of sudoku";
+
+pub(crate) fn register_sudoku(py: Python, m: &PyModule) -> PyResult<()> {
+    let sudoku = PyModule::new(py, "_sudoku")?;
+    sudoku.add_class::<Difficulty>()?;
+    sudoku.add_class::<Puzzle>()?;
+    sudoku.add_function(wrap_pyfunction!(generate, sudoku)?)?;
+    #[cfg(feature = "async")]
+    sudoku.add_function(wrap_pyfunction!(generate_async, sudoku)?)?;
+    sudoku.add_function(wrap_pyfunction!(solve, sudoku)?)?;
+    sudoku.add_function(wrap_pyfunction!(validate_move, sudoku)?)?;
+    sudoku.add_function(wrap_pyfunction!(grade, sudoku)?)?;
+    sudoku.add("__doc__", DOCSTRING)?;
+    m.add_submodule(sudoku)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        easy = {Difficulty::Easy},
+        medium = {Difficulty::Medium},
+        hard = {Difficulty::Hard},
+    )]
+    fn generates(difficulty: Difficulty) {
+        let puzzle = generate_with(difficulty, &mut StdRng::from_seed([7; 32]));
+        let board = Grid::from_rows(&puzzle.board).expect("Invalid board");
+        let solution = Grid::from_rows(&puzzle.solution).expect("Invalid solution");
+        assert_eq!(puzzle.difficulty, difficulty);
+        assert_eq!(grade_grid(&board), Some(difficulty));
+        assert!(board.clues() >= difficulty.min_clues());
+        let mut solved = None;
+        assert_eq!(board.count_solutions(2, &mut solved), 1);
+        assert_eq!(solved, Some(solution));
+    }
+
+    #[test]
+    fn persists() {
+        let puzzle = generate_with(Difficulty::Easy, &mut StdRng::from_seed([3; 32]));
+        let json = persist::to_json(&puzzle).expect("Failed to save");
+        assert_eq!(persist::from_json::<Puzzle>(&json), Ok(puzzle.clone()));
+        let bytes = persist::to_bytes(&puzzle).expect("Failed to save");
+        assert_eq!(persist::from_bytes::<Puzzle>(&bytes), Ok(puzzle.clone()));
+        let mut wrong = puzzle.clone();
+        let (row, column) = (0..SIZE * SIZE).map(|index| (index / SIZE, index % SIZE)).find(|(row, column)| puzzle.board[*row][*column] == 0).expect("An empty cell");
+        wrong.board[row][column] = puzzle.solution[row][column] % 9 + 1;
+        Puzzle::load(wrong).expect_err("Expected the wrong clue to be rejected");
+        let mut harder = puzzle.clone();
+        harder.difficulty = Difficulty::Hard;
+        Puzzle::load(harder).expect_err("Expected the wrong difficulty to be rejected");
+        let mut short = puzzle;
+        short.board.pop();
+        Puzzle::load(short).expect_err("Expected the short board to be rejected");
+    }
+
+    #[test]
+    fn grades() {
+        assert_eq!(grade_grid(&Grid::new()), None);
+        let mut clash = Grid::new();
+        clash.set(0, 0, 1);
+        clash.set(0, 1, 1);
+        assert_eq!(grade_grid(&clash), None);
+        let solution = Grid::random_solution(&mut StdRng::from_seed([0; 32]));
+        assert_eq!(grade_grid(&solution), Some(Difficulty::Easy));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,258 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use rand::prelude::*;
+
+pub const SIZE: usize = 9;
+const CELLS: usize = SIZE * SIZE;
+/// Every value a cell can have, as bits 1 to 9.
+const ALL_VALUES: u16 = 0b11_1111_1110;
+
+/// A sudoku board, 0 is an empty cell.
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct Grid {
+    cells: [u8; CELLS],
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Grid { cells: [0; CELLS] }
+    }
+
+    /// A grid from 9 rows of 9 values, each from 0 for empty to 9.
+    pub fn from_rows(rows: &[Vec<u8>]) -> Result<Self, String> {
+        if rows.len() != SIZE || rows.iter().any(|row| row.len() != SIZE) {
+            return Err("The board must be 9 rows of 9 cells".to_string());
+        }
+        let mut grid = Grid::new();
+        for (index, &value) in rows.iter().flatten().enumerate() {
+            if usize::from(value) > SIZE {
+                return Err(format!("Invalid value {value}, cells must be from 0 for empty to 9"));
+            }
+            grid.cells[index] = value;
+        }
+        Ok(grid)
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<u8>> {
+        self.cells.chunks(SIZE).map(<[u8]>::to_vec).collect()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> u8 {
+        self.cells[row * SIZE + column]
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, value: u8) {
+        self.cells[row * SIZE + column] = value;
+    }
+
+    pub fn clues(&self) -> usize {
+        self.cells.iter().filter(|value| **value != 0).count()
+    }
+
+    /// The indices of the cells that share a row, column or box with `index`, and `index` itself.
+    fn peers(index: usize) -> impl Iterator<Item = usize> {
+        let (row, column) = (index / SIZE, index % SIZE);
+        let (box_row, box_column) = (row / 3 * 3, column / 3 * 3);
+        (0..SIZE).flat_map(move |i| [row * SIZE + i, i * SIZE + column, (box_row + i / 3) * SIZE + box_column + i % 3])
+    }
+
+    /// The values that could go in the cell at `index` as bits, ignoring what is in it now.
+    fn candidates(&self, index: usize) -> u16 {
+        Self::peers(index)
+            .filter(|peer| *peer != index)
+            .fold(ALL_VALUES, |candidates, peer| candidates & !(1 << self.cells[peer]))
+    }
+
+    /// Whether `value` can go at `row`, `column` without the same value already being in its row, column or box.
+    pub fn can_place(&self, row: usize, column: usize, value: u8) -> bool {
+        (1..=SIZE as u8).contains(&value) && self.candidates(row * SIZE + column) & (1 << value) != 0
+    }
+
+    /// Whether none of the filled in cells clash with each other.
+    pub fn is_consistent(&self) -> bool {
+        self.cells
+            .iter()
+            .enumerate()
+            .all(|(index, &value)| value == 0 || self.candidates(index) & (1 << value) != 0)
+    }
+
+    /// The empty cell with the fewest candidates and its candidates, or `None` if the grid is full.
+    fn most_constrained(&self) -> Option<(usize, u16)> {
+        let mut best = None;
+        let mut fewest = u32::MAX;
+        for index in (0..CELLS).filter(|index| self.cells[*index] == 0) {
+            let candidates = self.candidates(index);
+            if candidates.count_ones() < fewest {
+                fewest = candidates.count_ones();
+                best = Some((index, candidates));
+                if candidates.count_ones() <= 1 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    /// Count the solutions, stopping at `limit`, the first one found is kept in `first`.
+    pub fn count_solutions(&self, limit: usize, first: &mut Option<Grid>) -> usize {
+        let mut count = 0;
+        self.clone().search(limit, &mut count, first, None);
+        count
+    }
+
+    /// A random solved grid.
+    pub fn random_solution(rng: &mut StdRng) -> Grid {
+        let mut solution = None;
+        Grid::new().search(1, &mut 0, &mut solution, Some(rng));
+        solution.expect("An empty grid always has a solution") // COV_EXCL_LINE
+    }
+
+    /// Backtracking, trying the values in a random order when there is an `rng`.
+    fn search(&mut self, limit: usize, count: &mut usize, first: &mut Option<Grid>, mut rng: Option<&mut StdRng>) {
+        let Some((index, candidates)) = self.most_constrained() else {
+            *count += 1;
+            first.get_or_insert_with(|| self.clone());
+            return;
+        };
+        let mut values = (1..=SIZE as u8).filter(|value| candidates & (1 << value) != 0).collect::<Vec<_>>();
+        if let Some(rng) = rng.as_deref_mut() {
+            values.shuffle(rng);
+        }
+        for value in values {
+            self.cells[index] = value;
+            self.search(limit, count, first, rng.as_deref_mut());
+            if *count >= limit {
+                break;
+            }
+        }
+        self.cells[index] = 0;
+    }
+
+    /// Fill in the cells that logic alone gives, returning whether hidden singles were needed.
+    ///
+    /// A naked single is a cell with only one candidate, a hidden single is a value with only one place
+    /// it can go in a row, column or box. Whatever can't be filled in that way is left empty.
+    pub fn fill_singles(&mut self) -> bool {
+        let mut used_hidden = false;
+        loop {
+            let candidates: [u16; CELLS] = std::array::from_fn(|index| match self.cells[index] {
+                0 => self.candidates(index),
+                _ => 0,
+            });
+            let naked = (0..CELLS)
+                .find(|index| candidates[*index].count_ones() == 1)
+                .map(|index| (index, candidates[index].trailing_zeros() as u8));
+            if let Some((index, value)) = naked.or_else(|| Self::hidden_single(&candidates)) {
+                used_hidden |= naked.is_none();
+                self.cells[index] = value;
+            } else {
+                return used_hidden;
+            }
+        }
+    }
+
+    fn hidden_single(candidates: &[u16; CELLS]) -> Option<(usize, u8)> {
+        let units = (0..SIZE).flat_map(|i| {
+            [
+                std::array::from_fn::<_, SIZE, _>(|j| i * SIZE + j),
+                std::array::from_fn(|j| j * SIZE + i),
+                std::array::from_fn(|j| (i / 3 * 3 + j / 3) * SIZE + i % 3 * 3 + j % 3),
+            ]
+        });
+        for unit in units {
+            for value in 1..=SIZE as u8 {
+                let mut places = unit.iter().filter(|index| candidates[**index] & (1 << value) != 0);
+                if let (Some(&index), None) = (places.next(), places.next()) {
+                    return Some((index, value));
+                }
+            }
+        }
+        None
+    }
+
+    pub fn is_full(&self) -> bool {
+        !self.cells.contains(&0)
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PUZZLE: &str =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    const SOLUTION: &str =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+    fn grid(digits: &str) -> Grid {
+        let mut grid = Grid::new();
+        for (index, digit) in digits.bytes().enumerate() {
+            grid.cells[index] = digit - b'0';
+        }
+        grid
+    }
+
+    #[test]
+    fn rows() {
+        let puzzle = grid(PUZZLE);
+        let rows = puzzle.to_rows();
+        assert_eq!(rows[0], vec![5, 3, 0, 0, 7, 0, 0, 0, 0]);
+        assert_eq!(Grid::from_rows(&rows), Ok(puzzle));
+        Grid::from_rows(&rows[..8]).expect_err("Expected the missing row to be rejected");
+        let mut bad = rows;
+        bad[0][0] = 10;
+        Grid::from_rows(&bad).expect_err("Expected the value to be rejected");
+    }
+
+    #[test]
+    fn placing() {
+        let puzzle = grid(PUZZLE);
+        assert!(puzzle.can_place(0, 2, 4));
+        // 5 is already in the row, 9 in the box and 8 in the column
+        assert!(!puzzle.can_place(0, 2, 5));
+        assert!(!puzzle.can_place(0, 2, 9));
+        assert!(!puzzle.can_place(0, 2, 8));
+        assert!(!puzzle.can_place(0, 2, 0));
+        // a cell's own value doesn't count against it
+        assert!(puzzle.can_place(0, 0, 5));
+        assert!(puzzle.is_consistent());
+        let mut clash = puzzle;
+        clash.set(0, 2, 5);
+        assert!(!clash.is_consistent());
+    }
+
+    #[test]
+    fn solving() {
+        let mut solution = None;
+        assert_eq!(grid(PUZZLE).count_solutions(2, &mut solution), 1);
+        assert_eq!(solution, Some(grid(SOLUTION)));
+        assert_eq!(Grid::new().count_solutions(2, &mut None), 2);
+        let mut clash = grid(PUZZLE);
+        clash.set(0, 2, 5);
+        assert_eq!(clash.count_solutions(2, &mut None), 0);
+    }
+
+    #[test]
+    fn random_solution() {
+        let solution = Grid::random_solution(&mut StdRng::from_seed([0; 32]));
+        assert!(solution.is_full());
+        assert!(solution.is_consistent());
+        assert_ne!(solution, Grid::random_solution(&mut StdRng::from_seed([1; 32])));
+    }
+
+    #[test]
+    fn singles() {
+        let mut puzzle = grid(PUZZLE);
+        puzzle.fill_singles();
+        assert_eq!(puzzle, grid(SOLUTION));
+    }
+}
+// COV_EXCL_STOP
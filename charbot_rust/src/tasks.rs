@@ -0,0 +1,104 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Running slow work on a pool of threads as an awaitable, so the asyncio event loop of the bot keeps
+//! handling events while it runs, and splitting batches of work across threads.
+//!
+//! The awaitables need the `async` feature and only the translations and what's built on them await, and
+//! without the `parallel` feature batches are worked through in turn instead.
+#[cfg(all(feature = "async", feature = "fluent"))]
+use pyo3::exceptions::PyRuntimeError;
+#[cfg(all(feature = "async", feature = "fluent"))]
+use pyo3::prelude::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// The most threads the pool has, however many cores there are, since the bot does little at once.
+#[cfg(all(feature = "async", feature = "fluent"))]
+const MAX_THREADS: usize = 8;
+
+/// Set up the tokio runtime the awaitable functions run on, it starts the first time something is awaited.
+#[cfg(all(feature = "async", feature = "fluent"))]
+fn init_runtime() {
+    static RUNTIME: std::sync::Once = std::sync::Once::new();
+    RUNTIME.call_once(|| {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from).min(MAX_THREADS);
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder.enable_all().worker_threads(1).max_blocking_threads(threads).thread_name("charbot_rust");
+        pyo3_asyncio::tokio::init(builder);
+    });
+}
+
+/// An awaitable of the running event loop, which is done with what `work` returns once a thread of the
+/// pool has run it.
+///
+/// `work` gets the GIL, so it can turn its arguments and result into python objects, and should let go
+/// of it with `allow_threads` for the slow part, like the functions it wraps already do.
+#[cfg(all(feature = "async", feature = "fluent"))]
+pub(crate) fn spawn<F>(py: Python<'_>, work: F) -> PyResult<&PyAny>
+where
+    F: FnOnce(Python) -> PyResult<PyObject> + Send + 'static,
+{
+    init_runtime();
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        // the work blocks, so it runs on the blocking threads and leaves the runtime's own free
+        tokio::task::spawn_blocking(move || Python::with_gil(work)).await.unwrap_or_else(|_| {
+            tracing::error!(target: "charbot_rust.tasks", "Work on the thread pool panicked");
+            Err(PyRuntimeError::new_err("The work panicked"))
+        })
+    })
+}
+
+/// `work` done to each of `items`, split across rayon's threads, one for each core, in the order of `items`.
+///
+/// It doesn't take the GIL, so call it inside `allow_threads` with everything already out of python.
+pub(crate) fn parallel_map<T, R, F>(items: Vec<T>, work: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    return items.into_par_iter().map(work).collect();
+    #[cfg(not(feature = "parallel"))]
+    return items.into_iter().map(work).collect();
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_order() {
+        let items = (0..1000).collect::<Vec<u64>>();
+        assert_eq!(parallel_map(items.clone(), |item| item * item), items.iter().map(|item| item * item).collect::<Vec<_>>());
+        assert_eq!(parallel_map(vec![String::from("one")], |item| item.len()), vec![3]);
+        assert_eq!(parallel_map(Vec::<u64>::new(), |item| item), Vec::<u64>::new());
+    }
+
+    #[test]
+    #[cfg(all(feature = "async", feature = "fluent"))]
+    fn runs_at_once() {
+        if std::thread::available_parallelism().map_or(1, usize::from) < 2 {
+            return;
+        }
+        // each job waits for the other, so they only finish if two threads run them at the same time
+        use std::sync::{mpsc, Arc, Barrier};
+        use std::time::Duration;
+
+        let (barrier, (sender, receiver)) = (Arc::new(Barrier::new(2)), mpsc::channel());
+        init_runtime();
+        for _ in 0..2 {
+            let (barrier, sender) = (Arc::clone(&barrier), sender.clone());
+            pyo3_asyncio::tokio::get_runtime().spawn_blocking(move || {
+                barrier.wait();
+                sender.send(()).expect("The test stopped listening");
+            });
+        }
+        for _ in 0..2 {
+            receiver.recv_timeout(Duration::from_secs(10)).expect("The jobs didn't run at once");
+        }
+    }
+}
+// COV_EXCL_STOP
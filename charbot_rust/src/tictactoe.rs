@@ -1,14 +1,18 @@
 // SPDX-FileCopyrightText: 2021 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>  // COV_EXCL_LINE
 //
 // SPDX-License-Identifier: MIT
-mod board; // COV_EXCL_LINE
+pub(crate) mod board; // COV_EXCL_LINE
 mod player;
 
 use rand::prelude::*;
-use pyo3::exceptions::PyException;
+use pyo3::exceptions::{PyException, PyValueError};
 use pyo3::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
 use crate::tictactoe::board::{Offset, Piece};
 use crate::points::Points;
+use crate::persist::{self, Persist};
+use crate::metrics;
 
 #[derive(Debug, PartialEq, Eq)] // COV_EXCL_LINE
 pub enum Difficulty {
@@ -33,7 +37,7 @@ impl Difficulty {
 // COV_EXCL_START
 #[pyclass(module = "tictactoe")]
 #[derive(Debug)]
-struct Game {
+pub(crate) struct Game {
     pub board: board::Board,
     pub player_x: Box<dyn player::Player>,
     pub player_o: Box<dyn player::Player>,
@@ -59,9 +63,9 @@ impl Game {
             Difficulty::Medium => {
                 if rng.gen_bool(0.5){ // COV_EXCL_LINE
                     x = player::choose_player("h");
-                    o = player::choose_player("m");
+                    o = player::choose_player("e");
                 } else {
-                    x = player::choose_player("m"); // COV_EXCL_LINE
+                    x = player::choose_player("e"); // COV_EXCL_LINE
                     o =player::choose_player("h"); // COV_EXCL_LINE
                     human_first = false; // COV_EXCL_LINE
 
@@ -73,9 +77,9 @@ impl Game {
                 human_first = false;
             },
             Difficulty::Random => {
-                let comp_mode: &str = vec!["m", "a", "r"].choose(&mut rng).unwrap();
+                let comp_mode: &str = ["e", "a", "r"].choose(&mut rng).unwrap();
                 let chance: f64 = match comp_mode {
-                    "m" => {0.5}, // COV_EXCL_LINE
+                    "e" => {0.5}, // COV_EXCL_LINE
                     "a" => {0.25},
                     "r" => {0.75}, // COV_EXCL_LINE
                     _ => {0.0} // COV_EXCL_LINE
@@ -109,6 +113,82 @@ impl Game {
             _ => {Err("Unexpected Logic Error".to_string())} // COV_EXCL_LINE
         }
     } // COV_EXCL_LINE
+
+    fn is_over(&self) -> bool {
+        self.board.is_victory().is_some() || self.board.is_draw()
+    }
+
+    /// Place the player's piece at `index`, then the computer's if that didn't end the game.
+    fn make_move(&mut self, index: board::Index) -> Result<Option<board::Index>, String> {
+        if self.is_over() {
+            return Err("The game is already over".to_string());
+        }
+        if !board::Board::is_valid_index(index) {
+            return Err(format!("Invalid index {index}, it must be from 0 to 8"));
+        }
+        if !self.board.cell_is_empty(index) {
+            return Err(format!("The cell at index {index} is already taken"));
+        }
+        let (human, computer, computer_piece) = match self.human_first {
+            true => (Piece::X, &self.player_o, Piece::O),
+            false => (Piece::O, &self.player_x, Piece::X),
+        };
+        self.board.place_piece(index, human);
+        if self.is_over() {
+            return Ok(None);
+        }
+        let comp_move = computer.play(&self.board, computer_piece);
+        self.board.place_piece(comp_move, computer_piece);
+        Ok(Some(comp_move))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct State {
+    board: [Piece; 9],
+    /// The players by the names `choose_player` takes.
+    player_x: String,
+    player_o: String,
+    human_first: bool,
+    points: Points,
+}
+
+impl Persist for Game {
+    const GAME: &'static str = "tictactoe";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State {
+            board: self.board.board,
+            player_x: self.player_x.name().to_string(),
+            player_o: self.player_o.name().to_string(),
+            human_first: self.human_first,
+            points: self.points.clone(),
+        }
+    }
+
+    fn load(state: State) -> Result<Self, String> {
+        let player = |name: &str| player::choose_player(name).ok_or_else(|| format!("Invalid player {name:?}"));
+        let (player_x, player_o) = (player(&state.player_x)?, player(&state.player_o)?);
+        let human = if state.human_first { &player_x } else { &player_o };
+        if human.name() != "human" {
+            return Err("The player who goes first must be the human".to_string());
+        }
+        let count = |piece| state.board.iter().filter(|cell| **cell == piece).count();
+        let (x, o) = (count(Piece::X), count(Piece::O));
+        if x != o && x != o + 1 {
+            return Err(format!("{x} Xs and {o} Os can't come from taking turns"));
+        }
+        let board = board::Board { board: state.board, n_pieces: (x + o) as u8 };
+        let game = Game { board, player_x, player_o, human_first: state.human_first, points: state.points };
+        // the computer always moves straight after the player, so a saved game is never waiting on it
+        let human_to_move = (x == o) == game.human_first;
+        if !game.is_over() && !human_to_move {
+            return Err("It can't be the computer's turn in a saved game".to_string());
+        }
+        Ok(game)
+    }
 }
 
 #[pymethods] // COV_EXCL_LINE
@@ -125,26 +205,20 @@ impl Game {
     }
 
 
-    fn play(&mut self, index: board::Index) -> Option<board::Index> {
-        let human = match self.human_first {
-            true => Piece::X,
-            false => Piece::O,
-        };
-        let computer = match self.human_first {
-            true => &self.player_o,
-            false => &self.player_x,
-        };
-        let computer_piece = match self.human_first {
-            true => Piece::O,
-            false => Piece::X,
-        };
-        self.board.place_piece(index, human);
-        if self.board.is_victory_for_player(human) | self.board.is_draw() {
-            return None;
+    fn play(&mut self, index: board::Index) -> PyResult<Option<board::Index>> {
+        metrics::observe("tictactoe.play", || self.make_move(index).map_err(PyErr::new::<PyValueError, _>))
+    }
+
+    fn legal_moves(&self) -> Vec<board::Index> {
+        if self.is_over() {
+            return Vec::new();
         }
-        let comp_move = computer.play(&self.board, computer_piece);
-        self.board.place_piece(comp_move, computer_piece);
-        Some(comp_move)
+        board::Board::VALID_INDICES.filter(|index| self.board.cell_is_empty(*index)).collect()
+    }
+
+    #[pyo3(name = "is_over")]
+    fn py_is_over(&self) -> bool {
+        self.is_over()
     }
 
     fn display_commands(&self) -> Vec<(Offset, Piece)> {
@@ -188,6 +262,25 @@ impl Game {
             self.points.loss
         }
     }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
 }
 
 // COV_EXCL_START
@@ -254,18 +347,40 @@ mod tests {
         let mut human_first = Game::new(1, StdRng::from_entropy()).expect("Failed to create game");
         let mut computer_first = Game::new(3, StdRng::from_entropy()).expect("Failed to create game");
         human_first.board.board = [Piece::X, Piece::X, Piece::Empty, Piece::Empty, Piece::O, Piece::Empty, Piece::O, Piece::Empty, Piece::Empty];
-        assert_eq!(None, human_first.play(2));
+        human_first.board.n_pieces = 4;
+        assert_eq!(Ok(None), human_first.make_move(2));
+        assert!(human_first.is_over());
+        human_first.make_move(3).expect_err("Expected the game to be over");
         if computer_first.board.cell_is_empty(0) {
-            computer_first.play(0).expect("Failed to play");
+            computer_first.make_move(0).expect("Failed to play").expect("Expected a computer move");
         } else {
-            computer_first.play(1).expect("Failed to play");
+            computer_first.make_move(1).expect("Failed to play").expect("Expected a computer move");
         }
 
     }
     #[test]
+    fn illegal_moves() {
+        let mut game = Game::new(3, StdRng::from_entropy()).expect("Failed to create game");
+        assert_eq!(game.legal_moves().len(), 8);
+        let taken = (0..9).find(|index| !game.board.cell_is_empty(*index)).expect("Computer didn't move");
+        game.make_move(taken).expect_err("Expected the taken cell to be rejected");
+        game.make_move(9).expect_err("Expected the index to be rejected");
+        assert_eq!(game.legal_moves().len(), 8);
+        assert!(!game.legal_moves().contains(&taken));
+    }
+    #[test]
+    fn perfect_play_never_loses() {
+        let mut game = Game::new(3, StdRng::from_entropy()).expect("Failed to create game");
+        while let Some(&index) = game.legal_moves().first() {
+            game.make_move(index).expect("Failed to play");
+        }
+        assert!(game.is_over());
+        assert!(!game.has_player_won());
+    }
+    #[test]
     fn display() {
         let mut game = Game::new(1, StdRng::from_entropy()).expect("Failed to create game");
-        game.play(1).expect("Failed to play");
+        game.make_move(1).expect("Failed to play");
         let commands = game.display_commands();
         assert_eq!(commands.len(), 9);
         for (i ,(_, command)) in commands.iter().enumerate() {
@@ -290,6 +405,36 @@ mod tests {
         assert!(!computer_first.has_player_lost());
     }
     #[test]
+    fn persists() {
+        let mut game = Game::new(2, StdRng::from_seed([0; 32])).expect("Failed to create game");
+        let index = game.legal_moves()[0];
+        game.make_move(index).expect("Failed to play");
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Game = persist::from_json(&json).expect("Failed to load");
+        assert_eq!(loaded.board.board, game.board.board);
+        assert_eq!(loaded.board.n_pieces, game.board.n_pieces);
+        assert_eq!((loaded.player_x.name(), loaded.player_o.name()), (game.player_x.name(), game.player_o.name()));
+        assert_eq!(loaded.human_first, game.human_first);
+        assert_eq!(loaded.points, game.points);
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        let loaded: Game = persist::from_bytes(&bytes).expect("Failed to load");
+        assert_eq!(loaded.legal_moves(), game.legal_moves());
+    }
+    #[test]
+    fn rejects_saves() {
+        let game = Game::new(1, StdRng::from_entropy()).expect("Failed to create game");
+        let state = |change: fn(&mut State)| {
+            let mut state = game.save();
+            change(&mut state);
+            Game::load(state)
+        };
+        state(|_| {}).expect("Failed to load");
+        state(|state| state.player_o = "nobody".to_string()).expect_err("Expected the player to be rejected");
+        state(|state| state.human_first = false).expect_err("Expected the computer going first to be rejected");
+        state(|state| state.board[0] = Piece::O).expect_err("Expected the extra O to be rejected");
+        state(|state| state.board[0] = Piece::X).expect_err("Expected it being the computer's turn to be rejected");
+    }
+    #[test]
     fn points() {
         let mut game = Game::new(1, StdRng::from_entropy()).expect("Failed to create game");
         assert_eq!(game.points(), game.points.loss, "1");
@@ -299,4 +444,4 @@ mod tests {
         assert_eq!(game.points(), game.points.draw, "3");
     }
 }
-// COV_EXCL_END
+// COV_EXCL_STOP
@@ -6,11 +6,12 @@ use std::fmt::{Display, Error, Formatter, Write};
 use std::ops::Deref;
 use pyo3::{IntoPy, pyclass, pymethods, PyObject, PyResult, Python};
 use pyo3::types::PyString;
+use serde::{Deserialize, Serialize};
 // COV_EXCL_STOP
 
 
 #[pyclass(module = "tictactoe")] // COV_EXCL_LINE
-#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)] // COV_EXCL_LINE
 pub enum Piece { // COV_EXCL_LINE
     X,
     O,
@@ -228,6 +229,14 @@ impl Board {
         false
     }
 
+    /// The cells of the line of three a player won with, if either has.
+    #[cfg_attr(not(feature = "images"), allow(dead_code))]
+    pub fn winning_line(&self) -> Option<(Index, Index, Index)> {
+        WINNING_INDICES.iter().copied().find(|&(a, b, c)| {
+            self.board[a] != Piece::Empty && self.board[a] == self.board[b] && self.board[b] == self.board[c]
+        })
+    }
+
     pub fn is_victory(&self) -> Option<Piece> {
         if self.is_victory_for_player(Piece::X) {
             Some(Piece::X)
@@ -328,5 +337,15 @@ mod tests {
         assert!(!board.is_victory_for_player(Piece::O));
         assert_eq!(format!("{}", board), "XXX\nXXX\nXXX");
     }
+    #[test]
+    fn winning_line() {
+        let mut board = Board::new();
+        assert_eq!(board.winning_line(), None);
+        for index in [2, 4, 6] {
+            board.place_piece(index, Piece::O);
+        }
+        board.place_piece(0, Piece::X);
+        assert_eq!(board.winning_line(), Some((2, 4, 6)));
+    }
 }
 // COV_EXCL_STOP
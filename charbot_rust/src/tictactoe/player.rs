@@ -1,5 +1,6 @@
 // SPDX-FileCopyrightText: 2021 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
 // SPDX-License-Identifier: MIT
+mod heuristic_player;
 mod human_player;
 mod minimax_player;
 mod random_player;
@@ -7,6 +8,7 @@ mod random_player;
 use super::board::{Board, Index, Piece};
 use std::fmt::{Debug, Display};
 
+pub use heuristic_player::HeuristicPlayer;
 pub use human_player::HumanPlayer;
 pub use minimax_player::MinimaxPlayer;
 pub use random_player::RandomPlayer;
@@ -14,11 +16,14 @@ pub use random_player::RandomPlayer;
 
 pub trait Player: Display + Debug {
     fn play(&self, board: &Board, piece: Piece) -> Index;
+    /// The name `choose_player` makes this player from, so saved games get the same player back.
+    fn name(&self) -> &'static str;
 }
 
 pub fn choose_player(c: &str) -> Option<Box<dyn Player>> {
     match c.to_lowercase().as_str() {
         "human" | "h" => Some(Box::new(HumanPlayer)),
+        "heuristic" | "e" => Some(Box::new(HeuristicPlayer)),
         "minimax" | "m" => Some(Box::new(MinimaxPlayer::new(false))),
         "alphabeta" | "a" => Some(Box::new(MinimaxPlayer::new(true))),
         "random" | "r" => Some(Box::new(RandomPlayer)),
@@ -55,6 +60,8 @@ mod tests {
     #[parameterized(
         human_long = {"human", "human"},
         human_short = {"h", "human"},
+        heuristic_long = {"heuristic", "heuristic"},
+        heuristic_short = {"e", "heuristic"},
         minimax_long = {"minimax", "minimax"},
         minimax_short = {"m", "minimax"},
         alphabeta_long = {"alphabeta", "alphabeta"},
@@ -63,7 +70,8 @@ mod tests {
         random_short = {"r", "random"},
     )]
     fn test_choose_player(player: &str, expected: &str) {
-        choose_player(player).unwrap_or_else(|| panic!("Could not create player {}", expected));
+        let player = choose_player(player).unwrap_or_else(|| panic!("Could not create player {}", expected));
+        assert_eq!(player.name(), expected);
     }
     #[test]
     fn test_choose_player_invalid() {
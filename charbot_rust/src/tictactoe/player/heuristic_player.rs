@@ -0,0 +1,78 @@
+// SPDX-FileCopyrightText: 2021 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+// SPDX-License-Identifier: MIT
+use super::Player;
+use crate::tictactoe::board::{Board, Index, Piece};
+use rand::seq::IteratorRandom;
+use std::fmt::{Display, Error, Formatter};
+
+/// Plays like a person who knows the rules but doesn't look ahead: win, block, centre, corner, anything.
+///
+/// It can be beaten with a fork, unlike the minimax player.
+#[derive(Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub struct HeuristicPlayer;
+
+const CENTRE: Index = 4;
+const CORNERS: [Index; 4] = [0, 2, 6, 8];
+
+impl Display for HeuristicPlayer {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> Result<(), Error> {
+        formatter.write_str("Heuristic player")
+    }
+}
+
+impl HeuristicPlayer {
+    /// An empty cell that would complete a line for `piece`, if there is one.
+    fn winning_move(board: &Board, piece: Piece) -> Option<Index> {
+        Board::VALID_INDICES.filter(|index| board.cell_is_empty(*index)).find(|&index| {
+            let mut new_board = board.clone();
+            new_board.place_piece(index, piece);
+            new_board.is_victory_for_player(piece)
+        })
+    }
+}
+
+impl Player for HeuristicPlayer {
+    fn play(&self, board: &Board, piece: Piece) -> Index {
+        let mut rng = rand::thread_rng();
+        Self::winning_move(board, piece)
+            .or_else(|| Self::winning_move(board, piece.swap()))
+            .or_else(|| board.cell_is_empty(CENTRE).then_some(CENTRE))
+            .or_else(|| CORNERS.into_iter().filter(|index| board.cell_is_empty(*index)).choose(&mut rng))
+            .or_else(|| Board::VALID_INDICES.filter(|index| board.cell_is_empty(*index)).choose(&mut rng))
+            .unwrap()
+    }
+
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board_from(pieces: [Piece; 9]) -> Board {
+        let mut board = Board::new();
+        for (index, piece) in pieces.into_iter().enumerate() {
+            if piece != Piece::Empty {
+                board.place_piece(index, piece);
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn test_heuristic_player() {
+        use Piece::{Empty as E, O, X};
+        let player = HeuristicPlayer;
+        assert_eq!(player.play(&Board::new(), X), 4);
+        assert!(CORNERS.contains(&player.play(&board_from([E, E, E, E, X, E, E, E, E]), O)));
+        // winning beats blocking
+        assert_eq!(player.play(&board_from([X, X, E, O, O, E, E, E, E]), O), 5);
+        assert_eq!(player.play(&board_from([X, X, E, E, O, E, E, E, E]), O), 2);
+        assert_eq!(player.play(&board_from([X, O, X, X, O, O, O, X, E]), X), 8);
+        assert_eq!("Heuristic player", format!("{}", player));
+    }
+}
+// COV_EXCL_STOP
@@ -18,6 +18,10 @@ impl Player for HumanPlayer {
     fn play(&self, board: &Board, _: Piece) -> Index {
         Board::VALID_INDICES.find(|index| board.cell_is_empty(*index)).unwrap()
     }
+
+    fn name(&self) -> &'static str {
+        "human"
+    }
 }
 
 // COV_EXCL_START
@@ -130,6 +130,10 @@ impl Player for MinimaxPlayer {
 
         best_move
     }
+
+    fn name(&self) -> &'static str {
+        if self.alpha_beta { "alphabeta" } else { "minimax" }
+    }
 }
 
 // COV_EXCL_START
@@ -23,6 +23,10 @@ impl Player for RandomPlayer {
             .choose(&mut rand::thread_rng())
             .unwrap()
     }
+
+    fn name(&self) -> &'static str {
+        "random"
+    }
 }
 
 // COV_EXCL_START
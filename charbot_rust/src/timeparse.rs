@@ -0,0 +1,697 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+mod words; // COV_EXCL_LINE
+mod duration;
+mod zones;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use crate::cooldowns::{self, tz::{self, Zone}};
+use crate::fluent::{self, bundle};
+use crate::metrics;
+use crate::shrugman::fold;
+use words::{Phrase, Unit, Words};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// When a day is set without a time, like "friday".
+const DEFAULT_HOUR: u32 = 9;
+/// When "tonight" is set without a time.
+const TONIGHT_HOUR: u32 = 20;
+
+// COV_EXCL_START
+#[pyclass(module = "timeparse")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct When {
+    /// The unix timestamp in seconds of the time that was found.
+    #[pyo3(get)]
+    timestamp: i64,
+    /// The index in the text of the first character of the words the time was found in.
+    #[pyo3(get)]
+    start: usize,
+    /// The index in the text just after the last character of the words the time was found in.
+    #[pyo3(get)]
+    end: usize,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl When {
+    fn __repr__(&self) -> String {
+        format!("When(timestamp={}, start={}, end={})", self.timestamp, self.start, self.end)
+    }
+}
+
+// COV_EXCL_START
+#[pyclass(module = "timeparse")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timezone {
+    /// The name of the zone in the tz database, like `Europe/Amsterdam`.
+    #[pyo3(get)]
+    name: String,
+    /// How many seconds ahead of UTC the clocks in the zone are, behind if it is negative.
+    #[pyo3(get)]
+    offset: i64,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Timezone {
+    fn __repr__(&self) -> String {
+        format!("Timezone(name={:?}, offset={})", self.name, self.offset)
+    }
+}
+
+/// A word or number of the text, lowercase and without accents, and which characters it came from.
+#[derive(Debug)]
+struct Token {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+/// `text` split into runs of letters and runs of digits, so "9pm" is "9" then "pm", with the colons
+/// of times kept as their own tokens and everything else dropped.
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut last = None;
+    for (index, letter) in text.chars().enumerate() {
+        let class = match letter {
+            _ if letter.is_alphabetic() => Some(0),
+            _ if letter.is_ascii_digit() => Some(1),
+            ':' => Some(2),
+            _ => None,
+        };
+        match tokens.last_mut() {
+            _ if class.is_none() => {}
+            // runs of letters and of digits carry on, colons are a token each
+            Some(token) if class != Some(2) && class == last => {
+                token.text.extend(letter.to_lowercase().map(fold));
+                token.end = index + 1;
+            }
+            _ => tokens.push(Token { text: letter.to_lowercase().map(fold).collect(), start: index, end: index + 1 }),
+        }
+        last = class;
+    }
+    tokens
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+enum Meridiem {
+    Am,
+    Pm,
+}
+
+/// A time of day as it was written, before it's known which day it's on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+struct Clock {
+    hour: u32,
+    minute: u32,
+    meridiem: Option<Meridiem>,
+}
+
+impl Clock {
+    /// The hour from 0 to 23, with hours without a meridiem taken as in the evening if `evening`.
+    fn hour(self, evening: bool) -> u32 {
+        match self.meridiem {
+            Some(Meridiem::Am) => self.hour % 12,
+            Some(Meridiem::Pm) => self.hour % 12 + 12,
+            None if evening && (1..12).contains(&self.hour) => self.hour + 12,
+            None => self.hour,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+enum Day {
+    /// Some days after today.
+    Offset(i64),
+    Tonight,
+    /// The next of a day of the week, from 0 for sunday.
+    Weekday(i64),
+    Date { month: u32, day: u32, year: Option<i64> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+enum Found {
+    /// A duration from now, like "in 2 hours".
+    After(Vec<(i64, Unit)>),
+    /// A day, a time, or both, like "tomorrow at 9pm".
+    At(Option<Day>, Option<Clock>),
+}
+
+struct Parser<'a> {
+    tokens: &'a [Token],
+    words: &'static Words,
+}
+
+impl Parser<'_> {
+    fn text(&self, at: usize) -> Option<&str> {
+        self.tokens.get(at).map(|token| token.text.as_str())
+    }
+
+    /// Where the longest of `phrases` at `at` ends.
+    fn phrase(&self, at: usize, phrases: &[Phrase]) -> Option<usize> {
+        phrases
+            .iter()
+            .filter(|phrase| phrase.iter().enumerate().all(|(offset, word)| self.text(at + offset) == Some(*word)))
+            .map(|phrase| at + phrase.len())
+            .max()
+    }
+
+    fn word(&self, at: usize, words: &[&str]) -> Option<usize> {
+        let text = self.text(at)?;
+        words.contains(&text).then_some(at + 1)
+    }
+
+    fn digits(&self, at: usize) -> Option<(u32, usize)> {
+        let text = self.text(at)?;
+        // long runs of digits are ids or the like, not times
+        if text.len() > 6 || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+            return None;
+        }
+        Some((text.parse().ok()?, at + 1))
+    }
+
+    fn number(&self, at: usize) -> Option<(u32, usize)> {
+        self.digits(at).or_else(|| {
+            let text = self.text(at)?;
+            self.words.numbers.iter().find(|(word, _)| *word == text).map(|(_, number)| (*number, at + 1))
+        })
+    }
+
+    /// A number and a unit, and any more joined onto it, like "2 hours and 30 minutes" or "1h30m".
+    fn duration(&self, at: usize) -> Option<(Vec<(i64, Unit)>, usize)> {
+        let mut parts = Vec::new();
+        let mut end = at;
+        loop {
+            let start = if parts.is_empty() { end } else { self.phrase(end, self.words.and).unwrap_or(end) };
+            let Some((count, after)) = self.number(start) else { break };
+            let Some(unit) = self.text(after).and_then(|text| self.words.units.iter().find(|(word, _)| *word == text)) else { break };
+            parts.push((i64::from(count), unit.1));
+            end = after + 1;
+        }
+        (!parts.is_empty()).then_some((parts, end))
+    }
+
+    fn relative(&self, at: usize) -> Option<(Found, usize)> {
+        let (parts, end) = self.duration(self.phrase(at, self.words.relative)?)?;
+        Some((Found::After(parts), end))
+    }
+
+    fn meridiem(&self, at: usize) -> Option<(Meridiem, usize)> {
+        let am = self.phrase(at, self.words.am).map(|end| (Meridiem::Am, end));
+        let pm = self.phrase(at, self.words.pm).map(|end| (Meridiem::Pm, end));
+        am.into_iter().chain(pm).max_by_key(|(_, end)| *end)
+    }
+
+    /// A time of day, like "at 9", "9:30pm", "21h30" or "noon". Bare numbers are only taken for
+    /// hours after a word like "at", so other numbers in the text aren't.
+    fn clock(&self, at: usize) -> Option<(Clock, usize)> {
+        let after_at = self.phrase(at, self.words.at);
+        let start = after_at.unwrap_or(at);
+        if let Some(end) = self.phrase(start, self.words.noon) {
+            return Some((Clock { hour: 12, minute: 0, meridiem: None }, end));
+        }
+        if let Some(end) = self.phrase(start, self.words.midnight) {
+            return Some((Clock { hour: 0, minute: 0, meridiem: None }, end));
+        }
+        let (hour, mut end) = if after_at.is_some() { self.number(start)? } else { self.digits(start)? };
+        let mut minute = 0;
+        let mut explicit = false;
+        let minutes = |at: usize| self.digits(at).filter(|(minute, _)| self.text(at).map_or(0, str::len) == 2 && *minute < 60);
+        if self.text(end) == Some(":") {
+            (minute, end) = minutes(end + 1)?;
+            explicit = true;
+        } else if let Some(after) = self.phrase(end, self.words.oclock) {
+            end = after;
+            if let Some((after_minute, after)) = minutes(end) {
+                (minute, end) = (after_minute, after);
+            }
+            explicit = true;
+        }
+        let meridiem = self.meridiem(end);
+        if let Some((_, after)) = meridiem {
+            end = after;
+        }
+        let meridiem = meridiem.map(|(meridiem, _)| meridiem);
+        let valid = match meridiem {
+            Some(_) => (1..=12).contains(&hour),
+            None => hour < 24,
+        };
+        (valid && (explicit || meridiem.is_some() || after_at.is_some())).then_some((Clock { hour, minute, meridiem }, end))
+    }
+
+    fn weekday(&self, at: usize) -> Option<(i64, usize)> {
+        let start = self.phrase(at, self.words.next).unwrap_or(at);
+        let text = self.text(start)?;
+        let day = self.words.weekdays.iter().position(|names| names.contains(&text))?;
+        let end = self.phrase(start + 1, self.words.next_after).unwrap_or(start + 1);
+        Some((day as i64, end))
+    }
+
+    fn month(&self, at: usize) -> Option<(u32, usize)> {
+        let text = self.text(at)?;
+        let month = self.words.months.iter().position(|names| names.contains(&text))?;
+        Some((month as u32 + 1, at + 1))
+    }
+
+    /// A day of the month and the month, either way around, and maybe a year, like "5th of march 2025".
+    fn date(&self, at: usize) -> Option<(Day, usize)> {
+        let day_of_month = |at: usize| {
+            let (day, end) = self.digits(at).filter(|(day, _)| (1..=31).contains(day))?;
+            Some((day, self.word(end, self.words.ordinals).unwrap_or(end)))
+        };
+        let (month, day, end) = match day_of_month(at) {
+            Some((day, end)) => {
+                let (month, end) = self.month(self.phrase(end, self.words.of).unwrap_or(end))?;
+                (month, day, end)
+            }
+            None => {
+                let (month, end) = self.month(at)?;
+                let (day, end) = day_of_month(end)?;
+                (month, day, end)
+            }
+        };
+        match self.digits(end).filter(|(year, _)| (1000..=9999).contains(year)) {
+            Some((year, end)) => Some((Day::Date { month, day, year: Some(i64::from(year)) }, end)),
+            None => Some((Day::Date { month, day, year: None }, end)),
+        }
+    }
+
+    fn day(&self, at: usize) -> Option<(Day, usize)> {
+        let words = self.words;
+        let plain = |at: usize| {
+            let named = [(words.after_tomorrow, Day::Offset(2)), (words.tomorrow, Day::Offset(1)), (words.today, Day::Offset(0)), (words.tonight, Day::Tonight)];
+            named
+                .into_iter()
+                .find_map(|(phrases, day)| self.phrase(at, phrases).map(|end| (day, end)))
+                .or_else(|| self.weekday(at).map(|(weekday, end)| (Day::Weekday(weekday), end)))
+                .or_else(|| self.date(at))
+        };
+        self.phrase(at, words.on).and_then(plain).or_else(|| plain(at))
+    }
+
+    /// A day, a time, or both, either way around.
+    fn moment(&self, at: usize) -> Option<(Found, usize)> {
+        if let Some((day, end)) = self.day(at) {
+            return Some(match self.clock(end) {
+                Some((clock, end)) => (Found::At(Some(day), Some(clock)), end),
+                None => (Found::At(Some(day), None), end),
+            });
+        }
+        let (clock, end) = self.clock(at)?;
+        Some(match self.day(end) {
+            Some((day, end)) => (Found::At(Some(day), Some(clock)), end),
+            None => (Found::At(None, Some(clock)), end),
+        })
+    }
+}
+
+/// When `found` is from `now`, in `zone`, if it's a real time.
+fn resolve(found: &Found, zone: &Zone, now: i64) -> Option<i64> {
+    let local = zone.to_local(now);
+    let today = local.div_euclid(SECONDS_PER_DAY);
+    let time_of_day = local.rem_euclid(SECONDS_PER_DAY);
+    let at = |day: i64, hour: u32, minute: u32| zone.to_utc(day * SECONDS_PER_DAY + i64::from(hour) * 3600 + i64::from(minute) * 60);
+    let timestamp = match found {
+        Found::After(parts) => {
+            let (mut seconds, mut days, mut months) = (0i64, 0i64, 0i64);
+            for (count, unit) in parts {
+                match unit {
+                    Unit::Second => seconds += count,
+                    Unit::Minute => seconds += count * 60,
+                    Unit::Hour => seconds += count * 3600,
+                    Unit::Day => days += count,
+                    Unit::Week => days += count * 7,
+                    Unit::Month => months += count,
+                    Unit::Year => months += count * 12,
+                }
+            }
+            if days == 0 && months == 0 {
+                now + seconds
+            } else {
+                // days and months keep the time on the clock, even when the clocks change in between
+                let day = add_months(today, months)? + days;
+                zone.to_utc(day * SECONDS_PER_DAY + time_of_day) + seconds
+            }
+        }
+        Found::At(None, Some(clock)) => {
+            // the next time the clock shows it, in the evening if the morning has passed
+            let candidates = [at(today, clock.hour(false), clock.minute), at(today, clock.hour(true), clock.minute), at(today + 1, clock.hour(false), clock.minute)];
+            candidates.into_iter().find(|time| *time > now)?
+        }
+        Found::At(Some(day), clock) => {
+            let hours = |default: u32, evening: bool| clock.map_or((default, 0), |clock| (clock.hour(evening), clock.minute));
+            match *day {
+                Day::Offset(offset) => {
+                    let (hour, minute) = hours(DEFAULT_HOUR, false);
+                    at(today + offset, hour, minute)
+                }
+                Day::Tonight => {
+                    let (hour, minute) = hours(TONIGHT_HOUR, true);
+                    at(today, hour, minute)
+                }
+                Day::Weekday(weekday) => {
+                    // never today, "friday" on a friday is the next one
+                    let ahead = (weekday - tz::weekday(today)).rem_euclid(7);
+                    let (hour, minute) = hours(DEFAULT_HOUR, false);
+                    at(today + if ahead == 0 { 7 } else { ahead }, hour, minute)
+                }
+                Day::Date { month, day, year } => {
+                    let (hour, minute) = hours(DEFAULT_HOUR, false);
+                    let on = |year: i64| date(year, month, day).map(|days| at(days, hour, minute));
+                    match year {
+                        Some(year) => on(year)?,
+                        None => {
+                            let (this_year, _, _) = tz::civil_from_days(today);
+                            // a date that has passed this year is next year's, or the next leap year's for a 29th of february
+                            (this_year..=this_year + 8).filter_map(on).find(|time| *time > now)?
+                        }
+                    }
+                }
+            }
+        }
+        Found::At(None, None) => return None,
+    };
+    cooldowns::check(timestamp).ok()
+}
+
+/// The days since 1970-01-01 of a date, if it's a real one.
+fn date(year: i64, month: u32, day: u32) -> Option<i64> {
+    let first = tz::days_from_civil(year, month, 1);
+    let length = if month == 12 { tz::days_from_civil(year + 1, 1, 1) } else { tz::days_from_civil(year, month + 1, 1) } - first;
+    (i64::from(day) <= length).then_some(first + i64::from(day) - 1)
+}
+
+/// `months` after the date `days` since 1970-01-01, on the last day of the month if it's too short.
+fn add_months(days: i64, months: i64) -> Option<i64> {
+    if months == 0 {
+        return Some(days);
+    }
+    let (year, month, day) = tz::civil_from_days(days);
+    let total = year.checked_mul(12)?.checked_add(i64::from(month) - 1)?.checked_add(months)?;
+    let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+    (1..=day).rev().find_map(|day| date(year, month, day))
+}
+
+/// The first time in `text` from `now`, in `tz`, in the words of `locale`.
+fn parse_at(text: &str, locale: &str, tz: &str, now: i64) -> Result<Option<When>, String> {
+    let zone = cooldowns::zone(tz)?;
+    let now = cooldowns::check(now)?;
+    // the first locale with words of its own that translating into `locale` would use
+    let chain = bundle::fallback_chain(locale).unwrap_or_else(|| vec![bundle::negotiate(&[locale])]);
+    let Some(words) = chain.into_iter().find_map(words::words) else {
+        return Ok(None);
+    };
+    let tokens = tokenize(text);
+    let parser = Parser { tokens: &tokens, words };
+    for at in 0..tokens.len() {
+        let found = parser.relative(at).or_else(|| parser.moment(at));
+        if let Some((found, end)) = found {
+            if let Some(timestamp) = resolve(&found, &zone, now) {
+                return Ok(Some(When { timestamp, start: tokens[at].start, end: tokens[end - 1].end }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[pyfunction]
+#[pyo3(signature = (text, locale="en-US", tz="UTC", now=None), text_signature = "
+parse_when(text, locale='en-US', tz='UTC', now=None, /)
+--
+
+Find when a reminder is for in a message, like 'in 2 hours', 'tomorrow at 9pm', 'dans 3 jours' or
+'mañana a las 9'.
+
+Durations from now, days, times of day, and days with times are understood, in the words of the
+locale. Days without a time are at 9am, and tonight without a time is at 8pm. Times without a day
+are the next time the clock shows them, in the evening if the morning has passed, and days of the
+week are the next one after today.
+
+Parameters
+----------
+text : str
+    The message to find the time in.
+locale : str
+    The locale the message is written in, falling back like translations do. The default is 'en-US'.
+tz : str
+    The name of the time zone in the tz database the user is in, like 'Europe/Paris'. The default is
+    'UTC'.
+now : int | None
+    The unix timestamp in seconds to take times from. The default is None, for now.
+
+Returns
+-------
+When | None
+    The first time found, with where it is in the text, or None if there isn't one.
+
+Raises
+------
+ValueError
+    If the time zone isn't in the tz database, or now isn't from year 1 to 9999.
+")]
+fn parse_when(text: &str, locale: &str, tz: &str, now: Option<i64>) -> PyResult<Option<When>> {
+    metrics::observe("timeparse.parse_when", || parse_at(text, locale, tz, now.unwrap_or_else(cooldowns::now)).map_err(PyErr::new::<PyValueError, _>))
+}
+
+#[pyfunction]
+#[pyo3(signature = (text, locale="en-US", maximum=None), text_signature = "
+parse_duration(text, locale='en-US', maximum=None, /)
+--
+
+Read a compact duration like '90s', '1h30m', '2d 12h' or '1w' as a number of seconds, the other way
+around from humanize_duration with compact=True.
+
+The parts are whole numbers each followed by a unit, w, d, h, m or s, or the compact units of the
+locale like 'j' in french. They go from the biggest unit to the smallest, each once, with or without
+spaces between them.
+
+Parameters
+----------
+text : str
+    The duration.
+locale : str
+    The locale the duration is written in, falling back like translations do. Locales added at
+    runtime in other languages than the built in ones only have the units above, with no grouping.
+    The default is 'en-US'.
+maximum : int | None
+    The most seconds the duration can be. The default is None, for no limit.
+
+Returns
+-------
+int
+    The number of seconds.
+
+Raises
+------
+ValueError
+    If the duration isn't valid, saying at which character, or it's more than maximum.
+")]
+fn parse_duration(text: &str, locale: &str, maximum: Option<u64>) -> PyResult<u64> {
+    metrics::observe("timeparse.parse_duration", || {
+        duration::parse(text, fluent::formats(locale).ok(), maximum)
+            .map_err(|duration::Error { position, reason }| PyValueError::new_err(format!("Invalid duration {text:?} at {position}, {reason}")))
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (query, now=None), text_signature = "
+find_timezone(query, now=None, /)
+--
+
+Find the time zone someone means from what they typed, like 'amsterdam', 'EST', 'gmt+5:30' or
+'pacific'.
+
+Cities and countries with one time zone are matched forgiving typos and accents, common
+abbreviations and names like 'CET' or 'eastern' are the zone most people mean by them, and fixed
+offsets are the Etc zone with that offset, or a place with it when it isn't whole hours. Names in
+the tz database, including old ones like 'Asia/Calcutta', are used as they are.
+
+Parameters
+----------
+query : str
+    What the user typed.
+now : int | None
+    The unix timestamp in seconds to give the offset at. The default is None, for now.
+
+Returns
+-------
+Timezone | None
+    The time zone, or None if nothing is close enough.
+
+Raises
+------
+ValueError
+    If now isn't from year 1 to 9999.
+RuntimeError
+    If the embedded tz database is missing a zone or one of its tables.
+")]
+fn find_timezone(query: &str, now: Option<i64>) -> PyResult<Option<Timezone>> {
+    metrics::observe("timeparse.find_timezone", || {
+        let now = cooldowns::check(now.unwrap_or_else(cooldowns::now)).map_err(PyErr::new::<PyValueError, _>)?;
+        let found = zones::find(query, now).map_err(PyErr::new::<PyRuntimeError, _>)?;
+        Ok(found.map(|(name, offset)| Timezone { name, offset }))
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based parsing of when reminders are for, durations and time zones, in the bot's languages";
+
+pub(crate) fn register_timeparse(py: Python, m: &PyModule) -> PyResult<()> {
+    let timeparse = PyModule::new(py, "_timeparse")?;
+    timeparse.add_class::<When>()?;
+    timeparse.add_class::<Timezone>()?;
+    timeparse.add_function(wrap_pyfunction!(parse_when, timeparse)?)?;
+    timeparse.add_function(wrap_pyfunction!(parse_duration, timeparse)?)?;
+    timeparse.add_function(wrap_pyfunction!(find_timezone, timeparse)?)?;
+    timeparse.add("__doc__", DOCSTRING)?;
+    m.add_submodule(timeparse)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    const HOUR: i64 = 3600;
+
+    fn utc(year: i64, month: u32, day: u32, hour: i64, minute: i64) -> i64 {
+        tz::days_from_civil(year, month, day) * SECONDS_PER_DAY + hour * HOUR + minute * 60
+    }
+
+    /// A wednesday afternoon.
+    fn now() -> i64 {
+        utc(2024, 3, 6, 15, 0)
+    }
+
+    fn find(text: &str, locale: &str) -> Option<(i64, String)> {
+        let when = parse_at(text, locale, "UTC", now()).expect("Valid zone")?;
+        Some((when.timestamp, text.chars().skip(when.start).take(when.end - when.start).collect()))
+    }
+
+    #[parameterized(
+        hours = {"remind me in 2 hours to stretch", now() + 2 * HOUR, "in 2 hours"},
+        compact = {"in 1h30m", now() + 90 * 60, "in 1h30m"},
+        joined = {"in 2 hours and 30 minutes please", now() + 150 * 60, "in 2 hours and 30 minutes"},
+        words = {"in an hour", now() + HOUR, "in an hour"},
+        week = {"in a week", utc(2024, 3, 13, 15, 0), "in a week"},
+        month = {"in 1 month", utc(2024, 4, 6, 15, 0), "in 1 month"},
+        tomorrow_at = {"tomorrow at 9pm", utc(2024, 3, 7, 21, 0), "tomorrow at 9pm"},
+        at_tomorrow = {"at 9 p.m. tomorrow", utc(2024, 3, 7, 21, 0), "at 9 p.m. tomorrow"},
+        evening = {"at 10", utc(2024, 3, 6, 22, 0), "at 10"},
+        later_today = {"at 16:30", utc(2024, 3, 6, 16, 30), "at 16:30"},
+        passed = {"at 14:00", utc(2024, 3, 7, 14, 0), "at 14:00"},
+        weekday = {"friday", utc(2024, 3, 8, 9, 0), "friday"},
+        same_weekday = {"next wednesday at noon", utc(2024, 3, 13, 12, 0), "next wednesday at noon"},
+        date = {"on march 10 at midnight", utc(2024, 3, 10, 0, 0), "on march 10 at midnight"},
+        past_date = {"on the 5th of march", utc(2025, 3, 5, 9, 0), "on the 5th of march"},
+        year = {"jan 1st 2030", utc(2030, 1, 1, 9, 0), "jan 1st 2030"},
+        tonight = {"tonight at 8", utc(2024, 3, 6, 20, 0), "tonight at 8"},
+        day_after = {"the day after tomorrow", utc(2024, 3, 8, 9, 0), "the day after tomorrow"},
+    )]
+    fn english(text: &str, timestamp: i64, span: &str) {
+        assert_eq!(find(text, "en-US"), Some((timestamp, span.to_string())));
+    }
+
+    #[parameterized(
+        days = {"rappelle-moi dans 3 jours", utc(2024, 3, 9, 15, 0), "dans 3 jours"},
+        tomorrow = {"demain à 21h30", utc(2024, 3, 7, 21, 30), "demain à 21h30"},
+        weekday = {"lundi prochain", utc(2024, 3, 11, 9, 0), "lundi prochain"},
+        tonight = {"ce soir à 8 heures", utc(2024, 3, 6, 20, 0), "ce soir à 8 heures"},
+        date = {"le 1er avril", utc(2024, 4, 1, 9, 0), "le 1er avril"},
+        after_tomorrow = {"après-demain à midi", utc(2024, 3, 8, 12, 0), "après-demain à midi"},
+    )]
+    fn french(text: &str, timestamp: i64, span: &str) {
+        assert_eq!(find(text, "fr"), Some((timestamp, span.to_string())));
+    }
+
+    #[parameterized(
+        tomorrow = {"mañana a las 9", utc(2024, 3, 7, 9, 0), "mañana a las 9"},
+        night = {"mañana a las 9 de la noche", utc(2024, 3, 7, 21, 0), "mañana a las 9 de la noche"},
+        minutes = {"en 10 minutos", now() + 600, "en 10 minutos"},
+        weeks = {"dentro de dos semanas", utc(2024, 3, 20, 15, 0), "dentro de dos semanas"},
+        after_tomorrow = {"pasado mañana", utc(2024, 3, 8, 9, 0), "pasado mañana"},
+        weekday = {"el próximo lunes a la 1", utc(2024, 3, 11, 1, 0), "el próximo lunes a la 1"},
+        date = {"el 20 de marzo", utc(2024, 3, 20, 9, 0), "el 20 de marzo"},
+    )]
+    fn spanish(text: &str, timestamp: i64, span: &str) {
+        assert_eq!(find(text, "es-ES"), Some((timestamp, span.to_string())));
+    }
+
+    #[parameterized(
+        hours = {"herinner me over 2 uur", now() + 2 * HOUR, "over 2 uur"},
+        tomorrow = {"morgen om 9 uur", utc(2024, 3, 7, 9, 0), "morgen om 9 uur"},
+        tonight = {"vanavond om 8 uur", utc(2024, 3, 6, 20, 0), "vanavond om 8 uur"},
+        weekday = {"volgende maandag", utc(2024, 3, 11, 9, 0), "volgende maandag"},
+        evening = {"overmorgen om 7 uur 's avonds", utc(2024, 3, 8, 19, 0), "overmorgen om 7 uur 's avonds"},
+    )]
+    fn dutch(text: &str, timestamp: i64, span: &str) {
+        assert_eq!(find(text, "nl"), Some((timestamp, span.to_string())));
+    }
+
+    #[test]
+    fn nothing_found() {
+        for text in ["hello there", "meet me at the store", "in the morning", "I sat down 3 times", "on february 30", "ping 123456789"] {
+            assert_eq!(find(text, "en-US"), None, "{text}");
+        }
+        // the words are the locale's own
+        assert_eq!(find("dans 3 jours", "en-US"), None);
+        assert_eq!(find("in 3 days", "fr"), None);
+    }
+
+    #[test]
+    fn first_found() {
+        assert_eq!(find("meet in the hall in 2 hours", "en-US"), Some((now() + 2 * HOUR, "in 2 hours".to_string())));
+        assert_eq!(find("in 5 minutes or tomorrow", "en-US"), Some((now() + 300, "in 5 minutes".to_string())));
+    }
+
+    #[test]
+    fn locales() {
+        // regional locales fall back like translations do, and unknown ones to english
+        assert_eq!(find("demain", "fr-CA").map(|(time, _)| time), Some(utc(2024, 3, 7, 9, 0)));
+        assert_eq!(find("mañana", "es-AR").map(|(time, _)| time), Some(utc(2024, 3, 7, 9, 0)));
+        assert_eq!(find("tomorrow", "xx-YY").map(|(time, _)| time), Some(utc(2024, 3, 7, 9, 0)));
+        assert_eq!(find("tomorrow", "pseudo").map(|(time, _)| time), Some(utc(2024, 3, 7, 9, 0)));
+    }
+
+    #[test]
+    fn time_zones() {
+        // the clocks go forward in paris early on 2024-03-31, so that day is 23 hours long
+        let now = utc(2024, 3, 30, 12, 0);
+        let when = |text: &str| parse_at(text, "en-US", "Europe/Paris", now).expect("Valid zone").map(|when| when.timestamp);
+        assert_eq!(when("in 1 day"), Some(utc(2024, 3, 31, 11, 0)));
+        assert_eq!(when("in 24 hours"), Some(utc(2024, 3, 31, 12, 0)));
+        assert_eq!(when("tomorrow at 9am"), Some(utc(2024, 3, 31, 7, 0)));
+        assert_eq!(when("at 9pm"), Some(utc(2024, 3, 30, 20, 0)));
+    }
+
+    #[test]
+    fn months() {
+        let day = |year, month, day| tz::days_from_civil(year, month, day);
+        assert_eq!(add_months(day(2024, 1, 31), 1), Some(day(2024, 2, 29)));
+        assert_eq!(add_months(day(2023, 1, 31), 1), Some(day(2023, 2, 28)));
+        assert_eq!(add_months(day(2024, 11, 15), 3), Some(day(2025, 2, 15)));
+        assert_eq!(add_months(day(2024, 2, 29), 12), Some(day(2025, 2, 28)));
+        assert_eq!(date(2024, 2, 30), None);
+    }
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize("Tomorrow, 9:30PM à Montréal");
+        let texts = tokens.iter().map(|token| token.text.as_str()).collect::<Vec<_>>();
+        assert_eq!(texts, ["tomorrow", "9", ":", "30", "pm", "a", "montreal"]);
+        assert_eq!((tokens[6].start, tokens[6].end), (19, 27));
+    }
+
+    #[test]
+    fn rejects() {
+        parse_at("in 2 hours", "en-US", "Not/AZone", now()).expect_err("Expected an unknown zone to be rejected");
+        parse_at("in 2 hours", "en-US", "UTC", i64::MAX).expect_err("Expected the timestamp to be rejected");
+        // times past year 9999 aren't found
+        assert_eq!(parse_at("in 9000 years", "en-US", "UTC", now()), Ok(None));
+    }
+}
+// COV_EXCL_STOP
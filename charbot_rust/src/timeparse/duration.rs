@@ -0,0 +1,180 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Compact durations like `1h30m` or `2d 12h`, which `humanize_duration` writes with `compact=True`.
+use crate::fluent::duration::compact_units;
+use crate::fluent::Formats;
+
+const WEEK: u64 = 604_800;
+/// The units every locale understands, since they're what people type whatever language they speak.
+const UNITS: [(&str, u64); 5] = [("w", WEEK), ("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)];
+
+/// A duration that couldn't be read, and the index of the character where reading it went wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Error {
+    pub(super) position: usize,
+    pub(super) reason: String,
+}
+
+fn error(position: usize, reason: impl Into<String>) -> Error {
+    Error { position, reason: reason.into() }
+}
+
+/// Read `text` as a number of seconds, in the compact units of `formats` or the english ones.
+///
+/// The parts are whole numbers each followed by a unit, biggest unit first and each unit once, with or
+/// without spaces between them. Numbers can be grouped like `humanize_duration` groups them with
+/// `formats`, like `1,500d`, and without formats they can't be grouped at all.
+pub(super) fn parse(text: &str, formats: Option<Formats>, maximum: Option<u64>) -> Result<u64, Error> {
+    let mut units = UNITS.to_vec();
+    let mut group = None;
+    if let Some(formats) = formats {
+        let (names, separator) = compact_units(formats);
+        for unit in names {
+            if !units.contains(&unit) {
+                units.push(unit);
+            }
+        }
+        group = Some(separator);
+    }
+    let letters: Vec<char> = text.chars().collect();
+    let skip_spaces = |mut at: usize| {
+        while letters.get(at).is_some_and(|letter| letter.is_whitespace()) {
+            at += 1;
+        }
+        at
+    };
+    let mut at = skip_spaces(0);
+    if at == letters.len() {
+        return Err(error(at, "it is empty"));
+    }
+    let (mut total, mut smallest) = (0u64, None);
+    while at < letters.len() {
+        let mut value: u64 = 0;
+        let number_start = at;
+        while at < letters.len() {
+            let digits = letters[at + 1..].iter().take_while(|digit| digit.is_ascii_digit()).count();
+            if Some(letters[at]) == group && at > number_start && digits == 3 {
+                at += 1;
+            } else if let Some(digit) = letters[at].to_digit(10) {
+                value = value.checked_mul(10).and_then(|value| value.checked_add(u64::from(digit))).ok_or_else(|| error(number_start, "the number is too big"))?;
+                at += 1;
+            } else {
+                break;
+            }
+        }
+        if at == number_start {
+            return Err(error(at, "expected a number"));
+        }
+        let unit_start = at;
+        while letters.get(at).is_some_and(|letter| letter.is_alphabetic()) {
+            at += 1;
+        }
+        if at == unit_start {
+            return Err(error(at, "expected a unit after the number, like h or m"));
+        }
+        let name: String = letters[unit_start..at].iter().flat_map(|letter| letter.to_lowercase()).collect();
+        let Some((_, seconds)) = units.iter().find(|(unit, _)| *unit == name) else {
+            let known: Vec<&str> = units.iter().map(|(unit, _)| *unit).collect();
+            return Err(error(unit_start, format!("{name:?} isn't a unit, use one of {}", known.join(", "))));
+        };
+        if smallest.is_some_and(|smallest| *seconds >= smallest) {
+            return Err(error(unit_start, "the units must go from the biggest to the smallest, each once"));
+        }
+        smallest = Some(*seconds);
+        total = value.checked_mul(*seconds).and_then(|part| total.checked_add(part)).ok_or_else(|| error(number_start, "the duration is too long"))?;
+        at = skip_spaces(at);
+    }
+    if let Some(maximum) = maximum.filter(|maximum| total > *maximum) {
+        return Err(error(0, format!("{total} seconds is more than the most of {maximum}")));
+    }
+    Ok(total)
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fluent::duration::humanize_duration;
+    use yare::parameterized;
+
+    #[parameterized(
+        seconds = {"90s", 90},
+        compact = {"1h30m", 5_400},
+        days = {"2d12h", 216_000},
+        week = {"1w", WEEK},
+        spaced = {"  1h 30m 15s ", 5_415},
+        zero = {"0s", 0},
+        capitals = {"1H30M", 5_400},
+        grouped = {"1,500d", 129_600_000},
+        every_unit = {"1w2d3h4m5s", WEEK + 2 * 86_400 + 3 * 3_600 + 4 * 60 + 5},
+    )]
+    fn english(text: &str, expected: u64) {
+        assert_eq!(parse(text, Some(Formats::AmericanEnglish), None), Ok(expected));
+    }
+
+    #[parameterized(
+        spanish_minutes = {"2min 30s", Formats::EuropeanSpanish, 150},
+        spanish_english = {"2m30s", Formats::EuropeanSpanish, 150},
+        spanish_grouped = {"1.500d", Formats::EuropeanSpanish, 129_600_000},
+        french_days = {"2j 3h", Formats::French, 183_600},
+        french_grouped = {"1\u{202f}500j", Formats::French, 129_600_000},
+        dutch_hours = {"2u 2m", Formats::Dutch, 7_320},
+    )]
+    fn locales(text: &str, formats: Formats, expected: u64) {
+        assert_eq!(parse(text, Some(formats), None), Ok(expected));
+    }
+
+    #[parameterized(
+        empty = {"", 0, "it is empty"},
+        spaces = {"   ", 3, "it is empty"},
+        no_number = {"h", 0, "expected a number"},
+        negative = {"-5m", 0, "expected a number"},
+        fraction = {"1.5h", 1, "expected a unit after the number, like h or m"},
+        no_unit = {"1h30", 4, "expected a unit after the number, like h or m"},
+        unknown = {"1h3x", 3, "\"x\" isn't a unit, use one of w, d, h, m, s"},
+        word = {"2 hours", 1, "expected a unit after the number, like h or m"},
+        long_word = {"2hours", 1, "\"hours\" isn't a unit, use one of w, d, h, m, s"},
+        five_digits = {"1,5000d", 1, "expected a unit after the number, like h or m"},
+        order = {"30m1h", 4, "the units must go from the biggest to the smallest, each once"},
+        twice = {"1h 2h", 4, "the units must go from the biggest to the smallest, each once"},
+        bad_group = {"1,50d", 1, "expected a unit after the number, like h or m"},
+        leading_group = {",500d", 0, "expected a number"},
+        too_big = {"99999999999999999999s", 0, "the number is too big"},
+        too_long = {"99999999999999w", 0, "the duration is too long"},
+    )]
+    fn invalid(text: &str, position: usize, reason: &str) {
+        assert_eq!(parse(text, Some(Formats::AmericanEnglish), None), Err(error(position, reason)));
+    }
+
+    #[test]
+    fn locale_units() {
+        let error = parse("1x", Some(Formats::EuropeanSpanish), None).expect_err("Not a unit");
+        assert_eq!(error.reason, "\"x\" isn't a unit, use one of w, d, h, m, s, min");
+    }
+
+    #[test]
+    fn without_formats() {
+        assert_eq!(parse("1h30m", None, None), Ok(5_400));
+        assert_eq!(parse("1,500d", None, None), Err(error(1, "expected a unit after the number, like h or m")));
+    }
+
+    #[test]
+    fn maximum() {
+        assert_eq!(parse("1d", Some(Formats::AmericanEnglish), Some(86_400)), Ok(86_400));
+        assert_eq!(parse("1d1s", Some(Formats::AmericanEnglish), Some(86_400)), Err(error(0, "86401 seconds is more than the most of 86400")));
+    }
+
+    #[parameterized(
+        english = {Formats::AmericanEnglish},
+        spanish = {Formats::EuropeanSpanish},
+        french = {Formats::French},
+        dutch = {Formats::Dutch},
+    )]
+    fn inverse_of_humanize(formats: Formats) {
+        for seconds in [0, 1, 59, 61, 3_600, 5_415, 90_061, 86_400 * 1_500 + 7] {
+            assert_eq!(parse(&humanize_duration(formats, seconds, 4, true), Some(formats), None), Ok(seconds));
+        }
+    }
+}
+// COV_EXCL_STOP
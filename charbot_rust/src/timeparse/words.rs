@@ -0,0 +1,290 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! The words each locale writes times with, as the lowercase tokens they're split into, without accents.
+use crate::fluent::bundle::AvailableLocales;
+
+/// One or more words in a row, like `["day", "after", "tomorrow"]`.
+pub type Phrase = &'static [&'static str];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)] // COV_EXCL_LINE
+pub enum Unit {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+pub struct Words {
+    /// Before a duration from now, like "in".
+    pub relative: &'static [Phrase],
+    /// Between the parts of a duration, like "and".
+    pub and: &'static [Phrase],
+    pub units: &'static [(&'static str, Unit)],
+    pub numbers: &'static [(&'static str, u32)],
+    pub today: &'static [Phrase],
+    pub tomorrow: &'static [Phrase],
+    pub after_tomorrow: &'static [Phrase],
+    pub tonight: &'static [Phrase],
+    /// Before a day, like "on".
+    pub on: &'static [Phrase],
+    /// Before a weekday, like "next".
+    pub next: &'static [Phrase],
+    /// After a weekday, like "prochain".
+    pub next_after: &'static [Phrase],
+    /// The names of the days of the week, from sunday.
+    pub weekdays: [&'static [&'static str]; 7],
+    /// The names of the months, from january.
+    pub months: [&'static [&'static str]; 12],
+    /// Between a day of the month and the month, like "of".
+    pub of: &'static [Phrase],
+    /// After a day of the month, like the "th" of "5th".
+    pub ordinals: &'static [&'static str],
+    /// Before a time, like "at".
+    pub at: &'static [Phrase],
+    /// After an hour, like "o'clock".
+    pub oclock: &'static [Phrase],
+    pub am: &'static [Phrase],
+    pub pm: &'static [Phrase],
+    pub noon: &'static [Phrase],
+    pub midnight: &'static [Phrase],
+}
+
+static ENGLISH: Words = Words {
+    relative: &[&["in"]],
+    and: &[&["and"]],
+    units: &[
+        ("s", Unit::Second), ("sec", Unit::Second), ("secs", Unit::Second), ("second", Unit::Second), ("seconds", Unit::Second),
+        ("m", Unit::Minute), ("min", Unit::Minute), ("mins", Unit::Minute), ("minute", Unit::Minute), ("minutes", Unit::Minute),
+        ("h", Unit::Hour), ("hr", Unit::Hour), ("hrs", Unit::Hour), ("hour", Unit::Hour), ("hours", Unit::Hour),
+        ("d", Unit::Day), ("day", Unit::Day), ("days", Unit::Day),
+        ("w", Unit::Week), ("wk", Unit::Week), ("wks", Unit::Week), ("week", Unit::Week), ("weeks", Unit::Week),
+        ("mo", Unit::Month), ("month", Unit::Month), ("months", Unit::Month),
+        ("y", Unit::Year), ("yr", Unit::Year), ("yrs", Unit::Year), ("year", Unit::Year), ("years", Unit::Year),
+    ],
+    numbers: &[
+        ("a", 1), ("an", 1), ("one", 1), ("two", 2), ("three", 3), ("four", 4), ("five", 5), ("six", 6),
+        ("seven", 7), ("eight", 8), ("nine", 9), ("ten", 10), ("eleven", 11), ("twelve", 12),
+    ],
+    today: &[&["today"]],
+    tomorrow: &[&["tomorrow"], &["tmr"], &["tmrw"]],
+    after_tomorrow: &[&["day", "after", "tomorrow"]],
+    tonight: &[&["tonight"]],
+    on: &[&["on", "the"], &["on"], &["the"]],
+    next: &[&["next"], &["this"]],
+    next_after: &[],
+    // the short names that are also words, like "sat", are left out so they aren't taken for days
+    weekdays: [
+        &["sunday"],
+        &["monday"],
+        &["tuesday", "tue", "tues"],
+        &["wednesday", "wed"],
+        &["thursday", "thu", "thurs"],
+        &["friday", "fri"],
+        &["saturday"],
+    ],
+    months: [
+        &["january", "jan"],
+        &["february", "feb"],
+        &["march", "mar"],
+        &["april", "apr"],
+        &["may"],
+        &["june", "jun"],
+        &["july", "jul"],
+        &["august", "aug"],
+        &["september", "sep", "sept"],
+        &["october", "oct"],
+        &["november", "nov"],
+        &["december", "dec"],
+    ],
+    of: &[&["of"]],
+    ordinals: &["st", "nd", "rd", "th"],
+    at: &[&["at"]],
+    oclock: &[&["o", "clock"]],
+    am: &[&["am"], &["a", "m"]],
+    pm: &[&["pm"], &["p", "m"]],
+    noon: &[&["noon"], &["midday"]],
+    midnight: &[&["midnight"]],
+};
+
+static SPANISH: Words = Words {
+    relative: &[&["en"], &["dentro", "de"]],
+    and: &[&["y"]],
+    units: &[
+        ("s", Unit::Second), ("seg", Unit::Second), ("segundo", Unit::Second), ("segundos", Unit::Second),
+        ("m", Unit::Minute), ("min", Unit::Minute), ("minuto", Unit::Minute), ("minutos", Unit::Minute),
+        ("h", Unit::Hour), ("hora", Unit::Hour), ("horas", Unit::Hour),
+        ("d", Unit::Day), ("dia", Unit::Day), ("dias", Unit::Day),
+        ("semana", Unit::Week), ("semanas", Unit::Week),
+        ("mes", Unit::Month), ("meses", Unit::Month),
+        ("año", Unit::Year), ("años", Unit::Year),
+    ],
+    numbers: &[
+        ("un", 1), ("una", 1), ("uno", 1), ("dos", 2), ("tres", 3), ("cuatro", 4), ("cinco", 5), ("seis", 6),
+        ("siete", 7), ("ocho", 8), ("nueve", 9), ("diez", 10), ("once", 11), ("doce", 12),
+    ],
+    today: &[&["hoy"]],
+    tomorrow: &[&["mañana"]],
+    after_tomorrow: &[&["pasado", "mañana"]],
+    tonight: &[&["esta", "noche"]],
+    on: &[&["el"]],
+    next: &[&["proximo"], &["este"]],
+    next_after: &[&["que", "viene"]],
+    weekdays: [
+        &["domingo"],
+        &["lunes"],
+        &["martes"],
+        &["miercoles"],
+        &["jueves"],
+        &["viernes"],
+        &["sabado"],
+    ],
+    months: [
+        &["enero"],
+        &["febrero"],
+        &["marzo"],
+        &["abril"],
+        &["mayo"],
+        &["junio"],
+        &["julio"],
+        &["agosto"],
+        &["septiembre", "setiembre"],
+        &["octubre"],
+        &["noviembre"],
+        &["diciembre"],
+    ],
+    of: &[&["de"]],
+    ordinals: &[],
+    at: &[&["a", "las"], &["a", "la"]],
+    oclock: &[&["en", "punto"]],
+    am: &[&["de", "la", "mañana"]],
+    pm: &[&["de", "la", "tarde"], &["de", "la", "noche"]],
+    noon: &[&["mediodia"]],
+    midnight: &[&["medianoche"]],
+};
+
+static FRENCH: Words = Words {
+    relative: &[&["dans"], &["d", "ici"]],
+    and: &[&["et"]],
+    units: &[
+        ("s", Unit::Second), ("sec", Unit::Second), ("seconde", Unit::Second), ("secondes", Unit::Second),
+        ("min", Unit::Minute), ("minute", Unit::Minute), ("minutes", Unit::Minute),
+        ("h", Unit::Hour), ("heure", Unit::Hour), ("heures", Unit::Hour),
+        ("j", Unit::Day), ("jour", Unit::Day), ("jours", Unit::Day),
+        ("semaine", Unit::Week), ("semaines", Unit::Week),
+        ("mois", Unit::Month),
+        ("an", Unit::Year), ("ans", Unit::Year), ("annee", Unit::Year), ("annees", Unit::Year),
+    ],
+    numbers: &[
+        ("un", 1), ("une", 1), ("deux", 2), ("trois", 3), ("quatre", 4), ("cinq", 5), ("six", 6),
+        ("sept", 7), ("huit", 8), ("neuf", 9), ("dix", 10), ("onze", 11), ("douze", 12),
+    ],
+    today: &[&["aujourd", "hui"]],
+    tomorrow: &[&["demain"]],
+    after_tomorrow: &[&["apres", "demain"]],
+    tonight: &[&["ce", "soir"]],
+    on: &[&["le"]],
+    next: &[&["ce"]],
+    next_after: &[&["prochain"]],
+    weekdays: [
+        &["dimanche"],
+        &["lundi"],
+        &["mardi"],
+        &["mercredi"],
+        &["jeudi"],
+        &["vendredi"],
+        &["samedi"],
+    ],
+    months: [
+        &["janvier"],
+        &["fevrier"],
+        &["mars"],
+        &["avril"],
+        &["mai"],
+        &["juin"],
+        &["juillet"],
+        &["aout"],
+        &["septembre"],
+        &["octobre"],
+        &["novembre"],
+        &["decembre"],
+    ],
+    of: &[],
+    ordinals: &["er", "e"],
+    at: &[&["a"], &["vers"]],
+    oclock: &[&["h"], &["heure"], &["heures"]],
+    am: &[&["du", "matin"]],
+    pm: &[&["de", "l", "apres", "midi"], &["du", "soir"]],
+    noon: &[&["midi"]],
+    midnight: &[&["minuit"]],
+};
+
+static DUTCH: Words = Words {
+    relative: &[&["over"], &["binnen"]],
+    and: &[&["en"]],
+    units: &[
+        ("s", Unit::Second), ("sec", Unit::Second), ("seconde", Unit::Second), ("seconden", Unit::Second),
+        ("m", Unit::Minute), ("min", Unit::Minute), ("minuut", Unit::Minute), ("minuten", Unit::Minute),
+        ("u", Unit::Hour), ("uur", Unit::Hour), ("uren", Unit::Hour),
+        ("d", Unit::Day), ("dag", Unit::Day), ("dagen", Unit::Day),
+        ("w", Unit::Week), ("week", Unit::Week), ("weken", Unit::Week),
+        ("maand", Unit::Month), ("maanden", Unit::Month),
+        ("j", Unit::Year), ("jaar", Unit::Year), ("jaren", Unit::Year),
+    ],
+    numbers: &[
+        ("een", 1), ("twee", 2), ("drie", 3), ("vier", 4), ("vijf", 5), ("zes", 6),
+        ("zeven", 7), ("acht", 8), ("negen", 9), ("tien", 10), ("elf", 11), ("twaalf", 12),
+    ],
+    today: &[&["vandaag"]],
+    tomorrow: &[&["morgen"]],
+    after_tomorrow: &[&["overmorgen"]],
+    tonight: &[&["vanavond"]],
+    on: &[&["op"]],
+    next: &[&["volgende"], &["aanstaande"], &["komende"]],
+    next_after: &[],
+    weekdays: [
+        &["zondag"],
+        &["maandag"],
+        &["dinsdag"],
+        &["woensdag"],
+        &["donderdag"],
+        &["vrijdag"],
+        &["zaterdag"],
+    ],
+    months: [
+        &["januari"],
+        &["februari"],
+        &["maart"],
+        &["april"],
+        &["mei"],
+        &["juni"],
+        &["juli"],
+        &["augustus"],
+        &["september"],
+        &["oktober"],
+        &["november"],
+        &["december"],
+    ],
+    of: &[],
+    ordinals: &["e", "ste", "de"],
+    at: &[&["om"]],
+    oclock: &[&["uur"], &["u"]],
+    am: &[&["s", "ochtends"], &["s", "morgens"]],
+    pm: &[&["s", "middags"], &["s", "avonds"]],
+    noon: &[],
+    midnight: &[&["middernacht"]],
+};
+
+/// The words of `locale`, runtime locales have none of their own and the pseudo locale is english underneath.
+pub fn words(locale: AvailableLocales) -> Option<&'static Words> {
+    match locale {
+        AvailableLocales::AmericanEnglish | AvailableLocales::Pseudo => Some(&ENGLISH),
+        AvailableLocales::EuropeanSpanish => Some(&SPANISH),
+        AvailableLocales::French => Some(&FRENCH),
+        AvailableLocales::Dutch => Some(&DUTCH),
+        AvailableLocales::Custom(_) => None,
+    }
+}
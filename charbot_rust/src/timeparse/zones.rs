@@ -0,0 +1,316 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Finding the zone in the tz database someone means, from what they'd type, like `amsterdam` or `EST`.
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use crate::cooldowns::{self, tz};
+use crate::shrugman::fold;
+use crate::trivia::similarity;
+
+/// How alike a query has to be to a place for the place to be found.
+const MIN_SCORE: f64 = 0.75;
+/// Countries count for a little less than cities, so a city named like a country is found first.
+const COUNTRY_WEIGHT: f64 = 0.95;
+/// The furthest from UTC any place's clocks are, in seconds.
+const OFFSETS: std::ops::RangeInclusive<i64> = -12 * 3600..=14 * 3600;
+
+/// What people call the zones they live in, and the zone most of them mean.
+const ALIASES: [(&str, &str); 57] = [
+    ("utc", "UTC"),
+    ("gmt", "UTC"),
+    ("z", "UTC"),
+    ("zulu", "UTC"),
+    ("universal", "UTC"),
+    ("est", "America/New_York"),
+    ("edt", "America/New_York"),
+    ("eastern", "America/New_York"),
+    ("cst", "America/Chicago"),
+    ("cdt", "America/Chicago"),
+    ("central", "America/Chicago"),
+    ("mst", "America/Denver"),
+    ("mdt", "America/Denver"),
+    ("mountain", "America/Denver"),
+    ("pst", "America/Los_Angeles"),
+    ("pdt", "America/Los_Angeles"),
+    ("pacific", "America/Los_Angeles"),
+    ("akst", "America/Anchorage"),
+    ("akdt", "America/Anchorage"),
+    ("alaska", "America/Anchorage"),
+    ("hst", "Pacific/Honolulu"),
+    ("hawaii", "Pacific/Honolulu"),
+    ("ast", "America/Halifax"),
+    ("adt", "America/Halifax"),
+    ("atlantic", "America/Halifax"),
+    ("nst", "America/St_Johns"),
+    ("ndt", "America/St_Johns"),
+    ("newfoundland", "America/St_Johns"),
+    ("bst", "Europe/London"),
+    ("uk", "Europe/London"),
+    ("wet", "Europe/Lisbon"),
+    ("cet", "Europe/Paris"),
+    ("cest", "Europe/Paris"),
+    ("eet", "Europe/Athens"),
+    ("eest", "Europe/Athens"),
+    ("msk", "Europe/Moscow"),
+    ("ist", "Asia/Kolkata"),
+    ("pkt", "Asia/Karachi"),
+    ("wib", "Asia/Jakarta"),
+    ("sgt", "Asia/Singapore"),
+    ("hkt", "Asia/Hong_Kong"),
+    ("pht", "Asia/Manila"),
+    ("jst", "Asia/Tokyo"),
+    ("kst", "Asia/Seoul"),
+    ("awst", "Australia/Perth"),
+    ("acst", "Australia/Adelaide"),
+    ("acdt", "Australia/Adelaide"),
+    ("aest", "Australia/Sydney"),
+    ("aedt", "Australia/Sydney"),
+    ("nzst", "Pacific/Auckland"),
+    ("nzdt", "Pacific/Auckland"),
+    ("brt", "America/Sao_Paulo"),
+    ("art", "America/Argentina/Buenos_Aires"),
+    ("wat", "Africa/Lagos"),
+    ("cat", "Africa/Maputo"),
+    ("eat", "Africa/Nairobi"),
+    ("sast", "Africa/Johannesburg"),
+];
+
+/// A zone from `zone.tab`, and the names it can be found by.
+#[derive(Debug)]
+struct Place {
+    name: String,
+    /// Normalized names, with how much a match on each counts for.
+    keys: Vec<(String, f64)>,
+}
+
+impl Place {
+    fn score(&self, words: &str) -> f64 {
+        self.keys.iter().map(|(key, weight)| weight * closeness(words, key)).fold(0.0, f64::max)
+    }
+}
+
+// read the first time a zone is looked for, in the order of zone.tab so ties go to the same place every time
+static PLACES: Lazy<Result<Vec<Place>, String>> = Lazy::new(|| Ok(places(tz::table("zone.tab")?, tz::table("iso3166.tab")?)));
+
+/// The places, or why the tables of the tz database they're from couldn't be read.
+fn all_places() -> Result<&'static [Place], String> {
+    PLACES.as_deref().map_err(Clone::clone)
+}
+
+/// The rows of a tab separated table of the tz database, without its comments.
+fn rows(table: &str) -> impl Iterator<Item = Vec<&str>> {
+    table.lines().filter(|line| !line.starts_with('#')).map(|line| line.split('\t').collect())
+}
+
+/// The places in `zones`, a `zone.tab`, named by their city and by their country from `countries`, an
+/// `iso3166.tab`, if it is the only zone the country has.
+fn places(zones: &str, countries: &str) -> Vec<Place> {
+    let countries: HashMap<&str, &str> = rows(countries).filter(|row| row.len() >= 2).map(|row| (row[0], row[1])).collect();
+    let zones: Vec<Vec<&str>> = rows(zones).filter(|row| row.len() >= 3).collect();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for row in &zones {
+        *counts.entry(row[0]).or_default() += 1;
+    }
+    zones
+        .iter()
+        .map(|row| {
+            let name = row[2];
+            let city = name.rsplit('/').next().unwrap_or(name);
+            let mut keys = vec![(normalize(name), 1.0), (normalize(city), 1.0)];
+            if let Some(country) = countries.get(row[0]).filter(|_| counts[row[0]] == 1) {
+                keys.push((normalize(country), COUNTRY_WEIGHT));
+            }
+            Place { name: name.to_string(), keys }
+        })
+        .collect()
+}
+
+/// Lowercase words without accents, with anything between them like `_` or `/` as one space.
+fn normalize(text: &str) -> String {
+    let letters: String = text.chars().flat_map(char::to_lowercase).map(fold).map(|letter| if letter.is_alphanumeric() { letter } else { ' ' }).collect();
+    letters.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// How well `words` matches `key`, from 0 to 1 for the same.
+///
+/// The start of a key counts for nearly as much, so `amst` is Amsterdam, and anything else by how few
+/// letters are different, so typos like `amsterdm` are forgiven.
+fn closeness(words: &str, key: &str) -> f64 {
+    if words == key {
+        return 1.0;
+    }
+    let (length, key_length) = (words.chars().count(), key.chars().count());
+    if length >= 3 && key.starts_with(words) {
+        return 0.8 + 0.2 * length as f64 / key_length as f64;
+    }
+    similarity(words, key)
+}
+
+/// The offset from UTC in seconds of a fixed offset like `gmt+5:30`, `UTC-3` or `+0100`.
+fn fixed_offset(query: &str) -> Option<i64> {
+    let compact: String = query.chars().filter(|letter| !letter.is_whitespace()).flat_map(char::to_lowercase).collect();
+    let rest = compact.strip_prefix("utc").or_else(|| compact.strip_prefix("gmt")).unwrap_or(&compact);
+    let (sign, rest) = match rest.chars().next()? {
+        '+' => (1, &rest[1..]),
+        '-' => (-1, &rest[1..]),
+        _ => return None,
+    };
+    if !rest.chars().all(|letter| letter.is_ascii_digit() || letter == ':') {
+        return None;
+    }
+    let (hours, minutes) = match rest.split_once(':') {
+        Some((hours, minutes)) if minutes.len() == 2 => (hours, minutes),
+        Some(_) => return None,
+        None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+        None => (rest, "0"),
+    };
+    if hours.is_empty() || hours.len() > 2 {
+        return None;
+    }
+    let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+    if minutes >= 60 {
+        return None;
+    }
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+/// A zone that is `offset` from UTC at `now`.
+fn offset_zone(offset: i64, now: i64) -> Result<Option<String>, String> {
+    if !OFFSETS.contains(&offset) {
+        return Ok(None);
+    }
+    if offset == 0 {
+        return Ok(Some("UTC".to_string()));
+    }
+    if offset % 3600 == 0 {
+        // the Etc zones are named the way POSIX writes offsets, with the sign for west of greenwich
+        return Ok(Some(format!("Etc/GMT{:+}", -offset / 3600)));
+    }
+    // there aren't Etc zones for the offsets between hours, but there are places that use them
+    let place = all_places()?.iter().find(|place| cooldowns::zone(&place.name).is_ok_and(|zone| zone.offset(now) == offset));
+    Ok(place.map(|place| place.name.clone()))
+}
+
+/// The name in the tz database of the zone `query` means.
+fn lookup(query: &str, now: i64) -> Result<Option<String>, String> {
+    let query = query.trim();
+    if let Some(offset) = fixed_offset(query) {
+        return offset_zone(offset, now);
+    }
+    let words = normalize(query);
+    if words.is_empty() {
+        return Ok(None);
+    }
+    if let Some((_, name)) = ALIASES.iter().find(|(alias, _)| *alias == words) {
+        return Ok(Some(name.to_string()));
+    }
+    // names that aren't in zone.tab, like the links for old names such as Asia/Calcutta
+    if query.contains('/') && cooldowns::zone(query).is_ok() {
+        return Ok(Some(query.to_string()));
+    }
+    let mut best: Option<(f64, &Place)> = None;
+    for place in all_places()? {
+        let score = place.score(&words);
+        if score >= MIN_SCORE && best.map_or(true, |(best, _)| score > best) {
+            best = Some((score, place));
+        }
+    }
+    Ok(best.map(|(_, place)| place.name.clone()))
+}
+
+/// The zone `query` means and its offset from UTC in seconds at `now`, if there is one.
+pub(super) fn find(query: &str, now: i64) -> Result<Option<(String, i64)>, String> {
+    let Some(name) = lookup(query, now)? else {
+        return Ok(None);
+    };
+    // the aliases and the places are all zones, so one that doesn't load is missing from the embedded tz database
+    let zone = cooldowns::zone(&name)?;
+    Ok(Some((name, zone.offset(now))))
+}
+
+// COV_EXCL_START
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    /// 2024-01-15 at midnight UTC, in winter in the north.
+    const JANUARY: i64 = 1_705_276_800;
+    /// 2024-07-15 at midnight UTC, in summer in the north.
+    const JULY: i64 = 1_721_001_600;
+
+    #[parameterized(
+        city = {"amsterdam", "Europe/Amsterdam", 3_600},
+        capitals = {"Amsterdam", "Europe/Amsterdam", 3_600},
+        typo = {"amsterdm", "Europe/Amsterdam", 3_600},
+        start = {"amst", "Europe/Amsterdam", 3_600},
+        spaces = {"new york", "America/New_York", -18_000},
+        underscore = {"New_York", "America/New_York", -18_000},
+        no_space = {"newyork", "America/New_York", -18_000},
+        full_name = {"europe/paris", "Europe/Paris", 3_600},
+        accents = {"são paulo", "America/Sao_Paulo", -10_800},
+        country = {"japan", "Asia/Tokyo", 32_400},
+        abbreviation = {"EST", "America/New_York", -18_000},
+        region = {"pacific", "America/Los_Angeles", -28_800},
+        india = {"IST", "Asia/Kolkata", 19_800},
+        gmt = {"gmt", "UTC", 0},
+        half_hour = {"gmt+5:30", "Asia/Kolkata", 19_800},
+        whole_hours = {"UTC+2", "Etc/GMT-2", 7_200},
+        west = {"-0300", "Etc/GMT+3", -10_800},
+        spaced_offset = {"GMT - 5", "Etc/GMT+5", -18_000},
+        zero_offset = {"utc+0", "UTC", 0},
+        old_name = {"Asia/Calcutta", "Asia/Calcutta", 19_800},
+    )]
+    fn found(query: &str, name: &str, offset: i64) {
+        assert_eq!(find(query, JANUARY), Ok(Some((name.to_string(), offset))));
+    }
+
+    #[test]
+    fn offset_now() {
+        assert_eq!(find("amsterdam", JULY), Ok(Some((String::from("Europe/Amsterdam"), 7_200))));
+        assert_eq!(find("pacific", JULY), Ok(Some((String::from("America/Los_Angeles"), -25_200))));
+    }
+
+    #[parameterized(
+        empty = {""},
+        spaces = {"   "},
+        punctuation = {"?!"},
+        gibberish = {"qwertyuiop"},
+        too_far = {"gmt+15"},
+        bad_minutes = {"+5:75"},
+        not_a_zone = {"Not/AZone"},
+    )]
+    fn not_found(query: &str) {
+        assert_eq!(find(query, JANUARY), Ok(None));
+    }
+
+    #[parameterized(
+        hours = {"+5", Some(18_000)},
+        minutes = {"+5:30", Some(19_800)},
+        digits = {"-0330", Some(-12_600)},
+        three_digits = {"+530", Some(19_800)},
+        prefixed = {"UTC-8", Some(-28_800)},
+        no_sign = {"5", None},
+        no_hours = {"+:30", None},
+        short_minutes = {"+5:3", None},
+        long_hours = {"+12345", None},
+        words = {"gmt+five", None},
+        bare = {"gmt", None},
+    )]
+    fn offsets(query: &str, expected: Option<i64>) {
+        assert_eq!(fixed_offset(query), expected);
+    }
+
+    #[test]
+    fn countries_with_one_zone() {
+        let zones = "#code\tcoordinates\tTZ\tcomments\nNL\t+5222+00454\tEurope/Amsterdam\nUS\t+404251-0740023\tAmerica/New_York\nUS\t+415100-0873900\tAmerica/Chicago\n";
+        let countries = "#code\tname\nNL\tNetherlands\nUS\tUnited States\n";
+        let places = places(zones, countries);
+        assert_eq!(places.iter().map(|place| place.name.as_str()).collect::<Vec<_>>(), ["Europe/Amsterdam", "America/New_York", "America/Chicago"]);
+        assert_eq!(places[0].keys, [(String::from("europe amsterdam"), 1.0), (String::from("amsterdam"), 1.0), (String::from("netherlands"), COUNTRY_WEIGHT)]);
+        assert_eq!(places[1].keys.len(), 2);
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,291 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::metrics;
+
+/// Words left out of answers, in the languages the bot is translated to, so "The Eiffel Tower" is "eiffel tower".
+const ARTICLES: [&str; 17] = [
+    "the", "a", "an", // english
+    "el", "la", "los", "las", "un", "una", // spanish
+    "le", "les", "une", "l", // french, which shares some with spanish
+    "de", "het", "een", "d", // dutch, and the french de and d'
+];
+
+/// The letters `letter` is compared as, without accents, with ligatures spelled out and full width letters as ascii.
+///
+/// Unlike shrugman, which keeps letters like `ñ` apart since they're separate letters to guess, an
+/// answer is matched however it was typed.
+fn fold(letter: char) -> &'static str {
+    match letter {
+        'á' | 'à' | 'â' | 'ä' | 'ã' | 'å' | 'ā' => "a",
+        'é' | 'è' | 'ê' | 'ë' | 'ē' | 'ę' => "e",
+        'í' | 'ì' | 'î' | 'ï' | 'ī' => "i",
+        'ó' | 'ò' | 'ô' | 'ö' | 'õ' | 'ø' | 'ō' => "o",
+        'ú' | 'ù' | 'û' | 'ü' | 'ū' => "u",
+        'ý' | 'ÿ' => "y",
+        'ç' | 'ć' | 'č' => "c",
+        'ñ' | 'ń' => "n",
+        'š' | 'ś' => "s",
+        'ž' | 'ź' | 'ż' => "z",
+        'ł' => "l",
+        'ß' => "ss",
+        'æ' => "ae",
+        'œ' => "oe",
+        '&' => " and ",
+        _ => "",
+    }
+}
+
+/// The words of `text` as they are compared: lowercase, folded, without punctuation or articles.
+///
+/// Combining accents are dropped, so an accent typed as its own character matches the letter with it.
+/// Apostrophes are dropped without splitting the word, apart from the french l' and d', and other
+/// punctuation splits words. The articles are only left in if the answer is nothing but articles,
+/// like the band The The.
+fn normalize(text: &str) -> Vec<String> {
+    let mut folded = String::new();
+    for letter in text.chars().flat_map(char::to_lowercase) {
+        match letter {
+            // combining diacritical marks
+            '\u{300}'..='\u{36f}' => {}
+            // the french l' and d' are words of their own, other apostrophes are part of the word
+            '\'' | '\u{2019}' => {
+                if folded.rsplit(' ').next().is_some_and(|word| word == "l" || word == "d") {
+                    folded.push(' ');
+                }
+            }
+            // full width forms, from full width phones and keyboards
+            '\u{ff01}'..='\u{ff5e}' => folded.extend(char::from_u32(letter as u32 - 0xfee0)),
+            _ if !fold(letter).is_empty() => folded.push_str(fold(letter)),
+            _ if letter.is_alphanumeric() => folded.push(letter),
+            _ => folded.push(' '),
+        }
+    }
+    let words = folded.split(|letter: char| !letter.is_alphanumeric()).filter(|word| !word.is_empty()).map(str::to_string).collect::<Vec<_>>();
+    let kept = words.iter().filter(|word| !ARTICLES.contains(&word.as_str())).cloned().collect::<Vec<_>>();
+    if kept.is_empty() {
+        words
+    } else {
+        kept
+    }
+}
+
+/// How many letters have to be added, removed or changed to turn `first` into `second`.
+fn distance(first: &[char], second: &[char]) -> usize {
+    let mut previous = (0..=second.len()).collect::<Vec<_>>();
+    for (row, a) in first.iter().enumerate() {
+        let mut current = vec![row + 1];
+        for (column, b) in second.iter().enumerate() {
+            let substitution = previous[column] + usize::from(a != b);
+            current.push(substitution.min(previous[column + 1] + 1).min(current[column] + 1));
+        }
+        previous = current;
+    }
+    previous[second.len()]
+}
+
+/// How alike two words are, from 0 for nothing in common to 1 for the same.
+pub(crate) fn similarity(first: &str, second: &str) -> f64 {
+    let (first, second) = (first.chars().collect::<Vec<_>>(), second.chars().collect::<Vec<_>>());
+    let longest = first.len().max(second.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - distance(&first, &second) as f64 / longest as f64
+}
+
+/// How much of `words` is covered by `other`, each word matched to its closest in `other` and weighed by its length.
+fn coverage(words: &[String], other: &[String]) -> f64 {
+    let total = words.iter().map(|word| word.chars().count()).sum::<usize>() as f64;
+    let covered = words
+        .iter()
+        .map(|word| {
+            let best = other.iter().map(|candidate| similarity(word, candidate)).fold(0.0, f64::max);
+            word.chars().count() as f64 * best
+        })
+        .sum::<f64>();
+    covered / total
+}
+
+/// How well `given` matches `answer`, from 0 to 1, after both are normalized.
+///
+/// Words are matched to each other, so the order they're in doesn't matter and a typo only costs the
+/// word it's in, and the score is how much of each the other covers, both ways, so leaving words out
+/// or adding extra costs as much. Answers are also compared with the spaces left out, so "eiffeltower"
+/// matches too.
+fn score(given: &str, answer: &str) -> f64 {
+    let (given, answer) = (normalize(given), normalize(answer));
+    if given.is_empty() || answer.is_empty() {
+        return if given.is_empty() && answer.is_empty() { 1.0 } else { 0.0 };
+    }
+    let (precision, recall) = (coverage(&given, &answer), coverage(&answer, &given));
+    let words = if precision + recall == 0.0 { 0.0 } else { 2.0 * precision * recall / (precision + recall) };
+    words.max(similarity(&given.concat(), &answer.concat()))
+}
+
+// COV_EXCL_START
+#[pyclass(module = "trivia")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Match {
+    /// The accepted answer closest to what was given.
+    #[pyo3(get)]
+    answer: String,
+    #[pyo3(get)]
+    score: f64,
+    /// Whether the score reaches the threshold.
+    #[pyo3(get)]
+    is_correct: bool,
+}
+// COV_EXCL_STOP
+
+#[pymethods] // COV_EXCL_LINE
+impl Match {
+    fn __repr__(&self) -> String {
+        format!("Match(answer={:?}, score={:.3}, is_correct={})", self.answer, self.score, if self.is_correct { "True" } else { "False" })
+    }
+}
+
+fn best_match(given: &str, accepted: &[String], threshold: f64) -> Result<Match, String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!("Invalid threshold {threshold}, it must be from 0 to 1"));
+    }
+    let (answer, score) = accepted
+        .iter()
+        .map(|answer| (answer, score(given, answer)))
+        // the first answer wins a tie, so the main answer can be listed first
+        .fold(None, |best: Option<(&String, f64)>, (answer, score)| match best {
+            Some((_, best_score)) if best_score >= score => best,
+            _ => Some((answer, score)),
+        })
+        .ok_or_else(|| "There has to be at least one accepted answer".to_string())?;
+    Ok(Match { answer: answer.clone(), score, is_correct: score >= threshold })
+}
+
+#[pyfunction]
+#[pyo3(signature = (given, accepted, threshold=0.8), text_signature = "
+match_answer(given, accepted, threshold=0.8, /)
+--
+
+Score an answer to a trivia question against the answers accepted for it.
+
+Both are compared lowercase and without accents, punctuation or articles, and then word by word
+allowing for typos, so 'the eifel tower' matches 'Eiffel Tower'.
+
+Parameters
+----------
+given : str
+    The answer that was given.
+accepted : list[str]
+    The answers accepted for the question, the first one wins a tie.
+threshold : float
+    The score from 0 to 1 an answer needs to count as correct. The default is 0.8.
+
+Returns
+-------
+Match
+    The accepted answer closest to the one given, its score, and whether that reaches the threshold.
+
+Raises
+------
+ValueError
+    If there are no accepted answers or the threshold isn't from 0 to 1.
+")]
+fn match_answer(given: &str, accepted: Vec<String>, threshold: f64) -> PyResult<Match> {
+    metrics::observe("trivia.match_answer", || best_match(given, &accepted, threshold).map_err(PyErr::new::<PyValueError, _>))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based fuzzy matching of trivia answers";
+
+pub(crate) fn register_trivia(py: Python, m: &PyModule) -> PyResult<()> {
+    let trivia = PyModule::new(py, "_trivia")?;
+    trivia.add_class::<Match>()?;
+    trivia.add_function(wrap_pyfunction!(match_answer, trivia)?)?;
+    trivia.add("__doc__", DOCSTRING)?;
+    m.add_submodule(trivia)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn accepted(answers: &[&str]) -> Vec<String> {
+        answers.iter().map(ToString::to_string).collect()
+    }
+
+    #[parameterized(
+        articles = {"The Eiffel Tower", vec!["eiffel", "tower"]},
+        punctuation = {"Eiffel-Tower!", vec!["eiffel", "tower"]},
+        apostrophes = {"Ender's Game", vec!["enders", "game"]},
+        accents = {"Pokémon", vec!["pokemon"]},
+        combining = {"Poke\u{301}mon", vec!["pokemon"]},
+        elision = {"L'Arc de Triomphe", vec!["arc", "triomphe"]},
+        ligatures = {"Œuvre straße", vec!["oeuvre", "strasse"]},
+        full_width = {"ＡＢＣ", vec!["abc"]},
+        ampersand = {"Simon & Garfunkel", vec!["simon", "and", "garfunkel"]},
+        spanish = {"La Casa de Papel", vec!["casa", "papel"]},
+        only_articles = {"The The", vec!["the", "the"]},
+        empty = {" ?! ", vec![]},
+    )]
+    fn normalizes(text: &str, words: Vec<&str>) {
+        assert_eq!(normalize(text), words);
+    }
+
+    #[test]
+    fn distances() {
+        let chars = |text: &str| text.chars().collect::<Vec<_>>();
+        assert_eq!(distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(distance(&chars("same"), &chars("same")), 0);
+    }
+
+    #[parameterized(
+        exact = {"The Eiffel Tower", "eiffel tower", 1.0},
+        reordered = {"tower eiffel", "Eiffel Tower", 1.0},
+        joined = {"eiffeltower", "Eiffel Tower", 1.0},
+        unrelated = {"xyz", "abc", 0.0},
+        nothing = {"", "Eiffel Tower", 0.0},
+    )]
+    fn scores(given: &str, answer: &str, expected: f64) {
+        assert!((score(given, answer) - expected).abs() < 1e-9, "Expected {expected}, got {}", score(given, answer));
+    }
+
+    #[test]
+    fn partial_credit() {
+        let typo = score("eifel tower", "Eiffel Tower");
+        let half = score("tower", "Eiffel Tower");
+        assert!(typo > 0.9 && typo < 1.0, "Got {typo}");
+        // half the answer is partly right, but not enough to count at the default threshold
+        assert!(half > 0.5 && half < 0.8, "Got {half}");
+        assert!(score("Eiffel Tower Paris France", "Eiffel Tower") < 0.8);
+    }
+
+    #[test]
+    fn matching() {
+        let answers = accepted(&["Eiffel Tower", "Tour Eiffel"]);
+        let found = best_match("the eifel tower", &answers, 0.8).expect("Expected a match");
+        assert_eq!(found.answer, "Eiffel Tower");
+        assert!(found.is_correct);
+        let found = best_match("la tour eiffel", &answers, 0.8).expect("Expected a match");
+        assert_eq!((found.answer.as_str(), found.score), ("Tour Eiffel", 1.0));
+        let found = best_match("big ben", &answers, 0.8).expect("Expected a match");
+        assert!(!found.is_correct);
+        assert!(best_match("big ben", &answers, 0.0).expect("Expected a match").is_correct);
+        // both score the same, so the first is taken
+        let found = best_match("tower", &accepted(&["Tower 1", "Tower 2"]), 0.8).expect("Expected a match");
+        assert_eq!(found.answer, "Tower 1");
+    }
+
+    #[test]
+    fn rejects() {
+        best_match("anything", &[], 0.8).expect_err("Expected no answers to be rejected");
+        best_match("anything", &accepted(&["answer"]), 1.5).expect_err("Expected the threshold to be rejected");
+        best_match("anything", &accepted(&["answer"]), f64::NAN).expect_err("Expected the threshold to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,365 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::buffer::{Buffer, Bytes};
+use crate::persist::{self, Persist};
+use crate::metrics;
+
+const SIZE: usize = 4;
+/// The tile that wins the game, play can go on after it.
+const WINNING_TILE: u32 = 2048;
+/// How often a new tile is a 4 rather than a 2.
+const FOUR_CHANCE: f64 = 0.1;
+
+/// The emoji for an empty cell, and the padding before numbers narrower than the widest tile.
+const EMPTY_EMOJI: &str = "\u{2b1b}";
+const DIGIT_EMOJI: [&str; 10] = [
+    "0\u{fe0f}\u{20e3}",
+    "1\u{fe0f}\u{20e3}",
+    "2\u{fe0f}\u{20e3}",
+    "3\u{fe0f}\u{20e3}",
+    "4\u{fe0f}\u{20e3}",
+    "5\u{fe0f}\u{20e3}",
+    "6\u{fe0f}\u{20e3}",
+    "7\u{fe0f}\u{20e3}",
+    "8\u{fe0f}\u{20e3}",
+    "9\u{fe0f}\u{20e3}",
+];
+
+#[pyclass(module = "twenty48")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum Direction { // COV_EXCL_LINE
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    /// The cells of line `line` as (row, column), starting from the edge the tiles slide towards.
+    fn line(self, line: usize) -> [(usize, usize); SIZE] {
+        std::array::from_fn(|i| match self {
+            Direction::Up => (i, line),
+            Direction::Down => (SIZE - 1 - i, line),
+            Direction::Left => (line, i),
+            Direction::Right => (line, SIZE - 1 - i),
+        })
+    }
+}
+
+/// Slide the tiles of a line towards its start, merging equal pairs once, returning the new line and the points.
+fn slide_line(line: [u32; SIZE]) -> ([u32; SIZE], u32) {
+    let mut slid = [0; SIZE];
+    let mut points = 0;
+    let mut next = 0;
+    let mut can_merge = false;
+    for tile in line.into_iter().filter(|tile| *tile != 0) {
+        if can_merge && slid[next - 1] == tile {
+            slid[next - 1] *= 2;
+            points += slid[next - 1];
+            can_merge = false;
+        } else {
+            slid[next] = tile;
+            next += 1;
+            can_merge = true;
+        }
+    }
+    (slid, points)
+}
+
+// COV_EXCL_START
+#[pyclass(module = "twenty48")]
+#[derive(Debug)]
+struct Board {
+    /// The tiles as rows from the top, 0 for the empty cells.
+    cells: [[u32; SIZE]; SIZE],
+    score: u32,
+    rng: StdRng,
+}
+// COV_EXCL_STOP
+
+impl Board {
+    fn new(rng: StdRng) -> Self {
+        let mut board = Board {
+            cells: [[0; SIZE]; SIZE],
+            score: 0,
+            rng,
+        };
+        board.spawn();
+        board.spawn();
+        board
+    }
+
+    /// Put a 2, or sometimes a 4, in a random empty cell.
+    fn spawn(&mut self) {
+        let empty = (0..SIZE * SIZE).filter(|index| self.cells[index / SIZE][index % SIZE] == 0);
+        if let Some(index) = empty.choose(&mut self.rng) {
+            self.cells[index / SIZE][index % SIZE] = if self.rng.gen_bool(FOUR_CHANCE) { 4 } else { 2 };
+        }
+    }
+
+    /// Slide every line towards `direction` without spawning a tile, returning whether anything moved.
+    fn shift(&mut self, direction: Direction) -> bool {
+        let mut moved = false;
+        for line in 0..SIZE {
+            let cells = direction.line(line);
+            let (slid, points) = slide_line(cells.map(|(row, column)| self.cells[row][column]));
+            for ((row, column), tile) in cells.into_iter().zip(slid) {
+                moved |= self.cells[row][column] != tile;
+                self.cells[row][column] = tile;
+            }
+            self.score += points;
+        }
+        moved
+    }
+
+    fn can_move(&self) -> bool {
+        (0..SIZE).any(|row| {
+            (0..SIZE).any(|column| {
+                let tile = self.cells[row][column];
+                tile == 0
+                    || (row + 1 < SIZE && self.cells[row + 1][column] == tile)
+                    || (column + 1 < SIZE && self.cells[row][column + 1] == tile)
+            })
+        })
+    }
+
+    fn tile_emoji(tile: u32, width: usize) -> String {
+        if tile == 0 {
+            return EMPTY_EMOJI.repeat(width);
+        }
+        let digits = tile.to_string();
+        EMPTY_EMOJI.repeat(width - digits.len())
+            + &digits.bytes().map(|digit| DIGIT_EMOJI[usize::from(digit - b'0')]).collect::<String>()
+    }
+}
+
+#[pymethods] // COV_EXCL_LINE
+impl Board {
+    #[new]
+    #[pyo3(signature = (seed=None))]
+    fn __new__(seed: Option<u64>) -> Self { // COV_EXCL_LINE
+        Self::new(seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64))
+    }
+
+    /// The tiles as 4 rows from the top, 0 for the empty cells.
+    #[getter]
+    fn grid(&self) -> Vec<Vec<u32>> {
+        self.cells.iter().map(|row| row.to_vec()).collect()
+    }
+
+    /// The total of every merged tile.
+    #[getter]
+    fn score(&self) -> u32 {
+        self.score
+    }
+
+    #[getter]
+    fn highest_tile(&self) -> u32 {
+        self.cells.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// Slide the tiles towards `direction`, then add a new tile if anything moved.
+    fn slide(&mut self, direction: Direction) -> bool {
+        metrics::timed("twenty48.slide", || {
+            let moved = self.shift(direction);
+            if moved {
+                self.spawn();
+            }
+            moved
+        })
+    }
+
+    fn has_won(&self) -> bool {
+        self.highest_tile() >= WINNING_TILE
+    }
+
+    fn is_over(&self) -> bool {
+        !self.can_move()
+    }
+
+    /// The grid as a line of emoji per row, every tile as wide as the widest is so the columns line up.
+    fn to_emoji(&self) -> String {
+        let width = self.highest_tile().max(1).to_string().len();
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|tile| Self::tile_emoji(*tile, width)).collect::<Vec<_>>().join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn __str__(&self) -> String {
+        self.to_emoji()
+    }
+
+    fn to_bytes(&self) -> PyResult<Buffer> {
+        let bytes = persist::to_bytes(self).map_err(PyErr::new::<PyValueError, _>)?;
+        Ok(Buffer::from(bytes))
+    }
+
+    #[staticmethod]
+    fn from_bytes(data: Bytes) -> PyResult<Self> {
+        persist::from_bytes(&data).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    fn to_json(&self) -> PyResult<String> {
+        persist::to_json(self).map_err(PyErr::new::<PyValueError, _>)
+    }
+
+    #[staticmethod]
+    fn from_json(data: &str) -> PyResult<Self> {
+        persist::from_json(data).map_err(PyErr::new::<PyValueError, _>)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct State {
+    cells: [[u32; SIZE]; SIZE],
+    score: u32,
+}
+
+impl Persist for Board {
+    const GAME: &'static str = "twenty48";
+    const VERSION: u32 = 1;
+    type State = State;
+
+    fn save(&self) -> State {
+        State { cells: self.cells, score: self.score }
+    }
+
+    /// The random generator isn't saved, a loaded board spawns tiles from a new one.
+    fn load(state: State) -> Result<Self, String> {
+        if let Some(tile) = state.cells.iter().flatten().find(|tile| **tile == 1 || !(**tile == 0 || tile.is_power_of_two())) {
+            return Err(format!("Invalid tile {tile}, tiles are powers of two from 2"));
+        }
+        Ok(Board { cells: state.cells, score: state.score, rng: StdRng::from_entropy() })
+    }
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of 2048";
+
+pub(crate) fn register_twenty48(py: Python, m: &PyModule) -> PyResult<()> {
+    let twenty48 = PyModule::new(py, "_twenty48")?;
+    twenty48.add_class::<Direction>()?;
+    twenty48.add_class::<Board>()?;
+    twenty48.add("__doc__", DOCSTRING)?;
+    m.add_submodule(twenty48)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    fn board(cells: [[u32; SIZE]; SIZE]) -> Board {
+        Board {
+            cells,
+            score: 0,
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+
+    #[parameterized(
+        empty = {[0, 0, 0, 0], [0, 0, 0, 0], 0},
+        gaps = {[0, 2, 0, 4], [2, 4, 0, 0], 0},
+        pair = {[2, 2, 0, 0], [4, 0, 0, 0], 4},
+        once = {[2, 2, 2, 2], [4, 4, 0, 0], 8},
+        merged_not_again = {[4, 2, 2, 0], [4, 4, 0, 0], 4},
+        first_pair = {[2, 2, 2, 0], [4, 2, 0, 0], 4},
+        no_merge = {[2, 4, 8, 16], [2, 4, 8, 16], 0},
+    )]
+    fn slides_lines(line: [u32; SIZE], expected: [u32; SIZE], points: u32) {
+        assert_eq!(slide_line(line), (expected, points));
+    }
+
+    #[test]
+    fn directions() {
+        let start = [[2, 0, 0, 2], [0, 4, 0, 0], [0, 4, 0, 0], [8, 0, 0, 0]];
+        let mut up = board(start);
+        assert!(up.shift(Direction::Up));
+        assert_eq!(up.cells, [[2, 8, 0, 2], [8, 0, 0, 0], [0, 0, 0, 0], [0, 0, 0, 0]]);
+        assert_eq!(up.score(), 8);
+        let mut down = board(start);
+        assert!(down.shift(Direction::Down));
+        assert_eq!(down.cells, [[0, 0, 0, 0], [0, 0, 0, 0], [2, 0, 0, 0], [8, 8, 0, 2]]);
+        let mut left = board(start);
+        assert!(left.shift(Direction::Left));
+        assert_eq!(left.cells, [[4, 0, 0, 0], [4, 0, 0, 0], [4, 0, 0, 0], [8, 0, 0, 0]]);
+        assert_eq!(left.score(), 4);
+        let mut right = board(start);
+        assert!(right.shift(Direction::Right));
+        assert_eq!(right.cells, [[0, 0, 0, 4], [0, 0, 0, 4], [0, 0, 0, 4], [0, 0, 0, 8]]);
+        assert!(!right.shift(Direction::Right));
+    }
+
+    #[test]
+    fn spawning() {
+        let fresh = Board::__new__(Some(42));
+        assert_eq!(fresh.cells.iter().flatten().filter(|tile| **tile != 0).count(), 2);
+        assert_eq!(fresh.grid(), Board::__new__(Some(42)).grid());
+        let mut game = board([[2, 0, 0, 0], [0; SIZE], [0; SIZE], [0; SIZE]]);
+        assert!(!game.slide(Direction::Up));
+        assert_eq!(game.cells.iter().flatten().filter(|tile| **tile != 0).count(), 1);
+        assert!(game.slide(Direction::Down));
+        assert_eq!(game.cells.iter().flatten().filter(|tile| **tile != 0).count(), 2);
+        assert!(game.cells.iter().flatten().all(|tile| [0, 2, 4].contains(tile)));
+    }
+
+    #[test]
+    fn game_over() {
+        let mut game = board([[2, 4, 2, 4], [4, 2, 4, 2], [2, 4, 2, 4], [4, 2, 4, 2]]);
+        assert!(game.is_over());
+        for direction in [Direction::Up, Direction::Down, Direction::Left, Direction::Right] {
+            assert!(!game.slide(direction));
+        }
+        game.cells[3][3] = 4;
+        assert!(!game.is_over());
+        game.cells[3][3] = 0;
+        assert!(!game.is_over());
+    }
+
+    #[test]
+    fn winning() {
+        let mut game = board([[1024, 1024, 0, 0], [0; SIZE], [0; SIZE], [0; SIZE]]);
+        assert!(!game.has_won());
+        game.slide(Direction::Left);
+        assert!(game.has_won());
+        assert_eq!(game.highest_tile(), 2048);
+        assert_eq!(game.score(), 2048);
+    }
+
+    #[test]
+    fn emoji() {
+        let game = board([[2, 0, 0, 0], [0, 16, 0, 0], [0; SIZE], [0; SIZE]]);
+        let two = "2\u{fe0f}\u{20e3}";
+        let one = "1\u{fe0f}\u{20e3}";
+        let six = "6\u{fe0f}\u{20e3}";
+        let empty = "\u{2b1b}\u{2b1b}";
+        let lines = game.to_emoji();
+        let lines = lines.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], format!("\u{2b1b}{two} {empty} {empty} {empty}"));
+        assert_eq!(lines[1], format!("{empty} {one}{six} {empty} {empty}"));
+        assert_eq!(lines[3], [empty; SIZE].join(" "));
+    }
+
+    #[test]
+    fn persists() {
+        let mut game = board([[2, 2, 0, 0], [0, 16, 0, 0], [0; SIZE], [0, 0, 0, 4]]);
+        game.slide(Direction::Left);
+        let json = persist::to_json(&game).expect("Failed to save");
+        let loaded: Board = persist::from_json(&json).expect("Failed to load");
+        assert_eq!((loaded.cells, loaded.score), (game.cells, game.score));
+        let bytes = persist::to_bytes(&game).expect("Failed to save");
+        assert_eq!(persist::from_bytes::<Board>(&bytes).map(|loaded| loaded.cells), Ok(game.cells));
+        Board::load(State { cells: [[3, 0, 0, 0], [0; SIZE], [0; SIZE], [0; SIZE]], score: 0 }).expect_err("Expected the tile to be rejected");
+        Board::load(State { cells: [[1, 0, 0, 0], [0; SIZE], [0; SIZE], [0; SIZE]], score: 0 }).expect_err("Expected the tile to be rejected");
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,304 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::collections::{HashMap, HashSet};
+
+use once_cell::sync::Lazy;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDate;
+use crate::assets;
+use crate::fluent::bundle::{self, AvailableLocales};
+use crate::metrics;
+use crate::shrugman::fold;
+
+/// The words of a locale that can be guessed, and the common ones among them that can be the answer.
+struct Dictionary {
+    answers: Vec<&'static str>,
+    /// Every word that can be guessed, the answers included, normalized so checking a guess is one lookup.
+    words: HashSet<Vec<char>>,
+}
+
+fn list(path: &str) -> impl Iterator<Item = &'static str> {
+    let list = assets::static_text(path).expect("The word lists are assets");
+    list.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+impl Dictionary {
+    fn load(locale: &str) -> Dictionary {
+        let answers = list(&format!("wordle/answers/{locale}.txt")).collect::<Vec<_>>();
+        let guesses = list(&format!("wordle/guesses/{locale}.txt"));
+        let words = answers.iter().copied().chain(guesses).map(normalize).collect();
+        Dictionary { answers, words }
+    }
+}
+
+// each dictionary is only read the first time it is used
+static EN_US: Lazy<Dictionary> = Lazy::new(|| Dictionary::load("en-US"));
+static ES_ES: Lazy<Dictionary> = Lazy::new(|| Dictionary::load("es-ES"));
+static FR: Lazy<Dictionary> = Lazy::new(|| Dictionary::load("fr"));
+static NL: Lazy<Dictionary> = Lazy::new(|| Dictionary::load("nl"));
+
+fn dictionary(locale: AvailableLocales) -> &'static Dictionary {
+    match locale {
+        // runtime locales have no words of their own, and the pseudo locale is english underneath
+        AvailableLocales::AmericanEnglish | AvailableLocales::Custom(_) | AvailableLocales::Pseudo => &EN_US,
+        AvailableLocales::EuropeanSpanish => &ES_ES,
+        AvailableLocales::French => &FR,
+        AvailableLocales::Dutch => &NL,
+    }
+}
+
+/// The letters of `word` as they are compared, lowercase and without accents.
+fn normalize(word: &str) -> Vec<char> {
+    word.chars().flat_map(char::to_lowercase).map(fold).collect()
+}
+
+#[pyclass(module = "wordle")] // COV_EXCL_LINE
+#[derive(PartialEq, Eq, Clone, Copy, Debug)] // COV_EXCL_LINE
+pub enum LetterState { // COV_EXCL_LINE
+    /// The letter is in that spot of the answer.
+    Correct,
+    /// The letter is in the answer, but somewhere else.
+    Present,
+    /// The letter isn't in the answer, or every time it is was already accounted for by other guesses of it.
+    Absent,
+}
+
+/// The state of each letter of `guess` against `answer`.
+///
+/// Letters in the right spot are marked first, then each other letter is only present while the
+/// answer has copies of it left over, so guessing a letter twice doesn't mark both for one copy.
+fn evaluate_guess(guess: &str, answer: &str) -> Result<Vec<LetterState>, String> {
+    let (guess, answer) = (normalize(guess), normalize(answer));
+    if guess.len() != answer.len() {
+        return Err(format!(
+            "The guess has {} letters, but the answer has {}",
+            guess.len(),
+            answer.len()
+        ));
+    }
+    let mut states = vec![LetterState::Absent; guess.len()];
+    let mut left = HashMap::new();
+    for (index, (guessed, actual)) in guess.iter().zip(&answer).enumerate() {
+        if guessed == actual {
+            states[index] = LetterState::Correct;
+        } else {
+            *left.entry(actual).or_insert(0) += 1;
+        }
+    }
+    for (state, guessed) in states.iter_mut().zip(&guess) {
+        if *state == LetterState::Correct {
+            continue;
+        }
+        if let Some(count) = left.get_mut(guessed).filter(|count| **count > 0) {
+            *count -= 1;
+            *state = LetterState::Present;
+        }
+    }
+    Ok(states)
+}
+
+/// SplitMix64, a fixed hash so the daily word doesn't change with the rust or rand version.
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+/// The word for the day `ordinal`, counted like `date.toordinal()`.
+fn word_for_day(ordinal: i64, seed: u64, locale: AvailableLocales) -> &'static str {
+    let answers = &dictionary(locale).answers;
+    let hash = mix(mix(seed) ^ ordinal as u64);
+    answers[(hash % answers.len() as u64) as usize]
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+evaluate(guess, answer, /)
+--
+
+Compare a guess to the answer, letter by letter.
+
+Letters are compared lowercase and without accents, so 'arbol' is correct for 'árbol'. A letter
+guessed more times than it is in the answer is only present as many times as it is there, with the
+ones in the right spot counted first.
+
+Parameters
+----------
+guess : str
+    The word guessed.
+answer : str
+    The word to guess.
+
+Returns
+-------
+list[LetterState]
+    The state of each letter of the guess, in order.
+
+Raises
+------
+ValueError
+    If the guess and the answer don't have the same number of letters.
+")]
+fn evaluate(guess: &str, answer: &str) -> PyResult<Vec<LetterState>> {
+    metrics::observe("wordle.evaluate", || evaluate_guess(guess, answer).map_err(PyErr::new::<PyValueError, _>))
+}
+
+#[pyfunction]
+#[pyo3(signature = (word, locale="en-US"), text_signature = "
+is_word(word, locale='en-US', /)
+--
+
+Check if a word can be guessed in a locale, so guesses can be limited to real words.
+
+Any word in the dictionary of the locale can be guessed, not just the common ones the daily word is
+picked from.
+
+Parameters
+----------
+word : str
+    The word to check, case and accents are ignored.
+locale : str
+    The locale of the dictionary, those without one use the closest that has one, which is en-US if
+    nothing else. The default is 'en-US'.
+
+Returns
+-------
+bool
+    True if the word is in the dictionary, False otherwise.
+")]
+fn is_word(word: &str, locale: &str) -> bool {
+    metrics::timed("wordle.is_word", || dictionary(bundle::closest_locale(locale)).words.contains(&normalize(word)))
+}
+
+#[pyfunction]
+#[pyo3(signature = (date, locale="en-US", seed=0), text_signature = "
+daily_word(date, locale='en-US', seed=0, /)
+--
+
+The word for a day, the same for everyone who asks for that day, locale and seed.
+
+It is one of the common words of the locale, never one of the rarer ones that can only be guessed.
+
+Parameters
+----------
+date : datetime.date
+    The day to pick the word for.
+locale : str
+    The locale of the dictionary to pick from, those without one use the closest that has one, which
+    is en-US if nothing else. The default is 'en-US'.
+seed : int
+    Mixed into the pick, so a different seed gives a different run of words. The default is 0.
+
+Returns
+-------
+str
+    The word, with any accents it has.
+")]
+fn daily_word(date: &PyDate, locale: &str, seed: u64) -> PyResult<&'static str> {
+    metrics::observe("wordle.daily_word", || {
+        let ordinal = date.call_method0("toordinal")?.extract::<i64>()?;
+        Ok(word_for_day(ordinal, seed, bundle::closest_locale(locale)))
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based implementation of wordle";
+
+pub(crate) fn register_wordle(py: Python, m: &PyModule) -> PyResult<()> {
+    let wordle = PyModule::new(py, "_wordle")?;
+    wordle.add_class::<LetterState>()?;
+    wordle.add_function(wrap_pyfunction!(evaluate, wordle)?)?;
+    wordle.add_function(wrap_pyfunction!(is_word, wordle)?)?;
+    wordle.add_function(wrap_pyfunction!(daily_word, wordle)?)?;
+    wordle.add("__doc__", DOCSTRING)?;
+    m.add_submodule(wordle)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+    use LetterState::{Absent as A, Correct as C, Present as P};
+
+    #[parameterized(
+        english = {AvailableLocales::AmericanEnglish},
+        spanish = {AvailableLocales::EuropeanSpanish},
+        french = {AvailableLocales::French},
+        dutch = {AvailableLocales::Dutch},
+    )]
+    fn word_lists(locale: AvailableLocales) {
+        let answers = list(&format!("wordle/answers/{}.txt", locale.as_str())).collect::<Vec<_>>();
+        let guesses = list(&format!("wordle/guesses/{}.txt", locale.as_str())).collect::<Vec<_>>();
+        assert!(answers.len() >= 500);
+        let mut seen = HashSet::new();
+        for word in answers.iter().chain(&guesses) {
+            assert_eq!(word.chars().count(), 5, "Bad word {word}");
+            assert!(word.chars().all(|letter| letter.is_alphabetic() && letter.is_lowercase()), "Bad word {word}");
+            assert!(seen.insert(word), "{word} is listed twice");
+        }
+    }
+
+    #[parameterized(
+        solved = {"crane", "crane", vec![C, C, C, C, C]},
+        nothing = {"crane", "ghost", vec![A, A, A, A, A]},
+        moved = {"evade", "abide", vec![A, A, P, C, C]},
+        extra_copy_absent = {"speed", "abide", vec![A, A, P, A, P]},
+        correct_copy_first = {"geese", "these", vec![A, A, C, C, C]},
+        two_correct_copies = {"lolly", "hello", vec![A, P, C, C, A]},
+        both_present = {"otter", "tutor", vec![P, P, C, A, C]},
+        accents = {"arbol", "Árbol", vec![C, C, C, C, C]},
+        nya = {"nandu", "ñandú", vec![A, C, C, C, C]},
+    )]
+    fn evaluates(guess: &str, answer: &str, expected: Vec<LetterState>) {
+        assert_eq!(evaluate_guess(guess, answer), Ok(expected));
+    }
+
+    #[test]
+    fn wrong_length() {
+        evaluate_guess("cranes", "crane").expect_err("Expected the guess to be rejected");
+    }
+
+    #[test]
+    fn dictionary() {
+        assert!(is_word("Crane", "en-US"));
+        assert!(!is_word("crnae", "en-US"));
+        assert!(is_word("arbol", "es-MX"));
+        assert!(!is_word("arbre", "es-ES"));
+        assert!(is_word("ecole", "fr"));
+        assert!(is_word("fiets", "nl"));
+        assert!(is_word("crane", "pt-BR"));
+        assert!(is_word("abbes", "en-US"));
+        assert!(is_word("Niños", "es-ES"));
+        assert!(!is_word("abbes", "fr"));
+    }
+
+    #[test]
+    fn daily() {
+        let day = 738_000;
+        let word = word_for_day(day, 0, AvailableLocales::AmericanEnglish);
+        assert_eq!(word, word_for_day(day, 0, AvailableLocales::AmericanEnglish));
+        assert!(EN_US.answers.contains(&word));
+        let month = (day..day + 30)
+            .map(|day| word_for_day(day, 0, AvailableLocales::AmericanEnglish))
+            .collect::<std::collections::HashSet<_>>();
+        assert!(month.len() > 15);
+        let seeded = (day..day + 30)
+            .map(|day| word_for_day(day, 1, AvailableLocales::AmericanEnglish))
+            .collect::<Vec<_>>();
+        let unseeded = (day..day + 30)
+            .map(|day| word_for_day(day, 0, AvailableLocales::AmericanEnglish))
+            .collect::<Vec<_>>();
+        assert_ne!(seeded, unseeded);
+        let spanish = word_for_day(day, 0, AvailableLocales::EuropeanSpanish);
+        assert!(ES_ES.answers.contains(&spanish));
+        let answers = (day..day + 1000)
+            .map(|day| word_for_day(day, 0, AvailableLocales::AmericanEnglish))
+            .collect::<HashSet<_>>();
+        assert!(answers.iter().all(|answer| !list("wordle/guesses/en-US.txt").any(|guess| guess == *answer)));
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,253 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+use std::cmp::Reverse;
+
+use once_cell::sync::Lazy;
+use pyo3::prelude::*;
+use rand::prelude::*;
+use crate::assets;
+use crate::fluent::bundle::{self, AvailableLocales};
+use crate::metrics;
+use crate::shrugman::fold;
+
+/// A word from a list, with its letters as they are compared, sorted so checking it can be spelled is one walk through both.
+struct Entry {
+    word: &'static str,
+    letters: Vec<char>,
+}
+
+fn parse(path: &str) -> Vec<Entry> {
+    let list = assets::static_text(path).expect("The word lists are assets");
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|word| Entry { word, letters: letters(word) })
+        .collect()
+}
+
+// each list is only parsed the first time it is searched
+static EN_US: Lazy<Vec<Entry>> = Lazy::new(|| parse("words/en-US.txt"));
+static ES_ES: Lazy<Vec<Entry>> = Lazy::new(|| parse("words/es-ES.txt"));
+static FR: Lazy<Vec<Entry>> = Lazy::new(|| parse("words/fr.txt"));
+static NL: Lazy<Vec<Entry>> = Lazy::new(|| parse("words/nl.txt"));
+
+fn dictionary(locale: AvailableLocales) -> &'static [Entry] {
+    match locale {
+        // runtime locales have no words of their own, and the pseudo locale is english underneath
+        AvailableLocales::AmericanEnglish | AvailableLocales::Custom(_) | AvailableLocales::Pseudo => &EN_US,
+        AvailableLocales::EuropeanSpanish => &ES_ES,
+        AvailableLocales::French => &FR,
+        AvailableLocales::Dutch => &NL,
+    }
+}
+
+/// The letters of `text`, lowercase, folded like shrugman guesses and sorted, without anything that isn't a letter.
+fn letters(text: &str) -> Vec<char> {
+    let mut letters = text.chars().flat_map(char::to_lowercase).filter(|letter| letter.is_alphabetic()).map(fold).collect::<Vec<_>>();
+    letters.sort_unstable();
+    letters
+}
+
+/// Whether `word` can be spelled from `letters`, using each letter at most once, both sorted.
+fn spells(word: &[char], letters: &[char]) -> bool {
+    let mut available = letters.iter();
+    word.iter().all(|letter| available.any(|candidate| candidate == letter))
+}
+
+/// The words in the list for `locale` with at least `min_len` letters that can be spelled from `letters`.
+///
+/// The longest words come first, since they score the most, then the rest in the order of the list.
+fn find(letters_given: &str, locale: AvailableLocales, min_len: usize) -> Vec<&'static str> {
+    let given = letters(letters_given);
+    let mut found = dictionary(locale)
+        .iter()
+        .filter(|entry| entry.letters.len() >= min_len && spells(&entry.letters, &given))
+        .map(|entry| entry.word)
+        .collect::<Vec<_>>();
+    // a stable sort keeps words of the same length in the order of the list
+    found.sort_by_key(|word| Reverse(word.chars().count()));
+    found
+}
+
+/// The letters of `word` in a random order, which isn't the order they started in unless every letter is the same.
+fn shuffle(word: &str, rng: &mut impl Rng) -> String {
+    let mut shuffled = word.chars().collect::<Vec<_>>();
+    if shuffled.iter().all(|letter| *letter == shuffled[0]) {
+        return word.to_string();
+    }
+    loop {
+        shuffled.shuffle(rng);
+        if shuffled.iter().copied().ne(word.chars()) {
+            return shuffled.into_iter().collect();
+        }
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (word, seed=None), text_signature = "
+scramble(word, seed=None, /)
+--
+
+Scramble the letters of a word, for unscrambling games.
+
+Parameters
+----------
+word : str
+    The word to scramble.
+seed : int | None
+    The seed for the order of the letters, so a scramble can be reproduced. The default is None, for a random order.
+
+Returns
+-------
+str
+    The letters of the word in a different order, unless every letter is the same.
+")]
+fn scramble(word: &str, seed: Option<u64>) -> String {
+    metrics::timed("words.scramble", || {
+        let mut rng = seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64);
+        shuffle(word, &mut rng)
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "
+is_anagram(first, second, /)
+--
+
+Check if two words or phrases are spelled with the same letters.
+
+Case, accents, spaces and punctuation are ignored, so 'Dormitory' is an anagram of 'dirty room!'. Letters
+like 'ñ' that are letters of their own in a language aren't the same as the letter without the accent.
+
+Parameters
+----------
+first : str
+    The first word or phrase.
+second : str
+    The second word or phrase.
+
+Returns
+-------
+bool
+    True if both have the same letters, as many times each, False otherwise.
+")]
+fn is_anagram(first: &str, second: &str) -> bool {
+    metrics::timed("words.is_anagram", || letters(first) == letters(second))
+}
+
+#[pyfunction]
+#[pyo3(signature = (letters, locale="en-US", min_len=3), text_signature = "
+find_words(letters, locale='en-US', min_len=3, /)
+--
+
+Find the words that can be spelled from some letters, for a countdown style letters game.
+
+The words come from a word list for each language the bot is translated to, built into the library.
+
+Parameters
+----------
+letters : str
+    The letters, each can be used once for each time it is given. Case and accents are ignored, and
+    anything that isn't a letter.
+locale : str
+    The locale to use the word list of, the closest one is used if it doesn't have one of its own. The default is 'en-US'.
+min_len : int
+    The fewest letters a word can have. The default is 3.
+
+Returns
+-------
+list[str]
+    The words, the longest first.
+")]
+fn find_words(letters: &str, locale: &str, min_len: usize) -> Vec<&'static str> {
+    metrics::timed("words.find_words", || find(letters, bundle::closest_locale(locale), min_len))
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based word scrambling, anagrams and word finding";
+
+pub(crate) fn register_words(py: Python, m: &PyModule) -> PyResult<()> {
+    let words = PyModule::new(py, "_words")?;
+    words.add_function(wrap_pyfunction!(scramble, words)?)?;
+    words.add_function(wrap_pyfunction!(is_anagram, words)?)?;
+    words.add_function(wrap_pyfunction!(find_words, words)?)?;
+    words.add("__doc__", DOCSTRING)?;
+    m.add_submodule(words)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yare::parameterized;
+
+    #[parameterized(
+        english = {AvailableLocales::AmericanEnglish},
+        spanish = {AvailableLocales::EuropeanSpanish},
+        french = {AvailableLocales::French},
+        dutch = {AvailableLocales::Dutch},
+    )]
+    fn word_lists(locale: AvailableLocales) {
+        let entries = dictionary(locale);
+        assert!(entries.len() >= 500);
+        for entry in entries {
+            assert!(entry.word.chars().all(|letter| letter.is_alphabetic() && letter.is_lowercase()), "Bad word {}", entry.word);
+            assert_eq!(entry.letters.len(), entry.word.chars().count());
+        }
+    }
+
+    #[test]
+    fn letters_are_normalized() {
+        assert_eq!(letters("Été, 2!"), vec!['e', 'e', 't']);
+        assert_eq!(letters("año"), vec!['a', 'o', 'ñ']);
+    }
+
+    #[test]
+    fn spelling() {
+        let spell = |word: &str, given: &str| spells(&letters(word), &letters(given));
+        assert!(spell("tea", "eatx"));
+        assert!(spell("tee", "etexe"));
+        assert!(!spell("tee", "tex"));
+        assert!(!spell("tea", "te"));
+        assert!(spell("", "abc"));
+    }
+
+    #[test]
+    fn finding() {
+        assert!(EN_US.len() >= 50_000);
+        assert_eq!(find("tpare", AvailableLocales::AmericanEnglish, 5), vec!["apter", "prate", "taper"]);
+        let found = find("tpare", AvailableLocales::AmericanEnglish, 3);
+        assert_eq!(found[..3], ["apter", "prate", "taper"]);
+        assert!(["part", "pear", "rate", "tape", "tear", "apt", "art", "ate", "ear", "eat", "era", "tea"].iter().all(|word| found.contains(word)));
+        assert!(found.iter().all(|word| word.len() >= 3));
+        assert!(find("", AvailableLocales::AmericanEnglish, 1).is_empty());
+        // accents don't have to be typed, but the words are given with them
+        assert!(find("ete", AvailableLocales::French, 3).contains(&"été"));
+        assert!(find_words("année", "fr-CA", 3).contains(&"année"));
+        assert_eq!(find_words("tpare", "pt-BR", 5), ["apter", "prate", "taper"]);
+        assert_eq!(find_words("tpare", "pt-BR", 6), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn anagrams() {
+        assert!(is_anagram("Dormitory", "dirty room!"));
+        assert!(is_anagram("listen", "silent"));
+        assert!(is_anagram("été", "tee"));
+        assert!(!is_anagram("listen", "listens"));
+        assert!(!is_anagram("año", "ano"));
+    }
+
+    #[test]
+    fn scrambling() {
+        for word in ["listen", "ab", "Countdown"] {
+            let scrambled = scramble(word, None);
+            assert_ne!(scrambled, word);
+            assert!(is_anagram(&scrambled, word));
+        }
+        assert_eq!(scramble("aaa", None), "aaa");
+        assert_eq!(scramble("", None), "");
+        assert_eq!(scramble("letters", Some(3)), scramble("letters", Some(3)));
+    }
+}
+// COV_EXCL_STOP
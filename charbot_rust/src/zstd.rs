@@ -0,0 +1,218 @@
+// SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+//
+// SPDX-License-Identifier: MIT
+//! Compressing serialized game states and archives of messages with zstd before they go in the
+//! database, decompressing them with a limit on how big they can get, and training dictionaries for
+//! the many small things that are alike.
+use ::zstd::bulk::{Compressor, Decompressor};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::buffer::{Buffer, Bytes, Data};
+use crate::metrics;
+
+/// What zstd calls an error from decompressing to more than there's room for, which is the limit.
+const TOO_SMALL: &str = "Destination buffer is too small";
+
+/// `data` compressed at `level`, with the size of the data and a checksum in the frame.
+fn compress_data(data: &[u8], level: i32, dictionary: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let mut compressor = match dictionary {
+        Some(dictionary) => Compressor::with_dictionary(level, dictionary).map_err(|error| format!("Invalid dictionary: {error}"))?,
+        None => Compressor::new(level).map_err(|error| error.to_string())?,
+    };
+    compressor.include_checksum(true).map_err(|error| error.to_string())?;
+    compressor.compress(data).map_err(|error| format!("Couldn't compress the data: {error}"))
+}
+
+/// The zstd frames in `data` decompressed, if they're at most `max_size` bytes.
+fn decompress_data(data: &[u8], max_size: usize, dictionary: Option<&[u8]>) -> Result<Vec<u8>, String> {
+    let mut decompressor = match dictionary {
+        Some(dictionary) => Decompressor::with_dictionary(dictionary).map_err(|error| format!("Invalid dictionary: {error}"))?,
+        None => Decompressor::new().map_err(|error| error.to_string())?,
+    };
+    decompressor.decompress(data, max_size).map_err(|error| match error.to_string() {
+        problem if problem == TOO_SMALL => format!("The data decompresses to more than {max_size} bytes"),
+        problem => format!("Invalid zstd data: {problem}"),
+    })
+}
+
+/// A dictionary at most `size` bytes, trained on `samples`.
+fn train(samples: &[&[u8]], size: usize) -> Result<Vec<u8>, String> {
+    if size < 256 {
+        return Err(format!("Invalid size {size}, it must be at least 256"));
+    }
+    let total: usize = samples.iter().map(|sample| sample.len()).sum();
+    if samples.len() < 2 || total <= size {
+        return Err(format!("There have to be at least 2 samples and more than {size} bytes of them, got {} and {total}", samples.len()));
+    }
+    ::zstd::dict::from_samples(samples, size).map_err(|error| format!("Couldn't train a dictionary: {error}"))
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, /, level=3, dictionary=None), text_signature = "
+compress(data, /, level=3, dictionary=None)
+--
+
+Compress data with zstd, like a serialized game state or an archive of messages.
+
+Parameters
+----------
+data : str | bytes-like
+    The data. Text is compressed as UTF-8.
+level : int
+    How hard to try to make it smaller, from 1 to 22, with higher levels being slower. The default is
+    3.
+dictionary : bytes-like, optional
+    A dictionary, like one from ``train_dict``, which decompressing it needs too.
+
+Returns
+-------
+Buffer
+    A zstd frame, with the size of the data and a checksum, which any zstd can decompress.
+
+Raises
+------
+ValueError
+    If the level isn't from 1 to 22, or the dictionary is invalid.
+")]
+fn compress(py: Python, data: Data, level: i32, dictionary: Option<Bytes>) -> PyResult<Buffer> {
+    if !(1..=22).contains(&level) {
+        return Err(PyValueError::new_err(format!("Invalid level {level}, it must be from 1 to 22")));
+    }
+    metrics::observe("zstd.compress", || {
+        let compressed = py.allow_threads(|| compress_data(data.as_bytes(), level, dictionary.as_deref()));
+        compressed.map(Buffer::from).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (data, max_size, /, dictionary=None), text_signature = "
+decompress(data, max_size, /, dictionary=None)
+--
+
+Decompress zstd frames, stopping once they'd be more than ``max_size`` bytes, so a small row in the
+database can't decompress to more memory than there is.
+
+Parameters
+----------
+data : bytes-like
+    One or more zstd frames, from ``compress`` or any other zstd.
+max_size : int
+    The most bytes it can decompress to.
+dictionary : bytes-like, optional
+    The dictionary it was compressed with, if any.
+
+Returns
+-------
+Buffer
+    The data.
+
+Raises
+------
+ValueError
+    If it would be more than ``max_size`` bytes, it isn't zstd, it's cut off or corrupted, or it needs
+    a different dictionary.
+")]
+fn decompress(py: Python, data: Bytes, max_size: usize, dictionary: Option<Bytes>) -> PyResult<Buffer> {
+    metrics::observe("zstd.decompress", || {
+        let data = py.allow_threads(|| decompress_data(&data, max_size, dictionary.as_deref()));
+        data.map(Buffer::from).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+#[pyfunction]
+#[pyo3(signature = (samples, /, size=16384), text_signature = "
+train_dict(samples, /, size=16384)
+--
+
+Train a dictionary for compressing small things that are alike, like the states of the same game,
+which on their own have too little in them to compress well.
+
+Parameters
+----------
+samples : list[str | bytes-like]
+    Examples of what's going to be compressed, with more bytes all together than the dictionary.
+size : int
+    The most bytes the dictionary can be, at least 256. The default is 16384.
+
+Returns
+-------
+Buffer
+    A dictionary trained the way the zstd command line trains them, which it and the zstd libraries
+    can use too.
+
+Raises
+------
+ValueError
+    If there are fewer than 2 samples, too few bytes of them, the size is smaller than 256, or the
+    samples have too little in common to train a dictionary on.
+")]
+fn train_dict(py: Python, samples: Vec<Data>, size: usize) -> PyResult<Buffer> {
+    metrics::observe("zstd.train_dict", || {
+        let samples: Vec<&[u8]> = samples.iter().map(Data::as_bytes).collect();
+        let dictionary = py.allow_threads(|| train(&samples, size));
+        dictionary.map(Buffer::from).map_err(PyErr::new::<PyValueError, _>)
+    })
+}
+
+// COV_EXCL_START
+const DOCSTRING: &str = "Rust based zstd compression and decompression, with dictionaries";
+
+pub(crate) fn register_zstd(py: Python, m: &PyModule) -> PyResult<()> {
+    let zstd = PyModule::new(py, "_zstd")?;
+    zstd.add_function(wrap_pyfunction!(compress, zstd)?)?;
+    zstd.add_function(wrap_pyfunction!(decompress, zstd)?)?;
+    zstd.add_function(wrap_pyfunction!(train_dict, zstd)?)?;
+    zstd.add("__doc__", DOCSTRING)?;
+    m.add_submodule(zstd)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states() -> Vec<Vec<u8>> {
+        (0..200)
+            .map(|id| format!(r#"{{"game": "slots", "user": {id}, "state": {{"reels": [{}, {}, {}], "bet": 10, "balance": {}}}}}"#, id % 7, id % 5, id % 3, 1000 + id * 13).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let data = "the quick brown fox jumps over the lazy dog ".repeat(100);
+        for level in [1, 3, 19] {
+            let compressed = compress_data(data.as_bytes(), level, None).unwrap();
+            assert!(compressed.len() < data.len() / 10, "{level}");
+            assert_eq!(decompress_data(&compressed, data.len(), None).as_deref(), Ok(data.as_bytes()));
+        }
+        assert_eq!(decompress_data(&compress_data(b"", 3, None).unwrap(), 0, None), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn limits() {
+        let compressed = compress_data(&[b'z'; 1000], 3, None).unwrap();
+        assert_eq!(decompress_data(&compressed, 999, None), Err(String::from("The data decompresses to more than 999 bytes")));
+        let error = decompress_data(b"not zstd at all", 1000, None).unwrap_err();
+        assert!(error.starts_with("Invalid zstd data: "), "{error}");
+        let error = decompress_data(&compressed[..compressed.len() - 2], 1000, None).unwrap_err();
+        assert!(error.starts_with("Invalid zstd data: "), "{error}");
+    }
+
+    #[test]
+    fn dictionaries() {
+        let samples = states();
+        let samples = samples.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let dictionary = train(&samples, 1024).unwrap();
+        assert!(dictionary.len() <= 1024);
+        let compressed = compress_data(samples[7], 3, Some(&dictionary)).unwrap();
+        assert!(compressed.len() < compress_data(samples[7], 3, None).unwrap().len());
+        assert_eq!(decompress_data(&compressed, 1000, Some(&dictionary)).as_deref(), Ok(samples[7]));
+        assert_eq!(train(&samples, 255), Err(String::from("Invalid size 255, it must be at least 256")));
+        assert_eq!(
+            train(&samples[..1], 256),
+            Err(format!("There have to be at least 2 samples and more than 256 bytes of them, got 1 and {}", samples[0].len()))
+        );
+    }
+}
+// COV_EXCL_STOP
@@ -0,0 +1,292 @@
+# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+"""Generate type stubs for the extension module from what it says about itself.
+
+Every function and method of the extension has a text signature, and the ones taking or giving more than
+the obvious list their types in the Parameters and Returns sections of their numpy style docstrings, so
+the stubs are made from those with the extension built and installed. Anything without a type is Any.
+
+The stubs in ``python/charbot_rust`` are kept by hand, with more in them than can be generated, so this
+writes to another directory, to compare them with or to start the stub of a new module from::
+
+    maturin develop
+    python stubgen.py generated
+    diff -r generated python/charbot_rust
+
+To check the stubs kept by hand still have every function, class and member of the extension, taking the
+same parameters, which CI does, it can diff those instead, leaving out the types::
+
+    python stubgen.py --check python/charbot_rust
+"""
+from __future__ import annotations
+
+import ast
+import difflib
+import inspect
+import re
+import sys
+import types
+from pathlib import Path
+from typing import Any
+
+# the types a translation's arguments can be, written out wherever translations take arguments
+ARG_TYPES = "bool | int | float | str | datetime.datetime | list[str] | enum.Enum | None"
+BYTES_LIKE = "bytes | bytearray | memoryview | Buffer"
+# the modules of the standard library the docstrings name types from
+STDLIB = {"asyncio", "datetime", "enum", "os"}
+# what the dunder methods give, which their docstrings don't say
+DUNDER_RETURNS = {
+    "__bool__": "bool",
+    "__bytes__": "bytes",
+    "__contains__": "bool",
+    "__eq__": "bool",
+    "__hash__": "int",
+    "__len__": "int",
+    "__ne__": "bool",
+    "__repr__": "str",
+    "__str__": "str",
+}
+DUNDERS = {*DUNDER_RETURNS, "__iter__", "__next__", "__getitem__", "__buffer__"}
+HEADER = """# -*- coding: utf-8 -*-
+# SPDX-FileCopyrightText: 2022 Bluesy1 <68259537+Bluesy1@users.noreply.github.com>
+# SPDX-License-Identifier: MIT
+"""
+
+
+def annotation(text: str) -> str:
+    """Turn a type from a numpy docstring, like ``{'en-US', 'fr'}, optional``, into an annotation."""
+    text = re.sub(r",\s*(optional|default.*)$", "", text.strip())
+    text = re.sub(r"\{([^{}]*)\}", lambda choices: f"Literal[{choices.group(1)}]", text)
+    text = re.sub(r"\bbytes-like\b", BYTES_LIKE, text)
+    text = re.sub(r"\bcallable\b", "Callable[..., Any]", text)
+    return text.replace(ARG_TYPES, "ArgTypes")
+
+
+def sections(doc: str | None) -> tuple[dict[str, str], str | None]:
+    """The types of the parameters in a numpy docstring by their names, and the type it returns."""
+    parameters: dict[str, str] = {}
+    returns = None
+    section = None
+    lines = inspect.cleandoc(doc or "").splitlines()
+    for line, underline in zip(lines, [*lines[1:], ""]):
+        if underline and set(underline) == {"-"}:
+            section = line.strip()
+        elif line and not line.startswith(" ") and set(line) != {"-"}:
+            if section == "Parameters" and " : " in line:
+                name, kind = line.split(" : ", 1)
+                parameters[name.strip()] = annotation(kind)
+            elif section == "Returns" and returns is None:
+                returns = annotation(line.split(" : ", 1)[-1])
+    return parameters, returns
+
+
+def docstring(doc: str | None, indent: str) -> list[str]:
+    """The lines of a docstring in a stub, or none if there isn't one."""
+    if not doc or not doc.strip():
+        return []
+    lines = inspect.cleandoc(doc).replace('"""', r"\"\"\"").splitlines()
+    body = [f"{indent}{line}" if line else "" for line in lines[1:]]
+    closing = [f'{indent}"""'] if body else []
+    return [f'{indent}"""{lines[0]}' + ('"""' if not body else ""), *body, *closing]
+
+
+def default(value: Any) -> str:
+    """The default of a parameter as it's written in a stub."""
+    if value is None or isinstance(value, (bool, int, float, str)):
+        return repr(value)
+    return "..."
+
+
+def signature(name: str, obj: Any, doc: str | None, returns: str | None, method: bool) -> str:
+    """The ``def`` line of a function or method, annotated from its docstring."""
+    parameters, documented = sections(doc)
+    try:
+        params = list(inspect.signature(obj).parameters.values())
+    except (TypeError, ValueError):
+        # without a text signature all there is to say is that it takes something
+        params = [
+            inspect.Parameter("args", inspect.Parameter.VAR_POSITIONAL),
+            inspect.Parameter("kwargs", inspect.Parameter.VAR_KEYWORD),
+        ]
+    if method and (not params or params[0].name not in {"self", "cls"}):
+        # the text signatures of constructors and of methods without one leave out self
+        params.insert(0, inspect.Parameter("self", inspect.Parameter.POSITIONAL_OR_KEYWORD))
+    written = []
+    positional_only = False
+    for index, param in enumerate(params):
+        if positional_only and param.kind is not inspect.Parameter.POSITIONAL_ONLY:
+            written.append("/")
+            positional_only = False
+        if param.kind is inspect.Parameter.KEYWORD_ONLY and not any(p.startswith("*") for p in written):
+            written.append("*")
+        prefix = {inspect.Parameter.VAR_POSITIONAL: "*", inspect.Parameter.VAR_KEYWORD: "**"}.get(param.kind, "")
+        text = f"{prefix}{param.name}"
+        if not (method and index == 0 and param.name in {"self", "cls"}):
+            text += f": {parameters.get(param.name, 'Any')}"
+        if param.default is not inspect.Parameter.empty:
+            text += f" = {default(param.default)}"
+        written.append(text)
+        positional_only = param.kind is inspect.Parameter.POSITIONAL_ONLY
+    if positional_only:
+        written.append("/")
+    return f"def {name}({', '.join(written)}) -> {returns or documented or DUNDER_RETURNS.get(name, 'Any')}:"
+
+
+def function_stub(name: str, obj: Any, indent: str = "", method: bool = False) -> list[str]:
+    """The stub of a function or method, with its docstring."""
+    doc = getattr(obj, "__doc__", None)
+    return [f"{indent}{signature(name, obj, doc, None, method)}", *docstring(doc, indent + "    "), f"{indent}    ..."]
+
+
+def class_stub(name: str, cls: type) -> list[str]:
+    """The stub of a class, with its constructor, methods, properties and constants."""
+    bases = [base.__name__ for base in cls.__bases__ if base is not object]
+    lines = [f"class {name}{'(' + ', '.join(bases) + ')' if bases else ''}:", *docstring(cls.__doc__, "    ")]
+    if issubclass(cls, BaseException):
+        return [*lines, "    ..."]
+    # the parameters of the constructor are documented on the class
+    members = [[f"    {signature('__init__', cls, cls.__doc__, 'None', method=True)}", "        ..."]]
+    for member, value in sorted(vars(cls).items()):
+        if member.startswith("_") and member not in DUNDERS:
+            continue
+        if isinstance(value, cls):
+            members.append([f"    {member}: {name}"])
+        elif inspect.isgetsetdescriptor(value) or inspect.isdatadescriptor(value):
+            getter = [f"    def {member}(self) -> Any:", *docstring(value.__doc__, "        "), "        ..."]
+            members.append(["    @property", *getter])
+        elif isinstance(value, (classmethod, staticmethod)) or type(value).__name__ == "classmethod_descriptor":
+            kind = "classmethod" if "classmethod" in type(value).__name__ else "staticmethod"
+            method = function_stub(member, getattr(cls, member), "    ", method=kind == "classmethod")
+            members.append([f"    @{kind}", *method])
+        elif callable(value):
+            members.append(function_stub(member, value, "    ", method=True))
+    return lines + [line for member in members for line in member]
+
+
+def module_stub(module: types.ModuleType, siblings: set[str]) -> str:
+    """The stub of a module of the extension, importing what its annotations refer to."""
+    stubs = []
+    for name, value in sorted(vars(module).items()):
+        if name.startswith("_"):
+            continue
+        if isinstance(value, type):
+            stubs.append("\n".join(class_stub(name, value)))
+        elif callable(value):
+            stubs.append("\n".join(function_stub(name, value)))
+    body = "\n\n".join(stubs)
+    imports = ["from typing import Any, Callable, Literal"]
+    imports += [f"import {stdlib}" for stdlib in sorted(STDLIB) if re.search(rf"\b{stdlib}\.", body)]
+    imports += [f"from . import {sibling}" for sibling in sorted(siblings) if re.search(rf"\b{sibling}\.[A-Z]", body)]
+    prelude = ""
+    if module.__name__.endswith("_charbot_rust"):
+        prelude = f"ArgTypes = {ARG_TYPES}\n\n"
+    elif shared := [name for name in ("ArgTypes", "Buffer") if re.search(rf"\b{name}\b", body)]:
+        imports.append(f"from .charbot_rust import {', '.join(shared)}")
+    return HEADER + "\n".join(imports) + "\n\n" + prelude + body + "\n"
+
+
+def generate_stubs(extension: types.ModuleType) -> dict[str, str]:
+    """The stub of the extension and of each of its submodules, by the file name each goes in."""
+    submodules = {
+        name: value
+        for name, value in vars(extension).items()
+        if isinstance(value, types.ModuleType) and name.startswith("_")
+    }
+    siblings = {name[1:] for name in submodules}
+    stubs = {"charbot_rust.pyi": module_stub(extension, siblings)}
+    for name, submodule in submodules.items():
+        stubs[f"{name[1:]}.pyi"] = module_stub(submodule, siblings)
+    return stubs
+
+
+def parameters(arguments: ast.arguments) -> str:
+    """The parameters of a ``def`` without their types or defaults, only whether they have one."""
+    names = [*arguments.posonlyargs, *arguments.args]
+    defaults = [None] * (len(names) - len(arguments.defaults)) + list(arguments.defaults)
+    written = [arg.arg + ("=..." if value is not None else "") for arg, value in zip(names, defaults)]
+    if names and names[0].arg in {"self", "cls"}:
+        written.pop(0)
+    if arguments.posonlyargs:
+        written.insert(len(arguments.posonlyargs) - (len(names) - len(written)), "/")
+    if arguments.vararg:
+        written.append(f"*{arguments.vararg.arg}")
+    elif arguments.kwonlyargs:
+        written.append("*")
+    for arg, value in zip(arguments.kwonlyargs, arguments.kw_defaults):
+        written.append(arg.arg + ("=..." if value is not None else ""))
+    if arguments.kwarg:
+        written.append(f"**{arguments.kwarg.arg}")
+    return f"({', '.join(written)})"
+
+
+def outline(stub: str) -> dict[str, set[str]]:
+    """What a stub says there is, each public function, class and member by its name, with the parameters
+    of each of its ``def`` lines, overloads included."""
+    found: dict[str, set[str]] = {}
+
+    def add(name: str, node: ast.stmt) -> None:
+        if isinstance(node, (ast.FunctionDef, ast.AsyncFunctionDef)):
+            # properties are attributes, however the stub writes them
+            is_property = any(isinstance(d, ast.Name) and d.id == "property" for d in node.decorator_list)
+            found.setdefault(name, set()).add("" if is_property else parameters(node.args))
+        elif isinstance(node, (ast.AnnAssign, ast.Assign)):
+            found.setdefault(name, set()).add("")
+
+    def public(name: str) -> bool:
+        return not name.startswith("_") or name == "__init__"
+
+    for node in ast.parse(stub).body:
+        if isinstance(node, ast.ClassDef) and public(node.name):
+            found.setdefault(node.name, set()).add("class")
+            for member in node.body:
+                targets = getattr(member, "targets", None) or [getattr(member, "target", None)]
+                name = getattr(member, "name", None) or next(
+                    (target.id for target in targets if isinstance(target, ast.Name)), None
+                )
+                if name and public(name):
+                    add(f"{node.name}.{name}", member)
+        elif name := getattr(node, "name", None):
+            if public(name):
+                add(name, node)
+    return found
+
+
+def check(generated: dict[str, str], directory: Path) -> list[str]:
+    """A diff of what the stubs kept by hand in ``directory`` leave out or say differently from the
+    generated ones, which is empty if they're up to date."""
+    diff = []
+    for file, stub in sorted(generated.items()):
+        path = directory / file
+        kept = outline(path.read_text(encoding="utf-8")) if path.exists() else {}
+        expected, actual = [], []
+        for name, signatures in sorted(outline(stub).items()):
+            # without a text signature the generated stub can't say what a function takes
+            if signatures == {"(*args, **kwargs)"}:
+                signatures = kept.get(name) or signatures
+            expected += [f"{name}{signature}\n" for signature in sorted(signatures)]
+            matching = signatures & kept.get(name, set())
+            actual += [f"{name}{signature}\n" for signature in sorted(matching or kept.get(name, set()))]
+        diff += difflib.unified_diff(actual, expected, fromfile=str(path), tofile=f"generated/{file}")
+    return diff
+
+
+def main() -> None:
+    if len(sys.argv) == 3 and sys.argv[1] == "--check":
+        from charbot_rust import _charbot_rust  # pyright: ignore[reportPrivateUsage]
+
+        diff = check(generate_stubs(_charbot_rust), Path(sys.argv[2]))
+        sys.stdout.writelines(diff)
+        sys.exit(1 if diff else 0)
+    if len(sys.argv) != 2:
+        sys.exit(f"usage: {sys.argv[0]} OUTPUT_DIRECTORY | --check STUB_DIRECTORY")
+    from charbot_rust import _charbot_rust  # pyright: ignore[reportPrivateUsage]
+
+    output = Path(sys.argv[1])
+    output.mkdir(parents=True, exist_ok=True)
+    for file, stub in generate_stubs(_charbot_rust).items():
+        (output / file).write_text(stub, encoding="utf-8")
+
+
+if __name__ == "__main__":
+    main()